@@ -0,0 +1,8 @@
+fn main() {
+    // Tells whether the buffer protocol is available to the Python bindings,
+    // see `pyo3::buffer`.
+    println!("cargo:rustc-check-cfg=cfg(Py_LIMITED_API)");
+    println!("cargo:rustc-check-cfg=cfg(Py_3_11)");
+    #[cfg(feature = "python")]
+    pyo3_build_config::use_pyo3_cfgs();
+}
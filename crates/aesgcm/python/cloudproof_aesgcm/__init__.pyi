@@ -1,31 +1,150 @@
+from typing import Any, Callable, Tuple, Union
+
+BytesLike = Union[bytes, bytearray, memoryview, Any]
+
 class Aes256Gcm:
-    """Use aes256gcm standard rust implementation"""
+    """Use aes256gcm standard rust implementation
+
+    All inputs accept any object implementing the buffer protocol
+    (bytes, bytearray, memoryview, numpy arrays...). The bytes objects are
+    read in place; the other buffers are copied when the module targets the
+    Python 3.7 stable ABI, which does not expose the buffer protocol. The GIL
+    is released during encryption and decryption.
+    """
 
     @staticmethod
     def encrypt(
-        key: bytes, nonce: bytes, plaintext: bytes, authenticated_data: bytes
+        key: BytesLike,
+        nonce: BytesLike,
+        plaintext: BytesLike,
+        authenticated_data: BytesLike,
     ) -> bytes:
         """AES256GCM encryption
 
         Args:
-            key (bytes): symmetric key - 32 bytes
-            plaintext (bytes): data to encrypt
-            authenticated_data (bytes): authenticated data
+            key (BytesLike): symmetric key - 32 bytes
+            nonce (BytesLike): nonce - 12 bytes
+            plaintext (BytesLike): data to encrypt
+            authenticated_data (BytesLike): authenticated data
 
         Returns:
             bytes
         """
     @staticmethod
     def decrypt(
-        key: bytes, nonce: bytes, ciphertext: bytes, authenticated_data: bytes
+        key: BytesLike,
+        nonce: BytesLike,
+        ciphertext: BytesLike,
+        authenticated_data: BytesLike,
     ) -> bytes:
         """AES256GCM decryption
 
         Args:
-            key (bytes): symmetric key - 32 bytes
-            ciphertext (bytes): data to encrypt
-            authenticated_data (bytes): authenticated data
+            key (BytesLike): symmetric key - 32 bytes
+            nonce (BytesLike): nonce - 12 bytes
+            ciphertext (BytesLike): data to decrypt
+            authenticated_data (BytesLike): authenticated data
+
+        Returns:
+            bytes
+        """
+    @staticmethod
+    def derive_key(
+        master_key: BytesLike,
+        context: BytesLike,
+        salt: BytesLike,
+    ) -> bytes:
+        """HKDF-SHA256 key derivation
+
+        Args:
+            master_key (BytesLike): master key - at least 32 bytes
+            context (BytesLike): context the derived key is bound to
+            salt (BytesLike): salt, may be empty
+
+        Returns:
+            bytes: derived key - 32 bytes
+        """
+
+class XChaCha20Poly1305:
+    """Use XChaCha20-Poly1305 standard rust implementation
+
+    Faster than AES256GCM on the platforms without hardware AES support.
+    Nonces are long enough to be drawn at random. All inputs accept any
+    object implementing the buffer protocol. The GIL is released during
+    encryption and decryption.
+    """
+
+    @staticmethod
+    def encrypt(
+        key: BytesLike,
+        nonce: BytesLike,
+        plaintext: BytesLike,
+        authenticated_data: BytesLike,
+    ) -> bytes:
+        """XChaCha20-Poly1305 encryption
+
+        Args:
+            key (BytesLike): symmetric key - 32 bytes
+            nonce (BytesLike): nonce - 24 bytes
+            plaintext (BytesLike): data to encrypt
+            authenticated_data (BytesLike): authenticated data
+
+        Returns:
+            bytes
+        """
+    @staticmethod
+    def decrypt(
+        key: BytesLike,
+        nonce: BytesLike,
+        ciphertext: BytesLike,
+        authenticated_data: BytesLike,
+    ) -> bytes:
+        """XChaCha20-Poly1305 decryption
+
+        Args:
+            key (BytesLike): symmetric key - 32 bytes
+            nonce (BytesLike): nonce - 24 bytes
+            ciphertext (BytesLike): data to decrypt
+            authenticated_data (BytesLike): authenticated data
 
         Returns:
             bytes
         """
+
+class Aes256GcmEncryptor:
+    """AES256GCM encryptor managing its own nonces.
+
+    Nonces are derived from a monotonically increasing counter. Before each
+    encryption, the next counter value is given to `persist_counter`: if it
+    raises, the encryption is aborted. The encryptor should be restored using
+    the last persisted value so that no nonce is ever reused.
+
+    Args:
+        key (BytesLike): symmetric key - 32 bytes
+        next_counter (int): last persisted counter value, 0 for a new key
+        persist_counter (Callable[[int], None]): persists the next counter value
+    """
+
+    def __init__(
+        self,
+        key: BytesLike,
+        next_counter: int,
+        persist_counter: Callable[[int], None],
+    ): ...
+    @property
+    def next_counter(self) -> int:
+        """Counter value that will be used by the next encryption."""
+    def encrypt_with_counter(
+        self,
+        plaintext: BytesLike,
+        authenticated_data: BytesLike,
+    ) -> Tuple[bytes, bytes]:
+        """AES256GCM encryption using the next nonce
+
+        Args:
+            plaintext (BytesLike): data to encrypt
+            authenticated_data (BytesLike): authenticated data
+
+        Returns:
+            Tuple[bytes, bytes]: (nonce, ciphertext)
+        """
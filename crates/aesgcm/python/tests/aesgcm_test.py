@@ -4,6 +4,8 @@ import unittest
 
 from cloudproof_aesgcm import (
     Aes256Gcm,
+    Aes256GcmEncryptor,
+    XChaCha20Poly1305,
 )
 
 KEY = os.urandom(32)
@@ -27,6 +29,89 @@ class TestEncryption(unittest.TestCase):
         print(type(cleartext))
         assert plaintext == bytes(cleartext)
 
+    def test_buffer_protocol_inputs(self) -> None:
+        """
+        AESGCM accepts bytearray and memoryview inputs
+        """
+        plaintext = os.urandom(1024)
+        ciphertext = Aes256Gcm.encrypt(
+            bytearray(KEY), memoryview(NONCE), bytearray(plaintext), AUTHENTICATED_DATA
+        )
+        cleartext = Aes256Gcm.decrypt(
+            memoryview(KEY), NONCE, memoryview(ciphertext), bytearray(AUTHENTICATED_DATA)
+        )
+        assert plaintext == cleartext
+
+    def test_encrypt_with_counter(self) -> None:
+        """
+        AESGCM encryptor never reuses a nonce
+        """
+        persisted = []
+        encryptor = Aes256GcmEncryptor(KEY, 0, persisted.append)
+
+        plaintext = os.urandom(1024)
+        nonce_1, ciphertext = encryptor.encrypt_with_counter(
+            plaintext, AUTHENTICATED_DATA
+        )
+        nonce_2, _ = encryptor.encrypt_with_counter(plaintext, AUTHENTICATED_DATA)
+        assert nonce_1 != nonce_2
+        assert persisted == [1, 2]
+        assert encryptor.next_counter == 2
+
+        cleartext = Aes256Gcm.decrypt(KEY, nonce_1, ciphertext, AUTHENTICATED_DATA)
+        assert plaintext == cleartext
+
+        def fail(_: int) -> None:
+            raise IOError('disk full')
+
+        encryptor = Aes256GcmEncryptor(KEY, persisted[-1], fail)
+        with self.assertRaises(Exception):
+            encryptor.encrypt_with_counter(plaintext, AUTHENTICATED_DATA)
+        assert encryptor.next_counter == 2
+
+    def test_derive_key(self) -> None:
+        """
+        AESGCM keys derived from a master key are bound to their context
+        """
+        key_1 = Aes256Gcm.derive_key(KEY, b'record 1', b'')
+        key_2 = Aes256Gcm.derive_key(KEY, b'record 2', b'')
+        assert len(key_1) == 32
+        assert key_1 != key_2
+        assert key_1 == Aes256Gcm.derive_key(bytearray(KEY), b'record 1', b'')
+
+        plaintext = os.urandom(1024)
+        ciphertext = Aes256Gcm.encrypt(key_1, NONCE, plaintext, AUTHENTICATED_DATA)
+        with self.assertRaises(Exception):
+            Aes256Gcm.decrypt(key_2, NONCE, ciphertext, AUTHENTICATED_DATA)
+
+        with self.assertRaises(Exception):
+            Aes256Gcm.derive_key(KEY[:16], b'record 1', b'')
+
+
+class TestXChaCha20Poly1305(unittest.TestCase):
+    """
+    Test XChaCha20-Poly1305 encryption and decryption
+    """
+
+    def test_encrypt(self) -> None:
+        """
+        XChaCha20-Poly1305 test encrypt decrypt
+        """
+        nonce = os.urandom(24)
+        plaintext = os.urandom(1024)
+        ciphertext = XChaCha20Poly1305.encrypt(
+            KEY, nonce, plaintext, AUTHENTICATED_DATA
+        )
+        cleartext = XChaCha20Poly1305.decrypt(
+            bytearray(KEY), nonce, memoryview(ciphertext), AUTHENTICATED_DATA
+        )
+        assert plaintext == cleartext
+
+        with self.assertRaises(Exception):
+            XChaCha20Poly1305.decrypt(KEY, nonce, ciphertext, b'')
+        with self.assertRaises(Exception):
+            XChaCha20Poly1305.encrypt(KEY, NONCE, plaintext, AUTHENTICATED_DATA)
+
 
 if __name__ == '__main__':
     unittest.main()
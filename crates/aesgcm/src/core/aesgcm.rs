@@ -1,6 +1,6 @@
 use cosmian_crypto_core::{
-    Aes256Gcm as Aes256GcmRust, CryptoCoreError, Dem, FixedSizeCBytes, Instantiable, Nonce,
-    SymmetricKey,
+    Aes256Gcm as Aes256GcmRust, CryptoCoreError, Dem, DemInPlace, FixedSizeCBytes, Instantiable,
+    Nonce, SymmetricKey,
 };
 
 use crate::error::AesGcmError;
@@ -37,6 +37,38 @@ pub fn encrypt(
     Ok(Aes256GcmRust::new(&key).encrypt(&nonce, plaintext, Some(authenticated_data))?)
 }
 
+/// Encrypts the given plaintext in place.
+///
+/// Arguments:
+///
+/// * `key`: 32-byte array
+/// * `nonce`: 12-byte array
+/// * `buffer`: the data to encrypt, overwritten by the ciphertext
+/// * `authenticated_data`: an additional data that is authenticated during
+///   encryption
+///
+/// Returns:
+///
+/// the 16-byte authentication tag, which `decrypt` expects right after the
+/// ciphertext
+pub fn encrypt_in_place(
+    key: &[u8],
+    nonce: &[u8],
+    buffer: &mut [u8],
+    authenticated_data: &[u8],
+) -> Result<Vec<u8>, AesGcmError> {
+    let key: [u8; Aes256GcmRust::KEY_LENGTH] = key.try_into()?;
+    let nonce: [u8; Aes256GcmRust::NONCE_LENGTH] = nonce.try_into()?;
+
+    let key = SymmetricKey::try_from_bytes(key)?;
+    let nonce = Nonce::try_from_bytes(nonce)?;
+    Ok(Aes256GcmRust::new(&key).encrypt_in_place_detached(
+        &nonce,
+        buffer,
+        Some(authenticated_data),
+    )?)
+}
+
 /// The `decrypt` function parameters are:
 ///
 /// Arguments:
@@ -80,7 +112,7 @@ pub fn decrypt(
 mod tests {
     use cosmian_crypto_core::Aes256Gcm;
 
-    use crate::core::aesgcm::{decrypt, encrypt};
+    use crate::core::aesgcm::{decrypt, encrypt, encrypt_in_place};
 
     #[test]
     fn test_encrypt_decrypt() {
@@ -92,4 +124,25 @@ mod tests {
         let cleartext = decrypt(&key, &nonce, &ciphertext, authenticated_data).unwrap();
         assert_eq!(plaintext.to_vec(), cleartext);
     }
+    #[test]
+    fn test_encrypt_in_place() {
+        let key = vec![42_u8; Aes256Gcm::KEY_LENGTH];
+        let nonce = vec![42_u8; Aes256Gcm::NONCE_LENGTH];
+        let plaintext = b"plaintext";
+        let authenticated_data = b"authenticated_data";
+
+        let mut buffer = plaintext.to_vec();
+        let tag = encrypt_in_place(&key, &nonce, &mut buffer, authenticated_data).unwrap();
+        buffer.extend_from_slice(&tag);
+        assert_eq!(
+            buffer,
+            encrypt(&key, &nonce, plaintext, authenticated_data).unwrap()
+        );
+        assert_eq!(
+            decrypt(&key, &nonce, &buffer, authenticated_data).unwrap(),
+            plaintext
+        );
+
+        assert!(encrypt_in_place(&key[1..], &nonce, &mut buffer, authenticated_data).is_err());
+    }
 }
@@ -0,0 +1,149 @@
+use cosmian_crypto_core::{
+    Aes256Gcm as Aes256GcmRust, Dem, FixedSizeCBytes, Instantiable, Nonce, SymmetricKey,
+};
+
+use crate::error::AesGcmError;
+
+/// Callback used to persist the next counter value before a nonce is used.
+pub type PersistCounter = Box<dyn FnMut(u64) -> Result<(), String> + Send>;
+
+/// Number of leading zero bytes of the nonces derived from the counter.
+const NONCE_PADDING_LENGTH: usize = Aes256GcmRust::NONCE_LENGTH - 8;
+
+/// AES256 GCM encryptor managing its own nonces.
+///
+/// Nonces are derived from a monotonically increasing 64-bit counter:
+///
+/// ```txt
+/// nonce = 0x00000000 || big-endian(counter)
+/// ```
+///
+/// Before a nonce is used, the next counter value is given to the
+/// `persist_counter` callback. If it fails, the encryption is aborted and the
+/// counter is not incremented. Since the counter is persisted before being
+/// used, a crash can only make counter values be skipped, never reused, as
+/// long as the encryptor is restored from the last persisted value.
+///
+/// A given key must only be used by a single encryptor.
+pub struct Aes256GcmEncryptor {
+    aes: Aes256GcmRust,
+    next_counter: u64,
+    persist_counter: PersistCounter,
+}
+
+impl Aes256GcmEncryptor {
+    /// Instantiates a new encryptor.
+    ///
+    /// Arguments:
+    ///
+    /// * `key`: 32-byte array
+    /// * `next_counter`: the last value persisted by `persist_counter`, or 0
+    ///   if this key has never been used
+    /// * `persist_counter`: callback persisting the next counter value
+    pub fn new(
+        key: &[u8],
+        next_counter: u64,
+        persist_counter: PersistCounter,
+    ) -> Result<Self, AesGcmError> {
+        let key = SymmetricKey::try_from_bytes(key.try_into()?)?;
+        Ok(Self {
+            aes: Aes256GcmRust::new(&key),
+            next_counter,
+            persist_counter,
+        })
+    }
+
+    /// Returns the counter value that will be used by the next encryption.
+    #[must_use]
+    pub const fn next_counter(&self) -> u64 {
+        self.next_counter
+    }
+
+    /// Returns the nonce derived from the given counter value.
+    #[must_use]
+    pub fn nonce_from_counter(counter: u64) -> [u8; Aes256GcmRust::NONCE_LENGTH] {
+        let mut nonce = [0; Aes256GcmRust::NONCE_LENGTH];
+        nonce[NONCE_PADDING_LENGTH..].copy_from_slice(&counter.to_be_bytes());
+        nonce
+    }
+
+    /// Encrypts the given plaintext using the next counter value as nonce.
+    ///
+    /// Returns the nonce used and the ciphertext. The ciphertext can be
+    /// decrypted using [`decrypt`](crate::decrypt) with this nonce.
+    pub fn encrypt_with_counter(
+        &mut self,
+        plaintext: &[u8],
+        authenticated_data: &[u8],
+    ) -> Result<([u8; Aes256GcmRust::NONCE_LENGTH], Vec<u8>), AesGcmError> {
+        let counter = self.next_counter;
+        let next_counter = counter.checked_add(1).ok_or(AesGcmError::NonceExhausted)?;
+
+        (self.persist_counter)(next_counter).map_err(AesGcmError::CounterPersistence)?;
+        self.next_counter = next_counter;
+
+        let nonce = Self::nonce_from_counter(counter);
+        let ciphertext = self.aes.encrypt(
+            &Nonce::try_from_bytes(nonce)?,
+            plaintext,
+            Some(authenticated_data),
+        )?;
+        Ok((nonce, ciphertext))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::{Arc, Mutex};
+
+    use cosmian_crypto_core::Aes256Gcm;
+
+    use super::*;
+    use crate::decrypt;
+
+    #[test]
+    fn test_encrypt_with_counter() {
+        let key = [42_u8; Aes256Gcm::KEY_LENGTH];
+        let persisted = Arc::new(Mutex::new(0));
+        let persist_counter: PersistCounter = {
+            let persisted = persisted.clone();
+            Box::new(move |counter| {
+                *persisted.lock().unwrap() = counter;
+                Ok(())
+            })
+        };
+        let mut encryptor = Aes256GcmEncryptor::new(&key, 0, persist_counter).unwrap();
+
+        let (nonce_1, ciphertext) = encryptor.encrypt_with_counter(b"plaintext", b"ad").unwrap();
+        assert_eq!(*persisted.lock().unwrap(), 1);
+        let (nonce_2, _) = encryptor.encrypt_with_counter(b"plaintext", b"ad").unwrap();
+        assert_eq!(*persisted.lock().unwrap(), 2);
+        assert_ne!(nonce_1, nonce_2);
+        assert_eq!(
+            decrypt(&key, &nonce_1, &ciphertext, b"ad").unwrap(),
+            b"plaintext".to_vec()
+        );
+
+        // Restoring the encryptor from the persisted value does not reuse any
+        // nonce.
+        let next_counter = *persisted.lock().unwrap();
+        let mut encryptor =
+            Aes256GcmEncryptor::new(&key, next_counter, Box::new(|_| Ok(()))).unwrap();
+        let (nonce_3, _) = encryptor.encrypt_with_counter(b"plaintext", b"ad").unwrap();
+        assert_ne!(nonce_3, nonce_1);
+        assert_ne!(nonce_3, nonce_2);
+
+        // A nonce is not used if the counter cannot be persisted.
+        let mut encryptor =
+            Aes256GcmEncryptor::new(&key, 3, Box::new(|_| Err("disk full".to_string()))).unwrap();
+        assert!(encryptor.encrypt_with_counter(b"plaintext", b"ad").is_err());
+        assert_eq!(encryptor.next_counter(), 3);
+
+        // The counter cannot wrap around.
+        let mut encryptor = Aes256GcmEncryptor::new(&key, u64::MAX, Box::new(|_| Ok(()))).unwrap();
+        assert!(matches!(
+            encryptor.encrypt_with_counter(b"plaintext", b"ad"),
+            Err(AesGcmError::NonceExhausted)
+        ));
+    }
+}
@@ -0,0 +1,60 @@
+use cosmian_crypto_core::Aes256Gcm as Aes256GcmRust;
+use hkdf::Hkdf;
+use sha2::Sha256;
+
+use crate::error::AesGcmError;
+
+/// Minimum length of the master key given to `derive_key`.
+pub const MIN_MASTER_KEY_LENGTH: usize = 32;
+
+/// Derives an AES256 GCM key from a master key using HKDF-SHA256 (RFC 5869).
+///
+/// Arguments:
+///
+/// * `master_key`: the secret input keying material, at least 32 bytes
+/// * `context`: the HKDF `info`, binding the derived key to its usage (e.g.
+///   the application name and the record identifier); distinct contexts yield
+///   independent keys
+/// * `salt`: the HKDF salt, may be empty
+///
+/// Returns:
+///
+/// the 32-byte derived key
+pub fn derive_key(
+    master_key: &[u8],
+    context: &[u8],
+    salt: &[u8],
+) -> Result<[u8; Aes256GcmRust::KEY_LENGTH], AesGcmError> {
+    if master_key.len() < MIN_MASTER_KEY_LENGTH {
+        return Err(AesGcmError::KeyDerivation(format!(
+            "the master key must be at least {MIN_MASTER_KEY_LENGTH} bytes long, got {}",
+            master_key.len()
+        )));
+    }
+    let mut key = [0; Aes256GcmRust::KEY_LENGTH];
+    Hkdf::<Sha256>::new(Some(salt), master_key)
+        .expand(context, &mut key)
+        .map_err(|e| AesGcmError::KeyDerivation(e.to_string()))?;
+    Ok(key)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::core::kdf::derive_key;
+
+    #[test]
+    fn test_derive_key() {
+        let master_key = [42_u8; 32];
+        let key = derive_key(&master_key, b"record 1", b"salt").unwrap();
+        assert_eq!(
+            hex::encode(key),
+            "23ee64f39c637f6dc00034544bb61a44eee2791ea83a2a3d9804e1285df8f7e7"
+        );
+
+        // Keys are bound to their context and salt.
+        assert_ne!(key, derive_key(&master_key, b"record 2", b"salt").unwrap());
+        assert_ne!(key, derive_key(&master_key, b"record 1", b"").unwrap());
+
+        assert!(derive_key(&master_key[..31], b"record 1", b"salt").is_err());
+    }
+}
@@ -1 +1,4 @@
 pub mod aesgcm;
+pub mod encryptor;
+pub mod kdf;
+pub mod xchacha20poly1305;
@@ -0,0 +1,100 @@
+//! XChaCha20-Poly1305 encryption, an alternative to AES256 GCM for the
+//! platforms without hardware AES support (e.g. older Android devices) where
+//! AES GCM is slow.
+//!
+//! Its 24-byte nonces are long enough to be drawn at random without any risk
+//! of collision.
+
+use cosmian_crypto_core::{
+    CryptoCoreError, Dem, FixedSizeCBytes, Instantiable, Nonce, SymmetricKey,
+    XChaCha20Poly1305 as XChaCha20Poly1305Rust,
+};
+
+use crate::error::AesGcmError;
+
+/// The `encrypt` function parameters are:
+///
+/// Arguments:
+///
+/// * `key`: 32-byte array
+/// * `nonce`: 24-byte array
+/// * `plaintext`: the data to encrypt
+/// * `authenticated_data`: an additional data that is authenticated during
+///   encryption
+///
+/// Returns:
+///
+/// the ciphertext if succeeds
+pub fn encrypt(
+    key: &[u8],
+    nonce: &[u8],
+    plaintext: &[u8],
+    authenticated_data: &[u8],
+) -> Result<Vec<u8>, AesGcmError> {
+    let key: [u8; XChaCha20Poly1305Rust::KEY_LENGTH] = key.try_into()?;
+    let nonce: [u8; XChaCha20Poly1305Rust::NONCE_LENGTH] = nonce.try_into()?;
+
+    let key = SymmetricKey::try_from_bytes(key)?;
+    let nonce = Nonce::try_from_bytes(nonce)?;
+    Ok(XChaCha20Poly1305Rust::new(&key).encrypt(&nonce, plaintext, Some(authenticated_data))?)
+}
+
+/// The `decrypt` function parameters are:
+///
+/// Arguments:
+///
+/// * `key`: 32-byte array
+/// * `nonce`: 24-byte array
+/// * `ciphertext`: the data to decrypt
+/// * `authenticated_data`: an additional data used during encryption
+///
+/// Returns:
+///
+/// the plaintext if succeeds
+pub fn decrypt(
+    key: &[u8],
+    nonce: &[u8],
+    ciphertext: &[u8],
+    authenticated_data: &[u8],
+) -> Result<Vec<u8>, AesGcmError> {
+    if ciphertext.len() < XChaCha20Poly1305Rust::MAC_LENGTH {
+        return Err(AesGcmError::CryptoCore(
+            CryptoCoreError::CiphertextTooSmallError {
+                ciphertext_len: ciphertext.len(),
+                min: XChaCha20Poly1305Rust::MAC_LENGTH as u64,
+            },
+        ));
+    }
+    let key: [u8; XChaCha20Poly1305Rust::KEY_LENGTH] = key.try_into()?;
+    let nonce: [u8; XChaCha20Poly1305Rust::NONCE_LENGTH] = nonce.try_into()?;
+
+    let key = SymmetricKey::try_from_bytes(key)?;
+    let nonce = Nonce::try_from_bytes(nonce)?;
+    Ok(XChaCha20Poly1305Rust::new(&key).decrypt(&nonce, ciphertext, Some(authenticated_data))?)
+}
+
+#[cfg(test)]
+mod tests {
+    use cosmian_crypto_core::XChaCha20Poly1305;
+
+    use crate::core::xchacha20poly1305::{decrypt, encrypt};
+
+    #[test]
+    fn test_encrypt_decrypt() {
+        let key = vec![42_u8; XChaCha20Poly1305::KEY_LENGTH];
+        let nonce = vec![42_u8; XChaCha20Poly1305::NONCE_LENGTH];
+        let plaintext = b"plaintext";
+        let authenticated_data = b"authenticated_data";
+        let ciphertext = encrypt(&key, &nonce, plaintext, authenticated_data).unwrap();
+        assert_eq!(
+            ciphertext.len(),
+            plaintext.len() + XChaCha20Poly1305::MAC_LENGTH
+        );
+        let cleartext = decrypt(&key, &nonce, &ciphertext, authenticated_data).unwrap();
+        assert_eq!(plaintext.to_vec(), cleartext);
+
+        assert!(decrypt(&key, &nonce, &ciphertext, b"other data").is_err());
+        // AES256 GCM nonces are too short.
+        assert!(encrypt(&key, &nonce[..12], plaintext, authenticated_data).is_err());
+    }
+}
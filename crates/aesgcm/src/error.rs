@@ -10,6 +10,9 @@ use wasm_bindgen::JsValue;
 pub enum AesGcmError {
     CryptoCore(CryptoCoreError),
     TryFromSliceError(TryFromSliceError),
+    NonceExhausted,
+    CounterPersistence(String),
+    KeyDerivation(String),
 }
 
 impl Display for AesGcmError {
@@ -17,6 +20,11 @@ impl Display for AesGcmError {
         match self {
             Self::CryptoCore(err) => write!(f, "{err}"),
             Self::TryFromSliceError(err) => write!(f, "{err}"),
+            Self::NonceExhausted => write!(f, "all the nonces of this key have been used"),
+            Self::CounterPersistence(err) => {
+                write!(f, "failed persisting the nonce counter: {err}")
+            }
+            Self::KeyDerivation(err) => write!(f, "key derivation error: {err}"),
         }
     }
 }
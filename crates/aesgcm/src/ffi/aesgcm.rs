@@ -1,6 +1,10 @@
-use cosmian_ffi_utils::{ffi_read_bytes, ffi_unwrap, ffi_write_bytes, ErrorCode};
+use cosmian_crypto_core::Aes256Gcm;
+use cosmian_ffi_utils::{
+    error::{set_last_error, FfiError},
+    ffi_guard, ffi_not_null, ffi_read_bytes, ffi_unwrap, ffi_write_bytes, ErrorCode,
+};
 
-use crate::{decrypt, encrypt};
+use crate::{decrypt, encrypt, encrypt_in_place};
 
 unsafe extern "C" fn aesgcm(
     output_ptr: *mut u8,
@@ -53,19 +57,21 @@ pub unsafe extern "C" fn h_aes256gcm_encrypt(
     authenticated_data_ptr: *const i8,
     authenticated_data_len: i32,
 ) -> i32 {
-    aesgcm(
-        output_ptr,
-        output_len,
-        plaintext_ptr,
-        plaintext_len,
-        key_ptr,
-        key_len,
-        nonce_ptr,
-        nonce_len,
-        authenticated_data_ptr,
-        authenticated_data_len,
-        true,
-    )
+    ffi_guard!({
+        aesgcm(
+            output_ptr,
+            output_len,
+            plaintext_ptr,
+            plaintext_len,
+            key_ptr,
+            key_len,
+            nonce_ptr,
+            nonce_len,
+            authenticated_data_ptr,
+            authenticated_data_len,
+            true,
+        )
+    })
 }
 
 #[no_mangle]
@@ -81,17 +87,89 @@ pub unsafe extern "C" fn h_aes256gcm_decrypt(
     authenticated_data_ptr: *const i8,
     authenticated_data_len: i32,
 ) -> i32 {
-    aesgcm(
-        output_ptr,
-        output_len,
-        ciphertext_ptr,
-        ciphertext_len,
-        key_ptr,
-        key_len,
-        nonce_ptr,
-        nonce_len,
-        authenticated_data_ptr,
-        authenticated_data_len,
-        false,
-    )
+    ffi_guard!({
+        aesgcm(
+            output_ptr,
+            output_len,
+            ciphertext_ptr,
+            ciphertext_len,
+            key_ptr,
+            key_len,
+            nonce_ptr,
+            nonce_len,
+            authenticated_data_ptr,
+            authenticated_data_len,
+            false,
+        )
+    })
+}
+
+#[no_mangle]
+/// Encrypts the plaintext stored in the given buffer in place, appending the
+/// authentication tag. This avoids allocating and copying the output of large
+/// payloads.
+///
+/// The buffer should be able to hold 16 more bytes than the plaintext: if its
+/// capacity is too small, nothing is encrypted, `buffer_len` is set to the
+/// needed capacity and 1 is returned.
+///
+/// - `buffer_ptr`             : plaintext, overwritten by ciphertext || tag
+/// - `buffer_len`             : plaintext length, set to the ciphertext length
+/// - `buffer_capacity`        : size allocated to the buffer
+/// - `key_ptr`                : symmetric key - 32 bytes
+/// - `key_len`                : symmetric key length
+/// - `nonce_ptr`              : nonce - 12 bytes
+/// - `nonce_len`              : nonce length
+/// - `authenticated_data_ptr` : authenticated data
+/// - `authenticated_data_len` : authenticated data length
+/// # Safety
+pub unsafe extern "C" fn h_aes256gcm_encrypt_in_place(
+    buffer_ptr: *mut u8,
+    buffer_len: *mut i32,
+    buffer_capacity: i32,
+    key_ptr: *const i8,
+    key_len: i32,
+    nonce_ptr: *const i8,
+    nonce_len: i32,
+    authenticated_data_ptr: *const i8,
+    authenticated_data_len: i32,
+) -> i32 {
+    ffi_guard!({
+        ffi_not_null!("buffer_ptr", buffer_ptr);
+        ffi_not_null!("buffer_len", buffer_len);
+        let plaintext_len = ffi_unwrap!(
+            usize::try_from(*buffer_len),
+            "buffer_len should be positive",
+            ErrorCode::InvalidArgument("buffer_len".to_string())
+        );
+        let key_bytes = ffi_read_bytes!("key", key_ptr, key_len);
+        let nonce_bytes = ffi_read_bytes!("nonce", nonce_ptr, nonce_len);
+        let authenticated_data = ffi_read_bytes!(
+            "authenticated_data",
+            authenticated_data_ptr,
+            authenticated_data_len
+        );
+
+        let ciphertext_len = plaintext_len + Aes256Gcm::MAC_LENGTH;
+        if usize::try_from(buffer_capacity).unwrap_or_default() < ciphertext_len {
+            set_last_error(FfiError::Generic(format!(
+                "The pre-allocated buffer is too small; need {ciphertext_len} bytes, allocated \
+                 {buffer_capacity}"
+            )));
+            *buffer_len = ciphertext_len as i32;
+            return 1;
+        }
+
+        let buffer = std::slice::from_raw_parts_mut(buffer_ptr, ciphertext_len);
+        let (plaintext, tag) = buffer.split_at_mut(plaintext_len);
+        let computed_tag = ffi_unwrap!(
+            encrypt_in_place(key_bytes, nonce_bytes, plaintext, authenticated_data),
+            "AES-256 GCM encryption error",
+            ErrorCode::Encryption
+        );
+        tag.copy_from_slice(&computed_tag);
+        *buffer_len = ciphertext_len as i32;
+
+        0
+    })
 }
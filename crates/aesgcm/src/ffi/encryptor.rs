@@ -0,0 +1,167 @@
+use std::{
+    collections::HashMap,
+    sync::{
+        atomic::{AtomicI32, Ordering},
+        Arc, Mutex,
+    },
+};
+
+use cosmian_crypto_core::Aes256Gcm;
+use cosmian_ffi_utils::{
+    error::{set_last_error, FfiError},
+    ffi_bail, ffi_guard, ffi_not_null, ffi_read_bytes, ffi_unwrap, ffi_write_bytes, ErrorCode,
+};
+use lazy_static::lazy_static;
+
+use crate::Aes256GcmEncryptor;
+
+/// Persists the given next counter value. Returns 0 on success.
+pub type PersistCounterCallback = extern "C" fn(next_counter: u64) -> i32;
+
+lazy_static! {
+    // Each encryptor has its own lock, so that the map is not locked while a
+    // counter is persisted.
+    static ref ENCRYPTOR_MAP: Mutex<HashMap<i32, Arc<Mutex<Aes256GcmEncryptor>>>> =
+        Mutex::new(HashMap::new());
+    static ref NEXT_ENCRYPTOR_ID: AtomicI32 = AtomicI32::new(0);
+}
+
+#[no_mangle]
+/// Instantiates an AES256 GCM encryptor managing its own nonces and returns a
+/// handle to it.
+///
+/// Before each encryption, the next counter value is given to the
+/// `persist_counter` callback. The encryption is aborted if it does not
+/// return 0. The encryptor should be restored using the last persisted value.
+/// The callback may call the other functions of this library, but must not
+/// encrypt using the same encryptor.
+///
+/// WARNING: [`h_aes256gcm_encryptor_destroy()`](h_aes256gcm_encryptor_destroy)
+/// should be called to reclaim the memory.
+///
+/// - `encryptor_handle` : Output handle to the encryptor
+/// - `key_ptr`          : symmetric key - 32 bytes
+/// - `key_len`          : symmetric key length
+/// - `next_counter`     : last persisted counter value, 0 for a new key
+/// - `persist_counter`  : callback persisting the next counter value
+/// # Safety
+pub unsafe extern "C" fn h_aes256gcm_encryptor_new(
+    encryptor_handle: *mut i32,
+    key_ptr: *const i8,
+    key_len: i32,
+    next_counter: u64,
+    persist_counter: PersistCounterCallback,
+) -> i32 {
+    ffi_guard!({
+        let key_bytes = ffi_read_bytes!("key", key_ptr, key_len);
+        let encryptor = ffi_unwrap!(
+            Aes256GcmEncryptor::new(
+                key_bytes,
+                next_counter,
+                Box::new(move |counter| match persist_counter(counter) {
+                    0 => Ok(()),
+                    code => Err(format!("persist counter callback returned {code}")),
+                }),
+            ),
+            "error instantiating AES-256 GCM encryptor",
+            ErrorCode::InvalidArgument("key".to_string())
+        );
+
+        let id = NEXT_ENCRYPTOR_ID.fetch_add(1, Ordering::Acquire);
+        ENCRYPTOR_MAP
+            .lock()
+            .expect("a mutex on the encryptor map failed")
+            .insert(id, Arc::new(Mutex::new(encryptor)));
+        *encryptor_handle = id;
+
+        0
+    })
+}
+
+#[no_mangle]
+/// Encrypts the given plaintext using the next nonce of the given encryptor.
+///
+/// The output is the concatenation of the 12-byte nonce used and the
+/// ciphertext, which can be decrypted using `h_aes256gcm_decrypt()`.
+///
+/// The output buffer should be able to hold 28 more bytes than the plaintext:
+/// if it is too small, nothing is encrypted and no nonce is used, `output_len`
+/// is set to the needed size and 1 is returned.
+///
+/// - `output_ptr`             : Output buffer containing nonce || ciphertext
+/// - `output_len`             : Size of the output buffer
+/// - `encryptor_handle`       : handle to the encryptor
+/// - `plaintext_ptr`          : data to encrypt
+/// - `plaintext_len`          : data length
+/// - `authenticated_data_ptr` : authenticated data
+/// - `authenticated_data_len` : authenticated data length
+/// # Safety
+pub unsafe extern "C" fn h_aes256gcm_encryptor_encrypt(
+    output_ptr: *mut u8,
+    output_len: *mut i32,
+    encryptor_handle: i32,
+    plaintext_ptr: *const i8,
+    plaintext_len: i32,
+    authenticated_data_ptr: *const i8,
+    authenticated_data_len: i32,
+) -> i32 {
+    ffi_guard!({
+        ffi_not_null!("output_ptr", output_ptr);
+        ffi_not_null!("output_len", output_len);
+        let plaintext = ffi_read_bytes!("plaintext", plaintext_ptr, plaintext_len);
+        let authenticated_data = ffi_read_bytes!(
+            "authenticated_data",
+            authenticated_data_ptr,
+            authenticated_data_len
+        );
+
+        // Checks the output size before a nonce is used.
+        let needed_len = Aes256Gcm::NONCE_LENGTH + plaintext.len() + Aes256Gcm::MAC_LENGTH;
+        if usize::try_from(*output_len).unwrap_or_default() < needed_len {
+            set_last_error(FfiError::Generic(format!(
+                "The pre-allocated output_ptr buffer is too small; need {needed_len} bytes, \
+                 allocated {}",
+                *output_len
+            )));
+            *output_len = needed_len as i32;
+            return 1;
+        }
+
+        let encryptor = ENCRYPTOR_MAP
+            .lock()
+            .expect("a mutex on the encryptor map failed")
+            .get(&encryptor_handle)
+            .cloned();
+        let Some(encryptor) = encryptor else {
+            ffi_bail!(format!("no encryptor with handle: {encryptor_handle}"));
+        };
+        let Ok(mut encryptor) = encryptor.lock() else {
+            ffi_bail!(format!("encryptor {encryptor_handle} is poisoned"));
+        };
+
+        let (nonce, ciphertext) = ffi_unwrap!(
+            encryptor.encrypt_with_counter(plaintext, authenticated_data),
+            "AES-256 GCM encryption error",
+            ErrorCode::Encryption
+        );
+        drop(encryptor);
+
+        let output = [nonce.as_slice(), &ciphertext].concat();
+        ffi_write_bytes!("output_ptr", &output, output_ptr, output_len);
+    })
+}
+
+#[no_mangle]
+/// Reclaims the memory of an encryptor instantiated with
+/// [`h_aes256gcm_encryptor_new()`](h_aes256gcm_encryptor_new).
+///
+/// # Safety
+pub unsafe extern "C" fn h_aes256gcm_encryptor_destroy(encryptor_handle: i32) -> i32 {
+    ffi_guard!({
+        ENCRYPTOR_MAP
+            .lock()
+            .expect("a mutex on the encryptor map failed")
+            .remove(&encryptor_handle);
+        0
+    })
+}
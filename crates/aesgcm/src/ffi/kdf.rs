@@ -0,0 +1,43 @@
+use cosmian_ffi_utils::{ffi_guard, ffi_read_bytes, ffi_unwrap, ffi_write_bytes, ErrorCode};
+
+use crate::derive_key;
+
+#[no_mangle]
+/// Derives an AES256 GCM key from a master key using HKDF-SHA256.
+///
+/// - `output_ptr`     : output derived key - 32 bytes
+/// - `output_len`     : output derived key length
+/// - `master_key_ptr` : master key - at least 32 bytes
+/// - `master_key_len` : master key length
+/// - `context_ptr`    : context the derived key is bound to
+/// - `context_len`    : context length
+/// - `salt_ptr`       : salt, may be null
+/// - `salt_len`       : salt length
+/// # Safety
+pub unsafe extern "C" fn h_aes256gcm_derive_key(
+    output_ptr: *mut u8,
+    output_len: *mut i32,
+    master_key_ptr: *const i8,
+    master_key_len: i32,
+    context_ptr: *const i8,
+    context_len: i32,
+    salt_ptr: *const i8,
+    salt_len: i32,
+) -> i32 {
+    ffi_guard!({
+        let master_key = ffi_read_bytes!("master_key", master_key_ptr, master_key_len);
+        let context = ffi_read_bytes!("context", context_ptr, context_len);
+        let salt = if salt_ptr.is_null() || salt_len == 0 {
+            &[]
+        } else {
+            ffi_read_bytes!("salt", salt_ptr, salt_len)
+        };
+        let key = ffi_unwrap!(
+            derive_key(master_key, context, salt),
+            "AES-256 GCM key derivation error",
+            ErrorCode::InvalidArgument("master_key".to_string())
+        );
+
+        ffi_write_bytes!("output_ptr", &key, output_ptr, output_len);
+    })
+}
@@ -1,4 +1,7 @@
 mod aesgcm;
+mod encryptor;
+mod kdf;
+mod xchacha20poly1305;
 
 #[cfg(test)]
 mod tests;
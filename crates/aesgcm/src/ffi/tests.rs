@@ -1,7 +1,17 @@
-use cosmian_crypto_core::Aes256Gcm;
+use std::sync::atomic::{AtomicU64, Ordering};
+
+use cosmian_crypto_core::{Aes256Gcm, XChaCha20Poly1305};
 use cosmian_ffi_utils::error::get_last_error;
 
-use super::aesgcm::{h_aes256gcm_decrypt, h_aes256gcm_encrypt};
+use super::{
+    aesgcm::{h_aes256gcm_decrypt, h_aes256gcm_encrypt, h_aes256gcm_encrypt_in_place},
+    encryptor::{
+        h_aes256gcm_encryptor_destroy, h_aes256gcm_encryptor_encrypt, h_aes256gcm_encryptor_new,
+    },
+    kdf::h_aes256gcm_derive_key,
+    xchacha20poly1305::{h_xchacha20poly1305_decrypt, h_xchacha20poly1305_encrypt},
+};
+use crate::{derive_key, encrypt};
 
 #[test]
 fn test_aes256gcm_encrypt_decrypt() {
@@ -77,3 +87,315 @@ fn test_aes256gcm_encrypt_decrypt() {
         assert_eq!(plaintext.to_vec(), cleartext_bytes.to_vec());
     }
 }
+
+#[test]
+fn test_aes256gcm_encrypt_in_place() {
+    let key = [42_u8; Aes256Gcm::KEY_LENGTH];
+    let nonce = [42_u8; Aes256Gcm::NONCE_LENGTH];
+    let authenticated_data = b"authenticated_data";
+    let plaintext = b"plaintext";
+
+    let encrypt_in_place = |buffer: &mut Vec<u8>, buffer_len: &mut i32| unsafe {
+        h_aes256gcm_encrypt_in_place(
+            buffer.as_mut_ptr(),
+            buffer_len,
+            buffer.len() as i32,
+            key.as_ptr().cast(),
+            key.len() as i32,
+            nonce.as_ptr().cast(),
+            nonce.len() as i32,
+            authenticated_data.as_ptr().cast(),
+            authenticated_data.len() as i32,
+        )
+    };
+
+    // The buffer cannot hold the tag: it is left untouched.
+    let mut buffer = plaintext.to_vec();
+    let mut buffer_len = plaintext.len() as i32;
+    assert_eq!(encrypt_in_place(&mut buffer, &mut buffer_len), 1);
+    assert_eq!(buffer_len as usize, plaintext.len() + Aes256Gcm::MAC_LENGTH);
+    assert_eq!(buffer, plaintext);
+
+    let mut buffer = [plaintext.as_slice(), &[0; Aes256Gcm::MAC_LENGTH]].concat();
+    let mut buffer_len = plaintext.len() as i32;
+    let ret = encrypt_in_place(&mut buffer, &mut buffer_len);
+    assert!(
+        0 == ret,
+        "AESGCM FFI in place encryption failed. Exit with error: {ret}, error message: {:?}",
+        get_last_error()
+    );
+    assert_eq!(buffer_len as usize, buffer.len());
+    assert_eq!(
+        buffer,
+        encrypt(&key, &nonce, plaintext, authenticated_data).unwrap()
+    );
+}
+
+static PERSISTED_COUNTER: AtomicU64 = AtomicU64::new(0);
+
+extern "C" fn persist_counter(next_counter: u64) -> i32 {
+    PERSISTED_COUNTER.store(next_counter, Ordering::SeqCst);
+    0
+}
+
+#[test]
+fn test_aes256gcm_encryptor() {
+    let key = [42_u8; Aes256Gcm::KEY_LENGTH];
+    let authenticated_data = b"authenticated_data";
+    let plaintext = b"plaintext";
+
+    unsafe {
+        let mut handle = -1;
+        let ret = h_aes256gcm_encryptor_new(
+            &mut handle,
+            key.as_ptr().cast(),
+            key.len() as i32,
+            7,
+            persist_counter,
+        );
+        assert!(
+            0 == ret,
+            "AESGCM FFI encryptor instantiation failed. Exit with error: {ret}, error message: \
+             {:?}",
+            get_last_error()
+        );
+
+        let mut outputs = Vec::new();
+        for _ in 0..2 {
+            let mut output =
+                vec![0u8; Aes256Gcm::NONCE_LENGTH + plaintext.len() + Aes256Gcm::MAC_LENGTH];
+            let mut output_len = output.len() as i32;
+            let ret = h_aes256gcm_encryptor_encrypt(
+                output.as_mut_ptr(),
+                &mut output_len,
+                handle,
+                plaintext.as_ptr().cast(),
+                plaintext.len() as i32,
+                authenticated_data.as_ptr().cast(),
+                authenticated_data.len() as i32,
+            );
+            assert!(
+                0 == ret,
+                "AESGCM FFI encryptor encryption failed. Exit with error: {ret}, error message: \
+                 {:?}",
+                get_last_error()
+            );
+            output.truncate(output_len as usize);
+            outputs.push(output);
+        }
+        assert_eq!(PERSISTED_COUNTER.load(Ordering::SeqCst), 9);
+
+        // No nonce is used if the output buffer is too small.
+        let mut output = vec![0u8; 8];
+        let mut output_len = output.len() as i32;
+        let ret = h_aes256gcm_encryptor_encrypt(
+            output.as_mut_ptr(),
+            &mut output_len,
+            handle,
+            plaintext.as_ptr().cast(),
+            plaintext.len() as i32,
+            authenticated_data.as_ptr().cast(),
+            authenticated_data.len() as i32,
+        );
+        assert_eq!(ret, 1);
+        assert_eq!(
+            output_len as usize,
+            Aes256Gcm::NONCE_LENGTH + plaintext.len() + Aes256Gcm::MAC_LENGTH
+        );
+        assert_eq!(PERSISTED_COUNTER.load(Ordering::SeqCst), 9);
+
+        // Each encryption uses a different nonce.
+        let (nonce, ciphertext) = outputs[0].split_at(Aes256Gcm::NONCE_LENGTH);
+        assert_ne!(nonce, &outputs[1][..Aes256Gcm::NONCE_LENGTH]);
+
+        let mut cleartext = vec![0u8; ciphertext.len()];
+        let mut cleartext_len = cleartext.len() as i32;
+        let ret = h_aes256gcm_decrypt(
+            cleartext.as_mut_ptr(),
+            &mut cleartext_len,
+            ciphertext.as_ptr().cast(),
+            ciphertext.len() as i32,
+            key.as_ptr().cast(),
+            key.len() as i32,
+            nonce.as_ptr().cast(),
+            nonce.len() as i32,
+            authenticated_data.as_ptr().cast(),
+            authenticated_data.len() as i32,
+        );
+        assert!(
+            0 == ret,
+            "AESGCM FFI decryption failed. Exit with error: {ret}, error message: {:?}",
+            get_last_error()
+        );
+        assert_eq!(plaintext.to_vec(), cleartext[..cleartext_len as usize]);
+
+        assert_eq!(0, h_aes256gcm_encryptor_destroy(handle));
+
+        // The destroyed encryptor cannot be used anymore.
+        let mut output = vec![0u8; 64];
+        let mut output_len = output.len() as i32;
+        let ret = h_aes256gcm_encryptor_encrypt(
+            output.as_mut_ptr(),
+            &mut output_len,
+            handle,
+            plaintext.as_ptr().cast(),
+            plaintext.len() as i32,
+            authenticated_data.as_ptr().cast(),
+            authenticated_data.len() as i32,
+        );
+        assert_ne!(0, ret);
+    }
+}
+
+extern "C" fn reentrant_persist_counter(_next_counter: u64) -> i32 {
+    // Reclaiming an unknown encryptor needs the encryptor map.
+    unsafe { h_aes256gcm_encryptor_destroy(i32::MAX) }
+}
+
+#[test]
+fn test_aes256gcm_encryptor_reentrant_callback() {
+    let key = [42_u8; Aes256Gcm::KEY_LENGTH];
+    let plaintext = b"plaintext";
+
+    unsafe {
+        let mut handle = -1;
+        let ret = h_aes256gcm_encryptor_new(
+            &mut handle,
+            key.as_ptr().cast(),
+            key.len() as i32,
+            0,
+            reentrant_persist_counter,
+        );
+        assert_eq!(ret, 0);
+
+        let mut output =
+            vec![0u8; Aes256Gcm::NONCE_LENGTH + plaintext.len() + Aes256Gcm::MAC_LENGTH];
+        let mut output_len = output.len() as i32;
+        let ret = h_aes256gcm_encryptor_encrypt(
+            output.as_mut_ptr(),
+            &mut output_len,
+            handle,
+            plaintext.as_ptr().cast(),
+            plaintext.len() as i32,
+            plaintext.as_ptr().cast(),
+            plaintext.len() as i32,
+        );
+        assert!(
+            0 == ret,
+            "AESGCM FFI encryptor encryption failed. Exit with error: {ret}, error message: {:?}",
+            get_last_error()
+        );
+        assert_eq!(0, h_aes256gcm_encryptor_destroy(handle));
+    }
+}
+
+#[test]
+fn test_aes256gcm_derive_key() {
+    let master_key = [42_u8; 32];
+    let context = b"record 1";
+    let salt = b"salt";
+
+    unsafe {
+        let mut key = [0_u8; Aes256Gcm::KEY_LENGTH];
+        let mut key_len = key.len() as i32;
+        let ret = h_aes256gcm_derive_key(
+            key.as_mut_ptr(),
+            &mut key_len,
+            master_key.as_ptr().cast(),
+            master_key.len() as i32,
+            context.as_ptr().cast(),
+            context.len() as i32,
+            salt.as_ptr().cast(),
+            salt.len() as i32,
+        );
+        assert!(
+            0 == ret,
+            "AESGCM FFI key derivation failed. Exit with error: {ret}, error message: {:?}",
+            get_last_error()
+        );
+        assert_eq!(key_len as usize, Aes256Gcm::KEY_LENGTH);
+        assert_eq!(key, derive_key(&master_key, context, salt).unwrap());
+
+        // A null salt is an empty salt.
+        let ret = h_aes256gcm_derive_key(
+            key.as_mut_ptr(),
+            &mut key_len,
+            master_key.as_ptr().cast(),
+            master_key.len() as i32,
+            context.as_ptr().cast(),
+            context.len() as i32,
+            std::ptr::null(),
+            0,
+        );
+        assert_eq!(0, ret);
+        assert_eq!(key, derive_key(&master_key, context, b"").unwrap());
+    }
+}
+
+#[test]
+fn test_xchacha20poly1305_encrypt_decrypt() {
+    let key = [42_u8; XChaCha20Poly1305::KEY_LENGTH];
+    let nonce = [42_u8; XChaCha20Poly1305::NONCE_LENGTH];
+    let authenticated_data = b"authenticated_data";
+    let plaintext = b"plaintext";
+
+    unsafe {
+        let mut ciphertext = vec![0u8; plaintext.len() + XChaCha20Poly1305::MAC_LENGTH];
+        let mut ciphertext_len = ciphertext.len() as i32;
+        let ret = h_xchacha20poly1305_encrypt(
+            ciphertext.as_mut_ptr(),
+            &mut ciphertext_len,
+            plaintext.as_ptr().cast(),
+            plaintext.len() as i32,
+            key.as_ptr().cast(),
+            key.len() as i32,
+            nonce.as_ptr().cast(),
+            nonce.len() as i32,
+            authenticated_data.as_ptr().cast(),
+            authenticated_data.len() as i32,
+        );
+        assert!(
+            0 == ret,
+            "XChaCha20-Poly1305 FFI encryption failed. Exit with error: {ret}, error message: \
+             {:?}",
+            get_last_error()
+        );
+
+        let mut cleartext = vec![0u8; ciphertext_len as usize];
+        let mut cleartext_len = cleartext.len() as i32;
+        let ret = h_xchacha20poly1305_decrypt(
+            cleartext.as_mut_ptr(),
+            &mut cleartext_len,
+            ciphertext.as_ptr().cast(),
+            ciphertext_len,
+            key.as_ptr().cast(),
+            key.len() as i32,
+            nonce.as_ptr().cast(),
+            nonce.len() as i32,
+            authenticated_data.as_ptr().cast(),
+            authenticated_data.len() as i32,
+        );
+        assert!(
+            0 == ret,
+            "XChaCha20-Poly1305 FFI decryption failed. Exit with error: {ret}, error message: \
+             {:?}",
+            get_last_error()
+        );
+        assert_eq!(plaintext.to_vec(), cleartext[..cleartext_len as usize]);
+
+        // AES256 GCM cannot decrypt XChaCha20-Poly1305 ciphertexts.
+        let ret = h_aes256gcm_decrypt(
+            cleartext.as_mut_ptr(),
+            &mut cleartext_len,
+            ciphertext.as_ptr().cast(),
+            ciphertext_len,
+            key.as_ptr().cast(),
+            key.len() as i32,
+            nonce[..Aes256Gcm::NONCE_LENGTH].as_ptr().cast(),
+            Aes256Gcm::NONCE_LENGTH as i32,
+            authenticated_data.as_ptr().cast(),
+            authenticated_data.len() as i32,
+        );
+        assert_ne!(0, ret);
+    }
+}
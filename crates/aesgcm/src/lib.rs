@@ -1,6 +1,9 @@
-/// The `cloudproof_rust` subcrate `aesgcm` brings the standard AES256 GCM
-/// implementation which has been audited by the NCC Group, with no significant
-/// findings. Refer to <https://research.nccgroup.com/2020/02/26/public-report-rustcrypto-aes-gcm-and-chacha20poly1305-implementation-review/>
+//! The `cloudproof_rust` subcrate `aesgcm` brings the standard AES256 GCM
+//! implementation which has been audited by the NCC Group, with no significant
+//! findings. Refer to <https://research.nccgroup.com/2020/02/26/public-report-rustcrypto-aes-gcm-and-chacha20poly1305-implementation-review/>
+//!
+//! XChaCha20-Poly1305 is also provided for the platforms without hardware AES
+//! support, where AES GCM is slow.
 
 #[cfg(feature = "ffi")]
 pub mod ffi;
@@ -14,4 +17,11 @@ pub mod wasm_bindgen;
 mod core;
 mod error;
 
-pub use crate::core::aesgcm::{decrypt, encrypt};
+pub use crate::core::{
+    aesgcm::{decrypt, encrypt, encrypt_in_place},
+    encryptor::{Aes256GcmEncryptor, PersistCounter},
+    kdf::{derive_key, MIN_MASTER_KEY_LENGTH},
+    xchacha20poly1305::{
+        decrypt as xchacha20poly1305_decrypt, encrypt as xchacha20poly1305_encrypt,
+    },
+};
@@ -1,29 +1,208 @@
-use pyo3::{pyclass, pymethods, PyResult};
+use std::ops::Deref;
 
-use crate::{decrypt, encrypt};
+#[cfg(any(not(Py_LIMITED_API), Py_3_11))]
+use pyo3::{buffer::PyBuffer, exceptions::PyBufferError};
+use pyo3::{
+    pyclass, pymethods, types::PyBytes, FromPyObject, Py, PyAny, PyObject, PyResult, Python,
+};
+
+use crate::{
+    decrypt, derive_key, encrypt, xchacha20poly1305_decrypt, xchacha20poly1305_encrypt,
+    Aes256GcmEncryptor as Aes256GcmEncryptorRust,
+};
+
+/// Read-only view over any contiguous Python object implementing the buffer
+/// protocol (`bytes`, `bytearray`, `memoryview`, numpy arrays...).
+///
+/// The bytes are read in place: the buffers must not be modified by another
+/// thread during the call. The Python 3.7 stable ABI targeted by the CPython
+/// wheels does not expose the buffer protocol, in which case the buffers other
+/// than `bytes` are copied.
+pub enum BytesLike<'a> {
+    Bytes(&'a [u8]),
+    #[cfg(any(not(Py_LIMITED_API), Py_3_11))]
+    Buffer(PyBuffer<u8>),
+}
+
+impl<'a> FromPyObject<'a> for BytesLike<'a> {
+    #[cfg(any(not(Py_LIMITED_API), Py_3_11))]
+    fn extract(ob: &'a PyAny) -> PyResult<Self> {
+        if let Ok(bytes) = ob.downcast::<PyBytes>() {
+            return Ok(Self::Bytes(bytes.as_bytes()));
+        }
+        let buffer = PyBuffer::<u8>::get(ob)?;
+        if !buffer.is_c_contiguous() {
+            return Err(PyBufferError::new_err("buffer is not contiguous"));
+        }
+        Ok(Self::Buffer(buffer))
+    }
+
+    #[cfg(not(any(not(Py_LIMITED_API), Py_3_11)))]
+    fn extract(ob: &'a PyAny) -> PyResult<Self> {
+        if let Ok(bytes) = ob.downcast::<PyBytes>() {
+            return Ok(Self::Bytes(bytes.as_bytes()));
+        }
+        let bytes = ob
+            .py()
+            .get_type::<PyBytes>()
+            .call1((ob,))?
+            .downcast::<PyBytes>()?;
+        Ok(Self::Bytes(bytes.as_bytes()))
+    }
+}
+
+impl Deref for BytesLike<'_> {
+    type Target = [u8];
+
+    fn deref(&self) -> &Self::Target {
+        match self {
+            Self::Bytes(bytes) => bytes,
+            #[cfg(any(not(Py_LIMITED_API), Py_3_11))]
+            Self::Buffer(buffer) => {
+                if buffer.len_bytes() == 0 {
+                    return &[];
+                }
+                // SAFETY: the buffer is C-contiguous, holds `len_bytes()`
+                // bytes and stays valid until it is released on drop.
+                unsafe {
+                    std::slice::from_raw_parts(buffer.buf_ptr().cast::<u8>(), buffer.len_bytes())
+                }
+            }
+        }
+    }
+}
 
 #[pyclass]
 pub struct Aes256Gcm;
 
 #[pymethods]
 impl Aes256Gcm {
+    /// Encrypts `plaintext`, releasing the GIL during the encryption.
     #[staticmethod]
     fn encrypt(
-        key: Vec<u8>,
-        nonce: Vec<u8>,
-        plaintext: Vec<u8>,
-        authenticated_data: Vec<u8>,
-    ) -> PyResult<Vec<u8>> {
-        Ok(encrypt(&key, &nonce, &plaintext, &authenticated_data)?)
+        key: BytesLike,
+        nonce: BytesLike,
+        plaintext: BytesLike,
+        authenticated_data: BytesLike,
+        py: Python,
+    ) -> PyResult<Py<PyBytes>> {
+        let ciphertext =
+            py.allow_threads(|| encrypt(&key, &nonce, &plaintext, &authenticated_data))?;
+        Ok(PyBytes::new(py, &ciphertext).into())
     }
 
+    /// Decrypts `ciphertext`, releasing the GIL during the decryption.
     #[staticmethod]
     fn decrypt(
-        key: Vec<u8>,
-        nonce: Vec<u8>,
-        ciphertext: Vec<u8>,
-        authenticated_data: Vec<u8>,
-    ) -> PyResult<Vec<u8>> {
-        Ok(decrypt(&key, &nonce, &ciphertext, &authenticated_data)?)
+        key: BytesLike,
+        nonce: BytesLike,
+        ciphertext: BytesLike,
+        authenticated_data: BytesLike,
+        py: Python,
+    ) -> PyResult<Py<PyBytes>> {
+        let plaintext =
+            py.allow_threads(|| decrypt(&key, &nonce, &ciphertext, &authenticated_data))?;
+        Ok(PyBytes::new(py, &plaintext).into())
+    }
+
+    /// Derives a key bound to `context` from `master_key` using HKDF-SHA256.
+    #[staticmethod]
+    fn derive_key(
+        master_key: BytesLike,
+        context: BytesLike,
+        salt: BytesLike,
+        py: Python,
+    ) -> PyResult<Py<PyBytes>> {
+        let key = derive_key(&master_key, &context, &salt)?;
+        Ok(PyBytes::new(py, &key).into())
+    }
+}
+
+/// XChaCha20-Poly1305 encryption, faster than AES256 GCM on the platforms
+/// without hardware AES support.
+#[pyclass]
+pub struct XChaCha20Poly1305;
+
+#[pymethods]
+impl XChaCha20Poly1305 {
+    /// Encrypts `plaintext`, releasing the GIL during the encryption.
+    #[staticmethod]
+    fn encrypt(
+        key: BytesLike,
+        nonce: BytesLike,
+        plaintext: BytesLike,
+        authenticated_data: BytesLike,
+        py: Python,
+    ) -> PyResult<Py<PyBytes>> {
+        let ciphertext = py.allow_threads(|| {
+            xchacha20poly1305_encrypt(&key, &nonce, &plaintext, &authenticated_data)
+        })?;
+        Ok(PyBytes::new(py, &ciphertext).into())
+    }
+
+    /// Decrypts `ciphertext`, releasing the GIL during the decryption.
+    #[staticmethod]
+    fn decrypt(
+        key: BytesLike,
+        nonce: BytesLike,
+        ciphertext: BytesLike,
+        authenticated_data: BytesLike,
+        py: Python,
+    ) -> PyResult<Py<PyBytes>> {
+        let plaintext = py.allow_threads(|| {
+            xchacha20poly1305_decrypt(&key, &nonce, &ciphertext, &authenticated_data)
+        })?;
+        Ok(PyBytes::new(py, &plaintext).into())
+    }
+}
+
+/// AES256 GCM encryptor managing its own nonces.
+///
+/// Before each encryption, the next counter value is given to the
+/// `persist_counter` callable. If it raises, the encryption is aborted.
+#[pyclass]
+pub struct Aes256GcmEncryptor(Aes256GcmEncryptorRust);
+
+#[pymethods]
+impl Aes256GcmEncryptor {
+    #[new]
+    fn new(key: BytesLike, next_counter: u64, persist_counter: PyObject) -> PyResult<Self> {
+        let persist_counter = Box::new(move |counter: u64| {
+            Python::with_gil(|py| {
+                persist_counter
+                    .call1(py, (counter,))
+                    .map(|_| ())
+                    .map_err(|e| e.to_string())
+            })
+        });
+        Ok(Self(Aes256GcmEncryptorRust::new(
+            &key,
+            next_counter,
+            persist_counter,
+        )?))
+    }
+
+    /// Counter value that will be used by the next encryption.
+    #[getter]
+    fn next_counter(&self) -> u64 {
+        self.0.next_counter()
+    }
+
+    /// Encrypts `plaintext` using the next nonce, releasing the GIL during
+    /// the encryption.
+    ///
+    /// Returns the nonce used and the ciphertext.
+    fn encrypt_with_counter(
+        &mut self,
+        plaintext: BytesLike,
+        authenticated_data: BytesLike,
+        py: Python,
+    ) -> PyResult<(Py<PyBytes>, Py<PyBytes>)> {
+        let (nonce, ciphertext) =
+            py.allow_threads(|| self.0.encrypt_with_counter(&plaintext, &authenticated_data))?;
+        Ok((
+            PyBytes::new(py, &nonce).into(),
+            PyBytes::new(py, &ciphertext).into(),
+        ))
     }
 }
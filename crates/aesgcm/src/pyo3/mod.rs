@@ -1,6 +1,6 @@
 use pyo3::{pymodule, types::PyModule, PyResult, Python};
 
-use self::aesgcm::Aes256Gcm;
+use self::aesgcm::{Aes256Gcm, Aes256GcmEncryptor, XChaCha20Poly1305};
 
 mod aesgcm;
 
@@ -8,6 +8,8 @@ mod aesgcm;
 #[pymodule]
 fn cloudproof_aesgcm(_py: Python, m: &PyModule) -> PyResult<()> {
     m.add_class::<Aes256Gcm>()?;
+    m.add_class::<Aes256GcmEncryptor>()?;
+    m.add_class::<XChaCha20Poly1305>()?;
 
     Ok(())
 }
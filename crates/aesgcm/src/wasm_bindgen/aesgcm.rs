@@ -1,7 +1,7 @@
 use js_sys::Uint8Array;
 use wasm_bindgen::{prelude::wasm_bindgen, JsValue};
 
-use crate::{decrypt, encrypt};
+use crate::{decrypt, derive_key, encrypt, xchacha20poly1305_decrypt, xchacha20poly1305_encrypt};
 
 fn aes256gcm(
     input_data: Vec<u8>,
@@ -38,3 +38,35 @@ pub fn webassembly_aes256gcm_decrypt(
 ) -> Result<Uint8Array, JsValue> {
     aes256gcm(ciphertext, key, nonce, authenticated_data, false)
 }
+
+#[wasm_bindgen]
+pub fn webassembly_aes256gcm_derive_key(
+    master_key: Vec<u8>,
+    context: Vec<u8>,
+    salt: Vec<u8>,
+) -> Result<Uint8Array, JsValue> {
+    let key = derive_key(&master_key, &context, &salt)?;
+    Ok(Uint8Array::from(key.as_slice()))
+}
+
+#[wasm_bindgen]
+pub fn webassembly_xchacha20poly1305_encrypt(
+    plaintext: Vec<u8>,
+    key: Vec<u8>,
+    nonce: Vec<u8>,
+    authenticated_data: Vec<u8>,
+) -> Result<Uint8Array, JsValue> {
+    let ciphertext = xchacha20poly1305_encrypt(&key, &nonce, &plaintext, &authenticated_data)?;
+    Ok(Uint8Array::from(ciphertext.as_slice()))
+}
+
+#[wasm_bindgen]
+pub fn webassembly_xchacha20poly1305_decrypt(
+    ciphertext: Vec<u8>,
+    key: Vec<u8>,
+    nonce: Vec<u8>,
+    authenticated_data: Vec<u8>,
+) -> Result<Uint8Array, JsValue> {
+    let plaintext = xchacha20poly1305_decrypt(&key, &nonce, &ciphertext, &authenticated_data)?;
+    Ok(Uint8Array::from(plaintext.as_slice()))
+}
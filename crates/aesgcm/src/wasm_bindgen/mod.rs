@@ -1,4 +1,5 @@
 mod aesgcm;
+mod subtle;
 
 #[cfg(test)]
 mod tests;
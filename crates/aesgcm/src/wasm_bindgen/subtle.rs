@@ -0,0 +1,155 @@
+//! AES-256-GCM delegated to the WebCrypto API.
+//!
+//! In browsers, `crypto.subtle` is hardware accelerated and much faster than
+//! the WASM implementation on large files. It is only available in secure
+//! contexts (HTTPS pages): elsewhere, these functions fall back to the Rust
+//! implementation.
+//!
+//! The produced ciphertexts are the same as the ones produced by the
+//! synchronous functions: `webassembly_aes256gcm_encrypt_async` and
+//! `webassembly_aes256gcm_encrypt` outputs can be decrypted by either
+//! `webassembly_aes256gcm_decrypt_async` or `webassembly_aes256gcm_decrypt`.
+
+use cosmian_crypto_core::Aes256Gcm;
+use js_sys::{Array, Function, Object, Promise, Reflect, Uint8Array};
+use wasm_bindgen::{prelude::wasm_bindgen, JsCast, JsValue};
+use wasm_bindgen_futures::JsFuture;
+
+use crate::{decrypt, encrypt, error::AesGcmError};
+
+fn get_property(value: &JsValue, property: &str) -> Result<JsValue, JsValue> {
+    Reflect::get(value, &JsValue::from_str(property))
+}
+
+/// Returns `crypto.subtle`, or `None` if the WebCrypto API is not available,
+/// e.g. in non-secure contexts.
+fn subtle() -> Option<JsValue> {
+    let crypto = get_property(&js_sys::global(), "crypto").ok()?;
+    let subtle = get_property(&crypto, "subtle").ok()?;
+    (!subtle.is_undefined() && !subtle.is_null()).then_some(subtle)
+}
+
+/// Calls the given method of the given object and awaits the returned
+/// promise.
+async fn call_async_method(
+    object: &JsValue,
+    method: &str,
+    args: &Array,
+) -> Result<JsValue, JsValue> {
+    let promise = get_property(object, method)?
+        .dyn_into::<Function>()?
+        .apply(object, args)?
+        .dyn_into::<Promise>()?;
+    JsFuture::from(promise).await
+}
+
+/// Encrypts or decrypts the given data using `crypto.subtle`.
+async fn subtle_aes256gcm(
+    subtle: &JsValue,
+    method: &str,
+    key: &[u8],
+    nonce: &[u8],
+    data: &Uint8Array,
+    authenticated_data: &Uint8Array,
+) -> Result<Uint8Array, JsValue> {
+    let algorithm = Object::new();
+    Reflect::set(&algorithm, &"name".into(), &"AES-GCM".into())?;
+    let args = Array::new();
+    args.push(&"raw".into());
+    args.push(&Uint8Array::from(key));
+    args.push(&algorithm);
+    args.push(&JsValue::FALSE);
+    args.push(&Array::of1(&method.into()));
+    let key = call_async_method(subtle, "importKey", &args).await?;
+
+    let params = Object::new();
+    Reflect::set(&params, &"name".into(), &"AES-GCM".into())?;
+    Reflect::set(&params, &"iv".into(), &Uint8Array::from(nonce))?;
+    Reflect::set(
+        &params,
+        &"tagLength".into(),
+        &((Aes256Gcm::MAC_LENGTH * 8) as u32).into(),
+    )?;
+    Reflect::set(&params, &"additionalData".into(), authenticated_data)?;
+
+    let res = call_async_method(subtle, method, &Array::of3(&params, &key, data)).await?;
+    Ok(Uint8Array::new(&res))
+}
+
+/// Checks the lengths of the given key and nonce, the WebCrypto API accepting
+/// other lengths.
+fn check_lengths(key: &[u8], nonce: &[u8]) -> Result<(), AesGcmError> {
+    <[u8; Aes256Gcm::KEY_LENGTH]>::try_from(key)?;
+    <[u8; Aes256Gcm::NONCE_LENGTH]>::try_from(nonce)?;
+    Ok(())
+}
+
+/// Encrypts the given plaintext using `crypto.subtle`, or the Rust
+/// implementation if the WebCrypto API is not available.
+///
+/// Same parameters and output as `webassembly_aes256gcm_encrypt`.
+#[wasm_bindgen]
+pub async fn webassembly_aes256gcm_encrypt_async(
+    plaintext: Uint8Array,
+    key: Vec<u8>,
+    nonce: Vec<u8>,
+    authenticated_data: Uint8Array,
+) -> Result<Uint8Array, JsValue> {
+    check_lengths(&key, &nonce)?;
+    if let Some(subtle) = subtle() {
+        subtle_aes256gcm(
+            &subtle,
+            "encrypt",
+            &key,
+            &nonce,
+            &plaintext,
+            &authenticated_data,
+        )
+        .await
+        .map_err(|e| JsValue::from_str(&format!("Error encrypting plaintext: {e:?}")))
+    } else {
+        let ciphertext = encrypt(
+            &key,
+            &nonce,
+            &plaintext.to_vec(),
+            &authenticated_data.to_vec(),
+        )?;
+        Ok(Uint8Array::from(ciphertext.as_slice()))
+    }
+}
+
+/// Decrypts the given ciphertext using `crypto.subtle`, or the Rust
+/// implementation if the WebCrypto API is not available.
+///
+/// Same parameters and output as `webassembly_aes256gcm_decrypt`.
+#[wasm_bindgen]
+pub async fn webassembly_aes256gcm_decrypt_async(
+    ciphertext: Uint8Array,
+    key: Vec<u8>,
+    nonce: Vec<u8>,
+    authenticated_data: Uint8Array,
+) -> Result<Uint8Array, JsValue> {
+    check_lengths(&key, &nonce)?;
+    if let Some(subtle) = subtle() {
+        // `crypto.subtle` rejects with an `OperationError` upon authentication
+        // failure.
+        subtle_aes256gcm(
+            &subtle,
+            "decrypt",
+            &key,
+            &nonce,
+            &ciphertext,
+            &authenticated_data,
+        )
+        .await
+        .map_err(|e| JsValue::from_str(&format!("Error decrypting ciphertext: {e:?}")))
+    } else {
+        let plaintext = decrypt(
+            &key,
+            &nonce,
+            &ciphertext.to_vec(),
+            &authenticated_data.to_vec(),
+        )?;
+        Ok(Uint8Array::from(plaintext.as_slice()))
+    }
+}
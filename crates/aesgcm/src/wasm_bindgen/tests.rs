@@ -1,7 +1,15 @@
-use cosmian_crypto_core::Aes256Gcm;
+use cosmian_crypto_core::{Aes256Gcm, XChaCha20Poly1305};
+use js_sys::Uint8Array;
 use wasm_bindgen_test::wasm_bindgen_test;
 
-use crate::wasm_bindgen::aesgcm::{webassembly_aes256gcm_decrypt, webassembly_aes256gcm_encrypt};
+use crate::wasm_bindgen::{
+    aesgcm::{
+        webassembly_aes256gcm_decrypt, webassembly_aes256gcm_derive_key,
+        webassembly_aes256gcm_encrypt, webassembly_xchacha20poly1305_decrypt,
+        webassembly_xchacha20poly1305_encrypt,
+    },
+    subtle::{webassembly_aes256gcm_decrypt_async, webassembly_aes256gcm_encrypt_async},
+};
 
 #[wasm_bindgen_test]
 fn test_encrypt_decrypt() {
@@ -25,3 +33,93 @@ fn test_encrypt_decrypt() {
     .unwrap();
     assert_eq!(plaintext.to_vec(), cleartext.to_vec());
 }
+
+#[wasm_bindgen_test]
+async fn test_encrypt_decrypt_async() {
+    let key = [42_u8; Aes256Gcm::KEY_LENGTH];
+    let nonce = [42_u8; Aes256Gcm::NONCE_LENGTH];
+    let authentication_data = vec![0_u8; 1024];
+    let plaintext = vec![1_u8; 1 << 20];
+
+    // Ciphertexts are the same as the ones of the synchronous functions.
+    let ciphertext = webassembly_aes256gcm_encrypt_async(
+        Uint8Array::from(plaintext.as_slice()),
+        key.to_vec(),
+        nonce.to_vec(),
+        Uint8Array::from(authentication_data.as_slice()),
+    )
+    .await
+    .unwrap();
+    assert_eq!(
+        ciphertext.to_vec(),
+        webassembly_aes256gcm_encrypt(
+            plaintext.clone(),
+            key.to_vec(),
+            nonce.to_vec(),
+            authentication_data.clone(),
+        )
+        .unwrap()
+        .to_vec()
+    );
+    let cleartext = webassembly_aes256gcm_decrypt_async(
+        ciphertext.clone(),
+        key.to_vec(),
+        nonce.to_vec(),
+        Uint8Array::from(authentication_data.as_slice()),
+    )
+    .await
+    .unwrap();
+    assert_eq!(plaintext, cleartext.to_vec());
+
+    // Authentication failures and invalid keys are reported.
+    assert!(webassembly_aes256gcm_decrypt_async(
+        ciphertext,
+        key.to_vec(),
+        nonce.to_vec(),
+        Uint8Array::new_with_length(0),
+    )
+    .await
+    .is_err());
+    assert!(webassembly_aes256gcm_encrypt_async(
+        Uint8Array::from(plaintext.as_slice()),
+        vec![42; 16],
+        nonce.to_vec(),
+        Uint8Array::new_with_length(0),
+    )
+    .await
+    .is_err());
+}
+
+#[wasm_bindgen_test]
+fn test_derive_key() {
+    let master_key = vec![42_u8; 32];
+    let key_1 =
+        webassembly_aes256gcm_derive_key(master_key.clone(), b"record 1".to_vec(), vec![]).unwrap();
+    let key_2 = webassembly_aes256gcm_derive_key(master_key, b"record 2".to_vec(), vec![]).unwrap();
+    assert_eq!(key_1.length() as usize, Aes256Gcm::KEY_LENGTH);
+    assert_ne!(key_1.to_vec(), key_2.to_vec());
+    assert!(webassembly_aes256gcm_derive_key(vec![42; 16], vec![], vec![]).is_err());
+}
+
+#[wasm_bindgen_test]
+fn test_xchacha20poly1305_encrypt_decrypt() {
+    let key = [42_u8; XChaCha20Poly1305::KEY_LENGTH];
+    let nonce = [42_u8; XChaCha20Poly1305::NONCE_LENGTH];
+    let authentication_data = vec![0_u8; 1024];
+    let plaintext = b"plaintext";
+    let ciphertext = webassembly_xchacha20poly1305_encrypt(
+        plaintext.to_vec(),
+        key.to_vec(),
+        nonce.to_vec(),
+        authentication_data.clone(),
+    )
+    .unwrap();
+    let cleartext = webassembly_xchacha20poly1305_decrypt(
+        ciphertext.to_vec(),
+        key.to_vec(),
+        nonce.to_vec(),
+        authentication_data,
+    )
+    .unwrap();
+    assert_eq!(plaintext.to_vec(), cleartext.to_vec());
+}
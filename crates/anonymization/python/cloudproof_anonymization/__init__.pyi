@@ -1,4 +1,4 @@
-from typing import List, Optional
+from typing import Any, List, Optional, Tuple
 
 class Hasher:
     """
@@ -44,7 +44,7 @@ class NoiseGenerator:
 
     @staticmethod
     def new_with_parameters(
-        method_name: str, mean: float, std_dev: float
+        method_name: str, mean: float, std_dev: float, seed: Optional[int] = None
     ) -> NoiseGenerator:
         """
         Instantiate a `NoiseGenerator` using mean and standard deviation.
@@ -53,10 +53,14 @@ class NoiseGenerator:
             method_name (str): The noise distribution to use ("Gaussian" or "Laplace").
             mean (float): Mean of the noise distribution.
             std_dev (float): The standard deviation of the noise distribution.
+            seed (Optional[int]): Seed making the noise reproducible, for tests only.
         """
     @staticmethod
     def new_with_bounds(
-        method_name: str, min_bound: float, max_bound: float
+        method_name: str,
+        min_bound: float,
+        max_bound: float,
+        seed: Optional[int] = None,
     ) -> NoiseGenerator:
         """
         Instantiate a `NoiseGenerator` with bound constraints.
@@ -65,6 +69,34 @@ class NoiseGenerator:
             method_name (str): The noise distribution to use ("Uniform", "Gaussian", or "Laplace").
             min_bound (float): The lower bound of the range of possible generated noise values.
             max_bound (float): The upper bound of the range of possible generated noise values.
+            seed (Optional[int]): Seed making the noise reproducible, for tests only.
+        """
+    @staticmethod
+    def new_with_parameters_and_bounds(
+        method_name: str,
+        mean: float,
+        std_dev: float,
+        min_bound: float,
+        max_bound: float,
+        seed: Optional[int] = None,
+    ) -> NoiseGenerator:
+        """
+        Instantiate a `NoiseGenerator` using mean and standard deviation, the distribution
+        being truncated to the given bounds: the noise never exceeds them.
+
+        Args:
+            method_name (str): The noise distribution to use ("Gaussian" or "Laplace").
+            mean (float): Mean of the noise distribution, between the bounds.
+            std_dev (float): The standard deviation of the noise distribution.
+            min_bound (float): The lower bound of the generated noise values.
+            max_bound (float): The upper bound of the generated noise values.
+            seed (Optional[int]): Seed making the noise reproducible, for tests only.
+        """
+    def is_seeded(self) -> bool:
+        """
+        Returns whether this generator was created with a seed. The noise of a
+        seeded generator can be removed by anyone knowing the seed: it must not
+        be used in production.
         """
     def apply_on_float(self, data: float) -> float:
         """
@@ -144,12 +176,14 @@ class WordMasker:
     A class to mask a list of words in a text.
     """
 
-    def __init__(self, words_list: List[str]) -> None:
+    def __init__(self, words_list: List[str], language: Optional[str] = None) -> None:
         """
         Creates a new WordMasker instance.
 
         Args:
             words_to_block (List[str]): A list of strings containing the words to be masked in the text.
+            language (Optional[str]): language of the texts, one of "English", "French" or
+                "German". The stop words of this language are never masked.
         """
     def apply(self, data: str) -> str:
         """
@@ -167,12 +201,14 @@ class WordTokenizer:
     A class to tokenize a list of words in a text.
     """
 
-    def __init__(self, words_list: List[str]) -> None:
+    def __init__(self, words_list: List[str], language: Optional[str] = None) -> None:
         """
         Creates a new instance of WordTokenizer.
 
         Args:
             words_list (List[str]): A list of strings representing the words to be replaced with tokens.
+            language (Optional[str]): language of the texts, one of "English", "French" or
+                "German". The stop words of this language are never replaced.
         """
     def apply(self, data: str) -> str:
         """
@@ -195,9 +231,17 @@ class WordPatternMasker:
         Creates a new instance of `WordPatternMasker` with the provided pattern
         regex and replace string.
 
+        The replace string may reference the capture groups of the pattern by
+        index or name, e.g. `${1}` or `${year}`, to keep parts of the matches.
+        Use `$$` for a literal `$`.
+
         Args:
             pattern (str): The pattern regex to search for.
             replace (str): The string to replace the matched patterns.
+
+        Raises:
+            Exception: if the pattern is invalid or the replace string
+            references a capture group absent from the pattern.
         """
     def apply(self, data: str) -> str:
         """
@@ -212,7 +256,7 @@ class WordPatternMasker:
 
 class NumberAggregator:
     """
-    A class to round numbers to a desired power of ten.
+    A class to round numbers to a desired power of ten, or to replace them with the label of their bucket.
     """
 
     def __init__(self, power_of_ten: int) -> None:
@@ -222,6 +266,22 @@ class NumberAggregator:
         Args:
             power_of_ten (int): The power of ten to round the numbers to.
         """
+    @staticmethod
+    def new_with_buckets(
+        edges: List[float], labels: Optional[List[str]] = None
+    ) -> NumberAggregator:
+        """
+        Instantiate a `NumberAggregator` replacing the numbers with the label of their bucket.
+
+        The `n` edges delimit `n + 1` buckets: the numbers lower than the first edge,
+        the numbers between two consecutive edges (lower edge included)
+        and the numbers greater than or equal to the last edge.
+
+        Args:
+            edges (List[float]): The bucket edges, in strictly increasing order.
+            labels (Optional[List[str]]): The labels of the `n + 1` buckets.
+                Defaults to the bucket bounds, e.g. `< 18`, `[18, 26)` and `>= 26`.
+        """
     def apply_on_float(self, data: float) -> str:
         """
         Rounds a floating point number to the desired power of ten.
@@ -266,20 +326,75 @@ class DateAggregator:
             str: The rounded date in RFC 3339.
         """
 
+class DateShifter:
+    """
+    A class shifting all the dates of a same entity by the same secret offset.
+    """
+
+    def __init__(self, key: bytes, max_shift: int, time_unit: str) -> None:
+        """
+        Creates a new instance of `DateShifter`.
+
+        Args:
+            key (bytes): secret key of at least 32 bytes used to derive the offsets.
+            max_shift (int): maximum absolute offset, expressed in `time_unit`.
+            time_unit (str): unit of the offsets, months and years are not supported.
+        """
+    def apply_on_date(self, entity_id: str, date_str: str) -> str:
+        """
+        Shifts the provided date by the offset associated to the entity.
+
+        Args:
+            entity_id (str): identifier of the entity the date belongs to.
+            date_str (str): A string representing the date to be shifted.
+
+        Returns:
+            str: The shifted date in RFC 3339.
+        """
+    def apply_on_dates(self, entity_id: str, dates: List[str]) -> List[str]:
+        """
+        Shifts all the provided dates by the offset associated to the entity.
+
+        Args:
+            entity_id (str): identifier of the entity the dates belong to.
+            dates (List[str]): strings representing the dates to be shifted.
+
+        Returns:
+            List[str]: The shifted dates in RFC 3339.
+        """
+
 class NumberScaler:
     """
     A class to scale and translate floating points or integer values.
     """
 
-    def __init__(self, mean: float, std_dev: float, scale: float, translation: float):
+    def __init__(
+        self,
+        mean: float,
+        std_dev: float,
+        scale: float,
+        translation: float,
+        key: Optional[bytes] = None,
+    ):
         """
         Initializes a new instance of `NumberScaler`.
 
+        When a key is given, the scaler is reversible: the scaling and
+        translation factors are modified using the key, and the key holders
+        can restore the original values. This is not encryption: anyone
+        knowing two original values and their scaled values can revert the
+        scaling without the key.
+
         Args:
             mean (float): The mean of the data distribution.
             std_dev (float): The standard deviation of the data distribution.
             scale (float): The scaling factor.
             translation (float): The translation factor.
+            key (Optional[bytes]): secret key of at least 32 bytes making the scaler reversible.
+        """
+    def is_reversible(self) -> bool:
+        """
+        Returns whether this scaler was created with a key and can be reverted.
         """
     def apply_on_float(self, data: float) -> float:
         """
@@ -301,3 +416,222 @@ class NumberScaler:
         Returns:
             int: The scaled value as an integer.
         """
+    def revert_on_float(self, data: float) -> float:
+        """
+        Restores a floating-point number scaled by a reversible scaler.
+
+        Args:
+            data (float): A scaled floating-point number.
+
+        Returns:
+            float: The original value.
+        """
+    def revert_on_int(self, data: int) -> int:
+        """
+        Restores an integer scaled by a reversible scaler.
+
+        Args:
+            data (int): A scaled integer.
+
+        Returns:
+            int: The original value.
+        """
+
+class GeoMasker:
+    """
+    A class hiding the precise location of geographic coordinates, either by
+    moving them within a radius or by reducing them to a geohash.
+    """
+
+    def __init__(
+        self, radius: float, key: Optional[bytes] = None, seed: Optional[int] = None
+    ) -> None:
+        """
+        Creates a new instance of `GeoMasker`.
+
+        When a key is given, the coordinates of a same entity can be moved by
+        the same secret displacement, derived from the entity identifier.
+
+        Args:
+            radius (float): maximum displacement of the coordinates, in meters.
+            key (Optional[bytes]): secret key of at least 32 bytes used to derive the displacements.
+            seed (Optional[int]): seed making the random displacements reproducible, for tests only.
+        """
+    def is_consistent(self) -> bool:
+        """
+        Returns whether this masker was created with a key.
+        """
+    def is_seeded(self) -> bool:
+        """
+        Returns whether this masker was created with a seed. A seeded masker
+        must not be used in production.
+        """
+    def apply_on_coordinates(
+        self, latitude: float, longitude: float
+    ) -> Tuple[float, float]:
+        """
+        Moves the provided coordinates to a random point within the radius.
+
+        Args:
+            latitude (float): latitude in degrees.
+            longitude (float): longitude in degrees.
+
+        Returns:
+            Tuple[float, float]: The blurred latitude and longitude.
+        """
+    def apply_on_entity_coordinates(
+        self, entity_id: str, latitude: float, longitude: float
+    ) -> Tuple[float, float]:
+        """
+        Moves the provided coordinates by the displacement associated to the entity.
+
+        Args:
+            entity_id (str): identifier of the entity the coordinates belong to.
+            latitude (float): latitude in degrees.
+            longitude (float): longitude in degrees.
+
+        Returns:
+            Tuple[float, float]: The blurred latitude and longitude.
+        """
+    @staticmethod
+    def to_geohash(latitude: float, longitude: float, precision: int) -> str:
+        """
+        Encodes the provided coordinates into a geohash.
+
+        Args:
+            latitude (float): latitude in degrees.
+            longitude (float): longitude in degrees.
+            precision (int): number of characters of the geohash, from 1 to 12.
+
+        Returns:
+            str: The geohash of the cell containing the coordinates.
+        """
+    @staticmethod
+    def truncate_geohash(geohash: str, precision: int) -> str:
+        """
+        Truncates the provided geohash.
+
+        Args:
+            geohash (str): a valid geohash.
+            precision (int): number of characters to keep, from 1 to 12.
+
+        Returns:
+            str: The truncated geohash.
+        """
+
+class EmailMasker:
+    """
+    A class masking the local part of email addresses while keeping their
+    domain, or the category of their domain.
+    """
+
+    def __init__(
+        self,
+        key: bytes,
+        local_part_masking: str = ...,
+        domain_masking: str = ...,
+    ) -> None:
+        """
+        Creates a new instance of `EmailMasker`.
+
+        Args:
+            key (bytes): secret key of 32 bytes.
+            local_part_masking (str): `Hash` to replace the local part with a keyed hash,
+                or `FPE` to encrypt it while keeping its length. Defaults to `Hash`.
+            domain_masking (str): `Preserve` to keep the domain, or `Category` to replace
+                it with `personal.invalid`, `education.invalid`, `government.invalid` or
+                `business.invalid`. Defaults to `Preserve`.
+        """
+    def apply(self, email: str) -> str:
+        """
+        Masks the provided email address.
+
+        Args:
+            email (str): the email address to mask.
+
+        Returns:
+            str: The masked email address.
+        """
+    def revert(self, masked_email: str) -> str:
+        """
+        Restores the local part of an email address masked using FPE.
+
+        Args:
+            masked_email (str): the masked email address.
+
+        Returns:
+            str: The email address, with its masked domain.
+        """
+
+class PhoneMasker:
+    """
+    A class masking the subscriber part of phone numbers while keeping their
+    country and area codes.
+    """
+
+    def __init__(self, area_code_digits: int, key: Optional[bytes] = None) -> None:
+        """
+        Creates a new instance of `PhoneMasker`.
+
+        The masked digits are replaced with `X`, or, when a key is given, with
+        digits derived from the phone number.
+
+        Args:
+            area_code_digits (int): number of digits kept after the country code or trunk prefix.
+            key (Optional[bytes]): secret key of at least 32 bytes used to derive the digits.
+        """
+    def apply(self, phone_number: str) -> str:
+        """
+        Masks the provided phone number, keeping its formatting.
+
+        Args:
+            phone_number (str): the phone number to mask.
+
+        Returns:
+            str: The masked phone number.
+        """
+
+class AnonymizationPipeline:
+    """
+    Applies an anonymization technique per column of CSV chunks or Arrow record batches.
+    """
+
+    def __init__(self, config: str, record_report: bool = False):
+        """
+        Args:
+            config (str): JSON object mapping column names to anonymization techniques.
+            record_report (bool, optional): Whether to record the transformations applied,
+                see `report`. Defaults to False.
+        """
+    def report(self) -> Optional[dict]:
+        """
+        Returns the report of the transformations applied so far, across all the
+        anonymized CSV chunks and record batches.
+
+        For each anonymized column, the report gives the technique applied, the
+        number of values anonymized and modified, and their total length before and
+        after anonymization. It holds no value.
+
+        Returns:
+            Optional[dict]: The report, or None if the transformations are not recorded.
+        """
+    def apply_to_csv(self, csv: str) -> str:
+        """
+        Anonymizes the configured columns of the provided CSV chunk.
+
+        Args:
+            csv (str): CSV chunk starting with its header line.
+
+        Returns:
+            str: The anonymized CSV chunk, with the same header line.
+        """
+    def apply_to_record_batch(self, batch: Any) -> Any:
+        """
+        Anonymizes the configured columns of the provided record batch.
+
+        Args:
+            batch (pyarrow.RecordBatch): record batch to anonymize.
+
+        Returns:
+            pyarrow.RecordBatch: The anonymized record batch.
+        """
@@ -2,12 +2,18 @@
 import unittest
 from datetime import datetime, timezone
 
+import pyarrow as pa
 from cloudproof_anonymization import (
+    AnonymizationPipeline,
     DateAggregator,
+    DateShifter,
+    EmailMasker,
+    GeoMasker,
     Hasher,
     NoiseGenerator,
     NumberAggregator,
     NumberScaler,
+    PhoneMasker,
     WordMasker,
     WordPatternMasker,
     WordTokenizer,
@@ -90,6 +96,39 @@ class TestNoiseGen(unittest.TestCase):
         with self.assertRaises(Exception):
             noise_generator = NoiseGenerator.new_with_parameters('Uniform', 1.0, 0.0)
 
+    def test_truncated_float(self) -> None:
+        for method_name in ['Gaussian', 'Laplace']:
+            noise_generator = NoiseGenerator.new_with_parameters_and_bounds(
+                method_name, 0.0, 10.0, -1.0, 2.0
+            )
+            for _ in range(100):
+                noisy_data = noise_generator.apply_on_float(40.0)
+                self.assertGreaterEqual(noisy_data, 39.0)
+                self.assertLessEqual(noisy_data, 42.0)
+
+            with self.assertRaises(Exception):
+                noise_generator = NoiseGenerator.new_with_parameters_and_bounds(
+                    method_name, 5.0, 1.0, -1.0, 1.0
+                )
+
+    def test_seeded(self) -> None:
+        noise_generator = NoiseGenerator.new_with_parameters('Gaussian', 0.0, 1.0)
+        self.assertFalse(noise_generator.is_seeded())
+
+        # generators created with the same seed generate the same noise
+        noisy_data = [
+            NoiseGenerator.new_with_bounds('Laplace', -10, 10, seed=42).apply_on_float(
+                40.0
+            )
+            for _ in range(2)
+        ]
+        self.assertEqual(noisy_data[0], noisy_data[1])
+
+        noise_generator = NoiseGenerator.new_with_parameters_and_bounds(
+            'Gaussian', 0.0, 1.0, -2.0, 2.0, seed=42
+        )
+        self.assertTrue(noise_generator.is_seeded())
+
     def test_gaussian_int(self) -> None:
         noise_generator = NoiseGenerator.new_with_parameters('Gaussian', 0.0, 1.0)
         noisy_data = noise_generator.apply_on_int(40)
@@ -201,6 +240,15 @@ class TestWordMasking(unittest.TestCase):
         expected_result = 'The XXXX! XXXX fox, Jumps over the lazy XXXX.'
         self.assertEqual(expected_result, word_masker.apply(data))
 
+    def test_word_masker_language(self) -> None:
+        word_masker = WordMasker(['été', 'de', 'Müller'], 'French')
+        data = "L'été de Jean-Müller"
+        expected_result = "L'XXXX de Jean-XXXX"
+        self.assertEqual(expected_result, word_masker.apply(data))
+
+        with self.assertRaises(Exception):
+            WordMasker(['été'], 'Klingon')
+
     def test_word_tokenizer(self) -> None:
         word_tokenizer = WordTokenizer(['password', 'secret'])
         text = 'My password is secret'
@@ -227,6 +275,17 @@ class TestWordMasking(unittest.TestCase):
         with self.assertRaises(Exception):
             WordPatternMasker('(', 'XXX')
 
+    def test_word_pattern_masker_capture_groups(self) -> None:
+        masker = WordPatternMasker(r'(?<year>\d{4})-\d{2}-\d{2}', '${year}-XX-XX')
+        self.assertEqual(
+            masker.apply('Born on 1987-04-12, hired on 2015-09-01.'),
+            'Born on 1987-XX-XX, hired on 2015-XX-XX.',
+        )
+
+        # Unknown capture group
+        with self.assertRaises(Exception):
+            WordPatternMasker(r'(\d{4})-\d{2}', '${month}')
+
 
 class TestAggregator(unittest.TestCase):
     def test_number_aggregator_with_invalid_exponent(self) -> None:
@@ -247,6 +306,23 @@ class TestAggregator(unittest.TestCase):
         self.assertEqual(na.apply_on_int(999), '1000')
         self.assertEqual(na.apply_on_int(499), '0')
 
+    def test_number_aggregator_with_buckets(self):
+        na = NumberAggregator.new_with_buckets(
+            [18, 26, 65], ['0-17', '18-25', '26-64', '65+']
+        )
+        self.assertEqual(na.apply_on_int(17), '0-17')
+        self.assertEqual(na.apply_on_int(18), '18-25')
+        self.assertEqual(na.apply_on_float(64.5), '26-64')
+        self.assertEqual(na.apply_on_int(65), '65+')
+
+        na = NumberAggregator.new_with_buckets([0.5, 10])
+        self.assertEqual(na.apply_on_int(1), '[0.5, 10)')
+
+        with self.assertRaises(Exception):
+            NumberAggregator.new_with_buckets([10, 1])
+        with self.assertRaises(Exception):
+            NumberAggregator.new_with_buckets([18], ['minor'])
+
     def test_date_aggregator(self):
         # Test rounding to the nearest minute
         aggregator = DateAggregator('Minute')
@@ -293,6 +369,33 @@ class TestAggregator(unittest.TestCase):
             aggregator = DateAggregator('InvalidUnit')
 
 
+class TestDateShifter(unittest.TestCase):
+    def test_date_shifter(self):
+        shifter = DateShifter(b'\x2a' * 32, 30, 'Day')
+        input_dates = ['2023-04-07T12:34:56-05:00', '2023-04-10T08:00:00-05:00']
+        shifted_dates = shifter.apply_on_dates('patient-1', input_dates)
+
+        # intervals between the dates of an entity are preserved
+        inputs = [datetime.fromisoformat(date) for date in input_dates]
+        outputs = [datetime.fromisoformat(date) for date in shifted_dates]
+        self.assertEqual(outputs[1] - outputs[0], inputs[1] - inputs[0])
+
+        shift = outputs[0] - inputs[0]
+        self.assertNotEqual(shift.days, 0)
+        self.assertLessEqual(abs(shift.days), 30)
+        self.assertEqual(outputs[0].tzinfo, inputs[0].tzinfo)
+
+        # offsets are deterministic
+        self.assertEqual(
+            shifter.apply_on_date('patient-1', input_dates[0]), shifted_dates[0]
+        )
+
+        with self.assertRaises(Exception):
+            DateShifter(b'short key', 30, 'Day')
+        with self.assertRaises(Exception):
+            DateShifter(b'\x2a' * 32, 30, 'Month')
+
+
 class TestNumberScaler(unittest.TestCase):
     def test_apply_on_float(self):
         # Test with scaling factor of 2 and translation factor of 1
@@ -320,6 +423,167 @@ class TestNumberScaler(unittest.TestCase):
         self.assertEqual(scaler.apply_on_int(9), -1)
         self.assertEqual(scaler.apply_on_int(11), 1)
 
+    def test_reversible(self):
+        key = b'\x2a' * 32
+        scaler = NumberScaler(10, 5, 20, -50, key)
+        self.assertTrue(scaler.is_reversible())
+        self.assertEqual(scaler.revert_on_int(scaler.apply_on_int(1234)), 1234)
+        self.assertAlmostEqual(
+            scaler.revert_on_float(scaler.apply_on_float(12.34)), 12.34
+        )
+
+        # Scalers without a key cannot be reverted
+        scaler = NumberScaler(10, 5, 20, -50)
+        self.assertFalse(scaler.is_reversible())
+        with self.assertRaises(Exception):
+            scaler.revert_on_int(1234)
+
+        # The key must be at least 32 bytes long
+        with self.assertRaises(Exception):
+            NumberScaler(10, 5, 20, -50, b'\x2a' * 16)
+
+
+class TestGeoMasker(unittest.TestCase):
+    def test_blurring(self):
+        masker = GeoMasker(1000)
+        self.assertFalse(masker.is_consistent())
+        latitude, longitude = masker.apply_on_coordinates(48.8566, 2.3522)
+        self.assertAlmostEqual(latitude, 48.8566, delta=0.01)
+        self.assertAlmostEqual(longitude, 2.3522, delta=0.02)
+
+        with self.assertRaises(Exception):
+            masker.apply_on_entity_coordinates('patient-1', 48.8566, 2.3522)
+        with self.assertRaises(Exception):
+            masker.apply_on_coordinates(100, 2.3522)
+
+    def test_consistent_blurring(self):
+        masker = GeoMasker(1000, b'\x2a' * 32)
+        self.assertTrue(masker.is_consistent())
+        # displacements are deterministic
+        self.assertEqual(
+            masker.apply_on_entity_coordinates('patient-1', 48.8566, 2.3522),
+            masker.apply_on_entity_coordinates('patient-1', 48.8566, 2.3522),
+        )
+
+        with self.assertRaises(Exception):
+            GeoMasker(1000, b'short key')
+
+    def test_seeded_blurring(self):
+        self.assertFalse(GeoMasker(1000).is_seeded())
+        masker = GeoMasker(1000, seed=42)
+        self.assertTrue(masker.is_seeded())
+        # seeded displacements are reproducible
+        self.assertEqual(
+            masker.apply_on_coordinates(48.8566, 2.3522),
+            GeoMasker(1000, seed=42).apply_on_coordinates(48.8566, 2.3522),
+        )
+
+    def test_geohash(self):
+        self.assertEqual(GeoMasker.to_geohash(48.8566, 2.3522, 7), 'u09tvw0')
+        self.assertEqual(GeoMasker.truncate_geohash('u09tvw0', 4), 'u09t')
+
+        with self.assertRaises(Exception):
+            GeoMasker.to_geohash(48.8566, 2.3522, 13)
+
+
+class TestEmailMasker(unittest.TestCase):
+    def test_hash(self):
+        masker = EmailMasker(b'\x2a' * 32)
+        masked = masker.apply('john.doe@cosmian.com')
+        self.assertTrue(masked.endswith('@cosmian.com'))
+        self.assertEqual(masker.apply('John.Doe@cosmian.com'), masked)
+
+        with self.assertRaises(Exception):
+            masker.revert(masked)
+
+    def test_fpe(self):
+        masker = EmailMasker(b'\x2a' * 32, 'FPE', 'Category')
+        masked = masker.apply('john.doe@gmail.com')
+        self.assertTrue(masked.endswith('@personal.invalid'))
+        self.assertEqual(masker.revert(masked), 'john.doe@personal.invalid')
+
+        with self.assertRaises(Exception):
+            EmailMasker(b'short key')
+        with self.assertRaises(Exception):
+            EmailMasker(b'\x2a' * 32, 'Encrypt')
+
+
+class TestPhoneMasker(unittest.TestCase):
+    def test_masking(self):
+        masker = PhoneMasker(3)
+        self.assertEqual(masker.apply('+1 (212) 555-0123'), '+1 (212) XXX-XXXX')
+        self.assertEqual(masker.apply('212-555-0123'), '212-XXX-XXXX')
+
+        with self.assertRaises(Exception):
+            masker.apply('+1 212')
+
+    def test_consistent_masking(self):
+        masker = PhoneMasker(1, b'\x2a' * 32)
+        masked = masker.apply('+33 1 23 45 67 89')
+        self.assertTrue(masked.startswith('+33 1 '))
+        self.assertEqual(masker.apply('+33 1 23 45 67 89'), masked)
+
+        with self.assertRaises(Exception):
+            PhoneMasker(1, b'short key')
+
+
+class TestAnonymizationPipeline(unittest.TestCase):
+    config = (
+        '{'
+        '"email": {"type": "hash", "method": "SHA2"},'
+        '"salary": {"type": "number_aggregator", "power_of_ten_exponent": 3, "data_type": "integer"}'
+        '}'
+    )
+
+    def test_csv(self) -> None:
+        pipeline = AnonymizationPipeline(self.config)
+        res = pipeline.apply_to_csv(
+            'name,email,salary\nalice,alice@example.com,41234\nbob,,38999\n'
+        )
+        lines = res.splitlines()
+        self.assertEqual(lines[0], 'name,email,salary')
+        name, email, salary = lines[1].split(',')
+        self.assertEqual(name, 'alice')
+        self.assertNotEqual(email, 'alice@example.com')
+        self.assertEqual(salary, '41000')
+        # empty fields are left untouched
+        self.assertEqual(lines[2], 'bob,,39000')
+
+        with self.assertRaises(Exception):
+            pipeline.apply_to_csv('name,salary\nalice,41234\n')
+
+    def test_record_batch(self) -> None:
+        pipeline = AnonymizationPipeline(self.config)
+        batch = pa.RecordBatch.from_pydict(
+            {
+                'name': ['alice', 'bob'],
+                'email': ['alice@example.com', None],
+                'salary': pa.array([41234, 38999], type=pa.int64()),
+            }
+        )
+        res = pipeline.apply_to_record_batch(batch)
+        self.assertEqual(res.schema, batch.schema)
+        self.assertEqual(res.column('name').to_pylist(), ['alice', 'bob'])
+        self.assertNotEqual(res.column('email')[0].as_py(), 'alice@example.com')
+        self.assertIsNone(res.column('email')[1].as_py())
+        self.assertEqual(res.column('salary').to_pylist(), [41000, 39000])
+
+    def test_report(self) -> None:
+        self.assertIsNone(AnonymizationPipeline(self.config).report())
+
+        pipeline = AnonymizationPipeline(self.config, record_report=True)
+        pipeline.apply_to_csv('name,email,salary\nalice,alice@example.com,41234\nbob,,38999\n')
+        report = pipeline.report()
+        self.assertEqual(report['columns']['email']['technique'], 'hash')
+        self.assertEqual(report['columns']['email']['values'], 1)
+        self.assertEqual(report['columns']['salary']['values'], 2)
+        self.assertEqual(report['columns']['salary']['changed'], 2)
+        self.assertNotIn('alice', str(report))
+
+    def test_invalid_config(self) -> None:
+        with self.assertRaises(Exception):
+            AnonymizationPipeline('{"salary": {"type": "number_aggregator"}}')
+
 
 if __name__ == '__main__':
     unittest.main()
@@ -0,0 +1,342 @@
+use base64::{engine::general_purpose, Engine as _};
+use serde::Deserialize;
+
+use crate::{
+    ano_error,
+    core::{
+        AnoError, DateAggregator, HashMethod, Hasher, Language, NoiseGenerator, NumberAggregator,
+        NumberScaler, TimeUnit, WordMasker, WordPatternMasker, WordTokenizer,
+    },
+};
+
+/// Type of the data to anonymize, used to parse the input string.
+#[derive(Debug, Clone, Copy, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DataType {
+    Float,
+    Integer,
+    Date,
+}
+
+/// Parameters of the noise distribution.
+#[derive(Debug, Deserialize)]
+#[serde(untagged)]
+pub enum NoiseParameters {
+    // Tried first: the other variants would ignore the extra fields.
+    TruncatedParameters {
+        mean: f64,
+        std_dev: f64,
+        min_bound: f64,
+        max_bound: f64,
+    },
+    Parameters {
+        mean: f64,
+        std_dev: f64,
+    },
+    Bounds {
+        min_bound: f64,
+        max_bound: f64,
+    },
+}
+
+/// JSON descriptor of the anonymization technique to apply.
+///
+/// The `type` field selects the technique, the other fields are its
+/// parameters:
+///
+/// ```json
+/// { "type": "hash", "method": "SHA2", "salt": "<base64>" }
+/// { "type": "noise", "method": "Gaussian", "mean": 0.0, "std_dev": 1.0, "data_type": "float" }
+/// { "type": "noise", "method": "Uniform", "min_bound": -10.0, "max_bound": 10.0, "data_type": "integer" }
+/// { "type": "noise", "method": "Laplace", "mean": 0.0, "std_dev": 2.0, "min_bound": -5.0, "max_bound": 5.0, "data_type": "float" }
+/// { "type": "word_masker", "words": ["quick", "brown"] }
+/// { "type": "word_tokenizer", "words": ["quick", "brown"], "language": "English" }
+/// { "type": "word_pattern_masker", "pattern": "\\d+", "replace": "XXX" }
+/// { "type": "number_aggregator", "power_of_ten_exponent": 2, "data_type": "float" }
+/// { "type": "number_aggregator", "bucket_edges": [18, 26, 65], "bucket_labels": ["0-17", "18-25", "26-64", "65+"], "data_type": "integer" }
+/// { "type": "date_aggregator", "time_unit": "Hour" }
+/// { "type": "number_scaler", "mean": 10.0, "std_deviation": 5.0, "scale": 2.0, "translate": 1.0, "data_type": "integer" }
+/// { "type": "number_scaler", "mean": 10.0, "std_deviation": 5.0, "scale": 20.0, "translate": 1.0, "data_type": "float", "key": "<base64>", "revert": false }
+/// ```
+///
+/// A `noise` given both its `mean` and `std_dev` and its bounds is truncated
+/// to these bounds (see [`NoiseGenerator::new_with_parameters_and_bounds`]).
+///
+/// A `number_aggregator` takes either a `power_of_ten_exponent` or
+/// `bucket_edges`, optionally along with `bucket_labels` (see
+/// [`NumberAggregator::new_with_buckets`]).
+///
+/// A `number_scaler` given a `key` is reversible: the scaled values are
+/// restored by applying the same configuration with `"revert": true`.
+#[derive(Debug, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum AnonymizationConfig {
+    Hash {
+        method: String,
+        salt: Option<String>,
+    },
+    Noise {
+        method: String,
+        #[serde(flatten)]
+        parameters: NoiseParameters,
+        data_type: DataType,
+    },
+    WordMasker {
+        words: Vec<String>,
+        language: Option<String>,
+    },
+    WordTokenizer {
+        words: Vec<String>,
+        language: Option<String>,
+    },
+    WordPatternMasker {
+        pattern: String,
+        replace: String,
+    },
+    NumberAggregator {
+        power_of_ten_exponent: Option<i32>,
+        bucket_edges: Option<Vec<f64>>,
+        bucket_labels: Option<Vec<String>>,
+        data_type: DataType,
+    },
+    DateAggregator {
+        time_unit: String,
+    },
+    NumberScaler {
+        mean: f64,
+        std_deviation: f64,
+        scale: f64,
+        translate: f64,
+        data_type: DataType,
+        key: Option<String>,
+        #[serde(default)]
+        revert: bool,
+    },
+}
+
+/// Anonymization technique built from an [`AnonymizationConfig`], ready to be
+/// applied on many values.
+pub(crate) enum Anonymizer {
+    Hash(Hasher),
+    Noise(NoiseGenerator<f64>, DataType),
+    WordMasker(WordMasker),
+    WordTokenizer(WordTokenizer),
+    WordPatternMasker(WordPatternMasker),
+    NumberAggregator(NumberAggregator, DataType),
+    DateAggregator(DateAggregator),
+    NumberScaler(NumberScaler, DataType, bool),
+}
+
+fn parse_float(input: &str) -> Result<f64, AnoError> {
+    input
+        .parse()
+        .map_err(|e| ano_error!("cannot parse `{input}` as a float: {e}"))
+}
+
+fn parse_int(input: &str) -> Result<i64, AnoError> {
+    input
+        .parse()
+        .map_err(|e| ano_error!("cannot parse `{input}` as an integer: {e}"))
+}
+
+fn as_words(words: &[String]) -> Vec<&str> {
+    words.iter().map(String::as_str).collect()
+}
+
+impl AnonymizationConfig {
+    /// Builds the anonymization technique described by this configuration.
+    pub(crate) fn build(&self) -> Result<Anonymizer, AnoError> {
+        match self {
+            Self::Hash { method, salt } => {
+                let salt = salt
+                    .as_ref()
+                    .map(|salt| general_purpose::STANDARD.decode(salt))
+                    .transpose()
+                    .map_err(|e| ano_error!("salt is not a valid base64 string: {e}"))?;
+                Ok(Anonymizer::Hash(Hasher::new(HashMethod::new(
+                    method, salt,
+                )?)))
+            }
+            Self::Noise {
+                method,
+                parameters,
+                data_type,
+            } => {
+                let noise_generator = match *parameters {
+                    NoiseParameters::TruncatedParameters {
+                        mean,
+                        std_dev,
+                        min_bound,
+                        max_bound,
+                    } => NoiseGenerator::new_with_parameters_and_bounds(
+                        method, mean, std_dev, min_bound, max_bound,
+                    ),
+                    NoiseParameters::Parameters { mean, std_dev } => {
+                        NoiseGenerator::new_with_parameters(method, mean, std_dev)
+                    }
+                    NoiseParameters::Bounds {
+                        min_bound,
+                        max_bound,
+                    } => NoiseGenerator::new_with_bounds(method, min_bound, max_bound),
+                }?;
+                Ok(Anonymizer::Noise(noise_generator, *data_type))
+            }
+            Self::WordMasker { words, language } => {
+                let word_masker = match language {
+                    Some(language) => WordMasker::new_with_language(
+                        &as_words(words),
+                        Language::try_from(language.as_str())?,
+                    ),
+                    None => WordMasker::new(&as_words(words)),
+                };
+                Ok(Anonymizer::WordMasker(word_masker))
+            }
+            Self::WordTokenizer { words, language } => {
+                let word_tokenizer = match language {
+                    Some(language) => WordTokenizer::new_with_language(
+                        &as_words(words),
+                        Language::try_from(language.as_str())?,
+                    )?,
+                    None => WordTokenizer::new(&as_words(words))?,
+                };
+                Ok(Anonymizer::WordTokenizer(word_tokenizer))
+            }
+            Self::WordPatternMasker { pattern, replace } => Ok(Anonymizer::WordPatternMasker(
+                WordPatternMasker::new(pattern, replace)?,
+            )),
+            Self::NumberAggregator {
+                power_of_ten_exponent,
+                bucket_edges,
+                bucket_labels,
+                data_type,
+            } => {
+                if let DataType::Date = data_type {
+                    return Err(ano_error!("NumberAggregator cannot be applied on dates"));
+                }
+                let aggregator = match (power_of_ten_exponent, bucket_edges) {
+                    (Some(exponent), None) if bucket_labels.is_none() => {
+                        NumberAggregator::new(*exponent)?
+                    }
+                    (None, Some(edges)) => {
+                        NumberAggregator::new_with_buckets(edges.clone(), bucket_labels.clone())?
+                    }
+                    _ => {
+                        return Err(ano_error!(
+                            "NumberAggregator requires either a `power_of_ten_exponent` or \
+                             `bucket_edges`"
+                        ));
+                    }
+                };
+                Ok(Anonymizer::NumberAggregator(aggregator, *data_type))
+            }
+            Self::DateAggregator { time_unit } => Ok(Anonymizer::DateAggregator(
+                DateAggregator::new(TimeUnit::try_from(time_unit.as_str())?),
+            )),
+            Self::NumberScaler {
+                mean,
+                std_deviation,
+                scale,
+                translate,
+                data_type,
+                key,
+                revert,
+            } => {
+                if let DataType::Date = data_type {
+                    return Err(ano_error!("NumberScaler cannot be applied on dates"));
+                }
+                let scaler = match key {
+                    Some(key) => {
+                        let key = general_purpose::STANDARD
+                            .decode(key)
+                            .map_err(|e| ano_error!("key is not a valid base64 string: {e}"))?;
+                        NumberScaler::new_reversible(
+                            *mean,
+                            *std_deviation,
+                            *scale,
+                            *translate,
+                            &key,
+                        )?
+                    }
+                    None => NumberScaler::new(*mean, *std_deviation, *scale, *translate),
+                };
+                Ok(Anonymizer::NumberScaler(scaler, *data_type, *revert))
+            }
+        }
+    }
+
+    /// Returns the name of the technique, as the `type` of the configuration.
+    #[must_use]
+    pub const fn technique(&self) -> &'static str {
+        match self {
+            Self::Hash { .. } => "hash",
+            Self::Noise { .. } => "noise",
+            Self::WordMasker { .. } => "word_masker",
+            Self::WordTokenizer { .. } => "word_tokenizer",
+            Self::WordPatternMasker { .. } => "word_pattern_masker",
+            Self::NumberAggregator { .. } => "number_aggregator",
+            Self::DateAggregator { .. } => "date_aggregator",
+            Self::NumberScaler { .. } => "number_scaler",
+        }
+    }
+
+    /// Builds the anonymization technique described by this configuration and
+    /// applies it on the given input.
+    pub fn apply(&self, input: &str) -> Result<String, AnoError> {
+        self.build()?.apply(input)
+    }
+}
+
+impl Anonymizer {
+    /// Applies the anonymization technique on the given input.
+    pub(crate) fn apply(&mut self, input: &str) -> Result<String, AnoError> {
+        match self {
+            Self::Hash(hasher) => hasher.apply_str(input),
+            Self::Noise(noise_generator, data_type) => match data_type {
+                DataType::Float => Ok(noise_generator
+                    .apply_on_float(parse_float(input)?)
+                    .to_string()),
+                DataType::Integer => {
+                    Ok(noise_generator.apply_on_int(parse_int(input)?).to_string())
+                }
+                DataType::Date => noise_generator.apply_on_date(input),
+            },
+            Self::WordMasker(word_masker) => Ok(word_masker.apply(input)),
+            Self::WordTokenizer(word_tokenizer) => Ok(word_tokenizer.apply(input)),
+            Self::WordPatternMasker(word_pattern_masker) => Ok(word_pattern_masker.apply(input)),
+            Self::NumberAggregator(aggregator, data_type) => match data_type {
+                DataType::Float => Ok(aggregator.apply_on_float(parse_float(input)?)),
+                DataType::Integer => Ok(aggregator.apply_on_int(parse_int(input)?)),
+                DataType::Date => Err(ano_error!("NumberAggregator cannot be applied on dates")),
+            },
+            Self::DateAggregator(aggregator) => aggregator.apply_on_date(input),
+            Self::NumberScaler(scaler, data_type, revert) => match (data_type, revert) {
+                (DataType::Float, false) => {
+                    Ok(scaler.apply_on_float(parse_float(input)?).to_string())
+                }
+                (DataType::Float, true) => {
+                    Ok(scaler.revert_on_float(parse_float(input)?)?.to_string())
+                }
+                (DataType::Integer, false) => {
+                    Ok(scaler.apply_on_int(parse_int(input)?).to_string())
+                }
+                (DataType::Integer, true) => {
+                    Ok(scaler.revert_on_int(parse_int(input)?)?.to_string())
+                }
+                (DataType::Date, _) => Err(ano_error!("NumberScaler cannot be applied on dates")),
+            },
+        }
+    }
+
+    /// Returns `true` if the anonymized values are free text (e.g. hashes or
+    /// masked words) rather than values of the type of the input.
+    pub(crate) fn outputs_text(&self) -> bool {
+        match self {
+            Self::Hash(_)
+            | Self::WordMasker(_)
+            | Self::WordTokenizer(_)
+            | Self::WordPatternMasker(_) => true,
+            Self::NumberAggregator(aggregator, _) => aggregator.outputs_labels(),
+            _ => false,
+        }
+    }
+}
@@ -0,0 +1,134 @@
+use chrono::{DateTime, TimeZone};
+use cosmian_crypto_core::kdf256;
+
+use super::{datetime_to_rfc3339, AnoError, TimeUnit};
+use crate::ano_error;
+
+/// Minimum length of the key used to derive the offsets.
+pub const DATE_SHIFTER_KEY_MIN_LENGTH: usize = 32;
+
+/// A data anonymization technique shifting all the dates belonging to the same
+/// entity by the same secret offset.
+///
+/// The offset is derived from the entity identifier using a secret key: the
+/// intervals between the dates of a given entity are preserved while their
+/// absolute values are hidden.
+///
+/// Example usage:
+///
+/// ```
+/// use cloudproof_anonymization::core::{DateShifter, TimeUnit};
+///
+/// let shifter = DateShifter::new(&[42; 32], 30, TimeUnit::Day).unwrap();
+/// let admission = shifter.apply_on_date("patient-1", "2023-04-07T12:34:56Z");
+/// let discharge = shifter.apply_on_date("patient-1", "2023-04-10T12:34:56Z");
+/// // both dates are shifted by the same number of days
+/// ```
+pub struct DateShifter {
+    key: Vec<u8>,
+    max_shift: u64,
+    unit_in_seconds: i64,
+}
+
+impl DateShifter {
+    /// Creates a new `DateShifter`.
+    ///
+    /// # Arguments
+    ///
+    /// * `key`: secret key used to derive the offsets, at least 32 bytes long.
+    /// * `max_shift`: the maximum absolute offset, expressed in `time_unit`.
+    /// * `time_unit`: granularity of the offsets. Months and years do not have
+    ///   a fixed duration and are not supported.
+    pub fn new(key: &[u8], max_shift: u64, time_unit: TimeUnit) -> Result<Self, AnoError> {
+        if key.len() < DATE_SHIFTER_KEY_MIN_LENGTH {
+            return Err(ano_error!(
+                "Date shifting key must be at least {DATE_SHIFTER_KEY_MIN_LENGTH} bytes long, \
+                 given {}.",
+                key.len()
+            ));
+        }
+        if max_shift == 0 {
+            return Err(ano_error!("Maximum shift must be greater than 0."));
+        }
+        let unit_in_seconds = match time_unit {
+            TimeUnit::Second => 1,
+            TimeUnit::Minute => 60,
+            TimeUnit::Hour => 3_600,
+            TimeUnit::Day => 86_400,
+            TimeUnit::Month | TimeUnit::Year => {
+                return Err(ano_error!(
+                    "Months and years do not have a fixed duration and cannot be used to shift \
+                     dates."
+                ));
+            }
+        };
+        if max_shift > (i64::MAX / unit_in_seconds) as u64 {
+            return Err(ano_error!("Maximum shift {max_shift} is too large."));
+        }
+        Ok(Self {
+            key: key.to_vec(),
+            max_shift,
+            unit_in_seconds,
+        })
+    }
+
+    /// Returns the offset in seconds associated to the given entity.
+    ///
+    /// The offset is uniformly distributed in `[-max_shift, max_shift]` time
+    /// units and is never null.
+    #[must_use]
+    pub fn offset(&self, entity_id: &str) -> i64 {
+        let mut bytes = [0; 8];
+        kdf256!(&mut bytes, &self.key, entity_id.as_bytes());
+        // Draw among the 2 * max_shift non-null offsets, the bias is negligible
+        // since the maximum shift is small compared to 2^64.
+        let draw = (u64::from_be_bytes(bytes) % (2 * self.max_shift)) as i64;
+        let max_shift = self.max_shift as i64;
+        let shift = if draw < max_shift {
+            draw - max_shift
+        } else {
+            draw - max_shift + 1
+        };
+        shift * self.unit_in_seconds
+    }
+
+    /// Shifts the given date by the offset associated to the given entity.
+    ///
+    /// # Arguments
+    ///
+    /// * `entity_id`: identifier of the entity the date belongs to.
+    /// * `date_str`: a date string in the RFC3339 format.
+    ///
+    /// # Returns
+    ///
+    /// The shifted date in RFC3339 format.
+    pub fn apply_on_date(&self, entity_id: &str, date_str: &str) -> Result<String, AnoError> {
+        let date = DateTime::parse_from_rfc3339(date_str)?;
+        let tz = date.timezone();
+        let shifted_timestamp = date
+            .timestamp()
+            .checked_add(self.offset(entity_id))
+            .ok_or_else(|| ano_error!("Shifting date `{date_str}` overflows."))?;
+        datetime_to_rfc3339(
+            tz.timestamp_opt(shifted_timestamp, date.timestamp_subsec_nanos()),
+            date_str,
+        )
+    }
+
+    /// Shifts all the given dates by the offset associated to the given entity.
+    ///
+    /// # Arguments
+    ///
+    /// * `entity_id`: identifier of the entity the dates belong to.
+    /// * `dates`: list of date strings in the RFC3339 format.
+    ///
+    /// # Returns
+    ///
+    /// The shifted dates in RFC3339 format.
+    pub fn apply_on_dates(&self, entity_id: &str, dates: &[&str]) -> Result<Vec<String>, AnoError> {
+        dates
+            .iter()
+            .map(|date_str| self.apply_on_date(entity_id, date_str))
+            .collect()
+    }
+}
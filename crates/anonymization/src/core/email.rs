@@ -0,0 +1,228 @@
+use cloudproof_fpe::core::{Alphabet, KEY_LENGTH};
+use hmac::{Hmac, Mac};
+use sha2::Sha256;
+
+use super::AnoError;
+use crate::ano_error;
+
+/// Length of the keys used by the `EmailMasker`.
+pub const EMAIL_MASKER_KEY_LENGTH: usize = KEY_LENGTH;
+
+/// Number of hexadecimal characters of the pseudonyms replacing the local
+/// parts.
+const HASHED_LOCAL_PART_LENGTH: usize = 16;
+
+/// FPE tweak used to encrypt the local parts.
+const FPE_TWEAK: &[u8] = b"EmailMasker";
+
+/// Domains of the free email providers, categorized as `personal`.
+const PERSONAL_DOMAINS: &[&str] = &[
+    "aol.com",
+    "free.fr",
+    "gmail.com",
+    "gmx.com",
+    "gmx.de",
+    "gmx.net",
+    "googlemail.com",
+    "hotmail.com",
+    "hotmail.fr",
+    "icloud.com",
+    "laposte.net",
+    "live.com",
+    "mail.com",
+    "me.com",
+    "msn.com",
+    "orange.fr",
+    "outlook.com",
+    "outlook.fr",
+    "proton.me",
+    "protonmail.com",
+    "sfr.fr",
+    "t-online.de",
+    "web.de",
+    "yahoo.com",
+    "yahoo.fr",
+    "yandex.ru",
+];
+
+/// How the local part of the email addresses (before the `@`) is masked.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LocalPartMasking {
+    /// Replaces the local part with a keyed hash: the same address always gets
+    /// the same pseudonym, which cannot be reverted.
+    Hash,
+    /// Encrypts the alphanumeric characters of the local part using FPE,
+    /// keeping its length and punctuation. The local part can be restored
+    /// using the key, but must hold at least 4 alphanumeric characters.
+    Fpe,
+}
+
+impl TryFrom<&str> for LocalPartMasking {
+    type Error = AnoError;
+
+    fn try_from(value: &str) -> Result<Self, Self::Error> {
+        match value {
+            "Hash" => Ok(Self::Hash),
+            "FPE" => Ok(Self::Fpe),
+            _ => Err(ano_error!(
+                "Unknown local part masking {value}, expected Hash or FPE."
+            )),
+        }
+    }
+}
+
+/// How the domain of the email addresses (after the `@`) is masked.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DomainMasking {
+    /// Keeps the domain.
+    Preserve,
+    /// Replaces the domain with its category: `personal` for the free email
+    /// providers, `education` and `government` for the academic and
+    /// governmental domains, `business` otherwise. The category is given as a
+    /// domain under the reserved `.invalid` top-level domain, e.g.
+    /// `personal.invalid`.
+    Category,
+}
+
+impl TryFrom<&str> for DomainMasking {
+    type Error = AnoError;
+
+    fn try_from(value: &str) -> Result<Self, Self::Error> {
+        match value {
+            "Preserve" => Ok(Self::Preserve),
+            "Category" => Ok(Self::Category),
+            _ => Err(ano_error!(
+                "Unknown domain masking {value}, expected Preserve or Category."
+            )),
+        }
+    }
+}
+
+/// Returns the category of the given lowercase domain.
+fn domain_category(domain: &str) -> &'static str {
+    let labels = domain.split('.').collect::<Vec<_>>();
+    let has_label = |candidates: &[&str]| {
+        labels[..labels.len() - 1]
+            .iter()
+            .any(|label| candidates.contains(label))
+    };
+    let top_level = labels[labels.len() - 1];
+    if PERSONAL_DOMAINS.contains(&domain) {
+        "personal"
+    } else if top_level == "edu" || has_label(&["ac", "edu"]) {
+        "education"
+    } else if top_level == "gov" || top_level == "mil" || has_label(&["gov", "gouv"]) {
+        "government"
+    } else {
+        "business"
+    }
+}
+
+/// A data anonymization technique masking the local part of email addresses
+/// while keeping their domain, or the category of their domain.
+///
+/// Example usage:
+///
+/// ```
+/// use cloudproof_anonymization::core::{DomainMasking, EmailMasker, LocalPartMasking};
+///
+/// let masker =
+///     EmailMasker::new(&[42; 32], LocalPartMasking::Fpe, DomainMasking::Preserve).unwrap();
+/// let masked = masker.apply("john.doe@cosmian.com").unwrap();
+/// assert!(masked.ends_with("@cosmian.com"));
+/// assert_eq!(masker.revert(&masked).unwrap(), "john.doe@cosmian.com");
+/// ```
+pub struct EmailMasker {
+    key: Vec<u8>,
+    local_part_masking: LocalPartMasking,
+    domain_masking: DomainMasking,
+}
+
+impl EmailMasker {
+    /// Creates a new `EmailMasker`.
+    ///
+    /// # Arguments
+    ///
+    /// * `key`: secret key, 32 bytes long.
+    /// * `local_part_masking`: how the local part is masked.
+    /// * `domain_masking`: how the domain is masked.
+    pub fn new(
+        key: &[u8],
+        local_part_masking: LocalPartMasking,
+        domain_masking: DomainMasking,
+    ) -> Result<Self, AnoError> {
+        if key.len() != EMAIL_MASKER_KEY_LENGTH {
+            return Err(ano_error!(
+                "Email masking key must be {EMAIL_MASKER_KEY_LENGTH} bytes long, given {}.",
+                key.len()
+            ));
+        }
+        Ok(Self {
+            key: key.to_vec(),
+            local_part_masking,
+            domain_masking,
+        })
+    }
+
+    /// Masks the given email address.
+    ///
+    /// The domain is compared case-insensitively and returned in lowercase.
+    pub fn apply(&self, email: &str) -> Result<String, AnoError> {
+        let (local_part, domain) = split_email(email)?;
+        let masked_local_part = match self.local_part_masking {
+            LocalPartMasking::Hash => {
+                let mut mac = <Hmac<Sha256>>::new_from_slice(&self.key)
+                    .map_err(|e| ano_error!("Invalid HMAC key: {e}"))?;
+                mac.update(local_part.to_lowercase().as_bytes());
+                let mut pseudonym = hex::encode(mac.finalize().into_bytes());
+                pseudonym.truncate(HASHED_LOCAL_PART_LENGTH);
+                pseudonym
+            }
+            LocalPartMasking::Fpe => Alphabet::alpha_numeric()
+                .encrypt(&self.key, FPE_TWEAK, local_part)
+                .map_err(|e| ano_error!("Error encrypting the local part of `{email}`: {e}"))?,
+        };
+        let masked_domain = match self.domain_masking {
+            DomainMasking::Preserve => domain,
+            DomainMasking::Category => format!("{}.invalid", domain_category(&domain)),
+        };
+        Ok(format!("{masked_local_part}@{masked_domain}"))
+    }
+
+    /// Restores the local part of the given email address masked using FPE.
+    ///
+    /// The domain is returned as is: the original domain cannot be restored
+    /// from its category.
+    pub fn revert(&self, masked_email: &str) -> Result<String, AnoError> {
+        if self.local_part_masking != LocalPartMasking::Fpe {
+            return Err(ano_error!(
+                "Only the email addresses masked using FPE can be reverted."
+            ));
+        }
+        let (local_part, domain) = split_email(masked_email)?;
+        let local_part = Alphabet::alpha_numeric()
+            .decrypt(&self.key, FPE_TWEAK, local_part)
+            .map_err(|e| ano_error!("Error decrypting the local part of `{masked_email}`: {e}"))?;
+        Ok(format!("{local_part}@{domain}"))
+    }
+}
+
+/// Splits the given email address into its local part and its lowercase
+/// domain.
+fn split_email(email: &str) -> Result<(&str, String), AnoError> {
+    let email = email.trim();
+    let (local_part, domain) = email
+        .rsplit_once('@')
+        .ok_or_else(|| ano_error!("Invalid email address `{email}`: missing `@`."))?;
+    if local_part.is_empty() {
+        return Err(ano_error!(
+            "Invalid email address `{email}`: empty local part."
+        ));
+    }
+    if domain.is_empty() || domain.split('.').any(str::is_empty) {
+        return Err(ano_error!(
+            "Invalid email address `{email}`: invalid domain."
+        ));
+    }
+    Ok((local_part, domain.to_lowercase()))
+}
@@ -0,0 +1,308 @@
+use std::{
+    f64::consts::PI,
+    sync::{Arc, Mutex},
+};
+
+use cosmian_crypto_core::{kdf256, reexport::rand_core::SeedableRng, CsRng};
+use rand::Rng;
+
+use super::{number::to_unit_interval, AnoError};
+use crate::ano_error;
+
+/// Minimum length of the key used to derive the displacements of a consistent
+/// `GeoMasker`.
+pub const GEO_MASKER_KEY_MIN_LENGTH: usize = 32;
+
+/// Maximum supported geohash precision, i.e. number of characters.
+pub const GEOHASH_MAX_PRECISION: usize = 12;
+
+/// Mean Earth radius in meters.
+const EARTH_RADIUS: f64 = 6_371_008.8;
+
+/// Alphabet of the geohash base32 encoding.
+const GEOHASH_ALPHABET: &[u8; 32] = b"0123456789bcdefghjkmnpqrstuvwxyz";
+
+/// A data anonymization technique hiding the precise location of geographic
+/// coordinates.
+///
+/// Coordinates are either moved to a random point within a given radius, or
+/// reduced to a geohash of a given precision, i.e. to the cell containing them.
+///
+/// A masker created with [`GeoMasker::new_consistent`] can also move all the
+/// coordinates belonging to the same entity by the same secret displacement,
+/// derived from the entity identifier using a secret key: the relative
+/// positions of the locations of a given entity are preserved while their
+/// absolute values are hidden.
+///
+/// Example usage:
+///
+/// ```
+/// use cloudproof_anonymization::core::GeoMasker;
+///
+/// let masker = GeoMasker::new_consistent(1_000.0, &[42; 32]).unwrap();
+/// let (latitude, longitude) = masker
+///     .apply_on_entity_coordinates("patient-1", 48.8566, 2.3522)
+///     .unwrap();
+/// let geohash = GeoMasker::to_geohash(48.8566, 2.3522, 5).unwrap();
+/// assert_eq!(geohash, "u09tv");
+/// ```
+pub struct GeoMasker {
+    radius: f64,
+    key: Option<Vec<u8>>,
+    rng: Arc<Mutex<CsRng>>,
+    seeded: bool,
+}
+
+impl GeoMasker {
+    /// Creates a new `GeoMasker` moving the coordinates to a random point
+    /// within the given radius.
+    ///
+    /// # Arguments
+    ///
+    /// * `radius`: maximum displacement of the coordinates, in meters.
+    pub fn new(radius: f64) -> Result<Self, AnoError> {
+        if !radius.is_finite() || radius <= 0.0 {
+            return Err(ano_error!(
+                "Radius must be a positive finite number, given {radius}."
+            ));
+        }
+        if radius > PI * EARTH_RADIUS {
+            return Err(ano_error!(
+                "Radius must be smaller than half of the Earth circumference, given {radius}."
+            ));
+        }
+        Ok(Self {
+            radius,
+            key: None,
+            rng: Arc::new(Mutex::new(CsRng::from_entropy())),
+            seeded: false,
+        })
+    }
+
+    /// Creates a new `GeoMasker` also able to move all the coordinates of the
+    /// same entity by the same secret displacement.
+    ///
+    /// # Arguments
+    ///
+    /// * `radius`: maximum displacement of the coordinates, in meters.
+    /// * `key`: secret key used to derive the displacements, at least 32 bytes
+    ///   long.
+    pub fn new_consistent(radius: f64, key: &[u8]) -> Result<Self, AnoError> {
+        if key.len() < GEO_MASKER_KEY_MIN_LENGTH {
+            return Err(ano_error!(
+                "Geo masking key must be at least {GEO_MASKER_KEY_MIN_LENGTH} bytes long, given \
+                 {}.",
+                key.len()
+            ));
+        }
+        let mut masker = Self::new(radius)?;
+        masker.key = Some(key.to_vec());
+        Ok(masker)
+    }
+
+    /// Seeds the random generator of this masker with the given seed, making
+    /// the displacements of [`GeoMasker::apply_on_coordinates`] reproducible.
+    ///
+    /// Seeded maskers are meant for tests only: anyone knowing the seed can
+    /// recover the original coordinates.
+    #[must_use]
+    pub fn with_seed(mut self, seed: u64) -> Self {
+        self.rng = Arc::new(Mutex::new(CsRng::seed_from_u64(seed)));
+        self.seeded = true;
+        self
+    }
+
+    /// Returns `true` if this masker was seeded with [`GeoMasker::with_seed`].
+    #[must_use]
+    pub const fn is_seeded(&self) -> bool {
+        self.seeded
+    }
+
+    /// Returns `true` if this masker was created with a key and can move the
+    /// coordinates of an entity consistently.
+    #[must_use]
+    pub const fn is_consistent(&self) -> bool {
+        self.key.is_some()
+    }
+
+    /// Moves the given coordinates to a random point within the radius.
+    ///
+    /// # Arguments
+    ///
+    /// * `latitude`: latitude in degrees, in `[-90, 90]`.
+    /// * `longitude`: longitude in degrees, in `[-180, 180]`.
+    ///
+    /// # Returns
+    ///
+    /// The blurred latitude and longitude in degrees.
+    pub fn apply_on_coordinates(
+        &self,
+        latitude: f64,
+        longitude: f64,
+    ) -> Result<(f64, f64), AnoError> {
+        check_coordinates(latitude, longitude)?;
+        let (distance_draw, bearing_draw) = {
+            let mut rng = self.rng.lock().expect("failed locking the RNG");
+            (rng.gen::<f64>(), rng.gen::<f64>())
+        };
+        Ok(self.displace(latitude, longitude, distance_draw, bearing_draw))
+    }
+
+    /// Moves the given coordinates by the secret displacement associated to the
+    /// given entity.
+    ///
+    /// # Arguments
+    ///
+    /// * `entity_id`: identifier of the entity the coordinates belong to.
+    /// * `latitude`: latitude in degrees, in `[-90, 90]`.
+    /// * `longitude`: longitude in degrees, in `[-180, 180]`.
+    ///
+    /// # Returns
+    ///
+    /// The blurred latitude and longitude in degrees.
+    pub fn apply_on_entity_coordinates(
+        &self,
+        entity_id: &str,
+        latitude: f64,
+        longitude: f64,
+    ) -> Result<(f64, f64), AnoError> {
+        let Some(key) = &self.key else {
+            return Err(ano_error!(
+                "GeoMasker must be created with a key to consistently mask the coordinates of an \
+                 entity."
+            ));
+        };
+        check_coordinates(latitude, longitude)?;
+        let mut bytes = [0; 16];
+        kdf256!(&mut bytes, key, b"GeoMasker", entity_id.as_bytes());
+        let (distance_bytes, bearing_bytes) = bytes.split_at(8);
+        Ok(self.displace(
+            latitude,
+            longitude,
+            to_unit_interval(distance_bytes),
+            to_unit_interval(bearing_bytes),
+        ))
+    }
+
+    /// Moves the given coordinates along the great circle of the bearing
+    /// derived from `bearing_draw`, by a distance derived from `distance_draw`.
+    ///
+    /// Both draws are in `[0, 1)`, the resulting points are uniformly
+    /// distributed in the disk of radius `self.radius`.
+    fn displace(
+        &self,
+        latitude: f64,
+        longitude: f64,
+        distance_draw: f64,
+        bearing_draw: f64,
+    ) -> (f64, f64) {
+        let angular_distance = self.radius * distance_draw.sqrt() / EARTH_RADIUS;
+        let bearing = 2.0 * PI * bearing_draw;
+        let (lat, lon) = (latitude.to_radians(), longitude.to_radians());
+
+        let new_lat = lat
+            .sin()
+            .mul_add(
+                angular_distance.cos(),
+                lat.cos() * angular_distance.sin() * bearing.cos(),
+            )
+            .clamp(-1.0, 1.0)
+            .asin();
+        let new_lon = lon
+            + (bearing.sin() * angular_distance.sin() * lat.cos())
+                .atan2(lat.sin().mul_add(-new_lat.sin(), angular_distance.cos()));
+
+        // Normalize the longitude in `[-180, 180)`.
+        let new_lon = (new_lon.to_degrees() + 540.0).rem_euclid(360.0) - 180.0;
+        (new_lat.to_degrees(), new_lon)
+    }
+
+    /// Encodes the given coordinates into a geohash of the given precision.
+    ///
+    /// The geohash identifies the cell containing the coordinates: the lower
+    /// the precision, the larger the cell (about 5 km wide for a precision of
+    /// 5, 150 m for a precision of 7).
+    ///
+    /// # Arguments
+    ///
+    /// * `latitude`: latitude in degrees, in `[-90, 90]`.
+    /// * `longitude`: longitude in degrees, in `[-180, 180]`.
+    /// * `precision`: number of characters of the geohash, in `[1, 12]`.
+    pub fn to_geohash(latitude: f64, longitude: f64, precision: usize) -> Result<String, AnoError> {
+        check_coordinates(latitude, longitude)?;
+        check_precision(precision)?;
+
+        let mut lat_range = (-90.0, 90.0);
+        let mut lon_range = (-180.0, 180.0);
+        let mut geohash = String::with_capacity(precision);
+        let mut is_longitude = true;
+        for _ in 0..precision {
+            let mut index = 0;
+            for _ in 0..5 {
+                let (range, value) = if is_longitude {
+                    (&mut lon_range, longitude)
+                } else {
+                    (&mut lat_range, latitude)
+                };
+                let middle = (range.0 + range.1) / 2.0;
+                index <<= 1;
+                if value >= middle {
+                    index |= 1;
+                    range.0 = middle;
+                } else {
+                    range.1 = middle;
+                }
+                is_longitude = !is_longitude;
+            }
+            geohash.push(char::from(GEOHASH_ALPHABET[index]));
+        }
+        Ok(geohash)
+    }
+
+    /// Truncates the given geohash to the given precision.
+    ///
+    /// # Arguments
+    ///
+    /// * `geohash`: a valid geohash.
+    /// * `precision`: number of characters to keep, in `[1, 12]`.
+    pub fn truncate_geohash(geohash: &str, precision: usize) -> Result<String, AnoError> {
+        check_precision(precision)?;
+        if let Some(c) = geohash
+            .chars()
+            .find(|c| !c.is_ascii() || !GEOHASH_ALPHABET.contains(&(*c as u8)))
+        {
+            return Err(ano_error!(
+                "Invalid character {c:?} in geohash `{geohash}`."
+            ));
+        }
+        if geohash.len() < precision {
+            return Err(ano_error!(
+                "Geohash `{geohash}` is shorter than the requested precision {precision}."
+            ));
+        }
+        Ok(geohash[..precision].to_string())
+    }
+}
+
+fn check_coordinates(latitude: f64, longitude: f64) -> Result<(), AnoError> {
+    if !(-90.0..=90.0).contains(&latitude) {
+        return Err(ano_error!(
+            "Latitude must be in [-90, 90], given {latitude}."
+        ));
+    }
+    if !(-180.0..=180.0).contains(&longitude) {
+        return Err(ano_error!(
+            "Longitude must be in [-180, 180], given {longitude}."
+        ));
+    }
+    Ok(())
+}
+
+fn check_precision(precision: usize) -> Result<(), AnoError> {
+    if precision == 0 || precision > GEOHASH_MAX_PRECISION {
+        return Err(ano_error!(
+            "Geohash precision must be in [1, {GEOHASH_MAX_PRECISION}], given {precision}."
+        ));
+    }
+    Ok(())
+}
@@ -1,10 +1,60 @@
 use argon2::Argon2;
 use base64::{engine::general_purpose, Engine as _};
 use cosmian_crypto_core::reexport::tiny_keccak::{Hasher as _, Sha3};
+use hmac::{Hmac, Mac};
 use sha2::{Digest, Sha256};
 
 use crate::{ano_error, core::AnoError};
 
+/// Minimum length of the keys used by the keyed hashes.
+pub const HMAC_KEY_MIN_LENGTH: usize = 32;
+
+/// Length of the key version tag prefixing the keyed hashes.
+const KEY_VERSION_LENGTH: usize = 4;
+
+/// Length of the keyed hashes: key version tag || HMAC-SHA256.
+pub const KEYED_HASH_LENGTH: usize = KEY_VERSION_LENGTH + 32;
+
+/// Secret key used by the keyed hashes, tagged with its version.
+///
+/// The version is embedded in the keyed hashes so that the key used to
+/// compute a given pseudonym can be identified upon key rotation.
+pub struct HmacKey {
+    version: u32,
+    key: Vec<u8>,
+}
+
+impl HmacKey {
+    pub fn new(version: u32, key: &[u8]) -> Result<Self, AnoError> {
+        if key.len() < HMAC_KEY_MIN_LENGTH {
+            return Err(ano_error!(
+                "HMAC key must be at least {HMAC_KEY_MIN_LENGTH} bytes long, given {}.",
+                key.len()
+            ));
+        }
+        Ok(Self {
+            version,
+            key: key.to_vec(),
+        })
+    }
+
+    #[must_use]
+    pub const fn version(&self) -> u32 {
+        self.version
+    }
+
+    /// Returns `version || HMAC-SHA256(key, data)`.
+    fn tag(&self, data: &[u8]) -> Result<[u8; KEYED_HASH_LENGTH], AnoError> {
+        let mut mac = <Hmac<Sha256>>::new_from_slice(&self.key)
+            .map_err(|e| ano_error!("Invalid HMAC key: {e}"))?;
+        mac.update(data);
+        let mut output = [0; KEYED_HASH_LENGTH];
+        output[..KEY_VERSION_LENGTH].copy_from_slice(&self.version.to_be_bytes());
+        output[KEY_VERSION_LENGTH..].copy_from_slice(&mac.finalize().into_bytes());
+        Ok(output)
+    }
+}
+
 // Available hashing methods
 #[derive(PartialEq, Eq)]
 pub enum HashMethod {
@@ -114,4 +164,90 @@ impl Hasher {
             }
         }
     }
+    /// Applies the chosen hash method to the input data, then keys the result
+    /// using HMAC-SHA256.
+    ///
+    /// Keyed hashes cannot be recomputed without the key, even for low entropy
+    /// data. They can be rotated to a new key using [`Self::rehash_bytes`],
+    /// without the input data.
+    ///
+    /// # Arguments
+    ///
+    /// * `keys` - history of the keys, oldest first. A keyed hash rotated from
+    ///   a key to the next one equals the keyed hash computed using these two
+    ///   keys: the data indexed after a key rotation should be hashed using
+    ///   the whole history to match the rotated pseudonyms.
+    /// * `data` - input data to be hashed.
+    ///
+    /// # Returns
+    ///
+    /// The version of the last key followed by the HMAC.
+    pub fn apply_keyed_bytes(
+        &self,
+        keys: &[HmacKey],
+        data: &[u8],
+    ) -> Result<[u8; KEYED_HASH_LENGTH], AnoError> {
+        let (first_key, next_keys) = keys
+            .split_first()
+            .ok_or_else(|| ano_error!("At least one HMAC key is required."))?;
+        let mut output = first_key.tag(&self.apply_bytes(data)?)?;
+        for key in next_keys {
+            output = key.tag(&output[KEY_VERSION_LENGTH..])?;
+        }
+        Ok(output)
+    }
+
+    /// Base64-encoded version of [`Self::apply_keyed_bytes`].
+    pub fn apply_keyed_str(&self, keys: &[HmacKey], data: &str) -> Result<String, AnoError> {
+        let hashed_bytes = self.apply_keyed_bytes(keys, data.as_bytes())?;
+        Ok(general_purpose::STANDARD.encode(hashed_bytes))
+    }
+
+    /// Rotates a keyed hash computed using `old_key` to `new_key`.
+    ///
+    /// The result is tagged with the version of the new key, and equals the
+    /// keyed hash computed using the key history `[.., old_key, new_key]`.
+    ///
+    /// # Arguments
+    ///
+    /// * `old_key` - key used to compute the given keyed hash. Its version
+    ///   must match the one embedded in the hash.
+    /// * `new_key` - key to rotate to.
+    /// * `hash` - keyed hash to rotate.
+    pub fn rehash_bytes(
+        old_key: &HmacKey,
+        new_key: &HmacKey,
+        hash: &[u8],
+    ) -> Result<[u8; KEYED_HASH_LENGTH], AnoError> {
+        if hash.len() != KEYED_HASH_LENGTH {
+            return Err(ano_error!(
+                "Keyed hash must be {KEYED_HASH_LENGTH} bytes long, given {}.",
+                hash.len()
+            ));
+        }
+        let (version_bytes, mac) = hash.split_at(KEY_VERSION_LENGTH);
+        let mut version = [0; KEY_VERSION_LENGTH];
+        version.copy_from_slice(version_bytes);
+        let version = u32::from_be_bytes(version);
+        if version != old_key.version {
+            return Err(ano_error!(
+                "Keyed hash was computed using the key version {version}, not {}.",
+                old_key.version
+            ));
+        }
+        new_key.tag(mac)
+    }
+
+    /// Base64 version of [`Self::rehash_bytes`].
+    pub fn rehash_str(
+        old_key: &HmacKey,
+        new_key: &HmacKey,
+        hash: &str,
+    ) -> Result<String, AnoError> {
+        let hash = general_purpose::STANDARD
+            .decode(hash)
+            .map_err(|e| AnoError::ConversionError(e.to_string()))?;
+        let hashed_bytes = Self::rehash_bytes(old_key, new_key, &hash)?;
+        Ok(general_purpose::STANDARD.encode(hashed_bytes))
+    }
 }
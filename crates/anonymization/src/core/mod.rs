@@ -2,19 +2,46 @@ pub mod error;
 pub use error::AnoError;
 
 mod hash;
-pub use hash::{HashMethod, Hasher};
+pub use hash::{HashMethod, Hasher, HmacKey, HMAC_KEY_MIN_LENGTH, KEYED_HASH_LENGTH};
 
 mod noise;
-pub use noise::{Laplace, NoiseGenerator, NoiseMethod};
+pub use noise::{Laplace, NoiseGenerator, NoiseMethod, Truncated};
 
 mod word;
-pub use word::{WordMasker, WordPatternMasker, WordTokenizer};
+pub use word::{Language, WordMasker, WordPatternMasker, WordTokenizer};
 
 mod number;
-pub use number::{DateAggregator, NumberAggregator, NumberScaler};
+pub use number::{DateAggregator, NumberAggregator, NumberScaler, NUMBER_SCALER_KEY_MIN_LENGTH};
 
 mod date_helper;
 pub use date_helper::{datetime_to_rfc3339, TimeUnit};
 
+mod date_shifter;
+pub use date_shifter::{DateShifter, DATE_SHIFTER_KEY_MIN_LENGTH};
+
+mod email;
+pub use email::{DomainMasking, EmailMasker, LocalPartMasking, EMAIL_MASKER_KEY_LENGTH};
+
+mod geo;
+pub use geo::{GeoMasker, GEOHASH_MAX_PRECISION, GEO_MASKER_KEY_MIN_LENGTH};
+
+mod phone;
+pub use phone::{PhoneMasker, PHONE_MASKER_KEY_MIN_LENGTH};
+
+#[cfg(feature = "pipeline")]
+mod config;
+#[cfg(feature = "pipeline")]
+pub use config::{AnonymizationConfig, DataType, NoiseParameters};
+
+#[cfg(feature = "pipeline")]
+mod pipeline;
+#[cfg(feature = "pipeline")]
+pub use pipeline::AnonymizationPipeline;
+
+#[cfg(feature = "pipeline")]
+mod report;
+#[cfg(feature = "pipeline")]
+pub use report::{ColumnReport, TransformationReport};
+
 #[cfg(test)]
 mod tests;
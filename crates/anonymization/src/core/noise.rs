@@ -17,6 +17,8 @@ where
     Gaussian(Normal<F>),
     Laplace(Laplace<F>),
     Uniform(Uniform<F>),
+    TruncatedGaussian(Truncated<Normal<F>, F>),
+    TruncatedLaplace(Truncated<Laplace<F>, F>),
 }
 
 impl<F> NoiseMethod<F>
@@ -30,6 +32,8 @@ where
             Self::Gaussian(distr) => distr.sample(rng),
             Self::Laplace(distr) => distr.sample(rng),
             Self::Uniform(distr) => distr.sample(rng),
+            Self::TruncatedGaussian(distr) => distr.sample(rng),
+            Self::TruncatedLaplace(distr) => distr.sample(rng),
         }
     }
 }
@@ -84,6 +88,67 @@ where
     }
 }
 
+/// Maximum number of samples rejected by a [`Truncated`] distribution before
+/// falling back to the uniform distribution over its bounds.
+const MAX_REJECTIONS: usize = 1000;
+
+/// A distribution truncated to the `[min, max]` interval, the mean of the
+/// underlying distribution lying in this interval.
+///
+/// Samples out of the bounds are rejected, which preserves the shape of the
+/// distribution inside the bounds, unlike clamping which accumulates the
+/// rejected mass on the bounds. If `MAX_REJECTIONS` samples in a row are
+/// rejected, the interval is so narrow compared to the spread of the
+/// distribution that the latter is almost flat over it: a uniform sample is
+/// returned instead.
+pub struct Truncated<D, F> {
+    distribution: D,
+    min: F,
+    max: F,
+}
+
+impl<D, F: Float> Truncated<D, F> {
+    /// Truncates the given distribution to the `[min, max]` interval.
+    ///
+    /// # Arguments
+    ///
+    /// * `distribution` - The distribution to truncate.
+    /// * `mean` - The mean of this distribution, which must lie in the bounds.
+    /// * `min` - The lower bound of the generated values.
+    /// * `max` - The upper bound of the generated values.
+    pub fn new(distribution: D, mean: F, min: F, max: F) -> Result<Self, AnoError> {
+        if min >= max {
+            return Err(ano_error!("Min bound must be inferior to Max bound."));
+        }
+        if mean < min || mean > max {
+            return Err(ano_error!(
+                "The mean of a truncated distribution must lie between its bounds."
+            ));
+        }
+        Ok(Self {
+            distribution,
+            min,
+            max,
+        })
+    }
+}
+
+impl<D, F> Distribution<F> for Truncated<D, F>
+where
+    D: Distribution<F>,
+    F: Float + rand_distr::uniform::SampleUniform,
+{
+    fn sample<R: Rng + ?Sized>(&self, rng: &mut R) -> F {
+        for _ in 0..MAX_REJECTIONS {
+            let value = self.distribution.sample(rng);
+            if self.min <= value && value <= self.max {
+                return value;
+            }
+        }
+        Uniform::new_inclusive(self.min, self.max).sample(rng)
+    }
+}
+
 pub struct NoiseGenerator<F>
 where
     F: Float + rand_distr::uniform::SampleUniform,
@@ -91,6 +156,7 @@ where
 {
     method: NoiseMethod<F>,
     rng: Arc<Mutex<CsRng>>,
+    seeded: bool,
 }
 
 impl<F> NoiseGenerator<F>
@@ -127,11 +193,16 @@ where
         Ok(Self {
             method,
             rng: Arc::new(Mutex::new(CsRng::from_entropy())),
+            seeded: false,
         })
     }
 
     /// Instantiate a `NoiseGenerator` with bound constraints.
     ///
+    /// The Gaussian and Laplace distributions are centered between the bounds
+    /// and truncated to them, with a spread such that almost no sample needs
+    /// to be rejected.
+    ///
     /// # Arguments
     ///
     /// * `method_name`: The noise distribution to use ("Uniform", "Gaussian",
@@ -155,23 +226,93 @@ where
                 let mean = (max_bound + min_bound) / F::from(2).unwrap();
                 // 5σ => 99.99994% of values will be in the bounds
                 let std_dev = (mean - min_bound) / F::from(5).unwrap();
-                Ok(NoiseMethod::Gaussian(Normal::new(mean, std_dev)?))
+                Ok(NoiseMethod::TruncatedGaussian(Truncated::new(
+                    Normal::new(mean, std_dev)?,
+                    mean,
+                    min_bound,
+                    max_bound,
+                )?))
             }
             "Laplace" => {
                 let mean = (max_bound + min_bound) / F::from(2).unwrap();
                 // confidence interval at 1-a: μ ± β * ln(1/a)
                 let beta = (mean - min_bound) / -F::ln(F::from(0.00005).unwrap());
-                Ok(NoiseMethod::Laplace(Laplace::<F>::new(mean, beta)))
+                Ok(NoiseMethod::TruncatedLaplace(Truncated::new(
+                    Laplace::<F>::new(mean, beta),
+                    mean,
+                    min_bound,
+                    max_bound,
+                )?))
             }
-            "Uniform" => Ok(NoiseMethod::Uniform(Uniform::new(min_bound, max_bound))),
+            "Uniform" => Ok(NoiseMethod::Uniform(Uniform::new_inclusive(
+                min_bound, max_bound,
+            ))),
             _ => Err(ano_error!("No supported distribution {}.", method_name)),
         }?;
         Ok(Self {
             method,
             rng: Arc::new(Mutex::new(CsRng::from_entropy())),
+            seeded: false,
         })
     }
 
+    /// Instantiate a `NoiseGenerator` using mean and standard deviation, the
+    /// distribution being truncated to the given bounds.
+    ///
+    /// The generated noise always lies between the bounds, without the bias
+    /// clamping it afterwards would introduce.
+    ///
+    /// # Arguments
+    ///
+    /// * `method_name` - the noise distribution to use ("Gaussian" or
+    ///   "Laplace")
+    /// * `mean` - mean of the noise distribution, between the bounds
+    /// * `std_dev` - the standard deviation of the noise distribution
+    /// * `min_bound` - the lower bound of the generated noise values
+    /// * `max_bound` - the upper bound of the generated noise values
+    pub fn new_with_parameters_and_bounds(
+        method_name: &str,
+        mean: F,
+        std_dev: F,
+        min_bound: F,
+        max_bound: F,
+    ) -> Result<Self, AnoError> {
+        let method = match Self::new_with_parameters(method_name, mean, std_dev)?.method {
+            NoiseMethod::Gaussian(normal) => {
+                NoiseMethod::TruncatedGaussian(Truncated::new(normal, mean, min_bound, max_bound)?)
+            }
+            NoiseMethod::Laplace(laplace) => {
+                NoiseMethod::TruncatedLaplace(Truncated::new(laplace, mean, min_bound, max_bound)?)
+            }
+            _ => return Err(ano_error!("{method_name} is not a supported distribution.")),
+        };
+        Ok(Self {
+            method,
+            rng: Arc::new(Mutex::new(CsRng::from_entropy())),
+            seeded: false,
+        })
+    }
+
+    /// Seeds the random generator of this `NoiseGenerator` with the given
+    /// seed, making the generated noise reproducible.
+    ///
+    /// Seeded generators are meant for tests only: anyone knowing the seed can
+    /// remove the noise. Use [`NoiseGenerator::is_seeded`] to reject them in
+    /// production.
+    #[must_use]
+    pub fn with_seed(mut self, seed: u64) -> Self {
+        self.rng = Arc::new(Mutex::new(CsRng::seed_from_u64(seed)));
+        self.seeded = true;
+        self
+    }
+
+    /// Returns `true` if this generator was seeded with
+    /// [`NoiseGenerator::with_seed`], i.e. if its noise is reproducible.
+    #[must_use]
+    pub const fn is_seeded(&self) -> bool {
+        self.seeded
+    }
+
     /// Adds noise generated from a chosen distribution to the input data.
     ///
     /// # Arguments
@@ -1,11 +1,13 @@
 use chrono::{DateTime, Datelike, TimeZone, Timelike};
+use cosmian_crypto_core::kdf256;
 use rand_distr::num_traits::Pow;
 
 use super::{datetime_to_rfc3339, AnoError, TimeUnit};
 use crate::ano_error;
 
 /// The `NumberAggregator` is a data anonymization technique used to round
-/// sensitive measurements to the desired power of ten.
+/// sensitive measurements to the desired power of ten, or to replace them with
+/// the label of the bucket they fall in.
 ///
 /// Example usage:
 ///
@@ -15,9 +17,26 @@ use crate::ano_error;
 /// let num_agg = NumberAggregator::new(2).unwrap();
 /// let anonymized_float = num_agg.apply_on_float(1234.5678); // returns "1200"
 /// let anonymized_int = num_agg.apply_on_int(56789); // returns "56800"
+///
+/// let age_agg = NumberAggregator::new_with_buckets(
+///     vec![18.0, 26.0, 65.0],
+///     Some(vec!["0-17".into(), "18-25".into(), "26-64".into(), "65+".into()]),
+/// )
+/// .unwrap();
+/// let anonymized_age = age_agg.apply_on_int(42); // returns "26-64"
 /// ```
 pub struct NumberAggregator {
-    power_of_ten_exponent: i32,
+    aggregation: Aggregation,
+}
+
+enum Aggregation {
+    PowerOfTen(i32),
+    /// Lower edges of the buckets (but the first one) and labels of the
+    /// buckets: there is one label more than edges.
+    Buckets {
+        edges: Vec<f64>,
+        labels: Vec<String>,
+    },
 }
 
 impl NumberAggregator {
@@ -35,7 +54,62 @@ impl NumberAggregator {
             ));
         }
         Ok(Self {
-            power_of_ten_exponent,
+            aggregation: Aggregation::PowerOfTen(power_of_ten_exponent),
+        })
+    }
+
+    /// Creates a new instance of `NumberAggregator` replacing the numbers with
+    /// the label of their bucket.
+    ///
+    /// The `n` given edges delimit `n + 1` buckets: the numbers lower than the
+    /// first edge, the numbers between two consecutive edges (lower edge
+    /// included) and the numbers greater than or equal to the last edge.
+    ///
+    /// # Arguments
+    ///
+    /// * `edges` - The bucket edges, in strictly increasing order.
+    /// * `labels` - The labels of the `n + 1` buckets. Defaults to the bucket
+    ///   bounds, e.g. `< 18`, `[18, 26)` and `>= 26`.
+    pub fn new_with_buckets(
+        edges: Vec<f64>,
+        labels: Option<Vec<String>>,
+    ) -> Result<Self, AnoError> {
+        if edges.is_empty() {
+            return Err(ano_error!("At least one bucket edge is required."));
+        }
+        if let Some(edge) = edges.iter().find(|edge| !edge.is_finite()) {
+            return Err(ano_error!("Bucket edges must be finite, given {edge}."));
+        }
+        if let Some(window) = edges.windows(2).find(|window| window[0] >= window[1]) {
+            return Err(ano_error!(
+                "Bucket edges must be strictly increasing, given {} before {}.",
+                window[0],
+                window[1]
+            ));
+        }
+        let labels = match labels {
+            Some(labels) => {
+                if labels.len() != edges.len() + 1 {
+                    return Err(ano_error!(
+                        "{} bucket edges require {} labels, given {}.",
+                        edges.len(),
+                        edges.len() + 1,
+                        labels.len()
+                    ));
+                }
+                labels
+            }
+            None => std::iter::once(format!("< {}", edges[0]))
+                .chain(
+                    edges
+                        .windows(2)
+                        .map(|window| format!("[{}, {})", window[0], window[1])),
+                )
+                .chain(std::iter::once(format!(">= {}", edges[edges.len() - 1])))
+                .collect(),
+        };
+        Ok(Self {
+            aggregation: Aggregation::Buckets { edges, labels },
         })
     }
 
@@ -47,14 +121,22 @@ impl NumberAggregator {
     ///
     /// # Returns
     ///
-    /// A string representation of the rounded number.
+    /// A string representation of the rounded number, or the label of its
+    /// bucket.
     #[must_use]
     pub fn apply_on_float(&self, data: f64) -> String {
-        if self.power_of_ten_exponent < 0 {
-            return format!("{:.1$}", data, -self.power_of_ten_exponent as usize);
+        match &self.aggregation {
+            Aggregation::PowerOfTen(exponent) => {
+                if *exponent < 0 {
+                    return format!("{:.1$}", data, -exponent as usize);
+                }
+                let r = 10f64.pow(*exponent);
+                format!("{}", (data / r).round() * r)
+            }
+            Aggregation::Buckets { edges, labels } => {
+                labels[edges.partition_point(|edge| *edge <= data)].clone()
+            }
         }
-        let r = 10f64.pow(self.power_of_ten_exponent);
-        format!("{}", (data / r).round() * r)
     }
 
     /// Rounds an integer to the desired power of ten.
@@ -65,11 +147,24 @@ impl NumberAggregator {
     ///
     /// # Returns
     ///
-    /// A string representation of the rounded number.
+    /// A string representation of the rounded number, or the label of its
+    /// bucket.
     #[must_use]
     pub fn apply_on_int(&self, data: i64) -> String {
-        let r = 10f64.pow(self.power_of_ten_exponent);
-        format!("{:.0}", (data as f64 / r).round() * r)
+        match &self.aggregation {
+            Aggregation::PowerOfTen(exponent) => {
+                let r = 10f64.pow(*exponent);
+                format!("{:.0}", (data as f64 / r).round() * r)
+            }
+            Aggregation::Buckets { .. } => self.apply_on_float(data as f64),
+        }
+    }
+
+    /// Returns `true` if the numbers are replaced with bucket labels rather
+    /// than rounded.
+    #[cfg(feature = "pipeline")]
+    pub(crate) const fn outputs_labels(&self) -> bool {
+        matches!(self.aggregation, Aggregation::Buckets { .. })
     }
 }
 
@@ -141,13 +236,30 @@ impl DateAggregator {
     }
 }
 
+/// Minimum length of the key used by a reversible `NumberScaler`.
+pub const NUMBER_SCALER_KEY_MIN_LENGTH: usize = 32;
+
 /// A data anonymization method that scales individual values while keeping the
 /// overall distribution of the data.
+///
+/// A scaler created with [`NumberScaler::new_reversible`] additionally applies
+/// a scaling and translation derived from a key, which the key holders can
+/// revert using [`NumberScaler::revert_on_float`] and
+/// [`NumberScaler::revert_on_int`]. Scalers created with [`NumberScaler::new`]
+/// are not meant to be reverted.
+///
+/// # Security
+///
+/// Keyed scaling is not encryption: it is a deterministic affine map, that
+/// anyone knowing two original values and their scaled values can recover and
+/// revert without the key. It only hides the scaling and translation factors
+/// used, and must not be relied upon to protect the original values.
 pub struct NumberScaler {
     mean: f64,
     std_deviation: f64,
     scale: f64,
     translate: f64,
+    reversible: bool,
 }
 
 impl NumberScaler {
@@ -166,9 +278,78 @@ impl NumberScaler {
             std_deviation,
             scale,
             translate,
+            reversible: false,
         }
     }
 
+    /// Creates a new reversible `NumberScaler` instance.
+    ///
+    /// The scaling factor is multiplied by a factor in `[1, 2)` and the
+    /// translation factor is shifted by up to `scale`, both derived from the
+    /// given key. See [`NumberScaler`] for the limits of this protection.
+    ///
+    /// # Arguments
+    ///
+    /// * `mean`: The mean of the data distribution.
+    /// * `std_deviation`: The standard deviation of the data distribution.
+    /// * `scale`: The scaling factor. Its absolute value must be greater than
+    ///   or equal to the standard deviation for integers to be restored
+    ///   exactly.
+    /// * `translate`: The translation factor.
+    /// * `key`: secret key, at least 32 bytes long.
+    pub fn new_reversible(
+        mean: f64,
+        std_deviation: f64,
+        scale: f64,
+        translate: f64,
+        key: &[u8],
+    ) -> Result<Self, AnoError> {
+        if key.len() < NUMBER_SCALER_KEY_MIN_LENGTH {
+            return Err(ano_error!(
+                "Number scaling key must be at least {NUMBER_SCALER_KEY_MIN_LENGTH} bytes long, \
+                 given {}.",
+                key.len()
+            ));
+        }
+        if std_deviation == 0.0 || !std_deviation.is_finite() {
+            return Err(ano_error!(
+                "Standard deviation must be a non-null finite number, given {std_deviation}."
+            ));
+        }
+        if scale.abs() < std_deviation.abs() || !scale.is_finite() {
+            return Err(ano_error!(
+                "Scaling factor must be finite and greater than or equal to the standard \
+                 deviation in absolute value, given {scale}."
+            ));
+        }
+        if !translate.is_finite() {
+            return Err(ano_error!(
+                "Translation factor must be a finite number, given {translate}."
+            ));
+        }
+
+        let mut bytes = [0; 16];
+        kdf256!(&mut bytes, key, b"NumberScaler");
+        let (factor_bytes, offset_bytes) = bytes.split_at(8);
+        let secret_factor = 1.0 + to_unit_interval(factor_bytes);
+        let secret_offset = to_unit_interval(offset_bytes).mul_add(2.0, -1.0) * scale;
+
+        Ok(Self {
+            mean,
+            std_deviation,
+            scale: scale * secret_factor,
+            translate: translate + secret_offset,
+            reversible: true,
+        })
+    }
+
+    /// Returns `true` if this scaler was created with a key and can be
+    /// reverted.
+    #[must_use]
+    pub const fn is_reversible(&self) -> bool {
+        self.reversible
+    }
+
     /// Applies the scaling and translation on a floating-point number.
     ///
     /// # Arguments
@@ -198,4 +379,45 @@ impl NumberScaler {
     pub fn apply_on_int(&self, data: i64) -> i64 {
         self.apply_on_float(data as f64).round() as i64
     }
+
+    /// Restores a floating-point number scaled by this reversible scaler, up
+    /// to the floating-point rounding errors.
+    ///
+    /// # Arguments
+    ///
+    /// * `data`: A scaled floating-point number.
+    ///
+    /// # Returns
+    ///
+    /// The original value, or an error if this scaler is not reversible.
+    pub fn revert_on_float(&self, data: f64) -> Result<f64, AnoError> {
+        if !self.reversible {
+            return Err(ano_error!(
+                "This NumberScaler is not reversible: it must be created with a key."
+            ));
+        }
+        let normalized_data = (data - self.translate) / self.scale;
+        Ok(normalized_data.mul_add(self.std_deviation, self.mean))
+    }
+
+    /// Restores an integer scaled by this reversible scaler.
+    ///
+    /// # Arguments
+    ///
+    /// * `data`: A scaled integer.
+    ///
+    /// # Returns
+    ///
+    /// The original value, or an error if this scaler is not reversible.
+    pub fn revert_on_int(&self, data: i64) -> Result<i64, AnoError> {
+        Ok(self.revert_on_float(data as f64)?.round() as i64)
+    }
+}
+
+/// Maps the given 8 bytes to a float in `[0, 1)`.
+pub(super) fn to_unit_interval(bytes: &[u8]) -> f64 {
+    let mut array = [0; 8];
+    array.copy_from_slice(bytes);
+    // Keep the 53 most significant bits to fit in the mantissa of a `f64`.
+    (u64::from_be_bytes(array) >> 11) as f64 / (1_u64 << 53) as f64
 }
@@ -0,0 +1,171 @@
+use cosmian_crypto_core::kdf256;
+
+use super::AnoError;
+use crate::ano_error;
+
+/// Minimum length of the key used to derive the masked digits of a consistent
+/// `PhoneMasker`.
+pub const PHONE_MASKER_KEY_MIN_LENGTH: usize = 32;
+
+/// Character replacing the masked digits of a non-consistent `PhoneMasker`.
+const MASK_CHARACTER: char = 'X';
+
+/// Country calling codes made of two digits. The codes `1` and `7` are made of
+/// one digit, all the others of three digits.
+const TWO_DIGIT_COUNTRY_CODES: &[&str] = &[
+    "20", "27", "30", "31", "32", "33", "34", "36", "39", "40", "41", "43", "44", "45", "46", "47",
+    "48", "49", "51", "52", "53", "54", "55", "56", "57", "58", "60", "61", "62", "63", "64", "65",
+    "66", "81", "82", "84", "86", "90", "91", "92", "93", "94", "95", "98",
+];
+
+/// Returns the number of digits of the country calling code the given digits
+/// start with.
+fn country_code_length(digits: &str) -> usize {
+    if digits.starts_with('1') || digits.starts_with('7') {
+        1
+    } else if TWO_DIGIT_COUNTRY_CODES.contains(&digits.get(..2).unwrap_or_default()) {
+        2
+    } else {
+        3
+    }
+}
+
+/// A data anonymization technique masking the subscriber part of phone numbers
+/// while keeping their country and area codes.
+///
+/// International numbers start with `+` or `00` followed by the country
+/// calling code. The country code, the given number of area code digits and,
+/// for national numbers, the leading trunk prefix `0` are kept. The formatting
+/// characters (spaces, dashes, dots, slashes and parentheses) are kept as is.
+///
+/// The other digits are replaced with `X`, or, for a masker created with
+/// [`PhoneMasker::new_consistent`], with digits derived from the whole number
+/// using a secret key: the same number is always masked the same way.
+///
+/// Example usage:
+///
+/// ```
+/// use cloudproof_anonymization::core::PhoneMasker;
+///
+/// let masker = PhoneMasker::new(1);
+/// assert_eq!(masker.apply("+33 1 23 45 67 89").unwrap(), "+33 1 XX XX XX XX");
+/// assert_eq!(masker.apply("01 23 45 67 89").unwrap(), "01 XX XX XX XX");
+/// ```
+pub struct PhoneMasker {
+    area_code_digits: usize,
+    key: Option<Vec<u8>>,
+}
+
+impl PhoneMasker {
+    /// Creates a new `PhoneMasker` replacing the masked digits with `X`.
+    ///
+    /// # Arguments
+    ///
+    /// * `area_code_digits`: number of digits kept after the country code or
+    ///   the trunk prefix.
+    #[must_use]
+    pub const fn new(area_code_digits: usize) -> Self {
+        Self {
+            area_code_digits,
+            key: None,
+        }
+    }
+
+    /// Creates a new `PhoneMasker` replacing the masked digits with digits
+    /// derived from the phone number using the given key.
+    ///
+    /// # Arguments
+    ///
+    /// * `area_code_digits`: number of digits kept after the country code or
+    ///   the trunk prefix.
+    /// * `key`: secret key used to derive the masked digits, at least 32 bytes
+    ///   long.
+    pub fn new_consistent(area_code_digits: usize, key: &[u8]) -> Result<Self, AnoError> {
+        if key.len() < PHONE_MASKER_KEY_MIN_LENGTH {
+            return Err(ano_error!(
+                "Phone masking key must be at least {PHONE_MASKER_KEY_MIN_LENGTH} bytes long, \
+                 given {}.",
+                key.len()
+            ));
+        }
+        Ok(Self {
+            area_code_digits,
+            key: Some(key.to_vec()),
+        })
+    }
+
+    /// Masks the given phone number.
+    pub fn apply(&self, phone_number: &str) -> Result<String, AnoError> {
+        let phone_number = phone_number.trim();
+        let (prefix, number) = match phone_number.strip_prefix('+') {
+            Some(number) => ("+", number),
+            None => ("", phone_number),
+        };
+        if let Some(c) = number
+            .chars()
+            .find(|c| !c.is_ascii_digit() && !" -./()".contains(*c))
+        {
+            return Err(ano_error!(
+                "Invalid phone number `{phone_number}`: unexpected character `{c}`."
+            ));
+        }
+        let digits = number
+            .chars()
+            .filter(char::is_ascii_digit)
+            .collect::<String>();
+
+        let kept_digits = if !prefix.is_empty() {
+            country_code_length(&digits) + self.area_code_digits
+        } else if let Some(international) = digits.strip_prefix("00") {
+            2 + country_code_length(international) + self.area_code_digits
+        } else if digits.starts_with('0') {
+            1 + self.area_code_digits
+        } else {
+            self.area_code_digits
+        };
+        if kept_digits >= digits.len() {
+            return Err(ano_error!(
+                "Invalid phone number `{phone_number}`: no digit left to mask after the country \
+                 and area codes."
+            ));
+        }
+
+        let mut masked_digits = self
+            .masked_digits(&digits, digits.len() - kept_digits)
+            .into_iter();
+        let mut masked = String::with_capacity(phone_number.len());
+        masked.push_str(prefix);
+        let mut digit_index = 0;
+        for c in number.chars() {
+            if c.is_ascii_digit() {
+                masked.push(if digit_index < kept_digits {
+                    c
+                } else {
+                    masked_digits.next().unwrap_or(MASK_CHARACTER)
+                });
+                digit_index += 1;
+            } else {
+                masked.push(c);
+            }
+        }
+        Ok(masked)
+    }
+
+    /// Returns the `n` characters replacing the masked digits of the phone
+    /// number made of the given digits.
+    fn masked_digits(&self, digits: &str, n: usize) -> Vec<char> {
+        let Some(key) = &self.key else {
+            return vec![MASK_CHARACTER; n];
+        };
+        let mut bytes = vec![0; 2 * n];
+        kdf256!(&mut bytes, key, b"PhoneMasker", digits.as_bytes());
+        bytes
+            .chunks_exact(2)
+            .map(|chunk| {
+                // The modulo bias of 16-bit draws is negligible.
+                let draw = u16::from_le_bytes([chunk[0], chunk[1]]) % 10;
+                char::from_digit(u32::from(draw), 10).unwrap_or(MASK_CHARACTER)
+            })
+            .collect()
+    }
+}
@@ -0,0 +1,303 @@
+//! Anonymization of whole datasets, column by column.
+//!
+//! The pipeline is configured with a JSON object mapping column names to
+//! [`AnonymizationConfig`]s:
+//!
+//! ```json
+//! {
+//!     "email": { "type": "hash", "method": "SHA2" },
+//!     "salary": { "type": "number_aggregator", "power_of_ten_exponent": 3, "data_type": "integer" }
+//! }
+//! ```
+//!
+//! Columns absent from the configuration are left untouched, and null values
+//! (or empty CSV fields) are never anonymized.
+//!
+//! A pipeline created [`with_report`](AnonymizationPipeline::with_report)
+//! records the transformations it applies into a [`TransformationReport`].
+
+use std::{
+    collections::HashMap,
+    sync::{Arc, Mutex, PoisonError},
+};
+
+use arrow::{
+    array::{Array, ArrayRef, AsArray, StringArray},
+    compute::{cast, cast_with_options, CastOptions},
+    datatypes::{DataType, Schema},
+    record_batch::RecordBatch,
+};
+
+use crate::{
+    ano_error,
+    core::{
+        config::Anonymizer, report::ColumnReport, AnoError, AnonymizationConfig,
+        TransformationReport,
+    },
+};
+
+/// Applies an anonymization technique per column on Arrow record batches or
+/// CSV chunks.
+pub struct AnonymizationPipeline {
+    columns: HashMap<String, AnonymizationConfig>,
+    /// Transformations applied so far, if recorded.
+    report: Option<Mutex<TransformationReport>>,
+}
+
+impl AnonymizationPipeline {
+    /// Creates a new pipeline applying the given technique on each column.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if one of the configurations is invalid.
+    pub fn new(columns: HashMap<String, AnonymizationConfig>) -> Result<Self, AnoError> {
+        for (column, config) in &columns {
+            config
+                .build()
+                .map_err(|e| ano_error!("invalid configuration of column `{column}`: {e}"))?;
+        }
+        Ok(Self {
+            columns,
+            report: None,
+        })
+    }
+
+    /// Creates a new pipeline from a JSON object mapping column names to
+    /// anonymization configurations.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the JSON cannot be parsed or if one of the
+    /// configurations is invalid.
+    pub fn from_json(json: &str) -> Result<Self, AnoError> {
+        let columns = serde_json::from_str(json)
+            .map_err(|e| ano_error!("error parsing the pipeline configuration: {e}"))?;
+        Self::new(columns)
+    }
+
+    /// Records the transformations applied by this pipeline, across all the
+    /// record batches and CSV chunks it anonymizes. The report can be
+    /// retrieved using [`report`](Self::report).
+    #[must_use]
+    pub fn with_report(mut self) -> Self {
+        self.report = Some(Mutex::default());
+        self
+    }
+
+    /// Returns the transformations applied so far, or `None` if they are not
+    /// recorded.
+    #[must_use]
+    pub fn report(&self) -> Option<TransformationReport> {
+        self.report.as_ref().map(|report| {
+            // The report is always left consistent: ignore poisoning.
+            report
+                .lock()
+                .unwrap_or_else(PoisonError::into_inner)
+                .clone()
+        })
+    }
+
+    /// Adds the transformations applied on a record batch or CSV chunk to the
+    /// report.
+    fn record(&self, transformations: Option<TransformationReport>) {
+        if let (Some(report), Some(transformations)) = (&self.report, transformations) {
+            report
+                .lock()
+                .unwrap_or_else(PoisonError::into_inner)
+                .merge(&transformations);
+        }
+    }
+
+    /// Returns an empty report if the transformations are recorded.
+    fn new_report(&self) -> Option<TransformationReport> {
+        self.report
+            .as_ref()
+            .map(|_| TransformationReport::default())
+    }
+
+    /// Returns the name of the technique applied on the given column.
+    fn technique(&self, column: &str) -> &'static str {
+        self.columns
+            .get(column)
+            .map_or("", AnonymizationConfig::technique)
+    }
+
+    /// Builds the anonymizers of the given columns.
+    ///
+    /// Returns an error if a configured column is missing: a typo in the
+    /// configuration would otherwise leave a column in clear.
+    fn build_anonymizers<'a>(
+        &self,
+        column_names: impl IntoIterator<Item = &'a str>,
+    ) -> Result<HashMap<&'a str, Anonymizer>, AnoError> {
+        let anonymizers = column_names
+            .into_iter()
+            .filter_map(|name| {
+                self.columns
+                    .get(name)
+                    .map(|config| config.build().map(|anonymizer| (name, anonymizer)))
+            })
+            .collect::<Result<HashMap<_, _>, _>>()?;
+        if let Some(missing) = self
+            .columns
+            .keys()
+            .find(|name| !anonymizers.contains_key(name.as_str()))
+        {
+            return Err(ano_error!("column `{missing}` not found"));
+        }
+        Ok(anonymizers)
+    }
+
+    /// Anonymizes the configured columns of the given record batch.
+    ///
+    /// Values are anonymized through their string representation. The
+    /// anonymized columns keep their type, except the non-string columns
+    /// anonymized by a technique producing text (hashes, masked words), which
+    /// become `Utf8` columns.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if a configured column is missing or if a value cannot
+    /// be anonymized.
+    pub fn apply_to_record_batch(&self, batch: &RecordBatch) -> Result<RecordBatch, AnoError> {
+        let schema = batch.schema();
+        let mut anonymizers =
+            self.build_anonymizers(schema.fields().iter().map(|field| field.name().as_str()))?;
+        let mut report = self.new_report();
+
+        let columns = schema
+            .fields()
+            .iter()
+            .zip(batch.columns())
+            .map(|(field, column)| {
+                let name = field.name().as_str();
+                match anonymizers.get_mut(name) {
+                    Some(anonymizer) => anonymize_array(
+                        anonymizer,
+                        column,
+                        report
+                            .as_mut()
+                            .map(|report| report.column(name, self.technique(name))),
+                    )
+                    .map_err(|e| ano_error!("error anonymizing column `{name}`: {e}")),
+                    None => Ok(Arc::clone(column)),
+                }
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        self.record(report);
+
+        let fields = schema
+            .fields()
+            .iter()
+            .zip(&columns)
+            .map(|(field, column)| {
+                field
+                    .as_ref()
+                    .clone()
+                    .with_data_type(column.data_type().clone())
+            })
+            .collect::<Vec<_>>();
+        RecordBatch::try_new(
+            Arc::new(Schema::new_with_metadata(fields, schema.metadata().clone())),
+            columns,
+        )
+        .map_err(|e| ano_error!("error building the anonymized record batch: {e}"))
+    }
+
+    /// Anonymizes the configured columns of the given CSV chunk.
+    ///
+    /// The chunk must start with the header line giving the column names;
+    /// each chunk of a large file should therefore be prefixed with it. The
+    /// output has the same header line.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the CSV is malformed, if a configured column is
+    /// missing or if a value cannot be anonymized.
+    pub fn apply_to_csv(&self, csv: &str) -> Result<String, AnoError> {
+        let mut reader = csv::Reader::from_reader(csv.as_bytes());
+        let headers = reader
+            .headers()
+            .map_err(|e| ano_error!("error reading the CSV header: {e}"))?
+            .clone();
+        let mut anonymizers = self.build_anonymizers(headers.iter())?;
+        let mut report = self.new_report();
+
+        let mut writer = csv::Writer::from_writer(Vec::with_capacity(csv.len()));
+        writer
+            .write_record(&headers)
+            .map_err(|e| ano_error!("error writing the CSV header: {e}"))?;
+        for record in reader.records() {
+            let record = record.map_err(|e| ano_error!("error reading a CSV record: {e}"))?;
+            let anonymized_record = headers
+                .iter()
+                .zip(record.iter())
+                .map(|(name, value)| match anonymizers.get_mut(name) {
+                    Some(anonymizer) if !value.is_empty() => {
+                        let anonymized = anonymizer
+                            .apply(value)
+                            .map_err(|e| ano_error!("error anonymizing column `{name}`: {e}"))?;
+                        if let Some(report) = &mut report {
+                            report
+                                .column(name, self.technique(name))
+                                .record(value, &anonymized);
+                        }
+                        Ok::<_, AnoError>(anonymized)
+                    }
+                    _ => Ok(value.to_string()),
+                })
+                .collect::<Result<Vec<_>, _>>()?;
+            writer
+                .write_record(&anonymized_record)
+                .map_err(|e| ano_error!("error writing a CSV record: {e}"))?;
+        }
+
+        self.record(report);
+
+        let output = writer
+            .into_inner()
+            .map_err(|e| ano_error!("error writing the CSV: {e}"))?;
+        String::from_utf8(output).map_err(|e| ano_error!("error writing the CSV: {e}"))
+    }
+}
+
+/// Anonymizes all the non-null values of the given array, recording the
+/// transformations into the given report if any.
+fn anonymize_array(
+    anonymizer: &mut Anonymizer,
+    array: &ArrayRef,
+    mut report: Option<&mut ColumnReport>,
+) -> Result<ArrayRef, AnoError> {
+    let strings = cast(array, &DataType::Utf8).map_err(|e| ano_error!("{e}"))?;
+    let anonymized = strings
+        .as_string::<i32>()
+        .iter()
+        .map(|value| {
+            value
+                .map(|value| {
+                    let anonymized = anonymizer.apply(value)?;
+                    if let Some(report) = report.as_deref_mut() {
+                        report.record(value, &anonymized);
+                    }
+                    Ok::<_, AnoError>(anonymized)
+                })
+                .transpose()
+        })
+        .collect::<Result<StringArray, _>>()?;
+
+    let data_type = array.data_type();
+    let is_string = matches!(
+        data_type,
+        DataType::Utf8 | DataType::LargeUtf8 | DataType::Utf8View
+    );
+    if anonymizer.outputs_text() && !is_string {
+        return Ok(Arc::new(anonymized));
+    }
+    // Fail rather than silently replacing the values which cannot be cast
+    // back with nulls.
+    let cast_options = CastOptions {
+        safe: false,
+        ..Default::default()
+    };
+    cast_with_options(&anonymized, data_type, &cast_options).map_err(|e| ano_error!("{e}"))
+}
@@ -0,0 +1,99 @@
+//! Report of the transformations applied by an anonymization pipeline.
+//!
+//! Auditors may require evidence that the configured techniques were actually
+//! applied. The report only holds statistics about each anonymized column:
+//! neither the original nor the anonymized values are recorded.
+//!
+//! ```json
+//! {
+//!     "columns": {
+//!         "email": {
+//!             "technique": "hash",
+//!             "values": 2,
+//!             "changed": 2,
+//!             "input_length": 37,
+//!             "output_length": 88
+//!         }
+//!     }
+//! }
+//! ```
+
+use std::collections::BTreeMap;
+
+use serde::Serialize;
+
+use crate::{ano_error, core::AnoError};
+
+/// Transformations applied on the values of a column.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct ColumnReport {
+    /// Anonymization technique applied, as the `type` of its configuration.
+    pub technique: String,
+    /// Number of values anonymized. Null values and empty CSV fields are not
+    /// anonymized.
+    pub values: usize,
+    /// Number of values modified by the anonymization.
+    pub changed: usize,
+    /// Total length in bytes of the values before anonymization.
+    pub input_length: usize,
+    /// Total length in bytes of the values after anonymization.
+    pub output_length: usize,
+}
+
+impl ColumnReport {
+    fn new(technique: &str) -> Self {
+        Self {
+            technique: technique.to_string(),
+            ..Self::default()
+        }
+    }
+
+    /// Records the anonymization of the given value.
+    pub(crate) fn record(&mut self, input: &str, output: &str) {
+        self.values += 1;
+        if input != output {
+            self.changed += 1;
+        }
+        self.input_length += input.len();
+        self.output_length += output.len();
+    }
+
+    fn merge(&mut self, other: &Self) {
+        self.values += other.values;
+        self.changed += other.changed;
+        self.input_length += other.input_length;
+        self.output_length += other.output_length;
+    }
+}
+
+/// Transformations applied by a pipeline, per column.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct TransformationReport {
+    pub columns: BTreeMap<String, ColumnReport>,
+}
+
+impl TransformationReport {
+    /// Returns the report of the given column, created with the given
+    /// technique if needed.
+    pub(crate) fn column(&mut self, column: &str, technique: &str) -> &mut ColumnReport {
+        self.columns
+            .entry(column.to_string())
+            .or_insert_with(|| ColumnReport::new(technique))
+    }
+
+    /// Adds the transformations of the given report to this report.
+    pub(crate) fn merge(&mut self, other: &Self) {
+        for (column, report) in &other.columns {
+            self.column(column, &report.technique).merge(report);
+        }
+    }
+
+    /// Serializes this report into JSON.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the serialization fails.
+    pub fn to_json(&self) -> Result<String, AnoError> {
+        serde_json::to_string(self).map_err(|e| ano_error!("error serializing the report: {e}"))
+    }
+}
@@ -5,8 +5,9 @@ use chrono::{DateTime, Datelike, Timelike};
 
 use super::{NumberAggregator, WordMasker};
 use crate::core::{
-    AnoError, DateAggregator, HashMethod, Hasher, NoiseGenerator, NumberScaler, TimeUnit,
-    WordPatternMasker, WordTokenizer,
+    AnoError, DateAggregator, DateShifter, DomainMasking, EmailMasker, GeoMasker, HashMethod,
+    Hasher, HmacKey, Language, LocalPartMasking, NoiseGenerator, NumberScaler, PhoneMasker,
+    TimeUnit, WordPatternMasker, WordTokenizer, KEYED_HASH_LENGTH,
 };
 
 #[test]
@@ -50,6 +51,44 @@ fn test_hash_argon2() -> Result<(), AnoError> {
     Ok(())
 }
 
+#[test]
+fn test_keyed_hash_rotation() -> Result<(), AnoError> {
+    let hasher = Hasher::new(HashMethod::SHA2(Some(b"example salt".to_vec())));
+    let key_1 = HmacKey::new(1, &[1; 32])?;
+    let key_2 = HmacKey::new(2, &[2; 32])?;
+
+    let hash_1 = hasher.apply_keyed_bytes(&[HmacKey::new(1, &[1; 32])?], b"test hmac")?;
+    assert_eq!(hash_1.len(), KEYED_HASH_LENGTH);
+    assert_eq!(hash_1[..4], 1_u32.to_be_bytes());
+    // Same data, another key.
+    assert_ne!(
+        hash_1[4..],
+        hasher.apply_keyed_bytes(&[HmacKey::new(1, &[3; 32])?], b"test hmac")?[4..]
+    );
+
+    // Rotated hashes match the hashes computed using the key history.
+    let hash_2 = Hasher::rehash_bytes(&key_1, &key_2, &hash_1)?;
+    assert_eq!(hash_2[..4], 2_u32.to_be_bytes());
+    let keys = [key_1, key_2];
+    assert_eq!(hash_2, hasher.apply_keyed_bytes(&keys, b"test hmac")?);
+    assert_eq!(
+        Hasher::rehash_str(
+            &keys[0],
+            &keys[1],
+            &hasher.apply_keyed_str(&keys[..1], "test hmac")?
+        )?,
+        hasher.apply_keyed_str(&keys, "test hmac")?
+    );
+
+    // The version of the old key must match.
+    assert!(Hasher::rehash_bytes(&keys[1], &keys[0], &hash_1).is_err());
+    assert!(Hasher::rehash_bytes(&keys[0], &keys[1], &hash_1[1..]).is_err());
+    assert!(hasher.apply_keyed_bytes(&[], b"test hmac").is_err());
+    assert!(HmacKey::new(1, &[1; 31]).is_err());
+
+    Ok(())
+}
+
 #[test]
 fn test_noise_gaussian_f64() -> Result<(), AnoError> {
     let mut gaussian_noise_generator = NoiseGenerator::new_with_parameters("Gaussian", 0.0, 1.0)?;
@@ -94,6 +133,86 @@ fn test_noise_uniform_f64() -> Result<(), AnoError> {
     Ok(())
 }
 
+#[test]
+fn test_noise_truncated() -> Result<(), AnoError> {
+    for method_name in ["Gaussian", "Laplace"] {
+        // Most samples of this distribution are out of the bounds.
+        let mut noise_generator =
+            NoiseGenerator::new_with_parameters_and_bounds(method_name, 0.0, 10.0, -1.0, 2.0)?;
+        for _ in 0..1000 {
+            let noisy_data = noise_generator.apply_on_float(40.0);
+            assert!((39.0..=42.0).contains(&noisy_data));
+        }
+
+        // Narrow bounds are sampled uniformly.
+        let mut noise_generator =
+            NoiseGenerator::new_with_parameters_and_bounds(method_name, 0.0, 1e9, -1.0, 1.0)?;
+        let noisy_data = noise_generator.apply_on_float(40.0);
+        assert!((39.0..=41.0).contains(&noisy_data));
+
+        // Bounded Gaussian and Laplace noises are truncated.
+        let mut noise_generator = NoiseGenerator::new_with_bounds(method_name, -1.0, 1.0)?;
+        for _ in 0..1000 {
+            let noisy_data = noise_generator.apply_on_float(40.0);
+            assert!((39.0..=41.0).contains(&noisy_data));
+        }
+
+        // The mean must lie between the bounds.
+        let res = NoiseGenerator::new_with_parameters_and_bounds(method_name, 5.0, 1.0, -1.0, 1.0);
+        assert!(res.is_err());
+        let res = NoiseGenerator::new_with_parameters_and_bounds(method_name, 0.0, 1.0, 1.0, -1.0);
+        assert!(res.is_err());
+    }
+
+    let res = NoiseGenerator::new_with_parameters_and_bounds("Uniform", 0.0, 1.0, -1.0, 1.0);
+    assert!(res.is_err());
+
+    Ok(())
+}
+
+#[test]
+fn test_noise_seeded() -> Result<(), AnoError> {
+    let mut noise_generator = NoiseGenerator::new_with_parameters("Laplace", 0.0, 1.0)?;
+    assert!(!noise_generator.is_seeded());
+    let noisy_data = noise_generator.apply_on_float(40.0);
+    assert_ne!(noisy_data, noise_generator.apply_on_float(40.0));
+
+    // Generators seeded with the same seed generate the same noise.
+    let mut noise_generator =
+        NoiseGenerator::new_with_parameters("Laplace", 0.0, 1.0)?.with_seed(42);
+    assert!(noise_generator.is_seeded());
+    let noisy_data = (0..10)
+        .map(|_| noise_generator.apply_on_float(40.0))
+        .collect::<Vec<_>>();
+    let mut noise_generator =
+        NoiseGenerator::new_with_parameters("Laplace", 0.0, 1.0)?.with_seed(42);
+    for data in noisy_data {
+        assert_eq!(data, noise_generator.apply_on_float(40.0));
+    }
+
+    let mut noise_generator = NoiseGenerator::new_with_bounds("Uniform", -10.0, 10.0)?.with_seed(1);
+    assert_ne!(
+        noise_generator.apply_on_float(40.0),
+        NoiseGenerator::new_with_bounds("Uniform", -10.0, 10.0)?
+            .with_seed(2)
+            .apply_on_float(40.0)
+    );
+
+    // Seeded maskers blur the coordinates reproducibly.
+    let masker = GeoMasker::new(1_000.0)?;
+    assert!(!masker.is_seeded());
+    let masker = masker.with_seed(42);
+    assert!(masker.is_seeded());
+    assert_eq!(
+        masker.apply_on_coordinates(48.8566, 2.3522)?,
+        GeoMasker::new(1_000.0)?
+            .with_seed(42)
+            .apply_on_coordinates(48.8566, 2.3522)?
+    );
+
+    Ok(())
+}
+
 #[test]
 fn test_noise_gaussian_i64() -> Result<(), AnoError> {
     let mut gaussian_noise_generator = NoiseGenerator::new_with_parameters("Gaussian", 0.0, 1.0)?;
@@ -264,6 +383,34 @@ fn test_mask_word() -> Result<(), AnoError> {
     Ok(())
 }
 
+#[test]
+fn test_mask_word_language() -> Result<(), AnoError> {
+    let block_words = vec!["été", "Müller", "aujourd'hui", "John", "de", "Straße"];
+
+    let word_masker = WordMasker::new(&block_words);
+    assert_eq!(
+        word_masker.apply("L'été de John's friend, aujourd'hui: Jean-Müller (Straße)."),
+        "L'XXXX XXXX XXXX's friend, XXXX: Jean-XXXX (XXXX)."
+    );
+
+    // Stop words are never masked.
+    let word_masker = WordMasker::new_with_language(&block_words, Language::French);
+    assert_eq!(
+        word_masker.apply("L'été de Jean Müller"),
+        "L'XXXX de Jean XXXX"
+    );
+
+    let word_tokenizer = WordTokenizer::new_with_language(&block_words, Language::German)?;
+    let safe_str = word_tokenizer.apply("Herr Müller wohnt in der Straße");
+    assert!(safe_str.starts_with("Herr "));
+    assert!(safe_str.contains(" wohnt in der "));
+    assert!(!safe_str.contains("Müller"));
+    assert!(!safe_str.contains("Straße"));
+
+    assert!(Language::try_from("Klingon").is_err());
+    Ok(())
+}
+
 #[test]
 fn test_token_word() -> Result<(), AnoError> {
     let input_str = String::from("confidential : contains secret documents with confidential info");
@@ -298,6 +445,32 @@ fn test_word_pattern() -> Result<(), AnoError> {
     Ok(())
 }
 
+#[test]
+fn test_word_pattern_capture_groups() -> Result<(), AnoError> {
+    let input_str = "Born on 1987-04-12, hired on 2015-09-01.";
+
+    // Keep the year of the matched dates.
+    let masker = WordPatternMasker::new(r"(?<year>\d{4})-\d{2}-\d{2}", "${year}-XX-XX")?;
+    assert_eq!(
+        masker.apply(input_str),
+        "Born on 1987-XX-XX, hired on 2015-XX-XX."
+    );
+
+    let masker = WordPatternMasker::new(r"(\d{4})-(\d{2})-\d{2}", "$$${1}/${2}")?;
+    assert_eq!(
+        masker.apply(input_str),
+        "Born on $1987/04, hired on $2015/09."
+    );
+
+    // Referencing absent capture groups fails.
+    assert!(WordPatternMasker::new(r"(\d{4})-\d{2}", "${2}").is_err());
+    assert!(WordPatternMasker::new(r"(?<year>\d{4})-\d{2}", "${month}").is_err());
+    // `$1XX` references the group named `1XX`.
+    assert!(WordPatternMasker::new(r"(\d{4})-\d{2}", "$1XX").is_err());
+
+    Ok(())
+}
+
 #[test]
 fn test_float_aggregation() -> Result<(), AnoError> {
     let float_aggregator = NumberAggregator::new(-1)?;
@@ -335,6 +508,39 @@ fn test_int_aggregation() -> Result<(), AnoError> {
     Ok(())
 }
 
+#[test]
+fn test_bucket_aggregation() -> Result<(), AnoError> {
+    let age_aggregator = NumberAggregator::new_with_buckets(
+        vec![18.0, 26.0, 65.0],
+        Some(vec![
+            "0-17".to_string(),
+            "18-25".to_string(),
+            "26-64".to_string(),
+            "65+".to_string(),
+        ]),
+    )?;
+    assert_eq!(age_aggregator.apply_on_int(0), "0-17");
+    assert_eq!(age_aggregator.apply_on_int(17), "0-17");
+    assert_eq!(age_aggregator.apply_on_int(18), "18-25");
+    assert_eq!(age_aggregator.apply_on_float(25.9), "18-25");
+    assert_eq!(age_aggregator.apply_on_int(64), "26-64");
+    assert_eq!(age_aggregator.apply_on_int(65), "65+");
+    assert_eq!(age_aggregator.apply_on_int(120), "65+");
+
+    let aggregator = NumberAggregator::new_with_buckets(vec![0.5, 10.0], None)?;
+    assert_eq!(aggregator.apply_on_float(-3.0), "< 0.5");
+    assert_eq!(aggregator.apply_on_int(1), "[0.5, 10)");
+    assert_eq!(aggregator.apply_on_int(10), ">= 10");
+
+    assert!(NumberAggregator::new_with_buckets(vec![], None).is_err());
+    assert!(NumberAggregator::new_with_buckets(vec![1.0, 1.0], None).is_err());
+    assert!(NumberAggregator::new_with_buckets(vec![2.0, 1.0], None).is_err());
+    assert!(NumberAggregator::new_with_buckets(vec![f64::NAN], None).is_err());
+    assert!(NumberAggregator::new_with_buckets(vec![1.0], Some(vec!["low".to_string()])).is_err());
+
+    Ok(())
+}
+
 #[test]
 fn test_time_aggregation() -> Result<(), AnoError> {
     let time_aggregator = DateAggregator::new(TimeUnit::Hour);
@@ -405,3 +611,490 @@ fn test_int_scale() {
 
     assert!(n1 >= n2);
 }
+
+#[test]
+fn test_reversible_scale() -> Result<(), AnoError> {
+    let key = [42; 32];
+    let scaler = NumberScaler::new_reversible(10.0, 5.0, 20.0, -50.0, &key)?;
+    assert!(scaler.is_reversible());
+
+    // Without the key, values are scaled differently.
+    let public_scaler = NumberScaler::new(10.0, 5.0, 20.0, -50.0);
+    assert!(!public_scaler.is_reversible());
+    assert!(public_scaler.revert_on_float(1.0).is_err());
+    assert_ne!(scaler.apply_on_int(20), public_scaler.apply_on_int(20));
+
+    for data in [-1_000_000, -17, 0, 1, 19, 20, 123_456_789] {
+        assert_eq!(scaler.revert_on_int(scaler.apply_on_int(data))?, data);
+    }
+    for data in [-1_000.5, 0.0, 19.5, 20.0, 123_456.789] {
+        assert_relative_eq!(
+            scaler.revert_on_float(scaler.apply_on_float(data))?,
+            data,
+            max_relative = 1e-12
+        );
+    }
+
+    // The order is preserved.
+    assert!(scaler.apply_on_float(20.0) > scaler.apply_on_float(19.5));
+
+    // This is not encryption: two known pairs revert any value without the key.
+    let (x1, y1) = (0.0, scaler.apply_on_float(0.0));
+    let (x2, y2) = (100.0, scaler.apply_on_float(100.0));
+    let y = scaler.apply_on_float(42.0);
+    assert_relative_eq!(
+        (y - y1).mul_add((x2 - x1) / (y2 - y1), x1),
+        42.0,
+        max_relative = 1e-9
+    );
+
+    // Another key gives other values.
+    let other_scaler = NumberScaler::new_reversible(10.0, 5.0, 20.0, -50.0, &[0; 32])?;
+    assert_ne!(scaler.apply_on_int(20), other_scaler.apply_on_int(20));
+
+    assert!(NumberScaler::new_reversible(10.0, 5.0, 20.0, -50.0, &[42; 16]).is_err());
+    assert!(NumberScaler::new_reversible(10.0, 5.0, 2.0, -50.0, &key).is_err());
+    assert!(NumberScaler::new_reversible(10.0, 0.0, 2.0, -50.0, &key).is_err());
+    Ok(())
+}
+
+#[test]
+fn test_date_shifting() -> Result<(), AnoError> {
+    let key = [42; 32];
+    let date_shifter = DateShifter::new(&key, 30, TimeUnit::Day)?;
+
+    let admission = "2023-04-07T12:34:56-05:00";
+    let discharge = "2023-04-10T08:00:00-05:00";
+    let shifted = date_shifter.apply_on_dates("patient-1", &[admission, discharge])?;
+
+    // Intervals between the dates of an entity are preserved.
+    let interval =
+        DateTime::parse_from_rfc3339(discharge)? - DateTime::parse_from_rfc3339(admission)?;
+    let shifted_admission = DateTime::parse_from_rfc3339(&shifted[0])?;
+    let shifted_discharge = DateTime::parse_from_rfc3339(&shifted[1])?;
+    assert_eq!(shifted_discharge - shifted_admission, interval);
+
+    // The shift is within bounds, non-null and keeps the time of the day.
+    let shift = shifted_admission - DateTime::parse_from_rfc3339(admission)?;
+    assert!(shift.num_days() != 0 && shift.num_days().abs() <= 30);
+    assert_eq!(shift.num_seconds() % 86_400, 0);
+    assert_eq!(
+        shifted_admission.timezone(),
+        DateTime::parse_from_rfc3339(admission)?.timezone()
+    );
+
+    // The offset is deterministic for a given key and entity.
+    assert_eq!(
+        DateShifter::new(&key, 30, TimeUnit::Day)?.apply_on_date("patient-1", admission)?,
+        shifted[0]
+    );
+
+    // Different entities are very likely to be shifted differently.
+    let offsets = (0..100)
+        .map(|i| date_shifter.offset(&format!("patient-{i}")))
+        .collect::<HashSet<_>>();
+    assert!(offsets.len() > 1);
+
+    // Invalid parameters are rejected.
+    assert!(DateShifter::new(&key[..16], 30, TimeUnit::Day).is_err());
+    assert!(DateShifter::new(&key, 0, TimeUnit::Day).is_err());
+    assert!(DateShifter::new(&key, 30, TimeUnit::Month).is_err());
+    assert!(
+        date_shifter
+            .apply_on_date("patient-1", "not a date")
+            .is_err()
+    );
+
+    Ok(())
+}
+
+/// Great-circle distance in meters between two points given in degrees.
+fn haversine_distance((lat1, lon1): (f64, f64), (lat2, lon2): (f64, f64)) -> f64 {
+    let (lat1, lat2) = (lat1.to_radians(), lat2.to_radians());
+    let d_lat = lat2 - lat1;
+    let d_lon = (lon2 - lon1).to_radians();
+    let a = (d_lat / 2.0).sin().powi(2) + lat1.cos() * lat2.cos() * (d_lon / 2.0).sin().powi(2);
+    2.0 * 6_371_008.8 * a.sqrt().asin()
+}
+
+#[test]
+fn test_geo_blurring() -> Result<(), AnoError> {
+    let paris = (48.8566, 2.3522);
+    let masker = GeoMasker::new(1_000.0)?;
+    assert!(!masker.is_consistent());
+
+    // Blurred coordinates are within the radius and differ between calls.
+    let blurred = (0..100)
+        .map(|_| masker.apply_on_coordinates(paris.0, paris.1))
+        .collect::<Result<Vec<_>, _>>()?;
+    for point in &blurred {
+        assert!(haversine_distance(paris, *point) <= 1_000.0 + 1e-6);
+    }
+    assert!(blurred.windows(2).any(|points| points[0] != points[1]));
+
+    // Longitudes wrap around the antimeridian.
+    let (_, longitude) = GeoMasker::new(10_000.0)?.apply_on_coordinates(0.0, 180.0)?;
+    assert!((-180.0..180.0).contains(&longitude));
+
+    // Invalid parameters are rejected.
+    assert!(GeoMasker::new(0.0).is_err());
+    assert!(GeoMasker::new(f64::NAN).is_err());
+    assert!(masker.apply_on_coordinates(91.0, 0.0).is_err());
+    assert!(masker.apply_on_coordinates(0.0, -181.0).is_err());
+    assert!(
+        masker
+            .apply_on_entity_coordinates("patient-1", paris.0, paris.1)
+            .is_err()
+    );
+
+    Ok(())
+}
+
+#[test]
+fn test_geo_consistent_blurring() -> Result<(), AnoError> {
+    let key = [42; 32];
+    let masker = GeoMasker::new_consistent(1_000.0, &key)?;
+    assert!(masker.is_consistent());
+
+    let home = (48.8566, 2.3522);
+    let work = (48.8606, 2.3376);
+    let blurred_home = masker.apply_on_entity_coordinates("patient-1", home.0, home.1)?;
+    let blurred_work = masker.apply_on_entity_coordinates("patient-1", work.0, work.1)?;
+    assert!(haversine_distance(home, blurred_home) <= 1_000.0 + 1e-6);
+
+    // The displacement is deterministic for a given key and entity, and keeps
+    // the distances between the locations of an entity.
+    assert_eq!(
+        GeoMasker::new_consistent(1_000.0, &key)?.apply_on_entity_coordinates(
+            "patient-1",
+            home.0,
+            home.1
+        )?,
+        blurred_home
+    );
+    assert_relative_eq!(
+        haversine_distance(blurred_home, blurred_work),
+        haversine_distance(home, work),
+        max_relative = 1e-3
+    );
+
+    // Different entities are very likely to be moved differently.
+    assert_ne!(
+        masker.apply_on_entity_coordinates("patient-2", home.0, home.1)?,
+        blurred_home
+    );
+
+    assert!(GeoMasker::new_consistent(1_000.0, &key[..16]).is_err());
+
+    Ok(())
+}
+
+#[test]
+fn test_geohash() -> Result<(), AnoError> {
+    assert_eq!(GeoMasker::to_geohash(48.8566, 2.3522, 7)?, "u09tvw0");
+    assert_eq!(
+        GeoMasker::to_geohash(57.64911, 10.40744, 11)?,
+        "u4pruydqqvj"
+    );
+    assert_eq!(GeoMasker::to_geohash(-90.0, -180.0, 3)?, "000");
+
+    // Truncating a geohash is the same as encoding with a lower precision.
+    let geohash = GeoMasker::to_geohash(48.8566, 2.3522, 12)?;
+    assert_eq!(
+        GeoMasker::truncate_geohash(&geohash, 5)?,
+        GeoMasker::to_geohash(48.8566, 2.3522, 5)?
+    );
+
+    assert!(GeoMasker::to_geohash(48.8566, 2.3522, 0).is_err());
+    assert!(GeoMasker::to_geohash(48.8566, 2.3522, 13).is_err());
+    assert!(GeoMasker::truncate_geohash("u09tv", 6).is_err());
+    assert!(GeoMasker::truncate_geohash("u09ta", 3).is_err());
+
+    Ok(())
+}
+
+#[test]
+fn test_email_masking() -> Result<(), AnoError> {
+    let key = [42; 32];
+
+    let masker = EmailMasker::new(&key, LocalPartMasking::Hash, DomainMasking::Preserve)?;
+    let masked = masker.apply("John.Doe@Cosmian.com")?;
+    let (local_part, domain) = masked.split_once('@').unwrap();
+    assert_eq!(local_part.len(), 16);
+    assert_eq!(domain, "cosmian.com");
+    // The same address is always masked the same way.
+    assert_eq!(masker.apply("john.doe@cosmian.com")?, masked);
+    assert_ne!(masker.apply("jane.doe@cosmian.com")?, masked);
+    assert!(masker.revert(&masked).is_err());
+
+    let masker = EmailMasker::new(&key, LocalPartMasking::Fpe, DomainMasking::Preserve)?;
+    let masked = masker.apply("john.doe+news@cosmian.com")?;
+    assert_ne!(masked, "john.doe+news@cosmian.com");
+    assert_eq!(masked.len(), "john.doe+news@cosmian.com".len());
+    assert_eq!(masked.find(['.', '+']), Some(4));
+    assert!(masked.ends_with("@cosmian.com"));
+    assert_eq!(masker.revert(&masked)?, "john.doe+news@cosmian.com");
+
+    let masker = EmailMasker::new(&key, LocalPartMasking::Fpe, DomainMasking::Category)?;
+    for (email, category) in [
+        ("john.doe@gmail.com", "personal"),
+        ("john.doe@mit.edu", "education"),
+        ("john.doe@ox.ac.uk", "education"),
+        ("john.doe@interieur.gouv.fr", "government"),
+        ("john.doe@cosmian.com", "business"),
+    ] {
+        assert!(masker
+            .apply(email)?
+            .ends_with(&format!("@{category}.invalid")));
+    }
+
+    assert!(masker.apply("john.doe").is_err());
+    assert!(masker.apply("@cosmian.com").is_err());
+    assert!(masker.apply("john.doe@cosmian..com").is_err());
+    // FPE requires a minimum number of characters.
+    assert!(masker.apply("jd@cosmian.com").is_err());
+    assert!(EmailMasker::new(&key[..16], LocalPartMasking::Hash, DomainMasking::Preserve).is_err());
+
+    Ok(())
+}
+
+#[test]
+fn test_phone_masking() -> Result<(), AnoError> {
+    let masker = PhoneMasker::new(1);
+    assert_eq!(masker.apply("+33 1 23 45 67 89")?, "+33 1 XX XX XX XX");
+    assert_eq!(masker.apply("0033 1 23 45 67 89")?, "0033 1 XX XX XX XX");
+    assert_eq!(masker.apply("01.23.45.67.89")?, "01.XX.XX.XX.XX");
+    assert_eq!(
+        PhoneMasker::new(3).apply("+1 (212) 555-0123")?,
+        "+1 (212) XXX-XXXX"
+    );
+    assert_eq!(
+        PhoneMasker::new(2).apply("+352 26 12 34 56")?,
+        "+352 26 XX XX XX"
+    );
+
+    assert!(masker.apply("+33 1 23 45 67 89 ext. 12").is_err());
+    assert!(masker.apply("+33 1").is_err());
+
+    let key = [42; 32];
+    let masker = PhoneMasker::new_consistent(3, &key)?;
+    let masked = masker.apply("+1 (212) 555-0123")?;
+    assert!(masked.starts_with("+1 (212) "));
+    assert!(masked
+        .chars()
+        .zip("+1 (212) 555-0123".chars())
+        .all(|(m, c)| m.is_ascii_digit() == c.is_ascii_digit()));
+    // The same number is always masked the same way.
+    assert_eq!(masker.apply("+1 (212) 555-0123")?, masked);
+    assert_ne!(masker.apply("+1 (212) 555-0124")?, masked);
+
+    assert!(PhoneMasker::new_consistent(3, &key[..16]).is_err());
+
+    Ok(())
+}
+
+#[cfg(feature = "pipeline")]
+mod pipeline {
+    use std::sync::Arc;
+
+    use arrow::{
+        array::{Array, ArrayRef, AsArray, Float64Array, Int64Array, StringArray},
+        datatypes::{DataType, Float64Type, Int64Type},
+        record_batch::RecordBatch,
+    };
+
+    use crate::core::{AnoError, AnonymizationPipeline, HashMethod, Hasher};
+
+    const CONFIG: &str = r#"{
+        "email": { "type": "hash", "method": "SHA2" },
+        "id": { "type": "hash", "method": "SHA2" },
+        "salary": { "type": "number_aggregator", "power_of_ten_exponent": 3, "data_type": "integer" },
+        "score": { "type": "noise", "method": "Gaussian", "mean": 0.0, "std_dev": 1.0, "data_type": "float" },
+        "comment": { "type": "word_masker", "words": ["confidential"] }
+    }"#;
+
+    fn sha2(data: &str) -> Result<String, AnoError> {
+        Hasher::new(HashMethod::SHA2(None)).apply_str(data)
+    }
+
+    #[test]
+    fn test_pipeline_record_batch() -> Result<(), AnoError> {
+        let pipeline = AnonymizationPipeline::from_json(CONFIG)?;
+        let batch = RecordBatch::try_from_iter([
+            (
+                "email",
+                Arc::new(StringArray::from(vec![Some("alice@example.com"), None])) as ArrayRef,
+            ),
+            ("id", Arc::new(Int64Array::from(vec![1, 2])) as ArrayRef),
+            (
+                "salary",
+                Arc::new(Int64Array::from(vec![51_234, 48_999])) as ArrayRef,
+            ),
+            (
+                "score",
+                Arc::new(Float64Array::from(vec![0.5, 100.0])) as ArrayRef,
+            ),
+            (
+                "comment",
+                Arc::new(StringArray::from(vec!["confidential data", "public"])) as ArrayRef,
+            ),
+            (
+                "city",
+                Arc::new(StringArray::from(vec!["Paris", "Lyon"])) as ArrayRef,
+            ),
+        ])
+        .unwrap();
+
+        let anonymized = pipeline.apply_to_record_batch(&batch)?;
+        assert_eq!(anonymized.num_rows(), 2);
+        assert_eq!(anonymized.schema().fields().len(), 6);
+
+        // Null values are kept.
+        let emails = anonymized.column(0).as_string::<i32>();
+        assert_eq!(emails.value(0), sha2("alice@example.com")?);
+        assert!(emails.is_null(1));
+
+        // Hashed integers become strings.
+        assert_eq!(anonymized.column(1).data_type(), &DataType::Utf8);
+        assert_eq!(anonymized.column(1).as_string::<i32>().value(1), sha2("2")?);
+
+        // Numbers keep their type.
+        let salaries = anonymized.column(2).as_primitive::<Int64Type>();
+        assert_eq!(salaries.values(), &[51_000, 49_000]);
+        let scores = anonymized.column(3).as_primitive::<Float64Type>();
+        assert_eq!(scores.len(), 2);
+
+        let comments = anonymized.column(4).as_string::<i32>();
+        assert_eq!(comments.value(0), "XXXX data");
+        assert_eq!(comments.value(1), "public");
+
+        // Columns absent from the configuration are left untouched.
+        assert_eq!(anonymized.column(5), batch.column(5));
+
+        // Configured columns must exist.
+        let batch = batch.project(&[0, 1, 2, 3]).unwrap();
+        assert!(pipeline.apply_to_record_batch(&batch).is_err());
+
+        // Anonymized values must be castable back to the column type.
+        let pipeline = AnonymizationPipeline::from_json(
+            r#"{ "id": { "type": "noise", "method": "Gaussian", "mean": 0.0, "std_dev": 1.0, "data_type": "float" } }"#,
+        )?;
+        let batch = RecordBatch::try_from_iter([(
+            "id",
+            Arc::new(Int64Array::from(vec![1, 2])) as ArrayRef,
+        )])
+        .unwrap();
+        assert!(pipeline.apply_to_record_batch(&batch).is_err());
+
+        // Bucketed numbers become their bucket labels.
+        let pipeline = AnonymizationPipeline::from_json(
+            r#"{ "id": { "type": "number_aggregator", "bucket_edges": [2], "bucket_labels": ["low", "high"], "data_type": "integer" } }"#,
+        )?;
+        let anonymized = pipeline.apply_to_record_batch(&batch)?;
+        assert_eq!(anonymized.column(0).data_type(), &DataType::Utf8);
+        assert_eq!(anonymized.column(0).as_string::<i32>().value(0), "low");
+        assert_eq!(anonymized.column(0).as_string::<i32>().value(1), "high");
+        Ok(())
+    }
+
+    #[test]
+    fn test_pipeline_csv() -> Result<(), AnoError> {
+        let pipeline = AnonymizationPipeline::from_json(
+            r#"{
+                "email": { "type": "hash", "method": "SHA2" },
+                "comment": { "type": "word_masker", "words": ["confidential"] }
+            }"#,
+        )?;
+        let csv = "email,comment,city\n\
+                   alice@example.com,\"confidential, really\",Paris\n\
+                   ,public,Lyon\n";
+        assert_eq!(
+            pipeline.apply_to_csv(csv)?,
+            format!(
+                "email,comment,city\n{},\"XXXX, really\",Paris\n,public,Lyon\n",
+                sha2("alice@example.com")?
+            )
+        );
+
+        // Configured columns must exist.
+        assert!(pipeline
+            .apply_to_csv("email,city\nbob@example.com,Nice\n")
+            .is_err());
+        // Values must be valid for the technique.
+        let pipeline = AnonymizationPipeline::from_json(
+            r#"{ "age": { "type": "number_aggregator", "power_of_ten_exponent": 1, "data_type": "integer" } }"#,
+        )?;
+        assert_eq!(pipeline.apply_to_csv("age\n42\n")?, "age\n40\n");
+        let pipeline = AnonymizationPipeline::from_json(
+            r#"{ "age": { "type": "number_aggregator", "bucket_edges": [18, 65], "data_type": "integer" } }"#,
+        )?;
+        assert_eq!(pipeline.apply_to_csv("age\n42\n")?, "age\n\"[18, 65)\"\n");
+        assert!(pipeline.apply_to_csv("age\nforty\n").is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_pipeline_report() -> Result<(), AnoError> {
+        let config = r#"{
+            "email": { "type": "hash", "method": "SHA2" },
+            "comment": { "type": "word_masker", "words": ["confidential"] }
+        }"#;
+        assert!(AnonymizationPipeline::from_json(config)?.report().is_none());
+
+        let pipeline = AnonymizationPipeline::from_json(config)?.with_report();
+        let csv = "email,comment,city\n\
+                   alice@example.com,confidential data,Paris\n\
+                   ,public,Lyon\n";
+        pipeline.apply_to_csv(csv)?;
+        let batch = RecordBatch::try_from_iter([
+            (
+                "email",
+                Arc::new(StringArray::from(vec![Some("bob@example.com"), None])) as ArrayRef,
+            ),
+            (
+                "comment",
+                Arc::new(StringArray::from(vec!["public", "public"])) as ArrayRef,
+            ),
+        ])
+        .unwrap();
+        pipeline.apply_to_record_batch(&batch)?;
+
+        // Transformations are accumulated across runs, and only the anonymized
+        // values are counted.
+        let report = pipeline.report().unwrap();
+        assert_eq!(report.columns.len(), 2);
+        let emails = &report.columns["email"];
+        assert_eq!(emails.technique, "hash");
+        assert_eq!((emails.values, emails.changed), (2, 2));
+        assert_eq!(
+            emails.input_length,
+            "alice@example.com".len() + "bob@example.com".len()
+        );
+        assert_eq!(
+            emails.output_length,
+            sha2("alice@example.com")?.len() + sha2("bob@example.com")?.len()
+        );
+        let comments = &report.columns["comment"];
+        assert_eq!(comments.technique, "word_masker");
+        assert_eq!((comments.values, comments.changed), (4, 1));
+        assert_eq!(comments.input_length, 17 + 3 * 6);
+        assert_eq!(comments.output_length, 9 + 3 * 6);
+
+        // No value is recorded.
+        let json = report.to_json()?;
+        assert!(json.contains(r#""technique":"word_masker""#));
+        assert!(!json.contains("alice") && !json.contains("confidential"));
+        Ok(())
+    }
+
+    #[test]
+    fn test_pipeline_config() {
+        assert!(AnonymizationPipeline::from_json("not json").is_err());
+        assert!(AnonymizationPipeline::from_json(r#"{ "a": { "type": "unknown" } }"#).is_err());
+        // Invalid parameters are rejected when building the pipeline.
+        assert!(AnonymizationPipeline::from_json(
+            r#"{ "a": { "type": "word_pattern_masker", "pattern": "(", "replace": "X" } }"#
+        )
+        .is_err());
+    }
+}
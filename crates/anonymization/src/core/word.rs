@@ -3,8 +3,105 @@ use std::collections::{HashMap, HashSet};
 use cosmian_crypto_core::CsRng;
 use rand::{RngCore, SeedableRng};
 use regex::Regex;
+use unicode_segmentation::UnicodeSegmentation;
 
 use super::AnoError;
+use crate::ano_error;
+
+/// Language of the texts to anonymize.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Language {
+    English,
+    French,
+    German,
+}
+
+impl TryFrom<&str> for Language {
+    type Error = AnoError;
+
+    fn try_from(value: &str) -> Result<Self, Self::Error> {
+        match value {
+            "English" => Ok(Self::English),
+            "French" => Ok(Self::French),
+            "German" => Ok(Self::German),
+            _ => Err(ano_error!("Unknown language {}", value)),
+        }
+    }
+}
+
+impl Language {
+    /// Returns the most common words of this language. These words are never
+    /// masked since they carry no sensitive information.
+    #[must_use]
+    pub const fn stop_words(self) -> &'static [&'static str] {
+        match self {
+            Self::English => &[
+                "a", "an", "and", "are", "as", "at", "be", "but", "by", "for", "from", "has",
+                "have", "he", "her", "his", "i", "in", "is", "it", "its", "of", "on", "or", "our",
+                "she", "that", "the", "their", "they", "this", "to", "was", "we", "were", "with",
+                "you",
+            ],
+            Self::French => &[
+                "à", "au", "aux", "avec", "c", "ce", "ces", "d", "dans", "de", "des", "du", "elle",
+                "en", "est", "et", "il", "j", "je", "l", "la", "le", "les", "leur", "lui", "m",
+                "ma", "mais", "me", "mes", "n", "ne", "nous", "on", "ou", "par", "pas", "pour",
+                "qu", "que", "qui", "s", "sa", "se", "ses", "son", "sur", "t", "ta", "te", "un",
+                "une", "vous",
+            ],
+            Self::German => &[
+                "auf", "aus", "bei", "das", "dem", "den", "der", "des", "die", "du", "ein", "eine",
+                "einem", "einen", "einer", "er", "es", "für", "ich", "im", "in", "ist", "mit",
+                "nicht", "sie", "sind", "und", "von", "vom", "wir", "zu", "zum", "zur",
+            ],
+        }
+    }
+}
+
+/// Returns `true` if the given character is an apostrophe.
+fn is_apostrophe(c: char) -> bool {
+    c == '\'' || c == '\u{2019}'
+}
+
+/// Replaces the words of the given text for which `replace` returns a
+/// replacement.
+///
+/// Words are delimited following the Unicode word boundaries (UAX #29), which
+/// keeps accented and compound words such as "aujourd'hui" whole. If a word
+/// containing apostrophes has no replacement, its parts are tried instead, so
+/// that elided words ("l'été") and possessives ("John's") can be replaced.
+fn replace_words(data: &str, replace: impl Fn(&str) -> Option<String>) -> String {
+    let mut result = String::with_capacity(data.len());
+    for segment in data.split_word_bounds() {
+        if !segment.chars().any(char::is_alphanumeric) {
+            result.push_str(segment);
+        } else if let Some(replacement) = replace(segment) {
+            result.push_str(&replacement);
+        } else {
+            for part in segment.split_inclusive(is_apostrophe) {
+                let word = part.trim_end_matches(is_apostrophe);
+                match replace(word) {
+                    Some(replacement) if !word.is_empty() => result.push_str(&replacement),
+                    _ => result.push_str(word),
+                }
+                result.push_str(&part[word.len()..]);
+            }
+        }
+    }
+    result
+}
+
+/// Returns the lowercase target words, without the stop words of the given
+/// language.
+fn normalize_targets<'a>(
+    target_words: &'a [&str],
+    language: Option<Language>,
+) -> impl Iterator<Item = String> + 'a {
+    let stop_words = language.map_or(&[][..], Language::stop_words);
+    target_words
+        .iter()
+        .map(|word| word.to_lowercase())
+        .filter(move |word| !stop_words.contains(&word.as_str()))
+}
 
 pub struct WordTokenizer {
     /// A mapping of words to random tokens.
@@ -19,13 +116,28 @@ impl WordTokenizer {
     ///
     /// * `target_words`: words to be replaced by tokens.
     pub fn new(target_words: &[&str]) -> Result<Self, AnoError> {
+        Self::build(target_words, None)
+    }
+
+    /// Creates a new instance of `WordTokenizer` for texts in the given
+    /// language. The stop words of this language are never replaced.
+    ///
+    /// # Arguments
+    ///
+    /// * `target_words`: words to be replaced by tokens.
+    /// * `language`: language of the texts.
+    pub fn new_with_language(target_words: &[&str], language: Language) -> Result<Self, AnoError> {
+        Self::build(target_words, Some(language))
+    }
+
+    fn build(target_words: &[&str], language: Option<Language>) -> Result<Self, AnoError> {
         let mut mapping = HashMap::with_capacity(target_words.len());
         let mut rng = CsRng::from_entropy();
 
-        for word in target_words {
+        for word in normalize_targets(target_words, language) {
             let mut uuid = [0; 16];
             rng.try_fill_bytes(&mut uuid)?;
-            mapping.insert(word.to_lowercase(), hex::encode_upper(uuid));
+            mapping.insert(word, hex::encode_upper(uuid));
         }
         Ok(Self {
             word_token_mapping: mapping,
@@ -43,14 +155,9 @@ impl WordTokenizer {
     /// Texts containing tokens in place of sensitive words.
     #[must_use]
     pub fn apply(&self, data: &str) -> String {
-        let re = Regex::new(r"\b\w+\b").unwrap();
-        let result = re.replace_all(data, |caps: &regex::Captures| {
-            match self.word_token_mapping.get(&caps[0].to_lowercase()) {
-                Some(token) => token.to_string(),
-                None => caps[0].to_string(),
-            }
-        });
-        result.into_owned()
+        replace_words(data, |word| {
+            self.word_token_mapping.get(&word.to_lowercase()).cloned()
+        })
     }
 }
 
@@ -70,7 +177,22 @@ impl WordMasker {
     #[must_use]
     pub fn new(words_to_block: &[&str]) -> Self {
         Self {
-            word_list: words_to_block.iter().map(|s| s.to_lowercase()).collect(),
+            word_list: normalize_targets(words_to_block, None).collect(),
+        }
+    }
+
+    /// Creates a new `WordMasker` instance for texts in the given language.
+    /// The stop words of this language are never masked.
+    ///
+    /// # Arguments
+    ///
+    /// * `words_to_block`: A slice of string references containing the words to
+    ///   be masked in the text.
+    /// * `language`: language of the texts.
+    #[must_use]
+    pub fn new_with_language(words_to_block: &[&str], language: Language) -> Self {
+        Self {
+            word_list: normalize_targets(words_to_block, Some(language)).collect(),
         }
     }
 
@@ -85,16 +207,40 @@ impl WordMasker {
     /// Text without the sensitive words.
     #[must_use]
     pub fn apply(&self, data: &str) -> String {
-        let re = Regex::new(r"\b\w+\b").unwrap();
-        let result = re.replace_all(data, |caps: &regex::Captures| {
-            if self.word_list.contains(&caps[0].to_lowercase()) {
-                MASK.to_string()
-            } else {
-                caps[0].to_string()
+        replace_words(data, |word| {
+            self.word_list
+                .contains(&word.to_lowercase())
+                .then(|| MASK.to_string())
+        })
+    }
+}
+
+/// Returns the names of the capture groups referenced by the given replacement
+/// template: `$name` or `${name}`, where `name` is a group index or name. `$$`
+/// is a literal `$`.
+fn referenced_groups(template: &str) -> Vec<&str> {
+    let mut groups = Vec::new();
+    let mut rest = template;
+    while let Some(position) = rest.find('$') {
+        rest = &rest[position + 1..];
+        if let Some(braced) = rest.strip_prefix('{') {
+            if let Some(end) = braced.find('}') {
+                groups.push(&braced[..end]);
+                rest = &braced[end + 1..];
+            }
+        } else if let Some(literal) = rest.strip_prefix('$') {
+            rest = literal;
+        } else {
+            let end = rest
+                .find(|c: char| !(c == '_' || c.is_ascii_alphanumeric()))
+                .unwrap_or(rest.len());
+            if end > 0 {
+                groups.push(&rest[..end]);
             }
-        });
-        result.into_owned()
+            rest = &rest[end..];
+        }
     }
+    groups
 }
 
 pub struct WordPatternMasker {
@@ -106,13 +252,37 @@ impl WordPatternMasker {
     /// Creates a new instance of `WordPatternMasker` with the provided pattern
     /// regex and replace string.
     ///
+    /// The replace string may reference the capture groups of the pattern by
+    /// index or name, e.g. `${1}` or `${year}`, to keep parts of the matches:
+    /// the pattern `(?<year>\d{4})-\d{2}-\d{2}` with the replace string
+    /// `${year}-XX-XX` only keeps the year of the matched dates. Use `$$` for a
+    /// literal `$`.
+    ///
     /// # Arguments
     ///
     /// * `pattern_regex` - The pattern regex to search for.
     /// * `replace_str` - The string to replace the matched patterns.
+    ///
+    /// # Errors
+    ///
+    /// Fails if the pattern is not a valid regex, or if the replace string
+    /// references a capture group absent from the pattern.
     pub fn new(pattern_regex: &str, replace_str: &str) -> Result<Self, AnoError> {
+        let pattern = Regex::new(pattern_regex)?;
+        for group in referenced_groups(replace_str) {
+            let exists = match group.parse::<usize>() {
+                Ok(index) => index < pattern.captures_len(),
+                Err(_) => pattern.capture_names().flatten().any(|name| name == group),
+            };
+            if !exists {
+                return Err(ano_error!(
+                    "the replace string references the capture group `{group}` absent from the \
+                     pattern `{pattern_regex}`"
+                ));
+            }
+        }
         Ok(Self {
-            pattern: Regex::new(pattern_regex)?,
+            pattern,
             replacer: replace_str.to_string(),
         })
     }
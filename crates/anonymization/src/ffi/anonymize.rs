@@ -0,0 +1,49 @@
+use cosmian_ffi_utils::{ffi_guard, ffi_read_string, ffi_unwrap, ffi_write_bytes, ErrorCode};
+
+use crate::core::AnonymizationConfig;
+
+#[no_mangle]
+/// Anonymizes the given input using the technique described by the given JSON
+/// configuration.
+///
+/// This single entry point covers all the anonymization techniques, see
+/// [`AnonymizationConfig`] for the supported configurations. Numbers are passed
+/// and returned as their string representations.
+///
+/// # Safety
+///
+/// This function is marked as `unsafe` due to the usage of raw pointers, which
+/// need to be properly allocated and dereferenced by the caller.
+///
+/// # Arguments
+///
+/// * `output_ptr` - a pointer to the buffer where the anonymized data will be
+///   written.
+/// * `output_len` - a pointer to the size of the `output_ptr` buffer. After the
+///   function call, it is updated with the actual size of the anonymized data.
+/// * `config_ptr` - a pointer to a C string holding the JSON configuration.
+/// * `input_ptr` - a pointer to a C string holding the data to anonymize.
+pub unsafe extern "C" fn h_anonymize(
+    output_ptr: *mut u8,
+    output_len: *mut i32,
+    config_ptr: *const i8,
+    input_ptr: *const i8,
+) -> i32 {
+    ffi_guard!({
+        let config_str = ffi_read_string!("config", config_ptr);
+        let input_str = ffi_read_string!("input", input_ptr);
+
+        let config = ffi_unwrap!(
+            serde_json::from_str::<AnonymizationConfig>(&config_str),
+            "error parsing anonymization configuration",
+            ErrorCode::InvalidArgument("config".to_string())
+        );
+        let output = ffi_unwrap!(
+            config.apply(&input_str),
+            "error anonymizing input",
+            ErrorCode::InvalidArgument("input".to_string())
+        );
+
+        ffi_write_bytes!("output_ptr", output.as_bytes(), output_ptr, output_len);
+    })
+}
@@ -0,0 +1,168 @@
+use cosmian_ffi_utils::{
+    ffi_guard, ffi_read_bytes, ffi_read_string, ffi_unwrap, ffi_write_bytes, ErrorCode,
+};
+
+use crate::core::{DomainMasking, EmailMasker, LocalPartMasking, PhoneMasker};
+
+#[no_mangle]
+/// Masks the local part of the given email address, keeping its domain or the
+/// category of its domain.
+///
+/// # Safety
+///
+/// This function is marked as `unsafe` due to the usage of raw pointers, which
+/// need to be properly allocated and dereferenced by the caller.
+///
+/// # Arguments
+///
+/// * `output_ptr` - a pointer to the buffer where the masked email address
+///   will be written.
+/// * `output_len` - a pointer to the size of the `output_ptr` buffer. After the
+///   function call, it is updated with the actual size of the masked email
+///   address.
+/// * `email_ptr` - a pointer to a C string holding the email address to mask.
+/// * `key_ptr` - a pointer to the 32-byte key.
+/// * `key_len` - the length of the key.
+/// * `local_part_masking_ptr` - a pointer to a C string holding the masking of
+///   the local part: `Hash` or `FPE`.
+/// * `domain_masking_ptr` - a pointer to a C string holding the masking of the
+///   domain: `Preserve` or `Category`.
+pub unsafe extern "C" fn h_mask_email(
+    output_ptr: *mut u8,
+    output_len: *mut i32,
+    email_ptr: *const i8,
+    key_ptr: *const i8,
+    key_len: i32,
+    local_part_masking_ptr: *const i8,
+    domain_masking_ptr: *const i8,
+) -> i32 {
+    ffi_guard!({
+        let email_str = ffi_read_string!("email", email_ptr);
+        let key_bytes = ffi_read_bytes!("key", key_ptr, key_len);
+        let local_part_masking_str = ffi_read_string!("local_part_masking", local_part_masking_ptr);
+        let domain_masking_str = ffi_read_string!("domain_masking", domain_masking_ptr);
+
+        let local_part_masking = ffi_unwrap!(
+            LocalPartMasking::try_from(local_part_masking_str.as_str()),
+            "error parsing local part masking",
+            ErrorCode::InvalidArgument(local_part_masking_str)
+        );
+        let domain_masking = ffi_unwrap!(
+            DomainMasking::try_from(domain_masking_str.as_str()),
+            "error parsing domain masking",
+            ErrorCode::InvalidArgument(domain_masking_str)
+        );
+        let email_masker = ffi_unwrap!(
+            EmailMasker::new(key_bytes, local_part_masking, domain_masking),
+            "error initializing EmailMasker",
+            ErrorCode::InvalidArgument("EmailMasker".to_string())
+        );
+        let masked = ffi_unwrap!(
+            email_masker.apply(&email_str),
+            "error masking email address",
+            ErrorCode::InvalidArgument("email".to_string())
+        );
+
+        ffi_write_bytes!("output_ptr", masked.as_bytes(), output_ptr, output_len);
+    })
+}
+
+#[no_mangle]
+/// Restores the local part of the given email address masked using FPE.
+///
+/// # Safety
+///
+/// This function is marked as `unsafe` due to the usage of raw pointers, which
+/// need to be properly allocated and dereferenced by the caller.
+///
+/// # Arguments
+///
+/// * `output_ptr` - a pointer to the buffer where the email address will be
+///   written.
+/// * `output_len` - a pointer to the size of the `output_ptr` buffer. After the
+///   function call, it is updated with the actual size of the email address.
+/// * `masked_email_ptr` - a pointer to a C string holding the masked email
+///   address.
+/// * `key_ptr` - a pointer to the 32-byte key used to mask the email address.
+/// * `key_len` - the length of the key.
+pub unsafe extern "C" fn h_unmask_email(
+    output_ptr: *mut u8,
+    output_len: *mut i32,
+    masked_email_ptr: *const i8,
+    key_ptr: *const i8,
+    key_len: i32,
+) -> i32 {
+    ffi_guard!({
+        let masked_email_str = ffi_read_string!("masked_email", masked_email_ptr);
+        let key_bytes = ffi_read_bytes!("key", key_ptr, key_len);
+
+        let email_masker = ffi_unwrap!(
+            EmailMasker::new(key_bytes, LocalPartMasking::Fpe, DomainMasking::Preserve),
+            "error initializing EmailMasker",
+            ErrorCode::InvalidArgument("EmailMasker".to_string())
+        );
+        let email = ffi_unwrap!(
+            email_masker.revert(&masked_email_str),
+            "error unmasking email address",
+            ErrorCode::InvalidArgument("masked_email".to_string())
+        );
+
+        ffi_write_bytes!("output_ptr", email.as_bytes(), output_ptr, output_len);
+    })
+}
+
+#[no_mangle]
+/// Masks the given phone number, keeping its country code and the given number
+/// of area code digits.
+///
+/// If a key is given, the masked digits are derived from the phone number
+/// using the key, otherwise they are replaced with `X`.
+///
+/// # Safety
+///
+/// This function is marked as `unsafe` due to the usage of raw pointers, which
+/// need to be properly allocated and dereferenced by the caller.
+///
+/// # Arguments
+///
+/// * `output_ptr` - a pointer to the buffer where the masked phone number will
+///   be written.
+/// * `output_len` - a pointer to the size of the `output_ptr` buffer. After the
+///   function call, it is updated with the actual size of the masked phone
+///   number.
+/// * `phone_number_ptr` - a pointer to a C string holding the phone number to
+///   mask.
+/// * `area_code_digits` - the number of digits kept after the country code or
+///   the trunk prefix.
+/// * `key_ptr` - a pointer to the key used to derive the masked digits (at
+///   least 32 bytes), or null to replace them with `X`.
+/// * `key_len` - the length of the key.
+pub unsafe extern "C" fn h_mask_phone_number(
+    output_ptr: *mut u8,
+    output_len: *mut i32,
+    phone_number_ptr: *const i8,
+    area_code_digits: u32,
+    key_ptr: *const i8,
+    key_len: i32,
+) -> i32 {
+    ffi_guard!({
+        let phone_number_str = ffi_read_string!("phone_number", phone_number_ptr);
+        let phone_masker = if key_ptr.is_null() {
+            PhoneMasker::new(area_code_digits as usize)
+        } else {
+            let key_bytes = ffi_read_bytes!("key", key_ptr, key_len);
+            ffi_unwrap!(
+                PhoneMasker::new_consistent(area_code_digits as usize, key_bytes),
+                "error initializing PhoneMasker",
+                ErrorCode::InvalidArgument("PhoneMasker".to_string())
+            )
+        };
+        let masked = ffi_unwrap!(
+            phone_masker.apply(&phone_number_str),
+            "error masking phone number",
+            ErrorCode::InvalidArgument("phone_number".to_string())
+        );
+
+        ffi_write_bytes!("output_ptr", masked.as_bytes(), output_ptr, output_len);
+    })
+}
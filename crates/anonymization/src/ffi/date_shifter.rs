@@ -0,0 +1,71 @@
+use cosmian_ffi_utils::{
+    ffi_guard, ffi_read_bytes, ffi_read_string, ffi_unwrap, ffi_write_bytes, ErrorCode,
+};
+
+use crate::core::{DateShifter, TimeUnit};
+
+#[no_mangle]
+/// Shifts the given date by the secret offset associated to the given entity.
+///
+/// All the dates of a same entity are shifted by the same offset, derived from
+/// the entity identifier using the given key.
+///
+/// # Safety
+///
+/// This function is marked as `unsafe` due to the usage of raw pointers, which
+/// need to be properly allocated and dereferenced by the caller.
+///
+/// # Arguments
+///
+/// * `output_ptr` - a pointer to the buffer where the shifted date will be
+///   written.
+/// * `output_len` - a pointer to the size of the `output_ptr` buffer. After the
+///   function call, it is updated with the actual size of the shifted date.
+/// * `key_ptr` - a pointer to the key used to derive the offsets (at least 32
+///   bytes).
+/// * `key_len` - the length of the key.
+/// * `max_shift` - the maximum absolute offset, expressed in `time_unit`.
+/// * `time_unit_ptr` - a pointer to a C string naming the time unit of the
+///   offsets (`Second`, `Minute`, `Hour` or `Day`).
+/// * `entity_id_ptr` - a pointer to a C string identifying the entity.
+/// * `date_ptr` - a pointer to a C string holding an RFC3339 date.
+pub unsafe extern "C" fn h_shift_date(
+    output_ptr: *mut u8,
+    output_len: *mut i32,
+    key_ptr: *const i8,
+    key_len: i32,
+    max_shift: u32,
+    time_unit_ptr: *const i8,
+    entity_id_ptr: *const i8,
+    date_ptr: *const i8,
+) -> i32 {
+    ffi_guard!({
+        let key_bytes = ffi_read_bytes!("key", key_ptr, key_len);
+        let time_unit_str = ffi_read_string!("time_unit", time_unit_ptr);
+        let entity_id_str = ffi_read_string!("entity_id", entity_id_ptr);
+        let date_str = ffi_read_string!("date", date_ptr);
+
+        let time_unit = ffi_unwrap!(
+            TimeUnit::try_from(time_unit_str.as_str()),
+            "time unit not supported",
+            ErrorCode::InvalidArgument("time_unit".to_string())
+        );
+        let date_shifter = ffi_unwrap!(
+            DateShifter::new(key_bytes, u64::from(max_shift), time_unit),
+            "error initializing DateShifter",
+            ErrorCode::InvalidArgument("DateShifter".to_string())
+        );
+        let shifted_date = ffi_unwrap!(
+            date_shifter.apply_on_date(&entity_id_str, &date_str),
+            "error shifting date",
+            ErrorCode::InvalidArgument("date".to_string())
+        );
+
+        ffi_write_bytes!(
+            "output_ptr",
+            shifted_date.as_bytes(),
+            output_ptr,
+            output_len
+        );
+    })
+}
@@ -0,0 +1,140 @@
+use cosmian_ffi_utils::{
+    ffi_guard, ffi_read_bytes, ffi_read_string, ffi_unwrap, ffi_write_bytes, ErrorCode,
+};
+
+use crate::core::GeoMasker;
+
+#[no_mangle]
+/// Moves the given coordinates to a random point within the given radius.
+///
+/// If a key and an entity identifier are given, all the coordinates of this
+/// entity are moved by the same secret displacement, derived from the entity
+/// identifier using the key.
+///
+/// # Safety
+///
+/// This function is marked as `unsafe` due to the usage of raw pointers, which
+/// need to be properly allocated and dereferenced by the caller.
+///
+/// # Arguments
+///
+/// * `output_latitude` - a pointer to the variable where the blurred latitude
+///   will be written.
+/// * `output_longitude` - a pointer to the variable where the blurred
+///   longitude will be written.
+/// * `radius` - the maximum displacement, in meters.
+/// * `key_ptr` - a pointer to the key used to derive the displacements (at
+///   least 32 bytes), or null to draw a random displacement.
+/// * `key_len` - the length of the key.
+/// * `entity_id_ptr` - a pointer to a C string identifying the entity, ignored
+///   if no key is given.
+/// * `latitude` - the latitude to blur, in degrees.
+/// * `longitude` - the longitude to blur, in degrees.
+pub unsafe extern "C" fn h_blur_coordinates(
+    output_latitude: *mut f64,
+    output_longitude: *mut f64,
+    radius: f64,
+    key_ptr: *const i8,
+    key_len: i32,
+    entity_id_ptr: *const i8,
+    latitude: f64,
+    longitude: f64,
+) -> i32 {
+    ffi_guard!({
+        let blurred = if key_ptr.is_null() {
+            let geo_masker = ffi_unwrap!(
+                GeoMasker::new(radius),
+                "error initializing GeoMasker",
+                ErrorCode::InvalidArgument("GeoMasker".to_string())
+            );
+            geo_masker.apply_on_coordinates(latitude, longitude)
+        } else {
+            let key_bytes = ffi_read_bytes!("key", key_ptr, key_len);
+            let entity_id_str = ffi_read_string!("entity_id", entity_id_ptr);
+            let geo_masker = ffi_unwrap!(
+                GeoMasker::new_consistent(radius, key_bytes),
+                "error initializing GeoMasker",
+                ErrorCode::InvalidArgument("GeoMasker".to_string())
+            );
+            geo_masker.apply_on_entity_coordinates(&entity_id_str, latitude, longitude)
+        };
+        let (blurred_latitude, blurred_longitude) = ffi_unwrap!(
+            blurred,
+            "error blurring coordinates",
+            ErrorCode::InvalidArgument("coordinates".to_string())
+        );
+
+        *output_latitude = blurred_latitude;
+        *output_longitude = blurred_longitude;
+        0
+    })
+}
+
+#[no_mangle]
+/// Encodes the given coordinates into a geohash of the given precision.
+///
+/// # Safety
+///
+/// This function is marked as `unsafe` due to the usage of raw pointers, which
+/// need to be properly allocated and dereferenced by the caller.
+///
+/// # Arguments
+///
+/// * `output_ptr` - a pointer to the buffer where the geohash will be written.
+/// * `output_len` - a pointer to the size of the `output_ptr` buffer. After the
+///   function call, it is updated with the actual size of the geohash.
+/// * `latitude` - the latitude to encode, in degrees.
+/// * `longitude` - the longitude to encode, in degrees.
+/// * `precision` - the number of characters of the geohash, from 1 to 12.
+pub unsafe extern "C" fn h_geohash(
+    output_ptr: *mut u8,
+    output_len: *mut i32,
+    latitude: f64,
+    longitude: f64,
+    precision: u32,
+) -> i32 {
+    ffi_guard!({
+        let geohash = ffi_unwrap!(
+            GeoMasker::to_geohash(latitude, longitude, precision as usize),
+            "error encoding geohash",
+            ErrorCode::InvalidArgument("coordinates".to_string())
+        );
+
+        ffi_write_bytes!("output_ptr", geohash.as_bytes(), output_ptr, output_len);
+    })
+}
+
+#[no_mangle]
+/// Truncates the given geohash to the given precision.
+///
+/// # Safety
+///
+/// This function is marked as `unsafe` due to the usage of raw pointers, which
+/// need to be properly allocated and dereferenced by the caller.
+///
+/// # Arguments
+///
+/// * `output_ptr` - a pointer to the buffer where the truncated geohash will be
+///   written.
+/// * `output_len` - a pointer to the size of the `output_ptr` buffer. After the
+///   function call, it is updated with the actual size of the truncated
+///   geohash.
+/// * `geohash_ptr` - a pointer to a C string holding the geohash to truncate.
+/// * `precision` - the number of characters to keep, from 1 to 12.
+pub unsafe extern "C" fn h_truncate_geohash(
+    output_ptr: *mut u8,
+    output_len: *mut i32,
+    geohash_ptr: *const i8,
+    precision: u32,
+) -> i32 {
+    ffi_guard!({
+        let geohash_str = ffi_read_string!("geohash", geohash_ptr);
+        let truncated = ffi_unwrap!(
+            GeoMasker::truncate_geohash(&geohash_str, precision as usize),
+            "error truncating geohash",
+            ErrorCode::InvalidArgument("geohash".to_string())
+        );
+
+        ffi_write_bytes!("output_ptr", truncated.as_bytes(), output_ptr, output_len);
+    })
+}
@@ -1,2 +1,17 @@
+mod anonymize;
+pub use anonymize::h_anonymize;
+
+mod contact;
+pub use contact::{h_mask_email, h_mask_phone_number, h_unmask_email};
+
+mod date_shifter;
+pub use date_shifter::h_shift_date;
+
+mod geo;
+pub use geo::{h_blur_coordinates, h_geohash, h_truncate_geohash};
+
+mod pipeline;
+pub use pipeline::{h_anonymize_csv, h_anonymize_csv_with_report, h_anonymize_record_batch};
+
 #[cfg(test)]
 mod tests;
@@ -0,0 +1,201 @@
+use arrow::{
+    array::{Array, StructArray},
+    datatypes::DataType,
+    ffi::{from_ffi, to_ffi, FFI_ArrowArray, FFI_ArrowSchema},
+    record_batch::RecordBatch,
+};
+use cosmian_ffi_utils::{
+    ffi_bail, ffi_guard, ffi_not_null, ffi_read_string, ffi_unwrap, ffi_write_bytes, ErrorCode,
+};
+
+use crate::core::AnonymizationPipeline;
+
+#[no_mangle]
+/// Anonymizes the columns of the given CSV chunk using the techniques
+/// described by the given JSON pipeline configuration.
+///
+/// See [`AnonymizationPipeline`] for the format of the configuration. The
+/// chunk must start with its header line.
+///
+/// # Safety
+///
+/// This function is marked as `unsafe` due to the usage of raw pointers, which
+/// need to be properly allocated and dereferenced by the caller.
+///
+/// # Arguments
+///
+/// * `output_ptr` - a pointer to the buffer where the anonymized CSV will be
+///   written.
+/// * `output_len` - a pointer to the size of the `output_ptr` buffer. After the
+///   function call, it is updated with the actual size of the anonymized CSV.
+/// * `config_ptr` - a pointer to a C string holding the JSON configuration.
+/// * `input_ptr` - a pointer to a C string holding the CSV chunk to anonymize.
+pub unsafe extern "C" fn h_anonymize_csv(
+    output_ptr: *mut u8,
+    output_len: *mut i32,
+    config_ptr: *const i8,
+    input_ptr: *const i8,
+) -> i32 {
+    ffi_guard!({
+        let config_str = ffi_read_string!("config", config_ptr);
+        let input_str = ffi_read_string!("input", input_ptr);
+
+        let pipeline = ffi_unwrap!(
+            AnonymizationPipeline::from_json(&config_str),
+            "error parsing anonymization pipeline configuration",
+            ErrorCode::InvalidArgument("config".to_string())
+        );
+        let output = ffi_unwrap!(
+            pipeline.apply_to_csv(&input_str),
+            "error anonymizing CSV",
+            ErrorCode::InvalidArgument("input".to_string())
+        );
+
+        ffi_write_bytes!("output_ptr", output.as_bytes(), output_ptr, output_len);
+    })
+}
+
+#[no_mangle]
+/// Anonymizes the columns of the given CSV chunk like
+/// [`h_anonymize_csv`](h_anonymize_csv), and writes the report of the
+/// transformations applied.
+///
+/// The report is a JSON object giving, for each anonymized column, the
+/// technique applied, the number of values anonymized and modified, and their
+/// total length before and after anonymization. It holds no value.
+///
+/// # Safety
+///
+/// This function is marked as `unsafe` due to the usage of raw pointers, which
+/// need to be properly allocated and dereferenced by the caller.
+///
+/// # Arguments
+///
+/// * `output_ptr` - a pointer to the buffer where the anonymized CSV will be
+///   written.
+/// * `output_len` - a pointer to the size of the `output_ptr` buffer. After the
+///   function call, it is updated with the actual size of the anonymized CSV.
+/// * `report_ptr` - a pointer to the buffer where the JSON report will be
+///   written.
+/// * `report_len` - a pointer to the size of the `report_ptr` buffer. After the
+///   function call, it is updated with the actual size of the report.
+/// * `config_ptr` - a pointer to a C string holding the JSON configuration.
+/// * `input_ptr` - a pointer to a C string holding the CSV chunk to anonymize.
+pub unsafe extern "C" fn h_anonymize_csv_with_report(
+    output_ptr: *mut u8,
+    output_len: *mut i32,
+    report_ptr: *mut u8,
+    report_len: *mut i32,
+    config_ptr: *const i8,
+    input_ptr: *const i8,
+) -> i32 {
+    ffi_guard!({
+        let config_str = ffi_read_string!("config", config_ptr);
+        let input_str = ffi_read_string!("input", input_ptr);
+
+        let pipeline = ffi_unwrap!(
+            AnonymizationPipeline::from_json(&config_str),
+            "error parsing anonymization pipeline configuration",
+            ErrorCode::InvalidArgument("config".to_string())
+        )
+        .with_report();
+        let output = ffi_unwrap!(
+            pipeline.apply_to_csv(&input_str),
+            "error anonymizing CSV",
+            ErrorCode::InvalidArgument("input".to_string())
+        );
+        let report = ffi_unwrap!(
+            pipeline.report().unwrap_or_default().to_json(),
+            "error serializing anonymization report",
+            ErrorCode::Serialization
+        );
+
+        ffi_write_bytes!(
+            "output_ptr",
+            output.as_bytes(),
+            output_ptr,
+            output_len,
+            "report_ptr",
+            report.as_bytes(),
+            report_ptr,
+            report_len
+        );
+    })
+}
+
+#[no_mangle]
+/// Anonymizes the columns of the given Arrow record batch using the
+/// techniques described by the given JSON pipeline configuration.
+///
+/// The record batches are exchanged through the [Arrow C data
+/// interface](https://arrow.apache.org/docs/format/CDataInterface.html) as
+/// struct arrays, e.g. using `RecordBatch._export_to_c()` and
+/// `RecordBatch._import_from_c()` with pyarrow. See [`AnonymizationPipeline`]
+/// for the format of the configuration.
+///
+/// # Safety
+///
+/// This function is marked as `unsafe` due to the usage of raw pointers, which
+/// need to be properly allocated and dereferenced by the caller.
+///
+/// The input array is moved: it is released by this function, even on
+/// failure. On success, the caller owns the output array and schema and must
+/// release them.
+///
+/// # Arguments
+///
+/// * `output_array_ptr` - a pointer to an uninitialized `ArrowArray` where the
+///   anonymized record batch will be written.
+/// * `output_schema_ptr` - a pointer to an uninitialized `ArrowSchema` where
+///   the schema of the anonymized record batch will be written.
+/// * `config_ptr` - a pointer to a C string holding the JSON configuration.
+/// * `input_array_ptr` - a pointer to the `ArrowArray` of the record batch to
+///   anonymize.
+/// * `input_schema_ptr` - a pointer to the `ArrowSchema` of the record batch
+///   to anonymize.
+pub unsafe extern "C" fn h_anonymize_record_batch(
+    output_array_ptr: *mut FFI_ArrowArray,
+    output_schema_ptr: *mut FFI_ArrowSchema,
+    config_ptr: *const i8,
+    input_array_ptr: *mut FFI_ArrowArray,
+    input_schema_ptr: *const FFI_ArrowSchema,
+) -> i32 {
+    ffi_guard!({
+        ffi_not_null!("output_array_ptr", output_array_ptr);
+        ffi_not_null!("output_schema_ptr", output_schema_ptr);
+        ffi_not_null!("input_array_ptr", input_array_ptr);
+        ffi_not_null!("input_schema_ptr", input_schema_ptr);
+        // Take ownership of the input array first so that it is released on
+        // failure.
+        let input_array = FFI_ArrowArray::from_raw(input_array_ptr);
+        let config_str = ffi_read_string!("config", config_ptr);
+
+        let pipeline = ffi_unwrap!(
+            AnonymizationPipeline::from_json(&config_str),
+            "error parsing anonymization pipeline configuration",
+            ErrorCode::InvalidArgument("config".to_string())
+        );
+        let input_data = ffi_unwrap!(
+            from_ffi(input_array, &*input_schema_ptr),
+            "error importing Arrow record batch",
+            ErrorCode::Serialization
+        );
+        if !matches!(input_data.data_type(), DataType::Struct(_)) {
+            ffi_bail!("the Arrow array of a record batch should be a struct array");
+        }
+        let input_batch = RecordBatch::from(StructArray::from(input_data));
+        let output_batch = ffi_unwrap!(
+            pipeline.apply_to_record_batch(&input_batch),
+            "error anonymizing record batch",
+            ErrorCode::InvalidArgument("input".to_string())
+        );
+        let (output_array, output_schema) = ffi_unwrap!(
+            to_ffi(&StructArray::from(output_batch).to_data()),
+            "error exporting Arrow record batch",
+            ErrorCode::Serialization
+        );
+        std::ptr::write(output_array_ptr, output_array);
+        std::ptr::write(output_schema_ptr, output_schema);
+        0
+    })
+}
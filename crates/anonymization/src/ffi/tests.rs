@@ -1,2 +1,448 @@
+use std::{ffi::CString, sync::Arc};
+
+use arrow::{
+    array::{Array, ArrayRef, AsArray, Int64Array, StringArray, StructArray},
+    datatypes::Int64Type,
+    ffi::{from_ffi, to_ffi, FFI_ArrowArray, FFI_ArrowSchema},
+    record_batch::RecordBatch,
+};
+use base64::{engine::general_purpose, Engine as _};
+use chrono::DateTime;
+use cosmian_ffi_utils::error::get_last_error;
+
+use super::{
+    h_anonymize, h_anonymize_csv, h_anonymize_csv_with_report, h_anonymize_record_batch,
+    h_blur_coordinates, h_geohash, h_mask_email, h_mask_phone_number, h_shift_date,
+    h_truncate_geohash, h_unmask_email,
+};
+
+unsafe fn shift_date(key: &[u8], entity_id: &str, date: &str) -> Result<String, String> {
+    let time_unit = CString::new("Day").unwrap();
+    let entity_id = CString::new(entity_id).unwrap();
+    let date = CString::new(date).unwrap();
+
+    let mut output = vec![0_u8; 64];
+    let mut output_len = output.len() as i32;
+    let ret = h_shift_date(
+        output.as_mut_ptr(),
+        &mut output_len,
+        key.as_ptr().cast(),
+        key.len() as i32,
+        30,
+        time_unit.as_ptr().cast(),
+        entity_id.as_ptr().cast(),
+        date.as_ptr().cast(),
+    );
+    if ret == 0 {
+        output.truncate(output_len as usize);
+        Ok(String::from_utf8(output).unwrap())
+    } else {
+        Err(get_last_error())
+    }
+}
+
 #[test]
-fn ffi_anonymization() {}
+fn test_ffi_date_shifting() {
+    let key = [42_u8; 32];
+    unsafe {
+        let admission = shift_date(&key, "patient-1", "2023-04-07T12:34:56Z").unwrap();
+        let discharge = shift_date(&key, "patient-1", "2023-04-10T12:34:56Z").unwrap();
+        let interval = DateTime::parse_from_rfc3339(&discharge).unwrap()
+            - DateTime::parse_from_rfc3339(&admission).unwrap();
+        assert_eq!(interval.num_days(), 3);
+
+        assert!(shift_date(&key[..16], "patient-1", "2023-04-07T12:34:56Z").is_err());
+        assert!(shift_date(&key, "patient-1", "AAAA").is_err());
+    }
+}
+
+unsafe fn blur_coordinates(
+    key: Option<&[u8]>,
+    entity_id: &str,
+    latitude: f64,
+    longitude: f64,
+) -> Result<(f64, f64), String> {
+    let entity_id = CString::new(entity_id).unwrap();
+    let (key_ptr, key_len) = key.map_or((std::ptr::null(), 0), |key| {
+        (key.as_ptr().cast(), key.len() as i32)
+    });
+
+    let mut output_latitude = 0.0;
+    let mut output_longitude = 0.0;
+    let ret = h_blur_coordinates(
+        &mut output_latitude,
+        &mut output_longitude,
+        1_000.0,
+        key_ptr,
+        key_len,
+        entity_id.as_ptr().cast(),
+        latitude,
+        longitude,
+    );
+    if ret == 0 {
+        Ok((output_latitude, output_longitude))
+    } else {
+        Err(get_last_error())
+    }
+}
+
+#[test]
+fn test_ffi_geo_masking() {
+    let key = [42_u8; 32];
+    unsafe {
+        let blurred = blur_coordinates(None, "", 48.8566, 2.3522).unwrap();
+        assert!((blurred.0 - 48.8566).abs() < 0.01 && (blurred.1 - 2.3522).abs() < 0.02);
+
+        let consistent = blur_coordinates(Some(&key), "patient-1", 48.8566, 2.3522).unwrap();
+        assert_eq!(
+            blur_coordinates(Some(&key), "patient-1", 48.8566, 2.3522).unwrap(),
+            consistent
+        );
+
+        assert!(blur_coordinates(Some(&key[..16]), "patient-1", 48.8566, 2.3522).is_err());
+        assert!(blur_coordinates(None, "", 100.0, 2.3522).is_err());
+
+        let mut output = vec![0_u8; 16];
+        let mut output_len = output.len() as i32;
+        assert_eq!(
+            h_geohash(output.as_mut_ptr(), &mut output_len, 48.8566, 2.3522, 7),
+            0
+        );
+        assert_eq!(&output[..output_len as usize], b"u09tvw0");
+
+        let geohash = CString::new("u09tvw0").unwrap();
+        let mut output_len = output.len() as i32;
+        assert_eq!(
+            h_truncate_geohash(
+                output.as_mut_ptr(),
+                &mut output_len,
+                geohash.as_ptr().cast(),
+                4
+            ),
+            0
+        );
+        assert_eq!(&output[..output_len as usize], b"u09t");
+    }
+}
+
+fn read_output(ret: i32, mut output: Vec<u8>, output_len: i32) -> Result<String, String> {
+    if ret == 0 {
+        output.truncate(output_len as usize);
+        Ok(String::from_utf8(output).unwrap())
+    } else {
+        Err(get_last_error())
+    }
+}
+
+#[test]
+fn test_ffi_contact_masking() {
+    let key = [42_u8; 32];
+    unsafe {
+        let email = CString::new("john.doe@gmail.com").unwrap();
+        let local_part_masking = CString::new("FPE").unwrap();
+        let domain_masking = CString::new("Category").unwrap();
+        let mut output = vec![0_u8; 64];
+        let mut output_len = output.len() as i32;
+        let ret = h_mask_email(
+            output.as_mut_ptr(),
+            &mut output_len,
+            email.as_ptr().cast(),
+            key.as_ptr().cast(),
+            key.len() as i32,
+            local_part_masking.as_ptr().cast(),
+            domain_masking.as_ptr().cast(),
+        );
+        let masked = read_output(ret, output, output_len).unwrap();
+        assert!(masked.ends_with("@personal.invalid"));
+
+        let masked = CString::new(masked).unwrap();
+        let mut output = vec![0_u8; 64];
+        let mut output_len = output.len() as i32;
+        let ret = h_unmask_email(
+            output.as_mut_ptr(),
+            &mut output_len,
+            masked.as_ptr().cast(),
+            key.as_ptr().cast(),
+            key.len() as i32,
+        );
+        assert_eq!(
+            read_output(ret, output, output_len).unwrap(),
+            "john.doe@personal.invalid"
+        );
+
+        let unknown_masking = CString::new("Encrypt").unwrap();
+        let mut output = vec![0_u8; 64];
+        let mut output_len = output.len() as i32;
+        let ret = h_mask_email(
+            output.as_mut_ptr(),
+            &mut output_len,
+            email.as_ptr().cast(),
+            key.as_ptr().cast(),
+            key.len() as i32,
+            unknown_masking.as_ptr().cast(),
+            domain_masking.as_ptr().cast(),
+        );
+        assert!(read_output(ret, output, output_len).is_err());
+
+        let phone_number = CString::new("+33 1 23 45 67 89").unwrap();
+        let mut output = vec![0_u8; 64];
+        let mut output_len = output.len() as i32;
+        let ret = h_mask_phone_number(
+            output.as_mut_ptr(),
+            &mut output_len,
+            phone_number.as_ptr().cast(),
+            1,
+            std::ptr::null(),
+            0,
+        );
+        assert_eq!(
+            read_output(ret, output, output_len).unwrap(),
+            "+33 1 XX XX XX XX"
+        );
+
+        let mut output = vec![0_u8; 64];
+        let mut output_len = output.len() as i32;
+        let ret = h_mask_phone_number(
+            output.as_mut_ptr(),
+            &mut output_len,
+            phone_number.as_ptr().cast(),
+            1,
+            key.as_ptr().cast(),
+            key.len() as i32,
+        );
+        let masked = read_output(ret, output, output_len).unwrap();
+        assert!(masked.starts_with("+33 1 ") && !masked.contains('X'));
+    }
+}
+
+unsafe fn anonymize(config: &str, input: &str) -> Result<String, String> {
+    let config = CString::new(config).unwrap();
+    let input = CString::new(input).unwrap();
+
+    let mut output = vec![0_u8; 1024];
+    let mut output_len = output.len() as i32;
+    let ret = h_anonymize(
+        output.as_mut_ptr(),
+        &mut output_len,
+        config.as_ptr().cast(),
+        input.as_ptr().cast(),
+    );
+    if ret == 0 {
+        output.truncate(output_len as usize);
+        Ok(String::from_utf8(output).unwrap())
+    } else {
+        Err(get_last_error())
+    }
+}
+
+#[test]
+fn test_ffi_anonymize() {
+    unsafe {
+        assert_eq!(
+            anonymize(r#"{ "type": "hash", "method": "SHA2" }"#, "test sha2").unwrap(),
+            "Px0txVYqBePXWF5K4xFn0Pa2mhnYA/jfsLtpIF70vJ8="
+        );
+        assert_eq!(
+            anonymize(
+                r#"{ "type": "word_masker", "words": ["quick", "brown"] }"#,
+                "The Quick! brown fox"
+            )
+            .unwrap(),
+            "The XXXX! XXXX fox"
+        );
+        assert_eq!(
+            anonymize(
+                r#"{ "type": "word_masker", "words": ["été", "de"], "language": "French" }"#,
+                "L'été de Jean"
+            )
+            .unwrap(),
+            "L'XXXX de Jean"
+        );
+        assert_eq!(
+            anonymize(
+                r#"{ "type": "number_aggregator", "power_of_ten_exponent": 2, "data_type": "integer" }"#,
+                "1234"
+            )
+            .unwrap(),
+            "1200"
+        );
+        assert_eq!(
+            anonymize(
+                r#"{ "type": "number_aggregator", "bucket_edges": [18, 26, 65], "bucket_labels": ["0-17", "18-25", "26-64", "65+"], "data_type": "integer" }"#,
+                "42"
+            )
+            .unwrap(),
+            "26-64"
+        );
+        let date = anonymize(
+            r#"{ "type": "date_aggregator", "time_unit": "Hour" }"#,
+            "2023-04-07T12:34:56Z",
+        )
+        .unwrap();
+        assert_eq!(
+            DateTime::parse_from_rfc3339(&date).unwrap(),
+            DateTime::parse_from_rfc3339("2023-04-07T12:00:00Z").unwrap()
+        );
+
+        let noisy = anonymize(
+            r#"{ "type": "noise", "method": "Uniform", "min_bound": -5, "max_bound": 5, "data_type": "integer" }"#,
+            "40",
+        )
+        .unwrap()
+        .parse::<i64>()
+        .unwrap();
+        assert!((35..=45).contains(&noisy));
+
+        let noisy = anonymize(
+            r#"{ "type": "noise", "method": "Gaussian", "mean": 0, "std_dev": 100, "min_bound": -5, "max_bound": 5, "data_type": "float" }"#,
+            "40",
+        )
+        .unwrap()
+        .parse::<f64>()
+        .unwrap();
+        assert!((35.0..=45.0).contains(&noisy));
+
+        // A keyed number scaler can be reverted.
+        let key = general_purpose::STANDARD.encode([42; 32]);
+        let scaled = anonymize(
+            &format!(
+                r#"{{ "type": "number_scaler", "mean": 10, "std_deviation": 5, "scale": 20, "translate": 1, "data_type": "integer", "key": "{key}" }}"#
+            ),
+            "1234",
+        )
+        .unwrap();
+        let restored = anonymize(
+            &format!(
+                r#"{{ "type": "number_scaler", "mean": 10, "std_deviation": 5, "scale": 20, "translate": 1, "data_type": "integer", "key": "{key}", "revert": true }}"#
+            ),
+            &scaled,
+        )
+        .unwrap();
+        assert_eq!(restored, "1234");
+
+        // Invalid configurations and inputs are rejected.
+        assert!(anonymize(r#"{ "type": "unknown" }"#, "data").is_err());
+        assert!(anonymize(r#"{ "type": "hash", "method": "Argon2" }"#, "data").is_err());
+        assert!(anonymize(
+            r#"{ "type": "number_aggregator", "power_of_ten_exponent": 2, "bucket_edges": [18], "data_type": "integer" }"#,
+            "42"
+        )
+        .is_err());
+        assert!(anonymize(
+            r#"{ "type": "number_scaler", "mean": 0, "std_deviation": 1, "scale": 1, "translate": 0, "data_type": "float" }"#,
+            "not a number"
+        )
+        .is_err());
+    }
+}
+
+const PIPELINE_CONFIG: &str = r#"{
+    "email": { "type": "hash", "method": "SHA2" },
+    "salary": { "type": "number_aggregator", "power_of_ten_exponent": 3, "data_type": "integer" }
+}"#;
+
+#[test]
+fn test_ffi_anonymize_csv() {
+    let config = CString::new(PIPELINE_CONFIG).unwrap();
+    let input = CString::new("email,salary,city\ntest sha2,51234,Paris\n").unwrap();
+
+    let mut output = vec![0_u8; 1024];
+    let mut output_len = output.len() as i32;
+    let ret = unsafe {
+        h_anonymize_csv(
+            output.as_mut_ptr(),
+            &mut output_len,
+            config.as_ptr().cast(),
+            input.as_ptr().cast(),
+        )
+    };
+    assert_eq!(ret, 0, "{}", get_last_error());
+    output.truncate(output_len as usize);
+    assert_eq!(
+        String::from_utf8(output).unwrap(),
+        "email,salary,city\nPx0txVYqBePXWF5K4xFn0Pa2mhnYA/jfsLtpIF70vJ8=,51000,Paris\n"
+    );
+}
+
+#[test]
+fn test_ffi_anonymize_csv_with_report() {
+    let config = CString::new(PIPELINE_CONFIG).unwrap();
+    let input = CString::new("email,salary,city\ntest sha2,51234,Paris\n").unwrap();
+
+    let mut output = vec![0_u8; 1024];
+    let mut output_len = output.len() as i32;
+    let mut report = vec![0_u8; 1024];
+    let mut report_len = report.len() as i32;
+    let ret = unsafe {
+        h_anonymize_csv_with_report(
+            output.as_mut_ptr(),
+            &mut output_len,
+            report.as_mut_ptr(),
+            &mut report_len,
+            config.as_ptr().cast(),
+            input.as_ptr().cast(),
+        )
+    };
+    assert_eq!(ret, 0, "{}", get_last_error());
+    output.truncate(output_len as usize);
+    assert_eq!(
+        String::from_utf8(output).unwrap(),
+        "email,salary,city\nPx0txVYqBePXWF5K4xFn0Pa2mhnYA/jfsLtpIF70vJ8=,51000,Paris\n"
+    );
+    report.truncate(report_len as usize);
+    assert_eq!(
+        String::from_utf8(report).unwrap(),
+        r#"{"columns":{"email":{"technique":"hash","values":1,"changed":1,"input_length":9,"output_length":44},"salary":{"technique":"number_aggregator","values":1,"changed":1,"input_length":5,"output_length":5}}}"#
+    );
+}
+
+unsafe fn anonymize_record_batch(batch: RecordBatch) -> Result<RecordBatch, String> {
+    let config = CString::new(PIPELINE_CONFIG).unwrap();
+    let (mut input_array, input_schema) = to_ffi(&StructArray::from(batch).to_data()).unwrap();
+    let mut output_array = FFI_ArrowArray::empty();
+    let mut output_schema = FFI_ArrowSchema::empty();
+
+    let ret = h_anonymize_record_batch(
+        &mut output_array,
+        &mut output_schema,
+        config.as_ptr().cast(),
+        &mut input_array,
+        &input_schema,
+    );
+    // The input array is always moved.
+    assert!(input_array.is_released());
+    if ret == 0 {
+        let data = from_ffi(output_array, &output_schema).unwrap();
+        Ok(RecordBatch::from(StructArray::from(data)))
+    } else {
+        Err(get_last_error())
+    }
+}
+
+#[test]
+fn test_ffi_anonymize_record_batch() {
+    let batch = RecordBatch::try_from_iter([
+        (
+            "email",
+            Arc::new(StringArray::from(vec!["test sha2"])) as ArrayRef,
+        ),
+        (
+            "salary",
+            Arc::new(Int64Array::from(vec![51_234])) as ArrayRef,
+        ),
+    ])
+    .unwrap();
+    let anonymized = unsafe { anonymize_record_batch(batch.clone()) }.unwrap();
+    assert_eq!(
+        anonymized.column(0).as_string::<i32>().value(0),
+        "Px0txVYqBePXWF5K4xFn0Pa2mhnYA/jfsLtpIF70vJ8="
+    );
+    assert_eq!(
+        anonymized.column(1).as_primitive::<Int64Type>().value(0),
+        51_000
+    );
+
+    // Configured columns must exist.
+    let batch = batch.project(&[0]).unwrap();
+    assert!(unsafe { anonymize_record_batch(batch) }.is_err());
+}
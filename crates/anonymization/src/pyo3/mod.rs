@@ -6,6 +6,12 @@ macro_rules! pyo3_unwrap {
     };
 }
 
+mod py_contact;
+use py_contact::{EmailMasker, PhoneMasker};
+
+mod py_geo;
+use py_geo::GeoMasker;
+
 mod py_hash;
 use py_hash::Hasher;
 
@@ -16,7 +22,10 @@ mod py_word;
 use py_word::{WordMasker, WordPatternMasker, WordTokenizer};
 
 mod py_number;
-use py_number::{DateAggregator, NumberAggregator, NumberScaler};
+use py_number::{DateAggregator, DateShifter, NumberAggregator, NumberScaler};
+
+mod py_pipeline;
+use py_pipeline::AnonymizationPipeline;
 
 /// A Python module implemented in Rust.
 #[pymodule]
@@ -28,7 +37,12 @@ fn cloudproof_anonymization(_py: Python, m: &PyModule) -> PyResult<()> {
     m.add_class::<WordTokenizer>()?;
     m.add_class::<NumberAggregator>()?;
     m.add_class::<DateAggregator>()?;
+    m.add_class::<DateShifter>()?;
     m.add_class::<NumberScaler>()?;
+    m.add_class::<GeoMasker>()?;
+    m.add_class::<EmailMasker>()?;
+    m.add_class::<PhoneMasker>()?;
+    m.add_class::<AnonymizationPipeline>()?;
 
     Ok(())
 }
@@ -0,0 +1,69 @@
+use pyo3::prelude::*;
+
+use crate::core::{
+    DomainMasking, EmailMasker as EmailMaskerRust, LocalPartMasking,
+    PhoneMasker as PhoneMaskerRust,
+};
+
+#[pyclass]
+pub struct EmailMasker(EmailMaskerRust);
+
+#[pymethods]
+impl EmailMasker {
+    #[new]
+    #[pyo3(signature = (key, local_part_masking = "Hash", domain_masking = "Preserve"))]
+    pub fn new(key: Vec<u8>, local_part_masking: &str, domain_masking: &str) -> PyResult<Self> {
+        let local_part_masking = pyo3_unwrap!(
+            LocalPartMasking::try_from(local_part_masking),
+            "Error parsing local part masking"
+        );
+        let domain_masking = pyo3_unwrap!(
+            DomainMasking::try_from(domain_masking),
+            "Error parsing domain masking"
+        );
+        Ok(Self(pyo3_unwrap!(
+            EmailMaskerRust::new(&key, local_part_masking, domain_masking),
+            "Error initializing EmailMasker"
+        )))
+    }
+
+    pub fn apply(&self, email: &str) -> PyResult<String> {
+        Ok(pyo3_unwrap!(
+            self.0.apply(email),
+            "Error masking email address"
+        ))
+    }
+
+    pub fn revert(&self, masked_email: &str) -> PyResult<String> {
+        Ok(pyo3_unwrap!(
+            self.0.revert(masked_email),
+            "Error unmasking email address"
+        ))
+    }
+}
+
+#[pyclass]
+pub struct PhoneMasker(PhoneMaskerRust);
+
+#[pymethods]
+impl PhoneMasker {
+    #[new]
+    #[pyo3(signature = (area_code_digits, key = None))]
+    pub fn new(area_code_digits: usize, key: Option<Vec<u8>>) -> PyResult<Self> {
+        let masker = match key {
+            Some(key) => pyo3_unwrap!(
+                PhoneMaskerRust::new_consistent(area_code_digits, &key),
+                "Error initializing PhoneMasker"
+            ),
+            None => PhoneMaskerRust::new(area_code_digits),
+        };
+        Ok(Self(masker))
+    }
+
+    pub fn apply(&self, phone_number: &str) -> PyResult<String> {
+        Ok(pyo3_unwrap!(
+            self.0.apply(phone_number),
+            "Error masking phone number"
+        ))
+    }
+}
@@ -0,0 +1,67 @@
+use pyo3::prelude::*;
+
+use crate::core::GeoMasker as GeoMaskerRust;
+
+#[pyclass]
+pub struct GeoMasker(GeoMaskerRust);
+
+#[pymethods]
+impl GeoMasker {
+    #[new]
+    #[pyo3(signature = (radius, key = None, seed = None))]
+    pub fn new(radius: f64, key: Option<Vec<u8>>, seed: Option<u64>) -> PyResult<Self> {
+        let masker = match key {
+            Some(key) => GeoMaskerRust::new_consistent(radius, &key),
+            None => GeoMaskerRust::new(radius),
+        };
+        let masker = pyo3_unwrap!(masker, "Error initializing GeoMasker");
+        Ok(Self(match seed {
+            Some(seed) => masker.with_seed(seed),
+            None => masker,
+        }))
+    }
+
+    pub fn is_consistent(&self) -> bool {
+        self.0.is_consistent()
+    }
+
+    pub fn is_seeded(&self) -> bool {
+        self.0.is_seeded()
+    }
+
+    pub fn apply_on_coordinates(&self, latitude: f64, longitude: f64) -> PyResult<(f64, f64)> {
+        Ok(pyo3_unwrap!(
+            self.0.apply_on_coordinates(latitude, longitude),
+            "Error blurring coordinates"
+        ))
+    }
+
+    pub fn apply_on_entity_coordinates(
+        &self,
+        entity_id: &str,
+        latitude: f64,
+        longitude: f64,
+    ) -> PyResult<(f64, f64)> {
+        Ok(pyo3_unwrap!(
+            self.0
+                .apply_on_entity_coordinates(entity_id, latitude, longitude),
+            "Error blurring coordinates"
+        ))
+    }
+
+    #[staticmethod]
+    pub fn to_geohash(latitude: f64, longitude: f64, precision: usize) -> PyResult<String> {
+        Ok(pyo3_unwrap!(
+            GeoMaskerRust::to_geohash(latitude, longitude, precision),
+            "Error encoding geohash"
+        ))
+    }
+
+    #[staticmethod]
+    pub fn truncate_geohash(geohash: &str, precision: usize) -> PyResult<String> {
+        Ok(pyo3_unwrap!(
+            GeoMaskerRust::truncate_geohash(geohash, precision),
+            "Error truncating geohash"
+        ))
+    }
+}
@@ -5,22 +5,79 @@ use crate::core::NoiseGenerator as NoiseGeneratorRust;
 #[pyclass]
 pub struct NoiseGenerator(NoiseGeneratorRust<f64>);
 
+impl NoiseGenerator {
+    /// Seeds the given generator if a seed is given.
+    fn seeded(noise_generator: NoiseGeneratorRust<f64>, seed: Option<u64>) -> Self {
+        match seed {
+            Some(seed) => Self(noise_generator.with_seed(seed)),
+            None => Self(noise_generator),
+        }
+    }
+}
+
 #[pymethods]
 impl NoiseGenerator {
     #[staticmethod]
-    pub fn new_with_parameters(method_name: &str, mean: f64, std_dev: f64) -> PyResult<Self> {
-        Ok(Self(pyo3_unwrap!(
-            NoiseGeneratorRust::<f64>::new_with_parameters(method_name, mean, std_dev),
-            "Error initializing noise"
-        )))
+    #[pyo3(signature = (method_name, mean, std_dev, seed = None))]
+    pub fn new_with_parameters(
+        method_name: &str,
+        mean: f64,
+        std_dev: f64,
+        seed: Option<u64>,
+    ) -> PyResult<Self> {
+        Ok(Self::seeded(
+            pyo3_unwrap!(
+                NoiseGeneratorRust::<f64>::new_with_parameters(method_name, mean, std_dev),
+                "Error initializing noise"
+            ),
+            seed,
+        ))
+    }
+
+    #[staticmethod]
+    #[pyo3(signature = (method_name, min_bound, max_bound, seed = None))]
+    pub fn new_with_bounds(
+        method_name: &str,
+        min_bound: f64,
+        max_bound: f64,
+        seed: Option<u64>,
+    ) -> PyResult<Self> {
+        Ok(Self::seeded(
+            pyo3_unwrap!(
+                NoiseGeneratorRust::<f64>::new_with_bounds(method_name, min_bound, max_bound),
+                "Error initializing noise"
+            ),
+            seed,
+        ))
     }
 
     #[staticmethod]
-    pub fn new_with_bounds(method_name: &str, min_bound: f64, max_bound: f64) -> PyResult<Self> {
-        Ok(Self(pyo3_unwrap!(
-            NoiseGeneratorRust::<f64>::new_with_bounds(method_name, min_bound, max_bound),
-            "Error initializing noise"
-        )))
+    #[pyo3(signature = (method_name, mean, std_dev, min_bound, max_bound, seed = None))]
+    pub fn new_with_parameters_and_bounds(
+        method_name: &str,
+        mean: f64,
+        std_dev: f64,
+        min_bound: f64,
+        max_bound: f64,
+        seed: Option<u64>,
+    ) -> PyResult<Self> {
+        Ok(Self::seeded(
+            pyo3_unwrap!(
+                NoiseGeneratorRust::<f64>::new_with_parameters_and_bounds(
+                    method_name,
+                    mean,
+                    std_dev,
+                    min_bound,
+                    max_bound
+                ),
+                "Error initializing noise"
+            ),
+            seed,
+        ))
+    }
+
+    pub fn is_seeded(&self) -> bool {
+        self.0.is_seeded()
     }
 
     pub fn apply_on_float(&mut self, data: f64) -> f64 {
@@ -1,8 +1,8 @@
 use pyo3::prelude::*;
 
 use crate::core::{
-    DateAggregator as DateAggregatorRust, NumberAggregator as NumberAggregatorRust,
-    NumberScaler as NumberScalerRust, TimeUnit,
+    DateAggregator as DateAggregatorRust, DateShifter as DateShifterRust,
+    NumberAggregator as NumberAggregatorRust, NumberScaler as NumberScalerRust, TimeUnit,
 };
 
 #[pyclass]
@@ -18,6 +18,15 @@ impl NumberAggregator {
         )))
     }
 
+    #[staticmethod]
+    #[pyo3(signature = (edges, labels = None))]
+    pub fn new_with_buckets(edges: Vec<f64>, labels: Option<Vec<String>>) -> PyResult<Self> {
+        Ok(Self(pyo3_unwrap!(
+            NumberAggregatorRust::new_with_buckets(edges, labels),
+            "Error initializing NumberAggregator"
+        )))
+    }
+
     pub fn apply_on_float(&self, data: f64) -> String {
         self.0.apply_on_float(data)
     }
@@ -49,14 +58,67 @@ impl DateAggregator {
     }
 }
 
+#[pyclass]
+pub struct DateShifter(DateShifterRust);
+
+#[pymethods]
+impl DateShifter {
+    #[new]
+    pub fn new(key: &[u8], max_shift: u64, time_unit: &str) -> PyResult<Self> {
+        let time_unit_rust = pyo3_unwrap!(
+            TimeUnit::try_from(time_unit),
+            "Error initializing DateShifter"
+        );
+        Ok(Self(pyo3_unwrap!(
+            DateShifterRust::new(key, max_shift, time_unit_rust),
+            "Error initializing DateShifter"
+        )))
+    }
+
+    pub fn apply_on_date(&self, entity_id: &str, date_str: &str) -> PyResult<String> {
+        Ok(pyo3_unwrap!(
+            self.0.apply_on_date(entity_id, date_str),
+            "Error shifting date"
+        ))
+    }
+
+    pub fn apply_on_dates(&self, entity_id: &str, dates: Vec<&str>) -> PyResult<Vec<String>> {
+        Ok(pyo3_unwrap!(
+            self.0.apply_on_dates(entity_id, &dates),
+            "Error shifting dates"
+        ))
+    }
+}
+
 #[pyclass]
 pub struct NumberScaler(NumberScalerRust);
 
 #[pymethods]
 impl NumberScaler {
     #[new]
-    pub fn new(mean: f64, std_dev: f64, scale: f64, translation: f64) -> Self {
-        Self(NumberScalerRust::new(mean, std_dev, scale, translation))
+    pub fn new(
+        mean: f64,
+        std_dev: f64,
+        scale: f64,
+        translation: f64,
+        key: Option<Vec<u8>>,
+    ) -> PyResult<Self> {
+        match key {
+            Some(key) => Ok(Self(pyo3_unwrap!(
+                NumberScalerRust::new_reversible(mean, std_dev, scale, translation, &key),
+                "Error initializing NumberScaler"
+            ))),
+            None => Ok(Self(NumberScalerRust::new(
+                mean,
+                std_dev,
+                scale,
+                translation,
+            ))),
+        }
+    }
+
+    pub fn is_reversible(&self) -> bool {
+        self.0.is_reversible()
     }
 
     pub fn apply_on_float(&self, data: f64) -> f64 {
@@ -66,4 +128,18 @@ impl NumberScaler {
     pub fn apply_on_int(&self, data: i64) -> i64 {
         self.0.apply_on_int(data)
     }
+
+    pub fn revert_on_float(&self, data: f64) -> PyResult<f64> {
+        Ok(pyo3_unwrap!(
+            self.0.revert_on_float(data),
+            "Error reverting NumberScaler"
+        ))
+    }
+
+    pub fn revert_on_int(&self, data: i64) -> PyResult<i64> {
+        Ok(pyo3_unwrap!(
+            self.0.revert_on_int(data),
+            "Error reverting NumberScaler"
+        ))
+    }
 }
@@ -0,0 +1,88 @@
+use std::ptr::addr_of_mut;
+
+use arrow::{
+    array::{Array, StructArray},
+    datatypes::DataType,
+    ffi::{from_ffi, to_ffi, FFI_ArrowArray, FFI_ArrowSchema},
+    record_batch::RecordBatch,
+};
+use pyo3::{exceptions::PyValueError, prelude::*};
+
+use crate::core::AnonymizationPipeline as AnonymizationPipelineRust;
+
+#[pyclass]
+pub struct AnonymizationPipeline(AnonymizationPipelineRust);
+
+#[pymethods]
+impl AnonymizationPipeline {
+    #[new]
+    #[pyo3(signature = (config, record_report = false))]
+    fn new(config: &str, record_report: bool) -> PyResult<Self> {
+        let pipeline = pyo3_unwrap!(
+            AnonymizationPipelineRust::from_json(config),
+            "Error parsing the pipeline configuration"
+        );
+        Ok(Self(if record_report {
+            pipeline.with_report()
+        } else {
+            pipeline
+        }))
+    }
+
+    /// Returns the report of the transformations applied so far as a `dict`,
+    /// or `None` if the pipeline does not record them.
+    pub fn report(&self, py: Python) -> PyResult<PyObject> {
+        match self.0.report() {
+            Some(report) => {
+                let json = pyo3_unwrap!(report.to_json(), "Error serializing the report");
+                Ok(py.import("json")?.call_method1("loads", (json,))?.into())
+            }
+            None => Ok(py.None()),
+        }
+    }
+
+    pub fn apply_to_csv(&self, csv: &str) -> PyResult<String> {
+        Ok(pyo3_unwrap!(
+            self.0.apply_to_csv(csv),
+            "Error anonymizing the CSV"
+        ))
+    }
+
+    /// Anonymizes the given `pyarrow.RecordBatch`.
+    ///
+    /// The record batches are exchanged with pyarrow through the Arrow C data
+    /// interface: no data is serialized.
+    pub fn apply_to_record_batch(&self, batch: &PyAny, py: Python) -> PyResult<PyObject> {
+        let mut array = FFI_ArrowArray::empty();
+        let mut schema = FFI_ArrowSchema::empty();
+        batch.call_method1(
+            "_export_to_c",
+            (addr_of_mut!(array) as usize, addr_of_mut!(schema) as usize),
+        )?;
+        let data = pyo3_unwrap!(
+            unsafe { from_ffi(array, &schema) },
+            "Error importing the record batch"
+        );
+        if !matches!(data.data_type(), DataType::Struct(_)) {
+            return Err(PyValueError::new_err(
+                "Error importing the record batch: a RecordBatch is expected",
+            ));
+        }
+
+        let anonymized = pyo3_unwrap!(
+            self.0
+                .apply_to_record_batch(&RecordBatch::from(StructArray::from(data))),
+            "Error anonymizing the record batch"
+        );
+
+        let (mut array, mut schema) = pyo3_unwrap!(
+            to_ffi(&StructArray::from(anonymized).to_data()),
+            "Error exporting the record batch"
+        );
+        let record_batch = py.import("pyarrow")?.getattr("RecordBatch")?.call_method1(
+            "_import_from_c",
+            (addr_of_mut!(array) as usize, addr_of_mut!(schema) as usize),
+        )?;
+        Ok(record_batch.into())
+    }
+}
@@ -1,7 +1,7 @@
 use pyo3::prelude::*;
 
 use crate::core::{
-    WordMasker as WordMaskerRust, WordPatternMasker as WordPatternMaskerRust,
+    Language, WordMasker as WordMaskerRust, WordPatternMasker as WordPatternMaskerRust,
     WordTokenizer as WordTokenizerRust,
 };
 
@@ -11,8 +11,15 @@ pub struct WordMasker(WordMaskerRust);
 #[pymethods]
 impl WordMasker {
     #[new]
-    pub fn new(words_list: Vec<&str>) -> Self {
-        Self(WordMaskerRust::new(&words_list))
+    #[pyo3(signature = (words_list, language = None))]
+    pub fn new(words_list: Vec<&str>, language: Option<&str>) -> PyResult<Self> {
+        Ok(Self(match language {
+            Some(language) => WordMaskerRust::new_with_language(
+                &words_list,
+                pyo3_unwrap!(Language::try_from(language), "Error parsing language"),
+            ),
+            None => WordMaskerRust::new(&words_list),
+        }))
     }
 
     pub fn apply(&self, data: &str) -> String {
@@ -26,9 +33,17 @@ pub struct WordTokenizer(WordTokenizerRust);
 #[pymethods]
 impl WordTokenizer {
     #[new]
-    pub fn new(words_list: Vec<&str>) -> PyResult<Self> {
+    #[pyo3(signature = (words_list, language = None))]
+    pub fn new(words_list: Vec<&str>, language: Option<&str>) -> PyResult<Self> {
+        let word_tokenizer = match language {
+            Some(language) => WordTokenizerRust::new_with_language(
+                &words_list,
+                pyo3_unwrap!(Language::try_from(language), "Error parsing language"),
+            ),
+            None => WordTokenizerRust::new(&words_list),
+        };
         Ok(Self(pyo3_unwrap!(
-            WordTokenizerRust::new(&words_list),
+            word_tokenizer,
             "Error initializing WordTokenizer"
         )))
     }
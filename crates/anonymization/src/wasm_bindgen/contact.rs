@@ -0,0 +1,75 @@
+use wasm_bindgen::{prelude::wasm_bindgen, JsValue};
+
+use crate::core::{
+    DomainMasking, EmailMasker as EmailMaskerRust, LocalPartMasking,
+    PhoneMasker as PhoneMaskerRust,
+};
+
+#[wasm_bindgen]
+pub struct EmailMasker(EmailMaskerRust);
+
+#[wasm_bindgen]
+impl EmailMasker {
+    /// Creates a new `EmailMasker`.
+    ///
+    /// `local_part_masking` is either `Hash` or `FPE`, `domain_masking` is
+    /// either `Preserve` or `Category`.
+    #[wasm_bindgen(constructor)]
+    pub fn new(
+        key: &[u8],
+        local_part_masking: &str,
+        domain_masking: &str,
+    ) -> Result<EmailMasker, JsValue> {
+        let local_part_masking = wasm_unwrap!(
+            LocalPartMasking::try_from(local_part_masking),
+            "Error parsing local part masking"
+        );
+        let domain_masking = wasm_unwrap!(
+            DomainMasking::try_from(domain_masking),
+            "Error parsing domain masking"
+        );
+        Ok(Self(wasm_unwrap!(
+            EmailMaskerRust::new(key, local_part_masking, domain_masking),
+            "Error initializing EmailMasker"
+        )))
+    }
+
+    pub fn apply(&self, email: &str) -> Result<String, JsValue> {
+        Ok(wasm_unwrap!(
+            self.0.apply(email),
+            "Error masking email address"
+        ))
+    }
+
+    pub fn revert(&self, masked_email: &str) -> Result<String, JsValue> {
+        Ok(wasm_unwrap!(
+            self.0.revert(masked_email),
+            "Error unmasking email address"
+        ))
+    }
+}
+
+#[wasm_bindgen]
+pub struct PhoneMasker(PhoneMaskerRust);
+
+#[wasm_bindgen]
+impl PhoneMasker {
+    #[wasm_bindgen(constructor)]
+    pub fn new(area_code_digits: usize) -> PhoneMasker {
+        Self(PhoneMaskerRust::new(area_code_digits))
+    }
+
+    pub fn new_consistent(area_code_digits: usize, key: &[u8]) -> Result<PhoneMasker, JsValue> {
+        Ok(Self(wasm_unwrap!(
+            PhoneMaskerRust::new_consistent(area_code_digits, key),
+            "Error initializing PhoneMasker"
+        )))
+    }
+
+    pub fn apply(&self, phone_number: &str) -> Result<String, JsValue> {
+        Ok(wasm_unwrap!(
+            self.0.apply(phone_number),
+            "Error masking phone number"
+        ))
+    }
+}
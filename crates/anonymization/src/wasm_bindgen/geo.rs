@@ -0,0 +1,66 @@
+use wasm_bindgen::{prelude::wasm_bindgen, JsValue};
+
+use crate::core::GeoMasker as GeoMaskerRust;
+
+#[wasm_bindgen]
+pub struct GeoMasker(GeoMaskerRust);
+
+#[wasm_bindgen]
+impl GeoMasker {
+    #[wasm_bindgen(constructor)]
+    pub fn new(radius: f64) -> Result<GeoMasker, JsValue> {
+        Ok(Self(wasm_unwrap!(
+            GeoMaskerRust::new(radius),
+            "Error initializing GeoMasker"
+        )))
+    }
+
+    pub fn new_consistent(radius: f64, key: &[u8]) -> Result<GeoMasker, JsValue> {
+        Ok(Self(wasm_unwrap!(
+            GeoMaskerRust::new_consistent(radius, key),
+            "Error initializing GeoMasker"
+        )))
+    }
+
+    pub fn is_consistent(&self) -> bool {
+        self.0.is_consistent()
+    }
+
+    /// Returns the blurred coordinates as `[latitude, longitude]`.
+    pub fn apply_on_coordinates(&self, latitude: f64, longitude: f64) -> Result<Vec<f64>, JsValue> {
+        let (latitude, longitude) = wasm_unwrap!(
+            self.0.apply_on_coordinates(latitude, longitude),
+            "Error blurring coordinates"
+        );
+        Ok(vec![latitude, longitude])
+    }
+
+    /// Returns the blurred coordinates as `[latitude, longitude]`.
+    pub fn apply_on_entity_coordinates(
+        &self,
+        entity_id: &str,
+        latitude: f64,
+        longitude: f64,
+    ) -> Result<Vec<f64>, JsValue> {
+        let (latitude, longitude) = wasm_unwrap!(
+            self.0
+                .apply_on_entity_coordinates(entity_id, latitude, longitude),
+            "Error blurring coordinates"
+        );
+        Ok(vec![latitude, longitude])
+    }
+
+    pub fn to_geohash(latitude: f64, longitude: f64, precision: usize) -> Result<String, JsValue> {
+        Ok(wasm_unwrap!(
+            GeoMaskerRust::to_geohash(latitude, longitude, precision),
+            "Error encoding geohash"
+        ))
+    }
+
+    pub fn truncate_geohash(geohash: &str, precision: usize) -> Result<String, JsValue> {
+        Ok(wasm_unwrap!(
+            GeoMaskerRust::truncate_geohash(geohash, precision),
+            "Error truncating geohash"
+        ))
+    }
+}
@@ -4,9 +4,12 @@ macro_rules! wasm_unwrap {
     };
 }
 
+mod contact;
+mod geo;
 mod hash;
 mod noise;
 mod number;
+mod stream;
 #[cfg(test)]
 mod tests;
 mod word;
@@ -38,6 +38,32 @@ impl NoiseGeneratorWithBounds {
     }
 }
 
+#[wasm_bindgen]
+pub struct NoiseGeneratorWithParametersAndBounds(NoiseGeneratorRust<f64>);
+
+#[wasm_bindgen]
+impl NoiseGeneratorWithParametersAndBounds {
+    #[wasm_bindgen(constructor)]
+    pub fn new(
+        method_name: &str,
+        mean: f64,
+        std_dev: f64,
+        min_bound: f64,
+        max_bound: f64,
+    ) -> Result<NoiseGeneratorWithParametersAndBounds, JsValue> {
+        Ok(Self(wasm_unwrap!(
+            NoiseGeneratorRust::<f64>::new_with_parameters_and_bounds(
+                method_name,
+                mean,
+                std_dev,
+                min_bound,
+                max_bound
+            ),
+            "Error initializing noise with parameters and bounds"
+        )))
+    }
+}
+
 macro_rules! impl_noise {
     ($type_name:ty) => {
         #[wasm_bindgen]
@@ -97,3 +123,4 @@ macro_rules! impl_noise {
 
 impl_noise!(NoiseGeneratorWithParameters);
 impl_noise!(NoiseGeneratorWithBounds);
+impl_noise!(NoiseGeneratorWithParametersAndBounds);
@@ -1,8 +1,8 @@
 use wasm_bindgen::{prelude::wasm_bindgen, JsValue};
 
 use crate::core::{
-    DateAggregator as DateAggregatorRust, NumberAggregator as NumberAggregatorRust,
-    NumberScaler as NumberScalerRust, TimeUnit,
+    DateAggregator as DateAggregatorRust, DateShifter as DateShifterRust,
+    NumberAggregator as NumberAggregatorRust, NumberScaler as NumberScalerRust, TimeUnit,
 };
 
 #[wasm_bindgen]
@@ -18,6 +18,16 @@ impl NumberAggregator {
         )))
     }
 
+    pub fn new_with_buckets(
+        edges: Vec<f64>,
+        labels: Option<Vec<String>>,
+    ) -> Result<NumberAggregator, JsValue> {
+        Ok(Self(wasm_unwrap!(
+            NumberAggregatorRust::new_with_buckets(edges, labels),
+            "Error initializing NumberAggregator"
+        )))
+    }
+
     pub fn apply_on_float(&self, data: f64) -> String {
         self.0.apply_on_float(data)
     }
@@ -49,6 +59,31 @@ impl DateAggregator {
     }
 }
 
+#[wasm_bindgen]
+pub struct DateShifter(DateShifterRust);
+
+#[wasm_bindgen]
+impl DateShifter {
+    #[wasm_bindgen(constructor)]
+    pub fn new(key: &[u8], max_shift: u32, time_unit: &str) -> Result<DateShifter, JsValue> {
+        let time_unit_rust = wasm_unwrap!(
+            TimeUnit::try_from(time_unit),
+            "Error initializing DateShifter"
+        );
+        Ok(Self(wasm_unwrap!(
+            DateShifterRust::new(key, u64::from(max_shift), time_unit_rust),
+            "Error initializing DateShifter"
+        )))
+    }
+
+    pub fn apply_on_date(&self, entity_id: &str, date_str: &str) -> Result<String, JsValue> {
+        Ok(wasm_unwrap!(
+            self.0.apply_on_date(entity_id, date_str),
+            "Error shifting date"
+        ))
+    }
+}
+
 #[wasm_bindgen]
 pub struct NumberScaler(NumberScalerRust);
 
@@ -59,6 +94,23 @@ impl NumberScaler {
         Self(NumberScalerRust::new(mean, std_deviation, scale, translate))
     }
 
+    pub fn new_reversible(
+        mean: f64,
+        std_deviation: f64,
+        scale: f64,
+        translate: f64,
+        key: &[u8],
+    ) -> Result<NumberScaler, JsValue> {
+        Ok(Self(wasm_unwrap!(
+            NumberScalerRust::new_reversible(mean, std_deviation, scale, translate, key),
+            "Error initializing NumberScaler"
+        )))
+    }
+
+    pub fn is_reversible(&self) -> bool {
+        self.0.is_reversible()
+    }
+
     pub fn apply_on_float(&self, data: f64) -> f64 {
         self.0.apply_on_float(data)
     }
@@ -66,4 +118,18 @@ impl NumberScaler {
     pub fn apply_on_int(&self, data: i64) -> i64 {
         self.0.apply_on_int(data)
     }
+
+    pub fn revert_on_float(&self, data: f64) -> Result<f64, JsValue> {
+        Ok(wasm_unwrap!(
+            self.0.revert_on_float(data),
+            "Error reverting NumberScaler"
+        ))
+    }
+
+    pub fn revert_on_int(&self, data: i64) -> Result<i64, JsValue> {
+        Ok(wasm_unwrap!(
+            self.0.revert_on_int(data),
+            "Error reverting NumberScaler"
+        ))
+    }
 }
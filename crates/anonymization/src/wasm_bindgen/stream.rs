@@ -0,0 +1,212 @@
+//! Streaming anonymization of large texts, e.g. CSV files read in the browser.
+//!
+//! ```js
+//! const pipeline = new MaskingPipeline()
+//! pipeline.add_word_masker("confidential; secret")
+//! pipeline.add_word_pattern_masker("\\d{4}-\\d{4}", "XXXX-XXXX")
+//! const output = pipeline.apply_to_stream(
+//!     file.stream().pipeThrough(new TextDecoderStream())
+//! )
+//! ```
+//!
+//! Chunks are buffered until complete lines are received, so that the words
+//! split across chunks are masked as a whole.
+
+use std::{cell::RefCell, rc::Rc};
+
+use js_sys::{Object, Reflect};
+use wasm_bindgen::{prelude::*, JsValue};
+
+use crate::core::{
+    Language, WordMasker as WordMaskerRust, WordPatternMasker as WordPatternMaskerRust,
+    WordTokenizer as WordTokenizerRust,
+};
+
+#[wasm_bindgen]
+extern "C" {
+    #[wasm_bindgen(typescript_type = "ReadableStream<string>")]
+    pub type ReadableStream;
+
+    #[wasm_bindgen(method, catch, js_name = pipeThrough)]
+    fn pipe_through(
+        this: &ReadableStream,
+        transform: &TransformStream,
+    ) -> Result<ReadableStream, JsValue>;
+
+    type TransformStream;
+
+    #[wasm_bindgen(constructor, catch)]
+    fn new(transformer: &Object) -> Result<TransformStream, JsValue>;
+
+    type TransformStreamDefaultController;
+
+    #[wasm_bindgen(method, catch)]
+    fn enqueue(this: &TransformStreamDefaultController, chunk: &JsValue) -> Result<(), JsValue>;
+}
+
+type TransformCallback =
+    dyn FnMut(JsValue, TransformStreamDefaultController) -> Result<(), JsValue>;
+type FlushCallback = dyn FnMut(TransformStreamDefaultController) -> Result<(), JsValue>;
+
+enum TextMasker {
+    Word(WordMaskerRust),
+    Tokenizer(WordTokenizerRust),
+    Pattern(WordPatternMaskerRust),
+}
+
+impl TextMasker {
+    fn apply(&self, data: &str) -> String {
+        match self {
+            Self::Word(masker) => masker.apply(data),
+            Self::Tokenizer(tokenizer) => tokenizer.apply(data),
+            Self::Pattern(masker) => masker.apply(data),
+        }
+    }
+}
+
+fn apply_maskers(maskers: &[Rc<TextMasker>], data: &str) -> String {
+    maskers
+        .iter()
+        .fold(data.to_string(), |data, masker| masker.apply(&data))
+}
+
+fn parse_language(language: Option<String>) -> Result<Option<Language>, JsValue> {
+    Ok(match language {
+        Some(language) => Some(wasm_unwrap!(
+            Language::try_from(language.as_str()),
+            "Error parsing language"
+        )),
+        None => None,
+    })
+}
+
+/// Buffers text chunks until complete lines are received.
+#[derive(Default)]
+pub(super) struct LineBuffer(String);
+
+impl LineBuffer {
+    /// Appends the given chunk, returning the complete lines buffered so far.
+    pub(super) fn push(&mut self, chunk: &str) -> Option<String> {
+        self.0.push_str(chunk);
+        let end = self.0.rfind('\n')? + 1;
+        let remainder = self.0.split_off(end);
+        Some(std::mem::replace(&mut self.0, remainder))
+    }
+
+    /// Returns the last line if it is not terminated by a line break.
+    pub(super) fn flush(&mut self) -> Option<String> {
+        (!self.0.is_empty()).then(|| std::mem::take(&mut self.0))
+    }
+}
+
+/// Sequence of text maskers applied in their insertion order.
+#[wasm_bindgen]
+#[derive(Default)]
+pub struct MaskingPipeline {
+    maskers: Vec<Rc<TextMasker>>,
+}
+
+#[wasm_bindgen]
+impl MaskingPipeline {
+    #[wasm_bindgen(constructor)]
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Masks the given `;` separated words.
+    pub fn add_word_masker(
+        &mut self,
+        words_to_block: String,
+        language: Option<String>,
+    ) -> Result<(), JsValue> {
+        let words_to_block: Vec<&str> = words_to_block.split(';').map(str::trim).collect();
+        let masker = match parse_language(language)? {
+            Some(language) => WordMaskerRust::new_with_language(&words_to_block, language),
+            None => WordMaskerRust::new(&words_to_block),
+        };
+        self.maskers.push(Rc::new(TextMasker::Word(masker)));
+        Ok(())
+    }
+
+    /// Replaces the given `;` separated words with tokens.
+    pub fn add_word_tokenizer(
+        &mut self,
+        words_to_block: String,
+        language: Option<String>,
+    ) -> Result<(), JsValue> {
+        let words_to_block: Vec<&str> = words_to_block.split(';').map(str::trim).collect();
+        let tokenizer = match parse_language(language)? {
+            Some(language) => WordTokenizerRust::new_with_language(&words_to_block, language),
+            None => WordTokenizerRust::new(&words_to_block),
+        };
+        let tokenizer = wasm_unwrap!(tokenizer, "Error initializing WordTokenizer");
+        self.maskers.push(Rc::new(TextMasker::Tokenizer(tokenizer)));
+        Ok(())
+    }
+
+    /// Replaces the matches of the given regex with `replace_str`.
+    pub fn add_word_pattern_masker(
+        &mut self,
+        pattern_regex: &str,
+        replace_str: &str,
+    ) -> Result<(), JsValue> {
+        let masker = wasm_unwrap!(
+            WordPatternMaskerRust::new(pattern_regex, replace_str),
+            "Error with the given Regex"
+        );
+        self.maskers.push(Rc::new(TextMasker::Pattern(masker)));
+        Ok(())
+    }
+
+    pub fn apply(&self, data: &str) -> String {
+        apply_maskers(&self.maskers, data)
+    }
+
+    /// Returns a stream of the masked chunks of the given stream of strings.
+    ///
+    /// Maskers are applied to blocks of complete lines: patterns spanning
+    /// several lines may not be matched.
+    pub fn apply_to_stream(&self, input: &ReadableStream) -> Result<ReadableStream, JsValue> {
+        let buffer = Rc::new(RefCell::new(LineBuffer::default()));
+
+        let transform = {
+            let buffer = buffer.clone();
+            let maskers = self.maskers.clone();
+            Closure::<TransformCallback>::new(
+                move |chunk: JsValue, controller: TransformStreamDefaultController| {
+                    let chunk = chunk.as_string().ok_or_else(|| {
+                        JsValue::from_str("Error reading stream: chunks should be strings")
+                    })?;
+                    if let Some(lines) = buffer.borrow_mut().push(&chunk) {
+                        controller.enqueue(&JsValue::from(apply_maskers(&maskers, &lines)))?;
+                    }
+                    Ok(())
+                },
+            )
+        };
+
+        let flush = {
+            let maskers = self.maskers.clone();
+            Closure::<FlushCallback>::new(move |controller: TransformStreamDefaultController| {
+                if let Some(line) = buffer.borrow_mut().flush() {
+                    controller.enqueue(&JsValue::from(apply_maskers(&maskers, &line)))?;
+                }
+                Ok(())
+            })
+        };
+
+        let transformer = Object::new();
+        Reflect::set(
+            &transformer,
+            &JsValue::from_str("transform"),
+            &transform.into_js_value(),
+        )?;
+        Reflect::set(
+            &transformer,
+            &JsValue::from_str("flush"),
+            &flush.into_js_value(),
+        )?;
+        input.pipe_through(&TransformStream::new(&transformer)?)
+    }
+}
@@ -6,10 +6,18 @@ use chrono::{DateTime, Datelike, Timelike, Utc};
 use wasm_bindgen::JsValue;
 use wasm_bindgen_test::wasm_bindgen_test;
 
-use super::hash::Hasher;
+use super::{
+    contact::{EmailMasker, PhoneMasker},
+    geo::GeoMasker,
+    hash::Hasher,
+};
 use crate::wasm_bindgen::{
-    noise::{NoiseGeneratorWithBounds, NoiseGeneratorWithParameters},
-    number::{DateAggregator, NumberAggregator, NumberScaler},
+    noise::{
+        NoiseGeneratorWithBounds, NoiseGeneratorWithParameters,
+        NoiseGeneratorWithParametersAndBounds,
+    },
+    number::{DateAggregator, DateShifter, NumberAggregator, NumberScaler},
+    stream::{LineBuffer, MaskingPipeline},
     word::{WordMasker, WordPatternMasker, WordTokenizer},
 };
 
@@ -93,6 +101,21 @@ fn test_noise_uniform_f64() -> Result<(), JsValue> {
     Ok(())
 }
 
+#[wasm_bindgen_test]
+fn test_noise_truncated_f64() -> Result<(), JsValue> {
+    let mut noise_generator =
+        NoiseGeneratorWithParametersAndBounds::new("Gaussian", 0.0, 10.0, -1.0, 2.0)?;
+    for _ in 0..100 {
+        let noisy_data = noise_generator.apply_on_float(40.0);
+        assert!((39.0..=42.0).contains(&noisy_data));
+    }
+
+    let res = NoiseGeneratorWithParametersAndBounds::new("Laplace", 5.0, 1.0, -1.0, 1.0);
+    assert!(res.is_err());
+
+    Ok(())
+}
+
 #[wasm_bindgen_test]
 fn test_noise_gaussian_i64() -> Result<(), JsValue> {
     let mut gaussian_noise_generator = NoiseGeneratorWithParameters::new("Gaussian", 0.0, 1.0)?;
@@ -247,11 +270,15 @@ fn test_correlated_noise_uniform_date() -> Result<(), JsValue> {
 fn test_mask_word() -> Result<(), JsValue> {
     let input_str = String::from("Confidential: contains -secret- documents");
     let block_words = ["confidential", "SECRET"].join(";");
-    let word_masker = WordMasker::new(block_words);
+    let word_masker = WordMasker::new(block_words, None)?;
 
     let safe_str = word_masker.apply(&input_str);
 
     assert_eq!(safe_str, "XXXX: contains -XXXX- documents");
+
+    let block_words = ["été", "de"].join(";");
+    let word_masker = WordMasker::new(block_words, Some("French".to_string()))?;
+    assert_eq!(word_masker.apply("L'été de Jean"), "L'XXXX de Jean");
     Ok(())
 }
 
@@ -259,7 +286,7 @@ fn test_mask_word() -> Result<(), JsValue> {
 fn test_token_word() -> Result<(), JsValue> {
     let input_str = String::from("confidential : contains secret documents with confidential info");
     let block_words = ["confidential", "SECRET"].join(";");
-    let word_tokenizer = WordTokenizer::new(block_words)?;
+    let word_tokenizer = WordTokenizer::new(block_words, None)?;
 
     let safe_str = word_tokenizer.apply(&input_str);
 
@@ -289,6 +316,47 @@ fn test_word_pattern() -> Result<(), JsValue> {
     Ok(())
 }
 
+#[wasm_bindgen_test]
+fn test_word_pattern_capture_groups() -> Result<(), JsValue> {
+    let masker = WordPatternMasker::new(r"(?<year>\d{4})-\d{2}-\d{2}", "${year}-XX-XX")?;
+    assert_eq!(
+        masker.apply("Born on 1987-04-12, hired on 2015-09-01."),
+        "Born on 1987-XX-XX, hired on 2015-XX-XX."
+    );
+
+    assert!(WordPatternMasker::new(r"(\d{4})-\d{2}", "${month}").is_err());
+
+    Ok(())
+}
+
+#[wasm_bindgen_test]
+fn test_masking_pipeline() -> Result<(), JsValue> {
+    let mut pipeline = MaskingPipeline::new();
+    pipeline.add_word_masker(["confidential", "SECRET"].join(";"), None)?;
+    pipeline.add_word_pattern_masker(r"\d{4}-\d{4}", "####-####")?;
+
+    let input = "id,comment\n1,Confidential: card 1234-5678\n2,-secret- documents";
+    let expected = "id,comment\n1,XXXX: card ####-####\n2,-XXXX- documents";
+    assert_eq!(pipeline.apply(input), expected);
+
+    // Chunks split in the middle of words are only masked once their lines
+    // are complete.
+    let mut buffer = LineBuffer::default();
+    let mut output = String::new();
+    for chunk in [&input[..5], &input[5..18], &input[18..40], &input[40..]] {
+        if let Some(lines) = buffer.push(chunk) {
+            output.push_str(&pipeline.apply(&lines));
+        }
+    }
+    if let Some(line) = buffer.flush() {
+        output.push_str(&pipeline.apply(&line));
+    }
+    assert_eq!(output, expected);
+
+    assert!(pipeline.add_word_pattern_masker("[", "####").is_err());
+    Ok(())
+}
+
 #[wasm_bindgen_test]
 fn test_float_aggregation() -> Result<(), JsValue> {
     let float_aggregator = NumberAggregator::new(-1)?;
@@ -326,6 +394,27 @@ fn test_int_aggregation() -> Result<(), JsValue> {
     Ok(())
 }
 
+#[wasm_bindgen_test]
+fn test_bucket_aggregation() -> Result<(), JsValue> {
+    let age_aggregator = NumberAggregator::new_with_buckets(
+        vec![18.0, 26.0, 65.0],
+        Some(vec![
+            "0-17".to_string(),
+            "18-25".to_string(),
+            "26-64".to_string(),
+            "65+".to_string(),
+        ]),
+    )?;
+    assert_eq!(age_aggregator.apply_on_int(17), "0-17");
+    assert_eq!(age_aggregator.apply_on_float(42.5), "26-64");
+    assert_eq!(age_aggregator.apply_on_int(65), "65+");
+
+    let res = NumberAggregator::new_with_buckets(vec![65.0, 18.0], None);
+    assert!(res.is_err());
+
+    Ok(())
+}
+
 #[wasm_bindgen_test]
 fn test_time_aggregation() -> Result<(), JsValue> {
     let time_aggregator = DateAggregator::new("Hour")?;
@@ -348,6 +437,24 @@ fn test_time_aggregation() -> Result<(), JsValue> {
     Ok(())
 }
 
+#[wasm_bindgen_test]
+fn test_date_shifting() -> Result<(), JsValue> {
+    let date_shifter = DateShifter::new(&[42; 32], 30, "Day")?;
+    let admission = date_shifter.apply_on_date("patient-1", "2023-04-07T12:34:56Z")?;
+    let discharge = date_shifter.apply_on_date("patient-1", "2023-04-10T12:34:56Z")?;
+
+    let admission = DateTime::parse_from_rfc3339(&admission).unwrap();
+    let discharge = DateTime::parse_from_rfc3339(&discharge).unwrap();
+    assert_eq!((discharge - admission).num_days(), 3);
+    assert_eq!(admission.hour(), 12);
+
+    assert!(DateShifter::new(&[42; 16], 30, "Day").is_err());
+    assert!(DateShifter::new(&[42; 32], 30, "Year").is_err());
+    assert!(date_shifter.apply_on_date("patient-1", "AAAA").is_err());
+
+    Ok(())
+}
+
 #[wasm_bindgen_test]
 fn test_date_aggregation() -> Result<(), JsValue> {
     let date_aggregator = DateAggregator::new("Month")?;
@@ -386,3 +493,66 @@ fn test_int_scale() {
 
     assert!(n1 >= n2);
 }
+
+#[wasm_bindgen_test]
+fn test_reversible_scale() -> Result<(), JsValue> {
+    let scaler = NumberScaler::new_reversible(10.0, 5.0, 20.0, -50.0, &[42; 32])?;
+    assert!(scaler.is_reversible());
+    assert_eq!(scaler.revert_on_int(scaler.apply_on_int(1234))?, 1234);
+    assert_relative_eq!(
+        scaler.revert_on_float(scaler.apply_on_float(12.34))?,
+        12.34,
+        max_relative = 1e-12
+    );
+    assert!(NumberScaler::new(10.0, 5.0, 20.0, -50.0)
+        .revert_on_int(1234)
+        .is_err());
+    Ok(())
+}
+
+#[wasm_bindgen_test]
+fn test_geo_masking() -> Result<(), JsValue> {
+    let masker = GeoMasker::new(1_000.0)?;
+    let blurred = masker.apply_on_coordinates(48.8566, 2.3522)?;
+    assert_eq!(blurred.len(), 2);
+    assert!((blurred[0] - 48.8566).abs() < 0.01 && (blurred[1] - 2.3522).abs() < 0.02);
+    assert!(
+        masker
+            .apply_on_entity_coordinates("patient-1", 48.8566, 2.3522)
+            .is_err()
+    );
+
+    let masker = GeoMasker::new_consistent(1_000.0, &[42; 32])?;
+    assert!(masker.is_consistent());
+    assert_eq!(
+        masker.apply_on_entity_coordinates("patient-1", 48.8566, 2.3522)?,
+        masker.apply_on_entity_coordinates("patient-1", 48.8566, 2.3522)?
+    );
+
+    assert_eq!(GeoMasker::to_geohash(48.8566, 2.3522, 7)?, "u09tvw0");
+    assert_eq!(GeoMasker::truncate_geohash("u09tvw0", 4)?, "u09t");
+    assert!(GeoMasker::new_consistent(1_000.0, &[42; 16]).is_err());
+    assert!(GeoMasker::to_geohash(48.8566, 2.3522, 13).is_err());
+
+    Ok(())
+}
+
+#[wasm_bindgen_test]
+fn test_contact_masking() -> Result<(), JsValue> {
+    let masker = EmailMasker::new(&[42; 32], "FPE", "Category")?;
+    let masked = masker.apply("john.doe@cosmian.com")?;
+    assert!(masked.ends_with("@business.invalid"));
+    assert_eq!(masker.revert(&masked)?, "john.doe@business.invalid");
+    assert!(EmailMasker::new(&[42; 32], "FPE", "Remove").is_err());
+
+    let masker = PhoneMasker::new(1);
+    assert_eq!(masker.apply("+33 1 23 45 67 89")?, "+33 1 XX XX XX XX");
+    let masker = PhoneMasker::new_consistent(1, &[42; 32])?;
+    assert_eq!(
+        masker.apply("+33 1 23 45 67 89")?,
+        masker.apply("+33 1 23 45 67 89")?
+    );
+    assert!(PhoneMasker::new_consistent(1, &[42; 16]).is_err());
+
+    Ok(())
+}
@@ -1,7 +1,7 @@
 use wasm_bindgen::{prelude::wasm_bindgen, JsValue};
 
 use crate::core::{
-    WordMasker as WordMaskerRust, WordPatternMasker as WordPatternMaskerRust,
+    Language, WordMasker as WordMaskerRust, WordPatternMasker as WordPatternMaskerRust,
     WordTokenizer as WordTokenizerRust,
 };
 
@@ -11,10 +11,19 @@ pub struct WordMasker(WordMaskerRust);
 #[wasm_bindgen]
 impl WordMasker {
     #[wasm_bindgen(constructor)]
-    pub fn new(words_to_block: String) -> Self {
+    pub fn new(words_to_block: String, language: Option<String>) -> Result<WordMasker, JsValue> {
         let words_to_block: Vec<&str> = words_to_block.split(';').map(str::trim).collect();
 
-        Self(WordMaskerRust::new(&words_to_block))
+        Ok(Self(match language {
+            Some(language) => WordMaskerRust::new_with_language(
+                &words_to_block,
+                wasm_unwrap!(
+                    Language::try_from(language.as_str()),
+                    "Error parsing language"
+                ),
+            ),
+            None => WordMaskerRust::new(&words_to_block),
+        }))
     }
 
     pub fn apply(&self, data: &str) -> String {
@@ -28,11 +37,21 @@ pub struct WordTokenizer(WordTokenizerRust);
 #[wasm_bindgen]
 impl WordTokenizer {
     #[wasm_bindgen(constructor)]
-    pub fn new(words_to_block: String) -> Result<WordTokenizer, JsValue> {
+    pub fn new(words_to_block: String, language: Option<String>) -> Result<WordTokenizer, JsValue> {
         let words_to_block: Vec<&str> = words_to_block.split(';').map(str::trim).collect();
 
+        let word_tokenizer = match language {
+            Some(language) => WordTokenizerRust::new_with_language(
+                &words_to_block,
+                wasm_unwrap!(
+                    Language::try_from(language.as_str()),
+                    "Error parsing language"
+                ),
+            ),
+            None => WordTokenizerRust::new(&words_to_block),
+        };
         Ok(Self(wasm_unwrap!(
-            WordTokenizerRust::new(&words_to_block),
+            word_tokenizer,
             "Error initializing WordTokenizer"
         )))
     }
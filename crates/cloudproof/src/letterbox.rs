@@ -0,0 +1,219 @@
+//! Covercrypt + ECIES letterbox encryption.
+//!
+//! A letterbox ciphertext can be decrypted either by any Covercrypt user
+//! secret key matching the access policy, or by the owner of a given X25519
+//! recipient key (an auditor for instance).
+//!
+//! The payload is encrypted once using the symmetric key encapsulated in the
+//! Covercrypt header. This key is also sealed for the recipient using ECIES,
+//! the serialized Covercrypt header being used as authentication data to bind
+//! both headers together:
+//!
+//! ```txt
+//! ciphertext = LEB128(|H|) || H || LEB128(|E|) || E || AES256-GCM(K, m, ad)
+//!
+//! (K, H) = Covercrypt header encapsulation
+//! E      = ECIES(recipient_pk, K, H)
+//! ```
+
+use cloudproof_cover_crypt::reexport::{
+    cover_crypt::{
+        abe_policy::{AccessPolicy, Policy},
+        Covercrypt, EncryptedHeader, Error, MasterPublicKey, UserSecretKey,
+    },
+    crypto_core::{
+        bytes_ser_de::{Deserializer, Serializable, Serializer},
+        reexport::rand_core::SeedableRng,
+        Aes256Gcm, CsRng, Ecies, EciesSalsaSealBox, FixedSizeCBytes, SymmetricKey,
+        X25519PrivateKey, X25519PublicKey,
+    },
+};
+
+/// Parsed letterbox ciphertext.
+struct LetterboxCiphertext {
+    header_bytes: Vec<u8>,
+    sealed_key: Vec<u8>,
+    dem_ciphertext: Vec<u8>,
+}
+
+impl LetterboxCiphertext {
+    fn parse(ciphertext: &[u8]) -> Result<Self, Error> {
+        let mut de = Deserializer::new(ciphertext);
+        let header_bytes = de.read_vec()?;
+        let sealed_key = de.read_vec()?;
+        Ok(Self {
+            header_bytes,
+            sealed_key,
+            dem_ciphertext: de.finalize(),
+        })
+    }
+}
+
+/// Encrypts the given plaintext for the Covercrypt users matching the given
+/// access policy and for the owner of the given recipient public key.
+///
+/// - `authentication_data` : optional data used for authentication in the
+///   symmetric encryption of the payload
+pub fn encrypt(
+    cover_crypt: &Covercrypt,
+    policy: &Policy,
+    mpk: &MasterPublicKey,
+    access_policy: &AccessPolicy,
+    recipient_pk: &X25519PublicKey,
+    plaintext: &[u8],
+    authentication_data: Option<&[u8]>,
+) -> Result<Vec<u8>, Error> {
+    let (symmetric_key, encrypted_header) =
+        EncryptedHeader::generate(cover_crypt, policy, mpk, access_policy, None, None)?;
+    let header_bytes = encrypted_header.serialize()?;
+
+    let mut rng = CsRng::from_entropy();
+    let sealed_key =
+        EciesSalsaSealBox::encrypt(&mut rng, recipient_pk, &symmetric_key, Some(&header_bytes))?;
+
+    let dem_ciphertext = cover_crypt.encrypt(&symmetric_key, plaintext, authentication_data)?;
+
+    let mut ser = Serializer::new();
+    ser.write_vec(&header_bytes)?;
+    ser.write_vec(&sealed_key)?;
+    ser.write_array(&dem_ciphertext)?;
+    Ok(ser.finalize().to_vec())
+}
+
+/// Decrypts the given letterbox ciphertext using a Covercrypt user secret key.
+pub fn decrypt_with_user_key(
+    cover_crypt: &Covercrypt,
+    usk: &UserSecretKey,
+    ciphertext: &[u8],
+    authentication_data: Option<&[u8]>,
+) -> Result<Vec<u8>, Error> {
+    let ciphertext = LetterboxCiphertext::parse(ciphertext)?;
+    let cleartext_header =
+        EncryptedHeader::deserialize(&ciphertext.header_bytes)?.decrypt(cover_crypt, usk, None)?;
+    cover_crypt.decrypt(
+        &cleartext_header.symmetric_key,
+        &ciphertext.dem_ciphertext,
+        authentication_data,
+    )
+}
+
+/// Decrypts the given letterbox ciphertext using the recipient private key.
+pub fn decrypt_with_recipient_key(
+    cover_crypt: &Covercrypt,
+    recipient_sk: &X25519PrivateKey,
+    ciphertext: &[u8],
+    authentication_data: Option<&[u8]>,
+) -> Result<Vec<u8>, Error> {
+    let ciphertext = LetterboxCiphertext::parse(ciphertext)?;
+    let key_bytes = EciesSalsaSealBox::decrypt(
+        recipient_sk,
+        &ciphertext.sealed_key,
+        Some(&ciphertext.header_bytes),
+    )?;
+    let symmetric_key = SymmetricKey::<{ Aes256Gcm::KEY_LENGTH }>::try_from_slice(&key_bytes)?;
+    cover_crypt.decrypt(
+        &symmetric_key,
+        &ciphertext.dem_ciphertext,
+        authentication_data,
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use cloudproof_cover_crypt::reexport::cover_crypt::abe_policy::{
+        DimensionBuilder, EncryptionHint,
+    };
+
+    use super::*;
+
+    fn policy() -> Result<Policy, Error> {
+        let mut policy = Policy::new();
+        policy.add_dimension(DimensionBuilder::new(
+            "Department",
+            vec![
+                ("HR", EncryptionHint::Classic),
+                ("FIN", EncryptionHint::Classic),
+            ],
+            false,
+        ))?;
+        Ok(policy)
+    }
+
+    #[test]
+    fn test_letterbox() -> Result<(), Error> {
+        let policy = policy()?;
+        let cover_crypt = Covercrypt::default();
+        let (msk, mpk) = cover_crypt.generate_master_keys(&policy)?;
+        let hr_usk = cover_crypt.generate_user_secret_key(
+            &msk,
+            &AccessPolicy::from_boolean_expression("Department::HR")?,
+            &policy,
+        )?;
+        let fin_usk = cover_crypt.generate_user_secret_key(
+            &msk,
+            &AccessPolicy::from_boolean_expression("Department::FIN")?,
+            &policy,
+        )?;
+
+        let mut rng = CsRng::from_entropy();
+        let recipient_sk = X25519PrivateKey::new(&mut rng);
+        let recipient_pk = X25519PublicKey::from(&recipient_sk);
+        let other_sk = X25519PrivateKey::new(&mut rng);
+
+        let plaintext = b"letterbox plaintext";
+        let authentication_data = b"authentication data";
+        let ciphertext = encrypt(
+            &cover_crypt,
+            &policy,
+            &mpk,
+            &AccessPolicy::from_boolean_expression("Department::HR")?,
+            &recipient_pk,
+            plaintext,
+            Some(authentication_data),
+        )?;
+
+        // Both the Covercrypt user and the recipient can decrypt.
+        assert_eq!(
+            decrypt_with_user_key(
+                &cover_crypt,
+                &hr_usk,
+                &ciphertext,
+                Some(authentication_data)
+            )?,
+            plaintext
+        );
+        assert_eq!(
+            decrypt_with_recipient_key(
+                &cover_crypt,
+                &recipient_sk,
+                &ciphertext,
+                Some(authentication_data)
+            )?,
+            plaintext
+        );
+
+        // Wrong keys and wrong authentication data are rejected.
+        assert!(
+            decrypt_with_user_key(
+                &cover_crypt,
+                &fin_usk,
+                &ciphertext,
+                Some(authentication_data)
+            )
+            .is_err()
+        );
+        assert!(
+            decrypt_with_recipient_key(
+                &cover_crypt,
+                &other_sk,
+                &ciphertext,
+                Some(authentication_data)
+            )
+            .is_err()
+        );
+        assert!(
+            decrypt_with_recipient_key(&cover_crypt, &recipient_sk, &ciphertext, None).is_err()
+        );
+        Ok(())
+    }
+}
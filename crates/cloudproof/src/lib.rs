@@ -13,6 +13,9 @@ pub use cloudproof_findex::interfaces::ffi as findex_ffi;
 #[cfg(feature = "ffi")]
 pub use cloudproof_fpe::ffi as fpe_ffi;
 
+#[cfg(feature = "cloudproof_cover_crypt")]
+pub mod letterbox;
+
 // re-export of CoverCrypt and Crypto Core
 // so that projects that use their low level functionalities
 // do not have to depend on them directly, avoiding version conflicts.
@@ -0,0 +1,102 @@
+use cloudproof_cover_crypt::bench::generate_policy;
+use cosmian_cover_crypt::{abe_policy::AccessPolicy, Covercrypt, EncryptedHeader};
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion};
+
+/// Numbers of partitions of the benchmarked policies.
+const N_PARTITIONS: [usize; 4] = [1, 10, 100, 1000];
+
+/// Returns the disjunction of all the attributes of the single dimension of a
+/// generated policy.
+fn all_partitions(n_partitions: usize) -> AccessPolicy {
+    (0..n_partitions)
+        .map(|i| AccessPolicy::from_boolean_expression(&format!("D0::A{i}")).unwrap())
+        .reduce(|lhs, rhs| AccessPolicy::Or(Box::new(lhs), Box::new(rhs)))
+        .unwrap()
+}
+
+fn bench_keygen(c: &mut Criterion) {
+    let cover_crypt = Covercrypt::default();
+    let mut group = c.benchmark_group("CoverCrypt/keygen");
+    for n_partitions in N_PARTITIONS {
+        let policy = generate_policy(&[n_partitions]).unwrap();
+        group.bench_with_input(
+            BenchmarkId::new("master keys", n_partitions),
+            &policy,
+            |b, policy| b.iter(|| cover_crypt.generate_master_keys(policy).unwrap()),
+        );
+
+        let (msk, _) = cover_crypt.generate_master_keys(&policy).unwrap();
+        let access_policy = all_partitions(n_partitions);
+        group.bench_with_input(
+            BenchmarkId::new("user secret key", n_partitions),
+            &policy,
+            |b, policy| {
+                b.iter(|| {
+                    cover_crypt
+                        .generate_user_secret_key(&msk, &access_policy, policy)
+                        .unwrap()
+                });
+            },
+        );
+    }
+    group.finish();
+}
+
+fn bench_encryption(c: &mut Criterion) {
+    let cover_crypt = Covercrypt::default();
+    let mut group = c.benchmark_group("CoverCrypt/header");
+    for n_partitions in N_PARTITIONS {
+        let policy = generate_policy(&[n_partitions]).unwrap();
+        let (msk, mpk) = cover_crypt.generate_master_keys(&policy).unwrap();
+        let encryption_policy = all_partitions(n_partitions);
+        group.bench_with_input(
+            BenchmarkId::new("encryption", n_partitions),
+            &policy,
+            |b, policy| {
+                b.iter(|| {
+                    EncryptedHeader::generate(
+                        &cover_crypt,
+                        policy,
+                        &mpk,
+                        &encryption_policy,
+                        None,
+                        None,
+                    )
+                    .unwrap()
+                });
+            },
+        );
+
+        // The user secret key can only decrypt the last partition of the
+        // header.
+        let usk = cover_crypt
+            .generate_user_secret_key(
+                &msk,
+                &AccessPolicy::from_boolean_expression(&format!("D0::A{}", n_partitions - 1))
+                    .unwrap(),
+                &policy,
+            )
+            .unwrap();
+        let (_, encrypted_header) =
+            EncryptedHeader::generate(&cover_crypt, &policy, &mpk, &encryption_policy, None, None)
+                .unwrap();
+        group.bench_with_input(
+            BenchmarkId::new("decryption", n_partitions),
+            &encrypted_header,
+            |b, encrypted_header| {
+                b.iter(|| encrypted_header.decrypt(&cover_crypt, &usk, None).unwrap());
+            },
+        );
+    }
+    group.finish();
+}
+
+criterion_group!(
+    name = benches_cover_crypt;
+    config = Criterion::default().sample_size(10);
+    targets =
+        bench_keygen,
+        bench_encryption
+);
+
+criterion_main!(benches_cover_crypt);
@@ -0,0 +1,52 @@
+//! Prints the timings of the CoverCrypt operations for a given policy.
+//!
+//! ```sh
+//! # Policy read from a JSON file:
+//! cargo run --release --example bench_covercrypt -- 10 policy.json
+//! # Generated policy with two dimensions of 10 and 100 attributes:
+//! cargo run --release --example bench_covercrypt -- 10 10 100
+//! ```
+
+use std::{env, fs, process};
+
+use cloudproof_cover_crypt::bench::{bench_covercrypt, generate_policy};
+use cosmian_cover_crypt::abe_policy::Policy;
+
+const USAGE: &str = "usage: bench_covercrypt <iterations> (<policy file> | <n_attributes>...)";
+
+fn main() {
+    let args = env::args().skip(1).collect::<Vec<_>>();
+    let Some((n, policy_args)) = args.split_first() else {
+        eprintln!("{USAGE}");
+        process::exit(1);
+    };
+    let n = n.parse::<usize>().unwrap_or_else(|_| {
+        eprintln!("{USAGE}");
+        process::exit(1);
+    });
+
+    let n_attributes = policy_args
+        .iter()
+        .map(|arg| arg.parse::<usize>())
+        .collect::<Result<Vec<_>, _>>();
+    let policy = match (n_attributes, policy_args) {
+        (Ok(n_attributes), _) if !n_attributes.is_empty() => {
+            generate_policy(&n_attributes).expect("cannot generate the policy")
+        }
+        (_, [path]) => {
+            let bytes = fs::read(path).expect("cannot read the policy file");
+            Policy::parse_and_convert(&bytes).expect("cannot parse the policy")
+        }
+        _ => {
+            eprintln!("{USAGE}");
+            process::exit(1);
+        }
+    };
+
+    let results = bench_covercrypt(&policy, n).expect("cannot benchmark CoverCrypt");
+    println!("partitions          : {}", results.n_partitions);
+    println!("master keys         : {:?}", results.master_keygen);
+    println!("user secret key     : {:?}", results.user_keygen);
+    println!("header encryption   : {:?}", results.encrypt);
+    println!("header decryption   : {:?}", results.decrypt);
+}
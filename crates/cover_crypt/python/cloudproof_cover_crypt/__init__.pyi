@@ -1,4 +1,7 @@
-from typing import List, Optional, Tuple, Union
+from typing import Any, Iterable, List, Optional, Tuple, Union
+
+DEM_NONCE_LENGTH: int
+DEM_TAG_LENGTH: int
 
 class Attribute:
     """An attribute in a policy group is characterized by the axis policy name
@@ -95,54 +98,115 @@ class PolicyAxis:
             str
         """
 
+class PolicyMigrationReport:
+    """Coordinates removed and added by a policy edit.
+
+    Once the master keys are updated, the ciphertexts encrypted for the removed
+    coordinates become unreadable, and the user keys must be refreshed to
+    decrypt the ciphertexts encrypted for the added ones.
+    """
+
+    def removed_coordinates(self) -> List[str]:
+        """Returns the coordinates removed by the edit, as boolean expressions.
+
+        Returns:
+            List[str]
+        """
+    def added_coordinates(self) -> List[str]:
+        """Returns the coordinates added by the edit, as boolean expressions.
+
+        Returns:
+            List[str]
+        """
+    def has_unreadable_ciphertexts(self) -> bool:
+        """Whether some ciphertexts become unreadable once the master keys are updated.
+
+        Returns:
+            bool
+        """
+    def requires_user_key_refresh(self) -> bool:
+        """Whether user keys must be refreshed after updating the master keys.
+
+        Returns:
+            bool
+        """
+    def to_json(self) -> str:
+        """Formats the report to JSON.
+
+        Returns:
+            str
+        """
+
 class Policy:
     """A policy is a set of policy axes. A fixed number of attribute creations
     (revocations + additions) is allowed.
     """
 
     def __init__(self): ...
-    def add_axis(self, axis: PolicyAxis) -> None:
+    def add_axis(self, axis: PolicyAxis) -> PolicyMigrationReport:
         """Adds the given policy axis to the policy.
 
         Args:
             axis (PolicyAxis)
+
+        Returns:
+            PolicyMigrationReport: changes of the coordinates of the policy
         """
-    def remove_axis(self, axis_name: str) -> None:
+    def remove_axis(self, axis_name: str) -> PolicyMigrationReport:
         """Removes the given axis from the policy.
         Fails if there is no such axis in the policy.
 
             Args:
                 axis_name (str)
+
+            Returns:
+                PolicyMigrationReport: changes of the coordinates of the policy
         """
-    def add_attribute(self, attribute: Attribute, is_hybridized: bool) -> None:
+    def add_attribute(
+        self, attribute: Attribute, is_hybridized: bool
+    ) -> PolicyMigrationReport:
         """Adds the given attribute to the policy.
         Fails if the axis of the attribute does not exist in the policy.
 
             Args:
                 attribute (Attribute): The name and axis of the new attribute.
                 is_hybridized (bool): Whether to use post quantum keys for this attribute
+
+            Returns:
+                PolicyMigrationReport: changes of the coordinates of the policy
         """
-    def remove_attribute(self, attribute: Attribute) -> None:
+    def remove_attribute(self, attribute: Attribute) -> PolicyMigrationReport:
         """Removes the given attribute from the policy.
         Encrypting and decrypting for this attribute will no longer be possible once the keys are updated.
 
             Args:
                 attribute (Attribute)
+
+            Returns:
+                PolicyMigrationReport: changes of the coordinates of the policy
         """
-    def disable_attribute(self, attribute: Attribute) -> None:
+    def disable_attribute(self, attribute: Attribute) -> PolicyMigrationReport:
         """Marks an attribute as read only.
         The corresponding attribute key will be removed from the public key.
         But the decryption key will be kept to allow reading old ciphertext.
 
             Args:
                 attribute (Attribute)
+
+            Returns:
+                PolicyMigrationReport: changes of the coordinates of the policy
         """
-    def rename_attribute(self, attribute: Attribute, new_name: str) -> None:
+    def rename_attribute(
+        self, attribute: Attribute, new_name: str
+    ) -> PolicyMigrationReport:
         """Changes the name of an attribute.
 
         Args:
             attribute (Attribute)
             new_name (str)
+
+        Returns:
+            PolicyMigrationReport: changes of the coordinates of the policy
         """
     def attributes(self) -> List[Attribute]:
         """Returns the list of Attributes of this Policy.
@@ -160,6 +224,23 @@ class Policy:
         Returns:
             List[int]
         """
+    def hybridized_attributes(self) -> List[Attribute]:
+        """Returns the list of Attributes of this Policy created with a
+        hybridized encryption hint.
+
+        Returns:
+            List[Attribute]
+        """
+    def is_post_quantum(self, access_policy_str: str) -> bool:
+        """Checks whether all the encapsulations of the ciphertexts encrypted
+        under the given access policy are hybridized (post-quantum).
+
+        Args:
+            access_policy_str (str): access policy the data would be encrypted under
+
+        Returns:
+            bool
+        """
     def attribute_current_value(self, attribute: Attribute) -> int:
         """Retrieves the current value of an attribute.
 
@@ -181,6 +262,10 @@ class Policy:
         Returns:
             Policy
         """
+    def __copy__(self) -> Policy: ...
+    def __deepcopy__(self, memo: Any) -> Policy: ...
+    def __getstate__(self) -> bytes: ...
+    def __setstate__(self, state: bytes) -> None: ...
     @staticmethod
     def from_bytes(policy_json: bytes) -> Policy:
         """Reads policy from bytes.
@@ -191,6 +276,20 @@ class Policy:
         Returns:
             Policy
         """
+    @staticmethod
+    def from_json_spec(spec: str) -> Policy:
+        """Builds the policy described by the given JSON specification.
+
+        The specification maps each axis name to the list of its attributes.
+        Hierarchical axis names are suffixed with `::<` and hybridized
+        attribute names with `::+`.
+
+        Args:
+            spec (str): e.g. `{"Department": ["HR", "FIN::+"]}`
+
+        Returns:
+            Policy
+        """
 
 class MasterSecretKey:
     def to_bytes(self) -> bytes:
@@ -209,6 +308,16 @@ class MasterSecretKey:
         Returns:
             MasterSecretKey
         """
+    def deep_copy(self) -> MasterSecretKey:
+        """Performs deep copy of the key.
+
+        Returns:
+            MasterSecretKey
+        """
+    def __copy__(self) -> MasterSecretKey: ...
+    def __deepcopy__(self, memo: Any) -> MasterSecretKey: ...
+    def __getstate__(self) -> bytes: ...
+    def __setstate__(self, state: bytes) -> None: ...
 
 class MasterPublicKey:
     def to_bytes(self) -> bytes:
@@ -227,6 +336,16 @@ class MasterPublicKey:
         Returns:
             MasterPublicKey
         """
+    def deep_copy(self) -> MasterPublicKey:
+        """Performs deep copy of the key.
+
+        Returns:
+            MasterPublicKey
+        """
+    def __copy__(self) -> MasterPublicKey: ...
+    def __deepcopy__(self, memo: Any) -> MasterPublicKey: ...
+    def __getstate__(self) -> bytes: ...
+    def __setstate__(self, state: bytes) -> None: ...
 
 class UserSecretKey:
     def to_bytes(self) -> bytes:
@@ -245,6 +364,16 @@ class UserSecretKey:
         Returns:
             UserSecretKey
         """
+    def deep_copy(self) -> UserSecretKey:
+        """Performs deep copy of the key.
+
+        Returns:
+            UserSecretKey
+        """
+    def __copy__(self) -> UserSecretKey: ...
+    def __deepcopy__(self, memo: Any) -> UserSecretKey: ...
+    def __getstate__(self) -> bytes: ...
+    def __setstate__(self, state: bytes) -> None: ...
 
 class SymmetricKey:
     def to_bytes(self) -> bytes:
@@ -264,6 +393,82 @@ class SymmetricKey:
             SymmetricKey
         """
 
+class ColumnEncryptor:
+    """Encrypts the cells of a column under a single header: the header is
+    generated once for the access policy of the column, then each cell is
+    encrypted by the DEM.
+
+    Args:
+        policy (Policy): global policy
+        access_policy_str (str): access policy of the column
+        public_key (MasterPublicKey): CoverCrypt public key
+        header_metadata (Optional[bytes]): additional data to encrypt with the header
+        authentication_data (Optional[bytes]): authentication data of the header
+    """
+
+    def __init__(
+        self,
+        policy: Policy,
+        access_policy_str: str,
+        public_key: MasterPublicKey,
+        header_metadata: Optional[bytes] = ...,
+        authentication_data: Optional[bytes] = ...,
+    ): ...
+    @property
+    def encrypted_header(self) -> bytes:
+        """Encrypted header of the column, needed to decrypt its cells."""
+    def encrypt(
+        self,
+        cells: Iterable[Union[bytes, str, float, None]],
+        authentication_data: Optional[Iterable[Union[bytes, str]]] = ...,
+    ) -> List[Optional[bytes]]:
+        """Encrypts the given cells in parallel.
+
+        Args:
+            cells (Iterable[Union[bytes, str, float, None]]): cells of the column,
+                e.g. a pandas Series; `None` and `NaN` cells are kept null
+            authentication_data (Optional[Iterable[Union[bytes, str]]]): authentication data of each cell,
+                e.g. the record IDs binding each cell to its record
+
+        Returns:
+            List[Optional[bytes]]: encrypted cells, in the order of the cells
+        """
+
+class ColumnDecryptor:
+    """Decrypts the cells of a column encrypted by a `ColumnEncryptor`: the
+    header is decrypted once, then each cell is decrypted by the DEM.
+
+    Args:
+        usk (UserSecretKey): user secret key
+        encrypted_header (bytes): encrypted header of the column
+        authentication_data (Optional[bytes]): authentication data of the header
+    """
+
+    def __init__(
+        self,
+        usk: UserSecretKey,
+        encrypted_header: bytes,
+        authentication_data: Optional[bytes] = ...,
+    ): ...
+    @property
+    def header_metadata(self) -> bytes:
+        """Metadata encrypted with the header of the column."""
+    def decrypt(
+        self,
+        cells: Iterable[Union[bytes, float, None]],
+        authentication_data: Optional[Iterable[Union[bytes, str]]] = ...,
+    ) -> List[Optional[bytes]]:
+        """Decrypts the given cells in parallel.
+
+        Args:
+            cells (Iterable[Union[bytes, float, None]]): encrypted cells,
+                e.g. a pandas Series; `None` and `NaN` cells are kept null
+            authentication_data (Optional[Iterable[Union[bytes, str]]]): authentication data given upon encryption
+
+        Returns:
+            List[Optional[bytes]]: decrypted cells, in the order of the cells
+        """
+
 class CoverCrypt:
     """The engine is the main entry point for the core functionalities."""
 
@@ -305,6 +510,18 @@ class CoverCrypt:
             msk (MasterSecretKey): master secret key
             mpk (MasterPublicKey): master public key
         """
+    def rekey_all_master_keys(
+        self, policy: Policy, msk: MasterSecretKey, mpk: MasterPublicKey
+    ):
+        """Generate new keys for all the partitions of the master keys, without changing
+        the policy (periodic rotation). Unlike `rekey_master_keys` (policy rotation),
+        all the user keys will need to be refreshed after this step.
+
+        Args:
+            policy (Policy): global policy
+            msk (MasterSecretKey): master secret key
+            mpk (MasterPublicKey): master public key
+        """
     def prune_master_secret_key(
         self, access_policy_str: str, policy: Policy, msk: MasterSecretKey
     ):
@@ -345,6 +562,20 @@ class CoverCrypt:
             msk (MasterSecretKey): master secret key
             keep_old_accesses (bool): whether access to old partitions (i.e. before rotation) should be kept
         """
+    def refresh_user_secret_key_with_rotations(
+        self,
+        usk: UserSecretKey,
+        msk: MasterSecretKey,
+        keep_old_rotations: int,
+    ):
+        """Refreshes the user key according to the given master key, keeping at most
+        `keep_old_rotations` rotations older than the current one for each partition.
+
+        Args:
+            usk (UserSecretKey): the user key to refresh
+            msk (MasterSecretKey): master secret key
+            keep_old_rotations (int): number of old rotations to keep access to
+        """
     def encrypt_symmetric_block(
         self,
         symmetric_key: SymmetricKey,
@@ -377,6 +608,44 @@ class CoverCrypt:
         Returns:
             bytes: plaintext bytes
         """
+    @staticmethod
+    def split_symmetric_ciphertext(ciphertext: bytes) -> Tuple[bytes, bytes, bytes]:
+        """Splits a symmetric ciphertext into its nonce, encrypted data and tag,
+        e.g. to decrypt it using `cryptography`:
+        `AESGCM(key.to_bytes()).decrypt(nonce, encrypted_data + tag, aad)`
+
+        Args:
+            ciphertext (bytes): nonce || encrypted data || tag
+
+        Returns:
+            Tuple[bytes, bytes, bytes]: (nonce, encrypted data, tag)
+        """
+    @staticmethod
+    def join_symmetric_ciphertext(
+        nonce: bytes, encrypted_data: bytes, tag: bytes
+    ) -> bytes:
+        """Concatenates a nonce, encrypted data and tag into a symmetric ciphertext,
+        e.g. produced by `cryptography`:
+        `encrypted_data + tag = AESGCM(key.to_bytes()).encrypt(nonce, data, aad)`
+
+        Args:
+            nonce (bytes): 12-byte nonce
+            encrypted_data (bytes): encrypted data
+            tag (bytes): 16-byte authentication tag
+
+        Returns:
+            bytes: ciphertext bytes
+        """
+    @staticmethod
+    def split_hybrid_ciphertext(encrypted_bytes: bytes) -> Tuple[bytes, bytes]:
+        """Splits a hybrid ciphertext into the encrypted header and the symmetric ciphertext.
+
+        Args:
+            encrypted_bytes (bytes): encrypted header || symmetric ciphertext
+
+        Returns:
+            Tuple[bytes, bytes]: (encrypted header, symmetric ciphertext)
+        """
     def encrypt_header(
         self,
         policy: Policy,
@@ -424,6 +693,7 @@ class CoverCrypt:
         plaintext: bytes,
         header_metadata: Optional[bytes] = ...,
         authentication_data: Optional[bytes] = ...,
+        header_bucket_size: Optional[int] = ...,
     ) -> bytes:
         """Hybrid encryption. Concatenates the encrypted header and the symmetric
         ciphertext.
@@ -434,7 +704,10 @@ class CoverCrypt:
             pk (MasterPublicKey): CoverCrypt public key
             plaintext (bytes): plaintext to encrypt using the DEM
             header_metadata (Optional[bytes]): additional data to symmetrically encrypt in the header
-            authentication_data (Optional[bytes]): authentication data to use in symmetric encryptions
+            authentication_data (Optional[bytes]): authentication data to use in symmetric encryptions,
+                e.g. the record ID. It is not part of the ciphertext and should be given upon decryption.
+            header_bucket_size (Optional[int]): size of the buckets the encrypted header is padded to,
+                hiding the number of partitions of the access policy. It should be given upon decryption.
 
         Returns:
             bytes: ciphertext bytes
@@ -444,14 +717,157 @@ class CoverCrypt:
         usk: UserSecretKey,
         encrypted_bytes: bytes,
         authentication_data: Optional[bytes] = ...,
+        header_bucket_size: Optional[int] = ...,
     ) -> Tuple[bytes, bytes]:
         """Hybrid decryption.
 
         Args:
             usk (UserSecretKey): user secret key
             encrypted_bytes (bytes): encrypted header || symmetric ciphertext
-            authentication_data (Optional[bytes]): authentication data to use in symmetric decryptions
+            authentication_data (Optional[bytes]): authentication data given upon encryption,
+                the decryption fails otherwise
+            header_bucket_size (Optional[int]): header bucket size given upon encryption
 
         Returns:
             Tuple[bytes, bytes]: (plaintext bytes, header metadata bytes)
         """
+    def encrypt_with_cleartext_metadata(
+        self,
+        policy: Policy,
+        access_policy_str: str,
+        pk: MasterPublicKey,
+        plaintext: bytes,
+        cleartext_metadata: bytes,
+        header_metadata: Optional[bytes] = ...,
+        authentication_data: Optional[bytes] = ...,
+    ) -> bytes:
+        """Hybrid encryption. Prepends the given cleartext metadata to the
+        encrypted header and the symmetric ciphertext.
+
+        The cleartext metadata can be read without any key but is authenticated
+        upon decryption.
+
+        Args:
+            policy (Policy): global policy
+            access_policy_str (str): access policy
+            pk (MasterPublicKey): CoverCrypt public key
+            plaintext (bytes): plaintext to encrypt using the DEM
+            cleartext_metadata (bytes): metadata to authenticate but not encrypt
+            header_metadata (Optional[bytes]): additional data to symmetrically encrypt in the header
+            authentication_data (Optional[bytes]): authentication data to use in symmetric encryptions
+
+        Returns:
+            bytes: ciphertext bytes
+        """
+    @staticmethod
+    def read_cleartext_metadata(encrypted_bytes: bytes) -> bytes:
+        """Reads the cleartext metadata of a ciphertext produced by
+        `encrypt_with_cleartext_metadata` without any key.
+
+        This metadata is only authenticated upon decryption.
+
+        Args:
+            encrypted_bytes (bytes): cleartext metadata || encrypted header || symmetric ciphertext
+
+        Returns:
+            bytes: cleartext metadata bytes
+        """
+    def decrypt_with_cleartext_metadata(
+        self,
+        usk: UserSecretKey,
+        encrypted_bytes: bytes,
+        authentication_data: Optional[bytes] = ...,
+    ) -> Tuple[bytes, bytes, bytes]:
+        """Hybrid decryption of a ciphertext produced by
+        `encrypt_with_cleartext_metadata`.
+
+        Args:
+            usk (UserSecretKey): user secret key
+            encrypted_bytes (bytes): cleartext metadata || encrypted header || symmetric ciphertext
+            authentication_data (Optional[bytes]): authentication data to use in symmetric decryptions
+
+        Returns:
+            Tuple[bytes, bytes, bytes]: (plaintext bytes, header metadata bytes, cleartext metadata bytes)
+        """
+    def decrypt_with_keys(
+        self,
+        usks: List[UserSecretKey],
+        encrypted_bytes: bytes,
+        authentication_data: Optional[bytes] = ...,
+    ) -> Tuple[int, bytes, bytes]:
+        """Hybrid decryption using the first of the given user secret keys
+        able to decrypt the encrypted header.
+
+        Args:
+            usks (List[UserSecretKey]): candidate user secret keys
+            encrypted_bytes (bytes): encrypted header || symmetric ciphertext
+            authentication_data (Optional[bytes]): authentication data to use in symmetric decryptions
+
+        Returns:
+            Tuple[int, bytes, bytes]: (index of the key used, plaintext bytes, header metadata bytes)
+        """
+    def decrypt_bulk(
+        self,
+        usk: UserSecretKey,
+        ciphertexts: List[bytes],
+        authentication_data: Optional[List[bytes]] = ...,
+    ) -> List[bytes]:
+        """Hybrid decryption of many ciphertexts using the same user secret key.
+        The ciphertexts are decrypted in parallel, releasing the GIL.
+
+        Args:
+            usk (UserSecretKey): user secret key
+            ciphertexts (List[bytes]): encrypted headers || symmetric ciphertexts
+            authentication_data (Optional[List[bytes]]): authentication data given upon encryption, one per ciphertext
+
+        Returns:
+            List[bytes]: plaintexts, in the order of the ciphertexts
+        """
+    def user_key_coordinates(
+        self, usk: UserSecretKey, mpk: MasterPublicKey, policy: Policy
+    ) -> List[List[Attribute]]:
+        """Lists the coordinates of the policy the given user secret key can decrypt.
+        No ciphertext is needed.
+
+        Args:
+            usk (UserSecretKey): user secret key to audit
+            mpk (MasterPublicKey): master public key
+            policy (Policy): global policy
+
+        Returns:
+            List[List[Attribute]]: coordinates, each given as the list of its attributes
+        """
+    def user_key_rotations(
+        self, usk: UserSecretKey, policy: Policy
+    ) -> List[Tuple[List[Attribute], int]]:
+        """Lists the number of rotations of each coordinate of the policy the given
+        user secret key can decrypt: the current one upon its last refresh and the
+        older ones kept.
+
+        Args:
+            usk (UserSecretKey): user secret key to audit
+            policy (Policy): global policy
+
+        Returns:
+            List[Tuple[List[Attribute], int]]: coordinates, each given as the list of
+            its attributes, along with their number of rotations
+        """
+    def can_decrypt(
+        self,
+        usk: UserSecretKey,
+        mpk: MasterPublicKey,
+        encryption_policy_str: str,
+        policy: Policy,
+    ) -> bool:
+        """Checks whether the given user secret key can decrypt the ciphertexts
+        encrypted under the given access policy. No ciphertext is needed.
+
+        Args:
+            usk (UserSecretKey): user secret key to audit
+            mpk (MasterPublicKey): master public key
+            encryption_policy_str (str): encryption access policy
+            policy (Policy): global policy
+
+        Returns:
+            bool
+        """
@@ -1,8 +1,18 @@
 # -*- coding: utf-8 -*-
+import copy
+import json
+import os
+import pickle
 import unittest
 
+from cryptography.hazmat.primitives.ciphers.aead import AESGCM
+
 from cloudproof_cover_crypt import (
+    DEM_NONCE_LENGTH,
+    DEM_TAG_LENGTH,
     Attribute,
+    ColumnDecryptor,
+    ColumnEncryptor,
     CoverCrypt,
     MasterPublicKey,
     MasterSecretKey,
@@ -151,6 +161,42 @@ class TestPolicy(unittest.TestCase):
         policy.remove_axis('Secrecy')
         self.assertEqual(len(policy.attributes()), 0)
 
+    def test_policy_migration_report(self) -> None:
+        policy = self.policy()
+
+        # Renaming an attribute changes no coordinate
+        report = policy.rename_attribute(Attribute('Country', 'Spain'), 'Espagne')
+        self.assertEqual(report.removed_coordinates(), [])
+        self.assertEqual(report.added_coordinates(), [])
+        self.assertFalse(report.requires_user_key_refresh())
+
+        # Adding an attribute adds its coordinates
+        report = policy.add_attribute(Attribute('Country', 'Japan'), False)
+        self.assertEqual(
+            report.added_coordinates(),
+            [
+                'Country::Japan && Secrecy::High',
+                'Country::Japan && Secrecy::Low',
+                'Country::Japan && Secrecy::Medium',
+            ],
+        )
+        self.assertFalse(report.has_unreadable_ciphertexts())
+        self.assertTrue(report.requires_user_key_refresh())
+
+        # Removing an attribute makes the ciphertexts of its coordinates unreadable
+        report = policy.remove_attribute(Attribute('Country', 'Espagne'))
+        self.assertEqual(len(report.removed_coordinates()), 3)
+        self.assertTrue(report.has_unreadable_ciphertexts())
+
+        # Removing an axis changes all the coordinates
+        report = policy.remove_axis('Secrecy')
+        self.assertEqual(len(report.removed_coordinates()), 12)
+        self.assertEqual(len(report.added_coordinates()), 4)
+        self.assertEqual(
+            json.loads(report.to_json())['removed_coordinates'],
+            report.removed_coordinates(),
+        )
+
     def test_policy_cloning_serialization(self) -> None:
         policy = self.policy()
 
@@ -166,6 +212,37 @@ class TestPolicy(unittest.TestCase):
         with self.assertRaises(Exception):
             Policy.from_bytes('wrong data format'.encode())
 
+    def test_policy_from_json_spec(self) -> None:
+        policy = Policy.from_json_spec(
+            '{"Secrecy::<": ["Low", "Medium", "High::+"], "Country": ["France", "Germany"]}'
+        )
+        self.assertEqual(len(policy.attributes()), 5)
+
+        with self.assertRaisesRegex(Exception, 'attribute `France` of axis `Country`'):
+            Policy.from_json_spec('{"Country": ["France", "France"]}')
+
+    def test_is_post_quantum(self) -> None:
+        policy = Policy.from_json_spec(
+            '{"Secrecy::<": ["Low", "High::+"], "Country": ["France", "Germany"]}'
+        )
+        self.assertEqual(
+            [attribute.to_string() for attribute in policy.hybridized_attributes()],
+            ['Secrecy::High'],
+        )
+        self.assertTrue(policy.is_post_quantum('Country::France && Secrecy::High'))
+        self.assertFalse(policy.is_post_quantum('Country::France'))
+
+    def test_policy_pickling_deep_copy(self) -> None:
+        policy = self.policy()
+
+        unpickled_policy = pickle.loads(pickle.dumps(policy))
+        self.assertIsInstance(unpickled_policy, Policy)
+        self.assertEqual(unpickled_policy.to_bytes(), policy.to_bytes())
+
+        copy_policy = copy.deepcopy(policy)
+        self.assertIsInstance(copy_policy, Policy)
+        self.assertEqual(copy_policy.to_bytes(), policy.to_bytes())
+
 
 class TestKeyGeneration(unittest.TestCase):
     def setUp(self) -> None:
@@ -220,6 +297,35 @@ class TestKeyGeneration(unittest.TestCase):
         with self.assertRaises(Exception):
             UserSecretKey.from_bytes(b'wrong data')
 
+    def test_key_pickling_deep_copy(self) -> None:
+        target_policy = 'Secrecy::High && Country::France'
+        usk = self.cc.generate_user_secret_key(
+            self.msk,
+            target_policy,
+            self.policy,
+        )
+
+        for key in [self.msk, self.pk, usk]:
+            unpickled_key = pickle.loads(pickle.dumps(key))
+            self.assertIsInstance(unpickled_key, type(key))
+            self.assertEqual(unpickled_key.to_bytes(), key.to_bytes())
+
+            copy_key = copy.deepcopy(key)
+            self.assertIsInstance(copy_key, type(key))
+            self.assertEqual(copy_key.to_bytes(), key.to_bytes())
+
+        # unpickled keys are still usable
+        pk = pickle.loads(pickle.dumps(self.pk))
+        usk = pickle.loads(pickle.dumps(usk))
+        ciphertext = self.cc.encrypt(
+            pickle.loads(pickle.dumps(self.policy)),
+            target_policy,
+            pk,
+            b'plaintext',
+        )
+        plaintext, _ = self.cc.decrypt(copy.deepcopy(usk), ciphertext)
+        self.assertEqual(plaintext, b'plaintext')
+
     def test_sym_key_serialization(self) -> None:
         sym_key, _ = self.cc.encrypt_header(
             self.policy,
@@ -235,6 +341,38 @@ class TestKeyGeneration(unittest.TestCase):
             SymmetricKey.from_bytes(b'wrong data')
 
 
+    def test_user_key_access_audit(self) -> None:
+        usk = self.cc.generate_user_secret_key(
+            self.msk, 'Secrecy::Medium && Country::France', self.policy
+        )
+
+        # Secrecy is hierarchical: the user can read the lower levels
+        coordinates = self.cc.user_key_coordinates(usk, self.pk, self.policy)
+        self.assertEqual(
+            sorted(
+                ' && '.join(attribute.to_string() for attribute in coordinate)
+                for coordinate in coordinates
+            ),
+            [
+                'Country::France && Secrecy::Low',
+                'Country::France && Secrecy::Medium',
+            ],
+        )
+
+        self.assertTrue(
+            self.cc.can_decrypt(
+                usk, self.pk, 'Secrecy::Low && Country::France', self.policy
+            )
+        )
+        self.assertFalse(
+            self.cc.can_decrypt(
+                usk, self.pk, 'Secrecy::High && Country::France', self.policy
+            )
+        )
+        self.assertFalse(
+            self.cc.can_decrypt(usk, self.pk, 'Country::Spain', self.policy)
+        )
+
 class TestEncryption(unittest.TestCase):
     def setUp(self) -> None:
         country_axis = PolicyAxis(
@@ -302,6 +440,201 @@ class TestEncryption(unittest.TestCase):
         with self.assertRaises(Exception):
             self.cc.decrypt(sec_low_fr_sp_user, ciphertext, self.authenticated_data)
 
+    def test_encryption_decryption_with_authentication_data(self) -> None:
+        record_id = b'record 1'
+        ciphertext = self.cc.encrypt(
+            self.policy,
+            'Secrecy::High && Country::France',
+            self.pk,
+            self.plaintext,
+            authentication_data=record_id,
+        )
+
+        sec_high_fr_user = self.cc.generate_user_secret_key(
+            self.msk, 'Secrecy::High && Country::France', self.policy
+        )
+
+        plaintext, _ = self.cc.decrypt(
+            sec_high_fr_user, ciphertext, authentication_data=record_id
+        )
+        self.assertEqual(plaintext, self.plaintext)
+
+        # The ciphertext cannot be decrypted as another record.
+        with self.assertRaises(Exception):
+            self.cc.decrypt(sec_high_fr_user, ciphertext, b'record 2')
+        with self.assertRaises(Exception):
+            self.cc.decrypt(sec_high_fr_user, ciphertext)
+
+    def test_encryption_decryption_with_header_padding(self) -> None:
+        narrow_ciphertext = self.cc.encrypt(
+            self.policy,
+            'Secrecy::High && Country::France',
+            self.pk,
+            self.plaintext,
+            header_bucket_size=4096,
+        )
+        broad_ciphertext = self.cc.encrypt(
+            self.policy,
+            'Secrecy::High && (Country::France || Country::Spain)',
+            self.pk,
+            self.plaintext,
+            header_bucket_size=4096,
+        )
+        # The ciphertext length does not depend on the access policy.
+        self.assertEqual(len(narrow_ciphertext), len(broad_ciphertext))
+
+        sec_high_fr_user = self.cc.generate_user_secret_key(
+            self.msk, 'Secrecy::High && Country::France', self.policy
+        )
+        plaintext, _ = self.cc.decrypt(
+            sec_high_fr_user, narrow_ciphertext, header_bucket_size=4096
+        )
+        self.assertEqual(plaintext, self.plaintext)
+
+        # The bucket size given upon encryption is needed to decrypt.
+        with self.assertRaises(Exception):
+            self.cc.decrypt(sec_high_fr_user, narrow_ciphertext)
+
+    def test_encryption_decryption_with_cleartext_metadata(self) -> None:
+        cleartext_metadata = b'application/json'
+        ciphertext = self.cc.encrypt_with_cleartext_metadata(
+            self.policy,
+            'Secrecy::High && Country::France',
+            self.pk,
+            self.plaintext,
+            cleartext_metadata,
+            self.header_metadata,
+            self.authenticated_data,
+        )
+
+        # No key is needed to read the cleartext metadata
+        self.assertEqual(
+            CoverCrypt.read_cleartext_metadata(ciphertext), cleartext_metadata
+        )
+
+        sec_high_fr_user = self.cc.generate_user_secret_key(
+            self.msk, 'Secrecy::High && Country::France', self.policy
+        )
+
+        # Successful decryption
+        plaintext, header_metadata, metadata = self.cc.decrypt_with_cleartext_metadata(
+            sec_high_fr_user, ciphertext, self.authenticated_data
+        )
+        self.assertEqual(plaintext, self.plaintext)
+        self.assertEqual(header_metadata, bytes(self.header_metadata))
+        self.assertEqual(metadata, cleartext_metadata)
+
+        # Tampered cleartext metadata
+        tampered_ciphertext = bytearray(ciphertext)
+        tampered_ciphertext[1] ^= 1
+        with self.assertRaises(Exception):
+            self.cc.decrypt_with_cleartext_metadata(
+                sec_high_fr_user, bytes(tampered_ciphertext), self.authenticated_data
+            )
+
+    def test_decryption_with_keys(self) -> None:
+        ciphertext = self.cc.encrypt(
+            self.policy,
+            'Secrecy::Medium && Country::Spain',
+            self.pk,
+            self.plaintext,
+            self.header_metadata,
+            self.authenticated_data,
+        )
+
+        sec_high_fr_user = self.cc.generate_user_secret_key(
+            self.msk, 'Secrecy::High && Country::France', self.policy
+        )
+        sec_high_sp_user = self.cc.generate_user_secret_key(
+            self.msk, 'Secrecy::High && Country::Spain', self.policy
+        )
+
+        # The second key is able to decrypt the ciphertext
+        key_index, plaintext, header_metadata = self.cc.decrypt_with_keys(
+            [sec_high_fr_user, sec_high_sp_user], ciphertext, self.authenticated_data
+        )
+        self.assertEqual(key_index, 1)
+        self.assertEqual(plaintext, self.plaintext)
+        self.assertEqual(header_metadata, bytes(self.header_metadata))
+
+        # None of the keys is able to decrypt the ciphertext
+        with self.assertRaises(Exception):
+            self.cc.decrypt_with_keys(
+                [sec_high_fr_user], ciphertext, self.authenticated_data
+            )
+
+    def test_decryption_bulk(self) -> None:
+        plaintexts = [f'cell {i}'.encode() for i in range(100)]
+        authentication_data = [f'record {i}'.encode() for i in range(100)]
+        ciphertexts = [
+            self.cc.encrypt(
+                self.policy,
+                'Secrecy::Medium && Country::France',
+                self.pk,
+                plaintext,
+                None,
+                record_id,
+            )
+            for plaintext, record_id in zip(plaintexts, authentication_data)
+        ]
+
+        sec_high_fr_user = self.cc.generate_user_secret_key(
+            self.msk, 'Secrecy::High && Country::France', self.policy
+        )
+        self.assertEqual(
+            self.cc.decrypt_bulk(sec_high_fr_user, ciphertexts, authentication_data),
+            plaintexts,
+        )
+
+        # The authentication data are bound to their ciphertext
+        with self.assertRaises(Exception):
+            self.cc.decrypt_bulk(
+                sec_high_fr_user, ciphertexts, authentication_data[::-1]
+            )
+
+        # The user cannot decrypt all the ciphertexts
+        sec_high_sp_user = self.cc.generate_user_secret_key(
+            self.msk, 'Secrecy::High && Country::Spain', self.policy
+        )
+        with self.assertRaises(Exception):
+            self.cc.decrypt_bulk(sec_high_sp_user, ciphertexts, authentication_data)
+
+    def test_column_encryption_decryption(self) -> None:
+        cells = [f'cell {i}' if i % 10 != 0 else None for i in range(100)]
+        cells[5] = float('nan')
+        record_ids = [f'record {i}'.encode() for i in range(100)]
+
+        encryptor = ColumnEncryptor(
+            self.policy,
+            'Secrecy::Medium && Country::France',
+            self.pk,
+            self.header_metadata,
+        )
+        encrypted_cells = encryptor.encrypt(cells, record_ids)
+        self.assertIsNone(encrypted_cells[0])
+        self.assertIsNone(encrypted_cells[5])
+
+        sec_high_fr_user = self.cc.generate_user_secret_key(
+            self.msk, 'Secrecy::High && Country::France', self.policy
+        )
+        decryptor = ColumnDecryptor(sec_high_fr_user, encryptor.encrypted_header)
+        self.assertEqual(decryptor.header_metadata, self.header_metadata)
+        self.assertEqual(
+            decryptor.decrypt(encrypted_cells, record_ids),
+            [cell.encode() if isinstance(cell, str) else None for cell in cells],
+        )
+
+        # The cells are bound to their record
+        with self.assertRaises(Exception):
+            decryptor.decrypt(encrypted_cells, record_ids[::-1])
+
+        # The user cannot decrypt the header of the column
+        sec_high_sp_user = self.cc.generate_user_secret_key(
+            self.msk, 'Secrecy::High && Country::Spain', self.policy
+        )
+        with self.assertRaises(Exception):
+            ColumnDecryptor(sec_high_sp_user, encryptor.encrypted_header)
+
     def test_rekey_prune_encryption_decryption(self) -> None:
         target_policy = 'Secrecy::High && Country::France'
         ciphertext = self.cc.encrypt(
@@ -379,6 +712,86 @@ class TestEncryption(unittest.TestCase):
         plaintext, _ = self.cc.decrypt(user2, new_ciphertext, self.authenticated_data)
         self.assertEqual(bytes(plaintext), new_plaintext)
 
+    def test_rekey_all_master_keys(self) -> None:
+        ciphertext = self.cc.encrypt(
+            self.policy,
+            'Secrecy::Low && Country::Germany',
+            self.pk,
+            self.plaintext,
+            self.header_metadata,
+            self.authenticated_data,
+        )
+        user = self.cc.generate_user_secret_key(
+            self.msk,
+            'Secrecy::Low && Country::Germany',
+            self.policy,
+        )
+
+        self.cc.rekey_all_master_keys(self.policy, self.msk, self.pk)
+        new_ciphertext = self.cc.encrypt(
+            self.policy,
+            'Secrecy::Low && Country::Germany',
+            self.pk,
+            self.plaintext,
+            self.header_metadata,
+            self.authenticated_data,
+        )
+
+        # Every partition was rekeyed: the user key must be refreshed
+        with self.assertRaises(Exception):
+            self.cc.decrypt(user, new_ciphertext, self.authenticated_data)
+
+        self.cc.refresh_user_secret_key(user, self.msk, keep_old_accesses=True)
+        for c in [ciphertext, new_ciphertext]:
+            plaintext, _ = self.cc.decrypt(user, c, self.authenticated_data)
+            self.assertEqual(bytes(plaintext), self.plaintext)
+
+    def test_refresh_user_secret_key_with_rotations(self) -> None:
+        target_policy = 'Secrecy::Low && Country::Germany'
+        user = self.cc.generate_user_secret_key(self.msk, target_policy, self.policy)
+
+        # One ciphertext per rotation, the oldest first
+        ciphertexts = []
+        for _ in range(2):
+            ciphertexts.append(
+                self.cc.encrypt(
+                    self.policy,
+                    target_policy,
+                    self.pk,
+                    self.plaintext,
+                    self.header_metadata,
+                    self.authenticated_data,
+                )
+            )
+            self.cc.rekey_master_keys(target_policy, self.policy, self.msk, self.pk)
+        ciphertexts.append(
+            self.cc.encrypt(
+                self.policy,
+                target_policy,
+                self.pk,
+                self.plaintext,
+                self.header_metadata,
+                self.authenticated_data,
+            )
+        )
+
+        # Only the previous rotation is kept
+        self.cc.refresh_user_secret_key_with_rotations(user, self.msk, 1)
+        with self.assertRaises(Exception):
+            self.cc.decrypt(user, ciphertexts[0], self.authenticated_data)
+        for c in ciphertexts[1:]:
+            plaintext, _ = self.cc.decrypt(user, c, self.authenticated_data)
+            self.assertEqual(bytes(plaintext), self.plaintext)
+
+        rotations = self.cc.user_key_rotations(user, self.policy)
+        self.assertEqual(
+            [
+                (' && '.join(attribute.to_string() for attribute in coordinate), n)
+                for coordinate, n in rotations
+            ],
+            [('Country::Germany && Secrecy::Low', 2)],
+        )
+
     def test_decomposed_encryption_decryption(self) -> None:
         """Test individually the header and the symmetric encryption/decryption"""
         sym_key, enc_header = self.cc.encrypt_header(
@@ -407,6 +820,56 @@ class TestEncryption(unittest.TestCase):
         )
         self.assertEqual(bytes(decrypted_data), self.plaintext)
 
+    def test_dem_with_cryptography(self) -> None:
+        """Test the symmetric encryption using the `cryptography` library"""
+        sym_key, enc_header = self.cc.encrypt_header(
+            self.policy, 'Secrecy::Medium && Country::UK', self.pk
+        )
+        aesgcm = AESGCM(sym_key.to_bytes())
+
+        # Ciphertexts produced by CoverCrypt are decrypted by `cryptography`.
+        encrypted_bytes = self.cc.encrypt(
+            self.policy,
+            'Secrecy::Medium && Country::UK',
+            self.pk,
+            self.plaintext,
+            None,
+            self.authenticated_data,
+        )
+        header, ciphertext = CoverCrypt.split_hybrid_ciphertext(encrypted_bytes)
+        sec_med_uk_user = self.cc.generate_user_secret_key(
+            self.msk, 'Secrecy::Medium && Country::UK', self.policy
+        )
+        decrypted_sym_key, _ = self.cc.decrypt_header(
+            sec_med_uk_user, header, self.authenticated_data
+        )
+        nonce, encrypted_data, tag = CoverCrypt.split_symmetric_ciphertext(ciphertext)
+        self.assertEqual(len(nonce), DEM_NONCE_LENGTH)
+        self.assertEqual(len(tag), DEM_TAG_LENGTH)
+        self.assertEqual(
+            AESGCM(decrypted_sym_key.to_bytes()).decrypt(
+                nonce, encrypted_data + tag, self.authenticated_data
+            ),
+            self.plaintext,
+        )
+
+        # Ciphertexts produced by `cryptography` are decrypted by CoverCrypt.
+        nonce = os.urandom(DEM_NONCE_LENGTH)
+        encrypted = aesgcm.encrypt(nonce, self.plaintext, self.authenticated_data)
+        ciphertext = CoverCrypt.join_symmetric_ciphertext(
+            nonce, encrypted[:-DEM_TAG_LENGTH], encrypted[-DEM_TAG_LENGTH:]
+        )
+        decrypted_sym_key, _ = self.cc.decrypt_header(sec_med_uk_user, enc_header)
+        self.assertEqual(
+            self.cc.decrypt_symmetric_block(
+                decrypted_sym_key, ciphertext, self.authenticated_data
+            ),
+            self.plaintext,
+        )
+
+        with self.assertRaises(Exception):
+            CoverCrypt.join_symmetric_ciphertext(nonce[1:], encrypted, b'')
+
     def test_add_attribute(self) -> None:
         # User secret key
         decryption_policy = 'Secrecy::Low'
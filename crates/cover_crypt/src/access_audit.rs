@@ -0,0 +1,198 @@
+//! Auditing of the access rights granted by user secret keys.
+//!
+//! A user secret key holds the keys of the coordinates (partitions) of the
+//! policy its access policy grants access to. The coordinates of a user key
+//! are found by encapsulating a throwaway key for each coordinate of the
+//! policy using the master public key and checking whether the user key can
+//! decapsulate it: admin tools can tell who can read what before any data is
+//! encrypted.
+
+use cosmian_cover_crypt::{
+    abe_policy::{AccessPolicy, Attribute, Partition, Policy},
+    Covercrypt, EncryptedHeader, Error, MasterPublicKey, UserSecretKey,
+};
+
+/// Returns the attributes of the given policy grouped by axis. The axes are
+/// sorted by name.
+fn attributes_by_axis(policy: &Policy) -> Vec<Vec<Attribute>> {
+    let mut axes = Vec::<Vec<Attribute>>::new();
+    for attribute in policy.attributes() {
+        match axes
+            .iter_mut()
+            .find(|attributes| attributes[0].dimension == attribute.dimension)
+        {
+            Some(attributes) => attributes.push(attribute),
+            None => axes.push(vec![attribute]),
+        }
+    }
+    axes.sort_by(|lhs, rhs| lhs[0].dimension.cmp(&rhs[0].dimension));
+    axes
+}
+
+/// Returns all the coordinates of the given policy, each given as the list of
+/// its attributes, one per axis.
+pub(crate) fn coordinates(policy: &Policy) -> Vec<Vec<Attribute>> {
+    let axes = attributes_by_axis(policy);
+    if axes.is_empty() {
+        return Vec::new();
+    }
+    axes.iter()
+        .fold(vec![Vec::new()], |coordinates, attributes| {
+            coordinates
+                .iter()
+                .flat_map(|coordinate| {
+                    attributes.iter().map(move |attribute| {
+                        let mut coordinate = coordinate.clone();
+                        coordinate.push(attribute.clone());
+                        coordinate
+                    })
+                })
+                .collect()
+        })
+}
+
+/// Returns the partition of the given coordinate of the given policy.
+pub(crate) fn coordinate_partition(
+    policy: &Policy,
+    coordinate: &[Attribute],
+) -> Result<Partition, Error> {
+    let attribute_ids = coordinate
+        .iter()
+        .map(|attribute| policy.get_attribute_id(attribute))
+        .collect::<Result<Vec<_>, _>>()?;
+    Partition::from_attribute_ids(attribute_ids)
+}
+
+/// Returns `true` if the given user secret key can decrypt the ciphertexts
+/// encrypted under the given access policy.
+///
+/// No ciphertext is needed: a throwaway key is encapsulated under the given
+/// access policy using the master public key.
+pub fn can_decrypt(
+    policy: &Policy,
+    mpk: &MasterPublicKey,
+    usk: &UserSecretKey,
+    encryption_policy: &AccessPolicy,
+) -> Result<bool, Error> {
+    let cover_crypt = Covercrypt::default();
+    let (_, encrypted_header) =
+        EncryptedHeader::generate(&cover_crypt, policy, mpk, encryption_policy, None, None)?;
+    Ok(encrypted_header.decrypt(&cover_crypt, usk, None).is_ok())
+}
+
+/// Returns the coordinates of the given policy the given user secret key can
+/// decrypt, each given as the list of its attributes, one per axis sorted by
+/// name.
+///
+/// The coordinates which can no longer be encrypted for, such as the ones of
+/// disabled attributes, are not listed.
+pub fn user_key_coordinates(
+    policy: &Policy,
+    mpk: &MasterPublicKey,
+    usk: &UserSecretKey,
+) -> Result<Vec<Vec<Attribute>>, Error> {
+    let cover_crypt = Covercrypt::default();
+    let mut user_coordinates = Vec::new();
+    for coordinate in coordinates(policy) {
+        let encryption_policy = coordinate
+            .iter()
+            .cloned()
+            .map(AccessPolicy::Attr)
+            .reduce(|lhs, rhs| AccessPolicy::And(Box::new(lhs), Box::new(rhs)))
+            .expect("coordinates have one attribute per axis");
+        let Ok((_, encrypted_header)) =
+            EncryptedHeader::generate(&cover_crypt, policy, mpk, &encryption_policy, None, None)
+        else {
+            continue;
+        };
+        if encrypted_header.decrypt(&cover_crypt, usk, None).is_ok() {
+            user_coordinates.push(coordinate);
+        }
+    }
+    Ok(user_coordinates)
+}
+
+/// Formats the given coordinate as a boolean expression, e.g.
+/// `Department::HR && Security Level::Low Secret`.
+#[must_use]
+pub fn coordinate_to_boolean_expression(coordinate: &[Attribute]) -> String {
+    coordinate
+        .iter()
+        .map(ToString::to_string)
+        .collect::<Vec<_>>()
+        .join(" && ")
+}
+
+#[cfg(test)]
+mod tests {
+    use cosmian_cover_crypt::abe_policy::{DimensionBuilder, EncryptionHint};
+
+    use super::*;
+
+    #[test]
+    fn test_access_audit() -> Result<(), Error> {
+        let mut policy = Policy::new();
+        policy.add_dimension(DimensionBuilder::new(
+            "Security Level",
+            vec![
+                ("Protected", EncryptionHint::Classic),
+                ("Confidential", EncryptionHint::Classic),
+                ("Top Secret", EncryptionHint::Hybridized),
+            ],
+            true,
+        ))?;
+        policy.add_dimension(DimensionBuilder::new(
+            "Department",
+            vec![
+                ("HR", EncryptionHint::Classic),
+                ("FIN", EncryptionHint::Classic),
+            ],
+            false,
+        ))?;
+        let cover_crypt = Covercrypt::default();
+        let (msk, mpk) = cover_crypt.generate_master_keys(&policy)?;
+        let usk = cover_crypt.generate_user_secret_key(
+            &msk,
+            &AccessPolicy::from_boolean_expression(
+                "Department::FIN && Security Level::Confidential",
+            )?,
+            &policy,
+        )?;
+
+        // Security Level is hierarchical: the user can read the lower levels.
+        let mut coordinates = user_key_coordinates(&policy, &mpk, &usk)?
+            .iter()
+            .map(|coordinate| coordinate_to_boolean_expression(coordinate))
+            .collect::<Vec<_>>();
+        coordinates.sort();
+        assert_eq!(
+            coordinates,
+            [
+                "Department::FIN && Security Level::Confidential",
+                "Department::FIN && Security Level::Protected",
+            ]
+        );
+
+        for (encryption_policy, expected) in [
+            ("Department::FIN && Security Level::Protected", true),
+            (
+                "(Department::HR || Department::FIN) && Security Level::Confidential",
+                true,
+            ),
+            ("Department::FIN && Security Level::Top Secret", false),
+            ("Department::HR", false),
+        ] {
+            assert_eq!(
+                can_decrypt(
+                    &policy,
+                    &mpk,
+                    &usk,
+                    &AccessPolicy::from_boolean_expression(encryption_policy)?
+                )?,
+                expected,
+                "{encryption_policy}"
+            );
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,150 @@
+//! Timings of the CoverCrypt operations for a given policy.
+//!
+//! CoverCrypt keys and encrypted headers grow with the number of partitions
+//! of the policy, i.e. the number of combinations of one attribute per
+//! dimension. [`bench_covercrypt`] measures the main operations for a given
+//! policy so that integrators can size their policy before deploying it.
+
+use std::time::{Duration, Instant};
+
+use cosmian_cover_crypt::{
+    abe_policy::{AccessPolicy, Attribute, DimensionBuilder, EncryptionHint, Policy},
+    Covercrypt, EncryptedHeader, Error,
+};
+
+/// Average timings of the CoverCrypt operations for a given policy.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BenchResults {
+    /// Number of partitions of the policy.
+    pub n_partitions: usize,
+    /// Generation of the master keys.
+    pub master_keygen: Duration,
+    /// Generation of a user secret key for the last attribute of each
+    /// dimension.
+    pub user_keygen: Duration,
+    /// Encryption of a header for all the partitions of the policy.
+    pub encrypt: Duration,
+    /// Decryption of this header using the above user secret key.
+    pub decrypt: Duration,
+}
+
+impl BenchResults {
+    /// Returns the timings in microseconds as a JSON object.
+    #[must_use]
+    pub fn to_json(&self) -> serde_json::Value {
+        serde_json::json!({
+            "n_partitions": self.n_partitions,
+            "master_keygen_us": self.master_keygen.as_secs_f64() * 1e6,
+            "user_keygen_us": self.user_keygen.as_secs_f64() * 1e6,
+            "encrypt_us": self.encrypt.as_secs_f64() * 1e6,
+            "decrypt_us": self.decrypt.as_secs_f64() * 1e6,
+        })
+    }
+}
+
+/// Generates a policy with one non-hierarchical dimension per given number
+/// of attributes. The number of partitions of this policy is the product of
+/// these numbers.
+pub fn generate_policy(n_attributes: &[usize]) -> Result<Policy, Error> {
+    let mut policy = Policy::new();
+    for (i, n) in n_attributes.iter().enumerate() {
+        let names = (0..*n).map(|j| format!("A{j}")).collect::<Vec<_>>();
+        policy.add_dimension(DimensionBuilder::new(
+            &format!("D{i}"),
+            names
+                .iter()
+                .map(|name| (name.as_str(), EncryptionHint::Classic))
+                .collect(),
+            false,
+        ))?;
+    }
+    Ok(policy)
+}
+
+/// Runs the given operation `n` times (at least once) and returns its average
+/// duration along with its last result.
+fn time<T>(
+    n: usize,
+    mut operation: impl FnMut() -> Result<T, Error>,
+) -> Result<(Duration, T), Error> {
+    let n = n.max(1);
+    let start = Instant::now();
+    let mut res = operation()?;
+    for _ in 1..n {
+        res = operation()?;
+    }
+    Ok((
+        Duration::from_secs_f64(start.elapsed().as_secs_f64() / n as f64),
+        res,
+    ))
+}
+
+/// Measures the average duration of the CoverCrypt operations for the given
+/// policy over `n` iterations.
+pub fn bench_covercrypt(policy: &Policy, n: usize) -> Result<BenchResults, Error> {
+    let mut dimensions = Vec::<(String, Vec<Attribute>)>::new();
+    for attribute in policy.attributes() {
+        match dimensions
+            .iter_mut()
+            .find(|(name, _)| *name == attribute.dimension)
+        {
+            Some((_, attributes)) => attributes.push(attribute),
+            None => dimensions.push((attribute.dimension.clone(), vec![attribute])),
+        }
+    }
+    if dimensions.is_empty() {
+        return Err(Error::KeyError("the policy has no attribute".to_string()));
+    }
+    let n_partitions = dimensions
+        .iter()
+        .map(|(_, attributes)| attributes.len())
+        .product();
+
+    // Any attribute of the first dimension, whatever the other dimensions:
+    // this targets all the partitions.
+    let encryption_policy = dimensions[0]
+        .1
+        .iter()
+        .map(|attribute| AccessPolicy::Attr(attribute.clone()))
+        .reduce(|lhs, rhs| AccessPolicy::Or(Box::new(lhs), Box::new(rhs)))
+        .expect("dimensions are not empty");
+    let user_policy = dimensions
+        .iter()
+        .filter_map(|(_, attributes)| attributes.last().cloned().map(AccessPolicy::Attr))
+        .reduce(|lhs, rhs| AccessPolicy::And(Box::new(lhs), Box::new(rhs)))
+        .expect("dimensions are not empty");
+
+    let cover_crypt = Covercrypt::default();
+    let (master_keygen, (msk, mpk)) = time(n, || cover_crypt.generate_master_keys(policy))?;
+    let (user_keygen, usk) = time(n, || {
+        cover_crypt.generate_user_secret_key(&msk, &user_policy, policy)
+    })?;
+    let (encrypt, (_, encrypted_header)) = time(n, || {
+        EncryptedHeader::generate(&cover_crypt, policy, &mpk, &encryption_policy, None, None)
+    })?;
+    let (decrypt, _) = time(n, || encrypted_header.decrypt(&cover_crypt, &usk, None))?;
+
+    Ok(BenchResults {
+        n_partitions,
+        master_keygen,
+        user_keygen,
+        encrypt,
+        decrypt,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_bench_covercrypt() -> Result<(), Error> {
+        let policy = generate_policy(&[3, 4])?;
+        let results = bench_covercrypt(&policy, 2)?;
+        assert_eq!(results.n_partitions, 12);
+        assert_eq!(results.to_json()["n_partitions"], 12);
+
+        assert!(bench_covercrypt(&Policy::new(), 1).is_err());
+        Ok(())
+    }
+}
@@ -0,0 +1,182 @@
+//! Parallel hybrid decryption of many ciphertexts using the same user secret
+//! key.
+//!
+//! Decrypting the cells of a database column means decapsulating one header
+//! and running one DEM decryption per cell: these decryptions are independent
+//! and are spread over the threads of the [`rayon`] global pool.
+
+use std::fmt::Display;
+
+use cosmian_cover_crypt::{Covercrypt, EncryptedHeader, Error, UserSecretKey};
+use cosmian_crypto_core::bytes_ser_de::{Deserializer, Serializable};
+use rayon::prelude::*;
+
+/// Error returned by [`decrypt_bulk`].
+#[derive(Debug)]
+pub enum BulkDecryptionError {
+    /// The number of authentication data is neither 0 nor the number of
+    /// ciphertexts.
+    AuthenticationDataCount {
+        ciphertexts: usize,
+        authentication_data: usize,
+    },
+    /// The ciphertext at the given index cannot be decrypted.
+    Decryption { index: usize, error: Error },
+}
+
+impl Display for BulkDecryptionError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::AuthenticationDataCount {
+                ciphertexts,
+                authentication_data,
+            } => write!(
+                f,
+                "{authentication_data} authentication data given for {ciphertexts} ciphertexts, \
+                 expected none or one per ciphertext"
+            ),
+            Self::Decryption { index, error } => {
+                write!(f, "error decrypting ciphertext {index}: {error}")
+            }
+        }
+    }
+}
+
+impl std::error::Error for BulkDecryptionError {}
+
+/// Hybrid decrypts the given ciphertexts in parallel.
+///
+/// - `ciphertexts`         : each given as encrypted header || DEM ciphertext
+/// - `authentication_data` : either empty or one per ciphertext; an empty
+///   authentication data is equivalent to no authentication data
+///
+/// Returns the plaintexts in the order of the ciphertexts. Fails if any of the
+/// ciphertexts cannot be decrypted: the error gives the index of one of them.
+pub fn decrypt_bulk<C, A>(
+    cover_crypt: &Covercrypt,
+    usk: &UserSecretKey,
+    ciphertexts: &[C],
+    authentication_data: &[A],
+) -> Result<Vec<Vec<u8>>, BulkDecryptionError>
+where
+    C: AsRef<[u8]> + Sync,
+    A: AsRef<[u8]> + Sync,
+{
+    if !authentication_data.is_empty() && authentication_data.len() != ciphertexts.len() {
+        return Err(BulkDecryptionError::AuthenticationDataCount {
+            ciphertexts: ciphertexts.len(),
+            authentication_data: authentication_data.len(),
+        });
+    }
+
+    ciphertexts
+        .par_iter()
+        .enumerate()
+        .map(|(index, ciphertext)| {
+            let authentication_data = authentication_data
+                .get(index)
+                .map(AsRef::as_ref)
+                .filter(|authentication_data| !authentication_data.is_empty());
+            decrypt(cover_crypt, usk, ciphertext.as_ref(), authentication_data)
+                .map_err(|error| BulkDecryptionError::Decryption { index, error })
+        })
+        .collect()
+}
+
+fn decrypt(
+    cover_crypt: &Covercrypt,
+    usk: &UserSecretKey,
+    encrypted_bytes: &[u8],
+    authentication_data: Option<&[u8]>,
+) -> Result<Vec<u8>, Error> {
+    let mut de = Deserializer::new(encrypted_bytes);
+    // This will read the exact header size.
+    let encrypted_header = EncryptedHeader::read(&mut de)?;
+    // The rest is the symmetric ciphertext.
+    let ciphertext = de.finalize();
+
+    let cleartext_header = encrypted_header.decrypt(cover_crypt, usk, authentication_data)?;
+    cover_crypt.decrypt(
+        &cleartext_header.symmetric_key,
+        &ciphertext,
+        authentication_data,
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use cosmian_cover_crypt::{abe_policy::AccessPolicy, test_utils::policy};
+    use cosmian_crypto_core::bytes_ser_de::Serializer;
+
+    use super::*;
+
+    #[test]
+    fn test_decrypt_bulk() -> Result<(), Box<dyn std::error::Error>> {
+        let policy = policy()?;
+        let cover_crypt = Covercrypt::default();
+        let (msk, mpk) = cover_crypt.generate_master_keys(&policy)?;
+        let usk = cover_crypt.generate_user_secret_key(
+            &msk,
+            &AccessPolicy::from_boolean_expression(
+                "Department::MKG && Security Level::Top Secret",
+            )?,
+            &policy,
+        )?;
+
+        let encrypt = |access_policy: &str, plaintext: &[u8], authentication_data: &[u8]| {
+            let (symmetric_key, encrypted_header) = EncryptedHeader::generate(
+                &cover_crypt,
+                &policy,
+                &mpk,
+                &AccessPolicy::from_boolean_expression(access_policy)?,
+                None,
+                Some(authentication_data),
+            )?;
+            let ciphertext =
+                cover_crypt.encrypt(&symmetric_key, plaintext, Some(authentication_data))?;
+            let mut ser = Serializer::new();
+            encrypted_header.write(&mut ser)?;
+            ser.write_array(&ciphertext)?;
+            Ok::<_, Error>(ser.finalize().to_vec())
+        };
+
+        let plaintexts = (0..100)
+            .map(|i| format!("cell {i}").into_bytes())
+            .collect::<Vec<_>>();
+        let authentication_data = (0..100)
+            .map(|i| format!("record {i}").into_bytes())
+            .collect::<Vec<_>>();
+        let mut ciphertexts = plaintexts
+            .iter()
+            .zip(&authentication_data)
+            .map(|(plaintext, authentication_data)| {
+                encrypt(
+                    "Department::MKG && Security Level::Low Secret",
+                    plaintext,
+                    authentication_data,
+                )
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+
+        assert_eq!(
+            decrypt_bulk(&cover_crypt, &usk, &ciphertexts, &authentication_data)?,
+            plaintexts
+        );
+
+        // The authentication data are bound to their ciphertext.
+        assert!(decrypt_bulk(&cover_crypt, &usk, &ciphertexts, &[] as &[&[u8]]).is_err());
+        assert!(matches!(
+            decrypt_bulk(&cover_crypt, &usk, &ciphertexts, &authentication_data[..1]),
+            Err(BulkDecryptionError::AuthenticationDataCount { .. })
+        ));
+
+        // The ciphertexts the user cannot decrypt are reported.
+        ciphertexts[42] = encrypt("Department::HR", &plaintexts[42], &authentication_data[42])?;
+        assert!(matches!(
+            decrypt_bulk(&cover_crypt, &usk, &ciphertexts, &authentication_data),
+            Err(BulkDecryptionError::Decryption { index: 42, .. })
+        ));
+
+        Ok(())
+    }
+}
@@ -0,0 +1,182 @@
+//! Hybrid encryption with a cleartext metadata field.
+//!
+//! The cleartext metadata (content type, key version...) is prepended to the
+//! hybrid ciphertext so that it can be read without any user key:
+//!
+//! ```txt
+//! LEB128(cleartext_metadata.len()) || cleartext_metadata || encrypted_header || DEM ciphertext
+//! ```
+//!
+//! It is not encrypted but it is authenticated: the cleartext metadata is
+//! bound to the authentication data used by both the header and the DEM
+//! encryptions, so that any modification makes the decryption fail.
+
+use cosmian_cover_crypt::{
+    abe_policy::{AccessPolicy, Policy},
+    Covercrypt, EncryptedHeader, Error, MasterPublicKey, UserSecretKey,
+};
+use cosmian_crypto_core::bytes_ser_de::{Deserializer, Serializable, Serializer};
+
+/// Result of a hybrid decryption with cleartext metadata.
+pub struct DecryptedWithCleartextMetadata {
+    pub plaintext: Vec<u8>,
+    pub header_metadata: Option<Vec<u8>>,
+    pub cleartext_metadata: Vec<u8>,
+}
+
+/// Binds the cleartext metadata to the user authentication data.
+fn bind_authentication_data(
+    cleartext_metadata: &[u8],
+    authentication_data: Option<&[u8]>,
+) -> Result<Vec<u8>, Error> {
+    let mut ser = Serializer::new();
+    ser.write_vec(cleartext_metadata)?;
+    ser.write_array(authentication_data.unwrap_or_default())?;
+    Ok(ser.finalize().to_vec())
+}
+
+/// Hybrid encrypts the given plaintext and prepends the given cleartext
+/// metadata to the ciphertext.
+///
+/// - `cleartext_metadata`  : metadata readable without any key
+/// - `header_metadata`     : optional metadata encrypted in the header
+/// - `authentication_data` : optional data to use for authentication
+#[allow(clippy::too_many_arguments)]
+pub fn encrypt_with_cleartext_metadata(
+    cover_crypt: &Covercrypt,
+    policy: &Policy,
+    public_key: &MasterPublicKey,
+    access_policy: &AccessPolicy,
+    plaintext: &[u8],
+    cleartext_metadata: &[u8],
+    header_metadata: Option<&[u8]>,
+    authentication_data: Option<&[u8]>,
+) -> Result<Vec<u8>, Error> {
+    let authentication_data = bind_authentication_data(cleartext_metadata, authentication_data)?;
+
+    let (symmetric_key, encrypted_header) = EncryptedHeader::generate(
+        cover_crypt,
+        policy,
+        public_key,
+        access_policy,
+        header_metadata,
+        Some(&authentication_data),
+    )?;
+    let ciphertext = cover_crypt.encrypt(&symmetric_key, plaintext, Some(&authentication_data))?;
+
+    let mut ser = Serializer::with_capacity(
+        cleartext_metadata.len() + encrypted_header.length() + ciphertext.len(),
+    );
+    ser.write_vec(cleartext_metadata)?;
+    encrypted_header.write(&mut ser)?;
+    ser.write_array(&ciphertext)?;
+    Ok(ser.finalize().to_vec())
+}
+
+/// Reads the cleartext metadata of the given ciphertext without decrypting
+/// it.
+///
+/// The returned metadata is not authenticated until the ciphertext is
+/// decrypted using [`decrypt_with_cleartext_metadata`].
+pub fn read_cleartext_metadata(encrypted_bytes: &[u8]) -> Result<Vec<u8>, Error> {
+    Ok(Deserializer::new(encrypted_bytes).read_vec()?)
+}
+
+/// Hybrid decrypts the given ciphertext and verifies its cleartext metadata.
+///
+/// - `authentication_data` : optional data used for authentication upon
+///   encryption
+pub fn decrypt_with_cleartext_metadata(
+    cover_crypt: &Covercrypt,
+    usk: &UserSecretKey,
+    encrypted_bytes: &[u8],
+    authentication_data: Option<&[u8]>,
+) -> Result<DecryptedWithCleartextMetadata, Error> {
+    let mut de = Deserializer::new(encrypted_bytes);
+    let cleartext_metadata = de.read_vec()?;
+    // This will read the exact header size.
+    let encrypted_header = EncryptedHeader::read(&mut de)?;
+    // The rest is the symmetric ciphertext.
+    let ciphertext = de.finalize();
+
+    let authentication_data = bind_authentication_data(&cleartext_metadata, authentication_data)?;
+    let cleartext_header =
+        encrypted_header.decrypt(cover_crypt, usk, Some(&authentication_data))?;
+    let plaintext = cover_crypt.decrypt(
+        &cleartext_header.symmetric_key,
+        &ciphertext,
+        Some(&authentication_data),
+    )?;
+
+    Ok(DecryptedWithCleartextMetadata {
+        plaintext,
+        header_metadata: cleartext_header.metadata,
+        cleartext_metadata,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use cosmian_cover_crypt::test_utils::policy;
+
+    use super::*;
+
+    #[test]
+    fn test_cleartext_metadata() -> Result<(), Error> {
+        let policy = policy()?;
+        let cover_crypt = Covercrypt::default();
+        let (msk, mpk) = cover_crypt.generate_master_keys(&policy)?;
+        let access_policy =
+            AccessPolicy::from_boolean_expression("Department::MKG && Security Level::Top Secret")?;
+        let usk = cover_crypt.generate_user_secret_key(&msk, &access_policy, &policy)?;
+
+        let plaintext = b"plaintext";
+        let cleartext_metadata = b"application/json; key-version=2";
+        let authentication_data = b"authentication data";
+
+        let encrypted_bytes = encrypt_with_cleartext_metadata(
+            &cover_crypt,
+            &policy,
+            &mpk,
+            &access_policy,
+            plaintext,
+            cleartext_metadata,
+            Some(b"header metadata"),
+            Some(authentication_data),
+        )?;
+
+        // The cleartext metadata is readable without any key.
+        assert_eq!(
+            read_cleartext_metadata(&encrypted_bytes)?,
+            cleartext_metadata.to_vec()
+        );
+
+        let res = decrypt_with_cleartext_metadata(
+            &cover_crypt,
+            &usk,
+            &encrypted_bytes,
+            Some(authentication_data),
+        )?;
+        assert_eq!(res.plaintext, plaintext.to_vec());
+        assert_eq!(res.header_metadata, Some(b"header metadata".to_vec()));
+        assert_eq!(res.cleartext_metadata, cleartext_metadata.to_vec());
+
+        // Tampering with the cleartext metadata is detected.
+        let mut tampered_bytes = encrypted_bytes.clone();
+        tampered_bytes[1] ^= 1;
+        assert!(decrypt_with_cleartext_metadata(
+            &cover_crypt,
+            &usk,
+            &tampered_bytes,
+            Some(authentication_data)
+        )
+        .is_err());
+
+        // The authentication data is still checked.
+        assert!(
+            decrypt_with_cleartext_metadata(&cover_crypt, &usk, &encrypted_bytes, None).is_err()
+        );
+
+        Ok(())
+    }
+}
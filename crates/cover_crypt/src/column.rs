@@ -0,0 +1,215 @@
+//! Encryption of the cells of a database column under a single header.
+//!
+//! Encrypting all the cells of a column under the same access policy does not
+//! need one encapsulation per cell: a single encrypted header is generated for
+//! the column, and each cell is encrypted by the DEM using the symmetric key of
+//! this header. Decrypting the column then needs a single decapsulation.
+//!
+//! The DEM encryptions and decryptions of the cells are independent and are
+//! spread over the threads of the [`rayon`] global pool. Null cells are kept
+//! null.
+
+use std::fmt::Display;
+
+use cosmian_cover_crypt::{Covercrypt, Error};
+use cosmian_crypto_core::{Aes256Gcm, SymmetricKey};
+use rayon::prelude::*;
+
+/// Error returned when encrypting or decrypting the cells of a column.
+#[derive(Debug)]
+pub enum ColumnError {
+    /// The number of authentication data is neither 0 nor the number of
+    /// cells.
+    AuthenticationDataCount {
+        cells: usize,
+        authentication_data: usize,
+    },
+    /// The cell at the given index cannot be encrypted or decrypted.
+    Cell { index: usize, error: Error },
+}
+
+impl Display for ColumnError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::AuthenticationDataCount {
+                cells,
+                authentication_data,
+            } => write!(
+                f,
+                "{authentication_data} authentication data given for {cells} cells, expected \
+                 none or one per cell"
+            ),
+            Self::Cell { index, error } => write!(f, "error processing cell {index}: {error}"),
+        }
+    }
+}
+
+impl std::error::Error for ColumnError {}
+
+/// Applies the given DEM operation on all the non-null cells in parallel.
+fn process_cells<C, A>(
+    cells: &[Option<C>],
+    authentication_data: &[A],
+    operation: impl Fn(&[u8], Option<&[u8]>) -> Result<Vec<u8>, Error> + Sync,
+) -> Result<Vec<Option<Vec<u8>>>, ColumnError>
+where
+    C: AsRef<[u8]> + Sync,
+    A: AsRef<[u8]> + Sync,
+{
+    if !authentication_data.is_empty() && authentication_data.len() != cells.len() {
+        return Err(ColumnError::AuthenticationDataCount {
+            cells: cells.len(),
+            authentication_data: authentication_data.len(),
+        });
+    }
+
+    cells
+        .par_iter()
+        .enumerate()
+        .map(|(index, cell)| {
+            cell.as_ref()
+                .map(|cell| {
+                    let authentication_data = authentication_data
+                        .get(index)
+                        .map(AsRef::as_ref)
+                        .filter(|authentication_data| !authentication_data.is_empty());
+                    operation(cell.as_ref(), authentication_data)
+                        .map_err(|error| ColumnError::Cell { index, error })
+                })
+                .transpose()
+        })
+        .collect()
+}
+
+/// Encrypts the given cells in parallel using the symmetric key of the column
+/// header.
+///
+/// - `cells`               : plaintexts, `None` for the null cells
+/// - `authentication_data` : either empty or one per cell, e.g. the record
+///   IDs binding each cell to its record; an empty authentication data is
+///   equivalent to no authentication data
+///
+/// Returns the DEM ciphertexts in the order of the cells.
+pub fn encrypt_cells<C, A>(
+    cover_crypt: &Covercrypt,
+    symmetric_key: &SymmetricKey<{ Aes256Gcm::KEY_LENGTH }>,
+    cells: &[Option<C>],
+    authentication_data: &[A],
+) -> Result<Vec<Option<Vec<u8>>>, ColumnError>
+where
+    C: AsRef<[u8]> + Sync,
+    A: AsRef<[u8]> + Sync,
+{
+    process_cells(
+        cells,
+        authentication_data,
+        |plaintext, authentication_data| {
+            cover_crypt.encrypt(symmetric_key, plaintext, authentication_data)
+        },
+    )
+}
+
+/// Decrypts the given cells in parallel using the symmetric key of the column
+/// header.
+///
+/// - `cells`               : DEM ciphertexts, `None` for the null cells
+/// - `authentication_data` : the authentication data given upon encryption
+///
+/// Returns the plaintexts in the order of the cells. Fails if any of the cells
+/// cannot be decrypted: the error gives the index of one of them.
+pub fn decrypt_cells<C, A>(
+    cover_crypt: &Covercrypt,
+    symmetric_key: &SymmetricKey<{ Aes256Gcm::KEY_LENGTH }>,
+    cells: &[Option<C>],
+    authentication_data: &[A],
+) -> Result<Vec<Option<Vec<u8>>>, ColumnError>
+where
+    C: AsRef<[u8]> + Sync,
+    A: AsRef<[u8]> + Sync,
+{
+    process_cells(
+        cells,
+        authentication_data,
+        |ciphertext, authentication_data| {
+            cover_crypt.decrypt(symmetric_key, ciphertext, authentication_data)
+        },
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use cosmian_cover_crypt::{abe_policy::AccessPolicy, test_utils::policy, EncryptedHeader};
+
+    use super::*;
+
+    #[test]
+    fn test_column_encryption() -> Result<(), Box<dyn std::error::Error>> {
+        let policy = policy()?;
+        let cover_crypt = Covercrypt::default();
+        let (msk, mpk) = cover_crypt.generate_master_keys(&policy)?;
+        let usk = cover_crypt.generate_user_secret_key(
+            &msk,
+            &AccessPolicy::from_boolean_expression(
+                "Department::MKG && Security Level::Low Secret",
+            )?,
+            &policy,
+        )?;
+
+        // A single header is generated for the whole column.
+        let (symmetric_key, encrypted_header) = EncryptedHeader::generate(
+            &cover_crypt,
+            &policy,
+            &mpk,
+            &AccessPolicy::from_boolean_expression(
+                "Department::MKG && Security Level::Low Secret",
+            )?,
+            None,
+            None,
+        )?;
+
+        let cells = (0..100)
+            .map(|i| (i % 10 != 0).then(|| format!("cell {i}").into_bytes()))
+            .collect::<Vec<_>>();
+        let record_ids = (0..100)
+            .map(|i| format!("record {i}").into_bytes())
+            .collect::<Vec<_>>();
+        let encrypted_cells = encrypt_cells(&cover_crypt, &symmetric_key, &cells, &record_ids)?;
+        assert!(encrypted_cells[0].is_none());
+        assert_ne!(encrypted_cells[1].as_deref(), cells[1].as_deref());
+
+        let cleartext_header = encrypted_header.decrypt(&cover_crypt, &usk, None)?;
+        assert_eq!(
+            decrypt_cells(
+                &cover_crypt,
+                &cleartext_header.symmetric_key,
+                &encrypted_cells,
+                &record_ids
+            )?,
+            cells
+        );
+
+        // The cells are bound to their record.
+        let mut swapped_ids = record_ids.clone();
+        swapped_ids.swap(1, 2);
+        assert!(matches!(
+            decrypt_cells(
+                &cover_crypt,
+                &cleartext_header.symmetric_key,
+                &encrypted_cells,
+                &swapped_ids
+            ),
+            Err(ColumnError::Cell { .. })
+        ));
+        assert!(matches!(
+            decrypt_cells(
+                &cover_crypt,
+                &cleartext_header.symmetric_key,
+                &encrypted_cells,
+                &record_ids[..1]
+            ),
+            Err(ColumnError::AuthenticationDataCount { .. })
+        ));
+
+        Ok(())
+    }
+}
@@ -0,0 +1,153 @@
+//! Interoperability of the DEM ciphertexts with other AES256-GCM
+//! implementations.
+//!
+//! The symmetric ciphertexts produced by Covercrypt concatenate the nonce, the
+//! encrypted data and the authentication tag:
+//!
+//! ```txt
+//! DEM ciphertext = nonce (12 bytes) || encrypted data || tag (16 bytes)
+//! ```
+//!
+//! Most AES256-GCM libraries take the nonce separately, and some of them the
+//! tag too. The functions of this module convert between these formats, so
+//! that the symmetric key of an encrypted header can be used with an existing
+//! DEM implementation.
+
+use cosmian_cover_crypt::{EncryptedHeader, Error};
+use cosmian_crypto_core::{
+    bytes_ser_de::{Deserializer, Serializable},
+    Aes256Gcm, CryptoCoreError,
+};
+
+/// Length of the nonce of a DEM ciphertext.
+pub const DEM_NONCE_LENGTH: usize = Aes256Gcm::NONCE_LENGTH;
+
+/// Length of the authentication tag of a DEM ciphertext.
+pub const DEM_TAG_LENGTH: usize = Aes256Gcm::MAC_LENGTH;
+
+/// Parts of a DEM ciphertext, borrowed from it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DemParts<'a> {
+    pub nonce: &'a [u8],
+    pub encrypted_data: &'a [u8],
+    pub tag: &'a [u8],
+}
+
+/// Splits the given DEM ciphertext into its nonce, encrypted data and
+/// authentication tag.
+pub fn split_dem_ciphertext(ciphertext: &[u8]) -> Result<DemParts<'_>, Error> {
+    if ciphertext.len() < DEM_NONCE_LENGTH + DEM_TAG_LENGTH {
+        return Err(CryptoCoreError::CiphertextTooSmallError {
+            ciphertext_len: ciphertext.len(),
+            min: (DEM_NONCE_LENGTH + DEM_TAG_LENGTH) as u64,
+        }
+        .into());
+    }
+    let (nonce, ciphertext) = ciphertext.split_at(DEM_NONCE_LENGTH);
+    let (encrypted_data, tag) = ciphertext.split_at(ciphertext.len() - DEM_TAG_LENGTH);
+    Ok(DemParts {
+        nonce,
+        encrypted_data,
+        tag,
+    })
+}
+
+/// Concatenates the given nonce, encrypted data and authentication tag into a
+/// DEM ciphertext.
+pub fn join_dem_ciphertext(
+    nonce: &[u8],
+    encrypted_data: &[u8],
+    tag: &[u8],
+) -> Result<Vec<u8>, Error> {
+    if nonce.len() != DEM_NONCE_LENGTH {
+        return Err(CryptoCoreError::InvalidBytesLength(
+            "DEM nonce".to_string(),
+            nonce.len(),
+            Some(DEM_NONCE_LENGTH),
+        )
+        .into());
+    }
+    if tag.len() != DEM_TAG_LENGTH {
+        return Err(CryptoCoreError::InvalidBytesLength(
+            "DEM tag".to_string(),
+            tag.len(),
+            Some(DEM_TAG_LENGTH),
+        )
+        .into());
+    }
+    Ok([nonce, encrypted_data, tag].concat())
+}
+
+/// Splits the given hybrid ciphertext into the serialized encrypted header
+/// and the DEM ciphertext.
+///
+/// - `encrypted_bytes` : encrypted header || DEM ciphertext
+pub fn split_hybrid_ciphertext(encrypted_bytes: &[u8]) -> Result<(&[u8], &[u8]), Error> {
+    let mut de = Deserializer::new(encrypted_bytes);
+    // This will read the exact header size.
+    EncryptedHeader::read(&mut de)?;
+    let header_length = encrypted_bytes.len() - de.finalize().len();
+    Ok(encrypted_bytes.split_at(header_length))
+}
+
+#[cfg(test)]
+mod tests {
+    use cosmian_cover_crypt::{abe_policy::AccessPolicy, test_utils::policy, Covercrypt};
+    use cosmian_crypto_core::{
+        bytes_ser_de::Serializer, Dem, FixedSizeCBytes, Instantiable, Nonce,
+    };
+
+    use super::*;
+
+    #[test]
+    fn test_dem_interoperability() -> Result<(), Error> {
+        let policy = policy()?;
+        let cover_crypt = Covercrypt::default();
+        let (_, mpk) = cover_crypt.generate_master_keys(&policy)?;
+
+        let (symmetric_key, encrypted_header) = EncryptedHeader::generate(
+            &cover_crypt,
+            &policy,
+            &mpk,
+            &AccessPolicy::from_boolean_expression(
+                "Department::MKG && Security Level::Low Secret",
+            )?,
+            None,
+            None,
+        )?;
+        let plaintext = b"plaintext";
+        let ciphertext = cover_crypt.encrypt(&symmetric_key, plaintext, None)?;
+
+        let DemParts {
+            nonce,
+            encrypted_data,
+            tag,
+        } = split_dem_ciphertext(&ciphertext)?;
+        assert_eq!(nonce.len(), DEM_NONCE_LENGTH);
+        assert_eq!(encrypted_data.len(), plaintext.len());
+        assert_eq!(tag.len(), DEM_TAG_LENGTH);
+        assert_eq!(join_dem_ciphertext(nonce, encrypted_data, tag)?, ciphertext);
+
+        // The encrypted data and tag can be decrypted by an AES256-GCM
+        // implementation taking the nonce separately.
+        let decrypted = Aes256Gcm::new(&symmetric_key).decrypt(
+            &Nonce::try_from_slice(nonce)?,
+            &[encrypted_data, tag].concat(),
+            None,
+        )?;
+        assert_eq!(decrypted, plaintext);
+
+        let mut ser = Serializer::new();
+        encrypted_header.write(&mut ser)?;
+        ser.write_array(&ciphertext)?;
+        let encrypted_bytes = ser.finalize();
+        let (header_bytes, dem_ciphertext) = split_hybrid_ciphertext(&encrypted_bytes)?;
+        assert_eq!(header_bytes, encrypted_header.serialize()?.as_slice());
+        assert_eq!(dem_ciphertext, ciphertext);
+
+        assert!(split_dem_ciphertext(&ciphertext[..DEM_NONCE_LENGTH]).is_err());
+        assert!(join_dem_ciphertext(&nonce[1..], encrypted_data, tag).is_err());
+        assert!(join_dem_ciphertext(nonce, encrypted_data, &tag[1..]).is_err());
+        Ok(())
+    }
+}
@@ -0,0 +1,119 @@
+//! Size estimation of the Covercrypt keys and ciphertexts.
+//!
+//! The size of the keys and of the encrypted headers depends on the number of
+//! partitions of the policy covered by the access policies, and on whether
+//! these partitions are hybridized. The sizes are obtained by generating
+//! throwaway keys and headers for the given policies: no key given by the
+//! caller is needed.
+//!
+//! The returned sizes are the ones of the values returned by the bindings:
+//! the keys are serialized along with their format version (see
+//! [`crate::key_version`]).
+
+use cosmian_cover_crypt::{
+    abe_policy::{AccessPolicy, Policy},
+    Covercrypt, EncryptedHeader, Error,
+};
+use cosmian_crypto_core::{bytes_ser_de::Serializable, Aes256Gcm};
+
+use crate::key_version::serialize_versioned;
+
+/// Size overhead of the DEM ciphertext compared to the plaintext.
+pub const DEM_ENCRYPTION_OVERHEAD: usize = Aes256Gcm::NONCE_LENGTH + Aes256Gcm::MAC_LENGTH;
+
+/// Sizes of the serialized master keys.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct MasterKeySizes {
+    pub master_secret_key: usize,
+    pub master_public_key: usize,
+}
+
+/// Estimates the size of the serialized master keys of the given policy.
+pub fn estimate_master_key_sizes(policy: &Policy) -> Result<MasterKeySizes, Error> {
+    let (msk, mpk) = Covercrypt::default().generate_master_keys(policy)?;
+    Ok(MasterKeySizes {
+        master_secret_key: serialize_versioned(&msk)?.len(),
+        master_public_key: serialize_versioned(&mpk)?.len(),
+    })
+}
+
+/// Estimates the size of a serialized user secret key of the given access
+/// policy.
+pub fn estimate_user_secret_key_size(
+    policy: &Policy,
+    access_policy: &AccessPolicy,
+) -> Result<usize, Error> {
+    let cover_crypt = Covercrypt::default();
+    let (msk, _) = cover_crypt.generate_master_keys(policy)?;
+    let usk = cover_crypt.generate_user_secret_key(&msk, access_policy, policy)?;
+    Ok(serialize_versioned(&usk)?.len())
+}
+
+/// Estimates the size overhead of a hybrid ciphertext encrypted under the
+/// given access policy, compared to the plaintext.
+///
+/// The overhead is the size of the encrypted header without metadata plus
+/// [`DEM_ENCRYPTION_OVERHEAD`]: the hybrid encryption functions append the DEM
+/// ciphertext to the header without length prefix. Header metadata, if any,
+/// adds its own size plus [`DEM_ENCRYPTION_OVERHEAD`].
+pub fn estimate_ciphertext_overhead(
+    policy: &Policy,
+    access_policy: &AccessPolicy,
+) -> Result<usize, Error> {
+    let cover_crypt = Covercrypt::default();
+    let (_, mpk) = cover_crypt.generate_master_keys(policy)?;
+    let (_, encrypted_header) =
+        EncryptedHeader::generate(&cover_crypt, policy, &mpk, access_policy, None, None)?;
+    Ok(encrypted_header.length() + DEM_ENCRYPTION_OVERHEAD)
+}
+
+#[cfg(test)]
+mod tests {
+    use cosmian_cover_crypt::test_utils::policy;
+    use cosmian_crypto_core::bytes_ser_de::Serializer;
+
+    use super::*;
+
+    #[test]
+    fn test_estimations() -> Result<(), Error> {
+        let policy = policy()?;
+        let access_policy =
+            AccessPolicy::from_boolean_expression("Department::MKG && Security Level::Low Secret")?;
+        let cover_crypt = Covercrypt::default();
+        let (msk, mpk) = cover_crypt.generate_master_keys(&policy)?;
+
+        let sizes = estimate_master_key_sizes(&policy)?;
+        assert_eq!(sizes.master_secret_key, serialize_versioned(&msk)?.len());
+        assert_eq!(sizes.master_public_key, serialize_versioned(&mpk)?.len());
+
+        let usk = cover_crypt.generate_user_secret_key(&msk, &access_policy, &policy)?;
+        assert_eq!(
+            estimate_user_secret_key_size(&policy, &access_policy)?,
+            serialize_versioned(&usk)?.len()
+        );
+
+        // The estimation matches the size of an actual hybrid ciphertext.
+        let plaintext = b"plaintext";
+        let (symmetric_key, encrypted_header) =
+            EncryptedHeader::generate(&cover_crypt, &policy, &mpk, &access_policy, None, None)?;
+        let ciphertext = cover_crypt.encrypt(&symmetric_key, plaintext, None)?;
+        let mut ser = Serializer::new();
+        encrypted_header.write(&mut ser)?;
+        ser.write_array(&ciphertext)?;
+        let encrypted_bytes = ser.finalize();
+        assert_eq!(
+            encrypted_bytes.len(),
+            plaintext.len() + estimate_ciphertext_overhead(&policy, &access_policy)?
+        );
+
+        // Broader access policies cover more partitions.
+        let broad_access_policy = AccessPolicy::from_boolean_expression(
+            "Department::MKG || Department::FIN || Department::HR",
+        )?;
+        assert!(
+            estimate_ciphertext_overhead(&policy, &broad_access_policy)?
+                > estimate_ciphertext_overhead(&policy, &access_policy)?
+        );
+        Ok(())
+    }
+}
@@ -0,0 +1,206 @@
+use std::collections::HashMap;
+
+use cosmian_cover_crypt::{
+    abe_policy::{AccessPolicy, Policy},
+    MasterPublicKey, UserSecretKey,
+};
+use cosmian_ffi_utils::{
+    ffi_guard, ffi_not_null, ffi_read_bytes, ffi_read_string, ffi_unwrap, ffi_write_bytes,
+    ErrorCode,
+};
+
+use crate::{
+    access_audit::{can_decrypt, coordinate_to_boolean_expression, user_key_coordinates},
+    key_version::try_deserialize_any_version,
+    rotation::user_key_rotations,
+};
+
+#[no_mangle]
+/// Lists the coordinates of the policy the given user secret key can decrypt.
+///
+/// The coordinates are written as a JSON array of boolean expressions, e.g.
+/// `["Department::HR && Security Level::Low Secret"]`.
+///
+///  - `coordinates_ptr` : Output buffer containing the JSON coordinates
+///  - `coordinates_len` : Size of the coordinates output buffer
+///  - `usk_ptr`         : User secret key to audit
+///  - `usk_len`         : User secret key length
+///  - `mpk_ptr`         : Master public key
+///  - `mpk_len`         : Master public key length
+///  - `policy_ptr`      : Policy used to generate the keys
+///  - `policy_len`      : Size of the `Policy`
+///
+/// # Safety
+pub unsafe extern "C" fn h_user_key_coordinates(
+    coordinates_ptr: *mut i8,
+    coordinates_len: *mut i32,
+    usk_ptr: *const i8,
+    usk_len: i32,
+    mpk_ptr: *const i8,
+    mpk_len: i32,
+    policy_ptr: *const i8,
+    policy_len: i32,
+) -> i32 {
+    ffi_guard!({
+        let usk_bytes = ffi_read_bytes!("user secret key", usk_ptr, usk_len);
+        let usk = ffi_unwrap!(
+            try_deserialize_any_version::<UserSecretKey>(usk_bytes),
+            "error deserializing user secret key",
+            ErrorCode::Serialization
+        );
+        let mpk_bytes = ffi_read_bytes!("master public key", mpk_ptr, mpk_len);
+        let mpk = ffi_unwrap!(
+            try_deserialize_any_version::<MasterPublicKey>(mpk_bytes),
+            "error deserializing master public key",
+            ErrorCode::Serialization
+        );
+        let policy_bytes = ffi_read_bytes!("policy", policy_ptr, policy_len);
+        let policy = ffi_unwrap!(
+            Policy::parse_and_convert(policy_bytes),
+            "error deserializing policy",
+            ErrorCode::Serialization
+        );
+
+        let coordinates = ffi_unwrap!(
+            user_key_coordinates(&policy, &mpk, &usk),
+            "error listing user secret key coordinates",
+            ErrorCode::Covercrypt
+        );
+        let coordinates = coordinates
+            .iter()
+            .map(|coordinate| coordinate_to_boolean_expression(coordinate))
+            .collect::<Vec<_>>();
+        let coordinates_bytes = ffi_unwrap!(
+            serde_json::to_vec(&coordinates),
+            "error serializing coordinates",
+            ErrorCode::Serialization
+        );
+        ffi_write_bytes!(
+            "coordinates",
+            &coordinates_bytes,
+            coordinates_ptr,
+            coordinates_len
+        );
+    })
+}
+
+#[no_mangle]
+/// Checks whether the given user secret key can decrypt the ciphertexts
+/// encrypted under the given access policy. No ciphertext is needed.
+///
+///  - `can_decrypt_ptr`       : Output set to 1 if the user secret key can
+///    decrypt, 0 otherwise
+///  - `usk_ptr`               : User secret key to audit
+///  - `usk_len`               : User secret key length
+///  - `mpk_ptr`               : Master public key
+///  - `mpk_len`               : Master public key length
+///  - `encryption_policy_ptr` : null terminated encryption access policy
+///  - `policy_ptr`            : Policy used to generate the keys
+///  - `policy_len`            : Size of the `Policy`
+///
+/// # Safety
+pub unsafe extern "C" fn h_can_decrypt(
+    can_decrypt_ptr: *mut i32,
+    usk_ptr: *const i8,
+    usk_len: i32,
+    mpk_ptr: *const i8,
+    mpk_len: i32,
+    encryption_policy_ptr: *const i8,
+    policy_ptr: *const i8,
+    policy_len: i32,
+) -> i32 {
+    ffi_guard!({
+        ffi_not_null!("can_decrypt_ptr", can_decrypt_ptr);
+        let usk_bytes = ffi_read_bytes!("user secret key", usk_ptr, usk_len);
+        let usk = ffi_unwrap!(
+            try_deserialize_any_version::<UserSecretKey>(usk_bytes),
+            "error deserializing user secret key",
+            ErrorCode::Serialization
+        );
+        let mpk_bytes = ffi_read_bytes!("master public key", mpk_ptr, mpk_len);
+        let mpk = ffi_unwrap!(
+            try_deserialize_any_version::<MasterPublicKey>(mpk_bytes),
+            "error deserializing master public key",
+            ErrorCode::Serialization
+        );
+        let encryption_policy_string = ffi_read_string!("encryption policy", encryption_policy_ptr);
+        let encryption_policy = ffi_unwrap!(
+            AccessPolicy::from_boolean_expression(&encryption_policy_string),
+            "error parsing encryption policy",
+            ErrorCode::Serialization
+        );
+        let policy_bytes = ffi_read_bytes!("policy", policy_ptr, policy_len);
+        let policy = ffi_unwrap!(
+            Policy::parse_and_convert(policy_bytes),
+            "error deserializing policy",
+            ErrorCode::Serialization
+        );
+
+        let res = ffi_unwrap!(
+            can_decrypt(&policy, &mpk, &usk, &encryption_policy),
+            "error checking user secret key access",
+            ErrorCode::Covercrypt
+        );
+        *can_decrypt_ptr = i32::from(res);
+        0
+    })
+}
+
+#[no_mangle]
+/// Lists the number of rotations of each coordinate of the policy the given
+/// user secret key can decrypt: the current one upon its last refresh and the
+/// older ones kept.
+///
+/// The rotations are written as a JSON object mapping the coordinates, as
+/// boolean expressions, to their number of rotations, e.g.
+/// `{"Department::HR && Security Level::Low Secret": 2}`.
+///
+///  - `rotations_ptr`   : Output buffer containing the JSON rotations
+///  - `rotations_len`   : Size of the rotations output buffer
+///  - `usk_ptr`         : User secret key to audit
+///  - `usk_len`         : User secret key length
+///  - `policy_ptr`      : Policy used to generate the keys
+///  - `policy_len`      : Size of the `Policy`
+///
+/// # Safety
+pub unsafe extern "C" fn h_user_key_rotations(
+    rotations_ptr: *mut i8,
+    rotations_len: *mut i32,
+    usk_ptr: *const i8,
+    usk_len: i32,
+    policy_ptr: *const i8,
+    policy_len: i32,
+) -> i32 {
+    ffi_guard!({
+        let usk_bytes = ffi_read_bytes!("user secret key", usk_ptr, usk_len);
+        let usk = ffi_unwrap!(
+            try_deserialize_any_version::<UserSecretKey>(usk_bytes),
+            "error deserializing user secret key",
+            ErrorCode::Serialization
+        );
+        let policy_bytes = ffi_read_bytes!("policy", policy_ptr, policy_len);
+        let policy = ffi_unwrap!(
+            Policy::parse_and_convert(policy_bytes),
+            "error deserializing policy",
+            ErrorCode::Serialization
+        );
+
+        let rotations = ffi_unwrap!(
+            user_key_rotations(&policy, &usk),
+            "error listing user secret key rotations",
+            ErrorCode::Covercrypt
+        );
+        let rotations = rotations
+            .iter()
+            .map(|(coordinate, n_rotations)| {
+                (coordinate_to_boolean_expression(coordinate), *n_rotations)
+            })
+            .collect::<HashMap<_, _>>();
+        let rotations_bytes = ffi_unwrap!(
+            serde_json::to_vec(&rotations),
+            "error serializing rotations",
+            ErrorCode::Serialization
+        );
+        ffi_write_bytes!("rotations", &rotations_bytes, rotations_ptr, rotations_len);
+    })
+}
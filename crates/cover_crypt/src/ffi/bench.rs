@@ -0,0 +1,54 @@
+use cosmian_cover_crypt::abe_policy::Policy;
+use cosmian_ffi_utils::{ffi_guard, ffi_read_bytes, ffi_unwrap, ffi_write_bytes, ErrorCode};
+
+use crate::bench::bench_covercrypt;
+
+#[no_mangle]
+/// Measures the average duration of the CoverCrypt operations for the
+/// supplied Policy.
+///
+/// The results are written as a JSON object giving the number of partitions
+/// of the policy and the durations in microseconds of the master keys
+/// generation, the user secret key generation, the encryption of a header for
+/// all the partitions and its decryption.
+///
+///  - `results_ptr` : Output buffer containing the JSON results
+///  - `results_len` : Size of the results output buffer
+///  - `policy_ptr`  : Policy to benchmark
+///  - `policy_len`  : Size of the `Policy` to benchmark
+///  - `n`           : Number of iterations of each operation
+///
+/// # Safety
+pub unsafe extern "C" fn h_bench_covercrypt(
+    results_ptr: *mut i8,
+    results_len: *mut i32,
+    policy_ptr: *const i8,
+    policy_len: i32,
+    n: i32,
+) -> i32 {
+    ffi_guard!({
+        let policy_bytes = ffi_read_bytes!("policy", policy_ptr, policy_len);
+        let policy = ffi_unwrap!(
+            Policy::parse_and_convert(policy_bytes),
+            "error deserializing policy",
+            ErrorCode::Serialization
+        );
+        let n = ffi_unwrap!(
+            usize::try_from(n),
+            "the number of iterations should be positive",
+            ErrorCode::InvalidArgument("n".to_string())
+        );
+
+        let results = ffi_unwrap!(
+            bench_covercrypt(&policy, n),
+            "error benchmarking CoverCrypt",
+            ErrorCode::Covercrypt
+        );
+        let results_bytes = ffi_unwrap!(
+            serde_json::to_vec(&results.to_json()),
+            "error serializing benchmark results",
+            ErrorCode::Serialization
+        );
+        ffi_write_bytes!("results", &results_bytes, results_ptr, results_len);
+    })
+}
@@ -1,7 +1,15 @@
 use cosmian_cover_crypt::abe_policy::{
     AccessPolicy, Attribute, DimensionBuilder, EncryptionHint, Policy,
 };
-use cosmian_ffi_utils::{ffi_read_bytes, ffi_read_string, ffi_unwrap, ffi_write_bytes, ErrorCode};
+use cosmian_ffi_utils::{
+    ffi_guard, ffi_not_null, ffi_read_bytes, ffi_read_string, ffi_unwrap, ffi_write_bytes,
+    ErrorCode,
+};
+
+use crate::{
+    policy_migration::PolicyMigrationReport, policy_spec::policy_from_json_spec,
+    post_quantum::is_post_quantum,
+};
 
 /// This macro handles deserializing the policy from JS, deserializing an
 /// attribute from JS, and performing a specified action on the policy. It also
@@ -71,13 +79,42 @@ macro_rules! update_policy {
 /// # Safety
 #[no_mangle]
 pub unsafe extern "C" fn h_policy(policy_ptr: *mut i8, policy_len: *mut i32) -> i32 {
-    let policy = Policy::new();
-    let policy_bytes = ffi_unwrap!(
-        <Vec<u8>>::try_from(&policy),
-        "error deserializing policy",
-        ErrorCode::Serialization
-    );
-    ffi_write_bytes!("policy", &policy_bytes, policy_ptr, policy_len);
+    ffi_guard!({
+        let policy = Policy::new();
+        let policy_bytes = ffi_unwrap!(
+            <Vec<u8>>::try_from(&policy),
+            "error deserializing policy",
+            ErrorCode::Serialization
+        );
+        ffi_write_bytes!("policy", &policy_bytes, policy_ptr, policy_len);
+    })
+}
+
+/// Builds the policy described by the given JSON specification.
+///
+/// See [`policy_spec`](crate::policy_spec) for the specification format.
+///
+/// # Safety
+#[no_mangle]
+pub unsafe extern "C" fn h_policy_from_json_spec(
+    policy_ptr: *mut i8,
+    policy_len: *mut i32,
+    spec_ptr: *const i8,
+) -> i32 {
+    ffi_guard!({
+        let spec = ffi_read_string!("policy specification", spec_ptr);
+        let policy = ffi_unwrap!(
+            policy_from_json_spec(spec.as_bytes()),
+            "error loading policy specification",
+            ErrorCode::CovercryptPolicy
+        );
+        let policy_bytes = ffi_unwrap!(
+            <Vec<u8>>::try_from(&policy),
+            "error serializing policy",
+            ErrorCode::Serialization
+        );
+        ffi_write_bytes!("policy", &policy_bytes, policy_ptr, policy_len);
+    })
 }
 
 /// # Safety
@@ -89,36 +126,39 @@ pub unsafe extern "C" fn h_add_policy_axis(
     current_policy_len: i32,
     axis_ptr: *const i8,
 ) -> i32 {
-    let policy_bytes = ffi_read_bytes!("current policy", current_policy_ptr, current_policy_len);
-    let mut policy = ffi_unwrap!(
-        Policy::parse_and_convert(policy_bytes),
-        "error deserializing policy",
-        ErrorCode::Serialization
-    );
-    let axis_string = ffi_read_string!("axis", axis_ptr);
-    let axis: DimensionBuilder = ffi_unwrap!(
-        serde_json::from_str(&axis_string),
-        "error deserializing policy axis",
-        ErrorCode::Serialization
-    );
-
-    ffi_unwrap!(
-        policy.add_dimension(axis),
-        "error adding policy axis",
-        ErrorCode::CovercryptPolicy
-    );
-
-    let policy_bytes = ffi_unwrap!(
-        <Vec<u8>>::try_from(&policy),
-        "error serializing policy",
-        ErrorCode::Serialization
-    );
-    ffi_write_bytes!(
-        "updated policy",
-        &policy_bytes,
-        updated_policy_ptr,
-        updated_policy_len
-    );
+    ffi_guard!({
+        let policy_bytes =
+            ffi_read_bytes!("current policy", current_policy_ptr, current_policy_len);
+        let mut policy = ffi_unwrap!(
+            Policy::parse_and_convert(policy_bytes),
+            "error deserializing policy",
+            ErrorCode::Serialization
+        );
+        let axis_string = ffi_read_string!("axis", axis_ptr);
+        let axis: DimensionBuilder = ffi_unwrap!(
+            serde_json::from_str(&axis_string),
+            "error deserializing policy axis",
+            ErrorCode::Serialization
+        );
+
+        ffi_unwrap!(
+            policy.add_dimension(axis),
+            "error adding policy axis",
+            ErrorCode::CovercryptPolicy
+        );
+
+        let policy_bytes = ffi_unwrap!(
+            <Vec<u8>>::try_from(&policy),
+            "error serializing policy",
+            ErrorCode::Serialization
+        );
+        ffi_write_bytes!(
+            "updated policy",
+            &policy_bytes,
+            updated_policy_ptr,
+            updated_policy_len
+        );
+    })
 }
 
 /// # Safety
@@ -130,32 +170,35 @@ pub unsafe extern "C" fn h_remove_policy_axis(
     current_policy_len: i32,
     axis_name_ptr: *const i8,
 ) -> i32 {
-    let policy_bytes = ffi_read_bytes!("current policy", current_policy_ptr, current_policy_len);
-    let mut policy = ffi_unwrap!(
-        Policy::parse_and_convert(policy_bytes),
-        "error deserializing policy",
-        ErrorCode::Serialization
-    );
-
-    let axis_name = ffi_read_string!("axis name", axis_name_ptr);
-
-    ffi_unwrap!(
-        policy.remove_dimension(&axis_name),
-        "error removing policy axis",
-        ErrorCode::CovercryptPolicy
-    );
-
-    let policy_bytes = ffi_unwrap!(
-        <Vec<u8>>::try_from(&policy),
-        "error serializing policy",
-        ErrorCode::Serialization
-    );
-    ffi_write_bytes!(
-        "updated policy",
-        &policy_bytes,
-        updated_policy_ptr,
-        updated_policy_len
-    );
+    ffi_guard!({
+        let policy_bytes =
+            ffi_read_bytes!("current policy", current_policy_ptr, current_policy_len);
+        let mut policy = ffi_unwrap!(
+            Policy::parse_and_convert(policy_bytes),
+            "error deserializing policy",
+            ErrorCode::Serialization
+        );
+
+        let axis_name = ffi_read_string!("axis name", axis_name_ptr);
+
+        ffi_unwrap!(
+            policy.remove_dimension(&axis_name),
+            "error removing policy axis",
+            ErrorCode::CovercryptPolicy
+        );
+
+        let policy_bytes = ffi_unwrap!(
+            <Vec<u8>>::try_from(&policy),
+            "error serializing policy",
+            ErrorCode::Serialization
+        );
+        ffi_write_bytes!(
+            "updated policy",
+            &policy_bytes,
+            updated_policy_ptr,
+            updated_policy_len
+        );
+    })
 }
 
 /// # Safety
@@ -168,17 +211,19 @@ pub unsafe extern "C" fn h_add_policy_attribute(
     attribute: *const i8,
     is_hybridized: bool,
 ) -> i32 {
-    update_policy!(
-        updated_policy_ptr,
-        updated_policy_len,
-        current_policy_ptr,
-        current_policy_len,
-        attribute,
-        cc_policy,
-        cc_attr,
-        cc_policy.add_attribute(cc_attr, EncryptionHint::new(is_hybridized)),
-        "error adding policy attribute"
-    )
+    ffi_guard!({
+        update_policy!(
+            updated_policy_ptr,
+            updated_policy_len,
+            current_policy_ptr,
+            current_policy_len,
+            attribute,
+            cc_policy,
+            cc_attr,
+            cc_policy.add_attribute(cc_attr, EncryptionHint::new(is_hybridized)),
+            "error adding policy attribute"
+        )
+    })
 }
 
 /// # Safety
@@ -190,17 +235,19 @@ pub unsafe extern "C" fn h_remove_policy_attribute(
     current_policy_len: i32,
     attribute: *const i8,
 ) -> i32 {
-    update_policy!(
-        updated_policy_ptr,
-        updated_policy_len,
-        current_policy_ptr,
-        current_policy_len,
-        attribute,
-        cc_policy,
-        cc_attr,
-        cc_policy.remove_attribute(&cc_attr),
-        "error removing policy attribute"
-    )
+    ffi_guard!({
+        update_policy!(
+            updated_policy_ptr,
+            updated_policy_len,
+            current_policy_ptr,
+            current_policy_len,
+            attribute,
+            cc_policy,
+            cc_attr,
+            cc_policy.remove_attribute(&cc_attr),
+            "error removing policy attribute"
+        )
+    })
 }
 
 /// # Safety
@@ -212,17 +259,19 @@ pub unsafe extern "C" fn h_disable_policy_attribute(
     current_policy_len: i32,
     attribute: *const i8,
 ) -> i32 {
-    update_policy!(
-        updated_policy_ptr,
-        updated_policy_len,
-        current_policy_ptr,
-        current_policy_len,
-        attribute,
-        cc_policy,
-        cc_attr,
-        cc_policy.disable_attribute(&cc_attr),
-        "error disabling policy attribute"
-    )
+    ffi_guard!({
+        update_policy!(
+            updated_policy_ptr,
+            updated_policy_len,
+            current_policy_ptr,
+            current_policy_len,
+            attribute,
+            cc_policy,
+            cc_attr,
+            cc_policy.disable_attribute(&cc_attr),
+            "error disabling policy attribute"
+        )
+    })
 }
 
 #[no_mangle]
@@ -234,43 +283,152 @@ pub unsafe extern "C" fn h_rename_policy_attribute(
     attribute: *const i8,
     new_attribute_name_ptr: *const i8,
 ) -> i32 {
-    let new_attribute_name = ffi_read_string!("new attribute name", new_attribute_name_ptr);
-
-    update_policy!(
-        updated_policy_ptr,
-        updated_policy_len,
-        current_policy_ptr,
-        current_policy_len,
-        attribute,
-        cc_policy,
-        cc_attr,
-        cc_policy.rename_attribute(&cc_attr, new_attribute_name),
-        "error renaming policy attribute"
-    )
+    ffi_guard!({
+        let new_attribute_name = ffi_read_string!("new attribute name", new_attribute_name_ptr);
+
+        update_policy!(
+            updated_policy_ptr,
+            updated_policy_len,
+            current_policy_ptr,
+            current_policy_len,
+            attribute,
+            cc_policy,
+            cc_attr,
+            cc_policy.rename_attribute(&cc_attr, new_attribute_name),
+            "error renaming policy attribute"
+        )
+    })
+}
+
+/// Writes the migration report of the edit of the previous policy into the
+/// updated policy, e.g. as returned by the policy edition functions, as a JSON
+/// object.
+///
+/// The report lists the coordinates removed by the edit, for which the
+/// ciphertexts become unreadable once the master keys are updated, and the
+/// coordinates it adds, whose user keys must be refreshed.
+///
+/// - `report_ptr`          : Output buffer containing the JSON report
+/// - `report_len`          : Size of the report output buffer
+/// - `previous_policy_ptr` : Serialized policy before the edit
+/// - `previous_policy_len` : Size of the previous policy
+/// - `updated_policy_ptr`  : Serialized policy after the edit
+/// - `updated_policy_len`  : Size of the updated policy
+///
+/// # Safety
+#[no_mangle]
+pub unsafe extern "C" fn h_policy_migration_report(
+    report_ptr: *mut i8,
+    report_len: *mut i32,
+    previous_policy_ptr: *const i8,
+    previous_policy_len: i32,
+    updated_policy_ptr: *const i8,
+    updated_policy_len: i32,
+) -> i32 {
+    ffi_guard!({
+        let previous_policy_bytes =
+            ffi_read_bytes!("previous policy", previous_policy_ptr, previous_policy_len);
+        let previous_policy = ffi_unwrap!(
+            Policy::parse_and_convert(previous_policy_bytes),
+            "error deserializing previous policy",
+            ErrorCode::Serialization
+        );
+        let updated_policy_bytes =
+            ffi_read_bytes!("updated policy", updated_policy_ptr, updated_policy_len);
+        let updated_policy = ffi_unwrap!(
+            Policy::parse_and_convert(updated_policy_bytes),
+            "error deserializing updated policy",
+            ErrorCode::Serialization
+        );
+
+        let report = ffi_unwrap!(
+            PolicyMigrationReport::new(&previous_policy, &updated_policy),
+            "error computing the policy migration report",
+            ErrorCode::CovercryptPolicy
+        );
+        let report_bytes = ffi_unwrap!(
+            serde_json::to_vec(&report.to_json()),
+            "error serializing the policy migration report",
+            ErrorCode::Serialization
+        );
+        ffi_write_bytes!(
+            "policy migration report",
+            &report_bytes,
+            report_ptr,
+            report_len
+        );
+    })
 }
 
+/// Checks whether all the encapsulations of the ciphertexts encrypted under
+/// the given access policy are hybridized (post-quantum).
+///
+/// See [`post_quantum`](crate::post_quantum).
+///
+/// - `is_post_quantum_ptr`     : Output set to 1 if all the encapsulations are
+///   hybridized, 0 otherwise
+/// - `encryption_policy_ptr`   : null terminated encryption access policy
+/// - `policy_ptr`              : Serialized policy
+/// - `policy_len`              : Size of the policy
+///
 /// # Safety
+#[no_mangle]
+pub unsafe extern "C" fn h_is_post_quantum(
+    is_post_quantum_ptr: *mut i32,
+    encryption_policy_ptr: *const i8,
+    policy_ptr: *const i8,
+    policy_len: i32,
+) -> i32 {
+    ffi_guard!({
+        ffi_not_null!("is_post_quantum_ptr", is_post_quantum_ptr);
+        let encryption_policy_string = ffi_read_string!("encryption policy", encryption_policy_ptr);
+        let encryption_policy = ffi_unwrap!(
+            AccessPolicy::from_boolean_expression(&encryption_policy_string),
+            "error parsing encryption policy",
+            ErrorCode::Serialization
+        );
+        let policy_bytes = ffi_read_bytes!("policy", policy_ptr, policy_len);
+        let policy = ffi_unwrap!(
+            Policy::parse_and_convert(policy_bytes),
+            "error deserializing policy",
+            ErrorCode::Serialization
+        );
+
+        let res = ffi_unwrap!(
+            is_post_quantum(&policy, &encryption_policy),
+            "error checking the access policy encapsulations",
+            ErrorCode::CovercryptPolicy
+        );
+        *is_post_quantum_ptr = i32::from(res);
+        0
+    })
+}
+
 #[no_mangle]
 pub unsafe extern "C" fn h_validate_boolean_expression(boolean_expression_ptr: *const i8) -> i32 {
-    let boolean_expression = ffi_read_string!("boolean expression", boolean_expression_ptr);
-    ffi_unwrap!(
-        AccessPolicy::from_boolean_expression(&boolean_expression),
-        "error parsing boolean expression",
-        ErrorCode::Serialization
-    );
-    0
+    ffi_guard!({
+        let boolean_expression = ffi_read_string!("boolean expression", boolean_expression_ptr);
+        ffi_unwrap!(
+            AccessPolicy::from_boolean_expression(&boolean_expression),
+            "error parsing boolean expression",
+            ErrorCode::Serialization
+        );
+        0
+    })
 }
 
 /// # Safety
 #[no_mangle]
 pub unsafe extern "C" fn h_validate_attribute(attribute_ptr: *const i8) -> i32 {
-    let attribute_str = ffi_read_string!("attribute", attribute_ptr);
-    ffi_unwrap!(
-        AccessPolicy::from_boolean_expression(&attribute_str),
-        "error parsing attribute",
-        ErrorCode::Serialization
-    );
-    0
+    ffi_guard!({
+        let attribute_str = ffi_read_string!("attribute", attribute_ptr);
+        ffi_unwrap!(
+            AccessPolicy::from_boolean_expression(&attribute_str),
+            "error parsing attribute",
+            ErrorCode::Serialization
+        );
+        0
+    })
 }
 
 #[cfg(test)]
@@ -359,6 +517,7 @@ mod tests {
     fn test_edit_policy() {
         let policy = policy().unwrap();
         let mut policy_bytes = <Vec<u8>>::try_from(&policy).unwrap();
+        let initial_policy_bytes = policy_bytes.clone();
         let attributes = policy.attributes();
 
         assert_eq!(attributes.len(), 9);
@@ -498,5 +657,31 @@ mod tests {
         let ffi_attributes = ffi_edited_policy.attributes();
         // Check policy size
         assert_eq!(ffi_attributes.len(), 4);
+
+        // Removing an axis changes all the coordinates.
+        let report = unsafe {
+            let mut report_bytes = vec![0u8; 8192];
+            let report_ptr = report_bytes.as_mut_ptr().cast();
+            let mut report_len = report_bytes.len() as i32;
+
+            let res = h_policy_migration_report(
+                report_ptr,
+                &mut report_len,
+                initial_policy_bytes.as_ptr().cast(),
+                initial_policy_bytes.len() as i32,
+                policy_bytes.as_ptr().cast(),
+                policy_bytes.len() as i32,
+            );
+            assert_eq!(res, 0);
+            std::slice::from_raw_parts(report_ptr.cast(), report_len as usize).to_vec()
+        };
+        let report: serde_json::Value = serde_json::from_slice(&report).unwrap();
+        assert_eq!(report["removed_coordinates"].as_array().unwrap().len(), 20);
+        assert!(report["added_coordinates"]
+            .as_array()
+            .unwrap()
+            .contains(&serde_json::Value::from("Department::Sales")));
+        assert_eq!(report["has_unreadable_ciphertexts"], true);
+        assert_eq!(report["requires_user_key_refresh"], true);
     }
 }
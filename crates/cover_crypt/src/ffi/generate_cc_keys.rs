@@ -1,9 +1,26 @@
+use std::{
+    collections::HashMap,
+    sync::{
+        atomic::{AtomicI32, Ordering},
+        RwLock,
+    },
+};
+
 use cosmian_cover_crypt::{
     abe_policy::{AccessPolicy, Policy},
-    Covercrypt, MasterPublicKey, MasterSecretKey, UserSecretKey,
+    Covercrypt, Error, MasterPublicKey, MasterSecretKey, UserSecretKey,
 };
 use cosmian_crypto_core::bytes_ser_de::Serializable;
-use cosmian_ffi_utils::{ffi_read_bytes, ffi_read_string, ffi_unwrap, ffi_write_bytes, ErrorCode};
+use cosmian_ffi_utils::{
+    ffi_bail, ffi_guard, ffi_read_bytes, ffi_read_string, ffi_unwrap, ffi_write_bytes, ErrorCode,
+};
+use lazy_static::lazy_static;
+
+use crate::{
+    key_version::{serialize_versioned, try_deserialize_any_version, upgrade_key},
+    rekey::all_partitions_access_policy,
+    rotation::refresh_user_secret_key,
+};
 
 #[no_mangle]
 /// Generates the master authority keys for supplied Policy.
@@ -24,39 +41,41 @@ pub unsafe extern "C" fn h_generate_master_keys(
     policy_ptr: *const i8,
     policy_len: i32,
 ) -> i32 {
-    let policy_bytes = ffi_read_bytes!("policy", policy_ptr, policy_len);
-    let policy: Policy = ffi_unwrap!(
-        Policy::try_from(policy_bytes),
-        "error deserializing policy",
-        ErrorCode::Serialization
-    );
+    ffi_guard!({
+        let policy_bytes = ffi_read_bytes!("policy", policy_ptr, policy_len);
+        let policy: Policy = ffi_unwrap!(
+            Policy::try_from(policy_bytes),
+            "error deserializing policy",
+            ErrorCode::Serialization
+        );
 
-    let (msk, mpk) = ffi_unwrap!(
-        Covercrypt::default().generate_master_keys(&policy),
-        "error generating master keys",
-        ErrorCode::Covercrypt
-    );
+        let (msk, mpk) = ffi_unwrap!(
+            Covercrypt::default().generate_master_keys(&policy),
+            "error generating master keys",
+            ErrorCode::Covercrypt
+        );
 
-    let msk_bytes = ffi_unwrap!(
-        msk.serialize(),
-        "error serializing master secret key",
-        ErrorCode::Serialization
-    );
-    let mpk_bytes = ffi_unwrap!(
-        mpk.serialize(),
-        "error serializing public key",
-        ErrorCode::Serialization
-    );
-    ffi_write_bytes!(
-        "master secret key",
-        &msk_bytes,
-        msk_ptr,
-        msk_len,
-        "public key",
-        &mpk_bytes,
-        mpk_ptr,
-        mpk_len
-    );
+        let msk_bytes = ffi_unwrap!(
+            serialize_versioned(&msk),
+            "error serializing master secret key",
+            ErrorCode::Serialization
+        );
+        let mpk_bytes = ffi_unwrap!(
+            serialize_versioned(&mpk),
+            "error serializing public key",
+            ErrorCode::Serialization
+        );
+        ffi_write_bytes!(
+            "master secret key",
+            &msk_bytes,
+            msk_ptr,
+            msk_len,
+            "public key",
+            &mpk_bytes,
+            mpk_ptr,
+            mpk_len
+        );
+    })
 }
 
 #[no_mangle]
@@ -79,37 +98,163 @@ pub unsafe extern "C" fn h_generate_user_secret_key(
     policy_ptr: *const i8,
     policy_len: i32,
 ) -> i32 {
-    let msk_bytes = ffi_read_bytes!("master secret key", msk_ptr, msk_len);
-    let msk = ffi_unwrap!(
-        MasterSecretKey::deserialize(msk_bytes),
-        "error deserializing master secret key",
-        ErrorCode::Serialization
-    );
-    let policy_bytes = ffi_read_bytes!("policy", policy_ptr, policy_len);
-    let policy = ffi_unwrap!(
-        Policy::parse_and_convert(policy_bytes),
-        "error deserializing policy",
-        ErrorCode::Serialization
-    );
-    let user_policy_string = ffi_read_string!("access policy", user_policy_ptr);
-    let user_policy = ffi_unwrap!(
-        AccessPolicy::from_boolean_expression(user_policy_string.as_str()),
-        "error parsing user policy",
-        ErrorCode::Serialization
-    );
+    ffi_guard!({
+        let msk_bytes = ffi_read_bytes!("master secret key", msk_ptr, msk_len);
+        let msk = ffi_unwrap!(
+            try_deserialize_any_version::<MasterSecretKey>(msk_bytes),
+            "error deserializing master secret key",
+            ErrorCode::Serialization
+        );
+        let policy_bytes = ffi_read_bytes!("policy", policy_ptr, policy_len);
+        let policy = ffi_unwrap!(
+            Policy::parse_and_convert(policy_bytes),
+            "error deserializing policy",
+            ErrorCode::Serialization
+        );
+        let user_policy_string = ffi_read_string!("access policy", user_policy_ptr);
+        let user_policy = ffi_unwrap!(
+            AccessPolicy::from_boolean_expression(user_policy_string.as_str()),
+            "error parsing user policy",
+            ErrorCode::Serialization
+        );
 
-    let usk = ffi_unwrap!(
-        Covercrypt::default().generate_user_secret_key(&msk, &user_policy, &policy),
-        "error generating user secret key",
-        ErrorCode::Covercrypt
-    );
+        let usk = ffi_unwrap!(
+            Covercrypt::default().generate_user_secret_key(&msk, &user_policy, &policy),
+            "error generating user secret key",
+            ErrorCode::Covercrypt
+        );
 
-    let usk_bytes = ffi_unwrap!(
-        usk.serialize(),
-        "error serializing user secret key",
-        ErrorCode::Serialization
-    );
-    ffi_write_bytes!("user secret key", &usk_bytes, usk_ptr, usk_len);
+        let usk_bytes = ffi_unwrap!(
+            serialize_versioned(&usk),
+            "error serializing user secret key",
+            ErrorCode::Serialization
+        );
+        ffi_write_bytes!("user secret key", &usk_bytes, usk_ptr, usk_len);
+    })
+}
+
+// A static cache of the loaded master secret keys
+lazy_static! {
+    static ref MASTER_SECRET_KEY_MAP: RwLock<HashMap<i32, MasterSecretKeyCache>> =
+        RwLock::new(HashMap::new());
+    static ref NEXT_MASTER_SECRET_KEY_ID: std::sync::atomic::AtomicI32 = AtomicI32::new(0);
+}
+
+/// Cache used to store the master secret key and its policy on the Rust side
+/// when generating multiple user secret keys.
+pub struct MasterSecretKeyCache {
+    policy: Policy,
+    msk: MasterSecretKey,
+}
+
+#[no_mangle]
+/// Loads the master secret key and the policy on the Rust side and returns a
+/// handle to them. This handle can be reused when generating user secret keys
+/// which avoids deserializing these objects in each call.
+///
+/// WARNING: [`h_unload_master_secret_key()`](h_unload_master_secret_key)
+/// should be called to reclaim the memory.
+///
+/// - `msk_handle`  : Output handle to the loaded master secret key
+/// - `msk_ptr`     : master secret key
+/// - `msk_len`     : master secret key length
+/// - `policy_ptr`  : bytes of the policy used to generate the keys
+/// - `policy_len`  : length of the policy (in bytes)
+/// # Safety
+pub unsafe extern "C" fn h_load_master_secret_key(
+    msk_handle: *mut i32,
+    msk_ptr: *const i8,
+    msk_len: i32,
+    policy_ptr: *const i8,
+    policy_len: i32,
+) -> i32 {
+    ffi_guard!({
+        let msk_bytes = ffi_read_bytes!("master secret key", msk_ptr, msk_len);
+        let msk = ffi_unwrap!(
+            try_deserialize_any_version::<MasterSecretKey>(msk_bytes),
+            "error deserializing master secret key",
+            ErrorCode::Serialization
+        );
+        let policy_bytes = ffi_read_bytes!("policy", policy_ptr, policy_len);
+        let policy = ffi_unwrap!(
+            Policy::parse_and_convert(policy_bytes),
+            "error deserializing policy",
+            ErrorCode::Serialization
+        );
+
+        let cache = MasterSecretKeyCache { policy, msk };
+        let id = NEXT_MASTER_SECRET_KEY_ID.fetch_add(1, Ordering::Acquire);
+        let mut map = MASTER_SECRET_KEY_MAP
+            .write()
+            .expect("A write mutex on master secret key cache failed");
+        map.insert(id, cache);
+        *msk_handle = id;
+
+        0
+    })
+}
+
+#[no_mangle]
+/// Reclaims the memory of a master secret key loaded with
+/// [`h_load_master_secret_key()`](h_load_master_secret_key).
+///
+/// # Safety
+pub unsafe extern "C" fn h_unload_master_secret_key(msk_handle: i32) -> i32 {
+    ffi_guard!({
+        let mut map = MASTER_SECRET_KEY_MAP
+            .write()
+            .expect("A write mutex on master secret key cache failed");
+        map.remove(&msk_handle);
+        0
+    })
+}
+
+#[no_mangle]
+/// Generates a user secret key for the given access policy using a master
+/// secret key loaded with
+/// [`h_load_master_secret_key()`](h_load_master_secret_key).
+///
+/// - `usk_ptr`         : Output buffer containing user secret key
+/// - `usk_len`         : Size of the output buffer
+/// - `msk_handle`      : handle to the loaded master secret key
+/// - `user_policy_ptr` : null terminated access policy string
+/// # Safety
+pub unsafe extern "C" fn h_generate_user_secret_key_using_handle(
+    usk_ptr: *mut i8,
+    usk_len: *mut i32,
+    msk_handle: i32,
+    user_policy_ptr: *const i8,
+) -> i32 {
+    ffi_guard!({
+        let user_policy_string = ffi_read_string!("access policy", user_policy_ptr);
+        let user_policy = ffi_unwrap!(
+            AccessPolicy::from_boolean_expression(user_policy_string.as_str()),
+            "error parsing user policy",
+            ErrorCode::Serialization
+        );
+
+        let map = MASTER_SECRET_KEY_MAP
+            .read()
+            .expect("a read mutex on the master secret key cache failed");
+        let cache = if let Some(cache) = map.get(&msk_handle) {
+            cache
+        } else {
+            ffi_bail!(format!("no master secret key with handle: {msk_handle}"));
+        };
+
+        let usk = ffi_unwrap!(
+            Covercrypt::default().generate_user_secret_key(&cache.msk, &user_policy, &cache.policy),
+            "error generating user secret key",
+            ErrorCode::Covercrypt
+        );
+
+        let usk_bytes = ffi_unwrap!(
+            serialize_versioned(&usk),
+            "error serializing user secret key",
+            ErrorCode::Serialization
+        );
+        ffi_write_bytes!("user secret key", &usk_bytes, usk_ptr, usk_len);
+    })
 }
 
 #[no_mangle]
@@ -140,55 +285,57 @@ pub unsafe extern "C" fn h_update_master_keys(
     policy_ptr: *const i8,
     policy_len: i32,
 ) -> i32 {
-    let msk_bytes = ffi_read_bytes!(
-        "current master secret key",
-        current_msk_ptr,
-        current_msk_len
-    );
-    let mut msk = ffi_unwrap!(
-        MasterSecretKey::deserialize(msk_bytes),
-        "error deserializing master secret key",
-        ErrorCode::Serialization
-    );
-    let mpk_bytes = ffi_read_bytes!("current public key", current_mpk_ptr, current_mpk_len);
-    let mut mpk = ffi_unwrap!(
-        MasterPublicKey::deserialize(mpk_bytes),
-        "error deserializing public key",
-        ErrorCode::Serialization
-    );
-    let policy_bytes = ffi_read_bytes!("policy", policy_ptr, policy_len);
-    let policy = ffi_unwrap!(
-        Policy::parse_and_convert(policy_bytes),
-        "error deserializing policy",
-        ErrorCode::Serialization
-    );
+    ffi_guard!({
+        let msk_bytes = ffi_read_bytes!(
+            "current master secret key",
+            current_msk_ptr,
+            current_msk_len
+        );
+        let mut msk = ffi_unwrap!(
+            try_deserialize_any_version::<MasterSecretKey>(msk_bytes),
+            "error deserializing master secret key",
+            ErrorCode::Serialization
+        );
+        let mpk_bytes = ffi_read_bytes!("current public key", current_mpk_ptr, current_mpk_len);
+        let mut mpk = ffi_unwrap!(
+            try_deserialize_any_version::<MasterPublicKey>(mpk_bytes),
+            "error deserializing public key",
+            ErrorCode::Serialization
+        );
+        let policy_bytes = ffi_read_bytes!("policy", policy_ptr, policy_len);
+        let policy = ffi_unwrap!(
+            Policy::parse_and_convert(policy_bytes),
+            "error deserializing policy",
+            ErrorCode::Serialization
+        );
 
-    ffi_unwrap!(
-        Covercrypt::default().update_master_keys(&policy, &mut msk, &mut mpk),
-        "error updating master keys",
-        ErrorCode::Covercrypt
-    );
+        ffi_unwrap!(
+            Covercrypt::default().update_master_keys(&policy, &mut msk, &mut mpk),
+            "error updating master keys",
+            ErrorCode::Covercrypt
+        );
 
-    let msk_bytes = ffi_unwrap!(
-        msk.serialize(),
-        "error serializing master secret key",
-        ErrorCode::Serialization
-    );
-    let mpk_bytes = ffi_unwrap!(
-        mpk.serialize(),
-        "error serializing public key",
-        ErrorCode::Serialization
-    );
-    ffi_write_bytes!(
-        "updated master secret key",
-        &msk_bytes,
-        updated_msk_ptr,
-        updated_msk_len,
-        "updated public key",
-        &mpk_bytes,
-        updated_mpk_ptr,
-        updated_mpk_len
-    );
+        let msk_bytes = ffi_unwrap!(
+            serialize_versioned(&msk),
+            "error serializing master secret key",
+            ErrorCode::Serialization
+        );
+        let mpk_bytes = ffi_unwrap!(
+            serialize_versioned(&mpk),
+            "error serializing public key",
+            ErrorCode::Serialization
+        );
+        ffi_write_bytes!(
+            "updated master secret key",
+            &msk_bytes,
+            updated_msk_ptr,
+            updated_msk_len,
+            "updated public key",
+            &mpk_bytes,
+            updated_mpk_ptr,
+            updated_mpk_len
+        );
+    })
 }
 
 #[no_mangle]
@@ -221,61 +368,154 @@ pub unsafe extern "C" fn h_rekey_master_keys(
     policy_ptr: *const i8,
     policy_len: i32,
 ) -> i32 {
-    let msk_bytes = ffi_read_bytes!(
-        "current master secret key",
-        current_msk_ptr,
-        current_msk_len
-    );
-    let mut msk = ffi_unwrap!(
-        MasterSecretKey::deserialize(msk_bytes),
-        "error deserializing master secret key",
-        ErrorCode::Serialization
-    );
-    let mpk_bytes = ffi_read_bytes!("current public key", current_mpk_ptr, current_mpk_len);
-    let mut mpk = ffi_unwrap!(
-        MasterPublicKey::deserialize(mpk_bytes),
-        "error deserializing public key",
-        ErrorCode::Serialization
-    );
-    let access_policy_string = ffi_read_string!("access policy", access_policy_ptr);
-    let access_policy = ffi_unwrap!(
-        AccessPolicy::from_boolean_expression(&access_policy_string),
-        "error parsing user policy",
-        ErrorCode::Serialization
-    );
-    let policy_bytes = ffi_read_bytes!("policy", policy_ptr, policy_len);
-    let policy = ffi_unwrap!(
-        Policy::parse_and_convert(policy_bytes),
-        "error deserializing policy",
-        ErrorCode::Serialization
-    );
+    ffi_guard!({
+        let msk_bytes = ffi_read_bytes!(
+            "current master secret key",
+            current_msk_ptr,
+            current_msk_len
+        );
+        let mut msk = ffi_unwrap!(
+            try_deserialize_any_version::<MasterSecretKey>(msk_bytes),
+            "error deserializing master secret key",
+            ErrorCode::Serialization
+        );
+        let mpk_bytes = ffi_read_bytes!("current public key", current_mpk_ptr, current_mpk_len);
+        let mut mpk = ffi_unwrap!(
+            try_deserialize_any_version::<MasterPublicKey>(mpk_bytes),
+            "error deserializing public key",
+            ErrorCode::Serialization
+        );
+        let access_policy_string = ffi_read_string!("access policy", access_policy_ptr);
+        let access_policy = ffi_unwrap!(
+            AccessPolicy::from_boolean_expression(&access_policy_string),
+            "error parsing user policy",
+            ErrorCode::Serialization
+        );
+        let policy_bytes = ffi_read_bytes!("policy", policy_ptr, policy_len);
+        let policy = ffi_unwrap!(
+            Policy::parse_and_convert(policy_bytes),
+            "error deserializing policy",
+            ErrorCode::Serialization
+        );
 
-    ffi_unwrap!(
-        Covercrypt::default().rekey_master_keys(&access_policy, &policy, &mut msk, &mut mpk),
-        "error rekeying master keys",
-        ErrorCode::Covercrypt
-    );
+        ffi_unwrap!(
+            Covercrypt::default().rekey_master_keys(&access_policy, &policy, &mut msk, &mut mpk),
+            "error rekeying master keys",
+            ErrorCode::Covercrypt
+        );
 
-    let msk_bytes = ffi_unwrap!(
-        msk.serialize(),
-        "error serializing master secret key",
-        ErrorCode::Serialization
-    );
-    let mpk_bytes = ffi_unwrap!(
-        mpk.serialize(),
-        "error serializing public key",
-        ErrorCode::Serialization
-    );
-    ffi_write_bytes!(
-        "updated master secret key",
-        &msk_bytes,
-        updated_msk_ptr,
-        updated_msk_len,
-        "updated public key",
-        &mpk_bytes,
-        updated_mpk_ptr,
-        updated_mpk_len
-    );
+        let msk_bytes = ffi_unwrap!(
+            serialize_versioned(&msk),
+            "error serializing master secret key",
+            ErrorCode::Serialization
+        );
+        let mpk_bytes = ffi_unwrap!(
+            serialize_versioned(&mpk),
+            "error serializing public key",
+            ErrorCode::Serialization
+        );
+        ffi_write_bytes!(
+            "updated master secret key",
+            &msk_bytes,
+            updated_msk_ptr,
+            updated_msk_len,
+            "updated public key",
+            &mpk_bytes,
+            updated_mpk_ptr,
+            updated_mpk_len
+        );
+    })
+}
+
+#[no_mangle]
+/// Rekeys all the partitions of the master keys, without changing the policy.
+///
+/// Unlike `h_rekey_master_keys` (policy rotation), the keys of every partition
+/// are renewed: all the user secret keys must be refreshed. Returns
+/// `CovercryptPolicy` if the policy cannot be used to target the partitions,
+/// and `Covercrypt` if the master keys do not match the policy.
+///
+/// Cf [`rekey`](crate::rekey).
+///
+/// - `updated_msk_ptr`   : Output buffer for the updated master secret key
+/// - `updated_msk_len`   : Size of the updated master secret key output buffer
+/// - `updated_mpk_ptr`   : Output buffer for the updated master public key
+/// - `updated_mpk_len`   : Size of the updated master public key output buffer
+/// - `current_msk_ptr`   : current master secret key
+/// - `current_msk_len`   : current master secret key length
+/// - `current_mpk_ptr`   : current master public key
+/// - `current_mpk_len`   : current master public key length
+/// - `policy_ptr`        : Policy of the master keys (JSON)
+/// - `policy_len`        : length of the policy (in bytes)
+/// # Safety
+pub unsafe extern "C" fn h_rekey_all_master_keys(
+    updated_msk_ptr: *mut i8,
+    updated_msk_len: *mut i32,
+    updated_mpk_ptr: *mut i8,
+    updated_mpk_len: *mut i32,
+    current_msk_ptr: *const i8,
+    current_msk_len: i32,
+    current_mpk_ptr: *const i8,
+    current_mpk_len: i32,
+    policy_ptr: *const i8,
+    policy_len: i32,
+) -> i32 {
+    ffi_guard!({
+        let msk_bytes = ffi_read_bytes!(
+            "current master secret key",
+            current_msk_ptr,
+            current_msk_len
+        );
+        let mut msk = ffi_unwrap!(
+            try_deserialize_any_version::<MasterSecretKey>(msk_bytes),
+            "error deserializing master secret key",
+            ErrorCode::Serialization
+        );
+        let mpk_bytes = ffi_read_bytes!("current public key", current_mpk_ptr, current_mpk_len);
+        let mut mpk = ffi_unwrap!(
+            try_deserialize_any_version::<MasterPublicKey>(mpk_bytes),
+            "error deserializing public key",
+            ErrorCode::Serialization
+        );
+        let policy_bytes = ffi_read_bytes!("policy", policy_ptr, policy_len);
+        let policy = ffi_unwrap!(
+            Policy::parse_and_convert(policy_bytes),
+            "error deserializing policy",
+            ErrorCode::Serialization
+        );
+        let access_policy = ffi_unwrap!(
+            all_partitions_access_policy(&policy),
+            "error targeting all the partitions",
+            ErrorCode::CovercryptPolicy
+        );
+
+        ffi_unwrap!(
+            Covercrypt::default().rekey_master_keys(&access_policy, &policy, &mut msk, &mut mpk),
+            "error rekeying all master keys",
+            ErrorCode::Covercrypt
+        );
+
+        let msk_bytes = ffi_unwrap!(
+            serialize_versioned(&msk),
+            "error serializing master secret key",
+            ErrorCode::Serialization
+        );
+        let mpk_bytes = ffi_unwrap!(
+            serialize_versioned(&mpk),
+            "error serializing public key",
+            ErrorCode::Serialization
+        );
+        ffi_write_bytes!(
+            "updated master secret key",
+            &msk_bytes,
+            updated_msk_ptr,
+            updated_msk_len,
+            "updated public key",
+            &mpk_bytes,
+            updated_mpk_ptr,
+            updated_mpk_len
+        );
+    })
 }
 
 #[no_mangle]
@@ -302,47 +542,49 @@ pub unsafe extern "C" fn h_prune_master_secret_key(
     policy_ptr: *const i8,
     policy_len: i32,
 ) -> i32 {
-    let msk_bytes = ffi_read_bytes!(
-        "current master secret key",
-        current_msk_ptr,
-        current_msk_len
-    );
-    let mut msk = ffi_unwrap!(
-        MasterSecretKey::deserialize(msk_bytes),
-        "error deserializing master secret key",
-        ErrorCode::Serialization
-    );
-    let access_policy_string = ffi_read_string!("access policy", access_policy_ptr);
-    let access_policy = ffi_unwrap!(
-        AccessPolicy::from_boolean_expression(&access_policy_string),
-        "error parsing user policy",
-        ErrorCode::Serialization
-    );
-    let policy_bytes = ffi_read_bytes!("policy", policy_ptr, policy_len);
-    let policy = ffi_unwrap!(
-        Policy::parse_and_convert(policy_bytes),
-        "error deserializing policy",
-        ErrorCode::Serialization
-    );
+    ffi_guard!({
+        let msk_bytes = ffi_read_bytes!(
+            "current master secret key",
+            current_msk_ptr,
+            current_msk_len
+        );
+        let mut msk = ffi_unwrap!(
+            try_deserialize_any_version::<MasterSecretKey>(msk_bytes),
+            "error deserializing master secret key",
+            ErrorCode::Serialization
+        );
+        let access_policy_string = ffi_read_string!("access policy", access_policy_ptr);
+        let access_policy = ffi_unwrap!(
+            AccessPolicy::from_boolean_expression(&access_policy_string),
+            "error parsing user policy",
+            ErrorCode::Serialization
+        );
+        let policy_bytes = ffi_read_bytes!("policy", policy_ptr, policy_len);
+        let policy = ffi_unwrap!(
+            Policy::parse_and_convert(policy_bytes),
+            "error deserializing policy",
+            ErrorCode::Serialization
+        );
 
-    ffi_unwrap!(
-        Covercrypt::default().prune_master_secret_key(&access_policy, &policy, &mut msk),
-        "error pruning master secret key",
-        ErrorCode::Covercrypt
-    );
+        ffi_unwrap!(
+            Covercrypt::default().prune_master_secret_key(&access_policy, &policy, &mut msk),
+            "error pruning master secret key",
+            ErrorCode::Covercrypt
+        );
 
-    let msk_bytes = ffi_unwrap!(
-        msk.serialize(),
-        "error serializing master secret key",
-        ErrorCode::Serialization
-    );
+        let msk_bytes = ffi_unwrap!(
+            serialize_versioned(&msk),
+            "error serializing master secret key",
+            ErrorCode::Serialization
+        );
 
-    ffi_write_bytes!(
-        "updated master secret key",
-        &msk_bytes,
-        updated_msk_ptr,
-        updated_msk_len,
-    );
+        ffi_write_bytes!(
+            "updated master secret key",
+            &msk_bytes,
+            updated_msk_ptr,
+            updated_msk_len,
+        );
+    })
 }
 
 #[no_mangle]
@@ -371,38 +613,194 @@ pub unsafe extern "C" fn h_refresh_user_secret_key(
     current_usk_len: i32,
     preserve_old_partitions_access: i32,
 ) -> i32 {
-    let msk_bytes = ffi_read_bytes!("master secret key", msk_ptr, msk_len);
-    let msk = ffi_unwrap!(
-        MasterSecretKey::deserialize(msk_bytes),
-        "error deserializing master secret key",
-        ErrorCode::Serialization
-    );
-    let usk_bytes = ffi_read_bytes!("current user secret key", current_usk_ptr, current_usk_len);
-    let mut usk = ffi_unwrap!(
-        UserSecretKey::deserialize(usk_bytes),
-        "error deserializing user secret key",
-        ErrorCode::Serialization
-    );
+    ffi_guard!({
+        let msk_bytes = ffi_read_bytes!("master secret key", msk_ptr, msk_len);
+        let msk = ffi_unwrap!(
+            try_deserialize_any_version::<MasterSecretKey>(msk_bytes),
+            "error deserializing master secret key",
+            ErrorCode::Serialization
+        );
+        let usk_bytes =
+            ffi_read_bytes!("current user secret key", current_usk_ptr, current_usk_len);
+        let mut usk = ffi_unwrap!(
+            try_deserialize_any_version::<UserSecretKey>(usk_bytes),
+            "error deserializing user secret key",
+            ErrorCode::Serialization
+        );
 
-    ffi_unwrap!(
-        Covercrypt::default().refresh_user_secret_key(
-            &mut usk,
-            &msk,
-            preserve_old_partitions_access != 0
-        ),
-        "error refreshing user secret key",
-        ErrorCode::Covercrypt
-    );
+        ffi_unwrap!(
+            Covercrypt::default().refresh_user_secret_key(
+                &mut usk,
+                &msk,
+                preserve_old_partitions_access != 0
+            ),
+            "error refreshing user secret key",
+            ErrorCode::Covercrypt
+        );
 
-    let usk_bytes = ffi_unwrap!(
-        usk.serialize(),
-        "error serializing user secret key",
+        let usk_bytes = ffi_unwrap!(
+            serialize_versioned(&usk),
+            "error serializing user secret key",
+            ErrorCode::Serialization
+        );
+        ffi_write_bytes!(
+            "updated user secret key",
+            &usk_bytes,
+            updated_usk_ptr,
+            updated_usk_len
+        );
+    })
+}
+
+#[no_mangle]
+/// Refreshes the user secret key according to the given master key, keeping
+/// at most `keep_old_rotations` rotations older than the current one for each
+/// partition.
+///
+/// - `updated_usk_ptr`     : Output buffer containing the updated user secret
+///   key
+/// - `updated_usk_len`     : Size of the updated user secret key output buffer
+/// - `msk_ptr`             : master secret key
+/// - `msk_len`             : master secret key length
+/// - `current_usk_ptr`     : current user secret key
+/// - `current_usk_len`     : current user secret key length
+/// - `keep_old_rotations`  : number of old rotations to keep access to
+/// # Safety
+pub unsafe extern "C" fn h_refresh_user_secret_key_with_rotations(
+    updated_usk_ptr: *mut i8,
+    updated_usk_len: *mut i32,
+    msk_ptr: *const i8,
+    msk_len: i32,
+    current_usk_ptr: *const i8,
+    current_usk_len: i32,
+    keep_old_rotations: i32,
+) -> i32 {
+    ffi_guard!({
+        let msk_bytes = ffi_read_bytes!("master secret key", msk_ptr, msk_len);
+        let msk = ffi_unwrap!(
+            try_deserialize_any_version::<MasterSecretKey>(msk_bytes),
+            "error deserializing master secret key",
+            ErrorCode::Serialization
+        );
+        let usk_bytes =
+            ffi_read_bytes!("current user secret key", current_usk_ptr, current_usk_len);
+        let mut usk = ffi_unwrap!(
+            try_deserialize_any_version::<UserSecretKey>(usk_bytes),
+            "error deserializing user secret key",
+            ErrorCode::Serialization
+        );
+        let keep_old_rotations = ffi_unwrap!(
+            usize::try_from(keep_old_rotations),
+            "the number of old rotations to keep should be positive",
+            ErrorCode::InvalidArgument("keep_old_rotations".to_string())
+        );
+
+        ffi_unwrap!(
+            refresh_user_secret_key(&msk, &mut usk, keep_old_rotations),
+            "error refreshing user secret key",
+            ErrorCode::Covercrypt
+        );
+
+        let usk_bytes = ffi_unwrap!(
+            serialize_versioned(&usk),
+            "error serializing user secret key",
+            ErrorCode::Serialization
+        );
+        ffi_write_bytes!(
+            "updated user secret key",
+            &usk_bytes,
+            updated_usk_ptr,
+            updated_usk_len
+        );
+    })
+}
+
+unsafe fn upgrade_serialized_key<K: Serializable<Error = Error>>(
+    updated_key_ptr: *mut i8,
+    updated_key_len: *mut i32,
+    current_key_ptr: *const i8,
+    current_key_len: i32,
+) -> i32 {
+    let key_bytes = ffi_read_bytes!("current key", current_key_ptr, current_key_len);
+    let key_bytes = ffi_unwrap!(
+        upgrade_key::<K>(key_bytes),
+        "error upgrading key",
         ErrorCode::Serialization
     );
-    ffi_write_bytes!(
-        "updated user secret key",
-        &usk_bytes,
-        updated_usk_ptr,
-        updated_usk_len
-    );
+    ffi_write_bytes!("updated key", &key_bytes, updated_key_ptr, updated_key_len);
+}
+
+#[no_mangle]
+/// Converts a master secret key serialized in any supported format into the
+/// current format.
+///
+/// - `updated_msk_ptr` : Output buffer containing the upgraded master secret key
+/// - `updated_msk_len` : Size of the upgraded master secret key output buffer
+/// - `current_msk_ptr` : current master secret key
+/// - `current_msk_len` : current master secret key length
+/// # Safety
+pub unsafe extern "C" fn h_upgrade_master_secret_key(
+    updated_msk_ptr: *mut i8,
+    updated_msk_len: *mut i32,
+    current_msk_ptr: *const i8,
+    current_msk_len: i32,
+) -> i32 {
+    ffi_guard!({
+        upgrade_serialized_key::<MasterSecretKey>(
+            updated_msk_ptr,
+            updated_msk_len,
+            current_msk_ptr,
+            current_msk_len,
+        )
+    })
+}
+
+#[no_mangle]
+/// Converts a master public key serialized in any supported format into the
+/// current format.
+///
+/// - `updated_mpk_ptr` : Output buffer containing the upgraded master public key
+/// - `updated_mpk_len` : Size of the upgraded master public key output buffer
+/// - `current_mpk_ptr` : current master public key
+/// - `current_mpk_len` : current master public key length
+/// # Safety
+pub unsafe extern "C" fn h_upgrade_master_public_key(
+    updated_mpk_ptr: *mut i8,
+    updated_mpk_len: *mut i32,
+    current_mpk_ptr: *const i8,
+    current_mpk_len: i32,
+) -> i32 {
+    ffi_guard!({
+        upgrade_serialized_key::<MasterPublicKey>(
+            updated_mpk_ptr,
+            updated_mpk_len,
+            current_mpk_ptr,
+            current_mpk_len,
+        )
+    })
+}
+
+#[no_mangle]
+/// Converts a user secret key serialized in any supported format into the
+/// current format.
+///
+/// - `updated_usk_ptr` : Output buffer containing the upgraded user secret key
+/// - `updated_usk_len` : Size of the upgraded user secret key output buffer
+/// - `current_usk_ptr` : current user secret key
+/// - `current_usk_len` : current user secret key length
+/// # Safety
+pub unsafe extern "C" fn h_upgrade_user_secret_key(
+    updated_usk_ptr: *mut i8,
+    updated_usk_len: *mut i32,
+    current_usk_ptr: *const i8,
+    current_usk_len: i32,
+) -> i32 {
+    ffi_guard!({
+        upgrade_serialized_key::<UserSecretKey>(
+            updated_usk_ptr,
+            updated_usk_len,
+            current_usk_ptr,
+            current_usk_len,
+        )
+    })
 }
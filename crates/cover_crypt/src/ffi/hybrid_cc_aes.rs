@@ -1,5 +1,6 @@
 use std::{
     collections::HashMap,
+    path::Path,
     sync::{
         atomic::{AtomicI32, Ordering},
         RwLock,
@@ -12,13 +13,19 @@ use cosmian_cover_crypt::{
 };
 use cosmian_crypto_core::{
     bytes_ser_de::{Deserializer, Serializable, Serializer},
-    Aes256Gcm, FixedSizeCBytes, SymmetricKey,
+    Aes256Gcm, CryptoCoreError, FixedSizeCBytes, SymmetricKey,
 };
 use cosmian_ffi_utils::{
-    ffi_bail, ffi_read_bytes, ffi_read_string, ffi_unwrap, ffi_write_bytes, ErrorCode,
+    ffi_bail, ffi_guard, ffi_not_null, ffi_read_bytes, ffi_read_string, ffi_unwrap,
+    ffi_write_bytes, ErrorCode,
 };
 use lazy_static::lazy_static;
 
+use crate::{
+    bulk, cleartext_metadata, file, header_info, header_padding,
+    key_version::try_deserialize_any_version, multi_key,
+};
+
 // -------------------------------
 //         Encryption
 // -------------------------------
@@ -53,28 +60,30 @@ pub unsafe extern "C" fn h_create_encryption_cache(
     mpk_ptr: *const i8,
     mpk_len: i32,
 ) -> i32 {
-    let policy = ffi_read_bytes!("policy", policy_ptr, policy_len);
-    let policy = ffi_unwrap!(
-        Policy::try_from(policy),
-        "error deserializing policy",
-        ErrorCode::Serialization
-    );
-    let mpk = ffi_read_bytes!("public key", mpk_ptr, mpk_len);
-    let mpk = ffi_unwrap!(
-        MasterPublicKey::deserialize(mpk),
-        "error deserializing public key",
-        ErrorCode::Serialization
-    );
-
-    let cache = EncryptionCache { policy, mpk };
-    let id = NEXT_ENCRYPTION_CACHE_ID.fetch_add(1, Ordering::Acquire);
-    let mut map = ENCRYPTION_CACHE_MAP
-        .write()
-        .expect("A write mutex on encryption cache failed");
-    map.insert(id, cache);
-    *cache_handle = id;
-
-    0
+    ffi_guard!({
+        let policy = ffi_read_bytes!("policy", policy_ptr, policy_len);
+        let policy = ffi_unwrap!(
+            Policy::try_from(policy),
+            "error deserializing policy",
+            ErrorCode::Serialization
+        );
+        let mpk = ffi_read_bytes!("public key", mpk_ptr, mpk_len);
+        let mpk = ffi_unwrap!(
+            try_deserialize_any_version::<MasterPublicKey>(mpk),
+            "error deserializing public key",
+            ErrorCode::Serialization
+        );
+
+        let cache = EncryptionCache { policy, mpk };
+        let id = NEXT_ENCRYPTION_CACHE_ID.fetch_add(1, Ordering::Acquire);
+        let mut map = ENCRYPTION_CACHE_MAP
+            .write()
+            .expect("A write mutex on encryption cache failed");
+        map.insert(id, cache);
+        *cache_handle = id;
+
+        0
+    })
 }
 
 #[no_mangle]
@@ -84,16 +93,20 @@ pub unsafe extern "C" fn h_create_encryption_cache(
 ///
 /// # Safety
 pub unsafe extern "C" fn h_destroy_encryption_cache(cache_handle: i32) -> i32 {
-    let mut map = ENCRYPTION_CACHE_MAP
-        .write()
-        .expect("A write mutex on encryption cache failed");
-    map.remove(&cache_handle);
-    0
+    ffi_guard!({
+        let mut map = ENCRYPTION_CACHE_MAP
+            .write()
+            .expect("A write mutex on encryption cache failed");
+        map.remove(&cache_handle);
+        0
+    })
 }
 
 #[no_mangle]
 /// Encrypts a header using an encryption cache.
 ///
+/// Cf [`h_encrypt_header()`](h_encrypt_header) for the symmetric key output.
+///
 /// # Safety
 pub unsafe extern "C" fn h_encrypt_header_using_cache(
     symmetric_key_ptr: *mut i8,
@@ -107,80 +120,99 @@ pub unsafe extern "C" fn h_encrypt_header_using_cache(
     authentication_data_ptr: *const i8,
     authentication_data_len: i32,
 ) -> i32 {
-    let encryption_policy_bytes = ffi_read_string!("encryption policy", encryption_policy_ptr);
-    let encryption_policy = ffi_unwrap!(
-        AccessPolicy::from_boolean_expression(&encryption_policy_bytes),
-        "error parsing encryption policy",
-        ErrorCode::CovercryptPolicy
-    );
-
-    let header_metadata = if header_metadata_ptr.is_null() || header_metadata_len == 0 {
-        None
-    } else {
-        Some(ffi_read_bytes!(
-            "header metadata",
-            header_metadata_ptr,
-            header_metadata_len
-        ))
-    };
-
-    let authentication_data = if authentication_data_ptr.is_null() || authentication_data_len == 0 {
-        None
-    } else {
-        Some(ffi_read_bytes!(
-            "authentication data",
-            authentication_data_ptr,
-            authentication_data_len
-        ))
-    };
-
-    let map = ENCRYPTION_CACHE_MAP
-        .read()
-        .expect("a read mutex on the encryption cache failed");
-    let cache = if let Some(cache) = map.get(&cache_handle) {
-        cache
-    } else {
-        ffi_bail!(format!(
-            "Hybrid Cipher: no encryption cache with handle: {cache_handle}"
-        ));
-    };
-
-    let (symmetric_key, encrypted_header) = ffi_unwrap!(
-        EncryptedHeader::generate(
-            &Covercrypt::default(),
-            &cache.policy,
-            &cache.mpk,
-            &encryption_policy,
-            header_metadata,
-            authentication_data,
-        ),
-        "error encrypting CoverCrypt header",
-        ErrorCode::Covercrypt
-    );
-
-    let encrypted_header_bytes = ffi_unwrap!(
-        encrypted_header.serialize(),
-        "error serializing encrypted CoverCrypt header",
-        ErrorCode::Serialization
-    );
-
-    ffi_write_bytes!(
-        "symmetric key",
-        &symmetric_key,
-        symmetric_key_ptr,
-        symmetric_key_len,
-        "encrypted header",
-        &encrypted_header_bytes,
-        header_bytes_ptr,
-        header_bytes_len
-    );
+    ffi_guard!({
+        let encryption_policy_bytes = ffi_read_string!("encryption policy", encryption_policy_ptr);
+        let encryption_policy = ffi_unwrap!(
+            AccessPolicy::from_boolean_expression(&encryption_policy_bytes),
+            "error parsing encryption policy",
+            ErrorCode::CovercryptPolicy
+        );
+
+        let header_metadata = if header_metadata_ptr.is_null() || header_metadata_len == 0 {
+            None
+        } else {
+            Some(ffi_read_bytes!(
+                "header metadata",
+                header_metadata_ptr,
+                header_metadata_len
+            ))
+        };
+
+        let authentication_data =
+            if authentication_data_ptr.is_null() || authentication_data_len == 0 {
+                None
+            } else {
+                Some(ffi_read_bytes!(
+                    "authentication data",
+                    authentication_data_ptr,
+                    authentication_data_len
+                ))
+            };
+
+        let map = ENCRYPTION_CACHE_MAP
+            .read()
+            .expect("a read mutex on the encryption cache failed");
+        let cache = if let Some(cache) = map.get(&cache_handle) {
+            cache
+        } else {
+            ffi_bail!(format!(
+                "Hybrid Cipher: no encryption cache with handle: {cache_handle}"
+            ));
+        };
+
+        let (symmetric_key, encrypted_header) = ffi_unwrap!(
+            EncryptedHeader::generate(
+                &Covercrypt::default(),
+                &cache.policy,
+                &cache.mpk,
+                &encryption_policy,
+                header_metadata,
+                authentication_data,
+            ),
+            "error encrypting CoverCrypt header",
+            ErrorCode::Covercrypt
+        );
+
+        let encrypted_header_bytes = ffi_unwrap!(
+            encrypted_header.serialize(),
+            "error serializing encrypted CoverCrypt header",
+            ErrorCode::Serialization
+        );
+
+        if symmetric_key_ptr.is_null() {
+            *symmetric_key_len = 0;
+            ffi_write_bytes!(
+                "encrypted header",
+                &encrypted_header_bytes,
+                header_bytes_ptr,
+                header_bytes_len
+            );
+        } else {
+            ffi_write_bytes!(
+                "symmetric key",
+                &symmetric_key,
+                symmetric_key_ptr,
+                symmetric_key_len,
+                "encrypted header",
+                &encrypted_header_bytes,
+                header_bytes_ptr,
+                header_bytes_len
+            );
+        }
+    })
 }
 
 #[no_mangle]
 /// Encrypts a header without using an encryption cache.
 /// It is slower but does not require destroying any cache when done.
 ///
-/// The symmetric key and header bytes are returned in the first OUT parameters
+/// The symmetric key and header bytes are returned in the first OUT parameters.
+///
+/// The symmetric key is the DEM key of the header: it can be given to
+/// [`h_dem_encrypt()`](h_dem_encrypt) to encrypt several related objects under
+/// this single header, and is returned upon header decryption. No symmetric key
+/// is returned if `symmetric_key_ptr` is `NULL`.
+///
 /// # Safety
 pub unsafe extern "C" fn h_encrypt_header(
     symmetric_key_ptr: *mut i8,
@@ -197,73 +229,86 @@ pub unsafe extern "C" fn h_encrypt_header(
     authentication_data_ptr: *const i8,
     authentication_data_len: i32,
 ) -> i32 {
-    let policy = ffi_read_bytes!("policy", policy_ptr, policy_len);
-    let policy = ffi_unwrap!(
-        Policy::try_from(policy),
-        "error deserializing policy",
-        ErrorCode::Serialization
-    );
-    let mpk = ffi_read_bytes!("public key", mpk_ptr, mpk_len);
-    let mpk = ffi_unwrap!(
-        MasterPublicKey::deserialize(mpk),
-        "error deserializing public key",
-        ErrorCode::Serialization
-    );
-    let encryption_policy_string = ffi_read_string!("encryption policy", encryption_policy_ptr);
-    let encryption_policy = ffi_unwrap!(
-        AccessPolicy::from_boolean_expression(&encryption_policy_string),
-        "error parsing encryption policy",
-        ErrorCode::CovercryptPolicy
-    );
-    let header_metadata = if header_metadata_ptr.is_null() || header_metadata_len == 0 {
-        None
-    } else {
-        Some(ffi_read_bytes!(
-            "header metadata",
-            header_metadata_ptr,
-            header_metadata_len
-        ))
-    };
-
-    let authentication_data = if authentication_data_ptr.is_null() || authentication_data_len == 0 {
-        None
-    } else {
-        Some(ffi_read_bytes!(
-            "authentication data",
-            authentication_data_ptr,
-            authentication_data_len
-        ))
-    };
-
-    let (symmetric_key, encrypted_header) = ffi_unwrap!(
-        EncryptedHeader::generate(
-            &Covercrypt::default(),
-            &policy,
-            &mpk,
-            &encryption_policy,
-            header_metadata,
-            authentication_data
-        ),
-        "error encrypting CoverCrypt header",
-        ErrorCode::Encryption
-    );
-
-    let encrypted_header_bytes = ffi_unwrap!(
-        encrypted_header.serialize(),
-        "error serializing encrypted CoverCrypt header",
-        ErrorCode::Serialization
-    );
-
-    ffi_write_bytes!(
-        "symmetric key",
-        &symmetric_key,
-        symmetric_key_ptr,
-        symmetric_key_len,
-        "encrypted header",
-        &encrypted_header_bytes,
-        header_bytes_ptr,
-        header_bytes_len
-    );
+    ffi_guard!({
+        let policy = ffi_read_bytes!("policy", policy_ptr, policy_len);
+        let policy = ffi_unwrap!(
+            Policy::try_from(policy),
+            "error deserializing policy",
+            ErrorCode::Serialization
+        );
+        let mpk = ffi_read_bytes!("public key", mpk_ptr, mpk_len);
+        let mpk = ffi_unwrap!(
+            try_deserialize_any_version::<MasterPublicKey>(mpk),
+            "error deserializing public key",
+            ErrorCode::Serialization
+        );
+        let encryption_policy_string = ffi_read_string!("encryption policy", encryption_policy_ptr);
+        let encryption_policy = ffi_unwrap!(
+            AccessPolicy::from_boolean_expression(&encryption_policy_string),
+            "error parsing encryption policy",
+            ErrorCode::CovercryptPolicy
+        );
+        let header_metadata = if header_metadata_ptr.is_null() || header_metadata_len == 0 {
+            None
+        } else {
+            Some(ffi_read_bytes!(
+                "header metadata",
+                header_metadata_ptr,
+                header_metadata_len
+            ))
+        };
+
+        let authentication_data =
+            if authentication_data_ptr.is_null() || authentication_data_len == 0 {
+                None
+            } else {
+                Some(ffi_read_bytes!(
+                    "authentication data",
+                    authentication_data_ptr,
+                    authentication_data_len
+                ))
+            };
+
+        let (symmetric_key, encrypted_header) = ffi_unwrap!(
+            EncryptedHeader::generate(
+                &Covercrypt::default(),
+                &policy,
+                &mpk,
+                &encryption_policy,
+                header_metadata,
+                authentication_data
+            ),
+            "error encrypting CoverCrypt header",
+            ErrorCode::Encryption
+        );
+
+        let encrypted_header_bytes = ffi_unwrap!(
+            encrypted_header.serialize(),
+            "error serializing encrypted CoverCrypt header",
+            ErrorCode::Serialization
+        );
+
+        if symmetric_key_ptr.is_null() {
+            *symmetric_key_len = 0;
+            ffi_write_bytes!(
+                "encrypted header",
+                &encrypted_header_bytes,
+                header_bytes_ptr,
+                header_bytes_len
+            );
+        } else {
+            ffi_write_bytes!(
+                "symmetric key",
+                &symmetric_key,
+                symmetric_key_ptr,
+                symmetric_key_len,
+                "encrypted header",
+                &encrypted_header_bytes,
+                header_bytes_ptr,
+                header_bytes_len
+            );
+        }
+    })
 }
 
 // -------------------------------
@@ -297,22 +342,24 @@ pub unsafe extern "C" fn h_create_decryption_cache(
     usk_ptr: *const i8,
     usk_len: i32,
 ) -> i32 {
-    let usk_bytes = ffi_read_bytes!("user secret key", usk_ptr, usk_len);
-    let usk = ffi_unwrap!(
-        UserSecretKey::deserialize(usk_bytes),
-        "error deserializing user secret key",
-        ErrorCode::Serialization
-    );
-
-    let cache = DecryptionCache { usk };
-    let id = NEXT_DECRYPTION_CACHE_ID.fetch_add(1, Ordering::Acquire);
-    let mut map = DECRYPTION_CACHE_MAP
-        .write()
-        .expect("A write mutex on decryption cache failed");
-    map.insert(id, cache);
-    *cache_handle = id;
-
-    0
+    ffi_guard!({
+        let usk_bytes = ffi_read_bytes!("user secret key", usk_ptr, usk_len);
+        let usk = ffi_unwrap!(
+            try_deserialize_any_version::<UserSecretKey>(usk_bytes),
+            "error deserializing user secret key",
+            ErrorCode::Serialization
+        );
+
+        let cache = DecryptionCache { usk };
+        let id = NEXT_DECRYPTION_CACHE_ID.fetch_add(1, Ordering::Acquire);
+        let mut map = DECRYPTION_CACHE_MAP
+            .write()
+            .expect("A write mutex on decryption cache failed");
+        map.insert(id, cache);
+        *cache_handle = id;
+
+        0
+    })
 }
 
 #[no_mangle]
@@ -320,11 +367,13 @@ pub unsafe extern "C" fn h_create_decryption_cache(
 ///
 /// # Safety
 pub unsafe extern "C" fn h_destroy_decryption_cache(cache_handle: i32) -> i32 {
-    let mut map = DECRYPTION_CACHE_MAP
-        .write()
-        .expect("A write mutex on decryption cache failed");
-    map.remove(&cache_handle);
-    0
+    ffi_guard!({
+        let mut map = DECRYPTION_CACHE_MAP
+            .write()
+            .expect("A write mutex on decryption cache failed");
+        map.remove(&cache_handle);
+        0
+    })
 }
 
 #[no_mangle]
@@ -333,6 +382,10 @@ pub unsafe extern "C" fn h_destroy_decryption_cache(cache_handle: i32) -> i32 {
 ///
 /// No header metadata is returned if `header_metadata_ptr` is `NULL`.
 ///
+/// The symmetric key decrypts the objects encrypted under this header using
+/// [`h_dem_decrypt()`](h_dem_decrypt). It is not returned if
+/// `symmetric_key_ptr` is `NULL`.
+///
 /// # Safety
 pub unsafe extern "C" fn h_decrypt_header_using_cache(
     symmetric_key_ptr: *mut i8,
@@ -345,64 +398,84 @@ pub unsafe extern "C" fn h_decrypt_header_using_cache(
     authentication_data_len: i32,
     cache_handle: i32,
 ) -> i32 {
-    let encrypted_header_bytes = ffi_read_bytes!(
-        "encrypted header",
-        encrypted_header_ptr,
-        encrypted_header_len
-    );
-    let encrypted_header = ffi_unwrap!(
-        EncryptedHeader::deserialize(encrypted_header_bytes),
-        "error deserializing encrypted header",
-        ErrorCode::Serialization
-    );
-    let authentication_data = if authentication_data_ptr.is_null() || authentication_data_len == 0 {
-        None
-    } else {
-        Some(ffi_read_bytes!(
-            "authentication data",
-            authentication_data_ptr,
-            authentication_data_len
-        ))
-    };
-
-    let map = DECRYPTION_CACHE_MAP
-        .read()
-        .expect("a read mutex on the decryption cache failed");
-    let cache = if let Some(cache) = map.get(&cache_handle) {
-        cache
-    } else {
-        ffi_bail!(format!(
-            "Hybrid Cipher: no decryption cache with handle: {cache_handle}",
-        ));
-    };
-
-    let header = ffi_unwrap!(
-        encrypted_header.decrypt(&Covercrypt::default(), &cache.usk, authentication_data),
-        "error decrypting CoverCrypt header",
-        ErrorCode::Decryption
-    );
-
-    if header_metadata_ptr.is_null() {
-        *header_metadata_len = 0;
-        ffi_write_bytes!(
-            "symmetric key",
-            &header.symmetric_key,
-            symmetric_key_ptr,
-            symmetric_key_len
+    ffi_guard!({
+        let encrypted_header_bytes = ffi_read_bytes!(
+            "encrypted header",
+            encrypted_header_ptr,
+            encrypted_header_len
         );
-    } else {
-        let metadata = header.metadata.unwrap_or_default();
-        ffi_write_bytes!(
-            "symmetric key",
-            &header.symmetric_key,
-            symmetric_key_ptr,
-            symmetric_key_len,
-            "header metadata",
-            &metadata,
-            header_metadata_ptr,
-            header_metadata_len
+        let encrypted_header = ffi_unwrap!(
+            EncryptedHeader::deserialize(encrypted_header_bytes),
+            "error deserializing encrypted header",
+            ErrorCode::Serialization
+        );
+        let authentication_data =
+            if authentication_data_ptr.is_null() || authentication_data_len == 0 {
+                None
+            } else {
+                Some(ffi_read_bytes!(
+                    "authentication data",
+                    authentication_data_ptr,
+                    authentication_data_len
+                ))
+            };
+
+        let map = DECRYPTION_CACHE_MAP
+            .read()
+            .expect("a read mutex on the decryption cache failed");
+        let cache = if let Some(cache) = map.get(&cache_handle) {
+            cache
+        } else {
+            ffi_bail!(format!(
+                "Hybrid Cipher: no decryption cache with handle: {cache_handle}",
+            ));
+        };
+
+        let header = ffi_unwrap!(
+            encrypted_header.decrypt(&Covercrypt::default(), &cache.usk, authentication_data),
+            "error decrypting CoverCrypt header",
+            ErrorCode::Decryption
         );
-    }
+
+        let metadata = header.metadata.unwrap_or_default();
+        match (symmetric_key_ptr.is_null(), header_metadata_ptr.is_null()) {
+            (true, true) => {
+                *symmetric_key_len = 0;
+                *header_metadata_len = 0;
+                0
+            }
+            (true, false) => {
+                *symmetric_key_len = 0;
+                ffi_write_bytes!(
+                    "header metadata",
+                    &metadata,
+                    header_metadata_ptr,
+                    header_metadata_len
+                );
+            }
+            (false, true) => {
+                *header_metadata_len = 0;
+                ffi_write_bytes!(
+                    "symmetric key",
+                    &header.symmetric_key,
+                    symmetric_key_ptr,
+                    symmetric_key_len
+                );
+            }
+            (false, false) => {
+                ffi_write_bytes!(
+                    "symmetric key",
+                    &header.symmetric_key,
+                    symmetric_key_ptr,
+                    symmetric_key_len,
+                    "header metadata",
+                    &metadata,
+                    header_metadata_ptr,
+                    header_metadata_len
+                );
+            }
+        }
+    })
 }
 
 #[no_mangle]
@@ -411,6 +484,10 @@ pub unsafe extern "C" fn h_decrypt_header_using_cache(
 ///
 /// No header metadata is returned if `header_metadata_ptr` is `NULL`.
 ///
+/// The symmetric key decrypts the objects encrypted under this header using
+/// [`h_dem_decrypt()`](h_dem_decrypt). It is not returned if
+/// `symmetric_key_ptr` is `NULL`.
+///
 /// # Safety
 pub unsafe extern "C" fn h_decrypt_header(
     symmetric_key_ptr: *mut i8,
@@ -424,60 +501,80 @@ pub unsafe extern "C" fn h_decrypt_header(
     usk_ptr: *const i8,
     usk_len: i32,
 ) -> i32 {
-    let usk_bytes = ffi_read_bytes!("user secret key", usk_ptr, usk_len);
-    let usk = ffi_unwrap!(
-        UserSecretKey::deserialize(usk_bytes),
-        "error deserializing user secret key",
-        ErrorCode::Serialization
-    );
-    let encrypted_header_bytes = ffi_read_bytes!(
-        "encrypted header",
-        encrypted_header_ptr,
-        encrypted_header_len
-    );
-    let encrypted_header = ffi_unwrap!(
-        EncryptedHeader::deserialize(encrypted_header_bytes),
-        "encrypted header",
-        ErrorCode::Serialization
-    );
-
-    let authentication_data = if authentication_data_ptr.is_null() || authentication_data_len == 0 {
-        None
-    } else {
-        Some(ffi_read_bytes!(
-            "authentication data",
-            authentication_data_ptr,
-            authentication_data_len
-        ))
-    };
-
-    let decrypted_header = ffi_unwrap!(
-        encrypted_header.decrypt(&Covercrypt::default(), &usk, authentication_data),
-        "error decrypting CoverCrypt header",
-        ErrorCode::Decryption
-    );
-
-    if header_metadata_ptr.is_null() {
-        *header_metadata_len = 0;
-        ffi_write_bytes!(
-            "symmetric key",
-            &decrypted_header.symmetric_key,
-            symmetric_key_ptr,
-            symmetric_key_len
+    ffi_guard!({
+        let usk_bytes = ffi_read_bytes!("user secret key", usk_ptr, usk_len);
+        let usk = ffi_unwrap!(
+            try_deserialize_any_version::<UserSecretKey>(usk_bytes),
+            "error deserializing user secret key",
+            ErrorCode::Serialization
         );
-    } else {
-        let metadata = decrypted_header.metadata.unwrap_or_default();
-        ffi_write_bytes!(
-            "symmetric key",
-            &decrypted_header.symmetric_key,
-            symmetric_key_ptr,
-            symmetric_key_len,
-            "header metadata",
-            &metadata,
-            header_metadata_ptr,
-            header_metadata_len
+        let encrypted_header_bytes = ffi_read_bytes!(
+            "encrypted header",
+            encrypted_header_ptr,
+            encrypted_header_len
+        );
+        let encrypted_header = ffi_unwrap!(
+            EncryptedHeader::deserialize(encrypted_header_bytes),
+            "encrypted header",
+            ErrorCode::Serialization
         );
-    }
+
+        let authentication_data =
+            if authentication_data_ptr.is_null() || authentication_data_len == 0 {
+                None
+            } else {
+                Some(ffi_read_bytes!(
+                    "authentication data",
+                    authentication_data_ptr,
+                    authentication_data_len
+                ))
+            };
+
+        let decrypted_header = ffi_unwrap!(
+            encrypted_header.decrypt(&Covercrypt::default(), &usk, authentication_data),
+            "error decrypting CoverCrypt header",
+            ErrorCode::Decryption
+        );
+
+        let metadata = decrypted_header.metadata.unwrap_or_default();
+        match (symmetric_key_ptr.is_null(), header_metadata_ptr.is_null()) {
+            (true, true) => {
+                *symmetric_key_len = 0;
+                *header_metadata_len = 0;
+                0
+            }
+            (true, false) => {
+                *symmetric_key_len = 0;
+                ffi_write_bytes!(
+                    "header metadata",
+                    &metadata,
+                    header_metadata_ptr,
+                    header_metadata_len
+                );
+            }
+            (false, true) => {
+                *header_metadata_len = 0;
+                ffi_write_bytes!(
+                    "symmetric key",
+                    &decrypted_header.symmetric_key,
+                    symmetric_key_ptr,
+                    symmetric_key_len
+                );
+            }
+            (false, false) => {
+                ffi_write_bytes!(
+                    "symmetric key",
+                    &decrypted_header.symmetric_key,
+                    symmetric_key_ptr,
+                    symmetric_key_len,
+                    "header metadata",
+                    &metadata,
+                    header_metadata_ptr,
+                    header_metadata_len
+                );
+            }
+        }
+    })
 }
 
 #[no_mangle]
@@ -500,36 +597,39 @@ pub unsafe extern "C" fn h_dem_encrypt(
     plaintext_ptr: *const i8,
     plaintext_len: i32,
 ) -> i32 {
-    let plaintext = ffi_read_bytes!("plaintext", plaintext_ptr, plaintext_len);
-    let symmetric_key_bytes =
-        ffi_read_bytes!("symmetric key", symmetric_key_ptr, symmetric_key_len);
-    let symmetric_key_fixed_length = ffi_unwrap!(
-        symmetric_key_bytes.try_into(),
-        "error converting to fixed length",
-        ErrorCode::Serialization
-    );
-    let symmetric_key = ffi_unwrap!(
-        SymmetricKey::try_from_bytes(symmetric_key_fixed_length),
-        "error parsing symmetric key",
-        ErrorCode::Serialization
-    );
-    let authentication_data = if authentication_data_ptr.is_null() || authentication_data_len == 0 {
-        None
-    } else {
-        Some(ffi_read_bytes!(
-            "authentication data",
-            authentication_data_ptr,
-            authentication_data_len
-        ))
-    };
-
-    let ciphertext = ffi_unwrap!(
-        Covercrypt::default().encrypt(&symmetric_key, plaintext, authentication_data),
-        "error encrypting plaintext",
-        ErrorCode::Encryption
-    );
-
-    ffi_write_bytes!("ciphertext", &ciphertext, ciphertext_ptr, ciphertext_len);
+    ffi_guard!({
+        let plaintext = ffi_read_bytes!("plaintext", plaintext_ptr, plaintext_len);
+        let symmetric_key_bytes =
+            ffi_read_bytes!("symmetric key", symmetric_key_ptr, symmetric_key_len);
+        let symmetric_key_fixed_length = ffi_unwrap!(
+            symmetric_key_bytes.try_into(),
+            "error converting to fixed length",
+            ErrorCode::Serialization
+        );
+        let symmetric_key = ffi_unwrap!(
+            SymmetricKey::try_from_bytes(symmetric_key_fixed_length),
+            "error parsing symmetric key",
+            ErrorCode::Serialization
+        );
+        let authentication_data =
+            if authentication_data_ptr.is_null() || authentication_data_len == 0 {
+                None
+            } else {
+                Some(ffi_read_bytes!(
+                    "authentication data",
+                    authentication_data_ptr,
+                    authentication_data_len
+                ))
+            };
+
+        let ciphertext = ffi_unwrap!(
+            Covercrypt::default().encrypt(&symmetric_key, plaintext, authentication_data),
+            "error encrypting plaintext",
+            ErrorCode::Encryption
+        );
+
+        ffi_write_bytes!("ciphertext", &ciphertext, ciphertext_ptr, ciphertext_len);
+    })
 }
 
 #[no_mangle]
@@ -545,41 +645,52 @@ pub unsafe extern "C" fn h_dem_decrypt(
     ciphertext_ptr: *const i8,
     ciphertext_len: i32,
 ) -> i32 {
-    let ciphertext = ffi_read_bytes!("ciphertext", ciphertext_ptr, ciphertext_len);
-    let symmetric_key_bytes =
-        ffi_read_bytes!("symmetric key", symmetric_key_ptr, symmetric_key_len);
-    let symmetric_key_fixed_length = ffi_unwrap!(
-        symmetric_key_bytes.try_into(),
-        "error converting to fixed length",
-        ErrorCode::Serialization
-    );
-    let symmetric_key = ffi_unwrap!(
-        SymmetricKey::try_from_bytes(symmetric_key_fixed_length),
-        "error parsing symmetric key",
-        ErrorCode::Serialization
-    );
-    let authentication_data = if authentication_data_ptr.is_null() || authentication_data_len == 0 {
-        None
-    } else {
-        Some(ffi_read_bytes!(
-            "authentication data",
-            authentication_data_ptr,
-            authentication_data_len
-        ))
-    };
-
-    let plaintext = ffi_unwrap!(
-        Covercrypt::default().decrypt(&symmetric_key, ciphertext, authentication_data),
-        "error decrypting symmetric ciphertext",
-        ErrorCode::Decryption
-    );
-
-    ffi_write_bytes!("plaintext", &plaintext, plaintext_ptr, plaintext_len);
+    ffi_guard!({
+        let ciphertext = ffi_read_bytes!("ciphertext", ciphertext_ptr, ciphertext_len);
+        let symmetric_key_bytes =
+            ffi_read_bytes!("symmetric key", symmetric_key_ptr, symmetric_key_len);
+        let symmetric_key_fixed_length = ffi_unwrap!(
+            symmetric_key_bytes.try_into(),
+            "error converting to fixed length",
+            ErrorCode::Serialization
+        );
+        let symmetric_key = ffi_unwrap!(
+            SymmetricKey::try_from_bytes(symmetric_key_fixed_length),
+            "error parsing symmetric key",
+            ErrorCode::Serialization
+        );
+        let authentication_data =
+            if authentication_data_ptr.is_null() || authentication_data_len == 0 {
+                None
+            } else {
+                Some(ffi_read_bytes!(
+                    "authentication data",
+                    authentication_data_ptr,
+                    authentication_data_len
+                ))
+            };
+
+        let plaintext = ffi_unwrap!(
+            Covercrypt::default().decrypt(&symmetric_key, ciphertext, authentication_data),
+            "error decrypting symmetric ciphertext",
+            ErrorCode::Decryption
+        );
+
+        ffi_write_bytes!("plaintext", &plaintext, plaintext_ptr, plaintext_len);
+    })
 }
 
 #[no_mangle]
 /// Hybrid encrypt some content
 ///
+/// The authentication data (e.g. the record ID) authenticates the symmetric
+/// ciphertext and the header metadata. It is not part of the ciphertext and
+/// should be given upon decryption. No authentication data is used if
+/// `authentication_data_ptr` is `NULL` or `authentication_data_len` is 0.
+///
+/// The encrypted header is not padded, see
+/// [`h_hybrid_encrypt_padded()`](h_hybrid_encrypt_padded).
+///
 /// # Safety
 pub unsafe extern "C" fn h_hybrid_encrypt(
     ciphertext_ptr: *mut i8,
@@ -596,78 +707,133 @@ pub unsafe extern "C" fn h_hybrid_encrypt(
     authentication_data_ptr: *const i8,
     authentication_data_len: i32,
 ) -> i32 {
-    let policy_bytes = ffi_read_bytes!("policy", policy_ptr, policy_len);
-    let policy = ffi_unwrap!(
-        Policy::parse_and_convert(policy_bytes),
-        "error deserializing policy",
-        ErrorCode::Serialization
-    );
-    let encryption_policy_string = ffi_read_string!("encryption policy", encryption_policy_ptr);
-    let encryption_policy = ffi_unwrap!(
-        AccessPolicy::from_boolean_expression(&encryption_policy_string),
-        "error parsing encryption policy",
-        ErrorCode::Serialization
-    );
-    let plaintext = ffi_read_bytes!("plaintext", plaintext_ptr, plaintext_len);
-    let mpk_bytes = ffi_read_bytes!("public key", mpk_ptr, mpk_len);
-    let mpk = ffi_unwrap!(
-        MasterPublicKey::deserialize(mpk_bytes),
-        "error deserializing public key",
-        ErrorCode::Serialization
-    );
-    let header_metadata = if header_metadata_ptr.is_null() || header_metadata_len == 0 {
-        None
-    } else {
-        Some(ffi_read_bytes!(
-            "header metadata",
-            header_metadata_ptr,
-            header_metadata_len
-        ))
-    };
-
-    let authentication_data = if authentication_data_ptr.is_null() || authentication_data_len == 0 {
-        None
-    } else {
-        Some(ffi_read_bytes!(
-            "authentication data",
-            authentication_data_ptr,
-            authentication_data_len
-        ))
-    };
-
-    let (symmetric_key, encrypted_header) = ffi_unwrap!(
-        EncryptedHeader::generate(
-            &Covercrypt::default(),
-            &policy,
-            &mpk,
-            &encryption_policy,
-            header_metadata,
-            authentication_data
-        ),
-        "error encrypting CoverCrypt header",
-        ErrorCode::Encryption
-    );
-
-    let ciphertext = ffi_unwrap!(
-        Covercrypt::default().encrypt(&symmetric_key, plaintext, authentication_data,),
-        "error encrypting plaintext",
-        ErrorCode::Encryption
-    );
-
-    let mut ser = Serializer::with_capacity(encrypted_header.length() + ciphertext.len());
-    ffi_unwrap!(
-        ser.write(&encrypted_header),
-        "error serializing encrypted CoverCrypt header",
-        ErrorCode::Serialization
-    );
-    ffi_unwrap!(
-        ser.write_array(&ciphertext),
-        "error deserializing symmetric ciphertext",
-        ErrorCode::Serialization
-    );
-    let bytes = ser.finalize();
-
-    ffi_write_bytes!("ciphertext", &bytes, ciphertext_ptr, ciphertext_len);
+    h_hybrid_encrypt_padded(
+        ciphertext_ptr,
+        ciphertext_len,
+        policy_ptr,
+        policy_len,
+        mpk_ptr,
+        mpk_len,
+        encryption_policy_ptr,
+        plaintext_ptr,
+        plaintext_len,
+        header_metadata_ptr,
+        header_metadata_len,
+        authentication_data_ptr,
+        authentication_data_len,
+        0,
+    )
+}
+
+#[no_mangle]
+/// Hybrid encrypt some content, padding the encrypted header.
+///
+/// The encrypted header is padded up to a multiple of `header_bucket_size`
+/// bytes, so that the ciphertext length does not leak the number of partitions
+/// of the encryption policy. No padding is added if `header_bucket_size` is 0.
+///
+/// See [`h_hybrid_encrypt()`](h_hybrid_encrypt) for the other parameters.
+///
+/// # Safety
+pub unsafe extern "C" fn h_hybrid_encrypt_padded(
+    ciphertext_ptr: *mut i8,
+    ciphertext_len: *mut i32,
+    policy_ptr: *const i8,
+    policy_len: i32,
+    mpk_ptr: *const i8,
+    mpk_len: i32,
+    encryption_policy_ptr: *const i8,
+    plaintext_ptr: *const i8,
+    plaintext_len: i32,
+    header_metadata_ptr: *const i8,
+    header_metadata_len: i32,
+    authentication_data_ptr: *const i8,
+    authentication_data_len: i32,
+    header_bucket_size: i32,
+) -> i32 {
+    ffi_guard!({
+        let policy_bytes = ffi_read_bytes!("policy", policy_ptr, policy_len);
+        let policy = ffi_unwrap!(
+            Policy::parse_and_convert(policy_bytes),
+            "error deserializing policy",
+            ErrorCode::Serialization
+        );
+        let encryption_policy_string = ffi_read_string!("encryption policy", encryption_policy_ptr);
+        let encryption_policy = ffi_unwrap!(
+            AccessPolicy::from_boolean_expression(&encryption_policy_string),
+            "error parsing encryption policy",
+            ErrorCode::Serialization
+        );
+        let header_bucket_size = ffi_unwrap!(
+            usize::try_from(header_bucket_size),
+            "the header bucket size should be positive",
+            ErrorCode::InvalidArgument("header_bucket_size".to_string())
+        );
+        let plaintext = ffi_read_bytes!("plaintext", plaintext_ptr, plaintext_len);
+        let mpk_bytes = ffi_read_bytes!("public key", mpk_ptr, mpk_len);
+        let mpk = ffi_unwrap!(
+            try_deserialize_any_version::<MasterPublicKey>(mpk_bytes),
+            "error deserializing public key",
+            ErrorCode::Serialization
+        );
+        let header_metadata = if header_metadata_ptr.is_null() || header_metadata_len == 0 {
+            None
+        } else {
+            Some(ffi_read_bytes!(
+                "header metadata",
+                header_metadata_ptr,
+                header_metadata_len
+            ))
+        };
+
+        let authentication_data =
+            if authentication_data_ptr.is_null() || authentication_data_len == 0 {
+                None
+            } else {
+                Some(ffi_read_bytes!(
+                    "authentication data",
+                    authentication_data_ptr,
+                    authentication_data_len
+                ))
+            };
+
+        let (symmetric_key, encrypted_header) = ffi_unwrap!(
+            EncryptedHeader::generate(
+                &Covercrypt::default(),
+                &policy,
+                &mpk,
+                &encryption_policy,
+                header_metadata,
+                authentication_data
+            ),
+            "error encrypting CoverCrypt header",
+            ErrorCode::Encryption
+        );
+
+        let ciphertext = ffi_unwrap!(
+            Covercrypt::default().encrypt(&symmetric_key, plaintext, authentication_data,),
+            "error encrypting plaintext",
+            ErrorCode::Encryption
+        );
+
+        let mut ser = Serializer::with_capacity(
+            header_padding::padded_header_length(encrypted_header.length(), header_bucket_size)
+                + ciphertext.len(),
+        );
+        ffi_unwrap!(
+            header_padding::write_padded_header(&mut ser, &encrypted_header, header_bucket_size),
+            "error serializing encrypted CoverCrypt header",
+            ErrorCode::Serialization
+        );
+        ffi_unwrap!(
+            ser.write_array(&ciphertext),
+            "error deserializing symmetric ciphertext",
+            ErrorCode::Serialization
+        );
+        let bytes = ser.finalize();
+
+        ffi_write_bytes!("ciphertext", &bytes, ciphertext_ptr, ciphertext_len);
+    })
 }
 
 #[no_mangle]
@@ -675,6 +841,12 @@ pub unsafe extern "C" fn h_hybrid_encrypt(
 ///
 /// No header metadata is returned if `header_metadata_ptr` is `NULL`.
 ///
+/// The decryption fails if the authentication data differs from the one given
+/// upon encryption.
+///
+/// The encrypted header should not be padded, see
+/// [`h_hybrid_decrypt_padded()`](h_hybrid_decrypt_padded).
+///
 /// # Safety
 pub unsafe extern "C" fn h_hybrid_decrypt(
     plaintext_ptr: *mut i8,
@@ -688,64 +860,853 @@ pub unsafe extern "C" fn h_hybrid_decrypt(
     usk_ptr: *const i8,
     usk_len: i32,
 ) -> i32 {
-    let usk_bytes = ffi_read_bytes!("user secret key", usk_ptr, usk_len);
-    let usk = ffi_unwrap!(
-        UserSecretKey::deserialize(usk_bytes),
-        "error deserializing user secret key",
-        ErrorCode::Serialization
-    );
-    let authentication_data = if authentication_data_ptr.is_null() || authentication_data_len == 0 {
-        None
-    } else {
-        Some(ffi_read_bytes!(
-            "authentication data",
-            authentication_data_ptr,
-            authentication_data_len
-        ))
-    };
-
-    let ciphertext = ffi_read_bytes!("ciphertext", ciphertext_ptr, ciphertext_len);
-    let mut de = Deserializer::new(ciphertext);
-    let encrypted_header = ffi_unwrap!(
-        // this will read the exact header size
-        de.read::<EncryptedHeader>(),
-        "error deserializing encrypted CoverCrypt header",
-        ErrorCode::Serialization
-    );
-    // the rest is the symmetric ciphertext
-    let encrypted_content = de.finalize();
-
-    // Decrypt header
-    let decrypted_header = ffi_unwrap!(
-        encrypted_header.decrypt(&Covercrypt::default(), &usk, authentication_data),
-        "error decrypting CoverCrypt header",
-        ErrorCode::Decryption
-    );
-
-    let plaintext = ffi_unwrap!(
-        Covercrypt::default().decrypt(
-            &decrypted_header.symmetric_key,
-            &encrypted_content,
-            authentication_data,
-        ),
-        "error decrypting symmetric ciphertext",
-        ErrorCode::Decryption
-    );
-
-    if header_metadata_ptr.is_null() {
-        *header_metadata_len = 0;
-        ffi_write_bytes!("plaintext", &plaintext, plaintext_ptr, plaintext_len);
-    } else {
-        let metadata = decrypted_header.metadata.unwrap_or_default();
+    h_hybrid_decrypt_padded(
+        plaintext_ptr,
+        plaintext_len,
+        header_metadata_ptr,
+        header_metadata_len,
+        ciphertext_ptr,
+        ciphertext_len,
+        authentication_data_ptr,
+        authentication_data_len,
+        usk_ptr,
+        usk_len,
+        0,
+    )
+}
+
+#[no_mangle]
+/// Hybrid decrypt some content encrypted using
+/// [`h_hybrid_encrypt_padded()`](h_hybrid_encrypt_padded).
+///
+/// The `header_bucket_size` should be the one given upon encryption.
+///
+/// See [`h_hybrid_decrypt()`](h_hybrid_decrypt) for the other parameters.
+///
+/// # Safety
+pub unsafe extern "C" fn h_hybrid_decrypt_padded(
+    plaintext_ptr: *mut i8,
+    plaintext_len: *mut i32,
+    header_metadata_ptr: *mut i8,
+    header_metadata_len: *mut i32,
+    ciphertext_ptr: *const i8,
+    ciphertext_len: i32,
+    authentication_data_ptr: *const i8,
+    authentication_data_len: i32,
+    usk_ptr: *const i8,
+    usk_len: i32,
+    header_bucket_size: i32,
+) -> i32 {
+    ffi_guard!({
+        let usk_bytes = ffi_read_bytes!("user secret key", usk_ptr, usk_len);
+        let usk = ffi_unwrap!(
+            try_deserialize_any_version::<UserSecretKey>(usk_bytes),
+            "error deserializing user secret key",
+            ErrorCode::Serialization
+        );
+        let authentication_data =
+            if authentication_data_ptr.is_null() || authentication_data_len == 0 {
+                None
+            } else {
+                Some(ffi_read_bytes!(
+                    "authentication data",
+                    authentication_data_ptr,
+                    authentication_data_len
+                ))
+            };
+        let header_bucket_size = ffi_unwrap!(
+            usize::try_from(header_bucket_size),
+            "the header bucket size should be positive",
+            ErrorCode::InvalidArgument("header_bucket_size".to_string())
+        );
+
+        let ciphertext = ffi_read_bytes!("ciphertext", ciphertext_ptr, ciphertext_len);
+        // the padded header is followed by the symmetric ciphertext
+        let (encrypted_header, encrypted_content) = ffi_unwrap!(
+            header_padding::read_padded_header(ciphertext, header_bucket_size),
+            "error deserializing encrypted CoverCrypt header",
+            ErrorCode::Serialization
+        );
+
+        // Decrypt header
+        let decrypted_header = ffi_unwrap!(
+            encrypted_header.decrypt(&Covercrypt::default(), &usk, authentication_data),
+            "error decrypting CoverCrypt header",
+            ErrorCode::Decryption
+        );
+
+        let plaintext = ffi_unwrap!(
+            Covercrypt::default().decrypt(
+                &decrypted_header.symmetric_key,
+                encrypted_content,
+                authentication_data,
+            ),
+            "error decrypting symmetric ciphertext",
+            ErrorCode::Decryption
+        );
+
+        if header_metadata_ptr.is_null() {
+            *header_metadata_len = 0;
+            ffi_write_bytes!("plaintext", &plaintext, plaintext_ptr, plaintext_len);
+        } else {
+            let metadata = decrypted_header.metadata.unwrap_or_default();
+            ffi_write_bytes!(
+                "plaintext",
+                &plaintext,
+                plaintext_ptr,
+                plaintext_len,
+                "header metadata",
+                &metadata,
+                header_metadata_ptr,
+                header_metadata_len
+            );
+        }
+    })
+}
+
+#[no_mangle]
+/// Hybrid encrypts some content and prepends the given cleartext metadata to
+/// the ciphertext.
+///
+/// The cleartext metadata can be read without any key using
+/// [`h_read_cleartext_metadata()`](h_read_cleartext_metadata) but is
+/// authenticated upon decryption.
+///
+/// # Safety
+pub unsafe extern "C" fn h_hybrid_encrypt_with_cleartext_metadata(
+    ciphertext_ptr: *mut i8,
+    ciphertext_len: *mut i32,
+    policy_ptr: *const i8,
+    policy_len: i32,
+    mpk_ptr: *const i8,
+    mpk_len: i32,
+    encryption_policy_ptr: *const i8,
+    plaintext_ptr: *const i8,
+    plaintext_len: i32,
+    cleartext_metadata_ptr: *const i8,
+    cleartext_metadata_len: i32,
+    header_metadata_ptr: *const i8,
+    header_metadata_len: i32,
+    authentication_data_ptr: *const i8,
+    authentication_data_len: i32,
+) -> i32 {
+    ffi_guard!({
+        let policy_bytes = ffi_read_bytes!("policy", policy_ptr, policy_len);
+        let policy = ffi_unwrap!(
+            Policy::parse_and_convert(policy_bytes),
+            "error deserializing policy",
+            ErrorCode::Serialization
+        );
+        let encryption_policy_string = ffi_read_string!("encryption policy", encryption_policy_ptr);
+        let encryption_policy = ffi_unwrap!(
+            AccessPolicy::from_boolean_expression(&encryption_policy_string),
+            "error parsing encryption policy",
+            ErrorCode::Serialization
+        );
+        let plaintext = ffi_read_bytes!("plaintext", plaintext_ptr, plaintext_len);
+        let mpk_bytes = ffi_read_bytes!("public key", mpk_ptr, mpk_len);
+        let mpk = ffi_unwrap!(
+            try_deserialize_any_version::<MasterPublicKey>(mpk_bytes),
+            "error deserializing public key",
+            ErrorCode::Serialization
+        );
+        let cleartext_metadata: &[u8] =
+            if cleartext_metadata_ptr.is_null() || cleartext_metadata_len == 0 {
+                &[]
+            } else {
+                ffi_read_bytes!(
+                    "cleartext metadata",
+                    cleartext_metadata_ptr,
+                    cleartext_metadata_len
+                )
+            };
+        let header_metadata = if header_metadata_ptr.is_null() || header_metadata_len == 0 {
+            None
+        } else {
+            Some(ffi_read_bytes!(
+                "header metadata",
+                header_metadata_ptr,
+                header_metadata_len
+            ))
+        };
+
+        let authentication_data =
+            if authentication_data_ptr.is_null() || authentication_data_len == 0 {
+                None
+            } else {
+                Some(ffi_read_bytes!(
+                    "authentication data",
+                    authentication_data_ptr,
+                    authentication_data_len
+                ))
+            };
+
+        let ciphertext = ffi_unwrap!(
+            cleartext_metadata::encrypt_with_cleartext_metadata(
+                &Covercrypt::default(),
+                &policy,
+                &mpk,
+                &encryption_policy,
+                plaintext,
+                cleartext_metadata,
+                header_metadata,
+                authentication_data,
+            ),
+            "error encrypting plaintext",
+            ErrorCode::Encryption
+        );
+
+        ffi_write_bytes!("ciphertext", &ciphertext, ciphertext_ptr, ciphertext_len);
+    })
+}
+
+#[no_mangle]
+/// Reads the cleartext metadata of a ciphertext produced by
+/// [`h_hybrid_encrypt_with_cleartext_metadata()`](h_hybrid_encrypt_with_cleartext_metadata)
+/// without any key.
+///
+/// This metadata is only authenticated upon decryption.
+///
+/// # Safety
+pub unsafe extern "C" fn h_read_cleartext_metadata(
+    cleartext_metadata_ptr: *mut i8,
+    cleartext_metadata_len: *mut i32,
+    ciphertext_ptr: *const i8,
+    ciphertext_len: i32,
+) -> i32 {
+    ffi_guard!({
+        let ciphertext = ffi_read_bytes!("ciphertext", ciphertext_ptr, ciphertext_len);
+        let metadata = ffi_unwrap!(
+            cleartext_metadata::read_cleartext_metadata(ciphertext),
+            "error reading cleartext metadata",
+            ErrorCode::Serialization
+        );
+        ffi_write_bytes!(
+            "cleartext metadata",
+            &metadata,
+            cleartext_metadata_ptr,
+            cleartext_metadata_len
+        );
+    })
+}
+
+#[no_mangle]
+/// Reads structural information about the encrypted header of the given
+/// hybrid ciphertext without any key, e.g. to validate or route ciphertexts
+/// on the storage side.
+///
+/// The information is written as a JSON object giving the length of the
+/// header, the length of its key encapsulation, the number of partitions and
+/// hybridized partitions it is encapsulated for, whether it is hybridized and
+/// whether it holds encrypted metadata. The authentication data is never
+/// stored in the ciphertext.
+///
+///  - `header_info_ptr` : Output buffer containing the JSON information
+///  - `header_info_len` : Size of the information output buffer
+///  - `ciphertext_ptr`  : Hybrid ciphertext, or encrypted header
+///  - `ciphertext_len`  : Size of the ciphertext
+///
+/// # Safety
+pub unsafe extern "C" fn h_get_header_metadata(
+    header_info_ptr: *mut i8,
+    header_info_len: *mut i32,
+    ciphertext_ptr: *const i8,
+    ciphertext_len: i32,
+) -> i32 {
+    ffi_guard!({
+        let ciphertext = ffi_read_bytes!("ciphertext", ciphertext_ptr, ciphertext_len);
+        let header_info = ffi_unwrap!(
+            header_info::read_header_info(ciphertext),
+            "error reading encrypted header",
+            ErrorCode::Serialization
+        );
+        let header_info_bytes = ffi_unwrap!(
+            serde_json::to_vec(&header_info.to_json()),
+            "error serializing header information",
+            ErrorCode::Serialization
+        );
+        ffi_write_bytes!(
+            "header information",
+            &header_info_bytes,
+            header_info_ptr,
+            header_info_len
+        );
+    })
+}
+
+#[no_mangle]
+/// Hybrid decrypts a ciphertext produced by
+/// [`h_hybrid_encrypt_with_cleartext_metadata()`](h_hybrid_encrypt_with_cleartext_metadata)
+/// and returns its plaintext, header metadata and authenticated cleartext
+/// metadata.
+///
+/// # Safety
+pub unsafe extern "C" fn h_hybrid_decrypt_with_cleartext_metadata(
+    plaintext_ptr: *mut i8,
+    plaintext_len: *mut i32,
+    header_metadata_ptr: *mut i8,
+    header_metadata_len: *mut i32,
+    cleartext_metadata_ptr: *mut i8,
+    cleartext_metadata_len: *mut i32,
+    ciphertext_ptr: *const i8,
+    ciphertext_len: i32,
+    authentication_data_ptr: *const i8,
+    authentication_data_len: i32,
+    usk_ptr: *const i8,
+    usk_len: i32,
+) -> i32 {
+    ffi_guard!({
+        let usk_bytes = ffi_read_bytes!("user secret key", usk_ptr, usk_len);
+        let usk = ffi_unwrap!(
+            try_deserialize_any_version::<UserSecretKey>(usk_bytes),
+            "error deserializing user secret key",
+            ErrorCode::Serialization
+        );
+        let authentication_data =
+            if authentication_data_ptr.is_null() || authentication_data_len == 0 {
+                None
+            } else {
+                Some(ffi_read_bytes!(
+                    "authentication data",
+                    authentication_data_ptr,
+                    authentication_data_len
+                ))
+            };
+        let ciphertext = ffi_read_bytes!("ciphertext", ciphertext_ptr, ciphertext_len);
+
+        let res = ffi_unwrap!(
+            cleartext_metadata::decrypt_with_cleartext_metadata(
+                &Covercrypt::default(),
+                &usk,
+                ciphertext,
+                authentication_data,
+            ),
+            "error decrypting ciphertext",
+            ErrorCode::Decryption
+        );
+
+        let header_metadata = res.header_metadata.unwrap_or_default();
         ffi_write_bytes!(
             "plaintext",
-            &plaintext,
+            &res.plaintext,
             plaintext_ptr,
             plaintext_len,
             "header metadata",
-            &metadata,
+            &header_metadata,
+            header_metadata_ptr,
+            header_metadata_len,
+            "cleartext metadata",
+            &res.cleartext_metadata,
+            cleartext_metadata_ptr,
+            cleartext_metadata_len
+        );
+    })
+}
+
+#[no_mangle]
+/// Hybrid decrypts some content using the first of the given user secret keys
+/// able to decrypt its header.
+///
+/// The user secret keys are serialized as `LEB128(n_keys) || LEB128(usk_1.len())
+/// || usk_1 || ...`. The index of the key that decrypted the ciphertext is
+/// written to `key_index`.
+///
+/// # Safety
+pub unsafe extern "C" fn h_hybrid_decrypt_with_keys(
+    plaintext_ptr: *mut i8,
+    plaintext_len: *mut i32,
+    header_metadata_ptr: *mut i8,
+    header_metadata_len: *mut i32,
+    key_index: *mut i32,
+    ciphertext_ptr: *const i8,
+    ciphertext_len: i32,
+    authentication_data_ptr: *const i8,
+    authentication_data_len: i32,
+    usks_ptr: *const i8,
+    usks_len: i32,
+) -> i32 {
+    ffi_guard!({
+        let usks_bytes = ffi_read_bytes!("user secret keys", usks_ptr, usks_len);
+        let usks = ffi_unwrap!(
+            deserialize_user_secret_keys(usks_bytes),
+            "error deserializing user secret keys",
+            ErrorCode::Serialization
+        );
+        let authentication_data =
+            if authentication_data_ptr.is_null() || authentication_data_len == 0 {
+                None
+            } else {
+                Some(ffi_read_bytes!(
+                    "authentication data",
+                    authentication_data_ptr,
+                    authentication_data_len
+                ))
+            };
+        let ciphertext = ffi_read_bytes!("ciphertext", ciphertext_ptr, ciphertext_len);
+
+        let res = ffi_unwrap!(
+            multi_key::decrypt_with_keys(
+                &Covercrypt::default(),
+                &usks,
+                ciphertext,
+                authentication_data
+            ),
+            "error decrypting ciphertext",
+            ErrorCode::Decryption
+        );
+        *key_index = ffi_unwrap!(
+            i32::try_from(res.key_index),
+            "key index overflow",
+            ErrorCode::Decryption
+        );
+
+        let header_metadata = res.header_metadata.unwrap_or_default();
+        ffi_write_bytes!(
+            "plaintext",
+            &res.plaintext,
+            plaintext_ptr,
+            plaintext_len,
+            "header metadata",
+            &header_metadata,
+            header_metadata_ptr,
+            header_metadata_len
+        );
+    })
+}
+
+/// Deserializes a list of user secret keys serialized as `LEB128(n_keys) ||
+/// LEB128(usk_1.len()) || usk_1 || ...`.
+fn deserialize_user_secret_keys(
+    bytes: &[u8],
+) -> Result<Vec<UserSecretKey>, cosmian_cover_crypt::Error> {
+    let mut de = Deserializer::new(bytes);
+    let n_keys = de.read_leb128_u64()?;
+    (0..n_keys)
+        .map(|_| try_deserialize_any_version::<UserSecretKey>(&de.read_vec()?))
+        .collect()
+}
+
+#[no_mangle]
+/// Hybrid decrypts the given ciphertexts in parallel using the given user
+/// secret key.
+///
+/// The ciphertexts, the authentication data and the plaintexts are serialized
+/// as `LEB128(n) || LEB128(bytes_1.len()) || bytes_1 || ...`. Either no
+/// authentication data or one per ciphertext must be given; an empty
+/// authentication data is equivalent to no authentication data. The
+/// plaintexts are written in the order of the ciphertexts.
+///
+/// The decryption fails if any of the ciphertexts cannot be decrypted.
+///
+/// # Safety
+pub unsafe extern "C" fn h_hybrid_decrypt_bulk(
+    plaintexts_ptr: *mut i8,
+    plaintexts_len: *mut i32,
+    ciphertexts_ptr: *const i8,
+    ciphertexts_len: i32,
+    authentication_data_ptr: *const i8,
+    authentication_data_len: i32,
+    usk_ptr: *const i8,
+    usk_len: i32,
+) -> i32 {
+    ffi_guard!({
+        let usk_bytes = ffi_read_bytes!("user secret key", usk_ptr, usk_len);
+        let usk = ffi_unwrap!(
+            try_deserialize_any_version::<UserSecretKey>(usk_bytes),
+            "error deserializing user secret key",
+            ErrorCode::Serialization
+        );
+        let ciphertexts_bytes = ffi_read_bytes!("ciphertexts", ciphertexts_ptr, ciphertexts_len);
+        let ciphertexts = ffi_unwrap!(
+            deserialize_byte_strings(ciphertexts_bytes),
+            "error deserializing ciphertexts",
+            ErrorCode::Serialization
+        );
+        let authentication_data =
+            if authentication_data_ptr.is_null() || authentication_data_len == 0 {
+                Vec::new()
+            } else {
+                ffi_unwrap!(
+                    deserialize_byte_strings(ffi_read_bytes!(
+                        "authentication data",
+                        authentication_data_ptr,
+                        authentication_data_len
+                    )),
+                    "error deserializing authentication data",
+                    ErrorCode::Serialization
+                )
+            };
+
+        let plaintexts = ffi_unwrap!(
+            bulk::decrypt_bulk(
+                &Covercrypt::default(),
+                &usk,
+                &ciphertexts,
+                &authentication_data
+            ),
+            "error decrypting ciphertexts",
+            ErrorCode::Decryption
+        );
+
+        let mut ser = Serializer::new();
+        ffi_unwrap!(
+            ser.write_leb128_u64(plaintexts.len() as u64),
+            "error serializing plaintexts",
+            ErrorCode::Serialization
+        );
+        for plaintext in &plaintexts {
+            ffi_unwrap!(
+                ser.write_vec(plaintext),
+                "error serializing plaintexts",
+                ErrorCode::Serialization
+            );
+        }
+        let plaintexts_bytes = ser.finalize();
+        ffi_write_bytes!(
+            "plaintexts",
+            &plaintexts_bytes,
+            plaintexts_ptr,
+            plaintexts_len
+        );
+    })
+}
+
+/// Deserializes a list of byte strings serialized as `LEB128(n) ||
+/// LEB128(bytes_1.len()) || bytes_1 || ...`.
+fn deserialize_byte_strings(bytes: &[u8]) -> Result<Vec<Vec<u8>>, CryptoCoreError> {
+    let mut de = Deserializer::new(bytes);
+    let n = de.read_leb128_u64()?;
+    (0..n).map(|_| de.read_vec()).collect()
+}
+
+#[no_mangle]
+/// Hybrid encrypts the given input file into the given output file.
+///
+/// The file is encrypted chunk by chunk and is never entirely loaded in
+/// memory. Cf [`file`](crate::file) for the ciphertext format. The output file
+/// is only written upon success.
+///
+/// # Safety
+pub unsafe extern "C" fn h_hybrid_encrypt_file(
+    input_path_ptr: *const i8,
+    output_path_ptr: *const i8,
+    policy_ptr: *const i8,
+    policy_len: i32,
+    mpk_ptr: *const i8,
+    mpk_len: i32,
+    encryption_policy_ptr: *const i8,
+    header_metadata_ptr: *const i8,
+    header_metadata_len: i32,
+    authentication_data_ptr: *const i8,
+    authentication_data_len: i32,
+) -> i32 {
+    ffi_guard!({
+        let input_path = ffi_read_string!("input path", input_path_ptr);
+        let output_path = ffi_read_string!("output path", output_path_ptr);
+        let policy_bytes = ffi_read_bytes!("policy", policy_ptr, policy_len);
+        let policy = ffi_unwrap!(
+            Policy::parse_and_convert(policy_bytes),
+            "error deserializing policy",
+            ErrorCode::Serialization
+        );
+        let encryption_policy_string = ffi_read_string!("encryption policy", encryption_policy_ptr);
+        let encryption_policy = ffi_unwrap!(
+            AccessPolicy::from_boolean_expression(&encryption_policy_string),
+            "error parsing encryption policy",
+            ErrorCode::Serialization
+        );
+        let mpk_bytes = ffi_read_bytes!("public key", mpk_ptr, mpk_len);
+        let mpk = ffi_unwrap!(
+            try_deserialize_any_version::<MasterPublicKey>(mpk_bytes),
+            "error deserializing public key",
+            ErrorCode::Serialization
+        );
+        let header_metadata = if header_metadata_ptr.is_null() || header_metadata_len == 0 {
+            None
+        } else {
+            Some(ffi_read_bytes!(
+                "header metadata",
+                header_metadata_ptr,
+                header_metadata_len
+            ))
+        };
+        let authentication_data =
+            if authentication_data_ptr.is_null() || authentication_data_len == 0 {
+                None
+            } else {
+                Some(ffi_read_bytes!(
+                    "authentication data",
+                    authentication_data_ptr,
+                    authentication_data_len
+                ))
+            };
+
+        ffi_unwrap!(
+            file::encrypt_file(
+                &Covercrypt::default(),
+                &policy,
+                &mpk,
+                &encryption_policy,
+                Path::new(&input_path),
+                Path::new(&output_path),
+                header_metadata,
+                authentication_data,
+            ),
+            "error encrypting file",
+            ErrorCode::Encryption
+        );
+
+        0
+    })
+}
+
+#[no_mangle]
+/// Hybrid decrypts the given input file into the given output file.
+///
+/// The output file is only written upon success. No header metadata is
+/// returned if `header_metadata_ptr` is `NULL`, in which case
+/// `header_metadata_len` may be `NULL` too.
+///
+/// # Safety
+pub unsafe extern "C" fn h_hybrid_decrypt_file(
+    header_metadata_ptr: *mut i8,
+    header_metadata_len: *mut i32,
+    input_path_ptr: *const i8,
+    output_path_ptr: *const i8,
+    authentication_data_ptr: *const i8,
+    authentication_data_len: i32,
+    usk_ptr: *const i8,
+    usk_len: i32,
+) -> i32 {
+    ffi_guard!({
+        if !header_metadata_ptr.is_null() {
+            ffi_not_null!("header metadata length", header_metadata_len);
+        }
+        let input_path = ffi_read_string!("input path", input_path_ptr);
+        let output_path = ffi_read_string!("output path", output_path_ptr);
+        let usk_bytes = ffi_read_bytes!("user secret key", usk_ptr, usk_len);
+        let usk = ffi_unwrap!(
+            try_deserialize_any_version::<UserSecretKey>(usk_bytes),
+            "error deserializing user secret key",
+            ErrorCode::Serialization
+        );
+        let authentication_data =
+            if authentication_data_ptr.is_null() || authentication_data_len == 0 {
+                None
+            } else {
+                Some(ffi_read_bytes!(
+                    "authentication data",
+                    authentication_data_ptr,
+                    authentication_data_len
+                ))
+            };
+
+        let header_metadata = ffi_unwrap!(
+            file::decrypt_file(
+                &Covercrypt::default(),
+                &usk,
+                Path::new(&input_path),
+                Path::new(&output_path),
+                authentication_data,
+            ),
+            "error decrypting file",
+            ErrorCode::Decryption
+        );
+
+        if header_metadata_ptr.is_null() {
+            if !header_metadata_len.is_null() {
+                *header_metadata_len = 0;
+            }
+            return 0;
+        }
+        let header_metadata = header_metadata.unwrap_or_default();
+        ffi_write_bytes!(
+            "header metadata",
+            &header_metadata,
             header_metadata_ptr,
             header_metadata_len
         );
-    }
+    })
+}
+
+#[no_mangle]
+/// Hybrid encrypts some content using an encryption cache.
+///
+/// Cf [`h_create_encryption_cache()`](h_create_encryption_cache).
+///
+/// # Safety
+pub unsafe extern "C" fn h_hybrid_encrypt_using_cache(
+    ciphertext_ptr: *mut i8,
+    ciphertext_len: *mut i32,
+    cache_handle: i32,
+    encryption_policy_ptr: *const i8,
+    plaintext_ptr: *const i8,
+    plaintext_len: i32,
+    header_metadata_ptr: *const i8,
+    header_metadata_len: i32,
+    authentication_data_ptr: *const i8,
+    authentication_data_len: i32,
+) -> i32 {
+    ffi_guard!({
+        let encryption_policy_string = ffi_read_string!("encryption policy", encryption_policy_ptr);
+        let encryption_policy = ffi_unwrap!(
+            AccessPolicy::from_boolean_expression(&encryption_policy_string),
+            "error parsing encryption policy",
+            ErrorCode::CovercryptPolicy
+        );
+        let plaintext = ffi_read_bytes!("plaintext", plaintext_ptr, plaintext_len);
+        let header_metadata = if header_metadata_ptr.is_null() || header_metadata_len == 0 {
+            None
+        } else {
+            Some(ffi_read_bytes!(
+                "header metadata",
+                header_metadata_ptr,
+                header_metadata_len
+            ))
+        };
+
+        let authentication_data =
+            if authentication_data_ptr.is_null() || authentication_data_len == 0 {
+                None
+            } else {
+                Some(ffi_read_bytes!(
+                    "authentication data",
+                    authentication_data_ptr,
+                    authentication_data_len
+                ))
+            };
+
+        let map = ENCRYPTION_CACHE_MAP
+            .read()
+            .expect("a read mutex on the encryption cache failed");
+        let cache = if let Some(cache) = map.get(&cache_handle) {
+            cache
+        } else {
+            ffi_bail!(format!(
+                "Hybrid Cipher: no encryption cache with handle: {cache_handle}"
+            ));
+        };
+
+        let (symmetric_key, encrypted_header) = ffi_unwrap!(
+            EncryptedHeader::generate(
+                &Covercrypt::default(),
+                &cache.policy,
+                &cache.mpk,
+                &encryption_policy,
+                header_metadata,
+                authentication_data
+            ),
+            "error encrypting CoverCrypt header",
+            ErrorCode::Encryption
+        );
+
+        let ciphertext = ffi_unwrap!(
+            Covercrypt::default().encrypt(&symmetric_key, plaintext, authentication_data),
+            "error encrypting plaintext",
+            ErrorCode::Encryption
+        );
+
+        let mut ser = Serializer::with_capacity(encrypted_header.length() + ciphertext.len());
+        ffi_unwrap!(
+            ser.write(&encrypted_header),
+            "error serializing encrypted CoverCrypt header",
+            ErrorCode::Serialization
+        );
+        ffi_unwrap!(
+            ser.write_array(&ciphertext),
+            "error serializing symmetric ciphertext",
+            ErrorCode::Serialization
+        );
+        let bytes = ser.finalize();
+
+        ffi_write_bytes!("ciphertext", &bytes, ciphertext_ptr, ciphertext_len);
+    })
+}
+
+#[no_mangle]
+/// Hybrid decrypts some content using a decryption cache.
+///
+/// No header metadata is returned if `header_metadata_ptr` is `NULL`.
+///
+/// Cf [`h_create_decryption_cache()`](h_create_decryption_cache).
+///
+/// # Safety
+pub unsafe extern "C" fn h_hybrid_decrypt_using_cache(
+    plaintext_ptr: *mut i8,
+    plaintext_len: *mut i32,
+    header_metadata_ptr: *mut i8,
+    header_metadata_len: *mut i32,
+    ciphertext_ptr: *const i8,
+    ciphertext_len: i32,
+    authentication_data_ptr: *const i8,
+    authentication_data_len: i32,
+    cache_handle: i32,
+) -> i32 {
+    ffi_guard!({
+        let authentication_data =
+            if authentication_data_ptr.is_null() || authentication_data_len == 0 {
+                None
+            } else {
+                Some(ffi_read_bytes!(
+                    "authentication data",
+                    authentication_data_ptr,
+                    authentication_data_len
+                ))
+            };
+
+        let ciphertext = ffi_read_bytes!("ciphertext", ciphertext_ptr, ciphertext_len);
+        let mut de = Deserializer::new(ciphertext);
+        let encrypted_header = ffi_unwrap!(
+            // this will read the exact header size
+            de.read::<EncryptedHeader>(),
+            "error deserializing encrypted CoverCrypt header",
+            ErrorCode::Serialization
+        );
+        // the rest is the symmetric ciphertext
+        let encrypted_content = de.finalize();
+
+        let map = DECRYPTION_CACHE_MAP
+            .read()
+            .expect("a read mutex on the decryption cache failed");
+        let cache = if let Some(cache) = map.get(&cache_handle) {
+            cache
+        } else {
+            ffi_bail!(format!(
+                "Hybrid Cipher: no decryption cache with handle: {cache_handle}",
+            ));
+        };
+
+        let decrypted_header = ffi_unwrap!(
+            encrypted_header.decrypt(&Covercrypt::default(), &cache.usk, authentication_data),
+            "error decrypting CoverCrypt header",
+            ErrorCode::Decryption
+        );
+
+        let plaintext = ffi_unwrap!(
+            Covercrypt::default().decrypt(
+                &decrypted_header.symmetric_key,
+                &encrypted_content,
+                authentication_data,
+            ),
+            "error decrypting symmetric ciphertext",
+            ErrorCode::Decryption
+        );
+
+        if header_metadata_ptr.is_null() {
+            *header_metadata_len = 0;
+            ffi_write_bytes!("plaintext", &plaintext, plaintext_ptr, plaintext_len);
+        } else {
+            let metadata = decrypted_header.metadata.unwrap_or_default();
+            ffi_write_bytes!(
+                "plaintext",
+                &plaintext,
+                plaintext_ptr,
+                plaintext_len,
+                "header metadata",
+                &metadata,
+                header_metadata_ptr,
+                header_metadata_len
+            );
+        }
+    })
 }
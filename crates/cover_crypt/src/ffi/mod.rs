@@ -1,3 +1,5 @@
+mod access_audit;
+mod bench;
 mod cc_policy;
 mod generate_cc_keys;
 mod hybrid_cc_aes;
@@ -6,16 +6,36 @@ use cosmian_cover_crypt::{
     CleartextHeader, Covercrypt, EncryptedHeader, Error, MasterPublicKey, MasterSecretKey,
     UserSecretKey,
 };
-use cosmian_crypto_core::{bytes_ser_de::Serializable, Aes256Gcm, FixedSizeCBytes, SymmetricKey};
-use cosmian_ffi_utils::error::h_get_error;
-
-use crate::ffi::{
-    generate_cc_keys::{h_generate_master_keys, h_generate_user_secret_key},
-    hybrid_cc_aes::{
-        h_create_decryption_cache, h_create_encryption_cache, h_decrypt_header,
-        h_decrypt_header_using_cache, h_destroy_decryption_cache, h_destroy_encryption_cache,
-        h_encrypt_header, h_encrypt_header_using_cache, h_hybrid_decrypt, h_hybrid_encrypt,
+use cosmian_crypto_core::{
+    bytes_ser_de::{Deserializer, Serializable, Serializer},
+    Aes256Gcm, FixedSizeCBytes, SymmetricKey,
+};
+use cosmian_ffi_utils::{error::h_get_error, ErrorCode};
+
+use crate::{
+    ffi::{
+        access_audit::{h_can_decrypt, h_user_key_coordinates, h_user_key_rotations},
+        bench::h_bench_covercrypt,
+        cc_policy::{h_is_post_quantum, h_policy_from_json_spec},
+        generate_cc_keys::{
+            h_generate_master_keys, h_generate_user_secret_key,
+            h_generate_user_secret_key_using_handle, h_load_master_secret_key,
+            h_refresh_user_secret_key_with_rotations, h_rekey_all_master_keys,
+            h_unload_master_secret_key, h_upgrade_user_secret_key,
+        },
+        hybrid_cc_aes::{
+            h_create_decryption_cache, h_create_encryption_cache, h_decrypt_header,
+            h_decrypt_header_using_cache, h_dem_decrypt, h_dem_encrypt, h_destroy_decryption_cache,
+            h_destroy_encryption_cache, h_encrypt_header, h_encrypt_header_using_cache,
+            h_get_header_metadata, h_hybrid_decrypt, h_hybrid_decrypt_bulk, h_hybrid_decrypt_file,
+            h_hybrid_decrypt_padded, h_hybrid_decrypt_using_cache,
+            h_hybrid_decrypt_with_cleartext_metadata, h_hybrid_decrypt_with_keys,
+            h_hybrid_encrypt_file, h_hybrid_encrypt_padded, h_hybrid_encrypt_using_cache,
+            h_hybrid_encrypt_with_cleartext_metadata, h_read_cleartext_metadata,
+        },
     },
+    key_version::{serialize_versioned, try_deserialize_any_version, KEY_FORMAT_VERSION},
+    policy_spec::policy_from_json_spec,
 };
 
 unsafe fn encrypt_header(
@@ -222,6 +242,102 @@ fn test_ffi_hybrid_header() -> Result<(), Error> {
     Ok(())
 }
 
+#[test]
+fn test_ffi_multiple_objects_under_one_header() {
+    unsafe {
+        let policy = policy().unwrap();
+        let cover_crypt = Covercrypt::default();
+        let (msk, mpk) = cover_crypt.generate_master_keys(&policy).unwrap();
+        let usk = cover_crypt
+            .generate_user_secret_key(
+                &msk,
+                &AccessPolicy::from_boolean_expression(
+                    "Department::FIN && Security Level::Top Secret",
+                )
+                .unwrap(),
+                &policy,
+            )
+            .unwrap();
+
+        let (symmetric_key, encrypted_header) = encrypt_header(
+            &policy,
+            "Department::FIN && Security Level::Low Secret",
+            &mpk,
+            &[],
+            &[],
+        );
+
+        // Encrypt several objects under the same header.
+        let plaintexts = [b"first object".as_slice(), b"second object".as_slice()];
+        let ciphertexts = plaintexts
+            .iter()
+            .map(|plaintext| {
+                let mut ciphertext = vec![0u8; 1024];
+                let mut ciphertext_len = ciphertext.len() as i32;
+                unwrap_ffi_error(h_dem_encrypt(
+                    ciphertext.as_mut_ptr().cast(),
+                    &mut ciphertext_len,
+                    symmetric_key.as_ptr().cast(),
+                    symmetric_key.len() as i32,
+                    std::ptr::null(),
+                    0,
+                    plaintext.as_ptr().cast(),
+                    plaintext.len() as i32,
+                ));
+                ciphertext.truncate(ciphertext_len as usize);
+                ciphertext
+            })
+            .collect::<Vec<_>>();
+
+        // The symmetric key returned upon header decryption decrypts them all.
+        let decrypted_header = decrypt_header(&encrypted_header, &usk, &[]);
+        for (plaintext, ciphertext) in plaintexts.iter().zip(&ciphertexts) {
+            let mut decrypted = vec![0u8; 1024];
+            let mut decrypted_len = decrypted.len() as i32;
+            unwrap_ffi_error(h_dem_decrypt(
+                decrypted.as_mut_ptr().cast(),
+                &mut decrypted_len,
+                decrypted_header.symmetric_key.as_ptr().cast(),
+                decrypted_header.symmetric_key.len() as i32,
+                std::ptr::null(),
+                0,
+                ciphertext.as_ptr().cast(),
+                ciphertext.len() as i32,
+            ));
+            decrypted.truncate(decrypted_len as usize);
+            assert_eq!(plaintext, &decrypted);
+        }
+
+        // The symmetric key is not returned if no buffer is given.
+        let policy_bytes: Vec<u8> = (&policy).try_into().unwrap();
+        let mpk_bytes = mpk.serialize().unwrap();
+        let encryption_policy =
+            CString::new("Department::FIN && Security Level::Low Secret").unwrap();
+        let mut symmetric_key_len = 32;
+        let mut header_bytes = vec![0u8; 8128];
+        let mut header_len = header_bytes.len() as i32;
+        unwrap_ffi_error(h_encrypt_header(
+            std::ptr::null_mut(),
+            &mut symmetric_key_len,
+            header_bytes.as_mut_ptr().cast(),
+            &mut header_len,
+            policy_bytes.as_ptr().cast(),
+            policy_bytes.len() as i32,
+            mpk_bytes.as_ptr().cast(),
+            mpk_bytes.len() as i32,
+            encryption_policy.as_ptr(),
+            std::ptr::null(),
+            0,
+            std::ptr::null(),
+            0,
+        ));
+        assert_eq!(symmetric_key_len, 0);
+        header_bytes.truncate(header_len as usize);
+        let encrypted_header = EncryptedHeader::deserialize(&header_bytes).unwrap();
+        assert!(encrypted_header.decrypt(&cover_crypt, &usk, None).is_ok());
+    }
+}
+
 unsafe fn encrypt_header_using_cache(
     public_key: &MasterPublicKey,
     policy: &Policy,
@@ -405,8 +521,8 @@ unsafe fn generate_master_keys(policy: &Policy) -> (MasterSecretKey, MasterPubli
     let msk_bytes = std::slice::from_raw_parts(msk_ptr.cast(), msk_len as usize);
     let mpk_bytes = std::slice::from_raw_parts(mpk_ptr.cast(), mpk_len as usize);
 
-    let msk = MasterSecretKey::deserialize(msk_bytes).unwrap();
-    let mpk = MasterPublicKey::deserialize(mpk_bytes).unwrap();
+    let msk = try_deserialize_any_version::<MasterSecretKey>(msk_bytes).unwrap();
+    let mpk = try_deserialize_any_version::<MasterPublicKey>(mpk_bytes).unwrap();
 
     (msk, mpk)
 }
@@ -450,7 +566,7 @@ unsafe fn generate_user_secret_key(
 
     let user_key_bytes = std::slice::from_raw_parts(usk_ptr.cast(), usk_len as usize).to_vec();
 
-    UserSecretKey::deserialize(&user_key_bytes).unwrap()
+    try_deserialize_any_version::<UserSecretKey>(&user_key_bytes).unwrap()
 }
 
 #[test]
@@ -472,6 +588,111 @@ fn test_ffi_keygen() {
     let _usk = unsafe { generate_user_secret_key(&master_keys.0, access_policy, &policy) };
 }
 
+#[test]
+fn test_ffi_upgrade_key() {
+    let policy = policy().unwrap();
+    let (msk, _) = unsafe { generate_master_keys(&policy) };
+    let usk = unsafe {
+        generate_user_secret_key(
+            &msk,
+            "Department::FIN && Security Level::Top Secret",
+            &policy,
+        )
+    };
+
+    // Keys serialized before the introduction of the format version.
+    let legacy_usk_bytes = usk.serialize().unwrap();
+
+    let mut usk_bytes = vec![0u8; 2 * legacy_usk_bytes.len()];
+    let usk_ptr = usk_bytes.as_mut_ptr().cast();
+    let mut usk_len = usk_bytes.len() as i32;
+
+    unsafe {
+        unwrap_ffi_error(h_upgrade_user_secret_key(
+            usk_ptr,
+            &mut usk_len,
+            legacy_usk_bytes.as_ptr().cast(),
+            legacy_usk_bytes.len() as i32,
+        ));
+    }
+    usk_bytes.truncate(usk_len as usize);
+
+    assert_eq!(usk_bytes[0], KEY_FORMAT_VERSION);
+    assert_eq!(usk_bytes, *serialize_versioned(&usk).unwrap());
+}
+
+#[test]
+fn test_ffi_rekey_all_master_keys() {
+    let policy = policy().unwrap();
+    let (msk, mpk) = unsafe { generate_master_keys(&policy) };
+    let mut usk = unsafe {
+        generate_user_secret_key(
+            &msk,
+            "Department::FIN && Security Level::Low Secret",
+            &policy,
+        )
+    };
+    let msk_bytes = serialize_versioned(&msk).unwrap();
+    let mpk_bytes = serialize_versioned(&mpk).unwrap();
+
+    let rekey_all = |policy: &Policy| {
+        let policy_bytes: Vec<u8> = policy.try_into().unwrap();
+        let mut updated_msk_bytes = vec![0u8; 16 * 1024];
+        let mut updated_msk_len = updated_msk_bytes.len() as i32;
+        let mut updated_mpk_bytes = vec![0u8; 16 * 1024];
+        let mut updated_mpk_len = updated_mpk_bytes.len() as i32;
+        let res = unsafe {
+            h_rekey_all_master_keys(
+                updated_msk_bytes.as_mut_ptr().cast(),
+                &mut updated_msk_len,
+                updated_mpk_bytes.as_mut_ptr().cast(),
+                &mut updated_mpk_len,
+                msk_bytes.as_ptr().cast(),
+                msk_bytes.len() as i32,
+                mpk_bytes.as_ptr().cast(),
+                mpk_bytes.len() as i32,
+                policy_bytes.as_ptr().cast(),
+                policy_bytes.len() as i32,
+            )
+        };
+        updated_msk_bytes.truncate(updated_msk_len as usize);
+        updated_mpk_bytes.truncate(updated_mpk_len as usize);
+        (res, updated_msk_bytes, updated_mpk_bytes)
+    };
+
+    let (res, updated_msk_bytes, updated_mpk_bytes) = rekey_all(&policy);
+    unsafe { unwrap_ffi_error(res) };
+    let updated_msk = try_deserialize_any_version::<MasterSecretKey>(&updated_msk_bytes).unwrap();
+    let updated_mpk = try_deserialize_any_version::<MasterPublicKey>(&updated_mpk_bytes).unwrap();
+
+    // Every partition was rekeyed: the user key must be refreshed.
+    let cover_crypt = Covercrypt::default();
+    let access_policy =
+        AccessPolicy::from_boolean_expression("Department::FIN && Security Level::Low Secret")
+            .unwrap();
+    let (_, old_header) =
+        EncryptedHeader::generate(&cover_crypt, &policy, &mpk, &access_policy, None, None).unwrap();
+    let (_, new_header) = EncryptedHeader::generate(
+        &cover_crypt,
+        &policy,
+        &updated_mpk,
+        &access_policy,
+        None,
+        None,
+    )
+    .unwrap();
+    assert!(new_header.decrypt(&cover_crypt, &usk, None).is_err());
+    cover_crypt
+        .refresh_user_secret_key(&mut usk, &updated_msk, true)
+        .unwrap();
+    new_header.decrypt(&cover_crypt, &usk, None).unwrap();
+    old_header.decrypt(&cover_crypt, &usk, None).unwrap();
+
+    // A policy without attribute cannot target the partitions.
+    let (res, _, _) = rekey_all(&Policy::new());
+    assert_eq!(res, i32::from(ErrorCode::CovercryptPolicy));
+}
+
 //
 // Encrypt / decrypt
 //
@@ -483,6 +704,7 @@ unsafe fn encrypt(
     plaintext: &[u8],
     header_metadata: &[u8],
     authentication_data: &[u8],
+    header_bucket_size: i32,
 ) -> Vec<u8> {
     let mut ciphertext_bytes = vec![0u8; 8128];
     let ciphertext_ptr = ciphertext_bytes.as_mut_ptr().cast();
@@ -499,7 +721,7 @@ unsafe fn encrypt(
     let encryption_policy_cs = CString::new(encryption_policy).unwrap();
     let encryption_policy_ptr = encryption_policy_cs.as_ptr();
 
-    unwrap_ffi_error(h_hybrid_encrypt(
+    unwrap_ffi_error(h_hybrid_encrypt_padded(
         ciphertext_ptr,
         &mut ciphertext_len,
         policy_ptr,
@@ -513,6 +735,7 @@ unsafe fn encrypt(
         header_metadata.len() as i32,
         authentication_data.as_ptr().cast(),
         authentication_data.len() as i32,
+        header_bucket_size,
     ));
 
     let ciphertext_bytes =
@@ -524,6 +747,7 @@ unsafe fn decrypt(
     ciphertext: &[u8],
     user_decryption_key: &UserSecretKey,
     authentication_data: &[u8],
+    header_bucket_size: i32,
 ) -> (Vec<u8>, Vec<u8>) {
     // use a large enough buffer size
     let mut plaintext = vec![0u8; 8192];
@@ -545,7 +769,7 @@ unsafe fn decrypt(
     let user_decryption_key_ptr = user_decryption_key_bytes.as_ptr().cast();
     let user_decryption_key_len = user_decryption_key_bytes.len() as i32;
 
-    unwrap_ffi_error(h_hybrid_decrypt(
+    unwrap_ffi_error(h_hybrid_decrypt_padded(
         plaintext_ptr,
         &mut plaintext_len,
         metadata_ptr,
@@ -556,6 +780,7 @@ unsafe fn decrypt(
         authentication_data_len,
         user_decryption_key_ptr,
         user_decryption_key_len,
+        header_bucket_size,
     ));
 
     let plaintext =
@@ -601,11 +826,887 @@ fn test_encrypt_decrypt() {
             &plaintext,
             &header_metadata,
             &authentication_data,
+            0,
         );
 
-        let (plaintext_, header_metadata_) = decrypt(&ciphertext, &usk, &authentication_data);
+        let (plaintext_, header_metadata_) = decrypt(&ciphertext, &usk, &authentication_data, 0);
+
+        assert_eq!(plaintext, plaintext_);
+        assert_eq!(header_metadata, header_metadata_);
+    }
+}
+
+#[test]
+fn test_encrypt_decrypt_with_record_id() {
+    unsafe {
+        let policy = policy().unwrap();
+        let cover_crypt = Covercrypt::default();
+        let (msk, mpk) = cover_crypt.generate_master_keys(&policy).unwrap();
+        let usk = cover_crypt
+            .generate_user_secret_key(
+                &msk,
+                &AccessPolicy::from_boolean_expression(
+                    "Department::FIN && Security Level::Top Secret",
+                )
+                .unwrap(),
+                &policy,
+            )
+            .unwrap();
+
+        let plaintext = vec![16, 17, 18, 19, 20, 21];
+        let ciphertext = encrypt(
+            &policy,
+            &mpk,
+            "Department::FIN && Security Level::Low Secret",
+            &plaintext,
+            &[],
+            b"record 1",
+            0,
+        );
+        let (plaintext_, _) = decrypt(&ciphertext, &usk, b"record 1", 0);
+        assert_eq!(plaintext, plaintext_);
+
+        // The ciphertext cannot be decrypted as another record.
+        let usk_bytes = usk.serialize().unwrap();
+        for authentication_data in [b"record 2".as_slice(), &[]] {
+            let mut plaintext = vec![0u8; 8192];
+            let mut plaintext_len = plaintext.len() as i32;
+            let mut header_metadata_len = 0;
+            let res = h_hybrid_decrypt(
+                plaintext.as_mut_ptr().cast(),
+                &mut plaintext_len,
+                std::ptr::null_mut(),
+                &mut header_metadata_len,
+                ciphertext.as_ptr().cast(),
+                ciphertext.len() as i32,
+                authentication_data.as_ptr().cast(),
+                authentication_data.len() as i32,
+                usk_bytes.as_ptr().cast(),
+                usk_bytes.len() as i32,
+            );
+            assert_ne!(0, res);
+        }
+    }
+}
+
+#[test]
+fn test_encrypt_decrypt_with_header_padding() {
+    unsafe {
+        let policy = policy().unwrap();
+        let cover_crypt = Covercrypt::default();
+        let (msk, mpk) = cover_crypt.generate_master_keys(&policy).unwrap();
+        let usk = cover_crypt
+            .generate_user_secret_key(
+                &msk,
+                &AccessPolicy::from_boolean_expression(
+                    "Department::FIN && Security Level::Top Secret",
+                )
+                .unwrap(),
+                &policy,
+            )
+            .unwrap();
+
+        let plaintext = vec![16, 17, 18, 19, 20, 21];
+        let narrow_ciphertext = encrypt(
+            &policy,
+            &mpk,
+            "Department::FIN && Security Level::Low Secret",
+            &plaintext,
+            &[],
+            &[],
+            4096,
+        );
+        let broad_ciphertext = encrypt(
+            &policy,
+            &mpk,
+            "(Department::FIN || Department::HR) && Security Level::Low Secret",
+            &plaintext,
+            &[],
+            &[],
+            4096,
+        );
+        // The ciphertext length does not depend on the encryption policy.
+        assert_eq!(narrow_ciphertext.len(), broad_ciphertext.len());
+        for ciphertext in [&narrow_ciphertext, &broad_ciphertext] {
+            let (plaintext_, _) = decrypt(ciphertext, &usk, &[], 4096);
+            assert_eq!(plaintext, plaintext_);
+        }
+
+        // The bucket size given upon encryption is needed to decrypt.
+        let usk_bytes = usk.serialize().unwrap();
+        for header_bucket_size in [0, 1024, -1] {
+            let mut plaintext = vec![0u8; 8192];
+            let mut plaintext_len = plaintext.len() as i32;
+            let mut header_metadata_len = 0;
+            let res = h_hybrid_decrypt_padded(
+                plaintext.as_mut_ptr().cast(),
+                &mut plaintext_len,
+                std::ptr::null_mut(),
+                &mut header_metadata_len,
+                narrow_ciphertext.as_ptr().cast(),
+                narrow_ciphertext.len() as i32,
+                std::ptr::null(),
+                0,
+                usk_bytes.as_ptr().cast(),
+                usk_bytes.len() as i32,
+                header_bucket_size,
+            );
+            assert_ne!(0, res);
+        }
+    }
+}
+
+#[test]
+fn test_encrypt_decrypt_with_cleartext_metadata() {
+    unsafe {
+        let policy = policy().unwrap();
+        let encryption_policy = "Department::FIN && Security Level::Low Secret";
+
+        let cover_crypt = Covercrypt::default();
+        let (msk, mpk) = cover_crypt.generate_master_keys(&policy).unwrap();
+        let user_access_policy =
+            AccessPolicy::from_boolean_expression("Department::FIN && Security Level::Top Secret")
+                .unwrap();
+        let usk = cover_crypt
+            .generate_user_secret_key(&msk, &user_access_policy, &policy)
+            .unwrap();
+
+        let plaintext = vec![16, 17, 18, 19, 20, 21];
+        let cleartext_metadata = b"application/octet-stream".to_vec();
+        let header_metadata = vec![1, 2, 3, 4, 5, 6, 7, 8, 9];
+        let authentication_data = [10, 11, 12, 13, 14];
+
+        //
+        // Encrypt
+        //
+        let policy_bytes: Vec<u8> = (&policy).try_into().unwrap();
+        let mpk_bytes = mpk.serialize().unwrap();
+        let encryption_policy_cs = CString::new(encryption_policy).unwrap();
+
+        let mut ciphertext = vec![0u8; 8192];
+        let mut ciphertext_len = ciphertext.len() as i32;
+        unwrap_ffi_error(h_hybrid_encrypt_with_cleartext_metadata(
+            ciphertext.as_mut_ptr().cast(),
+            &mut ciphertext_len,
+            policy_bytes.as_ptr().cast(),
+            policy_bytes.len() as i32,
+            mpk_bytes.as_ptr().cast(),
+            mpk_bytes.len() as i32,
+            encryption_policy_cs.as_ptr(),
+            plaintext.as_ptr().cast(),
+            plaintext.len() as i32,
+            cleartext_metadata.as_ptr().cast(),
+            cleartext_metadata.len() as i32,
+            header_metadata.as_ptr().cast(),
+            header_metadata.len() as i32,
+            authentication_data.as_ptr().cast(),
+            authentication_data.len() as i32,
+        ));
+        ciphertext.truncate(ciphertext_len as usize);
+
+        //
+        // Read the cleartext metadata without any key
+        //
+        let mut metadata = vec![0u8; 8192];
+        let mut metadata_len = metadata.len() as i32;
+        unwrap_ffi_error(h_read_cleartext_metadata(
+            metadata.as_mut_ptr().cast(),
+            &mut metadata_len,
+            ciphertext.as_ptr().cast(),
+            ciphertext.len() as i32,
+        ));
+        metadata.truncate(metadata_len as usize);
+        assert_eq!(cleartext_metadata, metadata);
+
+        //
+        // Decrypt
+        //
+        let usk_bytes = usk.serialize().unwrap();
+        let decrypt = |ciphertext: &[u8]| {
+            let mut plaintext = vec![0u8; 8192];
+            let mut plaintext_len = plaintext.len() as i32;
+            let mut header_metadata = vec![0u8; 8192];
+            let mut header_metadata_len = header_metadata.len() as i32;
+            let mut cleartext_metadata = vec![0u8; 8192];
+            let mut cleartext_metadata_len = cleartext_metadata.len() as i32;
+            let res = h_hybrid_decrypt_with_cleartext_metadata(
+                plaintext.as_mut_ptr().cast(),
+                &mut plaintext_len,
+                header_metadata.as_mut_ptr().cast(),
+                &mut header_metadata_len,
+                cleartext_metadata.as_mut_ptr().cast(),
+                &mut cleartext_metadata_len,
+                ciphertext.as_ptr().cast(),
+                ciphertext.len() as i32,
+                authentication_data.as_ptr().cast(),
+                authentication_data.len() as i32,
+                usk_bytes.as_ptr().cast(),
+                usk_bytes.len() as i32,
+            );
+            plaintext.truncate(plaintext_len as usize);
+            header_metadata.truncate(header_metadata_len as usize);
+            cleartext_metadata.truncate(cleartext_metadata_len as usize);
+            (res, plaintext, header_metadata, cleartext_metadata)
+        };
+
+        let (res, plaintext_, header_metadata_, cleartext_metadata_) = decrypt(&ciphertext);
+        unwrap_ffi_error(res);
+        assert_eq!(plaintext, plaintext_);
+        assert_eq!(header_metadata, header_metadata_);
+        assert_eq!(cleartext_metadata, cleartext_metadata_);
+
+        // Tampering with the cleartext metadata makes the decryption fail.
+        let mut tampered_ciphertext = ciphertext.clone();
+        tampered_ciphertext[1] ^= 1;
+        assert_ne!(0, decrypt(&tampered_ciphertext).0);
+    }
+}
+
+#[test]
+fn test_decrypt_with_keys() {
+    unsafe {
+        let policy = policy().unwrap();
+        let encryption_policy = "Department::MKG && Security Level::Low Secret";
+
+        let cover_crypt = Covercrypt::default();
+        let (msk, mpk) = cover_crypt.generate_master_keys(&policy).unwrap();
+        let usks = [
+            "Department::HR && Security Level::Top Secret",
+            "Department::MKG && Security Level::Top Secret",
+        ]
+        .map(|access_policy| {
+            cover_crypt
+                .generate_user_secret_key(
+                    &msk,
+                    &AccessPolicy::from_boolean_expression(access_policy).unwrap(),
+                    &policy,
+                )
+                .unwrap()
+        });
+
+        let plaintext = vec![16, 17, 18, 19, 20, 21];
+        let header_metadata = vec![1, 2, 3, 4, 5, 6, 7, 8, 9];
+        let authentication_data = vec![10, 11, 12, 13, 14];
+
+        let ciphertext = encrypt(
+            &policy,
+            &mpk,
+            encryption_policy,
+            &plaintext,
+            &header_metadata,
+            &authentication_data,
+            0,
+        );
 
+        let decrypt = |usks: &[UserSecretKey]| {
+            let mut ser = Serializer::new();
+            ser.write_leb128_u64(usks.len() as u64).unwrap();
+            for usk in usks {
+                ser.write_vec(&usk.serialize().unwrap()).unwrap();
+            }
+            let usks_bytes = ser.finalize();
+
+            let mut plaintext = vec![0u8; 8192];
+            let mut plaintext_len = plaintext.len() as i32;
+            let mut header_metadata = vec![0u8; 8192];
+            let mut header_metadata_len = header_metadata.len() as i32;
+            let mut key_index = -1;
+            let res = h_hybrid_decrypt_with_keys(
+                plaintext.as_mut_ptr().cast(),
+                &mut plaintext_len,
+                header_metadata.as_mut_ptr().cast(),
+                &mut header_metadata_len,
+                &mut key_index,
+                ciphertext.as_ptr().cast(),
+                ciphertext.len() as i32,
+                authentication_data.as_ptr().cast(),
+                authentication_data.len() as i32,
+                usks_bytes.as_ptr().cast(),
+                usks_bytes.len() as i32,
+            );
+            plaintext.truncate(plaintext_len as usize);
+            header_metadata.truncate(header_metadata_len as usize);
+            (res, key_index, plaintext, header_metadata)
+        };
+
+        let (res, key_index, plaintext_, header_metadata_) = decrypt(&usks);
+        unwrap_ffi_error(res);
+        assert_eq!(1, key_index);
         assert_eq!(plaintext, plaintext_);
         assert_eq!(header_metadata, header_metadata_);
+
+        // None of the given keys can decrypt the ciphertext.
+        assert_ne!(0, decrypt(&usks[..1]).0);
+    }
+}
+
+#[test]
+fn test_decrypt_bulk() {
+    unsafe {
+        let policy = policy().unwrap();
+
+        let cover_crypt = Covercrypt::default();
+        let (msk, mpk) = cover_crypt.generate_master_keys(&policy).unwrap();
+        let usk = cover_crypt
+            .generate_user_secret_key(
+                &msk,
+                &AccessPolicy::from_boolean_expression(
+                    "Department::MKG && Security Level::Top Secret",
+                )
+                .unwrap(),
+                &policy,
+            )
+            .unwrap();
+        let usk_bytes = usk.serialize().unwrap();
+
+        let plaintexts = (0..10_u8).map(|i| vec![i; 16]).collect::<Vec<_>>();
+        let authentication_data = (0..10_u8).map(|i| vec![i + 100; 4]).collect::<Vec<_>>();
+        let ciphertexts = plaintexts
+            .iter()
+            .zip(&authentication_data)
+            .map(|(plaintext, authentication_data)| {
+                encrypt(
+                    &policy,
+                    &mpk,
+                    "Department::MKG && Security Level::Low Secret",
+                    plaintext,
+                    &[],
+                    authentication_data,
+                    0,
+                )
+            })
+            .collect::<Vec<_>>();
+
+        let serialize = |byte_strings: &[Vec<u8>]| {
+            let mut ser = Serializer::new();
+            ser.write_leb128_u64(byte_strings.len() as u64).unwrap();
+            for bytes in byte_strings {
+                ser.write_vec(bytes).unwrap();
+            }
+            ser.finalize().to_vec()
+        };
+        let decrypt = |authentication_data: &[Vec<u8>]| {
+            let ciphertexts_bytes = serialize(&ciphertexts);
+            let authentication_data_bytes = serialize(authentication_data);
+            let mut plaintexts = vec![0u8; 8192];
+            let mut plaintexts_len = plaintexts.len() as i32;
+            let res = h_hybrid_decrypt_bulk(
+                plaintexts.as_mut_ptr().cast(),
+                &mut plaintexts_len,
+                ciphertexts_bytes.as_ptr().cast(),
+                ciphertexts_bytes.len() as i32,
+                authentication_data_bytes.as_ptr().cast(),
+                authentication_data_bytes.len() as i32,
+                usk_bytes.as_ptr().cast(),
+                usk_bytes.len() as i32,
+            );
+            plaintexts.truncate(plaintexts_len as usize);
+            (res, plaintexts)
+        };
+
+        let (res, plaintexts_bytes) = decrypt(&authentication_data);
+        unwrap_ffi_error(res);
+        let mut de = Deserializer::new(&plaintexts_bytes);
+        let n = de.read_leb128_u64().unwrap();
+        let plaintexts_ = (0..n).map(|_| de.read_vec().unwrap()).collect::<Vec<_>>();
+        assert_eq!(plaintexts, plaintexts_);
+
+        // The authentication data are bound to their ciphertext.
+        let mut shuffled_authentication_data = authentication_data.clone();
+        shuffled_authentication_data.swap(0, 1);
+        assert_ne!(0, decrypt(&shuffled_authentication_data).0);
+        assert_ne!(0, decrypt(&authentication_data[..1]).0);
+    }
+}
+
+#[test]
+fn test_encrypt_decrypt_file() {
+    unsafe {
+        let policy = policy().unwrap();
+        let encryption_policy = "Department::FIN && Security Level::Low Secret";
+
+        let cover_crypt = Covercrypt::default();
+        let (msk, mpk) = cover_crypt.generate_master_keys(&policy).unwrap();
+        let usk = cover_crypt
+            .generate_user_secret_key(
+                &msk,
+                &AccessPolicy::from_boolean_expression(
+                    "Department::FIN && Security Level::Top Secret",
+                )
+                .unwrap(),
+                &policy,
+            )
+            .unwrap();
+
+        let dir = std::env::temp_dir();
+        let input_path = dir.join("cloudproof_cover_crypt_ffi_file_input");
+        let encrypted_path = dir.join("cloudproof_cover_crypt_ffi_file_encrypted");
+        let output_path = dir.join("cloudproof_cover_crypt_ffi_file_output");
+        let path_cs = |path: &std::path::Path| CString::new(path.to_str().unwrap()).unwrap();
+
+        let plaintext = vec![42; 100_000];
+        let header_metadata = vec![1, 2, 3, 4, 5, 6, 7, 8, 9];
+        let authentication_data = [10, 11, 12, 13, 14];
+        std::fs::write(&input_path, &plaintext).unwrap();
+
+        let policy_bytes: Vec<u8> = (&policy).try_into().unwrap();
+        let mpk_bytes = mpk.serialize().unwrap();
+        let encryption_policy_cs = CString::new(encryption_policy).unwrap();
+        unwrap_ffi_error(h_hybrid_encrypt_file(
+            path_cs(&input_path).as_ptr(),
+            path_cs(&encrypted_path).as_ptr(),
+            policy_bytes.as_ptr().cast(),
+            policy_bytes.len() as i32,
+            mpk_bytes.as_ptr().cast(),
+            mpk_bytes.len() as i32,
+            encryption_policy_cs.as_ptr(),
+            header_metadata.as_ptr().cast(),
+            header_metadata.len() as i32,
+            authentication_data.as_ptr().cast(),
+            authentication_data.len() as i32,
+        ));
+
+        let usk_bytes = usk.serialize().unwrap();
+        let mut header_metadata_ = vec![0u8; 8192];
+        let mut header_metadata_len = header_metadata_.len() as i32;
+        unwrap_ffi_error(h_hybrid_decrypt_file(
+            header_metadata_.as_mut_ptr().cast(),
+            &mut header_metadata_len,
+            path_cs(&encrypted_path).as_ptr(),
+            path_cs(&output_path).as_ptr(),
+            authentication_data.as_ptr().cast(),
+            authentication_data.len() as i32,
+            usk_bytes.as_ptr().cast(),
+            usk_bytes.len() as i32,
+        ));
+        header_metadata_.truncate(header_metadata_len as usize);
+
+        assert_eq!(plaintext, std::fs::read(&output_path).unwrap());
+        assert_eq!(header_metadata, header_metadata_);
+
+        // The header metadata length is required to return the metadata.
+        assert_eq!(
+            -1,
+            h_hybrid_decrypt_file(
+                header_metadata_.as_mut_ptr().cast(),
+                std::ptr::null_mut(),
+                path_cs(&encrypted_path).as_ptr(),
+                path_cs(&output_path).as_ptr(),
+                authentication_data.as_ptr().cast(),
+                authentication_data.len() as i32,
+                usk_bytes.as_ptr().cast(),
+                usk_bytes.len() as i32,
+            )
+        );
+        unwrap_ffi_error(h_hybrid_decrypt_file(
+            std::ptr::null_mut(),
+            std::ptr::null_mut(),
+            path_cs(&encrypted_path).as_ptr(),
+            path_cs(&output_path).as_ptr(),
+            authentication_data.as_ptr().cast(),
+            authentication_data.len() as i32,
+            usk_bytes.as_ptr().cast(),
+            usk_bytes.len() as i32,
+        ));
+        assert_eq!(plaintext, std::fs::read(&output_path).unwrap());
+
+        for path in [input_path, encrypted_path, output_path] {
+            std::fs::remove_file(path).unwrap();
+        }
+    }
+}
+
+#[test]
+fn test_encrypt_decrypt_using_handles() {
+    unsafe {
+        let policy = policy().unwrap();
+        let policy_bytes: Vec<u8> = (&policy).try_into().unwrap();
+        let (msk, mpk) = generate_master_keys(&policy);
+
+        //
+        // Load the keys once
+        //
+        let msk_bytes = msk.serialize().unwrap();
+        let mut msk_handle = 0;
+        unwrap_ffi_error(h_load_master_secret_key(
+            &mut msk_handle,
+            msk_bytes.as_ptr().cast(),
+            msk_bytes.len() as i32,
+            policy_bytes.as_ptr().cast(),
+            policy_bytes.len() as i32,
+        ));
+
+        let mpk_bytes = mpk.serialize().unwrap();
+        let mut encryption_handle = 0;
+        unwrap_ffi_error(h_create_encryption_cache(
+            &mut encryption_handle,
+            policy_bytes.as_ptr().cast(),
+            policy_bytes.len() as i32,
+            mpk_bytes.as_ptr().cast(),
+            mpk_bytes.len() as i32,
+        ));
+
+        let mut usk_bytes = vec![0u8; 37696];
+        let mut usk_len = usk_bytes.len() as i32;
+        let user_policy = CString::new("Department::FIN && Security Level::Top Secret").unwrap();
+        unwrap_ffi_error(h_generate_user_secret_key_using_handle(
+            usk_bytes.as_mut_ptr().cast(),
+            &mut usk_len,
+            msk_handle,
+            user_policy.as_ptr(),
+        ));
+        usk_bytes.truncate(usk_len as usize);
+
+        let mut decryption_handle = 0;
+        unwrap_ffi_error(h_create_decryption_cache(
+            &mut decryption_handle,
+            usk_bytes.as_ptr().cast(),
+            usk_bytes.len() as i32,
+        ));
+
+        //
+        // Encrypt / decrypt several messages with the same handles
+        //
+        let encryption_policy =
+            CString::new("(Department::HR || Department::FIN) && Security Level::Low Secret")
+                .unwrap();
+        let header_metadata = vec![1, 2, 3, 4, 5, 6, 7, 8, 9];
+        let authentication_data = [10, 11, 12, 13, 14];
+
+        for i in 0..3_u8 {
+            let plaintext = vec![i; 16];
+
+            let mut ciphertext = vec![0u8; 8128];
+            let mut ciphertext_len = ciphertext.len() as i32;
+            unwrap_ffi_error(h_hybrid_encrypt_using_cache(
+                ciphertext.as_mut_ptr().cast(),
+                &mut ciphertext_len,
+                encryption_handle,
+                encryption_policy.as_ptr(),
+                plaintext.as_ptr().cast(),
+                plaintext.len() as i32,
+                header_metadata.as_ptr().cast(),
+                header_metadata.len() as i32,
+                authentication_data.as_ptr().cast(),
+                authentication_data.len() as i32,
+            ));
+            ciphertext.truncate(ciphertext_len as usize);
+
+            let mut plaintext_ = vec![0u8; 8192];
+            let mut plaintext_len = plaintext_.len() as i32;
+            let mut metadata = vec![0u8; 8192];
+            let mut metadata_len = metadata.len() as i32;
+            unwrap_ffi_error(h_hybrid_decrypt_using_cache(
+                plaintext_.as_mut_ptr().cast(),
+                &mut plaintext_len,
+                metadata.as_mut_ptr().cast(),
+                &mut metadata_len,
+                ciphertext.as_ptr().cast(),
+                ciphertext.len() as i32,
+                authentication_data.as_ptr().cast(),
+                authentication_data.len() as i32,
+                decryption_handle,
+            ));
+            plaintext_.truncate(plaintext_len as usize);
+            metadata.truncate(metadata_len as usize);
+
+            assert_eq!(plaintext, plaintext_);
+            assert_eq!(header_metadata, metadata);
+        }
+
+        unwrap_ffi_error(h_destroy_decryption_cache(decryption_handle));
+        unwrap_ffi_error(h_destroy_encryption_cache(encryption_handle));
+        unwrap_ffi_error(h_unload_master_secret_key(msk_handle));
+    }
+}
+
+#[test]
+fn test_bench_covercrypt() {
+    let policy = policy().unwrap();
+    let policy_bytes: Vec<u8> = (&policy).try_into().unwrap();
+
+    let mut results = vec![0u8; 1024];
+    let mut results_len = results.len() as i32;
+    unsafe {
+        unwrap_ffi_error(h_bench_covercrypt(
+            results.as_mut_ptr().cast(),
+            &mut results_len,
+            policy_bytes.as_ptr().cast(),
+            policy_bytes.len() as i32,
+            1,
+        ));
+    }
+    results.truncate(results_len as usize);
+
+    let results: serde_json::Value = serde_json::from_slice(&results).unwrap();
+    assert_eq!(
+        results["n_partitions"],
+        crate::bench::bench_covercrypt(&policy, 1)
+            .unwrap()
+            .n_partitions
+    );
+    assert!(results["decrypt_us"].is_number());
+}
+
+#[test]
+fn test_get_header_metadata() {
+    let policy = policy().unwrap();
+    let cover_crypt = Covercrypt::default();
+    let (_, mpk) = cover_crypt.generate_master_keys(&policy).unwrap();
+    let (_, encrypted_header) = EncryptedHeader::generate(
+        &cover_crypt,
+        &policy,
+        &mpk,
+        &AccessPolicy::from_boolean_expression("Department::MKG && Security Level::Protected")
+            .unwrap(),
+        Some(b"header metadata"),
+        None,
+    )
+    .unwrap();
+    let mut ciphertext = encrypted_header.serialize().unwrap().to_vec();
+    ciphertext.extend_from_slice(b"DEM ciphertext");
+
+    let mut header_info = vec![0u8; 1024];
+    let mut header_info_len = header_info.len() as i32;
+    unsafe {
+        unwrap_ffi_error(h_get_header_metadata(
+            header_info.as_mut_ptr().cast(),
+            &mut header_info_len,
+            ciphertext.as_ptr().cast(),
+            ciphertext.len() as i32,
+        ));
+    }
+    header_info.truncate(header_info_len as usize);
+
+    let header_info: serde_json::Value = serde_json::from_slice(&header_info).unwrap();
+    assert_eq!(header_info["header_length"], encrypted_header.length());
+    assert_eq!(header_info["n_partitions"], 1);
+    assert_eq!(header_info["is_hybridized"], false);
+    assert_eq!(header_info["has_metadata"], true);
+}
+
+#[test]
+fn test_policy_from_json_spec() {
+    let spec = CString::new(r#"{"Department": ["HR", "FIN::+"]}"#).unwrap();
+    let mut policy_bytes = vec![0u8; 8192];
+    let mut policy_len = policy_bytes.len() as i32;
+    unsafe {
+        unwrap_ffi_error(h_policy_from_json_spec(
+            policy_bytes.as_mut_ptr().cast(),
+            &mut policy_len,
+            spec.as_ptr(),
+        ));
+    }
+    policy_bytes.truncate(policy_len as usize);
+    let policy = Policy::parse_and_convert(&policy_bytes).unwrap();
+    assert_eq!(policy.attributes().len(), 2);
+
+    let spec = CString::new(r#"{"Department": []}"#).unwrap();
+    let mut policy_len = policy_bytes.len() as i32;
+    let res = unsafe {
+        h_policy_from_json_spec(
+            policy_bytes.as_mut_ptr().cast(),
+            &mut policy_len,
+            spec.as_ptr(),
+        )
+    };
+    assert_ne!(res, 0);
+}
+
+#[test]
+fn test_is_post_quantum() {
+    let policy = policy_from_json_spec(
+        br#"{
+            "Security Level::<": ["Protected", "Confidential", "Top Secret::+"],
+            "Department": ["HR", "FIN"]
+        }"#,
+    )
+    .unwrap();
+    let policy_bytes: Vec<u8> = (&policy).try_into().unwrap();
+
+    let is_post_quantum = |encryption_policy: &str| {
+        let encryption_policy = CString::new(encryption_policy).unwrap();
+        let mut is_post_quantum = -1;
+        let res = unsafe {
+            h_is_post_quantum(
+                &mut is_post_quantum,
+                encryption_policy.as_ptr(),
+                policy_bytes.as_ptr().cast(),
+                policy_bytes.len() as i32,
+            )
+        };
+        assert_eq!(res, 0);
+        is_post_quantum
+    };
+
+    assert_eq!(is_post_quantum("Security Level::Top Secret"), 1);
+    assert_eq!(
+        is_post_quantum("Department::FIN && Security Level::Top Secret"),
+        1
+    );
+    assert_eq!(is_post_quantum("Department::FIN"), 0);
+    assert_eq!(is_post_quantum("Security Level::Protected"), 0);
+}
+
+#[test]
+fn test_user_key_access_audit() {
+    let policy = policy_from_json_spec(
+        br#"{
+            "Security Level::<": ["Protected", "Confidential", "Top Secret::+"],
+            "Department": ["HR", "FIN"]
+        }"#,
+    )
+    .unwrap();
+    let policy_bytes: Vec<u8> = (&policy).try_into().unwrap();
+    let cover_crypt = Covercrypt::default();
+    let (msk, mpk) = cover_crypt.generate_master_keys(&policy).unwrap();
+    let usk = cover_crypt
+        .generate_user_secret_key(
+            &msk,
+            &AccessPolicy::from_boolean_expression(
+                "Department::FIN && Security Level::Confidential",
+            )
+            .unwrap(),
+            &policy,
+        )
+        .unwrap();
+    let usk_bytes = serialize_versioned(&usk).unwrap();
+    let mpk_bytes = serialize_versioned(&mpk).unwrap();
+
+    let mut coordinates = vec![0u8; 1024];
+    let mut coordinates_len = coordinates.len() as i32;
+    unsafe {
+        unwrap_ffi_error(h_user_key_coordinates(
+            coordinates.as_mut_ptr().cast(),
+            &mut coordinates_len,
+            usk_bytes.as_ptr().cast(),
+            usk_bytes.len() as i32,
+            mpk_bytes.as_ptr().cast(),
+            mpk_bytes.len() as i32,
+            policy_bytes.as_ptr().cast(),
+            policy_bytes.len() as i32,
+        ));
+    }
+    coordinates.truncate(coordinates_len as usize);
+    let mut coordinates: Vec<String> = serde_json::from_slice(&coordinates).unwrap();
+    coordinates.sort();
+    assert_eq!(
+        coordinates,
+        [
+            "Department::FIN && Security Level::Confidential",
+            "Department::FIN && Security Level::Protected",
+        ]
+    );
+
+    for (encryption_policy, expected) in [
+        ("Department::FIN && Security Level::Protected", 1),
+        ("Department::HR && Security Level::Protected", 0),
+    ] {
+        let encryption_policy = CString::new(encryption_policy).unwrap();
+        let mut can_decrypt = -1;
+        unsafe {
+            unwrap_ffi_error(h_can_decrypt(
+                &mut can_decrypt,
+                usk_bytes.as_ptr().cast(),
+                usk_bytes.len() as i32,
+                mpk_bytes.as_ptr().cast(),
+                mpk_bytes.len() as i32,
+                encryption_policy.as_ptr(),
+                policy_bytes.as_ptr().cast(),
+                policy_bytes.len() as i32,
+            ));
+        }
+        assert_eq!(can_decrypt, expected);
+    }
+}
+
+#[test]
+fn test_refresh_user_secret_key_with_rotations() {
+    let policy = policy().unwrap();
+    let policy_bytes: Vec<u8> = (&policy).try_into().unwrap();
+    let access_policy =
+        AccessPolicy::from_boolean_expression("Department::FIN && Security Level::Protected")
+            .unwrap();
+    let cover_crypt = Covercrypt::default();
+    let (mut msk, mut mpk) = cover_crypt.generate_master_keys(&policy).unwrap();
+    let usk = cover_crypt
+        .generate_user_secret_key(&msk, &access_policy, &policy)
+        .unwrap();
+
+    // One header per rotation, the oldest first.
+    let mut headers = Vec::new();
+    for _ in 0..2 {
+        let (_, header) =
+            EncryptedHeader::generate(&cover_crypt, &policy, &mpk, &access_policy, None, None)
+                .unwrap();
+        headers.push(header);
+        cover_crypt
+            .rekey_master_keys(&access_policy, &policy, &mut msk, &mut mpk)
+            .unwrap();
+    }
+    let (_, header) =
+        EncryptedHeader::generate(&cover_crypt, &policy, &mpk, &access_policy, None, None).unwrap();
+    headers.push(header);
+
+    let msk_bytes = serialize_versioned(&msk).unwrap();
+    let usk_bytes = serialize_versioned(&usk).unwrap();
+    let mut refreshed_usk_bytes = vec![0u8; 8192];
+    let mut refreshed_usk_len = refreshed_usk_bytes.len() as i32;
+    unsafe {
+        unwrap_ffi_error(h_refresh_user_secret_key_with_rotations(
+            refreshed_usk_bytes.as_mut_ptr().cast(),
+            &mut refreshed_usk_len,
+            msk_bytes.as_ptr().cast(),
+            msk_bytes.len() as i32,
+            usk_bytes.as_ptr().cast(),
+            usk_bytes.len() as i32,
+            1,
+        ));
+    }
+    refreshed_usk_bytes.truncate(refreshed_usk_len as usize);
+    let refreshed_usk = try_deserialize_any_version::<UserSecretKey>(&refreshed_usk_bytes).unwrap();
+    assert!(headers[0]
+        .decrypt(&cover_crypt, &refreshed_usk, None)
+        .is_err());
+    headers[1]
+        .decrypt(&cover_crypt, &refreshed_usk, None)
+        .unwrap();
+    headers[2]
+        .decrypt(&cover_crypt, &refreshed_usk, None)
+        .unwrap();
+
+    let mut rotations = vec![0u8; 1024];
+    let mut rotations_len = rotations.len() as i32;
+    unsafe {
+        unwrap_ffi_error(h_user_key_rotations(
+            rotations.as_mut_ptr().cast(),
+            &mut rotations_len,
+            refreshed_usk_bytes.as_ptr().cast(),
+            refreshed_usk_bytes.len() as i32,
+            policy_bytes.as_ptr().cast(),
+            policy_bytes.len() as i32,
+        ));
+    }
+    rotations.truncate(rotations_len as usize);
+    let rotations: serde_json::Value = serde_json::from_slice(&rotations).unwrap();
+    assert_eq!(
+        rotations,
+        serde_json::json!({ "Department::FIN && Security Level::Protected": 2 })
+    );
+
+    // Negative numbers of rotations are rejected.
+    unsafe {
+        assert_ne!(
+            h_refresh_user_secret_key_with_rotations(
+                refreshed_usk_bytes.as_mut_ptr().cast(),
+                &mut refreshed_usk_len,
+                msk_bytes.as_ptr().cast(),
+                msk_bytes.len() as i32,
+                usk_bytes.as_ptr().cast(),
+                usk_bytes.len() as i32,
+                -1,
+            ),
+            0
+        );
     }
 }
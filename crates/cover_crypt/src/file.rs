@@ -0,0 +1,448 @@
+//! Hybrid encryption of files.
+//!
+//! Files are encrypted chunk by chunk so that they are never entirely loaded
+//! in memory. The payload is encrypted using the STREAM construction (with a
+//! 32-bit big-endian counter) of AES256-GCM, keyed by the symmetric key
+//! encapsulated in the Covercrypt header:
+//!
+//! ```txt
+//! encrypted_header || nonce || c_1 || ... || c_n
+//! ```
+//!
+//! All the chunks hold `CHUNK_LENGTH` bytes of plaintext but the last one,
+//! which is shorter (possibly empty). The authentication data is used to
+//! authenticate each chunk.
+
+use std::{
+    fmt::Display,
+    fs::{self, File, OpenOptions},
+    io::{BufReader, BufWriter, Cursor, ErrorKind, Read, Write},
+    path::{Path, PathBuf},
+};
+
+use cosmian_cover_crypt::{
+    abe_policy::{AccessPolicy, Policy},
+    Covercrypt, EncryptedHeader, Error, MasterPublicKey, UserSecretKey,
+};
+use cosmian_crypto_core::{
+    bytes_ser_de::{Deserializer, Serializable},
+    reexport::{
+        aead::Payload,
+        rand_core::{RngCore, SeedableRng},
+    },
+    Aes256Gcm, CryptoCoreError, CsRng, DemStream, Instantiable, Nonce, RandomFixedSizeCBytes,
+};
+
+/// Length of the plaintext chunks.
+pub const CHUNK_LENGTH: usize = 1 << 16;
+
+/// Size of the first chunk read when looking for the end of the encrypted
+/// header. It is doubled until the header can be read.
+const HEADER_CHUNK_LENGTH: usize = 4096;
+
+#[derive(Debug)]
+pub enum FileError {
+    Covercrypt(Error),
+    Io(std::io::Error),
+}
+
+impl Display for FileError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Covercrypt(e) => write!(f, "{e}"),
+            Self::Io(e) => write!(f, "I/O error: {e}"),
+        }
+    }
+}
+
+impl std::error::Error for FileError {}
+
+impl From<Error> for FileError {
+    fn from(e: Error) -> Self {
+        Self::Covercrypt(e)
+    }
+}
+
+impl From<CryptoCoreError> for FileError {
+    fn from(e: CryptoCoreError) -> Self {
+        Self::Covercrypt(Error::from(e))
+    }
+}
+
+impl From<std::io::Error> for FileError {
+    fn from(e: std::io::Error) -> Self {
+        Self::Io(e)
+    }
+}
+
+/// Reads from the given reader until the given buffer is full or the end of
+/// the stream is reached. Returns the number of bytes read.
+fn read_chunk(reader: &mut impl Read, buffer: &mut [u8]) -> Result<usize, std::io::Error> {
+    let mut n = 0;
+    while n < buffer.len() {
+        match reader.read(&mut buffer[n..])? {
+            0 => break,
+            m => n += m,
+        }
+    }
+    Ok(n)
+}
+
+/// Reads the encrypted header at the beginning of the given stream, and
+/// returns it along with the bytes read after it.
+fn read_encrypted_header(reader: &mut impl Read) -> Result<(EncryptedHeader, Vec<u8>), FileError> {
+    let mut buffer = Vec::new();
+    let mut chunk_length = HEADER_CHUNK_LENGTH;
+    loop {
+        let n = reader
+            .by_ref()
+            .take(chunk_length as u64)
+            .read_to_end(&mut buffer)?;
+        let mut de = Deserializer::new(&buffer);
+        match de.read::<EncryptedHeader>() {
+            Ok(header) => return Ok((header, de.finalize())),
+            Err(e) if n < chunk_length => return Err(e.into()),
+            Err(_) => chunk_length *= 2,
+        }
+    }
+}
+
+/// Hybrid encrypts the content of the given reader into the given writer.
+///
+/// - `header_metadata`     : optional metadata encrypted in the header
+/// - `authentication_data` : optional data to use for authentication
+#[allow(clippy::too_many_arguments)]
+pub fn encrypt_stream(
+    cover_crypt: &Covercrypt,
+    policy: &Policy,
+    public_key: &MasterPublicKey,
+    access_policy: &AccessPolicy,
+    reader: &mut impl Read,
+    writer: &mut impl Write,
+    header_metadata: Option<&[u8]>,
+    authentication_data: Option<&[u8]>,
+) -> Result<(), FileError> {
+    let (symmetric_key, encrypted_header) = EncryptedHeader::generate(
+        cover_crypt,
+        policy,
+        public_key,
+        access_policy,
+        header_metadata,
+        authentication_data,
+    )?;
+    writer.write_all(&encrypted_header.serialize()?)?;
+
+    let nonce = Nonce::<{ Aes256Gcm::NONCE_LENGTH }>::new(&mut CsRng::from_entropy());
+    writer.write_all(nonce.as_bytes())?;
+
+    let aad = authentication_data.unwrap_or_default();
+    let mut encryptor = Aes256Gcm::new(&symmetric_key).into_stream_encryptor_be32(&nonce);
+    let mut chunk = vec![0; CHUNK_LENGTH];
+    loop {
+        let n = read_chunk(reader, &mut chunk)?;
+        if n < CHUNK_LENGTH {
+            let ciphertext = encryptor
+                .encrypt_last(Payload {
+                    msg: &chunk[..n],
+                    aad,
+                })
+                .map_err(CryptoCoreError::from)?;
+            writer.write_all(&ciphertext)?;
+            break;
+        }
+        let ciphertext = encryptor
+            .encrypt_next(Payload { msg: &chunk, aad })
+            .map_err(CryptoCoreError::from)?;
+        writer.write_all(&ciphertext)?;
+    }
+    writer.flush()?;
+    Ok(())
+}
+
+/// Hybrid decrypts the content of the given reader into the given writer.
+///
+/// Returns the header metadata. Each chunk is authenticated before being
+/// written, but a truncated ciphertext is only detected upon reading the end
+/// of the stream: the output should be discarded upon error.
+///
+/// - `authentication_data` : optional data to use for authentication
+pub fn decrypt_stream(
+    cover_crypt: &Covercrypt,
+    usk: &UserSecretKey,
+    reader: &mut impl Read,
+    writer: &mut impl Write,
+    authentication_data: Option<&[u8]>,
+) -> Result<Option<Vec<u8>>, FileError> {
+    let (encrypted_header, remaining_bytes) = read_encrypted_header(reader)?;
+    let cleartext_header = encrypted_header.decrypt(cover_crypt, usk, authentication_data)?;
+    let mut reader = Cursor::new(remaining_bytes).chain(reader);
+
+    let mut nonce = [0; Aes256Gcm::NONCE_LENGTH];
+    reader.read_exact(&mut nonce)?;
+    let nonce = Nonce::from(nonce);
+
+    let aad = authentication_data.unwrap_or_default();
+    let mut decryptor =
+        Aes256Gcm::new(&cleartext_header.symmetric_key).into_stream_decryptor_be32(&nonce);
+    let mut chunk = vec![0; CHUNK_LENGTH + Aes256Gcm::MAC_LENGTH];
+    loop {
+        let n = read_chunk(&mut reader, &mut chunk)?;
+        if n < chunk.len() {
+            let plaintext = decryptor
+                .decrypt_last(Payload {
+                    msg: &chunk[..n],
+                    aad,
+                })
+                .map_err(CryptoCoreError::from)?;
+            writer.write_all(&plaintext)?;
+            break;
+        }
+        let plaintext = decryptor
+            .decrypt_next(Payload { msg: &chunk, aad })
+            .map_err(CryptoCoreError::from)?;
+        writer.write_all(&plaintext)?;
+    }
+    writer.flush()?;
+    Ok(cleartext_header.metadata)
+}
+
+/// Returns a new temporary path in the directory of the given output path.
+fn temporary_path(output_path: &Path) -> Result<PathBuf, FileError> {
+    let file_name = output_path.file_name().ok_or_else(|| {
+        std::io::Error::new(
+            ErrorKind::InvalidInput,
+            format!("invalid output file path: {}", output_path.display()),
+        )
+    })?;
+    let mut temporary_name = file_name.to_os_string();
+    temporary_name.push(format!(".{:016x}.tmp", CsRng::from_entropy().next_u64()));
+    Ok(output_path.with_file_name(temporary_name))
+}
+
+/// Writes the output file using the given function.
+///
+/// The output is written to a temporary file, renamed to the output path upon
+/// success and removed upon error: the output path never holds a partial
+/// output.
+fn write_file<T>(
+    output_path: &Path,
+    write: impl FnOnce(&mut BufWriter<File>) -> Result<T, FileError>,
+) -> Result<T, FileError> {
+    let temporary_path = temporary_path(output_path)?;
+    let file = OpenOptions::new()
+        .write(true)
+        .create_new(true)
+        .open(&temporary_path)?;
+    let mut writer = BufWriter::new(file);
+    let res = write(&mut writer).and_then(|res| {
+        writer
+            .into_inner()
+            .map_err(|e| e.into_error())?
+            .sync_all()?;
+        fs::rename(&temporary_path, output_path)?;
+        Ok(res)
+    });
+    if res.is_err() {
+        // The writing error is more relevant than a removal error.
+        let _ = fs::remove_file(&temporary_path);
+    }
+    res
+}
+
+/// Hybrid encrypts the given input file into the given output file.
+///
+/// The output file is only written upon success. See [`encrypt_stream`] for
+/// the parameters.
+#[allow(clippy::too_many_arguments)]
+pub fn encrypt_file(
+    cover_crypt: &Covercrypt,
+    policy: &Policy,
+    public_key: &MasterPublicKey,
+    access_policy: &AccessPolicy,
+    input_path: &Path,
+    output_path: &Path,
+    header_metadata: Option<&[u8]>,
+    authentication_data: Option<&[u8]>,
+) -> Result<(), FileError> {
+    let mut reader = BufReader::new(File::open(input_path)?);
+    write_file(output_path, |writer| {
+        encrypt_stream(
+            cover_crypt,
+            policy,
+            public_key,
+            access_policy,
+            &mut reader,
+            writer,
+            header_metadata,
+            authentication_data,
+        )
+    })
+}
+
+/// Hybrid decrypts the given input file into the given output file, and
+/// returns the header metadata.
+///
+/// The output file is only written upon success.
+pub fn decrypt_file(
+    cover_crypt: &Covercrypt,
+    usk: &UserSecretKey,
+    input_path: &Path,
+    output_path: &Path,
+    authentication_data: Option<&[u8]>,
+) -> Result<Option<Vec<u8>>, FileError> {
+    let mut reader = BufReader::new(File::open(input_path)?);
+    write_file(output_path, |writer| {
+        decrypt_stream(cover_crypt, usk, &mut reader, writer, authentication_data)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use cosmian_cover_crypt::test_utils::policy;
+
+    use super::*;
+
+    #[test]
+    fn test_encrypt_decrypt_stream() -> Result<(), FileError> {
+        let policy = policy()?;
+        let cover_crypt = Covercrypt::default();
+        let (msk, mpk) = cover_crypt.generate_master_keys(&policy)?;
+        let access_policy =
+            AccessPolicy::from_boolean_expression("Department::MKG && Security Level::Low Secret")?;
+        let usk = cover_crypt.generate_user_secret_key(
+            &msk,
+            &AccessPolicy::from_boolean_expression(
+                "Department::MKG && Security Level::Top Secret",
+            )?,
+            &policy,
+        )?;
+        let authentication_data = b"authentication data";
+
+        // Empty, single-chunk, exact multiple and multi-chunk plaintexts.
+        for plaintext_length in [0, 42, CHUNK_LENGTH, 2 * CHUNK_LENGTH + 42] {
+            let plaintext = (0..plaintext_length)
+                .map(|i| (i % 251) as u8)
+                .collect::<Vec<_>>();
+
+            let mut ciphertext = Vec::new();
+            encrypt_stream(
+                &cover_crypt,
+                &policy,
+                &mpk,
+                &access_policy,
+                &mut plaintext.as_slice(),
+                &mut ciphertext,
+                Some(b"header metadata"),
+                Some(authentication_data),
+            )?;
+
+            let mut decrypted = Vec::new();
+            let header_metadata = decrypt_stream(
+                &cover_crypt,
+                &usk,
+                &mut ciphertext.as_slice(),
+                &mut decrypted,
+                Some(authentication_data),
+            )?;
+            assert_eq!(decrypted, plaintext);
+            assert_eq!(header_metadata, Some(b"header metadata".to_vec()));
+
+            // The authentication data is checked.
+            assert!(decrypt_stream(
+                &cover_crypt,
+                &usk,
+                &mut ciphertext.as_slice(),
+                &mut Vec::new(),
+                None,
+            )
+            .is_err());
+
+            // Truncated ciphertexts are rejected.
+            let truncated = &ciphertext[..ciphertext.len() - Aes256Gcm::MAC_LENGTH];
+            assert!(decrypt_stream(
+                &cover_crypt,
+                &usk,
+                &mut &truncated[..],
+                &mut Vec::new(),
+                Some(authentication_data),
+            )
+            .is_err());
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_encrypt_decrypt_file() -> Result<(), FileError> {
+        let policy = policy()?;
+        let cover_crypt = Covercrypt::default();
+        let (msk, mpk) = cover_crypt.generate_master_keys(&policy)?;
+        let access_policy =
+            AccessPolicy::from_boolean_expression("Department::FIN && Security Level::Protected")?;
+        let usk = cover_crypt.generate_user_secret_key(&msk, &access_policy, &policy)?;
+
+        let dir = std::env::temp_dir();
+        let input_path = dir.join("cloudproof_cover_crypt_file_input");
+        let encrypted_path = dir.join("cloudproof_cover_crypt_file_encrypted");
+        let output_path = dir.join("cloudproof_cover_crypt_file_output");
+
+        let plaintext = vec![42; 3 * CHUNK_LENGTH / 2];
+        fs::write(&input_path, &plaintext)?;
+        encrypt_file(
+            &cover_crypt,
+            &policy,
+            &mpk,
+            &access_policy,
+            &input_path,
+            &encrypted_path,
+            None,
+            None,
+        )?;
+        let header_metadata =
+            decrypt_file(&cover_crypt, &usk, &encrypted_path, &output_path, None)?;
+        assert_eq!(header_metadata, None);
+        assert_eq!(fs::read(&output_path)?, plaintext);
+
+        // The output file is left untouched upon error.
+        assert!(decrypt_file(
+            &cover_crypt,
+            &usk,
+            &encrypted_path,
+            &output_path,
+            Some(b"wrong authentication data"),
+        )
+        .is_err());
+        assert_eq!(fs::read(&output_path)?, plaintext);
+
+        let failed_path = dir.join("cloudproof_cover_crypt_file_failed");
+        assert!(encrypt_file(
+            &cover_crypt,
+            &policy,
+            &mpk,
+            &AccessPolicy::from_boolean_expression("Department::UNKNOWN")?,
+            &input_path,
+            &failed_path,
+            None,
+            None,
+        )
+        .is_err());
+        assert!(!failed_path.exists());
+
+        // No temporary file is left behind.
+        for entry in fs::read_dir(&dir)? {
+            let file_name = entry?.file_name();
+            let file_name = file_name.to_string_lossy();
+            assert!(
+                !(file_name.starts_with("cloudproof_cover_crypt_file_")
+                    && file_name.ends_with(".tmp")),
+                "temporary file left: {file_name}"
+            );
+        }
+
+        for path in [input_path, encrypted_path, output_path] {
+            fs::remove_file(path)?;
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,226 @@
+//! Structural information about an encrypted header, read without any key.
+//!
+//! Storage layers can use it to validate or route the ciphertexts they
+//! receive, e.g. to check that records requiring post-quantum protection were
+//! encrypted under a hybridized partition. The encrypted header of the exact
+//! Covercrypt version this crate depends on is serialized as:
+//!
+//! ```txt
+//! encrypted header = encapsulation || LEB128(metadata.len()) || encrypted metadata
+//! encapsulation    = c1 || c2 || tag || LEB128(n_partitions)
+//!                    || (LEB128(is_hybridized) || key encapsulation) * n_partitions
+//! ```
+//!
+//! The header is first deserialized using [`EncryptedHeader`], which checks
+//! it and gives the lengths of its parts. The key encapsulations are not
+//! exposed by Covercrypt: they are then counted by walking their
+//! serialization, mirroring `KeyEncapsulation::read`.
+//!
+//! The authentication data given upon encryption is never stored in the
+//! ciphertext: whether it was used cannot be told without decrypting.
+
+use cosmian_cover_crypt::{core::SYM_KEY_LENGTH, EncryptedHeader, Error};
+use cosmian_crypto_core::{
+    bytes_ser_de::{Deserializer, Serializable},
+    CryptoCoreError, FixedSizeCBytes, R25519PublicKey,
+};
+use pqc_kyber::KYBER_INDCPA_BYTES;
+
+/// Length of the tag of the encapsulation, private to Covercrypt.
+const TAG_LENGTH: usize = 16;
+
+/// Length of the serialized encapsulation before the key encapsulations: the
+/// two ephemeral public keys and the tag.
+const ENCAPSULATION_PREFIX_LENGTH: usize = 2 * R25519PublicKey::LENGTH + TAG_LENGTH;
+
+/// Structural information about an encrypted header.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct HeaderInfo {
+    /// Length of the encrypted header, metadata included.
+    pub header_length: usize,
+    /// Length of the key encapsulation part of the header.
+    pub encapsulation_length: usize,
+    /// Number of partitions the key is encapsulated for.
+    pub n_partitions: usize,
+    /// Number of these partitions using a hybridized (post-quantum)
+    /// encapsulation.
+    pub n_hybridized_partitions: usize,
+    /// Length of the encrypted header metadata, 0 if there is none.
+    pub metadata_length: usize,
+}
+
+impl HeaderInfo {
+    /// Whether the key is encapsulated using post-quantum cryptography for at
+    /// least one partition.
+    #[must_use]
+    pub const fn is_hybridized(&self) -> bool {
+        self.n_hybridized_partitions > 0
+    }
+
+    /// Returns the header information as a JSON object.
+    #[must_use]
+    pub fn to_json(&self) -> serde_json::Value {
+        serde_json::json!({
+            "header_length": self.header_length,
+            "encapsulation_length": self.encapsulation_length,
+            "n_partitions": self.n_partitions,
+            "n_hybridized_partitions": self.n_hybridized_partitions,
+            "is_hybridized": self.is_hybridized(),
+            "has_metadata": self.metadata_length > 0,
+        })
+    }
+}
+
+/// Reads the information of the encrypted header at the start of the given
+/// bytes, e.g. a hybrid ciphertext.
+pub fn read_header_info(encrypted_bytes: &[u8]) -> Result<HeaderInfo, Error> {
+    // Check this is a valid header and get its length.
+    let mut de = Deserializer::new(encrypted_bytes);
+    let encrypted_header = EncryptedHeader::read(&mut de)?;
+    let header_length = encrypted_bytes.len() - de.finalize().len();
+    let encapsulation_length = encrypted_header.encapsulation.length();
+    let metadata_length = encrypted_header
+        .encrypted_metadata
+        .as_ref()
+        .map_or(0, Vec::len);
+
+    let mut de = Deserializer::new(
+        encrypted_bytes
+            .get(ENCAPSULATION_PREFIX_LENGTH..encapsulation_length)
+            .ok_or_else(|| malformed("encapsulation too short"))?,
+    );
+    let n_partitions =
+        usize::try_from(de.read_leb128_u64()?).map_err(|e| malformed(e.to_string()))?;
+    let mut n_hybridized_partitions = 0;
+    for _ in 0..n_partitions {
+        if de.read_leb128_u64()? == 1 {
+            de.read_array::<KYBER_INDCPA_BYTES>()?;
+            n_hybridized_partitions += 1;
+        } else {
+            de.read_array::<SYM_KEY_LENGTH>()?;
+        }
+    }
+    if !de.finalize().is_empty() {
+        return Err(malformed("unexpected key encapsulation length"));
+    }
+
+    Ok(HeaderInfo {
+        header_length,
+        encapsulation_length,
+        n_partitions,
+        n_hybridized_partitions,
+        metadata_length,
+    })
+}
+
+fn malformed(message: impl std::fmt::Display) -> Error {
+    CryptoCoreError::GenericDeserializationError(format!("malformed encrypted header: {message}"))
+        .into()
+}
+
+#[cfg(test)]
+mod tests {
+    use cosmian_cover_crypt::{abe_policy::AccessPolicy, Covercrypt};
+
+    use super::*;
+    use crate::policy_spec::policy_from_json_spec;
+
+    #[test]
+    fn test_header_info() -> Result<(), Error> {
+        let policy = policy_from_json_spec(br#"{"Department": ["HR", "MKG", "FIN::+"]}"#).unwrap();
+        let cover_crypt = Covercrypt::default();
+        let (_, mpk) = cover_crypt.generate_master_keys(&policy)?;
+
+        let header = |access_policy: &str, metadata: Option<&[u8]>| -> Result<Vec<u8>, Error> {
+            let (_, encrypted_header) = EncryptedHeader::generate(
+                &cover_crypt,
+                &policy,
+                &mpk,
+                &AccessPolicy::from_boolean_expression(access_policy)?,
+                metadata,
+                Some(b"authentication data"),
+            )?;
+            Ok(encrypted_header.serialize()?.to_vec())
+        };
+
+        let classic = header("Department::HR || Department::MKG", None)?;
+        let info = read_header_info(&classic)?;
+        assert_eq!(info.header_length, classic.len());
+        assert_eq!(info.n_partitions, 2);
+        assert!(!info.is_hybridized());
+        assert_eq!(info.metadata_length, 0);
+        assert_eq!(info.encapsulation_length, classic.len() - 1);
+
+        // Trailing bytes such as a DEM ciphertext are ignored.
+        let mut hybridized = header("Department::HR || Department::FIN", Some(b"metadata"))?;
+        let header_length = hybridized.len();
+        hybridized.extend_from_slice(b"DEM ciphertext");
+        let info = read_header_info(&hybridized)?;
+        assert_eq!(info.header_length, header_length);
+        assert_eq!(info.n_partitions, 2);
+        assert_eq!(info.n_hybridized_partitions, 1);
+        assert!(info.metadata_length > 0);
+        assert_eq!(
+            info.encapsulation_length,
+            ENCAPSULATION_PREFIX_LENGTH + 1 + (1 + SYM_KEY_LENGTH) + (1 + KYBER_INDCPA_BYTES)
+        );
+        assert_eq!(info.to_json()["is_hybridized"], true);
+        assert_eq!(info.to_json()["has_metadata"], true);
+
+        assert!(read_header_info(&classic[..classic.len() - 1]).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_encapsulation_layout() -> Result<(), Error> {
+        let policy =
+            policy_from_json_spec(br#"{"Department": ["HR", "MKG", "R&D", "FIN::+"]}"#).unwrap();
+        let cover_crypt = Covercrypt::default();
+        let (_, mpk) = cover_crypt.generate_master_keys(&policy)?;
+        let header = |access_policy: &str| -> Result<(Vec<u8>, usize), Error> {
+            let (_, encrypted_header) = EncryptedHeader::generate(
+                &cover_crypt,
+                &policy,
+                &mpk,
+                &AccessPolicy::from_boolean_expression(access_policy)?,
+                None,
+                None,
+            )?;
+            Ok((
+                encrypted_header.serialize()?.to_vec(),
+                encrypted_header.encapsulation.length(),
+            ))
+        };
+
+        // The number of partitions directly follows the ephemeral public keys
+        // and the tag.
+        for (n_partitions, access_policy) in [
+            "Department::HR",
+            "Department::HR || Department::MKG",
+            "Department::HR || Department::MKG || Department::R&D",
+        ]
+        .into_iter()
+        .enumerate()
+        {
+            let (bytes, encapsulation_length) = header(access_policy)?;
+            assert_eq!(
+                usize::from(bytes[ENCAPSULATION_PREFIX_LENGTH]),
+                n_partitions + 1
+            );
+            assert_eq!(
+                encapsulation_length,
+                ENCAPSULATION_PREFIX_LENGTH + 1 + (n_partitions + 1) * (1 + SYM_KEY_LENGTH)
+            );
+        }
+
+        // Lengths of the classic and hybridized key encapsulations, as
+        // computed by Covercrypt.
+        let (_, classic_length) = header("Department::HR")?;
+        let (_, hybridized_length) = header("Department::FIN")?;
+        assert_eq!(
+            hybridized_length - classic_length,
+            KYBER_INDCPA_BYTES - SYM_KEY_LENGTH
+        );
+        Ok(())
+    }
+}
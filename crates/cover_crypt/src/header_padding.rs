@@ -0,0 +1,138 @@
+//! Padding of the encrypted headers to a fixed-size bucket.
+//!
+//! The size of an encrypted header grows with the number of partitions covered
+//! by the access policy: the length of a hybrid ciphertext leaks how broad its
+//! access policy is. Padding the header with zeros up to the next multiple of
+//! a bucket size hides this number, as long as the headers fit in the same
+//! bucket:
+//!
+//! ```txt
+//! hybrid ciphertext = encrypted header || 0x00 padding || DEM ciphertext
+//! ```
+//!
+//! The padding is not self-describing: the bucket size given upon encryption
+//! must be given upon decryption. A bucket size of 0 disables the padding.
+
+use cosmian_cover_crypt::{EncryptedHeader, Error};
+use cosmian_crypto_core::{
+    bytes_ser_de::{Deserializer, Serializable, Serializer},
+    CryptoCoreError,
+};
+
+/// Returns the length of a header of the given length once padded to the given
+/// bucket size.
+#[must_use]
+pub fn padded_header_length(header_length: usize, bucket_size: usize) -> usize {
+    if bucket_size == 0 {
+        header_length
+    } else {
+        header_length.div_ceil(bucket_size) * bucket_size
+    }
+}
+
+/// Writes the given encrypted header followed by its padding.
+///
+/// Returns the number of bytes written.
+pub fn write_padded_header(
+    ser: &mut Serializer,
+    encrypted_header: &EncryptedHeader,
+    bucket_size: usize,
+) -> Result<usize, Error> {
+    let header_length = encrypted_header.write(ser)?;
+    let padded_length = padded_header_length(header_length, bucket_size);
+    ser.write_array(&vec![0; padded_length - header_length])?;
+    Ok(padded_length)
+}
+
+/// Reads the padded encrypted header of the given hybrid ciphertext.
+///
+/// - `encrypted_bytes` : padded encrypted header || DEM ciphertext
+///
+/// Returns the encrypted header and the DEM ciphertext.
+pub fn read_padded_header(
+    encrypted_bytes: &[u8],
+    bucket_size: usize,
+) -> Result<(EncryptedHeader, &[u8]), Error> {
+    let mut de = Deserializer::new(encrypted_bytes);
+    // This will read the exact header size.
+    let encrypted_header = EncryptedHeader::read(&mut de)?;
+    let header_length = encrypted_bytes.len() - de.finalize().len();
+    let padded_length = padded_header_length(header_length, bucket_size);
+    if encrypted_bytes.len() < padded_length {
+        return Err(CryptoCoreError::InvalidBytesLength(
+            "padded encrypted header".to_string(),
+            encrypted_bytes.len(),
+            Some(padded_length),
+        )
+        .into());
+    }
+    let (padded_header, ciphertext) = encrypted_bytes.split_at(padded_length);
+    if padded_header[header_length..].iter().any(|byte| *byte != 0) {
+        return Err(CryptoCoreError::GenericDeserializationError(format!(
+            "invalid encrypted header padding for a bucket size of {bucket_size}"
+        ))
+        .into());
+    }
+    Ok((encrypted_header, ciphertext))
+}
+
+#[cfg(test)]
+mod tests {
+    use cosmian_cover_crypt::{abe_policy::AccessPolicy, test_utils::policy, Covercrypt};
+
+    use super::*;
+
+    #[test]
+    fn test_header_padding() -> Result<(), Error> {
+        let policy = policy()?;
+        let cover_crypt = Covercrypt::default();
+        let (msk, mpk) = cover_crypt.generate_master_keys(&policy)?;
+        let usk = cover_crypt.generate_user_secret_key(
+            &msk,
+            &AccessPolicy::from_boolean_expression(
+                "Department::MKG && Security Level::Low Secret",
+            )?,
+            &policy,
+        )?;
+
+        let encrypt = |access_policy: &str, bucket_size: usize| {
+            let (symmetric_key, encrypted_header) = EncryptedHeader::generate(
+                &cover_crypt,
+                &policy,
+                &mpk,
+                &AccessPolicy::from_boolean_expression(access_policy)?,
+                None,
+                None,
+            )?;
+            let ciphertext = cover_crypt.encrypt(&symmetric_key, b"plaintext", None)?;
+            let mut ser = Serializer::new();
+            write_padded_header(&mut ser, &encrypted_header, bucket_size)?;
+            ser.write_array(&ciphertext)?;
+            Ok::<_, Error>(ser.finalize().to_vec())
+        };
+        let decrypt = |encrypted_bytes: &[u8], bucket_size: usize| {
+            let (encrypted_header, ciphertext) = read_padded_header(encrypted_bytes, bucket_size)?;
+            let cleartext_header = encrypted_header.decrypt(&cover_crypt, &usk, None)?;
+            cover_crypt.decrypt(&cleartext_header.symmetric_key, ciphertext, None)
+        };
+
+        // Without padding, the ciphertext length depends on the access policy.
+        let narrow = "Department::MKG && Security Level::Low Secret";
+        let broad = "(Department::MKG || Department::FIN) && Security Level::Low Secret";
+        assert_ne!(encrypt(narrow, 0)?.len(), encrypt(broad, 0)?.len());
+
+        // With padding, both headers fit in the same bucket.
+        let narrow_ciphertext = encrypt(narrow, 4096)?;
+        assert_eq!(narrow_ciphertext.len(), encrypt(broad, 4096)?.len());
+        assert_eq!(decrypt(&narrow_ciphertext, 4096)?, b"plaintext");
+
+        // The same bucket size is needed upon decryption.
+        assert!(decrypt(&narrow_ciphertext, 0).is_err());
+        assert!(decrypt(&narrow_ciphertext, 1024).is_err());
+
+        assert_eq!(padded_header_length(100, 0), 100);
+        assert_eq!(padded_header_length(100, 64), 128);
+        assert_eq!(padded_header_length(128, 64), 128);
+        Ok(())
+    }
+}
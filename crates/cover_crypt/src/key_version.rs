@@ -0,0 +1,113 @@
+//! Versioned serialization of the Covercrypt keys.
+//!
+//! Serialized master secret keys, master public keys and user secret keys are
+//! prefixed with a format version byte:
+//!
+//! ```txt
+//! versioned key = KEY_FORMAT_VERSION || serialized key
+//! ```
+//!
+//! Keys serialized without this header (format version 0) are still accepted
+//! by [`try_deserialize_any_version`]. They should be serialized again using
+//! [`serialize_versioned`] (see [`upgrade_key`]) in order to be stored in the
+//! current format.
+
+use cosmian_cover_crypt::Error;
+use cosmian_crypto_core::{
+    bytes_ser_de::{Serializable, Serializer},
+    reexport::zeroize::Zeroizing,
+};
+
+/// Current format version of the serialized keys.
+pub const KEY_FORMAT_VERSION: u8 = 1;
+
+/// Serializes the given key, prefixed with the current format version.
+pub fn serialize_versioned<K: Serializable<Error = Error>>(
+    key: &K,
+) -> Result<Zeroizing<Vec<u8>>, Error> {
+    let mut ser = Serializer::with_capacity(1 + key.length());
+    ser.write_array(&[KEY_FORMAT_VERSION])?;
+    key.write(&mut ser)?;
+    Ok(ser.finalize())
+}
+
+/// Deserializes the given key, serialized in the current format or in any
+/// older one.
+pub fn try_deserialize_any_version<K: Serializable<Error = Error>>(
+    bytes: &[u8],
+) -> Result<K, Error> {
+    if let Some((&KEY_FORMAT_VERSION, key_bytes)) = bytes.split_first() {
+        if let Ok(key) = K::deserialize(key_bytes) {
+            return Ok(key);
+        }
+    }
+    // Keys serialized before the introduction of the version header.
+    K::deserialize(bytes)
+        .map_err(|e| Error::KeyError(format!("unsupported key serialization format: {e}")))
+}
+
+/// Converts the given serialized key into the current format.
+pub fn upgrade_key<K: Serializable<Error = Error>>(
+    bytes: &[u8],
+) -> Result<Zeroizing<Vec<u8>>, Error> {
+    serialize_versioned(&try_deserialize_any_version::<K>(bytes)?)
+}
+
+#[cfg(test)]
+mod tests {
+    use cosmian_cover_crypt::{
+        abe_policy::AccessPolicy, test_utils::policy, Covercrypt, MasterPublicKey, MasterSecretKey,
+        UserSecretKey,
+    };
+
+    use super::*;
+
+    #[test]
+    fn test_versioned_keys() -> Result<(), Error> {
+        let policy = policy()?;
+        let cover_crypt = Covercrypt::default();
+        let (msk, mpk) = cover_crypt.generate_master_keys(&policy)?;
+        let usk = cover_crypt.generate_user_secret_key(
+            &msk,
+            &AccessPolicy::from_boolean_expression(
+                "Department::MKG && Security Level::Low Secret",
+            )?,
+            &policy,
+        )?;
+
+        // The key maps are unordered: two serializations of the same key may
+        // differ, hence the comparison of the deserialized keys.
+        let msk_bytes = serialize_versioned(&msk)?;
+        assert_eq!(msk_bytes[0], KEY_FORMAT_VERSION);
+        assert_eq!(MasterSecretKey::deserialize(&msk_bytes[1..])?, msk);
+        assert_eq!(
+            try_deserialize_any_version::<MasterSecretKey>(&msk_bytes)?,
+            msk
+        );
+
+        // Legacy keys are upgraded.
+        let mpk_bytes = upgrade_key::<MasterPublicKey>(&mpk.serialize()?)?;
+        assert_eq!(mpk_bytes[0], KEY_FORMAT_VERSION);
+        assert_eq!(
+            try_deserialize_any_version::<MasterPublicKey>(&mpk_bytes)?,
+            mpk
+        );
+        let usk_bytes = upgrade_key::<UserSecretKey>(&usk.serialize()?)?;
+        assert_eq!(usk_bytes[0], KEY_FORMAT_VERSION);
+        assert_eq!(
+            try_deserialize_any_version::<UserSecretKey>(&usk_bytes)?,
+            usk
+        );
+
+        // Upgrading a key in the current format keeps it unchanged.
+        let upgraded_bytes = upgrade_key::<UserSecretKey>(&usk_bytes)?;
+        assert_eq!(upgraded_bytes[0], KEY_FORMAT_VERSION);
+        assert_eq!(
+            try_deserialize_any_version::<UserSecretKey>(&upgraded_bytes)?,
+            usk
+        );
+
+        assert!(try_deserialize_any_version::<UserSecretKey>(&usk_bytes[1..10]).is_err());
+        Ok(())
+    }
+}
@@ -1,4 +1,83 @@
 //! Implement interfaces with other languages.
+//!
+//! # Authentication data
+//!
+//! All the encryption and decryption functions take an optional
+//! `authentication_data`, used as associated data by the DEM (AES256-GCM):
+//!
+//! - it authenticates the symmetric ciphertext and the header metadata, both
+//!   encrypted by the DEM using the key encapsulated in the header;
+//! - it is not stored in the ciphertext: the same authentication data must be
+//!   given upon decryption, otherwise the decryption fails;
+//! - the encapsulation itself is not bound to it: a header without metadata
+//!   can be decrypted using any authentication data, but the symmetric key
+//!   obtained cannot decrypt the symmetric ciphertext;
+//! - an empty authentication data is equivalent to no authentication data.
+//!
+//! Using the record ID as authentication data ties each ciphertext to its
+//! record: a ciphertext copied to another record cannot be decrypted.
+//!
+//! # Header padding
+//!
+//! The hybrid encryption and decryption functions take an optional header
+//! bucket size: the encrypted header is padded up to a multiple of this size
+//! so that the ciphertext length does not leak the number of partitions of the
+//! access policy. See [`header_padding`].
+//!
+//! # Post-quantum protection
+//!
+//! Whether a ciphertext is protected by a hybridized (post-quantum)
+//! encapsulation depends on the partitions of its access policy, not on the
+//! encryption call: the master public key only holds post-quantum keys for the
+//! partitions of the attributes created with a hybridized encryption hint.
+//! The encryption functions therefore cannot force a hybridized encapsulation.
+//! Records requiring post-quantum protection under an otherwise classic
+//! policy should be encrypted under a dedicated hybridized attribute, e.g.
+//! `Top Secret::+` in a [`policy_spec`]. See [`post_quantum`].
+//!
+//! # Key rotation
+//!
+//! A policy rotation rekeys the partitions targeted by an access policy, e.g.
+//! when a user holding these attributes leaves. Rekeying all the master keys
+//! renews every partition without changing the policy, e.g. for a periodic
+//! rotation. In both cases the user keys must be refreshed. See [`rekey`].
+//!
+//! The number of old rotations a refreshed user key keeps access to can be
+//! bounded, e.g. to forbid access to the data encrypted before an incident.
+//! See [`rotation`].
+//!
+//! # Policy edits
+//!
+//! Editing a policy referenced by existing keys may make some ciphertexts
+//! unreadable and require refreshing the user keys. The migration report of
+//! an edit lists the coordinates it removes and adds. See
+//! [`policy_migration`].
+//!
+//! # Key serialization
+//!
+//! The keys returned by the bindings are serialized along with a format
+//! version, and the keys given to them can be serialized in any supported
+//! format. See [`key_version`].
+
+pub mod access_audit;
+pub mod bench;
+#[cfg(feature = "rayon")]
+pub mod bulk;
+pub mod cleartext_metadata;
+#[cfg(feature = "rayon")]
+pub mod column;
+pub mod dem;
+pub mod estimation;
+pub mod file;
+pub mod header_info;
+pub mod header_padding;
+pub mod key_version;
+pub mod multi_key;
+pub mod policy_migration;
+pub mod policy_spec;
+pub mod post_quantum;
+pub mod rekey;
+pub mod rotation;
 
 #[cfg(feature = "ffi")]
 pub mod ffi;
@@ -0,0 +1,129 @@
+//! Hybrid decryption using several candidate user secret keys.
+//!
+//! A gateway holding several user secret keys (one per department for
+//! instance) does not know in advance which one can decrypt a given
+//! ciphertext. The encrypted header is parsed once and the keys are tried in
+//! turn until one of them can decrypt it.
+
+use cosmian_cover_crypt::{Covercrypt, EncryptedHeader, Error, UserSecretKey};
+use cosmian_crypto_core::bytes_ser_de::{Deserializer, Serializable};
+
+/// Result of a hybrid decryption using several candidate user secret keys.
+pub struct DecryptedWithKeys {
+    /// Index of the user secret key that decrypted the ciphertext.
+    pub key_index: usize,
+    pub plaintext: Vec<u8>,
+    pub header_metadata: Option<Vec<u8>>,
+}
+
+/// Hybrid decrypts the given ciphertext using the first of the given user
+/// secret keys able to decrypt its header.
+///
+/// - `encrypted_bytes`     : encrypted header || DEM ciphertext
+/// - `authentication_data` : optional data used for authentication upon
+///   encryption
+pub fn decrypt_with_keys<'a>(
+    cover_crypt: &Covercrypt,
+    usks: impl IntoIterator<Item = &'a UserSecretKey>,
+    encrypted_bytes: &[u8],
+    authentication_data: Option<&[u8]>,
+) -> Result<DecryptedWithKeys, Error> {
+    let mut de = Deserializer::new(encrypted_bytes);
+    // This will read the exact header size.
+    let encrypted_header = EncryptedHeader::read(&mut de)?;
+    // The rest is the symmetric ciphertext.
+    let ciphertext = de.finalize();
+
+    let mut last_error = None;
+    for (key_index, usk) in usks.into_iter().enumerate() {
+        match encrypted_header.decrypt(cover_crypt, usk, authentication_data) {
+            Ok(cleartext_header) => {
+                // The header could be decrypted: any DEM error is not due to
+                // the user key.
+                let plaintext = cover_crypt.decrypt(
+                    &cleartext_header.symmetric_key,
+                    &ciphertext,
+                    authentication_data,
+                )?;
+                return Ok(DecryptedWithKeys {
+                    key_index,
+                    plaintext,
+                    header_metadata: cleartext_header.metadata,
+                });
+            }
+            Err(e) => last_error = Some(e),
+        }
+    }
+    Err(last_error.unwrap_or_else(|| Error::KeyError("no user secret key given".to_string())))
+}
+
+#[cfg(test)]
+mod tests {
+    use cosmian_cover_crypt::{abe_policy::AccessPolicy, test_utils::policy};
+    use cosmian_crypto_core::bytes_ser_de::Serializer;
+
+    use super::*;
+
+    #[test]
+    fn test_decrypt_with_keys() -> Result<(), Error> {
+        let policy = policy()?;
+        let cover_crypt = Covercrypt::default();
+        let (msk, mpk) = cover_crypt.generate_master_keys(&policy)?;
+        let usks = [
+            "Department::HR && Security Level::Top Secret",
+            "Department::MKG && Security Level::Top Secret",
+        ]
+        .into_iter()
+        .map(|access_policy| {
+            cover_crypt.generate_user_secret_key(
+                &msk,
+                &AccessPolicy::from_boolean_expression(access_policy)?,
+                &policy,
+            )
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+
+        let plaintext = b"plaintext";
+        let authentication_data = b"authentication data";
+        let (symmetric_key, encrypted_header) = EncryptedHeader::generate(
+            &cover_crypt,
+            &policy,
+            &mpk,
+            &AccessPolicy::from_boolean_expression(
+                "Department::MKG && Security Level::Low Secret",
+            )?,
+            Some(b"header metadata"),
+            Some(authentication_data),
+        )?;
+        let ciphertext =
+            cover_crypt.encrypt(&symmetric_key, plaintext, Some(authentication_data))?;
+        let mut ser = Serializer::new();
+        encrypted_header.write(&mut ser)?;
+        ser.write_array(&ciphertext)?;
+        let encrypted_bytes = ser.finalize();
+
+        let res = decrypt_with_keys(
+            &cover_crypt,
+            &usks,
+            &encrypted_bytes,
+            Some(authentication_data),
+        )?;
+        assert_eq!(res.key_index, 1);
+        assert_eq!(res.plaintext, plaintext.to_vec());
+        assert_eq!(res.header_metadata, Some(b"header metadata".to_vec()));
+
+        // None of the keys can decrypt the header.
+        assert!(decrypt_with_keys(
+            &cover_crypt,
+            &usks[..1],
+            &encrypted_bytes,
+            Some(authentication_data)
+        )
+        .is_err());
+        assert!(
+            decrypt_with_keys(&cover_crypt, std::iter::empty(), &encrypted_bytes, None).is_err()
+        );
+
+        Ok(())
+    }
+}
@@ -0,0 +1,189 @@
+//! Migration plans of the policy edits.
+//!
+//! The coordinates (partitions) of a policy are the combinations of one
+//! attribute per axis. Editing a policy referenced by existing keys changes
+//! its coordinates: once the master keys are updated using the edited policy,
+//! the ciphertexts encrypted for the removed coordinates can no longer be
+//! decrypted, and the user keys must be refreshed to decrypt the ciphertexts
+//! encrypted for the added ones.
+//!
+//! Adding or removing an axis changes all the coordinates, adding or removing
+//! an attribute only changes the coordinates of this attribute. Renaming or
+//! disabling an attribute changes none: the coordinates are compared using the
+//! attribute IDs, not their names.
+
+use std::collections::HashMap;
+
+use cosmian_cover_crypt::{abe_policy::Policy, Error};
+
+use crate::access_audit::{coordinate_to_boolean_expression, coordinates};
+
+/// Changes of the coordinates of a policy caused by an edit, telling operators
+/// how to roll it out.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct PolicyMigrationReport {
+    /// Coordinates of the previous policy no longer in the updated one, as
+    /// boolean expressions using the previous attribute names. The ciphertexts
+    /// encrypted for them become unreadable once the master keys are updated.
+    pub removed_coordinates: Vec<String>,
+    /// Coordinates of the updated policy not in the previous one, as boolean
+    /// expressions. The user keys granting access to them must be refreshed to
+    /// decrypt the ciphertexts encrypted for them.
+    pub added_coordinates: Vec<String>,
+}
+
+/// Returns the boolean expressions of the coordinates of the given policy,
+/// indexed by the sorted IDs of their attributes.
+fn coordinates_by_ids(policy: &Policy) -> Result<HashMap<Vec<u32>, String>, Error> {
+    coordinates(policy)
+        .into_iter()
+        .map(|coordinate| {
+            let mut ids = coordinate
+                .iter()
+                .map(|attribute| policy.get_attribute_id(attribute))
+                .collect::<Result<Vec<_>, _>>()?;
+            ids.sort_unstable();
+            Ok((ids, coordinate_to_boolean_expression(&coordinate)))
+        })
+        .collect()
+}
+
+impl PolicyMigrationReport {
+    /// Computes the migration report of the edit of `previous_policy` into
+    /// `updated_policy`. The coordinates are sorted.
+    pub fn new(previous_policy: &Policy, updated_policy: &Policy) -> Result<Self, Error> {
+        let previous_coordinates = coordinates_by_ids(previous_policy)?;
+        let updated_coordinates = coordinates_by_ids(updated_policy)?;
+
+        let mut removed_coordinates = previous_coordinates
+            .iter()
+            .filter(|(ids, _)| !updated_coordinates.contains_key(*ids))
+            .map(|(_, coordinate)| coordinate.clone())
+            .collect::<Vec<_>>();
+        removed_coordinates.sort();
+        let mut added_coordinates = updated_coordinates
+            .iter()
+            .filter(|(ids, _)| !previous_coordinates.contains_key(*ids))
+            .map(|(_, coordinate)| coordinate.clone())
+            .collect::<Vec<_>>();
+        added_coordinates.sort();
+
+        Ok(Self {
+            removed_coordinates,
+            added_coordinates,
+        })
+    }
+
+    /// Whether some ciphertexts become unreadable once the master keys are
+    /// updated.
+    #[must_use]
+    pub fn has_unreadable_ciphertexts(&self) -> bool {
+        !self.removed_coordinates.is_empty()
+    }
+
+    /// Whether user keys must be refreshed after updating the master keys.
+    #[must_use]
+    pub fn requires_user_key_refresh(&self) -> bool {
+        !self.removed_coordinates.is_empty() || !self.added_coordinates.is_empty()
+    }
+
+    /// Returns the report as a JSON object.
+    #[must_use]
+    pub fn to_json(&self) -> serde_json::Value {
+        serde_json::json!({
+            "removed_coordinates": self.removed_coordinates,
+            "added_coordinates": self.added_coordinates,
+            "has_unreadable_ciphertexts": self.has_unreadable_ciphertexts(),
+            "requires_user_key_refresh": self.requires_user_key_refresh(),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use cosmian_cover_crypt::abe_policy::{Attribute, DimensionBuilder, EncryptionHint};
+
+    use super::*;
+
+    fn policy() -> Result<Policy, Error> {
+        let mut policy = Policy::new();
+        policy.add_dimension(DimensionBuilder::new(
+            "Security Level",
+            vec![
+                ("Protected", EncryptionHint::Classic),
+                ("Confidential", EncryptionHint::Classic),
+            ],
+            true,
+        ))?;
+        policy.add_dimension(DimensionBuilder::new(
+            "Department",
+            vec![
+                ("HR", EncryptionHint::Classic),
+                ("FIN", EncryptionHint::Classic),
+            ],
+            false,
+        ))?;
+        Ok(policy)
+    }
+
+    #[test]
+    fn test_policy_migration_report() -> Result<(), Error> {
+        let previous_policy = policy()?;
+
+        // Renaming or disabling attributes changes no coordinate.
+        let mut updated_policy = previous_policy.clone();
+        updated_policy.rename_attribute(
+            &Attribute::new("Department", "HR"),
+            "Human Resources".to_string(),
+        )?;
+        updated_policy.disable_attribute(&Attribute::new("Department", "FIN"))?;
+        let report = PolicyMigrationReport::new(&previous_policy, &updated_policy)?;
+        assert_eq!(report, PolicyMigrationReport::default());
+        assert!(!report.requires_user_key_refresh());
+
+        // Adding an attribute adds its coordinates.
+        let mut updated_policy = previous_policy.clone();
+        updated_policy
+            .add_attribute(Attribute::new("Department", "MKG"), EncryptionHint::Classic)?;
+        let report = PolicyMigrationReport::new(&previous_policy, &updated_policy)?;
+        assert!(report.removed_coordinates.is_empty());
+        assert_eq!(
+            report.added_coordinates,
+            [
+                "Department::MKG && Security Level::Confidential",
+                "Department::MKG && Security Level::Protected",
+            ]
+        );
+        assert!(!report.has_unreadable_ciphertexts());
+        assert!(report.requires_user_key_refresh());
+
+        // Removing an attribute removes its coordinates.
+        let mut updated_policy = previous_policy.clone();
+        updated_policy.remove_attribute(&Attribute::new("Department", "HR"))?;
+        let report = PolicyMigrationReport::new(&previous_policy, &updated_policy)?;
+        assert_eq!(
+            report.removed_coordinates,
+            [
+                "Department::HR && Security Level::Confidential",
+                "Department::HR && Security Level::Protected",
+            ]
+        );
+        assert!(report.added_coordinates.is_empty());
+        assert!(report.has_unreadable_ciphertexts());
+
+        // Removing an axis changes all the coordinates.
+        let mut updated_policy = previous_policy.clone();
+        updated_policy.remove_dimension("Security Level")?;
+        let report = PolicyMigrationReport::new(&previous_policy, &updated_policy)?;
+        assert_eq!(report.removed_coordinates.len(), 4);
+        assert_eq!(
+            report.added_coordinates,
+            ["Department::FIN", "Department::HR"]
+        );
+        assert_eq!(
+            report.to_json()["requires_user_key_refresh"],
+            serde_json::Value::Bool(true)
+        );
+        Ok(())
+    }
+}
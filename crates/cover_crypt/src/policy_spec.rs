@@ -0,0 +1,223 @@
+//! Policy specifications, i.e. human-readable JSON templates of policies.
+//!
+//! A specification maps each axis name to the list of its attributes:
+//!
+//! ```json
+//! {
+//!     "Security Level::<": ["Protected", "Confidential", "Top Secret::+"],
+//!     "Department": ["R&D", "HR", "MKG", "FIN"]
+//! }
+//! ```
+//!
+//! - an axis name suffixed with `::<` is hierarchical: its attributes are
+//!   listed from the lowest to the highest;
+//! - an attribute name suffixed with `::+` uses hybridized (post-quantum)
+//!   encryption.
+//!
+//! Specifications can be kept under version control and instantiated
+//! identically from all the bindings.
+
+use std::{collections::HashSet, fmt::Display};
+
+use cosmian_cover_crypt::{
+    abe_policy::{DimensionBuilder, EncryptionHint, Policy},
+    Error,
+};
+use serde_json::Value;
+
+/// Suffix of the hierarchical axis names.
+const HIERARCHICAL_SUFFIX: &str = "::<";
+
+/// Suffix of the hybridized attribute names.
+const HYBRIDIZED_SUFFIX: &str = "::+";
+
+/// Error raised while loading a policy specification.
+#[derive(Debug)]
+pub enum PolicySpecError {
+    /// The specification is not a valid JSON object.
+    InvalidSpecification(String),
+    /// The given axis is invalid.
+    InvalidAxis { axis: String, reason: String },
+    /// The given attribute of the given axis is invalid.
+    InvalidAttribute {
+        axis: String,
+        attribute: String,
+        reason: String,
+    },
+    /// The policy cannot be built from the specification.
+    Policy(Error),
+}
+
+impl Display for PolicySpecError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::InvalidSpecification(reason) => {
+                write!(f, "invalid policy specification: {reason}")
+            }
+            Self::InvalidAxis { axis, reason } => {
+                write!(f, "invalid policy specification: axis `{axis}`: {reason}")
+            }
+            Self::InvalidAttribute {
+                axis,
+                attribute,
+                reason,
+            } => write!(
+                f,
+                "invalid policy specification: attribute `{attribute}` of axis `{axis}`: {reason}"
+            ),
+            Self::Policy(err) => write!(f, "invalid policy specification: {err}"),
+        }
+    }
+}
+
+impl std::error::Error for PolicySpecError {}
+
+impl From<Error> for PolicySpecError {
+    fn from(e: Error) -> Self {
+        Self::Policy(e)
+    }
+}
+
+/// Checks the given axis or attribute name, returning the reason of its
+/// rejection if any.
+fn check_name(name: &str) -> Option<&'static str> {
+    if name.trim().is_empty() {
+        Some("the name is empty")
+    } else if name.contains("::") {
+        Some("the name contains the `::` separator")
+    } else {
+        None
+    }
+}
+
+/// Builds the dimension specified by the given axis name and attributes.
+fn dimension_from_spec(
+    axis_spec: &str,
+    attributes: &Value,
+) -> Result<DimensionBuilder, PolicySpecError> {
+    let (axis, hierarchical) = axis_spec
+        .strip_suffix(HIERARCHICAL_SUFFIX)
+        .map_or((axis_spec, false), |axis| (axis, true));
+    let invalid_axis = |reason: &str| PolicySpecError::InvalidAxis {
+        axis: axis.to_string(),
+        reason: reason.to_string(),
+    };
+    if let Some(reason) = check_name(axis) {
+        return Err(invalid_axis(reason));
+    }
+
+    let attributes = attributes
+        .as_array()
+        .ok_or_else(|| invalid_axis("the attributes should be given as an array of strings"))?;
+    if attributes.is_empty() {
+        return Err(invalid_axis("the axis has no attribute"));
+    }
+
+    let mut names = HashSet::with_capacity(attributes.len());
+    let mut properties = Vec::with_capacity(attributes.len());
+    for attribute_spec in attributes {
+        let attribute_spec = attribute_spec.as_str().ok_or_else(|| {
+            invalid_axis(&format!("the attribute {attribute_spec} is not a string"))
+        })?;
+        let (attribute, encryption_hint) = attribute_spec
+            .strip_suffix(HYBRIDIZED_SUFFIX)
+            .map_or((attribute_spec, EncryptionHint::Classic), |attribute| {
+                (attribute, EncryptionHint::Hybridized)
+            });
+        let invalid_attribute = |reason: &str| PolicySpecError::InvalidAttribute {
+            axis: axis.to_string(),
+            attribute: attribute.to_string(),
+            reason: reason.to_string(),
+        };
+        if let Some(reason) = check_name(attribute) {
+            return Err(invalid_attribute(reason));
+        }
+        if !names.insert(attribute) {
+            return Err(invalid_attribute("the attribute is listed several times"));
+        }
+        properties.push((attribute, encryption_hint));
+    }
+
+    Ok(DimensionBuilder::new(axis, properties, hierarchical))
+}
+
+/// Builds the policy described by the given JSON specification.
+///
+/// Validation errors name the offending axis or attribute.
+pub fn policy_from_json_spec(spec: &[u8]) -> Result<Policy, PolicySpecError> {
+    let spec = serde_json::from_slice::<Value>(spec)
+        .map_err(|e| PolicySpecError::InvalidSpecification(e.to_string()))?;
+    let axes = spec.as_object().ok_or_else(|| {
+        PolicySpecError::InvalidSpecification(
+            "the specification should be a JSON object mapping axes to their attributes"
+                .to_string(),
+        )
+    })?;
+    if axes.is_empty() {
+        return Err(PolicySpecError::InvalidSpecification(
+            "the specification has no axis".to_string(),
+        ));
+    }
+
+    let mut policy = Policy::new();
+    for (axis_spec, attributes) in axes {
+        policy.add_dimension(dimension_from_spec(axis_spec, attributes)?)?;
+    }
+    Ok(policy)
+}
+
+#[cfg(test)]
+mod tests {
+    use cosmian_cover_crypt::abe_policy::Attribute;
+
+    use super::*;
+
+    #[test]
+    fn test_policy_from_json_spec() -> Result<(), PolicySpecError> {
+        let policy = policy_from_json_spec(
+            br#"{
+                "Security Level::<": ["Protected", "Confidential", "Top Secret::+"],
+                "Department": ["R&D", "HR", "MKG", "FIN"]
+            }"#,
+        )?;
+        let attributes = policy.attributes();
+        assert_eq!(attributes.len(), 7);
+        assert!(attributes.contains(&Attribute::new("Security Level", "Top Secret")));
+        assert!(attributes.contains(&Attribute::new("Department", "R&D")));
+        Ok(())
+    }
+
+    #[test]
+    fn test_invalid_json_spec() {
+        for (spec, expected) in [
+            (r#"["Department"]"#, "should be a JSON object"),
+            ("{}", "has no axis"),
+            (
+                r#"{"Department": []}"#,
+                "axis `Department`: the axis has no attribute",
+            ),
+            (r#"{"Department": "HR"}"#, "axis `Department`"),
+            (r#"{"Dep::artment": ["HR"]}"#, "axis `Dep::artment`"),
+            (
+                r#"{"Department": ["HR", 1]}"#,
+                "the attribute 1 is not a string",
+            ),
+            (
+                r#"{"Department": ["HR", "HR::+"]}"#,
+                "attribute `HR` of axis `Department`: the attribute is listed several times",
+            ),
+            (
+                r#"{"Department::<": ["", "HR"]}"#,
+                "attribute `` of axis `Department`",
+            ),
+        ] {
+            let err = policy_from_json_spec(spec.as_bytes())
+                .unwrap_err()
+                .to_string();
+            assert!(
+                err.contains(expected),
+                "`{err}` should contain `{expected}`"
+            );
+        }
+    }
+}
@@ -0,0 +1,154 @@
+//! Post-quantum protection of selected records.
+//!
+//! Covercrypt encapsulates the key of a ciphertext once for each partition
+//! targeted by its access policy, using a hybridized (post-quantum)
+//! encapsulation only for the partitions of the attributes created with a
+//! hybridized encryption hint: the master public key holds no post-quantum
+//! key for the other partitions, hence no encryption call can force it.
+//! Moreover, any classic encapsulation of a ciphertext is enough to recover
+//! its key.
+//!
+//! Encryption calls therefore cannot force a post-quantum encapsulation, and
+//! no per-call override is provided: restricting the access policy to its
+//! hybridized coordinates would silently narrow the set of users able to
+//! decrypt. Records requiring post-quantum protection under an otherwise
+//! classic policy are instead encrypted under hybridized attributes, e.g. a
+//! `Department::Legal` attribute added with a hybridized encryption hint, or
+//! `Top Secret::+` in a [`policy_spec`](crate::policy_spec).
+//! [`is_post_quantum`] checks that all the encapsulations of an access policy
+//! are hybridized, e.g. to refuse encrypting such records under any other
+//! access policy.
+
+use cosmian_cover_crypt::{
+    abe_policy::{AccessPolicy, Attribute, AttributeStatus, EncryptionHint, Policy},
+    Error,
+};
+
+use crate::access_audit::{coordinate_partition, coordinates};
+
+/// Returns the attributes of the given policy created with a hybridized
+/// encryption hint.
+pub fn hybridized_attributes(policy: &Policy) -> Result<Vec<Attribute>, Error> {
+    let mut attributes = Vec::new();
+    for attribute in policy.attributes() {
+        if policy.get_attribute_hybridization_hint(&attribute)? == EncryptionHint::Hybridized {
+            attributes.push(attribute);
+        }
+    }
+    Ok(attributes)
+}
+
+/// Returns the coordinates targeted by the given access policy which use a
+/// hybridized encapsulation, each given as the list of its attributes, one
+/// per axis sorted by name.
+///
+/// The coordinates which can no longer be encrypted for, such as the ones of
+/// disabled attributes, are not listed.
+pub fn hybridized_coordinates(
+    policy: &Policy,
+    access_policy: &AccessPolicy,
+) -> Result<Vec<Vec<Attribute>>, Error> {
+    let targeted_partitions = policy.access_policy_to_partitions(access_policy, false)?;
+    let partitions = policy.generate_all_partitions()?;
+    let mut hybridized_coordinates = Vec::new();
+    for coordinate in coordinates(policy) {
+        let partition = coordinate_partition(policy, &coordinate)?;
+        if targeted_partitions.contains(&partition)
+            && partitions.get(&partition)
+                == Some(&(EncryptionHint::Hybridized, AttributeStatus::EncryptDecrypt))
+        {
+            hybridized_coordinates.push(coordinate);
+        }
+    }
+    Ok(hybridized_coordinates)
+}
+
+/// Returns `true` if all the encapsulations of the ciphertexts encrypted
+/// under the given access policy are hybridized, i.e. if each coordinate it
+/// targets uses a hybridized encapsulation.
+///
+/// Returns `false` if the given access policy targets no coordinate which can
+/// still be encrypted for.
+pub fn is_post_quantum(policy: &Policy, access_policy: &AccessPolicy) -> Result<bool, Error> {
+    let targeted_partitions = policy.access_policy_to_partitions(access_policy, false)?;
+    let mut is_targeted = false;
+    for (partition, (hint, status)) in policy.generate_all_partitions()? {
+        if status == AttributeStatus::EncryptDecrypt && targeted_partitions.contains(&partition) {
+            if hint != EncryptionHint::Hybridized {
+                return Ok(false);
+            }
+            is_targeted = true;
+        }
+    }
+    Ok(is_targeted)
+}
+
+#[cfg(test)]
+mod tests {
+    use cosmian_cover_crypt::{abe_policy::DimensionBuilder, Covercrypt, EncryptedHeader};
+    use cosmian_crypto_core::bytes_ser_de::Serializable;
+
+    use super::*;
+    use crate::header_info::read_header_info;
+
+    #[test]
+    fn test_post_quantum_access_policies() -> Result<(), Error> {
+        let mut policy = Policy::new();
+        policy.add_dimension(DimensionBuilder::new(
+            "Security Level",
+            vec![
+                ("Protected", EncryptionHint::Classic),
+                ("Confidential", EncryptionHint::Classic),
+            ],
+            true,
+        ))?;
+        policy.add_dimension(DimensionBuilder::new(
+            "Department",
+            vec![
+                ("HR", EncryptionHint::Classic),
+                ("FIN", EncryptionHint::Classic),
+            ],
+            false,
+        ))?;
+        let access_policy = AccessPolicy::from_boolean_expression("Security Level::Confidential")?;
+        assert!(hybridized_attributes(&policy)?.is_empty());
+        assert!(hybridized_coordinates(&policy, &access_policy)?.is_empty());
+        assert!(!is_post_quantum(&policy, &access_policy)?);
+
+        // Add a hybridized department for the high-sensitivity records.
+        let legal = Attribute::new("Department", "Legal");
+        policy.add_attribute(legal.clone(), EncryptionHint::Hybridized)?;
+        assert_eq!(hybridized_attributes(&policy)?, vec![legal.clone()]);
+        assert_eq!(
+            hybridized_coordinates(&policy, &access_policy)?,
+            vec![vec![
+                legal.clone(),
+                Attribute::new("Security Level", "Confidential")
+            ]]
+        );
+        // The other departments still use a classic encapsulation.
+        assert!(!is_post_quantum(&policy, &access_policy)?);
+        let legal_access_policy = AccessPolicy::from_boolean_expression("Department::Legal")?;
+        assert!(is_post_quantum(&policy, &legal_access_policy)?);
+
+        let cover_crypt = Covercrypt::default();
+        let (_, mpk) = cover_crypt.generate_master_keys(&policy)?;
+        let (_, encrypted_header) = EncryptedHeader::generate(
+            &cover_crypt,
+            &policy,
+            &mpk,
+            &legal_access_policy,
+            None,
+            None,
+        )?;
+        let header_info = read_header_info(&encrypted_header.serialize()?)?;
+        assert_eq!(header_info.n_partitions, 2);
+        assert_eq!(header_info.n_hybridized_partitions, 2);
+
+        // Disabled attributes cannot be encrypted for anymore.
+        policy.disable_attribute(&legal)?;
+        assert!(hybridized_coordinates(&policy, &access_policy)?.is_empty());
+        assert!(!is_post_quantum(&policy, &legal_access_policy)?);
+        Ok(())
+    }
+}
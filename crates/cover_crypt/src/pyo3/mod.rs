@@ -2,8 +2,9 @@ use pyo3::{pymodule, types::PyModule, PyResult, Python};
 
 /// Implements the basic functionalities of a key in python.
 ///
-/// - implements `deep_copy`
+/// - implements `deep_copy`, `__copy__` and `__deepcopy__`
 /// - converts to and from `PyBytes`
+/// - implements the pickle protocol
 ///
 /// # Parameters
 ///
@@ -16,22 +17,57 @@ macro_rules! impl_key_byte {
             pub fn to_bytes(&self, py: Python) -> PyResult<Py<PyBytes>> {
                 Ok(PyBytes::new(
                     py,
-                    &self
-                        .0
-                        .serialize()
+                    &crate::key_version::serialize_versioned(&self.0)
                         .map_err(|e| PyTypeError::new_err(e.to_string()))?,
                 )
                 .into())
             }
 
-            /// Reads key from bytes
+            /// Reads key from bytes, serialized in the current format or in any
+            /// older one
             #[staticmethod]
             pub fn from_bytes(key_bytes: &[u8]) -> PyResult<Self> {
-                match <$rust_type>::deserialize(key_bytes) {
+                match crate::key_version::try_deserialize_any_version::<$rust_type>(key_bytes) {
                     Ok(key) => Ok(Self(key)),
                     Err(e) => Err(PyTypeError::new_err(e.to_string())),
                 }
             }
+
+            /// Performs deep copy of the key.
+            pub fn deep_copy(&self) -> PyResult<Self> {
+                let bytes = crate::key_version::serialize_versioned(&self.0)
+                    .map_err(|e| PyTypeError::new_err(e.to_string()))?;
+                Self::from_bytes(&bytes)
+            }
+
+            pub fn __copy__(&self) -> PyResult<Self> {
+                self.deep_copy()
+            }
+
+            pub fn __deepcopy__(&self, _memo: &PyAny) -> PyResult<Self> {
+                self.deep_copy()
+            }
+
+            /// Returns the serialized key as pickled state.
+            pub fn __getstate__(&self, py: Python) -> PyResult<Py<PyBytes>> {
+                self.to_bytes(py)
+            }
+
+            /// Restores the key from the given pickled state.
+            pub fn __setstate__(&mut self, state: &[u8]) -> PyResult<()> {
+                *self = Self::from_bytes(state)?;
+                Ok(())
+            }
+
+            /// Keys cannot be instantiated empty: unpickling calls
+            /// `from_bytes` on the pickled state instead.
+            pub fn __reduce__(
+                slf: &PyCell<Self>,
+                py: Python,
+            ) -> PyResult<(PyObject, (Py<PyBytes>,))> {
+                let from_bytes = slf.get_type().getattr("from_bytes")?.into();
+                Ok((from_bytes, (slf.borrow().__getstate__(py)?,)))
+            }
         }
     };
 }
@@ -43,9 +79,11 @@ macro_rules! pyo3_unwrap {
 }
 
 mod py_abe_policy;
+mod py_column;
 mod py_cover_crypt;
 
-use py_abe_policy::{Attribute, Policy, PolicyAxis};
+use py_abe_policy::{Attribute, Policy, PolicyAxis, PolicyMigrationReport};
+use py_column::{ColumnDecryptor, ColumnEncryptor};
 use py_cover_crypt::{CoverCrypt, MasterPublicKey, MasterSecretKey, SymmetricKey, UserSecretKey};
 
 /// A Python module implemented in Rust.
@@ -54,10 +92,15 @@ fn cloudproof_cover_crypt(_py: Python, m: &PyModule) -> PyResult<()> {
     m.add_class::<Attribute>()?;
     m.add_class::<PolicyAxis>()?;
     m.add_class::<Policy>()?;
+    m.add_class::<PolicyMigrationReport>()?;
     m.add_class::<CoverCrypt>()?;
     m.add_class::<SymmetricKey>()?;
     m.add_class::<MasterSecretKey>()?;
     m.add_class::<MasterPublicKey>()?;
     m.add_class::<UserSecretKey>()?;
+    m.add_class::<ColumnEncryptor>()?;
+    m.add_class::<ColumnDecryptor>()?;
+    m.add("DEM_NONCE_LENGTH", crate::dem::DEM_NONCE_LENGTH)?;
+    m.add("DEM_TAG_LENGTH", crate::dem::DEM_TAG_LENGTH)?;
     Ok(())
 }
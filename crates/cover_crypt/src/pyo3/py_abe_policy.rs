@@ -1,5 +1,9 @@
-use cosmian_cover_crypt::abe_policy::{
-    Attribute as AttributeRust, DimensionBuilder, EncryptionHint, Policy as PolicyRust,
+use cosmian_cover_crypt::{
+    abe_policy::{
+        AccessPolicy, Attribute as AttributeRust, DimensionBuilder, EncryptionHint,
+        Policy as PolicyRust,
+    },
+    Error,
 };
 use pyo3::{
     exceptions::{PyException, PyTypeError, PyValueError},
@@ -7,6 +11,12 @@ use pyo3::{
     types::{PyBytes, PyList},
 };
 
+use crate::{
+    policy_migration::PolicyMigrationReport as PolicyMigrationReportRust,
+    policy_spec::policy_from_json_spec,
+    post_quantum::{hybridized_attributes, is_post_quantum},
+};
+
 /// An attribute in a policy group is characterized by the axis policy name
 /// and its unique name within this axis.
 ///
@@ -15,7 +25,7 @@ use pyo3::{
 ///     name (str): unique attribute name within this axis
 #[pyclass]
 #[derive(Clone)]
-pub struct Attribute(AttributeRust);
+pub struct Attribute(pub(super) AttributeRust);
 
 #[pymethods]
 impl Attribute {
@@ -165,11 +175,66 @@ impl PolicyAxis {
     }
 }
 
+/// Coordinates removed and added by a policy edit.
+///
+/// Once the master keys are updated, the ciphertexts encrypted for the removed
+/// coordinates become unreadable, and the user keys must be refreshed to
+/// decrypt the ciphertexts encrypted for the added ones.
+#[pyclass]
+pub struct PolicyMigrationReport(PolicyMigrationReportRust);
+
+#[pymethods]
+impl PolicyMigrationReport {
+    /// Returns the coordinates removed by the edit, as boolean expressions.
+    pub fn removed_coordinates(&self) -> Vec<String> {
+        self.0.removed_coordinates.clone()
+    }
+
+    /// Returns the coordinates added by the edit, as boolean expressions.
+    pub fn added_coordinates(&self) -> Vec<String> {
+        self.0.added_coordinates.clone()
+    }
+
+    /// Whether some ciphertexts become unreadable once the master keys are
+    /// updated.
+    pub fn has_unreadable_ciphertexts(&self) -> bool {
+        self.0.has_unreadable_ciphertexts()
+    }
+
+    /// Whether user keys must be refreshed after updating the master keys.
+    pub fn requires_user_key_refresh(&self) -> bool {
+        self.0.requires_user_key_refresh()
+    }
+
+    /// Formats the report to JSON.
+    pub fn to_json(&self) -> String {
+        self.0.to_json().to_string()
+    }
+
+    fn __repr__(&self) -> String {
+        self.to_json()
+    }
+}
+
 /// A policy is a set of policy axes. A fixed number of attribute creations
 /// (revocations + additions) is allowed.
 #[pyclass]
 pub struct Policy(pub(super) PolicyRust);
 
+impl Policy {
+    /// Applies the given edit to this policy and returns its migration report.
+    fn edit(
+        &mut self,
+        edit: impl FnOnce(&mut PolicyRust) -> Result<(), Error>,
+    ) -> PyResult<PolicyMigrationReport> {
+        let previous_policy = self.0.clone();
+        edit(&mut self.0).map_err(|e| PyException::new_err(e.to_string()))?;
+        PolicyMigrationReportRust::new(&previous_policy, &self.0)
+            .map(PolicyMigrationReport)
+            .map_err(|e| PyException::new_err(e.to_string()))
+    }
+}
+
 #[pymethods]
 impl Policy {
     /// Generates a new policy object with the given number of attribute
@@ -181,49 +246,45 @@ impl Policy {
     }
 
     /// Adds the given policy axis to the policy.
-    pub fn add_axis(&mut self, axis: &PolicyAxis) -> PyResult<()> {
-        self.0
-            .add_dimension(axis.0.clone())
-            .map_err(|e| PyException::new_err(e.to_string()))
+    pub fn add_axis(&mut self, axis: &PolicyAxis) -> PyResult<PolicyMigrationReport> {
+        self.edit(|policy| policy.add_dimension(axis.0.clone()))
     }
 
     /// Removes the given axis from the policy.
-    pub fn remove_axis(&mut self, axis_name: &str) -> PyResult<()> {
-        self.0
-            .remove_dimension(axis_name)
-            .map_err(|e| PyException::new_err(e.to_string()))
+    pub fn remove_axis(&mut self, axis_name: &str) -> PyResult<PolicyMigrationReport> {
+        self.edit(|policy| policy.remove_dimension(axis_name))
     }
 
     /// Adds the given attribute to the policy.
-    pub fn add_attribute(&mut self, attribute: Attribute, is_hybridized: bool) -> PyResult<()> {
-        self.0
-            .add_attribute(attribute.0, EncryptionHint::new(is_hybridized))
-            .map_err(|e| PyException::new_err(e.to_string()))
+    pub fn add_attribute(
+        &mut self,
+        attribute: Attribute,
+        is_hybridized: bool,
+    ) -> PyResult<PolicyMigrationReport> {
+        self.edit(|policy| policy.add_attribute(attribute.0, EncryptionHint::new(is_hybridized)))
     }
 
     /// Removes the given attribute from the policy
     /// Encrypting and decrypting for this attribute will no longer be possible
     /// once the keys are updated.
-    pub fn remove_attribute(&mut self, attribute: &Attribute) -> PyResult<()> {
-        self.0
-            .remove_attribute(&attribute.0)
-            .map_err(|e| PyException::new_err(e.to_string()))
+    pub fn remove_attribute(&mut self, attribute: &Attribute) -> PyResult<PolicyMigrationReport> {
+        self.edit(|policy| policy.remove_attribute(&attribute.0))
     }
 
     /// Marks an attribute as read only.
     /// The corresponding attribute key will be removed from the public key.
     /// But the decryption key will be kept to allow reading old ciphertext.
-    pub fn disable_attribute(&mut self, attribute: &Attribute) -> PyResult<()> {
-        self.0
-            .disable_attribute(&attribute.0)
-            .map_err(|e| PyException::new_err(e.to_string()))
+    pub fn disable_attribute(&mut self, attribute: &Attribute) -> PyResult<PolicyMigrationReport> {
+        self.edit(|policy| policy.disable_attribute(&attribute.0))
     }
 
     /// Changes the name of an attribute.
-    pub fn rename_attribute(&mut self, attribute: &Attribute, new_name: String) -> PyResult<()> {
-        self.0
-            .rename_attribute(&attribute.0, new_name)
-            .map_err(|e| PyException::new_err(e.to_string()))
+    pub fn rename_attribute(
+        &mut self,
+        attribute: &Attribute,
+        new_name: String,
+    ) -> PyResult<PolicyMigrationReport> {
+        self.edit(|policy| policy.rename_attribute(&attribute.0, new_name))
     }
 
     /// Returns the list of Attributes of this Policy.
@@ -231,6 +292,22 @@ impl Policy {
         self.0.attributes().into_iter().map(Attribute).collect()
     }
 
+    /// Returns the list of Attributes of this Policy created with a hybridized
+    /// encryption hint.
+    pub fn hybridized_attributes(&self) -> PyResult<Vec<Attribute>> {
+        hybridized_attributes(&self.0)
+            .map(|attributes| attributes.into_iter().map(Attribute).collect())
+            .map_err(|e| PyException::new_err(e.to_string()))
+    }
+
+    /// Checks whether all the encapsulations of the ciphertexts encrypted
+    /// under the given access policy are hybridized (post-quantum).
+    pub fn is_post_quantum(&self, access_policy_str: &str) -> PyResult<bool> {
+        let access_policy = AccessPolicy::from_boolean_expression(access_policy_str)
+            .map_err(|e| PyTypeError::new_err(e.to_string()))?;
+        is_post_quantum(&self.0, &access_policy).map_err(|e| PyException::new_err(e.to_string()))
+    }
+
     /// Retrieves the current value of an attribute.
     pub fn attribute_current_value(&self, attribute: &Attribute) -> PyResult<u32> {
         self.0
@@ -253,6 +330,14 @@ impl Policy {
             .map_err(|e| PyTypeError::new_err(format!("Error deserializing attributes: {e}")))
     }
 
+    /// Builds the policy described by the given JSON specification.
+    #[staticmethod]
+    pub fn from_json_spec(spec: &str) -> PyResult<Self> {
+        policy_from_json_spec(spec.as_bytes())
+            .map(Self)
+            .map_err(|e| PyValueError::new_err(e.to_string()))
+    }
+
     /// Returns a string representation of the policy.
     fn __repr__(&self) -> String {
         format!("{}", &self.0)
@@ -262,4 +347,23 @@ impl Policy {
     pub fn deep_copy(&self) -> Self {
         Self(self.0.clone())
     }
+
+    pub fn __copy__(&self) -> Self {
+        self.deep_copy()
+    }
+
+    pub fn __deepcopy__(&self, _memo: &PyAny) -> Self {
+        self.deep_copy()
+    }
+
+    /// Returns the JSON serialized policy as pickled state.
+    pub fn __getstate__(&self, py: Python) -> PyResult<Py<PyBytes>> {
+        self.to_bytes(py)
+    }
+
+    /// Restores the policy from the given pickled state.
+    pub fn __setstate__(&mut self, state: &PyBytes) -> PyResult<()> {
+        *self = Self::from_bytes(state)?;
+        Ok(())
+    }
 }
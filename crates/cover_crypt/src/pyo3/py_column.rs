@@ -0,0 +1,247 @@
+use cosmian_cover_crypt::{abe_policy::AccessPolicy, Covercrypt, EncryptedHeader};
+use cosmian_crypto_core::{bytes_ser_de::Serializable, Aes256Gcm, SymmetricKey};
+use pyo3::{
+    exceptions::PyTypeError,
+    prelude::*,
+    types::{PyBytes, PyFloat, PyString},
+};
+
+use crate::{
+    column,
+    pyo3::{
+        py_abe_policy::Policy,
+        py_cover_crypt::{MasterPublicKey, UserSecretKey},
+    },
+};
+
+/// Reads the cells of the given column, which can be any iterable such as a
+/// list or a pandas `Series`.
+///
+/// `None` and `NaN` (the missing values of pandas) are read as null cells,
+/// strings are encoded in UTF-8.
+fn read_cells(cells: &PyAny) -> PyResult<Vec<Option<Vec<u8>>>> {
+    cells
+        .iter()?
+        .map(|cell| {
+            let cell = cell?;
+            if cell.is_none() {
+                Ok(None)
+            } else if let Ok(bytes) = cell.downcast::<PyBytes>() {
+                Ok(Some(bytes.as_bytes().to_vec()))
+            } else if let Ok(string) = cell.downcast::<PyString>() {
+                Ok(Some(string.to_str()?.as_bytes().to_vec()))
+            } else if cell
+                .downcast::<PyFloat>()
+                .is_ok_and(|float| float.value().is_nan())
+            {
+                Ok(None)
+            } else {
+                Err(PyTypeError::new_err(format!(
+                    "cells should be bytes, str or None, got {}",
+                    cell.get_type().name()?
+                )))
+            }
+        })
+        .collect()
+}
+
+/// Reads the optional authentication data of the cells, one per cell.
+fn read_authentication_data(authentication_data: Option<&PyAny>) -> PyResult<Vec<Vec<u8>>> {
+    authentication_data.map_or_else(
+        || Ok(Vec::new()),
+        |authentication_data| {
+            read_cells(authentication_data)?
+                .into_iter()
+                .map(|data| {
+                    data.ok_or_else(|| {
+                        PyTypeError::new_err("authentication data should not be null")
+                    })
+                })
+                .collect()
+        },
+    )
+}
+
+fn to_py_cells(py: Python, cells: Vec<Option<Vec<u8>>>) -> Vec<Option<Py<PyBytes>>> {
+    cells
+        .into_iter()
+        .map(|cell| cell.map(|cell| PyBytes::new(py, &cell).into()))
+        .collect()
+}
+
+/// Encrypts the cells of a column under a single header.
+///
+/// The header is generated once for the given access policy: each cell is
+/// then encrypted by the DEM, in parallel and releasing the GIL. The encrypted
+/// header should be stored along with the column, e.g. in its metadata.
+#[pyclass]
+pub struct ColumnEncryptor {
+    cover_crypt: Covercrypt,
+    symmetric_key: SymmetricKey<{ Aes256Gcm::KEY_LENGTH }>,
+    encrypted_header: Vec<u8>,
+}
+
+#[pymethods]
+impl ColumnEncryptor {
+    /// Generates the header of the column.
+    ///
+    /// Parameters:
+    ///
+    /// - `policy`              : global policy
+    /// - `access_policy_str`   : access policy of the column
+    /// - `public_key`          : CoverCrypt public key
+    /// - `header_metadata`     : additional data to encrypt with the header
+    /// - `authentication_data` : authentication data of the header
+    #[new]
+    fn new(
+        policy: &Policy,
+        access_policy_str: &str,
+        public_key: &MasterPublicKey,
+        header_metadata: Option<Vec<u8>>,
+        authentication_data: Option<Vec<u8>>,
+    ) -> PyResult<Self> {
+        let access_policy = pyo3_unwrap!(
+            AccessPolicy::from_boolean_expression(access_policy_str),
+            "error parsing access policy"
+        );
+        let cover_crypt = Covercrypt::default();
+        let (symmetric_key, encrypted_header) = pyo3_unwrap!(
+            EncryptedHeader::generate(
+                &cover_crypt,
+                &policy.0,
+                &public_key.0,
+                &access_policy,
+                header_metadata.as_deref(),
+                authentication_data.as_deref(),
+            ),
+            "error encrypting CoverCrypt header"
+        );
+        let encrypted_header = pyo3_unwrap!(
+            encrypted_header.serialize(),
+            "error serializing CoverCrypt header"
+        );
+        Ok(Self {
+            cover_crypt,
+            symmetric_key,
+            encrypted_header: encrypted_header.to_vec(),
+        })
+    }
+
+    /// Encrypted header of the column, needed to decrypt its cells.
+    #[getter]
+    pub fn encrypted_header(&self, py: Python) -> Py<PyBytes> {
+        PyBytes::new(py, &self.encrypted_header).into()
+    }
+
+    /// Encrypts the given cells.
+    ///
+    /// Parameters:
+    ///
+    /// - `cells`               : iterable of bytes or str, e.g. a pandas
+    ///   `Series`; `None` and `NaN` cells are kept null
+    /// - `authentication_data` : authentication data of each cell, e.g. the
+    ///   record IDs binding each cell to its record
+    ///
+    /// Returns: list of the encrypted cells, in the order of the cells
+    pub fn encrypt(
+        &self,
+        cells: &PyAny,
+        authentication_data: Option<&PyAny>,
+        py: Python,
+    ) -> PyResult<Vec<Option<Py<PyBytes>>>> {
+        let cells = read_cells(cells)?;
+        let authentication_data = read_authentication_data(authentication_data)?;
+        let encrypted_cells = pyo3_unwrap!(
+            py.allow_threads(|| {
+                column::encrypt_cells(
+                    &self.cover_crypt,
+                    &self.symmetric_key,
+                    &cells,
+                    &authentication_data,
+                )
+            }),
+            "error encrypting cells"
+        );
+        Ok(to_py_cells(py, encrypted_cells))
+    }
+}
+
+/// Decrypts the cells of a column encrypted by a `ColumnEncryptor`.
+///
+/// The header is decrypted once: each cell is then decrypted by the DEM, in
+/// parallel and releasing the GIL.
+#[pyclass]
+pub struct ColumnDecryptor {
+    cover_crypt: Covercrypt,
+    symmetric_key: SymmetricKey<{ Aes256Gcm::KEY_LENGTH }>,
+    header_metadata: Vec<u8>,
+}
+
+#[pymethods]
+impl ColumnDecryptor {
+    /// Decrypts the header of the column.
+    ///
+    /// Parameters:
+    ///
+    /// - `usk`                 : user secret key
+    /// - `encrypted_header`    : encrypted header of the column
+    /// - `authentication_data` : authentication data of the header
+    #[new]
+    fn new(
+        usk: &UserSecretKey,
+        encrypted_header: Vec<u8>,
+        authentication_data: Option<Vec<u8>>,
+    ) -> PyResult<Self> {
+        let cover_crypt = Covercrypt::default();
+        let encrypted_header = pyo3_unwrap!(
+            EncryptedHeader::deserialize(&encrypted_header),
+            "error deserializing encrypted header"
+        );
+        let cleartext_header = pyo3_unwrap!(
+            encrypted_header.decrypt(&cover_crypt, &usk.0, authentication_data.as_deref()),
+            "error decrypting header"
+        );
+        Ok(Self {
+            cover_crypt,
+            symmetric_key: cleartext_header.symmetric_key,
+            header_metadata: cleartext_header.metadata.unwrap_or_default(),
+        })
+    }
+
+    /// Metadata encrypted with the header of the column.
+    #[getter]
+    pub fn header_metadata(&self, py: Python) -> Py<PyBytes> {
+        PyBytes::new(py, &self.header_metadata).into()
+    }
+
+    /// Decrypts the given cells.
+    ///
+    /// Parameters:
+    ///
+    /// - `cells`               : iterable of encrypted cells, e.g. a pandas
+    ///   `Series`; `None` and `NaN` cells are kept null
+    /// - `authentication_data` : authentication data given upon encryption
+    ///
+    /// Returns: list of the decrypted cells, in the order of the cells
+    pub fn decrypt(
+        &self,
+        cells: &PyAny,
+        authentication_data: Option<&PyAny>,
+        py: Python,
+    ) -> PyResult<Vec<Option<Py<PyBytes>>>> {
+        let cells = read_cells(cells)?;
+        let authentication_data = read_authentication_data(authentication_data)?;
+        let decrypted_cells = pyo3_unwrap!(
+            py.allow_threads(|| {
+                column::decrypt_cells(
+                    &self.cover_crypt,
+                    &self.symmetric_key,
+                    &cells,
+                    &authentication_data,
+                )
+            }),
+            "error decrypting cells"
+        );
+        Ok(to_py_cells(py, decrypted_cells))
+    }
+}
@@ -3,12 +3,16 @@ use cosmian_cover_crypt::{
     MasterSecretKey as MasterSecretKeyRust, UserSecretKey as UserSecretKeyRust,
 };
 use cosmian_crypto_core::{
-    bytes_ser_de::{Deserializer, Serializable, Serializer},
+    bytes_ser_de::{Serializable, Serializer},
     Aes256Gcm, FixedSizeCBytes, SymmetricKey as SymmetricKeyRust,
 };
 use pyo3::{exceptions::PyTypeError, prelude::*, types::PyBytes};
 
-use crate::pyo3::py_abe_policy::Policy;
+use crate::{
+    access_audit, bulk, cleartext_metadata, dem, header_padding, multi_key,
+    pyo3::py_abe_policy::{Attribute, Policy},
+    rekey, rotation,
+};
 
 // Pyo3 doc on classes
 // https://pyo3.rs/v0.16.2/class.html
@@ -19,12 +23,12 @@ pub struct MasterSecretKey(MasterSecretKeyRust);
 impl_key_byte!(MasterSecretKey, MasterSecretKeyRust);
 
 #[pyclass]
-pub struct MasterPublicKey(MasterPublicKeyRust);
+pub struct MasterPublicKey(pub(super) MasterPublicKeyRust);
 
 impl_key_byte!(MasterPublicKey, MasterPublicKeyRust);
 
 #[pyclass]
-pub struct UserSecretKey(UserSecretKeyRust);
+pub struct UserSecretKey(pub(super) UserSecretKeyRust);
 
 impl_key_byte!(UserSecretKey, UserSecretKeyRust);
 
@@ -122,6 +126,25 @@ impl CoverCrypt {
         Ok(())
     }
 
+    /// Generate new keys for all the partitions of the master keys, without
+    /// changing the policy (periodic rotation). Unlike `rekey_master_keys`
+    /// (policy rotation), all the user keys will need to be refreshed.
+    ///  - `policy`         : global policy
+    ///  - `msk`            : master secret key
+    ///  - `mpk`            : master public key
+    pub fn rekey_all_master_keys(
+        &self,
+        policy: &Policy,
+        msk: &mut MasterSecretKey,
+        mpk: &mut MasterPublicKey,
+    ) -> PyResult<()> {
+        pyo3_unwrap!(
+            rekey::rekey_all_master_keys(&policy.0, &mut msk.0, &mut mpk.0),
+            "error rekeying all master keys"
+        );
+        Ok(())
+    }
+
     /// Removes old keys associated to the given master keys from the master
     /// keys. This will permanently remove access to old ciphers.
     ///  - `access_policy`  : describe the keys to prune
@@ -202,6 +225,29 @@ impl CoverCrypt {
         Ok(())
     }
 
+    /// Refreshes the user key according to the given master key, keeping at
+    /// most `keep_old_rotations` rotations older than the current one for each
+    /// partition.
+    ///
+    /// Parameters:
+    ///
+    /// - `usk`                 : the user key to refresh
+    /// - `msk`                 : master secret key
+    /// - `keep_old_rotations`  : number of old rotations to keep access to
+    pub fn refresh_user_secret_key_with_rotations(
+        &self,
+        usk: &mut UserSecretKey,
+        msk: &MasterSecretKey,
+        keep_old_rotations: usize,
+    ) -> PyResult<()> {
+        pyo3_unwrap!(
+            rotation::refresh_user_secret_key(&msk.0, &mut usk.0, keep_old_rotations),
+            "error refreshing user secret key"
+        );
+
+        Ok(())
+    }
+
     /// Encrypts data symmetrically in a block.
     ///
     /// Parameters:
@@ -255,6 +301,89 @@ impl CoverCrypt {
         Ok(PyBytes::new(py, &plaintext).into())
     }
 
+    /// Splits a symmetric ciphertext into its nonce, encrypted data and
+    /// authentication tag, e.g. to decrypt it using the `AESGCM` class of the
+    /// `cryptography` library:
+    ///
+    /// `AESGCM(key.to_bytes()).decrypt(nonce, encrypted_data + tag, aad)`
+    ///
+    /// Parameters:
+    ///
+    /// - `ciphertext`  : nonce || encrypted data || tag
+    ///
+    /// Returns: (nonce bytes, encrypted data bytes, tag bytes)
+    #[staticmethod]
+    pub fn split_symmetric_ciphertext(
+        ciphertext: Vec<u8>,
+        py: Python,
+    ) -> PyResult<(Py<PyBytes>, Py<PyBytes>, Py<PyBytes>)> {
+        let dem::DemParts {
+            nonce,
+            encrypted_data,
+            tag,
+        } = pyo3_unwrap!(
+            dem::split_dem_ciphertext(&ciphertext),
+            "error splitting symmetric ciphertext"
+        );
+        Ok((
+            PyBytes::new(py, nonce).into(),
+            PyBytes::new(py, encrypted_data).into(),
+            PyBytes::new(py, tag).into(),
+        ))
+    }
+
+    /// Concatenates a nonce, encrypted data and authentication tag into a
+    /// symmetric ciphertext, e.g. produced by the `AESGCM` class of the
+    /// `cryptography` library:
+    ///
+    /// `encrypted_data + tag = AESGCM(key.to_bytes()).encrypt(nonce, data,
+    /// aad)`
+    ///
+    /// Parameters:
+    ///
+    /// - `nonce`           : 12-byte nonce
+    /// - `encrypted_data`  : encrypted data
+    /// - `tag`             : 16-byte authentication tag
+    ///
+    /// Returns: ciphertext bytes
+    #[staticmethod]
+    pub fn join_symmetric_ciphertext(
+        nonce: Vec<u8>,
+        encrypted_data: Vec<u8>,
+        tag: Vec<u8>,
+        py: Python,
+    ) -> PyResult<Py<PyBytes>> {
+        let ciphertext = pyo3_unwrap!(
+            dem::join_dem_ciphertext(&nonce, &encrypted_data, &tag),
+            "error joining symmetric ciphertext"
+        );
+        Ok(PyBytes::new(py, &ciphertext).into())
+    }
+
+    /// Splits a hybrid ciphertext into the encrypted header and the symmetric
+    /// ciphertext, so that the latter can be decrypted by an existing DEM
+    /// implementation using the key returned by `decrypt_header()`.
+    ///
+    /// Parameters:
+    ///
+    /// - `encrypted_bytes` : encrypted header || symmetric ciphertext
+    ///
+    /// Returns: (encrypted header bytes, symmetric ciphertext bytes)
+    #[staticmethod]
+    pub fn split_hybrid_ciphertext(
+        encrypted_bytes: Vec<u8>,
+        py: Python,
+    ) -> PyResult<(Py<PyBytes>, Py<PyBytes>)> {
+        let (header, ciphertext) = pyo3_unwrap!(
+            dem::split_hybrid_ciphertext(&encrypted_bytes),
+            "error deserializing encrypted header"
+        );
+        Ok((
+            PyBytes::new(py, header).into(),
+            PyBytes::new(py, ciphertext).into(),
+        ))
+    }
+
     /// Generates an encrypted header. A header contains the following elements:
     ///
     /// - `encapsulation_size`  : the size of the symmetric key encapsulation
@@ -356,7 +485,11 @@ impl CoverCrypt {
     /// - `header_metadata`     : additional data to symmetrically encrypt in
     ///   the header
     /// - `authentication_data` : authentication data to use in symmetric
-    ///   encryptions
+    ///   encryptions, e.g. the record ID. It is not part of the ciphertext and
+    ///   should be given upon decryption.
+    /// - `header_bucket_size`  : size of the buckets the encrypted header is
+    ///   padded to, hiding the number of partitions of the access policy. It
+    ///   should be given upon decryption.
     ///
     /// Returns: ciphertext bytes
     #[allow(clippy::too_many_arguments)]
@@ -368,6 +501,7 @@ impl CoverCrypt {
         plaintext: Vec<u8>,
         header_metadata: Option<Vec<u8>>,
         authentication_data: Option<Vec<u8>>,
+        header_bucket_size: Option<usize>,
         py: Python,
     ) -> PyResult<Py<PyBytes>> {
         let access_policy = AccessPolicy::from_boolean_expression(access_policy_str)
@@ -391,10 +525,14 @@ impl CoverCrypt {
             "error encrypting plaintext"
         );
 
-        // Encrypted header and ciphertext are concatenated.
-        let mut ser = Serializer::with_capacity(encrypted_header.length() + ciphertext.len());
+        // Padded encrypted header and ciphertext are concatenated.
+        let header_bucket_size = header_bucket_size.unwrap_or_default();
+        let mut ser = Serializer::with_capacity(
+            header_padding::padded_header_length(encrypted_header.length(), header_bucket_size)
+                + ciphertext.len(),
+        );
         pyo3_unwrap!(
-            encrypted_header.write(&mut ser),
+            header_padding::write_padded_header(&mut ser, &encrypted_header, header_bucket_size),
             "error serializing CoverCrypt header"
         );
         pyo3_unwrap!(ser.write_array(&ciphertext), "error serializing ciphertext");
@@ -408,8 +546,9 @@ impl CoverCrypt {
     ///
     /// - `usk`                 : user secret key
     /// - `encrypted_bytes`     : encrypted header || symmetric ciphertext
-    /// - `authentication_data` : authentication data to use in symmetric
-    ///   decryptions
+    /// - `authentication_data` : authentication data given upon encryption, the
+    ///   decryption fails otherwise
+    /// - `header_bucket_size`  : header bucket size given upon encryption
     ///
     ///  Returns: (plaintext bytes, header metadata bytes)
     pub fn decrypt(
@@ -417,16 +556,17 @@ impl CoverCrypt {
         usk: &UserSecretKey,
         encrypted_bytes: Vec<u8>,
         authentication_data: Option<Vec<u8>>,
+        header_bucket_size: Option<usize>,
         py: Python,
     ) -> PyResult<(Py<PyBytes>, Py<PyBytes>)> {
-        let mut de = Deserializer::new(encrypted_bytes.as_slice());
-        let header = pyo3_unwrap!(
-            // this will read the exact header size
-            EncryptedHeader::read(&mut de),
+        // the padded header is followed by the symmetric ciphertext
+        let (header, ciphertext) = pyo3_unwrap!(
+            header_padding::read_padded_header(
+                &encrypted_bytes,
+                header_bucket_size.unwrap_or_default()
+            ),
             "error deserializing encrypted header"
         );
-        // the rest is the symmetric ciphertext
-        let ciphertext = de.finalize();
 
         let cleartext_header = pyo3_unwrap!(
             header.decrypt(&self.0, &usk.0, authentication_data.as_deref()),
@@ -436,7 +576,7 @@ impl CoverCrypt {
         let plaintext = pyo3_unwrap!(
             self.0.decrypt(
                 &cleartext_header.symmetric_key,
-                ciphertext.as_slice(),
+                ciphertext,
                 authentication_data.as_deref(),
             ),
             "error decrypting ciphertext"
@@ -447,4 +587,263 @@ impl CoverCrypt {
             PyBytes::new(py, &cleartext_header.metadata.unwrap_or_default()).into(),
         ))
     }
+
+    /// Hybrid encryption. Prepends the given cleartext metadata to the
+    /// encrypted header and the symmetric ciphertext.
+    ///
+    /// The cleartext metadata can be read without any key but is
+    /// authenticated upon decryption.
+    ///
+    /// Parameters:
+    ///
+    /// - `policy`              : global policy
+    /// - `access_policy_str`   : access policy
+    /// - `pk`                  : CoverCrypt public key
+    /// - `plaintext`           : plaintext to encrypt using the DEM
+    /// - `cleartext_metadata`  : metadata to authenticate but not encrypt
+    /// - `header_metadata`     : additional data to symmetrically encrypt in
+    ///   the header
+    /// - `authentication_data` : authentication data to use in symmetric
+    ///   encryptions
+    ///
+    /// Returns: ciphertext bytes
+    #[allow(clippy::too_many_arguments)]
+    pub fn encrypt_with_cleartext_metadata(
+        &self,
+        policy: &Policy,
+        access_policy_str: &str,
+        pk: &MasterPublicKey,
+        plaintext: Vec<u8>,
+        cleartext_metadata: Vec<u8>,
+        header_metadata: Option<Vec<u8>>,
+        authentication_data: Option<Vec<u8>>,
+        py: Python,
+    ) -> PyResult<Py<PyBytes>> {
+        let access_policy = AccessPolicy::from_boolean_expression(access_policy_str)
+            .map_err(|e| PyTypeError::new_err(format!("Access policy creation failed: {e}")))?;
+
+        let ciphertext = pyo3_unwrap!(
+            cleartext_metadata::encrypt_with_cleartext_metadata(
+                &self.0,
+                &policy.0,
+                &pk.0,
+                &access_policy,
+                &plaintext,
+                &cleartext_metadata,
+                header_metadata.as_deref(),
+                authentication_data.as_deref(),
+            ),
+            "error encrypting plaintext"
+        );
+
+        Ok(PyBytes::new(py, &ciphertext).into())
+    }
+
+    /// Reads the cleartext metadata of a ciphertext produced by
+    /// `encrypt_with_cleartext_metadata()` without any key.
+    ///
+    /// This metadata is only authenticated upon decryption.
+    ///
+    /// Parameters:
+    ///
+    /// - `encrypted_bytes`     : cleartext metadata || encrypted header ||
+    ///   symmetric ciphertext
+    ///
+    /// Returns: cleartext metadata bytes
+    #[staticmethod]
+    pub fn read_cleartext_metadata(encrypted_bytes: Vec<u8>, py: Python) -> PyResult<Py<PyBytes>> {
+        let metadata = pyo3_unwrap!(
+            cleartext_metadata::read_cleartext_metadata(&encrypted_bytes),
+            "error reading cleartext metadata"
+        );
+        Ok(PyBytes::new(py, &metadata).into())
+    }
+
+    /// Hybrid decryption of a ciphertext produced by
+    /// `encrypt_with_cleartext_metadata()`.
+    ///
+    /// Parameters:
+    ///
+    /// - `usk`                 : user secret key
+    /// - `encrypted_bytes`     : cleartext metadata || encrypted header ||
+    ///   symmetric ciphertext
+    /// - `authentication_data` : authentication data to use in symmetric
+    ///   decryptions
+    ///
+    ///  Returns: (plaintext bytes, header metadata bytes, cleartext metadata
+    /// bytes)
+    pub fn decrypt_with_cleartext_metadata(
+        &self,
+        usk: &UserSecretKey,
+        encrypted_bytes: Vec<u8>,
+        authentication_data: Option<Vec<u8>>,
+        py: Python,
+    ) -> PyResult<(Py<PyBytes>, Py<PyBytes>, Py<PyBytes>)> {
+        let res = pyo3_unwrap!(
+            cleartext_metadata::decrypt_with_cleartext_metadata(
+                &self.0,
+                &usk.0,
+                &encrypted_bytes,
+                authentication_data.as_deref(),
+            ),
+            "error decrypting ciphertext"
+        );
+
+        Ok((
+            PyBytes::new(py, &res.plaintext).into(),
+            PyBytes::new(py, &res.header_metadata.unwrap_or_default()).into(),
+            PyBytes::new(py, &res.cleartext_metadata).into(),
+        ))
+    }
+
+    /// Hybrid decryption using the first of the given user secret keys able
+    /// to decrypt the encrypted header.
+    ///
+    /// Parameters:
+    ///
+    /// - `usks`                : candidate user secret keys
+    /// - `encrypted_bytes`     : encrypted header || symmetric ciphertext
+    /// - `authentication_data` : authentication data to use in symmetric
+    ///   decryptions
+    ///
+    ///  Returns: (index of the key used, plaintext bytes, header metadata
+    /// bytes)
+    pub fn decrypt_with_keys(
+        &self,
+        usks: Vec<PyRef<UserSecretKey>>,
+        encrypted_bytes: Vec<u8>,
+        authentication_data: Option<Vec<u8>>,
+        py: Python,
+    ) -> PyResult<(usize, Py<PyBytes>, Py<PyBytes>)> {
+        let res = pyo3_unwrap!(
+            multi_key::decrypt_with_keys(
+                &self.0,
+                usks.iter().map(|usk| &usk.0),
+                &encrypted_bytes,
+                authentication_data.as_deref(),
+            ),
+            "error decrypting ciphertext"
+        );
+
+        Ok((
+            res.key_index,
+            PyBytes::new(py, &res.plaintext).into(),
+            PyBytes::new(py, &res.header_metadata.unwrap_or_default()).into(),
+        ))
+    }
+
+    /// Hybrid decryption of many ciphertexts using the same user secret key.
+    ///
+    /// The ciphertexts are decrypted in parallel, releasing the GIL.
+    ///
+    /// Parameters:
+    ///
+    /// - `usk`                 : user secret key
+    /// - `ciphertexts`         : encrypted headers || symmetric ciphertexts
+    /// - `authentication_data` : authentication data given upon encryption,
+    ///   one per ciphertext
+    ///
+    /// Returns: plaintexts, in the order of the ciphertexts
+    pub fn decrypt_bulk(
+        &self,
+        usk: &UserSecretKey,
+        ciphertexts: Vec<Vec<u8>>,
+        authentication_data: Option<Vec<Vec<u8>>>,
+        py: Python,
+    ) -> PyResult<Vec<Py<PyBytes>>> {
+        let authentication_data = authentication_data.unwrap_or_default();
+        let plaintexts = pyo3_unwrap!(
+            py.allow_threads(|| {
+                bulk::decrypt_bulk(&self.0, &usk.0, &ciphertexts, &authentication_data)
+            }),
+            "error decrypting ciphertexts"
+        );
+
+        Ok(plaintexts
+            .iter()
+            .map(|plaintext| PyBytes::new(py, plaintext).into())
+            .collect())
+    }
+
+    /// Lists the coordinates of the policy the given user secret key can
+    /// decrypt. No ciphertext is needed.
+    ///
+    /// Parameters:
+    ///
+    /// - `usk`     : user secret key to audit
+    /// - `mpk`     : master public key
+    /// - `policy`  : global policy
+    ///
+    /// Returns: list of coordinates, each given as the list of its attributes
+    pub fn user_key_coordinates(
+        &self,
+        usk: &UserSecretKey,
+        mpk: &MasterPublicKey,
+        policy: &Policy,
+    ) -> PyResult<Vec<Vec<Attribute>>> {
+        let coordinates = pyo3_unwrap!(
+            access_audit::user_key_coordinates(&policy.0, &mpk.0, &usk.0),
+            "error listing user secret key coordinates"
+        );
+        Ok(coordinates
+            .into_iter()
+            .map(|coordinate| coordinate.into_iter().map(Attribute).collect())
+            .collect())
+    }
+
+    /// Lists the number of rotations of each coordinate of the policy the
+    /// given user secret key can decrypt: the current one upon its last
+    /// refresh and the older ones kept.
+    ///
+    /// Parameters:
+    ///
+    /// - `usk`     : user secret key to audit
+    /// - `policy`  : global policy
+    ///
+    /// Returns: list of coordinates, each given as the list of its attributes,
+    /// along with their number of rotations
+    pub fn user_key_rotations(
+        &self,
+        usk: &UserSecretKey,
+        policy: &Policy,
+    ) -> PyResult<Vec<(Vec<Attribute>, usize)>> {
+        let rotations = pyo3_unwrap!(
+            rotation::user_key_rotations(&policy.0, &usk.0),
+            "error listing user secret key rotations"
+        );
+        Ok(rotations
+            .into_iter()
+            .map(|(coordinate, n_rotations)| {
+                (coordinate.into_iter().map(Attribute).collect(), n_rotations)
+            })
+            .collect())
+    }
+
+    /// Checks whether the given user secret key can decrypt the ciphertexts
+    /// encrypted under the given access policy. No ciphertext is needed.
+    ///
+    /// Parameters:
+    ///
+    /// - `usk`                     : user secret key to audit
+    /// - `mpk`                     : master public key
+    /// - `encryption_policy_str`   : encryption access policy
+    /// - `policy`                  : global policy
+    ///
+    /// Returns: bool
+    pub fn can_decrypt(
+        &self,
+        usk: &UserSecretKey,
+        mpk: &MasterPublicKey,
+        encryption_policy_str: &str,
+        policy: &Policy,
+    ) -> PyResult<bool> {
+        let encryption_policy = pyo3_unwrap!(
+            AccessPolicy::from_boolean_expression(encryption_policy_str),
+            "error parsing encryption policy"
+        );
+        Ok(pyo3_unwrap!(
+            access_audit::can_decrypt(&policy.0, &mpk.0, &usk.0, &encryption_policy),
+            "error checking user secret key access"
+        ))
+    }
 }
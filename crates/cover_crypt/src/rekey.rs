@@ -0,0 +1,93 @@
+//! Periodic rotation of all the master keys, e.g. for crypto-hygiene.
+//!
+//! Unlike a policy rotation, which rekeys the partitions targeted by an access
+//! policy (`rekey_master_keys`) or adds and removes partitions after a policy
+//! edit (`update_master_keys`), rekeying all the master keys keeps the policy
+//! unchanged and renews the keys of every partition:
+//!
+//! - the new master public key must be distributed, the ciphertexts it
+//!   produces cannot be decrypted by the user keys until they are refreshed;
+//! - every user secret key must then be refreshed using the new master secret
+//!   key, keeping the old accesses to decrypt the existing ciphertexts;
+//! - the old keys can eventually be pruned from the master secret key, once
+//!   the existing ciphertexts have been re-encrypted.
+
+use cosmian_cover_crypt::{
+    abe_policy::{AccessPolicy, Policy},
+    Covercrypt, Error, MasterPublicKey, MasterSecretKey,
+};
+
+/// Returns the access policy targeting all the partitions of the given policy.
+///
+/// Each partition holds exactly one attribute of each dimension: the
+/// disjunction of the attributes of a single dimension targets each partition
+/// once, whereas the disjunction of all the attributes would target them
+/// once per dimension, which `rekey_master_keys` rejects.
+pub fn all_partitions_access_policy(policy: &Policy) -> Result<AccessPolicy, Error> {
+    let attributes = policy.attributes();
+    let dimension = attributes
+        .iter()
+        .map(|attribute| &attribute.dimension)
+        .min()
+        .ok_or_else(|| Error::KeyError("the policy has no attribute".to_string()))?;
+    attributes
+        .iter()
+        .filter(|attribute| &attribute.dimension == dimension)
+        .cloned()
+        .map(AccessPolicy::Attr)
+        .reduce(|lhs, rhs| AccessPolicy::Or(Box::new(lhs), Box::new(rhs)))
+        .ok_or_else(|| Error::KeyError("the policy has no attribute".to_string()))
+}
+
+/// Rekeys all the partitions of the given master keys, without changing the
+/// policy.
+///
+/// The old keys are kept in the master secret key so that the user keys
+/// refreshed with `keep_old_accesses` can still decrypt the existing
+/// ciphertexts.
+pub fn rekey_all_master_keys(
+    policy: &Policy,
+    msk: &mut MasterSecretKey,
+    mpk: &mut MasterPublicKey,
+) -> Result<(), Error> {
+    let access_policy = all_partitions_access_policy(policy)?;
+    Covercrypt::default().rekey_master_keys(&access_policy, policy, msk, mpk)
+}
+
+#[cfg(test)]
+mod tests {
+    use cosmian_cover_crypt::{test_utils::policy, EncryptedHeader};
+
+    use super::*;
+
+    #[test]
+    fn test_rekey_all_master_keys() -> Result<(), Error> {
+        let policy = policy()?;
+        let cover_crypt = Covercrypt::default();
+        let (mut msk, mut mpk) = cover_crypt.generate_master_keys(&policy)?;
+        let access_policy =
+            AccessPolicy::from_boolean_expression("Department::FIN && Security Level::Low Secret")?;
+        let mut usk = cover_crypt.generate_user_secret_key(&msk, &access_policy, &policy)?;
+        let (_, old_header) =
+            EncryptedHeader::generate(&cover_crypt, &policy, &mpk, &access_policy, None, None)?;
+
+        rekey_all_master_keys(&policy, &mut msk, &mut mpk)?;
+        let (_, new_header) =
+            EncryptedHeader::generate(&cover_crypt, &policy, &mpk, &access_policy, None, None)?;
+        assert!(new_header.decrypt(&cover_crypt, &usk, None).is_err());
+
+        cover_crypt.refresh_user_secret_key(&mut usk, &msk, true)?;
+        new_header.decrypt(&cover_crypt, &usk, None)?;
+        old_header.decrypt(&cover_crypt, &usk, None)?;
+
+        // Every partition is targeted exactly once.
+        assert_eq!(
+            policy
+                .access_policy_to_partitions(&all_partitions_access_policy(&policy)?, false)?
+                .len(),
+            policy.generate_all_partitions()?.len()
+        );
+        assert!(all_partitions_access_policy(&Policy::new()).is_err());
+        Ok(())
+    }
+}
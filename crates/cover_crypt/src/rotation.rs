@@ -0,0 +1,302 @@
+//! Bounded rotation history of the user secret keys.
+//!
+//! Each coordinate (partition) of a master secret key holds one subkey per
+//! rotation since it was last pruned, the newest first. A user secret key
+//! refreshed keeping its old accesses receives all of them, and can therefore
+//! decrypt all the ciphertexts encrypted since the last prune.
+//! [`refresh_user_secret_key`] bounds the number of old rotations kept by the
+//! user key, e.g. so that a key refreshed after an incident cannot decrypt the
+//! data encrypted before it, and [`user_key_rotations`] lists the rotations a
+//! user key holds.
+//!
+//! Covercrypt 14 does not expose the subkeys: they are read from the key
+//! serializations, mirroring `MasterSecretKey::read` and `UserSecretKey::read`
+//! of the exact Covercrypt version this crate depends on:
+//!
+//! ```txt
+//! master secret key = s1 || s2 || s || subkeys || kmac key
+//! user secret key   = a || b || subkeys || kmac
+//! subkeys           = LEB128(n_coordinates)
+//!                     || (LEB128(coordinate.len()) || coordinate
+//!                         || LEB128(n_subkeys) || subkey * n_subkeys) * n_coordinates
+//! subkey            = LEB128(is_hybridized) || kyber secret key (if hybridized) || x
+//! ```
+
+use std::collections::HashMap;
+
+use cosmian_cover_crypt::{
+    abe_policy::{Attribute, Policy},
+    Covercrypt, Error, MasterSecretKey, UserSecretKey,
+};
+use cosmian_crypto_core::{
+    bytes_ser_de::{Deserializer, Serializable, Serializer},
+    reexport::zeroize::Zeroizing,
+    FixedSizeCBytes, R25519PrivateKey,
+};
+use pqc_kyber::KYBER_INDCPA_SECRETKEYBYTES;
+
+use crate::access_audit::{coordinate_partition, coordinates};
+
+/// Subkey chains of a serialized master or user secret key.
+struct SubkeyChains<'a> {
+    /// Serialized key before the subkeys.
+    prefix: &'a [u8],
+    /// Serialized subkeys of each coordinate, the newest first.
+    chains: Vec<(&'a [u8], Vec<&'a [u8]>)>,
+    /// Serialized key after the subkeys: the KMAC key or signature, if any.
+    suffix: &'a [u8],
+}
+
+impl<'a> SubkeyChains<'a> {
+    /// Parses the given serialized key, in which the subkeys start after
+    /// `prefix_length` bytes.
+    fn parse(key_bytes: &'a [u8], prefix_length: usize) -> Result<Self, Error> {
+        if key_bytes.len() < prefix_length {
+            return Err(Error::KeyError("serialized key is too short".to_string()));
+        }
+        let (prefix, subkeys_bytes) = key_bytes.split_at(prefix_length);
+        let mut de = Deserializer::new(subkeys_bytes);
+        // Position of the deserializer in `subkeys_bytes`.
+        let position = |de: &Deserializer| subkeys_bytes.len() - de.value().len();
+        let n_coordinates = <usize>::try_from(de.read_leb128_u64()?)?;
+        let mut chains = Vec::new();
+        for _ in 0..n_coordinates {
+            let coordinate = de.read_vec_as_ref()?;
+            let n_subkeys = <usize>::try_from(de.read_leb128_u64()?)?;
+            let mut subkeys = Vec::new();
+            for _ in 0..n_subkeys {
+                let start = position(&de);
+                if de.read_leb128_u64()? == 1 {
+                    de.read_array::<KYBER_INDCPA_SECRETKEYBYTES>()?;
+                }
+                de.read_array::<{ R25519PrivateKey::LENGTH }>()?;
+                subkeys.push(&subkeys_bytes[start..position(&de)]);
+            }
+            chains.push((coordinate, subkeys));
+        }
+        Ok(Self {
+            prefix,
+            chains,
+            suffix: &subkeys_bytes[position(&de)..],
+        })
+    }
+
+    /// Serializes the key back.
+    fn write(&self) -> Result<Zeroizing<Vec<u8>>, Error> {
+        let mut ser = Serializer::new();
+        ser.write_array(self.prefix)?;
+        ser.write_leb128_u64(self.chains.len() as u64)?;
+        for (coordinate, subkeys) in &self.chains {
+            ser.write_vec(coordinate)?;
+            ser.write_leb128_u64(subkeys.len() as u64)?;
+            for subkey in subkeys {
+                ser.write_array(subkey)?;
+            }
+        }
+        ser.write_array(self.suffix)?;
+        Ok(ser.finalize())
+    }
+}
+
+/// Refreshes the given user secret key, keeping at most `keep_old_rotations`
+/// rotations older than the current one for each coordinate.
+///
+/// With `keep_old_rotations` set to 0, the user key can only decrypt the
+/// ciphertexts encrypted since the last rotation of each coordinate.
+pub fn refresh_user_secret_key(
+    msk: &MasterSecretKey,
+    usk: &mut UserSecretKey,
+    keep_old_rotations: usize,
+) -> Result<(), Error> {
+    // Refreshing the user key keeping all the old accesses using a master
+    // secret key truncated to the rotations to keep signs the user key as the
+    // original master secret key would, the KMAC key being unchanged.
+    let msk_bytes = msk.serialize()?;
+    let mut msk_chains = SubkeyChains::parse(&msk_bytes, 3 * R25519PrivateKey::LENGTH)?;
+    for (_, subkeys) in &mut msk_chains.chains {
+        subkeys.truncate(keep_old_rotations.saturating_add(1));
+    }
+    let truncated_msk = MasterSecretKey::deserialize(&msk_chains.write()?)?;
+    Covercrypt::default().refresh_user_secret_key(usk, &truncated_msk, true)
+}
+
+/// Returns the coordinates of the given policy the given user secret key holds
+/// keys for, each given as the list of its attributes, one per axis sorted by
+/// name, along with the number of rotations of this coordinate the user key
+/// can decrypt: the current one upon its last refresh and the older ones
+/// kept.
+///
+/// The coordinates of the attributes removed from the policy are not listed.
+pub fn user_key_rotations(
+    policy: &Policy,
+    usk: &UserSecretKey,
+) -> Result<Vec<(Vec<Attribute>, usize)>, Error> {
+    let usk_bytes = usk.serialize()?;
+    let usk_chains = SubkeyChains::parse(&usk_bytes, 2 * R25519PrivateKey::LENGTH)?;
+    let n_rotations = usk_chains
+        .chains
+        .iter()
+        .map(|(coordinate, subkeys)| (*coordinate, subkeys.len()))
+        .collect::<HashMap<_, _>>();
+
+    let mut rotations = Vec::new();
+    for coordinate in coordinates(policy) {
+        let partition = coordinate_partition(policy, &coordinate)?;
+        if let Some(&n) = n_rotations.get(&*partition) {
+            rotations.push((coordinate, n));
+        }
+    }
+    Ok(rotations)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashSet;
+
+    use cosmian_cover_crypt::{
+        abe_policy::AccessPolicy, core::KMAC_KEY_LENGTH, test_utils::policy, EncryptedHeader,
+    };
+
+    use super::*;
+    use crate::{access_audit::coordinate_to_boolean_expression, rekey::rekey_all_master_keys};
+
+    /// Length of a serialized classic subkey.
+    const CLASSIC_SUBKEY_LENGTH: usize = 1 + R25519PrivateKey::LENGTH;
+
+    /// Length of a serialized hybridized subkey.
+    const HYBRIDIZED_SUBKEY_LENGTH: usize =
+        1 + KYBER_INDCPA_SECRETKEYBYTES + R25519PrivateKey::LENGTH;
+
+    /// Length of the KMAC signing the user secret keys.
+    const KMAC_LENGTH: usize = 32;
+
+    /// Checks the given key is parsed as the given number of chains of
+    /// `n_rotations` subkeys followed by a suffix of `suffix_length` bytes,
+    /// and serialized back as is.
+    fn check_layout(
+        key_bytes: &[u8],
+        prefix_length: usize,
+        partitions: &HashSet<Vec<u8>>,
+        n_rotations: usize,
+        suffix_length: usize,
+    ) -> Result<(), Error> {
+        let chains = SubkeyChains::parse(key_bytes, prefix_length)?;
+        assert_eq!(&**chains.write()?, key_bytes);
+        assert_eq!(chains.suffix.len(), suffix_length);
+        assert_eq!(
+            chains
+                .chains
+                .iter()
+                .map(|(coordinate, _)| coordinate.to_vec())
+                .collect::<HashSet<_>>(),
+            *partitions
+        );
+        let subkeys = chains
+            .chains
+            .iter()
+            .flat_map(|(_, subkeys)| subkeys)
+            .collect::<Vec<_>>();
+        assert_eq!(subkeys.len(), partitions.len() * n_rotations);
+        assert!(subkeys.iter().all(|subkey| {
+            subkey.len() == CLASSIC_SUBKEY_LENGTH || subkey.len() == HYBRIDIZED_SUBKEY_LENGTH
+        }));
+        // The test policy hybridizes the `Top Secret` security level only.
+        assert!(subkeys
+            .iter()
+            .any(|subkey| subkey.len() == CLASSIC_SUBKEY_LENGTH));
+        assert!(subkeys
+            .iter()
+            .any(|subkey| subkey.len() == HYBRIDIZED_SUBKEY_LENGTH));
+        Ok(())
+    }
+
+    #[test]
+    fn test_key_layouts() -> Result<(), Error> {
+        let policy = policy()?;
+        let cover_crypt = Covercrypt::default();
+        let (mut msk, mut mpk) = cover_crypt.generate_master_keys(&policy)?;
+        let access_policy =
+            AccessPolicy::from_boolean_expression("Department::FIN && Security Level::Top Secret")?;
+        let mut usk = cover_crypt.generate_user_secret_key(&msk, &access_policy, &policy)?;
+        rekey_all_master_keys(&policy, &mut msk, &mut mpk)?;
+        cover_crypt.refresh_user_secret_key(&mut usk, &msk, true)?;
+
+        let msk_partitions = coordinates(&policy)
+            .iter()
+            .map(|coordinate| coordinate_partition(&policy, coordinate).map(|p| p.to_vec()))
+            .collect::<Result<HashSet<_>, _>>()?;
+        check_layout(
+            &msk.serialize()?,
+            3 * R25519PrivateKey::LENGTH,
+            &msk_partitions,
+            2,
+            KMAC_KEY_LENGTH,
+        )?;
+
+        let usk_partitions = user_key_rotations(&policy, &usk)?
+            .into_iter()
+            .map(|(coordinate, _)| coordinate_partition(&policy, &coordinate).map(|p| p.to_vec()))
+            .collect::<Result<HashSet<_>, _>>()?;
+        // Each security level up to `Top Secret`, in the `FIN` department.
+        assert_eq!(usk_partitions.len(), 5);
+        check_layout(
+            &usk.serialize()?,
+            2 * R25519PrivateKey::LENGTH,
+            &usk_partitions,
+            2,
+            KMAC_LENGTH,
+        )
+    }
+
+    #[test]
+    fn test_bounded_rotations() -> Result<(), Error> {
+        let policy = policy()?;
+        let cover_crypt = Covercrypt::default();
+        let (mut msk, mut mpk) = cover_crypt.generate_master_keys(&policy)?;
+        let access_policy =
+            AccessPolicy::from_boolean_expression("Department::FIN && Security Level::Protected")?;
+        let mut usk = cover_crypt.generate_user_secret_key(&msk, &access_policy, &policy)?;
+
+        // One header per rotation, the oldest first.
+        let mut headers = Vec::new();
+        for _ in 0..3 {
+            let (_, header) =
+                EncryptedHeader::generate(&cover_crypt, &policy, &mpk, &access_policy, None, None)?;
+            headers.push(header);
+            rekey_all_master_keys(&policy, &mut msk, &mut mpk)?;
+        }
+        let (_, header) =
+            EncryptedHeader::generate(&cover_crypt, &policy, &mpk, &access_policy, None, None)?;
+        headers.push(header);
+
+        let mut all_rotations_usk = UserSecretKey::deserialize(&usk.serialize()?)?;
+        cover_crypt.refresh_user_secret_key(&mut all_rotations_usk, &msk, true)?;
+        let rotations = user_key_rotations(&policy, &all_rotations_usk)?;
+        assert_eq!(rotations.len(), 1);
+        assert_eq!(
+            coordinate_to_boolean_expression(&rotations[0].0),
+            "Department::FIN && Security Level::Protected"
+        );
+        assert_eq!(rotations[0].1, 4);
+
+        refresh_user_secret_key(&msk, &mut usk, 1)?;
+        assert_eq!(user_key_rotations(&policy, &usk)?[0].1, 2);
+        assert!(headers[0].decrypt(&cover_crypt, &usk, None).is_err());
+        assert!(headers[1].decrypt(&cover_crypt, &usk, None).is_err());
+        headers[2].decrypt(&cover_crypt, &usk, None)?;
+        headers[3].decrypt(&cover_crypt, &usk, None)?;
+
+        // The refreshed key is still signed by the master secret key, and
+        // regular refreshes do not restore the dropped rotations.
+        cover_crypt.refresh_user_secret_key(&mut usk, &msk, true)?;
+        assert_eq!(user_key_rotations(&policy, &usk)?[0].1, 2);
+
+        refresh_user_secret_key(&msk, &mut all_rotations_usk, 0)?;
+        assert_eq!(user_key_rotations(&policy, &all_rotations_usk)?[0].1, 1);
+        assert!(headers[2]
+            .decrypt(&cover_crypt, &all_rotations_usk, None)
+            .is_err());
+        headers[3].decrypt(&cover_crypt, &all_rotations_usk, None)?;
+        Ok(())
+    }
+}
@@ -1,7 +1,14 @@
-use cosmian_cover_crypt::abe_policy::{Attribute, DimensionBuilder, EncryptionHint, Policy};
+use cosmian_cover_crypt::abe_policy::{
+    AccessPolicy, Attribute, DimensionBuilder, EncryptionHint, Policy,
+};
 use js_sys::{Boolean, JsString, Reflect};
 use wasm_bindgen::{prelude::wasm_bindgen, JsValue};
 
+use crate::{
+    policy_migration::PolicyMigrationReport, policy_spec::policy_from_json_spec,
+    post_quantum::is_post_quantum,
+};
+
 /// This macro handles deserializing the policy from JS, deserializing an
 /// attribute from JS, and performing a specified action on the policy. It also
 /// ensures proper error handling and serialization of the updated policy into
@@ -102,6 +109,17 @@ pub fn webassembly_policy() -> Result<Vec<u8>, JsValue> {
     serde_json::to_vec(&Policy::new()).map_err(|e| JsValue::from_str(&e.to_string()))
 }
 
+#[wasm_bindgen]
+pub fn webassembly_policy_from_json_spec(spec: String) -> Result<Vec<u8>, JsValue> {
+    let cc_policy =
+        policy_from_json_spec(spec.as_bytes()).map_err(|e| JsValue::from_str(&e.to_string()))?;
+    serde_json::to_vec(&cc_policy).map_err(|e| {
+        JsValue::from_str(&format!(
+            "Error serializing the policy into the response: {e}"
+        ))
+    })
+}
+
 #[wasm_bindgen]
 pub fn webassembly_add_axis(policy: Vec<u8>, axis: String) -> Result<Vec<u8>, JsValue> {
     let mut cc_policy = wasm_unwrap!(
@@ -201,3 +219,50 @@ pub fn webassembly_rename_attribute(
         "Error renaming attribute from the policy"
     )
 }
+
+/// Returns the migration report of the edit of the previous policy into the
+/// updated policy, as a JSON string.
+///
+/// The report lists the coordinates removed by the edit, for which the
+/// ciphertexts become unreadable once the master keys are updated, and the
+/// coordinates it adds, whose user keys must be refreshed.
+#[wasm_bindgen]
+pub fn webassembly_policy_migration_report(
+    previous_policy: Vec<u8>,
+    updated_policy: Vec<u8>,
+) -> Result<String, JsValue> {
+    let previous_policy = wasm_unwrap!(
+        Policy::parse_and_convert(&previous_policy),
+        "Error deserializing the previous policy"
+    );
+    let updated_policy = wasm_unwrap!(
+        Policy::parse_and_convert(&updated_policy),
+        "Error deserializing the updated policy"
+    );
+    let report = wasm_unwrap!(
+        PolicyMigrationReport::new(&previous_policy, &updated_policy),
+        "Error computing the policy migration report"
+    );
+    Ok(report.to_json().to_string())
+}
+
+/// Checks whether all the encapsulations of the ciphertexts encrypted under
+/// the given access policy are hybridized (post-quantum).
+///
+/// - `policy`          : serialized policy
+/// - `access_policy`   : access policy the data would be encrypted under
+#[wasm_bindgen]
+pub fn webassembly_is_post_quantum(policy: Vec<u8>, access_policy: &str) -> Result<bool, JsValue> {
+    let policy = wasm_unwrap!(
+        Policy::parse_and_convert(&policy),
+        "Error deserializing policy"
+    );
+    let access_policy = wasm_unwrap!(
+        AccessPolicy::from_boolean_expression(access_policy),
+        "Error reading access policy"
+    );
+    Ok(wasm_unwrap!(
+        is_post_quantum(&policy, &access_policy),
+        "Error checking the access policy encapsulations"
+    ))
+}
@@ -0,0 +1,119 @@
+use cosmian_cover_crypt::{
+    abe_policy::{AccessPolicy, Policy},
+    MasterPublicKey, UserSecretKey,
+};
+use js_sys::{Array, JsString, Map, Uint8Array};
+use wasm_bindgen::prelude::*;
+
+use crate::{
+    access_audit::{can_decrypt, coordinate_to_boolean_expression, user_key_coordinates},
+    key_version::try_deserialize_any_version,
+    rotation::user_key_rotations,
+};
+
+/// Lists the coordinates of the policy the given user secret key can decrypt,
+/// as boolean expressions (e.g. `Department::HR && Security Level::Low
+/// Secret`).
+///
+/// - `usk_bytes`       : user secret key in bytes
+/// - `mpk_bytes`       : master public key in bytes
+/// - `policy_bytes`    : serialized policy
+#[wasm_bindgen]
+pub fn webassembly_user_key_coordinates(
+    usk_bytes: Uint8Array,
+    mpk_bytes: Uint8Array,
+    policy_bytes: Vec<u8>,
+) -> Result<Array, JsValue> {
+    let usk = wasm_unwrap!(
+        try_deserialize_any_version::<UserSecretKey>(&usk_bytes.to_vec()),
+        "Error deserializing user secret key"
+    );
+    let mpk = wasm_unwrap!(
+        try_deserialize_any_version::<MasterPublicKey>(&mpk_bytes.to_vec()),
+        "Error deserializing master public key"
+    );
+    let policy = wasm_unwrap!(
+        Policy::parse_and_convert(&policy_bytes),
+        "Error deserializing policy"
+    );
+    let coordinates = wasm_unwrap!(
+        user_key_coordinates(&policy, &mpk, &usk),
+        "Error listing user secret key coordinates"
+    );
+    Ok(coordinates
+        .iter()
+        .map(|coordinate| JsString::from(coordinate_to_boolean_expression(coordinate)))
+        .collect())
+}
+
+/// Checks whether the given user secret key can decrypt the ciphertexts
+/// encrypted under the given access policy. No ciphertext is needed.
+///
+/// - `usk_bytes`               : user secret key in bytes
+/// - `mpk_bytes`               : master public key in bytes
+/// - `encryption_policy_str`   : encryption access policy (boolean expression)
+/// - `policy_bytes`            : serialized policy
+#[wasm_bindgen]
+pub fn webassembly_can_decrypt(
+    usk_bytes: Uint8Array,
+    mpk_bytes: Uint8Array,
+    encryption_policy_str: &str,
+    policy_bytes: Vec<u8>,
+) -> Result<bool, JsValue> {
+    let usk = wasm_unwrap!(
+        try_deserialize_any_version::<UserSecretKey>(&usk_bytes.to_vec()),
+        "Error deserializing user secret key"
+    );
+    let mpk = wasm_unwrap!(
+        try_deserialize_any_version::<MasterPublicKey>(&mpk_bytes.to_vec()),
+        "Error deserializing master public key"
+    );
+    let encryption_policy = wasm_unwrap!(
+        AccessPolicy::from_boolean_expression(encryption_policy_str),
+        "Error deserializing encryption policy"
+    );
+    let policy = wasm_unwrap!(
+        Policy::parse_and_convert(&policy_bytes),
+        "Error deserializing policy"
+    );
+    Ok(wasm_unwrap!(
+        can_decrypt(&policy, &mpk, &usk, &encryption_policy),
+        "Error checking user secret key access"
+    ))
+}
+
+/// Lists the number of rotations of each coordinate of the policy the given
+/// user secret key can decrypt: the current one upon its last refresh and the
+/// older ones kept.
+///
+/// Returns a map from the coordinates, as boolean expressions, to their
+/// number of rotations.
+///
+/// - `usk_bytes`       : user secret key in bytes
+/// - `policy_bytes`    : serialized policy
+#[wasm_bindgen]
+pub fn webassembly_user_key_rotations(
+    usk_bytes: Uint8Array,
+    policy_bytes: Vec<u8>,
+) -> Result<Map, JsValue> {
+    let usk = wasm_unwrap!(
+        try_deserialize_any_version::<UserSecretKey>(&usk_bytes.to_vec()),
+        "Error deserializing user secret key"
+    );
+    let policy = wasm_unwrap!(
+        Policy::parse_and_convert(&policy_bytes),
+        "Error deserializing policy"
+    );
+    let rotations = wasm_unwrap!(
+        user_key_rotations(&policy, &usk),
+        "Error listing user secret key rotations"
+    );
+    let map = Map::new();
+    for (coordinate, n_rotations) in &rotations {
+        map.set(
+            &JsString::from(coordinate_to_boolean_expression(coordinate)).into(),
+            &JsValue::from(*n_rotations),
+        );
+    }
+    Ok(map)
+}
@@ -0,0 +1,109 @@
+//! Structured decryption errors.
+//!
+//! The decryption functions throw JS `Error`s holding a `kind` property, so
+//! that applications can tell a user without access to a ciphertext from a
+//! corrupted ciphertext, e.g. to display "you don't have access" instead of a
+//! generic failure. Messages are truncated to [`MAX_ERROR_MESSAGE_LENGTH`]
+//! bytes.
+
+use std::fmt::Display;
+
+use cosmian_cover_crypt::{Covercrypt, EncryptedHeader, UserSecretKey};
+use cosmian_crypto_core::bytes_ser_de::Deserializer;
+use js_sys::Reflect;
+use wasm_bindgen::prelude::*;
+
+/// Maximum length in bytes of the messages of the decryption errors.
+pub const MAX_ERROR_MESSAGE_LENGTH: usize = 256;
+
+#[wasm_bindgen(typescript_custom_section)]
+const DECRYPTION_ERROR: &str = r#"
+/** Error thrown by the decryption functions. */
+export type DecryptionError = Error & {
+  kind: "InvalidKey" | "MalformedCiphertext" | "InsufficientAccessPolicy" | "IntegrityFailure",
+};
+"#;
+
+/// Kind of a decryption error, given as the `kind` property of the JS error.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DecryptionErrorKind {
+    /// The given key cannot be deserialized.
+    InvalidKey,
+    /// The ciphertext cannot be parsed, e.g. it is truncated.
+    MalformedCiphertext,
+    /// The user secret key has no access to the partitions of the ciphertext.
+    InsufficientAccessPolicy,
+    /// The ciphertext cannot be authenticated: it was corrupted, or the
+    /// authentication data differs from the one given upon encryption.
+    IntegrityFailure,
+}
+
+impl DecryptionErrorKind {
+    #[must_use]
+    pub const fn as_str(self) -> &'static str {
+        match self {
+            Self::InvalidKey => "InvalidKey",
+            Self::MalformedCiphertext => "MalformedCiphertext",
+            Self::InsufficientAccessPolicy => "InsufficientAccessPolicy",
+            Self::IntegrityFailure => "IntegrityFailure",
+        }
+    }
+
+    /// Returns the kind of the failure to decrypt the given header using the
+    /// given user secret key.
+    ///
+    /// Only called upon failure: the header is decapsulated again to tell
+    /// whether the key has access to it, in which case the header metadata
+    /// could not be authenticated.
+    pub fn of_header(header: &EncryptedHeader, usk: &UserSecretKey) -> Self {
+        if Covercrypt::default()
+            .decaps(usk, &header.encapsulation)
+            .is_ok()
+        {
+            Self::IntegrityFailure
+        } else {
+            Self::InsufficientAccessPolicy
+        }
+    }
+
+    /// Returns the kind of the failure to decrypt the given ciphertext with
+    /// cleartext metadata using the given user secret key.
+    pub fn of_cleartext_metadata_ciphertext(encrypted_bytes: &[u8], usk: &UserSecretKey) -> Self {
+        let mut de = Deserializer::new(encrypted_bytes);
+        match de
+            .read_vec()
+            .ok()
+            .and_then(|_| de.read::<EncryptedHeader>().ok())
+        {
+            Some(header) => Self::of_header(&header, usk),
+            None => Self::MalformedCiphertext,
+        }
+    }
+}
+
+/// Truncates the given message to [`MAX_ERROR_MESSAGE_LENGTH`] bytes, on a
+/// character boundary.
+fn bounded(mut message: String) -> String {
+    if message.len() > MAX_ERROR_MESSAGE_LENGTH {
+        let mut length = MAX_ERROR_MESSAGE_LENGTH - "...".len();
+        while !message.is_char_boundary(length) {
+            length -= 1;
+        }
+        message.truncate(length);
+        message.push_str("...");
+    }
+    message
+}
+
+/// Creates a JS `Error` of the given kind, holding the given message
+/// truncated to [`MAX_ERROR_MESSAGE_LENGTH`] bytes.
+pub fn decryption_error(kind: DecryptionErrorKind, message: impl Display) -> JsValue {
+    let error = js_sys::Error::new(&bounded(message.to_string()));
+    // Setting a property of a fresh `Error` object cannot fail.
+    let _ = Reflect::set(
+        &error,
+        &JsValue::from_str("kind"),
+        &JsValue::from_str(kind.as_str()),
+    );
+    error.into()
+}
@@ -0,0 +1,97 @@
+// needed to remove wasm_bindgen warnings
+#![allow(non_upper_case_globals)]
+
+use cosmian_cover_crypt::abe_policy::{AccessPolicy, Policy};
+use js_sys::{Object, Reflect};
+use wasm_bindgen::prelude::*;
+
+use crate::estimation::{
+    estimate_ciphertext_overhead, estimate_master_key_sizes, estimate_user_secret_key_size,
+};
+
+/// Estimates the size overhead of a hybrid ciphertext encrypted under the
+/// given access policy, compared to the plaintext, without needing any key.
+///
+/// The header metadata is not taken into account.
+///
+/// - `policy_bytes`    : serialized policy
+/// - `access_policy`   : access policy
+#[wasm_bindgen]
+pub fn webassembly_estimate_ciphertext_overhead(
+    policy_bytes: Vec<u8>,
+    access_policy: String,
+) -> Result<usize, JsValue> {
+    let policy = wasm_unwrap!(
+        Policy::parse_and_convert(&policy_bytes),
+        "Error deserializing policy"
+    );
+    let access_policy = wasm_unwrap!(
+        AccessPolicy::from_boolean_expression(&access_policy),
+        "Error reading access policy"
+    );
+    Ok(wasm_unwrap!(
+        estimate_ciphertext_overhead(&policy, &access_policy),
+        "Error estimating ciphertext overhead"
+    ))
+}
+
+#[wasm_bindgen]
+extern "C" {
+    #[wasm_bindgen(typescript_type = "{ masterSecretKey: number, masterPublicKey: number }")]
+    pub type MasterKeySizes;
+}
+
+/// Estimates the size of the serialized master keys of the given policy.
+///
+/// - `policy_bytes`    : serialized policy
+#[wasm_bindgen]
+pub fn webassembly_estimate_master_key_sizes(
+    policy_bytes: Vec<u8>,
+) -> Result<MasterKeySizes, JsValue> {
+    let policy = wasm_unwrap!(
+        Policy::parse_and_convert(&policy_bytes),
+        "Error deserializing policy"
+    );
+    let sizes = wasm_unwrap!(
+        estimate_master_key_sizes(&policy),
+        "Error estimating master key sizes"
+    );
+
+    let obj = Object::new();
+    Reflect::set(
+        &obj,
+        &JsValue::from_str("masterSecretKey"),
+        &JsValue::from(sizes.master_secret_key),
+    )?;
+    Reflect::set(
+        &obj,
+        &JsValue::from_str("masterPublicKey"),
+        &JsValue::from(sizes.master_public_key),
+    )?;
+
+    Ok(MasterKeySizes::from(JsValue::from(obj)))
+}
+
+/// Estimates the size of a serialized user secret key of the given access
+/// policy.
+///
+/// - `policy_bytes`        : serialized policy
+/// - `access_policy_str`   : user access policy (boolean expression as string)
+#[wasm_bindgen]
+pub fn webassembly_estimate_user_secret_key_size(
+    policy_bytes: Vec<u8>,
+    access_policy_str: &str,
+) -> Result<usize, JsValue> {
+    let policy = wasm_unwrap!(
+        Policy::parse_and_convert(&policy_bytes),
+        "Error deserializing policy"
+    );
+    let access_policy = wasm_unwrap!(
+        AccessPolicy::from_boolean_expression(access_policy_str),
+        "Error deserializing access policy"
+    );
+    Ok(wasm_unwrap!(
+        estimate_user_secret_key_size(&policy, &access_policy),
+        "Error estimating user secret key size"
+    ))
+}
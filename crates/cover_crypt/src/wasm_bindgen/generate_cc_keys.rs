@@ -1,11 +1,16 @@
 use cosmian_cover_crypt::{
     abe_policy::{AccessPolicy, Policy},
-    Covercrypt, MasterSecretKey,
+    Covercrypt, MasterPublicKey, MasterSecretKey, UserSecretKey,
 };
-use cosmian_crypto_core::bytes_ser_de::Serializable;
 use js_sys::Uint8Array;
 use wasm_bindgen::prelude::*;
 
+use crate::{
+    key_version::{serialize_versioned, try_deserialize_any_version, upgrade_key},
+    rekey::rekey_all_master_keys,
+    rotation::refresh_user_secret_key,
+};
+
 /// Generate the master authority keys for supplied Policy
 ///
 /// - `policy`  : global policy data (JSON)
@@ -24,8 +29,14 @@ pub fn webassembly_generate_master_keys(policy_bytes: Vec<u8>) -> Result<Uint8Ar
     );
 
     // Serialize master keys
-    let msk_bytes = wasm_unwrap!(msk.serialize(), "Error serializing master secret key");
-    let mpk_bytes = wasm_unwrap!(mpk.serialize(), "Error serializing master public key");
+    let msk_bytes = wasm_unwrap!(
+        serialize_versioned(&msk),
+        "Error serializing master secret key"
+    );
+    let mpk_bytes = wasm_unwrap!(
+        serialize_versioned(&mpk),
+        "Error serializing master public key"
+    );
 
     let mut master_keys_bytes = Vec::with_capacity(4 + msk_bytes.len() + msk_bytes.len());
     master_keys_bytes.extend_from_slice(&u32::to_be_bytes(wasm_unwrap!(
@@ -37,6 +48,57 @@ pub fn webassembly_generate_master_keys(policy_bytes: Vec<u8>) -> Result<Uint8Ar
     Ok(Uint8Array::from(&master_keys_bytes[..]))
 }
 
+/// Rekeys all the partitions of the given master keys, without changing the
+/// policy (periodic rotation).
+///
+/// Unlike a policy rotation, the keys of every partition are renewed: all the
+/// user secret keys must be refreshed. The updated master keys are returned in
+/// the same format as `webassembly_generate_master_keys`.
+///
+/// - `msk_bytes`   : master secret key in bytes
+/// - `mpk_bytes`   : master public key in bytes
+/// - `policy`      : global policy data (JSON)
+#[wasm_bindgen]
+pub fn webassembly_rekey_all_master_keys(
+    msk_bytes: Uint8Array,
+    mpk_bytes: Uint8Array,
+    policy_bytes: Vec<u8>,
+) -> Result<Uint8Array, JsValue> {
+    let mut msk = wasm_unwrap!(
+        try_deserialize_any_version::<MasterSecretKey>(&msk_bytes.to_vec()),
+        "Error deserializing master secret key"
+    );
+    let mut mpk = wasm_unwrap!(
+        try_deserialize_any_version::<MasterPublicKey>(&mpk_bytes.to_vec()),
+        "Error deserializing master public key"
+    );
+    let policy = wasm_unwrap!(
+        Policy::parse_and_convert(&policy_bytes),
+        "Error deserializing policy"
+    );
+    wasm_unwrap!(
+        rekey_all_master_keys(&policy, &mut msk, &mut mpk),
+        "Error rekeying all master keys"
+    );
+
+    let msk_bytes = wasm_unwrap!(
+        serialize_versioned(&msk),
+        "Error serializing master secret key"
+    );
+    let mpk_bytes = wasm_unwrap!(
+        serialize_versioned(&mpk),
+        "Error serializing master public key"
+    );
+    let mut master_keys_bytes = Vec::with_capacity(4 + msk_bytes.len() + mpk_bytes.len());
+    master_keys_bytes.extend_from_slice(&u32::to_be_bytes(wasm_unwrap!(
+        msk_bytes.len().try_into(),
+        "Error while converting usize to u32"
+    )));
+    master_keys_bytes.extend_from_slice(&msk_bytes);
+    master_keys_bytes.extend_from_slice(&mpk_bytes);
+    Ok(Uint8Array::from(&master_keys_bytes[..]))
+}
+
 /// Generate a user secret key.
 ///
 /// - `msk_bytes`           : master secret key in bytes
@@ -49,7 +111,7 @@ pub fn webassembly_generate_user_secret_key(
     policy_bytes: Vec<u8>,
 ) -> Result<Uint8Array, JsValue> {
     let msk = wasm_unwrap!(
-        MasterSecretKey::deserialize(&msk_bytes.to_vec()),
+        try_deserialize_any_version::<MasterSecretKey>(&msk_bytes.to_vec()),
         "Error deserializing master secret key"
     );
     let policy = wasm_unwrap!(
@@ -64,6 +126,108 @@ pub fn webassembly_generate_user_secret_key(
         Covercrypt::default().generate_user_secret_key(&msk, &access_policy, &policy),
         "Error generating user secret key"
     );
-    let user_key_bytes = wasm_unwrap!(user_key.serialize(), "Error serializing user key");
+    let user_key_bytes = wasm_unwrap!(serialize_versioned(&user_key), "Error serializing user key");
     Ok(Uint8Array::from(user_key_bytes.as_slice()))
 }
+
+/// Converts a master secret key serialized in any supported format into the
+/// current format.
+///
+/// - `msk_bytes`   : master secret key in bytes
+#[wasm_bindgen]
+pub fn webassembly_upgrade_master_secret_key(msk_bytes: Uint8Array) -> Result<Uint8Array, JsValue> {
+    let msk_bytes = wasm_unwrap!(
+        upgrade_key::<MasterSecretKey>(&msk_bytes.to_vec()),
+        "Error upgrading master secret key"
+    );
+    Ok(Uint8Array::from(msk_bytes.as_slice()))
+}
+
+/// Converts a master public key serialized in any supported format into the
+/// current format.
+///
+/// - `mpk_bytes`   : master public key in bytes
+#[wasm_bindgen]
+pub fn webassembly_upgrade_master_public_key(mpk_bytes: Uint8Array) -> Result<Uint8Array, JsValue> {
+    let mpk_bytes = wasm_unwrap!(
+        upgrade_key::<MasterPublicKey>(&mpk_bytes.to_vec()),
+        "Error upgrading master public key"
+    );
+    Ok(Uint8Array::from(mpk_bytes.as_slice()))
+}
+
+/// Converts a user secret key serialized in any supported format into the
+/// current format.
+///
+/// - `usk_bytes`   : user secret key in bytes
+#[wasm_bindgen]
+pub fn webassembly_upgrade_user_secret_key(usk_bytes: Uint8Array) -> Result<Uint8Array, JsValue> {
+    let usk_bytes = wasm_unwrap!(
+        upgrade_key::<UserSecretKey>(&usk_bytes.to_vec()),
+        "Error upgrading user secret key"
+    );
+    Ok(Uint8Array::from(usk_bytes.as_slice()))
+}
+
+/// Refreshes the user secret key according to the given master secret key.
+///
+/// The user key is granted access to the current partitions, as determined by
+/// its access policy. The access to the rotated partitions is either entirely
+/// kept or entirely dropped, see
+/// `webassembly_refresh_user_secret_key_with_rotations` to keep only the most
+/// recent rotations.
+///
+/// - `usk_bytes`           : user secret key in bytes
+/// - `msk_bytes`           : master secret key in bytes
+/// - `keep_old_accesses`   : whether the access to the rotated partitions
+///   should be kept
+#[wasm_bindgen]
+pub fn webassembly_refresh_user_secret_key(
+    usk_bytes: Uint8Array,
+    msk_bytes: Uint8Array,
+    keep_old_accesses: bool,
+) -> Result<Uint8Array, JsValue> {
+    let mut usk = wasm_unwrap!(
+        try_deserialize_any_version::<UserSecretKey>(&usk_bytes.to_vec()),
+        "Error deserializing user secret key"
+    );
+    let msk = wasm_unwrap!(
+        try_deserialize_any_version::<MasterSecretKey>(&msk_bytes.to_vec()),
+        "Error deserializing master secret key"
+    );
+    wasm_unwrap!(
+        Covercrypt::default().refresh_user_secret_key(&mut usk, &msk, keep_old_accesses),
+        "Error refreshing user secret key"
+    );
+    let usk_bytes = wasm_unwrap!(serialize_versioned(&usk), "Error serializing user key");
+    Ok(Uint8Array::from(usk_bytes.as_slice()))
+}
+
+/// Refreshes the user secret key according to the given master secret key,
+/// keeping at most `keep_old_rotations` rotations older than the current one
+/// for each partition.
+///
+/// - `usk_bytes`           : user secret key in bytes
+/// - `msk_bytes`           : master secret key in bytes
+/// - `keep_old_rotations`  : number of old rotations to keep access to
+#[wasm_bindgen]
+pub fn webassembly_refresh_user_secret_key_with_rotations(
+    usk_bytes: Uint8Array,
+    msk_bytes: Uint8Array,
+    keep_old_rotations: usize,
+) -> Result<Uint8Array, JsValue> {
+    let mut usk = wasm_unwrap!(
+        try_deserialize_any_version::<UserSecretKey>(&usk_bytes.to_vec()),
+        "Error deserializing user secret key"
+    );
+    let msk = wasm_unwrap!(
+        try_deserialize_any_version::<MasterSecretKey>(&msk_bytes.to_vec()),
+        "Error deserializing master secret key"
+    );
+    wasm_unwrap!(
+        refresh_user_secret_key(&msk, &mut usk, keep_old_rotations),
+        "Error refreshing user secret key"
+    );
+    let usk_bytes = wasm_unwrap!(serialize_versioned(&usk), "Error serializing user key");
+    Ok(Uint8Array::from(usk_bytes.as_slice()))
+}
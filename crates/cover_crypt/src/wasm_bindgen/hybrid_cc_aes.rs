@@ -11,6 +11,19 @@ use cosmian_crypto_core::{
 use js_sys::{Object, Reflect, Uint8Array};
 use wasm_bindgen::prelude::*;
 
+use super::decryption_error::{decryption_error, DecryptionErrorKind};
+use crate::{cleartext_metadata, header_padding, key_version::try_deserialize_any_version};
+
+/// Generates an encrypted header. Returns the concatenation of the
+/// encapsulated symmetric key and the serialized encrypted header.
+///
+/// - `policy_bytes`        : serialized policy
+/// - `access_policy`       : access policy
+/// - `public_key_bytes`    : CoverCrypt public key
+/// - `header_metadata`     : additional data to symmetrically encrypt in the
+///   header
+/// - `authentication_data` : optional data authenticating the header metadata,
+///   to be given upon decryption
 #[wasm_bindgen]
 pub fn webassembly_encrypt_hybrid_header(
     policy_bytes: Vec<u8>,
@@ -28,7 +41,7 @@ pub fn webassembly_encrypt_hybrid_header(
         "Error reading access policy"
     );
     let public_key = wasm_unwrap!(
-        MasterPublicKey::deserialize(&public_key_bytes.to_vec()),
+        try_deserialize_any_version::<MasterPublicKey>(&public_key_bytes.to_vec()),
         "Error deserializing public key"
     );
     let header_metadata = if header_metadata.is_null() {
@@ -70,6 +83,11 @@ pub fn webassembly_encrypt_hybrid_header(
 
 /// Decrypt with a user decryption key an encrypted header
 /// of a resource encrypted using an hybrid crypto scheme.
+///
+/// The `authentication_data` given upon encryption is only checked if the
+/// header contains metadata.
+///
+/// Throws a `DecryptionError` upon failure.
 #[wasm_bindgen]
 pub fn webassembly_decrypt_hybrid_header(
     usk_bytes: Uint8Array,
@@ -78,8 +96,9 @@ pub fn webassembly_decrypt_hybrid_header(
 ) -> Result<Uint8Array, JsValue> {
     //
     // Parse user decryption key
-    let usk = wasm_unwrap!(
-        UserSecretKey::deserialize(usk_bytes.to_vec().as_slice()),
+    let usk = wasm_decryption_unwrap!(
+        try_deserialize_any_version::<UserSecretKey>(usk_bytes.to_vec().as_slice()),
+        DecryptionErrorKind::InvalidKey,
         "Error deserializing user decryption key"
     );
     let authentication_data = if authentication_data.is_null() {
@@ -90,17 +109,22 @@ pub fn webassembly_decrypt_hybrid_header(
 
     //
     // Parse encrypted header
-    let encrypted_header = wasm_unwrap!(
+    let encrypted_header = wasm_decryption_unwrap!(
         EncryptedHeader::deserialize(encrypted_header_bytes.to_vec().as_slice(),),
+        DecryptionErrorKind::MalformedCiphertext,
         "Error deserializing encrypted header"
     );
 
     //
     // Finally decrypt symmetric key using given user decryption key
-    let cleartext_header = wasm_unwrap!(
-        encrypted_header.decrypt(&Covercrypt::default(), &usk, authentication_data.as_deref(),),
-        "Error decrypting header"
-    );
+    let cleartext_header = encrypted_header
+        .decrypt(&Covercrypt::default(), &usk, authentication_data.as_deref())
+        .map_err(|e| {
+            decryption_error(
+                DecryptionErrorKind::of_header(&encrypted_header, &usk),
+                format_args!("Error decrypting header: {e:?}"),
+            )
+        })?;
 
     Ok(Uint8Array::from(
         wasm_unwrap!(
@@ -155,6 +179,8 @@ pub fn webassembly_encrypt_symmetric_block(
 }
 
 /// Symmetrically Decrypt encrypted data in a block.
+///
+/// Throws a `DecryptionError` upon failure.
 #[wasm_bindgen]
 pub fn webassembly_decrypt_symmetric_block(
     symmetric_key_bytes: Uint8Array,
@@ -163,12 +189,14 @@ pub fn webassembly_decrypt_symmetric_block(
 ) -> Result<Uint8Array, JsValue> {
     //
     // Parse symmetric key
-    let symmetric_key_fixed: [u8; Aes256Gcm::KEY_LENGTH] = wasm_unwrap!(
+    let symmetric_key_fixed: [u8; Aes256Gcm::KEY_LENGTH] = wasm_decryption_unwrap!(
         symmetric_key_bytes.to_vec().try_into(),
+        DecryptionErrorKind::InvalidKey,
         "Error converting symmetric key"
     );
-    let symmetric_key = wasm_unwrap!(
+    let symmetric_key = wasm_decryption_unwrap!(
         SymmetricKey::try_from_bytes(symmetric_key_fixed),
+        DecryptionErrorKind::InvalidKey,
         "Error parsing symmetric key"
     );
 
@@ -180,12 +208,13 @@ pub fn webassembly_decrypt_symmetric_block(
         Some(authentication_data.to_vec())
     };
 
-    let cleartext = wasm_unwrap!(
+    let cleartext = wasm_decryption_unwrap!(
         Covercrypt::default().decrypt(
             &symmetric_key,
             &encrypted_bytes.to_vec(),
             authentication_data.as_deref(),
         ),
+        DecryptionErrorKind::IntegrityFailure,
         "Error decrypting block"
     );
 
@@ -193,16 +222,19 @@ pub fn webassembly_decrypt_symmetric_block(
 }
 
 /// Generates both a encrypted header and a DEM encryption of the `plaintext`,
-/// with the header metadata as associated data.
+/// with the authentication data as associated data.
 ///
-/// - `metadata_bytes`      : serialized metadata
 /// - `policy_bytes`        : serialized policy
-/// - `attribute_bytes`     : serialized attributes to use in the encapsulation
+/// - `access_policy`       : access policy
 /// - `pk`                  : CoverCrypt public key
 /// - `plaintext`           : message to encrypt with the DEM
 /// - `header_metadata`     : additional data to symmetrically encrypt in the
 ///   header
-/// - `authentication_data` : optional data used for authentication
+/// - `authentication_data` : optional data authenticating the DEM ciphertext
+///   and the header metadata, e.g. the record ID, to be given upon decryption
+/// - `header_bucket_size`  : optional size of the buckets the encrypted header
+///   is padded to, hiding the number of partitions of the access policy, to be
+///   given upon decryption
 #[wasm_bindgen]
 pub fn webassembly_hybrid_encrypt(
     policy_bytes: Vec<u8>,
@@ -211,6 +243,7 @@ pub fn webassembly_hybrid_encrypt(
     plaintext: Uint8Array,
     header_metadata: Uint8Array,
     authentication_data: Uint8Array,
+    header_bucket_size: Option<u32>,
 ) -> Result<Uint8Array, JsValue> {
     let policy = wasm_unwrap!(
         serde_json::from_slice(&policy_bytes),
@@ -221,7 +254,7 @@ pub fn webassembly_hybrid_encrypt(
         "Error reading access policy"
     );
     let pk = wasm_unwrap!(
-        MasterPublicKey::deserialize(&pk.to_vec()),
+        try_deserialize_any_version::<MasterPublicKey>(&pk.to_vec()),
         "Error parsing public key"
     );
     let header_metadata = if header_metadata.is_null() {
@@ -260,10 +293,14 @@ pub fn webassembly_hybrid_encrypt(
         "Error encrypting symmetric plaintext"
     );
 
-    // concatenate the encrypted header and the ciphertext
-    let mut ser = Serializer::with_capacity(encrypted_header.length() + ciphertext.len());
+    // concatenate the padded encrypted header and the ciphertext
+    let header_bucket_size = header_bucket_size.unwrap_or_default() as usize;
+    let mut ser = Serializer::with_capacity(
+        header_padding::padded_header_length(encrypted_header.length(), header_bucket_size)
+            + ciphertext.len(),
+    );
     wasm_unwrap!(
-        ser.write(&encrypted_header),
+        header_padding::write_padded_header(&mut ser, &encrypted_header, header_bucket_size),
         "Error serializing encrypted header"
     );
     wasm_unwrap!(ser.write_array(&ciphertext), "Error writing ciphertext");
@@ -271,37 +308,43 @@ pub fn webassembly_hybrid_encrypt(
 }
 
 /// Decrypt the DEM ciphertext with the header encapsulated symmetric key,
-/// with the header metadata as associated data.
+/// with the authentication data as associated data.
 ///
 /// - `usk_bytes`           : serialized user secret key
 /// - `encrypted_bytes`     : concatenation of the encrypted header and the DEM
 ///   ciphertext
-/// - `authentication_data` : optional data used for authentication
+/// - `authentication_data` : data given upon encryption, the decryption fails
+///   otherwise
+/// - `header_bucket_size`  : header bucket size given upon encryption
 ///
 /// Return the decrypted data (additional data in header and cleartext) as a
 /// binary format: 1. LEB128 length of the additional data bytes
 /// 2. additional data bytes
 /// 3. cleartext bytes
+///
+/// Throws a `DecryptionError` upon failure.
 #[wasm_bindgen]
 pub fn webassembly_hybrid_decrypt(
     usk_bytes: Uint8Array,
     encrypted_bytes: Uint8Array,
     authentication_data: Uint8Array,
+    header_bucket_size: Option<u32>,
 ) -> Result<Uint8Array, JsValue> {
-    // Read encrypted bytes as the concatenation of an encrypted header and a DEM
-    // ciphertext.
+    // Read encrypted bytes as the concatenation of a padded encrypted header and
+    // a DEM ciphertext.
     let encrypted_bytes = encrypted_bytes.to_vec();
-    let mut de = Deserializer::new(&encrypted_bytes);
-    let header = wasm_unwrap!(
-        // This will read the exact header size.
-        de.read::<EncryptedHeader>(),
+    let (header, ciphertext) = wasm_decryption_unwrap!(
+        header_padding::read_padded_header(
+            &encrypted_bytes,
+            header_bucket_size.unwrap_or_default() as usize
+        ),
+        DecryptionErrorKind::MalformedCiphertext,
         "Error deserializing encrypted header"
     );
-    // The rest is the symmetric ciphertext.
-    let ciphertext = de.finalize();
 
-    let usk = wasm_unwrap!(
-        UserSecretKey::deserialize(usk_bytes.to_vec().as_slice()),
+    let usk = wasm_decryption_unwrap!(
+        try_deserialize_any_version::<UserSecretKey>(usk_bytes.to_vec().as_slice()),
+        DecryptionErrorKind::InvalidKey,
         "Error deserializing user secret key"
     );
 
@@ -315,17 +358,22 @@ pub fn webassembly_hybrid_decrypt(
     let cover_crypt = Covercrypt::default();
 
     // Decrypt header
-    let cleartext_header = wasm_unwrap!(
-        header.decrypt(&cover_crypt, &usk, authentication_data.as_deref()),
-        "Error decrypting header"
-    );
-
-    let cleartext = wasm_unwrap!(
+    let cleartext_header = header
+        .decrypt(&cover_crypt, &usk, authentication_data.as_deref())
+        .map_err(|e| {
+            decryption_error(
+                DecryptionErrorKind::of_header(&header, &usk),
+                format_args!("Error decrypting header: {e:?}"),
+            )
+        })?;
+
+    let cleartext = wasm_decryption_unwrap!(
         cover_crypt.decrypt(
             &cleartext_header.symmetric_key,
-            ciphertext.as_slice(),
+            ciphertext,
             authentication_data.as_deref(),
         ),
+        DecryptionErrorKind::IntegrityFailure,
         "Error decrypting ciphertext"
     );
 
@@ -341,6 +389,150 @@ pub fn webassembly_hybrid_decrypt(
     Ok(Uint8Array::from(ser.finalize().as_slice()))
 }
 
+/// Encrypt with the DEM the given plaintext and prepend the given cleartext
+/// metadata to the encrypted header and the DEM ciphertext.
+///
+/// The cleartext metadata can be read without any key using
+/// `webassembly_read_cleartext_metadata` but is authenticated upon
+/// decryption.
+///
+/// - `policy_bytes`        : global policy
+/// - `access_policy`       : access policy
+/// - `pk`                  : CoverCrypt public key
+/// - `plaintext`           : message to encrypt with the DEM
+/// - `cleartext_metadata`  : metadata to authenticate but not encrypt
+/// - `header_metadata`     : additional data to symmetrically encrypt in the
+///   header
+/// - `authentication_data` : optional data used for authentication
+#[wasm_bindgen]
+pub fn webassembly_hybrid_encrypt_with_cleartext_metadata(
+    policy_bytes: Vec<u8>,
+    access_policy: String,
+    pk: Uint8Array,
+    plaintext: Uint8Array,
+    cleartext_metadata: Uint8Array,
+    header_metadata: Uint8Array,
+    authentication_data: Uint8Array,
+) -> Result<Uint8Array, JsValue> {
+    let policy = wasm_unwrap!(
+        serde_json::from_slice(&policy_bytes),
+        "Error parsing policy"
+    );
+    let access_policy = wasm_unwrap!(
+        AccessPolicy::from_boolean_expression(&access_policy),
+        "Error reading access policy"
+    );
+    let pk = wasm_unwrap!(
+        try_deserialize_any_version::<MasterPublicKey>(&pk.to_vec()),
+        "Error parsing public key"
+    );
+    let header_metadata = if header_metadata.is_null() {
+        None
+    } else {
+        Some(header_metadata.to_vec())
+    };
+
+    let authentication_data = if authentication_data.is_null() {
+        None
+    } else {
+        Some(authentication_data.to_vec())
+    };
+
+    let encrypted_bytes = wasm_unwrap!(
+        cleartext_metadata::encrypt_with_cleartext_metadata(
+            &Covercrypt::default(),
+            &policy,
+            &pk,
+            &access_policy,
+            &plaintext.to_vec(),
+            &cleartext_metadata.to_vec(),
+            header_metadata.as_deref(),
+            authentication_data.as_deref(),
+        ),
+        "Error encrypting plaintext"
+    );
+    Ok(Uint8Array::from(encrypted_bytes.as_slice()))
+}
+
+/// Read the cleartext metadata of a ciphertext produced by
+/// `webassembly_hybrid_encrypt_with_cleartext_metadata` without any key.
+///
+/// This metadata is only authenticated upon decryption.
+#[wasm_bindgen]
+pub fn webassembly_read_cleartext_metadata(
+    encrypted_bytes: Uint8Array,
+) -> Result<Uint8Array, JsValue> {
+    let metadata = wasm_unwrap!(
+        cleartext_metadata::read_cleartext_metadata(&encrypted_bytes.to_vec()),
+        "Error reading cleartext metadata"
+    );
+    Ok(Uint8Array::from(metadata.as_slice()))
+}
+
+/// Decrypt a ciphertext produced by
+/// `webassembly_hybrid_encrypt_with_cleartext_metadata`.
+///
+/// - `usk_bytes`           : serialized user secret key
+/// - `encrypted_bytes`     : concatenation of the cleartext metadata, the
+///   encrypted header and the DEM ciphertext
+/// - `authentication_data` : optional data used for authentication
+///
+/// Return the decrypted data as a binary format:
+/// 1. LEB128 length of the header metadata bytes
+/// 2. header metadata bytes
+/// 3. LEB128 length of the cleartext metadata bytes
+/// 4. cleartext metadata bytes
+/// 5. plaintext bytes
+///
+/// Throws a `DecryptionError` upon failure.
+#[wasm_bindgen]
+pub fn webassembly_hybrid_decrypt_with_cleartext_metadata(
+    usk_bytes: Uint8Array,
+    encrypted_bytes: Uint8Array,
+    authentication_data: Uint8Array,
+) -> Result<Uint8Array, JsValue> {
+    let usk = wasm_decryption_unwrap!(
+        try_deserialize_any_version::<UserSecretKey>(usk_bytes.to_vec().as_slice()),
+        DecryptionErrorKind::InvalidKey,
+        "Error deserializing user secret key"
+    );
+
+    let authentication_data = if authentication_data.is_null() {
+        None
+    } else {
+        Some(authentication_data.to_vec())
+    };
+
+    let encrypted_bytes = encrypted_bytes.to_vec();
+    let res = cleartext_metadata::decrypt_with_cleartext_metadata(
+        &Covercrypt::default(),
+        &usk,
+        &encrypted_bytes,
+        authentication_data.as_deref(),
+    )
+    .map_err(|e| {
+        decryption_error(
+            DecryptionErrorKind::of_cleartext_metadata_ciphertext(&encrypted_bytes, &usk),
+            format_args!("Error decrypting ciphertext: {e:?}"),
+        )
+    })?;
+
+    let mut ser = Serializer::new();
+    wasm_unwrap!(
+        ser.write_vec(res.header_metadata.unwrap_or_default().as_slice()),
+        "Cannot serialize the decrypted header metadata into response"
+    );
+    wasm_unwrap!(
+        ser.write_vec(res.cleartext_metadata.as_slice()),
+        "Cannot serialize the cleartext metadata into response"
+    );
+    wasm_unwrap!(
+        ser.write_array(res.plaintext.as_slice()),
+        "Cannot serialize the plaintext into response"
+    );
+    Ok(Uint8Array::from(ser.finalize().as_slice()))
+}
+
 #[wasm_bindgen]
 extern "C" {
     #[wasm_bindgen(typescript_type = "{ encryptedHeader: Uint8Array, ciphertext: Uint8Array }")]
@@ -4,9 +4,26 @@ macro_rules! wasm_unwrap {
     };
 }
 
+/// Same as `wasm_unwrap!` but throws a decryption error of the given kind.
+macro_rules! wasm_decryption_unwrap {
+    ($res:expr, $kind:expr, $msg:literal) => {
+        $res.map_err(|e| {
+            $crate::wasm_bindgen::decryption_error::decryption_error(
+                $kind,
+                format_args!("{}: {e:?}", $msg),
+            )
+        })?
+    };
+}
+
 mod abe_policy;
+mod access_audit;
+mod decryption_error;
+mod estimation;
 mod generate_cc_keys;
 mod hybrid_cc_aes;
+mod stream_encryption;
+mod webcrypto;
 
 #[cfg(test)]
 mod tests;
@@ -0,0 +1,203 @@
+//! Streaming hybrid encryption of large files, e.g. files read in the browser.
+//!
+//! ```js
+//! const encryptor = new HybridStreamEncryptor(
+//!     policy, accessPolicy, publicKey, headerMetadata, authenticationData,
+//!     (processed, total) => progressBar.value = processed / total,
+//!     file.size,
+//! )
+//! const chunks = []
+//! for await (const chunk of file.stream()) {
+//!     chunks.push(encryptor.update(chunk))
+//! }
+//! chunks.push(encryptor.finalize())
+//! ```
+//!
+//! The output has the format of the encrypted files, see [`crate::file`]: it
+//! can be decrypted by the file decryption functions of the other interfaces.
+//!
+//! The progress callback is called with the number of plaintext bytes
+//! encrypted so far and the total length given upon construction, if any.
+
+use std::ops::Deref;
+
+use cosmian_cover_crypt::{abe_policy::AccessPolicy, Covercrypt, EncryptedHeader, MasterPublicKey};
+use cosmian_crypto_core::{
+    bytes_ser_de::Serializable,
+    reexport::{
+        aead::{stream::EncryptorBE32, Payload},
+        rand_core::SeedableRng,
+    },
+    Aes256Gcm, CsRng, DemStream, Instantiable, Nonce, RandomFixedSizeCBytes,
+};
+use js_sys::{Function, Uint8Array};
+use wasm_bindgen::prelude::*;
+
+use crate::{file::CHUNK_LENGTH, key_version::try_deserialize_any_version};
+
+type StreamEncryptor = EncryptorBE32<<Aes256Gcm as Deref>::Target>;
+
+/// Encrypts a file chunk by chunk, reporting the progress of the encryption.
+#[wasm_bindgen]
+pub struct HybridStreamEncryptor {
+    /// `None` once finalized.
+    encryptor: Option<StreamEncryptor>,
+    authentication_data: Vec<u8>,
+    /// Encrypted header and nonce, output before the first encrypted chunk.
+    prefix: Vec<u8>,
+    /// Plaintext bytes not encrypted yet, less than `CHUNK_LENGTH`.
+    buffer: Vec<u8>,
+    processed: f64,
+    total: Option<f64>,
+    on_progress: Option<Function>,
+}
+
+impl HybridStreamEncryptor {
+    /// Calls the progress callback, if any.
+    fn report_progress(&self) -> Result<(), JsValue> {
+        if let Some(on_progress) = &self.on_progress {
+            on_progress.call2(
+                &JsValue::NULL,
+                &JsValue::from_f64(self.processed),
+                &self.total.map_or(JsValue::UNDEFINED, JsValue::from_f64),
+            )?;
+        }
+        Ok(())
+    }
+
+    /// Returns the pending prefix followed by the given ciphertext.
+    fn output(&mut self, ciphertext: &[u8]) -> Uint8Array {
+        let mut output = std::mem::take(&mut self.prefix);
+        output.extend_from_slice(ciphertext);
+        Uint8Array::from(output.as_slice())
+    }
+}
+
+#[wasm_bindgen]
+impl HybridStreamEncryptor {
+    /// Generates the encrypted header of the file.
+    ///
+    /// - `policy_bytes`        : serialized policy
+    /// - `access_policy`       : access policy
+    /// - `pk`                  : CoverCrypt public key
+    /// - `header_metadata`     : additional data to symmetrically encrypt in
+    ///   the header
+    /// - `authentication_data` : optional data authenticating each chunk, to be
+    ///   given upon decryption
+    /// - `on_progress`         : optional callback called with the number of
+    ///   bytes encrypted so far and `total_length`
+    /// - `total_length`        : optional length of the plaintext, given to
+    ///   the progress callback
+    #[wasm_bindgen(constructor)]
+    #[allow(clippy::too_many_arguments)]
+    pub fn new(
+        policy_bytes: Vec<u8>,
+        access_policy: String,
+        pk: Uint8Array,
+        header_metadata: Uint8Array,
+        authentication_data: Uint8Array,
+        on_progress: Option<Function>,
+        total_length: Option<f64>,
+    ) -> Result<HybridStreamEncryptor, JsValue> {
+        let policy = wasm_unwrap!(
+            serde_json::from_slice(&policy_bytes),
+            "Error parsing policy"
+        );
+        let access_policy = wasm_unwrap!(
+            AccessPolicy::from_boolean_expression(&access_policy),
+            "Error reading access policy"
+        );
+        let pk = wasm_unwrap!(
+            try_deserialize_any_version::<MasterPublicKey>(&pk.to_vec()),
+            "Error parsing public key"
+        );
+        let header_metadata = if header_metadata.is_null() {
+            None
+        } else {
+            Some(header_metadata.to_vec())
+        };
+        let authentication_data = if authentication_data.is_null() {
+            None
+        } else {
+            Some(authentication_data.to_vec())
+        };
+
+        let (symmetric_key, encrypted_header) = wasm_unwrap!(
+            EncryptedHeader::generate(
+                &Covercrypt::default(),
+                &policy,
+                &pk,
+                &access_policy,
+                header_metadata.as_deref(),
+                authentication_data.as_deref(),
+            ),
+            "Error encrypting header"
+        );
+        let mut prefix = wasm_unwrap!(
+            encrypted_header.serialize(),
+            "Error serializing encrypted header"
+        )
+        .to_vec();
+
+        let nonce = Nonce::<{ Aes256Gcm::NONCE_LENGTH }>::new(&mut CsRng::from_entropy());
+        prefix.extend_from_slice(nonce.as_bytes());
+
+        Ok(Self {
+            encryptor: Some(Aes256Gcm::new(&symmetric_key).into_stream_encryptor_be32(&nonce)),
+            authentication_data: authentication_data.unwrap_or_default(),
+            prefix,
+            buffer: Vec::with_capacity(CHUNK_LENGTH),
+            processed: 0.0,
+            total: total_length,
+            on_progress,
+        })
+    }
+
+    /// Encrypts the given plaintext chunk, of any length, and returns the
+    /// encrypted bytes to append to the output. The first call also returns
+    /// the encrypted header.
+    pub fn update(&mut self, plaintext: Uint8Array) -> Result<Uint8Array, JsValue> {
+        let Some(encryptor) = self.encryptor.as_mut() else {
+            return Err(JsValue::from_str("The encryption is already finalized"));
+        };
+        self.buffer.extend(plaintext.to_vec());
+
+        let n_chunks = self.buffer.len() / CHUNK_LENGTH;
+        let mut ciphertext = Vec::with_capacity(n_chunks * (CHUNK_LENGTH + Aes256Gcm::MAC_LENGTH));
+        for chunk in self.buffer.chunks_exact(CHUNK_LENGTH) {
+            ciphertext.extend(wasm_unwrap!(
+                encryptor.encrypt_next(Payload {
+                    msg: chunk,
+                    aad: &self.authentication_data,
+                }),
+                "Error encrypting chunk"
+            ));
+        }
+        self.buffer.drain(..n_chunks * CHUNK_LENGTH);
+
+        if n_chunks > 0 {
+            self.processed += (n_chunks * CHUNK_LENGTH) as f64;
+            self.report_progress()?;
+        }
+        Ok(self.output(&ciphertext))
+    }
+
+    /// Encrypts the remaining plaintext and returns the last encrypted bytes
+    /// to append to the output. No more chunk can be encrypted afterwards.
+    pub fn finalize(&mut self) -> Result<Uint8Array, JsValue> {
+        let Some(encryptor) = self.encryptor.take() else {
+            return Err(JsValue::from_str("The encryption is already finalized"));
+        };
+        let ciphertext = wasm_unwrap!(
+            encryptor.encrypt_last(Payload {
+                msg: &self.buffer,
+                aad: &self.authentication_data,
+            }),
+            "Error encrypting last chunk"
+        );
+        self.processed += self.buffer.len() as f64;
+        self.buffer.clear();
+        self.report_progress()?;
+        Ok(self.output(&ciphertext))
+    }
+}
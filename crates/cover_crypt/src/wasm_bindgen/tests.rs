@@ -1,18 +1,45 @@
 use cosmian_cover_crypt::{
-    abe_policy::Policy, core::SYM_KEY_LENGTH, test_utils::policy, CleartextHeader, EncryptedHeader,
-    MasterPublicKey, MasterSecretKey, UserSecretKey,
+    abe_policy::{AccessPolicy, Policy},
+    core::SYM_KEY_LENGTH,
+    test_utils::policy,
+    CleartextHeader, Covercrypt, EncryptedHeader, MasterPublicKey, MasterSecretKey, UserSecretKey,
 };
 use cosmian_crypto_core::bytes_ser_de::{Deserializer, Serializable};
-use js_sys::{Object, Reflect, Uint8Array};
+use js_sys::{Array, Function, Object, Reflect, Uint8Array};
 use wasm_bindgen::{JsCast, JsValue};
 use wasm_bindgen_test::wasm_bindgen_test;
 
-use crate::wasm_bindgen::{
-    abe_policy::webassembly_rename_attribute,
-    generate_cc_keys::{webassembly_generate_master_keys, webassembly_generate_user_secret_key},
-    hybrid_cc_aes::{
-        webassembly_decrypt_hybrid_header, webassembly_encrypt_hybrid_header,
-        webassembly_hybrid_decrypt, webassembly_hybrid_encrypt, webassembly_split_encrypted_header,
+use crate::{
+    file::{decrypt_stream, CHUNK_LENGTH},
+    key_version::{serialize_versioned, try_deserialize_any_version},
+    wasm_bindgen::{
+        abe_policy::{
+            webassembly_is_post_quantum, webassembly_policy_from_json_spec,
+            webassembly_policy_migration_report, webassembly_remove_attribute,
+            webassembly_rename_attribute,
+        },
+        access_audit::{
+            webassembly_can_decrypt, webassembly_user_key_coordinates,
+            webassembly_user_key_rotations,
+        },
+        decryption_error::MAX_ERROR_MESSAGE_LENGTH,
+        estimation::{
+            webassembly_estimate_ciphertext_overhead, webassembly_estimate_master_key_sizes,
+            webassembly_estimate_user_secret_key_size,
+        },
+        generate_cc_keys::{
+            webassembly_generate_master_keys, webassembly_generate_user_secret_key,
+            webassembly_refresh_user_secret_key,
+            webassembly_refresh_user_secret_key_with_rotations, webassembly_rekey_all_master_keys,
+        },
+        hybrid_cc_aes::{
+            webassembly_decrypt_hybrid_header, webassembly_encrypt_hybrid_header,
+            webassembly_hybrid_decrypt, webassembly_hybrid_decrypt_with_cleartext_metadata,
+            webassembly_hybrid_encrypt, webassembly_hybrid_encrypt_with_cleartext_metadata,
+            webassembly_read_cleartext_metadata, webassembly_split_encrypted_header,
+        },
+        stream_encryption::HybridStreamEncryptor,
+        webcrypto::{webassembly_hybrid_decrypt_webcrypto, webassembly_hybrid_encrypt_webcrypto},
     },
 };
 
@@ -111,6 +138,7 @@ fn test_encrypt_decrypt() {
         Uint8Array::from(plaintext.as_bytes()),
         Uint8Array::from(header_metadata.as_slice()),
         Uint8Array::from(authentication_data.as_slice()),
+        None,
     )
     .unwrap();
 
@@ -133,6 +161,7 @@ fn test_encrypt_decrypt() {
             Uint8Array::from(usk.as_slice()),
             Uint8Array::from(enc_header.as_slice()),
             Uint8Array::from(authentication_data.as_slice()),
+            None,
         )
         .unwrap()
         .to_vec();
@@ -149,6 +178,7 @@ fn test_encrypt_decrypt() {
         Uint8Array::from(usk.as_slice()),
         res,
         Uint8Array::from(authentication_data.as_slice()),
+        None,
     )
     .unwrap()
     .to_vec();
@@ -161,6 +191,368 @@ fn test_encrypt_decrypt() {
     assert_eq!(header_metadata, decrypted_header_metadata);
 }
 
+#[wasm_bindgen_test]
+fn test_encrypt_decrypt_with_header_padding() {
+    let policy = policy().unwrap();
+    let policy_bytes = serde_json::to_vec(&policy).unwrap();
+    let master_keys = webassembly_generate_master_keys(policy_bytes.clone())
+        .unwrap()
+        .to_vec();
+    let msk_len = u32::from_be_bytes(<[u8; 4]>::try_from(&master_keys[..4]).unwrap()) as usize;
+    let usk = webassembly_generate_user_secret_key(
+        Uint8Array::from(&master_keys[4..msk_len + 4]),
+        "Department::FIN && Security Level::Top Secret",
+        policy_bytes.clone(),
+    )
+    .unwrap();
+
+    let plaintext = b"My secret message!";
+    let encrypt = |access_policy: &str| {
+        webassembly_hybrid_encrypt(
+            policy_bytes.clone(),
+            access_policy.to_string(),
+            Uint8Array::from(&master_keys[4 + msk_len..]),
+            Uint8Array::from(plaintext.as_slice()),
+            JsValue::NULL.unchecked_into(),
+            JsValue::NULL.unchecked_into(),
+            Some(4096),
+        )
+        .unwrap()
+    };
+    let narrow_ciphertext = encrypt("Department::FIN && Security Level::Low Secret");
+    let broad_ciphertext =
+        encrypt("(Department::FIN || Department::HR) && Security Level::Low Secret");
+
+    // The ciphertext length does not depend on the access policy.
+    assert_eq!(narrow_ciphertext.length(), broad_ciphertext.length());
+
+    let res = webassembly_hybrid_decrypt(
+        usk.clone(),
+        narrow_ciphertext.clone(),
+        JsValue::NULL.unchecked_into(),
+        Some(4096),
+    )
+    .unwrap()
+    .to_vec();
+    let mut de = Deserializer::new(res.as_slice());
+    assert!(de.read_vec().unwrap().is_empty());
+    assert_eq!(plaintext.as_slice(), de.finalize());
+
+    // The bucket size given upon encryption is needed to decrypt.
+    assert!(webassembly_hybrid_decrypt(
+        usk,
+        narrow_ciphertext,
+        JsValue::NULL.unchecked_into(),
+        None
+    )
+    .is_err());
+}
+
+#[wasm_bindgen_test]
+fn test_encrypt_decrypt_with_cleartext_metadata() {
+    let policy = policy().unwrap();
+    let access_policy_string = "Department::FIN && Security Level::Top Secret";
+
+    let policy_bytes = serde_json::to_vec(&policy).unwrap();
+    let master_keys = webassembly_generate_master_keys(policy_bytes.clone())
+        .unwrap()
+        .to_vec();
+    let msk_len = u32::from_be_bytes(<[u8; 4]>::try_from(&master_keys[..4]).unwrap()) as usize;
+    let usk = webassembly_generate_user_secret_key(
+        Uint8Array::from(&master_keys[4..msk_len + 4]),
+        access_policy_string,
+        policy_bytes.clone(),
+    )
+    .unwrap()
+    .to_vec();
+
+    let cleartext_metadata = b"application/json".to_vec();
+    let header_metadata = vec![1, 2, 3, 4, 5, 6, 7, 8, 9];
+    let authentication_data = vec![10, 11, 12, 13, 14];
+    let plaintext = "My secret message!";
+
+    let encrypted_bytes = webassembly_hybrid_encrypt_with_cleartext_metadata(
+        policy_bytes,
+        access_policy_string.to_string(),
+        Uint8Array::from(&master_keys[4 + msk_len..]),
+        Uint8Array::from(plaintext.as_bytes()),
+        Uint8Array::from(cleartext_metadata.as_slice()),
+        Uint8Array::from(header_metadata.as_slice()),
+        Uint8Array::from(authentication_data.as_slice()),
+    )
+    .unwrap();
+
+    // The cleartext metadata is readable without any key.
+    assert_eq!(
+        cleartext_metadata,
+        webassembly_read_cleartext_metadata(encrypted_bytes.clone())
+            .unwrap()
+            .to_vec()
+    );
+
+    let res = webassembly_hybrid_decrypt_with_cleartext_metadata(
+        Uint8Array::from(usk.as_slice()),
+        encrypted_bytes.clone(),
+        Uint8Array::from(authentication_data.as_slice()),
+    )
+    .unwrap()
+    .to_vec();
+
+    let mut de = Deserializer::new(res.as_slice());
+    let decrypted_header_metadata = de.read_vec().unwrap();
+    let decrypted_cleartext_metadata = de.read_vec().unwrap();
+    let decrypted_plaintext = de.finalize();
+
+    assert_eq!(plaintext.as_bytes(), decrypted_plaintext);
+    assert_eq!(header_metadata, decrypted_header_metadata);
+    assert_eq!(cleartext_metadata, decrypted_cleartext_metadata);
+
+    // Tampering with the cleartext metadata makes the decryption fail.
+    let mut tampered_bytes = encrypted_bytes.to_vec();
+    tampered_bytes[1] ^= 1;
+    assert!(webassembly_hybrid_decrypt_with_cleartext_metadata(
+        Uint8Array::from(usk.as_slice()),
+        Uint8Array::from(tampered_bytes.as_slice()),
+        Uint8Array::from(authentication_data.as_slice()),
+    )
+    .is_err());
+}
+
+#[wasm_bindgen_test]
+fn test_decryption_error_kinds() {
+    let policy = policy().unwrap();
+    let policy_bytes = serde_json::to_vec(&policy).unwrap();
+    let master_keys = webassembly_generate_master_keys(policy_bytes.clone())
+        .unwrap()
+        .to_vec();
+    let msk_len = u32::from_be_bytes(<[u8; 4]>::try_from(&master_keys[..4]).unwrap()) as usize;
+    let usk = webassembly_generate_user_secret_key(
+        Uint8Array::from(&master_keys[4..msk_len + 4]),
+        "Department::FIN && Security Level::Top Secret",
+        policy_bytes.clone(),
+    )
+    .unwrap()
+    .to_vec();
+    let other_usk = webassembly_generate_user_secret_key(
+        Uint8Array::from(&master_keys[4..msk_len + 4]),
+        "Department::MKG && Security Level::Low Secret",
+        policy_bytes.clone(),
+    )
+    .unwrap()
+    .to_vec();
+
+    let encrypted_bytes = webassembly_hybrid_encrypt(
+        policy_bytes,
+        "Department::FIN && Security Level::Top Secret".to_string(),
+        Uint8Array::from(&master_keys[4 + msk_len..]),
+        Uint8Array::from(b"My secret message!".as_slice()),
+        Uint8Array::from(b"header metadata".as_slice()),
+        Uint8Array::from(b"authentication data".as_slice()),
+        None,
+    )
+    .unwrap()
+    .to_vec();
+
+    let decryption_error_kind = |usk: &[u8], encrypted_bytes: &[u8]| {
+        let err = webassembly_hybrid_decrypt(
+            Uint8Array::from(usk),
+            Uint8Array::from(encrypted_bytes),
+            Uint8Array::from(b"authentication data".as_slice()),
+            None,
+        )
+        .unwrap_err();
+        let message = Reflect::get(&err, &"message".into())
+            .unwrap()
+            .as_string()
+            .unwrap();
+        assert!(message.len() <= MAX_ERROR_MESSAGE_LENGTH);
+        Reflect::get(&err, &"kind".into())
+            .unwrap()
+            .as_string()
+            .unwrap()
+    };
+
+    assert_eq!(
+        decryption_error_kind(&other_usk, &encrypted_bytes),
+        "InsufficientAccessPolicy"
+    );
+
+    let mut tampered_bytes = encrypted_bytes.clone();
+    *tampered_bytes.last_mut().unwrap() ^= 1;
+    assert_eq!(
+        decryption_error_kind(&usk, &tampered_bytes),
+        "IntegrityFailure"
+    );
+
+    assert_eq!(
+        decryption_error_kind(&usk, &encrypted_bytes[..10]),
+        "MalformedCiphertext"
+    );
+
+    assert_eq!(
+        decryption_error_kind(&usk[..usk.len() / 2], &encrypted_bytes),
+        "InvalidKey"
+    );
+}
+
+#[wasm_bindgen_test]
+async fn test_encrypt_decrypt_webcrypto() {
+    let policy = policy().unwrap();
+    let access_policy_string = "Department::FIN && Security Level::Top Secret";
+
+    let policy_bytes = serde_json::to_vec(&policy).unwrap();
+    let master_keys = webassembly_generate_master_keys(policy_bytes.clone())
+        .unwrap()
+        .to_vec();
+    let msk_len = u32::from_be_bytes(<[u8; 4]>::try_from(&master_keys[..4]).unwrap()) as usize;
+    let usk = webassembly_generate_user_secret_key(
+        Uint8Array::from(&master_keys[4..msk_len + 4]),
+        access_policy_string,
+        policy_bytes.clone(),
+    )
+    .unwrap()
+    .to_vec();
+
+    let header_metadata = vec![1, 2, 3, 4, 5, 6, 7, 8, 9];
+    let authentication_data = vec![10, 11, 12, 13, 14];
+    let plaintext = vec![42; 10_000];
+
+    let check = |res: Uint8Array| {
+        let res = res.to_vec();
+        let mut de = Deserializer::new(res.as_slice());
+        let decrypted_header_metadata = de.read_vec().unwrap();
+        let decrypted_plaintext = de.finalize();
+        assert_eq!(plaintext, decrypted_plaintext);
+        assert_eq!(header_metadata, decrypted_header_metadata);
+    };
+
+    // WebCrypto encryption is compatible with the pure WASM decryption.
+    let encrypted_bytes = webassembly_hybrid_encrypt_webcrypto(
+        policy_bytes.clone(),
+        access_policy_string.to_string(),
+        Uint8Array::from(&master_keys[4 + msk_len..]),
+        Uint8Array::from(plaintext.as_slice()),
+        Uint8Array::from(header_metadata.as_slice()),
+        Uint8Array::from(authentication_data.as_slice()),
+    )
+    .await
+    .unwrap();
+    check(
+        webassembly_hybrid_decrypt(
+            Uint8Array::from(usk.as_slice()),
+            encrypted_bytes.clone(),
+            Uint8Array::from(authentication_data.as_slice()),
+            None,
+        )
+        .unwrap(),
+    );
+    check(
+        webassembly_hybrid_decrypt_webcrypto(
+            Uint8Array::from(usk.as_slice()),
+            encrypted_bytes.clone(),
+            Uint8Array::from(authentication_data.as_slice()),
+        )
+        .await
+        .unwrap(),
+    );
+
+    // Pure WASM encryption is compatible with the WebCrypto decryption.
+    let encrypted_bytes = webassembly_hybrid_encrypt(
+        policy_bytes,
+        access_policy_string.to_string(),
+        Uint8Array::from(&master_keys[4 + msk_len..]),
+        Uint8Array::from(plaintext.as_slice()),
+        Uint8Array::from(header_metadata.as_slice()),
+        Uint8Array::from(authentication_data.as_slice()),
+        None,
+    )
+    .unwrap();
+    check(
+        webassembly_hybrid_decrypt_webcrypto(
+            Uint8Array::from(usk.as_slice()),
+            encrypted_bytes.clone(),
+            Uint8Array::from(authentication_data.as_slice()),
+        )
+        .await
+        .unwrap(),
+    );
+
+    // The authentication data is checked.
+    assert!(webassembly_hybrid_decrypt_webcrypto(
+        Uint8Array::from(usk.as_slice()),
+        encrypted_bytes,
+        Uint8Array::from(b"wrong".as_slice()),
+    )
+    .await
+    .is_err());
+}
+
+#[wasm_bindgen_test]
+fn test_stream_encryption() {
+    let policy = policy().unwrap();
+    let policy_bytes = serde_json::to_vec(&policy).unwrap();
+    let access_policy = "Department::FIN && Security Level::Top Secret";
+    let master_keys = webassembly_generate_master_keys(policy_bytes.clone())
+        .unwrap()
+        .to_vec();
+    let msk_len = u32::from_be_bytes(<[u8; 4]>::try_from(&master_keys[..4]).unwrap()) as usize;
+    let usk = webassembly_generate_user_secret_key(
+        Uint8Array::from(&master_keys[4..msk_len + 4]),
+        access_policy,
+        policy_bytes.clone(),
+    )
+    .unwrap()
+    .to_vec();
+
+    let header_metadata = vec![1, 2, 3];
+    let authentication_data = vec![4, 5, 6];
+    let plaintext = (0..2 * CHUNK_LENGTH + 100)
+        .map(|i| (i % 251) as u8)
+        .collect::<Vec<_>>();
+
+    // Records the progress events.
+    let progress = Array::new();
+    let on_progress = Function::new_with_args("processed, total", "this.push([processed, total])")
+        .bind0(&progress);
+
+    let mut encryptor = HybridStreamEncryptor::new(
+        policy_bytes,
+        access_policy.to_string(),
+        Uint8Array::from(&master_keys[4 + msk_len..]),
+        Uint8Array::from(header_metadata.as_slice()),
+        Uint8Array::from(authentication_data.as_slice()),
+        Some(on_progress),
+        Some(plaintext.len() as f64),
+    )
+    .unwrap();
+    let mut ciphertext = Vec::new();
+    for chunk in plaintext.chunks(CHUNK_LENGTH / 3 * 2) {
+        ciphertext.extend(encryptor.update(Uint8Array::from(chunk)).unwrap().to_vec());
+    }
+    ciphertext.extend(encryptor.finalize().unwrap().to_vec());
+    assert!(encryptor.finalize().is_err());
+
+    // The progress is reported each time chunks are encrypted.
+    let last_progress = Array::from(&progress.get(progress.length() - 1));
+    assert_eq!(progress.length(), 3);
+    assert_eq!(last_progress.get(0).as_f64(), Some(plaintext.len() as f64));
+    assert_eq!(last_progress.get(1).as_f64(), Some(plaintext.len() as f64));
+
+    // The output has the format of the encrypted files.
+    let usk = try_deserialize_any_version::<UserSecretKey>(&usk).unwrap();
+    let mut decrypted = Vec::new();
+    let decrypted_header_metadata = decrypt_stream(
+        &Covercrypt::default(),
+        &usk,
+        &mut ciphertext.as_slice(),
+        &mut decrypted,
+        Some(&authentication_data),
+    )
+    .unwrap();
+    assert_eq!(decrypted, plaintext);
+    assert_eq!(decrypted_header_metadata, Some(header_metadata));
+}
+
 #[wasm_bindgen_test]
 fn test_generate_keys() {
     //
@@ -178,8 +570,8 @@ fn test_generate_keys() {
 
     //
     // Check deserialization
-    MasterSecretKey::deserialize(msk_bytes).unwrap();
-    MasterPublicKey::deserialize(&master_keys_vec[4 + msk_size..]).unwrap();
+    try_deserialize_any_version::<MasterSecretKey>(msk_bytes).unwrap();
+    try_deserialize_any_version::<MasterPublicKey>(&master_keys_vec[4 + msk_size..]).unwrap();
 
     //
     // Generate user secret key
@@ -190,7 +582,7 @@ fn test_generate_keys() {
     )
     .unwrap()
     .to_vec();
-    let usk = UserSecretKey::deserialize(&usk_bytes).unwrap();
+    let usk = try_deserialize_any_version::<UserSecretKey>(&usk_bytes).unwrap();
 
     //
     // Rename attribute `Department::FIN` -> `Department::Finance`
@@ -202,15 +594,37 @@ fn test_generate_keys() {
     .unwrap();
     let new_policy = serde_json::from_slice(&new_policy_bytes).unwrap();
 
+    //
+    // Renaming an attribute changes no coordinate, removing one removes its
+    // coordinates
+    let report: serde_json::Value = serde_json::from_str(
+        &webassembly_policy_migration_report(policy_bytes.clone(), new_policy_bytes.clone())
+            .unwrap(),
+    )
+    .unwrap();
+    assert_eq!(report["requires_user_key_refresh"], false);
+    let report: serde_json::Value = serde_json::from_str(
+        &webassembly_policy_migration_report(
+            new_policy_bytes.clone(),
+            webassembly_remove_attribute(new_policy_bytes.clone(), "Department::HR".to_string())
+                .unwrap(),
+        )
+        .unwrap(),
+    )
+    .unwrap();
+    assert_eq!(report["removed_coordinates"].as_array().unwrap().len(), 5);
+    assert_eq!(report["has_unreadable_ciphertexts"], true);
+
     //
     // Generate master keys
     let master_keys = webassembly_generate_master_keys(new_policy_bytes.clone()).unwrap();
     let master_keys_vec = master_keys.to_vec();
     let secret_key_size = u32::from_be_bytes(master_keys_vec[..4].try_into().unwrap()) as usize;
     let secret_key_bytes = &master_keys_vec[4..4 + secret_key_size];
-    MasterSecretKey::deserialize(secret_key_bytes).unwrap();
+    try_deserialize_any_version::<MasterSecretKey>(secret_key_bytes).unwrap();
     let master_public_key =
-        MasterPublicKey::deserialize(&master_keys_vec[4 + secret_key_size..]).unwrap();
+        try_deserialize_any_version::<MasterPublicKey>(&master_keys_vec[4 + secret_key_size..])
+            .unwrap();
 
     //
     // Encrypt / decrypt
@@ -242,10 +656,263 @@ fn test_generate_keys() {
     )
     .unwrap()
     .to_vec();
-    let usk = UserSecretKey::deserialize(&usk_bytes).unwrap();
+    let usk = try_deserialize_any_version::<UserSecretKey>(&usk_bytes).unwrap();
 
     //
     // Decrypt with the refreshed secret key (it now works)
     //
     decrypt_header(&encrypted_header, &usk, &authentication_data).unwrap();
 }
+
+#[wasm_bindgen_test]
+fn test_estimations() {
+    let policy = policy().unwrap();
+    let access_policy_string = "Department::FIN && Security Level::Top Secret";
+    let policy_bytes = serde_json::to_vec(&policy).unwrap();
+
+    let master_keys = webassembly_generate_master_keys(policy_bytes.clone())
+        .unwrap()
+        .to_vec();
+    let msk_len = u32::from_be_bytes(<[u8; 4]>::try_from(&master_keys[..4]).unwrap()) as usize;
+    let sizes = JsValue::from(webassembly_estimate_master_key_sizes(policy_bytes.clone()).unwrap());
+    let msk_size = Reflect::get(&sizes, &JsValue::from_str("masterSecretKey"))
+        .unwrap()
+        .as_f64()
+        .unwrap() as usize;
+    let mpk_size = Reflect::get(&sizes, &JsValue::from_str("masterPublicKey"))
+        .unwrap()
+        .as_f64()
+        .unwrap() as usize;
+    assert_eq!(msk_size, msk_len);
+    assert_eq!(mpk_size, master_keys.len() - 4 - msk_len);
+
+    let usk = webassembly_generate_user_secret_key(
+        Uint8Array::from(&master_keys[4..msk_len + 4]),
+        access_policy_string,
+        policy_bytes.clone(),
+    )
+    .unwrap();
+    assert_eq!(
+        webassembly_estimate_user_secret_key_size(policy_bytes.clone(), access_policy_string)
+            .unwrap(),
+        usk.length() as usize
+    );
+
+    // Without header metadata, the hybrid ciphertext matches the estimation.
+    let plaintext = b"plaintext";
+    let ciphertext = webassembly_hybrid_encrypt(
+        policy_bytes.clone(),
+        access_policy_string.to_string(),
+        Uint8Array::from(&master_keys[4 + msk_len..]),
+        Uint8Array::from(plaintext.as_slice()),
+        JsValue::NULL.unchecked_into(),
+        JsValue::NULL.unchecked_into(),
+        None,
+    )
+    .unwrap();
+    assert_eq!(
+        ciphertext.length() as usize,
+        plaintext.len()
+            + webassembly_estimate_ciphertext_overhead(
+                policy_bytes,
+                access_policy_string.to_string()
+            )
+            .unwrap()
+    );
+}
+
+#[wasm_bindgen_test]
+fn test_is_post_quantum() {
+    let policy_bytes = webassembly_policy_from_json_spec(
+        r#"{
+            "Security Level::<": ["Protected", "Confidential", "Top Secret::+"],
+            "Department": ["HR", "FIN"]
+        }"#
+        .to_string(),
+    )
+    .unwrap();
+    assert!(
+        webassembly_is_post_quantum(policy_bytes.clone(), "Security Level::Top Secret").unwrap()
+    );
+    assert!(!webassembly_is_post_quantum(policy_bytes, "Department::FIN").unwrap());
+}
+
+#[wasm_bindgen_test]
+fn test_user_key_access_audit() {
+    let policy = policy().unwrap();
+    let policy_bytes: Vec<u8> = (&policy).try_into().unwrap();
+    let cover_crypt = Covercrypt::default();
+    let (msk, mpk) = cover_crypt.generate_master_keys(&policy).unwrap();
+    let usk = cover_crypt
+        .generate_user_secret_key(
+            &msk,
+            &AccessPolicy::from_boolean_expression("Department::FIN && Security Level::Protected")
+                .unwrap(),
+            &policy,
+        )
+        .unwrap();
+    let usk_bytes = Uint8Array::from(serialize_versioned(&usk).unwrap().as_slice());
+    let mpk_bytes = Uint8Array::from(serialize_versioned(&mpk).unwrap().as_slice());
+
+    let coordinates = webassembly_user_key_coordinates(
+        usk_bytes.clone(),
+        mpk_bytes.clone(),
+        policy_bytes.clone(),
+    )
+    .unwrap();
+    assert_eq!(coordinates.length(), 1);
+    assert_eq!(
+        coordinates.get(0).as_string().unwrap(),
+        "Department::FIN && Security Level::Protected"
+    );
+
+    assert!(webassembly_can_decrypt(
+        usk_bytes.clone(),
+        mpk_bytes.clone(),
+        "Department::FIN",
+        policy_bytes.clone()
+    )
+    .unwrap());
+    assert!(
+        !webassembly_can_decrypt(usk_bytes, mpk_bytes, "Department::HR", policy_bytes).unwrap()
+    );
+}
+
+#[wasm_bindgen_test]
+fn test_refresh_user_secret_key() {
+    let policy = policy().unwrap();
+    let access_policy = "Department::FIN && Security Level::Low Secret";
+    let cover_crypt = Covercrypt::default();
+    let (mut msk, mut mpk) = cover_crypt.generate_master_keys(&policy).unwrap();
+    let usk = cover_crypt
+        .generate_user_secret_key(
+            &msk,
+            &AccessPolicy::from_boolean_expression(access_policy).unwrap(),
+            &policy,
+        )
+        .unwrap();
+    let old_header = encrypt_header(&policy, access_policy.to_string(), &mpk, &[], &[]).unwrap();
+
+    // Rotate the `Department::FIN` partitions.
+    cover_crypt
+        .rekey_master_keys(
+            &AccessPolicy::from_boolean_expression("Department::FIN").unwrap(),
+            &policy,
+            &mut msk,
+            &mut mpk,
+        )
+        .unwrap();
+    let new_header = encrypt_header(&policy, access_policy.to_string(), &mpk, &[], &[]).unwrap();
+    assert!(decrypt_header(&new_header, &usk, &[]).is_err());
+
+    let refresh = |keep_old_accesses: bool| {
+        let usk_bytes = webassembly_refresh_user_secret_key(
+            Uint8Array::from(serialize_versioned(&usk).unwrap().as_slice()),
+            Uint8Array::from(serialize_versioned(&msk).unwrap().as_slice()),
+            keep_old_accesses,
+        )
+        .unwrap()
+        .to_vec();
+        try_deserialize_any_version::<UserSecretKey>(&usk_bytes).unwrap()
+    };
+
+    let refreshed_usk = refresh(true);
+    decrypt_header(&new_header, &refreshed_usk, &[]).unwrap();
+    decrypt_header(&old_header, &refreshed_usk, &[]).unwrap();
+
+    let refreshed_usk = refresh(false);
+    decrypt_header(&new_header, &refreshed_usk, &[]).unwrap();
+    assert!(decrypt_header(&old_header, &refreshed_usk, &[]).is_err());
+}
+
+#[wasm_bindgen_test]
+fn test_refresh_user_secret_key_with_rotations() {
+    let policy = policy().unwrap();
+    let policy_bytes = serde_json::to_vec(&policy).unwrap();
+    let access_policy = "Department::FIN && Security Level::Low Secret";
+    let cover_crypt = Covercrypt::default();
+    let (mut msk, mut mpk) = cover_crypt.generate_master_keys(&policy).unwrap();
+    let usk = cover_crypt
+        .generate_user_secret_key(
+            &msk,
+            &AccessPolicy::from_boolean_expression(access_policy).unwrap(),
+            &policy,
+        )
+        .unwrap();
+
+    // Rotate the `Department::FIN` partitions twice.
+    let mut headers = Vec::new();
+    for _ in 0..2 {
+        headers.push(encrypt_header(&policy, access_policy.to_string(), &mpk, &[], &[]).unwrap());
+        cover_crypt
+            .rekey_master_keys(
+                &AccessPolicy::from_boolean_expression("Department::FIN").unwrap(),
+                &policy,
+                &mut msk,
+                &mut mpk,
+            )
+            .unwrap();
+    }
+    headers.push(encrypt_header(&policy, access_policy.to_string(), &mpk, &[], &[]).unwrap());
+
+    let usk_bytes = webassembly_refresh_user_secret_key_with_rotations(
+        Uint8Array::from(serialize_versioned(&usk).unwrap().as_slice()),
+        Uint8Array::from(serialize_versioned(&msk).unwrap().as_slice()),
+        1,
+    )
+    .unwrap();
+    let refreshed_usk = try_deserialize_any_version::<UserSecretKey>(&usk_bytes.to_vec()).unwrap();
+    assert!(decrypt_header(&headers[0], &refreshed_usk, &[]).is_err());
+    decrypt_header(&headers[1], &refreshed_usk, &[]).unwrap();
+    decrypt_header(&headers[2], &refreshed_usk, &[]).unwrap();
+
+    // Security Level is hierarchical: the user key holds two coordinates.
+    let rotations = webassembly_user_key_rotations(usk_bytes, policy_bytes).unwrap();
+    assert_eq!(rotations.size(), 2);
+    for coordinate in [
+        "Department::FIN && Security Level::Low Secret",
+        "Department::FIN && Security Level::Protected",
+    ] {
+        assert_eq!(
+            rotations.get(&JsValue::from_str(coordinate)).as_f64(),
+            Some(2.0)
+        );
+    }
+}
+
+#[wasm_bindgen_test]
+fn test_rekey_all_master_keys() {
+    let policy = policy().unwrap();
+    let policy_bytes = serde_json::to_vec(&policy).unwrap();
+    let access_policy = "Department::HR && Security Level::Protected";
+    let cover_crypt = Covercrypt::default();
+    let (msk, mpk) = cover_crypt.generate_master_keys(&policy).unwrap();
+    let mut usk = cover_crypt
+        .generate_user_secret_key(
+            &msk,
+            &AccessPolicy::from_boolean_expression(access_policy).unwrap(),
+            &policy,
+        )
+        .unwrap();
+    let old_header = encrypt_header(&policy, access_policy.to_string(), &mpk, &[], &[]).unwrap();
+
+    let master_keys = webassembly_rekey_all_master_keys(
+        Uint8Array::from(serialize_versioned(&msk).unwrap().as_slice()),
+        Uint8Array::from(serialize_versioned(&mpk).unwrap().as_slice()),
+        policy_bytes,
+    )
+    .unwrap()
+    .to_vec();
+    let msk_len = u32::from_be_bytes(<[u8; 4]>::try_from(&master_keys[..4]).unwrap()) as usize;
+    let msk = try_deserialize_any_version::<MasterSecretKey>(&master_keys[4..msk_len + 4]).unwrap();
+    let mpk = try_deserialize_any_version::<MasterPublicKey>(&master_keys[msk_len + 4..]).unwrap();
+
+    // Every partition was rekeyed: the user key must be refreshed.
+    let new_header = encrypt_header(&policy, access_policy.to_string(), &mpk, &[], &[]).unwrap();
+    assert!(decrypt_header(&new_header, &usk, &[]).is_err());
+    cover_crypt
+        .refresh_user_secret_key(&mut usk, &msk, true)
+        .unwrap();
+    decrypt_header(&new_header, &usk, &[]).unwrap();
+    decrypt_header(&old_header, &usk, &[]).unwrap();
+}
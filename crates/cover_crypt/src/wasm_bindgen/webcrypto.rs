@@ -0,0 +1,278 @@
+//! Hybrid encryption delegating the DEM to the WebCrypto API.
+//!
+//! Header operations are performed in Rust while the bulk AES-GCM encryption
+//! is performed by `crypto.subtle`, which is hardware accelerated in browsers.
+//! Plaintexts and ciphertexts are never copied into the WASM memory.
+//!
+//! The produced ciphertexts are the same as the ones produced by the pure WASM
+//! functions: `webassembly_hybrid_encrypt_webcrypto` and
+//! `webassembly_hybrid_encrypt` outputs can be decrypted by either
+//! `webassembly_hybrid_decrypt_webcrypto` or `webassembly_hybrid_decrypt`.
+
+use cosmian_cover_crypt::{
+    abe_policy::AccessPolicy, Covercrypt, EncryptedHeader, MasterPublicKey, UserSecretKey,
+};
+use cosmian_crypto_core::{
+    bytes_ser_de::{Deserializer, Serializable, Serializer},
+    reexport::rand_core::SeedableRng,
+    Aes256Gcm, CsRng, FixedSizeCBytes, Nonce, RandomFixedSizeCBytes,
+};
+use js_sys::{Array, Function, Object, Promise, Reflect, Uint8Array};
+use wasm_bindgen::{prelude::*, JsCast};
+use wasm_bindgen_futures::JsFuture;
+
+use super::decryption_error::{decryption_error, DecryptionErrorKind};
+use crate::key_version::try_deserialize_any_version;
+
+/// Size of the first chunk read when looking for the end of the encrypted
+/// header. It is doubled until the header can be read.
+const HEADER_CHUNK_LENGTH: u32 = 4096;
+
+fn get_property(value: &JsValue, property: &str) -> Result<JsValue, JsValue> {
+    Reflect::get(value, &JsValue::from_str(property))
+}
+
+/// Calls the given method of the given object and awaits the returned
+/// promise.
+async fn call_async_method(
+    object: &JsValue,
+    method: &str,
+    args: &Array,
+) -> Result<JsValue, JsValue> {
+    let promise = get_property(object, method)?
+        .dyn_into::<Function>()?
+        .apply(object, args)?
+        .dyn_into::<Promise>()?;
+    JsFuture::from(promise).await
+}
+
+/// Imports the given raw key as an AES-GCM `CryptoKey` allowed for the given
+/// usage.
+async fn import_key(subtle: &JsValue, key: &[u8], usage: &str) -> Result<JsValue, JsValue> {
+    let algorithm = Object::new();
+    Reflect::set(&algorithm, &"name".into(), &"AES-GCM".into())?;
+    let args = Array::new();
+    args.push(&"raw".into());
+    args.push(&Uint8Array::from(key));
+    args.push(&algorithm);
+    args.push(&JsValue::FALSE);
+    args.push(&Array::of1(&usage.into()));
+    call_async_method(subtle, "importKey", &args).await
+}
+
+/// Encrypts or decrypts the given data using `crypto.subtle`.
+async fn subtle_aes_gcm(
+    method: &str,
+    key: &[u8],
+    nonce: &Uint8Array,
+    data: &Uint8Array,
+    authentication_data: Option<&Uint8Array>,
+) -> Result<Uint8Array, JsValue> {
+    let subtle = get_property(&get_property(&js_sys::global(), "crypto")?, "subtle")?;
+    if subtle.is_undefined() {
+        return Err(JsValue::from_str("WebCrypto API is not available"));
+    }
+    let key = import_key(&subtle, key, method).await?;
+
+    let params = Object::new();
+    Reflect::set(&params, &"name".into(), &"AES-GCM".into())?;
+    Reflect::set(&params, &"iv".into(), nonce)?;
+    Reflect::set(
+        &params,
+        &"tagLength".into(),
+        &((Aes256Gcm::MAC_LENGTH * 8) as u32).into(),
+    )?;
+    if let Some(authentication_data) = authentication_data {
+        Reflect::set(&params, &"additionalData".into(), authentication_data)?;
+    }
+
+    let res = call_async_method(&subtle, method, &Array::of3(&params, &key, data)).await?;
+    Ok(Uint8Array::new(&res))
+}
+
+/// Concatenates the given arrays into a new JS array.
+fn concat(arrays: &[&Uint8Array]) -> Uint8Array {
+    let res = Uint8Array::new_with_length(arrays.iter().map(|array| array.length()).sum());
+    let mut offset = 0;
+    for array in arrays {
+        res.set(array, offset);
+        offset += array.length();
+    }
+    res
+}
+
+/// Reads the encrypted header at the beginning of the given bytes, and
+/// returns it along with its length, only copying the bytes needed into the
+/// WASM memory.
+fn read_encrypted_header(encrypted_bytes: &Uint8Array) -> Result<(EncryptedHeader, u32), JsValue> {
+    let mut chunk_length = HEADER_CHUNK_LENGTH.min(encrypted_bytes.length());
+    loop {
+        let chunk = encrypted_bytes.subarray(0, chunk_length).to_vec();
+        let mut de = Deserializer::new(&chunk);
+        match de.read::<EncryptedHeader>() {
+            Ok(header) => {
+                let header_length = chunk_length - de.finalize().len() as u32;
+                return Ok((header, header_length));
+            }
+            Err(e) if chunk_length == encrypted_bytes.length() => {
+                return Err(decryption_error(
+                    DecryptionErrorKind::MalformedCiphertext,
+                    format_args!("Error deserializing encrypted header: {e:?}"),
+                ));
+            }
+            Err(_) => chunk_length = (2 * chunk_length).min(encrypted_bytes.length()),
+        }
+    }
+}
+
+fn optional_array(array: Uint8Array) -> Option<Uint8Array> {
+    if array.is_null() || array.is_undefined() {
+        None
+    } else {
+        Some(array)
+    }
+}
+
+/// Hybrid encrypts the given plaintext, delegating the DEM encryption to
+/// `crypto.subtle`.
+///
+/// Same parameters and output as `webassembly_hybrid_encrypt`.
+#[wasm_bindgen]
+pub async fn webassembly_hybrid_encrypt_webcrypto(
+    policy_bytes: Vec<u8>,
+    access_policy: String,
+    pk: Uint8Array,
+    plaintext: Uint8Array,
+    header_metadata: Uint8Array,
+    authentication_data: Uint8Array,
+) -> Result<Uint8Array, JsValue> {
+    let policy = wasm_unwrap!(
+        serde_json::from_slice(&policy_bytes),
+        "Error parsing policy"
+    );
+    let access_policy = wasm_unwrap!(
+        AccessPolicy::from_boolean_expression(&access_policy),
+        "Error reading access policy"
+    );
+    let pk = wasm_unwrap!(
+        try_deserialize_any_version::<MasterPublicKey>(&pk.to_vec()),
+        "Error parsing public key"
+    );
+    let header_metadata = optional_array(header_metadata).map(|array| array.to_vec());
+    let authentication_data = optional_array(authentication_data);
+
+    let (symmetric_key, encrypted_header) = wasm_unwrap!(
+        EncryptedHeader::generate(
+            &Covercrypt::default(),
+            &policy,
+            &pk,
+            &access_policy,
+            header_metadata.as_deref(),
+            authentication_data
+                .as_ref()
+                .map(Uint8Array::to_vec)
+                .as_deref(),
+        ),
+        "Error encrypting header"
+    );
+    let encrypted_header_bytes = wasm_unwrap!(
+        encrypted_header.serialize(),
+        "Error serializing encrypted header"
+    );
+
+    let nonce = Nonce::<{ Aes256Gcm::NONCE_LENGTH }>::new(&mut CsRng::from_entropy());
+    let nonce = Uint8Array::from(nonce.as_bytes());
+    let ciphertext = subtle_aes_gcm(
+        "encrypt",
+        &symmetric_key.to_bytes(),
+        &nonce,
+        &plaintext,
+        authentication_data.as_ref(),
+    )
+    .await?;
+
+    Ok(concat(&[
+        &Uint8Array::from(encrypted_header_bytes.as_slice()),
+        &nonce,
+        &ciphertext,
+    ]))
+}
+
+/// Hybrid decrypts the given ciphertext, delegating the DEM decryption to
+/// `crypto.subtle`.
+///
+/// Same parameters and output as `webassembly_hybrid_decrypt`. Throws a
+/// `DecryptionError` upon failure, unless the WebCrypto API is not available.
+#[wasm_bindgen]
+pub async fn webassembly_hybrid_decrypt_webcrypto(
+    usk_bytes: Uint8Array,
+    encrypted_bytes: Uint8Array,
+    authentication_data: Uint8Array,
+) -> Result<Uint8Array, JsValue> {
+    let usk = wasm_decryption_unwrap!(
+        try_deserialize_any_version::<UserSecretKey>(usk_bytes.to_vec().as_slice()),
+        DecryptionErrorKind::InvalidKey,
+        "Error deserializing user secret key"
+    );
+    let authentication_data = optional_array(authentication_data);
+
+    let (encrypted_header, header_length) = read_encrypted_header(&encrypted_bytes)?;
+    let cleartext_header = encrypted_header
+        .decrypt(
+            &Covercrypt::default(),
+            &usk,
+            authentication_data
+                .as_ref()
+                .map(Uint8Array::to_vec)
+                .as_deref(),
+        )
+        .map_err(|e| {
+            decryption_error(
+                DecryptionErrorKind::of_header(&encrypted_header, &usk),
+                format_args!("Error decrypting header: {e:?}"),
+            )
+        })?;
+
+    let nonce_end = header_length + Aes256Gcm::NONCE_LENGTH as u32;
+    if encrypted_bytes.length() < nonce_end + Aes256Gcm::MAC_LENGTH as u32 {
+        return Err(decryption_error(
+            DecryptionErrorKind::MalformedCiphertext,
+            "Error decrypting ciphertext: ciphertext too small",
+        ));
+    }
+    let plaintext = subtle_aes_gcm(
+        "decrypt",
+        &cleartext_header.symmetric_key.to_bytes(),
+        &encrypted_bytes.subarray(header_length, nonce_end),
+        &encrypted_bytes.subarray(nonce_end, encrypted_bytes.length()),
+        authentication_data.as_ref(),
+    )
+    .await
+    .map_err(|e| {
+        // `crypto.subtle` rejects with an `OperationError` upon authentication
+        // failure.
+        if get_property(&e, "name")
+            .ok()
+            .and_then(|name| name.as_string())
+            .as_deref()
+            == Some("OperationError")
+        {
+            decryption_error(
+                DecryptionErrorKind::IntegrityFailure,
+                format_args!("Error decrypting ciphertext: {e:?}"),
+            )
+        } else {
+            JsValue::from_str(&format!("Error decrypting ciphertext: {e:?}"))
+        }
+    })?;
+
+    let mut ser = Serializer::new();
+    wasm_unwrap!(
+        ser.write_vec(cleartext_header.metadata.unwrap_or_default().as_slice()),
+        "Cannot serialize the decrypted header metadata into response"
+    );
+    Ok(concat(&[
+        &Uint8Array::from(ser.finalize().as_slice()),
+        &plaintext,
+    ]))
+}
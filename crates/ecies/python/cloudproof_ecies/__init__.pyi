@@ -1,4 +1,80 @@
-from typing import Tuple
+from typing import Any, Optional, Tuple
+
+class Ecies:
+    """Use ECIES with an algorithm selected at runtime:
+    `x25519-xsalsa20-poly1305` (default) or `p256-aes128-gcm` for environments
+    restricted to NIST curves. The latter requires the `nist_curves` feature."""
+
+    @staticmethod
+    def generate_key_pair(algorithm: Optional[str] = None) -> Tuple[bytes, bytes]:
+        """
+        Generate a key pair for the given algorithm
+
+        Returns:
+            Tuple[bytes, bytes]: (public key, private key)
+        """
+    @staticmethod
+    def encryption_overhead(algorithm: Optional[str] = None) -> int:
+        """Size overhead of the ciphertexts of the given algorithm
+
+        Returns:
+            int
+        """
+    @staticmethod
+    def encrypt(
+        plaintext: bytes,
+        public_key_bytes: bytes,
+        authenticated_data: bytes,
+        algorithm: Optional[str] = None,
+    ) -> bytes:
+        """ECIES encryption with the given algorithm
+
+        Returns:
+            bytes
+        """
+    @staticmethod
+    def decrypt(
+        ciphertext: bytes,
+        private_key_bytes: bytes,
+        authenticated_data: bytes,
+        algorithm: Optional[str] = None,
+    ) -> bytes:
+        """ECIES decryption with the given algorithm
+
+        Returns:
+            bytes
+        """
+    @staticmethod
+    def envelope_encryption_overhead(algorithm: Optional[str] = None) -> int:
+        """Size overhead of the envelopes of the given algorithm
+
+        Returns:
+            int
+        """
+    @staticmethod
+    def envelope_encrypt(
+        plaintext: bytes,
+        public_key_bytes: bytes,
+        authenticated_data: bytes,
+        algorithm: Optional[str] = None,
+    ) -> bytes:
+        """ECIES encryption with the given algorithm, returning a versioned
+        envelope recording this algorithm
+
+        Returns:
+            bytes
+        """
+    @staticmethod
+    def envelope_decrypt(
+        envelope: bytes,
+        private_key_bytes: bytes,
+        authenticated_data: bytes,
+    ) -> bytes:
+        """ECIES decryption of an envelope, using the algorithm it records
+
+        Returns:
+            bytes
+        """
 
 class EciesSalsaSealBox:
     """Use Ecies scheme"""
@@ -27,3 +103,161 @@ class EciesSalsaSealBox:
         Returns:
             bytes
         """
+
+class EciesX25519Authenticated:
+    """Use ECIES with sender authentication: the sender static private key is
+    mixed into the key agreement so that the recipient can verify the origin of
+    the ciphertext."""
+
+    @staticmethod
+    def generate_key_pair() -> Tuple[bytes, bytes]:
+        """
+        Generate an X25519 key pair, usable as sender or recipient key pair
+
+        Returns:
+            Tuple[bytes, bytes]: (public key, private key)
+        """
+    @staticmethod
+    def encrypt(
+        plaintext: bytes,
+        sender_private_key_bytes: bytes,
+        recipient_public_key_bytes: bytes,
+        authenticated_data: bytes,
+    ) -> bytes:
+        """Authenticated ECIES encryption
+
+        Returns:
+            bytes
+        """
+    @staticmethod
+    def decrypt(
+        ciphertext: bytes,
+        recipient_private_key_bytes: bytes,
+        sender_public_key_bytes: bytes,
+        authenticated_data: bytes,
+    ) -> bytes:
+        """Authenticated ECIES decryption, fails if the ciphertext was not
+        encrypted by the owner of the given sender public key
+
+        Returns:
+            bytes
+        """
+
+class EciesX25519Kyber768:
+    """Use hybrid post-quantum ECIES: the key is derived from both an X25519
+    and a Kyber768 shared secret. Requires the `pq` feature.
+
+    The format is not stable yet: it will change when ML-KEM-768 replaces
+    the round 3 Kyber768 used, which is affected by KyberSlash. Do not use it
+    to protect long-lived data."""
+
+    @staticmethod
+    def generate_key_pair() -> Tuple[bytes, bytes]:
+        """
+        Generate an X25519 + Kyber768 key pair
+
+        Returns:
+            Tuple[bytes, bytes]: (public key, private key)
+        """
+    @staticmethod
+    def encrypt(
+        plaintext: bytes, public_key_bytes: bytes, authenticated_data: bytes
+    ) -> bytes:
+        """Hybrid ECIES encryption
+
+        Returns:
+            bytes
+        """
+    @staticmethod
+    def decrypt(
+        ciphertext: bytes, private_key_bytes: bytes, authenticated_data: bytes
+    ) -> bytes:
+        """Hybrid ECIES decryption
+
+        Returns:
+            bytes
+        """
+
+class X25519Mnemonic:
+    """Back up X25519 key pairs as BIP39 mnemonics"""
+
+    @staticmethod
+    def generate_key_pair() -> Tuple[str, bytes, bytes]:
+        """
+        Generate a 24-word BIP39 mnemonic and the X25519 key pair it backs up
+
+        Returns:
+            Tuple[str, bytes, bytes]: (mnemonic, public key, private key)
+        """
+    @staticmethod
+    def key_pair_from_mnemonic(mnemonic: str) -> Tuple[bytes, bytes]:
+        """
+        Derive the X25519 key pair backed up by the given BIP39 mnemonic
+
+        Returns:
+            Tuple[bytes, bytes]: (public key, private key)
+        """
+
+class PrivateKey:
+    """X25519 private key held in Rust memory and zeroized when garbage
+    collected or destroyed, instead of lingering in Python `bytes`. Can be used
+    as a context manager destroying the key on exit."""
+
+    @staticmethod
+    def generate() -> PrivateKey:
+        """
+        Generate a new private key
+
+        Returns:
+            PrivateKey
+        """
+    @staticmethod
+    def from_bytes(private_key: bytes) -> PrivateKey:
+        """
+        Read the private key from its raw bytes
+
+        Returns:
+            PrivateKey
+        """
+    @staticmethod
+    def from_pem(pem: str) -> PrivateKey:
+        """
+        Read the private key from a PKCS#8 PEM
+
+        Returns:
+            PrivateKey
+        """
+    def to_bytes(self) -> bytes:
+        """
+        Copy the raw bytes of the private key to Python
+
+        Returns:
+            bytes
+        """
+    def to_pem(self) -> str:
+        """
+        Export the private key as a PKCS#8 PEM, e.g. to store it in a keyring
+
+        Returns:
+            str
+        """
+    def public_key(self) -> bytes:
+        """
+        Public key of this private key
+
+        Returns:
+            bytes
+        """
+    def decrypt(self, ciphertext: bytes, authenticated_data: bytes) -> bytes:
+        """ECIES salsa seal box decryption with this private key
+
+        Returns:
+            bytes
+        """
+    def destroy(self) -> None:
+        """Zeroize the private key: any later use of this object fails"""
+    @property
+    def is_destroyed(self) -> bool:
+        """Whether the private key has been destroyed"""
+    def __enter__(self) -> PrivateKey: ...
+    def __exit__(self, exc_type: Any, exc_value: Any, traceback: Any) -> bool: ...
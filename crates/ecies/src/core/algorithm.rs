@@ -0,0 +1,298 @@
+use std::{fmt::Display, str::FromStr};
+
+use cosmian_crypto_core::{
+    reexport::rand_core::CryptoRngCore, CryptoCoreError, Ecies, EciesSalsaSealBox, FixedSizeCBytes,
+    X25519PrivateKey, X25519PublicKey,
+};
+#[cfg(feature = "nist_curves")]
+use cosmian_crypto_core::{EciesP256Aes128, P256PrivateKey, P256PublicKey};
+
+/// ECIES algorithms selectable at runtime.
+///
+/// Keys and ciphertexts are handled as bytes so that the algorithm can be
+/// chosen by the caller of the FFI, Python and WebAssembly interfaces.
+///
+/// The NIST P-256 variant is meant for environments restricted to NIST curves
+/// (e.g. FIPS) and requires the `nist_curves` feature.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum EciesAlgorithm {
+    /// X25519 sealed box with XSalsa20-Poly1305.
+    #[default]
+    X25519XSalsa20Poly1305,
+    /// NIST P-256 with AES128-GCM.
+    #[cfg(feature = "nist_curves")]
+    P256Aes128Gcm,
+}
+
+impl EciesAlgorithm {
+    const X25519_XSALSA20_POLY1305: &'static str = "x25519-xsalsa20-poly1305";
+    const P256_AES128_GCM: &'static str = "p256-aes128-gcm";
+
+    /// Identifier of the algorithm in an [`EciesEnvelope`](crate::EciesEnvelope).
+    ///
+    /// Identifiers are never reused: `3` is reserved for X25519 + Kyber768.
+    #[must_use]
+    pub const fn id(self) -> u8 {
+        match self {
+            Self::X25519XSalsa20Poly1305 => 1,
+            #[cfg(feature = "nist_curves")]
+            Self::P256Aes128Gcm => 2,
+        }
+    }
+
+    /// Returns the algorithm of the given envelope identifier.
+    pub fn from_id(id: u8) -> Result<Self, CryptoCoreError> {
+        match id {
+            1 => Ok(Self::X25519XSalsa20Poly1305),
+            #[cfg(feature = "nist_curves")]
+            2 => Ok(Self::P256Aes128Gcm),
+            #[cfg(not(feature = "nist_curves"))]
+            2 => Err(CryptoCoreError::ConversionError(format!(
+                "ECIES error: algorithm `{}` requires the `nist_curves` feature",
+                Self::P256_AES128_GCM
+            ))),
+            _ => Err(CryptoCoreError::ConversionError(format!(
+                "ECIES error: unknown algorithm identifier {id}"
+            ))),
+        }
+    }
+
+    /// Size overhead of the ciphertext compared to the plaintext.
+    #[must_use]
+    pub const fn encryption_overhead(self) -> usize {
+        match self {
+            Self::X25519XSalsa20Poly1305 => EciesSalsaSealBox::ENCRYPTION_OVERHEAD,
+            #[cfg(feature = "nist_curves")]
+            Self::P256Aes128Gcm => EciesP256Aes128::ENCRYPTION_OVERHEAD,
+        }
+    }
+
+    /// Length of the public keys. Ciphertexts start with an ephemeral public
+    /// key of this length.
+    #[must_use]
+    pub const fn public_key_length(self) -> usize {
+        match self {
+            Self::X25519XSalsa20Poly1305 => X25519PublicKey::LENGTH,
+            #[cfg(feature = "nist_curves")]
+            Self::P256Aes128Gcm => P256PublicKey::LENGTH,
+        }
+    }
+
+    /// Generates a new key pair.
+    ///
+    /// Returns the public key followed by the private key.
+    pub fn generate_key_pair(self, rng: &mut impl CryptoRngCore) -> (Vec<u8>, Vec<u8>) {
+        match self {
+            Self::X25519XSalsa20Poly1305 => {
+                let private_key = X25519PrivateKey::new(rng);
+                let public_key = X25519PublicKey::from(&private_key);
+                (
+                    public_key.to_bytes().to_vec(),
+                    private_key.to_bytes().to_vec(),
+                )
+            }
+            #[cfg(feature = "nist_curves")]
+            Self::P256Aes128Gcm => {
+                let private_key = P256PrivateKey::new(rng);
+                let public_key = P256PublicKey::from(&private_key);
+                (
+                    public_key.to_bytes().to_vec(),
+                    private_key.to_bytes().to_vec(),
+                )
+            }
+        }
+    }
+
+    /// Encrypts the given plaintext for the owner of the given public key.
+    pub fn encrypt(
+        self,
+        rng: &mut impl CryptoRngCore,
+        public_key: &[u8],
+        plaintext: &[u8],
+        authentication_data: Option<&[u8]>,
+    ) -> Result<Vec<u8>, CryptoCoreError> {
+        match self {
+            Self::X25519XSalsa20Poly1305 => EciesSalsaSealBox::encrypt(
+                rng,
+                &X25519PublicKey::try_from_slice(public_key)?,
+                plaintext,
+                authentication_data,
+            ),
+            #[cfg(feature = "nist_curves")]
+            Self::P256Aes128Gcm => EciesP256Aes128::encrypt(
+                rng,
+                &P256PublicKey::try_from_slice(public_key)?,
+                plaintext,
+                authentication_data,
+            ),
+        }
+    }
+
+    /// Decrypts the given ciphertext using the given private key.
+    pub fn decrypt(
+        self,
+        private_key: &[u8],
+        ciphertext: &[u8],
+        authentication_data: Option<&[u8]>,
+    ) -> Result<Vec<u8>, CryptoCoreError> {
+        match self {
+            Self::X25519XSalsa20Poly1305 => EciesSalsaSealBox::decrypt(
+                &X25519PrivateKey::try_from_slice(private_key)?,
+                ciphertext,
+                authentication_data,
+            ),
+            #[cfg(feature = "nist_curves")]
+            Self::P256Aes128Gcm => EciesP256Aes128::decrypt(
+                &P256PrivateKey::try_from_slice(private_key)?,
+                ciphertext,
+                authentication_data,
+            ),
+        }
+    }
+
+    /// Encrypts the given plaintext for the owner of the given public key.
+    ///
+    /// Returns the ephemeral public key and the ciphertext separately, for
+    /// storage formats keeping the key material apart from the data. Their
+    /// concatenation is the ciphertext returned by [`encrypt`](Self::encrypt).
+    pub fn encrypt_detached(
+        self,
+        rng: &mut impl CryptoRngCore,
+        public_key: &[u8],
+        plaintext: &[u8],
+        authentication_data: Option<&[u8]>,
+    ) -> Result<(Vec<u8>, Vec<u8>), CryptoCoreError> {
+        let mut ephemeral_public_key =
+            self.encrypt(rng, public_key, plaintext, authentication_data)?;
+        let ciphertext = ephemeral_public_key.split_off(self.public_key_length());
+        Ok((ephemeral_public_key, ciphertext))
+    }
+
+    /// Decrypts the given ciphertext, encrypted along with the given ephemeral
+    /// public key by [`encrypt_detached`](Self::encrypt_detached), using the
+    /// given private key.
+    pub fn decrypt_detached(
+        self,
+        private_key: &[u8],
+        ephemeral_public_key: &[u8],
+        ciphertext: &[u8],
+        authentication_data: Option<&[u8]>,
+    ) -> Result<Vec<u8>, CryptoCoreError> {
+        if ephemeral_public_key.len() != self.public_key_length() {
+            return Err(CryptoCoreError::InvalidBytesLength(
+                "ephemeral public key".to_string(),
+                ephemeral_public_key.len(),
+                Some(self.public_key_length()),
+            ));
+        }
+        self.decrypt(
+            private_key,
+            &[ephemeral_public_key, ciphertext].concat(),
+            authentication_data,
+        )
+    }
+}
+
+impl FromStr for EciesAlgorithm {
+    type Err = CryptoCoreError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            Self::X25519_XSALSA20_POLY1305 => Ok(Self::X25519XSalsa20Poly1305),
+            #[cfg(feature = "nist_curves")]
+            Self::P256_AES128_GCM => Ok(Self::P256Aes128Gcm),
+            #[cfg(not(feature = "nist_curves"))]
+            Self::P256_AES128_GCM => Err(CryptoCoreError::ConversionError(format!(
+                "ECIES error: algorithm `{s}` requires the `nist_curves` feature"
+            ))),
+            _ => Err(CryptoCoreError::ConversionError(format!(
+                "ECIES error: unknown algorithm `{s}`, expected `{}` or `{}`",
+                Self::X25519_XSALSA20_POLY1305,
+                Self::P256_AES128_GCM
+            ))),
+        }
+    }
+}
+
+impl Display for EciesAlgorithm {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::X25519XSalsa20Poly1305 => write!(f, "{}", Self::X25519_XSALSA20_POLY1305),
+            #[cfg(feature = "nist_curves")]
+            Self::P256Aes128Gcm => write!(f, "{}", Self::P256_AES128_GCM),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use cosmian_crypto_core::{reexport::rand_core::SeedableRng, CsRng};
+
+    use super::*;
+
+    fn check_algorithm(algorithm: EciesAlgorithm) -> Result<(), CryptoCoreError> {
+        let mut rng = CsRng::from_entropy();
+        let plaintext = b"plaintext";
+        let authentication_data = b"authentication data";
+
+        assert_eq!(algorithm.to_string().parse::<EciesAlgorithm>()?, algorithm);
+        assert_eq!(EciesAlgorithm::from_id(algorithm.id())?, algorithm);
+
+        let (public_key, private_key) = algorithm.generate_key_pair(&mut rng);
+        let ciphertext =
+            algorithm.encrypt(&mut rng, &public_key, plaintext, Some(authentication_data))?;
+        assert_eq!(
+            ciphertext.len(),
+            plaintext.len() + algorithm.encryption_overhead()
+        );
+        let decrypted = algorithm.decrypt(&private_key, &ciphertext, Some(authentication_data))?;
+        assert_eq!(decrypted, plaintext);
+
+        // The detached ephemeral public key prefixes the attached ciphertext.
+        let (ephemeral_public_key, detached_ciphertext) =
+            algorithm.encrypt_detached(&mut rng, &public_key, plaintext, None)?;
+        assert_eq!(ephemeral_public_key.len(), algorithm.public_key_length());
+        assert_eq!(
+            algorithm.decrypt_detached(
+                &private_key,
+                &ephemeral_public_key,
+                &detached_ciphertext,
+                None
+            )?,
+            plaintext
+        );
+        assert_eq!(
+            algorithm.decrypt(
+                &private_key,
+                &[ephemeral_public_key.as_slice(), &detached_ciphertext].concat(),
+                None
+            )?,
+            plaintext
+        );
+        assert!(algorithm
+            .decrypt_detached(&private_key, &[0; 8], &detached_ciphertext, None)
+            .is_err());
+
+        // Decryption fails with another key.
+        let (_, other_private_key) = algorithm.generate_key_pair(&mut rng);
+        assert!(algorithm
+            .decrypt(&other_private_key, &ciphertext, Some(authentication_data))
+            .is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_ecies_algorithms() -> Result<(), CryptoCoreError> {
+        assert_eq!(
+            EciesAlgorithm::default(),
+            EciesAlgorithm::X25519XSalsa20Poly1305
+        );
+        assert!("unknown".parse::<EciesAlgorithm>().is_err());
+        assert!(EciesAlgorithm::from_id(0).is_err());
+
+        check_algorithm(EciesAlgorithm::X25519XSalsa20Poly1305)?;
+        #[cfg(feature = "nist_curves")]
+        check_algorithm(EciesAlgorithm::P256Aes128Gcm)?;
+        Ok(())
+    }
+}
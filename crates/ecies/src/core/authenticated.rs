@@ -0,0 +1,215 @@
+use cosmian_crypto_core::{
+    kdf256, reexport::rand_core::CryptoRngCore, CryptoCoreError, Dem, Instantiable, Nonce,
+    SymmetricKey, X25519PrivateKey, X25519PublicKey, XChaCha20Poly1305, X25519_PUBLIC_KEY_LENGTH,
+};
+
+/// Authenticated ECIES based on X25519 and XChaCha20-Poly1305.
+///
+/// Contrary to a sealed box, the sender static private key is mixed into the
+/// key agreement: only the owner of the sender private key and the owner of
+/// the recipient private key can compute the encryption key. A successful
+/// decryption therefore proves the ciphertext was produced by the sender whose
+/// public key was given to [`Self::decrypt`].
+///
+/// __Algorithm details__
+///
+/// ```txt
+/// e, E = ephemeral key pair
+/// k    = SHAKE256(DH(e, R) || DH(s, R) || E || S || R)
+/// n    = SHAKE256(E || S || R)
+/// c    = E || XChaCha20-Poly1305(k, n, m, ad)
+/// ```
+///
+/// where `s, S` is the sender key pair and `R` the recipient public key.
+///
+/// This mode does not provide non-repudiation: the recipient can also forge
+/// ciphertexts that decrypt with the sender public key.
+pub struct EciesX25519Authenticated;
+
+/// Domain separation tags of the derived key and nonce.
+const KEY_TAG: &[u8] = b"ECIES X25519 authenticated key";
+const NONCE_TAG: &[u8] = b"ECIES X25519 authenticated nonce";
+
+impl EciesX25519Authenticated {
+    /// Size overhead of the ciphertext compared to the plaintext.
+    pub const ENCRYPTION_OVERHEAD: usize = X25519_PUBLIC_KEY_LENGTH + XChaCha20Poly1305::MAC_LENGTH;
+
+    /// Derives the symmetric key and nonce from the shared points and the
+    /// public keys of all the parties.
+    fn derive_key_and_nonce(
+        ephemeral_shared_point: &X25519PublicKey,
+        static_shared_point: &X25519PublicKey,
+        ephemeral_pk: &X25519PublicKey,
+        sender_pk: &X25519PublicKey,
+        recipient_pk: &X25519PublicKey,
+    ) -> Result<
+        (
+            SymmetricKey<{ XChaCha20Poly1305::KEY_LENGTH }>,
+            Nonce<{ XChaCha20Poly1305::NONCE_LENGTH }>,
+        ),
+        CryptoCoreError,
+    > {
+        // A null shared point means one of the public keys is of small order.
+        if ephemeral_shared_point.as_bytes() == &[0; X25519_PUBLIC_KEY_LENGTH]
+            || static_shared_point.as_bytes() == &[0; X25519_PUBLIC_KEY_LENGTH]
+        {
+            return Err(CryptoCoreError::ConversionError(
+                "ECIES error: invalid public key".to_string(),
+            ));
+        }
+
+        let mut key = SymmetricKey::default();
+        kdf256!(
+            &mut key,
+            KEY_TAG,
+            ephemeral_shared_point.as_bytes(),
+            static_shared_point.as_bytes(),
+            ephemeral_pk.as_bytes(),
+            sender_pk.as_bytes(),
+            recipient_pk.as_bytes()
+        );
+
+        let mut nonce = Nonce([0; XChaCha20Poly1305::NONCE_LENGTH]);
+        kdf256!(
+            &mut nonce.0,
+            NONCE_TAG,
+            ephemeral_pk.as_bytes(),
+            sender_pk.as_bytes(),
+            recipient_pk.as_bytes()
+        );
+
+        Ok((key, nonce))
+    }
+
+    /// Encrypts the given plaintext for the given recipient, authenticating
+    /// it with the sender private key.
+    ///
+    /// The optional authentication data is bound to the ciphertext and must
+    /// be given again upon decryption.
+    pub fn encrypt<R: CryptoRngCore>(
+        rng: &mut R,
+        sender_sk: &X25519PrivateKey,
+        recipient_pk: &X25519PublicKey,
+        plaintext: &[u8],
+        authentication_data: Option<&[u8]>,
+    ) -> Result<Vec<u8>, CryptoCoreError> {
+        let ephemeral_sk = X25519PrivateKey::new(rng);
+        let ephemeral_pk = X25519PublicKey::from(&ephemeral_sk);
+
+        let (key, nonce) = Self::derive_key_and_nonce(
+            &recipient_pk.dh(&ephemeral_sk),
+            &recipient_pk.dh(sender_sk),
+            &ephemeral_pk,
+            &X25519PublicKey::from(sender_sk),
+            recipient_pk,
+        )?;
+
+        let ciphertext_plus_tag =
+            XChaCha20Poly1305::new(&key).encrypt(&nonce, plaintext, authentication_data)?;
+
+        let mut res = Vec::with_capacity(X25519_PUBLIC_KEY_LENGTH + ciphertext_plus_tag.len());
+        res.extend(ephemeral_pk.to_bytes());
+        res.extend(ciphertext_plus_tag);
+        Ok(res)
+    }
+
+    /// Decrypts the given ciphertext and verifies it was produced by the owner
+    /// of the given sender public key.
+    ///
+    /// Decryption fails if the ciphertext was encrypted by another sender, for
+    /// another recipient or with other authentication data.
+    pub fn decrypt(
+        recipient_sk: &X25519PrivateKey,
+        sender_pk: &X25519PublicKey,
+        ciphertext: &[u8],
+        authentication_data: Option<&[u8]>,
+    ) -> Result<Vec<u8>, CryptoCoreError> {
+        if ciphertext.len() < Self::ENCRYPTION_OVERHEAD {
+            return Err(CryptoCoreError::CiphertextTooSmallError {
+                ciphertext_len: ciphertext.len(),
+                min: Self::ENCRYPTION_OVERHEAD as u64,
+            });
+        }
+        let ephemeral_pk =
+            X25519PublicKey::try_from_slice(&ciphertext[..X25519_PUBLIC_KEY_LENGTH])?;
+
+        let (key, nonce) = Self::derive_key_and_nonce(
+            &ephemeral_pk.dh(recipient_sk),
+            &sender_pk.dh(recipient_sk),
+            &ephemeral_pk,
+            sender_pk,
+            &X25519PublicKey::from(recipient_sk),
+        )?;
+
+        XChaCha20Poly1305::new(&key).decrypt(
+            &nonce,
+            &ciphertext[X25519_PUBLIC_KEY_LENGTH..],
+            authentication_data,
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use cosmian_crypto_core::{
+        reexport::rand_core::SeedableRng, CsRng, X25519PrivateKey, X25519PublicKey,
+    };
+
+    use super::EciesX25519Authenticated;
+
+    #[test]
+    fn test_authenticated_encryption() {
+        let mut rng = CsRng::from_entropy();
+        let sender_sk = X25519PrivateKey::new(&mut rng);
+        let sender_pk = X25519PublicKey::from(&sender_sk);
+        let recipient_sk = X25519PrivateKey::new(&mut rng);
+        let recipient_pk = X25519PublicKey::from(&recipient_sk);
+        let other_sk = X25519PrivateKey::new(&mut rng);
+        let other_pk = X25519PublicKey::from(&other_sk);
+
+        let plaintext = b"plaintext";
+        let ad = b"authenticated_data";
+
+        let ciphertext = EciesX25519Authenticated::encrypt(
+            &mut rng,
+            &sender_sk,
+            &recipient_pk,
+            plaintext,
+            Some(ad),
+        )
+        .unwrap();
+        assert_eq!(
+            ciphertext.len(),
+            plaintext.len() + EciesX25519Authenticated::ENCRYPTION_OVERHEAD
+        );
+
+        let cleartext =
+            EciesX25519Authenticated::decrypt(&recipient_sk, &sender_pk, &ciphertext, Some(ad))
+                .unwrap();
+        assert_eq!(plaintext.to_vec(), cleartext);
+
+        // Another sender public key does not verify.
+        assert!(
+            EciesX25519Authenticated::decrypt(&recipient_sk, &other_pk, &ciphertext, Some(ad))
+                .is_err()
+        );
+        // Another recipient cannot decrypt.
+        assert!(
+            EciesX25519Authenticated::decrypt(&other_sk, &sender_pk, &ciphertext, Some(ad))
+                .is_err()
+        );
+        // Authentication data must match.
+        assert!(
+            EciesX25519Authenticated::decrypt(&recipient_sk, &sender_pk, &ciphertext, None)
+                .is_err()
+        );
+        // Truncated ciphertexts are rejected.
+        assert!(EciesX25519Authenticated::decrypt(
+            &recipient_sk,
+            &sender_pk,
+            &ciphertext[..EciesX25519Authenticated::ENCRYPTION_OVERHEAD - 1],
+            Some(ad)
+        )
+        .is_err());
+    }
+}
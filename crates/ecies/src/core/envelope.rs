@@ -0,0 +1,235 @@
+use cosmian_crypto_core::{reexport::rand_core::CryptoRngCore, CryptoCoreError};
+
+use super::algorithm::EciesAlgorithm;
+
+/// Self-describing ECIES ciphertext.
+///
+/// Stored ciphertexts should be envelopes: the algorithm used is read from the
+/// envelope upon decryption, so that algorithms can be added without breaking
+/// the existing ciphertexts.
+///
+/// __Format__
+///
+/// ```txt
+/// envelope = magic (4 bytes) || version (1 byte) || algorithm id (1 byte)
+///            || u16_be(E.len()) || E || u8(n.len()) || n || c
+/// ```
+///
+/// where `E` is the ephemeral public key, `n` the nonce, empty for the
+/// algorithms deriving it from the keys, and `c` the ciphertext. The
+/// concatenation `E || n || c` is the ciphertext of the algorithm, see
+/// [`EciesAlgorithm::id`] for the algorithm identifiers.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EciesEnvelope {
+    pub algorithm: EciesAlgorithm,
+    pub ephemeral_public_key: Vec<u8>,
+    pub nonce: Vec<u8>,
+    pub ciphertext: Vec<u8>,
+}
+
+impl EciesEnvelope {
+    /// Magic bytes starting all the envelopes.
+    pub const MAGIC: [u8; 4] = *b"CMEC";
+
+    /// Current version of the envelope format.
+    pub const VERSION: u8 = 1;
+
+    /// Length of the envelope header, before the ephemeral public key.
+    const HEADER_LENGTH: usize = Self::MAGIC.len() + 1 + 1 + 2;
+
+    /// Returns the length of the envelope of a plaintext minus the length of
+    /// this plaintext, for the given algorithm.
+    #[must_use]
+    pub const fn encryption_overhead(algorithm: EciesAlgorithm) -> usize {
+        Self::HEADER_LENGTH + 1 + algorithm.encryption_overhead()
+    }
+
+    /// Wraps the given ciphertext of the given algorithm into an envelope,
+    /// e.g. to migrate the ciphertexts stored before the envelope format.
+    pub fn from_ciphertext(
+        algorithm: EciesAlgorithm,
+        mut ciphertext: Vec<u8>,
+    ) -> Result<Self, CryptoCoreError> {
+        if ciphertext.len() < algorithm.encryption_overhead() {
+            return Err(CryptoCoreError::InvalidBytesLength(
+                "ECIES ciphertext".to_string(),
+                ciphertext.len(),
+                Some(algorithm.encryption_overhead()),
+            ));
+        }
+        let ciphertext_tail = ciphertext.split_off(algorithm.public_key_length());
+        Ok(Self {
+            algorithm,
+            ephemeral_public_key: ciphertext,
+            nonce: Vec::new(),
+            ciphertext: ciphertext_tail,
+        })
+    }
+
+    /// Encrypts the given plaintext for the owner of the given public key.
+    pub fn encrypt(
+        algorithm: EciesAlgorithm,
+        rng: &mut impl CryptoRngCore,
+        public_key: &[u8],
+        plaintext: &[u8],
+        authentication_data: Option<&[u8]>,
+    ) -> Result<Self, CryptoCoreError> {
+        Self::from_ciphertext(
+            algorithm,
+            algorithm.encrypt(rng, public_key, plaintext, authentication_data)?,
+        )
+    }
+
+    /// Decrypts this envelope using the given private key.
+    pub fn decrypt(
+        &self,
+        private_key: &[u8],
+        authentication_data: Option<&[u8]>,
+    ) -> Result<Vec<u8>, CryptoCoreError> {
+        self.algorithm.decrypt(
+            private_key,
+            &[
+                self.ephemeral_public_key.as_slice(),
+                &self.nonce,
+                &self.ciphertext,
+            ]
+            .concat(),
+            authentication_data,
+        )
+    }
+
+    /// Serializes this envelope.
+    pub fn to_bytes(&self) -> Result<Vec<u8>, CryptoCoreError> {
+        let key_length = u16::try_from(self.ephemeral_public_key.len())
+            .map_err(|e| CryptoCoreError::ConversionError(e.to_string()))?;
+        let nonce_length = u8::try_from(self.nonce.len())
+            .map_err(|e| CryptoCoreError::ConversionError(e.to_string()))?;
+        let mut bytes = Vec::with_capacity(
+            Self::HEADER_LENGTH
+                + self.ephemeral_public_key.len()
+                + 1
+                + self.nonce.len()
+                + self.ciphertext.len(),
+        );
+        bytes.extend_from_slice(&Self::MAGIC);
+        bytes.push(Self::VERSION);
+        bytes.push(self.algorithm.id());
+        bytes.extend_from_slice(&key_length.to_be_bytes());
+        bytes.extend_from_slice(&self.ephemeral_public_key);
+        bytes.push(nonce_length);
+        bytes.extend_from_slice(&self.nonce);
+        bytes.extend_from_slice(&self.ciphertext);
+        Ok(bytes)
+    }
+
+    /// Parses the given envelope.
+    pub fn parse(bytes: &[u8]) -> Result<Self, CryptoCoreError> {
+        let truncated =
+            || CryptoCoreError::ConversionError("ECIES error: truncated envelope".to_string());
+
+        let (magic, bytes) = bytes.split_first_chunk::<4>().ok_or_else(truncated)?;
+        if *magic != Self::MAGIC {
+            return Err(CryptoCoreError::ConversionError(
+                "ECIES error: not an envelope".to_string(),
+            ));
+        }
+        let ([version, algorithm_id], bytes) =
+            bytes.split_first_chunk::<2>().ok_or_else(truncated)?;
+        if *version != Self::VERSION {
+            return Err(CryptoCoreError::ConversionError(format!(
+                "ECIES error: unsupported envelope version {version}"
+            )));
+        }
+        let algorithm = EciesAlgorithm::from_id(*algorithm_id)?;
+
+        let (key_length, bytes) = bytes.split_first_chunk::<2>().ok_or_else(truncated)?;
+        let key_length = usize::from(u16::from_be_bytes(*key_length));
+        if key_length != algorithm.public_key_length() {
+            return Err(CryptoCoreError::InvalidBytesLength(
+                "ephemeral public key".to_string(),
+                key_length,
+                Some(algorithm.public_key_length()),
+            ));
+        }
+        let (ephemeral_public_key, bytes) =
+            bytes.split_at_checked(key_length).ok_or_else(truncated)?;
+        let (nonce_length, bytes) = bytes.split_first().ok_or_else(truncated)?;
+        let (nonce, ciphertext) = bytes
+            .split_at_checked(usize::from(*nonce_length))
+            .ok_or_else(truncated)?;
+
+        Ok(Self {
+            algorithm,
+            ephemeral_public_key: ephemeral_public_key.to_vec(),
+            nonce: nonce.to_vec(),
+            ciphertext: ciphertext.to_vec(),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use cosmian_crypto_core::{reexport::rand_core::SeedableRng, CsRng};
+
+    use super::*;
+
+    fn check_envelope(algorithm: EciesAlgorithm) -> Result<(), CryptoCoreError> {
+        let mut rng = CsRng::from_entropy();
+        let (public_key, private_key) = algorithm.generate_key_pair(&mut rng);
+
+        let envelope = EciesEnvelope::encrypt(
+            algorithm,
+            &mut rng,
+            &public_key,
+            b"plaintext",
+            Some(b"authentication data"),
+        )?;
+        let bytes = envelope.to_bytes()?;
+        assert_eq!(bytes[..4], EciesEnvelope::MAGIC);
+        assert_eq!(bytes[5], algorithm.id());
+        assert_eq!(
+            bytes.len(),
+            b"plaintext".len() + EciesEnvelope::encryption_overhead(algorithm)
+        );
+
+        let parsed = EciesEnvelope::parse(&bytes)?;
+        assert_eq!(parsed, envelope);
+        assert_eq!(
+            parsed.decrypt(&private_key, Some(b"authentication data"))?,
+            b"plaintext"
+        );
+
+        // Ciphertexts produced without envelope can be wrapped.
+        let ciphertext = algorithm.encrypt(&mut rng, &public_key, b"plaintext", None)?;
+        let envelope = EciesEnvelope::from_ciphertext(algorithm, ciphertext)?;
+        let parsed = EciesEnvelope::parse(&envelope.to_bytes()?)?;
+        assert_eq!(parsed.decrypt(&private_key, None)?, b"plaintext");
+
+        // Any truncation is detected.
+        for length in 0..algorithm.public_key_length() + 9 {
+            assert!(EciesEnvelope::parse(&bytes[..length]).is_err());
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_envelope() -> Result<(), CryptoCoreError> {
+        check_envelope(EciesAlgorithm::X25519XSalsa20Poly1305)?;
+        #[cfg(feature = "nist_curves")]
+        check_envelope(EciesAlgorithm::P256Aes128Gcm)?;
+
+        let mut bytes = EciesEnvelope::from_ciphertext(
+            EciesAlgorithm::default(),
+            vec![0; EciesAlgorithm::default().encryption_overhead()],
+        )?
+        .to_bytes()?;
+        bytes[4] = 2;
+        assert!(EciesEnvelope::parse(&bytes).is_err());
+        bytes[4] = EciesEnvelope::VERSION;
+        bytes[5] = 0;
+        assert!(EciesEnvelope::parse(&bytes).is_err());
+        bytes[0] = b'X';
+        assert!(EciesEnvelope::parse(&bytes).is_err());
+        Ok(())
+    }
+}
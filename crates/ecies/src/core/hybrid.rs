@@ -0,0 +1,351 @@
+use cosmian_crypto_core::{
+    kdf256, reexport::rand_core::CryptoRngCore, CryptoCoreError, Dem, FixedSizeCBytes,
+    Instantiable, Nonce, Secret, SymmetricKey, X25519PrivateKey, X25519PublicKey,
+    XChaCha20Poly1305, X25519_PUBLIC_KEY_LENGTH,
+};
+use pqc_kyber::{
+    KYBER_CIPHERTEXTBYTES, KYBER_INDCPA_SECRETKEYBYTES, KYBER_PUBLICKEYBYTES, KYBER_SECRETKEYBYTES,
+    KYBER_SSBYTES,
+};
+use tiny_keccak::{Hasher, Sha3};
+
+/// Public key of the hybrid X25519 + Kyber768 ECIES.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct X25519Kyber768PublicKey {
+    x25519: X25519PublicKey,
+    kyber: [u8; KYBER_PUBLICKEYBYTES],
+}
+
+impl X25519Kyber768PublicKey {
+    pub const LENGTH: usize = X25519_PUBLIC_KEY_LENGTH + KYBER_PUBLICKEYBYTES;
+
+    /// Serializes the public key as `X25519 public key || Kyber public key`.
+    #[must_use]
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = Vec::with_capacity(Self::LENGTH);
+        bytes.extend(self.x25519.to_bytes());
+        bytes.extend(self.kyber);
+        bytes
+    }
+
+    pub fn try_from_slice(bytes: &[u8]) -> Result<Self, CryptoCoreError> {
+        if bytes.len() != Self::LENGTH {
+            return Err(CryptoCoreError::InvalidBytesLength(
+                "X25519 Kyber768 public key".to_string(),
+                bytes.len(),
+                Some(Self::LENGTH),
+            ));
+        }
+        let (x25519, kyber_bytes) = bytes.split_at(X25519_PUBLIC_KEY_LENGTH);
+        let mut kyber = [0; KYBER_PUBLICKEYBYTES];
+        kyber.copy_from_slice(kyber_bytes);
+        Ok(Self {
+            x25519: X25519PublicKey::try_from_slice(x25519)?,
+            kyber,
+        })
+    }
+}
+
+/// Private key of the hybrid X25519 + Kyber768 ECIES.
+pub struct X25519Kyber768PrivateKey {
+    x25519: X25519PrivateKey,
+    kyber: Secret<KYBER_SECRETKEYBYTES>,
+}
+
+impl X25519Kyber768PrivateKey {
+    pub const LENGTH: usize = X25519PrivateKey::LENGTH + KYBER_SECRETKEYBYTES;
+
+    /// Generates a new random private key.
+    pub fn new<R: CryptoRngCore>(rng: &mut R) -> Self {
+        let mut keypair = pqc_kyber::keypair(rng);
+        let kyber = Secret::from_unprotected_bytes(&mut keypair.secret);
+        Self {
+            x25519: X25519PrivateKey::new(rng),
+            kyber,
+        }
+    }
+
+    /// Returns the public key associated to this private key.
+    #[must_use]
+    pub fn public_key(&self) -> X25519Kyber768PublicKey {
+        // A Kyber secret key embeds the public key after the IND-CPA secret
+        // key.
+        let mut kyber = [0; KYBER_PUBLICKEYBYTES];
+        kyber.copy_from_slice(
+            &self.kyber
+                [KYBER_INDCPA_SECRETKEYBYTES..KYBER_INDCPA_SECRETKEYBYTES + KYBER_PUBLICKEYBYTES],
+        );
+        X25519Kyber768PublicKey {
+            x25519: X25519PublicKey::from(&self.x25519),
+            kyber,
+        }
+    }
+
+    /// Serializes the private key as `X25519 private key || Kyber private
+    /// key`.
+    #[must_use]
+    pub fn to_bytes(&self) -> Secret<{ Self::LENGTH }> {
+        let mut bytes = Secret::new();
+        bytes[..X25519PrivateKey::LENGTH].copy_from_slice(&self.x25519.to_bytes());
+        bytes[X25519PrivateKey::LENGTH..].copy_from_slice(&self.kyber);
+        bytes
+    }
+
+    pub fn try_from_slice(bytes: &[u8]) -> Result<Self, CryptoCoreError> {
+        if bytes.len() != Self::LENGTH {
+            return Err(CryptoCoreError::InvalidBytesLength(
+                "X25519 Kyber768 private key".to_string(),
+                bytes.len(),
+                Some(Self::LENGTH),
+            ));
+        }
+        let (x25519, kyber_bytes) = bytes.split_at(X25519PrivateKey::LENGTH);
+        let mut kyber = Secret::new();
+        kyber.copy_from_slice(kyber_bytes);
+        Ok(Self {
+            x25519: X25519PrivateKey::try_from_slice(x25519)?,
+            kyber,
+        })
+    }
+}
+
+/// Hybrid post-quantum ECIES based on X25519, Kyber768 and
+/// XChaCha20-Poly1305.
+///
+/// The symmetric key is derived from both an X25519 and a Kyber768 shared
+/// secret: the ciphertext remains confidential as long as one of the two
+/// schemes is not broken.
+///
+/// __Algorithm details__
+///
+/// The shared secrets are combined using the X-Wing combiner (see
+/// draft-connolly-cfrg-xwing-kem):
+///
+/// ```txt
+/// (c_K, k_K) = Kyber768.Encaps(R_K)
+/// e, E       = ephemeral X25519 key pair
+/// k          = SHA3-256(k_K || DH(e, R_X) || E || R_X || XWingLabel)
+/// n          = SHAKE256(label || E || R_X)
+/// c          = c_K || E || XChaCha20-Poly1305(k, n, m, ad)
+/// ```
+///
+/// where `(R_X, R_K)` is the recipient public key.
+///
+/// __Unstable format__
+///
+/// This is not X-Wing yet: Kyber768 is the round 3 Kyber implemented by
+/// `pqc_kyber` 0.4, not ML-KEM-768 (FIPS 203), and this implementation is
+/// affected by KyberSlash (RUSTSEC-2023-0079). Keys and ciphertexts will
+/// change when ML-KEM-768 replaces it, checked against the X-Wing test
+/// vectors: this scheme must not be used to protect long-lived data until
+/// then.
+pub struct EciesX25519Kyber768;
+
+/// Label of the X-Wing combiner.
+const XWING_LABEL: &[u8] = br"\.//^\";
+
+/// Domain separation tag of the derived nonce.
+const NONCE_TAG: &[u8] = b"ECIES X25519 Kyber768 nonce";
+
+impl EciesX25519Kyber768 {
+    /// Size overhead of the ciphertext compared to the plaintext.
+    pub const ENCRYPTION_OVERHEAD: usize =
+        KYBER_CIPHERTEXTBYTES + X25519_PUBLIC_KEY_LENGTH + XChaCha20Poly1305::MAC_LENGTH;
+
+    /// Generates a new random key pair.
+    pub fn generate_key_pair<R: CryptoRngCore>(
+        rng: &mut R,
+    ) -> (X25519Kyber768PublicKey, X25519Kyber768PrivateKey) {
+        let private_key = X25519Kyber768PrivateKey::new(rng);
+        (private_key.public_key(), private_key)
+    }
+
+    /// Derives the symmetric key and nonce from the shared secrets and the
+    /// X25519 public keys.
+    fn derive_key_and_nonce(
+        kyber_shared_secret: &Secret<KYBER_SSBYTES>,
+        x25519_shared_point: &X25519PublicKey,
+        ephemeral_pk: &X25519PublicKey,
+        recipient_pk: &X25519PublicKey,
+    ) -> Result<
+        (
+            SymmetricKey<{ XChaCha20Poly1305::KEY_LENGTH }>,
+            Nonce<{ XChaCha20Poly1305::NONCE_LENGTH }>,
+        ),
+        CryptoCoreError,
+    > {
+        // A null shared point means the public key is of small order.
+        if x25519_shared_point.as_bytes() == &[0; X25519_PUBLIC_KEY_LENGTH] {
+            return Err(CryptoCoreError::ConversionError(
+                "ECIES error: invalid public key".to_string(),
+            ));
+        }
+
+        let mut key = SymmetricKey::default();
+        let mut hasher = Sha3::v256();
+        hasher.update(kyber_shared_secret);
+        hasher.update(x25519_shared_point.as_bytes());
+        hasher.update(ephemeral_pk.as_bytes());
+        hasher.update(recipient_pk.as_bytes());
+        hasher.update(XWING_LABEL);
+        hasher.finalize(&mut key);
+
+        let mut nonce = Nonce([0; XChaCha20Poly1305::NONCE_LENGTH]);
+        kdf256!(
+            &mut nonce.0,
+            NONCE_TAG,
+            ephemeral_pk.as_bytes(),
+            recipient_pk.as_bytes()
+        );
+
+        Ok((key, nonce))
+    }
+
+    /// Encrypts the given plaintext for the given recipient.
+    ///
+    /// The optional authentication data is bound to the ciphertext and must
+    /// be given again upon decryption.
+    pub fn encrypt<R: CryptoRngCore>(
+        rng: &mut R,
+        recipient_pk: &X25519Kyber768PublicKey,
+        plaintext: &[u8],
+        authentication_data: Option<&[u8]>,
+    ) -> Result<Vec<u8>, CryptoCoreError> {
+        let (kyber_ciphertext, mut kyber_shared_secret) =
+            pqc_kyber::encapsulate(&recipient_pk.kyber, rng).map_err(|e| {
+                CryptoCoreError::ConversionError(format!("ECIES error: Kyber encapsulation: {e}"))
+            })?;
+        let kyber_shared_secret = Secret::from_unprotected_bytes(&mut kyber_shared_secret);
+
+        let ephemeral_sk = X25519PrivateKey::new(rng);
+        let ephemeral_pk = X25519PublicKey::from(&ephemeral_sk);
+
+        let (key, nonce) = Self::derive_key_and_nonce(
+            &kyber_shared_secret,
+            &recipient_pk.x25519.dh(&ephemeral_sk),
+            &ephemeral_pk,
+            &recipient_pk.x25519,
+        )?;
+
+        let ciphertext_plus_tag =
+            XChaCha20Poly1305::new(&key).encrypt(&nonce, plaintext, authentication_data)?;
+
+        let mut res = Vec::with_capacity(Self::ENCRYPTION_OVERHEAD + plaintext.len());
+        res.extend(kyber_ciphertext);
+        res.extend(ephemeral_pk.to_bytes());
+        res.extend(ciphertext_plus_tag);
+        Ok(res)
+    }
+
+    /// Decrypts the given ciphertext.
+    ///
+    /// Decryption fails if the ciphertext was encrypted for another recipient
+    /// or with other authentication data.
+    pub fn decrypt(
+        recipient_sk: &X25519Kyber768PrivateKey,
+        ciphertext: &[u8],
+        authentication_data: Option<&[u8]>,
+    ) -> Result<Vec<u8>, CryptoCoreError> {
+        if ciphertext.len() < Self::ENCRYPTION_OVERHEAD {
+            return Err(CryptoCoreError::CiphertextTooSmallError {
+                ciphertext_len: ciphertext.len(),
+                min: Self::ENCRYPTION_OVERHEAD as u64,
+            });
+        }
+        let (kyber_ciphertext, ciphertext) = ciphertext.split_at(KYBER_CIPHERTEXTBYTES);
+        let (ephemeral_pk, ciphertext) = ciphertext.split_at(X25519_PUBLIC_KEY_LENGTH);
+        let ephemeral_pk = X25519PublicKey::try_from_slice(ephemeral_pk)?;
+
+        let mut kyber_shared_secret = pqc_kyber::decapsulate(kyber_ciphertext, &recipient_sk.kyber)
+            .map_err(|e| {
+                CryptoCoreError::ConversionError(format!("ECIES error: Kyber decapsulation: {e}"))
+            })?;
+        let kyber_shared_secret = Secret::from_unprotected_bytes(&mut kyber_shared_secret);
+
+        let (key, nonce) = Self::derive_key_and_nonce(
+            &kyber_shared_secret,
+            &ephemeral_pk.dh(&recipient_sk.x25519),
+            &ephemeral_pk,
+            &X25519PublicKey::from(&recipient_sk.x25519),
+        )?;
+
+        XChaCha20Poly1305::new(&key).decrypt(&nonce, ciphertext, authentication_data)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use cosmian_crypto_core::{reexport::rand_core::SeedableRng, CsRng, Secret, X25519PublicKey};
+
+    use super::{EciesX25519Kyber768, X25519Kyber768PrivateKey, X25519Kyber768PublicKey};
+
+    #[test]
+    fn test_hybrid_encryption() {
+        let mut rng = CsRng::from_entropy();
+        let (recipient_pk, recipient_sk) = EciesX25519Kyber768::generate_key_pair(&mut rng);
+        let (_, other_sk) = EciesX25519Kyber768::generate_key_pair(&mut rng);
+
+        let plaintext = b"plaintext";
+        let ad = b"authenticated_data";
+
+        let ciphertext =
+            EciesX25519Kyber768::encrypt(&mut rng, &recipient_pk, plaintext, Some(ad)).unwrap();
+        assert_eq!(
+            ciphertext.len(),
+            plaintext.len() + EciesX25519Kyber768::ENCRYPTION_OVERHEAD
+        );
+
+        let cleartext = EciesX25519Kyber768::decrypt(&recipient_sk, &ciphertext, Some(ad)).unwrap();
+        assert_eq!(plaintext.to_vec(), cleartext);
+
+        // Another recipient cannot decrypt.
+        assert!(EciesX25519Kyber768::decrypt(&other_sk, &ciphertext, Some(ad)).is_err());
+        // Authentication data must match.
+        assert!(EciesX25519Kyber768::decrypt(&recipient_sk, &ciphertext, None).is_err());
+        // Truncated ciphertexts are rejected.
+        assert!(EciesX25519Kyber768::decrypt(
+            &recipient_sk,
+            &ciphertext[..EciesX25519Kyber768::ENCRYPTION_OVERHEAD - 1],
+            Some(ad)
+        )
+        .is_err());
+    }
+
+    #[test]
+    fn test_xwing_combiner() {
+        // X-Wing label, as ASCII art.
+        assert_eq!(super::XWING_LABEL, [0x5c, 0x2e, 0x2f, 0x2f, 0x5e, 0x5c]);
+
+        // SHA3-256 of the concatenated inputs, computed with Python's hashlib.
+        let pk = |byte| X25519PublicKey::try_from_slice(&[byte; 32]).unwrap();
+        let (key, _) = EciesX25519Kyber768::derive_key_and_nonce(
+            &Secret::from_unprotected_bytes(&mut [1; 32]),
+            &pk(2),
+            &pk(3),
+            &pk(4),
+        )
+        .unwrap();
+        assert_eq!(
+            hex::encode(&*key),
+            "5c6bfaf8c3ec48ab3cee7c12129b39913b8a7fa1234115da7e1c55608ad19fb6"
+        );
+    }
+
+    #[test]
+    fn test_key_serialization() {
+        let mut rng = CsRng::from_entropy();
+        let (pk, sk) = EciesX25519Kyber768::generate_key_pair(&mut rng);
+
+        let pk_bytes = pk.to_bytes();
+        assert_eq!(pk_bytes.len(), X25519Kyber768PublicKey::LENGTH);
+        assert_eq!(
+            pk,
+            X25519Kyber768PublicKey::try_from_slice(&pk_bytes).unwrap()
+        );
+
+        let sk = X25519Kyber768PrivateKey::try_from_slice(&sk.to_bytes()).unwrap();
+        assert_eq!(pk, sk.public_key());
+
+        assert!(X25519Kyber768PublicKey::try_from_slice(&pk_bytes[1..]).is_err());
+    }
+}
@@ -0,0 +1,107 @@
+use bip39::Mnemonic;
+use cosmian_crypto_core::{
+    kdf256,
+    reexport::{rand_core::CryptoRngCore, zeroize::Zeroizing},
+    CryptoCoreError, X25519PrivateKey, X25519PublicKey, CURVE_25519_SECRET_LENGTH,
+};
+
+/// BIP39 mnemonic backup of X25519 key pairs.
+///
+/// The private key is derived from the BIP39 seed of the mnemonic:
+///
+/// ```txt
+/// seed = BIP39-seed(mnemonic, "")
+/// sk   = SHAKE256(TAG || seed)
+/// ```
+///
+/// Newly generated mnemonics hold 24 English words (256 bits of entropy), but
+/// any valid English BIP39 mnemonic can be used to derive a key pair.
+pub struct X25519Mnemonic;
+
+/// Domain separation tag of the derived private key.
+const MNEMONIC_TAG: &[u8] = b"ECIES X25519 mnemonic";
+
+/// Length of the entropy of the generated mnemonics.
+const MNEMONIC_ENTROPY_LENGTH: usize = 32;
+
+impl X25519Mnemonic {
+    /// Generates a new 24-word mnemonic.
+    pub fn generate_mnemonic(rng: &mut impl CryptoRngCore) -> Result<String, CryptoCoreError> {
+        let mut entropy = Zeroizing::new([0; MNEMONIC_ENTROPY_LENGTH]);
+        rng.fill_bytes(&mut *entropy);
+        let mnemonic = Mnemonic::from_entropy(&*entropy).map_err(|e| {
+            CryptoCoreError::ConversionError(format!("ECIES error: mnemonic generation: {e}"))
+        })?;
+        Ok(mnemonic.to_string())
+    }
+
+    /// Generates a new mnemonic and returns it along with the derived key
+    /// pair.
+    pub fn generate_key_pair(
+        rng: &mut impl CryptoRngCore,
+    ) -> Result<(String, X25519PublicKey, X25519PrivateKey), CryptoCoreError> {
+        let mnemonic = Self::generate_mnemonic(rng)?;
+        let (public_key, private_key) = Self::key_pair_from_mnemonic(&mnemonic)?;
+        Ok((mnemonic, public_key, private_key))
+    }
+
+    /// Derives the key pair backed up by the given mnemonic.
+    pub fn key_pair_from_mnemonic(
+        mnemonic: &str,
+    ) -> Result<(X25519PublicKey, X25519PrivateKey), CryptoCoreError> {
+        let mnemonic = Mnemonic::parse(mnemonic).map_err(|e| {
+            CryptoCoreError::ConversionError(format!("ECIES error: invalid mnemonic: {e}"))
+        })?;
+        Self::derive_key_pair(&mnemonic)
+    }
+
+    fn derive_key_pair(
+        mnemonic: &Mnemonic,
+    ) -> Result<(X25519PublicKey, X25519PrivateKey), CryptoCoreError> {
+        let seed = Zeroizing::new(mnemonic.to_seed(""));
+        let mut private_key = Zeroizing::new([0; CURVE_25519_SECRET_LENGTH]);
+        kdf256!(&mut *private_key, MNEMONIC_TAG, &*seed);
+        let private_key = X25519PrivateKey::try_from_bytes(*private_key)?;
+        Ok((X25519PublicKey::from(&private_key), private_key))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use cosmian_crypto_core::{reexport::rand_core::SeedableRng, CsRng};
+
+    use super::*;
+
+    #[test]
+    fn test_mnemonic_key_pair() {
+        let mut rng = CsRng::from_entropy();
+        let (mnemonic, public_key, private_key) =
+            X25519Mnemonic::generate_key_pair(&mut rng).unwrap();
+        assert_eq!(mnemonic.split_whitespace().count(), 24);
+
+        let (restored_public_key, restored_private_key) =
+            X25519Mnemonic::key_pair_from_mnemonic(&mnemonic).unwrap();
+        assert_eq!(restored_public_key, public_key);
+        assert_eq!(restored_private_key.as_bytes(), private_key.as_bytes());
+
+        // Mnemonics are checksummed.
+        assert!(X25519Mnemonic::key_pair_from_mnemonic(&["abandon"; 24].join(" ")).is_err());
+        assert!(X25519Mnemonic::key_pair_from_mnemonic("not a mnemonic").is_err());
+    }
+
+    /// Changing the derivation makes the existing backups unrecoverable.
+    #[test]
+    fn test_mnemonic_known_answer() {
+        let mnemonic = "abandon abandon abandon abandon abandon abandon abandon abandon abandon \
+                        abandon abandon about";
+        let (public_key, private_key) = X25519Mnemonic::key_pair_from_mnemonic(mnemonic).unwrap();
+        assert_eq!(
+            hex::encode(private_key.as_bytes()),
+            "b419bd1f8a84a439ea5ba37b8dfb777a4c2f92e1bb5699a785d1602180f23b68"
+        );
+        assert_eq!(
+            hex::encode(public_key.as_bytes()),
+            "e19ec75b3464584207de34234533e970f43dbca78362706175cdf78ea96a4b2f"
+        );
+    }
+}
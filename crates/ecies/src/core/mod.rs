@@ -0,0 +1,7 @@
+pub mod algorithm;
+pub mod authenticated;
+pub mod envelope;
+#[cfg(feature = "pq")]
+pub mod hybrid;
+pub mod mnemonic;
+pub mod pem;
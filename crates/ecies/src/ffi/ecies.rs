@@ -2,7 +2,11 @@ use cosmian_crypto_core::{
     reexport::rand_core::SeedableRng, CsRng, Ecies, EciesSalsaSealBox, FixedSizeCBytes,
     X25519PrivateKey, X25519PublicKey,
 };
-use cosmian_ffi_utils::{ffi_read_bytes, ffi_unwrap, ffi_write_bytes, ErrorCode};
+use cosmian_ffi_utils::{
+    ffi_guard, ffi_read_bytes, ffi_read_string, ffi_unwrap, ffi_write_bytes, ErrorCode,
+};
+
+use crate::{EciesAlgorithm, EciesEnvelope, EciesX25519Authenticated};
 
 #[no_mangle]
 pub unsafe extern "C" fn h_ecies_x25519_generate_key_pair(
@@ -11,20 +15,22 @@ pub unsafe extern "C" fn h_ecies_x25519_generate_key_pair(
     private_key_ptr: *mut u8,
     private_key_len: *mut i32,
 ) -> i32 {
-    let mut rng = CsRng::from_entropy();
-    let private_key = X25519PrivateKey::new(&mut rng);
-    let public_key = X25519PublicKey::from(&private_key);
-
-    ffi_write_bytes!(
-        "public_key_ptr",
-        &public_key.to_bytes(),
-        public_key_ptr,
-        public_key_len
-        "private_key_ptr",
-        &private_key.to_bytes(),
-        private_key_ptr,
-        private_key_len
-    );
+    ffi_guard!({
+        let mut rng = CsRng::from_entropy();
+        let private_key = X25519PrivateKey::new(&mut rng);
+        let public_key = X25519PublicKey::from(&private_key);
+
+        ffi_write_bytes!(
+            "public_key_ptr",
+            &public_key.to_bytes(),
+            public_key_ptr,
+            public_key_len
+            "private_key_ptr",
+            &private_key.to_bytes(),
+            private_key_ptr,
+            private_key_len
+        );
+    })
 }
 
 unsafe extern "C" fn ecies_salsa_seal_box(
@@ -111,17 +117,19 @@ pub unsafe extern "C" fn h_ecies_salsa_seal_box_encrypt(
     authentication_data_ptr: *const i8,
     authentication_data_len: i32,
 ) -> i32 {
-    ecies_salsa_seal_box(
-        output_ptr,
-        output_len,
-        plaintext_ptr,
-        plaintext_len,
-        public_key_ptr,
-        public_key_len,
-        authentication_data_ptr,
-        authentication_data_len,
-        true,
-    )
+    ffi_guard!({
+        ecies_salsa_seal_box(
+            output_ptr,
+            output_len,
+            plaintext_ptr,
+            plaintext_len,
+            public_key_ptr,
+            public_key_len,
+            authentication_data_ptr,
+            authentication_data_len,
+            true,
+        )
+    })
 }
 
 #[no_mangle]
@@ -140,15 +148,542 @@ pub unsafe extern "C" fn h_ecies_salsa_seal_box_decrypt(
     authentication_data_ptr: *const i8,
     authentication_data_len: i32,
 ) -> i32 {
-    ecies_salsa_seal_box(
-        output_ptr,
-        output_len,
-        ciphertext_ptr,
-        ciphertext_len,
-        private_key_ptr,
-        private_key_len,
-        authentication_data_ptr,
-        authentication_data_len,
-        false,
-    )
+    ffi_guard!({
+        ecies_salsa_seal_box(
+            output_ptr,
+            output_len,
+            ciphertext_ptr,
+            ciphertext_len,
+            private_key_ptr,
+            private_key_len,
+            authentication_data_ptr,
+            authentication_data_len,
+            false,
+        )
+    })
+}
+
+#[no_mangle]
+/// Encrypts the given plaintext for the given recipient, authenticating it
+/// with the sender private key.
+///
+/// # Safety
+///
+/// All pointers must be valid and the lengths must match their buffers.
+pub unsafe extern "C" fn h_ecies_x25519_authenticated_encrypt(
+    output_ptr: *mut u8,
+    output_len: *mut i32,
+    plaintext_ptr: *const i8,
+    plaintext_len: i32,
+    sender_private_key_ptr: *const i8,
+    sender_private_key_len: i32,
+    recipient_public_key_ptr: *const i8,
+    recipient_public_key_len: i32,
+    authentication_data_ptr: *const i8,
+    authentication_data_len: i32,
+) -> i32 {
+    ffi_guard!({
+        let plaintext_bytes = ffi_read_bytes!("plaintext", plaintext_ptr, plaintext_len);
+        let sender_private_key_bytes = ffi_read_bytes!(
+            "sender_private_key",
+            sender_private_key_ptr,
+            sender_private_key_len
+        );
+        let recipient_public_key_bytes = ffi_read_bytes!(
+            "recipient_public_key",
+            recipient_public_key_ptr,
+            recipient_public_key_len
+        );
+        let authentication_data_bytes = ffi_read_bytes!(
+            "authentication_data",
+            authentication_data_ptr,
+            authentication_data_len
+        );
+
+        let sender_private_key = ffi_unwrap!(
+            X25519PrivateKey::try_from_slice(sender_private_key_bytes),
+            "ECIES error: sender private key deserializing",
+            ErrorCode::Serialization
+        );
+        let recipient_public_key = ffi_unwrap!(
+            X25519PublicKey::try_from_slice(recipient_public_key_bytes),
+            "ECIES error: recipient public key deserializing",
+            ErrorCode::Serialization
+        );
+
+        let mut rng = CsRng::from_entropy();
+        let output = ffi_unwrap!(
+            EciesX25519Authenticated::encrypt(
+                &mut rng,
+                &sender_private_key,
+                &recipient_public_key,
+                plaintext_bytes,
+                Some(authentication_data_bytes)
+            ),
+            "ECIES error: encryption",
+            ErrorCode::Encryption
+        );
+        ffi_write_bytes!("output_ptr", &output, output_ptr, output_len);
+    })
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn h_ecies_x25519_authenticated_get_encryption_overhead() -> u32 {
+    EciesX25519Authenticated::ENCRYPTION_OVERHEAD as u32
+}
+
+#[no_mangle]
+/// Decrypts the given ciphertext and verifies it was encrypted by the owner of
+/// the given sender public key.
+///
+/// # Safety
+///
+/// All pointers must be valid and the lengths must match their buffers.
+pub unsafe extern "C" fn h_ecies_x25519_authenticated_decrypt(
+    output_ptr: *mut u8,
+    output_len: *mut i32,
+    ciphertext_ptr: *const i8,
+    ciphertext_len: i32,
+    recipient_private_key_ptr: *const i8,
+    recipient_private_key_len: i32,
+    sender_public_key_ptr: *const i8,
+    sender_public_key_len: i32,
+    authentication_data_ptr: *const i8,
+    authentication_data_len: i32,
+) -> i32 {
+    ffi_guard!({
+        let ciphertext_bytes = ffi_read_bytes!("ciphertext", ciphertext_ptr, ciphertext_len);
+        let recipient_private_key_bytes = ffi_read_bytes!(
+            "recipient_private_key",
+            recipient_private_key_ptr,
+            recipient_private_key_len
+        );
+        let sender_public_key_bytes = ffi_read_bytes!(
+            "sender_public_key",
+            sender_public_key_ptr,
+            sender_public_key_len
+        );
+        let authentication_data_bytes = ffi_read_bytes!(
+            "authentication_data",
+            authentication_data_ptr,
+            authentication_data_len
+        );
+
+        let recipient_private_key = ffi_unwrap!(
+            X25519PrivateKey::try_from_slice(recipient_private_key_bytes),
+            "ECIES error: recipient private key deserializing",
+            ErrorCode::Serialization
+        );
+        let sender_public_key = ffi_unwrap!(
+            X25519PublicKey::try_from_slice(sender_public_key_bytes),
+            "ECIES error: sender public key deserializing",
+            ErrorCode::Serialization
+        );
+
+        let output = ffi_unwrap!(
+            EciesX25519Authenticated::decrypt(
+                &recipient_private_key,
+                &sender_public_key,
+                ciphertext_bytes,
+                Some(authentication_data_bytes)
+            ),
+            "ECIES error: decryption",
+            ErrorCode::Decryption
+        );
+        ffi_write_bytes!("output_ptr", &output, output_ptr, output_len);
+    })
+}
+
+/// Reads the ECIES algorithm from the given C string, defaulting to the X25519
+/// sealed box if the pointer is null.
+macro_rules! ffi_read_algorithm {
+    ($algorithm_ptr:ident) => {
+        if $algorithm_ptr.is_null() {
+            EciesAlgorithm::default()
+        } else {
+            let algorithm_str = ffi_read_string!("algorithm", $algorithm_ptr);
+            ffi_unwrap!(
+                algorithm_str.parse::<EciesAlgorithm>(),
+                "ECIES error: algorithm parsing",
+                ErrorCode::InvalidArgument("algorithm".to_string())
+            )
+        }
+    };
+}
+
+#[no_mangle]
+/// Generates a new key pair for the given ECIES algorithm.
+///
+/// # Safety
+///
+/// All pointers must be valid and the lengths must match their buffers. The
+/// algorithm is either null, selecting the default X25519 sealed box, or a C
+/// string among `x25519-xsalsa20-poly1305` and `p256-aes128-gcm`.
+pub unsafe extern "C" fn h_ecies_generate_key_pair(
+    public_key_ptr: *mut u8,
+    public_key_len: *mut i32,
+    private_key_ptr: *mut u8,
+    private_key_len: *mut i32,
+    algorithm_ptr: *const i8,
+) -> i32 {
+    ffi_guard!({
+        let algorithm = ffi_read_algorithm!(algorithm_ptr);
+
+        let mut rng = CsRng::from_entropy();
+        let (public_key, private_key) = algorithm.generate_key_pair(&mut rng);
+
+        ffi_write_bytes!(
+            "public_key_ptr",
+            &public_key,
+            public_key_ptr,
+            public_key_len
+            "private_key_ptr",
+            &private_key,
+            private_key_ptr,
+            private_key_len
+        );
+    })
+}
+
+#[no_mangle]
+/// Writes the size overhead of the ciphertexts of the given ECIES algorithm.
+///
+/// # Safety
+///
+/// `overhead` must be a valid pointer. The algorithm is either null or a C
+/// string, see `h_ecies_generate_key_pair`.
+pub unsafe extern "C" fn h_ecies_get_encryption_overhead(
+    overhead: *mut u32,
+    algorithm_ptr: *const i8,
+) -> i32 {
+    ffi_guard!({
+        let algorithm = ffi_read_algorithm!(algorithm_ptr);
+        *overhead = algorithm.encryption_overhead() as u32;
+        0
+    })
+}
+
+#[no_mangle]
+/// Encrypts the given plaintext for the owner of the given public key, using
+/// the given ECIES algorithm.
+///
+/// # Safety
+///
+/// All pointers must be valid and the lengths must match their buffers. The
+/// algorithm is either null or a C string, see `h_ecies_generate_key_pair`.
+pub unsafe extern "C" fn h_ecies_encrypt(
+    output_ptr: *mut u8,
+    output_len: *mut i32,
+    plaintext_ptr: *const i8,
+    plaintext_len: i32,
+    public_key_ptr: *const i8,
+    public_key_len: i32,
+    authentication_data_ptr: *const i8,
+    authentication_data_len: i32,
+    algorithm_ptr: *const i8,
+) -> i32 {
+    ffi_guard!({
+        let algorithm = ffi_read_algorithm!(algorithm_ptr);
+        let plaintext_bytes = ffi_read_bytes!("plaintext", plaintext_ptr, plaintext_len);
+        let public_key_bytes = ffi_read_bytes!("public_key", public_key_ptr, public_key_len);
+        let authentication_data_bytes = ffi_read_bytes!(
+            "authentication_data",
+            authentication_data_ptr,
+            authentication_data_len
+        );
+
+        let mut rng = CsRng::from_entropy();
+        let output = ffi_unwrap!(
+            algorithm.encrypt(
+                &mut rng,
+                public_key_bytes,
+                plaintext_bytes,
+                Some(authentication_data_bytes)
+            ),
+            "ECIES error: encryption",
+            ErrorCode::Encryption
+        );
+        ffi_write_bytes!("output_ptr", &output, output_ptr, output_len);
+    })
+}
+
+#[no_mangle]
+/// Decrypts the given ciphertext using the given private key and ECIES
+/// algorithm.
+///
+/// # Safety
+///
+/// All pointers must be valid and the lengths must match their buffers. The
+/// algorithm is either null or a C string, see `h_ecies_generate_key_pair`.
+pub unsafe extern "C" fn h_ecies_decrypt(
+    output_ptr: *mut u8,
+    output_len: *mut i32,
+    ciphertext_ptr: *const i8,
+    ciphertext_len: i32,
+    private_key_ptr: *const i8,
+    private_key_len: i32,
+    authentication_data_ptr: *const i8,
+    authentication_data_len: i32,
+    algorithm_ptr: *const i8,
+) -> i32 {
+    ffi_guard!({
+        let algorithm = ffi_read_algorithm!(algorithm_ptr);
+        let ciphertext_bytes = ffi_read_bytes!("ciphertext", ciphertext_ptr, ciphertext_len);
+        let private_key_bytes = ffi_read_bytes!("private_key", private_key_ptr, private_key_len);
+        let authentication_data_bytes = ffi_read_bytes!(
+            "authentication_data",
+            authentication_data_ptr,
+            authentication_data_len
+        );
+
+        let output = ffi_unwrap!(
+            algorithm.decrypt(
+                private_key_bytes,
+                ciphertext_bytes,
+                Some(authentication_data_bytes)
+            ),
+            "ECIES error: decryption",
+            ErrorCode::Decryption
+        );
+        ffi_write_bytes!("output_ptr", &output, output_ptr, output_len);
+    })
+}
+
+#[no_mangle]
+/// Writes the length of the public keys of the given ECIES algorithm, i.e. the
+/// length of the ephemeral public keys written by `h_ecies_encrypt_detached`.
+///
+/// # Safety
+///
+/// `length` must be a valid pointer. The algorithm is either null or a C
+/// string, see `h_ecies_generate_key_pair`.
+pub unsafe extern "C" fn h_ecies_get_public_key_length(
+    length: *mut u32,
+    algorithm_ptr: *const i8,
+) -> i32 {
+    ffi_guard!({
+        let algorithm = ffi_read_algorithm!(algorithm_ptr);
+        *length = algorithm.public_key_length() as u32;
+        0
+    })
+}
+
+#[no_mangle]
+/// Encrypts the given plaintext for the owner of the given public key, using
+/// the given ECIES algorithm, and writes the ephemeral public key and the
+/// ciphertext to separate buffers.
+///
+/// Their concatenation is the ciphertext written by `h_ecies_encrypt`.
+///
+/// # Safety
+///
+/// All pointers must be valid and the lengths must match their buffers. The
+/// algorithm is either null or a C string, see `h_ecies_generate_key_pair`.
+pub unsafe extern "C" fn h_ecies_encrypt_detached(
+    ephemeral_public_key_ptr: *mut u8,
+    ephemeral_public_key_len: *mut i32,
+    output_ptr: *mut u8,
+    output_len: *mut i32,
+    plaintext_ptr: *const i8,
+    plaintext_len: i32,
+    public_key_ptr: *const i8,
+    public_key_len: i32,
+    authentication_data_ptr: *const i8,
+    authentication_data_len: i32,
+    algorithm_ptr: *const i8,
+) -> i32 {
+    ffi_guard!({
+        let algorithm = ffi_read_algorithm!(algorithm_ptr);
+        let plaintext_bytes = ffi_read_bytes!("plaintext", plaintext_ptr, plaintext_len);
+        let public_key_bytes = ffi_read_bytes!("public_key", public_key_ptr, public_key_len);
+        let authentication_data_bytes = ffi_read_bytes!(
+            "authentication_data",
+            authentication_data_ptr,
+            authentication_data_len
+        );
+
+        let mut rng = CsRng::from_entropy();
+        let (ephemeral_public_key, output) = ffi_unwrap!(
+            algorithm.encrypt_detached(
+                &mut rng,
+                public_key_bytes,
+                plaintext_bytes,
+                Some(authentication_data_bytes)
+            ),
+            "ECIES error: encryption",
+            ErrorCode::Encryption
+        );
+        ffi_write_bytes!(
+            "ephemeral_public_key_ptr",
+            &ephemeral_public_key,
+            ephemeral_public_key_ptr,
+            ephemeral_public_key_len
+            "output_ptr",
+            &output,
+            output_ptr,
+            output_len
+        );
+    })
+}
+
+#[no_mangle]
+/// Decrypts the given ciphertext, written along with the given ephemeral
+/// public key by `h_ecies_encrypt_detached`, using the given private key and
+/// ECIES algorithm.
+///
+/// # Safety
+///
+/// All pointers must be valid and the lengths must match their buffers. The
+/// algorithm is either null or a C string, see `h_ecies_generate_key_pair`.
+pub unsafe extern "C" fn h_ecies_decrypt_detached(
+    output_ptr: *mut u8,
+    output_len: *mut i32,
+    ephemeral_public_key_ptr: *const i8,
+    ephemeral_public_key_len: i32,
+    ciphertext_ptr: *const i8,
+    ciphertext_len: i32,
+    private_key_ptr: *const i8,
+    private_key_len: i32,
+    authentication_data_ptr: *const i8,
+    authentication_data_len: i32,
+    algorithm_ptr: *const i8,
+) -> i32 {
+    ffi_guard!({
+        let algorithm = ffi_read_algorithm!(algorithm_ptr);
+        let ephemeral_public_key_bytes = ffi_read_bytes!(
+            "ephemeral_public_key",
+            ephemeral_public_key_ptr,
+            ephemeral_public_key_len
+        );
+        let ciphertext_bytes = ffi_read_bytes!("ciphertext", ciphertext_ptr, ciphertext_len);
+        let private_key_bytes = ffi_read_bytes!("private_key", private_key_ptr, private_key_len);
+        let authentication_data_bytes = ffi_read_bytes!(
+            "authentication_data",
+            authentication_data_ptr,
+            authentication_data_len
+        );
+
+        let output = ffi_unwrap!(
+            algorithm.decrypt_detached(
+                private_key_bytes,
+                ephemeral_public_key_bytes,
+                ciphertext_bytes,
+                Some(authentication_data_bytes)
+            ),
+            "ECIES error: decryption",
+            ErrorCode::Decryption
+        );
+        ffi_write_bytes!("output_ptr", &output, output_ptr, output_len);
+    })
+}
+
+#[no_mangle]
+/// Writes the size overhead of the envelopes of the given ECIES algorithm.
+///
+/// # Safety
+///
+/// `overhead` must be a valid pointer. The algorithm is either null or a C
+/// string, see `h_ecies_generate_key_pair`.
+pub unsafe extern "C" fn h_ecies_envelope_get_encryption_overhead(
+    overhead: *mut u32,
+    algorithm_ptr: *const i8,
+) -> i32 {
+    ffi_guard!({
+        let algorithm = ffi_read_algorithm!(algorithm_ptr);
+        *overhead = EciesEnvelope::encryption_overhead(algorithm) as u32;
+        0
+    })
+}
+
+#[no_mangle]
+/// Encrypts the given plaintext for the owner of the given public key, using
+/// the given ECIES algorithm, and writes the resulting envelope.
+///
+/// The envelope records the algorithm used, it can be decrypted by
+/// `h_ecies_envelope_decrypt` without specifying it.
+///
+/// # Safety
+///
+/// All pointers must be valid and the lengths must match their buffers. The
+/// algorithm is either null or a C string, see `h_ecies_generate_key_pair`.
+pub unsafe extern "C" fn h_ecies_envelope_encrypt(
+    output_ptr: *mut u8,
+    output_len: *mut i32,
+    plaintext_ptr: *const i8,
+    plaintext_len: i32,
+    public_key_ptr: *const i8,
+    public_key_len: i32,
+    authentication_data_ptr: *const i8,
+    authentication_data_len: i32,
+    algorithm_ptr: *const i8,
+) -> i32 {
+    ffi_guard!({
+        let algorithm = ffi_read_algorithm!(algorithm_ptr);
+        let plaintext_bytes = ffi_read_bytes!("plaintext", plaintext_ptr, plaintext_len);
+        let public_key_bytes = ffi_read_bytes!("public_key", public_key_ptr, public_key_len);
+        let authentication_data_bytes = ffi_read_bytes!(
+            "authentication_data",
+            authentication_data_ptr,
+            authentication_data_len
+        );
+
+        let mut rng = CsRng::from_entropy();
+        let envelope = ffi_unwrap!(
+            EciesEnvelope::encrypt(
+                algorithm,
+                &mut rng,
+                public_key_bytes,
+                plaintext_bytes,
+                Some(authentication_data_bytes)
+            ),
+            "ECIES error: encryption",
+            ErrorCode::Encryption
+        );
+        let output = ffi_unwrap!(
+            envelope.to_bytes(),
+            "ECIES error: envelope serialization",
+            ErrorCode::Serialization
+        );
+        ffi_write_bytes!("output_ptr", &output, output_ptr, output_len);
+    })
+}
+
+#[no_mangle]
+/// Decrypts the given envelope, written by `h_ecies_envelope_encrypt`, using
+/// the given private key. The algorithm is read from the envelope.
+///
+/// # Safety
+///
+/// All pointers must be valid and the lengths must match their buffers.
+pub unsafe extern "C" fn h_ecies_envelope_decrypt(
+    output_ptr: *mut u8,
+    output_len: *mut i32,
+    envelope_ptr: *const i8,
+    envelope_len: i32,
+    private_key_ptr: *const i8,
+    private_key_len: i32,
+    authentication_data_ptr: *const i8,
+    authentication_data_len: i32,
+) -> i32 {
+    ffi_guard!({
+        let envelope_bytes = ffi_read_bytes!("envelope", envelope_ptr, envelope_len);
+        let private_key_bytes = ffi_read_bytes!("private_key", private_key_ptr, private_key_len);
+        let authentication_data_bytes = ffi_read_bytes!(
+            "authentication_data",
+            authentication_data_ptr,
+            authentication_data_len
+        );
+
+        let envelope = ffi_unwrap!(
+            EciesEnvelope::parse(envelope_bytes),
+            "ECIES error: envelope parsing",
+            ErrorCode::Serialization
+        );
+        let output = ffi_unwrap!(
+            envelope.decrypt(private_key_bytes, Some(authentication_data_bytes)),
+            "ECIES error: decryption",
+            ErrorCode::Decryption
+        );
+        ffi_write_bytes!("output_ptr", &output, output_ptr, output_len);
+    })
 }
@@ -0,0 +1,129 @@
+use cosmian_crypto_core::{reexport::rand_core::SeedableRng, CsRng};
+use cosmian_ffi_utils::{ffi_guard, ffi_read_bytes, ffi_unwrap, ffi_write_bytes, ErrorCode};
+
+use crate::{EciesX25519Kyber768, X25519Kyber768PrivateKey, X25519Kyber768PublicKey};
+
+#[no_mangle]
+/// Generates a new X25519 + Kyber768 key pair.
+///
+/// # Safety
+///
+/// All pointers must be valid and the lengths must match their buffers.
+pub unsafe extern "C" fn h_ecies_x25519_kyber768_generate_key_pair(
+    public_key_ptr: *mut u8,
+    public_key_len: *mut i32,
+    private_key_ptr: *mut u8,
+    private_key_len: *mut i32,
+) -> i32 {
+    ffi_guard!({
+        let mut rng = CsRng::from_entropy();
+        let (public_key, private_key) = EciesX25519Kyber768::generate_key_pair(&mut rng);
+
+        ffi_write_bytes!(
+            "public_key_ptr",
+            &public_key.to_bytes(),
+            public_key_ptr,
+            public_key_len
+            "private_key_ptr",
+            &*private_key.to_bytes(),
+            private_key_ptr,
+            private_key_len
+        );
+    })
+}
+
+#[no_mangle]
+/// Encrypts the given plaintext for the given X25519 + Kyber768 public key.
+///
+/// # Safety
+///
+/// All pointers must be valid and the lengths must match their buffers.
+pub unsafe extern "C" fn h_ecies_x25519_kyber768_encrypt(
+    output_ptr: *mut u8,
+    output_len: *mut i32,
+    plaintext_ptr: *const i8,
+    plaintext_len: i32,
+    public_key_ptr: *const i8,
+    public_key_len: i32,
+    authentication_data_ptr: *const i8,
+    authentication_data_len: i32,
+) -> i32 {
+    ffi_guard!({
+        let plaintext_bytes = ffi_read_bytes!("plaintext", plaintext_ptr, plaintext_len);
+        let public_key_bytes = ffi_read_bytes!("public_key", public_key_ptr, public_key_len);
+        let authentication_data_bytes = ffi_read_bytes!(
+            "authentication_data",
+            authentication_data_ptr,
+            authentication_data_len
+        );
+
+        let public_key = ffi_unwrap!(
+            X25519Kyber768PublicKey::try_from_slice(public_key_bytes),
+            "ECIES error: public key deserializing",
+            ErrorCode::Serialization
+        );
+
+        let mut rng = CsRng::from_entropy();
+        let output = ffi_unwrap!(
+            EciesX25519Kyber768::encrypt(
+                &mut rng,
+                &public_key,
+                plaintext_bytes,
+                Some(authentication_data_bytes)
+            ),
+            "ECIES error: encryption",
+            ErrorCode::Encryption
+        );
+        ffi_write_bytes!("output_ptr", &output, output_ptr, output_len);
+    })
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn h_ecies_x25519_kyber768_get_encryption_overhead() -> u32 {
+    EciesX25519Kyber768::ENCRYPTION_OVERHEAD as u32
+}
+
+#[no_mangle]
+/// Decrypts the given ciphertext using the given X25519 + Kyber768 private
+/// key.
+///
+/// # Safety
+///
+/// All pointers must be valid and the lengths must match their buffers.
+pub unsafe extern "C" fn h_ecies_x25519_kyber768_decrypt(
+    output_ptr: *mut u8,
+    output_len: *mut i32,
+    ciphertext_ptr: *const i8,
+    ciphertext_len: i32,
+    private_key_ptr: *const i8,
+    private_key_len: i32,
+    authentication_data_ptr: *const i8,
+    authentication_data_len: i32,
+) -> i32 {
+    ffi_guard!({
+        let ciphertext_bytes = ffi_read_bytes!("ciphertext", ciphertext_ptr, ciphertext_len);
+        let private_key_bytes = ffi_read_bytes!("private_key", private_key_ptr, private_key_len);
+        let authentication_data_bytes = ffi_read_bytes!(
+            "authentication_data",
+            authentication_data_ptr,
+            authentication_data_len
+        );
+
+        let private_key = ffi_unwrap!(
+            X25519Kyber768PrivateKey::try_from_slice(private_key_bytes),
+            "ECIES error: private key deserializing",
+            ErrorCode::Serialization
+        );
+
+        let output = ffi_unwrap!(
+            EciesX25519Kyber768::decrypt(
+                &private_key,
+                ciphertext_bytes,
+                Some(authentication_data_bytes)
+            ),
+            "ECIES error: decryption",
+            ErrorCode::Decryption
+        );
+        ffi_write_bytes!("output_ptr", &output, output_ptr, output_len);
+    })
+}
@@ -1,4 +1,6 @@
 mod ecies;
+#[cfg(feature = "pq")]
+mod hybrid;
 
 #[cfg(test)]
 mod tests;
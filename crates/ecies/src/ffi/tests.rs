@@ -1,11 +1,22 @@
 use cosmian_crypto_core::{
-    Ecies, EciesSalsaSealBox, FixedSizeCBytes, X25519PrivateKey, X25519PublicKey,
+    reexport::rand_core::SeedableRng, CsRng, Ecies, EciesSalsaSealBox, FixedSizeCBytes,
+    X25519PrivateKey, X25519PublicKey,
 };
+use std::ffi::CString;
+
 use cosmian_ffi_utils::error::get_last_error;
 
-use crate::ffi::ecies::{
-    h_ecies_salsa_seal_box_decrypt, h_ecies_salsa_seal_box_encrypt,
-    h_ecies_x25519_generate_key_pair,
+use crate::{
+    ffi::ecies::{
+        h_ecies_decrypt, h_ecies_decrypt_detached, h_ecies_encrypt, h_ecies_encrypt_detached,
+        h_ecies_envelope_decrypt, h_ecies_envelope_encrypt,
+        h_ecies_envelope_get_encryption_overhead, h_ecies_generate_key_pair,
+        h_ecies_get_encryption_overhead, h_ecies_get_public_key_length,
+        h_ecies_salsa_seal_box_decrypt, h_ecies_salsa_seal_box_encrypt,
+        h_ecies_x25519_authenticated_decrypt, h_ecies_x25519_authenticated_encrypt,
+        h_ecies_x25519_generate_key_pair,
+    },
+    EciesX25519Authenticated,
 };
 
 #[test]
@@ -92,3 +103,357 @@ fn encrypt_decrypt() {
         assert_eq!(plaintext.to_vec(), cleartext_bytes.to_vec());
     }
 }
+
+#[test]
+fn authenticated_encrypt_decrypt() {
+    let mut rng = CsRng::from_entropy();
+    let sender_private_key = X25519PrivateKey::new(&mut rng).to_bytes();
+    let sender_public_key =
+        X25519PublicKey::from(&X25519PrivateKey::try_from_bytes(sender_private_key).unwrap())
+            .to_bytes();
+    let recipient_private_key = X25519PrivateKey::new(&mut rng).to_bytes();
+    let recipient_public_key =
+        X25519PublicKey::from(&X25519PrivateKey::try_from_bytes(recipient_private_key).unwrap())
+            .to_bytes();
+
+    let plaintext = b"plaintext";
+    let authenticated_data = b"authenticated_data";
+
+    let mut ciphertext = vec![0u8; plaintext.len() + EciesX25519Authenticated::ENCRYPTION_OVERHEAD];
+    let mut ciphertext_len = ciphertext.len() as i32;
+    let mut cleartext = vec![0u8; plaintext.len()];
+    let mut cleartext_len = cleartext.len() as i32;
+
+    unsafe {
+        let ret = h_ecies_x25519_authenticated_encrypt(
+            ciphertext.as_mut_ptr(),
+            &mut ciphertext_len,
+            plaintext.as_ptr().cast(),
+            plaintext.len() as i32,
+            sender_private_key.as_ptr().cast(),
+            sender_private_key.len() as i32,
+            recipient_public_key.as_ptr().cast(),
+            recipient_public_key.len() as i32,
+            authenticated_data.as_ptr().cast(),
+            authenticated_data.len() as i32,
+        );
+        assert!(
+            0 == ret,
+            "ECIES FFI authenticated encryption failed. Exit with error: {ret}, error message: \
+             {:?}",
+            get_last_error()
+        );
+
+        let ret = h_ecies_x25519_authenticated_decrypt(
+            cleartext.as_mut_ptr(),
+            &mut cleartext_len,
+            ciphertext.as_ptr().cast(),
+            ciphertext_len,
+            recipient_private_key.as_ptr().cast(),
+            recipient_private_key.len() as i32,
+            sender_public_key.as_ptr().cast(),
+            sender_public_key.len() as i32,
+            authenticated_data.as_ptr().cast(),
+            authenticated_data.len() as i32,
+        );
+        assert!(
+            0 == ret,
+            "ECIES FFI authenticated decryption failed. Exit with error: {ret}, error message: \
+             {:?}",
+            get_last_error()
+        );
+        assert_eq!(
+            plaintext.to_vec(),
+            cleartext[..cleartext_len as usize].to_vec()
+        );
+
+        // Decrypting with the wrong sender public key fails.
+        let ret = h_ecies_x25519_authenticated_decrypt(
+            cleartext.as_mut_ptr(),
+            &mut cleartext_len,
+            ciphertext.as_ptr().cast(),
+            ciphertext_len,
+            recipient_private_key.as_ptr().cast(),
+            recipient_private_key.len() as i32,
+            recipient_public_key.as_ptr().cast(),
+            recipient_public_key.len() as i32,
+            authenticated_data.as_ptr().cast(),
+            authenticated_data.len() as i32,
+        );
+        assert!(0 != ret);
+    }
+}
+
+/// Encrypts and decrypts using the generic ECIES functions with the given
+/// algorithm, or the default one if `None`.
+unsafe fn algorithm_encrypt_decrypt(algorithm: Option<&str>) {
+    let algorithm = algorithm.map(|algorithm| CString::new(algorithm).unwrap());
+    let algorithm_ptr = algorithm
+        .as_ref()
+        .map_or(std::ptr::null(), |algorithm| algorithm.as_ptr());
+    let plaintext = b"plaintext";
+    let authenticated_data = b"authenticated_data";
+
+    let mut public_key = vec![0u8; 64];
+    let mut public_key_len = public_key.len() as i32;
+    let mut private_key = vec![0u8; 64];
+    let mut private_key_len = private_key.len() as i32;
+    let ret = h_ecies_generate_key_pair(
+        public_key.as_mut_ptr(),
+        &mut public_key_len,
+        private_key.as_mut_ptr(),
+        &mut private_key_len,
+        algorithm_ptr,
+    );
+    assert!(
+        0 == ret,
+        "ECIES FFI key pair generation failed. Exit with error: {ret}, error message: {:?}",
+        get_last_error()
+    );
+
+    let mut overhead = 0;
+    let ret = h_ecies_get_encryption_overhead(&mut overhead, algorithm_ptr);
+    assert_eq!(0, ret);
+
+    let mut ciphertext = vec![0u8; plaintext.len() + overhead as usize];
+    let mut ciphertext_len = ciphertext.len() as i32;
+    let ret = h_ecies_encrypt(
+        ciphertext.as_mut_ptr(),
+        &mut ciphertext_len,
+        plaintext.as_ptr().cast(),
+        plaintext.len() as i32,
+        public_key.as_ptr().cast(),
+        public_key_len,
+        authenticated_data.as_ptr().cast(),
+        authenticated_data.len() as i32,
+        algorithm_ptr,
+    );
+    assert!(
+        0 == ret,
+        "ECIES FFI encryption failed. Exit with error: {ret}, error message: {:?}",
+        get_last_error()
+    );
+    assert_eq!(ciphertext_len as usize, ciphertext.len());
+
+    let mut cleartext = vec![0u8; plaintext.len()];
+    let mut cleartext_len = cleartext.len() as i32;
+    let ret = h_ecies_decrypt(
+        cleartext.as_mut_ptr(),
+        &mut cleartext_len,
+        ciphertext.as_ptr().cast(),
+        ciphertext_len,
+        private_key.as_ptr().cast(),
+        private_key_len,
+        authenticated_data.as_ptr().cast(),
+        authenticated_data.len() as i32,
+        algorithm_ptr,
+    );
+    assert!(
+        0 == ret,
+        "ECIES FFI decryption failed. Exit with error: {ret}, error message: {:?}",
+        get_last_error()
+    );
+    assert_eq!(
+        plaintext.to_vec(),
+        cleartext[..cleartext_len as usize].to_vec()
+    );
+
+    // Detached ephemeral public key.
+    let mut public_key_length = 0;
+    let ret = h_ecies_get_public_key_length(&mut public_key_length, algorithm_ptr);
+    assert_eq!(0, ret);
+
+    let mut ephemeral_public_key = vec![0u8; public_key_length as usize];
+    let mut ephemeral_public_key_len = ephemeral_public_key.len() as i32;
+    let mut ciphertext =
+        vec![0u8; plaintext.len() + overhead as usize - ephemeral_public_key.len()];
+    let mut ciphertext_len = ciphertext.len() as i32;
+    let ret = h_ecies_encrypt_detached(
+        ephemeral_public_key.as_mut_ptr(),
+        &mut ephemeral_public_key_len,
+        ciphertext.as_mut_ptr(),
+        &mut ciphertext_len,
+        plaintext.as_ptr().cast(),
+        plaintext.len() as i32,
+        public_key.as_ptr().cast(),
+        public_key_len,
+        authenticated_data.as_ptr().cast(),
+        authenticated_data.len() as i32,
+        algorithm_ptr,
+    );
+    assert!(
+        0 == ret,
+        "ECIES FFI detached encryption failed. Exit with error: {ret}, error message: {:?}",
+        get_last_error()
+    );
+    assert_eq!(
+        ephemeral_public_key_len as usize,
+        ephemeral_public_key.len()
+    );
+    assert_eq!(ciphertext_len as usize, ciphertext.len());
+
+    let mut cleartext = vec![0u8; plaintext.len()];
+    let mut cleartext_len = cleartext.len() as i32;
+    let ret = h_ecies_decrypt_detached(
+        cleartext.as_mut_ptr(),
+        &mut cleartext_len,
+        ephemeral_public_key.as_ptr().cast(),
+        ephemeral_public_key_len,
+        ciphertext.as_ptr().cast(),
+        ciphertext_len,
+        private_key.as_ptr().cast(),
+        private_key_len,
+        authenticated_data.as_ptr().cast(),
+        authenticated_data.len() as i32,
+        algorithm_ptr,
+    );
+    assert!(
+        0 == ret,
+        "ECIES FFI detached decryption failed. Exit with error: {ret}, error message: {:?}",
+        get_last_error()
+    );
+    assert_eq!(
+        plaintext.to_vec(),
+        cleartext[..cleartext_len as usize].to_vec()
+    );
+
+    // Envelope, decrypted without specifying the algorithm.
+    let mut overhead = 0;
+    let ret = h_ecies_envelope_get_encryption_overhead(&mut overhead, algorithm_ptr);
+    assert_eq!(0, ret);
+
+    let mut envelope = vec![0u8; plaintext.len() + overhead as usize];
+    let mut envelope_len = envelope.len() as i32;
+    let ret = h_ecies_envelope_encrypt(
+        envelope.as_mut_ptr(),
+        &mut envelope_len,
+        plaintext.as_ptr().cast(),
+        plaintext.len() as i32,
+        public_key.as_ptr().cast(),
+        public_key_len,
+        authenticated_data.as_ptr().cast(),
+        authenticated_data.len() as i32,
+        algorithm_ptr,
+    );
+    assert!(
+        0 == ret,
+        "ECIES FFI envelope encryption failed. Exit with error: {ret}, error message: {:?}",
+        get_last_error()
+    );
+    assert_eq!(envelope_len as usize, envelope.len());
+
+    let mut cleartext = vec![0u8; plaintext.len()];
+    let mut cleartext_len = cleartext.len() as i32;
+    let ret = h_ecies_envelope_decrypt(
+        cleartext.as_mut_ptr(),
+        &mut cleartext_len,
+        envelope.as_ptr().cast(),
+        envelope_len,
+        private_key.as_ptr().cast(),
+        private_key_len,
+        authenticated_data.as_ptr().cast(),
+        authenticated_data.len() as i32,
+    );
+    assert!(
+        0 == ret,
+        "ECIES FFI envelope decryption failed. Exit with error: {ret}, error message: {:?}",
+        get_last_error()
+    );
+    assert_eq!(
+        plaintext.to_vec(),
+        cleartext[..cleartext_len as usize].to_vec()
+    );
+}
+
+#[test]
+fn algorithm_encrypt_decrypt_default() {
+    unsafe {
+        algorithm_encrypt_decrypt(None);
+        algorithm_encrypt_decrypt(Some("x25519-xsalsa20-poly1305"));
+
+        let unknown = CString::new("unknown").unwrap();
+        let mut overhead = 0;
+        let ret = h_ecies_get_encryption_overhead(&mut overhead, unknown.as_ptr());
+        assert!(0 != ret);
+    }
+}
+
+#[cfg(feature = "nist_curves")]
+#[test]
+fn algorithm_encrypt_decrypt_p256() {
+    unsafe { algorithm_encrypt_decrypt(Some("p256-aes128-gcm")) };
+}
+
+#[cfg(feature = "pq")]
+#[test]
+fn x25519_kyber768_encrypt_decrypt() {
+    use crate::{
+        ffi::hybrid::{
+            h_ecies_x25519_kyber768_decrypt, h_ecies_x25519_kyber768_encrypt,
+            h_ecies_x25519_kyber768_generate_key_pair,
+        },
+        EciesX25519Kyber768, X25519Kyber768PrivateKey, X25519Kyber768PublicKey,
+    };
+
+    let plaintext = b"plaintext";
+    let authenticated_data = b"authenticated_data";
+
+    let mut public_key = vec![0u8; X25519Kyber768PublicKey::LENGTH];
+    let mut public_key_len = public_key.len() as i32;
+    let mut private_key = vec![0u8; X25519Kyber768PrivateKey::LENGTH];
+    let mut private_key_len = private_key.len() as i32;
+    let mut ciphertext = vec![0u8; plaintext.len() + EciesX25519Kyber768::ENCRYPTION_OVERHEAD];
+    let mut ciphertext_len = ciphertext.len() as i32;
+    let mut cleartext = vec![0u8; plaintext.len()];
+    let mut cleartext_len = cleartext.len() as i32;
+
+    unsafe {
+        let ret = h_ecies_x25519_kyber768_generate_key_pair(
+            public_key.as_mut_ptr(),
+            &mut public_key_len,
+            private_key.as_mut_ptr(),
+            &mut private_key_len,
+        );
+        assert!(
+            0 == ret,
+            "ECIES FFI key pair generation failed. Exit with error: {ret}, error message: {:?}",
+            get_last_error()
+        );
+
+        let ret = h_ecies_x25519_kyber768_encrypt(
+            ciphertext.as_mut_ptr(),
+            &mut ciphertext_len,
+            plaintext.as_ptr().cast(),
+            plaintext.len() as i32,
+            public_key.as_ptr().cast(),
+            public_key_len,
+            authenticated_data.as_ptr().cast(),
+            authenticated_data.len() as i32,
+        );
+        assert!(
+            0 == ret,
+            "ECIES FFI hybrid encryption failed. Exit with error: {ret}, error message: {:?}",
+            get_last_error()
+        );
+
+        let ret = h_ecies_x25519_kyber768_decrypt(
+            cleartext.as_mut_ptr(),
+            &mut cleartext_len,
+            ciphertext.as_ptr().cast(),
+            ciphertext_len,
+            private_key.as_ptr().cast(),
+            private_key_len,
+            authenticated_data.as_ptr().cast(),
+            authenticated_data.len() as i32,
+        );
+        assert!(
+            0 == ret,
+            "ECIES FFI hybrid decryption failed. Exit with error: {ret}, error message: {:?}",
+            get_last_error()
+        );
+        assert_eq!(
+            plaintext.to_vec(),
+            cleartext[..cleartext_len as usize].to_vec()
+        );
+    }
+}
@@ -6,3 +6,15 @@ pub mod pyo3;
 
 #[cfg(feature = "wasm")]
 pub mod wasm_bindgen;
+
+mod core;
+
+pub use crate::core::{
+    algorithm::EciesAlgorithm, authenticated::EciesX25519Authenticated, envelope::EciesEnvelope,
+    mnemonic::X25519Mnemonic, pem::X25519Pem,
+};
+
+#[cfg(feature = "pq")]
+pub use crate::core::hybrid::{
+    EciesX25519Kyber768, X25519Kyber768PrivateKey, X25519Kyber768PublicKey,
+};
@@ -4,6 +4,16 @@ use cosmian_crypto_core::{
 };
 use pyo3::{exceptions::PyException, pyclass, pymethods, PyResult};
 
+use crate::{
+    EciesAlgorithm, EciesEnvelope, EciesX25519Authenticated as EciesX25519AuthenticatedRust,
+    X25519Mnemonic as X25519MnemonicRust,
+};
+#[cfg(feature = "pq")]
+use crate::{
+    EciesX25519Kyber768 as EciesX25519Kyber768Rust, X25519Kyber768PrivateKey,
+    X25519Kyber768PublicKey,
+};
+
 #[pyclass]
 pub struct EciesSalsaSealBox;
 
@@ -70,3 +80,255 @@ impl EciesSalsaSealBox {
         Ok(plaintext)
     }
 }
+
+/// Parses the given ECIES algorithm, defaulting to the X25519 sealed box.
+fn parse_algorithm(algorithm: Option<&str>) -> PyResult<EciesAlgorithm> {
+    algorithm.map_or(Ok(EciesAlgorithm::default()), |algorithm| {
+        algorithm
+            .parse()
+            .map_err(|e| PyException::new_err(format!("{e:?}")))
+    })
+}
+
+/// ECIES with an algorithm selectable at runtime: `x25519-xsalsa20-poly1305`
+/// (default) or `p256-aes128-gcm`.
+#[pyclass]
+pub struct Ecies;
+
+#[pymethods]
+impl Ecies {
+    #[staticmethod]
+    #[pyo3(signature = (algorithm = None))]
+    fn generate_key_pair(algorithm: Option<&str>) -> PyResult<(Vec<u8>, Vec<u8>)> {
+        let algorithm = parse_algorithm(algorithm)?;
+        let mut rng = CsRng::from_entropy();
+        Ok(algorithm.generate_key_pair(&mut rng))
+    }
+
+    #[staticmethod]
+    #[pyo3(signature = (algorithm = None))]
+    fn encryption_overhead(algorithm: Option<&str>) -> PyResult<usize> {
+        Ok(parse_algorithm(algorithm)?.encryption_overhead())
+    }
+
+    #[staticmethod]
+    #[pyo3(signature = (plaintext, public_key, authenticated_data, algorithm = None))]
+    fn encrypt(
+        plaintext: Vec<u8>,
+        public_key: Vec<u8>,
+        authenticated_data: Vec<u8>,
+        algorithm: Option<&str>,
+    ) -> PyResult<Vec<u8>> {
+        let algorithm = parse_algorithm(algorithm)?;
+        let mut rng = CsRng::from_entropy();
+        algorithm
+            .encrypt(&mut rng, &public_key, &plaintext, Some(&authenticated_data))
+            .map_err(|e| PyException::new_err(format!("ECIES error: encryption: {e:?}")))
+    }
+
+    #[staticmethod]
+    #[pyo3(signature = (ciphertext, private_key, authenticated_data, algorithm = None))]
+    fn decrypt(
+        ciphertext: Vec<u8>,
+        private_key: Vec<u8>,
+        authenticated_data: Vec<u8>,
+        algorithm: Option<&str>,
+    ) -> PyResult<Vec<u8>> {
+        let algorithm = parse_algorithm(algorithm)?;
+        algorithm
+            .decrypt(&private_key, &ciphertext, Some(&authenticated_data))
+            .map_err(|e| PyException::new_err(format!("ECIES error: decryption: {e:?}")))
+    }
+
+    #[staticmethod]
+    #[pyo3(signature = (algorithm = None))]
+    fn envelope_encryption_overhead(algorithm: Option<&str>) -> PyResult<usize> {
+        Ok(EciesEnvelope::encryption_overhead(parse_algorithm(
+            algorithm,
+        )?))
+    }
+
+    #[staticmethod]
+    #[pyo3(signature = (plaintext, public_key, authenticated_data, algorithm = None))]
+    fn envelope_encrypt(
+        plaintext: Vec<u8>,
+        public_key: Vec<u8>,
+        authenticated_data: Vec<u8>,
+        algorithm: Option<&str>,
+    ) -> PyResult<Vec<u8>> {
+        let algorithm = parse_algorithm(algorithm)?;
+        let mut rng = CsRng::from_entropy();
+        EciesEnvelope::encrypt(
+            algorithm,
+            &mut rng,
+            &public_key,
+            &plaintext,
+            Some(&authenticated_data),
+        )
+        .and_then(|envelope| envelope.to_bytes())
+        .map_err(|e| PyException::new_err(format!("ECIES error: encryption: {e:?}")))
+    }
+
+    #[staticmethod]
+    fn envelope_decrypt(
+        envelope: Vec<u8>,
+        private_key: Vec<u8>,
+        authenticated_data: Vec<u8>,
+    ) -> PyResult<Vec<u8>> {
+        EciesEnvelope::parse(&envelope)
+            .and_then(|envelope| envelope.decrypt(&private_key, Some(&authenticated_data)))
+            .map_err(|e| PyException::new_err(format!("ECIES error: decryption: {e:?}")))
+    }
+}
+
+#[pyclass]
+pub struct EciesX25519Authenticated;
+
+#[pymethods]
+impl EciesX25519Authenticated {
+    #[staticmethod]
+    fn generate_key_pair() -> PyResult<(Vec<u8>, Vec<u8>)> {
+        EciesSalsaSealBox::generate_key_pair()
+    }
+
+    #[staticmethod]
+    fn encrypt(
+        plaintext: Vec<u8>,
+        sender_private_key: Vec<u8>,
+        recipient_public_key: Vec<u8>,
+        authenticated_data: Vec<u8>,
+    ) -> PyResult<Vec<u8>> {
+        let mut rng = CsRng::from_entropy();
+        let sender_private_key =
+            X25519PrivateKey::try_from_slice(&sender_private_key).map_err(|e| {
+                PyException::new_err(format!(
+                    "ECIES error: sender private key deserializing: {e:?}"
+                ))
+            })?;
+        let recipient_public_key =
+            X25519PublicKey::try_from_slice(&recipient_public_key).map_err(|e| {
+                PyException::new_err(format!(
+                    "ECIES error: recipient public key deserializing: {e:?}"
+                ))
+            })?;
+
+        EciesX25519AuthenticatedRust::encrypt(
+            &mut rng,
+            &sender_private_key,
+            &recipient_public_key,
+            &plaintext,
+            Some(&authenticated_data),
+        )
+        .map_err(|e| PyException::new_err(format!("ECIES error: encryption: {e:?}")))
+    }
+
+    #[staticmethod]
+    fn decrypt(
+        ciphertext: Vec<u8>,
+        recipient_private_key: Vec<u8>,
+        sender_public_key: Vec<u8>,
+        authenticated_data: Vec<u8>,
+    ) -> PyResult<Vec<u8>> {
+        let recipient_private_key = X25519PrivateKey::try_from_slice(&recipient_private_key)
+            .map_err(|e| {
+                PyException::new_err(format!(
+                    "ECIES error: recipient private key deserializing: {e:?}"
+                ))
+            })?;
+        let sender_public_key =
+            X25519PublicKey::try_from_slice(&sender_public_key).map_err(|e| {
+                PyException::new_err(format!(
+                    "ECIES error: sender public key deserializing: {e:?}"
+                ))
+            })?;
+
+        EciesX25519AuthenticatedRust::decrypt(
+            &recipient_private_key,
+            &sender_public_key,
+            &ciphertext,
+            Some(&authenticated_data),
+        )
+        .map_err(|e| PyException::new_err(format!("ECIES error: decryption: {e:?}")))
+    }
+}
+
+#[pyclass]
+pub struct X25519Mnemonic;
+
+#[pymethods]
+impl X25519Mnemonic {
+    /// Generates a new BIP39 mnemonic and the X25519 key pair it backs up.
+    #[staticmethod]
+    fn generate_key_pair() -> PyResult<(String, Vec<u8>, Vec<u8>)> {
+        let mut rng = CsRng::from_entropy();
+        let (mnemonic, public_key, private_key) =
+            X25519MnemonicRust::generate_key_pair(&mut rng)
+                .map_err(|e| PyException::new_err(format!("{e:?}")))?;
+        Ok((
+            mnemonic,
+            public_key.to_bytes().to_vec(),
+            private_key.to_bytes().to_vec(),
+        ))
+    }
+
+    /// Derives the X25519 key pair backed up by the given BIP39 mnemonic.
+    #[staticmethod]
+    fn key_pair_from_mnemonic(mnemonic: &str) -> PyResult<(Vec<u8>, Vec<u8>)> {
+        let (public_key, private_key) = X25519MnemonicRust::key_pair_from_mnemonic(mnemonic)
+            .map_err(|e| PyException::new_err(format!("{e:?}")))?;
+        Ok((
+            public_key.to_bytes().to_vec(),
+            private_key.to_bytes().to_vec(),
+        ))
+    }
+}
+
+/// Hybrid post-quantum ECIES based on X25519 and Kyber768.
+#[cfg(feature = "pq")]
+#[pyclass]
+pub struct EciesX25519Kyber768;
+
+#[cfg(feature = "pq")]
+#[pymethods]
+impl EciesX25519Kyber768 {
+    #[staticmethod]
+    fn generate_key_pair() -> PyResult<(Vec<u8>, Vec<u8>)> {
+        let mut rng = CsRng::from_entropy();
+        let (public_key, private_key) = EciesX25519Kyber768Rust::generate_key_pair(&mut rng);
+        Ok((public_key.to_bytes(), private_key.to_bytes().to_vec()))
+    }
+
+    #[staticmethod]
+    fn encrypt(
+        plaintext: Vec<u8>,
+        public_key: Vec<u8>,
+        authenticated_data: Vec<u8>,
+    ) -> PyResult<Vec<u8>> {
+        let mut rng = CsRng::from_entropy();
+        let public_key = X25519Kyber768PublicKey::try_from_slice(&public_key).map_err(|e| {
+            PyException::new_err(format!("ECIES error: public key deserializing: {e:?}"))
+        })?;
+
+        EciesX25519Kyber768Rust::encrypt(
+            &mut rng,
+            &public_key,
+            &plaintext,
+            Some(&authenticated_data),
+        )
+        .map_err(|e| PyException::new_err(format!("ECIES error: encryption: {e:?}")))
+    }
+
+    #[staticmethod]
+    fn decrypt(
+        ciphertext: Vec<u8>,
+        private_key: Vec<u8>,
+        authenticated_data: Vec<u8>,
+    ) -> PyResult<Vec<u8>> {
+        let private_key = X25519Kyber768PrivateKey::try_from_slice(&private_key).map_err(|e| {
+            PyException::new_err(format!("ECIES error: private key deserializing: {e:?}"))
+        })?;
+
+        EciesX25519Kyber768Rust::decrypt(&private_key, &ciphertext, Some(&authenticated_data))
+            .map_err(|e| PyException::new_err(format!("ECIES error: decryption: {e:?}")))
+    }
+}
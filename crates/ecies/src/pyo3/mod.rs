@@ -1,13 +1,25 @@
 use pyo3::{pymodule, types::PyModule, PyResult, Python};
 
-use self::ecies::EciesSalsaSealBox;
+#[cfg(feature = "pq")]
+use self::ecies::EciesX25519Kyber768;
+use self::{
+    ecies::{Ecies, EciesSalsaSealBox, EciesX25519Authenticated, X25519Mnemonic},
+    private_key::PrivateKey,
+};
 
 mod ecies;
+mod private_key;
 
 /// A Python module implemented in Rust.
 #[pymodule]
 fn cloudproof_ecies(_py: Python, m: &PyModule) -> PyResult<()> {
+    m.add_class::<Ecies>()?;
     m.add_class::<EciesSalsaSealBox>()?;
+    m.add_class::<EciesX25519Authenticated>()?;
+    m.add_class::<X25519Mnemonic>()?;
+    m.add_class::<PrivateKey>()?;
+    #[cfg(feature = "pq")]
+    m.add_class::<EciesX25519Kyber768>()?;
 
     Ok(())
 }
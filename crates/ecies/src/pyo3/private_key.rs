@@ -0,0 +1,113 @@
+use cosmian_crypto_core::{
+    reexport::rand_core::SeedableRng, CsRng, Ecies, EciesSalsaSealBox, FixedSizeCBytes,
+    X25519PrivateKey, X25519PublicKey,
+};
+use pyo3::{
+    exceptions::PyException, pyclass, pymethods, types::PyBytes, Py, PyAny, PyResult, Python,
+};
+
+use crate::X25519Pem;
+
+/// X25519 private key held in Rust memory.
+///
+/// Passing private keys around as `bytes` leaves copies in Python objects
+/// that are never erased. The key held by this class is zeroized when the
+/// object is garbage collected or explicitly destroyed; only `to_bytes` and
+/// `to_pem` copy it to Python.
+#[pyclass]
+pub struct PrivateKey(Option<X25519PrivateKey>);
+
+impl PrivateKey {
+    fn key(&self) -> PyResult<&X25519PrivateKey> {
+        self.0
+            .as_ref()
+            .ok_or_else(|| PyException::new_err("ECIES error: the private key has been destroyed"))
+    }
+}
+
+#[pymethods]
+impl PrivateKey {
+    /// Generates a new private key.
+    #[staticmethod]
+    fn generate() -> Self {
+        let mut rng = CsRng::from_entropy();
+        Self(Some(X25519PrivateKey::new(&mut rng)))
+    }
+
+    /// Reads the private key from its raw bytes.
+    #[staticmethod]
+    fn from_bytes(private_key: &[u8]) -> PyResult<Self> {
+        X25519PrivateKey::try_from_slice(private_key)
+            .map(|private_key| Self(Some(private_key)))
+            .map_err(|e| {
+                PyException::new_err(format!("ECIES error: private key deserializing: {e:?}"))
+            })
+    }
+
+    /// Reads the private key from a PKCS#8 PEM.
+    #[staticmethod]
+    fn from_pem(pem: &str) -> PyResult<Self> {
+        X25519Pem::private_key_from_pem(pem)
+            .map(|private_key| Self(Some(private_key)))
+            .map_err(|e| PyException::new_err(format!("{e:?}")))
+    }
+
+    /// Copies the raw bytes of the private key to Python.
+    fn to_bytes(&self, py: Python) -> PyResult<Py<PyBytes>> {
+        Ok(PyBytes::new(py, self.key()?.as_bytes()).into())
+    }
+
+    /// Exports the private key as a PKCS#8 PEM, e.g. to store it in a keyring.
+    fn to_pem(&self) -> PyResult<String> {
+        Ok(X25519Pem::private_key_to_pem(self.key()?).to_string())
+    }
+
+    /// Returns the public key of this private key.
+    fn public_key(&self, py: Python) -> PyResult<Py<PyBytes>> {
+        Ok(PyBytes::new(py, X25519PublicKey::from(self.key()?).as_bytes()).into())
+    }
+
+    /// Decrypts the given `EciesSalsaSealBox` ciphertext without exposing the
+    /// private key to Python.
+    fn decrypt(
+        &self,
+        ciphertext: &[u8],
+        authenticated_data: &[u8],
+        py: Python,
+    ) -> PyResult<Py<PyBytes>> {
+        let plaintext =
+            EciesSalsaSealBox::decrypt(self.key()?, ciphertext, Some(authenticated_data))
+                .map_err(|e| PyException::new_err(format!("ECIES error: decryption: {e:?}")))?;
+        Ok(PyBytes::new(py, &plaintext).into())
+    }
+
+    /// Zeroizes the private key. Any later use of this object fails.
+    fn destroy(&mut self) {
+        // The key is zeroized on drop.
+        self.0 = None;
+    }
+
+    /// Whether `destroy` has been called.
+    #[getter]
+    fn is_destroyed(&self) -> bool {
+        self.0.is_none()
+    }
+
+    fn __enter__(slf: Py<Self>) -> Py<Self> {
+        slf
+    }
+
+    /// Destroys the private key when leaving the `with` block.
+    fn __exit__(&mut self, _exc_type: &PyAny, _exc_value: &PyAny, _traceback: &PyAny) -> bool {
+        self.destroy();
+        false
+    }
+
+    fn __repr__(&self) -> &'static str {
+        if self.0.is_some() {
+            "PrivateKey(<secret>)"
+        } else {
+            "PrivateKey(<destroyed>)"
+        }
+    }
+}
@@ -5,6 +5,10 @@ use cosmian_crypto_core::{
 use js_sys::Uint8Array;
 use wasm_bindgen::{prelude::wasm_bindgen, JsValue};
 
+use crate::{EciesAlgorithm, EciesEnvelope, EciesX25519Authenticated, X25519Mnemonic};
+#[cfg(feature = "pq")]
+use crate::{EciesX25519Kyber768, X25519Kyber768PrivateKey, X25519Kyber768PublicKey};
+
 #[wasm_bindgen]
 pub fn webassembly_x25519_generate_key_pair() -> Result<Uint8Array, JsValue> {
     let mut rng = CsRng::from_entropy();
@@ -18,6 +22,31 @@ pub fn webassembly_x25519_generate_key_pair() -> Result<Uint8Array, JsValue> {
     Ok(Uint8Array::from(pk.as_slice()))
 }
 
+/// Generates a new 24-word BIP39 mnemonic backing up an X25519 key pair.
+///
+/// Use `webassembly_x25519_key_pair_from_mnemonic` to derive the key pair.
+#[wasm_bindgen]
+pub fn webassembly_x25519_generate_mnemonic() -> Result<String, JsValue> {
+    let mut rng = CsRng::from_entropy();
+    X25519Mnemonic::generate_mnemonic(&mut rng)
+        .map_err(|e| JsValue::from_str(&format!("ECIES error: {e:?}")))
+}
+
+/// Derives the X25519 key pair backed up by the given BIP39 mnemonic.
+///
+/// Returns the public key followed by the private key, as
+/// `webassembly_x25519_generate_key_pair`.
+#[wasm_bindgen]
+pub fn webassembly_x25519_key_pair_from_mnemonic(mnemonic: &str) -> Result<Uint8Array, JsValue> {
+    let (public_key, private_key) = X25519Mnemonic::key_pair_from_mnemonic(mnemonic)
+        .map_err(|e| JsValue::from_str(&format!("ECIES error: {e:?}")))?;
+
+    let mut pk = public_key.to_bytes().to_vec();
+    pk.extend_from_slice(&private_key.to_bytes());
+
+    Ok(Uint8Array::from(pk.as_slice()))
+}
+
 #[wasm_bindgen]
 pub fn webassembly_ecies_salsa_seal_box_encrypt(
     plaintext: Vec<u8>,
@@ -63,3 +92,218 @@ pub fn webassembly_ecies_salsa_seal_box_decrypt(
             .map_err(|e| JsValue::from_str(&format!("ECIES error: decryption: {e:?}")))?;
     Ok(Uint8Array::from(plaintext.as_slice()))
 }
+
+#[wasm_bindgen]
+pub fn webassembly_ecies_x25519_authenticated_encrypt(
+    plaintext: Vec<u8>,
+    sender_private_key: Vec<u8>,
+    recipient_public_key: Vec<u8>,
+    authenticated_data: Vec<u8>,
+) -> Result<Uint8Array, JsValue> {
+    let mut rng = CsRng::from_entropy();
+    let sender_private_key =
+        X25519PrivateKey::try_from_slice(&sender_private_key).map_err(|e| {
+            JsValue::from_str(&format!(
+                "ECIES error: sender private key deserializing: {e:?}"
+            ))
+        })?;
+    let recipient_public_key =
+        X25519PublicKey::try_from_slice(&recipient_public_key).map_err(|e| {
+            JsValue::from_str(&format!(
+                "ECIES error: recipient public key deserializing: {e:?}"
+            ))
+        })?;
+
+    let ciphertext = EciesX25519Authenticated::encrypt(
+        &mut rng,
+        &sender_private_key,
+        &recipient_public_key,
+        &plaintext,
+        Some(&authenticated_data),
+    )
+    .map_err(|e| JsValue::from_str(&format!("ECIES error: encryption: {e:?}")))?;
+
+    Ok(Uint8Array::from(ciphertext.as_slice()))
+}
+
+#[wasm_bindgen]
+pub fn webassembly_ecies_x25519_authenticated_decrypt(
+    ciphertext: Vec<u8>,
+    recipient_private_key: Vec<u8>,
+    sender_public_key: Vec<u8>,
+    authenticated_data: Vec<u8>,
+) -> Result<Uint8Array, JsValue> {
+    let recipient_private_key =
+        X25519PrivateKey::try_from_slice(&recipient_private_key).map_err(|e| {
+            JsValue::from_str(&format!(
+                "ECIES error: recipient private key deserializing: {e:?}"
+            ))
+        })?;
+    let sender_public_key = X25519PublicKey::try_from_slice(&sender_public_key).map_err(|e| {
+        JsValue::from_str(&format!(
+            "ECIES error: sender public key deserializing: {e:?}"
+        ))
+    })?;
+
+    let plaintext = EciesX25519Authenticated::decrypt(
+        &recipient_private_key,
+        &sender_public_key,
+        &ciphertext,
+        Some(&authenticated_data),
+    )
+    .map_err(|e| JsValue::from_str(&format!("ECIES error: decryption: {e:?}")))?;
+
+    Ok(Uint8Array::from(plaintext.as_slice()))
+}
+
+/// Parses the given ECIES algorithm, defaulting to the X25519 sealed box.
+fn parse_algorithm(algorithm: Option<String>) -> Result<EciesAlgorithm, JsValue> {
+    algorithm.map_or(Ok(EciesAlgorithm::default()), |algorithm| {
+        algorithm
+            .parse()
+            .map_err(|e| JsValue::from_str(&format!("{e:?}")))
+    })
+}
+
+/// Generates a new key pair for the given ECIES algorithm
+/// (`x25519-xsalsa20-poly1305` by default, or `p256-aes128-gcm`).
+///
+/// Returns the public key followed by the private key. Public keys are 32
+/// bytes long for X25519 and 33 bytes long for P-256.
+#[wasm_bindgen]
+pub fn webassembly_ecies_generate_key_pair(
+    algorithm: Option<String>,
+) -> Result<Uint8Array, JsValue> {
+    let algorithm = parse_algorithm(algorithm)?;
+    let mut rng = CsRng::from_entropy();
+    let (mut pk, sk) = algorithm.generate_key_pair(&mut rng);
+    pk.extend_from_slice(&sk);
+
+    Ok(Uint8Array::from(pk.as_slice()))
+}
+
+/// Encrypts the given plaintext using the given ECIES algorithm
+/// (`x25519-xsalsa20-poly1305` by default, or `p256-aes128-gcm`).
+#[wasm_bindgen]
+pub fn webassembly_ecies_encrypt(
+    plaintext: Vec<u8>,
+    public_key: Vec<u8>,
+    authenticated_data: Vec<u8>,
+    algorithm: Option<String>,
+) -> Result<Uint8Array, JsValue> {
+    let algorithm = parse_algorithm(algorithm)?;
+    let mut rng = CsRng::from_entropy();
+    let ciphertext = algorithm
+        .encrypt(&mut rng, &public_key, &plaintext, Some(&authenticated_data))
+        .map_err(|e| JsValue::from_str(&format!("ECIES error: encryption: {e:?}")))?;
+
+    Ok(Uint8Array::from(ciphertext.as_slice()))
+}
+
+/// Decrypts the given ciphertext using the given ECIES algorithm
+/// (`x25519-xsalsa20-poly1305` by default, or `p256-aes128-gcm`).
+#[wasm_bindgen]
+pub fn webassembly_ecies_decrypt(
+    ciphertext: Vec<u8>,
+    private_key: Vec<u8>,
+    authenticated_data: Vec<u8>,
+    algorithm: Option<String>,
+) -> Result<Uint8Array, JsValue> {
+    let algorithm = parse_algorithm(algorithm)?;
+    let plaintext = algorithm
+        .decrypt(&private_key, &ciphertext, Some(&authenticated_data))
+        .map_err(|e| JsValue::from_str(&format!("ECIES error: decryption: {e:?}")))?;
+
+    Ok(Uint8Array::from(plaintext.as_slice()))
+}
+
+/// Encrypts the given plaintext using the given ECIES algorithm
+/// (`x25519-xsalsa20-poly1305` by default, or `p256-aes128-gcm`).
+///
+/// Returns a versioned envelope recording the algorithm used.
+#[wasm_bindgen]
+pub fn webassembly_ecies_envelope_encrypt(
+    plaintext: Vec<u8>,
+    public_key: Vec<u8>,
+    authenticated_data: Vec<u8>,
+    algorithm: Option<String>,
+) -> Result<Uint8Array, JsValue> {
+    let algorithm = parse_algorithm(algorithm)?;
+    let mut rng = CsRng::from_entropy();
+    let envelope = EciesEnvelope::encrypt(
+        algorithm,
+        &mut rng,
+        &public_key,
+        &plaintext,
+        Some(&authenticated_data),
+    )
+    .and_then(|envelope| envelope.to_bytes())
+    .map_err(|e| JsValue::from_str(&format!("ECIES error: encryption: {e:?}")))?;
+
+    Ok(Uint8Array::from(envelope.as_slice()))
+}
+
+/// Decrypts the given envelope using the algorithm it records.
+#[wasm_bindgen]
+pub fn webassembly_ecies_envelope_decrypt(
+    envelope: Vec<u8>,
+    private_key: Vec<u8>,
+    authenticated_data: Vec<u8>,
+) -> Result<Uint8Array, JsValue> {
+    let plaintext = EciesEnvelope::parse(&envelope)
+        .and_then(|envelope| envelope.decrypt(&private_key, Some(&authenticated_data)))
+        .map_err(|e| JsValue::from_str(&format!("ECIES error: decryption: {e:?}")))?;
+
+    Ok(Uint8Array::from(plaintext.as_slice()))
+}
+
+/// Generates a new X25519 + Kyber768 key pair.
+///
+/// Returns the public key followed by the private key.
+#[cfg(feature = "pq")]
+#[wasm_bindgen]
+pub fn webassembly_x25519_kyber768_generate_key_pair() -> Result<Uint8Array, JsValue> {
+    let mut rng = CsRng::from_entropy();
+    let (public_key, private_key) = EciesX25519Kyber768::generate_key_pair(&mut rng);
+
+    let mut pk = public_key.to_bytes();
+    pk.extend_from_slice(&private_key.to_bytes());
+
+    Ok(Uint8Array::from(pk.as_slice()))
+}
+
+#[cfg(feature = "pq")]
+#[wasm_bindgen]
+pub fn webassembly_ecies_x25519_kyber768_encrypt(
+    plaintext: Vec<u8>,
+    public_key: Vec<u8>,
+    authenticated_data: Vec<u8>,
+) -> Result<Uint8Array, JsValue> {
+    let mut rng = CsRng::from_entropy();
+    let public_key = X25519Kyber768PublicKey::try_from_slice(&public_key)
+        .map_err(|e| JsValue::from_str(&format!("ECIES error: public key deserializing: {e:?}")))?;
+
+    let ciphertext =
+        EciesX25519Kyber768::encrypt(&mut rng, &public_key, &plaintext, Some(&authenticated_data))
+            .map_err(|e| JsValue::from_str(&format!("ECIES error: encryption: {e:?}")))?;
+
+    Ok(Uint8Array::from(ciphertext.as_slice()))
+}
+
+#[cfg(feature = "pq")]
+#[wasm_bindgen]
+pub fn webassembly_ecies_x25519_kyber768_decrypt(
+    ciphertext: Vec<u8>,
+    private_key: Vec<u8>,
+    authenticated_data: Vec<u8>,
+) -> Result<Uint8Array, JsValue> {
+    let private_key = X25519Kyber768PrivateKey::try_from_slice(&private_key).map_err(|e| {
+        JsValue::from_str(&format!("ECIES error: private key deserializing: {e:?}"))
+    })?;
+
+    let plaintext =
+        EciesX25519Kyber768::decrypt(&private_key, &ciphertext, Some(&authenticated_data))
+            .map_err(|e| JsValue::from_str(&format!("ECIES error: decryption: {e:?}")))?;
+
+    Ok(Uint8Array::from(plaintext.as_slice()))
+}
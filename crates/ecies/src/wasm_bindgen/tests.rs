@@ -2,8 +2,12 @@ use cosmian_crypto_core::{FixedSizeCBytes, X25519PublicKey};
 use wasm_bindgen_test::wasm_bindgen_test;
 
 use crate::wasm_bindgen::ecies::{
+    webassembly_ecies_decrypt, webassembly_ecies_encrypt, webassembly_ecies_envelope_decrypt,
+    webassembly_ecies_envelope_encrypt, webassembly_ecies_generate_key_pair,
     webassembly_ecies_salsa_seal_box_decrypt, webassembly_ecies_salsa_seal_box_encrypt,
-    webassembly_x25519_generate_key_pair,
+    webassembly_ecies_x25519_authenticated_decrypt, webassembly_ecies_x25519_authenticated_encrypt,
+    webassembly_x25519_generate_key_pair, webassembly_x25519_generate_mnemonic,
+    webassembly_x25519_key_pair_from_mnemonic,
 };
 
 #[wasm_bindgen_test]
@@ -29,3 +33,162 @@ fn test_encrypt_decrypt() {
     .unwrap();
     assert_eq!(plaintext.to_vec(), cleartext.to_vec());
 }
+
+fn algorithm_encrypt_decrypt(algorithm: Option<&str>, public_key_length: usize) {
+    let key_pair = webassembly_ecies_generate_key_pair(algorithm.map(String::from))
+        .unwrap()
+        .to_vec();
+    let (public_key, private_key) = key_pair.split_at(public_key_length);
+
+    let plaintext = b"plaintext";
+    let authenticated_data = b"authenticated_data";
+
+    let ciphertext = webassembly_ecies_encrypt(
+        plaintext.to_vec(),
+        public_key.to_vec(),
+        authenticated_data.to_vec(),
+        algorithm.map(String::from),
+    )
+    .unwrap();
+    let cleartext = webassembly_ecies_decrypt(
+        ciphertext.to_vec(),
+        private_key.to_vec(),
+        authenticated_data.to_vec(),
+        algorithm.map(String::from),
+    )
+    .unwrap();
+    assert_eq!(plaintext.to_vec(), cleartext.to_vec());
+
+    let envelope = webassembly_ecies_envelope_encrypt(
+        plaintext.to_vec(),
+        public_key.to_vec(),
+        authenticated_data.to_vec(),
+        algorithm.map(String::from),
+    )
+    .unwrap();
+    let cleartext = webassembly_ecies_envelope_decrypt(
+        envelope.to_vec(),
+        private_key.to_vec(),
+        authenticated_data.to_vec(),
+    )
+    .unwrap();
+    assert_eq!(plaintext.to_vec(), cleartext.to_vec());
+}
+
+#[wasm_bindgen_test]
+fn test_algorithm_encrypt_decrypt() {
+    algorithm_encrypt_decrypt(None, X25519PublicKey::LENGTH);
+    algorithm_encrypt_decrypt(Some("x25519-xsalsa20-poly1305"), X25519PublicKey::LENGTH);
+    #[cfg(feature = "nist_curves")]
+    algorithm_encrypt_decrypt(
+        Some("p256-aes128-gcm"),
+        cosmian_crypto_core::P256PublicKey::LENGTH,
+    );
+    assert!(webassembly_ecies_generate_key_pair(Some("unknown".to_string())).is_err());
+}
+
+#[wasm_bindgen_test]
+fn test_authenticated_encrypt_decrypt() {
+    let sender_key_pair = webassembly_x25519_generate_key_pair().unwrap().to_vec();
+    let recipient_key_pair = webassembly_x25519_generate_key_pair().unwrap().to_vec();
+    let (sender_public_key, sender_private_key) = sender_key_pair.split_at(X25519PublicKey::LENGTH);
+    let (recipient_public_key, recipient_private_key) =
+        recipient_key_pair.split_at(X25519PublicKey::LENGTH);
+
+    let plaintext = b"plaintext";
+    let authenticated_data = b"authenticated_data";
+
+    let ciphertext = webassembly_ecies_x25519_authenticated_encrypt(
+        plaintext.to_vec(),
+        sender_private_key.to_vec(),
+        recipient_public_key.to_vec(),
+        authenticated_data.to_vec(),
+    )
+    .unwrap();
+    let cleartext = webassembly_ecies_x25519_authenticated_decrypt(
+        ciphertext.to_vec(),
+        recipient_private_key.to_vec(),
+        sender_public_key.to_vec(),
+        authenticated_data.to_vec(),
+    )
+    .unwrap();
+    assert_eq!(plaintext.to_vec(), cleartext.to_vec());
+
+    // The sender is verified upon decryption.
+    assert!(webassembly_ecies_x25519_authenticated_decrypt(
+        ciphertext.to_vec(),
+        recipient_private_key.to_vec(),
+        recipient_public_key.to_vec(),
+        authenticated_data.to_vec(),
+    )
+    .is_err());
+}
+
+#[wasm_bindgen_test]
+fn test_mnemonic_key_pair() {
+    let mnemonic = webassembly_x25519_generate_mnemonic().unwrap();
+    let key_pair = webassembly_x25519_key_pair_from_mnemonic(&mnemonic)
+        .unwrap()
+        .to_vec();
+    assert_eq!(
+        key_pair,
+        webassembly_x25519_key_pair_from_mnemonic(&mnemonic)
+            .unwrap()
+            .to_vec()
+    );
+    let (public_key, private_key) = key_pair.split_at(X25519PublicKey::LENGTH);
+
+    let ciphertext = webassembly_ecies_salsa_seal_box_encrypt(
+        b"plaintext".to_vec(),
+        public_key.to_vec(),
+        b"authenticated_data".to_vec(),
+    )
+    .unwrap();
+    let cleartext = webassembly_ecies_salsa_seal_box_decrypt(
+        ciphertext.to_vec(),
+        private_key.to_vec(),
+        b"authenticated_data".to_vec(),
+    )
+    .unwrap();
+    assert_eq!(b"plaintext".to_vec(), cleartext.to_vec());
+
+    assert!(webassembly_x25519_key_pair_from_mnemonic("not a mnemonic").is_err());
+}
+
+#[cfg(feature = "pq")]
+#[wasm_bindgen_test]
+fn test_x25519_kyber768_encrypt_decrypt() {
+    use crate::{
+        wasm_bindgen::ecies::{
+            webassembly_ecies_x25519_kyber768_decrypt, webassembly_ecies_x25519_kyber768_encrypt,
+            webassembly_x25519_kyber768_generate_key_pair,
+        },
+        X25519Kyber768PublicKey,
+    };
+
+    let key_pair = webassembly_x25519_kyber768_generate_key_pair()
+        .unwrap()
+        .to_vec();
+    let (public_key, private_key) = key_pair.split_at(X25519Kyber768PublicKey::LENGTH);
+
+    let ciphertext = webassembly_ecies_x25519_kyber768_encrypt(
+        b"plaintext".to_vec(),
+        public_key.to_vec(),
+        b"authenticated_data".to_vec(),
+    )
+    .unwrap();
+    let cleartext = webassembly_ecies_x25519_kyber768_decrypt(
+        ciphertext.to_vec(),
+        private_key.to_vec(),
+        b"authenticated_data".to_vec(),
+    )
+    .unwrap();
+    assert_eq!(b"plaintext".to_vec(), cleartext.to_vec());
+
+    assert!(webassembly_ecies_x25519_kyber768_decrypt(
+        ciphertext.to_vec(),
+        private_key.to_vec(),
+        b"other data".to_vec(),
+    )
+    .is_err());
+}
@@ -1,4 +1,4 @@
-use core::{cell::RefCell, fmt::Display};
+use core::{any::Any, cell::RefCell, fmt::Display};
 use std::ffi::CString;
 
 use crate::ErrorCode;
@@ -7,6 +7,7 @@ use crate::ErrorCode;
 pub enum FfiError {
     NullPointer(String),
     Generic(String),
+    Panic(String),
 }
 
 impl Display for FfiError {
@@ -14,6 +15,7 @@ impl Display for FfiError {
         match self {
             Self::NullPointer(pointer_name) => write!(f, "{pointer_name} shouldn't be null"),
             Self::Generic(err) => write!(f, "{err}"),
+            Self::Panic(msg) => write!(f, "panicked: {msg}"),
         }
     }
 }
@@ -33,6 +35,18 @@ pub fn set_last_error(err: FfiError) {
     });
 }
 
+/// Returns the message carried by the given panic payload.
+#[must_use]
+pub fn panic_message(payload: &(dyn Any + Send)) -> String {
+    if let Some(msg) = payload.downcast_ref::<&str>() {
+        (*msg).to_string()
+    } else if let Some(msg) = payload.downcast_ref::<String>() {
+        msg.clone()
+    } else {
+        "unknown panic payload".to_string()
+    }
+}
+
 /// Gets the last error message.
 #[inline]
 #[must_use]
@@ -56,9 +70,11 @@ pub fn get_last_error() -> String {
 /// - `error_message_ptr`   : pointer to the error message to set
 #[no_mangle]
 pub unsafe extern "C" fn h_set_error(error_message_ptr: *const i8) -> i32 {
-    let error_message = ffi_read_string!("error message", error_message_ptr);
-    set_last_error(FfiError::Generic(error_message));
-    0
+    ffi_guard!({
+        let error_message = ffi_read_string!("error message", error_message_ptr);
+        set_last_error(FfiError::Generic(error_message));
+        0
+    })
 }
 
 /// Externally gets the most recent error recorded on the Rust side, clearing
@@ -76,14 +92,44 @@ pub unsafe extern "C" fn h_set_error(error_message_ptr: *const i8) -> i32 {
 /// - `error_len`: size of the allocated memory
 #[no_mangle]
 pub unsafe extern "C" fn h_get_error(error_ptr: *mut i8, error_len: *mut i32) -> i32 {
-    // Get the error message as a null terminated string.
-    let cs = ffi_unwrap!(
-        CString::new(get_last_error()),
-        "failed to convert error to CString",
-        ErrorCode::InvalidArgument("CString".to_string())
-    );
-
-    ffi_write_bytes!("error", cs.as_bytes(), error_ptr, error_len);
+    ffi_guard!({
+        // Get the error message as a null terminated string.
+        let cs = ffi_unwrap!(
+            CString::new(get_last_error()),
+            "failed to convert error to CString",
+            ErrorCode::InvalidArgument("CString".to_string())
+        );
+
+        ffi_write_bytes!("error", cs.as_bytes(), error_ptr, error_len);
+    })
+}
+
+/// Externally gets the mapping of the error codes returned by the FFI
+/// functions, as a JSON object associating each error name to its value:
+///
+/// ```json
+/// { "Success": 0, "BufferTooSmall": 1, ... }
+/// ```
+///
+/// # Safety
+///
+/// The pointer `table_ptr` should point to a buffer which has been allocated
+/// `table_len` bytes.
+///
+/// # Parameters
+///
+/// - `table_ptr`: pointer to the buffer to which to write the table
+/// - `table_len`: size of the allocated memory
+#[no_mangle]
+pub unsafe extern "C" fn h_get_error_code_table(table_ptr: *mut i8, table_len: *mut i32) -> i32 {
+    ffi_guard!({
+        let entries = ErrorCode::table()
+            .into_iter()
+            .map(|(name, code)| format!("\"{name}\":{code}"))
+            .collect::<Vec<_>>();
+        let table = format!("{{{}}}", entries.join(","));
+        ffi_write_bytes!("error code table", table.as_bytes(), table_ptr, table_len);
+    })
 }
 
 #[cfg(test)]
@@ -127,4 +173,43 @@ mod tests {
         };
         assert!(res.contains("shouldn't be null"));
     }
+
+    #[test]
+    fn test_panic_guard() {
+        unsafe extern "C" fn h_panic(should_panic: bool) -> i32 {
+            ffi_guard!({
+                assert!(!should_panic, "Emergency!!!");
+                0
+            })
+        }
+
+        assert_eq!(unsafe { h_panic(false) }, 0);
+        assert_eq!(unsafe { h_panic(true) }, i32::from(crate::ErrorCode::Panic));
+        assert!(get_last_error().contains("panicked: Emergency!!!"));
+    }
+
+    #[test]
+    fn test_error_code_table() {
+        let table = ErrorCode::table();
+        for (name, code) in &table {
+            let error_code = ErrorCode::from(*code);
+            assert_eq!(error_code.name(), *name);
+            assert_eq!(i32::from(error_code), *code);
+        }
+        // Values are unique.
+        let mut codes = table.iter().map(|(_, code)| *code).collect::<Vec<_>>();
+        codes.sort_unstable();
+        codes.dedup();
+        assert_eq!(codes.len(), table.len());
+
+        let res = unsafe {
+            let mut bytes = [0u8; 8192];
+            let ptr = bytes.as_mut_ptr().cast();
+            let mut len = bytes.len() as i32;
+            assert_eq!(h_get_error_code_table(ptr, &mut len), 0);
+            String::from_utf8(bytes[..len as usize].to_vec()).unwrap()
+        };
+        assert!(res.starts_with("{\"Success\":0,\"BufferTooSmall\":1,"));
+        assert!(res.ends_with("\"Managed\":42}"));
+    }
 }
@@ -23,9 +23,65 @@ pub enum ErrorCode {
     Tokio,                   // Tokio runtime error
     Fpe,                     // Format Preserving Encryption error
     Ecies,                   // Ecies error
+    Panic,                   // A Rust panic was caught
     Unknown(i32),            // An unknown code was retrieved
 }
 
+impl ErrorCode {
+    /// Returns the name of the error code, as exposed to the bindings.
+    #[must_use]
+    pub const fn name(&self) -> &'static str {
+        match self {
+            Self::Success => "Success",
+            Self::BufferTooSmall => "BufferTooSmall",
+            Self::MissingCallback => "MissingCallback",
+            Self::Serialization => "Serialization",
+            Self::Backend => "Backend",
+            Self::InvalidArgument(_) => "InvalidArgument",
+            Self::Findex => "Findex",
+            Self::Encryption => "Encryption",
+            Self::Decryption => "Decryption",
+            Self::Covercrypt => "Covercrypt",
+            Self::CovercryptPolicy => "CovercryptPolicy",
+            Self::Managed => "Managed",
+            Self::Tokio => "Tokio",
+            Self::Fpe => "Fpe",
+            Self::Ecies => "Ecies",
+            Self::Panic => "Panic",
+            Self::Unknown(_) => "Unknown",
+        }
+    }
+
+    /// Returns the name and value of all the known error codes.
+    ///
+    /// This is the authoritative mapping the bindings should rely on instead
+    /// of hard-coding the values.
+    #[must_use]
+    pub fn table() -> Vec<(&'static str, i32)> {
+        [
+            Self::Success,
+            Self::BufferTooSmall,
+            Self::MissingCallback,
+            Self::Serialization,
+            Self::Backend,
+            Self::InvalidArgument(String::new()),
+            Self::Findex,
+            Self::Encryption,
+            Self::Decryption,
+            Self::Covercrypt,
+            Self::CovercryptPolicy,
+            Self::Tokio,
+            Self::Fpe,
+            Self::Ecies,
+            Self::Panic,
+            Self::Managed,
+        ]
+        .into_iter()
+        .map(|code| (code.name(), i32::from(code)))
+        .collect()
+    }
+}
+
 impl From<ErrorCode> for i32 {
     fn from(code: ErrorCode) -> Self {
         match code {
@@ -43,6 +99,7 @@ impl From<ErrorCode> for i32 {
             ErrorCode::Tokio => 11,
             ErrorCode::Fpe => 12,
             ErrorCode::Ecies => 13,
+            ErrorCode::Panic => 14,
             ErrorCode::Managed => 42,
             ErrorCode::Unknown(code) => code,
         }
@@ -58,6 +115,7 @@ impl From<i32> for ErrorCode {
             2 => Self::MissingCallback,
             3 => Self::Serialization,
             4 => Self::Backend,
+            5 => Self::InvalidArgument(String::new()),
             6 => Self::Findex,
             7 => Self::Encryption,
             8 => Self::Decryption,
@@ -66,6 +124,7 @@ impl From<i32> for ErrorCode {
             11 => Self::Tokio,
             12 => Self::Fpe,
             13 => Self::Ecies,
+            14 => Self::Panic,
             42 => Self::Managed,
             code => Self::Unknown(code),
         }
@@ -89,6 +148,7 @@ impl Display for ErrorCode {
             Self::Tokio => write!(f, "tokio error"),
             Self::Fpe => write!(f, "format preserving encryption error"),
             Self::Ecies => write!(f, "ecies error"),
+            Self::Panic => write!(f, "rust panic"),
             Self::Managed => write!(f, "managed"),
             Self::Unknown(code) => write!(f, "unknown code ({code})"),
         }
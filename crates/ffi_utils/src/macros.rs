@@ -45,6 +45,28 @@ macro_rules! ffi_unwrap {
     };
 }
 
+/// Runs the given body of an exported function, catching any panic.
+///
+/// A panic must not unwind across the FFI boundary: it would abort the
+/// foreign runtime (e.g. the JVM). Instead, the panic message is set as last
+/// error and `ErrorCode::Panic` is returned.
+///
+/// - `body`    : function body, returning an `i32` error code
+#[macro_export]
+macro_rules! ffi_guard {
+    ($body:block) => {
+        match std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| -> i32 { $body })) {
+            Ok(code) => code,
+            Err(payload) => {
+                $crate::error::set_last_error($crate::error::FfiError::Panic(
+                    $crate::error::panic_message(&*payload),
+                ));
+                $crate::ErrorCode::Panic.into()
+            }
+        }
+    };
+}
+
 /// Returns with an error.
 ///
 /// Sets the last error to the given message and returns early with the given
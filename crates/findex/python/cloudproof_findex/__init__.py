@@ -4,6 +4,7 @@ from typing import Dict, List, Sequence, Union
 from .cloudproof_findex import *
 
 IndexedValuesAndKeywords = Dict[Union[Location, Keyword], Sequence[Union[str, Keyword]]]
+Aliases = Dict[Union[Keyword, str], Sequence[Union[str, Keyword]]]
 SearchResults = Dict[Union[Keyword, str, bytes], List[Location]]
 ProgressResults = Dict[Union[Keyword, str, bytes], List[Union[Location, Keyword]]]
 
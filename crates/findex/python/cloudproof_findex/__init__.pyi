@@ -1,8 +1,21 @@
-from typing import Callable, Dict, List, Optional, Sequence, Set, Union
+import asyncio
+
+from typing import Any, Callable, Dict, List, Optional, Sequence, Set, Tuple, Union
 
 IndexedValuesAndKeywords = Dict[Union[Location, Keyword], Sequence[Union[str, Keyword]]]
+Aliases = Dict[Union[Keyword, str], Sequence[Union[str, Keyword]]]
 SearchResults = Dict[Union[Keyword, str, bytes], List[Location]]
 ProgressResults = Dict[Union[Keyword, str, bytes], List[Union[Location, Keyword]]]
+Interrupt = Callable[[ProgressResults], bool]
+
+def set_log_level(level: str) -> None:
+    """Set the logs displayed by Findex, overriding the `RUST_LOG` environment
+    variable, e.g. `debug` to see the upsert conflicts, the retries and the
+    callback errors.
+
+    Args:
+        level (str): log directives using the `RUST_LOG` syntax
+    """
 
 class Keyword:
     """A `Keyword` is a byte vector used to index other values."""
@@ -136,7 +149,12 @@ class Key:
         """
 
 class PythonCallbacks:
-    """Callback structure used to instantiate a Findex DB interface."""
+    """Callback structure used to instantiate a Findex DB interface.
+
+    Callbacks may be coroutine functions: the returned coroutines are run to
+    completion on the callbacks event loop. When this loop is the running loop
+    of the application, Findex must be called from another thread, e.g. using
+    `await loop.run_in_executor(None, findex.search, keywords)`."""
 
     @staticmethod
     def new() -> PythonCallbacks:
@@ -151,6 +169,17 @@ class PythonCallbacks:
         """Sets the delete callback."""
     def set_dump_tokens(self, callback: object):
         """Sets the dump_tokens callback."""
+    def set_event_loop(self, event_loop: asyncio.AbstractEventLoop):
+        """Sets the event loop running the awaitable callbacks.
+
+        Findex waits for the callbacks run by this loop if it is running, or
+        runs it until they complete otherwise. It cannot be called from a
+        coroutine running on this loop."""
+    def get_event_loop(self) -> asyncio.AbstractEventLoop:
+        """Returns the event loop running the awaitable callbacks.
+
+        Defaults to an event loop shared by all the callback structures,
+        running in a background thread."""
 
 class AuthorizationToken:
     @staticmethod
@@ -189,27 +218,56 @@ class AuthorizationToken:
 class Findex:
     @staticmethod
     def new_with_sqlite_interface(
-        key: Key, label: str, entry_path: str, chain_path: Optional[str]=None
+        key: Key,
+        label: str,
+        entry_path: str,
+        chain_path: Optional[str]=None,
+        sqlcipher_key: Optional[str]=None,
     ) -> Findex:
         """Instantiate a new Findex instance using an SQLite interface.
 
+        Args:
+            sqlcipher_key (Optional[str]): key used to encrypt the database at
+                rest using SQLCipher (requires the `sqlcipher` feature)
+
         Returns:
             Findex
         """
     @staticmethod
     def new_with_redis_interface(
-        key: Key, label: str, entry_url: str, chain_url: Optional[str]=None
+        key: Key,
+        label: str,
+        entry_url: str,
+        chain_url: Optional[str]=None,
+        pipeline_size: Optional[int]=None,
     ) -> Findex:
         """Instantiate a new Findex instance using a Redis interface.
 
+        Args:
+            pipeline_size (Optional[int]): maximum number of keys fetched per
+                `MGET` command and of upserts sent per pipeline (defaults to 1000)
+
         Returns:
             Findex
         """
     @staticmethod
-    def new_with_rest_interface(label: str, token: str, entry_url: str,
-                                chain_url: Optional[str]=None) -> Findex:
+    def new_with_rest_interface(
+        label: str,
+        token: str,
+        entry_url: str,
+        chain_url: Optional[str] = None,
+        requests_per_second: Optional[float] = None,
+        max_concurrency: Optional[int] = None,
+        max_conflict_rounds: Optional[int] = None,
+    ) -> Findex:
         """Instantiate a new Findex instance using a REST interface.
 
+        The requests can be limited to the given number of requests per second
+        and of concurrent requests. Requests rejected with `429 Too Many Requests`
+        are retried after the delay given by the server. Entry Table upserts
+        rejected by concurrent writers are retried for at most
+        `max_conflict_rounds` rounds (defaults to 10).
+
         Returns:
             Findex
         """
@@ -219,18 +277,75 @@ class Findex:
         label: str,
         entry_callbacks: PythonCallbacks,
         chain_callbacks: Optional[PythonCallbacks]=None,
+        entry_table_number: int = 1,
     ) -> Findex:
         """Instantiate a new Findex instance using a custom interface.
 
+        The Entry Table can be sharded into `entry_table_number` tables to
+        spread the load: the entry fetch callback then returns the values found
+        in each table as a list of `(token, value)` pairs.
+
         Returns:
             Findex
         """
+    def set_keyword_normalization(
+        self,
+        lowercase: bool = False,
+        nfc: bool = False,
+        normalize: Optional[Callable[[bytes], bytes]] = None,
+    ) -> None:
+        """Normalize the keywords before adding, deleting or searching them, e.g. for
+        "Café", "café" and "CAFÉ" to be the same keyword. The search results are
+        returned under the keywords requested.
+
+        The same normalization must be used for the whole life of an index.
+
+        Args:
+            lowercase (bool): lowercase the UTF-8 keywords
+            nfc (bool): convert the UTF-8 keywords to the Unicode Normalization
+                Form C, after lowercasing them
+            normalize (Callable[[bytes], bytes], optional): callback applied last,
+                e.g. a stemmer, returning the normalized keyword bytes
+        """
+    def set_chain_padding(self, bucket_size: Optional[int] = None) -> None:
+        """Pad the chains with dummy values upon each addition and deletion, for
+        the number of values indexed under each keyword to only leak with a
+        bucket granularity. The dummy values are stripped from the search results.
+
+        The same padding should be used for the whole life of an index.
+
+        Args:
+            bucket_size (int, optional): number of values per bucket, at least 2,
+                or None to disable the padding
+        """
     def add(
         self,
         additions: IndexedValuesAndKeywords,
     ) -> Set[Keyword]:
         """Index the given values for the associated keywords.
 
+        Returns:
+            The set of new keywords."""
+    def add_dataframe(
+        self,
+        df: Any,
+        location_column: str,
+        keyword_columns: List[str],
+        tokenized_columns: Optional[List[str]] = None,
+    ) -> Set[Keyword]:
+        """Index the rows of the given pandas `DataFrame`: the location of each row
+        is indexed under the values of its keyword columns. The associations are built
+        natively, which is much faster than calling `add` on large `DataFrame`s.
+
+        Args:
+            df (pandas.DataFrame): rows to index
+            location_column (str): column holding the locations, `int` locations
+                being read as `Location.from_int` does
+            keyword_columns (List[str]): columns holding the keywords, missing values
+                being skipped
+            tokenized_columns (List[str], optional): keyword columns holding text,
+                split into lowercase words
+
         Returns:
             The set of new keywords."""
     def delete(
@@ -241,15 +356,79 @@ class Findex:
 
         Returns:
             The set of new keywords."""
+    def soft_delete(
+        self,
+        deletions: IndexedValuesAndKeywords,
+    ) -> Set[Keyword]:
+        """Remove the given values for the associated keywords from the index,
+        keeping a tombstone of each deleted location until the next compaction
+        so that the deletion can be undone.
+
+        Returns:
+            The set of new keywords."""
+    def count_tombstones(
+        self,
+        keywords: Sequence[Union[Keyword, str]],
+    ) -> Dict[Keyword, int]:
+        """Count the soft-deleted locations of the given keywords that can
+        still be restored.
+
+        Returns:
+            The number of restorable locations by keyword."""
+    def restore_deleted(
+        self,
+        keywords: Sequence[Union[Keyword, str]],
+    ) -> SearchResults:
+        """Index again the locations soft-deleted from the given keywords since
+        the last compaction.
+
+        Returns:
+            The locations restored by keyword."""
+    def add_aliases(
+        self,
+        aliases: Aliases,
+    ) -> Set[Keyword]:
+        """Index the given aliases for the associated keywords: searching for
+        an alias returns the values indexed for the keyword it points to.
+
+        Returns:
+            The set of new aliases."""
+    def delete_aliases(
+        self,
+        aliases: Aliases,
+    ) -> Set[Keyword]:
+        """Remove the given aliases of the associated keywords from the index.
+
+        Returns:
+            The set of removed aliases."""
     def search(
         self,
         keywords: Sequence[Union[Keyword, str]],
-        interrupt: Optional[Callable] = None,
+        interrupt: Optional[Interrupt] = None,
+        max_depth: Optional[int] = None,
     ) -> SearchResults:
         """Search for the given keywords in the index.
 
+        The interrupt is called at each search graph level with the
+        intermediate results. Returning `True` interrupts the search.
+
+        At most `max_depth` aliases are followed from each keyword if given.
+
         Returns:
             The values indexed for those tokens."""
+    def search_scored(
+        self,
+        keywords: Sequence[Union[Keyword, str]],
+        intersection: bool = False,
+    ) -> Dict[Location, int]:
+        """Search for the given keywords in the index and count the number of
+        keywords matched by each location found, e.g. to rank them.
+
+        If `intersection` is set, only the locations matching all the keywords
+        are returned.
+
+        Returns:
+            The number of keywords matched, per location."""
     def compact(
         self,
         new_key: Key,
@@ -260,3 +439,21 @@ class Findex:
         """Compact the index. Encrypts the compacted index using the new key
         and new label.
         """
+    def compact_partial(
+        self,
+        new_key: Key,
+        new_label: str,
+        n_entries: int,
+        data_filter: Optional[Callable] = None,
+    ) -> None:
+        """Compact the chains of `n_entries` randomly selected index entries,
+        allowing to compact huge indexes incrementally. Encrypts the whole
+        index using the new key and new label.
+        """
+    def statistics(self) -> Tuple[int, int]:
+        """Count the lines of the index tables. The number of Chain Table
+        lines per entry grows with the garbage accumulated since the last
+        compaction.
+
+        Returns:
+            (number of Entry Table lines, number of Chain Table lines)"""
@@ -1,5 +1,7 @@
 # -*- coding: utf-8 -*-
+import asyncio
 import os
+import pandas as pd
 import requests
 import redis
 import unittest
@@ -15,6 +17,7 @@ from cloudproof_findex import (
     Key,
     ProgressResults,
     PythonCallbacks,
+    set_log_level,
 )
 
 
@@ -100,6 +103,13 @@ class TestStructures(unittest.TestCase):
         with self.assertRaises(ValueError):
             Key.from_bytes(b'wrong size')
 
+    def test_set_log_level(self) -> None:
+        set_log_level('cloudproof_findex=debug')
+        set_log_level('warn')
+
+        with self.assertRaises(ValueError):
+            set_log_level('cloudproof_findex=not_a_level')
+
 
 def define_custom_db_interface(is_with_test: bool = False):
     table: dict = {}
@@ -183,6 +193,88 @@ def define_custom_db_interface(is_with_test: bool = False):
     return in_memory_db_interface
 
 
+def define_async_custom_db_interface():
+    table: dict = {}
+
+    async def fetch(uids):
+        await asyncio.sleep(0)
+        return {uid: table[uid] for uid in uids if uid in table}
+
+    async def upsert(old_values: dict, new_values: dict):
+        await asyncio.sleep(0)
+        res = {}
+        for uid, new_value in new_values.items():
+            current_value = table.get(uid)
+            if old_values.get(uid) == current_value:
+                table[uid] = new_value
+            else:
+                res[uid] = current_value
+        return res
+
+    async def insert(items):
+        await asyncio.sleep(0)
+        table.update(items)
+
+    async def delete(uids):
+        await asyncio.sleep(0)
+        for uid in uids:
+            table.pop(uid)
+
+    async def dump_tokens():
+        await asyncio.sleep(0)
+        return table.keys()
+
+    async_db_interface = PythonCallbacks.new()
+    async_db_interface.set_fetch(fetch)
+    async_db_interface.set_upsert(upsert)
+    async_db_interface.set_insert(insert)
+    async_db_interface.set_delete(delete)
+    async_db_interface.set_dump_tokens(dump_tokens)
+
+    return async_db_interface
+
+
+def define_sharded_custom_db_interface(shard_number: int):
+    shards: list = [{} for _ in range(shard_number)]
+
+    def shard(uid) -> dict:
+        return shards[uid[0] % shard_number]
+
+    def fetch(uids):
+        # a token is looked up in every shard
+        return [(uid, table[uid]) for table in shards for uid in uids if uid in table]
+
+    def upsert(old_values: dict, new_values: dict):
+        res = {}
+        for uid, new_value in new_values.items():
+            current_value = shard(uid).get(uid)
+            if old_values.get(uid) == current_value:
+                shard(uid)[uid] = new_value
+            else:
+                res[uid] = current_value
+        return res
+
+    def insert(items):
+        for uid, value in items.items():
+            shard(uid)[uid] = value
+
+    def delete(uids):
+        for uid in uids:
+            shard(uid).pop(uid)
+
+    def dump_tokens():
+        return [uid for table in shards for uid in table]
+
+    sharded_db_interface = PythonCallbacks.new()
+    sharded_db_interface.set_fetch(fetch)
+    sharded_db_interface.set_upsert(upsert)
+    sharded_db_interface.set_insert(insert)
+    sharded_db_interface.set_delete(delete)
+    sharded_db_interface.set_dump_tokens(dump_tokens)
+
+    return sharded_db_interface
+
+
 class TestFindex(unittest.TestCase):
     def setUp(self) -> None:
         # Create structures needed by Findex
@@ -214,6 +306,7 @@ class TestFindex(unittest.TestCase):
         )
 
         in_memory_db_interface = define_custom_db_interface()
+        async_db_interface = define_async_custom_db_interface()
 
         sqlite_path = '/tmp/cloudproof_findex.sqlite'
         redis_host = 'localhost'
@@ -244,8 +337,30 @@ class TestFindex(unittest.TestCase):
             'custom': Findex.new_with_custom_interface(
                 self.findex_key, self.label, in_memory_db_interface, in_memory_db_interface
             ),
+            'custom_async': Findex.new_with_custom_interface(
+                self.findex_key,
+                self.label,
+                async_db_interface,
+                async_db_interface,
+            ),
+            'custom_sharded': Findex.new_with_custom_interface(
+                self.findex_key,
+                self.label,
+                define_sharded_custom_db_interface(4),
+                define_custom_db_interface(),
+                entry_table_number=4,
+            ),
         }
 
+    def test_entry_table_number(self) -> None:
+        with self.assertRaises(Exception):
+            Findex.new_with_custom_interface(
+                self.findex_key,
+                self.label,
+                define_custom_db_interface(),
+                entry_table_number=0,
+            )
+
     def test_upsert(self) -> None:
         indexed_values_and_keywords: IndexedValuesAndKeywords = {
             Location.from_int(k): v for k, v in self.db.items()
@@ -326,6 +441,129 @@ class TestFindex(unittest.TestCase):
             # only one location found after early stopping
             self.assertEqual(len(res['Mar']), 1)
 
+            def failing_progress_callback(res: ProgressResults) -> bool:
+                raise ValueError('interrupted')
+
+            # errors raised by the callback are propagated
+            with self.assertRaises(Exception):
+                instance.search(['Mar'], interrupt=failing_progress_callback)
+
+    def test_aliases(self) -> None:
+        for interface, instance in self.findex_interfaces.items():
+            print(f'Test aliases on {interface} interface.')
+            instance.add({Location.from_int(1): ['Martin']})
+
+            res = instance.add_aliases({'Martin': ['Marti'], 'Marti': ['Mart']})
+            self.assertEqual(res, set(['Marti', 'Mart']))
+
+            res = instance.search(['Mart'])
+            self.assertEqual(len(res['Mart']), 1)
+
+            # only direct aliases are followed
+            res = instance.search(['Mart'], max_depth=1)
+            self.assertEqual(len(res['Mart']), 0)
+            res = instance.search(['Marti'], max_depth=1)
+            self.assertEqual(len(res['Marti']), 1)
+
+            instance.delete_aliases({'Marti': ['Mart']})
+            res = instance.search(['Mart'])
+            self.assertEqual(len(res['Mart']), 0)
+
+    def test_keyword_normalization(self) -> None:
+        def stem(keyword: bytes) -> bytes:
+            return keyword[:-1] if keyword.endswith(b's') else keyword
+
+        for interface, instance in self.findex_interfaces.items():
+            print(f'Test keyword normalization on {interface} interface.')
+            instance.set_keyword_normalization(lowercase=True, nfc=True, normalize=stem)
+            instance.add({Location.from_int(1): ['Caf\u00e9']})
+
+            res = instance.search(['caf\u00e9', 'CAF\u00c9S', 'Cafe\u0301'])
+            self.assertEqual(res['caf\u00e9'], [Location.from_int(1)])
+            self.assertEqual(res['CAF\u00c9S'], [Location.from_int(1)])
+            self.assertEqual(res['Cafe\u0301'], [Location.from_int(1)])
+
+            def failing_normalization(keyword: bytes) -> bytes:
+                raise ValueError('cannot normalize')
+
+            # errors raised by the callback are propagated
+            instance.set_keyword_normalization(normalize=failing_normalization)
+            with self.assertRaises(Exception):
+                instance.search(['Caf\u00e9'])
+
+    def test_chain_padding(self) -> None:
+        for interface, instance in self.findex_interfaces.items():
+            print(f'Test chain padding on {interface} interface.')
+            with self.assertRaises(Exception):
+                instance.set_chain_padding(1)
+
+            instance.set_chain_padding(4)
+            instance.add({Location.from_int(1): ['Padded']})
+            instance.delete({Location.from_int(1): ['Padded']})
+            instance.add({Location.from_int(2): ['Padded']})
+
+            # dummy values are stripped from the results
+            res = instance.search(['Padded'])
+            self.assertEqual(res['Padded'], [Location.from_int(2)])
+
+            instance.set_chain_padding(None)
+
+    def test_soft_delete(self) -> None:
+        for interface, instance in self.findex_interfaces.items():
+            print(f'Test soft deletion on {interface} interface.')
+            instance.add({Location.from_int(1): ['Robert'], Location.from_int(2): ['Robert']})
+
+            instance.soft_delete({Location.from_int(1): ['Robert']})
+            self.assertEqual(instance.search(['Robert'])['Robert'], [Location.from_int(2)])
+            self.assertEqual(instance.count_tombstones(['Robert']), {Keyword.from_string('Robert'): 1})
+
+            res = instance.restore_deleted(['Robert'])
+            self.assertEqual(res['Robert'], [Location.from_int(1)])
+            self.assertEqual(len(instance.search(['Robert'])['Robert']), 2)
+            self.assertEqual(instance.count_tombstones(['Robert'])['Robert'], 0)
+
+    def test_add_dataframe(self) -> None:
+        df = pd.DataFrame(
+            {
+                'id': [1, 2, 3],
+                'name': ['Robert', 'Jane', None],
+                'city': ['Paris', 'Paris', 'Nice'],
+                'bio': ['Loves Jazz, plays piano', 'Plays chess', None],
+            }
+        )
+        for interface, instance in self.findex_interfaces.items():
+            print(f'Test DataFrame indexing on {interface} interface.')
+            new_keywords = instance.add_dataframe(
+                df, 'id', ['name', 'city', 'bio'], tokenized_columns=['bio']
+            )
+            self.assertIn(Keyword.from_string('piano'), new_keywords)
+
+            res = instance.search(['Robert', 'Paris', 'Nice', 'plays', 'Jazz'])
+            self.assertEqual(res['Robert'], [Location.from_int(1)])
+            self.assertEqual(len(res['Paris']), 2)
+            self.assertEqual(res['Nice'], [Location.from_int(3)])
+            self.assertEqual(len(res['plays']), 2)
+            self.assertEqual(len(res['Jazz']), 0)
+
+            with self.assertRaises(Exception):
+                instance.add_dataframe(df, 'name', ['city'])
+
+    def test_search_scored(self) -> None:
+        for interface, instance in self.findex_interfaces.items():
+            print(f'Test scored search on {interface} interface.')
+            instance.add(
+                {
+                    Location.from_int(1): ['Robert', 'Doe'],
+                    Location.from_int(2): ['Robert'],
+                }
+            )
+
+            res = instance.search_scored(['Robert', 'Doe'])
+            self.assertEqual(res, {Location.from_int(1): 2, Location.from_int(2): 1})
+
+            res = instance.search_scored(['Robert', 'Doe'], intersection=True)
+            self.assertEqual(res, {Location.from_int(1): 2})
+
     def test_compact(self) -> None:
         indexed_values_and_keywords: IndexedValuesAndKeywords = {
             Location.from_int(k): v for k, v in self.db.items()
@@ -365,6 +603,98 @@ class TestFindex(unittest.TestCase):
             self.assertEqual(len(res['Martial']), 0)
             self.assertEqual(len(res['Wilkins']), 0)
 
+    def test_compact_partial(self) -> None:
+        indexed_values_and_keywords: IndexedValuesAndKeywords = {
+            Location.from_int(k): v for k, v in self.db.items()
+        }
+
+        interfaces = [
+            (interface, instance)
+            for interface, instance in self.findex_interfaces.items()
+            if interface == 'sqlite'
+        ]
+
+        for interface, instance in interfaces:
+            print(f'Test partial compacting on {interface} interface.')
+
+            instance.add(indexed_values_and_keywords)
+            n_entries, n_links = instance.statistics()
+            self.assertGreater(n_entries, 0)
+            self.assertGreaterEqual(n_links, n_entries)
+
+            def filter_obsolete_data(dataset: Set[Location]):
+                return {data for data in dataset if data != Location.from_int(2)}
+
+            # compacting no entry only re-encrypts the index
+            instance.compact_partial(
+                self.findex_key, 'My renewed label', 0, filter_obsolete_data
+            )
+            res = instance.search(['Martial'])
+            self.assertEqual(len(res['Martial']), 1)
+
+            # compacting all the entries is a full compaction
+            instance.compact_partial(
+                self.findex_key, 'My other label', n_entries, filter_obsolete_data
+            )
+            res = instance.search(['Martial', 'Sheperd'])
+            self.assertEqual(len(res['Martial']), 0)
+            self.assertEqual(len(res['Sheperd']), 2)
+            self.assertEqual(instance.statistics()[0], n_entries)
+
+
+class TestAsyncCallbacks(unittest.TestCase):
+    def setUp(self) -> None:
+        self.findex_key = Key.random()
+        self.label = 'My label.'
+
+    def new_findex(self, db_interface: PythonCallbacks) -> Findex:
+        return Findex.new_with_custom_interface(
+            self.findex_key, self.label, db_interface, db_interface
+        )
+
+    def test_default_event_loop(self) -> None:
+        instance = self.new_findex(define_async_custom_db_interface())
+        instance.add({Location.from_int(1): ['Robert']})
+
+        async def search_from_coroutine():
+            # The default event loop runs in its own thread.
+            return instance.search(['Robert'])
+
+        res = asyncio.run(search_from_coroutine())
+        self.assertEqual(res['Robert'], [Location.from_int(1)])
+
+    def test_stopped_event_loop(self) -> None:
+        event_loop = asyncio.new_event_loop()
+        try:
+            db_interface = define_async_custom_db_interface()
+            db_interface.set_event_loop(event_loop)
+            instance = self.new_findex(db_interface)
+            instance.add({Location.from_int(1): ['Robert']})
+            res = instance.search(['Robert'])
+            self.assertEqual(res['Robert'], [Location.from_int(1)])
+        finally:
+            event_loop.close()
+
+    def test_application_event_loop(self) -> None:
+        async def run():
+            # Callbacks bound to the event loop of the application.
+            event_loop = asyncio.get_running_loop()
+            db_interface = define_async_custom_db_interface()
+            db_interface.set_event_loop(event_loop)
+            instance = self.new_findex(db_interface)
+
+            await event_loop.run_in_executor(
+                None, instance.add, {Location.from_int(1): ['Robert']}
+            )
+            res = await event_loop.run_in_executor(None, instance.search, ['Robert'])
+            self.assertEqual(res['Robert'], [Location.from_int(1)])
+
+            # Blocking the loop running the callbacks is refused.
+            with self.assertRaises(Exception):
+                instance.search(['Robert'])
+
+        asyncio.run(run())
+
 
 if __name__ == '__main__':
     define_custom_db_interface(True)
@@ -1,4 +1,15 @@
 //! Defines the FFI types for the callbacks used in Findex.
+//!
+//! All the payloads given to and returned by the callbacks start with the
+//! [`CALLBACK_FORMAT_VERSION`](crate::ser_de::ffi_ser_de::CALLBACK_FORMAT_VERSION)
+//! byte, omitted in the serialization formats below. Callback implementations
+//! should check this version and fail on unsupported ones.
+//!
+//! The inputs are given as views into buffers owned by Findex: they are only
+//! valid for the duration of the callback call. Implementations must not free
+//! them, and must copy the bytes they need to keep after returning. The
+//! outputs are written into buffers owned by Findex, which reads them in
+//! place.
 
 use cosmian_ffi_utils::ErrorCode;
 use cosmian_findex::{Token, TokenToEncryptedValueMap, Tokens};
@@ -8,7 +19,7 @@ use crate::{
     db_interfaces::DbInterfaceError,
     ser_de::ffi_ser_de::{
         deserialize_edx_lines, deserialize_token_set, get_serialized_edx_lines_size_bound,
-        serialize_edx_lines, serialize_token_set,
+        serialize_versioned_edx_lines, serialize_versioned_token_set, strip_format_version,
     },
 };
 
@@ -69,6 +80,68 @@ pub type FilterObsoleteData = extern "C" fn(
     locations_len: u32,
 ) -> i32;
 
+/// Normalizes a keyword, e.g. using a stemmer.
+///
+/// The input and output are the raw keyword bytes, without format version. If
+/// the output buffer is too small, the callback should write the required
+/// length in `output_len` and return `ErrorCode::BufferTooSmall`: it is then
+/// called again with a buffer of this length.
+pub type Normalize = extern "C" fn(
+    output_ptr: *mut u8,
+    output_len: *mut u32,
+    keyword_ptr: *const u8,
+    keyword_len: u32,
+) -> i32;
+
+/// Returns the `output_len` first bytes of the given output buffer of a
+/// callback, without copying them.
+fn read_output<'a>(
+    output_bytes: &'a [u8],
+    output_len: u32,
+    callback: &str,
+) -> Result<&'a [u8], DbInterfaceError> {
+    output_bytes.get(..output_len as usize).ok_or_else(|| {
+        DbInterfaceError::Ffi(
+            format!("{callback}: output length exceeds the allocated buffer"),
+            ErrorCode::BufferTooSmall,
+        )
+    })
+}
+
+/// Returns the normalization of the given keyword by the given callback.
+pub(crate) fn normalize_keyword(
+    normalize: Normalize,
+    keyword: &[u8],
+) -> Result<Vec<u8>, DbInterfaceError> {
+    let keyword_len = u32::try_from(keyword.len())?;
+    // Normalized keywords are usually not longer than the original ones.
+    let mut output_bytes = vec![0_u8; 2 * keyword.len()];
+    let mut output_len = u32::try_from(output_bytes.len())?;
+    let mut err = (normalize)(
+        output_bytes.as_mut_ptr(),
+        &mut output_len,
+        keyword.as_ptr(),
+        keyword_len,
+    );
+    if ErrorCode::from(err) == ErrorCode::BufferTooSmall {
+        output_bytes = vec![0_u8; output_len as usize];
+        err = (normalize)(
+            output_bytes.as_mut_ptr(),
+            &mut output_len,
+            keyword.as_ptr(),
+            keyword_len,
+        );
+    }
+    if err == 0 {
+        read_output(&output_bytes, output_len, "normalize").map(<[u8]>::to_vec)
+    } else {
+        Err(DbInterfaceError::Ffi(
+            "normalize error".to_string(),
+            err.into(),
+        ))
+    }
+}
+
 /// Structure storing the callback functions passed through the FFI. It also
 /// stores the number of corresponding tables since to allow allocating the
 /// correct amount of memory.
@@ -90,6 +163,9 @@ pub type FilterObsoleteData = extern "C" fn(
 /// ```
 #[derive(Clone, Debug)]
 pub struct FfiCallbacks {
+    /// Number of tables the callbacks use: the Entry Table may be sharded into
+    /// several tables, `fetch` then returning up to one value per table for
+    /// each token. Only one Chain Table is allowed.
     pub table_number: usize,
     pub fetch: Option<Fetch>,
     pub upsert: Option<Upsert>,
@@ -116,14 +192,13 @@ impl FfiCallbacks {
         })?;
 
         let allocation_size =
-            get_serialized_edx_lines_size_bound::<LENGTH>(tokens.len(), self.table_number);
+            1 + get_serialized_edx_lines_size_bound::<LENGTH>(tokens.len(), self.table_number);
         trace!("fetch: output allocation_size: {}", allocation_size);
 
         let mut output_bytes = vec![0_u8; allocation_size];
-        let output_ptr = output_bytes.as_mut_ptr().cast();
         let mut output_len = u32::try_from(allocation_size)?;
 
-        let serialized_tokens = serialize_token_set(&tokens)?;
+        let serialized_tokens = serialize_versioned_token_set(&tokens)?;
         let serialized_tokens_len = u32::try_from(serialized_tokens.len())?;
 
         trace!(
@@ -132,19 +207,16 @@ impl FfiCallbacks {
         );
 
         let err = (fetch)(
-            output_ptr,
+            output_bytes.as_mut_ptr(),
             &mut output_len,
             serialized_tokens.as_ptr(),
             serialized_tokens_len,
         );
 
         if err == 0 {
-            let res = unsafe {
-                std::slice::from_raw_parts(output_ptr.cast_const(), output_len as usize).to_vec()
-            };
-
-            let token_encrypted_value_list =
-                deserialize_edx_lines(&res).map_err(DbInterfaceError::from)?;
+            let res = read_output(&output_bytes, output_len, "fetch")?;
+            let token_encrypted_value_list = deserialize_edx_lines(strip_format_version(res)?)
+                .map_err(DbInterfaceError::from)?;
             debug!(
                 "fetch: exiting successfully with {} values",
                 token_encrypted_value_list.len()
@@ -174,19 +246,18 @@ impl FfiCallbacks {
         })?;
 
         let allocation_size =
-            get_serialized_edx_lines_size_bound::<LENGTH>(new_values.len(), self.table_number);
+            1 + get_serialized_edx_lines_size_bound::<LENGTH>(new_values.len(), self.table_number);
 
         let mut output_bytes = vec![0_u8; allocation_size];
-        let output_ptr = output_bytes.as_mut_ptr().cast();
         let mut output_len = u32::try_from(allocation_size)?;
 
-        let serialized_old_values = serialize_edx_lines(&old_values)?;
-        let serialized_new_values = serialize_edx_lines(&new_values)?;
+        let serialized_old_values = serialize_versioned_edx_lines(&old_values)?;
+        let serialized_new_values = serialize_versioned_edx_lines(&new_values)?;
         let serialized_old_values_len = <u32>::try_from(serialized_old_values.len())?;
         let serialized_new_values_len = <u32>::try_from(serialized_new_values.len())?;
 
         let err = (upsert)(
-            output_ptr,
+            output_bytes.as_mut_ptr(),
             &mut output_len,
             serialized_old_values.as_ptr(),
             serialized_old_values_len,
@@ -195,11 +266,11 @@ impl FfiCallbacks {
         );
 
         if err == 0 {
-            let res = unsafe {
-                std::slice::from_raw_parts(output_ptr.cast_const(), output_len as usize).to_vec()
-            };
+            let res = read_output(&output_bytes, output_len, "upsert")?;
             let token_encrypted_value_map: cosmian_findex::TokenToEncryptedValueMap<LENGTH> =
-                deserialize_edx_lines(&res)?.into_iter().collect();
+                deserialize_edx_lines(strip_format_version(res)?)?
+                    .into_iter()
+                    .collect();
             debug!(
                 "upsert: exiting successfully with {} values",
                 token_encrypted_value_map.len()
@@ -225,7 +296,7 @@ impl FfiCallbacks {
             DbInterfaceError::MissingCallback("no insert callback found".to_string())
         })?;
 
-        let serialized_map = serialize_edx_lines(&map)?;
+        let serialized_map = serialize_versioned_edx_lines(&map)?;
         let serialized_map_len = <u32>::try_from(serialized_map.len())?;
 
         let err = (insert)(serialized_map.as_ptr(), serialized_map_len).into();
@@ -249,7 +320,7 @@ impl FfiCallbacks {
             DbInterfaceError::MissingCallback("no delete callback found".to_string())
         })?;
 
-        let serialized_uids = serialize_token_set(&tokens)?;
+        let serialized_uids = serialize_versioned_token_set(&tokens)?;
         let serialized_uids_len = <u32>::try_from(serialized_uids.len())?;
 
         let err = (delete)(serialized_uids.as_ptr(), serialized_uids_len).into();
@@ -273,28 +344,28 @@ impl FfiCallbacks {
             DbInterfaceError::MissingCallback("no dump_tokens callback found".to_string())
         })?;
 
-        let mut allocation_size = 1_000_000 * Token::LENGTH;
+        let allocation_size = 1_000_000 * Token::LENGTH;
         let mut output_bytes = vec![0_u8; allocation_size];
-        let output_ptr = output_bytes.as_mut_ptr().cast::<u8>();
         let mut output_len = u32::try_from(allocation_size)?;
 
-        let mut err = (dump_tokens)(output_ptr, &mut output_len).into();
+        let mut err = (dump_tokens)(output_bytes.as_mut_ptr(), &mut output_len).into();
 
         if ErrorCode::BufferTooSmall == err {
             // Second try in case not enough memory was allocated.
             // Use the length returned by the first function call.
-            allocation_size = output_len as usize;
-            let mut output_bytes = vec![0_u8; allocation_size];
-            let output_ptr = output_bytes.as_mut_ptr().cast::<u8>();
-            let mut output_len = u32::try_from(allocation_size)?;
-            err = (dump_tokens)(output_ptr, &mut output_len).into();
+            debug!(
+                allocated = allocation_size,
+                required = output_len,
+                "dump_tokens: output buffer too small, retrying"
+            );
+            output_bytes = vec![0_u8; output_len as usize];
+            err = (dump_tokens)(output_bytes.as_mut_ptr(), &mut output_len).into();
         }
 
         if ErrorCode::Success == err {
-            // TODO: why not directly use `output_bytes`?
-            let tokens_bytes =
-                unsafe { std::slice::from_raw_parts(output_ptr.cast_const(), output_len as usize) };
-            let tokens = deserialize_token_set(tokens_bytes).map_err(DbInterfaceError::from)?;
+            let tokens_bytes = read_output(&output_bytes, output_len, "FfiCallbacks token dump")?;
+            let tokens = deserialize_token_set(strip_format_version(tokens_bytes)?)
+                .map_err(DbInterfaceError::from)?;
             debug!("dump_tokens: exiting with {} tokens", tokens.len());
             Ok(tokens)
         } else {
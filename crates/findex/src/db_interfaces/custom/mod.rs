@@ -1,8 +1,73 @@
+use std::collections::HashMap;
+
 use async_trait::async_trait;
-use cosmian_findex::DbInterface;
+use cosmian_findex::{DbInterface, EncryptedValue, Token};
 
 use crate::db_interfaces::DbInterfaceError;
 
+/// Checks the number of tables of the custom backends: the Entry Table may be
+/// sharded into several tables to spread the load, but the Chain Table may
+/// not.
+pub(crate) fn check_table_numbers(
+    entry_table_number: usize,
+    chain_table_number: usize,
+) -> Result<(), DbInterfaceError> {
+    if entry_table_number == 0 {
+        return Err(DbInterfaceError::Other(
+            "the number of Entry Tables should be at least 1".to_string(),
+        ));
+    }
+    if chain_table_number != 1 {
+        return Err(DbInterfaceError::Other(format!(
+            "only one Chain Table is allowed, got {chain_table_number}"
+        )));
+    }
+    Ok(())
+}
+
+/// Checks that the given fetched lines hold at most one value per token and
+/// per table.
+pub(crate) fn check_fetched_lines<const LENGTH: usize>(
+    lines: &[(Token, EncryptedValue<LENGTH>)],
+    table_number: usize,
+) -> Result<(), DbInterfaceError> {
+    let mut counts = HashMap::<&Token, usize>::with_capacity(lines.len());
+    for (token, _) in lines {
+        let count = counts.entry(token).or_default();
+        *count += 1;
+        if *count > table_number {
+            return Err(DbInterfaceError::Other(format!(
+                "fetch returned {count} values for the same token while there are \
+                 {table_number} tables"
+            )));
+        }
+    }
+    Ok(())
+}
+
+/// Logs the errors returned by the callbacks of the given backend, with the
+/// error code given by the FFI callbacks if any.
+pub(crate) fn log_callback_error<T>(
+    backend: &str,
+    callback: &str,
+    res: Result<T, DbInterfaceError>,
+) -> Result<T, DbInterfaceError> {
+    if let Err(err) = &res {
+        match err {
+            #[cfg(feature = "ffi")]
+            DbInterfaceError::Ffi(_, code) => tracing::warn!(
+                backend,
+                callback,
+                code = %code,
+                error = %err,
+                "callback failed"
+            ),
+            _ => tracing::warn!(backend, callback, error = %err, "callback failed"),
+        }
+    }
+    res
+}
+
 /// Implements `EdxStore<$value_length>` for the given backend type, which wraps
 /// the given callback type.
 ///
@@ -32,7 +97,11 @@ macro_rules! impl_custom_backend {
             type Error = $crate::db_interfaces::custom::DbInterfaceError;
 
             async fn dump_tokens(&self) -> Result<cosmian_findex::Tokens, Self::Error> {
-                self.0.dump_tokens().await.map(Into::into)
+                $crate::db_interfaces::custom::log_callback_error(
+                    stringify!($backend_type),
+                    "dump_tokens",
+                    self.0.dump_tokens().await.map(Into::into),
+                )
             }
 
             async fn fetch(
@@ -40,7 +109,16 @@ macro_rules! impl_custom_backend {
                 uids: cosmian_findex::Tokens,
             ) -> Result<cosmian_findex::TokenWithEncryptedValueList<$value_length>, Self::Error>
             {
-                self.0.fetch(uids.into()).await.map(Into::into)
+                let lines: Vec<_> = $crate::db_interfaces::custom::log_callback_error(
+                    stringify!($backend_type),
+                    "fetch",
+                    self.0
+                        .fetch(uids.into())
+                        .await
+                        .map(|lines| lines.into_iter().collect()),
+                )?;
+                $crate::db_interfaces::custom::check_fetched_lines(&lines, self.0.table_number)?;
+                Ok(lines.into())
             }
 
             async fn upsert(
@@ -48,21 +126,44 @@ macro_rules! impl_custom_backend {
                 old_values: cosmian_findex::TokenToEncryptedValueMap<$value_length>,
                 new_values: cosmian_findex::TokenToEncryptedValueMap<$value_length>,
             ) -> Result<cosmian_findex::TokenToEncryptedValueMap<$value_length>, Self::Error> {
-                self.0
-                    .upsert(old_values.into(), new_values.into())
-                    .await
-                    .map(Into::into)
+                let n_values = new_values.len();
+                let rejected: cosmian_findex::TokenToEncryptedValueMap<$value_length> =
+                    $crate::db_interfaces::custom::log_callback_error(
+                        stringify!($backend_type),
+                        "upsert",
+                        self.0
+                            .upsert(old_values.into(), new_values.into())
+                            .await
+                            .map(Into::into),
+                    )?;
+                if !rejected.is_empty() {
+                    tracing::debug!(
+                        backend = stringify!($backend_type),
+                        rejected = rejected.len(),
+                        values = n_values,
+                        "upsert conflict: values modified by a concurrent writer"
+                    );
+                }
+                Ok(rejected)
             }
 
             async fn insert(
                 &self,
                 new_items: cosmian_findex::TokenToEncryptedValueMap<$value_length>,
             ) -> Result<(), Self::Error> {
-                self.0.insert(new_items.into()).await.map(Into::into)
+                $crate::db_interfaces::custom::log_callback_error(
+                    stringify!($backend_type),
+                    "insert",
+                    self.0.insert(new_items.into()).await.map(Into::into),
+                )
             }
 
             async fn delete(&self, uids: cosmian_findex::Tokens) -> Result<(), Self::Error> {
-                self.0.delete(uids.into()).await.map(Into::into)
+                $crate::db_interfaces::custom::log_callback_error(
+                    stringify!($backend_type),
+                    "delete",
+                    self.0.delete(uids.into()).await.map(Into::into),
+                )
             }
         }
     };
@@ -76,3 +177,31 @@ pub mod wasm;
 
 #[cfg(feature = "python")]
 pub mod python;
+
+#[cfg(test)]
+mod tests {
+    use cosmian_findex::ENTRY_LENGTH;
+
+    use super::*;
+
+    #[test]
+    fn test_table_numbers() {
+        assert!(check_table_numbers(1, 1).is_ok());
+        assert!(check_table_numbers(4, 1).is_ok());
+        assert!(check_table_numbers(0, 1).is_err());
+        assert!(check_table_numbers(1, 2).is_err());
+
+        let line = || {
+            (
+                Token::from([1; Token::LENGTH]),
+                EncryptedValue::<ENTRY_LENGTH>::try_from(
+                    [1; EncryptedValue::<ENTRY_LENGTH>::LENGTH].as_slice(),
+                )
+                .unwrap(),
+            )
+        };
+        let lines = vec![line(), line()];
+        assert!(check_fetched_lines(&lines, 2).is_ok());
+        assert!(check_fetched_lines(&lines, 1).is_err());
+    }
+}
@@ -2,8 +2,10 @@ use std::collections::{HashMap, HashSet};
 
 use cosmian_findex::{EncryptedValue, Token};
 use pyo3::{
+    exceptions::PyRuntimeError,
     prelude::*,
-    types::{PyBytes, PyDict},
+    sync::GILOnceCell,
+    types::{PyBytes, PyDict, PyTuple},
 };
 
 use crate::db_interfaces::DbInterfaceError;
@@ -26,14 +28,49 @@ use crate::db_interfaces::DbInterfaceError;
 /// | compact |  ET + CT  |           |  ET + CT  |  ET + CT  |       ET      |
 /// +---------+-----------+-----------+-----------+-----------+---------------+
 /// ```
-#[derive(Debug, Clone, Default)]
+///
+/// The Entry Table may be sharded into `table_number` tables: `fetch` then
+/// returns the values found in each of them, as a list of `(token, value)`
+/// pairs instead of a dictionary.
+///
+/// Callbacks may be coroutine functions (`async def`): the awaitable they
+/// return is run to completion on the callbacks event loop.
+///
+/// This loop defaults to an event loop shared by all the callbacks structures
+/// and running in a background thread, so that Findex can be called from any
+/// thread, coroutines included. Asynchronous storage clients bound to the loop
+/// of the application (e.g. `asyncpg` connections) require setting this loop
+/// as the callbacks event loop: Findex must then be called from another
+/// thread (e.g. using `loop.run_in_executor`) while the loop is running, since
+/// blocking the loop would prevent the callbacks from completing.
+#[derive(Debug, Clone)]
 #[pyclass]
 pub struct PythonCallbacks {
+    pub(crate) table_number: usize,
     pub(crate) fetch: Option<PyObject>,
     pub(crate) upsert: Option<PyObject>,
     pub(crate) insert: Option<PyObject>,
     pub(crate) delete: Option<PyObject>,
     pub(crate) dump_tokens: Option<PyObject>,
+    pub(crate) event_loop: Option<PyObject>,
+}
+
+/// Event loop running the awaitable callbacks when none is given. It runs
+/// forever in a daemon thread.
+static DEFAULT_EVENT_LOOP: GILOnceCell<PyObject> = GILOnceCell::new();
+
+impl Default for PythonCallbacks {
+    fn default() -> Self {
+        Self {
+            table_number: 1,
+            fetch: None,
+            upsert: None,
+            insert: None,
+            delete: None,
+            dump_tokens: None,
+            event_loop: None,
+        }
+    }
 }
 
 #[pymethods]
@@ -63,9 +100,79 @@ impl PythonCallbacks {
     pub fn set_dump_tokens(&mut self, callback: PyObject) {
         self.dump_tokens = Some(callback);
     }
+
+    pub fn set_event_loop(&mut self, event_loop: PyObject) {
+        self.event_loop = Some(event_loop);
+    }
+
+    pub fn get_event_loop(&self, py: Python<'_>) -> PyResult<PyObject> {
+        self.event_loop(py)
+            .map(|event_loop| event_loop.clone_ref(py))
+    }
 }
 
 impl PythonCallbacks {
+    fn event_loop(&self, py: Python<'_>) -> PyResult<&PyObject> {
+        match &self.event_loop {
+            Some(event_loop) => Ok(event_loop),
+            None => DEFAULT_EVENT_LOOP.get_or_try_init(py, || {
+                let event_loop = py.import("asyncio")?.call_method0("new_event_loop")?;
+                let kwargs = PyDict::new(py);
+                kwargs.set_item("target", event_loop.getattr("run_forever")?)?;
+                kwargs.set_item("name", "findex-callbacks")?;
+                kwargs.set_item("daemon", true)?;
+                py.import("threading")?
+                    .call_method("Thread", (), Some(kwargs))?
+                    .call_method0("start")?;
+                Ok(event_loop.into())
+            }),
+        }
+    }
+
+    /// Calls the given callback. If it returns an awaitable, runs it to
+    /// completion on the callbacks event loop and returns its result.
+    ///
+    /// A running loop is given the awaitable and waited for, which releases
+    /// the GIL; a loop that is not running is run until the awaitable
+    /// completes.
+    fn call(
+        &self,
+        py: Python<'_>,
+        callback: &PyObject,
+        args: impl IntoPy<Py<PyTuple>>,
+    ) -> PyResult<PyObject> {
+        let result = callback.call1(py, args)?;
+        if !py
+            .import("inspect")?
+            .call_method1("isawaitable", (result.as_ref(py),))?
+            .is_true()?
+        {
+            return Ok(result);
+        }
+        let asyncio = py.import("asyncio")?;
+        let event_loop = self.event_loop(py)?.as_ref(py);
+        if !event_loop.call_method0("is_running")?.is_true()? {
+            return event_loop
+                .call_method1("run_until_complete", (result,))
+                .map(Into::into);
+        }
+        if asyncio.call_method0("_get_running_loop")?.is(event_loop) {
+            // Closing the coroutine avoids a warning about it never being
+            // awaited.
+            if result.as_ref(py).hasattr("close")? {
+                result.call_method0(py, "close")?;
+            }
+            return Err(PyRuntimeError::new_err(
+                "Findex cannot wait for its callbacks from a coroutine running on their event \
+                 loop: call it from another thread, e.g. using `loop.run_in_executor`",
+            ));
+        }
+        asyncio
+            .call_method1("run_coroutine_threadsafe", (result, event_loop))?
+            .call_method0("result")
+            .map(Into::into)
+    }
+
     pub(crate) async fn fetch<const LENGTH: usize>(
         &self,
         tokens: HashSet<Token>,
@@ -76,11 +183,15 @@ impl PythonCallbacks {
                     .into_iter()
                     .map(|token| PyBytes::new(py, &token))
                     .collect::<Vec<_>>();
-                let results = fetch.call1(py, (py_tokens,)).map_err(|e| {
+                let results = self.call(py, fetch, (py_tokens,)).map_err(|e| {
                     DbInterfaceError::Python(format!("unwrapping error: {e} (fetch_entry)"))
                 })?;
-                let results: HashMap<[u8; Token::LENGTH], Vec<u8>> =
-                    results.extract(py).map_err(|e| {
+                // Sharded Entry Tables may return several values per token.
+                let results: Vec<([u8; Token::LENGTH], Vec<u8>)> = results
+                    .extract::<HashMap<[u8; Token::LENGTH], Vec<u8>>>(py)
+                    .map(|results| results.into_iter().collect())
+                    .or_else(|_| results.extract(py))
+                    .map_err(|e| {
                         DbInterfaceError::Python(format!(
                             "converting Python results: {e} (fetch_entry)"
                         ))
@@ -137,8 +248,8 @@ impl PythonCallbacks {
                         })?;
                 }
 
-                let rejected_lines = upsert
-                    .call1(py, (py_old_values, py_new_values))
+                let rejected_lines = self
+                    .call(py, upsert, (py_old_values, py_new_values))
                     .map_err(|e| DbInterfaceError::Python(format!("{e} (upsert)")))?;
 
                 let rejected_lines: HashMap<[u8; Token::LENGTH], Vec<u8>> =
@@ -187,7 +298,7 @@ impl PythonCallbacks {
                             ))
                         })?;
                 }
-                insert.call1(py, (py_new_links,)).map_err(|e| {
+                self.call(py, insert, (py_new_links,)).map_err(|e| {
                     DbInterfaceError::Python(format!(
                         "unwrapping callback error: {e} (insert_chain)"
                     ))
@@ -208,7 +319,7 @@ impl PythonCallbacks {
                     .iter()
                     .map(|uid| PyBytes::new(py, uid))
                     .collect::<Vec<_>>();
-                delete.call1(py, (py_uids,)).map_err(|e| {
+                self.call(py, delete, (py_uids,)).map_err(|e| {
                     DbInterfaceError::Python(format!(
                         "unwrapping callback error: {e} (insert_chain)"
                     ))
@@ -225,7 +336,7 @@ impl PythonCallbacks {
     pub(crate) async fn dump_tokens(&self) -> Result<HashSet<Token>, DbInterfaceError> {
         if let Some(dump_token) = &self.dump_tokens {
             Python::with_gil(|py| {
-                let results = dump_token.call0(py).map_err(|e| {
+                let results = self.call(py, dump_token, ()).map_err(|e| {
                     DbInterfaceError::Python(format!("unwrapping callback error: {e} (dump_token)"))
                 })?;
                 let py_result_table: HashSet<[u8; Token::LENGTH]> = results
@@ -63,8 +63,10 @@ macro_rules! call2 {
 }
 
 mod callbacks;
+mod sqlite;
 mod stores;
 
+pub use sqlite::{SqliteDatabase, SqliteTable, WasmSqliteChainBackend, WasmSqliteEntryBackend};
 pub use stores::WasmCallbacks;
 
 #[derive(Debug)]
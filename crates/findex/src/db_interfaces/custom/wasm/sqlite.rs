@@ -0,0 +1,311 @@
+//! `SQLite` implementation of the Findex backends for browsers.
+//!
+//! Browsers cannot open native `SQLite` files: the database is provided by the
+//! application, e.g. a [sql.js](https://sql.js.org) database or an
+//! [OPFS](https://sqlite.org/wasm/doc/trunk/persistence.md)-backed database of
+//! the official `SQLite` WASM build, through a thin `SqliteDatabase` handle
+//! executing one SQL statement at a time:
+//!
+//! ```js
+//! const db = new SQL.Database()
+//! const handle = {
+//!   exec: (sql, params) => db.exec(sql, params)[0]?.values ?? [],
+//! }
+//! const findex = await WasmFindex.new({
+//!   backend: 'sqlite',
+//!   entryDatabase: handle,
+//!   chainDatabase: handle,
+//! })
+//! ```
+//!
+//! The tables use the same schema as the native `SQLite` backend, so that an
+//! index built in a browser can be exported and opened natively.
+//!
+//! Upserts and insertions run in a transaction (`BEGIN` ... `COMMIT`): the
+//! database should not be used by other transactions at the same time.
+
+use std::collections::HashMap;
+
+use cosmian_findex::{
+    EncryptedValue, Token, TokenToEncryptedValueMap, TokenWithEncryptedValueList, Tokens,
+    ENTRY_LENGTH, LINK_LENGTH,
+};
+use js_sys::{Array, Promise, Uint8Array};
+use wasm_bindgen::{prelude::wasm_bindgen, JsValue};
+use wasm_bindgen_futures::JsFuture;
+
+use crate::db_interfaces::DbInterfaceError;
+
+#[wasm_bindgen(typescript_custom_section)]
+const SQLITE_DATABASE: &str = r#"
+/**
+ * SQLite database used by the `sqlite` backend. `exec` runs the given SQL
+ * statement, binding the `?` placeholders to the given parameters, and returns
+ * the rows it selected, each row being the list of its columns.
+ */
+export interface SqliteDatabase {
+  exec(sql: string, params: Uint8Array[]): Uint8Array[][] | Promise<Uint8Array[][]>;
+}
+"#;
+
+#[wasm_bindgen]
+extern "C" {
+    /// SQLite database provided by the application, see `SqliteDatabase` in
+    /// the TypeScript declarations.
+    #[wasm_bindgen(typescript_type = "SqliteDatabase")]
+    #[derive(Debug, Clone)]
+    pub type SqliteDatabase;
+
+    #[wasm_bindgen(method, catch)]
+    fn exec(this: &SqliteDatabase, sql: &str, params: &Array) -> Result<JsValue, JsValue>;
+}
+
+impl SqliteDatabase {
+    /// Executes the given statement and returns the selected rows.
+    async fn query(
+        &self,
+        sql: &str,
+        params: &[&[u8]],
+    ) -> Result<Vec<Vec<Vec<u8>>>, DbInterfaceError> {
+        let js_params = Array::new();
+        for param in params {
+            js_params.push(&Uint8Array::from(*param));
+        }
+        let res = JsFuture::from(Promise::resolve(&self.exec(sql, &js_params)?)).await?;
+        if res.is_undefined() || res.is_null() {
+            return Ok(Vec::new());
+        }
+        if !Array::is_array(&res) {
+            return Err(DbInterfaceError::Wasm(format!(
+                "`exec` should return an array of rows, got {res:?}"
+            )));
+        }
+        Array::from(&res)
+            .iter()
+            .map(|row| {
+                if Array::is_array(&row) {
+                    Ok(Array::from(&row)
+                        .iter()
+                        .map(|column| Uint8Array::from(column).to_vec())
+                        .collect())
+                } else {
+                    Err(DbInterfaceError::Wasm(format!(
+                        "each row returned by `exec` should be an array, got {row:?}"
+                    )))
+                }
+            })
+            .collect()
+    }
+
+    async fn execute(&self, sql: &str, params: &[&[u8]]) -> Result<(), DbInterfaceError> {
+        self.query(sql, params).await.map(|_| ())
+    }
+
+    /// Executes the given statements in a transaction, rolled back upon
+    /// failure.
+    async fn transaction<T>(
+        &self,
+        statements: impl std::future::Future<Output = Result<T, DbInterfaceError>>,
+    ) -> Result<T, DbInterfaceError> {
+        self.execute("BEGIN", &[]).await?;
+        match statements.await {
+            Ok(res) => {
+                self.execute("COMMIT", &[]).await?;
+                Ok(res)
+            }
+            Err(e) => {
+                // The original error is more relevant than a rollback failure.
+                let _ = self.execute("ROLLBACK", &[]).await;
+                Err(e)
+            }
+        }
+    }
+}
+
+fn placeholders(n: usize) -> String {
+    (0..n).map(|_| "?").collect::<Vec<_>>().join(",")
+}
+
+/// Table of a `SQLite` database provided by the application.
+#[derive(Debug, Clone)]
+pub struct SqliteTable {
+    database: SqliteDatabase,
+    name: &'static str,
+    /// The tables are not sharded.
+    pub(crate) table_number: usize,
+}
+
+impl SqliteTable {
+    /// Opens the given table, creating it if needed.
+    pub async fn open(
+        database: SqliteDatabase,
+        name: &'static str,
+    ) -> Result<Self, DbInterfaceError> {
+        database
+            .execute(
+                &format!(
+                    "CREATE TABLE IF NOT EXISTS {name} (
+                         uid               BLOB PRIMARY KEY,
+                         value             BLOB NOT NULL
+                     )"
+                ),
+                &[],
+            )
+            .await?;
+        Ok(Self {
+            database,
+            name,
+            table_number: 1,
+        })
+    }
+
+    pub(crate) async fn dump_tokens(&self) -> Result<Tokens, DbInterfaceError> {
+        self.database
+            .query(&format!("SELECT uid FROM {}", self.name), &[])
+            .await?
+            .into_iter()
+            .map(|row| {
+                let uid = row.first().ok_or_else(|| {
+                    DbInterfaceError::Wasm("`exec` returned an empty row".to_string())
+                })?;
+                Ok::<_, DbInterfaceError>(Token::try_from(uid.as_slice())?)
+            })
+            .collect()
+    }
+
+    pub(crate) async fn fetch<const LENGTH: usize>(
+        &self,
+        uids: Tokens,
+    ) -> Result<TokenWithEncryptedValueList<LENGTH>, DbInterfaceError> {
+        if uids.is_empty() {
+            return Ok(TokenWithEncryptedValueList::from(Vec::new()));
+        }
+        let params = uids.iter().map(|uid| &uid[..]).collect::<Vec<_>>();
+        self.database
+            .query(
+                &format!(
+                    "SELECT uid, value FROM {} WHERE uid IN ({})",
+                    self.name,
+                    placeholders(params.len())
+                ),
+                &params,
+            )
+            .await?
+            .into_iter()
+            .map(|row| match row.as_slice() {
+                [uid, value] => Ok((
+                    Token::try_from(uid.as_slice())?,
+                    EncryptedValue::try_from(value.as_slice())?,
+                )),
+                _ => Err(DbInterfaceError::Wasm(format!(
+                    "`exec` returned {} columns while 2 were selected",
+                    row.len()
+                ))),
+            })
+            .collect::<Result<Vec<_>, _>>()
+            .map(Into::into)
+    }
+
+    pub(crate) async fn upsert<const LENGTH: usize>(
+        &self,
+        old_values: TokenToEncryptedValueMap<LENGTH>,
+        new_values: TokenToEncryptedValueMap<LENGTH>,
+    ) -> Result<TokenToEncryptedValueMap<LENGTH>, DbInterfaceError> {
+        let mut conflicting_values = HashMap::with_capacity(new_values.len());
+        self.database
+            .transaction(async {
+                for (token, new_value) in new_values {
+                    let old_value = old_values.get(&token).map(<Vec<u8>>::from);
+                    let indexed_value = self
+                        .database
+                        .query(
+                            &format!("SELECT value FROM {} WHERE uid = ?", self.name),
+                            &[&token[..]],
+                        )
+                        .await?
+                        .into_iter()
+                        .next()
+                        .and_then(|row| row.into_iter().next());
+                    if indexed_value == old_value {
+                        self.database
+                            .execute(
+                                &format!("REPLACE INTO {} (uid, value) VALUES (?, ?)", self.name),
+                                &[&token[..], &<Vec<u8>>::from(&new_value)],
+                            )
+                            .await?;
+                    } else {
+                        let indexed_value = indexed_value.ok_or_else(|| {
+                            DbInterfaceError::Other(
+                                "Index values cannot be removed while upserting.".to_string(),
+                            )
+                        })?;
+                        conflicting_values
+                            .insert(token, EncryptedValue::try_from(indexed_value.as_slice())?);
+                    }
+                }
+                Ok::<_, DbInterfaceError>(())
+            })
+            .await?;
+        Ok(TokenToEncryptedValueMap::from(conflicting_values))
+    }
+
+    pub(crate) async fn insert<const LENGTH: usize>(
+        &self,
+        items: TokenToEncryptedValueMap<LENGTH>,
+    ) -> Result<(), DbInterfaceError> {
+        self.database
+            .transaction(async {
+                for (token, value) in items {
+                    self.database
+                        .execute(
+                            &format!("INSERT INTO {} (uid, value) VALUES (?, ?)", self.name),
+                            &[&token[..], &<Vec<u8>>::from(&value)],
+                        )
+                        .await?;
+                }
+                Ok::<_, DbInterfaceError>(())
+            })
+            .await
+    }
+
+    pub(crate) async fn delete(&self, uids: Tokens) -> Result<(), DbInterfaceError> {
+        if uids.is_empty() {
+            return Ok(());
+        }
+        let params = uids.iter().map(|uid| &uid[..]).collect::<Vec<_>>();
+        self.database
+            .execute(
+                &format!(
+                    "DELETE FROM {} WHERE uid IN ({})",
+                    self.name,
+                    placeholders(params.len())
+                ),
+                &params,
+            )
+            .await
+    }
+}
+
+#[derive(Debug)]
+pub struct WasmSqliteEntryBackend(SqliteTable);
+
+impl_custom_backend!(WasmSqliteEntryBackend, SqliteTable, ENTRY_LENGTH);
+
+impl WasmSqliteEntryBackend {
+    /// Opens the Entry Table of the given database.
+    pub async fn open(database: SqliteDatabase) -> Result<Self, DbInterfaceError> {
+        SqliteTable::open(database, "entry_table").await.map(Self)
+    }
+}
+
+#[derive(Debug)]
+pub struct WasmSqliteChainBackend(SqliteTable);
+
+impl_custom_backend!(WasmSqliteChainBackend, SqliteTable, LINK_LENGTH);
+
+impl WasmSqliteChainBackend {
+    /// Opens the Chain Table of the given database.
+    pub async fn open(database: SqliteDatabase) -> Result<Self, DbInterfaceError> {
+        SqliteTable::open(database, "chain_table").await.map(Self)
+    }
+}
@@ -1,5 +1,6 @@
 use cosmian_findex::{TokenToEncryptedValueMap, TokenWithEncryptedValueList, Tokens};
-use wasm_bindgen::prelude::wasm_bindgen;
+use js_sys::Reflect;
+use wasm_bindgen::{prelude::wasm_bindgen, JsCast, JsValue};
 
 use crate::{
     db_interfaces::{
@@ -28,9 +29,13 @@ use crate::{
 /// | compact |  ET + CT  |           |  ET + CT  |  ET + CT  |       ET      |
 /// +---------+-----------+-----------+-----------+-----------+---------------+
 /// ```
-#[derive(Clone, Debug, Default)]
+///
+/// The Entry Table may be sharded into `table_number` tables: `fetch` then
+/// returns the values found in each of them.
+#[derive(Clone, Debug)]
 #[wasm_bindgen]
 pub struct WasmCallbacks {
+    pub(crate) table_number: usize,
     dump_tokens: Option<DumpTokens>,
     fetch: Option<Fetch>,
     upsert: Option<Upsert>,
@@ -38,6 +43,19 @@ pub struct WasmCallbacks {
     delete: Option<Delete>,
 }
 
+impl Default for WasmCallbacks {
+    fn default() -> Self {
+        Self {
+            table_number: 1,
+            dump_tokens: None,
+            fetch: None,
+            upsert: None,
+            insert: None,
+            delete: None,
+        }
+    }
+}
+
 #[wasm_bindgen]
 impl WasmCallbacks {
     #[wasm_bindgen(constructor)]
@@ -91,6 +109,46 @@ impl WasmCallbacks {
     }
 }
 
+/// Returns the callback stored in the given property of the given object, if
+/// any.
+fn get_callback<T: JsCast>(object: &JsValue, property: &str) -> Result<Option<T>, String> {
+    let value = Reflect::get(object, &JsValue::from_str(property))
+        .map_err(|e| format!("could not get `{property}`: {e:?}"))?;
+    if value.is_undefined() || value.is_null() {
+        Ok(None)
+    } else if value.is_function() {
+        Ok(Some(value.unchecked_into()))
+    } else {
+        Err(format!("`{property}` should be a function"))
+    }
+}
+
+impl WasmCallbacks {
+    /// Reads the callbacks from the properties of the given object, which can
+    /// be a `WasmCallbacks` instance or a plain object: `fetch`, `upsert`,
+    /// `insert`, `delete` and `dumpTokens`.
+    ///
+    /// The `fetch` callback is needed by all the Findex operations and is
+    /// therefore required.
+    pub(crate) fn try_from_object(object: &JsValue) -> Result<Self, String> {
+        if !object.is_object() {
+            return Err("callbacks should be given as an object".to_string());
+        }
+        let callbacks = Self {
+            dump_tokens: get_callback(object, "dumpTokens")?,
+            fetch: get_callback(object, "fetch")?,
+            upsert: get_callback(object, "upsert")?,
+            insert: get_callback(object, "insert")?,
+            delete: get_callback(object, "delete")?,
+            ..Self::default()
+        };
+        if callbacks.fetch.is_none() {
+            return Err("the `fetch` callback is required".to_string());
+        }
+        Ok(callbacks)
+    }
+}
+
 impl WasmCallbacks {
     pub(crate) async fn dump_tokens(&self) -> Result<Tokens, DbInterfaceError> {
         let res = call0!(self, dump_tokens);
@@ -1,16 +1,24 @@
 use core::fmt::Display;
+#[cfg(feature = "rest-interface")]
+use std::time::Duration;
 use std::{array::TryFromSliceError, num::TryFromIntError};
 
 use cosmian_crypto_core::CryptoCoreError;
 #[cfg(feature = "ffi")]
 use cosmian_ffi_utils::ErrorCode;
 use cosmian_findex::{CoreError as FindexCoreError, DbInterfaceErrorTrait};
+#[cfg(feature = "etcd-interface")]
+use etcd_client::Error as EtcdError;
 #[cfg(feature = "wasm")]
 use js_sys::{JsString, Object};
+#[cfg(feature = "object-store-interface")]
+use object_store::Error as ObjectStoreError;
 #[cfg(feature = "redis-interface")]
 use redis::RedisError;
 #[cfg(feature = "sqlite-interface")]
 use rusqlite::Error as RusqliteError;
+#[cfg(feature = "grpc-interface")]
+use tonic::{transport::Error as TonicTransportError, Status as TonicStatus};
 #[cfg(feature = "wasm")]
 use wasm_bindgen::JsCast;
 #[cfg(feature = "wasm")]
@@ -25,6 +33,12 @@ pub enum DbInterfaceError {
     Rusqlite(RusqliteError),
     #[cfg(feature = "redis-interface")]
     Redis(RedisError),
+    #[cfg(feature = "grpc-interface")]
+    Grpc(String),
+    #[cfg(feature = "object-store-interface")]
+    ObjectStore(String),
+    #[cfg(feature = "etcd-interface")]
+    Etcd(String),
     MissingCallback(String),
     #[cfg(feature = "ffi")]
     Ffi(String, ErrorCode),
@@ -36,6 +50,19 @@ pub enum DbInterfaceError {
     Wasm(String),
     #[cfg(feature = "rest-interface")]
     MissingPermission(i32),
+    /// The REST server kept rejecting the request with `429 Too Many
+    /// Requests`, optionally giving the delay after which to retry.
+    #[cfg(feature = "rest-interface")]
+    RateLimited(Option<Duration>),
+    /// Some Entry Table tokens were rejected by the given number of
+    /// consecutive upserts because of concurrent modifications.
+    #[cfg(feature = "rest-interface")]
+    UpsertConflicts {
+        tokens: usize,
+        rounds: usize,
+    },
+    /// The index was instantiated in read-only mode and cannot be modified.
+    ReadOnly,
     Findex(FindexCoreError),
     CryptoCore(CryptoCoreError),
     Serialization(String),
@@ -52,6 +79,12 @@ impl Display for DbInterfaceError {
             Self::Rusqlite(err) => write!(f, "rusqlite: {err}"),
             #[cfg(feature = "redis-interface")]
             Self::Redis(err) => write!(f, "redis: {err}"),
+            #[cfg(feature = "grpc-interface")]
+            Self::Grpc(err) => write!(f, "grpc: {err}"),
+            #[cfg(feature = "object-store-interface")]
+            Self::ObjectStore(err) => write!(f, "object store: {err}"),
+            #[cfg(feature = "etcd-interface")]
+            Self::Etcd(err) => write!(f, "etcd: {err}"),
             Self::MissingCallback(err) => write!(f, "unknown callback: {err}"),
             #[cfg(feature = "ffi")]
             Self::Ffi(err, code) => write!(f, "{err}: {code}"),
@@ -63,6 +96,20 @@ impl Display for DbInterfaceError {
             Self::Wasm(err) => write!(f, "wasm callback error: {err}"),
             #[cfg(feature = "rest-interface")]
             Self::MissingPermission(err) => write!(f, "missing permission: {err}"),
+            #[cfg(feature = "rest-interface")]
+            Self::RateLimited(retry_after) => {
+                write!(f, "too many requests to the REST server")?;
+                match retry_after {
+                    Some(retry_after) => write!(f, ", retry after {}s", retry_after.as_secs()),
+                    None => Ok(()),
+                }
+            }
+            #[cfg(feature = "rest-interface")]
+            Self::UpsertConflicts { tokens, rounds } => write!(
+                f,
+                "{tokens} Entry Table token(s) still conflicting after {rounds} upsert rounds"
+            ),
+            Self::ReadOnly => write!(f, "the index was instantiated in read-only mode"),
             Self::CryptoCore(err) => write!(f, "crypto_core: {err}"),
             Self::Findex(err) => write!(f, "findex: {err}"),
             Self::Io(err) => write!(f, "io: {err}"),
@@ -92,6 +139,34 @@ impl From<RedisError> for DbInterfaceError {
     }
 }
 
+#[cfg(feature = "grpc-interface")]
+impl From<TonicStatus> for DbInterfaceError {
+    fn from(e: TonicStatus) -> Self {
+        Self::Grpc(format!("{}: {}", e.code(), e.message()))
+    }
+}
+
+#[cfg(feature = "grpc-interface")]
+impl From<TonicTransportError> for DbInterfaceError {
+    fn from(e: TonicTransportError) -> Self {
+        Self::Grpc(format!("transport: {e}"))
+    }
+}
+
+#[cfg(feature = "object-store-interface")]
+impl From<ObjectStoreError> for DbInterfaceError {
+    fn from(e: ObjectStoreError) -> Self {
+        Self::ObjectStore(e.to_string())
+    }
+}
+
+#[cfg(feature = "etcd-interface")]
+impl From<EtcdError> for DbInterfaceError {
+    fn from(e: EtcdError) -> Self {
+        Self::Etcd(e.to_string())
+    }
+}
+
 #[cfg(any(feature = "rest-interface", feature = "wasm", feature = "ffi"))]
 impl From<SerializationError> for DbInterfaceError {
     fn from(e: SerializationError) -> Self {
@@ -0,0 +1,319 @@
+//! etcd implementation of the Findex backends.
+//!
+//! Each line of a table is stored under the key made of the table prefix
+//! followed by its token. Entry Table upserts are compare-and-swap
+//! transactions: the new value is only written if the indexed value still
+//! matches the old one, which etcd guarantees to be linearizable. This backend
+//! suits deployments where the consistency model of Redis is not acceptable.
+
+use std::collections::HashMap;
+
+use async_trait::async_trait;
+use cosmian_findex::{
+    DbInterface, EncryptedValue, Token, TokenToEncryptedValueMap, TokenWithEncryptedValueList,
+    Tokens, ENTRY_LENGTH, LINK_LENGTH,
+};
+use etcd_client::{Client, Compare, CompareOp, GetOptions, KvClient, Txn, TxnOp, TxnOpResponse};
+use futures::future::try_join_all;
+use tracing::trace;
+
+use crate::db_interfaces::DbInterfaceError;
+
+/// Maximum number of operations in a transaction, as the default
+/// `--max-txn-ops` of etcd servers.
+pub const MAX_TXN_OPS: usize = 128;
+
+/// Connects to the etcd cluster reachable at the given comma-separated
+/// endpoints, e.g. `http://etcd-1:2379,http://etcd-2:2379`.
+async fn connect(endpoints: &str) -> Result<KvClient, DbInterfaceError> {
+    let endpoints = endpoints.split(',').map(str::trim).collect::<Vec<_>>();
+    Ok(Client::connect(endpoints, None).await?.kv_client())
+}
+
+/// Upserts the given line, returning the indexed value if it does not match
+/// the given old value.
+async fn upsert_line<const VALUE_LENGTH: usize>(
+    mut client: KvClient,
+    key: Vec<u8>,
+    old_value: Option<&EncryptedValue<VALUE_LENGTH>>,
+    new_value: &EncryptedValue<VALUE_LENGTH>,
+) -> Result<Option<EncryptedValue<VALUE_LENGTH>>, DbInterfaceError> {
+    let compare = match old_value {
+        Some(old_value) => Compare::value(key.clone(), CompareOp::Equal, Vec::from(old_value)),
+        // The line should not exist yet.
+        None => Compare::create_revision(key.clone(), CompareOp::Equal, 0),
+    };
+    let txn = Txn::new()
+        .when([compare])
+        .and_then([TxnOp::put(key.clone(), Vec::from(new_value), None)])
+        .or_else([TxnOp::get(key, None)]);
+    let response = client.txn(txn).await?;
+    if response.succeeded() {
+        return Ok(None);
+    }
+
+    let indexed_value = response
+        .op_responses()
+        .into_iter()
+        .find_map(|response| match response {
+            TxnOpResponse::Get(response) => response
+                .kvs()
+                .first()
+                .map(|kv| EncryptedValue::try_from(kv.value())),
+            _ => None,
+        })
+        .ok_or_else(|| {
+            DbInterfaceError::Other("Index values cannot be removed while upserting.".to_string())
+        })??;
+    Ok(Some(indexed_value))
+}
+
+/// Implements the etcd backend for the given `$type`, with values of size
+/// `$value_length` stored under the keys prefixed by `$default_prefix`.
+macro_rules! impl_etcd_backend {
+    ($type:ident, $value_length:ident, $default_prefix:literal) => {
+        impl $type {
+            /// Connects to the etcd cluster reachable at the given
+            /// comma-separated endpoints.
+            pub async fn connect(endpoints: &str) -> Result<Self, DbInterfaceError> {
+                Ok(Self::new(connect(endpoints).await?))
+            }
+
+            /// Stores the table using the given client.
+            #[must_use]
+            pub fn new(client: KvClient) -> Self {
+                Self {
+                    client,
+                    prefix: $default_prefix.to_vec(),
+                }
+            }
+
+            /// Sets the prefix of the keys of the table, e.g. to store several
+            /// indexes in the same cluster.
+            #[must_use]
+            pub fn with_prefix(mut self, prefix: impl Into<Vec<u8>>) -> Self {
+                self.prefix = prefix.into();
+                self
+            }
+
+            fn key(&self, token: &Token) -> Vec<u8> {
+                [self.prefix.as_slice(), token.as_slice()].concat()
+            }
+        }
+
+        impl std::fmt::Debug for $type {
+            fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+                f.debug_struct(stringify!($type))
+                    .field("prefix", &self.prefix)
+                    .finish()
+            }
+        }
+
+        #[async_trait(?Send)]
+        impl DbInterface<$value_length> for $type {
+            type Error = DbInterfaceError;
+
+            async fn dump_tokens(&self) -> Result<Tokens, Self::Error> {
+                let response = self
+                    .client
+                    .clone()
+                    .get(
+                        self.prefix.clone(),
+                        Some(GetOptions::new().with_prefix().with_keys_only()),
+                    )
+                    .await?;
+
+                trace!("dumping {} tokens", response.kvs().len());
+
+                response
+                    .kvs()
+                    .iter()
+                    .map(|kv| {
+                        Token::try_from(&kv.key()[self.prefix.len()..])
+                            .map_err(Self::Error::Findex)
+                    })
+                    .collect()
+            }
+
+            async fn fetch(
+                &self,
+                tokens: Tokens,
+            ) -> Result<TokenWithEncryptedValueList<$value_length>, Self::Error> {
+                trace!("fetch num tokens: {}", tokens.len());
+
+                let lines = try_join_all(tokens.into_iter().map(|token| {
+                    let mut client = self.client.clone();
+                    let key = self.key(&token);
+                    async move {
+                        let response = client.get(key, None).await?;
+                        let line = match response.kvs().first() {
+                            Some(kv) => Some((token, EncryptedValue::try_from(kv.value())?)),
+                            None => None,
+                        };
+                        Ok::<_, DbInterfaceError>(line)
+                    }
+                }))
+                .await?;
+
+                Ok(lines.into_iter().flatten().collect::<Vec<_>>().into())
+            }
+
+            async fn upsert(
+                &self,
+                old_values: TokenToEncryptedValueMap<$value_length>,
+                new_values: TokenToEncryptedValueMap<$value_length>,
+            ) -> Result<TokenToEncryptedValueMap<$value_length>, Self::Error> {
+                trace!("upsert num tokens: {}", new_values.len());
+
+                let rejected = try_join_all(new_values.iter().map(|(token, new_value)| {
+                    let client = self.client.clone();
+                    let key = self.key(token);
+                    let old_value = old_values.get(token);
+                    async move {
+                        let indexed_value = upsert_line(client, key, old_value, new_value).await?;
+                        Ok::<_, DbInterfaceError>(indexed_value.map(|value| (*token, value)))
+                    }
+                }))
+                .await?
+                .into_iter()
+                .flatten()
+                .collect::<HashMap<_, _>>();
+
+                trace!("upsert rejected: {}", rejected.len());
+
+                Ok(TokenToEncryptedValueMap::from(rejected))
+            }
+
+            async fn insert(
+                &self,
+                items: TokenToEncryptedValueMap<$value_length>,
+            ) -> Result<(), Self::Error> {
+                let puts = items
+                    .iter()
+                    .map(|(token, value)| TxnOp::put(self.key(token), Vec::from(value), None))
+                    .collect::<Vec<_>>();
+                for chunk in puts.chunks(MAX_TXN_OPS) {
+                    self.client
+                        .clone()
+                        .txn(Txn::new().and_then(chunk.to_vec()))
+                        .await?;
+                }
+                Ok(())
+            }
+
+            async fn delete(&self, tokens: Tokens) -> Result<(), Self::Error> {
+                let deletes = tokens
+                    .iter()
+                    .map(|token| TxnOp::delete(self.key(token), None))
+                    .collect::<Vec<_>>();
+                for chunk in deletes.chunks(MAX_TXN_OPS) {
+                    self.client
+                        .clone()
+                        .txn(Txn::new().and_then(chunk.to_vec()))
+                        .await?;
+                }
+                Ok(())
+            }
+        }
+    };
+}
+
+pub struct EtcdEntryBackend {
+    client: KvClient,
+    prefix: Vec<u8>,
+}
+
+impl_etcd_backend!(EtcdEntryBackend, ENTRY_LENGTH, b"findex/entry/");
+
+pub struct EtcdChainBackend {
+    client: KvClient,
+    prefix: Vec<u8>,
+}
+
+impl_etcd_backend!(EtcdChainBackend, LINK_LENGTH, b"findex/chain/");
+
+#[cfg(test)]
+mod tests {
+    use cosmian_crypto_core::Nonce;
+    use cosmian_findex::{MAC_LENGTH, NONCE_LENGTH};
+    use serial_test::serial;
+
+    use super::*;
+    use crate::{
+        db_interfaces::tests::{test_backend, test_dump_restore},
+        logger::log_init,
+        Configuration,
+    };
+
+    fn get_etcd_endpoints() -> String {
+        if let Ok(var_env) = std::env::var("ETCD_HOST") {
+            format!("http://{var_env}:2379")
+        } else {
+            "http://localhost:2379".to_string()
+        }
+    }
+
+    #[actix_rt::test]
+    #[serial]
+    async fn test_upsert_conflict() -> Result<(), DbInterfaceError> {
+        log_init();
+
+        let et = EtcdEntryBackend::connect(&get_etcd_endpoints())
+            .await?
+            .with_prefix("findex/test_upsert_conflict/");
+        let value = |i: u8| EncryptedValue {
+            nonce: Nonce::from([i; NONCE_LENGTH]),
+            ciphertext: [i; ENTRY_LENGTH],
+            tag: [i; MAC_LENGTH],
+        };
+        let token = Token::from([1; Token::LENGTH]);
+        et.delete(Tokens::from_iter([token])).await?;
+
+        let rejected = et
+            .upsert(
+                HashMap::new().into(),
+                [(token, value(1))].into_iter().collect(),
+            )
+            .await?;
+        assert!(rejected.is_empty());
+
+        // Upserting with an outdated old value is rejected.
+        let rejected = et
+            .upsert(
+                HashMap::new().into(),
+                [(token, value(2))].into_iter().collect(),
+            )
+            .await?;
+        assert_eq!(rejected.get(&token), Some(&value(1)));
+
+        let rejected = et
+            .upsert(
+                [(token, value(1))].into_iter().collect(),
+                [(token, value(2))].into_iter().collect(),
+            )
+            .await?;
+        assert!(rejected.is_empty());
+
+        let tokens = et.dump_tokens().await?;
+        assert_eq!(tokens.len(), 1);
+        assert!(tokens.contains(&token));
+
+        Ok(())
+    }
+
+    #[actix_rt::test]
+    #[serial]
+    async fn test_etcd_backend() {
+        log_init();
+        let config = Configuration::Etcd(get_etcd_endpoints(), get_etcd_endpoints());
+        test_backend(config).await;
+    }
+
+    #[actix_rt::test]
+    #[serial]
+    async fn test_etcd_dump_restore() {
+        log_init();
+        let config = Configuration::Etcd(get_etcd_endpoints(), get_etcd_endpoints());
+        test_dump_restore(config.clone(), config).await;
+    }
+}
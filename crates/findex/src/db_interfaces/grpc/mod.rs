@@ -0,0 +1,391 @@
+//! gRPC implementation of the Findex backends.
+//!
+//! Both tables are stored by a remote service implementing the `FindexStore`
+//! service published in `proto/findex_store.proto`. The table targeted by a
+//! request is given by its `table` field, so a single service can store both
+//! the Entry Table and the Chain Table.
+
+mod proto;
+
+use async_trait::async_trait;
+use cosmian_findex::{
+    DbInterface, EncryptedValue, Token, TokenToEncryptedValueMap, TokenWithEncryptedValueList,
+    Tokens, ENTRY_LENGTH, LINK_LENGTH,
+};
+pub use proto::FindexStoreClient;
+use proto::{
+    DeleteRequest, DumpTokensRequest, FetchRequest, InsertRequest, Line, Table, UpsertRequest,
+};
+use tracing::trace;
+
+use crate::db_interfaces::DbInterfaceError;
+
+/// Converts the given Findex lines into gRPC lines.
+fn to_lines<const VALUE_LENGTH: usize>(
+    items: &TokenToEncryptedValueMap<VALUE_LENGTH>,
+) -> Vec<Line> {
+    items
+        .iter()
+        .map(|(token, value)| Line {
+            token: token.to_vec(),
+            value: Vec::from(value),
+        })
+        .collect()
+}
+
+/// Converts the given gRPC lines into Findex lines.
+fn from_lines<const VALUE_LENGTH: usize>(
+    lines: Vec<Line>,
+) -> Result<Vec<(Token, EncryptedValue<VALUE_LENGTH>)>, DbInterfaceError> {
+    lines
+        .into_iter()
+        .map(|line| {
+            Ok((
+                Token::try_from(line.token.as_slice())?,
+                EncryptedValue::try_from(line.value.as_slice())?,
+            ))
+        })
+        .collect()
+}
+
+fn to_token_bytes(tokens: Tokens) -> Vec<Vec<u8>> {
+    tokens.into_iter().map(|token| token.to_vec()).collect()
+}
+
+macro_rules! impl_grpc_backend {
+    ($type:ident, $value_length:ident, $table:expr) => {
+        impl $type {
+            /// Connects to the `FindexStore` service served at the given URL.
+            pub async fn connect(url: &str) -> Result<Self, DbInterfaceError> {
+                Ok(Self(FindexStoreClient::connect(url.to_string()).await?))
+            }
+
+            /// Uses the given client to reach the `FindexStore` service.
+            #[must_use]
+            pub const fn new(client: FindexStoreClient) -> Self {
+                Self(client)
+            }
+        }
+
+        #[async_trait(?Send)]
+        impl DbInterface<$value_length> for $type {
+            type Error = DbInterfaceError;
+
+            async fn dump_tokens(&self) -> Result<Tokens, Self::Error> {
+                let response = self
+                    .0
+                    .dump_tokens(DumpTokensRequest {
+                        table: $table as i32,
+                    })
+                    .await?;
+
+                trace!("dumping {} tokens", response.tokens.len());
+
+                response
+                    .tokens
+                    .iter()
+                    .map(|token| Token::try_from(token.as_slice()).map_err(Self::Error::Findex))
+                    .collect()
+            }
+
+            async fn fetch(
+                &self,
+                tokens: Tokens,
+            ) -> Result<TokenWithEncryptedValueList<$value_length>, Self::Error> {
+                trace!("fetch num tokens: {}", tokens.len());
+
+                if tokens.is_empty() {
+                    return Ok(Default::default());
+                }
+
+                let response = self
+                    .0
+                    .fetch(FetchRequest {
+                        table: $table as i32,
+                        tokens: to_token_bytes(tokens),
+                    })
+                    .await?;
+
+                Ok(from_lines(response.lines)?.into())
+            }
+
+            async fn upsert(
+                &self,
+                old_values: TokenToEncryptedValueMap<$value_length>,
+                new_values: TokenToEncryptedValueMap<$value_length>,
+            ) -> Result<TokenToEncryptedValueMap<$value_length>, Self::Error> {
+                trace!("upsert num tokens: {}", new_values.len());
+
+                let response = self
+                    .0
+                    .upsert(UpsertRequest {
+                        table: $table as i32,
+                        old_values: to_lines(&old_values),
+                        new_values: to_lines(&new_values),
+                    })
+                    .await?;
+
+                trace!("upsert rejected: {}", response.lines.len());
+
+                Ok(from_lines(response.lines)?.into_iter().collect())
+            }
+
+            async fn insert(
+                &self,
+                items: TokenToEncryptedValueMap<$value_length>,
+            ) -> Result<(), Self::Error> {
+                self.0
+                    .insert(InsertRequest {
+                        table: $table as i32,
+                        lines: to_lines(&items),
+                    })
+                    .await?;
+                Ok(())
+            }
+
+            async fn delete(&self, tokens: Tokens) -> Result<(), Self::Error> {
+                self.0
+                    .delete(DeleteRequest {
+                        table: $table as i32,
+                        tokens: to_token_bytes(tokens),
+                    })
+                    .await?;
+                Ok(())
+            }
+        }
+    };
+}
+
+#[derive(Debug, Clone)]
+pub struct GrpcEntryBackend(FindexStoreClient);
+
+impl_grpc_backend!(GrpcEntryBackend, ENTRY_LENGTH, Table::Entry);
+
+#[derive(Debug, Clone)]
+pub struct GrpcChainBackend(FindexStoreClient);
+
+impl_grpc_backend!(GrpcChainBackend, LINK_LENGTH, Table::Chain);
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        collections::HashMap,
+        convert::Infallible,
+        future::{ready, Future, Ready},
+        pin::Pin,
+        sync::{Arc, Mutex},
+        task::{Context, Poll},
+    };
+
+    use cosmian_crypto_core::{CsRng, Nonce};
+    use cosmian_findex::{MAC_LENGTH, NONCE_LENGTH};
+    use rand::{RngCore, SeedableRng};
+    use tonic::{
+        body::BoxBody,
+        codec::ProstCodec,
+        codegen::{http, Service},
+        server::{Grpc, NamedService},
+        transport::{Body, Server},
+        Status,
+    };
+
+    use super::{
+        proto::{path, Empty, Lines, Tokens as ProtoTokens},
+        *,
+    };
+    use crate::{db_interfaces::tests::test_backend, Configuration};
+
+    /// Lines stored by table and token.
+    type StoredLines = HashMap<(i32, Vec<u8>), Vec<u8>>;
+
+    /// In-memory `FindexStore` server, dispatching the requests as the server
+    /// code `tonic-build` generates from the `.proto` does.
+    #[derive(Clone, Default)]
+    struct InMemoryStore(Arc<Mutex<StoredLines>>);
+
+    impl InMemoryStore {
+        fn dump_tokens(&self, request: DumpTokensRequest) -> ProtoTokens {
+            let lines = self.0.lock().unwrap();
+            ProtoTokens {
+                tokens: lines
+                    .keys()
+                    .filter(|(table, _)| *table == request.table)
+                    .map(|(_, token)| token.clone())
+                    .collect(),
+            }
+        }
+
+        fn fetch(&self, request: FetchRequest) -> Lines {
+            let lines = self.0.lock().unwrap();
+            Lines {
+                lines: request
+                    .tokens
+                    .into_iter()
+                    .filter_map(|token| {
+                        let value = lines.get(&(request.table, token.clone()))?.clone();
+                        Some(Line { token, value })
+                    })
+                    .collect(),
+            }
+        }
+
+        fn upsert(&self, request: UpsertRequest) -> Lines {
+            let mut lines = self.0.lock().unwrap();
+            let old_values = request
+                .old_values
+                .into_iter()
+                .map(|line| (line.token, line.value))
+                .collect::<HashMap<_, _>>();
+            let mut rejected = Vec::new();
+            for line in request.new_values {
+                let key = (request.table, line.token);
+                let current = lines.get(&key);
+                if current == old_values.get(&key.1) {
+                    lines.insert(key, line.value);
+                } else if let Some(current) = current {
+                    rejected.push(Line {
+                        value: current.clone(),
+                        token: key.1,
+                    });
+                }
+            }
+            Lines { lines: rejected }
+        }
+
+        fn insert(&self, request: InsertRequest) -> Empty {
+            let mut lines = self.0.lock().unwrap();
+            for line in request.lines {
+                lines.insert((request.table, line.token), line.value);
+            }
+            Empty {}
+        }
+
+        fn delete(&self, request: DeleteRequest) -> Empty {
+            let mut lines = self.0.lock().unwrap();
+            for token in request.tokens {
+                lines.remove(&(request.table, token));
+            }
+            Empty {}
+        }
+    }
+
+    /// Unary method of the in-memory store.
+    struct Method<F>(F);
+
+    impl<Req, Res, F: FnMut(Req) -> Res> Service<tonic::Request<Req>> for Method<F> {
+        type Error = Status;
+        type Future = Ready<Result<Self::Response, Status>>;
+        type Response = tonic::Response<Res>;
+
+        fn poll_ready(&mut self, _: &mut Context<'_>) -> Poll<Result<(), Status>> {
+            Poll::Ready(Ok(()))
+        }
+
+        fn call(&mut self, request: tonic::Request<Req>) -> Self::Future {
+            ready(Ok(tonic::Response::new((self.0)(request.into_inner()))))
+        }
+    }
+
+    impl Service<http::Request<Body>> for InMemoryStore {
+        type Error = Infallible;
+        type Future = Pin<Box<dyn Future<Output = Result<Self::Response, Infallible>> + Send>>;
+        type Response = http::Response<BoxBody>;
+
+        fn poll_ready(&mut self, _: &mut Context<'_>) -> Poll<Result<(), Infallible>> {
+            Poll::Ready(Ok(()))
+        }
+
+        fn call(&mut self, request: http::Request<Body>) -> Self::Future {
+            let store = self.clone();
+            Box::pin(async move {
+                let response = match request.uri().path() {
+                    path::DUMP_TOKENS => {
+                        Grpc::new(ProstCodec::default())
+                            .unary(Method(|r| store.dump_tokens(r)), request)
+                            .await
+                    }
+                    path::FETCH => {
+                        Grpc::new(ProstCodec::default())
+                            .unary(Method(|r| store.fetch(r)), request)
+                            .await
+                    }
+                    path::UPSERT => {
+                        Grpc::new(ProstCodec::default())
+                            .unary(Method(|r| store.upsert(r)), request)
+                            .await
+                    }
+                    path::INSERT => {
+                        Grpc::new(ProstCodec::default())
+                            .unary(Method(|r| store.insert(r)), request)
+                            .await
+                    }
+                    path::DELETE => {
+                        Grpc::new(ProstCodec::default())
+                            .unary(Method(|r| store.delete(r)), request)
+                            .await
+                    }
+                    path => Status::unimplemented(path).to_http(),
+                };
+                Ok(response)
+            })
+        }
+    }
+
+    impl NamedService for InMemoryStore {
+        const NAME: &'static str = "findex.FindexStore";
+    }
+
+    /// Serves a new in-memory store on a local port and returns its URL.
+    async fn serve_in_memory_store() -> String {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}", listener.local_addr().unwrap());
+        let incoming = futures::stream::unfold(listener, |listener| async move {
+            let stream = listener.accept().await.map(|(stream, _)| stream);
+            Some((stream, listener))
+        });
+        actix_rt::spawn(
+            Server::builder()
+                .add_service(InMemoryStore::default())
+                .serve_with_incoming(incoming),
+        );
+        url
+    }
+
+    #[actix_rt::test]
+    async fn test_grpc_backend() {
+        let url = serve_in_memory_store().await;
+        test_backend(Configuration::Grpc(url.clone(), url)).await;
+    }
+
+    #[test]
+    fn test_lines_conversion() -> Result<(), DbInterfaceError> {
+        let mut rng = CsRng::from_entropy();
+        let items = (0..10_u8)
+            .map(|i| {
+                let mut token = [0_u8; Token::LENGTH];
+                rng.fill_bytes(&mut token);
+                let value = EncryptedValue {
+                    nonce: Nonce::from([i; NONCE_LENGTH]),
+                    ciphertext: [i; ENTRY_LENGTH],
+                    tag: [i; MAC_LENGTH],
+                };
+                (Token::from(token), value)
+            })
+            .collect::<TokenToEncryptedValueMap<ENTRY_LENGTH>>();
+
+        let lines = to_lines(&items);
+        assert_eq!(lines.len(), items.len());
+        let res = from_lines::<ENTRY_LENGTH>(lines)?
+            .into_iter()
+            .collect::<TokenToEncryptedValueMap<ENTRY_LENGTH>>();
+        assert_eq!(res.len(), items.len());
+        for (token, value) in &*items {
+            assert_eq!(res.get(token), Some(value));
+        }
+
+        // Values of the wrong length are rejected.
+        assert!(from_lines::<LINK_LENGTH>(to_lines(&items)).is_err());
+
+        Ok(())
+    }
+}
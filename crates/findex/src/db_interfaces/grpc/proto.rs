@@ -0,0 +1,347 @@
+//! Messages and client of the `FindexStore` service defined in
+//! `proto/findex_store.proto`.
+//!
+//! This code mirrors what `tonic-build` would generate, so that no `protoc`
+//! installation is needed to build this crate. The tests check it against the
+//! published `.proto`: method paths, message field tags and wire types, and
+//! enumeration values.
+
+use prost::Message;
+use tonic::{
+    client::Grpc,
+    codec::ProstCodec,
+    codegen::http::uri::PathAndQuery,
+    transport::{Channel, Endpoint},
+    Request, Status,
+};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, ::prost::Enumeration)]
+#[repr(i32)]
+pub enum Table {
+    Entry = 0,
+    Chain = 1,
+}
+
+#[derive(Clone, Copy, PartialEq, ::prost::Message)]
+pub struct Empty {}
+
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct Tokens {
+    #[prost(bytes = "vec", repeated, tag = "1")]
+    pub tokens: Vec<Vec<u8>>,
+}
+
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct Line {
+    #[prost(bytes = "vec", tag = "1")]
+    pub token: Vec<u8>,
+    #[prost(bytes = "vec", tag = "2")]
+    pub value: Vec<u8>,
+}
+
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct Lines {
+    #[prost(message, repeated, tag = "1")]
+    pub lines: Vec<Line>,
+}
+
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct DumpTokensRequest {
+    #[prost(enumeration = "Table", tag = "1")]
+    pub table: i32,
+}
+
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct FetchRequest {
+    #[prost(enumeration = "Table", tag = "1")]
+    pub table: i32,
+    #[prost(bytes = "vec", repeated, tag = "2")]
+    pub tokens: Vec<Vec<u8>>,
+}
+
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct UpsertRequest {
+    #[prost(enumeration = "Table", tag = "1")]
+    pub table: i32,
+    #[prost(message, repeated, tag = "2")]
+    pub old_values: Vec<Line>,
+    #[prost(message, repeated, tag = "3")]
+    pub new_values: Vec<Line>,
+}
+
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct InsertRequest {
+    #[prost(enumeration = "Table", tag = "1")]
+    pub table: i32,
+    #[prost(message, repeated, tag = "2")]
+    pub lines: Vec<Line>,
+}
+
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct DeleteRequest {
+    #[prost(enumeration = "Table", tag = "1")]
+    pub table: i32,
+    #[prost(bytes = "vec", repeated, tag = "2")]
+    pub tokens: Vec<Vec<u8>>,
+}
+
+/// Paths of the `FindexStore` methods.
+pub mod path {
+    pub const DUMP_TOKENS: &str = "/findex.FindexStore/DumpTokens";
+    pub const FETCH: &str = "/findex.FindexStore/Fetch";
+    pub const UPSERT: &str = "/findex.FindexStore/Upsert";
+    pub const INSERT: &str = "/findex.FindexStore/Insert";
+    pub const DELETE: &str = "/findex.FindexStore/Delete";
+}
+
+/// Client of the `FindexStore` service.
+///
+/// Cloning a client is cheap: clones share the same underlying channel.
+#[derive(Debug, Clone)]
+pub struct FindexStoreClient {
+    inner: Grpc<Channel>,
+}
+
+impl FindexStoreClient {
+    /// Connects to the `FindexStore` service served at the given URL.
+    pub async fn connect(url: String) -> Result<Self, tonic::transport::Error> {
+        let channel = Endpoint::from_shared(url)?.connect().await?;
+        Ok(Self::new(channel))
+    }
+
+    /// Uses the given channel to reach the `FindexStore` service.
+    #[must_use]
+    pub fn new(channel: Channel) -> Self {
+        Self {
+            inner: Grpc::new(channel),
+        }
+    }
+
+    async fn unary<Req, Res>(&self, path: &'static str, request: Req) -> Result<Res, Status>
+    where
+        Req: Message + Send + 'static,
+        Res: Message + Default + Send + 'static,
+    {
+        let mut inner = self.inner.clone();
+        inner
+            .ready()
+            .await
+            .map_err(|e| Status::unknown(format!("service was not ready: {e}")))?;
+        let response = inner
+            .unary(
+                Request::new(request),
+                PathAndQuery::from_static(path),
+                ProstCodec::default(),
+            )
+            .await?;
+        Ok(response.into_inner())
+    }
+
+    pub async fn dump_tokens(&self, request: DumpTokensRequest) -> Result<Tokens, Status> {
+        self.unary(path::DUMP_TOKENS, request).await
+    }
+
+    pub async fn fetch(&self, request: FetchRequest) -> Result<Lines, Status> {
+        self.unary(path::FETCH, request).await
+    }
+
+    pub async fn upsert(&self, request: UpsertRequest) -> Result<Lines, Status> {
+        self.unary(path::UPSERT, request).await
+    }
+
+    pub async fn insert(&self, request: InsertRequest) -> Result<Empty, Status> {
+        self.unary(path::INSERT, request).await
+    }
+
+    pub async fn delete(&self, request: DeleteRequest) -> Result<Empty, Status> {
+        self.unary(path::DELETE, request).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use prost::{
+        bytes::Buf,
+        encoding::{decode_key, skip_field, DecodeContext, WireType},
+    };
+
+    use super::*;
+
+    const PROTO: &str = include_str!("../../../proto/findex_store.proto");
+
+    /// Returns the words of the given block, e.g. `message Line`, of the
+    /// published `.proto`, separated from the punctuation.
+    fn block_words(header: &str) -> Vec<String> {
+        let proto = PROTO
+            .lines()
+            .map(|line| line.split("//").next().unwrap_or_default())
+            .collect::<Vec<_>>()
+            .join("\n");
+        let start = proto
+            .find(&format!("{header} {{"))
+            .unwrap_or_else(|| panic!("`{header}` is not in the .proto"));
+        let block = &proto[start + header.len() + 2..];
+        let block = &block[..block.find('}').unwrap()];
+        block
+            .replace(['(', ')', ';', '='], " ")
+            .split_whitespace()
+            .map(ToString::to_string)
+            .collect()
+    }
+
+    /// Returns the tags and wire types of the fields of the given message of
+    /// the published `.proto`, sorted by tag.
+    fn proto_fields(message: &str) -> Vec<(u32, WireType)> {
+        let words = block_words(&format!("message {message}"));
+        let mut fields = words
+            .split(|word| word.parse::<u32>().is_ok())
+            .zip(words.iter().filter_map(|word| word.parse::<u32>().ok()))
+            .map(|(field, tag)| {
+                let field_type = field[field.len() - 2].as_str();
+                let wire_type = match field_type {
+                    "Table" => WireType::Varint,
+                    _ => WireType::LengthDelimited,
+                };
+                (tag, wire_type)
+            })
+            .collect::<Vec<_>>();
+        fields.sort_by_key(|(tag, _)| *tag);
+        fields
+    }
+
+    /// Returns the tags and wire types of the fields of the given encoded
+    /// message, sorted by tag.
+    fn encoded_fields(mut bytes: &[u8]) -> Vec<(u32, WireType)> {
+        let mut fields = Vec::new();
+        while bytes.has_remaining() {
+            let (tag, wire_type) = decode_key(&mut bytes).unwrap();
+            skip_field(wire_type, tag, &mut bytes, DecodeContext::default()).unwrap();
+            fields.push((tag, wire_type));
+        }
+        fields.sort_by_key(|(tag, _)| *tag);
+        fields.dedup();
+        fields
+    }
+
+    #[test]
+    fn test_methods_match_proto() {
+        let words = block_words("service FindexStore");
+        let methods = words
+            .split(|word| word == "rpc")
+            .filter(|method| !method.is_empty())
+            .map(|method| {
+                (
+                    format!("/findex.FindexStore/{}", method[0]),
+                    (method[1].clone(), method[3].clone()),
+                )
+            })
+            .collect::<HashMap<_, _>>();
+        let expected = [
+            (path::DUMP_TOKENS, "DumpTokensRequest", "Tokens"),
+            (path::FETCH, "FetchRequest", "Lines"),
+            (path::UPSERT, "UpsertRequest", "Lines"),
+            (path::INSERT, "InsertRequest", "Empty"),
+            (path::DELETE, "DeleteRequest", "Empty"),
+        ]
+        .into_iter()
+        .map(|(path, request, response)| {
+            (
+                path.to_string(),
+                (request.to_string(), response.to_string()),
+            )
+        })
+        .collect::<HashMap<_, _>>();
+        assert_eq!(methods, expected);
+    }
+
+    #[test]
+    fn test_messages_match_proto() {
+        let line = Line {
+            token: vec![1],
+            value: vec![2],
+        };
+        // Every field is set to a non-default value to be encoded.
+        let messages = [
+            ("Empty", Empty {}.encode_to_vec()),
+            (
+                "Tokens",
+                Tokens {
+                    tokens: vec![vec![1]],
+                }
+                .encode_to_vec(),
+            ),
+            ("Line", line.encode_to_vec()),
+            (
+                "Lines",
+                Lines {
+                    lines: vec![line.clone()],
+                }
+                .encode_to_vec(),
+            ),
+            (
+                "DumpTokensRequest",
+                DumpTokensRequest {
+                    table: Table::Chain as i32,
+                }
+                .encode_to_vec(),
+            ),
+            (
+                "FetchRequest",
+                FetchRequest {
+                    table: Table::Chain as i32,
+                    tokens: vec![vec![1]],
+                }
+                .encode_to_vec(),
+            ),
+            (
+                "UpsertRequest",
+                UpsertRequest {
+                    table: Table::Chain as i32,
+                    old_values: vec![line.clone()],
+                    new_values: vec![line.clone()],
+                }
+                .encode_to_vec(),
+            ),
+            (
+                "InsertRequest",
+                InsertRequest {
+                    table: Table::Chain as i32,
+                    lines: vec![line],
+                }
+                .encode_to_vec(),
+            ),
+            (
+                "DeleteRequest",
+                DeleteRequest {
+                    table: Table::Chain as i32,
+                    tokens: vec![vec![1]],
+                }
+                .encode_to_vec(),
+            ),
+        ];
+
+        assert_eq!(
+            PROTO.matches("\nmessage ").count(),
+            messages.len(),
+            "all the messages of the .proto must be checked"
+        );
+        for (message, bytes) in messages {
+            assert_eq!(encoded_fields(&bytes), proto_fields(message), "{message}");
+        }
+    }
+
+    #[test]
+    fn test_table_matches_proto() {
+        assert_eq!(
+            block_words("enum Table"),
+            [
+                "ENTRY".to_string(),
+                (Table::Entry as i32).to_string(),
+                "CHAIN".to_string(),
+                (Table::Chain as i32).to_string(),
+            ]
+        );
+    }
+}
@@ -9,16 +9,31 @@ pub mod rest;
 #[cfg(any(feature = "wasm", feature = "python", feature = "ffi",))]
 pub mod custom;
 
+#[cfg(feature = "etcd-interface")]
+pub mod etcd;
+
+#[cfg(feature = "grpc-interface")]
+pub mod grpc;
+
+#[cfg(feature = "object-store-interface")]
+pub mod object_store;
+
 #[cfg(feature = "redis-interface")]
 pub mod redis;
 
 #[cfg(feature = "sqlite-interface")]
 pub mod sqlite;
 
+#[cfg(feature = "telemetry")]
+pub mod traced;
+
 #[cfg(all(
     test,
     any(
+        feature = "etcd-interface",
         feature = "ffi",
+        feature = "grpc-interface",
+        feature = "object-store-interface",
         feature = "python",
         feature = "redis-interface",
         feature = "rest-interface",
@@ -0,0 +1,359 @@
+//! Object-store implementation of the Findex backends.
+//!
+//! Each line of a table is stored as one object named after the hexadecimal
+//! encoding of its token, under the path given in the table URL. Any store
+//! supported by the `object_store` crate can be used (S3, GCS, Azure Blob,
+//! local file system or in-memory), which allows serverless indexes stored
+//! directly in a bucket.
+//!
+//! Upserts rely on conditional puts. Stores that do not support them fall
+//! back to unconditional puts, in which case concurrent upserts on the same
+//! index are not safe.
+
+use std::{collections::HashMap, sync::Arc};
+
+use async_trait::async_trait;
+use cosmian_findex::{
+    DbInterface, EncryptedValue, Token, TokenToEncryptedValueMap, TokenWithEncryptedValueList,
+    Tokens, ENTRY_LENGTH, LINK_LENGTH,
+};
+use futures::{future::try_join_all, TryStreamExt};
+use object_store::{
+    parse_url_opts, path::Path, Error as ObjectStoreError, ObjectStore, PutMode, UpdateVersion,
+};
+use tracing::trace;
+use url::Url;
+
+use crate::db_interfaces::DbInterfaceError;
+
+/// Encodes the given token as an object name.
+fn encode_token(token: &Token) -> String {
+    token.iter().map(|byte| format!("{byte:02x}")).collect()
+}
+
+/// Decodes the token encoded in the given object name.
+fn decode_token(name: &str) -> Result<Token, DbInterfaceError> {
+    if name.len() != 2 * Token::LENGTH || !name.is_ascii() {
+        return Err(DbInterfaceError::ObjectStore(format!(
+            "`{name}` is not a valid token"
+        )));
+    }
+    let bytes = (0..name.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(&name[i..i + 2], 16))
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| {
+            DbInterfaceError::ObjectStore(format!("`{name}` is not a valid token: {e}"))
+        })?;
+    Ok(Token::try_from(bytes.as_slice())?)
+}
+
+/// Connects to the store targeted by the given URL. The path of the URL is
+/// used as prefix of the table objects.
+///
+/// The store configuration (credentials, region...) is read from the
+/// environment, e.g. `AWS_ACCESS_KEY_ID` or `AZURE_STORAGE_ACCOUNT_NAME`.
+fn connect(url: &str) -> Result<(Arc<dyn ObjectStore>, Path), DbInterfaceError> {
+    let url = Url::parse(url)
+        .map_err(|e| DbInterfaceError::ObjectStore(format!("invalid URL `{url}`: {e}")))?;
+    let options = std::env::vars().map(|(key, value)| (key.to_ascii_lowercase(), value));
+    let (store, prefix) = parse_url_opts(&url, options)?;
+    Ok((Arc::from(store), prefix))
+}
+
+/// Fetches the value stored at the given location, along with the version
+/// used to conditionally update it.
+async fn fetch_line<const VALUE_LENGTH: usize>(
+    store: &dyn ObjectStore,
+    location: &Path,
+) -> Result<Option<(EncryptedValue<VALUE_LENGTH>, UpdateVersion)>, DbInterfaceError> {
+    let result = match store.get(location).await {
+        Ok(result) => result,
+        Err(ObjectStoreError::NotFound { .. }) => return Ok(None),
+        Err(e) => return Err(e.into()),
+    };
+    let version = UpdateVersion {
+        e_tag: result.meta.e_tag.clone(),
+        version: result.meta.version.clone(),
+    };
+    let bytes = result.bytes().await?;
+    Ok(Some((EncryptedValue::try_from(bytes.as_ref())?, version)))
+}
+
+/// Upserts the given line, returning the indexed value if it does not match
+/// the given old value.
+async fn upsert_line<const VALUE_LENGTH: usize>(
+    store: &dyn ObjectStore,
+    location: &Path,
+    old_value: Option<&EncryptedValue<VALUE_LENGTH>>,
+    new_value: &EncryptedValue<VALUE_LENGTH>,
+) -> Result<Option<EncryptedValue<VALUE_LENGTH>>, DbInterfaceError> {
+    let indexed_line = fetch_line(store, location).await?;
+    let indexed_value = indexed_line.as_ref().map(|(value, _)| value);
+    if indexed_value != old_value {
+        return indexed_value.cloned().map(Some).ok_or_else(|| {
+            DbInterfaceError::Other("Index values cannot be removed while upserting.".to_string())
+        });
+    }
+
+    let mode = indexed_line.map_or(PutMode::Create, |(_, version)| PutMode::Update(version));
+    let bytes = Vec::from(new_value);
+    match store
+        .put_opts(location, bytes.clone().into(), mode.into())
+        .await
+    {
+        Ok(_) => Ok(None),
+        // The line has been modified since it was fetched.
+        Err(ObjectStoreError::AlreadyExists { .. } | ObjectStoreError::Precondition { .. }) => {
+            fetch_line(store, location)
+                .await?
+                .map(|(value, _)| Some(value))
+                .ok_or_else(|| {
+                    DbInterfaceError::Other(
+                        "Index values cannot be removed while upserting.".to_string(),
+                    )
+                })
+        }
+        Err(ObjectStoreError::NotImplemented) => {
+            trace!("conditional put not supported by the store, falling back to put");
+            store.put(location, bytes.into()).await?;
+            Ok(None)
+        }
+        Err(e) => Err(e.into()),
+    }
+}
+
+/// Implements the object-store backend for the given `$type`, with values of
+/// size `$value_length`.
+macro_rules! impl_object_store_backend {
+    ($type:ident, $value_length:ident) => {
+        impl $type {
+            /// Connects to the store targeted by the given URL, e.g.
+            /// `s3://bucket/index/entry` or `memory:///entry`.
+            pub fn connect(url: &str) -> Result<Self, DbInterfaceError> {
+                let (store, prefix) = connect(url)?;
+                Ok(Self { store, prefix })
+            }
+
+            /// Stores the table under the given prefix of the given store.
+            #[must_use]
+            pub fn new(store: Arc<dyn ObjectStore>, prefix: Path) -> Self {
+                Self { store, prefix }
+            }
+
+            fn location(&self, token: &Token) -> Path {
+                self.prefix.child(encode_token(token))
+            }
+        }
+
+        impl std::fmt::Debug for $type {
+            fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+                f.debug_struct(stringify!($type))
+                    .field("store", &self.store)
+                    .field("prefix", &self.prefix)
+                    .finish()
+            }
+        }
+
+        #[async_trait(?Send)]
+        impl DbInterface<$value_length> for $type {
+            type Error = DbInterfaceError;
+
+            async fn dump_tokens(&self) -> Result<Tokens, Self::Error> {
+                let objects = self
+                    .store
+                    .list(Some(&self.prefix))
+                    .try_collect::<Vec<_>>()
+                    .await?;
+
+                trace!("dumping {} tokens", objects.len());
+
+                objects
+                    .iter()
+                    .map(|object| {
+                        object.location.filename().map_or_else(
+                            || {
+                                Err(DbInterfaceError::ObjectStore(format!(
+                                    "invalid object location `{}`",
+                                    object.location
+                                )))
+                            },
+                            decode_token,
+                        )
+                    })
+                    .collect()
+            }
+
+            async fn fetch(
+                &self,
+                tokens: Tokens,
+            ) -> Result<TokenWithEncryptedValueList<$value_length>, Self::Error> {
+                trace!("fetch num tokens: {}", tokens.len());
+
+                let lines = try_join_all(tokens.into_iter().map(|token| async move {
+                    let line = fetch_line(&*self.store, &self.location(&token)).await?;
+                    Ok::<_, DbInterfaceError>(line.map(|(value, _)| (token, value)))
+                }))
+                .await?;
+
+                Ok(lines.into_iter().flatten().collect::<Vec<_>>().into())
+            }
+
+            async fn upsert(
+                &self,
+                old_values: TokenToEncryptedValueMap<$value_length>,
+                new_values: TokenToEncryptedValueMap<$value_length>,
+            ) -> Result<TokenToEncryptedValueMap<$value_length>, Self::Error> {
+                trace!("upsert num tokens: {}", new_values.len());
+
+                let rejected = try_join_all(new_values.iter().map(|(token, new_value)| {
+                    let old_value = old_values.get(token);
+                    async move {
+                        let indexed_value =
+                            upsert_line(&*self.store, &self.location(token), old_value, new_value)
+                                .await?;
+                        Ok::<_, DbInterfaceError>(indexed_value.map(|value| (*token, value)))
+                    }
+                }))
+                .await?
+                .into_iter()
+                .flatten()
+                .collect::<HashMap<_, _>>();
+
+                trace!("upsert rejected: {}", rejected.len());
+
+                Ok(TokenToEncryptedValueMap::from(rejected))
+            }
+
+            async fn insert(
+                &self,
+                items: TokenToEncryptedValueMap<$value_length>,
+            ) -> Result<(), Self::Error> {
+                try_join_all(items.iter().map(|(token, value)| {
+                    let location = self.location(token);
+                    async move { self.store.put(&location, Vec::from(value).into()).await }
+                }))
+                .await?;
+                Ok(())
+            }
+
+            async fn delete(&self, tokens: Tokens) -> Result<(), Self::Error> {
+                try_join_all(tokens.into_iter().map(|token| async move {
+                    match self.store.delete(&self.location(&token)).await {
+                        Ok(()) | Err(ObjectStoreError::NotFound { .. }) => Ok(()),
+                        Err(e) => Err(DbInterfaceError::from(e)),
+                    }
+                }))
+                .await?;
+                Ok(())
+            }
+        }
+    };
+}
+
+pub struct ObjectStoreEntryBackend {
+    store: Arc<dyn ObjectStore>,
+    prefix: Path,
+}
+
+impl_object_store_backend!(ObjectStoreEntryBackend, ENTRY_LENGTH);
+
+pub struct ObjectStoreChainBackend {
+    store: Arc<dyn ObjectStore>,
+    prefix: Path,
+}
+
+impl_object_store_backend!(ObjectStoreChainBackend, LINK_LENGTH);
+
+#[cfg(test)]
+mod tests {
+    use cosmian_crypto_core::{CsRng, Nonce};
+    use cosmian_findex::{MAC_LENGTH, NONCE_LENGTH};
+    use futures::executor::block_on;
+    use object_store::memory::InMemory;
+    use rand::{RngCore, SeedableRng};
+
+    use super::*;
+    use crate::{
+        db_interfaces::tests::{test_aliases, test_backend, test_dump_restore},
+        Configuration,
+    };
+
+    #[test]
+    fn test_token_encoding() -> Result<(), DbInterfaceError> {
+        let mut rng = CsRng::from_entropy();
+        let mut token = [0_u8; Token::LENGTH];
+        rng.fill_bytes(&mut token);
+        let token = Token::from(token);
+        assert_eq!(decode_token(&encode_token(&token))?, token);
+        assert!(decode_token("not a token").is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_upsert_conflict() -> Result<(), DbInterfaceError> {
+        let et = ObjectStoreEntryBackend::new(Arc::new(InMemory::new()), Path::from("entry"));
+        let value = |i: u8| EncryptedValue {
+            nonce: Nonce::from([i; NONCE_LENGTH]),
+            ciphertext: [i; ENTRY_LENGTH],
+            tag: [i; MAC_LENGTH],
+        };
+        let token = Token::from([1; Token::LENGTH]);
+
+        let rejected = block_on(et.upsert(
+            HashMap::new().into(),
+            [(token, value(1))].into_iter().collect(),
+        ))?;
+        assert!(rejected.is_empty());
+
+        // Upserting with an outdated old value is rejected.
+        let rejected = block_on(et.upsert(
+            HashMap::new().into(),
+            [(token, value(2))].into_iter().collect(),
+        ))?;
+        assert_eq!(rejected.get(&token), Some(&value(1)));
+
+        let rejected = block_on(et.upsert(
+            [(token, value(1))].into_iter().collect(),
+            [(token, value(2))].into_iter().collect(),
+        ))?;
+        assert!(rejected.is_empty());
+
+        let tokens = block_on(et.dump_tokens())?;
+        assert_eq!(tokens.len(), 1);
+        assert!(tokens.contains(&token));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_object_store_backend() {
+        let config = Configuration::ObjectStore(
+            "memory:///entry".to_string(),
+            "memory:///chain".to_string(),
+        );
+        block_on(test_backend(config));
+    }
+
+    #[test]
+    fn test_object_store_aliases() {
+        let config = Configuration::ObjectStore(
+            "memory:///entry".to_string(),
+            "memory:///chain".to_string(),
+        );
+        block_on(test_aliases(config));
+    }
+
+    #[test]
+    fn test_object_store_dump_restore() {
+        let source_config = Configuration::ObjectStore(
+            "memory:///entry".to_string(),
+            "memory:///chain".to_string(),
+        );
+        let target_config = Configuration::ObjectStore(
+            "memory:///entry".to_string(),
+            "memory:///chain".to_string(),
+        );
+        block_on(test_dump_restore(source_config, target_config));
+    }
+}
@@ -7,7 +7,7 @@ use cosmian_findex::{
     CoreError as FindexCoreError, DbInterface, EncryptedValue, Token, TokenToEncryptedValueMap,
     TokenWithEncryptedValueList, Tokens, ENTRY_LENGTH, LINK_LENGTH,
 };
-use redis::{aio::ConnectionManager, pipe, AsyncCommands, Script};
+use redis::{aio::ConnectionManager, cmd, pipe, AsyncCommands};
 use tracing::trace;
 
 use crate::db_interfaces::DbInterfaceError;
@@ -17,6 +17,10 @@ use crate::db_interfaces::DbInterfaceError;
 /// 0x00ef for the chain table
 const TABLE_PREFIX_LENGTH: usize = 2;
 
+/// Default maximum number of keys sent in a single `MGET` command or number of
+/// commands sent in a single pipeline.
+pub const DEFAULT_PIPELINE_SIZE: usize = 1_000;
+
 #[derive(Copy, Clone)]
 enum FindexTable {
     Entry = 0xee,
@@ -28,9 +32,59 @@ fn build_key(table: FindexTable, uid: &[u8]) -> Vec<u8> {
     [&[0x00, table as u8], uid].concat()
 }
 
+/// Returns the token stored under the given key if it belongs to the given
+/// table, `None` otherwise: the same database may hold keys of other
+/// applications.
+fn key_token(table: FindexTable, key: &[u8]) -> Option<Result<Token, DbInterfaceError>> {
+    if key.get(..TABLE_PREFIX_LENGTH)? == [0x00, table as u8] {
+        Some(Token::try_from(&key[TABLE_PREFIX_LENGTH..]).map_err(DbInterfaceError::Findex))
+    } else {
+        None
+    }
+}
+
+/// Fetches the values of the given tokens.
+///
+/// The keys are split into `MGET` commands of at most `pipeline_size` keys,
+/// sent in pipelines of at most `pipeline_size` commands. Missing values are
+/// returned empty.
+async fn fetch_values(
+    manager: &ConnectionManager,
+    table: FindexTable,
+    uids: &[Token],
+    pipeline_size: usize,
+) -> Result<Vec<Vec<u8>>, DbInterfaceError> {
+    let mut res = Vec::with_capacity(uids.len());
+    for pipeline_uids in uids.chunks(pipeline_size * pipeline_size) {
+        let mut pipeline = pipe();
+        for chunk in pipeline_uids.chunks(pipeline_size) {
+            let mut mget = cmd("MGET");
+            for uid in chunk {
+                mget.arg(build_key(table, uid));
+            }
+            pipeline.add_command(mget);
+        }
+        let values: Vec<Vec<Vec<u8>>> = pipeline.query_async(&mut manager.clone()).await?;
+        res.extend(values.into_iter().flatten());
+    }
+    Ok(res)
+}
+
+/// Loads the conditional upsert script on the Redis server and returns its
+/// SHA1 digest, used to call it with `EVALSHA`.
+async fn load_upsert_script(manager: &ConnectionManager) -> Result<String, DbInterfaceError> {
+    cmd("SCRIPT")
+        .arg("LOAD")
+        .arg(CONDITIONAL_UPSERT_SCRIPT)
+        .query_async(&mut manager.clone())
+        .await
+        .map_err(DbInterfaceError::from)
+}
+
 pub struct RedisEntryBackend {
     manager: ConnectionManager,
-    upsert_script: Script,
+    pipeline_size: usize,
+    upsert_script_sha: String,
 }
 
 impl std::fmt::Debug for RedisEntryBackend {
@@ -42,6 +96,12 @@ impl std::fmt::Debug for RedisEntryBackend {
 /// The conditional upsert script used to only update a table if the
 /// indexed value matches ARGV[2]. When the value does not match, the
 /// indexed value is returned.
+///
+/// Scripts are executed atomically, which makes each conditional upsert safe
+/// without WATCH/MULTI transactions (that cannot be used on the multiplexed
+/// connection of the `ConnectionManager`). They can therefore be pipelined.
+///
+/// The script is loaded once upon connection and called by its digest.
 const CONDITIONAL_UPSERT_SCRIPT: &str = r"
         local value=redis.call('GET',ARGV[1])
         if((value==false) or (not(value == false) and (ARGV[2] == value))) then
@@ -57,23 +117,29 @@ impl RedisEntryBackend {
     pub async fn connect(url: &str) -> Result<Self, DbInterfaceError> {
         let client = redis::Client::open(url)?;
         let manager = ConnectionManager::new(client).await?;
-
-        Ok(Self {
-            manager,
-            upsert_script: Script::new(CONDITIONAL_UPSERT_SCRIPT),
-        })
+        Self::connect_with_manager(manager).await
     }
 
     /// Connects to a Redis server with a `ConnectionManager`.
     pub async fn connect_with_manager(
         manager: ConnectionManager,
     ) -> Result<Self, DbInterfaceError> {
+        let upsert_script_sha = load_upsert_script(&manager).await?;
         Ok(Self {
             manager,
-            upsert_script: Script::new(CONDITIONAL_UPSERT_SCRIPT),
+            pipeline_size: DEFAULT_PIPELINE_SIZE,
+            upsert_script_sha,
         })
     }
 
+    /// Sets the maximum number of keys fetched per `MGET` command and the
+    /// maximum number of commands sent per pipeline.
+    #[must_use]
+    pub fn with_pipeline_size(mut self, pipeline_size: usize) -> Self {
+        self.pipeline_size = pipeline_size.max(1);
+        self
+    }
+
     /// Clear all indexes
     ///
     /// # Warning
@@ -84,6 +150,27 @@ impl RedisEntryBackend {
             .await?;
         Ok(())
     }
+
+    /// Conditionally upserts the given values in a single pipeline and returns
+    /// the values indexed instead of the rejected ones.
+    async fn upsert_chunk(
+        &self,
+        old_values: &TokenToEncryptedValueMap<ENTRY_LENGTH>,
+        new_values: &[(Token, EncryptedValue<ENTRY_LENGTH>)],
+    ) -> redis::RedisResult<Vec<Vec<u8>>> {
+        let mut pipeline = pipe();
+        for (uid, new_value) in new_values {
+            let old_value = old_values.get(uid).map(Vec::from).unwrap_or_default();
+            pipeline
+                .cmd("EVALSHA")
+                .arg(&self.upsert_script_sha)
+                .arg(0)
+                .arg(build_key(FindexTable::Entry, uid))
+                .arg(old_value)
+                .arg(Vec::from(new_value));
+        }
+        pipeline.query_async(&mut self.manager.clone()).await
+    }
 }
 
 #[async_trait(?Send)]
@@ -100,13 +187,7 @@ impl DbInterface<ENTRY_LENGTH> for RedisEntryBackend {
         trace!("dumping {} keywords (ET+CT)", keys.len());
 
         keys.iter()
-            .filter_map(|v| {
-                if v[..TABLE_PREFIX_LENGTH] == [0x00, FindexTable::Entry as u8] {
-                    Some(Token::try_from(&v[TABLE_PREFIX_LENGTH..]).map_err(Self::Error::Findex))
-                } else {
-                    None
-                }
-            })
+            .filter_map(|v| key_token(FindexTable::Entry, v))
             .collect()
     }
 
@@ -123,12 +204,8 @@ impl DbInterface<ENTRY_LENGTH> for RedisEntryBackend {
         // Collect into a vector to fix the order.
         let uids = tokens.into_iter().collect::<Vec<_>>();
 
-        let redis_keys = uids
-            .iter()
-            .map(|uid| build_key(FindexTable::Entry, uid))
-            .collect::<Vec<_>>();
-
-        let values: Vec<Vec<u8>> = self.manager.clone().mget(redis_keys).await?;
+        let values =
+            fetch_values(&self.manager, FindexTable::Entry, &uids, self.pipeline_size).await?;
 
         // Zip and filter empty values out.
         let res = uids
@@ -156,22 +233,22 @@ impl DbInterface<ENTRY_LENGTH> for RedisEntryBackend {
         trace!("upsert_entry_table num keywords {:?}", new_values.len());
 
         let mut rejected = HashMap::with_capacity(new_values.len());
-        for (uid, new_value) in new_values {
-            let new_value = Vec::from(&new_value);
-            let old_value = old_values.get(&uid).map(Vec::from).unwrap_or_default();
-            let key = build_key(FindexTable::Entry, &uid);
-
-            let indexed_value: Vec<_> = self
-                .upsert_script
-                .arg(key)
-                .arg(old_value)
-                .arg(new_value)
-                .invoke_async(&mut self.manager.clone())
-                .await?;
+        let new_values = new_values.into_iter().collect::<Vec<_>>();
+        for chunk in new_values.chunks(self.pipeline_size) {
+            let indexed_values = match self.upsert_chunk(&old_values, chunk).await {
+                // The server lost its scripts (restart, `SCRIPT FLUSH`...).
+                Err(e) if e.kind() == redis::ErrorKind::NoScriptError => {
+                    load_upsert_script(&self.manager).await?;
+                    self.upsert_chunk(&old_values, chunk).await?
+                }
+                res => res?,
+            };
 
-            if !indexed_value.is_empty() {
-                let encrypted_value = EncryptedValue::try_from(indexed_value.as_slice())?;
-                rejected.insert(uid, encrypted_value);
+            for ((uid, _), indexed_value) in chunk.iter().zip(indexed_values) {
+                if !indexed_value.is_empty() {
+                    let encrypted_value = EncryptedValue::try_from(indexed_value.as_slice())?;
+                    rejected.insert(*uid, encrypted_value);
+                }
             }
         }
 
@@ -207,11 +284,14 @@ impl DbInterface<ENTRY_LENGTH> for RedisEntryBackend {
     }
 }
 
-pub struct RedisChainBackend(ConnectionManager);
+pub struct RedisChainBackend {
+    manager: ConnectionManager,
+    pipeline_size: usize,
+}
 
 impl std::fmt::Debug for RedisChainBackend {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        f.debug_tuple("RedisChainBackend").finish()
+        f.debug_struct("RedisChainBackend").finish()
     }
 }
 
@@ -220,14 +300,28 @@ impl RedisChainBackend {
     pub async fn connect(url: &str) -> Result<Self, DbInterfaceError> {
         let client = redis::Client::open(url)?;
         let manager = ConnectionManager::new(client).await?;
-        Ok(Self(manager))
+        Ok(Self {
+            manager,
+            pipeline_size: DEFAULT_PIPELINE_SIZE,
+        })
     }
 
     /// Connects to a Redis server with a `ConnectionManager`.
     pub async fn connect_with_manager(
         manager: ConnectionManager,
     ) -> Result<Self, DbInterfaceError> {
-        Ok(Self(manager))
+        Ok(Self {
+            manager,
+            pipeline_size: DEFAULT_PIPELINE_SIZE,
+        })
+    }
+
+    /// Sets the maximum number of keys fetched per `MGET` command and the
+    /// maximum number of commands sent per pipeline.
+    #[must_use]
+    pub fn with_pipeline_size(mut self, pipeline_size: usize) -> Self {
+        self.pipeline_size = pipeline_size.max(1);
+        self
     }
 
     /// Clear all indexes
@@ -236,7 +330,7 @@ impl RedisChainBackend {
     /// This is definitive
     pub async fn clear_indexes(&self) -> Result<(), DbInterfaceError> {
         redis::cmd("FLUSHDB")
-            .query_async(&mut self.0.clone())
+            .query_async(&mut self.manager.clone())
             .await?;
         Ok(())
     }
@@ -247,7 +341,17 @@ impl DbInterface<LINK_LENGTH> for RedisChainBackend {
     type Error = DbInterfaceError;
 
     async fn dump_tokens(&self) -> Result<Tokens, Self::Error> {
-        panic!("No token dump is performed for the Chain Table.")
+        let keys: Vec<Vec<u8>> = self
+            .manager
+            .clone()
+            .keys(build_key(FindexTable::Chain, b"*"))
+            .await?;
+
+        trace!("dumping {} chain tokens", keys.len());
+
+        keys.iter()
+            .filter_map(|v| key_token(FindexTable::Chain, v))
+            .collect()
     }
 
     async fn fetch(
@@ -260,12 +364,8 @@ impl DbInterface<LINK_LENGTH> for RedisChainBackend {
         }
 
         let uids = tokens.into_iter().collect::<Vec<_>>();
-        let redis_keys = uids
-            .iter()
-            .map(|uid| build_key(FindexTable::Chain, uid))
-            .collect::<Vec<_>>();
-
-        let values: Vec<Vec<u8>> = self.0.clone().mget(redis_keys).await?;
+        let values =
+            fetch_values(&self.manager, FindexTable::Chain, &uids, self.pipeline_size).await?;
 
         // Zip and filter empty values out.
         let res = uids
@@ -297,7 +397,7 @@ impl DbInterface<LINK_LENGTH> for RedisChainBackend {
             pipe.set(build_key(FindexTable::Chain, k), Vec::from(v));
         }
         pipe.atomic()
-            .query_async(&mut self.0.clone())
+            .query_async(&mut self.manager.clone())
             .await
             .map_err(Self::Error::from)
     }
@@ -309,7 +409,7 @@ impl DbInterface<LINK_LENGTH> for RedisChainBackend {
         }
         pipeline
             .atomic()
-            .query_async(&mut self.0.clone())
+            .query_async(&mut self.manager.clone())
             .await
             .map_err(Self::Error::from)
     }
@@ -336,6 +436,21 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_key_token() {
+        let token = [1; Token::LENGTH];
+        let entry_key = build_key(FindexTable::Entry, &token);
+        assert_eq!(
+            key_token(FindexTable::Entry, &entry_key).unwrap().unwrap(),
+            Token::from(token)
+        );
+        assert!(key_token(FindexTable::Chain, &entry_key).is_none());
+        // Keys of other applications are skipped.
+        assert!(key_token(FindexTable::Entry, b"").is_none());
+        assert!(key_token(FindexTable::Entry, b"a").is_none());
+        assert!(key_token(FindexTable::Entry, b"session:42").is_none());
+    }
+
     #[actix_rt::test]
     #[serial]
     async fn test_upsert_conflict() -> Result<(), DbInterfaceError> {
@@ -427,6 +542,20 @@ mod tests {
             assert_eq!(prev_value, &changed_value);
         }
 
+        // Small pipelines fetch all the values.
+        let fetched = RedisEntryBackend::connect(&url)
+            .await?
+            .with_pipeline_size(10)
+            .fetch(uids.iter().map(|k| Token::from(*k)).collect())
+            .await?;
+        assert_eq!(fetched.len(), uids.len());
+
+        // The script is loaded again if the server lost it.
+        cmd("SCRIPT")
+            .arg("FLUSH")
+            .query_async(&mut et.manager.clone())
+            .await?;
+
         // The firs user upserts `new_value` to the 111 rejected UIDs from
         // `changed_value`.
         let rejected = et
@@ -456,7 +585,7 @@ mod tests {
             .await
             .unwrap();
 
-        let config = Configuration::Redis(url.clone(), url.clone());
+        let config = Configuration::Redis(url.clone(), url.clone(), DEFAULT_PIPELINE_SIZE);
         test_backend(config).await;
     }
 }
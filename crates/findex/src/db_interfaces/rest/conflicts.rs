@@ -0,0 +1,204 @@
+//! Handling of the Entry Table upsert conflicts on the REST backend.
+//!
+//! An upsert rejects the tokens whose value was modified by a concurrent
+//! writer since it was fetched. Findex then merges its modifications with the
+//! current values returned by the server and re-upserts all the rejected
+//! tokens in a single follow-up call. Under many concurrent writers, such a
+//! follow-up call sent right away is likely to conflict again.
+//!
+//! The [`ConflictTracker`] keeps track of the number of consecutive rounds
+//! each token was rejected:
+//!
+//! - a follow-up upsert is delayed by a random backoff growing with the
+//!   number of rounds, to spread the writers competing for the same tokens;
+//! - the upsert fails after the configured maximum number of rounds instead
+//!   of retrying forever.
+
+use std::{
+    collections::HashMap,
+    sync::{Mutex, MutexGuard, PoisonError},
+    time::Duration,
+};
+
+use cosmian_crypto_core::{
+    reexport::rand_core::{RngCore, SeedableRng},
+    CsRng,
+};
+use cosmian_findex::{Token, TokenToEncryptedValueMap};
+use tracing::{debug, warn};
+
+use crate::db_interfaces::DbInterfaceError;
+
+/// Default maximum number of consecutive rounds a token can be rejected by an
+/// upsert.
+pub const DEFAULT_MAX_CONFLICT_ROUNDS: usize = 10;
+
+/// Maximum backoff before the first follow-up upsert. This maximum is doubled
+/// at each round.
+const CONFLICT_BACKOFF: Duration = Duration::from_millis(20);
+
+/// Tracks the tokens rejected by the upserts of a REST backend.
+#[derive(Debug)]
+pub struct ConflictTracker {
+    max_rounds: usize,
+    /// Number of consecutive rounds each pending token was rejected.
+    rounds: Mutex<HashMap<Token, usize>>,
+}
+
+impl ConflictTracker {
+    #[must_use]
+    pub fn new(max_rounds: usize) -> Self {
+        Self {
+            max_rounds: max_rounds.max(1),
+            rounds: Mutex::default(),
+        }
+    }
+
+    #[must_use]
+    pub const fn max_rounds(&self) -> usize {
+        self.max_rounds
+    }
+
+    fn rounds(&self) -> MutexGuard<'_, HashMap<Token, usize>> {
+        // The map is always left consistent: ignore poisoning.
+        self.rounds.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    /// Returns the delay to wait before upserting the given tokens: 0 if none
+    /// of them was rejected before, a random backoff growing with the number
+    /// of rounds otherwise.
+    pub(crate) fn backoff(&self, tokens: &[Token]) -> Duration {
+        let round = {
+            let rounds = self.rounds();
+            tokens
+                .iter()
+                .filter_map(|token| rounds.get(token))
+                .max()
+                .copied()
+                .unwrap_or_default()
+        };
+        if round == 0 {
+            return Duration::ZERO;
+        }
+        let max_backoff = CONFLICT_BACKOFF.saturating_mul(1_u32 << (round - 1).min(16));
+        // Full jitter: the competing writers are spread over the whole window.
+        let jitter = f64::from(CsRng::from_entropy().next_u32()) / f64::from(u32::MAX);
+        let backoff = max_backoff.mul_f64(jitter);
+        debug!(
+            round,
+            tokens = tokens.len(),
+            backoff_ms = backoff.as_millis(),
+            "upsert conflict: delaying the follow-up upsert"
+        );
+        backoff
+    }
+
+    /// Records the outcome of the upsert of the given tokens: the rejected
+    /// tokens start a new round, the others are forgotten.
+    ///
+    /// Fails once a token was rejected by the maximum number of rounds, in
+    /// which case all the tokens of the upsert are forgotten for
+    /// the indexation to be retried later.
+    pub(crate) fn record<const VALUE_LENGTH: usize>(
+        &self,
+        tokens: &[Token],
+        rejected: &TokenToEncryptedValueMap<VALUE_LENGTH>,
+    ) -> Result<(), DbInterfaceError> {
+        let mut rounds = self.rounds();
+        let mut exceeded = 0;
+        for token in tokens {
+            if rejected.contains_key(token) {
+                let round = rounds.entry(*token).or_default();
+                *round += 1;
+                if *round >= self.max_rounds {
+                    exceeded += 1;
+                }
+            } else {
+                rounds.remove(token);
+            }
+        }
+        if !rejected.is_empty() {
+            debug!(
+                rejected = rejected.len(),
+                tokens = tokens.len(),
+                "upsert conflict: tokens modified by a concurrent writer"
+            );
+        }
+        if exceeded == 0 {
+            return Ok(());
+        }
+        warn!(
+            tokens = exceeded,
+            max_rounds = self.max_rounds,
+            "upsert conflict: giving up after the maximum number of rounds"
+        );
+        for token in tokens {
+            rounds.remove(token);
+        }
+        Err(DbInterfaceError::UpsertConflicts {
+            tokens: exceeded,
+            rounds: self.max_rounds,
+        })
+    }
+}
+
+impl Default for ConflictTracker {
+    fn default() -> Self {
+        Self::new(DEFAULT_MAX_CONFLICT_ROUNDS)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use cosmian_findex::{EncryptedValue, ENTRY_LENGTH};
+
+    use super::*;
+
+    fn values(tokens: &[u8]) -> TokenToEncryptedValueMap<ENTRY_LENGTH> {
+        tokens
+            .iter()
+            .map(|i| {
+                (
+                    Token::from([*i; Token::LENGTH]),
+                    EncryptedValue::try_from(
+                        [*i; EncryptedValue::<ENTRY_LENGTH>::LENGTH].as_slice(),
+                    )
+                    .unwrap(),
+                )
+            })
+            .collect::<HashMap<_, _>>()
+            .into()
+    }
+
+    fn tokens(tokens: &[u8]) -> Vec<Token> {
+        tokens
+            .iter()
+            .map(|i| Token::from([*i; Token::LENGTH]))
+            .collect()
+    }
+
+    #[test]
+    fn test_conflict_rounds() {
+        let tracker = ConflictTracker::new(3);
+        assert_eq!(tracker.backoff(&tokens(&[1, 2, 3])), Duration::ZERO);
+
+        // Tokens 1 and 2 are rejected, then token 2 is accepted.
+        tracker
+            .record(&tokens(&[1, 2, 3]), &values(&[1, 2]))
+            .unwrap();
+        assert!(tracker.backoff(&tokens(&[2])) <= CONFLICT_BACKOFF);
+        tracker.record(&tokens(&[1, 2]), &values(&[1])).unwrap();
+        assert!(tracker.backoff(&tokens(&[1])) <= CONFLICT_BACKOFF * 2);
+        assert_eq!(tracker.backoff(&tokens(&[2, 3])), Duration::ZERO);
+
+        // Token 1 reaches the maximum number of rounds.
+        assert!(matches!(
+            tracker.record(&tokens(&[1]), &values(&[1])),
+            Err(DbInterfaceError::UpsertConflicts {
+                tokens: 1,
+                rounds: 3
+            })
+        ));
+        assert_eq!(tracker.backoff(&tokens(&[1])), Duration::ZERO);
+    }
+}
@@ -1,8 +1,16 @@
 mod callback_prefix;
+mod conflicts;
+mod rate_limit;
+mod request_options;
+mod response_cache;
 mod stores;
 mod token;
 mod upsert_data;
 
 pub use callback_prefix::CallbackPrefix;
+pub use conflicts::{ConflictTracker, DEFAULT_MAX_CONFLICT_ROUNDS};
+pub use rate_limit::{RateLimit, RateLimiter, DEFAULT_MAX_RETRIES};
+pub use request_options::{FetchCredentials, RequestOptions};
+pub use response_cache::{ResponseCache, DEFAULT_RESPONSE_CACHE_CAPACITY};
 pub use stores::{RestChainBackend, RestEntryBackend, RestParameters};
 pub use token::AuthorizationToken;
@@ -0,0 +1,321 @@
+//! Client-side rate limiting of the requests sent to the REST server.
+//!
+//! Batch indexing jobs can send requests faster than the Findex Cloud tenant
+//! quota allows. The requests of both the Entry and the Chain tables go
+//! through the same [`RateLimiter`], which:
+//!
+//! - limits the number of requests per second using a token bucket allowing
+//!   bursts of up to one second of requests;
+//! - limits the number of requests in flight;
+//! - pauses all the requests when the server answers `429 Too Many Requests`,
+//!   for the duration given by its `Retry-After` header.
+
+use std::{
+    sync::{Mutex, MutexGuard, PoisonError},
+    time::Duration,
+};
+
+#[cfg(feature = "wasm")]
+use js_sys::{Date, Function, Promise};
+
+use super::conflicts::DEFAULT_MAX_CONFLICT_ROUNDS;
+#[cfg(feature = "wasm")]
+use wasm_bindgen::prelude::*;
+
+/// Default number of retries of a request rejected with `429 Too Many
+/// Requests`.
+pub const DEFAULT_MAX_RETRIES: usize = 3;
+
+/// Delay before the first retry of a rejected request when the server does not
+/// give any `Retry-After` header. This delay is doubled at each retry.
+const DEFAULT_RETRY_DELAY: Duration = Duration::from_millis(500);
+
+/// Interval at which a request waiting for a concurrency slot checks whether
+/// one was freed.
+const CONCURRENCY_POLL_INTERVAL: Duration = Duration::from_millis(10);
+
+/// Rate limit applied to the requests sent to the REST server.
+///
+/// It also bounds the number of rounds of the Entry Table upserts rejected
+/// because of concurrent writers (see [`ConflictTracker`]).
+///
+/// [`ConflictTracker`]: super::ConflictTracker
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct RateLimit {
+    /// Maximum number of requests per second, unlimited if `None` or not
+    /// positive.
+    pub requests_per_second: Option<f64>,
+    /// Maximum number of requests in flight, unlimited if `None`.
+    pub max_concurrency: Option<usize>,
+    /// Maximum number of retries of a request rejected with `429 Too Many
+    /// Requests`.
+    pub max_retries: usize,
+    /// Maximum number of consecutive upserts rejecting the same Entry Table
+    /// token.
+    pub max_conflict_rounds: usize,
+}
+
+impl Default for RateLimit {
+    fn default() -> Self {
+        Self {
+            requests_per_second: None,
+            max_concurrency: None,
+            max_retries: DEFAULT_MAX_RETRIES,
+            max_conflict_rounds: DEFAULT_MAX_CONFLICT_ROUNDS,
+        }
+    }
+}
+
+impl RateLimit {
+    #[must_use]
+    pub fn with_requests_per_second(mut self, requests_per_second: f64) -> Self {
+        self.requests_per_second = Some(requests_per_second);
+        self
+    }
+
+    #[must_use]
+    pub fn with_max_concurrency(mut self, max_concurrency: usize) -> Self {
+        self.max_concurrency = Some(max_concurrency.max(1));
+        self
+    }
+
+    #[must_use]
+    pub fn with_max_retries(mut self, max_retries: usize) -> Self {
+        self.max_retries = max_retries;
+        self
+    }
+
+    #[must_use]
+    pub fn with_max_conflict_rounds(mut self, max_conflict_rounds: usize) -> Self {
+        self.max_conflict_rounds = max_conflict_rounds.max(1);
+        self
+    }
+}
+
+#[derive(Debug)]
+struct State {
+    /// Number of requests which can be sent right away.
+    tokens: f64,
+    /// Time of the last refill of the token bucket, in milliseconds.
+    last_refill: f64,
+    /// Time before which no request can be sent, in milliseconds.
+    paused_until: f64,
+    /// Number of requests in flight.
+    in_flight: usize,
+}
+
+/// Rate limiter shared by the REST backends of a Findex instance.
+#[derive(Debug)]
+pub struct RateLimiter {
+    limit: RateLimit,
+    state: Mutex<State>,
+}
+
+/// Permission to send a request, returned by [`RateLimiter::acquire`]. The
+/// request is considered in flight until the permit is dropped.
+pub(crate) struct Permit<'a>(&'a RateLimiter);
+
+impl Drop for Permit<'_> {
+    fn drop(&mut self) {
+        self.0.state().in_flight -= 1;
+    }
+}
+
+impl RateLimiter {
+    #[must_use]
+    pub fn new(mut limit: RateLimit) -> Self {
+        limit.requests_per_second = limit
+            .requests_per_second
+            .filter(|requests_per_second| *requests_per_second > 0.0);
+        let tokens = limit.requests_per_second.map_or(0.0, burst_size);
+        Self {
+            limit,
+            state: Mutex::new(State {
+                tokens,
+                last_refill: now_ms(),
+                paused_until: 0.0,
+                in_flight: 0,
+            }),
+        }
+    }
+
+    #[must_use]
+    pub fn limit(&self) -> &RateLimit {
+        &self.limit
+    }
+
+    fn state(&self) -> MutexGuard<'_, State> {
+        // The state is always left consistent: ignore poisoning.
+        self.state.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    /// Waits until a request can be sent.
+    pub(crate) async fn acquire(&self) -> Permit<'_> {
+        loop {
+            let wait = {
+                let mut state = self.state();
+                let now = now_ms();
+                if let Some(requests_per_second) = self.limit.requests_per_second {
+                    state.tokens = (state.tokens
+                        + (now - state.last_refill) * requests_per_second / 1000.0)
+                        .min(burst_size(requests_per_second));
+                    state.last_refill = now;
+                }
+
+                if now < state.paused_until {
+                    Duration::from_secs_f64((state.paused_until - now) / 1000.0)
+                } else if let Some(requests_per_second) = self
+                    .limit
+                    .requests_per_second
+                    .filter(|_| state.tokens < 1.0)
+                {
+                    Duration::from_secs_f64((1.0 - state.tokens) / requests_per_second)
+                } else if self
+                    .limit
+                    .max_concurrency
+                    .is_some_and(|max_concurrency| state.in_flight >= max_concurrency)
+                {
+                    CONCURRENCY_POLL_INTERVAL
+                } else {
+                    if self.limit.requests_per_second.is_some() {
+                        state.tokens -= 1.0;
+                    }
+                    state.in_flight += 1;
+                    return Permit(self);
+                }
+            };
+            sleep(wait).await;
+        }
+    }
+
+    /// Pauses all the requests for the given duration.
+    pub(crate) fn pause(&self, duration: Duration) {
+        let mut state = self.state();
+        let paused_until = now_ms() + duration.as_secs_f64() * 1000.0;
+        state.paused_until = state.paused_until.max(paused_until);
+    }
+
+    /// Returns the delay before retrying a request rejected for the
+    /// `retry`-th time (starting at 0), given the `Retry-After` header of the
+    /// response, if any.
+    pub(crate) fn retry_delay(retry_after: Option<Duration>, retry: usize) -> Duration {
+        retry_after.unwrap_or_else(|| DEFAULT_RETRY_DELAY.saturating_mul(1_u32 << retry.min(31)))
+    }
+}
+
+impl Default for RateLimiter {
+    fn default() -> Self {
+        Self::new(RateLimit::default())
+    }
+}
+
+/// Size of the token bucket: bursts of up to one second of requests are
+/// allowed.
+fn burst_size(requests_per_second: f64) -> f64 {
+    requests_per_second.max(1.0)
+}
+
+/// Parses the value of a `Retry-After` header given in seconds. HTTP dates
+/// are not supported.
+pub(crate) fn parse_retry_after(value: &str) -> Option<Duration> {
+    value.trim().parse().ok().map(Duration::from_secs)
+}
+
+/// Returns the current time in milliseconds.
+#[cfg(not(feature = "wasm"))]
+fn now_ms() -> f64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::SystemTime::UNIX_EPOCH)
+        .map_or(0.0, |elapsed| elapsed.as_secs_f64() * 1000.0)
+}
+
+/// Returns the current time in milliseconds.
+// SystemTime::now() panics in WASM <https://github.com/rust-lang/rust/issues/48564>
+#[cfg(feature = "wasm")]
+fn now_ms() -> f64 {
+    Date::now()
+}
+
+#[cfg(not(feature = "wasm"))]
+pub(crate) async fn sleep(duration: Duration) {
+    tokio::time::sleep(duration).await;
+}
+
+#[cfg(feature = "wasm")]
+#[wasm_bindgen]
+extern "C" {
+    #[wasm_bindgen(js_name = setTimeout)]
+    fn set_timeout(handler: &Function, timeout: i32) -> JsValue;
+}
+
+#[cfg(feature = "wasm")]
+pub(crate) async fn sleep(duration: Duration) {
+    let timeout = i32::try_from(duration.as_millis()).unwrap_or(i32::MAX);
+    let promise = Promise::new(&mut |resolve, _| {
+        set_timeout(&resolve, timeout);
+    });
+    // The promise is never rejected.
+    let _ = wasm_bindgen_futures::JsFuture::from(promise).await;
+}
+
+#[cfg(all(test, not(feature = "wasm")))]
+mod tests {
+    use std::{sync::Arc, time::Instant};
+
+    use super::*;
+
+    #[actix_rt::test]
+    async fn test_requests_per_second() {
+        let limiter = RateLimiter::new(RateLimit::default().with_requests_per_second(20.0));
+        let start = Instant::now();
+        // The first 20 requests are a burst, the next 10 are rate limited.
+        for _ in 0..30 {
+            drop(limiter.acquire().await);
+        }
+        let elapsed = start.elapsed();
+        assert!(elapsed >= Duration::from_millis(450), "{elapsed:?}");
+        assert!(elapsed < Duration::from_secs(2), "{elapsed:?}");
+    }
+
+    #[actix_rt::test]
+    async fn test_max_concurrency() {
+        let limiter = Arc::new(RateLimiter::new(
+            RateLimit::default().with_max_concurrency(2),
+        ));
+        let first = limiter.acquire().await;
+        let _second = limiter.acquire().await;
+
+        let waiting_limiter = limiter.clone();
+        let waiting = actix_rt::spawn(async move {
+            let _permit = waiting_limiter.acquire().await;
+        });
+        actix_rt::time::sleep(Duration::from_millis(50)).await;
+        assert!(!waiting.is_finished());
+
+        drop(first);
+        waiting.await.unwrap();
+    }
+
+    #[actix_rt::test]
+    async fn test_pause() {
+        let limiter = RateLimiter::default();
+        limiter.pause(Duration::from_millis(200));
+        let start = Instant::now();
+        drop(limiter.acquire().await);
+        assert!(start.elapsed() >= Duration::from_millis(150));
+    }
+
+    #[test]
+    fn test_retry_after() {
+        assert_eq!(parse_retry_after(" 2 "), Some(Duration::from_secs(2)));
+        assert_eq!(parse_retry_after("Wed, 21 Oct 2015 07:28:00 GMT"), None);
+        assert_eq!(
+            RateLimiter::retry_delay(Some(Duration::from_secs(2)), 3),
+            Duration::from_secs(2)
+        );
+        assert_eq!(
+            RateLimiter::retry_delay(None, 2),
+            Duration::from_millis(2000)
+        );
+    }
+}
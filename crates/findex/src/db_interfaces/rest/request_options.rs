@@ -0,0 +1,110 @@
+//! Options of the HTTP requests sent to the REST server.
+//!
+//! In browsers, the requests are sent using `fetch`: talking to a Findex server
+//! served from another origin requires this server to allow the page origin
+//! (CORS). The credentials (cookies, TLS client certificates...) sent along
+//! with the cross-origin requests, and the additional headers expected by a
+//! gateway in front of the server, are configured here.
+
+use std::{fmt::Display, str::FromStr};
+
+use crate::db_interfaces::DbInterfaceError;
+
+/// Credentials sent by the browser along with the requests, as the
+/// `credentials` option of `fetch`. Ignored outside of browsers.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum FetchCredentials {
+    /// Never sends credentials.
+    Omit,
+    /// Only sends credentials to the page origin.
+    #[default]
+    SameOrigin,
+    /// Always sends credentials, including to other origins. The server should
+    /// then allow credentials for the page origin.
+    Include,
+}
+
+impl FromStr for FetchCredentials {
+    type Err = DbInterfaceError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "omit" => Ok(Self::Omit),
+            "same-origin" => Ok(Self::SameOrigin),
+            "include" => Ok(Self::Include),
+            _ => Err(DbInterfaceError::Other(format!(
+                "unknown fetch credentials `{s}`, expected `omit`, `same-origin` or `include`"
+            ))),
+        }
+    }
+}
+
+impl Display for FetchCredentials {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Omit => write!(f, "omit"),
+            Self::SameOrigin => write!(f, "same-origin"),
+            Self::Include => write!(f, "include"),
+        }
+    }
+}
+
+/// Options of the requests sent to the REST server.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct RequestOptions {
+    /// Headers added to each request, e.g. the API key of a gateway.
+    pub headers: Vec<(String, String)>,
+    /// Credentials sent by the browser along with the requests.
+    pub credentials: FetchCredentials,
+}
+
+impl RequestOptions {
+    #[must_use]
+    pub fn with_header(mut self, name: impl Into<String>, value: impl Into<String>) -> Self {
+        self.headers.push((name.into(), value.into()));
+        self
+    }
+
+    #[must_use]
+    pub const fn with_credentials(mut self, credentials: FetchCredentials) -> Self {
+        self.credentials = credentials;
+        self
+    }
+
+    /// Applies these options to the given request.
+    pub(crate) fn apply(&self, mut request: reqwest::RequestBuilder) -> reqwest::RequestBuilder {
+        for (name, value) in &self.headers {
+            request = request.header(name, value);
+        }
+        // `reqwest` only sends the requests using `fetch` in WASM.
+        #[cfg(target_arch = "wasm32")]
+        {
+            request = match self.credentials {
+                FetchCredentials::Omit => request.fetch_credentials_omit(),
+                FetchCredentials::SameOrigin => request.fetch_credentials_same_origin(),
+                FetchCredentials::Include => request.fetch_credentials_include(),
+            };
+        }
+        request
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_fetch_credentials() {
+        for credentials in [
+            FetchCredentials::Omit,
+            FetchCredentials::SameOrigin,
+            FetchCredentials::Include,
+        ] {
+            assert_eq!(
+                credentials.to_string().parse::<FetchCredentials>().unwrap(),
+                credentials
+            );
+        }
+        assert!("cors".parse::<FetchCredentials>().is_err());
+    }
+}
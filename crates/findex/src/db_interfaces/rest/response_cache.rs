@@ -0,0 +1,157 @@
+//! Caching of the Chain Table lines fetched from the REST server.
+//!
+//! Chain Table lines are never modified once written: repeated searches over a
+//! stable index fetch the same lines again and again. The [`ResponseCache`]
+//! keeps the last responses along with the `ETag` returned by the server. This
+//! `ETag` is sent back in the `If-None-Match` header of the next fetch of the
+//! same tokens, to which the server answers `304 Not Modified` without body
+//! if the lines did not change.
+//!
+//! Servers not returning an `ETag` are not affected: nothing is cached.
+
+use std::{
+    collections::HashMap,
+    sync::{Mutex, MutexGuard, PoisonError},
+};
+
+use cosmian_findex::{Token, Tokens};
+
+/// Default maximum number of responses cached by a REST backend.
+pub const DEFAULT_RESPONSE_CACHE_CAPACITY: usize = 1_000;
+
+/// Key of a cached response: the sorted tokens fetched.
+type ResponseKey = Vec<[u8; Token::LENGTH]>;
+
+/// Response cached along with its `ETag`.
+#[derive(Debug)]
+struct CachedResponse {
+    etag: String,
+    bytes: Vec<u8>,
+}
+
+/// Caches the responses of the fetch requests of a REST backend.
+#[derive(Debug)]
+pub struct ResponseCache {
+    capacity: usize,
+    responses: Mutex<HashMap<ResponseKey, CachedResponse>>,
+}
+
+impl ResponseCache {
+    #[must_use]
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            capacity: capacity.max(1),
+            responses: Mutex::default(),
+        }
+    }
+
+    fn responses(&self) -> MutexGuard<'_, HashMap<ResponseKey, CachedResponse>> {
+        // The map is always left consistent: ignore poisoning.
+        self.responses
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+    }
+
+    /// Returns the key of the response to the fetch of the given tokens: the
+    /// tokens are sorted since their order does not matter.
+    pub(crate) fn key(tokens: &Tokens) -> ResponseKey {
+        let mut key = tokens
+            .iter()
+            .map(|token| <[u8; Token::LENGTH]>::from(*token))
+            .collect::<Vec<_>>();
+        key.sort_unstable();
+        key
+    }
+
+    /// Returns the `ETag` of the response cached for the given key, if any.
+    pub(crate) fn etag(&self, key: &ResponseKey) -> Option<String> {
+        self.responses()
+            .get(key)
+            .map(|response| response.etag.clone())
+    }
+
+    /// Returns the response cached for the given key if its `ETag` is the
+    /// given one.
+    pub(crate) fn get(&self, key: &ResponseKey, etag: &str) -> Option<Vec<u8>> {
+        self.responses()
+            .get(key)
+            .filter(|response| response.etag == etag)
+            .map(|response| response.bytes.clone())
+    }
+
+    /// Caches the given response. An arbitrary response is evicted if the
+    /// cache is full.
+    pub(crate) fn insert(&self, key: ResponseKey, etag: String, bytes: Vec<u8>) {
+        let mut responses = self.responses();
+        if responses.len() >= self.capacity && !responses.contains_key(&key) {
+            if let Some(evicted) = responses.keys().next().cloned() {
+                responses.remove(&evicted);
+            }
+        }
+        responses.insert(key, CachedResponse { etag, bytes });
+    }
+
+    /// Returns the number of cached responses.
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.responses().len()
+    }
+
+    /// Returns `true` if no response is cached.
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.responses().is_empty()
+    }
+}
+
+impl Default for ResponseCache {
+    fn default() -> Self {
+        Self::new(DEFAULT_RESPONSE_CACHE_CAPACITY)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashSet;
+
+    use super::*;
+
+    fn tokens(ids: &[u8]) -> Tokens {
+        Tokens::from(
+            ids.iter()
+                .map(|id| Token::from([*id; Token::LENGTH]))
+                .collect::<HashSet<_>>(),
+        )
+    }
+
+    #[test]
+    fn test_response_cache() {
+        let cache = ResponseCache::new(2);
+        assert!(cache.is_empty());
+
+        // The key does not depend on the order of the tokens.
+        let key = ResponseCache::key(&tokens(&[1, 2, 3]));
+        assert_eq!(key, ResponseCache::key(&tokens(&[3, 1, 2])));
+
+        assert_eq!(cache.etag(&key), None);
+        cache.insert(key.clone(), "\"v1\"".to_string(), vec![1]);
+        assert_eq!(cache.etag(&key).as_deref(), Some("\"v1\""));
+        assert_eq!(cache.get(&key, "\"v1\""), Some(vec![1]));
+        assert_eq!(cache.get(&key, "\"v0\""), None);
+
+        // Responses are replaced.
+        cache.insert(key.clone(), "\"v2\"".to_string(), vec![2]);
+        assert_eq!(cache.get(&key, "\"v2\""), Some(vec![2]));
+        assert_eq!(cache.len(), 1);
+
+        // The capacity is never exceeded.
+        for i in 0..10 {
+            cache.insert(
+                ResponseCache::key(&tokens(&[i])),
+                "\"v1\"".to_string(),
+                vec![i],
+            );
+            assert!(cache.len() <= 2);
+        }
+    }
+}
@@ -1,6 +1,6 @@
 #[cfg(not(feature = "wasm"))]
 use std::time::SystemTime;
-use std::{ops::Deref, str::FromStr};
+use std::{ops::Deref, str::FromStr, sync::Arc};
 
 use async_trait::async_trait;
 use cosmian_crypto_core::bytes_ser_de::Serializable;
@@ -8,9 +8,16 @@ use cosmian_findex::{kmac, DbInterface, ENTRY_LENGTH, LINK_LENGTH};
 pub use cosmian_findex::{TokenToEncryptedValueMap, TokenWithEncryptedValueList, Tokens};
 #[cfg(feature = "wasm")]
 use js_sys::Date;
-use reqwest::Client;
+use reqwest::{
+    header::{ETAG, IF_NONE_MATCH, RETRY_AFTER},
+    Client, Response, StatusCode,
+};
 
-use super::{upsert_data::UpsertData, AuthorizationToken, CallbackPrefix};
+use super::{
+    rate_limit::{parse_retry_after, sleep, RateLimiter},
+    upsert_data::UpsertData,
+    AuthorizationToken, CallbackPrefix, ConflictTracker, RequestOptions, ResponseCache,
+};
 use crate::{
     db_interfaces::DbInterfaceError,
     ser_de::ffi_ser_de::{
@@ -26,6 +33,15 @@ pub const REQUEST_SIGNATURE_TIMEOUT_AS_SECS: u64 = 60;
 /// Callback signature length.
 pub const SIGNATURE_LENGTH: usize = 32;
 
+/// Reads the body of the given response.
+async fn read_bytes(response: Response) -> Result<Vec<u8>, DbInterfaceError> {
+    response.bytes().await.map(|r| r.to_vec()).map_err(|err| {
+        DbInterfaceError::Other(format!(
+            "Impossible to read the returned bytes from FindexREST server: {err}"
+        ))
+    })
+}
+
 macro_rules! impl_rest_backend {
     ($type:ident, $value_length:ident, $name:literal, $table_bit:expr) => {
         impl Deref for $type {
@@ -48,36 +64,59 @@ macro_rules! impl_rest_backend {
                 callback: CallbackPrefix,
                 bytes: &[u8],
             ) -> Result<Vec<u8>, DbInterfaceError> {
+                read_bytes(self.send(callback, bytes, None).await?).await
+            }
+
+            /// Posts the fetch request of the given tokens, revalidating the
+            /// response cached for these tokens if any.
+            async fn post_fetch(
+                &self,
+                callback: CallbackPrefix,
+                tokens: &Tokens,
+                bytes: &[u8],
+            ) -> Result<Vec<u8>, DbInterfaceError> {
+                let Some(cache) = &self.response_cache else {
+                    return self.post(callback, bytes).await;
+                };
+                let key = ResponseCache::key(tokens);
+                let cached_etag = cache.etag(&key);
+                let response = self.send(callback, bytes, cached_etag.as_deref()).await?;
+                if response.status() == StatusCode::NOT_MODIFIED {
+                    return match cached_etag.and_then(|etag| cache.get(&key, &etag)) {
+                        Some(res) => Ok(res),
+                        // The response was evicted in the meantime.
+                        None => self.post(callback, bytes).await,
+                    };
+                }
+                let etag = response
+                    .headers()
+                    .get(ETAG)
+                    .and_then(|value| value.to_str().ok())
+                    .map(ToOwned::to_owned);
+                let res = read_bytes(response).await?;
+                if let Some(etag) = etag {
+                    cache.insert(key, etag, res.clone());
+                }
+                Ok(res)
+            }
+
+            /// Sends the given `body` signed with the given `callback` key, and
+            /// returns the successful response.
+            ///
+            /// The given `ETag` is sent in the `If-None-Match` header, in
+            /// which case a `304 Not Modified` response is returned as well.
+            async fn send(
+                &self,
+                callback: CallbackPrefix,
+                bytes: &[u8],
+                etag: Option<&str>,
+            ) -> Result<Response, DbInterfaceError> {
                 let key = {
                     self.token
                         .get_key(&self.token.index_id, callback)
                         .ok_or_else(|| DbInterfaceError::MissingPermission(callback as i32))?
                 };
 
-                // SystemTime::now() panics in WASM <https://github.com/rust-lang/rust/issues/48564>
-                #[cfg(feature = "wasm")]
-                let current_timestamp = (Date::now() / 1000.0) as u64; // Date::now() returns milliseconds
-
-                #[cfg(not(feature = "wasm"))]
-                let current_timestamp = SystemTime::now()
-                    .duration_since(SystemTime::UNIX_EPOCH)
-                    .map_err(|_| {
-                        DbInterfaceError::Other("SystemTime is before UNIX_EPOCH".to_string())
-                    })?
-                    .as_secs();
-
-                let expiration_timestamp_bytes =
-                    (current_timestamp + REQUEST_SIGNATURE_TIMEOUT_AS_SECS).to_be_bytes();
-
-                let signature = kmac!(SIGNATURE_LENGTH, &key, &expiration_timestamp_bytes, bytes);
-
-                let mut body = Vec::with_capacity(
-                    signature.len() + expiration_timestamp_bytes.len() + bytes.len(),
-                );
-                body.extend(&signature);
-                body.extend(&expiration_timestamp_bytes);
-                body.extend(bytes);
-
                 let url = {
                     format!(
                         "{}/indexes/{}/{}",
@@ -87,33 +126,88 @@ macro_rules! impl_rest_backend {
                     )
                 };
 
-                let response = Client::new()
-                    .post(url)
-                    .body(body)
-                    .send()
-                    .await
-                    .map_err(|err| {
-                        DbInterfaceError::Other(format!(
-                            "Unable to send the request to FindexREST: {err}"
-                        ))
-                    })?;
-
-                if !response.status().is_success() {
-                    return Err(DbInterfaceError::Other(format!(
-                        "request to FindexREST server failed, status code is {}, response is {}",
-                        response.status(),
-                        response
-                            .text()
+                let mut retry = 0;
+                loop {
+                    let permit = self.rate_limiter.acquire().await;
+
+                    // The request is signed upon each attempt so that a retried
+                    // request does not expire.
+                    // SystemTime::now() panics in WASM <https://github.com/rust-lang/rust/issues/48564>
+                    #[cfg(feature = "wasm")]
+                    let current_timestamp = (Date::now() / 1000.0) as u64; // Date::now() returns milliseconds
+
+                    #[cfg(not(feature = "wasm"))]
+                    let current_timestamp = SystemTime::now()
+                        .duration_since(SystemTime::UNIX_EPOCH)
+                        .map_err(|_| {
+                            DbInterfaceError::Other("SystemTime is before UNIX_EPOCH".to_string())
+                        })?
+                        .as_secs();
+
+                    let expiration_timestamp_bytes =
+                        (current_timestamp + REQUEST_SIGNATURE_TIMEOUT_AS_SECS).to_be_bytes();
+
+                    let signature =
+                        kmac!(SIGNATURE_LENGTH, &key, &expiration_timestamp_bytes, bytes);
+
+                    let mut body = Vec::with_capacity(
+                        signature.len() + expiration_timestamp_bytes.len() + bytes.len(),
+                    );
+                    body.extend(&signature);
+                    body.extend(&expiration_timestamp_bytes);
+                    body.extend(bytes);
+
+                    let mut request = Client::new().post(&url).body(body);
+                    if let Some(etag) = etag {
+                        request = request.header(IF_NONE_MATCH, etag);
+                    }
+                    let response =
+                        self.request_options
+                            .apply(request)
+                            .send()
                             .await
-                            .unwrap_or_else(|_| "cannot parse response".to_owned())
-                    )));
+                            .map_err(|err| {
+                                DbInterfaceError::Other(format!(
+                                    "Unable to send the request to FindexREST: {err}"
+                                ))
+                            })?;
+
+                    if response.status() == StatusCode::TOO_MANY_REQUESTS {
+                        drop(permit);
+                        let retry_after = response
+                            .headers()
+                            .get(RETRY_AFTER)
+                            .and_then(|value| value.to_str().ok())
+                            .and_then(parse_retry_after);
+                        if retry >= self.rate_limiter.limit().max_retries {
+                            return Err(DbInterfaceError::RateLimited(retry_after));
+                        }
+                        // All the requests of this instance are paused since
+                        // they would be rejected as well.
+                        self.rate_limiter
+                            .pause(RateLimiter::retry_delay(retry_after, retry));
+                        retry += 1;
+                        continue;
+                    }
+
+                    if etag.is_some() && response.status() == StatusCode::NOT_MODIFIED {
+                        return Ok(response);
+                    }
+
+                    if !response.status().is_success() {
+                        return Err(DbInterfaceError::Other(format!(
+                            "request to FindexREST server failed, status code is {}, response is \
+                             {}",
+                            response.status(),
+                            response
+                                .text()
+                                .await
+                                .unwrap_or_else(|_| "cannot parse response".to_owned())
+                        )));
+                    }
+
+                    return Ok(response);
                 }
-
-                response.bytes().await.map(|r| r.to_vec()).map_err(|err| {
-                    DbInterfaceError::Other(format!(
-                        "Impossible to read the returned bytes from FindexREST server: {err}"
-                    ))
-                })
             }
         }
 
@@ -135,10 +229,11 @@ macro_rules! impl_rest_backend {
                 $crate::db_interfaces::rest::stores::TokenWithEncryptedValueList<$value_length>,
                 Self::Error,
             > {
-                let bytes = serialize_token_set(&tokens.into())?;
+                let bytes = serialize_token_set(&tokens)?;
                 let res = self
-                    .post(
+                    .post_fetch(
                         (CallbackPrefix::FetchEntry as u8 + $table_bit).try_into()?,
+                        &tokens,
                         &bytes,
                     )
                     .await?;
@@ -159,14 +254,26 @@ macro_rules! impl_rest_backend {
                 $crate::db_interfaces::rest::stores::TokenToEncryptedValueMap<$value_length>,
                 Self::Error,
             > {
+                // Spread the follow-up upserts of the writers competing for the
+                // same tokens.
+                let tokens = new_values.keys().copied().collect::<Vec<_>>();
+                let backoff = self.conflicts.backoff(&tokens);
+                if !backoff.is_zero() {
+                    sleep(backoff).await;
+                }
+
                 let modifications = UpsertData::<$value_length>::new(old_values, new_values);
                 let bytes = modifications.serialize()?;
 
                 let res = self.post(CallbackPrefix::Upsert, &bytes).await?;
 
-                deserialize_edx_lines(&res)
+                let rejected: $crate::db_interfaces::rest::stores::TokenToEncryptedValueMap<
+                    $value_length,
+                > = deserialize_edx_lines(&res)
                     .map(|v| v.into_iter().collect())
-                    .map_err(Self::Error::from)
+                    .map_err(Self::Error::from)?;
+                self.conflicts.record(&tokens, &rejected)?;
+                Ok(rejected)
             }
 
             async fn insert(
@@ -198,21 +305,65 @@ macro_rules! impl_rest_backend {
 }
 
 /// Parameters needed to instantiate a REST backend.
-#[derive(Debug, PartialEq, Eq)]
+#[derive(Debug)]
 pub struct RestParameters {
     token: AuthorizationToken,
     url: String,
+    rate_limiter: Arc<RateLimiter>,
+    conflicts: ConflictTracker,
+    request_options: RequestOptions,
+    // Boxed not to grow the REST Findex instances, the cache being large.
+    response_cache: Option<Box<ResponseCache>>,
 }
 
 impl RestParameters {
     #[must_use]
     pub fn new(token: AuthorizationToken, url: String) -> Self {
-        Self { token, url }
+        Self {
+            token,
+            url,
+            rate_limiter: Arc::default(),
+            conflicts: ConflictTracker::default(),
+            request_options: RequestOptions::default(),
+            response_cache: None,
+        }
     }
 
     pub fn from(token: &str, url: String) -> Result<Self, DbInterfaceError> {
         let token = AuthorizationToken::from_str(token)?;
-        Ok(Self { token, url })
+        Ok(Self::new(token, url))
+    }
+
+    /// Sets the rate limiter of the requests. The same rate limiter should be
+    /// shared by the Entry and the Chain table backends.
+    #[must_use]
+    pub fn with_rate_limiter(mut self, rate_limiter: Arc<RateLimiter>) -> Self {
+        self.rate_limiter = rate_limiter;
+        self
+    }
+
+    /// Sets the maximum number of consecutive upserts rejecting the same Entry
+    /// Table token before failing.
+    #[must_use]
+    pub fn with_max_conflict_rounds(mut self, max_conflict_rounds: usize) -> Self {
+        self.conflicts = ConflictTracker::new(max_conflict_rounds);
+        self
+    }
+
+    /// Sets the headers and the credentials of the requests.
+    #[must_use]
+    pub fn with_request_options(mut self, request_options: RequestOptions) -> Self {
+        self.request_options = request_options;
+        self
+    }
+
+    /// Caches up to `capacity` fetch responses, revalidated using the `ETag`
+    /// returned by the server, see [`ResponseCache`]. Only worth it for the
+    /// Chain Table, whose lines are never modified.
+    #[must_use]
+    pub fn with_response_cache(mut self, capacity: usize) -> Self {
+        self.response_cache = Some(Box::new(ResponseCache::new(capacity)));
+        self
     }
 }
 
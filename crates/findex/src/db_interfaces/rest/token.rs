@@ -68,6 +68,11 @@ impl FromStr for AuthorizationToken {
     type Err = DbInterfaceError;
 
     fn from_str(token: &str) -> Result<Self, Self::Err> {
+        if token.len() < INDEX_ID_LENGTH || !token.is_char_boundary(INDEX_ID_LENGTH) {
+            return Err(Self::Err::MalformedToken(format!(
+                "the token should start with an index ID of {INDEX_ID_LENGTH} characters"
+            )));
+        }
         let (index_id, tail) = token.split_at(INDEX_ID_LENGTH);
         let bytes = STANDARD.decode(tail).map_err(|e| {
             Self::Err::MalformedToken(format!("the keys section is not base64 encoded ({e})"))
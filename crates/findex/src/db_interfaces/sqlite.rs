@@ -1,38 +1,94 @@
 //! `SQLite` implementation of the Findex backends.
+//!
+//! With the `sqlcipher` feature, the database files can be encrypted at rest
+//! using [SQLCipher](https://www.zetetic.net/sqlcipher/): the key given upon
+//! instantiation is used to encrypt the whole database, in addition to the
+//! encryption of the indexes performed by Findex.
 
-use std::{collections::HashMap, ops::Deref, sync::RwLock};
+use std::{collections::HashMap, ops::Deref, sync::Mutex};
 
 use async_trait::async_trait;
 use cosmian_findex::{DbInterface, EncryptedValue, Token, ENTRY_LENGTH, LINK_LENGTH};
-use rusqlite::{params_from_iter, Connection, OptionalExtension};
+use rusqlite::{params_from_iter, Connection, OpenFlags, OptionalExtension};
 
 use crate::db_interfaces::DbInterfaceError;
 
+/// Opens the database at the given path, using the given key to decrypt it if
+/// any.
+///
+/// In read-only mode, the database is opened in immutable mode: `SQLite`
+/// neither locks it nor checks whether it was modified by another connection.
+fn open_connection(
+    db_path: &str,
+    key: Option<&str>,
+    read_only: bool,
+) -> Result<Connection, DbInterfaceError> {
+    let connection = if read_only {
+        // The path is given as an URI, whose special characters are escaped.
+        let db_uri = format!(
+            "file:{}?immutable=1",
+            db_path
+                .replace('%', "%25")
+                .replace('?', "%3f")
+                .replace('#', "%23")
+        );
+        Connection::open_with_flags(
+            db_uri,
+            OpenFlags::SQLITE_OPEN_READ_ONLY
+                | OpenFlags::SQLITE_OPEN_URI
+                | OpenFlags::SQLITE_OPEN_NO_MUTEX,
+        )?
+    } else {
+        Connection::open(db_path)?
+    };
+    #[cfg(feature = "sqlcipher")]
+    if let Some(key) = key {
+        connection.pragma_update(None, "key", key)?;
+    }
+    #[cfg(not(feature = "sqlcipher"))]
+    if key.is_some() {
+        return Err(DbInterfaceError::Other(
+            "encrypted SQLite databases require the `sqlcipher` feature".to_string(),
+        ));
+    }
+    Ok(connection)
+}
+
 /// Implements the `SQLite` backend for the given `$type`, with values of size
 /// `$value_length`.
 macro_rules! impl_sqlite_backend {
     ($type:ident, $value_length:ident, $table_name:literal) => {
         impl $type {
-            pub fn new(db_path: &str) -> Result<Self, DbInterfaceError> {
-                let connection = Connection::open(db_path)?;
-                connection
-                    .execute(
-                        &format!(
-                            "CREATE TABLE IF NOT EXISTS {} (
+            /// Opens the database at the given path. If a key is given, the
+            /// database is encrypted using SQLCipher.
+            pub fn new(db_path: &str, key: Option<&str>) -> Result<Self, DbInterfaceError> {
+                let connection = open_connection(db_path, key, false)?;
+                connection.execute(
+                    &format!(
+                        "CREATE TABLE IF NOT EXISTS {} (
                              uid               BLOB PRIMARY KEY,
                              value             BLOB NOT NULL
                          )",
-                            $table_name
-                        ),
-                        [],
-                    )
-                    .unwrap();
-                Ok($type(RwLock::new(connection)))
+                        $table_name
+                    ),
+                    [],
+                )?;
+                Ok($type(Mutex::new(connection)))
+            }
+
+            /// Opens the existing database at the given path in read-only
+            /// mode: the database must not be modified while in use.
+            pub fn open_read_only(
+                db_path: &str,
+                key: Option<&str>,
+            ) -> Result<Self, DbInterfaceError> {
+                let connection = open_connection(db_path, key, true)?;
+                Ok($type(Mutex::new(connection)))
             }
         }
 
         impl Deref for $type {
-            type Target = RwLock<Connection>;
+            type Target = Mutex<Connection>;
 
             fn deref(&self) -> &Self::Target {
                 &self.0
@@ -44,7 +100,7 @@ macro_rules! impl_sqlite_backend {
             type Error = DbInterfaceError;
 
             async fn dump_tokens(&self) -> Result<cosmian_findex::Tokens, Self::Error> {
-                let cnx = self.read().expect("poisoned mutex");
+                let cnx = self.lock().expect("poisoned mutex");
 
                 let mut stmt = cnx.prepare(&format!("SELECT uid FROM {}", $table_name))?;
 
@@ -61,7 +117,7 @@ macro_rules! impl_sqlite_backend {
                 tokens: cosmian_findex::Tokens,
             ) -> Result<cosmian_findex::TokenWithEncryptedValueList<$value_length>, Self::Error>
             {
-                let cnx = self.read().expect("poisoned mutex");
+                let cnx = self.lock().expect("poisoned mutex");
                 let mut stmt = cnx.prepare(&format!(
                     "SELECT uid, value FROM {} WHERE uid IN ({})",
                     $table_name,
@@ -107,7 +163,7 @@ macro_rules! impl_sqlite_backend {
                     .into_iter()
                     .map(|(token, new_value)| (token, (old_values.get(&token), new_value)));
 
-                let mut cnx = self.write().expect("poisoned mutex");
+                let mut cnx = self.lock().expect("poisoned mutex");
                 let tx = cnx.transaction()?;
                 for (token, (old_value, new_value)) in modifications {
                     let old_value = old_value.map(|v| <Vec<u8>>::from(v));
@@ -145,7 +201,7 @@ macro_rules! impl_sqlite_backend {
                 &self,
                 items: cosmian_findex::TokenToEncryptedValueMap<$value_length>,
             ) -> Result<(), Self::Error> {
-                let mut cnx = self.write().expect("poisoned mutex");
+                let mut cnx = self.lock().expect("poisoned mutex");
                 let tx = cnx.transaction()?;
                 for (token, value) in items {
                     tx.execute(
@@ -159,7 +215,7 @@ macro_rules! impl_sqlite_backend {
             }
 
             async fn delete(&self, tokens: cosmian_findex::Tokens) -> Result<(), Self::Error> {
-                let cnx = self.read().expect("poisoned mutex");
+                let cnx = self.lock().expect("poisoned mutex");
                 let mut stmt = cnx.prepare(&format!(
                     "DELETE FROM {} WHERE uid IN ({})",
                     $table_name,
@@ -176,12 +232,12 @@ macro_rules! impl_sqlite_backend {
 }
 
 #[derive(Debug)]
-pub struct SqlEntryBackend(RwLock<Connection>);
+pub struct SqlEntryBackend(Mutex<Connection>);
 
 impl_sqlite_backend!(SqlEntryBackend, ENTRY_LENGTH, "entry_table");
 
 #[derive(Debug)]
-pub struct SqlChainBackend(RwLock<Connection>);
+pub struct SqlChainBackend(Mutex<Connection>);
 
 impl_sqlite_backend!(SqlChainBackend, LINK_LENGTH, "chain_table");
 
@@ -191,9 +247,13 @@ mod tests {
 
     use futures::executor::block_on;
 
+    #[cfg(feature = "sqlcipher")]
+    use crate::InstantiatedFindex;
     use crate::{
         db_interfaces::tests::{
-            test_backend, test_generate_non_regression_db, test_non_regression,
+            test_add_in_chunks, test_aliases, test_backend, test_chain_padding, test_dump_restore,
+            test_generate_non_regression_db, test_keyword_normalization, test_non_regression,
+            test_partial_compaction, test_payloads, test_read_only, test_soft_delete, test_sync,
         },
         Configuration,
     };
@@ -207,10 +267,187 @@ mod tests {
         let config = Configuration::Sqlite(
             db_path.to_str().unwrap().to_string(),
             db_path.to_str().unwrap().to_string(),
+            None,
         );
         block_on(test_backend(config));
     }
 
+    #[cfg(feature = "sqlcipher")]
+    #[test]
+    fn test_sqlcipher_backend() {
+        let db_path = Path::new("../../target/sqlcipher.db");
+        if db_path.exists() {
+            std::fs::remove_file(db_path).unwrap();
+        }
+        let config = |key: &str| {
+            Configuration::Sqlite(
+                db_path.to_str().unwrap().to_string(),
+                db_path.to_str().unwrap().to_string(),
+                Some(key.to_string()),
+            )
+        };
+        block_on(test_backend(config("sqlcipher key")));
+
+        // The database cannot be opened without the correct key.
+        assert!(block_on(InstantiatedFindex::new(config("wrong key"))).is_err());
+    }
+
+    #[test]
+    fn test_sqlite_aliases() {
+        let db_path = Path::new("../../target/sqlite_aliases.db");
+        if db_path.exists() {
+            std::fs::remove_file(db_path).unwrap();
+        }
+        let config = Configuration::Sqlite(
+            db_path.to_str().unwrap().to_string(),
+            db_path.to_str().unwrap().to_string(),
+            None,
+        );
+        block_on(test_aliases(config));
+    }
+
+    #[test]
+    fn test_sqlite_soft_delete() {
+        let db_path = Path::new("../../target/sqlite_soft_delete.db");
+        if db_path.exists() {
+            std::fs::remove_file(db_path).unwrap();
+        }
+        let config = Configuration::Sqlite(
+            db_path.to_str().unwrap().to_string(),
+            db_path.to_str().unwrap().to_string(),
+            None,
+        );
+        block_on(test_soft_delete(config));
+    }
+
+    #[test]
+    fn test_sqlite_partial_compaction() {
+        let db_path = Path::new("../../target/sqlite_partial_compaction.db");
+        if db_path.exists() {
+            std::fs::remove_file(db_path).unwrap();
+        }
+        let config = Configuration::Sqlite(
+            db_path.to_str().unwrap().to_string(),
+            db_path.to_str().unwrap().to_string(),
+            None,
+        );
+        block_on(test_partial_compaction(config));
+    }
+
+    #[test]
+    fn test_sqlite_payloads() {
+        let db_path = Path::new("../../target/sqlite_payloads.db");
+        if db_path.exists() {
+            std::fs::remove_file(db_path).unwrap();
+        }
+        let config = Configuration::Sqlite(
+            db_path.to_str().unwrap().to_string(),
+            db_path.to_str().unwrap().to_string(),
+            None,
+        );
+        block_on(test_payloads(config));
+    }
+
+    #[test]
+    fn test_sqlite_add_in_chunks() {
+        let db_path = Path::new("../../target/sqlite_add_in_chunks.db");
+        if db_path.exists() {
+            std::fs::remove_file(db_path).unwrap();
+        }
+        let config = Configuration::Sqlite(
+            db_path.to_str().unwrap().to_string(),
+            db_path.to_str().unwrap().to_string(),
+            None,
+        );
+        block_on(test_add_in_chunks(config));
+    }
+
+    #[test]
+    fn test_sqlite_read_only() {
+        let db_path = Path::new("../../target/sqlite_read_only.db");
+        if db_path.exists() {
+            std::fs::remove_file(db_path).unwrap();
+        }
+        let config = Configuration::Sqlite(
+            db_path.to_str().unwrap().to_string(),
+            db_path.to_str().unwrap().to_string(),
+            None,
+        );
+        block_on(test_read_only(config));
+    }
+
+    #[test]
+    fn test_sqlite_keyword_normalization() {
+        let db_path = Path::new("../../target/sqlite_keyword_normalization.db");
+        if db_path.exists() {
+            std::fs::remove_file(db_path).unwrap();
+        }
+        let config = Configuration::Sqlite(
+            db_path.to_str().unwrap().to_string(),
+            db_path.to_str().unwrap().to_string(),
+            None,
+        );
+        block_on(test_keyword_normalization(config));
+    }
+
+    #[test]
+    fn test_sqlite_chain_padding() {
+        let db_path = Path::new("../../target/sqlite_chain_padding.db");
+        if db_path.exists() {
+            std::fs::remove_file(db_path).unwrap();
+        }
+        let config = Configuration::Sqlite(
+            db_path.to_str().unwrap().to_string(),
+            db_path.to_str().unwrap().to_string(),
+            None,
+        );
+        block_on(test_chain_padding(config));
+    }
+
+    #[test]
+    fn test_sqlite_dump_restore() {
+        let source_path = Path::new("../../target/sqlite_dump_source.db");
+        let target_path = Path::new("../../target/sqlite_dump_target.db");
+        for db_path in [source_path, target_path] {
+            if db_path.exists() {
+                std::fs::remove_file(db_path).unwrap();
+            }
+        }
+        let source_config = Configuration::Sqlite(
+            source_path.to_str().unwrap().to_string(),
+            source_path.to_str().unwrap().to_string(),
+            None,
+        );
+        let target_config = Configuration::Sqlite(
+            target_path.to_str().unwrap().to_string(),
+            target_path.to_str().unwrap().to_string(),
+            None,
+        );
+        block_on(test_dump_restore(source_config, target_config));
+    }
+
+    #[test]
+    fn test_sqlite_sync() {
+        let source_path = Path::new("../../target/sqlite_sync_source.db");
+        let target_path = Path::new("../../target/sqlite_sync_target.db");
+        for db_path in [source_path, target_path] {
+            if db_path.exists() {
+                std::fs::remove_file(db_path).unwrap();
+            }
+        }
+        let source_config = Configuration::Sqlite(
+            source_path.to_str().unwrap().to_string(),
+            source_path.to_str().unwrap().to_string(),
+            None,
+        );
+        let target_config = Configuration::Sqlite(
+            target_path.to_str().unwrap().to_string(),
+            target_path.to_str().unwrap().to_string(),
+            None,
+        );
+        block_on(test_sync(source_config, target_config));
+    }
+
     #[test]
     fn test_sqlite_non_regression() {
         // Test creating a new non-regression database.
@@ -222,6 +459,7 @@ mod tests {
         let config = Configuration::Sqlite(
             db_path.to_str().unwrap().to_string(),
             db_path.to_str().unwrap().to_string(),
+            None,
         );
         block_on(test_generate_non_regression_db(config.clone()));
         block_on(test_non_regression(config));
@@ -231,6 +469,7 @@ mod tests {
         let config = Configuration::Sqlite(
             db_path.to_str().unwrap().to_string(),
             db_path.to_str().unwrap().to_string(),
+            None,
         );
         block_on(test_non_regression(config));
     }
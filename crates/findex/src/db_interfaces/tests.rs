@@ -3,12 +3,16 @@
 // Used to avoid inserting `#[cfg(...)]` everywhere.
 #![allow(dead_code)]
 
-use std::collections::{HashMap, HashSet};
+use std::{
+    cell::Cell,
+    collections::{HashMap, HashSet},
+};
 
 use base64::{engine::general_purpose, Engine};
 use cosmian_crypto_core::{CsRng, FixedSizeCBytes, RandomFixedSizeCBytes};
 use cosmian_findex::{
-    Data, IndexedValue, IndexedValueToKeywordsMap, Keyword, Keywords, Label, UserKey,
+    Data, Error as FindexError, IndexedValue, IndexedValueToKeywordsMap, Keyword, Keywords, Label,
+    UserKey,
 };
 use faker_rand::{
     en_us::addresses::PostalCode,
@@ -24,7 +28,9 @@ use serde::{Deserialize, Serialize};
 use tracing::trace;
 
 use super::DbInterfaceError;
-use crate::{Configuration, InstantiatedFindex};
+use crate::{
+    ChainPadding, Configuration, InstantiatedFindex, KeywordNormalizer, PayloadKey, SyncStatistics,
+};
 
 #[allow(non_snake_case)]
 #[derive(Debug, Deserialize, Serialize)]
@@ -273,3 +279,649 @@ pub async fn test_generate_non_regression_db(config: Configuration) {
     insert_users(&findex, &key, &label).await;
     find_users(&findex, &key, &label).await;
 }
+
+/// This test:
+/// 1. Indexes each user in the source index.
+/// 2. Dumps the source index and restores it in the (empty) target index.
+/// 3. Asserts each user can be retrieved from the target index, and that
+///    both indexes hold the same tokens.
+pub async fn test_dump_restore(source_config: Configuration, target_config: Configuration) {
+    let is_non_regression = false;
+
+    let source = InstantiatedFindex::new(source_config).await.unwrap();
+    let key = get_key(is_non_regression);
+    let label = get_label(is_non_regression);
+
+    insert_users(&source, &key, &label).await;
+
+    let dump = source.dump().await.unwrap();
+
+    let target = InstantiatedFindex::new(target_config).await.unwrap();
+    target.restore(&dump).await.unwrap();
+
+    find_users(&target, &key, &label).await;
+
+    let source_tokens = source.dump_tokens().await.unwrap();
+    assert!(!source_tokens.is_empty());
+    assert_eq!(
+        source_tokens.iter().cloned().collect::<HashSet<_>>(),
+        target
+            .dump_tokens()
+            .await
+            .unwrap()
+            .iter()
+            .cloned()
+            .collect::<HashSet<_>>()
+    );
+}
+
+/// This test:
+/// 1. Indexes each user using a writable instance.
+/// 2. Asserts each user can be retrieved using a read-only instance, and that
+///    this instance rejects any modification of the index.
+pub async fn test_read_only(config: Configuration) {
+    let key = get_key(false);
+    let label = get_label(false);
+
+    let findex = InstantiatedFindex::new(config.clone()).await.unwrap();
+    assert!(!findex.is_read_only());
+    insert_users(&findex, &key, &label).await;
+    let dump = findex.dump().await.unwrap();
+    drop(findex);
+
+    let findex = InstantiatedFindex::new(config.with_read_only(true))
+        .await
+        .unwrap();
+    assert!(findex.is_read_only());
+    find_users(&findex, &key, &label).await;
+
+    let associations = || {
+        IndexedValueToKeywordsMap::from(HashMap::from([(
+            IndexedValue::Data(Data::from("location".as_bytes())),
+            HashSet::from([Keyword::from("keyword".as_bytes())]),
+        )]))
+    };
+    assert!(matches!(
+        findex.add(&key, &label, associations()).await,
+        Err(FindexError::DbInterface(DbInterfaceError::ReadOnly))
+    ));
+    assert!(matches!(
+        findex.delete(&key, &label, associations()).await,
+        Err(FindexError::DbInterface(DbInterfaceError::ReadOnly))
+    ));
+    assert!(matches!(
+        findex
+            .compact(
+                &key,
+                &key,
+                &label,
+                &label,
+                1.,
+                &|data| async move { Ok(data) }
+            )
+            .await,
+        Err(FindexError::DbInterface(DbInterfaceError::ReadOnly))
+    ));
+    assert!(matches!(
+        findex.restore(&dump).await,
+        Err(DbInterfaceError::ReadOnly)
+    ));
+    assert!(matches!(
+        findex.merge(&dump).await,
+        Err(DbInterfaceError::ReadOnly)
+    ));
+}
+
+/// This test:
+/// 1. Indexes a location under `"Café"` using a lowercasing and NFC
+///    normalization.
+/// 2. Asserts it is found under each casing and Unicode form of the keyword,
+///    and under the keyword requested.
+/// 3. Deletes it using another form of the keyword and asserts it cannot be
+///    found anymore.
+pub async fn test_keyword_normalization(config: Configuration) {
+    let findex = InstantiatedFindex::new(
+        config.with_keyword_normalizer(KeywordNormalizer::default().with_lowercase().with_nfc()),
+    )
+    .await
+    .unwrap();
+    let key = get_key(false);
+    let label = get_label(false);
+
+    let location = Data::from("cafe.pdf".as_bytes());
+    let associations = |keyword: &str| {
+        IndexedValueToKeywordsMap::from(HashMap::from([(
+            IndexedValue::Data(location.clone()),
+            HashSet::from([Keyword::from(keyword.as_bytes())]),
+        )]))
+    };
+    let new_keywords = findex
+        .add(&key, &label, associations("Caf\u{e9}"))
+        .await
+        .unwrap();
+    assert_eq!(new_keywords.len(), 1);
+    assert!(new_keywords.contains(&Keyword::from("caf\u{e9}".as_bytes())));
+
+    let search = |keyword: &str| {
+        let keyword = Keyword::from(keyword.as_bytes());
+        let (findex, key, label) = (&findex, &key, &label);
+        async move {
+            let results = findex
+                .search(
+                    key,
+                    label,
+                    Keywords::from(HashSet::from([keyword.clone()])),
+                    &|_| async { Ok(false) },
+                )
+                .await
+                .unwrap();
+            results.get(&keyword).cloned().unwrap_or_default()
+        }
+    };
+    for keyword in ["Caf\u{e9}", "caf\u{e9}", "CAF\u{c9}", "Cafe\u{301}"] {
+        assert_eq!(search(keyword).await, HashSet::from([location.clone()]));
+    }
+
+    findex
+        .delete(&key, &label, associations("CAFE\u{301}"))
+        .await
+        .unwrap();
+    assert!(search("caf\u{e9}").await.is_empty());
+}
+
+/// This test:
+/// 1. Indexes a location under a keyword using a chain padding, and another
+///    one under another keyword without padding.
+/// 2. Asserts the padded chain is longer, and that the dummy values are
+///    stripped from the search results.
+pub async fn test_chain_padding(config: Configuration) {
+    let mut findex =
+        InstantiatedFindex::new(config.with_chain_padding(Some(ChainPadding::new(8).unwrap())))
+            .await
+            .unwrap();
+    let key = get_key(false);
+    let label = get_label(false);
+
+    let location = Data::from("padded.pdf".as_bytes());
+    let associations = |keyword: &str| {
+        IndexedValueToKeywordsMap::from(HashMap::from([(
+            IndexedValue::Data(location.clone()),
+            HashSet::from([Keyword::from(keyword.as_bytes())]),
+        )]))
+    };
+    let n_links = findex.statistics().await.unwrap().n_links;
+    findex
+        .add(&key, &label, associations("padded"))
+        .await
+        .unwrap();
+    let padded_links = findex.statistics().await.unwrap().n_links - n_links;
+
+    findex.set_chain_padding(None);
+    let n_links = findex.statistics().await.unwrap().n_links;
+    findex
+        .add(&key, &label, associations("unpadded"))
+        .await
+        .unwrap();
+    let unpadded_links = findex.statistics().await.unwrap().n_links - n_links;
+    assert!(padded_links > unpadded_links);
+
+    for keyword in ["padded", "unpadded"] {
+        let keyword = Keyword::from(keyword.as_bytes());
+        let results = findex
+            .search(
+                &key,
+                &label,
+                Keywords::from(HashSet::from([keyword.clone()])),
+                &|_| async { Ok(false) },
+            )
+            .await
+            .unwrap();
+        assert_eq!(
+            results.get(&keyword),
+            Some(&HashSet::from([location.clone()]))
+        );
+    }
+}
+
+/// This test:
+/// 1. Indexes each user in the source index and synchronizes the (empty)
+///    target index with it.
+/// 2. Asserts each user can be retrieved from the target index, and that a
+///    second synchronization writes nothing.
+/// 3. Indexes a new value in the source index and asserts the next
+///    synchronization only writes the modified lines.
+pub async fn test_sync(source_config: Configuration, target_config: Configuration) {
+    let is_non_regression = false;
+
+    let source = InstantiatedFindex::new(source_config.clone())
+        .await
+        .unwrap();
+    let key = get_key(is_non_regression);
+    let label = get_label(is_non_regression);
+
+    insert_users(&source, &key, &label).await;
+
+    let statistics = InstantiatedFindex::sync(source_config.clone(), target_config.clone())
+        .await
+        .unwrap();
+    let source_statistics = source.statistics().await.unwrap();
+    assert_eq!(statistics.n_entries, source_statistics.n_entries);
+    assert_eq!(statistics.n_links, source_statistics.n_links);
+
+    let target = InstantiatedFindex::new(target_config.clone())
+        .await
+        .unwrap();
+    find_users(&target, &key, &label).await;
+
+    let statistics = InstantiatedFindex::sync(source_config.clone(), target_config.clone())
+        .await
+        .unwrap();
+    assert_eq!(statistics, SyncStatistics::default());
+
+    let keyword = Keyword::from("France".as_bytes());
+    let data = Data::from(b"new user".as_slice());
+    source
+        .add(
+            &key,
+            &label,
+            IndexedValueToKeywordsMap::from(HashMap::from([(
+                IndexedValue::Data(data.clone()),
+                HashSet::from([keyword.clone()]),
+            )])),
+        )
+        .await
+        .unwrap();
+
+    let statistics = InstantiatedFindex::sync(source_config, target_config)
+        .await
+        .unwrap();
+    assert_eq!(statistics.n_entries, 1);
+    assert!(statistics.n_links >= 1);
+
+    let res = target
+        .search(
+            &key,
+            &label,
+            Keywords::from_iter([keyword.clone()]),
+            &|_| async move { Ok(false) },
+        )
+        .await
+        .unwrap();
+    assert!(res.get(&keyword).unwrap().contains(&data));
+}
+
+/// This test:
+/// 1. Indexes a location under a keyword, and chains two aliases to this
+///    keyword.
+/// 2. Asserts the location is found from each alias, unless the number of
+///    aliases to follow exceeds the maximum search depth.
+/// 3. Asserts the location cannot be found from a deleted alias.
+pub async fn test_aliases(config: Configuration) {
+    let findex = InstantiatedFindex::new(config).await.unwrap();
+    let key = get_key(false);
+    let label = get_label(false);
+
+    let keyword = Keyword::from("Robert".as_bytes());
+    let alias = Keyword::from("Rob".as_bytes());
+    let nested_alias = Keyword::from("Ro".as_bytes());
+    let location = Data::from("robert.pdf".as_bytes());
+
+    findex
+        .add(
+            &key,
+            &label,
+            IndexedValueToKeywordsMap::from(HashMap::from([(
+                IndexedValue::Data(location.clone()),
+                HashSet::from([keyword.clone()]),
+            )])),
+        )
+        .await
+        .unwrap();
+    let new_aliases = findex
+        .add_aliases(
+            &key,
+            &label,
+            HashMap::from([
+                (keyword.clone(), HashSet::from([alias.clone()])),
+                (alias.clone(), HashSet::from([nested_alias.clone()])),
+            ]),
+        )
+        .await
+        .unwrap();
+    assert_eq!(new_aliases.len(), 2);
+
+    let search = |keyword: &Keyword, max_depth: usize| {
+        let keyword = keyword.clone();
+        let (findex, key, label, location) = (&findex, &key, &label, &location);
+        async move {
+            findex
+                .search_with_max_depth(
+                    key,
+                    label,
+                    Keywords::from_iter([keyword.clone()]),
+                    max_depth,
+                    &|_| async move { Ok(false) },
+                )
+                .await
+                .unwrap()
+                .get(&keyword)
+                .is_some_and(|data| data.contains(location))
+        }
+    };
+
+    assert!(search(&keyword, 0).await);
+    assert!(!search(&alias, 0).await);
+    assert!(search(&alias, 1).await);
+    assert!(!search(&nested_alias, 1).await);
+    assert!(search(&nested_alias, 2).await);
+
+    findex
+        .delete_aliases(
+            &key,
+            &label,
+            HashMap::from([(alias.clone(), HashSet::from([nested_alias.clone()]))]),
+        )
+        .await
+        .unwrap();
+    assert!(search(&alias, 1).await);
+    assert!(!search(&nested_alias, 2).await);
+}
+
+pub async fn test_add_in_chunks(config: Configuration) {
+    let findex = InstantiatedFindex::new(config).await.unwrap();
+    let key = get_key(false);
+    let label = get_label(false);
+
+    // 20 keywords indexing 10 locations each: 7 chunks of at most 30
+    // associations.
+    let keywords = (0..20)
+        .map(|i| Keyword::from(format!("keyword {i}").as_bytes()))
+        .collect::<Vec<_>>();
+    let additions = || {
+        IndexedValueToKeywordsMap::from(
+            (0..200)
+                .map(|i| {
+                    (
+                        IndexedValue::Data(Data::from(format!("location {i}").as_bytes())),
+                        HashSet::from([keywords[i % keywords.len()].clone()]),
+                    )
+                })
+                .collect::<HashMap<_, _>>(),
+        )
+    };
+
+    // Index the last chunks only, then resume from the first one.
+    let last_keywords = findex
+        .add_in_chunks(&key, &label, additions(), 30, 3)
+        .await
+        .unwrap();
+    assert_eq!(last_keywords.len(), 11);
+    let first_keywords = findex
+        .add_in_chunks(&key, &label, additions(), 30, 0)
+        .await
+        .unwrap();
+    assert_eq!(first_keywords.len(), 9);
+    assert!(first_keywords.iter().all(|kw| !last_keywords.contains(kw)));
+
+    let results = findex
+        .search(
+            &key,
+            &label,
+            Keywords::from_iter(keywords.clone()),
+            &|_| async move { Ok(false) },
+        )
+        .await
+        .unwrap();
+    for (i, keyword) in keywords.iter().enumerate() {
+        let data = results.get(keyword).unwrap();
+        assert_eq!(data.len(), 10);
+        assert!(data.contains(&Data::from(format!("location {i}").as_bytes())));
+    }
+}
+
+async fn search_keyword(
+    findex: &InstantiatedFindex,
+    key: &UserKey,
+    label: &Label,
+    keyword: &Keyword,
+) -> HashSet<Data> {
+    findex
+        .search(
+            key,
+            label,
+            Keywords::from_iter([keyword.clone()]),
+            &|_| async move { Ok(false) },
+        )
+        .await
+        .unwrap()
+        .get(keyword)
+        .cloned()
+        .unwrap_or_default()
+}
+
+async fn count_keyword_tombstones(
+    findex: &InstantiatedFindex,
+    key: &UserKey,
+    label: &Label,
+    keyword: &Keyword,
+) -> usize {
+    findex
+        .count_tombstones(key, label, Keywords::from_iter([keyword.clone()]))
+        .await
+        .unwrap()
+        .get(keyword)
+        .copied()
+        .unwrap_or_default()
+}
+
+pub async fn test_soft_delete(config: Configuration) {
+    let findex = InstantiatedFindex::new(config).await.unwrap();
+    let key = get_key(false);
+    let label = get_label(false);
+
+    let keyword = Keyword::from("Robert".as_bytes());
+    let locations = (0..3)
+        .map(|i| Data::from(format!("robert_{i}.pdf").as_bytes()))
+        .collect::<Vec<_>>();
+    let associations = |locations: &[Data]| {
+        IndexedValueToKeywordsMap::from(
+            locations
+                .iter()
+                .map(|location| {
+                    (
+                        IndexedValue::Data(location.clone()),
+                        HashSet::from([keyword.clone()]),
+                    )
+                })
+                .collect::<HashMap<_, _>>(),
+        )
+    };
+
+    findex
+        .add(&key, &label, associations(&locations))
+        .await
+        .unwrap();
+    findex
+        .soft_delete(&key, &label, associations(&locations[..1]))
+        .await
+        .unwrap();
+    assert_eq!(
+        search_keyword(&findex, &key, &label, &keyword).await,
+        locations[1..].iter().cloned().collect()
+    );
+    assert_eq!(
+        count_keyword_tombstones(&findex, &key, &label, &keyword).await,
+        1
+    );
+
+    // Restore the soft-deleted location.
+    let restored = findex
+        .restore_deleted(&key, &label, Keywords::from_iter([keyword.clone()]))
+        .await
+        .unwrap();
+    assert_eq!(
+        restored.get(&keyword),
+        Some(&HashSet::from([locations[0].clone()]))
+    );
+    assert_eq!(
+        search_keyword(&findex, &key, &label, &keyword).await,
+        locations.iter().cloned().collect()
+    );
+    assert_eq!(
+        count_keyword_tombstones(&findex, &key, &label, &keyword).await,
+        0
+    );
+
+    // Tombstones are removed upon compaction.
+    findex
+        .soft_delete(&key, &label, associations(&locations[..1]))
+        .await
+        .unwrap();
+    let new_key = get_key(false);
+    let new_label = get_label(false);
+    findex
+        .compact(&key, &new_key, &label, &new_label, 1., &|data| async move {
+            Ok(data)
+        })
+        .await
+        .unwrap();
+    assert_eq!(
+        count_keyword_tombstones(&findex, &new_key, &new_label, &keyword).await,
+        0
+    );
+    assert_eq!(
+        search_keyword(&findex, &new_key, &new_label, &keyword).await,
+        locations[1..].iter().cloned().collect()
+    );
+}
+
+pub async fn test_partial_compaction(config: Configuration) {
+    let findex = InstantiatedFindex::new(config).await.unwrap();
+    let key = get_key(false);
+    let label = get_label(false);
+
+    // Index 1000 keywords, each indexing a single location.
+    let n_total_entries = 1_000;
+    let associations = (0..n_total_entries)
+        .map(|i| {
+            (
+                IndexedValue::Data(Data::from(format!("location_{i}").as_bytes())),
+                HashSet::from([Keyword::from(format!("keyword_{i}").as_bytes())]),
+            )
+        })
+        .collect::<HashMap<_, _>>();
+    findex
+        .add(&key, &label, IndexedValueToKeywordsMap::from(associations))
+        .await
+        .unwrap();
+
+    // Each compacted entry passes its single location to the filter.
+    let n_entries = 100;
+    let n_compacted = Cell::new(0);
+    let new_key = get_key(false);
+    let new_label = get_label(false);
+    findex
+        .compact_partial(&key, &new_key, &label, &new_label, n_entries, &|data| {
+            n_compacted.set(n_compacted.get() + data.len());
+            async move { Ok(data) }
+        })
+        .await
+        .unwrap();
+    let n_compacted = n_compacted.get();
+    assert!(
+        (80..=n_entries).contains(&n_compacted),
+        "{n_compacted} entries compacted instead of about {n_entries}"
+    );
+
+    // All the entries are re-keyed.
+    let keyword = Keyword::from("keyword_0".as_bytes());
+    assert_eq!(
+        search_keyword(&findex, &new_key, &new_label, &keyword).await,
+        HashSet::from([Data::from("location_0".as_bytes())])
+    );
+}
+
+pub async fn test_payloads(config: Configuration) {
+    let findex = InstantiatedFindex::new(config).await.unwrap();
+    let key = get_key(false);
+    let label = get_label(false);
+    let payload_key = PayloadKey::new(&mut CsRng::from_entropy());
+
+    let keyword = Keyword::from("Robert".as_bytes());
+    let locations = (0..3)
+        .map(|i| Data::from(format!("robert_{i}.pdf").as_bytes()))
+        .collect::<Vec<_>>();
+    let payload = |location: &Data| [b"payload of ".as_slice(), location].concat();
+    findex
+        .add_with_payloads(
+            &key,
+            &label,
+            &payload_key,
+            locations
+                .iter()
+                .map(|location| {
+                    (
+                        location.clone(),
+                        (payload(location), HashSet::from([keyword.clone()])),
+                    )
+                })
+                .collect(),
+        )
+        .await
+        .unwrap();
+
+    // Plain searches only return the locations.
+    assert_eq!(
+        search_keyword(&findex, &key, &label, &keyword).await,
+        locations.iter().cloned().collect()
+    );
+
+    // The compaction filter only sees the locations, and the payloads are
+    // still readable after re-keying the index.
+    let new_key = get_key(false);
+    let new_label = get_label(false);
+    findex
+        .compact(&key, &new_key, &label, &new_label, 1., &|data| {
+            assert_eq!(data, locations.iter().cloned().collect());
+            let kept = data
+                .into_iter()
+                .filter(|location| location != &locations[0])
+                .collect();
+            async move { Ok(kept) }
+        })
+        .await
+        .unwrap();
+    let results = findex
+        .search_with_payloads(
+            &new_key,
+            &new_label,
+            &payload_key,
+            Keywords::from_iter([keyword.clone()]),
+            &|_| async { Ok(false) },
+        )
+        .await
+        .unwrap();
+    assert_eq!(
+        results.get(&keyword),
+        Some(
+            &locations[1..]
+                .iter()
+                .map(|location| (location.clone(), Some(payload(location))))
+                .collect()
+        )
+    );
+
+    // Locations are deleted along with their payload.
+    findex
+        .delete_with_payloads(
+            &new_key,
+            &new_label,
+            HashMap::from([(locations[1].clone(), HashSet::from([keyword.clone()]))]),
+        )
+        .await
+        .unwrap();
+    assert_eq!(
+        search_keyword(&findex, &new_key, &new_label, &keyword).await,
+        HashSet::from([locations[2].clone()])
+    );
+}
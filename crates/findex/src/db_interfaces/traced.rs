@@ -0,0 +1,111 @@
+//! Tracing instrumentation of the Findex backends.
+//!
+//! [`Traced`] wraps any backend and records a span around each call to the
+//! database, holding the backend and table names along with the number of
+//! tokens sent and received. Exported to an OpenTelemetry collector, these
+//! spans give the duration of each database round-trip of a Findex request.
+
+use async_trait::async_trait;
+use cosmian_findex::{DbInterface, TokenToEncryptedValueMap, TokenWithEncryptedValueList, Tokens};
+use tracing::{field, info_span, Instrument, Span};
+
+use crate::db_interfaces::DbInterfaceError;
+
+/// Backend wrapper recording a tracing span around each database call.
+#[derive(Debug)]
+pub struct Traced<Backend> {
+    backend: Backend,
+    backend_name: &'static str,
+    table_name: &'static str,
+}
+
+impl<Backend> Traced<Backend> {
+    /// Wraps the given backend. The backend and table names are recorded in
+    /// the spans.
+    pub const fn new(
+        backend: Backend,
+        backend_name: &'static str,
+        table_name: &'static str,
+    ) -> Self {
+        Self {
+            backend,
+            backend_name,
+            table_name,
+        }
+    }
+
+    fn span(&self, name: &'static str) -> Span {
+        info_span!(
+            "findex_backend",
+            otel.name = name,
+            backend = self.backend_name,
+            table = self.table_name,
+            n_tokens = field::Empty,
+            n_results = field::Empty,
+        )
+    }
+}
+
+#[async_trait(?Send)]
+impl<const VALUE_LENGTH: usize, Backend> DbInterface<VALUE_LENGTH> for Traced<Backend>
+where
+    Backend: DbInterface<VALUE_LENGTH, Error = DbInterfaceError>,
+{
+    type Error = DbInterfaceError;
+
+    async fn dump_tokens(&self) -> Result<Tokens, Self::Error> {
+        async {
+            let tokens = self.backend.dump_tokens().await?;
+            Span::current().record("n_results", tokens.len());
+            Ok(tokens)
+        }
+        .instrument(self.span("dump_tokens"))
+        .await
+    }
+
+    async fn fetch(
+        &self,
+        tokens: Tokens,
+    ) -> Result<TokenWithEncryptedValueList<VALUE_LENGTH>, Self::Error> {
+        let span = self.span("fetch");
+        span.record("n_tokens", tokens.len());
+        async {
+            let res = self.backend.fetch(tokens).await?;
+            Span::current().record("n_results", res.len());
+            Ok(res)
+        }
+        .instrument(span)
+        .await
+    }
+
+    async fn upsert(
+        &self,
+        old_values: TokenToEncryptedValueMap<VALUE_LENGTH>,
+        new_values: TokenToEncryptedValueMap<VALUE_LENGTH>,
+    ) -> Result<TokenToEncryptedValueMap<VALUE_LENGTH>, Self::Error> {
+        let span = self.span("upsert");
+        span.record("n_tokens", new_values.len());
+        async {
+            let rejected = self.backend.upsert(old_values, new_values).await?;
+            Span::current().record("n_results", rejected.len());
+            Ok(rejected)
+        }
+        .instrument(span)
+        .await
+    }
+
+    async fn insert(
+        &self,
+        items: TokenToEncryptedValueMap<VALUE_LENGTH>,
+    ) -> Result<(), Self::Error> {
+        let span = self.span("insert");
+        span.record("n_tokens", items.len());
+        self.backend.insert(items).instrument(span).await
+    }
+
+    async fn delete(&self, tokens: Tokens) -> Result<(), Self::Error> {
+        let span = self.span("delete");
+        span.record("n_tokens", tokens.len());
+        self.backend.delete(tokens).instrument(span).await
+    }
+}
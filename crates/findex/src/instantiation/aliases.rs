@@ -0,0 +1,49 @@
+//! Keyword aliases, e.g. used for fuzzy search.
+//!
+//! An alias is a keyword pointing to another keyword in the Findex graph:
+//! searching for the alias also returns the data indexed under the keyword it
+//! points to. For example, indexing the prefixes `"Ali"` and `"Alic"` as
+//! aliases of `"Alice"` allows finding the data indexed under `"Alice"` by
+//! searching for `"Ali"`.
+//!
+//! Aliases can be chained. The number of aliases followed upon search can be
+//! bounded using
+//! [`search_with_max_depth`](crate::InstantiatedFindex::search_with_max_depth).
+
+use std::collections::{HashMap, HashSet};
+
+use cosmian_findex::{IndexedValue, IndexedValueToKeywordsMap, Keyword};
+
+/// Aliases pointing to each keyword.
+pub type KeywordToAliasesMap = HashMap<Keyword, HashSet<Keyword>>;
+
+/// Converts the given aliases into the associations to add to (or delete from)
+/// the Findex graph: each keyword is indexed as a pointer for its aliases.
+pub(crate) fn aliases_to_associations(aliases: KeywordToAliasesMap) -> IndexedValueToKeywordsMap {
+    IndexedValueToKeywordsMap::from(
+        aliases
+            .into_iter()
+            .map(|(keyword, aliases)| (IndexedValue::Pointer(keyword), aliases))
+            .collect::<HashMap<_, _>>(),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_aliases_to_associations() {
+        let keyword = Keyword::from("Alice".as_bytes());
+        let aliases = HashSet::from([
+            Keyword::from("Ali".as_bytes()),
+            Keyword::from("Alic".as_bytes()),
+        ]);
+        let associations =
+            aliases_to_associations(HashMap::from([(keyword.clone(), aliases.clone())]));
+        assert_eq!(associations.len(), 1);
+        let pointed_aliases = &associations[&IndexedValue::Pointer(keyword)];
+        assert_eq!(pointed_aliases.len(), aliases.len());
+        assert!(aliases.iter().all(|alias| pointed_aliases.contains(alias)));
+    }
+}
@@ -0,0 +1,149 @@
+//! Indexation of large additions in bounded-size chunks.
+//!
+//! Indexing all the additions at once serializes them in a single upsert,
+//! which may exceed the limits of the backend (e.g. the buffers of the FFI
+//! callbacks). Additions are instead grouped by keyword and split into chunks
+//! of at most `chunk_size` associations, indexed one after the other.
+//!
+//! Chunks are built in a deterministic order: if the indexation of a chunk
+//! fails, indexing the same additions with the same chunk size resumes from
+//! the first chunk not indexed (see [`ChunkedAddError`]).
+
+use std::{
+    collections::{HashMap, HashSet},
+    fmt::Display,
+};
+
+use cosmian_findex::{
+    Data, Error as FindexError, IndexedValue, IndexedValueToKeywordsMap, Keyword, Keywords,
+};
+
+use crate::db_interfaces::DbInterfaceError;
+
+/// Default maximum number of associations indexed per chunk.
+pub const DEFAULT_CHUNK_SIZE: usize = 100_000;
+
+/// Error returned when the indexation of a chunk fails.
+#[derive(Debug)]
+pub struct ChunkedAddError {
+    /// Number of chunks successfully indexed: the indexation can be resumed
+    /// from this chunk.
+    pub indexed_chunks: usize,
+    /// Keywords added to the index by the chunks successfully indexed.
+    pub new_keywords: Keywords,
+    /// Error returned by the indexation of the failing chunk.
+    pub error: FindexError<DbInterfaceError>,
+}
+
+impl Display for ChunkedAddError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "indexation failed after {} chunk(s): {}",
+            self.indexed_chunks, self.error
+        )
+    }
+}
+
+impl std::error::Error for ChunkedAddError {}
+
+/// Splits the given additions into chunks of at most `chunk_size`
+/// associations.
+///
+/// Duplicated associations are merged. The associations of a given keyword are
+/// never split across chunks: a keyword indexing more than `chunk_size` values
+/// is indexed in a chunk of its own.
+pub(crate) fn chunk_additions(
+    additions: &IndexedValueToKeywordsMap,
+    chunk_size: usize,
+) -> Vec<IndexedValueToKeywordsMap> {
+    let mut values_per_keyword = HashMap::<Keyword, HashSet<IndexedValue<Keyword, Data>>>::new();
+    for (value, keywords) in additions.iter() {
+        for keyword in keywords.iter() {
+            values_per_keyword
+                .entry(keyword.clone())
+                .or_default()
+                .insert(value.clone());
+        }
+    }
+
+    // Sort the keywords for the chunks to be built in a deterministic order.
+    let mut values_per_keyword = values_per_keyword.into_iter().collect::<Vec<_>>();
+    values_per_keyword.sort_by_cached_key(|(keyword, _)| keyword.to_vec());
+
+    let mut chunks = Vec::new();
+    let mut chunk = HashMap::<IndexedValue<Keyword, Data>, HashSet<Keyword>>::new();
+    let mut chunk_length = 0;
+    for (keyword, values) in values_per_keyword {
+        if chunk_length != 0 && chunk_length + values.len() > chunk_size {
+            chunks.push(IndexedValueToKeywordsMap::from(std::mem::take(&mut chunk)));
+            chunk_length = 0;
+        }
+        chunk_length += values.len();
+        for value in values {
+            chunk.entry(value).or_default().insert(keyword.clone());
+        }
+    }
+    if chunk_length != 0 {
+        chunks.push(IndexedValueToKeywordsMap::from(chunk));
+    }
+    chunks
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_chunk_additions() {
+        let keywords = (0..10)
+            .map(|i| Keyword::from(format!("keyword {i}").as_bytes()))
+            .collect::<Vec<_>>();
+        let additions = IndexedValueToKeywordsMap::from(
+            (0..100_u8)
+                .map(|i| {
+                    (
+                        IndexedValue::Data(Data::from(vec![i])),
+                        HashSet::from([
+                            keywords[usize::from(i) % 10].clone(),
+                            keywords[usize::from(i) % 3].clone(),
+                        ]),
+                    )
+                })
+                .collect::<HashMap<_, _>>(),
+        );
+        let n_associations = additions.values().map(|kws| kws.len()).sum::<usize>();
+
+        let chunks = chunk_additions(&additions, 25);
+        assert!(chunks.len() > 1);
+        assert_eq!(
+            chunks
+                .iter()
+                .flat_map(|chunk| chunk.values().map(|kws| kws.len()))
+                .sum::<usize>(),
+            n_associations
+        );
+
+        // Each keyword is indexed in a single chunk.
+        for keyword in &keywords {
+            assert_eq!(
+                chunks
+                    .iter()
+                    .filter(|chunk| chunk.values().any(|kws| kws.contains(keyword)))
+                    .count(),
+                1
+            );
+        }
+
+        // Chunks are built in a deterministic order.
+        let other_chunks = chunk_additions(&additions, 25);
+        for (chunk, other_chunk) in chunks.iter().zip(&other_chunks) {
+            assert_eq!(chunk.len(), other_chunk.len());
+            assert!(chunk.iter().all(|(value, keywords)| {
+                other_chunk.get(value).is_some_and(|other_keywords| {
+                    keywords.iter().all(|kw| other_keywords.contains(kw))
+                })
+            }));
+        }
+    }
+}
@@ -0,0 +1,185 @@
+//! Index statistics and incremental compaction.
+//!
+//! Deleted and obsolete values are only removed from the chains upon
+//! compaction. Until then, the Chain Table keeps growing while the number of
+//! Entry Table lines (one per indexed keyword) stays the same: the number of
+//! Chain Table lines per Entry Table line is therefore a good indicator of the
+//! garbage accumulated since the last compaction.
+//!
+//! Huge indexes can be compacted incrementally, by compacting a given number
+//! of entries per run instead of the whole index at once.
+
+use std::sync::{Arc, Mutex, PoisonError};
+
+use async_trait::async_trait;
+use cosmian_findex::{
+    DbInterface, TokenToEncryptedValueMap, TokenWithEncryptedValueList, Tokens, ENTRY_LENGTH,
+    LINK_LENGTH,
+};
+
+use crate::db_interfaces::DbInterfaceError;
+
+/// [Euler's gamma constant](https://en.wikipedia.org/wiki/Euler%E2%80%93Mascheroni_constant),
+/// as used by Findex to compute the number of entries drawn upon compaction.
+const GAMMA: f64 = 0.5772;
+
+/// Number of lines stored in the index tables.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct IndexStatistics {
+    /// Number of Entry Table lines, i.e. number of indexed keywords.
+    pub n_entries: usize,
+    /// Number of Chain Table lines.
+    pub n_links: usize,
+}
+
+impl IndexStatistics {
+    /// Returns the average number of Chain Table lines per Entry Table line.
+    ///
+    /// An increase of this ratio between two compactions signals that garbage
+    /// accumulates in the chains.
+    #[must_use]
+    pub fn links_per_entry(&self) -> f64 {
+        if self.n_entries == 0 {
+            0.
+        } else {
+            self.n_links as f64 / self.n_entries as f64
+        }
+    }
+}
+
+/// Counts the lines of the given table.
+async fn count_lines<const VALUE_LENGTH: usize>(
+    table: &impl DbInterface<VALUE_LENGTH, Error = DbInterfaceError>,
+) -> Result<usize, DbInterfaceError> {
+    Ok(table.dump_tokens().await?.len())
+}
+
+/// Counts the lines of the given tables.
+///
+/// Both backends need to be able to dump their tokens.
+pub(crate) async fn table_statistics(
+    entry_table: &impl DbInterface<ENTRY_LENGTH, Error = DbInterfaceError>,
+    chain_table: &impl DbInterface<LINK_LENGTH, Error = DbInterfaceError>,
+) -> Result<IndexStatistics, DbInterfaceError> {
+    Ok(IndexStatistics {
+        n_entries: count_lines(entry_table).await?,
+        n_links: count_lines(chain_table).await?,
+    })
+}
+
+/// Returns the compacting rate to use to compact the given number of entries
+/// among the given total number of entries.
+///
+/// Findex draws `rate * (N * (log2(N) + GAMMA) + 0.5)` entries at random among
+/// the `N` entries of the index, so that all the entries are compacted after
+/// about `1 / rate` compactions: the rate returned makes it draw `n_entries`
+/// entries.
+pub(crate) fn partial_compacting_rate(n_entries: usize, n_total_entries: usize) -> f64 {
+    if n_total_entries == 0 || n_total_entries <= n_entries {
+        1.
+    } else {
+        let n_total_entries = n_total_entries as f64;
+        n_entries as f64 / n_total_entries.mul_add(n_total_entries.log2() + GAMMA, 0.5)
+    }
+}
+
+/// Token dump kept to serve the next dump of a backend, see [`KeptDump`].
+pub(crate) type KeptTokens = Arc<Mutex<Option<Tokens>>>;
+
+/// Backend wrapper serving its next token dump from kept tokens, if any.
+///
+/// The partial compaction dumps the Entry Table tokens to compute its
+/// compacting rate: the compaction reuses them instead of scanning the table
+/// again.
+#[derive(Debug)]
+pub(crate) struct KeptDump<Backend> {
+    backend: Backend,
+    kept_tokens: KeptTokens,
+}
+
+impl<Backend> KeptDump<Backend> {
+    pub(crate) const fn new(backend: Backend, kept_tokens: KeptTokens) -> Self {
+        Self {
+            backend,
+            kept_tokens,
+        }
+    }
+}
+
+#[async_trait(?Send)]
+impl<const VALUE_LENGTH: usize, Backend> DbInterface<VALUE_LENGTH> for KeptDump<Backend>
+where
+    Backend: DbInterface<VALUE_LENGTH, Error = DbInterfaceError>,
+{
+    type Error = DbInterfaceError;
+
+    async fn dump_tokens(&self) -> Result<Tokens, Self::Error> {
+        let kept_tokens = self
+            .kept_tokens
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .take();
+        match kept_tokens {
+            Some(tokens) => Ok(tokens),
+            None => self.backend.dump_tokens().await,
+        }
+    }
+
+    async fn fetch(
+        &self,
+        tokens: Tokens,
+    ) -> Result<TokenWithEncryptedValueList<VALUE_LENGTH>, Self::Error> {
+        self.backend.fetch(tokens).await
+    }
+
+    async fn upsert(
+        &self,
+        old_values: TokenToEncryptedValueMap<VALUE_LENGTH>,
+        new_values: TokenToEncryptedValueMap<VALUE_LENGTH>,
+    ) -> Result<TokenToEncryptedValueMap<VALUE_LENGTH>, Self::Error> {
+        self.backend.upsert(old_values, new_values).await
+    }
+
+    async fn insert(
+        &self,
+        items: TokenToEncryptedValueMap<VALUE_LENGTH>,
+    ) -> Result<(), Self::Error> {
+        self.backend.insert(items).await
+    }
+
+    async fn delete(&self, tokens: Tokens) -> Result<(), Self::Error> {
+        self.backend.delete(tokens).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_partial_compaction() {
+        assert!((partial_compacting_rate(10, 0) - 1.).abs() < f64::EPSILON);
+        assert!((partial_compacting_rate(10, 5) - 1.).abs() < f64::EPSILON);
+        assert!(partial_compacting_rate(0, 40).abs() < f64::EPSILON);
+        // Findex draws `rate * (N * (log2(N) + GAMMA) + 0.5)` entries.
+        let n_total_entries = 1_000_000_f64;
+        let n_draws = partial_compacting_rate(1_000, 1_000_000)
+            * n_total_entries.mul_add(n_total_entries.log2() + GAMMA, 0.5);
+        assert!((n_draws - 1_000.).abs() < 1e-6);
+
+        let statistics = IndexStatistics {
+            n_entries: 4,
+            n_links: 10,
+        };
+        assert!((statistics.links_per_entry() - 2.5).abs() < f64::EPSILON);
+        assert!(
+            IndexStatistics {
+                n_entries: 0,
+                n_links: 0
+            }
+            .links_per_entry()
+            .abs()
+                < f64::EPSILON
+        );
+    }
+}
@@ -3,9 +3,10 @@ use crate::db_interfaces::custom::ffi::FfiCallbacks;
 #[cfg(feature = "python")]
 use crate::db_interfaces::custom::python::PythonCallbacks;
 #[cfg(feature = "wasm")]
-use crate::db_interfaces::custom::wasm::WasmCallbacks;
+use crate::db_interfaces::custom::wasm::{SqliteDatabase, WasmCallbacks};
 #[cfg(feature = "rest-interface")]
-use crate::db_interfaces::rest::AuthorizationToken;
+use crate::db_interfaces::rest::{AuthorizationToken, RateLimit, RequestOptions};
+use crate::instantiation::{ChainPadding, KeywordNormalizer};
 
 /// Contains all parameters needed to instantiate the corresponding interfaces.
 ///
@@ -13,13 +14,20 @@ use crate::db_interfaces::rest::AuthorizationToken;
 /// Entry Table while the second ones are used to instantiate the Chain Table.
 #[derive(Clone)]
 pub enum Configuration {
-    /// REST DB interface requires an authorization token and a server URL for the Entry and the
-    /// Chain tables.
+    /// REST DB interface requires an authorization token, a server URL for the Entry and the
+    /// Chain tables, the rate limit of the requests sent to the server and their options.
     #[cfg(feature = "rest-interface")]
-    Rest(AuthorizationToken, String, String),
+    Rest(
+        AuthorizationToken,
+        String,
+        String,
+        RateLimit,
+        RequestOptions,
+    ),
 
     /// FFI DB interface requests FFI functions corresponding to the APIs used by the
-    /// Entry/Chain tables.
+    /// Entry/Chain tables. The Entry Table may be sharded into several tables,
+    /// see [`FfiCallbacks::table_number`].
     #[cfg(feature = "ffi")]
     Ffi(FfiCallbacks, FfiCallbacks),
 
@@ -28,17 +36,93 @@ pub enum Configuration {
     #[cfg(feature = "python")]
     Python(PythonCallbacks, PythonCallbacks),
 
-    /// SQLite DB interface requests a valid [`Connection`](rusqlite::Connection)
-    /// pointing to valid Entry/Chain tables.
+    /// SQLite DB interface requests the paths to the Entry/Chain tables and an
+    /// optional key used to encrypt them at rest (requires the `sqlcipher`
+    /// feature).
     #[cfg(feature = "sqlite-interface")]
-    Sqlite(String, String),
+    Sqlite(String, String, Option<String>),
 
-    /// Redis DB interface requests an URL to a valid instance.
+    /// Redis DB interface requests an URL to a valid instance and the maximum
+    /// number of keys or commands sent per request (see
+    /// [`DEFAULT_PIPELINE_SIZE`](crate::db_interfaces::redis::DEFAULT_PIPELINE_SIZE)).
     #[cfg(feature = "redis-interface")]
-    Redis(String, String),
+    Redis(String, String, usize),
+
+    /// gRPC DB interface requests the URLs of services implementing the
+    /// `FindexStore` service for the Entry/Chain tables.
+    #[cfg(feature = "grpc-interface")]
+    Grpc(String, String),
+
+    /// Object-store DB interface requests the URLs of the Entry/Chain tables,
+    /// e.g. `s3://bucket/index/entry`. The store configuration is read from
+    /// the environment.
+    #[cfg(feature = "object-store-interface")]
+    ObjectStore(String, String),
+
+    /// etcd DB interface requests the comma-separated endpoints of the
+    /// clusters storing the Entry/Chain tables, which can be the same cluster.
+    #[cfg(feature = "etcd-interface")]
+    Etcd(String, String),
 
     /// WASM DB interface requests WASM functions corresponding to the APIs used by
     /// the Entry/Chain tables.
     #[cfg(feature = "wasm")]
     Wasm(WasmCallbacks, WasmCallbacks),
+
+    /// WASM SQLite DB interface requests the SQLite databases provided by the
+    /// application for the Entry/Chain tables, which can be the same database.
+    #[cfg(feature = "wasm")]
+    WasmSqlite(SqliteDatabase, SqliteDatabase),
+
+    /// Read-only mode of the given configuration, for the services that must
+    /// never modify the index, e.g. analytics services.
+    ///
+    /// The instantiated Findex rejects the additions, deletions and
+    /// compactions. Backends supporting it open read-only connections: the
+    /// SQLite databases are opened in immutable mode and must therefore not be
+    /// modified while in use. The Redis URLs can point to replicas.
+    ReadOnly(Box<Configuration>),
+
+    /// The given configuration, with the keywords normalized by the given
+    /// normalizer before being added, deleted or searched, see
+    /// [`KeywordNormalizer`].
+    Normalized(Box<Configuration>, KeywordNormalizer),
+
+    /// The given configuration, with the chains padded with dummy values upon
+    /// each addition and deletion, see [`ChainPadding`].
+    Padded(Box<Configuration>, ChainPadding),
+}
+
+impl Configuration {
+    /// Returns the read-only mode of this configuration if `read_only` is
+    /// set, see [`Self::ReadOnly`].
+    #[must_use]
+    pub fn with_read_only(self, read_only: bool) -> Self {
+        if read_only {
+            Self::ReadOnly(Box::new(self))
+        } else {
+            self
+        }
+    }
+
+    /// Returns this configuration with the keywords normalized by the given
+    /// normalizer, see [`Self::Normalized`].
+    #[must_use]
+    pub fn with_keyword_normalizer(self, normalizer: KeywordNormalizer) -> Self {
+        if normalizer.is_identity() {
+            self
+        } else {
+            Self::Normalized(Box::new(self), normalizer)
+        }
+    }
+
+    /// Returns this configuration with the chains padded according to the
+    /// given padding, if any, see [`Self::Padded`].
+    #[must_use]
+    pub fn with_chain_padding(self, padding: Option<ChainPadding>) -> Self {
+        match padding {
+            Some(padding) => Self::Padded(Box::new(self), padding),
+            None => self,
+        }
+    }
 }
@@ -0,0 +1,159 @@
+//! Versioned binary format used to export and import a whole index.
+//!
+//! ```txt
+//! version (1 byte)
+//!     || LEB128(n_entries) || UID_1 || LEB128(value_1.len()) || value_1 || ...
+//!     || LEB128(n_links)   || UID_1 || LEB128(value_1.len()) || value_1 || ...
+//! ```
+//!
+//! Values are stored as they are found in the tables: the dump is as secure as
+//! the index itself and can only be used with the key and label that were used
+//! to build it.
+
+use std::collections::HashMap;
+
+use cosmian_crypto_core::bytes_ser_de::{Deserializer, Serializer};
+use cosmian_findex::{
+    DbInterface, EncryptedValue, Token, TokenToEncryptedValueMap, ENTRY_LENGTH, LINK_LENGTH,
+};
+
+use crate::db_interfaces::DbInterfaceError;
+
+/// Current version of the index dump format.
+pub const INDEX_DUMP_VERSION: u8 = 1;
+
+fn write_lines<const VALUE_LENGTH: usize>(
+    ser: &mut Serializer,
+    lines: &TokenToEncryptedValueMap<VALUE_LENGTH>,
+) -> Result<(), DbInterfaceError> {
+    ser.write_leb128_u64(lines.len() as u64)?;
+    for (token, value) in lines.iter() {
+        ser.write_array(token)?;
+        ser.write_vec(&<Vec<u8>>::from(value))?;
+    }
+    Ok(())
+}
+
+fn read_lines<const VALUE_LENGTH: usize>(
+    de: &mut Deserializer,
+) -> Result<TokenToEncryptedValueMap<VALUE_LENGTH>, DbInterfaceError> {
+    let length = usize::try_from(de.read_leb128_u64()?)?;
+    let mut lines = HashMap::with_capacity(length);
+    for _ in 0..length {
+        let token = Token::from(de.read_array()?);
+        let value = EncryptedValue::<VALUE_LENGTH>::try_from(de.read_vec()?.as_slice())?;
+        lines.insert(token, value);
+    }
+    Ok(TokenToEncryptedValueMap::from(lines))
+}
+
+/// Serializes the given Entry Table and Chain Table lines into an index dump.
+pub fn serialize_index_dump(
+    entries: &TokenToEncryptedValueMap<ENTRY_LENGTH>,
+    links: &TokenToEncryptedValueMap<LINK_LENGTH>,
+) -> Result<Vec<u8>, DbInterfaceError> {
+    let mut ser = Serializer::new();
+    ser.write_array(&[INDEX_DUMP_VERSION])?;
+    write_lines(&mut ser, entries)?;
+    write_lines(&mut ser, links)?;
+    Ok(ser.finalize().to_vec())
+}
+
+/// Deserializes an index dump into its Entry Table and Chain Table lines.
+pub fn deserialize_index_dump(
+    bytes: &[u8],
+) -> Result<
+    (
+        TokenToEncryptedValueMap<ENTRY_LENGTH>,
+        TokenToEncryptedValueMap<LINK_LENGTH>,
+    ),
+    DbInterfaceError,
+> {
+    let mut de = Deserializer::new(bytes);
+    let [version] = de.read_array::<1>()?;
+    if version != INDEX_DUMP_VERSION {
+        return Err(DbInterfaceError::Serialization(format!(
+            "unsupported index dump version {version}, expected {INDEX_DUMP_VERSION}"
+        )));
+    }
+    let entries = read_lines(&mut de)?;
+    let links = read_lines(&mut de)?;
+    if de.value().is_empty() {
+        Ok((entries, links))
+    } else {
+        Err(DbInterfaceError::Serialization(
+            "remaining bytes after index dump deserialization".to_string(),
+        ))
+    }
+}
+
+/// Fetches all the lines of the given tables and serializes them.
+///
+/// Both backends need to be able to dump their tokens.
+pub(crate) async fn dump_tables(
+    entry_table: &impl DbInterface<ENTRY_LENGTH, Error = DbInterfaceError>,
+    chain_table: &impl DbInterface<LINK_LENGTH, Error = DbInterfaceError>,
+) -> Result<Vec<u8>, DbInterfaceError> {
+    let entries = entry_table
+        .fetch(entry_table.dump_tokens().await?)
+        .await?
+        .into_iter()
+        .collect();
+    let links = chain_table
+        .fetch(chain_table.dump_tokens().await?)
+        .await?
+        .into_iter()
+        .collect();
+    serialize_index_dump(&entries, &links)
+}
+
+/// Inserts the lines of the given index dump into the given tables.
+///
+/// Lines are inserted as is: the target tables should not already contain
+/// any of the dumped tokens.
+pub(crate) async fn restore_tables(
+    entry_table: &impl DbInterface<ENTRY_LENGTH, Error = DbInterfaceError>,
+    chain_table: &impl DbInterface<LINK_LENGTH, Error = DbInterfaceError>,
+    bytes: &[u8],
+) -> Result<(), DbInterfaceError> {
+    let (entries, links) = deserialize_index_dump(bytes)?;
+    // Insert the chains first so that the index is never left with entries
+    // pointing to missing links.
+    chain_table.insert(links).await?;
+    entry_table.insert(entries).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_index_dump_serialization() {
+        let entries = TokenToEncryptedValueMap::from(HashMap::from_iter((0..3_u8).map(|i| {
+            (
+                Token::from([i; Token::LENGTH]),
+                EncryptedValue::try_from(
+                    vec![i; EncryptedValue::<ENTRY_LENGTH>::LENGTH].as_slice(),
+                )
+                .unwrap(),
+            )
+        })));
+        let links = TokenToEncryptedValueMap::from(HashMap::from_iter((0..5_u8).map(|i| {
+            (
+                Token::from([i; Token::LENGTH]),
+                EncryptedValue::try_from(vec![i; EncryptedValue::<LINK_LENGTH>::LENGTH].as_slice())
+                    .unwrap(),
+            )
+        })));
+
+        let bytes = serialize_index_dump(&entries, &links).unwrap();
+        let (entries_, links_) = deserialize_index_dump(&bytes).unwrap();
+        assert_eq!(*entries, *entries_);
+        assert_eq!(*links, *links_);
+
+        // Unknown versions are rejected.
+        let mut bytes = bytes;
+        bytes[0] = INDEX_DUMP_VERSION + 1;
+        assert!(deserialize_index_dump(&bytes).is_err());
+    }
+}
@@ -1,46 +1,167 @@
+#[cfg(feature = "rest-interface")]
+use std::sync::Arc;
 use std::{
+    cell::Cell,
     collections::{HashMap, HashSet},
     future::Future,
+    sync::PoisonError,
 };
 
+use cosmian_crypto_core::{reexport::rand_core::SeedableRng, CsRng};
 use cosmian_findex::{
-    ChainTable, Data, DxEnc, EntryTable, Error as FindexError, Findex, Index, IndexedValue,
-    IndexedValueToKeywordsMap, Keyword, KeywordToDataMap, Keywords, Label, UserKey, ENTRY_LENGTH,
-    LINK_LENGTH,
+    ChainTable, Data, DbInterface, DxEnc, EntryTable, Error as FindexError, Findex, Index,
+    IndexedValue, IndexedValueToKeywordsMap, Keyword, KeywordToDataMap, Keywords, Label, Tokens,
+    UserKey, ENTRY_LENGTH, LINK_LENGTH,
 };
 
+#[cfg(any(feature = "ffi", feature = "python", feature = "wasm"))]
+use crate::db_interfaces::custom::check_table_numbers;
 #[cfg(feature = "ffi")]
 use crate::db_interfaces::custom::ffi::{FfiChainBackend, FfiEntryBackend};
 #[cfg(feature = "python")]
 use crate::db_interfaces::custom::python::{PythonChainBackend, PythonEntryBackend};
 #[cfg(feature = "wasm")]
-use crate::db_interfaces::custom::wasm::{WasmChainBackend, WasmEntryBackend};
+use crate::db_interfaces::custom::wasm::{
+    WasmChainBackend, WasmEntryBackend, WasmSqliteChainBackend, WasmSqliteEntryBackend,
+};
+#[cfg(feature = "etcd-interface")]
+use crate::db_interfaces::etcd::{EtcdChainBackend, EtcdEntryBackend};
+#[cfg(feature = "grpc-interface")]
+use crate::db_interfaces::grpc::{GrpcChainBackend, GrpcEntryBackend};
+#[cfg(feature = "object-store-interface")]
+use crate::db_interfaces::object_store::{ObjectStoreChainBackend, ObjectStoreEntryBackend};
 #[cfg(feature = "redis-interface")]
 use crate::db_interfaces::redis::{RedisChainBackend, RedisEntryBackend};
 #[cfg(feature = "rest-interface")]
-use crate::db_interfaces::rest::{RestChainBackend, RestEntryBackend, RestParameters};
+use crate::db_interfaces::rest::{
+    RateLimiter, RestChainBackend, RestEntryBackend, RestParameters,
+    DEFAULT_RESPONSE_CACHE_CAPACITY,
+};
 #[cfg(feature = "sqlite-interface")]
 use crate::db_interfaces::sqlite::{SqlChainBackend, SqlEntryBackend};
-use crate::{db_interfaces::DbInterfaceError, Configuration};
+#[cfg(feature = "telemetry")]
+use crate::db_interfaces::traced::Traced;
+use crate::{
+    db_interfaces::DbInterfaceError,
+    instantiation::{
+        aliases::{aliases_to_associations, KeywordToAliasesMap},
+        chunking::{chunk_additions, ChunkedAddError},
+        compaction::{
+            partial_compacting_rate, table_statistics, IndexStatistics, KeptDump, KeptTokens,
+        },
+        dump::{dump_tables, restore_tables},
+        normalization::{restore_requested_keywords, KeywordNormalizer},
+        padding::{is_dummy, strip_dummies, strip_indexed_dummies, ChainPadding},
+        payload::{
+            attach_payloads, decrypt_payloads, group_by_location, strip_indexed_payloads,
+            strip_payloads, KeywordToPayloadsMap, PayloadKey,
+        },
+        scoring::{LocationToScoreMap, MatchMode},
+        sync::{sync_tables, SyncStatistics},
+        tombstone::{
+            find_tombstones, is_tombstone, strip_indexed_tombstones, strip_tombstones, tombstones,
+        },
+    },
+    Configuration,
+};
+
+/// Backend type of the instantiated Findex: when the `telemetry` feature is
+/// enabled, backends are wrapped to record a span around each database call.
+#[cfg(feature = "telemetry")]
+type Backend<B> = Traced<B>;
+#[cfg(not(feature = "telemetry"))]
+type Backend<B> = B;
+
+#[cfg(feature = "telemetry")]
+const fn backend<B>(
+    backend: B,
+    backend_name: &'static str,
+    table_name: &'static str,
+) -> Backend<B> {
+    Traced::new(backend, backend_name, table_name)
+}
+
+#[cfg(not(feature = "telemetry"))]
+const fn backend<B>(
+    backend: B,
+    _backend_name: &'static str,
+    _table_name: &'static str,
+) -> Backend<B> {
+    backend
+}
+
+/// Backend type of the instantiated Findex Entry Tables, able to reuse a token
+/// dump, see [`KeptDump`].
+type EntryBackend<B> = KeptDump<Backend<B>>;
+
+/// Findex instantiated with the given Entry and Chain Table backends.
+#[cfg(any(
+    feature = "sqlite-interface",
+    feature = "redis-interface",
+    feature = "rest-interface"
+))]
+type BackendFindex<E, C> = Findex<
+    DbInterfaceError,
+    EntryTable<ENTRY_LENGTH, EntryBackend<E>>,
+    ChainTable<LINK_LENGTH, Backend<C>>,
+>;
 
 /// Wrapper around Findex instantiations used for static dispatch.
+///
+/// In read-only mode, see [`Configuration::ReadOnly`], the functions modifying
+/// the index fail with [`DbInterfaceError::ReadOnly`] before any call to the
+/// backends.
+///
+/// If a [`KeywordNormalizer`] is given, see [`Configuration::Normalized`], the
+/// keywords are normalized before being added, deleted or searched. The search
+/// results are returned under the keywords requested.
+///
+/// If a [`ChainPadding`] is given, see [`Configuration::Padded`], the additions
+/// and deletions are padded with dummy values.
+#[derive(Debug)]
+pub struct InstantiatedFindex {
+    instance: Instance,
+    kept_entry_tokens: KeptTokens,
+    read_only: bool,
+    normalizer: Option<KeywordNormalizer>,
+    padding: Option<ChainPadding>,
+}
+
+/// Findex instantiation of each backend.
 #[derive(Debug)]
-pub enum InstantiatedFindex {
+enum Instance {
+    // The SQLite, Redis and REST instances are boxed since their backends are
+    // much larger than the other ones.
     #[cfg(feature = "sqlite-interface")]
-    Sqlite(
+    Sqlite(Box<BackendFindex<SqlEntryBackend, SqlChainBackend>>),
+
+    #[cfg(feature = "redis-interface")]
+    Redis(Box<BackendFindex<RedisEntryBackend, RedisChainBackend>>),
+
+    #[cfg(feature = "grpc-interface")]
+    Grpc(
         Findex<
             DbInterfaceError,
-            EntryTable<ENTRY_LENGTH, SqlEntryBackend>,
-            ChainTable<LINK_LENGTH, SqlChainBackend>,
+            EntryTable<ENTRY_LENGTH, EntryBackend<GrpcEntryBackend>>,
+            ChainTable<LINK_LENGTH, Backend<GrpcChainBackend>>,
         >,
     ),
 
-    #[cfg(feature = "redis-interface")]
-    Redis(
+    #[cfg(feature = "object-store-interface")]
+    ObjectStore(
         Findex<
             DbInterfaceError,
-            EntryTable<ENTRY_LENGTH, RedisEntryBackend>,
-            ChainTable<LINK_LENGTH, RedisChainBackend>,
+            EntryTable<ENTRY_LENGTH, EntryBackend<ObjectStoreEntryBackend>>,
+            ChainTable<LINK_LENGTH, Backend<ObjectStoreChainBackend>>,
+        >,
+    ),
+
+    #[cfg(feature = "etcd-interface")]
+    Etcd(
+        Findex<
+            DbInterfaceError,
+            EntryTable<ENTRY_LENGTH, EntryBackend<EtcdEntryBackend>>,
+            ChainTable<LINK_LENGTH, Backend<EtcdChainBackend>>,
         >,
     ),
 
@@ -48,8 +169,8 @@ pub enum InstantiatedFindex {
     Ffi(
         Findex<
             DbInterfaceError,
-            EntryTable<ENTRY_LENGTH, FfiEntryBackend>,
-            ChainTable<LINK_LENGTH, FfiChainBackend>,
+            EntryTable<ENTRY_LENGTH, EntryBackend<FfiEntryBackend>>,
+            ChainTable<LINK_LENGTH, Backend<FfiChainBackend>>,
         >,
     ),
 
@@ -57,96 +178,358 @@ pub enum InstantiatedFindex {
     Python(
         Findex<
             DbInterfaceError,
-            EntryTable<ENTRY_LENGTH, PythonEntryBackend>,
-            ChainTable<LINK_LENGTH, PythonChainBackend>,
+            EntryTable<ENTRY_LENGTH, EntryBackend<PythonEntryBackend>>,
+            ChainTable<LINK_LENGTH, Backend<PythonChainBackend>>,
         >,
     ),
     #[cfg(feature = "wasm")]
     Wasm(
         Findex<
             DbInterfaceError,
-            EntryTable<ENTRY_LENGTH, WasmEntryBackend>,
-            ChainTable<LINK_LENGTH, WasmChainBackend>,
+            EntryTable<ENTRY_LENGTH, EntryBackend<WasmEntryBackend>>,
+            ChainTable<LINK_LENGTH, Backend<WasmChainBackend>>,
         >,
     ),
 
-    #[cfg(feature = "rest-interface")]
-    Rest(
+    #[cfg(feature = "wasm")]
+    WasmSqlite(
         Findex<
             DbInterfaceError,
-            EntryTable<ENTRY_LENGTH, RestEntryBackend>,
-            ChainTable<LINK_LENGTH, RestChainBackend>,
+            EntryTable<ENTRY_LENGTH, EntryBackend<WasmSqliteEntryBackend>>,
+            ChainTable<LINK_LENGTH, Backend<WasmSqliteChainBackend>>,
         >,
     ),
+
+    #[cfg(feature = "rest-interface")]
+    Rest(Box<BackendFindex<RestEntryBackend, RestChainBackend>>),
 }
 
 impl InstantiatedFindex {
     /// Wrapper around Findex [`new`](Index::new) for static dispatch.
     pub async fn new(config: Configuration) -> Result<Self, DbInterfaceError> {
-        let findex = match config {
+        Self::instantiate(config, false).await
+    }
+
+    /// Instantiates Findex, opening read-only connections to the backends
+    /// supporting them if `read_only` is set.
+    async fn instantiate(config: Configuration, read_only: bool) -> Result<Self, DbInterfaceError> {
+        let kept_entry_tokens = KeptTokens::default();
+        let instance = match config {
+            Configuration::ReadOnly(config) => {
+                return Box::pin(Self::instantiate(*config, true)).await;
+            }
+
+            Configuration::Normalized(config, normalizer) => {
+                let mut findex = Box::pin(Self::instantiate(*config, read_only)).await?;
+                findex.set_keyword_normalizer(normalizer);
+                return Ok(findex);
+            }
+
+            Configuration::Padded(config, padding) => {
+                let mut findex = Box::pin(Self::instantiate(*config, read_only)).await?;
+                findex.set_chain_padding(Some(padding));
+                return Ok(findex);
+            }
+
             #[cfg(feature = "sqlite-interface")]
-            Configuration::Sqlite(entry_params, chain_params) => Self::Sqlite(Findex::new(
-                EntryTable::setup(SqlEntryBackend::new(&entry_params)?),
-                ChainTable::setup(SqlChainBackend::new(&chain_params)?),
-            )),
+            Configuration::Sqlite(entry_params, chain_params, key) => {
+                let (entry_backend, chain_backend) = if read_only {
+                    (
+                        SqlEntryBackend::open_read_only(&entry_params, key.as_deref())?,
+                        SqlChainBackend::open_read_only(&chain_params, key.as_deref())?,
+                    )
+                } else {
+                    (
+                        SqlEntryBackend::new(&entry_params, key.as_deref())?,
+                        SqlChainBackend::new(&chain_params, key.as_deref())?,
+                    )
+                };
+                Instance::Sqlite(Box::new(Findex::new(
+                    EntryTable::setup(KeptDump::new(
+                        backend(entry_backend, "sqlite", "entry"),
+                        kept_entry_tokens.clone(),
+                    )),
+                    ChainTable::setup(backend(chain_backend, "sqlite", "chain")),
+                )))
+            }
 
             #[cfg(feature = "redis-interface")]
-            Configuration::Redis(entry_params, chain_params) => Self::Redis(Findex::new(
-                EntryTable::setup(RedisEntryBackend::connect(&entry_params).await?),
-                ChainTable::setup(RedisChainBackend::connect(&chain_params).await?),
+            Configuration::Redis(entry_params, chain_params, pipeline_size) => {
+                Instance::Redis(Box::new(Findex::new(
+                    EntryTable::setup(KeptDump::new(
+                        backend(
+                            RedisEntryBackend::connect(&entry_params)
+                                .await?
+                                .with_pipeline_size(pipeline_size),
+                            "redis",
+                            "entry",
+                        ),
+                        kept_entry_tokens.clone(),
+                    )),
+                    ChainTable::setup(backend(
+                        RedisChainBackend::connect(&chain_params)
+                            .await?
+                            .with_pipeline_size(pipeline_size),
+                        "redis",
+                        "chain",
+                    )),
+                )))
+            }
+
+            #[cfg(feature = "grpc-interface")]
+            Configuration::Grpc(entry_params, chain_params) => Instance::Grpc(Findex::new(
+                EntryTable::setup(KeptDump::new(
+                    backend(
+                        GrpcEntryBackend::connect(&entry_params).await?,
+                        "grpc",
+                        "entry",
+                    ),
+                    kept_entry_tokens.clone(),
+                )),
+                ChainTable::setup(backend(
+                    GrpcChainBackend::connect(&chain_params).await?,
+                    "grpc",
+                    "chain",
+                )),
             )),
 
-            #[cfg(feature = "rest-interface")]
-            Configuration::Rest(token, entry_url, chain_url) => Self::Rest(Findex::new(
-                EntryTable::setup(RestEntryBackend::new(RestParameters::new(
-                    token.clone(),
-                    entry_url,
-                ))),
-                ChainTable::setup(RestChainBackend::new(RestParameters::new(token, chain_url))),
+            #[cfg(feature = "object-store-interface")]
+            Configuration::ObjectStore(entry_params, chain_params) => {
+                Instance::ObjectStore(Findex::new(
+                    EntryTable::setup(KeptDump::new(
+                        backend(
+                            ObjectStoreEntryBackend::connect(&entry_params)?,
+                            "object_store",
+                            "entry",
+                        ),
+                        kept_entry_tokens.clone(),
+                    )),
+                    ChainTable::setup(backend(
+                        ObjectStoreChainBackend::connect(&chain_params)?,
+                        "object_store",
+                        "chain",
+                    )),
+                ))
+            }
+
+            #[cfg(feature = "etcd-interface")]
+            Configuration::Etcd(entry_params, chain_params) => Instance::Etcd(Findex::new(
+                EntryTable::setup(KeptDump::new(
+                    backend(
+                        EtcdEntryBackend::connect(&entry_params).await?,
+                        "etcd",
+                        "entry",
+                    ),
+                    kept_entry_tokens.clone(),
+                )),
+                ChainTable::setup(backend(
+                    EtcdChainBackend::connect(&chain_params).await?,
+                    "etcd",
+                    "chain",
+                )),
             )),
 
+            #[cfg(feature = "rest-interface")]
+            Configuration::Rest(token, entry_url, chain_url, rate_limit, request_options) => {
+                // Both tables are served by the same tenant. Chain Table lines
+                // are never modified: their fetches are cached.
+                let rate_limiter = Arc::new(RateLimiter::new(rate_limit));
+                Instance::Rest(Box::new(Findex::new(
+                    EntryTable::setup(KeptDump::new(
+                        backend(
+                            RestEntryBackend::new(
+                                RestParameters::new(token.clone(), entry_url)
+                                    .with_rate_limiter(rate_limiter.clone())
+                                    .with_max_conflict_rounds(rate_limit.max_conflict_rounds)
+                                    .with_request_options(request_options.clone()),
+                            ),
+                            "rest",
+                            "entry",
+                        ),
+                        kept_entry_tokens.clone(),
+                    )),
+                    ChainTable::setup(backend(
+                        RestChainBackend::new(
+                            RestParameters::new(token, chain_url)
+                                .with_rate_limiter(rate_limiter)
+                                .with_request_options(request_options)
+                                .with_response_cache(DEFAULT_RESPONSE_CACHE_CAPACITY),
+                        ),
+                        "rest",
+                        "chain",
+                    )),
+                )))
+            }
+
             #[cfg(feature = "ffi")]
-            Configuration::Ffi(entry_params, chain_params) => Self::Ffi(Findex::new(
-                EntryTable::setup(FfiEntryBackend::new(entry_params)),
-                ChainTable::setup(FfiChainBackend::new(chain_params)),
-            )),
+            Configuration::Ffi(entry_params, chain_params) => {
+                check_table_numbers(entry_params.table_number, chain_params.table_number)?;
+                Instance::Ffi(Findex::new(
+                    EntryTable::setup(KeptDump::new(
+                        backend(FfiEntryBackend::new(entry_params), "ffi", "entry"),
+                        kept_entry_tokens.clone(),
+                    )),
+                    ChainTable::setup(backend(FfiChainBackend::new(chain_params), "ffi", "chain")),
+                ))
+            }
 
             #[cfg(feature = "python")]
-            Configuration::Python(entry_params, chain_params) => Self::Python(Findex::new(
-                EntryTable::setup(PythonEntryBackend::new(entry_params)),
-                ChainTable::setup(PythonChainBackend::new(chain_params)),
-            )),
+            Configuration::Python(entry_params, chain_params) => {
+                check_table_numbers(entry_params.table_number, chain_params.table_number)?;
+                Instance::Python(Findex::new(
+                    EntryTable::setup(KeptDump::new(
+                        backend(PythonEntryBackend::new(entry_params), "python", "entry"),
+                        kept_entry_tokens.clone(),
+                    )),
+                    ChainTable::setup(backend(
+                        PythonChainBackend::new(chain_params),
+                        "python",
+                        "chain",
+                    )),
+                ))
+            }
 
             #[cfg(feature = "wasm")]
-            Configuration::Wasm(entry_params, chain_params) => Self::Wasm(Findex::new(
-                EntryTable::setup(WasmEntryBackend::new(entry_params)),
-                ChainTable::setup(WasmChainBackend::new(chain_params)),
-            )),
+            Configuration::Wasm(entry_params, chain_params) => {
+                check_table_numbers(entry_params.table_number, chain_params.table_number)?;
+                Instance::Wasm(Findex::new(
+                    EntryTable::setup(KeptDump::new(
+                        backend(WasmEntryBackend::new(entry_params), "wasm", "entry"),
+                        kept_entry_tokens.clone(),
+                    )),
+                    ChainTable::setup(backend(
+                        WasmChainBackend::new(chain_params),
+                        "wasm",
+                        "chain",
+                    )),
+                ))
+            }
+
+            #[cfg(feature = "wasm")]
+            Configuration::WasmSqlite(entry_database, chain_database) => {
+                Instance::WasmSqlite(Findex::new(
+                    EntryTable::setup(KeptDump::new(
+                        backend(
+                            WasmSqliteEntryBackend::open(entry_database).await?,
+                            "wasm_sqlite",
+                            "entry",
+                        ),
+                        kept_entry_tokens.clone(),
+                    )),
+                    ChainTable::setup(backend(
+                        WasmSqliteChainBackend::open(chain_database).await?,
+                        "wasm_sqlite",
+                        "chain",
+                    )),
+                ))
+            }
         };
 
-        Ok(findex)
+        Ok(Self {
+            instance,
+            kept_entry_tokens,
+            read_only,
+            normalizer: None,
+            padding: None,
+        })
+    }
+
+    /// Sets the padding applied to the subsequent additions and deletions,
+    /// replacing the current one.
+    ///
+    /// The same padding should be used for the whole life of an index, see
+    /// [`ChainPadding`].
+    pub fn set_chain_padding(&mut self, padding: Option<ChainPadding>) {
+        self.padding = padding;
+    }
+
+    /// Returns the padding applied to the additions and deletions, if any.
+    #[must_use]
+    pub const fn chain_padding(&self) -> Option<ChainPadding> {
+        self.padding
+    }
+
+    /// Pads the given modifications with dummy values, if a padding is set.
+    fn pad(&self, modifications: IndexedValueToKeywordsMap) -> IndexedValueToKeywordsMap {
+        match &self.padding {
+            Some(padding) => padding.pad(modifications, &mut CsRng::from_entropy()),
+            None => modifications,
+        }
+    }
+
+    /// Sets the normalizer applied to the keywords of the subsequent
+    /// operations, replacing the current one.
+    ///
+    /// The same normalizer must be used for the whole life of an index, see
+    /// [`KeywordNormalizer`].
+    pub fn set_keyword_normalizer(&mut self, normalizer: KeywordNormalizer) {
+        self.normalizer = (!normalizer.is_identity()).then_some(normalizer);
+    }
+
+    /// Normalizes the keywords of the given associations, if a normalizer is
+    /// set.
+    fn normalize_associations(
+        &self,
+        associations: IndexedValueToKeywordsMap,
+    ) -> Result<IndexedValueToKeywordsMap, FindexError<DbInterfaceError>> {
+        match &self.normalizer {
+            Some(normalizer) => normalizer
+                .normalize_associations(&associations)
+                .map_err(FindexError::DbInterface),
+            None => Ok(associations),
+        }
+    }
+
+    /// Returns `true` if this Findex was instantiated in read-only mode.
+    #[must_use]
+    pub const fn is_read_only(&self) -> bool {
+        self.read_only
+    }
+
+    /// Returns an error if this Findex was instantiated in read-only mode.
+    const fn check_writable(&self) -> Result<(), DbInterfaceError> {
+        if self.read_only {
+            Err(DbInterfaceError::ReadOnly)
+        } else {
+            Ok(())
+        }
     }
 
     /// Wrapper around Findex [`keygen`](Index::keygen) for static dispatch.
     #[must_use]
     pub fn keygen(&self) -> UserKey {
-        match self {
+        match &self.instance {
             #[cfg(feature = "sqlite-interface")]
-            Self::Sqlite(findex) => findex.keygen(),
+            Instance::Sqlite(findex) => findex.keygen(),
             #[cfg(feature = "redis-interface")]
-            Self::Redis(findex) => findex.keygen(),
+            Instance::Redis(findex) => findex.keygen(),
+            #[cfg(feature = "grpc-interface")]
+            Instance::Grpc(findex) => findex.keygen(),
+            #[cfg(feature = "object-store-interface")]
+            Instance::ObjectStore(findex) => findex.keygen(),
+            #[cfg(feature = "etcd-interface")]
+            Instance::Etcd(findex) => findex.keygen(),
             #[cfg(feature = "ffi")]
-            Self::Ffi(findex) => findex.keygen(),
+            Instance::Ffi(findex) => findex.keygen(),
             #[cfg(feature = "python")]
-            Self::Python(findex) => findex.keygen(),
+            Instance::Python(findex) => findex.keygen(),
             #[cfg(feature = "wasm")]
-            Self::Wasm(findex) => findex.keygen(),
+            Instance::Wasm(findex) => findex.keygen(),
+            #[cfg(feature = "wasm")]
+            Instance::WasmSqlite(findex) => findex.keygen(),
             #[cfg(feature = "rest-interface")]
-            Self::Rest(findex) => findex.keygen(),
+            Instance::Rest(findex) => findex.keygen(),
         }
     }
 
     /// Wrapper around Findex [`search`](Index::search) for static dispatch.
+    ///
+    /// Dummy values inserted by the chain padding, see [`ChainPadding`], and
+    /// the tombstones written by [`soft_delete`](Self::soft_delete) are
+    /// stripped from both the intermediate and the final results. So are the
+    /// payloads attached by [`add_with_payloads`](Self::add_with_payloads):
+    /// only their locations are returned.
+    #[cfg_attr(feature = "telemetry", tracing::instrument(err, skip_all))]
     pub async fn search<
         F: Future<Output = Result<bool, String>>,
         Interrupt: Fn(HashMap<Keyword, HashSet<IndexedValue<Keyword, Data>>>) -> F,
@@ -157,69 +540,458 @@ impl InstantiatedFindex {
         keywords: Keywords,
         interrupt: &Interrupt,
     ) -> Result<KeywordToDataMap, FindexError<DbInterfaceError>> {
-        match self {
+        let interrupt = &|results: HashMap<Keyword, HashSet<IndexedValue<Keyword, Data>>>| {
+            interrupt(strip_indexed_payloads(results))
+        };
+        let results = self
+            .search_with_encrypted_payloads(key, label, keywords, interrupt)
+            .await?;
+        Ok(strip_payloads(results))
+    }
+
+    /// Searches the given keywords, stripping the dummy values and the
+    /// tombstones but not the payloads from the results.
+    async fn search_with_encrypted_payloads<
+        F: Future<Output = Result<bool, String>>,
+        Interrupt: Fn(HashMap<Keyword, HashSet<IndexedValue<Keyword, Data>>>) -> F,
+    >(
+        &self,
+        key: &UserKey,
+        label: &Label,
+        keywords: Keywords,
+        interrupt: &Interrupt,
+    ) -> Result<KeywordToDataMap, FindexError<DbInterfaceError>> {
+        let interrupt = &|results: HashMap<Keyword, HashSet<IndexedValue<Keyword, Data>>>| {
+            interrupt(strip_indexed_tombstones(strip_indexed_dummies(results)))
+        };
+        let results = self
+            .search_unstripped(key, label, keywords, interrupt)
+            .await?;
+        Ok(strip_tombstones(strip_dummies(results)))
+    }
+
+    /// Searches the given keywords without stripping the dummy values and the
+    /// tombstones from the results.
+    ///
+    /// The keywords are normalized first, if a normalizer is set: the
+    /// interrupt is given the results under the normalized keywords.
+    async fn search_unstripped<
+        F: Future<Output = Result<bool, String>>,
+        Interrupt: Fn(HashMap<Keyword, HashSet<IndexedValue<Keyword, Data>>>) -> F,
+    >(
+        &self,
+        key: &UserKey,
+        label: &Label,
+        keywords: Keywords,
+        interrupt: &Interrupt,
+    ) -> Result<KeywordToDataMap, FindexError<DbInterfaceError>> {
+        let Some(normalizer) = &self.normalizer else {
+            return self
+                .search_normalized(key, label, keywords, interrupt)
+                .await;
+        };
+        let requested = normalizer
+            .normalize_keywords(&keywords)
+            .map_err(FindexError::DbInterface)?;
+        let keywords = Keywords::from(requested.keys().cloned().collect::<HashSet<_>>());
+        let results = self
+            .search_normalized(key, label, keywords, interrupt)
+            .await?;
+        Ok(restore_requested_keywords(results, &requested))
+    }
+
+    /// Searches the given normalized keywords.
+    async fn search_normalized<
+        F: Future<Output = Result<bool, String>>,
+        Interrupt: Fn(HashMap<Keyword, HashSet<IndexedValue<Keyword, Data>>>) -> F,
+    >(
+        &self,
+        key: &UserKey,
+        label: &Label,
+        keywords: Keywords,
+        interrupt: &Interrupt,
+    ) -> Result<KeywordToDataMap, FindexError<DbInterfaceError>> {
+        match &self.instance {
             #[cfg(feature = "rest-interface")]
-            Self::Rest(findex) => findex.search(key, label, keywords, interrupt).await,
+            Instance::Rest(findex) => findex.search(key, label, keywords, interrupt).await,
             #[cfg(feature = "ffi")]
-            Self::Ffi(findex) => findex.search(key, label, keywords, interrupt).await,
+            Instance::Ffi(findex) => findex.search(key, label, keywords, interrupt).await,
             #[cfg(feature = "python")]
-            Self::Python(findex) => findex.search(key, label, keywords, interrupt).await,
+            Instance::Python(findex) => findex.search(key, label, keywords, interrupt).await,
             #[cfg(feature = "sqlite-interface")]
-            Self::Sqlite(findex) => findex.search(key, label, keywords, interrupt).await,
+            Instance::Sqlite(findex) => findex.search(key, label, keywords, interrupt).await,
             #[cfg(feature = "redis-interface")]
-            Self::Redis(findex) => findex.search(key, label, keywords, interrupt).await,
+            Instance::Redis(findex) => findex.search(key, label, keywords, interrupt).await,
+            #[cfg(feature = "grpc-interface")]
+            Instance::Grpc(findex) => findex.search(key, label, keywords, interrupt).await,
+            #[cfg(feature = "object-store-interface")]
+            Instance::ObjectStore(findex) => findex.search(key, label, keywords, interrupt).await,
+            #[cfg(feature = "etcd-interface")]
+            Instance::Etcd(findex) => findex.search(key, label, keywords, interrupt).await,
+            #[cfg(feature = "wasm")]
+            Instance::Wasm(findex) => findex.search(key, label, keywords, interrupt).await,
             #[cfg(feature = "wasm")]
-            Self::Wasm(findex) => findex.search(key, label, keywords, interrupt).await,
+            Instance::WasmSqlite(findex) => findex.search(key, label, keywords, interrupt).await,
         }
     }
 
     /// Wrapper around Findex [`add`](Index::add) for static dispatch.
+    ///
+    /// The additions are padded with dummy values if a padding is set, see
+    /// [`ChainPadding`].
+    #[cfg_attr(feature = "telemetry", tracing::instrument(err, skip_all))]
     pub async fn add(
         &self,
         key: &UserKey,
         label: &Label,
         additions: IndexedValueToKeywordsMap,
     ) -> Result<Keywords, FindexError<DbInterfaceError>> {
-        match self {
+        self.check_writable().map_err(FindexError::DbInterface)?;
+        let additions = self.pad(self.normalize_associations(additions)?);
+        match &self.instance {
             #[cfg(feature = "sqlite-interface")]
-            Self::Sqlite(findex) => findex.add(key, label, additions).await,
+            Instance::Sqlite(findex) => findex.add(key, label, additions).await,
             #[cfg(feature = "redis-interface")]
-            Self::Redis(findex) => findex.add(key, label, additions).await,
+            Instance::Redis(findex) => findex.add(key, label, additions).await,
+            #[cfg(feature = "grpc-interface")]
+            Instance::Grpc(findex) => findex.add(key, label, additions).await,
+            #[cfg(feature = "object-store-interface")]
+            Instance::ObjectStore(findex) => findex.add(key, label, additions).await,
+            #[cfg(feature = "etcd-interface")]
+            Instance::Etcd(findex) => findex.add(key, label, additions).await,
             #[cfg(feature = "ffi")]
-            Self::Ffi(findex) => findex.add(key, label, additions).await,
+            Instance::Ffi(findex) => findex.add(key, label, additions).await,
             #[cfg(feature = "python")]
-            Self::Python(findex) => findex.add(key, label, additions).await,
+            Instance::Python(findex) => findex.add(key, label, additions).await,
             #[cfg(feature = "wasm")]
-            Self::Wasm(findex) => findex.add(key, label, additions).await,
+            Instance::Wasm(findex) => findex.add(key, label, additions).await,
+            #[cfg(feature = "wasm")]
+            Instance::WasmSqlite(findex) => findex.add(key, label, additions).await,
             #[cfg(feature = "rest-interface")]
-            Self::Rest(findex) => findex.add(key, label, additions).await,
+            Instance::Rest(findex) => findex.add(key, label, additions).await,
         }
     }
 
     /// Wrapper around Findex [`delete`](Index::delete) for static dispatch.
+    ///
+    /// The deletions are padded with dummy values if a padding is set, see
+    /// [`ChainPadding`].
+    #[cfg_attr(feature = "telemetry", tracing::instrument(err, skip_all))]
     pub async fn delete(
         &self,
         key: &UserKey,
         label: &Label,
         deletions: IndexedValueToKeywordsMap,
     ) -> Result<Keywords, FindexError<DbInterfaceError>> {
-        match self {
+        self.check_writable().map_err(FindexError::DbInterface)?;
+        let deletions = self.pad(self.normalize_associations(deletions)?);
+        match &self.instance {
             #[cfg(feature = "sqlite-interface")]
-            Self::Sqlite(findex) => findex.delete(key, label, deletions).await,
+            Instance::Sqlite(findex) => findex.delete(key, label, deletions).await,
             #[cfg(feature = "redis-interface")]
-            Self::Redis(findex) => findex.delete(key, label, deletions).await,
+            Instance::Redis(findex) => findex.delete(key, label, deletions).await,
+            #[cfg(feature = "grpc-interface")]
+            Instance::Grpc(findex) => findex.delete(key, label, deletions).await,
+            #[cfg(feature = "object-store-interface")]
+            Instance::ObjectStore(findex) => findex.delete(key, label, deletions).await,
+            #[cfg(feature = "etcd-interface")]
+            Instance::Etcd(findex) => findex.delete(key, label, deletions).await,
             #[cfg(feature = "ffi")]
-            Self::Ffi(findex) => findex.delete(key, label, deletions).await,
+            Instance::Ffi(findex) => findex.delete(key, label, deletions).await,
             #[cfg(feature = "python")]
-            Self::Python(findex) => findex.delete(key, label, deletions).await,
+            Instance::Python(findex) => findex.delete(key, label, deletions).await,
+            #[cfg(feature = "wasm")]
+            Instance::Wasm(findex) => findex.delete(key, label, deletions).await,
             #[cfg(feature = "wasm")]
-            Self::Wasm(findex) => findex.delete(key, label, deletions).await,
+            Instance::WasmSqlite(findex) => findex.delete(key, label, deletions).await,
             #[cfg(feature = "rest-interface")]
-            Self::Rest(findex) => findex.delete(key, label, deletions).await,
+            Instance::Rest(findex) => findex.delete(key, label, deletions).await,
         }
     }
 
+    /// Indexes the given additions in chunks of at most `chunk_size`
+    /// associations, starting from the chunk `first_chunk`.
+    ///
+    /// Upon failure, the returned error holds the number of chunks indexed:
+    /// calling again this function with the same additions, the same chunk
+    /// size and this number as `first_chunk` resumes the indexation.
+    ///
+    /// Returns the new keywords added to the index by the chunks indexed.
+    pub async fn add_in_chunks(
+        &self,
+        key: &UserKey,
+        label: &Label,
+        additions: IndexedValueToKeywordsMap,
+        chunk_size: usize,
+        first_chunk: usize,
+    ) -> Result<Keywords, ChunkedAddError> {
+        let mut new_keywords = HashSet::new();
+        for (i, chunk) in chunk_additions(&additions, chunk_size)
+            .into_iter()
+            .enumerate()
+            .skip(first_chunk)
+        {
+            match self.add(key, label, chunk).await {
+                Ok(keywords) => new_keywords.extend(keywords.iter().cloned()),
+                Err(error) => {
+                    return Err(ChunkedAddError {
+                        indexed_chunks: i,
+                        new_keywords: Keywords::from(new_keywords),
+                        error,
+                    });
+                }
+            }
+        }
+        Ok(Keywords::from(new_keywords))
+    }
+
+    /// Deletes the given values, keeping a tombstone of each deleted location
+    /// until the next compaction.
+    ///
+    /// Until then, the deleted locations can be counted using
+    /// [`count_tombstones`](Self::count_tombstones) and restored using
+    /// [`restore_deleted`](Self::restore_deleted). Deleted pointers cannot be
+    /// restored.
+    pub async fn soft_delete(
+        &self,
+        key: &UserKey,
+        label: &Label,
+        deletions: IndexedValueToKeywordsMap,
+    ) -> Result<Keywords, FindexError<DbInterfaceError>> {
+        // Tombstones are indexed first for the deletion to be recoverable
+        // should it fail.
+        self.add(key, label, tombstones(&deletions)).await?;
+        self.delete(key, label, deletions).await
+    }
+
+    /// Returns the number of soft-deleted locations of each given keyword
+    /// that can still be restored.
+    ///
+    /// Only the locations directly indexed under the given keywords are
+    /// counted: aliases are not followed.
+    pub async fn count_tombstones(
+        &self,
+        key: &UserKey,
+        label: &Label,
+        keywords: Keywords,
+    ) -> Result<HashMap<Keyword, usize>, FindexError<DbInterfaceError>> {
+        let results = self
+            .search_unstripped(key, label, keywords, &|_| async { Ok(true) })
+            .await?;
+        Ok(find_tombstones(results)
+            .into_iter()
+            .map(|(keyword, tombstones)| (keyword, tombstones.deleted_locations.len()))
+            .collect())
+    }
+
+    /// Indexes again the locations soft-deleted from the given keywords since
+    /// the last compaction, and removes their tombstones.
+    ///
+    /// Returns the locations restored under each keyword.
+    pub async fn restore_deleted(
+        &self,
+        key: &UserKey,
+        label: &Label,
+        keywords: Keywords,
+    ) -> Result<KeywordToDataMap, FindexError<DbInterfaceError>> {
+        let results = self
+            .search_unstripped(key, label, keywords, &|_| async { Ok(true) })
+            .await?;
+
+        let mut additions = HashMap::<IndexedValue<Keyword, Data>, HashSet<Keyword>>::new();
+        let mut deletions = HashMap::<IndexedValue<Keyword, Data>, HashSet<Keyword>>::new();
+        let mut restored = HashMap::new();
+        for (keyword, tombstones) in find_tombstones(results) {
+            for location in &tombstones.deleted_locations {
+                additions
+                    .entry(IndexedValue::Data(location.clone()))
+                    .or_default()
+                    .insert(keyword.clone());
+            }
+            for tombstone in tombstones.tombstones {
+                deletions
+                    .entry(IndexedValue::Data(tombstone))
+                    .or_default()
+                    .insert(keyword.clone());
+            }
+            if !tombstones.deleted_locations.is_empty() {
+                restored.insert(keyword, tombstones.deleted_locations);
+            }
+        }
+
+        self.add(key, label, additions.into()).await?;
+        self.delete(key, label, deletions.into()).await?;
+        Ok(KeywordToDataMap::from_iter(restored))
+    }
+
+    /// Indexes the given locations along with their payload.
+    ///
+    /// Each payload is encrypted using the given payload key and stored in the
+    /// index along with its location. This key is independent from the Findex
+    /// key: the payloads can still be decrypted after compacting the index
+    /// with a new key. See [`search_with_payloads`](Self::search_with_payloads).
+    pub async fn add_with_payloads(
+        &self,
+        key: &UserKey,
+        label: &Label,
+        payload_key: &PayloadKey,
+        additions: HashMap<Data, (Vec<u8>, HashSet<Keyword>)>,
+    ) -> Result<Keywords, FindexError<DbInterfaceError>> {
+        let additions = attach_payloads(payload_key, additions, &mut CsRng::from_entropy())
+            .map_err(|e| FindexError::DbInterface(DbInterfaceError::CryptoCore(e)))?;
+        self.add(key, label, additions).await
+    }
+
+    /// Searches the given keywords and decrypts the payloads attached to the
+    /// locations found.
+    ///
+    /// Locations indexed without payload are returned with no payload. The
+    /// interrupt is given the locations found, without their payload.
+    pub async fn search_with_payloads<
+        F: Future<Output = Result<bool, String>>,
+        Interrupt: Fn(HashMap<Keyword, HashSet<IndexedValue<Keyword, Data>>>) -> F,
+    >(
+        &self,
+        key: &UserKey,
+        label: &Label,
+        payload_key: &PayloadKey,
+        keywords: Keywords,
+        interrupt: &Interrupt,
+    ) -> Result<KeywordToPayloadsMap, FindexError<DbInterfaceError>> {
+        let interrupt = &|results: HashMap<Keyword, HashSet<IndexedValue<Keyword, Data>>>| {
+            interrupt(strip_indexed_payloads(results))
+        };
+        let results = self
+            .search_with_encrypted_payloads(key, label, keywords, interrupt)
+            .await?;
+        decrypt_payloads(payload_key, results)
+            .map_err(|e| FindexError::DbInterface(DbInterfaceError::CryptoCore(e)))
+    }
+
+    /// Deletes the given locations from the given keywords, along with their
+    /// payloads.
+    ///
+    /// Since payloads are encrypted using a random nonce, the locations indexed
+    /// using [`add_with_payloads`](Self::add_with_payloads) cannot be deleted
+    /// using [`delete`](Self::delete): the data actually indexed under each
+    /// keyword is searched first. Locations indexed without payload are
+    /// deleted as well.
+    pub async fn delete_with_payloads(
+        &self,
+        key: &UserKey,
+        label: &Label,
+        deletions: HashMap<Data, HashSet<Keyword>>,
+    ) -> Result<Keywords, FindexError<DbInterfaceError>> {
+        let keywords = deletions
+            .values()
+            .flatten()
+            .cloned()
+            .collect::<HashSet<_>>();
+        let results = self
+            .search_with_encrypted_payloads(key, label, Keywords::from(keywords), &|_| async {
+                Ok(true)
+            })
+            .await?;
+
+        let mut indexed_deletions = HashMap::<IndexedValue<Keyword, Data>, HashSet<Keyword>>::new();
+        for (keyword, data) in results {
+            for (location, data) in group_by_location(data) {
+                if deletions
+                    .get(&location)
+                    .is_some_and(|keywords| keywords.contains(&keyword))
+                {
+                    for data in data {
+                        indexed_deletions
+                            .entry(IndexedValue::Data(data))
+                            .or_default()
+                            .insert(keyword.clone());
+                    }
+                }
+            }
+        }
+        self.delete(key, label, indexed_deletions.into()).await
+    }
+
+    /// Indexes each keyword as a pointer for its aliases: searching for an
+    /// alias then returns the data indexed under the keyword it points to.
+    ///
+    /// Returns the aliases that were not already indexed.
+    pub async fn add_aliases(
+        &self,
+        key: &UserKey,
+        label: &Label,
+        aliases: KeywordToAliasesMap,
+    ) -> Result<Keywords, FindexError<DbInterfaceError>> {
+        self.add(key, label, aliases_to_associations(aliases)).await
+    }
+
+    /// Removes the given aliases, i.e. the pointers from each alias to its
+    /// keyword.
+    pub async fn delete_aliases(
+        &self,
+        key: &UserKey,
+        label: &Label,
+        aliases: KeywordToAliasesMap,
+    ) -> Result<Keywords, FindexError<DbInterfaceError>> {
+        self.delete(key, label, aliases_to_associations(aliases))
+            .await
+    }
+
+    /// Searches the given keywords, following at most `max_depth` aliases
+    /// from each of them: a maximum depth of 0 only returns the data directly
+    /// indexed under the given keywords.
+    ///
+    /// The interrupt is still called at each level of the search graph and
+    /// can stop the search before the maximum depth is reached.
+    pub async fn search_with_max_depth<
+        F: Future<Output = Result<bool, String>>,
+        Interrupt: Fn(HashMap<Keyword, HashSet<IndexedValue<Keyword, Data>>>) -> F,
+    >(
+        &self,
+        key: &UserKey,
+        label: &Label,
+        keywords: Keywords,
+        max_depth: usize,
+        interrupt: &Interrupt,
+    ) -> Result<KeywordToDataMap, FindexError<DbInterfaceError>> {
+        let depth = Cell::new(0);
+        let interrupt = &|results: HashMap<Keyword, HashSet<IndexedValue<Keyword, Data>>>| {
+            let is_max_depth = depth.get() >= max_depth;
+            depth.set(depth.get() + 1);
+            let is_interrupted = interrupt(results);
+            async move { Ok::<_, String>(is_interrupted.await? || is_max_depth) }
+        };
+        self.search(key, label, keywords, interrupt).await
+    }
+
+    /// Searches the given keywords and returns the number of keywords matched
+    /// by each location found, for the application to rank them.
+    ///
+    /// In [`MatchMode::Intersection`], only the locations matching all the
+    /// given keywords are returned.
+    pub async fn search_scored<
+        F: Future<Output = Result<bool, String>>,
+        Interrupt: Fn(HashMap<Keyword, HashSet<IndexedValue<Keyword, Data>>>) -> F,
+    >(
+        &self,
+        key: &UserKey,
+        label: &Label,
+        keywords: Keywords,
+        mode: MatchMode,
+        interrupt: &Interrupt,
+    ) -> Result<LocationToScoreMap, FindexError<DbInterfaceError>> {
+        let results = self.search(key, label, keywords, interrupt).await?;
+        Ok(mode.score(&results))
+    }
+
     /// Wrapper around Findex [`compact`](Findex::compact) for static dispatch.
+    ///
+    /// Dummy values are not passed to the data filter and are always kept.
+    /// Tombstones are not passed either and are always removed: the locations
+    /// soft-deleted before the compaction cannot be restored anymore. The data
+    /// filter is given the locations without their payload; the payloads of
+    /// the locations it keeps are kept as is.
+    #[cfg_attr(feature = "telemetry", tracing::instrument(err, skip_all))]
     pub async fn compact<
         F: Future<Output = Result<HashSet<Data>, String>>,
         Filter: Fn(HashSet<Data>) -> F,
@@ -232,9 +1004,31 @@ impl InstantiatedFindex {
         compacting_rate: f64,
         data_filter: &Filter,
     ) -> Result<(), FindexError<DbInterfaceError>> {
-        match self {
+        self.check_writable().map_err(FindexError::DbInterface)?;
+        let data_filter = &|data: HashSet<Data>| {
+            let (dummies, data): (HashSet<_>, HashSet<_>) = data
+                .into_iter()
+                .filter(|data| !is_tombstone(data))
+                .partition(|data| is_dummy(data));
+            let mut locations = group_by_location(data);
+            let filtered_locations = data_filter(locations.keys().cloned().collect());
+            async move {
+                let mut filtered_data = HashSet::new();
+                for location in filtered_locations.await? {
+                    match locations.remove(&location) {
+                        Some(data) => filtered_data.extend(data),
+                        None => {
+                            filtered_data.insert(location);
+                        }
+                    }
+                }
+                filtered_data.extend(dummies);
+                Ok(filtered_data)
+            }
+        };
+        match &self.instance {
             #[cfg(feature = "sqlite-interface")]
-            Self::Sqlite(findex) => {
+            Instance::Sqlite(findex) => {
                 findex
                     .compact(
                         old_key,
@@ -247,7 +1041,46 @@ impl InstantiatedFindex {
                     .await
             }
             #[cfg(feature = "redis-interface")]
-            Self::Redis(findex) => {
+            Instance::Redis(findex) => {
+                findex
+                    .compact(
+                        old_key,
+                        new_key,
+                        old_label,
+                        new_label,
+                        compacting_rate,
+                        data_filter,
+                    )
+                    .await
+            }
+            #[cfg(feature = "grpc-interface")]
+            Instance::Grpc(findex) => {
+                findex
+                    .compact(
+                        old_key,
+                        new_key,
+                        old_label,
+                        new_label,
+                        compacting_rate,
+                        data_filter,
+                    )
+                    .await
+            }
+            #[cfg(feature = "object-store-interface")]
+            Instance::ObjectStore(findex) => {
+                findex
+                    .compact(
+                        old_key,
+                        new_key,
+                        old_label,
+                        new_label,
+                        compacting_rate,
+                        data_filter,
+                    )
+                    .await
+            }
+            #[cfg(feature = "etcd-interface")]
+            Instance::Etcd(findex) => {
                 findex
                     .compact(
                         old_key,
@@ -260,7 +1093,7 @@ impl InstantiatedFindex {
                     .await
             }
             #[cfg(feature = "ffi")]
-            Self::Ffi(findex) => {
+            Instance::Ffi(findex) => {
                 findex
                     .compact(
                         old_key,
@@ -273,7 +1106,20 @@ impl InstantiatedFindex {
                     .await
             }
             #[cfg(feature = "python")]
-            Self::Python(findex) => {
+            Instance::Python(findex) => {
+                findex
+                    .compact(
+                        old_key,
+                        new_key,
+                        old_label,
+                        new_label,
+                        compacting_rate,
+                        data_filter,
+                    )
+                    .await
+            }
+            #[cfg(feature = "wasm")]
+            Instance::Wasm(findex) => {
                 findex
                     .compact(
                         old_key,
@@ -286,7 +1132,7 @@ impl InstantiatedFindex {
                     .await
             }
             #[cfg(feature = "wasm")]
-            Self::Wasm(findex) => {
+            Instance::WasmSqlite(findex) => {
                 findex
                     .compact(
                         old_key,
@@ -299,7 +1145,7 @@ impl InstantiatedFindex {
                     .await
             }
             #[cfg(feature = "rest-interface")]
-            Self::Rest(findex) => {
+            Instance::Rest(findex) => {
                 findex
                     .compact(
                         old_key,
@@ -313,4 +1159,498 @@ impl InstantiatedFindex {
             }
         }
     }
+
+    /// Compacts the given number of index entries, randomly selected.
+    ///
+    /// This allows compacting huge indexes incrementally: each call compacts
+    /// the chains of at most `n_entries` entries instead of the whole index.
+    /// Since entries are drawn at random with replacement, a run may draw the
+    /// same entry twice, and a given entry may be compacted several times
+    /// before all the entries have been compacted. See
+    /// [`compact`](Self::compact) for the other parameters.
+    #[cfg_attr(feature = "telemetry", tracing::instrument(err, skip_all))]
+    pub async fn compact_partial<
+        F: Future<Output = Result<HashSet<Data>, String>>,
+        Filter: Fn(HashSet<Data>) -> F,
+    >(
+        &self,
+        old_key: &UserKey,
+        new_key: &UserKey,
+        old_label: &Label,
+        new_label: &Label,
+        n_entries: usize,
+        data_filter: &Filter,
+    ) -> Result<(), FindexError<DbInterfaceError>> {
+        self.check_writable().map_err(FindexError::DbInterface)?;
+        let entry_tokens = self.dump_tokens().await.map_err(FindexError::DbInterface)?;
+        let compacting_rate = partial_compacting_rate(n_entries, entry_tokens.len());
+        // The compaction reuses this dump instead of scanning the Entry Table
+        // again. The tokens are discarded if it fails before using them.
+        *self
+            .kept_entry_tokens
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner) = Some(entry_tokens);
+        let res = self
+            .compact(
+                old_key,
+                new_key,
+                old_label,
+                new_label,
+                compacting_rate,
+                data_filter,
+            )
+            .await;
+        self.kept_entry_tokens
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .take();
+        res
+    }
+
+    /// Counts the Entry Table and Chain Table lines of the index.
+    ///
+    /// Both backends need to be able to dump their tokens.
+    pub async fn statistics(&self) -> Result<IndexStatistics, DbInterfaceError> {
+        match &self.instance {
+            #[cfg(feature = "sqlite-interface")]
+            Instance::Sqlite(findex) => {
+                table_statistics(
+                    &*findex.findex_graph.findex_mm.entry_table,
+                    &*findex.findex_graph.findex_mm.chain_table,
+                )
+                .await
+            }
+            #[cfg(feature = "redis-interface")]
+            Instance::Redis(findex) => {
+                table_statistics(
+                    &*findex.findex_graph.findex_mm.entry_table,
+                    &*findex.findex_graph.findex_mm.chain_table,
+                )
+                .await
+            }
+            #[cfg(feature = "grpc-interface")]
+            Instance::Grpc(findex) => {
+                table_statistics(
+                    &*findex.findex_graph.findex_mm.entry_table,
+                    &*findex.findex_graph.findex_mm.chain_table,
+                )
+                .await
+            }
+            #[cfg(feature = "object-store-interface")]
+            Instance::ObjectStore(findex) => {
+                table_statistics(
+                    &*findex.findex_graph.findex_mm.entry_table,
+                    &*findex.findex_graph.findex_mm.chain_table,
+                )
+                .await
+            }
+            #[cfg(feature = "etcd-interface")]
+            Instance::Etcd(findex) => {
+                table_statistics(
+                    &*findex.findex_graph.findex_mm.entry_table,
+                    &*findex.findex_graph.findex_mm.chain_table,
+                )
+                .await
+            }
+            #[cfg(feature = "ffi")]
+            Instance::Ffi(findex) => {
+                table_statistics(
+                    &*findex.findex_graph.findex_mm.entry_table,
+                    &*findex.findex_graph.findex_mm.chain_table,
+                )
+                .await
+            }
+            #[cfg(feature = "python")]
+            Instance::Python(findex) => {
+                table_statistics(
+                    &*findex.findex_graph.findex_mm.entry_table,
+                    &*findex.findex_graph.findex_mm.chain_table,
+                )
+                .await
+            }
+            #[cfg(feature = "wasm")]
+            Instance::Wasm(findex) => {
+                table_statistics(
+                    &*findex.findex_graph.findex_mm.entry_table,
+                    &*findex.findex_graph.findex_mm.chain_table,
+                )
+                .await
+            }
+            #[cfg(feature = "wasm")]
+            Instance::WasmSqlite(findex) => {
+                table_statistics(
+                    &*findex.findex_graph.findex_mm.entry_table,
+                    &*findex.findex_graph.findex_mm.chain_table,
+                )
+                .await
+            }
+            #[cfg(feature = "rest-interface")]
+            Instance::Rest(findex) => {
+                table_statistics(
+                    &*findex.findex_graph.findex_mm.entry_table,
+                    &*findex.findex_graph.findex_mm.chain_table,
+                )
+                .await
+            }
+        }
+    }
+
+    /// Lists the tokens of the Entry Table, i.e. one token per indexed
+    /// keyword.
+    ///
+    /// Tokens are derived from the keywords using the Findex key and label:
+    /// they do not leak the keywords and can be used by audit or migration
+    /// tools to enumerate the index lines.
+    pub async fn dump_tokens(&self) -> Result<Tokens, DbInterfaceError> {
+        match &self.instance {
+            #[cfg(feature = "sqlite-interface")]
+            Instance::Sqlite(findex) => {
+                DbInterface::dump_tokens(&*findex.findex_graph.findex_mm.entry_table).await
+            }
+            #[cfg(feature = "redis-interface")]
+            Instance::Redis(findex) => {
+                DbInterface::dump_tokens(&*findex.findex_graph.findex_mm.entry_table).await
+            }
+            #[cfg(feature = "grpc-interface")]
+            Instance::Grpc(findex) => {
+                DbInterface::dump_tokens(&*findex.findex_graph.findex_mm.entry_table).await
+            }
+            #[cfg(feature = "object-store-interface")]
+            Instance::ObjectStore(findex) => {
+                DbInterface::dump_tokens(&*findex.findex_graph.findex_mm.entry_table).await
+            }
+            #[cfg(feature = "etcd-interface")]
+            Instance::Etcd(findex) => {
+                DbInterface::dump_tokens(&*findex.findex_graph.findex_mm.entry_table).await
+            }
+            #[cfg(feature = "ffi")]
+            Instance::Ffi(findex) => {
+                DbInterface::dump_tokens(&*findex.findex_graph.findex_mm.entry_table).await
+            }
+            #[cfg(feature = "python")]
+            Instance::Python(findex) => {
+                DbInterface::dump_tokens(&*findex.findex_graph.findex_mm.entry_table).await
+            }
+            #[cfg(feature = "wasm")]
+            Instance::Wasm(findex) => {
+                DbInterface::dump_tokens(&*findex.findex_graph.findex_mm.entry_table).await
+            }
+            #[cfg(feature = "wasm")]
+            Instance::WasmSqlite(findex) => {
+                DbInterface::dump_tokens(&*findex.findex_graph.findex_mm.entry_table).await
+            }
+            #[cfg(feature = "rest-interface")]
+            Instance::Rest(findex) => {
+                DbInterface::dump_tokens(&*findex.findex_graph.findex_mm.entry_table).await
+            }
+        }
+    }
+
+    /// Exports the whole index (Entry Table and Chain Table lines) into a
+    /// versioned binary dump.
+    ///
+    /// Both backends need to be able to dump their tokens. The dump can be
+    /// loaded in any other backend using [`restore`](Self::restore).
+    pub async fn dump(&self) -> Result<Vec<u8>, DbInterfaceError> {
+        match &self.instance {
+            #[cfg(feature = "sqlite-interface")]
+            Instance::Sqlite(findex) => {
+                dump_tables(
+                    &*findex.findex_graph.findex_mm.entry_table,
+                    &*findex.findex_graph.findex_mm.chain_table,
+                )
+                .await
+            }
+            #[cfg(feature = "redis-interface")]
+            Instance::Redis(findex) => {
+                dump_tables(
+                    &*findex.findex_graph.findex_mm.entry_table,
+                    &*findex.findex_graph.findex_mm.chain_table,
+                )
+                .await
+            }
+            #[cfg(feature = "grpc-interface")]
+            Instance::Grpc(findex) => {
+                dump_tables(
+                    &*findex.findex_graph.findex_mm.entry_table,
+                    &*findex.findex_graph.findex_mm.chain_table,
+                )
+                .await
+            }
+            #[cfg(feature = "object-store-interface")]
+            Instance::ObjectStore(findex) => {
+                dump_tables(
+                    &*findex.findex_graph.findex_mm.entry_table,
+                    &*findex.findex_graph.findex_mm.chain_table,
+                )
+                .await
+            }
+            #[cfg(feature = "etcd-interface")]
+            Instance::Etcd(findex) => {
+                dump_tables(
+                    &*findex.findex_graph.findex_mm.entry_table,
+                    &*findex.findex_graph.findex_mm.chain_table,
+                )
+                .await
+            }
+            #[cfg(feature = "ffi")]
+            Instance::Ffi(findex) => {
+                dump_tables(
+                    &*findex.findex_graph.findex_mm.entry_table,
+                    &*findex.findex_graph.findex_mm.chain_table,
+                )
+                .await
+            }
+            #[cfg(feature = "python")]
+            Instance::Python(findex) => {
+                dump_tables(
+                    &*findex.findex_graph.findex_mm.entry_table,
+                    &*findex.findex_graph.findex_mm.chain_table,
+                )
+                .await
+            }
+            #[cfg(feature = "wasm")]
+            Instance::Wasm(findex) => {
+                dump_tables(
+                    &*findex.findex_graph.findex_mm.entry_table,
+                    &*findex.findex_graph.findex_mm.chain_table,
+                )
+                .await
+            }
+            #[cfg(feature = "wasm")]
+            Instance::WasmSqlite(findex) => {
+                dump_tables(
+                    &*findex.findex_graph.findex_mm.entry_table,
+                    &*findex.findex_graph.findex_mm.chain_table,
+                )
+                .await
+            }
+            #[cfg(feature = "rest-interface")]
+            Instance::Rest(findex) => {
+                dump_tables(
+                    &*findex.findex_graph.findex_mm.entry_table,
+                    &*findex.findex_graph.findex_mm.chain_table,
+                )
+                .await
+            }
+        }
+    }
+
+    /// Imports an index dump produced by [`dump`](Self::dump).
+    ///
+    /// The dumped lines are inserted as is, the target tables should therefore
+    /// be empty. The restored index can be used with the key and label used to
+    /// build the original index.
+    pub async fn restore(&self, bytes: &[u8]) -> Result<(), DbInterfaceError> {
+        self.check_writable()?;
+        match &self.instance {
+            #[cfg(feature = "sqlite-interface")]
+            Instance::Sqlite(findex) => {
+                restore_tables(
+                    &*findex.findex_graph.findex_mm.entry_table,
+                    &*findex.findex_graph.findex_mm.chain_table,
+                    bytes,
+                )
+                .await
+            }
+            #[cfg(feature = "redis-interface")]
+            Instance::Redis(findex) => {
+                restore_tables(
+                    &*findex.findex_graph.findex_mm.entry_table,
+                    &*findex.findex_graph.findex_mm.chain_table,
+                    bytes,
+                )
+                .await
+            }
+            #[cfg(feature = "grpc-interface")]
+            Instance::Grpc(findex) => {
+                restore_tables(
+                    &*findex.findex_graph.findex_mm.entry_table,
+                    &*findex.findex_graph.findex_mm.chain_table,
+                    bytes,
+                )
+                .await
+            }
+            #[cfg(feature = "object-store-interface")]
+            Instance::ObjectStore(findex) => {
+                restore_tables(
+                    &*findex.findex_graph.findex_mm.entry_table,
+                    &*findex.findex_graph.findex_mm.chain_table,
+                    bytes,
+                )
+                .await
+            }
+            #[cfg(feature = "etcd-interface")]
+            Instance::Etcd(findex) => {
+                restore_tables(
+                    &*findex.findex_graph.findex_mm.entry_table,
+                    &*findex.findex_graph.findex_mm.chain_table,
+                    bytes,
+                )
+                .await
+            }
+            #[cfg(feature = "ffi")]
+            Instance::Ffi(findex) => {
+                restore_tables(
+                    &*findex.findex_graph.findex_mm.entry_table,
+                    &*findex.findex_graph.findex_mm.chain_table,
+                    bytes,
+                )
+                .await
+            }
+            #[cfg(feature = "python")]
+            Instance::Python(findex) => {
+                restore_tables(
+                    &*findex.findex_graph.findex_mm.entry_table,
+                    &*findex.findex_graph.findex_mm.chain_table,
+                    bytes,
+                )
+                .await
+            }
+            #[cfg(feature = "wasm")]
+            Instance::Wasm(findex) => {
+                restore_tables(
+                    &*findex.findex_graph.findex_mm.entry_table,
+                    &*findex.findex_graph.findex_mm.chain_table,
+                    bytes,
+                )
+                .await
+            }
+            #[cfg(feature = "wasm")]
+            Instance::WasmSqlite(findex) => {
+                restore_tables(
+                    &*findex.findex_graph.findex_mm.entry_table,
+                    &*findex.findex_graph.findex_mm.chain_table,
+                    bytes,
+                )
+                .await
+            }
+            #[cfg(feature = "rest-interface")]
+            Instance::Rest(findex) => {
+                restore_tables(
+                    &*findex.findex_graph.findex_mm.entry_table,
+                    &*findex.findex_graph.findex_mm.chain_table,
+                    bytes,
+                )
+                .await
+            }
+        }
+    }
+
+    /// Writes the lines of an index dump produced by [`dump`](Self::dump)
+    /// which are missing from this index or whose value differs.
+    ///
+    /// Contrary to [`restore`](Self::restore), the index does not need to be
+    /// empty: lines already holding the dumped value are not written again,
+    /// and lines absent from the dump are left untouched.
+    pub async fn merge(&self, bytes: &[u8]) -> Result<SyncStatistics, DbInterfaceError> {
+        self.check_writable()?;
+        match &self.instance {
+            #[cfg(feature = "sqlite-interface")]
+            Instance::Sqlite(findex) => {
+                sync_tables(
+                    &*findex.findex_graph.findex_mm.entry_table,
+                    &*findex.findex_graph.findex_mm.chain_table,
+                    bytes,
+                )
+                .await
+            }
+            #[cfg(feature = "redis-interface")]
+            Instance::Redis(findex) => {
+                sync_tables(
+                    &*findex.findex_graph.findex_mm.entry_table,
+                    &*findex.findex_graph.findex_mm.chain_table,
+                    bytes,
+                )
+                .await
+            }
+            #[cfg(feature = "grpc-interface")]
+            Instance::Grpc(findex) => {
+                sync_tables(
+                    &*findex.findex_graph.findex_mm.entry_table,
+                    &*findex.findex_graph.findex_mm.chain_table,
+                    bytes,
+                )
+                .await
+            }
+            #[cfg(feature = "object-store-interface")]
+            Instance::ObjectStore(findex) => {
+                sync_tables(
+                    &*findex.findex_graph.findex_mm.entry_table,
+                    &*findex.findex_graph.findex_mm.chain_table,
+                    bytes,
+                )
+                .await
+            }
+            #[cfg(feature = "etcd-interface")]
+            Instance::Etcd(findex) => {
+                sync_tables(
+                    &*findex.findex_graph.findex_mm.entry_table,
+                    &*findex.findex_graph.findex_mm.chain_table,
+                    bytes,
+                )
+                .await
+            }
+            #[cfg(feature = "ffi")]
+            Instance::Ffi(findex) => {
+                sync_tables(
+                    &*findex.findex_graph.findex_mm.entry_table,
+                    &*findex.findex_graph.findex_mm.chain_table,
+                    bytes,
+                )
+                .await
+            }
+            #[cfg(feature = "python")]
+            Instance::Python(findex) => {
+                sync_tables(
+                    &*findex.findex_graph.findex_mm.entry_table,
+                    &*findex.findex_graph.findex_mm.chain_table,
+                    bytes,
+                )
+                .await
+            }
+            #[cfg(feature = "wasm")]
+            Instance::Wasm(findex) => {
+                sync_tables(
+                    &*findex.findex_graph.findex_mm.entry_table,
+                    &*findex.findex_graph.findex_mm.chain_table,
+                    bytes,
+                )
+                .await
+            }
+            #[cfg(feature = "wasm")]
+            Instance::WasmSqlite(findex) => {
+                sync_tables(
+                    &*findex.findex_graph.findex_mm.entry_table,
+                    &*findex.findex_graph.findex_mm.chain_table,
+                    bytes,
+                )
+                .await
+            }
+            #[cfg(feature = "rest-interface")]
+            Instance::Rest(findex) => {
+                sync_tables(
+                    &*findex.findex_graph.findex_mm.entry_table,
+                    &*findex.findex_graph.findex_mm.chain_table,
+                    bytes,
+                )
+                .await
+            }
+        }
+    }
+
+    /// Synchronizes the index stored in the target backend with the one
+    /// stored in the source backend.
+    ///
+    /// Only the source lines missing from the target or whose value differs
+    /// are written, see [`merge`](Self::merge). Both indexes need to be used
+    /// with the same key and label. The source backend needs to be able to
+    /// dump its tokens.
+    pub async fn sync(
+        source_config: Configuration,
+        target_config: Configuration,
+    ) -> Result<SyncStatistics, DbInterfaceError> {
+        let dump = Self::new(source_config).await?.dump().await?;
+        Self::new(target_config).await?.merge(&dump).await
+    }
 }
@@ -50,8 +50,28 @@
 //! This also has the advantage of gathering all instantiation information in a
 //! single place.
 
+mod aliases;
+mod chunking;
+mod compaction;
 mod db_config;
+mod dump;
 mod findex;
+mod normalization;
+mod padding;
+mod payload;
+mod scoring;
+mod sync;
+mod tombstone;
 
+pub use aliases::KeywordToAliasesMap;
+pub use chunking::{ChunkedAddError, DEFAULT_CHUNK_SIZE};
+pub use compaction::IndexStatistics;
 pub use db_config::Configuration;
+pub use dump::{deserialize_index_dump, serialize_index_dump, INDEX_DUMP_VERSION};
 pub use findex::InstantiatedFindex;
+pub use normalization::{KeywordNormalizer, NormalizeKeyword};
+pub use padding::{is_dummy, ChainPadding, DUMMY_DATA_PREFIX};
+pub use payload::{KeywordToPayloadsMap, PayloadKey, PAYLOAD_DATA_PREFIX};
+pub use scoring::{LocationToScoreMap, MatchMode};
+pub use sync::SyncStatistics;
+pub use tombstone::{is_tombstone, TOMBSTONE_DATA_PREFIX};
@@ -0,0 +1,230 @@
+//! Keyword normalization, e.g. for case-insensitive search.
+//!
+//! Findex matches keywords byte for byte: `"Café"`, `"café"` and `"CAFÉ"` are
+//! three different keywords, and so are the composed and decomposed forms of
+//! `"é"`. A [`KeywordNormalizer`] given upon instantiation, see
+//! [`Configuration::with_keyword_normalizer`](crate::Configuration::with_keyword_normalizer),
+//! is applied to the keywords added, deleted and searched, so that they all
+//! resolve to the same keyword whatever the client language.
+//!
+//! The normalization steps are applied in this order:
+//!
+//! 1. lowercasing of the UTF-8 keywords;
+//! 2. Unicode NFC normalization of the UTF-8 keywords;
+//! 3. the user callback, e.g. a stemmer.
+//!
+//! Keywords that are not valid UTF-8 are only given to the callback.
+//!
+//! The same normalizer must be used for the whole life of an index: keywords
+//! indexed with another normalization cannot be found anymore.
+
+use std::{
+    collections::{HashMap, HashSet},
+    fmt::{self, Debug},
+    sync::Arc,
+};
+
+use cosmian_findex::{
+    Data, IndexedValue, IndexedValueToKeywordsMap, Keyword, KeywordToDataMap, Keywords,
+};
+use unicode_normalization::UnicodeNormalization;
+
+use crate::db_interfaces::DbInterfaceError;
+
+/// User normalization of a keyword. It must be deterministic.
+pub type NormalizeKeyword = dyn Fn(&[u8]) -> Result<Vec<u8>, String> + Send + Sync;
+
+/// Normalization applied to the keywords before they are indexed or searched.
+///
+/// The default normalizer leaves the keywords unchanged.
+#[derive(Clone, Default)]
+pub struct KeywordNormalizer {
+    lowercase: bool,
+    nfc: bool,
+    callback: Option<Arc<NormalizeKeyword>>,
+}
+
+impl KeywordNormalizer {
+    /// Lowercases the UTF-8 keywords.
+    #[must_use]
+    pub const fn with_lowercase(mut self) -> Self {
+        self.lowercase = true;
+        self
+    }
+
+    /// Converts the UTF-8 keywords to the Unicode Normalization Form C.
+    #[must_use]
+    pub const fn with_nfc(mut self) -> Self {
+        self.nfc = true;
+        self
+    }
+
+    /// Applies the given callback after the built-in normalizations.
+    #[must_use]
+    pub fn with_callback(mut self, callback: Arc<NormalizeKeyword>) -> Self {
+        self.callback = Some(callback);
+        self
+    }
+
+    /// Returns `true` if this normalizer leaves the keywords unchanged.
+    #[must_use]
+    pub const fn is_identity(&self) -> bool {
+        !self.lowercase && !self.nfc && self.callback.is_none()
+    }
+
+    /// Returns the normalization of the given keyword.
+    pub fn normalize(&self, keyword: &Keyword) -> Result<Keyword, DbInterfaceError> {
+        let mut bytes = keyword.to_vec();
+        if self.lowercase || self.nfc {
+            if let Ok(mut text) = String::from_utf8(bytes.clone()) {
+                if self.lowercase {
+                    text = text.to_lowercase();
+                }
+                if self.nfc {
+                    text = text.nfc().collect();
+                }
+                bytes = text.into_bytes();
+            }
+        }
+        if let Some(callback) = &self.callback {
+            bytes = callback(&bytes)
+                .map_err(|e| DbInterfaceError::Other(format!("cannot normalize keyword: {e}")))?;
+        }
+        Ok(Keyword::from(bytes))
+    }
+
+    /// Normalizes the given keywords and returns the keywords requested for
+    /// each normalized keyword.
+    pub(crate) fn normalize_keywords(
+        &self,
+        keywords: &Keywords,
+    ) -> Result<HashMap<Keyword, HashSet<Keyword>>, DbInterfaceError> {
+        let mut requested = HashMap::<Keyword, HashSet<Keyword>>::new();
+        for keyword in keywords.iter() {
+            requested
+                .entry(self.normalize(keyword)?)
+                .or_default()
+                .insert(keyword.clone());
+        }
+        Ok(requested)
+    }
+
+    /// Normalizes the keywords of the given associations, including the
+    /// keywords pointed to.
+    pub(crate) fn normalize_associations(
+        &self,
+        associations: &IndexedValueToKeywordsMap,
+    ) -> Result<IndexedValueToKeywordsMap, DbInterfaceError> {
+        let mut normalized = HashMap::<IndexedValue<Keyword, Data>, HashSet<Keyword>>::new();
+        for (value, keywords) in associations.iter() {
+            let value = match value {
+                IndexedValue::Pointer(keyword) => IndexedValue::Pointer(self.normalize(keyword)?),
+                data @ IndexedValue::Data(_) => data.clone(),
+            };
+            let keywords = keywords
+                .iter()
+                .map(|keyword| self.normalize(keyword))
+                .collect::<Result<HashSet<_>, _>>()?;
+            normalized.entry(value).or_default().extend(keywords);
+        }
+        Ok(normalized.into())
+    }
+}
+
+impl Debug for KeywordNormalizer {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("KeywordNormalizer")
+            .field("lowercase", &self.lowercase)
+            .field("nfc", &self.nfc)
+            .field("callback", &self.callback.is_some())
+            .finish()
+    }
+}
+
+/// Returns the given search results under the keywords requested, see
+/// [`KeywordNormalizer::normalize_keywords`].
+pub(crate) fn restore_requested_keywords(
+    results: KeywordToDataMap,
+    requested: &HashMap<Keyword, HashSet<Keyword>>,
+) -> KeywordToDataMap {
+    results
+        .into_iter()
+        .flat_map(|(normalized, data)| {
+            requested
+                .get(&normalized)
+                .into_iter()
+                .flatten()
+                .map(move |keyword| (keyword.clone(), data.clone()))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_keyword_normalization() {
+        let normalizer = KeywordNormalizer::default().with_lowercase().with_nfc();
+        let expected = Keyword::from("caf\u{e9}".as_bytes());
+        for keyword in [
+            "Caf\u{e9}",
+            "caf\u{e9}",
+            "CAF\u{c9}",
+            "Cafe\u{301}",
+            "CAFE\u{301}",
+        ] {
+            assert_eq!(
+                normalizer
+                    .normalize(&Keyword::from(keyword.as_bytes()))
+                    .unwrap(),
+                expected
+            );
+        }
+
+        // Non UTF-8 keywords are left unchanged.
+        let binary = Keyword::from([0xC3, 0x28, b'A'].as_slice());
+        assert_eq!(normalizer.normalize(&binary).unwrap(), binary);
+
+        // The callback is applied last.
+        let normalizer = normalizer.with_callback(Arc::new(|keyword: &[u8]| {
+            Ok(keyword.strip_suffix(b"s").unwrap_or(keyword).to_vec())
+        }));
+        assert_eq!(
+            normalizer
+                .normalize(&Keyword::from("CAF\u{c9}S".as_bytes()))
+                .unwrap(),
+            expected
+        );
+
+        let failing = KeywordNormalizer::default()
+            .with_callback(Arc::new(|_: &[u8]| Err("failure".to_string())));
+        assert!(failing.normalize(&expected).is_err());
+    }
+
+    #[test]
+    fn test_normalized_searches() {
+        let normalizer = KeywordNormalizer::default().with_lowercase();
+        let keywords = Keywords::from(HashSet::from([
+            Keyword::from("Alice".as_bytes()),
+            Keyword::from("ALICE".as_bytes()),
+            Keyword::from("Bob".as_bytes()),
+        ]));
+        let requested = normalizer.normalize_keywords(&keywords).unwrap();
+        assert_eq!(requested.len(), 2);
+
+        let location = Data::from("location".as_bytes());
+        let results = KeywordToDataMap::from_iter([(
+            Keyword::from("alice".as_bytes()),
+            HashSet::from([location.clone()]),
+        )]);
+        let results = restore_requested_keywords(results, &requested);
+        assert_eq!(results.len(), 2);
+        for keyword in ["Alice", "ALICE"] {
+            assert!(results
+                .get(&Keyword::from(keyword.as_bytes()))
+                .unwrap()
+                .contains(&location));
+        }
+    }
+}
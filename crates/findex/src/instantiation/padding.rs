@@ -0,0 +1,206 @@
+//! Keyword frequency hiding through dummy insertions.
+//!
+//! The length of the chain associated to a keyword leaks the number of values
+//! indexed under this keyword. When padding is used, each modification (be it
+//! an addition or a deletion) indexes as many dummy values as needed for the
+//! number of values indexed under each keyword to be a multiple of the bucket
+//! size. Chain lengths are therefore only leaked with a bucket granularity.
+//!
+//! The padding is configured per index upon instantiation, see
+//! [`Configuration::with_chain_padding`](crate::Configuration::with_chain_padding).
+//!
+//! Dummy values are random data prefixed with [`DUMMY_DATA_PREFIX`]. They are
+//! stripped from search results and always kept upon compaction.
+
+use std::collections::{HashMap, HashSet};
+
+use cosmian_crypto_core::{reexport::rand_core::RngCore, CsRng};
+use cosmian_findex::{Data, IndexedValue, IndexedValueToKeywordsMap, Keyword, KeywordToDataMap};
+
+use crate::db_interfaces::DbInterfaceError;
+
+/// Prefix of the dummy data used to pad the chains.
+pub const DUMMY_DATA_PREFIX: [u8; 16] = *b"\xffFindexPadding\xff\xff";
+
+/// Number of random bytes appended to the prefix of a dummy data, enough to
+/// make collisions negligible.
+const DUMMY_DATA_RANDOM_LENGTH: usize = 16;
+
+/// Padding applied to the chains upon modification.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ChainPadding {
+    bucket_size: usize,
+}
+
+impl ChainPadding {
+    /// Pads the number of values indexed under each keyword to a multiple of
+    /// the given bucket size.
+    ///
+    /// The same padding should be used for all the modifications of a given
+    /// index.
+    pub fn new(bucket_size: usize) -> Result<Self, DbInterfaceError> {
+        if bucket_size < 2 {
+            return Err(DbInterfaceError::Other(format!(
+                "padding bucket size should be at least 2, given {bucket_size}"
+            )));
+        }
+        Ok(Self { bucket_size })
+    }
+
+    #[must_use]
+    pub const fn bucket_size(&self) -> usize {
+        self.bucket_size
+    }
+
+    /// Returns the smallest multiple of the bucket size greater or equal to
+    /// the given number of values.
+    #[must_use]
+    pub const fn padded_length(&self, n_values: usize) -> usize {
+        n_values.div_ceil(self.bucket_size) * self.bucket_size
+    }
+
+    /// Adds dummy values to the given modifications for the number of values
+    /// modified under each keyword to be a multiple of the bucket size.
+    pub(crate) fn pad(
+        &self,
+        modifications: IndexedValueToKeywordsMap,
+        rng: &mut CsRng,
+    ) -> IndexedValueToKeywordsMap {
+        let mut modifications = modifications
+            .iter()
+            .map(|(value, keywords)| (value.clone(), keywords.iter().cloned().collect()))
+            .collect::<HashMap<IndexedValue<Keyword, Data>, HashSet<Keyword>>>();
+
+        // Count the values modified under each keyword, and the length of the
+        // longest one so that dummies cannot be told apart by their size.
+        let mut counts = HashMap::<Keyword, (usize, usize)>::new();
+        for (value, keywords) in &modifications {
+            let length = match value {
+                IndexedValue::Data(data) => data.len(),
+                IndexedValue::Pointer(keyword) => keyword.len(),
+            };
+            for keyword in keywords {
+                let (count, max_length) = counts.entry(keyword.clone()).or_default();
+                *count += 1;
+                *max_length = (*max_length).max(length);
+            }
+        }
+
+        for (keyword, (count, max_length)) in counts {
+            let length = max_length.max(DUMMY_DATA_PREFIX.len() + DUMMY_DATA_RANDOM_LENGTH);
+            for _ in count..self.padded_length(count) {
+                modifications.insert(
+                    IndexedValue::Data(new_dummy(length, rng)),
+                    HashSet::from([keyword.clone()]),
+                );
+            }
+        }
+
+        IndexedValueToKeywordsMap::from(modifications)
+    }
+}
+
+/// Generates a new random dummy data of the given length.
+fn new_dummy(length: usize, rng: &mut CsRng) -> Data {
+    let mut bytes = vec![0; length];
+    bytes[..DUMMY_DATA_PREFIX.len()].copy_from_slice(&DUMMY_DATA_PREFIX);
+    rng.fill_bytes(&mut bytes[DUMMY_DATA_PREFIX.len()..]);
+    Data::from(bytes)
+}
+
+/// Returns `true` if the given data is a dummy inserted to pad a chain.
+#[must_use]
+pub fn is_dummy(data: &[u8]) -> bool {
+    data.starts_with(&DUMMY_DATA_PREFIX)
+}
+
+/// Removes the dummy data from the given search results.
+pub(crate) fn strip_dummies(results: KeywordToDataMap) -> KeywordToDataMap {
+    results
+        .into_iter()
+        .map(|(keyword, data)| {
+            (
+                keyword,
+                data.into_iter().filter(|data| !is_dummy(data)).collect(),
+            )
+        })
+        .collect()
+}
+
+/// Removes the dummy data from the given intermediate search results.
+pub(crate) fn strip_indexed_dummies(
+    results: HashMap<Keyword, HashSet<IndexedValue<Keyword, Data>>>,
+) -> HashMap<Keyword, HashSet<IndexedValue<Keyword, Data>>> {
+    results
+        .into_iter()
+        .map(|(keyword, values)| {
+            (
+                keyword,
+                values
+                    .into_iter()
+                    .filter(|value| !matches!(value, IndexedValue::Data(data) if is_dummy(data)))
+                    .collect(),
+            )
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use cosmian_crypto_core::reexport::rand_core::SeedableRng;
+
+    use super::*;
+
+    #[test]
+    fn test_chain_padding() {
+        let mut rng = CsRng::from_entropy();
+        assert!(ChainPadding::new(1).is_err());
+        let padding = ChainPadding::new(4).unwrap();
+        assert_eq!(padding.padded_length(0), 0);
+        assert_eq!(padding.padded_length(1), 4);
+        assert_eq!(padding.padded_length(4), 4);
+        assert_eq!(padding.padded_length(5), 8);
+
+        let rare = Keyword::from("rare".as_bytes());
+        let common = Keyword::from("common".as_bytes());
+        let modifications =
+            IndexedValueToKeywordsMap::from(HashMap::from_iter((0..5_u8).map(|i| {
+                let keywords = if i == 0 {
+                    HashSet::from([rare.clone(), common.clone()])
+                } else {
+                    HashSet::from([common.clone()])
+                };
+                (IndexedValue::Data(Data::from(vec![i])), keywords)
+            })));
+
+        let padded = padding.pad(modifications, &mut rng);
+        let count = |keyword: &Keyword| {
+            padded
+                .values()
+                .filter(|keywords| keywords.contains(keyword))
+                .count()
+        };
+        assert_eq!(count(&rare), 4);
+        assert_eq!(count(&common), 8);
+
+        let results = KeywordToDataMap::from_iter([
+            (
+                rare.clone(),
+                padded
+                    .iter()
+                    .filter(|(_, keywords)| keywords.contains(&rare))
+                    .filter_map(|(value, _)| match value {
+                        IndexedValue::Data(data) => Some(data.clone()),
+                        IndexedValue::Pointer(_) => None,
+                    })
+                    .collect(),
+            ),
+            (common.clone(), HashSet::new()),
+        ]);
+        let results = strip_dummies(results);
+        assert_eq!(
+            results.get(&rare),
+            Some(&HashSet::from([Data::from(vec![0])]))
+        );
+    }
+}
@@ -0,0 +1,228 @@
+//! Encrypted payloads attached to the indexed locations.
+//!
+//! Simple applications can store a small payload (a document title, a
+//! database row...) along with each indexed location: searches then return
+//! the decrypted payloads directly, without a second round-trip to another
+//! datastore.
+//!
+//! A location indexed with a payload is stored as the following data:
+//!
+//! ```txt
+//! data = PAYLOAD_DATA_PREFIX || LEB128(|location|) || location || nonce || c
+//! c    = AES256-GCM(k, payload, location)
+//! ```
+//!
+//! where `k` is a [`PayloadKey`] independent from the Findex key: compacting
+//! the index with a new Findex key does not change the payloads, which stay
+//! readable using the same payload key.
+//!
+//! Payloads are stripped from the results of the other searches and from the
+//! data given to the compaction filters, which only see the locations.
+
+use std::collections::{HashMap, HashSet};
+
+use cosmian_crypto_core::{
+    bytes_ser_de::{Deserializer, Serializer},
+    Aes256Gcm, CryptoCoreError, CsRng, Dem, FixedSizeCBytes, Instantiable, Nonce,
+    RandomFixedSizeCBytes, SymmetricKey,
+};
+use cosmian_findex::{Data, IndexedValue, IndexedValueToKeywordsMap, Keyword, KeywordToDataMap};
+
+/// Prefix of the data holding a location along with its encrypted payload.
+pub const PAYLOAD_DATA_PREFIX: [u8; 16] = *b"\xffFindexPayload\xff\xff";
+
+/// Key encrypting the payloads.
+pub type PayloadKey = SymmetricKey<{ Aes256Gcm::KEY_LENGTH }>;
+
+/// Payloads indexed under each keyword, per location. Locations indexed
+/// without payload are associated to `None`.
+pub type KeywordToPayloadsMap = HashMap<Keyword, HashMap<Data, Option<Vec<u8>>>>;
+
+/// Encrypts the given payload and serializes it along with its location.
+fn encode(
+    aes: &Aes256Gcm,
+    location: &Data,
+    payload: &[u8],
+    rng: &mut CsRng,
+) -> Result<Data, CryptoCoreError> {
+    let nonce = Nonce::new(rng);
+    let ciphertext = aes.encrypt(&nonce, payload, Some(&location[..]))?;
+    let mut ser = Serializer::new();
+    ser.write_array(&PAYLOAD_DATA_PREFIX)?;
+    ser.write_vec(location)?;
+    ser.write_array(&nonce.0)?;
+    ser.write_array(&ciphertext)?;
+    Ok(Data::from(ser.finalize().to_vec()))
+}
+
+/// Splits the given data into its location and its encrypted payload.
+///
+/// Returns `None` if this data holds no payload.
+fn split(data: &[u8]) -> Result<Option<(Data, Vec<u8>)>, CryptoCoreError> {
+    let Some(bytes) = data.strip_prefix(&PAYLOAD_DATA_PREFIX) else {
+        return Ok(None);
+    };
+    let mut de = Deserializer::new(bytes);
+    let location = de.read_vec()?;
+    Ok(Some((Data::from(location), de.finalize())))
+}
+
+/// Returns the location indexed by the given data, with its payload removed.
+pub(crate) fn strip_payload(data: Data) -> Data {
+    match split(&data) {
+        Ok(Some((location, _))) => location,
+        _ => data,
+    }
+}
+
+/// Removes the payloads from the given search results.
+pub(crate) fn strip_payloads(results: KeywordToDataMap) -> KeywordToDataMap {
+    results
+        .into_iter()
+        .map(|(keyword, data)| (keyword, data.into_iter().map(strip_payload).collect()))
+        .collect()
+}
+
+/// Removes the payloads from the given intermediate search results.
+pub(crate) fn strip_indexed_payloads(
+    results: HashMap<Keyword, HashSet<IndexedValue<Keyword, Data>>>,
+) -> HashMap<Keyword, HashSet<IndexedValue<Keyword, Data>>> {
+    results
+        .into_iter()
+        .map(|(keyword, values)| {
+            let values = values
+                .into_iter()
+                .map(|value| match value {
+                    IndexedValue::Data(data) => IndexedValue::Data(strip_payload(data)),
+                    pointer @ IndexedValue::Pointer(_) => pointer,
+                })
+                .collect();
+            (keyword, values)
+        })
+        .collect()
+}
+
+/// Groups the given data by the location they index, with or without payload.
+pub(crate) fn group_by_location(data: HashSet<Data>) -> HashMap<Data, HashSet<Data>> {
+    let mut locations = HashMap::<_, HashSet<_>>::new();
+    for data in data {
+        locations
+            .entry(strip_payload(data.clone()))
+            .or_default()
+            .insert(data);
+    }
+    locations
+}
+
+/// Converts the given additions into Findex additions, encrypting the
+/// payloads with the given payload key.
+pub(crate) fn attach_payloads(
+    payload_key: &PayloadKey,
+    additions: HashMap<Data, (Vec<u8>, HashSet<Keyword>)>,
+    rng: &mut CsRng,
+) -> Result<IndexedValueToKeywordsMap, CryptoCoreError> {
+    let aes = Aes256Gcm::new(payload_key);
+    let additions = additions
+        .into_iter()
+        .map(|(location, (payload, keywords))| {
+            encode(&aes, &location, &payload, rng).map(|data| (IndexedValue::Data(data), keywords))
+        })
+        .collect::<Result<HashMap<_, _>, _>>()?;
+    Ok(IndexedValueToKeywordsMap::from(additions))
+}
+
+/// Decrypts the payloads of the given search results.
+pub(crate) fn decrypt_payloads(
+    payload_key: &PayloadKey,
+    results: impl IntoIterator<Item = (Keyword, HashSet<Data>)>,
+) -> Result<KeywordToPayloadsMap, CryptoCoreError> {
+    let aes = Aes256Gcm::new(payload_key);
+    results
+        .into_iter()
+        .map(|(keyword, data)| {
+            let payloads = data
+                .into_iter()
+                .map(|data| match split(&data)? {
+                    Some((location, encrypted_payload)) => {
+                        if encrypted_payload.len() < Aes256Gcm::NONCE_LENGTH {
+                            return Err(CryptoCoreError::CiphertextTooSmallError {
+                                ciphertext_len: encrypted_payload.len(),
+                                min: Aes256Gcm::NONCE_LENGTH as u64,
+                            });
+                        }
+                        let (nonce, ciphertext) =
+                            encrypted_payload.split_at(Aes256Gcm::NONCE_LENGTH);
+                        let payload = aes.decrypt(
+                            &Nonce::try_from_slice(nonce)?,
+                            ciphertext,
+                            Some(&location[..]),
+                        )?;
+                        Ok((location, Some(payload)))
+                    }
+                    None => Ok((data, None)),
+                })
+                .collect::<Result<HashMap<_, _>, _>>()?;
+            Ok((keyword, payloads))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use cosmian_crypto_core::reexport::rand_core::SeedableRng;
+
+    use super::*;
+
+    #[test]
+    fn test_payloads() {
+        let mut rng = CsRng::from_entropy();
+        let key = PayloadKey::new(&mut rng);
+        let keyword = Keyword::from("keyword".as_bytes());
+        let location = Data::from(b"location".to_vec());
+
+        let additions = attach_payloads(
+            &key,
+            HashMap::from([(
+                location.clone(),
+                (b"payload".to_vec(), HashSet::from([keyword.clone()])),
+            )]),
+            &mut rng,
+        )
+        .unwrap();
+        let data = additions
+            .keys()
+            .map(|value| match value {
+                IndexedValue::Data(data) => data.clone(),
+                IndexedValue::Pointer(_) => panic!("a location should be indexed"),
+            })
+            .collect::<HashSet<_>>();
+        assert_eq!(data.len(), 1);
+        assert_eq!(strip_payload(data.iter().next().unwrap().clone()), location);
+
+        // Locations indexed without payload are returned as is.
+        let plain_location = Data::from(b"plain".to_vec());
+        let mut results = data.clone();
+        results.insert(plain_location.clone());
+        assert_eq!(
+            group_by_location(results.clone()),
+            HashMap::from([
+                (location.clone(), data.clone()),
+                (
+                    plain_location.clone(),
+                    HashSet::from([plain_location.clone()])
+                ),
+            ])
+        );
+        let payloads = decrypt_payloads(&key, [(keyword.clone(), results)]).unwrap();
+        assert_eq!(
+            payloads[&keyword],
+            HashMap::from([
+                (location, Some(b"payload".to_vec())),
+                (plain_location, None)
+            ])
+        );
+
+        // Payloads cannot be decrypted using another key.
+        assert!(decrypt_payloads(&PayloadKey::new(&mut rng), [(keyword, data)]).is_err());
+    }
+}
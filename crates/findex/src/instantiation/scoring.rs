@@ -0,0 +1,89 @@
+//! Scoring of the search results.
+//!
+//! Applications searching several keywords usually rank the locations found
+//! by the number of keywords they match. The score of a location is this
+//! number: a location indexed under all the keywords searched scores as many
+//! points as there are keywords.
+//!
+//! In [`MatchMode::Intersection`], only the locations matching all the
+//! keywords are returned. In [`MatchMode::Union`], all the locations found are
+//! returned with their score.
+
+use std::collections::HashMap;
+
+use cosmian_findex::{Data, KeywordToDataMap};
+
+/// Number of keywords matched by each location found.
+pub type LocationToScoreMap = HashMap<Data, usize>;
+
+/// Locations returned by a scored search.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum MatchMode {
+    /// Returns the locations matching at least one keyword.
+    #[default]
+    Union,
+    /// Returns the locations matching all the keywords.
+    Intersection,
+}
+
+impl MatchMode {
+    /// Returns the number of keywords matched by each location of the given
+    /// search results, keeping only the locations matching all the keywords
+    /// in [`MatchMode::Intersection`].
+    #[must_use]
+    pub fn score(self, results: &KeywordToDataMap) -> LocationToScoreMap {
+        let mut scores = LocationToScoreMap::new();
+        for locations in results.values() {
+            for location in locations {
+                *scores.entry(location.clone()).or_default() += 1;
+            }
+        }
+        if self == Self::Intersection {
+            scores.retain(|_, score| *score == results.len());
+        }
+        scores
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashSet;
+
+    use cosmian_findex::Keyword;
+
+    use super::*;
+
+    #[test]
+    fn test_score() {
+        let location = |i: u8| Data::from(vec![i]);
+        let results = [
+            (
+                Keyword::from("Robert"),
+                HashSet::from([location(1), location(2)]),
+            ),
+            (
+                Keyword::from("Doe"),
+                HashSet::from([location(1), location(3)]),
+            ),
+            (Keyword::from("Smith"), HashSet::from([location(1)])),
+        ];
+
+        let scores = MatchMode::Union.score(&KeywordToDataMap::from_iter(results.clone()));
+        assert_eq!(
+            scores,
+            HashMap::from([(location(1), 3), (location(2), 1), (location(3), 1)])
+        );
+
+        let scores = MatchMode::Intersection.score(&KeywordToDataMap::from_iter(results.clone()));
+        assert_eq!(scores, HashMap::from([(location(1), 3)]));
+
+        // A keyword matching no location leaves an empty intersection.
+        let results = KeywordToDataMap::from_iter(
+            results
+                .into_iter()
+                .chain([(Keyword::from("Martin"), HashSet::new())]),
+        );
+        assert!(MatchMode::Intersection.score(&results).is_empty());
+        assert_eq!(MatchMode::Union.score(&results).len(), 3);
+    }
+}
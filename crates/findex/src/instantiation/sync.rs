@@ -0,0 +1,131 @@
+//! Differential synchronization of an index into another backend.
+//!
+//! Only the lines of the source index which are missing from the target index
+//! or whose value differs are written to the target; e.g. an offline-first
+//! application can reconcile the index of its local SQLite database with a
+//! remote one without copying the whole index.
+//!
+//! The synchronization goes one way: the lines of the target index which are
+//! absent from the source index are left untouched.
+
+use std::collections::HashMap;
+
+use cosmian_findex::{
+    DbInterface, EncryptedValue, Token, TokenToEncryptedValueMap, Tokens, ENTRY_LENGTH, LINK_LENGTH,
+};
+
+use crate::{db_interfaces::DbInterfaceError, instantiation::dump::deserialize_index_dump};
+
+/// Number of lines written to the target index by a synchronization.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct SyncStatistics {
+    /// Number of Entry Table lines written.
+    pub n_entries: usize,
+    /// Number of Chain Table lines written.
+    pub n_links: usize,
+}
+
+/// Lines to write to the target table: the current values of the lines to
+/// update, and the new values of all the lines to write.
+type LinesDiff<const VALUE_LENGTH: usize> = (
+    HashMap<Token, EncryptedValue<VALUE_LENGTH>>,
+    HashMap<Token, EncryptedValue<VALUE_LENGTH>>,
+);
+
+/// Fetches the given lines from the given table and returns the ones which
+/// are missing or differ.
+async fn diff_lines<const VALUE_LENGTH: usize>(
+    table: &impl DbInterface<VALUE_LENGTH, Error = DbInterfaceError>,
+    lines: TokenToEncryptedValueMap<VALUE_LENGTH>,
+) -> Result<LinesDiff<VALUE_LENGTH>, DbInterfaceError> {
+    let mut current_values = if lines.is_empty() {
+        HashMap::new()
+    } else {
+        table
+            .fetch(Tokens::from_iter(lines.keys().copied()))
+            .await?
+            .into_iter()
+            .collect::<HashMap<_, _>>()
+    };
+
+    let mut old_values = HashMap::new();
+    let mut new_values = HashMap::new();
+    for (token, value) in lines.into_iter() {
+        match current_values.remove(&token) {
+            Some(current_value) if current_value == value => {}
+            Some(current_value) => {
+                old_values.insert(token, current_value);
+                new_values.insert(token, value);
+            }
+            None => {
+                new_values.insert(token, value);
+            }
+        }
+    }
+    Ok((old_values, new_values))
+}
+
+/// Writes the Entry Table lines missing from the given table or differing.
+///
+/// Lines are upserted: a line modified concurrently in the target table is
+/// not overwritten and makes the synchronization fail.
+async fn sync_entries(
+    entry_table: &impl DbInterface<ENTRY_LENGTH, Error = DbInterfaceError>,
+    entries: TokenToEncryptedValueMap<ENTRY_LENGTH>,
+) -> Result<usize, DbInterfaceError> {
+    let (old_values, new_values) = diff_lines(entry_table, entries).await?;
+    let n_entries = new_values.len();
+    if n_entries != 0 {
+        let conflicts = entry_table
+            .upsert(
+                TokenToEncryptedValueMap::from(old_values),
+                TokenToEncryptedValueMap::from(new_values),
+            )
+            .await?;
+        if !conflicts.is_empty() {
+            return Err(DbInterfaceError::Other(format!(
+                "{} Entry Table line(s) modified during the synchronization",
+                conflicts.len()
+            )));
+        }
+    }
+    Ok(n_entries)
+}
+
+/// Writes the Chain Table lines missing from the given table or differing.
+///
+/// Chain Table lines cannot be upserted: differing lines are deleted before
+/// being inserted again.
+async fn sync_links(
+    chain_table: &impl DbInterface<LINK_LENGTH, Error = DbInterfaceError>,
+    links: TokenToEncryptedValueMap<LINK_LENGTH>,
+) -> Result<usize, DbInterfaceError> {
+    let (old_values, new_values) = diff_lines(chain_table, links).await?;
+    if !old_values.is_empty() {
+        chain_table
+            .delete(Tokens::from_iter(old_values.into_keys()))
+            .await?;
+    }
+    let n_links = new_values.len();
+    if n_links != 0 {
+        chain_table
+            .insert(TokenToEncryptedValueMap::from(new_values))
+            .await?;
+    }
+    Ok(n_links)
+}
+
+/// Writes the lines of the given index dump which are missing from the given
+/// tables or differ.
+pub(crate) async fn sync_tables(
+    entry_table: &impl DbInterface<ENTRY_LENGTH, Error = DbInterfaceError>,
+    chain_table: &impl DbInterface<LINK_LENGTH, Error = DbInterfaceError>,
+    bytes: &[u8],
+) -> Result<SyncStatistics, DbInterfaceError> {
+    let (entries, links) = deserialize_index_dump(bytes)?;
+    // Write the chains first so that the index is never left with entries
+    // pointing to missing links.
+    let n_links = sync_links(chain_table, links).await?;
+    let n_entries = sync_entries(entry_table, entries).await?;
+    Ok(SyncStatistics { n_entries, n_links })
+}
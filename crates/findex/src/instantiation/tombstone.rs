@@ -0,0 +1,165 @@
+//! Soft deletion: recoverable deletions until the next compaction.
+//!
+//! A Findex deletion cannot be undone without knowing the deleted values.
+//! When soft-deleting, a tombstone is indexed under each keyword along with
+//! the deletion: the deleted location prefixed with [`TOMBSTONE_DATA_PREFIX`].
+//! Tombstones are stripped from search results and can be counted, or used to
+//! restore the deleted locations, until the next compaction which removes
+//! them.
+
+use std::collections::{HashMap, HashSet};
+
+use cosmian_findex::{Data, IndexedValue, IndexedValueToKeywordsMap, Keyword, KeywordToDataMap};
+
+/// Prefix of the tombstones of the soft-deleted locations.
+pub const TOMBSTONE_DATA_PREFIX: [u8; 16] = *b"\xffFindexDeletion\xff";
+
+/// Returns `true` if the given data is the tombstone of a soft-deleted
+/// location.
+#[must_use]
+pub fn is_tombstone(data: &[u8]) -> bool {
+    data.starts_with(&TOMBSTONE_DATA_PREFIX)
+}
+
+fn new_tombstone(location: &[u8]) -> Data {
+    Data::from([TOMBSTONE_DATA_PREFIX.as_slice(), location].concat())
+}
+
+/// Returns the tombstones of the locations of the given deletions, indexed
+/// under the same keywords. Deleted pointers get no tombstone.
+pub(crate) fn tombstones(deletions: &IndexedValueToKeywordsMap) -> IndexedValueToKeywordsMap {
+    deletions
+        .iter()
+        .filter_map(|(value, keywords)| match value {
+            IndexedValue::Data(location) => Some((
+                IndexedValue::Data(new_tombstone(location)),
+                keywords.iter().cloned().collect::<HashSet<_>>(),
+            )),
+            IndexedValue::Pointer(_) => None,
+        })
+        .collect::<HashMap<_, _>>()
+        .into()
+}
+
+/// Tombstones found under each keyword, split into the locations still deleted
+/// and all the tombstones.
+pub(crate) struct KeywordTombstones {
+    pub deleted_locations: HashSet<Data>,
+    pub tombstones: HashSet<Data>,
+}
+
+/// Splits the given unstripped search results into the tombstones found under
+/// each keyword.
+///
+/// A location indexed again after its soft deletion is not considered deleted
+/// anymore, although its tombstone is kept until the next compaction.
+pub(crate) fn find_tombstones(results: KeywordToDataMap) -> HashMap<Keyword, KeywordTombstones> {
+    results
+        .into_iter()
+        .map(|(keyword, data)| {
+            let (tombstones, locations): (HashSet<_>, HashSet<_>) =
+                data.into_iter().partition(|data| is_tombstone(data));
+            let deleted_locations = tombstones
+                .iter()
+                .map(|tombstone| Data::from(tombstone[TOMBSTONE_DATA_PREFIX.len()..].to_vec()))
+                .filter(|location| !locations.contains(location))
+                .collect();
+            (
+                keyword,
+                KeywordTombstones {
+                    deleted_locations,
+                    tombstones,
+                },
+            )
+        })
+        .collect()
+}
+
+/// Removes the tombstones from the given search results.
+pub(crate) fn strip_tombstones(results: KeywordToDataMap) -> KeywordToDataMap {
+    results
+        .into_iter()
+        .map(|(keyword, data)| {
+            (
+                keyword,
+                data.into_iter()
+                    .filter(|data| !is_tombstone(data))
+                    .collect(),
+            )
+        })
+        .collect()
+}
+
+/// Removes the tombstones from the given intermediate search results.
+pub(crate) fn strip_indexed_tombstones(
+    results: HashMap<Keyword, HashSet<IndexedValue<Keyword, Data>>>,
+) -> HashMap<Keyword, HashSet<IndexedValue<Keyword, Data>>> {
+    results
+        .into_iter()
+        .map(|(keyword, values)| {
+            (
+                keyword,
+                values
+                    .into_iter()
+                    .filter(
+                        |value| !matches!(value, IndexedValue::Data(data) if is_tombstone(data)),
+                    )
+                    .collect(),
+            )
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_tombstones() {
+        let location = |location: &str| Data::from(location.as_bytes().to_vec());
+        let keyword = Keyword::from("keyword".as_bytes());
+        let deletions = IndexedValueToKeywordsMap::from(HashMap::from([
+            (
+                IndexedValue::Data(location("location 1")),
+                HashSet::from([keyword.clone()]),
+            ),
+            (
+                IndexedValue::Pointer(Keyword::from("alias".as_bytes())),
+                HashSet::from([keyword.clone()]),
+            ),
+        ]));
+        let tombstones = tombstones(&deletions);
+        assert_eq!(tombstones.len(), 1);
+        let (tombstone, keywords) = tombstones.iter().next().unwrap();
+        assert_eq!(tombstone, &IndexedValue::Data(new_tombstone(b"location 1")));
+        assert!(keywords.contains(&keyword));
+
+        // Location 2 was indexed again after its deletion.
+        let results = || {
+            KeywordToDataMap::from_iter([(
+                keyword.clone(),
+                HashSet::from([
+                    location("location 2"),
+                    location("location 3"),
+                    new_tombstone(b"location 1"),
+                    new_tombstone(b"location 2"),
+                ]),
+            )])
+        };
+        let found = find_tombstones(results());
+        let found = found.get(&keyword).unwrap();
+        assert_eq!(
+            found.deleted_locations,
+            HashSet::from([location("location 1")])
+        );
+        assert_eq!(found.tombstones.len(), 2);
+
+        assert_eq!(
+            strip_tombstones(results()).get(&keyword),
+            Some(&HashSet::from([
+                location("location 2"),
+                location("location 3")
+            ]))
+        );
+    }
+}
@@ -1,9 +1,18 @@
 //! Defines the Findex FFI API.
+//!
+//! Instantiated Findex are stored in a cache and referred to by their handle.
+//! The functions of this API are thread-safe: the same handle can be used
+//! concurrently from several threads, without any lock on the caller side.
 
 use std::{
     collections::{HashMap, HashSet},
+    num::TryFromIntError,
     str::FromStr,
-    sync::Mutex,
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc, RwLock,
+    },
+    time::{Duration, Instant},
 };
 
 use cosmian_crypto_core::{
@@ -12,42 +21,129 @@ use cosmian_crypto_core::{
 };
 use cosmian_ffi_utils::{
     error::{h_get_error, set_last_error, FfiError},
-    ffi_read_bytes, ffi_read_string, ffi_unwrap, ffi_write_bytes, ErrorCode,
+    ffi_guard, ffi_read_bytes, ffi_read_string, ffi_unwrap, ffi_write_bytes, ErrorCode,
 };
 use cosmian_findex::{
     Data, Error as FindexError, IndexedValue, IndexedValueToKeywordsMap, Keyword, Keywords, Label,
     USER_KEY_LENGTH,
 };
 use lazy_static::lazy_static;
-use tracing::trace;
+use tracing::{debug, trace};
 
 #[cfg(debug_assertions)]
 use crate::logger::log_init;
 use crate::{
     db_interfaces::{
         custom::ffi::{
-            Delete, DumpTokens, Fetch, FfiCallbacks, FilterObsoleteData, Insert, Interrupt, Upsert,
+            normalize_keyword, Delete, DumpTokens, Fetch, FfiCallbacks, FilterObsoleteData, Insert,
+            Interrupt, Normalize, Upsert,
         },
-        rest::{AuthorizationToken, CallbackPrefix},
+        redis::DEFAULT_PIPELINE_SIZE,
+        rest::{AuthorizationToken, CallbackPrefix, RateLimit, RequestOptions},
         DbInterfaceError,
     },
     ser_de::ffi_ser_de::{
-        deserialize_data_set, deserialize_indexed_values, deserialize_keyword_set,
+        add_format_version, deserialize_data_set, deserialize_indexed_values,
+        deserialize_keyword_set, deserialize_keyword_to_keywords, get_aliases_output_size,
         get_upsert_output_size, serialize_data_set, serialize_intermediate_results,
-        serialize_keyword_set,
+        serialize_keyword_set, serialize_location_scores, serialize_token_set,
+        strip_format_version, CALLBACK_FORMAT_VERSION,
     },
-    Configuration, InstantiatedFindex,
+    ChainPadding, Configuration, InstantiatedFindex, KeywordNormalizer, MatchMode,
 };
 
+/// A Findex instance stored in the instance cache.
+///
+/// Instances are `Send + Sync` and shared between the calls using the same
+/// handle: several operations (searches, additions...) can run concurrently
+/// on the same instance from different threads. Only the compaction, which
+/// replaces the key and the label, waits for the other operations to complete
+/// and blocks them while it runs.
+///
+/// The searches in progress are cancelled by incrementing the
+/// `search_generation`: each search aborts at its next round if the
+/// generation changed since it started.
+struct FindexInstance {
+    keying: RwLock<(SymmetricKey<USER_KEY_LENGTH>, Label)>,
+    findex: InstantiatedFindex,
+    search_generation: AtomicU64,
+}
+
 lazy_static! {
-    static ref FINDEX_INSTANCES: Mutex::<HashMap::<i32, (SymmetricKey<USER_KEY_LENGTH>, Label, InstantiatedFindex)>> =
-        Mutex::new(HashMap::new());
+    static ref FINDEX_INSTANCES: RwLock<HashMap<i32, Arc<FindexInstance>>> =
+        RwLock::new(HashMap::new());
+}
+
+/// Stores the given Findex instance in the cache and returns its handle.
+fn cache_instance(
+    key: SymmetricKey<USER_KEY_LENGTH>,
+    label: Label,
+    findex: InstantiatedFindex,
+) -> Result<i32, TryFromIntError> {
+    let mut cache = FINDEX_INSTANCES
+        .write()
+        .expect("Findex instance cache lock poisoned.");
+    let handle = <i32>::try_from(cache.len())?;
+    cache.insert(
+        handle,
+        Arc::new(FindexInstance {
+            keying: RwLock::new((key, label)),
+            findex,
+            search_generation: AtomicU64::new(0),
+        }),
+    );
+    Ok(handle)
+}
+
+/// Returns the Findex instance associated to the given handle.
+///
+/// The cache lock is only held while the instance is retrieved, not during
+/// the operation performed on it.
+fn get_instance(findex_handle: i32) -> Result<Arc<FindexInstance>, String> {
+    FINDEX_INSTANCES
+        .read()
+        .expect("Findex instance cache lock poisoned.")
+        .get(&findex_handle)
+        .cloned()
+        .ok_or_else(|| format!("no matching instance for handle {findex_handle}"))
+}
+
+/// Modifies the Findex instance associated to the given handle.
+///
+/// Fails if an operation is in progress on this instance.
+fn modify_instance(
+    findex_handle: i32,
+    modify: impl FnOnce(&mut InstantiatedFindex),
+) -> Result<(), String> {
+    let mut cache = FINDEX_INSTANCES
+        .write()
+        .expect("Findex instance cache lock poisoned.");
+    let instance = cache
+        .get_mut(&findex_handle)
+        .ok_or_else(|| format!("no matching instance for handle {findex_handle}"))?;
+    let instance = Arc::get_mut(instance).ok_or("an operation is in progress on the instance")?;
+    modify(&mut instance.findex);
+    Ok(())
+}
+
+/// Returns the version of the format of the payloads exchanged with the
+/// callbacks, see [`CALLBACK_FORMAT_VERSION`].
+///
+/// Callback implementations should check this version before instantiating
+/// Findex, and fail if they do not support it.
+#[no_mangle]
+pub extern "C" fn h_get_callback_format_version() -> i32 {
+    i32::from(CALLBACK_FORMAT_VERSION)
 }
 
 /// Creates a new Findex instance using a custom FFI backend.
 ///
 /// The new instance is stored in a cache and the handle returned.
 ///
+/// The Entry Table can be sharded into `entry_table_number` tables, at least
+/// one, to spread the load: the entry `fetch` callback then returns the values
+/// found in each table.
+///
 /// # Safety
 ///
 /// Cannot be safe since using FFI.
@@ -68,67 +164,64 @@ pub unsafe extern "C" fn h_instantiate_with_custom_interface(
     delete_chain: Delete,
     dump_tokens: DumpTokens,
 ) -> i32 {
-    #[cfg(debug_assertions)]
-    log_init();
-
-    let key_bytes = ffi_read_bytes!("key", key_ptr, key_len);
-    let key = ffi_unwrap!(
-        SymmetricKey::try_from_slice(key_bytes),
-        "error deserializing findex key",
-        ErrorCode::Serialization
-    );
-    trace!("Key successfully parsed");
-
-    let label_bytes = ffi_read_string!("label", label_ptr);
-    let label = Label::from(label_bytes.as_str());
-    trace!("Label successfully parsed: label: {label}");
-
-    let config = Configuration::Ffi(
-        // TODO: ensure null pointers are not given
-        FfiCallbacks {
-            table_number: entry_table_number as usize,
-            fetch: Some(fetch_entry),
-            upsert: Some(upsert_entry),
-            insert: Some(insert_entry),
-            delete: Some(delete_entry),
-            dump_tokens: Some(dump_tokens),
-        },
-        FfiCallbacks {
-            // Only one Chain table is allowed.
-            table_number: 1,
-            fetch: Some(fetch_chain),
-            upsert: None,
-            insert: Some(insert_chain),
-            delete: Some(delete_chain),
-            dump_tokens: None,
-        },
-    );
-
-    let rt = ffi_unwrap!(
-        tokio::runtime::Runtime::new(),
-        "error creating Tokio runtime",
-        ErrorCode::Tokio
-    );
-    let findex = ffi_unwrap!(
-        rt.block_on(InstantiatedFindex::new(config)),
-        "error instantiating Findex with custom backend",
-        ErrorCode::Findex
-    );
+    ffi_guard!({
+        #[cfg(debug_assertions)]
+        log_init();
+
+        let key_bytes = ffi_read_bytes!("key", key_ptr, key_len);
+        let key = ffi_unwrap!(
+            SymmetricKey::try_from_slice(key_bytes),
+            "error deserializing findex key",
+            ErrorCode::Serialization
+        );
+        trace!("Key successfully parsed");
+
+        let label_bytes = ffi_read_string!("label", label_ptr);
+        let label = Label::from(label_bytes.as_str());
+        trace!("Label successfully parsed: label: {label}");
+
+        let config = Configuration::Ffi(
+            // TODO: ensure null pointers are not given
+            FfiCallbacks {
+                table_number: entry_table_number as usize,
+                fetch: Some(fetch_entry),
+                upsert: Some(upsert_entry),
+                insert: Some(insert_entry),
+                delete: Some(delete_entry),
+                dump_tokens: Some(dump_tokens),
+            },
+            FfiCallbacks {
+                // Only one Chain table is allowed.
+                table_number: 1,
+                fetch: Some(fetch_chain),
+                upsert: None,
+                insert: Some(insert_chain),
+                delete: Some(delete_chain),
+                dump_tokens: None,
+            },
+        );
 
-    let mut cache = FINDEX_INSTANCES
-        .lock()
-        .expect("Findex instance cache lock poisoned.");
-    let handle = ffi_unwrap!(
-        <i32>::try_from(cache.len()),
-        "findex instance cache capacity overflow",
-        ErrorCode::Findex
-    );
+        let rt = ffi_unwrap!(
+            tokio::runtime::Runtime::new(),
+            "error creating Tokio runtime",
+            ErrorCode::Tokio
+        );
+        let findex = ffi_unwrap!(
+            rt.block_on(InstantiatedFindex::new(config)),
+            "error instantiating Findex with custom backend",
+            ErrorCode::Findex
+        );
 
-    cache.insert(handle, (key, label, findex));
+        let handle = ffi_unwrap!(
+            cache_instance(key, label, findex),
+            "findex instance cache capacity overflow",
+            ErrorCode::Findex
+        );
 
-    *findex_handle = handle;
+        *findex_handle = handle;
 
-    ErrorCode::Success.into()
+        ErrorCode::Success.into()
+    })
 }
 
 /// Instantiate a Findex using a REST backend.
@@ -151,58 +244,62 @@ pub unsafe extern "C" fn h_instantiate_with_rest_interface(
     entry_url_ptr: *const i8,
     chain_url_ptr: *const i8,
 ) -> i32 {
-    #[cfg(debug_assertions)]
-    log_init();
-
-    let label_bytes = ffi_read_string!("label", label_ptr);
-    let label = Label::from(label_bytes.as_str());
-    trace!("Label successfully parsed: label: {label}");
-
-    let token = ffi_read_string!("token", token_ptr);
-    trace!("Authorization token read: {token}");
-    let authorization_token = ffi_unwrap!(
-        crate::db_interfaces::rest::AuthorizationToken::from_str(&token),
-        "authorization token conversion failed",
-        ErrorCode::Backend
-    );
-
-    let entry_url = if entry_url_ptr.is_null() {
-        String::new()
-    } else {
-        ffi_read_string!("REST server Entry Table URL", entry_url_ptr)
-    };
+    ffi_guard!({
+        #[cfg(debug_assertions)]
+        log_init();
+
+        let label_bytes = ffi_read_string!("label", label_ptr);
+        let label = Label::from(label_bytes.as_str());
+        trace!("Label successfully parsed: label: {label}");
+
+        let token = ffi_read_string!("token", token_ptr);
+        trace!("Authorization token read: {token}");
+        let authorization_token = ffi_unwrap!(
+            crate::db_interfaces::rest::AuthorizationToken::from_str(&token),
+            "authorization token conversion failed",
+            ErrorCode::Backend
+        );
 
-    let chain_url = if chain_url_ptr.is_null() {
-        String::new()
-    } else {
-        ffi_read_string!("REST server Chain Table URL", chain_url_ptr)
-    };
-    let config = Configuration::Rest(authorization_token.clone(), entry_url, chain_url);
+        let entry_url = if entry_url_ptr.is_null() {
+            String::new()
+        } else {
+            ffi_read_string!("REST server Entry Table URL", entry_url_ptr)
+        };
+
+        let chain_url = if chain_url_ptr.is_null() {
+            String::new()
+        } else {
+            ffi_read_string!("REST server Chain Table URL", chain_url_ptr)
+        };
+        let config = Configuration::Rest(
+            authorization_token.clone(),
+            entry_url,
+            chain_url,
+            RateLimit::default(),
+            RequestOptions::default(),
+        );
 
-    let rt = ffi_unwrap!(
-        tokio::runtime::Runtime::new(),
-        "error creating Tokio runtime",
-        ErrorCode::Tokio
-    );
-    let findex = ffi_unwrap!(
-        rt.block_on(InstantiatedFindex::new(config)),
-        "error instantiating Findex with REST backend",
-        ErrorCode::Backend
-    );
+        let rt = ffi_unwrap!(
+            tokio::runtime::Runtime::new(),
+            "error creating Tokio runtime",
+            ErrorCode::Tokio
+        );
+        let findex = ffi_unwrap!(
+            rt.block_on(InstantiatedFindex::new(config)),
+            "error instantiating Findex with REST backend",
+            ErrorCode::Backend
+        );
 
-    let mut cache = FINDEX_INSTANCES
-        .lock()
-        .expect("Findex instance cache lock poisoned.");
-    let handle = ffi_unwrap!(
-        <i32>::try_from(cache.len()),
-        "findex instance cache capacity overflow",
-        ErrorCode::Findex
-    );
-    cache.insert(handle, (authorization_token.findex_key, label, findex));
+        let handle = ffi_unwrap!(
+            cache_instance(authorization_token.findex_key, label, findex),
+            "findex instance cache capacity overflow",
+            ErrorCode::Findex
+        );
 
-    *findex_handle = handle;
+        *findex_handle = handle;
 
-    ErrorCode::Success.into()
+        ErrorCode::Success.into()
+    })
 }
 
 /// Instantiate a Findex using a Redis backend.
@@ -213,6 +310,8 @@ pub unsafe extern "C" fn h_instantiate_with_rest_interface(
 /// - `label`                   : label used by Findex
 /// - `entry_table_redis_url`   : Redis entry table URL
 /// - `chain_table_redis_url`   : Redis chain table URL
+/// - `pipeline_size`           : maximum number of keys per `MGET` command and
+///   of commands per pipeline, 0 to use the default size
 ///
 /// # Safety
 ///
@@ -226,84 +325,85 @@ pub unsafe extern "C" fn h_instantiate_with_redis_interface(
     label_ptr: *const i8,
     entry_table_redis_url_ptr: *const i8,
     chain_table_redis_url_ptr: *const i8,
+    pipeline_size: i32,
 ) -> i32 {
-    #[cfg(debug_assertions)]
-    log_init();
-
-    let key_bytes = ffi_read_bytes!("key", key_ptr, key_len);
-    let key = ffi_unwrap!(
-        SymmetricKey::try_from_slice(key_bytes),
-        "error deserializing findex key",
-        ErrorCode::Serialization
-    );
-    trace!("Key successfully parsed");
-
-    let label_bytes = ffi_read_string!("label", label_ptr);
-    let label = Label::from(label_bytes.as_str());
-    trace!("Label successfully parsed: label: {label}");
-
-    let entry_table_redis_url =
-        ffi_read_string!("Redis entry table URL", entry_table_redis_url_ptr);
-    let chain_table_redis_url =
-        ffi_read_string!("Redis chain table URL", chain_table_redis_url_ptr);
-
-    let config = Configuration::Redis(entry_table_redis_url, chain_table_redis_url);
-
-    let rt = ffi_unwrap!(
-        tokio::runtime::Runtime::new(),
-        "error creating Tokio runtime",
-        ErrorCode::Tokio
-    );
-    let findex = ffi_unwrap!(
-        rt.block_on(InstantiatedFindex::new(config)),
-        "error instantiating Findex with REST backend",
-        ErrorCode::Findex
-    );
+    ffi_guard!({
+        #[cfg(debug_assertions)]
+        log_init();
+
+        let key_bytes = ffi_read_bytes!("key", key_ptr, key_len);
+        let key = ffi_unwrap!(
+            SymmetricKey::try_from_slice(key_bytes),
+            "error deserializing findex key",
+            ErrorCode::Serialization
+        );
+        trace!("Key successfully parsed");
+
+        let label_bytes = ffi_read_string!("label", label_ptr);
+        let label = Label::from(label_bytes.as_str());
+        trace!("Label successfully parsed: label: {label}");
+
+        let entry_table_redis_url =
+            ffi_read_string!("Redis entry table URL", entry_table_redis_url_ptr);
+        let chain_table_redis_url =
+            ffi_read_string!("Redis chain table URL", chain_table_redis_url_ptr);
+
+        let pipeline_size = match ffi_unwrap!(
+            usize::try_from(pipeline_size),
+            "the Redis pipeline size should be positive",
+            ErrorCode::Findex
+        ) {
+            0 => DEFAULT_PIPELINE_SIZE,
+            pipeline_size => pipeline_size,
+        };
+
+        let config =
+            Configuration::Redis(entry_table_redis_url, chain_table_redis_url, pipeline_size);
+
+        let rt = ffi_unwrap!(
+            tokio::runtime::Runtime::new(),
+            "error creating Tokio runtime",
+            ErrorCode::Tokio
+        );
+        let findex = ffi_unwrap!(
+            rt.block_on(InstantiatedFindex::new(config)),
+            "error instantiating Findex with Redis backend",
+            ErrorCode::Findex
+        );
 
-    let mut cache = FINDEX_INSTANCES
-        .lock()
-        .expect("Findex instance cache lock poisoned.");
-    let handle = ffi_unwrap!(
-        <i32>::try_from(cache.len()),
-        "findex instance cache capacity overflow",
-        ErrorCode::Findex
-    );
-    cache.insert(handle, (key, label, findex));
+        let handle = ffi_unwrap!(
+            cache_instance(key, label, findex),
+            "findex instance cache capacity overflow",
+            ErrorCode::Findex
+        );
 
-    *findex_handle = handle;
+        *findex_handle = handle;
 
-    ErrorCode::Success.into()
+        ErrorCode::Success.into()
+    })
 }
 
-/// Searches the index for the given keywords.
-///
-/// At each search recursion, the passed `interrupt` function is called with the
-/// results from the current recursion level. The search is interrupted is
-/// `true` is returned.
-///
-/// # Parameters
+/// Searches the index for the given keywords, following at most `max_depth`
+/// aliases if given.
 ///
-/// - `results`         : (output) search result
-/// - `findex_handle`   : Findex handle on the instance cache
-/// - `keywords`        : serialized list of keywords
-/// - `interrupt`       : user interrupt called at each search iteration
-///
-/// # Safety
+/// If a match mode is given, the number of keywords matched by each location
+/// found is written instead of the results of each keyword.
 ///
-/// Cannot be safe since using FFI.
-#[no_mangle]
-#[tracing::instrument(ret, skip_all)]
-pub unsafe extern "C" fn h_search(
+/// The search fails if it is cancelled using
+/// [`h_cancel_searches`](h_cancel_searches) or if it lasts longer than the
+/// given timeout. Both are checked between two search rounds.
+#[allow(clippy::too_many_arguments)]
+unsafe fn search(
     results_ptr: *mut u8,
     results_len: *mut i32,
     findex_handle: i32,
     keywords_ptr: *const u8,
     keywords_len: i32,
-    interrupt: Interrupt,
+    max_depth: Option<usize>,
+    match_mode: Option<MatchMode>,
+    timeout: Option<Duration>,
+    interrupt: Option<Interrupt>,
 ) -> i32 {
-    #[cfg(debug_assertions)]
-    log_init();
-
     let keywords = ffi_unwrap!(
         deserialize_keyword_set(ffi_read_bytes!("keywords", keywords_ptr, keywords_len)),
         "error deserializing keywords",
@@ -312,25 +412,41 @@ pub unsafe extern "C" fn h_search(
     let keywords = Keywords::from(keywords);
     trace!("Keywords successfully parsed: keywords: {keywords}");
 
+    let instance = ffi_unwrap!(
+        get_instance(findex_handle),
+        "cannot get a hold on the Findex instance",
+        ErrorCode::Findex
+    );
+
+    let generation = instance.search_generation.load(Ordering::Acquire);
+    let deadline = timeout.map(|timeout| Instant::now() + timeout);
+    let search_generation = &instance.search_generation;
+
     let user_interrupt = |res: HashMap<Keyword, HashSet<IndexedValue<Keyword, Data>>>| async move {
+        if search_generation.load(Ordering::Acquire) != generation {
+            return Err("the search was cancelled".to_string());
+        }
+        if deadline.is_some_and(|deadline| Instant::now() >= deadline) {
+            return Err("the search timed out".to_string());
+        }
+        let Some(interrupt) = interrupt else {
+            return Ok(false);
+        };
         trace!("user interrupt input: {res:?}");
-        let bytes = serialize_intermediate_results(&res).map_err(|e| e.to_string())?;
+        let bytes =
+            add_format_version(&serialize_intermediate_results(&res).map_err(|e| e.to_string())?);
         let length = <u32>::try_from(bytes.len()).map_err(|e| e.to_string())?;
         let is_interrupted = 1 == (interrupt)(bytes.as_ptr(), length);
         trace!("user interrupt output: = {is_interrupted}");
         Ok(is_interrupted)
     };
 
-    let cache = FINDEX_INSTANCES
-        .lock()
-        .expect("Findex instance cache lock poisoned.");
-    let (key, label, findex) = ffi_unwrap!(
-        cache
-            .get(&findex_handle)
-            .ok_or_else(|| format!("no matching instance for handle {findex_handle}")),
-        "cannot get a hold on the Findex instance",
-        ErrorCode::Findex
-    );
+    let keying = instance
+        .keying
+        .read()
+        .expect("Findex instance keying lock poisoned.");
+    let (key, label) = &*keying;
+    let findex = &instance.findex;
 
     let rt = ffi_unwrap!(
         tokio::runtime::Runtime::new(),
@@ -338,7 +454,16 @@ pub unsafe extern "C" fn h_search(
         ErrorCode::Tokio
     );
 
-    let res = rt.block_on(findex.search(key, label, keywords, &user_interrupt));
+    let res = rt.block_on(async {
+        match max_depth {
+            Some(max_depth) => {
+                findex
+                    .search_with_max_depth(key, label, keywords, max_depth, &user_interrupt)
+                    .await
+            }
+            None => findex.search(key, label, keywords, &user_interrupt).await,
+        }
+    });
 
     let results = match res {
         Ok(res) => res,
@@ -354,6 +479,20 @@ pub unsafe extern "C" fn h_search(
         }
     };
 
+    if let Some(match_mode) = match_mode {
+        let serialized_scores = ffi_unwrap!(
+            serialize_location_scores(&match_mode.score(&results)),
+            "error serializing scores",
+            ErrorCode::Serialization
+        );
+        ffi_write_bytes!(
+            "search scores",
+            &serialized_scores,
+            results_ptr,
+            results_len
+        );
+    }
+
     // Serialize the results.
     // We should be able to use the output buffer as the `Serializer` sink to avoid
     // to copy the buffer (right now the `crypto_core` serializer doesn't provide a
@@ -386,25 +525,322 @@ pub unsafe extern "C" fn h_search(
     ffi_write_bytes!("search results", &serialized_uids, results_ptr, results_len);
 }
 
-/// Adds the given associations to the index.
+/// Searches the index for the given keywords.
+///
+/// At each search recursion, the passed `interrupt` function is called with the
+/// results from the current recursion level. The search is interrupted if 1 is
+/// returned. A NULL `interrupt` never interrupts the search.
 ///
 /// # Parameters
 ///
-/// - `results`         : (output) list of new keywords added to the index
+/// - `results`         : (output) search result
 /// - `findex_handle`   : Findex handle on the instance cache
-/// - `associations`    : map of values to sets of keywords
+/// - `keywords`        : serialized list of keywords
+/// - `interrupt`       : optional user interrupt called at each search iteration
 ///
 /// # Safety
 ///
 /// Cannot be safe since using FFI.
 #[no_mangle]
 #[tracing::instrument(ret, skip_all)]
-pub unsafe extern "C" fn h_add(
+pub unsafe extern "C" fn h_search(
+    results_ptr: *mut u8,
+    results_len: *mut i32,
+    findex_handle: i32,
+    keywords_ptr: *const u8,
+    keywords_len: i32,
+    interrupt: Option<Interrupt>,
+) -> i32 {
+    ffi_guard!({
+        #[cfg(debug_assertions)]
+        log_init();
+
+        search(
+            results_ptr,
+            results_len,
+            findex_handle,
+            keywords_ptr,
+            keywords_len,
+            None,
+            None,
+            None,
+            interrupt,
+        )
+    })
+}
+
+/// Searches the index for the given keywords, following at most `max_depth`
+/// aliases from each of them: a maximum depth of 0 only returns the data
+/// directly indexed under the given keywords.
+///
+/// The `interrupt` function is called as in [`h_search`](h_search) and can
+/// stop the search before the maximum depth is reached.
+///
+/// # Parameters
+///
+/// - `results`         : (output) search result
+/// - `findex_handle`   : Findex handle on the instance cache
+/// - `keywords`        : serialized list of keywords
+/// - `max_depth`       : maximum number of aliases to follow
+/// - `interrupt`       : optional user interrupt called at each search iteration
+///
+/// # Safety
+///
+/// Cannot be safe since using FFI.
+#[no_mangle]
+#[tracing::instrument(ret, skip_all)]
+pub unsafe extern "C" fn h_search_with_max_depth(
+    results_ptr: *mut u8,
+    results_len: *mut i32,
+    findex_handle: i32,
+    keywords_ptr: *const u8,
+    keywords_len: i32,
+    max_depth: i32,
+    interrupt: Option<Interrupt>,
+) -> i32 {
+    ffi_guard!({
+        #[cfg(debug_assertions)]
+        log_init();
+
+        let max_depth = ffi_unwrap!(
+            usize::try_from(max_depth),
+            "the maximum search depth should be positive",
+            ErrorCode::Findex
+        );
+
+        search(
+            results_ptr,
+            results_len,
+            findex_handle,
+            keywords_ptr,
+            keywords_len,
+            Some(max_depth),
+            None,
+            None,
+            interrupt,
+        )
+    })
+}
+
+/// Searches the index for the given keywords and writes the number of keywords
+/// matched by each location found, to rank them.
+///
+/// The `interrupt` function is called as in [`h_search`](h_search).
+///
+/// # Parameters
+///
+/// - `results`         : (output) serialized locations and their score
+/// - `findex_handle`   : Findex handle on the instance cache
+/// - `keywords`        : serialized list of keywords
+/// - `intersection`    : if 1, only the locations matching all the keywords are
+///   returned
+/// - `interrupt`       : optional user interrupt called at each search iteration
+///
+/// # Safety
+///
+/// Cannot be safe since using FFI.
+#[no_mangle]
+#[tracing::instrument(ret, skip_all)]
+pub unsafe extern "C" fn h_search_scored(
+    results_ptr: *mut u8,
+    results_len: *mut i32,
+    findex_handle: i32,
+    keywords_ptr: *const u8,
+    keywords_len: i32,
+    intersection: i32,
+    interrupt: Option<Interrupt>,
+) -> i32 {
+    ffi_guard!({
+        #[cfg(debug_assertions)]
+        log_init();
+
+        let match_mode = if intersection == 1 {
+            MatchMode::Intersection
+        } else {
+            MatchMode::Union
+        };
+
+        search(
+            results_ptr,
+            results_len,
+            findex_handle,
+            keywords_ptr,
+            keywords_len,
+            None,
+            Some(match_mode),
+            None,
+            interrupt,
+        )
+    })
+}
+
+/// Searches the index for the given keywords, failing if the search lasts
+/// longer than `timeout_ms` milliseconds.
+///
+/// The timeout is checked between two search rounds: a search over a large
+/// alias graph is aborted at the first round starting after the timeout,
+/// instead of blocking until completion. The `interrupt` function is called as
+/// in [`h_search`](h_search).
+///
+/// # Parameters
+///
+/// - `results`         : (output) search result
+/// - `findex_handle`   : Findex handle on the instance cache
+/// - `keywords`        : serialized list of keywords
+/// - `timeout_ms`      : maximum duration of the search in milliseconds
+/// - `interrupt`       : optional user interrupt called at each search iteration
+///
+/// # Safety
+///
+/// Cannot be safe since using FFI.
+#[no_mangle]
+#[tracing::instrument(ret, skip_all)]
+pub unsafe extern "C" fn h_search_with_timeout(
+    results_ptr: *mut u8,
+    results_len: *mut i32,
+    findex_handle: i32,
+    keywords_ptr: *const u8,
+    keywords_len: i32,
+    timeout_ms: i32,
+    interrupt: Option<Interrupt>,
+) -> i32 {
+    ffi_guard!({
+        #[cfg(debug_assertions)]
+        log_init();
+
+        let timeout_ms = ffi_unwrap!(
+            u64::try_from(timeout_ms),
+            "the search timeout should be positive",
+            ErrorCode::InvalidArgument("timeout_ms".to_string())
+        );
+
+        search(
+            results_ptr,
+            results_len,
+            findex_handle,
+            keywords_ptr,
+            keywords_len,
+            None,
+            None,
+            Some(Duration::from_millis(timeout_ms)),
+            interrupt,
+        )
+    })
+}
+
+/// Cancels the searches in progress on the given Findex instance, e.g. upon
+/// user request from the host UI.
+///
+/// Each of these searches fails at its next round. Searches started after this
+/// call are not affected.
+///
+/// # Safety
+///
+/// Cannot be safe since using FFI.
+#[no_mangle]
+pub unsafe extern "C" fn h_cancel_searches(findex_handle: i32) -> i32 {
+    ffi_guard!({
+        let instance = ffi_unwrap!(
+            get_instance(findex_handle),
+            "cannot get a hold on the Findex instance",
+            ErrorCode::Findex
+        );
+        instance.search_generation.fetch_add(1, Ordering::AcqRel);
+        ErrorCode::Success.into()
+    })
+}
+
+/// Normalizes the keywords before adding, deleting or searching them using the
+/// given Findex instance, e.g. for "Café", "café" and "CAFÉ" to be the same
+/// keyword. The search results are returned under the keywords requested.
+///
+/// The UTF-8 keywords are lowercased if `lowercase` is not 0, then converted to
+/// the Unicode Normalization Form C if `nfc` is not 0, then given to the
+/// `normalize` callback if not null, see [`Normalize`].
+///
+/// This function should be called right after the instantiation: it fails if
+/// an operation is in progress on the instance. The same normalization must be
+/// used for the whole life of an index.
+///
+/// # Safety
+///
+/// Cannot be safe since using FFI.
+#[no_mangle]
+#[tracing::instrument(ret, skip_all)]
+pub unsafe extern "C" fn h_set_keyword_normalization(
+    findex_handle: i32,
+    lowercase: i32,
+    nfc: i32,
+    normalize: Option<Normalize>,
+) -> i32 {
+    ffi_guard!({
+        let mut normalizer = KeywordNormalizer::default();
+        if lowercase != 0 {
+            normalizer = normalizer.with_lowercase();
+        }
+        if nfc != 0 {
+            normalizer = normalizer.with_nfc();
+        }
+        if let Some(normalize) = normalize {
+            normalizer = normalizer.with_callback(Arc::new(move |keyword: &[u8]| {
+                normalize_keyword(normalize, keyword).map_err(|e| e.to_string())
+            }));
+        }
+
+        ffi_unwrap!(
+            modify_instance(findex_handle, |findex| findex
+                .set_keyword_normalizer(normalizer)),
+            "cannot set the keyword normalization",
+            ErrorCode::Findex
+        );
+        ErrorCode::Success.into()
+    })
+}
+
+/// Pads the chains of the index with dummy values upon each addition and
+/// deletion using the given Findex instance, for the number of values indexed
+/// under each keyword to be a multiple of `bucket_size`. A bucket size of 0
+/// disables the padding.
+///
+/// This function should be called right after the instantiation: it fails if
+/// an operation is in progress on the instance. The same padding should be
+/// used for the whole life of an index.
+///
+/// # Safety
+///
+/// Cannot be safe since using FFI.
+#[no_mangle]
+#[tracing::instrument(ret, skip_all)]
+pub unsafe extern "C" fn h_set_chain_padding(findex_handle: i32, bucket_size: u32) -> i32 {
+    ffi_guard!({
+        let padding = if bucket_size == 0 {
+            None
+        } else {
+            Some(ffi_unwrap!(
+                ChainPadding::new(bucket_size as usize),
+                "invalid chain padding",
+                ErrorCode::Findex
+            ))
+        };
+        ffi_unwrap!(
+            modify_instance(findex_handle, |findex| findex.set_chain_padding(padding)),
+            "cannot set the chain padding",
+            ErrorCode::Findex
+        );
+        ErrorCode::Success.into()
+    })
+}
+
+/// Adds the given associations to the index, in chunks of at most
+/// `chunk_size` associations starting from the chunk `first_chunk` if given.
+/// The number of chunks indexed is written in `indexed_chunks`.
+unsafe fn add(
     results_ptr: *mut u8,
     results_len: *mut i32,
     findex_handle: i32,
     associations_ptr: *const u8,
     associations_len: i32,
+    chunking: Option<(usize, usize, *mut i32)>,
 ) -> i32 {
     #[cfg(debug_assertions)]
     log_init();
@@ -418,25 +854,30 @@ pub unsafe extern "C" fn h_add(
 
     let output_size = get_upsert_output_size(&associations);
     if *results_len < output_size as i32 {
+        debug!(
+            allocated = *results_len,
+            required = output_size,
+            "add: results buffer too small"
+        );
         set_last_error(FfiError::Generic(format!(
             "The pre-allocated add results buffer is too small; need {} bytes, allocated {}",
-            output_size, results_len as i32
+            output_size, *results_len
         )));
         *results_len = output_size as i32;
         return ErrorCode::BufferTooSmall.into();
     }
 
-    let cache = FINDEX_INSTANCES
-        .lock()
-        .expect("Findex instance cache lock poisoned.");
-
-    let (key, label, findex) = ffi_unwrap!(
-        cache
-            .get(&findex_handle)
-            .ok_or_else(|| format!("no matching instance for handle {findex_handle}")),
+    let instance = ffi_unwrap!(
+        get_instance(findex_handle),
         "cannot get a hold on the Findex instance",
         ErrorCode::Findex
     );
+    let keying = instance
+        .keying
+        .read()
+        .expect("Findex instance keying lock poisoned.");
+    let (key, label) = &*keying;
+    let findex = &instance.findex;
 
     trace!("instantiated Findex: {findex:?}");
 
@@ -446,7 +887,25 @@ pub unsafe extern "C" fn h_add(
         ErrorCode::Tokio
     );
 
-    let res = rt.block_on(findex.add(key, label, associations));
+    let res = match chunking {
+        Some((chunk_size, first_chunk, indexed_chunks)) => {
+            let res = rt.block_on(findex.add_in_chunks(
+                key,
+                label,
+                associations,
+                chunk_size,
+                first_chunk,
+            ));
+            match res {
+                Ok(new_keywords) => Ok(new_keywords),
+                Err(e) => {
+                    *indexed_chunks = e.indexed_chunks as i32;
+                    Err(e.error)
+                }
+            }
+        }
+        None => rt.block_on(findex.add(key, label, associations)),
+    };
 
     let new_keywords = match res {
         Ok(new_keywords) => new_keywords,
@@ -477,6 +936,94 @@ pub unsafe extern "C" fn h_add(
     );
 }
 
+/// Adds the given associations to the index.
+///
+/// # Parameters
+///
+/// - `results`         : (output) list of new keywords added to the index
+/// - `findex_handle`   : Findex handle on the instance cache
+/// - `associations`    : map of values to sets of keywords
+///
+/// # Safety
+///
+/// Cannot be safe since using FFI.
+#[no_mangle]
+#[tracing::instrument(ret, skip_all)]
+pub unsafe extern "C" fn h_add(
+    results_ptr: *mut u8,
+    results_len: *mut i32,
+    findex_handle: i32,
+    associations_ptr: *const u8,
+    associations_len: i32,
+) -> i32 {
+    ffi_guard!({
+        add(
+            results_ptr,
+            results_len,
+            findex_handle,
+            associations_ptr,
+            associations_len,
+            None,
+        )
+    })
+}
+
+/// Adds the given associations to the index in chunks of at most `chunk_size`
+/// associations, in order not to exceed the buffer limits of the callbacks
+/// when indexing large amounts of data.
+///
+/// Upon failure, the number of chunks successfully indexed is written in
+/// `indexed_chunks`: calling again this function with the same associations,
+/// the same chunk size and this number as `first_chunk` resumes the
+/// indexation.
+///
+/// # Parameters
+///
+/// - `results`         : (output) list of new keywords added to the index
+/// - `indexed_chunks`  : (output) number of chunks indexed upon failure
+/// - `findex_handle`   : Findex handle on the instance cache
+/// - `associations`    : map of values to sets of keywords
+/// - `chunk_size`      : maximum number of associations indexed per chunk
+/// - `first_chunk`     : index of the first chunk to index (0 to index all)
+///
+/// # Safety
+///
+/// Cannot be safe since using FFI.
+#[no_mangle]
+#[allow(clippy::too_many_arguments)]
+#[tracing::instrument(ret, skip_all)]
+pub unsafe extern "C" fn h_add_in_chunks(
+    results_ptr: *mut u8,
+    results_len: *mut i32,
+    indexed_chunks: *mut i32,
+    findex_handle: i32,
+    associations_ptr: *const u8,
+    associations_len: i32,
+    chunk_size: i32,
+    first_chunk: i32,
+) -> i32 {
+    ffi_guard!({
+        let chunk_size = ffi_unwrap!(
+            usize::try_from(chunk_size),
+            "chunk size should be positive",
+            ErrorCode::Findex
+        );
+        let first_chunk = ffi_unwrap!(
+            usize::try_from(first_chunk),
+            "first chunk should be positive",
+            ErrorCode::Findex
+        );
+        add(
+            results_ptr,
+            results_len,
+            findex_handle,
+            associations_ptr,
+            associations_len,
+            Some((chunk_size, first_chunk, indexed_chunks)),
+        )
+    })
+}
+
 /// Removes the given associations from the index.
 ///
 /// # Parameters
@@ -497,39 +1044,123 @@ pub unsafe extern "C" fn h_delete(
     associations_ptr: *const u8,
     associations_len: i32,
 ) -> i32 {
-    #[cfg(debug_assertions)]
-    log_init();
+    ffi_guard!({
+        #[cfg(debug_assertions)]
+        log_init();
+
+        let associations_bytes =
+            ffi_read_bytes!("associations", associations_ptr, associations_len);
+        let associations = IndexedValueToKeywordsMap::from(ffi_unwrap!(
+            deserialize_indexed_values(associations_bytes),
+            "failed deserialize indexed values (associations)",
+            ErrorCode::Serialization
+        ));
 
-    let associations_bytes = ffi_read_bytes!("associations", associations_ptr, associations_len);
-    let associations = IndexedValueToKeywordsMap::from(ffi_unwrap!(
-        deserialize_indexed_values(associations_bytes),
-        "failed deserialize indexed values (associations)",
+        let output_size = get_upsert_output_size(&associations);
+        if *results_len < output_size as i32 {
+            set_last_error(FfiError::Generic(format!(
+                "The pre-allocated add results buffer is too small; need {} bytes, allocated {}",
+                output_size, results_len as i32
+            )));
+            *results_len = output_size as i32;
+            return ErrorCode::BufferTooSmall.into();
+        }
+
+        let instance = ffi_unwrap!(
+            get_instance(findex_handle),
+            "cannot get a hold on the Findex instance",
+            ErrorCode::Findex
+        );
+        let keying = instance
+            .keying
+            .read()
+            .expect("Findex instance keying lock poisoned.");
+        let (key, label) = &*keying;
+        let findex = &instance.findex;
+
+        trace!("instantiated Findex: {findex:?}");
+
+        let rt = ffi_unwrap!(
+            tokio::runtime::Runtime::new(),
+            "error creating Tokio runtime",
+            ErrorCode::Tokio
+        );
+
+        let res = rt.block_on(findex.delete(key, label, associations));
+
+        let new_keywords = match res {
+            Ok(new_keywords) => new_keywords,
+            Err(FindexError::DbInterface(DbInterfaceError::Ffi(msg, code))) => {
+                set_last_error(FfiError::Generic(format!(
+                    "backend error during `delete` operation: {msg}"
+                )));
+                return code.into();
+            }
+            Err(e) => {
+                set_last_error(FfiError::Generic(format!("findex `delete` error: {e}")));
+                return ErrorCode::Findex.into();
+            }
+        };
+
+        // Serialize the results.
+        let serialized_keywords = ffi_unwrap!(
+            serialize_keyword_set(&new_keywords),
+            "serialize new keywords",
+            ErrorCode::Serialization
+        );
+
+        ffi_write_bytes!(
+            "delete results",
+            &serialized_keywords,
+            results_ptr,
+            results_len
+        );
+    })
+}
+
+/// Adds (or deletes) the given aliases to (from) the index.
+unsafe fn upsert_aliases(
+    results_ptr: *mut u8,
+    results_len: *mut i32,
+    findex_handle: i32,
+    aliases_ptr: *const u8,
+    aliases_len: i32,
+    is_deletion: bool,
+) -> i32 {
+    let operation = if is_deletion {
+        "delete_aliases"
+    } else {
+        "add_aliases"
+    };
+
+    let aliases_bytes = ffi_read_bytes!("aliases", aliases_ptr, aliases_len);
+    let aliases = ffi_unwrap!(
+        deserialize_keyword_to_keywords(aliases_bytes),
+        "failed deserialize aliases",
         ErrorCode::Serialization
-    ));
+    );
 
-    let output_size = get_upsert_output_size(&associations);
+    let output_size = get_aliases_output_size(&aliases);
     if *results_len < output_size as i32 {
         set_last_error(FfiError::Generic(format!(
-            "The pre-allocated add results buffer is too small; need {} bytes, allocated {}",
+            "The {operation} results buffer is too small; need {} bytes, allocated {}",
             output_size, results_len as i32
         )));
         *results_len = output_size as i32;
         return ErrorCode::BufferTooSmall.into();
     }
 
-    let cache = FINDEX_INSTANCES
-        .lock()
-        .expect("Findex instance cache lock poisoned.");
-
-    let (key, label, findex) = ffi_unwrap!(
-        cache
-            .get(&findex_handle)
-            .ok_or_else(|| format!("no matching instance for handle {findex_handle}")),
+    let instance = ffi_unwrap!(
+        get_instance(findex_handle),
         "cannot get a hold on the Findex instance",
         ErrorCode::Findex
     );
-
-    trace!("instantiated Findex: {findex:?}");
+    let keying = instance
+        .keying
+        .read()
+        .expect("Findex instance keying lock poisoned.");
+    let (key, label) = &*keying;
+    let findex = &instance.findex;
 
     let rt = ffi_unwrap!(
         tokio::runtime::Runtime::new(),
@@ -537,37 +1168,117 @@ pub unsafe extern "C" fn h_delete(
         ErrorCode::Tokio
     );
 
-    let res = rt.block_on(findex.delete(key, label, associations));
+    let res = if is_deletion {
+        rt.block_on(findex.delete_aliases(key, label, aliases))
+    } else {
+        rt.block_on(findex.add_aliases(key, label, aliases))
+    };
 
-    let new_keywords = match res {
-        Ok(new_keywords) => new_keywords,
+    let new_aliases = match res {
+        Ok(new_aliases) => new_aliases,
         Err(FindexError::DbInterface(DbInterfaceError::Ffi(msg, code))) => {
             set_last_error(FfiError::Generic(format!(
-                "backend error during `delete` operation: {msg}"
+                "backend error during `{operation}` operation: {msg}"
             )));
             return code.into();
         }
         Err(e) => {
-            set_last_error(FfiError::Generic(format!("findex `delete` error: {e}")));
+            set_last_error(FfiError::Generic(format!(
+                "findex `{operation}` error: {e}"
+            )));
             return ErrorCode::Findex.into();
         }
     };
 
     // Serialize the results.
-    let serialized_keywords = ffi_unwrap!(
-        serialize_keyword_set(&new_keywords),
-        "serialize new keywords",
+    let serialized_aliases = ffi_unwrap!(
+        serialize_keyword_set(&new_aliases),
+        "serialize new aliases",
         ErrorCode::Serialization
     );
 
     ffi_write_bytes!(
-        "delete results",
-        &serialized_keywords,
+        "aliases results",
+        &serialized_aliases,
         results_ptr,
         results_len
     );
 }
 
+/// Adds the given aliases to the index: searching for an alias then returns
+/// the data indexed under the keyword it points to.
+///
+/// Like [`h_add`](h_add), this function should first be called with a
+/// `results_len` of 0 to get the size of the results buffer to allocate.
+///
+/// # Parameters
+///
+/// - `results`         : (output) list of new aliases added to the index
+/// - `findex_handle`   : Findex handle on the instance cache
+/// - `aliases`         : map of keywords to the sets of their aliases
+///
+/// # Safety
+///
+/// Cannot be safe since using FFI.
+#[no_mangle]
+#[tracing::instrument(ret, skip_all)]
+pub unsafe extern "C" fn h_add_aliases(
+    results_ptr: *mut u8,
+    results_len: *mut i32,
+    findex_handle: i32,
+    aliases_ptr: *const u8,
+    aliases_len: i32,
+) -> i32 {
+    ffi_guard!({
+        #[cfg(debug_assertions)]
+        log_init();
+
+        upsert_aliases(
+            results_ptr,
+            results_len,
+            findex_handle,
+            aliases_ptr,
+            aliases_len,
+            false,
+        )
+    })
+}
+
+/// Removes the given aliases from the index.
+///
+/// # Parameters
+///
+/// - `results`         : (output) list of aliases removed from the index
+/// - `findex_handle`   : Findex handle on the instance cache
+/// - `aliases`         : map of keywords to the sets of their aliases
+///
+/// # Safety
+///
+/// Cannot be safe since using FFI.
+#[no_mangle]
+#[tracing::instrument(ret, skip_all)]
+pub unsafe extern "C" fn h_delete_aliases(
+    results_ptr: *mut u8,
+    results_len: *mut i32,
+    findex_handle: i32,
+    aliases_ptr: *const u8,
+    aliases_len: i32,
+) -> i32 {
+    ffi_guard!({
+        #[cfg(debug_assertions)]
+        log_init();
+
+        upsert_aliases(
+            results_ptr,
+            results_len,
+            findex_handle,
+            aliases_ptr,
+            aliases_len,
+            true,
+        )
+    })
+}
+
 /// Replaces all the Index Entry Table UIDs and values. New UIDs are derived
 /// using the given label and the KMAC key derived from the new key. The
 /// values are decrypted using the DEM key derived from the key and
@@ -606,85 +1317,153 @@ pub unsafe extern "C" fn h_compact(
     compacting_rate: f64,
     filter_obsolete_data: FilterObsoleteData,
 ) -> i32 {
-    #[cfg(debug_assertions)]
-    log_init();
-
-    let filter = |data: HashSet<Data>| async {
-        let moved_data = data;
-        let bytes =
-            serialize_data_set(&moved_data).map_err(|e| format!("error serializing data: {e}"))?;
-        let mut res = vec![0; bytes.len()];
-        let mut res_length = res.len() as u32;
-        let err = (filter_obsolete_data)(
-            res.as_mut_ptr(),
-            &mut res_length,
-            bytes.as_ptr(),
-            bytes.len() as u32,
-        );
-
-        if err != 0 {
-            set_last_error(FfiError::Generic(format!("filter callback error: {err}")));
-            return Err(String::from("Filter error."));
-        }
-
-        deserialize_data_set(&res).map_err(|e| format!("error deserializing filtered data: {e}"))
-    };
+    ffi_guard!({
+        #[cfg(debug_assertions)]
+        log_init();
+
+        let filter = |data: HashSet<Data>| async {
+            let moved_data = data;
+            let bytes = add_format_version(
+                &serialize_data_set(&moved_data)
+                    .map_err(|e| format!("error serializing data: {e}"))?,
+            );
+            let mut res = vec![0; bytes.len()];
+            let mut res_length = res.len() as u32;
+            let err = (filter_obsolete_data)(
+                res.as_mut_ptr(),
+                &mut res_length,
+                bytes.as_ptr(),
+                bytes.len() as u32,
+            );
+
+            if err != 0 {
+                set_last_error(FfiError::Generic(format!("filter callback error: {err}")));
+                return Err(String::from("Filter error."));
+            }
+
+            strip_format_version(&res[..res.len().min(res_length as usize)])
+                .and_then(deserialize_data_set)
+                .map_err(|e| format!("error deserializing filtered data: {e}"))
+        };
+
+        let new_key_bytes = ffi_read_bytes!("new key", new_key_ptr, new_key_len);
+        let new_key = ffi_unwrap!(
+            SymmetricKey::try_from_slice(new_key_bytes),
+            "error deserializing new findex key",
+            ErrorCode::Serialization
+        );
 
-    let new_key_bytes = ffi_read_bytes!("new key", new_key_ptr, new_key_len);
-    let new_key = ffi_unwrap!(
-        SymmetricKey::try_from_slice(new_key_bytes),
-        "error deserializing new findex key",
-        ErrorCode::Serialization
-    );
+        let new_label_bytes = ffi_read_string!("new label", new_label_ptr);
+        let new_label = Label::from(new_label_bytes.as_str());
 
-    let new_label_bytes = ffi_read_string!("new label", new_label_ptr);
-    let new_label = Label::from(new_label_bytes.as_str());
+        let instance = ffi_unwrap!(
+            get_instance(findex_handle),
+            "cannot get a hold on the Findex instance",
+            ErrorCode::Findex
+        );
+        // Other operations wait for the compaction to complete.
+        let mut keying = instance
+            .keying
+            .write()
+            .expect("Findex instance keying lock poisoned.");
+        let (old_key, old_label) = &mut *keying;
+        let findex = &instance.findex;
+
+        let rt = ffi_unwrap!(
+            tokio::runtime::Runtime::new(),
+            "error creating Tokio runtime",
+            ErrorCode::Tokio
+        );
 
-    let mut cache = FINDEX_INSTANCES
-        .lock()
-        .expect("Findex instance cache lock poisoned.");
+        trace!("instantiated Findex: {findex:?}");
+        let res = rt.block_on(findex.compact(
+            old_key,
+            &new_key,
+            old_label,
+            &new_label,
+            compacting_rate,
+            &filter,
+        ));
+
+        match res {
+            Err(FindexError::DbInterface(DbInterfaceError::Ffi(msg, code))) => {
+                set_last_error(FfiError::Generic(format!(
+                    "backend error during `compact` operation: {msg}"
+                )));
+                code.into()
+            }
+            Err(e) => {
+                set_last_error(FfiError::Generic(format!("findex `compact` error: {e}")));
+                ErrorCode::Findex.into()
+            }
+            Ok(()) => {
+                *old_key = new_key;
+                *old_label = new_label;
+                ErrorCode::Success.into()
+            }
+        }
+    })
+}
 
-    let (old_key, old_label, findex) = ffi_unwrap!(
-        cache
-            .get_mut(&findex_handle)
-            .ok_or_else(|| format!("no matching instance for handle {findex_handle}")),
-        "cannot get a hold on the Findex instance",
-        ErrorCode::Findex
-    );
+/// Lists the tokens of the index Entry Table, i.e. one token per indexed
+/// keyword.
+///
+/// # Parameters
+///
+/// - `tokens`          : Returns the serialized set of tokens
+/// - `findex_handle`   : Findex handle on the instance cache
+///
+/// # Safety
+///
+/// Cannot be safe since using FFI.
+#[no_mangle]
+#[tracing::instrument(ret, skip_all)]
+pub unsafe extern "C" fn h_dump_tokens(
+    tokens_ptr: *mut u8,
+    tokens_len: *mut i32,
+    findex_handle: i32,
+) -> i32 {
+    ffi_guard!({
+        #[cfg(debug_assertions)]
+        log_init();
+
+        let instance = ffi_unwrap!(
+            get_instance(findex_handle),
+            "cannot get a hold on the Findex instance",
+            ErrorCode::Findex
+        );
+        let findex = &instance.findex;
 
-    let rt = ffi_unwrap!(
-        tokio::runtime::Runtime::new(),
-        "error creating Tokio runtime",
-        ErrorCode::Tokio
-    );
+        let rt = ffi_unwrap!(
+            tokio::runtime::Runtime::new(),
+            "error creating Tokio runtime",
+            ErrorCode::Tokio
+        );
 
-    trace!("instantiated Findex: {findex:?}");
-    let res = rt.block_on(findex.compact(
-        old_key,
-        &new_key,
-        old_label,
-        &new_label,
-        compacting_rate,
-        &filter,
-    ));
+        let tokens = match rt.block_on(findex.dump_tokens()) {
+            Ok(tokens) => tokens,
+            Err(DbInterfaceError::Ffi(msg, code)) => {
+                set_last_error(FfiError::Generic(format!(
+                    "backend error during `dump_tokens` operation: {msg}"
+                )));
+                return code.into();
+            }
+            Err(e) => {
+                set_last_error(FfiError::Generic(format!(
+                    "findex `dump_tokens` error: {e}"
+                )));
+                return ErrorCode::Findex.into();
+            }
+        };
+
+        let serialized_tokens = ffi_unwrap!(
+            serialize_token_set(&tokens),
+            "serialize tokens",
+            ErrorCode::Serialization
+        );
 
-    match res {
-        Err(FindexError::DbInterface(DbInterfaceError::Ffi(msg, code))) => {
-            set_last_error(FfiError::Generic(format!(
-                "backend error during `compact` operation: {msg}"
-            )));
-            code.into()
-        }
-        Err(e) => {
-            set_last_error(FfiError::Generic(format!("findex `compact` error: {e}")));
-            ErrorCode::Findex.into()
-        }
-        Ok(()) => {
-            *old_key = new_key;
-            *old_label = new_label;
-            ErrorCode::Success.into()
-        }
-    }
+        ffi_write_bytes!("tokens", &serialized_tokens, tokens_ptr, tokens_len);
+    })
 }
 
 /// Generate a new Findex token from the provided index ID and signature seeds,
@@ -712,81 +1491,106 @@ pub unsafe extern "C" fn h_generate_new_token(
     insert_chains_seed_ptr: *const u8,
     insert_chains_seed_len: i32,
 ) -> i32 {
-    #[cfg(debug_assertions)]
-    log_init();
+    ffi_guard!({
+        #[cfg(debug_assertions)]
+        log_init();
 
-    let index_id: String = ffi_read_string!("index id", index_id_ptr);
+        let index_id: String = ffi_read_string!("index id", index_id_ptr);
 
-    let fetch_entries_seed = ffi_read_bytes!(
-        "fetch_entries_seed",
-        fetch_entries_seed_ptr,
-        fetch_entries_seed_len
-    );
-    let fetch_chains_seed = ffi_read_bytes!(
-        "fetch_chains_seed",
-        fetch_chains_seed_ptr,
-        fetch_chains_seed_len
-    );
-    let upsert_entries_seed = ffi_read_bytes!(
-        "upsert_entries_seed",
-        upsert_entries_seed_ptr,
-        upsert_entries_seed_len
-    );
-    let insert_chains_seed = ffi_read_bytes!(
-        "insert_chains_seed",
-        insert_chains_seed_ptr,
-        insert_chains_seed_len
-    );
+        let fetch_entries_seed = ffi_read_bytes!(
+            "fetch_entries_seed",
+            fetch_entries_seed_ptr,
+            fetch_entries_seed_len
+        );
+        let fetch_chains_seed = ffi_read_bytes!(
+            "fetch_chains_seed",
+            fetch_chains_seed_ptr,
+            fetch_chains_seed_len
+        );
+        let upsert_entries_seed = ffi_read_bytes!(
+            "upsert_entries_seed",
+            upsert_entries_seed_ptr,
+            upsert_entries_seed_len
+        );
+        let insert_chains_seed = ffi_read_bytes!(
+            "insert_chains_seed",
+            insert_chains_seed_ptr,
+            insert_chains_seed_len
+        );
 
-    let mut seeds = HashMap::new();
-    seeds.insert(
-        CallbackPrefix::FetchEntry,
-        ffi_unwrap!(
-            SymmetricKey::try_from_slice(fetch_entries_seed),
-            "fetch_entries_seed is of wrong size",
-            ErrorCode::Serialization
-        ),
-    );
-    seeds.insert(
-        CallbackPrefix::FetchChain,
-        ffi_unwrap!(
-            SymmetricKey::try_from_slice(fetch_chains_seed),
-            "fetch_chains_seed is of wrong size",
-            ErrorCode::Serialization
-        ),
-    );
-    seeds.insert(
-        CallbackPrefix::Upsert,
-        ffi_unwrap!(
-            SymmetricKey::try_from_slice(upsert_entries_seed),
-            "upsert_entries_seed is of wrong size",
-            ErrorCode::Serialization
-        ),
-    );
-    seeds.insert(
-        CallbackPrefix::Insert,
-        ffi_unwrap!(
-            SymmetricKey::try_from_slice(insert_chains_seed),
-            "insert_chains_seed is of wrong size",
-            ErrorCode::Serialization
-        ),
-    );
+        let mut seeds = HashMap::new();
+        seeds.insert(
+            CallbackPrefix::FetchEntry,
+            ffi_unwrap!(
+                SymmetricKey::try_from_slice(fetch_entries_seed),
+                "fetch_entries_seed is of wrong size",
+                ErrorCode::Serialization
+            ),
+        );
+        seeds.insert(
+            CallbackPrefix::FetchChain,
+            ffi_unwrap!(
+                SymmetricKey::try_from_slice(fetch_chains_seed),
+                "fetch_chains_seed is of wrong size",
+                ErrorCode::Serialization
+            ),
+        );
+        seeds.insert(
+            CallbackPrefix::Upsert,
+            ffi_unwrap!(
+                SymmetricKey::try_from_slice(upsert_entries_seed),
+                "upsert_entries_seed is of wrong size",
+                ErrorCode::Serialization
+            ),
+        );
+        seeds.insert(
+            CallbackPrefix::Insert,
+            ffi_unwrap!(
+                SymmetricKey::try_from_slice(insert_chains_seed),
+                "insert_chains_seed is of wrong size",
+                ErrorCode::Serialization
+            ),
+        );
 
-    let mut rng = CsRng::from_entropy();
-    let findex_key = SymmetricKey::new(&mut rng);
+        let mut rng = CsRng::from_entropy();
+        let findex_key = SymmetricKey::new(&mut rng);
 
-    let token = ffi_unwrap!(
-        AuthorizationToken::new(index_id, findex_key, seeds),
-        "generate authorization token",
-        ErrorCode::Findex
-    );
+        let token = ffi_unwrap!(
+            AuthorizationToken::new(index_id, findex_key, seeds),
+            "generate authorization token",
+            ErrorCode::Findex
+        );
 
-    ffi_write_bytes!(
-        "search results",
-        token.to_string().as_bytes(),
-        token_ptr,
-        token_len
-    );
+        ffi_write_bytes!(
+            "search results",
+            token.to_string().as_bytes(),
+            token_ptr,
+            token_len
+        );
+    })
+}
+
+/// Sets the logs displayed by Findex, overriding the `RUST_LOG` environment
+/// variable, e.g. `debug` to see the upsert conflicts, the retries and the
+/// callback errors. The given level uses the `RUST_LOG` syntax.
+///
+/// Fails if another tracing subscriber is installed, e.g. by
+/// `h_install_otlp_exporter`.
+///
+/// # Safety
+///
+/// Cannot be safe since using FFI.
+#[no_mangle]
+pub unsafe extern "C" fn h_set_log_level(level_ptr: *const i8) -> i32 {
+    ffi_guard!({
+        let level = ffi_read_string!("log level", level_ptr);
+        ffi_unwrap!(
+            crate::logger::set_log_level(&level),
+            "error setting the log level",
+            ErrorCode::InvalidArgument("level".to_string())
+        );
+        ErrorCode::Success.into()
+    })
 }
 
 /// Re-export the `cosmian_ffi` `h_get_error` function to clients with the old
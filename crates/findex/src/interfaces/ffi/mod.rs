@@ -1,2 +1,5 @@
 //! Defines the FFI interface for Findex.
 mod api;
+
+#[cfg(feature = "telemetry")]
+mod telemetry;
@@ -0,0 +1,40 @@
+//! Defines the FFI API of the Findex telemetry.
+
+use cosmian_ffi_utils::{ffi_guard, ffi_read_string, ffi_unwrap, ErrorCode};
+
+use crate::telemetry::{install_otlp_exporter, shutdown_otlp_exporter};
+
+/// Installs an OpenTelemetry exporter sending the Findex tracing spans to the
+/// OTLP collector listening at the given gRPC endpoint.
+///
+/// Spans are filtered using the `RUST_LOG` environment variable. This function
+/// should be called once, before instantiating Findex.
+///
+/// # Safety
+///
+/// Cannot be safe since using FFI.
+#[no_mangle]
+pub unsafe extern "C" fn h_install_otlp_exporter(
+    endpoint_ptr: *const i8,
+    service_name_ptr: *const i8,
+) -> i32 {
+    ffi_guard!({
+        let endpoint = ffi_read_string!("endpoint", endpoint_ptr);
+        let service_name = ffi_read_string!("service name", service_name_ptr);
+        ffi_unwrap!(
+            install_otlp_exporter(&endpoint, &service_name),
+            "error installing the OTLP exporter",
+            ErrorCode::Backend
+        );
+        ErrorCode::Success.into()
+    })
+}
+
+/// Flushes the pending spans and shuts the OTLP exporter down.
+#[no_mangle]
+pub extern "C" fn h_shutdown_otlp_exporter() -> i32 {
+    ffi_guard!({
+        shutdown_otlp_exporter();
+        ErrorCode::Success.into()
+    })
+}
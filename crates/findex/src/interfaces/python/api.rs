@@ -1,6 +1,7 @@
 use std::{
     collections::{HashMap, HashSet},
     str::FromStr,
+    sync::Arc,
 };
 
 use cosmian_crypto_core::FixedSizeCBytes;
@@ -8,19 +9,24 @@ use cosmian_findex::{
     Data, IndexedValue as IndexedValueRust, IndexedValueToKeywordsMap, Keyword, KeywordToDataMap,
     Label, UserKey,
 };
-use pyo3::prelude::*;
+use pyo3::{prelude::*, types::PyBytes};
 use tokio::runtime::Runtime;
 
-use super::types::ToKeyword;
+use super::{dataframe::dataframe_to_associations, types::ToKeyword};
 use crate::{
-    db_interfaces::{custom::python::PythonCallbacks, rest::AuthorizationToken},
+    db_interfaces::{
+        custom::python::PythonCallbacks,
+        redis::DEFAULT_PIPELINE_SIZE,
+        rest::{AuthorizationToken, RateLimit, RequestOptions, DEFAULT_MAX_CONFLICT_ROUNDS},
+    },
     interfaces::python::types::{
         Key as KeyPy, Keyword as KeywordPy, Location as LocationPy, ToIndexedValue,
     },
-    Configuration, InstantiatedFindex,
+    ChainPadding, Configuration, InstantiatedFindex, KeywordNormalizer, KeywordToAliasesMap,
+    MatchMode,
 };
 
-#[pyclass(unsendable)]
+#[pyclass]
 pub struct Findex {
     runtime: Runtime,
     key: UserKey,
@@ -30,17 +36,20 @@ pub struct Findex {
 
 #[pymethods]
 impl Findex {
-    /// Instantiates Findex with an SQLite interface.
+    /// Instantiates Findex with an SQLite interface. If a `sqlcipher_key` is
+    /// given, the database is encrypted at rest using SQLCipher.
     #[staticmethod]
     pub fn new_with_sqlite_interface(
         key: &KeyPy,
         label: String,
         entry_db_path: String,
         chain_db_path: Option<String>,
+        sqlcipher_key: Option<String>,
     ) -> PyResult<Self> {
         let configuration = Configuration::Sqlite(
             entry_db_path.clone(),
             chain_db_path.unwrap_or(entry_db_path),
+            sqlcipher_key,
         );
         let runtime = pyo3_unwrap!(
             tokio::runtime::Runtime::new(),
@@ -66,9 +75,13 @@ impl Findex {
         label: String,
         entry_db_url: String,
         chain_db_url: Option<String>,
+        pipeline_size: Option<usize>,
     ) -> PyResult<Self> {
-        let configuration =
-            Configuration::Redis(entry_db_url.clone(), chain_db_url.unwrap_or(entry_db_url));
+        let configuration = Configuration::Redis(
+            entry_db_url.clone(),
+            chain_db_url.unwrap_or(entry_db_url),
+            pipeline_size.unwrap_or(DEFAULT_PIPELINE_SIZE),
+        );
         let runtime = pyo3_unwrap!(
             tokio::runtime::Runtime::new(),
             "error creating Tokio runtime"
@@ -87,17 +100,25 @@ impl Findex {
     }
 
     /// Instantiates Findex with a custom interface.
+    ///
+    /// The Entry Table can be sharded into `entry_table_number` tables to
+    /// spread the load: the entry `fetch` callback then returns the values
+    /// found in each table.
     #[staticmethod]
+    #[pyo3(signature = (
+        key, label, entry_callbacks, chain_callbacks = None, entry_table_number = 1
+    ))]
     pub fn new_with_custom_interface(
         key: &KeyPy,
         label: String,
-        entry_callbacks: PythonCallbacks,
+        mut entry_callbacks: PythonCallbacks,
         chain_callbacks: Option<PythonCallbacks>,
+        entry_table_number: usize,
     ) -> PyResult<Self> {
-        let configuration = Configuration::Python(
-            entry_callbacks.clone(),
-            chain_callbacks.unwrap_or(entry_callbacks),
-        );
+        let mut chain_callbacks = chain_callbacks.unwrap_or_else(|| entry_callbacks.clone());
+        entry_callbacks.table_number = entry_table_number;
+        chain_callbacks.table_number = 1;
+        let configuration = Configuration::Python(entry_callbacks, chain_callbacks);
         let runtime = pyo3_unwrap!(
             tokio::runtime::Runtime::new(),
             "error creating Tokio runtime"
@@ -116,12 +137,21 @@ impl Findex {
     }
 
     /// Instantiates Findex with a REST backend.
+    ///
+    /// The requests can be limited to the given number of requests per second
+    /// and of concurrent requests. Requests rejected with `429 Too Many
+    /// Requests` are retried after the delay given by the server. Entry Table
+    /// upserts rejected by concurrent writers are retried for at most
+    /// `max_conflict_rounds` rounds.
     #[staticmethod]
     pub fn new_with_rest_interface(
         label: String,
         token: String,
         entry_url: String,
         chain_url: Option<String>,
+        requests_per_second: Option<f64>,
+        max_concurrency: Option<usize>,
+        max_conflict_rounds: Option<usize>,
     ) -> PyResult<Self> {
         let token = pyo3_unwrap!(
             AuthorizationToken::from_str(&token),
@@ -137,7 +167,14 @@ impl Findex {
             runtime.block_on(InstantiatedFindex::new(Configuration::Rest(
                 token,
                 entry_url.clone(),
-                chain_url.unwrap_or(entry_url)
+                chain_url.unwrap_or(entry_url),
+                RateLimit {
+                    requests_per_second,
+                    max_concurrency,
+                    max_conflict_rounds: max_conflict_rounds.unwrap_or(DEFAULT_MAX_CONFLICT_ROUNDS),
+                    ..RateLimit::default()
+                },
+                RequestOptions::default(),
             ))),
             "error instantiating Findex with Redis backend"
         );
@@ -149,6 +186,70 @@ impl Findex {
         })
     }
 
+    /// Normalizes the keywords before adding, deleting or searching them, e.g.
+    /// for `"Café"`, `"café"` and `"CAFÉ"` to be the same keyword. The search
+    /// results are returned under the keywords requested.
+    ///
+    /// The same normalization must be used for the whole life of an index.
+    ///
+    /// # Parameters
+    ///
+    /// - `lowercase`   : lowercase the UTF-8 keywords
+    /// - `nfc`         : convert the UTF-8 keywords to the Unicode
+    ///   Normalization Form C, after lowercasing them
+    /// - `normalize`   : optional callback applied last, e.g. a stemmer, given
+    ///   the keyword bytes and returning the normalized bytes
+    #[pyo3(signature = (lowercase = false, nfc = false, normalize = None))]
+    pub fn set_keyword_normalization(
+        &mut self,
+        lowercase: bool,
+        nfc: bool,
+        normalize: Option<PyObject>,
+    ) {
+        let mut normalizer = KeywordNormalizer::default();
+        if lowercase {
+            normalizer = normalizer.with_lowercase();
+        }
+        if nfc {
+            normalizer = normalizer.with_nfc();
+        }
+        if let Some(normalize) = normalize {
+            normalizer = normalizer.with_callback(Arc::new(move |keyword: &[u8]| {
+                Python::with_gil(|py| {
+                    normalize
+                        .call1(py, (PyBytes::new(py, keyword),))
+                        .and_then(|normalized| normalized.extract::<Vec<u8>>(py))
+                        .map_err(|e| format!("error calling user normalization: {e}"))
+                })
+            }));
+        }
+        self.instance.set_keyword_normalizer(normalizer);
+    }
+
+    /// Pads the chains with dummy values upon each addition and deletion, for
+    /// the number of values indexed under each keyword to only leak with a
+    /// bucket granularity. The dummy values are stripped from the search
+    /// results.
+    ///
+    /// The same padding should be used for the whole life of an index.
+    ///
+    /// # Parameters
+    ///
+    /// - `bucket_size` : number of values per bucket, at least 2, or `None` to
+    ///   disable the padding
+    #[pyo3(signature = (bucket_size = None))]
+    pub fn set_chain_padding(&mut self, bucket_size: Option<usize>) -> PyResult<()> {
+        let padding = match bucket_size {
+            Some(bucket_size) => Some(pyo3_unwrap!(
+                ChainPadding::new(bucket_size),
+                "invalid chain padding"
+            )),
+            None => None,
+        };
+        self.instance.set_chain_padding(padding);
+        Ok(())
+    }
+
     /// Adds the given associations to the index.
     ///
     /// Any subsequent search for such a keyword will result in finding (at
@@ -178,6 +279,49 @@ impl Findex {
             .collect::<HashSet<_>>())
     }
 
+    /// Indexes the rows of the given pandas `DataFrame`: the location of each
+    /// row is indexed under the values of its keyword columns.
+    ///
+    /// The associations are built natively, which is much faster than building
+    /// the dictionary given to `add` for large `DataFrame`s.
+    ///
+    /// Returns the keywords newly added to the index.
+    ///
+    /// # Parameters
+    ///
+    /// - `df`                  : `DataFrame` to index
+    /// - `location_column`     : column holding the locations, `str`, `bytes`
+    ///   or `int` read as `Location.from_int` does
+    /// - `keyword_columns`     : columns holding the keywords, missing values
+    ///   are skipped
+    /// - `tokenized_columns`   : keyword columns holding text, split into
+    ///   lowercase words
+    #[pyo3(signature = (df, location_column, keyword_columns, tokenized_columns = None))]
+    pub fn add_dataframe(
+        &self,
+        df: &PyAny,
+        location_column: &str,
+        keyword_columns: Vec<String>,
+        tokenized_columns: Option<Vec<String>>,
+    ) -> PyResult<HashSet<KeywordPy>> {
+        let associations = dataframe_to_associations(
+            df,
+            location_column,
+            &keyword_columns,
+            &tokenized_columns.unwrap_or_default(),
+        )?;
+        let new_keywords = pyo3_unwrap!(
+            self.runtime
+                .block_on(self.instance.add(&self.key, &self.label, associations)),
+            "error blocking for addition"
+        );
+
+        Ok(new_keywords
+            .into_iter()
+            .map(KeywordPy)
+            .collect::<HashSet<_>>())
+    }
+
     /// Remove the given indexed values for the associated keywords from the
     /// index.
     ///
@@ -208,6 +352,127 @@ impl Findex {
             .collect::<HashSet<_>>())
     }
 
+    /// Removes the given indexed values for the associated keywords from the
+    /// index, keeping a tombstone of each deleted location until the next
+    /// compaction so that the deletion can be undone.
+    ///
+    /// # Parameters
+    ///
+    /// - `associations`    : associations to remove from the index
+    pub fn soft_delete(
+        &self,
+        associations: HashMap<ToIndexedValue, Vec<ToKeyword>>,
+    ) -> PyResult<HashSet<KeywordPy>> {
+        let new_keywords = pyo3_unwrap!(
+            self.runtime.block_on(self.instance.soft_delete(
+                &self.key,
+                &self.label,
+                indexed_values_and_keywords_to_rust(associations)
+            )),
+            "error blocking for soft deletion"
+        );
+
+        Ok(new_keywords
+            .into_iter()
+            .map(KeywordPy)
+            .collect::<HashSet<_>>())
+    }
+
+    /// Returns the number of soft-deleted locations of each given keyword
+    /// that can still be restored.
+    ///
+    /// # Parameters
+    ///
+    /// - `keywords`    : keywords to count the tombstones of
+    pub fn count_tombstones(
+        &self,
+        keywords: Vec<ToKeyword>,
+    ) -> PyResult<HashMap<KeywordPy, usize>> {
+        let counts = pyo3_unwrap!(
+            self.runtime.block_on(self.instance.count_tombstones(
+                &self.key,
+                &self.label,
+                keywords.into_iter().map(|keyword| keyword.0).collect()
+            )),
+            "error blocking for tombstones count"
+        );
+
+        Ok(counts
+            .into_iter()
+            .map(|(keyword, count)| (KeywordPy(keyword), count))
+            .collect())
+    }
+
+    /// Indexes again the locations soft-deleted from the given keywords since
+    /// the last compaction.
+    ///
+    /// Returns: locations restored by keyword
+    ///
+    /// # Parameters
+    ///
+    /// - `keywords`    : keywords to restore the deleted locations of
+    pub fn restore_deleted(
+        &self,
+        keywords: Vec<ToKeyword>,
+    ) -> PyResult<HashMap<KeywordPy, Vec<LocationPy>>> {
+        let restored = pyo3_unwrap!(
+            self.runtime.block_on(self.instance.restore_deleted(
+                &self.key,
+                &self.label,
+                keywords.into_iter().map(|keyword| keyword.0).collect()
+            )),
+            "error blocking for deletions restoration"
+        );
+
+        Ok(search_results_to_python(restored))
+    }
+
+    /// Adds the given aliases to the index: any subsequent search for an
+    /// alias will result in finding the values indexed for the keyword it
+    /// points to.
+    ///
+    /// Returns the aliases newly added to the index.
+    ///
+    /// # Parameters
+    ///
+    /// - `aliases`     : aliases to add, per aliased keyword
+    pub fn add_aliases(
+        &self,
+        aliases: HashMap<ToKeyword, Vec<ToKeyword>>,
+    ) -> PyResult<HashSet<KeywordPy>> {
+        let new_aliases = pyo3_unwrap!(
+            self.runtime.block_on(self.instance.add_aliases(
+                &self.key,
+                &self.label,
+                aliases_to_rust(aliases)
+            )),
+            "error blocking for aliases addition"
+        );
+
+        Ok(new_aliases.into_iter().map(KeywordPy).collect())
+    }
+
+    /// Removes the given aliases from the index.
+    ///
+    /// # Parameters
+    ///
+    /// - `aliases`     : aliases to remove, per aliased keyword
+    pub fn delete_aliases(
+        &self,
+        aliases: HashMap<ToKeyword, Vec<ToKeyword>>,
+    ) -> PyResult<HashSet<KeywordPy>> {
+        let deleted_aliases = pyo3_unwrap!(
+            self.runtime.block_on(self.instance.delete_aliases(
+                &self.key,
+                &self.label,
+                aliases_to_rust(aliases)
+            )),
+            "error blocking for aliases deletion"
+        );
+
+        Ok(deleted_aliases.into_iter().map(KeywordPy).collect())
+    }
+
     /// Recursively search Findex graphs for data corresponding to the
     /// given `Keyword`.
     ///
@@ -217,65 +482,113 @@ impl Findex {
     ///
     /// - `keywords`    : keywords to search in the index
     /// - `interrupt`   : optional callback to process intermediate search
-    ///   results.
-    #[pyo3(signature = (keywords, interrupt = None))]
+    ///   results. Returning `True` interrupts the search.
+    /// - `max_depth`   : optional maximum number of aliases to follow from
+    ///   each keyword
+    #[pyo3(signature = (keywords, interrupt = None, max_depth = None))]
     pub fn search(
         &self,
         keywords: Vec<ToKeyword>,
         interrupt: Option<PyObject>,
+        max_depth: Option<usize>,
     ) -> PyResult<HashMap<KeywordPy, Vec<LocationPy>>> {
         let keywords_set: HashSet<Keyword> =
             keywords.into_iter().map(|keyword| keyword.0).collect();
 
         let interrupt =
             |partial_results: HashMap<Keyword, HashSet<IndexedValueRust<Keyword, Data>>>| async {
-                if let Some(interrupt) = &interrupt {
-                    let res = Python::with_gil(|py| {
-                        let py_results = partial_results
-                            .into_iter()
-                            .map(|(keyword, locations)| {
-                                (
-                                    KeywordPy(keyword),
-                                    locations
-                                        .into_iter()
-                                        .map(|indexed_value| match indexed_value {
-                                            IndexedValueRust::Data(location) => {
-                                                LocationPy(location).into_py(py)
-                                            }
-                                            IndexedValueRust::Pointer(keyword) => {
-                                                KeywordPy(keyword).into_py(py)
-                                            }
-                                        })
-                                        .collect::<Vec<PyObject>>(),
-                                )
-                            })
-                            .collect::<HashMap<_, _>>();
-
-                        let ret = interrupt
-                            .call1(py, (py_results,))
-                            .expect("the bytes passed represent a correct key");
-
-                        ret.extract(py)
-                            .expect("the bytes passed represent a correct key")
-                    });
-
-                    Ok(res)
-                } else {
-                    Ok::<_, String>(false)
-                }
+                let Some(interrupt) = &interrupt else {
+                    return Ok(false);
+                };
+                Python::with_gil(|py| {
+                    let py_results = partial_results
+                        .into_iter()
+                        .map(|(keyword, locations)| {
+                            (
+                                KeywordPy(keyword),
+                                locations
+                                    .into_iter()
+                                    .map(|indexed_value| match indexed_value {
+                                        IndexedValueRust::Data(location) => {
+                                            LocationPy(location).into_py(py)
+                                        }
+                                        IndexedValueRust::Pointer(keyword) => {
+                                            KeywordPy(keyword).into_py(py)
+                                        }
+                                    })
+                                    .collect::<Vec<PyObject>>(),
+                            )
+                        })
+                        .collect::<HashMap<_, _>>();
+
+                    interrupt
+                        .call1(py, (py_results,))
+                        .and_then(|ret| ret.extract::<bool>(py))
+                        .map_err(|e| format!("error calling user interrupt: {e}"))
+                })
             };
 
-        let results = pyo3_unwrap!(
-            self.runtime.block_on(self.instance.search(
+        let results = match max_depth {
+            Some(max_depth) => pyo3_unwrap!(
+                self.runtime.block_on(self.instance.search_with_max_depth(
+                    &self.key,
+                    &self.label,
+                    keywords_set.into(),
+                    max_depth,
+                    &interrupt
+                )),
+                "error blocking for search"
+            ),
+            None => pyo3_unwrap!(
+                self.runtime.block_on(self.instance.search(
+                    &self.key,
+                    &self.label,
+                    keywords_set.into(),
+                    &interrupt
+                )),
+                "error blocking for search"
+            ),
+        };
+
+        Ok(search_results_to_python(results))
+    }
+
+    /// Searches the index for the given keywords and returns the number of
+    /// keywords matched by each location found, to rank them.
+    ///
+    /// # Parameters
+    ///
+    /// - `keywords`    : keywords to search in the index
+    /// - `intersection`: only return the locations matching all the keywords
+    #[pyo3(signature = (keywords, intersection = false))]
+    pub fn search_scored(
+        &self,
+        keywords: Vec<ToKeyword>,
+        intersection: bool,
+    ) -> PyResult<HashMap<LocationPy, usize>> {
+        let keywords_set: HashSet<Keyword> =
+            keywords.into_iter().map(|keyword| keyword.0).collect();
+        let mode = if intersection {
+            MatchMode::Intersection
+        } else {
+            MatchMode::Union
+        };
+
+        let scores = pyo3_unwrap!(
+            self.runtime.block_on(self.instance.search_scored(
                 &self.key,
                 &self.label,
                 keywords_set.into(),
-                &interrupt
+                mode,
+                &|_| async move { Ok(false) }
             )),
-            "error blocking for search"
+            "error blocking for scored search"
         );
 
-        Ok(search_results_to_python(results))
+        Ok(scores
+            .into_iter()
+            .map(|(location, score)| (LocationPy(location), score))
+            .collect())
     }
 
     /// Replace all the previous Index Entry Table UIDs and
@@ -303,31 +616,8 @@ impl Findex {
         compacting_rate: f64,
         filter: Option<PyObject>,
     ) -> PyResult<()> {
-        let data_filter = |indexed_data: HashSet<Data>| async {
-            if let Some(filter) = &filter {
-                Python::with_gil(|py| {
-                    let py_locations = indexed_data
-                        .into_iter()
-                        .map(LocationPy)
-                        .collect::<HashSet<LocationPy>>();
-
-                    let ret = filter
-                        .call1(py, (py_locations,))
-                        .expect("the bytes passed represent a correct key");
-
-                    ret.extract(py)
-                        .map_err(|e| format!("converting Python remaining locations: {e}"))
-                        .map(|remaining_locations: HashSet<LocationPy>| {
-                            remaining_locations
-                                .into_iter()
-                                .map(|py_location| py_location.0)
-                                .collect()
-                        })
-                })
-            } else {
-                Ok::<_, String>(indexed_data)
-            }
-        };
+        let data_filter =
+            |indexed_data: HashSet<Data>| async { filter_data(filter.as_ref(), indexed_data) };
 
         let new_label = Label::from(new_label.as_str());
 
@@ -349,6 +639,92 @@ impl Findex {
 
         Ok(())
     }
+
+    /// Compacts the given number of randomly selected index entries.
+    ///
+    /// Allows compacting huge indexes incrementally, e.g. during maintenance
+    /// windows. All the index entries are still re-encrypted using the new
+    /// key and label.
+    ///
+    /// Parameters
+    ///
+    /// - `new_key`   : newly generated key
+    /// - `new_label` : newly generated label
+    /// - `n_entries` : number of entries to compact
+    /// - `filter`    : optional callback returning the locations to keep
+    pub fn compact_partial(
+        &mut self,
+        new_key: &KeyPy,
+        new_label: String,
+        n_entries: usize,
+        filter: Option<PyObject>,
+    ) -> PyResult<()> {
+        let data_filter =
+            |indexed_data: HashSet<Data>| async { filter_data(filter.as_ref(), indexed_data) };
+
+        let new_label = Label::from(new_label.as_str());
+
+        pyo3_unwrap!(
+            self.runtime.block_on(self.instance.compact_partial(
+                &self.key,
+                &new_key.0,
+                &self.label,
+                &new_label,
+                n_entries,
+                &data_filter,
+            )),
+            "error while blocking for partial compact"
+        );
+
+        self.key = UserKey::try_from_bytes(new_key.0.to_bytes())
+            .expect("the bytes passed represent a correct key");
+        self.label = new_label;
+
+        Ok(())
+    }
+
+    /// Returns the number of Entry Table lines and the number of Chain Table
+    /// lines of the index.
+    ///
+    /// The number of Chain Table lines per entry grows with the garbage
+    /// accumulated since the last compaction.
+    pub fn statistics(&self) -> PyResult<(usize, usize)> {
+        let statistics = pyo3_unwrap!(
+            self.runtime.block_on(self.instance.statistics()),
+            "error while blocking for statistics"
+        );
+        Ok((statistics.n_entries, statistics.n_links))
+    }
+}
+
+/// Calls the given Python data filter on the given data. All the data is kept
+/// if no filter is given.
+fn filter_data(
+    filter: Option<&PyObject>,
+    indexed_data: HashSet<Data>,
+) -> Result<HashSet<Data>, String> {
+    let Some(filter) = filter else {
+        return Ok(indexed_data);
+    };
+    Python::with_gil(|py| {
+        let py_locations = indexed_data
+            .into_iter()
+            .map(LocationPy)
+            .collect::<HashSet<LocationPy>>();
+
+        let ret = filter
+            .call1(py, (py_locations,))
+            .map_err(|e| format!("calling Python data filter: {e}"))?;
+
+        ret.extract(py)
+            .map_err(|e| format!("converting Python remaining locations: {e}"))
+            .map(|remaining_locations: HashSet<LocationPy>| {
+                remaining_locations
+                    .into_iter()
+                    .map(|py_location| py_location.0)
+                    .collect()
+            })
+    })
 }
 
 fn indexed_values_and_keywords_to_rust(
@@ -366,6 +742,18 @@ fn indexed_values_and_keywords_to_rust(
     rust_indexed_values_and_keywords.into()
 }
 
+fn aliases_to_rust(py_aliases: HashMap<ToKeyword, Vec<ToKeyword>>) -> KeywordToAliasesMap {
+    py_aliases
+        .into_iter()
+        .map(|(keyword, aliases)| {
+            (
+                keyword.0,
+                aliases.into_iter().map(|alias| alias.0).collect(),
+            )
+        })
+        .collect()
+}
+
 fn search_results_to_python(
     search_results: KeywordToDataMap,
 ) -> HashMap<KeywordPy, Vec<LocationPy>> {
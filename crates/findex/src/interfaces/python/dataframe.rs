@@ -0,0 +1,104 @@
+//! Reads the associations to index from a pandas `DataFrame`.
+//!
+//! The associations are built natively, column by column: indexing a large
+//! `DataFrame` does not require building a Python dictionary of sets.
+
+use std::collections::{HashMap, HashSet};
+
+use cosmian_findex::{Data, IndexedValue, IndexedValueToKeywordsMap, Keyword};
+use pyo3::{
+    exceptions::PyValueError,
+    prelude::*,
+    types::{PyBytes, PyFloat, PyString},
+};
+
+/// Reads the given cell as bytes, or `None` for the missing values of pandas
+/// (`None` and `NaN`).
+///
+/// Strings are encoded in UTF-8. Integers are read as big-endian bytes by
+/// `int_as_bytes`, or as their string representation otherwise, like any other
+/// type.
+fn read_cell(cell: &PyAny, int_as_bytes: bool) -> PyResult<Option<Vec<u8>>> {
+    if cell.is_none()
+        || cell
+            .downcast::<PyFloat>()
+            .is_ok_and(|float| float.value().is_nan())
+    {
+        Ok(None)
+    } else if let Ok(bytes) = cell.downcast::<PyBytes>() {
+        Ok(Some(bytes.as_bytes().to_vec()))
+    } else if let Ok(string) = cell.downcast::<PyString>() {
+        Ok(Some(string.to_str()?.as_bytes().to_vec()))
+    } else if let (true, Ok(int)) = (int_as_bytes, cell.extract::<i64>()) {
+        Ok(Some(int.to_be_bytes().to_vec()))
+    } else {
+        Ok(Some(cell.str()?.to_str()?.as_bytes().to_vec()))
+    }
+}
+
+/// Splits the given text into lowercase words.
+fn tokenize(text: &str) -> impl Iterator<Item = Keyword> + '_ {
+    text.split(|c: char| !c.is_alphanumeric())
+        .filter(|word| !word.is_empty())
+        .map(|word| Keyword::from(word.to_lowercase().as_bytes()))
+}
+
+/// Builds the associations of the given `DataFrame`: the location of each row
+/// is indexed under the values of its keyword columns.
+///
+/// Integer locations are read as `Location.from_int` does. The values of the
+/// tokenized columns are split into lowercase words, each of them being a
+/// keyword. Missing values are skipped.
+pub(super) fn dataframe_to_associations(
+    df: &PyAny,
+    location_column: &str,
+    keyword_columns: &[String],
+    tokenized_columns: &[String],
+) -> PyResult<IndexedValueToKeywordsMap> {
+    if let Some(column) = tokenized_columns
+        .iter()
+        .find(|column| !keyword_columns.contains(column))
+    {
+        return Err(PyValueError::new_err(format!(
+            "the tokenized column `{column}` is not a keyword column"
+        )));
+    }
+
+    let locations = df
+        .get_item(location_column)?
+        .iter()?
+        .map(|cell| {
+            read_cell(cell?, true)?.map(Data::from).ok_or_else(|| {
+                PyValueError::new_err(format!(
+                    "the location column `{location_column}` has missing values"
+                ))
+            })
+        })
+        .collect::<PyResult<Vec<_>>>()?;
+
+    let mut associations = vec![HashSet::<Keyword>::new(); locations.len()];
+    for column in keyword_columns {
+        let tokenized = tokenized_columns.contains(column);
+        // All the columns of a `DataFrame` have the same length.
+        for (keywords, cell) in associations.iter_mut().zip(df.get_item(column)?.iter()?) {
+            if let Some(value) = read_cell(cell?, false)? {
+                if tokenized {
+                    keywords.extend(tokenize(&String::from_utf8_lossy(&value)));
+                } else {
+                    keywords.insert(Keyword::from(value.as_slice()));
+                }
+            }
+        }
+    }
+
+    // Rows sharing the same location are merged.
+    let mut map = HashMap::<IndexedValue<Keyword, Data>, HashSet<Keyword>>::new();
+    for (location, keywords) in locations.into_iter().zip(associations) {
+        if !keywords.is_empty() {
+            map.entry(IndexedValue::Data(location))
+                .or_default()
+                .extend(keywords);
+        }
+    }
+    Ok(map.into())
+}
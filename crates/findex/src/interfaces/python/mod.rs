@@ -3,6 +3,7 @@
 #[macro_use]
 mod macros;
 mod api;
+mod dataframe;
 mod types;
 
 use api::Findex;
@@ -12,6 +13,14 @@ use types::{Key, Keyword, Label, Location};
 use self::types::AuthorizationToken;
 use crate::db_interfaces::custom::python::PythonCallbacks;
 
+/// Sets the logs displayed by Findex, overriding the `RUST_LOG` environment
+/// variable, e.g. `debug` to see the upsert conflicts, the retries and the
+/// callback errors.
+#[pyfunction]
+fn set_log_level(level: &str) -> PyResult<()> {
+    crate::logger::set_log_level(level).map_err(pyo3::exceptions::PyValueError::new_err)
+}
+
 #[pymodule]
 fn cloudproof_findex(_py: Python, m: &PyModule) -> PyResult<()> {
     m.add_class::<Findex>()?;
@@ -20,5 +29,6 @@ fn cloudproof_findex(_py: Python, m: &PyModule) -> PyResult<()> {
     m.add_class::<Location>()?;
     m.add_class::<Keyword>()?;
     m.add_class::<PythonCallbacks>()?;
-    m.add_class::<AuthorizationToken>()
+    m.add_class::<AuthorizationToken>()?;
+    m.add_function(wrap_pyfunction!(set_log_level, m)?)
 }
@@ -54,6 +54,7 @@ impl<'a> FromPyObject<'a> for ToIndexedValue {
 }
 
 /// Interface to accept `Keyword` and String in `Upsert`
+#[derive(Hash, PartialEq, Eq)]
 pub struct ToKeyword(pub(super) KeywordRust);
 
 impl<'a> FromPyObject<'a> for ToKeyword {
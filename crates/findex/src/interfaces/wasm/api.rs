@@ -1,9 +1,6 @@
 //! Defines the Findex WASM API.
 
-use std::{
-    collections::{HashMap, HashSet},
-    str::FromStr,
-};
+use std::collections::{HashMap, HashSet};
 
 use cosmian_crypto_core::{
     reexport::rand_core::SeedableRng, CsRng, FixedSizeCBytes, RandomFixedSizeCBytes, SymmetricKey,
@@ -13,48 +10,63 @@ use js_sys::{Array, Function, Promise, Uint8Array};
 use wasm_bindgen::prelude::*;
 use wasm_bindgen_futures::JsFuture;
 
-use super::types::InterruptInput;
+use super::{
+    configuration::FindexConfiguration,
+    types::{Interrupt, InterruptInput},
+};
 use crate::{
-    db_interfaces::{
-        custom::wasm::WasmCallbacks,
-        rest::{AuthorizationToken, CallbackPrefix},
-    },
+    db_interfaces::rest::{AuthorizationToken, CallbackPrefix},
     interfaces::wasm::{
-        types::{ArrayOfKeywords, Filter, IndexedData, IndexedValuesAndKeywords, SearchResults},
+        types::{
+            ArrayOfKeywords, Filter, IndexedData, IndexedValuesAndKeywords, KeywordsAndAliases,
+            LocationScores, SearchResults,
+        },
         WasmError,
     },
-    Configuration, InstantiatedFindex,
+    ser_de::wasm_ser_de::{pack_search_results, uids_to_js_array},
+    Configuration, InstantiatedFindex, MatchMode,
 };
 
+/// Calls the given user interrupt with the given intermediate search results.
+///
+/// The interrupt may return a boolean or a promise of a boolean. Returns
+/// `false` if no interrupt is given.
+async fn call_interrupt(
+    interrupt: Option<&Interrupt>,
+    res: HashMap<Keyword, HashSet<IndexedValue<Keyword, Data>>>,
+) -> Result<bool, String> {
+    let Some(interrupt) = interrupt else {
+        return Ok(false);
+    };
+    let res = <InterruptInput>::try_from(res).map_err(|e| {
+        format!("Findex search: failed converting input of user interrupt into Js object: {e:?}")
+    })?;
+    let res = interrupt
+        .unchecked_ref::<Function>()
+        .call1(&JsValue::null(), &res)
+        .map_err(|e| format!("failed calling user interrupt: {e:?}"))?;
+    let interruption_flag = JsFuture::from(Promise::resolve(&res)).await.map_err(|e| {
+        format!("Findex search: failed getting the promised results from user interrupt: {e:?}")
+    })?;
+    interruption_flag.as_bool().ok_or_else(|| {
+        format!(
+            "Findex search: user interrupt does not return a boolean value: {interruption_flag:?}"
+        )
+    })
+}
+
 #[wasm_bindgen]
 pub struct WasmFindex(InstantiatedFindex);
 
 #[wasm_bindgen]
 impl WasmFindex {
-    /// Instantiates a Findex object from custom DB interfaces using the given
-    /// callbacks.
-    pub async fn new_with_custom_interface(
-        entry_callbacks: WasmCallbacks,
-        chain_callbacks: WasmCallbacks,
-    ) -> Result<WasmFindex, JsError> {
-        let config = Configuration::Wasm(entry_callbacks, chain_callbacks);
-        InstantiatedFindex::new(config)
-            .await
-            .map(Self)
-            .map_err(WasmError::from)
-            .map_err(JsError::from)
-    }
-
-    /// Instantiates a Findex object using REST interfaces, using the given token
-    /// and URLs.
-    pub async fn new_with_rest_interface(
-        token: String,
-        entry_url: String,
-        chain_url: String,
-    ) -> Result<WasmFindex, JsError> {
-        let config =
-            Configuration::Rest(AuthorizationToken::from_str(&token)?, entry_url, chain_url);
-
+    /// Instantiates a Findex object using the given configuration, which
+    /// selects the backend and gives its parameters.
+    ///
+    /// The configuration is validated first: the returned error names the
+    /// faulty property.
+    pub async fn new(configuration: FindexConfiguration) -> Result<WasmFindex, JsError> {
+        let config = Configuration::try_from(&configuration)?;
         InstantiatedFindex::new(config)
             .await
             .map(Self)
@@ -74,7 +86,7 @@ impl WasmFindex {
         key: Uint8Array,
         label: String,
         keywords: ArrayOfKeywords,
-        interrupt: Option<Function>,
+        interrupt: Option<Interrupt>,
     ) -> Result<SearchResults, JsError> {
         let key = SymmetricKey::try_from_slice(&key.to_vec()).map_err(|e| {
             WasmError(format!(
@@ -89,31 +101,8 @@ impl WasmFindex {
             .map(|word| Keyword::from(Uint8Array::new(&word).to_vec()))
             .collect::<HashSet<_>>();
 
-        let user_interrupt = |res: HashMap<Keyword, HashSet<IndexedValue<Keyword, Data>>>| async {
-            if let Some(interrupt) = &interrupt {
-                let res = <InterruptInput>::try_from(res).map_err(|e| {
-                    format!(
-                        "Findex search: failed converting input of user interrupt into Js object: \
-                     {e:?}"
-                    )
-                })?;
-                let res = interrupt
-                    .call1(&JsValue::null(), &res)
-                    .map_err(|e| format!("failed calling user interrupt: {e:?}"))?;
-                let interruption_flag =
-                    JsFuture::from(Promise::resolve(&res)).await.map_err(|e| {
-                        format!(
-                    "Findex search: failed getting the promised results from user interrupt: {e:?}"
-                )
-                    })?;
-                interruption_flag.as_bool().ok_or_else(|| {
-                    format!(
-                    "Findex search: user interrupt does not return a boolean value: {interrupt:?}"
-                )
-                })
-            } else {
-                Ok(false)
-            }
+        let user_interrupt = |res: HashMap<Keyword, HashSet<IndexedValue<Keyword, Data>>>| {
+            call_interrupt(interrupt.as_ref(), res)
         };
 
         let res = self
@@ -124,6 +113,107 @@ impl WasmFindex {
         <SearchResults>::try_from(&res).map_err(JsError::from)
     }
 
+    /// Searches this Findex instance for the given keywords, following at most
+    /// `max_depth` aliases from each of them.
+    ///
+    /// The interrupt behaves as in [`search`](Self::search).
+    pub async fn search_with_max_depth(
+        &self,
+        key: Uint8Array,
+        label: String,
+        keywords: ArrayOfKeywords,
+        max_depth: usize,
+        interrupt: Option<Interrupt>,
+    ) -> Result<SearchResults, JsError> {
+        let key = SymmetricKey::try_from_slice(&key.to_vec())
+            .map_err(|e| WasmError(format!("Findex search: failed parsing key: {e}")))?;
+        let label = Label::from(label.as_str());
+        let keywords = Array::from(&JsValue::from(keywords))
+            .iter()
+            .map(|word| Keyword::from(Uint8Array::new(&word).to_vec()))
+            .collect::<HashSet<_>>();
+
+        let res = self
+            .0
+            .search_with_max_depth(&key, &label, keywords.into(), max_depth, &|res| {
+                call_interrupt(interrupt.as_ref(), res)
+            })
+            .await?;
+
+        <SearchResults>::try_from(&res).map_err(JsError::from)
+    }
+
+    /// Searches this Findex instance for the given keywords and returns the
+    /// number of keywords matched by each location found, to rank them.
+    ///
+    /// If `intersection` is set, only the locations matching all the keywords
+    /// are returned. The interrupt behaves as in [`search`](Self::search).
+    pub async fn search_scored(
+        &self,
+        key: Uint8Array,
+        label: String,
+        keywords: ArrayOfKeywords,
+        intersection: bool,
+        interrupt: Option<Interrupt>,
+    ) -> Result<LocationScores, JsError> {
+        let key = SymmetricKey::try_from_slice(&key.to_vec())
+            .map_err(|e| WasmError(format!("Findex search: failed parsing key: {e}")))?;
+        let label = Label::from(label.as_str());
+        let keywords = Array::from(&JsValue::from(keywords))
+            .iter()
+            .map(|word| Keyword::from(Uint8Array::new(&word).to_vec()))
+            .collect::<HashSet<_>>();
+        let mode = if intersection {
+            MatchMode::Intersection
+        } else {
+            MatchMode::Union
+        };
+
+        let res = self
+            .0
+            .search_scored(&key, &label, keywords.into(), mode, &|res| {
+                call_interrupt(interrupt.as_ref(), res)
+            })
+            .await?;
+
+        <LocationScores>::try_from(&res).map_err(JsError::from)
+    }
+
+    /// Searches this Findex instance for the given keywords and returns the
+    /// results packed into a single `Uint8Array`.
+    ///
+    /// The returned array owns its `ArrayBuffer`: it can be transferred to
+    /// another thread (e.g. from a Web Worker to the main thread) without
+    /// copying the results. Use `webassembly_unpack_search_results` to read it.
+    ///
+    /// The interrupt behaves as in [`search`](Self::search).
+    pub async fn search_packed(
+        &self,
+        key: Uint8Array,
+        label: String,
+        keywords: ArrayOfKeywords,
+        interrupt: Option<Interrupt>,
+    ) -> Result<Uint8Array, JsError> {
+        let key = SymmetricKey::try_from_slice(&key.to_vec())
+            .map_err(|e| WasmError(format!("Findex search: failed parsing key: {e}")))?;
+        let label = Label::from(label.as_str());
+        let keywords = Array::from(&JsValue::from(keywords))
+            .iter()
+            .map(|word| Keyword::from(Uint8Array::new(&word).to_vec()))
+            .collect::<HashSet<_>>();
+
+        let res = self
+            .0
+            .search(&key, &label, keywords.into(), &|res| {
+                call_interrupt(interrupt.as_ref(), res)
+            })
+            .await?;
+
+        pack_search_results(&res)
+            .map_err(|e| WasmError(format!("Findex search: failed packing results: {e}")))
+            .map_err(JsError::from)
+    }
+
     /// Add the given values to this Findex index for the corresponding
     /// keywords.
     pub async fn add(
@@ -191,6 +281,67 @@ impl WasmFindex {
         Ok(<ArrayOfKeywords>::from(&res))
     }
 
+    /// Adds the given aliases to this Findex index: searching for an alias
+    /// returns the values indexed for the keyword it points to.
+    ///
+    /// Returns the aliases newly added to the index.
+    pub async fn add_aliases(
+        &self,
+        key: Uint8Array,
+        label: String,
+        aliases: KeywordsAndAliases,
+    ) -> Result<ArrayOfKeywords, JsError> {
+        let key = SymmetricKey::try_from_slice(&key.to_vec())
+            .map_err(|e| WasmError(format!("Findex add_aliases: failed parsing key: {e}")))?;
+        let label = Label::from(label.as_str());
+        let aliases = <HashMap<Keyword, HashSet<Keyword>>>::try_from(&aliases).map_err(|e| {
+            WasmError(format!(
+                "Findex add_aliases: failed parsing aliases from WASM: {e:?}"
+            ))
+        })?;
+
+        let new_aliases = self
+            .0
+            .add_aliases(&key, &label, aliases)
+            .await
+            .map_err(|e| {
+                WasmError(format!(
+                    "Findex add_aliases: failed adding aliases to the index: {e:?}"
+                ))
+            })?;
+
+        Ok(<ArrayOfKeywords>::from(&new_aliases))
+    }
+
+    /// Removes the given aliases from this Findex index.
+    pub async fn delete_aliases(
+        &self,
+        key: Uint8Array,
+        label: String,
+        aliases: KeywordsAndAliases,
+    ) -> Result<ArrayOfKeywords, JsError> {
+        let key = SymmetricKey::try_from_slice(&key.to_vec())
+            .map_err(|e| WasmError(format!("Findex delete_aliases: failed parsing key: {e}")))?;
+        let label = Label::from(label.as_str());
+        let aliases = <HashMap<Keyword, HashSet<Keyword>>>::try_from(&aliases).map_err(|e| {
+            WasmError(format!(
+                "Findex delete_aliases: failed parsing aliases from WASM: {e:?}"
+            ))
+        })?;
+
+        let res = self
+            .0
+            .delete_aliases(&key, &label, aliases)
+            .await
+            .map_err(|e| {
+                WasmError(format!(
+                    "Findex delete_aliases: failed removing aliases from the index: {e:?}"
+                ))
+            })?;
+
+        Ok(<ArrayOfKeywords>::from(&res))
+    }
+
     pub async fn compact(
         &self,
         old_key: Uint8Array,
@@ -253,6 +404,21 @@ impl WasmFindex {
                 )))
             })
     }
+
+    /// Lists the tokens of the index Entry Table, i.e. one token per indexed
+    /// keyword.
+    pub async fn dump_tokens(&self) -> Result<Array, JsError> {
+        let tokens = self.0.dump_tokens().await.map_err(|e| {
+            WasmError(format!(
+                "Findex dump tokens: failed listing the tokens: {e:?}"
+            ))
+        })?;
+        uids_to_js_array(&tokens).map_err(|e| {
+            JsError::from(WasmError(format!(
+                "Findex dump tokens: failed converting the tokens to Js array: {e:?}"
+            )))
+        })
+    }
 }
 
 #[wasm_bindgen]
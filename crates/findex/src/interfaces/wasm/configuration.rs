@@ -0,0 +1,553 @@
+//! Typed configuration of the Findex WASM instances.
+//!
+//! The configuration is a plain JS object whose `backend` property selects the
+//! backend used to store the indexes:
+//!
+//! ```js
+//! const findex = await WasmFindex.new({
+//!   backend: 'rest',
+//!   token,
+//!   entryUrl: 'https://findex.example.com',
+//!   chainUrl: 'https://findex.example.com',
+//!   requestsPerSecond: 50, // optional
+//!   maxConcurrency: 4,     // optional
+//!   maxConflictRounds: 10, // optional
+//!   credentials: 'include', // optional, `same-origin` by default
+//!   headers: { 'X-Api-Key': apiKey }, // optional
+//! })
+//!
+//! const findex = await WasmFindex.new({
+//!   backend: 'custom',
+//!   entryCallbacks: { fetch, upsert, insert, delete, dumpTokens },
+//!   chainCallbacks: { fetch, insert, delete },
+//!   entryTableNumber: 4, // optional, 1 by default
+//! })
+//!
+//! const findex = await WasmFindex.new({
+//!   backend: 'sqlite',
+//!   entryDatabase: db, // e.g. a sql.js or OPFS SQLite database wrapper
+//!   chainDatabase: db,
+//! })
+//! ```
+//!
+//! The keywords can be normalized before being added, deleted or searched by
+//! any backend, e.g. for `"Café"`, `"café"` and `"CAFÉ"` to be the same
+//! keyword:
+//!
+//! ```js
+//! const findex = await WasmFindex.new({
+//!   backend: 'rest',
+//!   // ...
+//!   keywordNormalization: { lowercase: true, nfc: true },
+//! })
+//! ```
+//!
+//! The chains can also be padded with dummy values upon each addition and
+//! deletion, for the number of values indexed under each keyword to only leak
+//! with a bucket granularity, using `paddingBucketSize: 4` (at least 2).
+//!
+//! The Entry Table of a custom backend can be sharded into `entryTableNumber`
+//! tables to spread the load: the entry `fetch` callback then returns the
+//! values found in each table.
+//!
+//! The REST backend sends the requests using `fetch`: a Findex server served
+//! from another origin should allow the page origin (CORS), and allow
+//! credentials if `credentials` is `include`.
+//!
+//! It is validated before instantiating Findex: each error names the faulty
+//! property and what was expected.
+
+use std::str::FromStr;
+
+use js_sys::{Object, Reflect};
+use reqwest::Url;
+use wasm_bindgen::{prelude::wasm_bindgen, JsCast, JsValue};
+
+use super::WasmError;
+use crate::{
+    db_interfaces::{
+        custom::wasm::{SqliteDatabase, WasmCallbacks},
+        rest::{AuthorizationToken, FetchCredentials, RateLimit, RequestOptions},
+    },
+    ChainPadding, Configuration, KeywordNormalizer,
+};
+
+#[wasm_bindgen(typescript_custom_section)]
+const FINDEX_CONFIGURATION: &str = r#"
+/** Callbacks implementing the Entry or Chain table of a custom backend. */
+export type FindexCallbacks = WasmCallbacks | {
+  fetch: (uids: Uint8Array[]) => Promise<{uid: Uint8Array, value: Uint8Array}[]>,
+  upsert?: (
+    oldValues: {uid: Uint8Array, value: Uint8Array}[],
+    newValues: {uid: Uint8Array, value: Uint8Array}[],
+  ) => Promise<{uid: Uint8Array, value: Uint8Array}[]>,
+  insert?: (uidsAndValues: {uid: Uint8Array, value: Uint8Array}[]) => Promise<void>,
+  delete?: (uids: Uint8Array[]) => Promise<void>,
+  dumpTokens?: () => Promise<Uint8Array[]>,
+};
+
+/** Normalization of the keywords, applied whatever the backend. */
+export type KeywordNormalization = {
+  lowercase?: boolean,
+  nfc?: boolean,
+};
+
+/** Backend used by a Findex instance. */
+export type FindexConfiguration = ({
+  backend: "rest",
+  token: string,
+  entryUrl: string,
+  chainUrl: string,
+  requestsPerSecond?: number,
+  maxConcurrency?: number,
+  maxConflictRounds?: number,
+  credentials?: "omit" | "same-origin" | "include",
+  headers?: Record<string, string>,
+} | {
+  backend: "custom",
+  entryCallbacks: FindexCallbacks,
+  chainCallbacks: FindexCallbacks,
+  entryTableNumber?: number,
+} | {
+  backend: "sqlite",
+  entryDatabase: SqliteDatabase,
+  chainDatabase: SqliteDatabase,
+}) & {
+  keywordNormalization?: KeywordNormalization,
+  paddingBucketSize?: number,
+};
+"#;
+
+#[wasm_bindgen]
+extern "C" {
+    /// Configuration of a Findex instance, see `FindexConfiguration` in the
+    /// TypeScript declarations.
+    #[wasm_bindgen(typescript_type = "FindexConfiguration")]
+    pub type FindexConfiguration;
+}
+
+fn invalid(message: impl std::fmt::Display) -> WasmError {
+    WasmError(format!("invalid Findex configuration: {message}"))
+}
+
+/// Returns the given property of the given object, `None` if it is undefined
+/// or null.
+fn get_optional(object: &JsValue, property: &str) -> Result<Option<JsValue>, WasmError> {
+    let value = Reflect::get(object, &JsValue::from_str(property))
+        .map_err(|e| invalid(format_args!("could not get `{property}`: {e:?}")))?;
+    Ok((!value.is_undefined() && !value.is_null()).then_some(value))
+}
+
+fn get_required(object: &JsValue, property: &str) -> Result<JsValue, WasmError> {
+    get_optional(object, property)?
+        .ok_or_else(|| invalid(format_args!("missing required property `{property}`")))
+}
+
+fn get_string(object: &JsValue, property: &str) -> Result<String, WasmError> {
+    get_required(object, property)?
+        .as_string()
+        .ok_or_else(|| invalid(format_args!("`{property}` should be a string")))
+}
+
+fn get_url(object: &JsValue, property: &str) -> Result<String, WasmError> {
+    let url = get_string(object, property)?;
+    match Url::parse(&url) {
+        Ok(parsed) if matches!(parsed.scheme(), "http" | "https") => Ok(url),
+        Ok(_) => Err(invalid(format_args!(
+            "`{property}` should be an http or https URL, got `{url}`"
+        ))),
+        Err(e) => Err(invalid(format_args!(
+            "`{property}` should be an absolute URL, got `{url}` ({e})"
+        ))),
+    }
+}
+
+/// Returns the given optional property as a positive number.
+fn get_positive_number(object: &JsValue, property: &str) -> Result<Option<f64>, WasmError> {
+    get_optional(object, property)?
+        .map(|value| {
+            value
+                .as_f64()
+                .filter(|number| number.is_finite() && *number > 0.0)
+                .ok_or_else(|| invalid(format_args!("`{property}` should be a positive number")))
+        })
+        .transpose()
+}
+
+/// Returns the given optional property as a positive integer.
+fn get_positive_integer(object: &JsValue, property: &str) -> Result<Option<usize>, WasmError> {
+    get_positive_number(object, property)?
+        .map(|number| {
+            if number.fract() == 0.0 {
+                Ok(number as usize)
+            } else {
+                Err(invalid(format_args!("`{property}` should be an integer")))
+            }
+        })
+        .transpose()
+}
+
+/// Returns the headers and the credentials of the REST requests.
+fn get_request_options(object: &JsValue) -> Result<RequestOptions, WasmError> {
+    let mut request_options = RequestOptions::default();
+    if let Some(credentials) = get_optional(object, "credentials")? {
+        let credentials = credentials
+            .as_string()
+            .ok_or_else(|| invalid("`credentials` should be a string"))?;
+        request_options = request_options.with_credentials(
+            FetchCredentials::from_str(&credentials)
+                .map_err(|e| invalid(format_args!("`credentials`: {e}")))?,
+        );
+    }
+    if let Some(headers) = get_optional(object, "headers")? {
+        if !headers.is_object() {
+            return Err(invalid("`headers` should be an object"));
+        }
+        for entry in Object::entries(&Object::from(headers)).iter() {
+            let name = Reflect::get_u32(&entry, 0)
+                .ok()
+                .and_then(|name| name.as_string())
+                .ok_or_else(|| invalid("`headers` names should be strings"))?;
+            let value = Reflect::get_u32(&entry, 1)
+                .ok()
+                .and_then(|value| value.as_string())
+                .ok_or_else(|| invalid(format_args!("`headers.{name}` should be a string")))?;
+            request_options = request_options.with_header(name, value);
+        }
+    }
+    Ok(request_options)
+}
+
+fn get_callbacks(object: &JsValue, property: &str) -> Result<WasmCallbacks, WasmError> {
+    WasmCallbacks::try_from_object(&get_required(object, property)?)
+        .map_err(|e| invalid(format_args!("`{property}`: {e}")))
+}
+
+fn get_database(object: &JsValue, property: &str) -> Result<SqliteDatabase, WasmError> {
+    let database = get_required(object, property)?;
+    let exec = Reflect::get(&database, &JsValue::from_str("exec"))
+        .map_err(|e| invalid(format_args!("could not get `{property}.exec`: {e:?}")))?;
+    if exec.is_function() {
+        Ok(database.unchecked_into())
+    } else {
+        Err(invalid(format_args!(
+            "`{property}` should be a SQLite database with an `exec` method"
+        )))
+    }
+}
+
+/// Returns the normalization of the keywords, the identity by default.
+fn get_keyword_normalizer(object: &JsValue) -> Result<KeywordNormalizer, WasmError> {
+    let mut normalizer = KeywordNormalizer::default();
+    let Some(normalization) = get_optional(object, "keywordNormalization")? else {
+        return Ok(normalizer);
+    };
+    if !normalization.is_object() {
+        return Err(invalid("`keywordNormalization` should be an object"));
+    }
+    let get_flag = |property: &str| {
+        get_optional(&normalization, property)?
+            .map_or(Some(false), |value| value.as_bool())
+            .ok_or_else(|| {
+                invalid(format_args!(
+                    "`keywordNormalization.{property}` should be a boolean"
+                ))
+            })
+    };
+    if get_flag("lowercase")? {
+        normalizer = normalizer.with_lowercase();
+    }
+    if get_flag("nfc")? {
+        normalizer = normalizer.with_nfc();
+    }
+    Ok(normalizer)
+}
+
+/// Returns the padding of the chains, none by default.
+fn get_chain_padding(object: &JsValue) -> Result<Option<ChainPadding>, WasmError> {
+    get_positive_integer(object, "paddingBucketSize")?
+        .map(|bucket_size| {
+            ChainPadding::new(bucket_size)
+                .map_err(|e| invalid(format_args!("`paddingBucketSize`: {e}")))
+        })
+        .transpose()
+}
+
+impl TryFrom<&FindexConfiguration> for Configuration {
+    type Error = WasmError;
+
+    fn try_from(configuration: &FindexConfiguration) -> Result<Self, Self::Error> {
+        let configuration: &JsValue = configuration;
+        if !configuration.is_object() {
+            return Err(invalid("the configuration should be an object"));
+        }
+        let backend = get_string(configuration, "backend")?;
+        let backend_configuration = match backend.as_str() {
+            "rest" => {
+                let token = AuthorizationToken::from_str(&get_string(configuration, "token")?)
+                    .map_err(|e| invalid(format_args!("malformed `token`: {e}")))?;
+                let entry_url = get_url(configuration, "entryUrl")?;
+                let chain_url = get_url(configuration, "chainUrl")?;
+                let requests_per_second = get_positive_number(configuration, "requestsPerSecond")?;
+                let max_concurrency = get_positive_integer(configuration, "maxConcurrency")?;
+                let mut rate_limit = RateLimit {
+                    requests_per_second,
+                    max_concurrency,
+                    ..RateLimit::default()
+                };
+                if let Some(max_conflict_rounds) =
+                    get_positive_integer(configuration, "maxConflictRounds")?
+                {
+                    rate_limit = rate_limit.with_max_conflict_rounds(max_conflict_rounds);
+                }
+                Ok(Self::Rest(
+                    token,
+                    entry_url,
+                    chain_url,
+                    rate_limit,
+                    get_request_options(configuration)?,
+                ))
+            }
+            "custom" => {
+                let mut entry_callbacks = get_callbacks(configuration, "entryCallbacks")?;
+                if let Some(entry_table_number) =
+                    get_positive_integer(configuration, "entryTableNumber")?
+                {
+                    entry_callbacks.table_number = entry_table_number;
+                }
+                Ok(Self::Wasm(
+                    entry_callbacks,
+                    get_callbacks(configuration, "chainCallbacks")?,
+                ))
+            }
+            "sqlite" => Ok(Self::WasmSqlite(
+                get_database(configuration, "entryDatabase")?,
+                get_database(configuration, "chainDatabase")?,
+            )),
+            _ => Err(invalid(format_args!(
+                "unknown backend `{backend}`, expected `rest`, `custom` or `sqlite`"
+            ))),
+        }?;
+        Ok(backend_configuration
+            .with_keyword_normalizer(get_keyword_normalizer(configuration)?)
+            .with_chain_padding(get_chain_padding(configuration)?))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use cosmian_findex::Keyword;
+    use js_sys::Function;
+    use wasm_bindgen_test::wasm_bindgen_test;
+
+    use super::*;
+
+    fn configuration(properties: &[(&str, JsValue)]) -> FindexConfiguration {
+        let object = Object::new();
+        for (property, value) in properties {
+            Reflect::set(&object, &JsValue::from_str(property), value).unwrap();
+        }
+        object.unchecked_into()
+    }
+
+    fn error(configuration: &FindexConfiguration) -> String {
+        Configuration::try_from(configuration)
+            .err()
+            .expect("the configuration should be invalid")
+            .to_string()
+    }
+
+    #[wasm_bindgen_test]
+    fn test_invalid_configurations() {
+        assert!(error(&configuration(&[])).contains("missing required property `backend`"));
+        assert!(error(&configuration(&[("backend", "redis".into())]))
+            .contains("unknown backend `redis`"));
+        assert!(error(&configuration(&[
+            ("backend", "rest".into()),
+            ("token", "abc".into()),
+        ]))
+        .contains("malformed `token`"));
+
+        let headers = Object::new();
+        Reflect::set(&headers, &JsValue::from_str("X-Api-Key"), &JsValue::from(1)).unwrap();
+        assert!(
+            get_request_options(&configuration(&[("headers", headers.into())]))
+                .unwrap_err()
+                .to_string()
+                .contains("`headers.X-Api-Key` should be a string")
+        );
+        assert!(
+            get_request_options(&configuration(&[("credentials", "cors".into())]))
+                .unwrap_err()
+                .to_string()
+                .contains("unknown fetch credentials `cors`")
+        );
+
+        let callbacks = Object::new();
+        assert!(error(&configuration(&[
+            ("backend", "custom".into()),
+            ("entryCallbacks", callbacks.clone().into()),
+            ("chainCallbacks", callbacks.clone().into()),
+        ]))
+        .contains("`entryCallbacks`: the `fetch` callback is required"));
+
+        Reflect::set(
+            &callbacks,
+            &JsValue::from_str("fetch"),
+            &Function::new_no_args("return []"),
+        )
+        .unwrap();
+        Reflect::set(&callbacks, &JsValue::from_str("upsert"), &JsValue::from(1)).unwrap();
+        assert!(error(&configuration(&[
+            ("backend", "custom".into()),
+            ("entryCallbacks", callbacks.into()),
+        ]))
+        .contains("`entryCallbacks`: `upsert` should be a function"));
+    }
+
+    #[wasm_bindgen_test]
+    fn test_request_options() {
+        let headers = Object::new();
+        Reflect::set(
+            &headers,
+            &JsValue::from_str("X-Api-Key"),
+            &JsValue::from_str("secret"),
+        )
+        .unwrap();
+        let request_options = get_request_options(&configuration(&[
+            ("credentials", "include".into()),
+            ("headers", headers.into()),
+        ]))
+        .unwrap();
+        assert_eq!(
+            request_options,
+            RequestOptions::default()
+                .with_credentials(FetchCredentials::Include)
+                .with_header("X-Api-Key", "secret")
+        );
+        assert_eq!(
+            get_request_options(&configuration(&[])).unwrap(),
+            RequestOptions::default()
+        );
+    }
+
+    #[wasm_bindgen_test]
+    fn test_custom_configuration() {
+        let callbacks = Object::new();
+        Reflect::set(
+            &callbacks,
+            &JsValue::from_str("fetch"),
+            &Function::new_no_args("return []"),
+        )
+        .unwrap();
+        let custom_configuration = configuration(&[
+            ("backend", "custom".into()),
+            ("entryCallbacks", callbacks.clone().into()),
+            ("chainCallbacks", callbacks.clone().into()),
+        ]);
+        assert!(matches!(
+            Configuration::try_from(&custom_configuration),
+            Ok(Configuration::Wasm(entry, chain))
+                if entry.table_number == 1 && chain.table_number == 1
+        ));
+
+        let sharded_configuration = configuration(&[
+            ("backend", "custom".into()),
+            ("entryCallbacks", callbacks.clone().into()),
+            ("chainCallbacks", callbacks.clone().into()),
+            ("entryTableNumber", JsValue::from(4)),
+        ]);
+        assert!(matches!(
+            Configuration::try_from(&sharded_configuration),
+            Ok(Configuration::Wasm(entry, chain))
+                if entry.table_number == 4 && chain.table_number == 1
+        ));
+        assert!(error(&configuration(&[
+            ("backend", "custom".into()),
+            ("entryCallbacks", callbacks.clone().into()),
+            ("chainCallbacks", callbacks.into()),
+            ("entryTableNumber", JsValue::from(0)),
+        ]))
+        .contains("`entryTableNumber` should be a positive number"));
+    }
+
+    #[wasm_bindgen_test]
+    fn test_sqlite_configuration() {
+        let database = Object::new();
+        assert!(error(&configuration(&[
+            ("backend", "sqlite".into()),
+            ("entryDatabase", database.clone().into()),
+            ("chainDatabase", database.clone().into()),
+        ]))
+        .contains("`entryDatabase` should be a SQLite database with an `exec` method"));
+
+        Reflect::set(
+            &database,
+            &JsValue::from_str("exec"),
+            &Function::new_no_args("return []"),
+        )
+        .unwrap();
+        let configuration = configuration(&[
+            ("backend", "sqlite".into()),
+            ("entryDatabase", database.clone().into()),
+            ("chainDatabase", database.into()),
+        ]);
+        assert!(matches!(
+            Configuration::try_from(&configuration),
+            Ok(Configuration::WasmSqlite(..))
+        ));
+    }
+
+    #[wasm_bindgen_test]
+    fn test_keyword_normalization_configuration() {
+        let normalization = Object::new();
+        Reflect::set(
+            &normalization,
+            &JsValue::from_str("lowercase"),
+            &JsValue::TRUE,
+        )
+        .unwrap();
+        let normalizer = get_keyword_normalizer(&configuration(&[(
+            "keywordNormalization",
+            normalization.into(),
+        )]))
+        .unwrap();
+        assert_eq!(
+            normalizer
+                .normalize(&Keyword::from("CAF\u{c9}".as_bytes()))
+                .unwrap(),
+            Keyword::from("caf\u{e9}".as_bytes())
+        );
+        assert!(get_keyword_normalizer(&configuration(&[]))
+            .unwrap()
+            .is_identity());
+
+        let normalization = Object::new();
+        Reflect::set(&normalization, &JsValue::from_str("nfc"), &JsValue::from(1)).unwrap();
+        assert!(
+            get_keyword_normalizer(&configuration(&[(
+                "keywordNormalization",
+                normalization.into()
+            )]))
+            .unwrap_err()
+            .to_string()
+            .contains("`keywordNormalization.nfc` should be a boolean")
+        );
+    }
+    #[wasm_bindgen_test]
+    fn test_chain_padding_configuration() {
+        assert_eq!(get_chain_padding(&configuration(&[])).unwrap(), None);
+        assert_eq!(
+            get_chain_padding(&configuration(&[("paddingBucketSize", 4.into())]))
+                .unwrap()
+                .map(|padding| padding.bucket_size()),
+            Some(4)
+        );
+        assert!(
+            get_chain_padding(&configuration(&[("paddingBucketSize", 1.into())]))
+                .unwrap_err()
+                .to_string()
+                .contains("`paddingBucketSize`")
+        );
+    }
+}
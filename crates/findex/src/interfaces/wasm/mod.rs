@@ -8,7 +8,9 @@ use wasm_bindgen::prelude::wasm_bindgen;
 use crate::db_interfaces::DbInterfaceError;
 
 pub mod api;
+pub mod configuration;
 pub mod types;
+pub mod worker;
 
 #[wasm_bindgen]
 pub async fn webassembly_logger_init() {
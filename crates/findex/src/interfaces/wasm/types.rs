@@ -7,7 +7,7 @@ use js_sys::{Array, JsString, Object, Reflect, Uint8Array};
 use wasm_bindgen::{prelude::wasm_bindgen, JsCast, JsValue};
 
 use super::WasmError;
-use crate::ser_de::wasm_ser_de::get_bytes_from_object_property;
+use crate::{ser_de::wasm_ser_de::get_bytes_from_object_property, LocationToScoreMap};
 
 #[wasm_bindgen]
 extern "C" {
@@ -47,10 +47,11 @@ impl TryFrom<&KeywordToDataMap> for SearchResults {
 
 #[wasm_bindgen]
 extern "C" {
-    /// See [`FindexCallbacks::progress()`](crate::core::FindexCallbacks::progress).
+    /// User interrupt called at each search graph level with the level's
+    /// results. Returning `true` stops the search.
     #[wasm_bindgen(
         typescript_type = "(progressResults: Array<{ keyword: Uint8Array, results: \
-                           Array<Uint8Array> }>) => Promise<Boolean>"
+                           Array<Uint8Array> }>) => Promise<Boolean> | Boolean"
     )]
     pub type Interrupt;
 }
@@ -228,3 +229,86 @@ impl TryFrom<&IndexedValuesAndKeywords> for HashMap<IndexedValue<Keyword, Data>,
         Ok(iv_and_words)
     }
 }
+
+#[wasm_bindgen]
+extern "C" {
+    /// Number of keywords matched by each location found by a scored search.
+    #[wasm_bindgen(typescript_type = "Array<{ location: Uint8Array, score: number }>")]
+    pub type LocationScores;
+}
+
+impl TryFrom<&LocationToScoreMap> for LocationScores {
+    type Error = WasmError;
+
+    fn try_from(scores: &LocationToScoreMap) -> Result<Self, Self::Error> {
+        let array = Array::new_with_length(scores.len() as u32);
+        for (i, (location, score)) in scores.iter().enumerate() {
+            let obj = Object::new();
+            Reflect::set(
+                &obj,
+                &JsValue::from_str("location"),
+                &Uint8Array::from(location.to_vec().as_slice()),
+            )
+            .map_err(|e| WasmError(format!("failed setting `location` into Js object: {e:?}")))?;
+            Reflect::set(&obj, &JsValue::from_str("score"), &JsValue::from(*score))
+                .map_err(|e| WasmError(format!("failed setting `score` into Js object: {e:?}")))?;
+            array.set(i as u32, obj.into());
+        }
+        Ok(Self::from(JsValue::from(array)))
+    }
+}
+
+#[wasm_bindgen]
+extern "C" {
+    /// JS Array of keywords and their associated aliases to upsert.
+    #[wasm_bindgen(typescript_type = "Array<{keyword: Uint8Array, aliases: Uint8Array[]}>")]
+    #[derive(Debug)]
+    pub type KeywordsAndAliases;
+}
+
+impl TryFrom<&KeywordsAndAliases> for HashMap<Keyword, HashSet<Keyword>> {
+    type Error = WasmError;
+
+    fn try_from(value: &KeywordsAndAliases) -> Result<Self, Self::Error> {
+        let array: &Array = value.dyn_ref().ok_or_else(|| {
+            WasmError(format!(
+                "`KeywordsAndAliases` should be an array, {} received.",
+                value
+                    .js_typeof()
+                    .dyn_ref::<JsString>()
+                    .map_or_else(|| "unknown type".to_owned(), |s| format!("{s}")),
+            ))
+        })?;
+
+        let mut keywords_and_aliases = HashMap::new();
+        for (i, try_obj) in array.values().into_iter().enumerate() {
+            let obj = try_obj.map_err(|e| {
+                WasmError(format!("could not get array element at index {i}: {e:?}"))
+            })?;
+            let keyword = get_bytes_from_object_property(&obj, "keyword").map_err(|e| {
+                WasmError(format!(
+                    "could not get `keyword` at offset {i} from '{value:?}': {e:?}"
+                ))
+            })?;
+            let aliases = Array::from(
+                &Reflect::get(&obj, &JsValue::from_str("aliases")).map_err(|e| {
+                    WasmError(format!("could not get `aliases` at offset {i}: {e:?}"))
+                })?,
+            );
+            let aliases = aliases
+                .values()
+                .into_iter()
+                .enumerate()
+                .map(|(j, try_alias)| {
+                    try_alias
+                        .map_err(|e| {
+                            WasmError(format!("could not get alias at index ({i}, {j}): {e:?}"))
+                        })
+                        .map(|alias| Keyword::from(Uint8Array::from(alias).to_vec()))
+                })
+                .collect::<Result<_, _>>()?;
+            keywords_and_aliases.insert(Keyword::from(keyword), aliases);
+        }
+        Ok(keywords_and_aliases)
+    }
+}
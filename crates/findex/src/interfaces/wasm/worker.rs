@@ -0,0 +1,227 @@
+//! Helpers to run Findex searches in a Web Worker.
+//!
+//! The main thread uses a [`FindexWorkerClient`] to post search requests to a
+//! worker holding a [`WasmFindex`] instance, which answers them using
+//! [`WasmFindex::serve_worker_request`]:
+//!
+//! ```js
+//! // worker.js
+//! const findex = await WasmFindex.new({ backend: 'rest', token, entryUrl, chainUrl })
+//! self.onmessage = (event) => findex.serve_worker_request(self, event.data)
+//!
+//! // main thread
+//! const client = new FindexWorkerClient(new Worker('worker.js'))
+//! const results = await client.search(key, label, keywords)
+//! ```
+//!
+//! Messages exchanged are:
+//!
+//! ```txt
+//! request  = { id: number, action: "search", key: Uint8Array, label: string, keywords: Array<Uint8Array> }
+//! response = { id: number, results: ArrayBuffer } | { id: number, error: Error }
+//! ```
+//!
+//! Results are packed into a single `ArrayBuffer` which is transferred to the
+//! main thread, and unpacked into views over this buffer: large result sets are
+//! never copied between threads.
+
+use std::{
+    cell::{Cell, RefCell},
+    collections::HashMap,
+    rc::Rc,
+};
+
+use js_sys::{Array, Function, Object, Promise, Reflect, Uint8Array};
+use wasm_bindgen::{prelude::*, JsCast};
+
+use super::{
+    api::WasmFindex,
+    types::{ArrayOfKeywords, SearchResults},
+    WasmError,
+};
+use crate::ser_de::wasm_ser_de::unpack_search_results;
+
+/// Unpacks search results returned by `WasmFindex.search_packed()`.
+///
+/// The returned keywords and results are views over the given array.
+#[wasm_bindgen]
+pub fn webassembly_unpack_search_results(packed: &Uint8Array) -> Result<SearchResults, JsError> {
+    unpack_search_results(packed)
+        .map_err(|e| WasmError(format!("failed unpacking search results: {e}")))
+        .map_err(JsError::from)
+}
+
+fn get_property(value: &JsValue, property: &str) -> Result<JsValue, WasmError> {
+    Reflect::get(value, &JsValue::from_str(property))
+        .map_err(|e| WasmError(format!("could not get `{property}`: {e:?}")))
+}
+
+fn set_property(object: &Object, property: &str, value: &JsValue) -> Result<(), WasmError> {
+    Reflect::set(object, &JsValue::from_str(property), value)
+        .map(|_| ())
+        .map_err(|e| WasmError(format!("failed setting `{property}` into Js object: {e:?}")))
+}
+
+/// Calls the given method of the given object with the given arguments.
+fn call_method(object: &JsValue, method: &str, args: &Array) -> Result<JsValue, WasmError> {
+    get_property(object, method)?
+        .dyn_into::<Function>()
+        .map_err(|_| WasmError(format!("`{method}` is not a function")))?
+        .apply(object, args)
+        .map_err(|e| WasmError(format!("failed calling `{method}`: {e:?}")))
+}
+
+fn get_request_id(message: &JsValue) -> Result<u32, WasmError> {
+    get_property(message, "id")?
+        .as_f64()
+        .map(|id| id as u32)
+        .ok_or_else(|| WasmError("message `id` should be a number".to_string()))
+}
+
+#[wasm_bindgen]
+impl WasmFindex {
+    /// Serves a request posted by a `FindexWorkerClient` and posts the
+    /// response back to it using the given worker scope.
+    ///
+    /// The results buffer is transferred along with the response.
+    pub async fn serve_worker_request(
+        &self,
+        scope: JsValue,
+        request: JsValue,
+    ) -> Result<(), JsError> {
+        let id = get_request_id(&request)?;
+        let response = Object::new();
+        set_property(&response, "id", &JsValue::from(id))?;
+
+        let transfer = Array::new();
+        match self.handle_worker_request(&request).await {
+            Ok(results) => {
+                let buffer = results.buffer();
+                set_property(&response, "results", &buffer)?;
+                transfer.push(&buffer);
+            }
+            Err(e) => set_property(&response, "error", &JsValue::from(e))?,
+        }
+
+        call_method(
+            &scope,
+            "postMessage",
+            &Array::of2(&response.into(), &transfer.into()),
+        )?;
+        Ok(())
+    }
+}
+
+impl WasmFindex {
+    async fn handle_worker_request(&self, request: &JsValue) -> Result<Uint8Array, JsError> {
+        let action = get_property(request, "action")?.as_string();
+        match action.as_deref() {
+            Some("search") => {
+                let key = Uint8Array::new(&get_property(request, "key")?);
+                let label = get_property(request, "label")?
+                    .as_string()
+                    .ok_or_else(|| WasmError("request `label` should be a string".to_string()))?;
+                let keywords = ArrayOfKeywords::from(get_property(request, "keywords")?);
+                self.search_packed(key, label, keywords, None).await
+            }
+            _ => Err(WasmError(format!("unsupported worker request action: {action:?}")).into()),
+        }
+    }
+}
+
+/// Resolve and reject functions of the promises of the pending requests.
+type PendingRequests = Rc<RefCell<HashMap<u32, (Function, Function)>>>;
+
+fn handle_worker_response(pending: &PendingRequests, event: &JsValue) -> Result<(), WasmError> {
+    let message = get_property(event, "data")?;
+    let id = get_request_id(&message)?;
+    let Some((resolve, reject)) = pending.borrow_mut().remove(&id) else {
+        // This response is not addressed to this client.
+        return Ok(());
+    };
+
+    let error = get_property(&message, "error")?;
+    let res = if error.is_undefined() {
+        let results = Uint8Array::new(&get_property(&message, "results")?);
+        match unpack_search_results(&results) {
+            Ok(results) => resolve.call1(&JsValue::NULL, &results),
+            Err(e) => reject.call1(&JsValue::NULL, &JsValue::from_str(&e.to_string())),
+        }
+    } else {
+        reject.call1(&JsValue::NULL, &error)
+    };
+    res.map(|_| ())
+        .map_err(|e| WasmError(format!("failed settling the request {id}: {e:?}")))
+}
+
+/// Main thread side of the Findex worker message protocol.
+///
+/// Posts the requests to the given worker and resolves the returned promises
+/// once the worker answers.
+#[wasm_bindgen]
+pub struct FindexWorkerClient {
+    worker: JsValue,
+    next_id: Cell<u32>,
+    pending: PendingRequests,
+    _listener: Closure<dyn FnMut(JsValue)>,
+}
+
+#[wasm_bindgen]
+impl FindexWorkerClient {
+    /// Instantiates a client for the given worker, which can be any object
+    /// exposing `postMessage()` and `addEventListener()`.
+    #[wasm_bindgen(constructor)]
+    pub fn new(worker: JsValue) -> Result<FindexWorkerClient, JsError> {
+        let pending = PendingRequests::default();
+        let listener = {
+            let pending = pending.clone();
+            Closure::<dyn FnMut(JsValue)>::new(move |event: JsValue| {
+                if let Err(e) = handle_worker_response(&pending, &event) {
+                    log::error!("Findex worker client: {e}");
+                }
+            })
+        };
+        call_method(
+            &worker,
+            "addEventListener",
+            &Array::of2(&JsValue::from_str("message"), listener.as_ref()),
+        )?;
+        Ok(Self {
+            worker,
+            next_id: Cell::new(0),
+            pending,
+            _listener: listener,
+        })
+    }
+
+    /// Posts a search request to the worker.
+    ///
+    /// The returned promise resolves to the search results, whose keywords
+    /// and results are views over the buffer transferred by the worker.
+    pub fn search(
+        &self,
+        key: Uint8Array,
+        label: String,
+        keywords: ArrayOfKeywords,
+    ) -> Result<Promise, JsError> {
+        let id = self.next_id.get();
+        self.next_id.set(id.wrapping_add(1));
+
+        let request = Object::new();
+        set_property(&request, "id", &JsValue::from(id))?;
+        set_property(&request, "action", &JsValue::from_str("search"))?;
+        set_property(&request, "key", &key)?;
+        set_property(&request, "label", &JsValue::from(label))?;
+        set_property(&request, "keywords", &keywords)?;
+
+        let promise = Promise::new(&mut |resolve, reject| {
+            self.pending.borrow_mut().insert(id, (resolve, reject));
+        });
+
+        if let Err(e) = call_method(&self.worker, "postMessage", &Array::of1(&request)) {
+            self.pending.borrow_mut().remove(&id);
+            return Err(e.into());
+        }
+        Ok(promise)
+    }
+}
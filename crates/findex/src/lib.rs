@@ -3,7 +3,10 @@
 pub mod db_interfaces;
 
 #[cfg(any(
+    feature = "etcd-interface",
     feature = "ffi",
+    feature = "grpc-interface",
+    feature = "object-store-interface",
     feature = "python",
     feature = "redis-interface",
     feature = "rest-interface",
@@ -15,18 +18,30 @@ mod instantiation;
 #[cfg(any(feature = "ffi", feature = "python", feature = "wasm"))]
 pub mod interfaces;
 
-#[cfg(any(test, feature = "ffi"))]
+#[cfg(any(test, feature = "ffi", feature = "python"))]
 pub mod logger;
 
 #[cfg(feature = "serialization")]
 pub mod ser_de;
 
+#[cfg(feature = "telemetry")]
+pub mod telemetry;
+
 #[cfg(any(
+    feature = "etcd-interface",
     feature = "ffi",
+    feature = "grpc-interface",
+    feature = "object-store-interface",
     feature = "python",
     feature = "redis-interface",
     feature = "rest-interface",
     feature = "sqlite-interface",
     feature = "wasm",
 ))]
-pub use instantiation::{Configuration, InstantiatedFindex};
+pub use instantiation::{
+    deserialize_index_dump, is_dummy, is_tombstone, serialize_index_dump, ChainPadding,
+    ChunkedAddError, Configuration, IndexStatistics, InstantiatedFindex, KeywordNormalizer,
+    KeywordToAliasesMap, KeywordToPayloadsMap, LocationToScoreMap, MatchMode, NormalizeKeyword,
+    PayloadKey, SyncStatistics, DEFAULT_CHUNK_SIZE, DUMMY_DATA_PREFIX, INDEX_DUMP_VERSION,
+    PAYLOAD_DATA_PREFIX, TOMBSTONE_DATA_PREFIX,
+};
@@ -1,23 +1,25 @@
-use std::sync::Once;
+use std::sync::{Once, OnceLock};
 
-use tracing_subscriber::{layer::SubscriberExt, util::SubscriberInitExt, EnvFilter};
+use tracing_subscriber::{
+    layer::SubscriberExt, reload::Handle, util::SubscriberInitExt, EnvFilter, Registry,
+};
 
 static LOG_INIT: Once = Once::new();
 
-/// # Panics
+/// Handle used to change the filter of the default subscriber at runtime. Only
+/// set if the default subscriber could be installed.
+static LOG_FILTER: OnceLock<Handle<EnvFilter, Registry>> = OnceLock::new();
+
+/// Installs the default tracing subscriber.
 ///
-/// Will panic if we cannot set global tracing subscriber
+/// Does nothing if a global subscriber is already installed (the OTLP exporter
+/// for instance).
 pub fn log_init() {
     LOG_INIT.call_once(|| {
         tracing_setup();
     });
 }
 
-/// # Panics
-///
-/// Will panic if:
-/// - we cannot set global subscriber
-/// - we cannot init the log tracer
 fn tracing_setup() {
     let format = tracing_subscriber::fmt::layer()
         .with_level(true)
@@ -28,11 +30,38 @@ fn tracing_setup() {
         .with_ansi(true)
         .compact();
 
-    let (filter, _reload_handle) =
+    let (filter, reload_handle) =
         tracing_subscriber::reload::Layer::new(EnvFilter::from_default_env());
 
-    tracing_subscriber::registry()
+    // Fails if a global subscriber is already installed.
+    if tracing_subscriber::registry()
         .with(filter)
         .with(format)
-        .init();
+        .try_init()
+        .is_ok()
+    {
+        let _ = LOG_FILTER.set(reload_handle);
+    }
+}
+
+/// Sets the logs to display, overriding the `RUST_LOG` environment variable.
+///
+/// The given directives use the `RUST_LOG` syntax: a level (`warn`, `debug`,
+/// `trace`...) or per-target levels, e.g.
+/// `cloudproof_findex::db_interfaces=debug`. The upsert conflicts, the retries
+/// and the callback errors are logged by the `cloudproof_findex::db_interfaces`
+/// targets.
+///
+/// Installs the default subscriber if needed.
+pub fn set_log_level(directives: &str) -> Result<(), String> {
+    log_init();
+    let filter = EnvFilter::try_new(directives)
+        .map_err(|e| format!("invalid log directives `{directives}`: {e}"))?;
+    LOG_FILTER
+        .get()
+        .ok_or_else(|| {
+            "cannot set the log level: another tracing subscriber is installed".to_string()
+        })?
+        .reload(filter)
+        .map_err(|e| format!("cannot set the log level: {e}"))
 }
@@ -1,6 +1,9 @@
 use std::collections::{HashMap, HashSet};
 
-use cosmian_crypto_core::bytes_ser_de::{Deserializer, Serializer};
+use cosmian_crypto_core::{
+    bytes_ser_de::{Deserializer, Serializer},
+    reexport::zeroize::Zeroizing,
+};
 use cosmian_findex::{
     Data, EncryptedValue, IndexedValue, Keyword, Keywords, Token, TokenToEncryptedValueMap,
     TokenWithEncryptedValueList, Tokens,
@@ -8,6 +11,44 @@ use cosmian_findex::{
 
 use crate::ser_de::SerializationError;
 
+/// Version of the format of the payloads exchanged with the FFI callbacks.
+///
+/// The payloads given to and returned by the callbacks are prefixed with this
+/// version:
+///
+/// ```txt
+/// versioned payload = CALLBACK_FORMAT_VERSION || payload
+/// ```
+///
+/// This version is incremented upon each change of the serialization of the
+/// payloads, allowing the callback implementations to detect that they do not
+/// support the format used by this library instead of corrupting the indexes.
+pub const CALLBACK_FORMAT_VERSION: u8 = 1;
+
+/// Prefixes the given payload with the [`CALLBACK_FORMAT_VERSION`].
+#[must_use]
+pub fn add_format_version(payload: &[u8]) -> Vec<u8> {
+    let mut bytes = Vec::with_capacity(1 + payload.len());
+    bytes.push(CALLBACK_FORMAT_VERSION);
+    bytes.extend_from_slice(payload);
+    bytes
+}
+
+/// Checks the format version of the given payload and returns the payload
+/// without it.
+pub fn strip_format_version(bytes: &[u8]) -> Result<&[u8], SerializationError> {
+    match bytes.split_first() {
+        Some((&CALLBACK_FORMAT_VERSION, payload)) => Ok(payload),
+        Some((version, _)) => Err(SerializationError(format!(
+            "unsupported callback format version {version}, expected \
+             {CALLBACK_FORMAT_VERSION}"
+        ))),
+        None => Err(SerializationError(
+            "missing callback format version".to_string(),
+        )),
+    }
+}
+
 /// Maximum number of bytes used by a LEB128 encoding.
 ///
 /// `8` LEB128 bytes can encode numbers up to `2^56` which should be an upper
@@ -44,15 +85,34 @@ pub fn get_upsert_output_size(
         .flat_map(|set| set.iter().map(|e| MAX_LEB128_ENCODING_SIZE + e.len()))
         .sum::<usize>()
 }
-pub fn serialize_token_set(set: &Tokens) -> Result<Vec<u8>, SerializationError> {
-    let mut ser = Serializer::with_capacity(set.len());
+fn write_token_set(ser: &mut Serializer, set: &Tokens) -> Result<(), SerializationError> {
     ser.write_leb128_u64(set.len() as u64)?;
     for element in set.iter() {
         ser.write_array(element)?;
     }
+    Ok(())
+}
+
+pub fn serialize_token_set(set: &Tokens) -> Result<Vec<u8>, SerializationError> {
+    let mut ser = Serializer::with_capacity(MAX_LEB128_ENCODING_SIZE + set.len() * Token::LENGTH);
+    write_token_set(&mut ser, set)?;
     Ok(ser.finalize().to_vec())
 }
 
+/// Serializes the given tokens prefixed with the [`CALLBACK_FORMAT_VERSION`].
+///
+/// The payload is written once into a buffer allocated upfront, which can be
+/// given as is to the callbacks.
+pub fn serialize_versioned_token_set(
+    set: &Tokens,
+) -> Result<Zeroizing<Vec<u8>>, SerializationError> {
+    let mut ser =
+        Serializer::with_capacity(1 + MAX_LEB128_ENCODING_SIZE + set.len() * Token::LENGTH);
+    ser.write_array(&[CALLBACK_FORMAT_VERSION])?;
+    write_token_set(&mut ser, set)?;
+    Ok(ser.finalize())
+}
+
 pub fn deserialize_token_set(bytes: &[u8]) -> Result<Tokens, SerializationError> {
     let mut de = Deserializer::new(bytes);
     let length = usize::try_from(de.read_leb128_u64()?)?;
@@ -116,18 +176,42 @@ pub fn deserialize_data_set(bytes: &[u8]) -> Result<HashSet<Data>, Serialization
     Ok(res)
 } //TODO: merge functions
 
-pub fn serialize_edx_lines<const VALUE_LENGTH: usize>(
+fn write_edx_lines<const VALUE_LENGTH: usize>(
+    ser: &mut Serializer,
     map: &TokenToEncryptedValueMap<VALUE_LENGTH>,
-) -> Result<Vec<u8>, SerializationError> {
-    let mut ser = Serializer::with_capacity(map.len());
+) -> Result<(), SerializationError> {
     ser.write_leb128_u64(map.len() as u64)?;
     for (uid, value) in map.iter() {
         ser.write_array(uid)?;
         ser.write_vec(&<Vec<u8>>::from(value))?;
     }
+    Ok(())
+}
+
+pub fn serialize_edx_lines<const VALUE_LENGTH: usize>(
+    map: &TokenToEncryptedValueMap<VALUE_LENGTH>,
+) -> Result<Vec<u8>, SerializationError> {
+    let mut ser = Serializer::with_capacity(get_serialized_edx_lines_size_bound::<VALUE_LENGTH>(
+        map.len(),
+        1,
+    ));
+    write_edx_lines(&mut ser, map)?;
     Ok(ser.finalize().to_vec())
 }
 
+/// Serializes the given EDX lines prefixed with the
+/// [`CALLBACK_FORMAT_VERSION`], cf. [`serialize_versioned_token_set`].
+pub fn serialize_versioned_edx_lines<const VALUE_LENGTH: usize>(
+    map: &TokenToEncryptedValueMap<VALUE_LENGTH>,
+) -> Result<Zeroizing<Vec<u8>>, SerializationError> {
+    let mut ser = Serializer::with_capacity(
+        1 + get_serialized_edx_lines_size_bound::<VALUE_LENGTH>(map.len(), 1),
+    );
+    ser.write_array(&[CALLBACK_FORMAT_VERSION])?;
+    write_edx_lines(&mut ser, map)?;
+    Ok(ser.finalize())
+}
+
 pub fn deserialize_edx_lines<const VALUE_LENGTH: usize>(
     bytes: &[u8],
 ) -> Result<TokenWithEncryptedValueList<VALUE_LENGTH>, SerializationError> {
@@ -179,6 +263,75 @@ pub fn deserialize_indexed_values(
     Ok(items)
 }
 
+/// Returns an upper bound on the size of the aliases returned by
+/// `h_add_aliases` (resp. `h_delete_aliases`), cf. [`get_upsert_output_size`].
+#[must_use]
+pub fn get_aliases_output_size(aliases: &HashMap<Keyword, HashSet<Keyword>>) -> usize {
+    aliases
+        .values()
+        .flat_map(|set| set.iter().map(|e| MAX_LEB128_ENCODING_SIZE + e.len()))
+        .sum::<usize>()
+}
+
+pub fn serialize_keyword_to_keywords(
+    map: &HashMap<Keyword, HashSet<Keyword>>,
+) -> Result<Vec<u8>, SerializationError> {
+    let mut ser = Serializer::with_capacity(map.len());
+    ser.write_leb128_u64(map.len() as u64)?;
+    for (keyword, keywords) in map {
+        ser.write_vec(keyword)?;
+        ser.write_leb128_u64(keywords.len() as u64)?;
+        for element in keywords {
+            ser.write_vec(element)?;
+        }
+    }
+    Ok(ser.finalize().to_vec())
+}
+
+pub fn deserialize_keyword_to_keywords(
+    bytes: &[u8],
+) -> Result<HashMap<Keyword, HashSet<Keyword>>, SerializationError> {
+    let mut de = Deserializer::new(bytes);
+    let length = <usize>::try_from(de.read_leb128_u64()?)?;
+    let mut items = HashMap::with_capacity(length);
+    for _ in 0..length {
+        let keyword = Keyword::from(de.read_vec()?);
+        let keywords_number = usize::try_from(de.read_leb128_u64()?)?;
+        let mut set = HashSet::with_capacity(keywords_number);
+        for _ in 0..keywords_number {
+            set.insert(Keyword::from(de.read_vec()?));
+        }
+        items.insert(keyword, set);
+    }
+    Ok(items)
+}
+
+pub fn serialize_location_scores(
+    scores: &HashMap<Data, usize>,
+) -> Result<Vec<u8>, SerializationError> {
+    let mut ser = Serializer::with_capacity(scores.len());
+    ser.write_leb128_u64(scores.len() as u64)?;
+    for (location, score) in scores {
+        ser.write_vec(location)?;
+        ser.write_leb128_u64(*score as u64)?;
+    }
+    Ok(ser.finalize().to_vec())
+}
+
+pub fn deserialize_location_scores(
+    bytes: &[u8],
+) -> Result<HashMap<Data, usize>, SerializationError> {
+    let mut de = Deserializer::new(bytes);
+    let length = <usize>::try_from(de.read_leb128_u64()?)?;
+    let mut scores = HashMap::with_capacity(length);
+    for _ in 0..length {
+        let location = Data::from(de.read_vec()?);
+        let score = usize::try_from(de.read_leb128_u64()?)?;
+        scores.insert(location, score);
+    }
+    Ok(scores)
+}
+
 pub fn serialize_intermediate_results(
     res: &HashMap<Keyword, HashSet<IndexedValue<Keyword, Data>>>,
 ) -> Result<Vec<u8>, SerializationError> {
@@ -201,6 +354,26 @@ mod tests {
 
     use super::*;
 
+    #[test]
+    fn test_format_version() {
+        let tokens = Tokens::from_iter([
+            Token::from([0; Token::LENGTH]),
+            Token::from([1; Token::LENGTH]),
+        ]);
+        let payload = serialize_token_set(&tokens).unwrap();
+        let versioned_payload = add_format_version(&payload);
+        assert_eq!(versioned_payload[0], CALLBACK_FORMAT_VERSION);
+        assert_eq!(strip_format_version(&versioned_payload).unwrap(), payload);
+        assert_eq!(
+            *serialize_versioned_token_set(&tokens).unwrap(),
+            versioned_payload
+        );
+
+        // Payloads using another format are rejected.
+        assert!(strip_format_version(&[CALLBACK_FORMAT_VERSION + 1, 0]).is_err());
+        assert!(strip_format_version(&[]).is_err());
+    }
+
     #[test]
     fn test_uid_set_serialization() {
         let uids = Tokens::from_iter([
@@ -254,6 +427,31 @@ mod tests {
         let _res = deserialize_indexed_values(&non_reg_vector).unwrap();
     }
 
+    #[test]
+    fn test_keyword_to_keywords_serialization() {
+        let aliases = HashMap::from([
+            (
+                Keyword::from("robert"),
+                HashSet::from([Keyword::from("rob"), Keyword::from("bob")]),
+            ),
+            (Keyword::from("rob"), HashSet::from([Keyword::from("ro")])),
+        ]);
+        let serialized_aliases = serialize_keyword_to_keywords(&aliases).unwrap();
+        let res = deserialize_keyword_to_keywords(&serialized_aliases).unwrap();
+        assert_eq!(aliases, res);
+    }
+
+    #[test]
+    fn test_location_scores_serialization() {
+        let scores = HashMap::from([
+            (Data::from("robert doe DB location"), 2),
+            (Data::from("robert smith DB location"), 1),
+        ]);
+        let serialized_scores = serialize_location_scores(&scores).unwrap();
+        let res = deserialize_location_scores(&serialized_scores).unwrap();
+        assert_eq!(scores, res);
+    }
+
     #[test]
     fn test_edx_lines_serialization() {
         let edx_lines = HashMap::<Token, EncryptedValue<{ Token::LENGTH }>>::from_iter([
@@ -305,5 +503,11 @@ mod tests {
             serialize_edx_lines(&TokenToEncryptedValueMap::from(edx_lines.clone())).unwrap();
         let res = deserialize_edx_lines(&serialized_lines).unwrap();
         assert_eq!(edx_lines, res.into_iter().collect());
+
+        let versioned_lines =
+            serialize_versioned_edx_lines(&TokenToEncryptedValueMap::from(edx_lines.clone()))
+                .unwrap();
+        let res = deserialize_edx_lines(strip_format_version(&versioned_lines).unwrap()).unwrap();
+        assert_eq!(edx_lines, res.into_iter().collect());
     }
 }
@@ -1,10 +1,12 @@
 use std::collections::HashSet;
 
-use cosmian_findex::{EncryptedValue, Token, TokenToEncryptedValueMap, Tokens};
+use cosmian_crypto_core::bytes_ser_de::{Deserializer, Serializer};
+use cosmian_findex::{EncryptedValue, KeywordToDataMap, Token, TokenToEncryptedValueMap, Tokens};
 use js_sys::{Array, JsString, Object, Reflect, Uint8Array};
 use wasm_bindgen::{JsCast, JsValue};
 
 use super::SerializationError;
+use crate::interfaces::wasm::types::SearchResults;
 
 fn set_bytes_in_object_property(
     obj: &JsValue,
@@ -124,10 +126,77 @@ pub fn edx_lines_to_js_array<const VALUE_LENGTH: usize>(
     Ok(res)
 }
 
+/// Packs the given search results into a `Uint8Array` owning its buffer.
+///
+/// The format is the one used by the FFI search:
+/// `LEB128(n_keywords) || (keyword || LEB128(n_data) || data*)*` where each
+/// keyword and datum is prefixed by its LEB128-encoded length.
+pub fn pack_search_results(results: &KeywordToDataMap) -> Result<Uint8Array, SerializationError> {
+    let mut ser = Serializer::new();
+    ser.write_leb128_u64(results.len() as u64)?;
+    for (keyword, data) in results.iter() {
+        ser.write_vec(keyword)?;
+        ser.write_leb128_u64(data.len() as u64)?;
+        for datum in data {
+            ser.write_vec(datum)?;
+        }
+    }
+    // `Uint8Array::from` copies the bytes into a new JS `ArrayBuffer`, contrary
+    // to views over the WASM memory which cannot be transferred.
+    Ok(Uint8Array::from(ser.finalize().as_slice()))
+}
+
+/// Reads the next serialized vector and returns a view over it in the packed
+/// array.
+fn read_view(
+    de: &mut Deserializer,
+    packed: &Uint8Array,
+    packed_length: usize,
+) -> Result<Uint8Array, SerializationError> {
+    let length = de.read_vec_as_ref()?.len();
+    let end = packed_length - de.value().len();
+    Ok(packed.subarray(u32::try_from(end - length)?, u32::try_from(end)?))
+}
+
+/// Unpacks search results packed by [`pack_search_results`].
+///
+/// The returned keywords and results are views over the given array: they
+/// are not copied into new `ArrayBuffer`s.
+pub fn unpack_search_results(packed: &Uint8Array) -> Result<SearchResults, SerializationError> {
+    let bytes = packed.to_vec();
+    let mut de = Deserializer::new(&bytes);
+
+    let n_keywords = usize::try_from(de.read_leb128_u64()?)?;
+    let array = Array::new_with_length(u32::try_from(n_keywords)?);
+    for i in 0..n_keywords {
+        let keyword = read_view(&mut de, packed, bytes.len())?;
+        let n_data = usize::try_from(de.read_leb128_u64()?)?;
+        let sub_array = Array::new_with_length(u32::try_from(n_data)?);
+        for j in 0..n_data {
+            sub_array.set(
+                u32::try_from(j)?,
+                read_view(&mut de, packed, bytes.len())?.into(),
+            );
+        }
+        let obj = Object::new();
+        Reflect::set(&obj, &JsValue::from_str("keyword"), &keyword)?;
+        Reflect::set(&obj, &JsValue::from_str("results"), &sub_array)?;
+        array.set(u32::try_from(i)?, obj.into());
+    }
+
+    if de.value().is_empty() {
+        Ok(SearchResults::from(JsValue::from(array)))
+    } else {
+        Err(SerializationError(
+            "remaining bytes after search results unpacking".to_string(),
+        ))
+    }
+}
+
 #[cfg(test)]
 mod tests {
 
-    use cosmian_findex::{Token, Tokens};
+    use cosmian_findex::{Data, Keyword, Token, Tokens};
     use wasm_bindgen_test::wasm_bindgen_test;
 
     use super::*;
@@ -200,4 +269,39 @@ mod tests {
         let res = js_value_to_edx_lines(&JsValue::from(&js_uids)).unwrap();
         assert_eq!(uids, res.into_iter().collect());
     }
+
+    #[wasm_bindgen_test]
+    fn test_search_results_packing() {
+        let results = KeywordToDataMap::from_iter([
+            (
+                Keyword::from("Felix".as_bytes()),
+                HashSet::from_iter([
+                    Data::from(b"Felix's location".to_vec()),
+                    Data::from(b"Cat's location".to_vec()),
+                ]),
+            ),
+            (Keyword::from("Robert".as_bytes()), HashSet::new()),
+        ]);
+
+        let packed = pack_search_results(&results).unwrap();
+        let unpacked = Array::from(&JsValue::from(unpack_search_results(&packed).unwrap()));
+        assert_eq!(unpacked.length(), 2);
+
+        for obj in unpacked.iter() {
+            let keyword = Keyword::from(
+                Uint8Array::new(&Reflect::get(&obj, &JsValue::from_str("keyword")).unwrap())
+                    .to_vec(),
+            );
+            let data = Array::from(&Reflect::get(&obj, &JsValue::from_str("results")).unwrap())
+                .iter()
+                .map(|datum| {
+                    let datum = Uint8Array::new(&datum);
+                    // Results are views over the packed buffer.
+                    assert_eq!(datum.buffer(), packed.buffer());
+                    Data::from(datum.to_vec())
+                })
+                .collect::<HashSet<_>>();
+            assert_eq!(results.get(&keyword), Some(&data));
+        }
+    }
 }
@@ -0,0 +1,79 @@
+//! Export of the Findex tracing spans to an OpenTelemetry collector.
+
+use std::sync::OnceLock;
+
+use opentelemetry::KeyValue;
+use opentelemetry_otlp::WithExportConfig;
+use opentelemetry_sdk::{runtime, trace, Resource};
+use tokio::runtime::Runtime;
+use tracing_subscriber::{layer::SubscriberExt, util::SubscriberInitExt, EnvFilter};
+
+use crate::db_interfaces::DbInterfaceError;
+
+/// Runtime running the batch span exporter.
+///
+/// FFI calls run on short-lived runtimes: the exporter needs its own runtime
+/// to outlive them.
+static EXPORTER_RUNTIME: OnceLock<Runtime> = OnceLock::new();
+
+/// Default filter used when `RUST_LOG` is not set.
+const DEFAULT_FILTER: &str = "cloudproof_findex=info";
+
+/// Installs a global tracing subscriber exporting the spans to the OTLP
+/// collector listening at the given gRPC endpoint (e.g.
+/// `http://localhost:4317`).
+///
+/// Spans are filtered using the `RUST_LOG` environment variable, defaulting to
+/// the `info` level of this crate. This function must be called before any
+/// other global subscriber is installed, and at most once.
+pub fn install_otlp_exporter(endpoint: &str, service_name: &str) -> Result<(), DbInterfaceError> {
+    if EXPORTER_RUNTIME.get().is_some() {
+        return Err(DbInterfaceError::Other(
+            "the OTLP exporter is already installed".to_string(),
+        ));
+    }
+
+    let rt = tokio::runtime::Builder::new_multi_thread()
+        .worker_threads(1)
+        .enable_all()
+        .build()?;
+
+    // The batch span processor is spawned on the current runtime.
+    let guard = rt.enter();
+    let tracer = opentelemetry_otlp::new_pipeline()
+        .tracing()
+        .with_exporter(
+            opentelemetry_otlp::new_exporter()
+                .tonic()
+                .with_endpoint(endpoint),
+        )
+        .with_trace_config(
+            trace::config().with_resource(Resource::new(vec![KeyValue::new(
+                "service.name",
+                service_name.to_string(),
+            )])),
+        )
+        .install_batch(runtime::Tokio)
+        .map_err(|e| DbInterfaceError::Other(format!("cannot install the OTLP exporter: {e}")))?;
+    drop(guard);
+
+    let filter =
+        EnvFilter::try_from_default_env().unwrap_or_else(|_| EnvFilter::new(DEFAULT_FILTER));
+
+    tracing_subscriber::registry()
+        .with(filter)
+        .with(tracing_opentelemetry::layer().with_tracer(tracer))
+        .try_init()
+        .map_err(|e| {
+            DbInterfaceError::Other(format!("cannot set the global tracing subscriber: {e}"))
+        })?;
+
+    EXPORTER_RUNTIME
+        .set(rt)
+        .map_err(|_| DbInterfaceError::Other("the OTLP exporter is already installed".to_string()))
+}
+
+/// Flushes the pending spans and shuts the OTLP exporter down.
+pub fn shutdown_otlp_exporter() {
+    opentelemetry::global::shutdown_tracer_provider();
+}
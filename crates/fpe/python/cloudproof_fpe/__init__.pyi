@@ -1,3 +1,5 @@
+from typing import List, Sequence, Set, Tuple
+
 class Alphabet:
     """
     The `Alphabet` class brings Format Preserving Encryption (FPE) functions
@@ -20,8 +22,27 @@ class Alphabet:
                 - "alpha_numeric",
                 - "utf",
                 - "chinese",
+                - "hiragana",
+                - "katakana",
+                - "japanese",
+                - "hangul",
                 - "latin1sup",
                 - "latin1sup_alphanum".
+                These ids can be composed: "alpha_numeric+latin1sup" is the union of
+                the given alphabets, and the characters following the first "-" are
+                removed from it, e.g. "alpha_numeric-0O1l".
+        """
+    @staticmethod
+    def from_unicode_ranges(ranges: Sequence[Tuple[int, int]]) -> Alphabet:
+        """
+        Creates an alphabet from inclusive ranges of Unicode code points.
+
+        Args:
+            ranges (Sequence[Tuple[int, int]]): the (start, end) code points
+                of each range.
+
+        Returns:
+            Alphabet: the alphabet holding the characters of the given ranges
         """
     def encrypt(self, key: bytes, tweak: bytes, plaintext: str) -> str:
         """
@@ -49,6 +70,71 @@ class Alphabet:
             str: A string containing the plaintext produced by decrypting the ciphertext
                 using the specified key and tweak.
         """
+    def encrypt_series(
+        self, key: bytes, tweak: bytes, plaintexts: Sequence[str]
+    ) -> List[str]:
+        """
+        Encrypts all the given plaintexts using the specified key and tweak.
+        The GIL is released and the plaintexts are encrypted in parallel.
+
+        Args:
+            key (bytes): A bytes object containing the key to be used for the encryption.
+            tweak (bytes): A bytes object containing the tweak to be used for the encryption.
+            plaintexts (Sequence[str]): The plaintexts to encrypt, e.g. a list of strings
+                or a numpy array of strings (`series.to_numpy()` for a pandas Series).
+
+        Returns:
+            List[str]: The ciphertexts, in the same order as the plaintexts.
+        """
+    def decrypt_series(
+        self, key: bytes, tweak: bytes, ciphertexts: Sequence[str]
+    ) -> List[str]:
+        """
+        Decrypts all the given ciphertexts using the specified key and tweak.
+        The GIL is released and the ciphertexts are decrypted in parallel.
+
+        Args:
+            key (bytes): A bytes object containing the key to be used for the decryption.
+            tweak (bytes): A bytes object containing the tweak to be used for the decryption.
+            ciphertexts (Sequence[str]): The ciphertexts to decrypt, e.g. a list of strings
+                or a numpy array of strings.
+
+        Returns:
+            List[str]: The plaintexts, in the same order as the ciphertexts.
+        """
+    def validate(self, input: str) -> Set[str]:
+        """
+        Returns the characters of the input that are not part of the alphabet.
+        These characters are left unencrypted.
+
+        Args:
+            input (str): A string to check against the alphabet.
+
+        Returns:
+            Set[str]: The set of characters not in the alphabet.
+        """
+    def minimum_plaintext_length(self) -> int:
+        """
+        Returns the minimum number of alphabet characters of the inputs for FPE
+        to be secure. Shorter inputs are rejected with an `InputTooShort` error.
+
+        Returns:
+            int: The minimum input length.
+        """
+    def pseudonymize_preview(self, template: str, seed: bytes) -> str:
+        """
+        Generates a fake value following the format template, for testing purposes.
+        Each `#` of the template is replaced by a character of the alphabet,
+        the other characters are kept. No key is involved.
+
+        Args:
+            template (str): The format of the value to generate, e.g. `###-##-####`.
+            seed (bytes): The seed of the generation, e.g. a row number.
+                The same seed always generates the same value.
+
+        Returns:
+            str: The generated value.
+        """
     def extend_with(self, additional_characters: str) -> None:
         """
         Extends the current alphabet with additional characters.
@@ -60,6 +146,24 @@ class Alphabet:
         Returns:
             None: This method does not return anything.
         """
+    def extend_with_alphabet(self, other: Alphabet) -> None:
+        """
+        Extends the current alphabet with the characters of another alphabet.
+
+        Args:
+            other (Alphabet): the alphabet whose characters to add.
+        """
+    def without(self, characters: str) -> Alphabet:
+        """
+        Returns a copy of the alphabet without the given characters,
+        e.g. to exclude the characters which are easily confused.
+
+        Args:
+            characters (str): the characters to remove from the alphabet.
+
+        Returns:
+            Alphabet: the alphabet without the given characters
+        """
 
 class Integer:
     """
@@ -131,6 +235,146 @@ class Integer:
         Returns:
             str: The decrypted string.
         """
+    def encrypt_fixed_width(self, key: bytes, tweak: bytes, plaintext: str) -> str:
+        """
+        Encrypts a numeric string of exactly `digits` digits using the FPE algorithm with the given key and tweak.
+        Leading zeros are preserved: the ciphertext has as many digits as the plaintext.
+
+        Args:
+            key (bytes): The key used for encryption.
+            tweak (bytes): The tweak used for encryption.
+            plaintext (str): The numeric string to be encrypted.
+
+        Returns:
+            str: The encrypted numeric string.
+        """
+    def decrypt_fixed_width(self, key: bytes, tweak: bytes, ciphertext: str) -> str:
+        """
+        Decrypts a numeric string of exactly `digits` digits using the FPE algorithm with the given key and tweak.
+        Leading zeros are preserved: the plaintext has as many digits as the ciphertext.
+
+        Args:
+            key (bytes): The key used for decryption.
+            tweak (bytes): The tweak used for decryption.
+            ciphertext (str): The numeric string to be decrypted.
+
+        Returns:
+            str: The decrypted numeric string.
+        """
+
+class CreditCard:
+    """
+    A class providing methods for encrypting and decrypting credit card
+    numbers using the FPE (Format Preserving Encryption) algorithm.
+
+    The first and last digits are kept in clear while the middle digits are
+    encrypted. The Luhn check digit is recomputed so that the ciphertext is a
+    valid card number.
+    """
+
+    def __init__(self, keep_first: int = 6, keep_last: int = 4):
+        """
+        Initializes a new CreditCard object.
+
+        Args:
+            keep_first (int): The number of leading digits kept in clear.
+            keep_last (int): The number of trailing digits kept in clear,
+                check digit included.
+        """
+    def encrypt(self, key: bytes, tweak: bytes, plaintext: str) -> str:
+        """
+        Encrypts the middle digits of the given credit card number.
+
+        Args:
+            key (bytes): The key used for encryption.
+            tweak (bytes): The tweak used for encryption.
+            plaintext (str): The card number, which may contain spaces and dashes.
+
+        Returns:
+            str: The encrypted card number.
+        """
+    def decrypt(self, key: bytes, tweak: bytes, ciphertext: str) -> str:
+        """
+        Decrypts the middle digits of the given credit card number.
+
+        Args:
+            key (bytes): The key used for decryption.
+            tweak (bytes): The tweak used for decryption.
+            ciphertext (str): The encrypted card number.
+
+        Returns:
+            str: The decrypted card number.
+        """
+
+class Decimal:
+    """
+    A class providing methods for encrypting and decrypting decimal numbers
+    with a fixed number of integer and fraction digits (e.g. money amounts)
+    using the FPE (Format Preserving Encryption) algorithm.
+
+    The sign and the scale of the decimals are preserved. Decimals are given
+    either as strings or as integers scaled by 10^fraction_digits.
+    """
+
+    def __init__(self, integer_digits: int, fraction_digits: int):
+        """
+        Initializes a new Decimal object.
+
+        Args:
+            integer_digits (int): The number of integer digits.
+            fraction_digits (int): The number of fraction digits.
+        """
+    def encrypt(self, key: bytes, tweak: bytes, plaintext: int) -> int:
+        """
+        Encrypts the given decimal scaled by 10^fraction_digits.
+
+        Args:
+            key (bytes): The key used for encryption.
+            tweak (bytes): The tweak used for encryption.
+            plaintext (int): The scaled decimal, e.g. -123456 for -1234.56
+                with 2 fraction digits.
+
+        Returns:
+            int: The encrypted scaled decimal.
+        """
+    def decrypt(self, key: bytes, tweak: bytes, ciphertext: int) -> int:
+        """
+        Decrypts the given decimal scaled by 10^fraction_digits.
+
+        Args:
+            key (bytes): The key used for decryption.
+            tweak (bytes): The tweak used for decryption.
+            ciphertext (int): The encrypted scaled decimal.
+
+        Returns:
+            int: The decrypted scaled decimal.
+        """
+    def encrypt_str(self, key: bytes, tweak: bytes, plaintext: str) -> str:
+        """
+        Encrypts the given decimal string.
+
+        Args:
+            key (bytes): The key used for encryption.
+            tweak (bytes): The tweak used for encryption.
+            plaintext (str): The decimal, e.g. '-1234.56'.
+
+        Returns:
+            str: The encrypted decimal, with exactly fraction_digits fraction
+                digits.
+        """
+    def decrypt_str(self, key: bytes, tweak: bytes, ciphertext: str) -> str:
+        """
+        Decrypts the given decimal string.
+
+        Args:
+            key (bytes): The key used for decryption.
+            tweak (bytes): The tweak used for decryption.
+            ciphertext (str): The encrypted decimal.
+
+        Returns:
+            str: The decrypted decimal, with exactly fraction_digits fraction
+                digits.
+        """
 
 class Float:
     """
@@ -168,3 +412,32 @@ class Float:
         Returns:
             float: the plaintext floating point number.
         """
+
+class FpeError(Exception):
+    """
+    Base class of the errors raised by the FPE methods.
+    """
+
+class KeyLengthError(FpeError):
+    """
+    The key does not have the expected length.
+    """
+
+    length: int
+    expected: int
+
+class AlphabetMismatch(FpeError):
+    """
+    The input contains characters that are not part of the alphabet.
+    """
+
+    chars: List[str]
+
+class InputTooShort(FpeError):
+    """
+    The input has fewer alphabet characters than the minimum required for FPE
+    to be secure.
+    """
+
+    length: int
+    min: int
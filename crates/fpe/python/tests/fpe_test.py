@@ -2,7 +2,17 @@
 import os
 import unittest
 
-from cloudproof_fpe import Alphabet, Float, Integer
+from cloudproof_fpe import (
+    Alphabet,
+    AlphabetMismatch,
+    CreditCard,
+    Decimal,
+    Float,
+    FpeError,
+    InputTooShort,
+    Integer,
+    KeyLengthError,
+)
 
 KEY_LENGTH = 32
 KEY = os.urandom(KEY_LENGTH)
@@ -30,6 +40,23 @@ class TestFpe(unittest.TestCase):
             assert len(credit_card_number) == len(ciphertext)
             assert cleartext == credit_card_number
 
+    def test_credit_card_helper(self) -> None:
+        """
+        FPE on credit card numbers keeping the BIN, the last digits and the
+        Luhn validity
+        """
+        credit_card = CreditCard()
+        credit_card_number = '4532-0151-1283-0366'
+        ciphertext = credit_card.encrypt(KEY, TWEAK, credit_card_number)
+        assert len(ciphertext) == len(credit_card_number)
+        assert ciphertext[:7] == credit_card_number[:7]
+        assert ciphertext[-4:-1] == credit_card_number[-4:-1]
+        assert credit_card.decrypt(KEY, TWEAK, ciphertext) == credit_card_number
+
+        with self.assertRaises(Exception):
+            # invalid Luhn check digit
+            credit_card.encrypt(KEY, TWEAK, '4532-0151-1283-0367')
+
     def test_chinese_text(self) -> None:
         """
         FPE on chinese text
@@ -46,6 +73,31 @@ class TestFpe(unittest.TestCase):
             assert len(chinese_text) == len(ciphertext)
             assert cleartext == chinese_text
 
+    def test_cjk_text(self) -> None:
+        """
+        FPE on japanese and korean text
+        """
+        for alphabet_id, texts in [
+            ('hiragana', ['やまだ たろう', 'すずき はなこ']),
+            ('katakana', ['ヤマダ タロウ', 'スズキ ハナコ']),
+            ('japanese', ['山田 太郎', '鈴木 はなこ']),
+            ('hangul', ['김민준', '이서연 박지호']),
+        ]:
+            alphabet = Alphabet(alphabet_id)
+            for text in texts:
+                ciphertext = alphabet.encrypt(KEY, TWEAK, text)
+                cleartext = alphabet.decrypt(KEY, TWEAK, ciphertext)
+                assert len(text) == len(ciphertext)
+                assert cleartext == text
+
+        # Hiragana and Katakana
+        alphabet = Alphabet.from_unicode_ranges([(0x3041, 0x3096), (0x30A1, 0x30FA)])
+        ciphertext = alphabet.encrypt(KEY, TWEAK, 'やまだ タロウ')
+        assert alphabet.decrypt(KEY, TWEAK, ciphertext) == 'やまだ タロウ'
+
+        with self.assertRaises(Exception):
+            Alphabet.from_unicode_ranges([(0x39, 0x30)])
+
     def test_utf_text(self) -> None:
         """
         FPE on utf text
@@ -80,6 +132,76 @@ class TestFpe(unittest.TestCase):
             assert len(custom_alphabet_text) == len(ciphertext)
             assert cleartext == custom_alphabet_text
 
+    def test_alphabet_composition(self) -> None:
+        """
+        Alphabets are composed from the predefined ones
+        """
+        alphabet = Alphabet('alpha_numeric')
+        alphabet.extend_with_alphabet(Alphabet.from_unicode_ranges([(0x40, 0x40)]))
+        assert alphabet.validate('john.doe@example.com') == {'.'}
+
+        alphabet = alphabet.without('0O1lI')
+        assert alphabet.validate('O0') == {'O', '0'}
+        assert Alphabet('alpha_numeric+latin1sup_alphanum-0O1lI').validate('é0') == {'0'}
+
+        ciphertext = alphabet.encrypt(KEY, TWEAK, 'john.doe@example.com')
+        assert alphabet.decrypt(KEY, TWEAK, ciphertext) == 'john.doe@example.com'
+
+        with self.assertRaises(Exception):
+            Alphabet('numeric').without('012345678')
+
+    def test_alphabet_validation(self) -> None:
+        """
+        Characters not in the alphabet are reported
+        """
+        alphabet = Alphabet('numeric')
+        assert alphabet.validate('1234123412341234') == set()
+        assert alphabet.validate('1234-1234 1234-123a') == {'-', ' ', 'a'}
+
+        with self.assertRaisesRegex(Exception, "'a' at position 4"):
+            alphabet.encrypt(KEY, TWEAK, '12-3a b')
+
+    def test_minimum_plaintext_length(self) -> None:
+        """
+        Inputs shorter than the minimum length are rejected
+        """
+        alphabet = Alphabet('numeric')
+        self.assertEqual(alphabet.minimum_plaintext_length(), 6)
+        alphabet.extend_with('abcdef')
+        self.assertEqual(alphabet.minimum_plaintext_length(), 5)
+
+        with self.assertRaises(InputTooShort) as context:
+            alphabet.encrypt(KEY, TWEAK, '1234')
+        assert context.exception.min == 5
+        alphabet.encrypt(KEY, TWEAK, '12345')
+
+    def test_pseudonymize_preview(self) -> None:
+        """
+        Deterministic fake values
+        """
+        alphabet = Alphabet('numeric')
+        ssn = alphabet.pseudonymize_preview('###-##-####', b'row 1')
+        self.assertRegex(ssn, r'^\d{3}-\d{2}-\d{4}$')
+        self.assertEqual(ssn, alphabet.pseudonymize_preview('###-##-####', b'row 1'))
+        self.assertNotEqual(ssn, alphabet.pseudonymize_preview('###-##-####', b'row 2'))
+
+    def test_series(self) -> None:
+        """
+        FPE on a whole column at once
+        """
+        alphabet = Alphabet('numeric')
+        column = [f'1234-5678-9012-{i:04}' for i in range(1000)]
+
+        ciphertexts = alphabet.encrypt_series(KEY, TWEAK, column)
+        assert len(ciphertexts) == len(column)
+        for plaintext, ciphertext in zip(column, ciphertexts):
+            assert ciphertext == alphabet.encrypt(KEY, TWEAK, plaintext)
+
+        assert alphabet.decrypt_series(KEY, TWEAK, ciphertexts) == column
+
+        with self.assertRaisesRegex(Exception, "'a' at position 4"):
+            alphabet.encrypt_series(KEY, TWEAK, ['1234', '12-3a b'])
+
     def test_numbers(self) -> None:
         """
         FPE on numbers.
@@ -112,6 +234,20 @@ class TestFpe(unittest.TestCase):
             cleartext = big_int.decrypt_big(KEY, TWEAK, ciphertext)
             assert cleartext == my_big_integer
 
+    def test_fixed_width_numbers(self) -> None:
+        """
+        FPE on numeric strings with leading zeros
+        """
+        zip_code = Integer(10, 6)
+        for my_zip_code in ['000000', '000123', '012345']:
+            ciphertext = zip_code.encrypt_fixed_width(KEY, TWEAK, my_zip_code)
+            cleartext = zip_code.decrypt_fixed_width(KEY, TWEAK, ciphertext)
+            assert len(ciphertext) == len(my_zip_code)
+            assert cleartext == my_zip_code
+
+        with self.assertRaises(Exception):
+            zip_code.encrypt_fixed_width(KEY, TWEAK, '00123')
+
     def test_floats(self) -> None:
         """
         FPE on floats
@@ -127,6 +263,51 @@ class TestFpe(unittest.TestCase):
             cleartext = flt.decrypt(KEY, TWEAK, ciphertext)
             assert cleartext == my_float
 
+    def test_decimals(self) -> None:
+        """
+        FPE on decimals preserving the sign and the scale
+        """
+        decimal = Decimal(9, 2)
+
+        for amount in ['0.00', '1234.56', '-1234.56', '-999999999.99']:
+            ciphertext = decimal.encrypt_str(KEY, TWEAK, amount)
+            assert ciphertext.startswith('-') == amount.startswith('-')
+            assert len(ciphertext.split('.')[1]) == 2
+            assert decimal.decrypt_str(KEY, TWEAK, ciphertext) == amount
+
+        # scaled integers
+        ciphertext = decimal.encrypt(KEY, TWEAK, -123456)
+        assert ciphertext < 0
+        assert decimal.decrypt(KEY, TWEAK, ciphertext) == -123456
+
+        with self.assertRaises(Exception):
+            # too many fraction digits
+            decimal.encrypt_str(KEY, TWEAK, '1.234')
+
+    def test_error_kinds(self) -> None:
+        """
+        Errors can be told apart without parsing their message
+        """
+        alphabet = Alphabet('numeric')
+
+        with self.assertRaises(KeyLengthError) as context:
+            alphabet.encrypt(KEY[:16], TWEAK, '1234567890')
+        assert context.exception.length == 16
+        assert context.exception.expected == KEY_LENGTH
+
+        with self.assertRaises(InputTooShort) as context:
+            alphabet.encrypt(KEY, TWEAK, '12-3a b')
+        assert context.exception.length == 3
+        assert context.exception.min == 6
+
+        with self.assertRaises(AlphabetMismatch) as context:
+            Integer(10, 6).encrypt_fixed_width(KEY, TWEAK, '0a-b12')
+        assert context.exception.chars == ['-', 'a', 'b']
+
+        # All the errors derive from `FpeError`.
+        with self.assertRaises(FpeError):
+            CreditCard().encrypt(KEY, TWEAK, '4532015112830367')
+
 
 if __name__ == '__main__':
     unittest.main()
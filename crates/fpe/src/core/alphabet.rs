@@ -1,4 +1,7 @@
-use std::{collections::HashMap, fmt::Display};
+use std::{
+    collections::{BTreeSet, HashMap},
+    fmt::Display,
+};
 
 use aes::Aes256;
 use cosmian_fpe::ff1::{FF1h, FlexibleNumeralString};
@@ -10,6 +13,10 @@ use crate::{ano_ensure, core::KEY_LENGTH};
 /// The recommended threshold according to NIST standards
 pub const RECOMMENDED_THRESHOLD: usize = 1_000_000;
 
+/// Placeholder of the characters generated by
+/// [`Alphabet::pseudonymize_preview`] in a format template.
+pub const PREVIEW_PLACEHOLDER: char = '#';
+
 /// Calculates the minimum length of the plaintext for FPE to be secure.
 pub fn min_plaintext_length(alphabet_len: usize) -> usize {
     ((RECOMMENDED_THRESHOLD as f32).log(alphabet_len as f32)).ceil() as usize
@@ -30,11 +37,17 @@ pub fn min_plaintext_length(alphabet_len: usize) -> usize {
 ///  - `Alphabet::hexa_decimal()`
 ///  - `Alphabet::alpha_numeric()`
 ///  - `Alphabet::chinese()`
+///  - `Alphabet::hiragana()`
+///  - `Alphabet::katakana()`
+///  - `Alphabet::japanese()`
+///  - `Alphabet::hangul()`
 ///  - `Alphabet::latin1sup()`
 ///  - `Alphabet::latin1sup_alphanum()`
 ///
 /// To build your own, for example the hexadecimal alphabet,
-/// use `Alphabet::try_from("0123456789abcdef").unwrap()`
+/// use `Alphabet::try_from("0123456789abcdef").unwrap()`, or
+/// `Alphabet::from_unicode_ranges(&[(0x30, 0x39), (0x61, 0x66)])` to build it
+/// from ranges of Unicode code points.
 ///
 /// See the `encrypt()` and `decrypt()` methods for usage
 #[derive(Debug, Clone)]
@@ -110,6 +123,51 @@ impl Alphabet {
         Self::try_from(alphabet)
     }
 
+    /// Creates an `Alphabet` from inclusive ranges of Unicode code points.
+    ///
+    /// Code points which are not valid characters (surrogates) are skipped.
+    /// Overlapping ranges are allowed.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cloudproof_fpe::core::Alphabet;
+    ///
+    /// // Hiragana and Katakana
+    /// let alphabet = Alphabet::from_unicode_ranges(&[(0x3041, 0x3096), (0x30A1, 0x30FA)]).unwrap();
+    /// assert_eq!(alphabet.alphabet_len(), 176);
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if a range is empty or if the resulting alphabet does
+    /// not contain between 2 and 2^16 characters.
+    pub fn from_unicode_ranges(ranges: &[(u32, u32)]) -> Result<Self, AnoError> {
+        if let Some((start, end)) = ranges.iter().find(|(start, end)| start > end) {
+            return Err(AnoError::FPE(format!(
+                "Invalid Unicode range: start {start:#X} is greater than end {end:#X}"
+            )));
+        }
+        let chars = ranges
+            .iter()
+            .flat_map(|&(start, end)| start..=end)
+            .filter_map(char::from_u32)
+            .sorted()
+            .unique()
+            .collect_vec();
+        if chars.len() < 2 || chars.len() >= 1 << 16 {
+            return Err(AnoError::FPE(format!(
+                "Alphabet must contain between 2 and 2^16 characters. This alphabet contains {} \
+                 characters",
+                chars.len()
+            )));
+        }
+        Ok(Self {
+            min_text_length: min_plaintext_length(chars.len()),
+            chars,
+        })
+    }
+
     fn extend_(&mut self, additional_characters: Vec<char>) {
         self.chars.extend(additional_characters);
         // Sort the characters and remove duplicates
@@ -147,6 +205,58 @@ impl Alphabet {
         self.extend_(additional_characters.chars().collect::<Vec<_>>());
     }
 
+    /// Extends the alphabet with the characters of another alphabet.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cloudproof_fpe::core::Alphabet;
+    ///
+    /// let mut alphabet = Alphabet::alpha_lower();
+    /// alphabet.extend_with_alphabet(&Alphabet::numeric());
+    /// assert_eq!(alphabet.alphabet_len(), 36);
+    /// ```
+    pub fn extend_with_alphabet(&mut self, other: &Self) {
+        self.extend_(other.chars.clone());
+    }
+
+    /// Returns a copy of the alphabet without the given characters, e.g. to
+    /// exclude the characters which are easily confused.
+    ///
+    /// Characters which are not part of the alphabet are ignored.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cloudproof_fpe::core::Alphabet;
+    ///
+    /// let alphabet = Alphabet::alpha_numeric().without("0O1l").unwrap();
+    /// assert_eq!(alphabet.alphabet_len(), 58);
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if less than 2 characters remain.
+    pub fn without(&self, characters: &str) -> Result<Self, AnoError> {
+        let chars = self
+            .chars
+            .iter()
+            .filter(|c| !characters.contains(**c))
+            .copied()
+            .collect_vec();
+        if chars.len() < 2 {
+            return Err(AnoError::FPE(format!(
+                "Alphabet must contain between 2 and 2^16 characters. This alphabet contains {} \
+                 characters",
+                chars.len()
+            )));
+        }
+        Ok(Self {
+            min_text_length: min_plaintext_length(chars.len()),
+            chars,
+        })
+    }
+
     /// Returns the number of characters in the alphabet.
     #[must_use]
     pub fn alphabet_len(&self) -> usize {
@@ -189,6 +299,29 @@ impl Alphabet {
         Some(self.chars[pos])
     }
 
+    /// Returns the set of characters of the given input that are not part of
+    /// the alphabet.
+    ///
+    /// These characters are not encrypted and are left in place in the
+    /// ciphertext. An empty set means the whole input is covered by the
+    /// alphabet.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cloudproof_fpe::core::Alphabet;
+    ///
+    /// let missing = Alphabet::numeric().validate("1234-5678 9");
+    /// assert_eq!(missing.into_iter().collect::<String>(), " -");
+    /// ```
+    #[must_use]
+    pub fn validate(&self, input: &str) -> BTreeSet<char> {
+        input
+            .chars()
+            .filter(|c| self.char_to_position(*c).is_none())
+            .collect()
+    }
+
     /// Creates a `RebasedString` from a `&str` by replacing every character in
     /// the input with the corresponding index in the `alphabet_chars`
     /// slice. Non-alphabet characters are stored as separate `u16` values
@@ -239,6 +372,23 @@ impl Alphabet {
         Ok(result.into_iter().collect::<String>())
     }
 
+    /// Builds an FPE error from the given message, listing the characters of
+    /// the input that are not part of the alphabet along with their positions.
+    fn fpe_error(&self, message: String, non_alphabet_chars: &HashMap<usize, char>) -> AnoError {
+        if non_alphabet_chars.is_empty() {
+            return AnoError::FPE(message);
+        }
+        let details = non_alphabet_chars
+            .iter()
+            .sorted()
+            .map(|(position, c)| format!("{c:?} at position {position}"))
+            .join(", ");
+        AnoError::FPE(format!(
+            "{message}. The input contains {} character(s) not in the alphabet: {details}",
+            non_alphabet_chars.len()
+        ))
+    }
+
     /// Encrypts the plaintext using the given `key` and `tweak` using
     /// Format-Preserving Encryption (FPE).
     ///
@@ -258,30 +408,34 @@ impl Alphabet {
     ///
     /// # Errors
     ///
-    /// Returns an error if the plaintext contains characters not in the
-    /// alphabet, or if the encryption fails.
+    /// Returns an error if the key does not have the expected length, if the
+    /// plaintext has too few characters of the alphabet, or if the encryption
+    /// fails.
     pub fn encrypt(&self, key: &[u8], tweak: &[u8], plaintext: &str) -> Result<String, AnoError> {
         let (stripped_input, non_alphabet_chars) = self.rebase(plaintext);
 
         // Ensure the stripped input length meets the minimum security threshold
         ano_ensure!(
             stripped_input.len() >= self.minimum_plaintext_length(),
-            "The stripped input length of {} is too short. It should be at least {} given the \
-             alphabet length of {}.",
-            stripped_input.len(),
-            self.minimum_plaintext_length(),
-            self.alphabet_len()
+            AnoError::InputTooShort {
+                length: stripped_input.len(),
+                min: self.minimum_plaintext_length(),
+                non_alphabet_chars: non_alphabet_chars.into_iter().sorted().collect(),
+            }
         );
 
-        if key.len() != KEY_LENGTH {
-            return Err(AnoError::KeySize(key.len(), KEY_LENGTH));
-        }
+        ano_ensure!(
+            key.len() == KEY_LENGTH,
+            AnoError::KeySize(key.len(), KEY_LENGTH)
+        );
 
         let fpe_ff = FF1h::<Aes256>::new(key, self.alphabet_len() as u32)
             .map_err(|e| AnoError::FPE(format!("failed instantiating FF1: {e}")))?;
         let ciphertext_ns = fpe_ff
             .encrypt(tweak, &FlexibleNumeralString::from(stripped_input))
-            .map_err(|e| AnoError::FPE(format!("FF1 encryption failed: {e}")))?;
+            .map_err(|e| {
+                self.fpe_error(format!("FF1 encryption failed: {e}"), &non_alphabet_chars)
+            })?;
 
         // Get ciphertext as u32-vector
         let ciphertext = Vec::<u16>::from(ciphertext_ns);
@@ -308,16 +462,23 @@ impl Alphabet {
     ///
     /// # Errors
     ///
-    /// Returns an error if the ciphertext contains characters not in the
-    /// alphabet, or if the decryption fails.
+    /// Returns an error if the key does not have the expected length, or if
+    /// the decryption fails.
     pub fn decrypt(&self, key: &[u8], tweak: &[u8], ciphertext: &str) -> Result<String, AnoError> {
         let (stripped_input, non_alphabet_chars) = self.rebase(ciphertext);
 
+        ano_ensure!(
+            key.len() == KEY_LENGTH,
+            AnoError::KeySize(key.len(), KEY_LENGTH)
+        );
+
         let fpe_ff = FF1h::<Aes256>::new(key, self.alphabet_len() as u32)
             .map_err(|e| AnoError::FPE(format!("failed instantiating FF1: {e}")))?;
         let plaintext_ns = fpe_ff
             .decrypt(tweak, &FlexibleNumeralString::from(stripped_input))
-            .map_err(|e| AnoError::FPE(format!("FF1 decryption failed: {e}")))?;
+            .map_err(|e| {
+                self.fpe_error(format!("FF1 decryption failed: {e}"), &non_alphabet_chars)
+            })?;
 
         // Get plaintext as u32-vector
         let plaintext = Vec::<u16>::from(plaintext_ns);
@@ -326,6 +487,60 @@ impl Alphabet {
     }
 }
 
+impl Alphabet {
+    /// Generates a fake value following the given format template, for
+    /// testing purposes.
+    ///
+    /// Each [`PREVIEW_PLACEHOLDER`] of the template is replaced by a character
+    /// of the alphabet, the other characters are kept. The generated value
+    /// only depends on the template and on the given seed (e.g. a row number):
+    /// no key is involved, so the values can be used to populate test
+    /// environments without leaking anything about the production data.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cloudproof_fpe::core::Alphabet;
+    ///
+    /// let alphabet = Alphabet::numeric();
+    /// let ssn = alphabet.pseudonymize_preview("###-##-####", b"row 1").unwrap();
+    /// assert_eq!(ssn.len(), 11);
+    /// assert_eq!(ssn, alphabet.pseudonymize_preview("###-##-####", b"row 1").unwrap());
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the generation fails.
+    pub fn pseudonymize_preview(&self, template: &str, seed: &[u8]) -> Result<String, AnoError> {
+        let n_placeholders = template
+            .chars()
+            .filter(|c| *c == PREVIEW_PLACEHOLDER)
+            .count();
+        // The values are the FF1 encryption under a public key of a string of
+        // zeros, tweaked by the seed. This string should be long enough for
+        // FF1 to accept it.
+        let length = n_placeholders.max(self.minimum_plaintext_length());
+        let fpe_ff = FF1h::<Aes256>::new(&[0; KEY_LENGTH], self.alphabet_len() as u32)
+            .map_err(|e| AnoError::FPE(format!("failed instantiating FF1: {e}")))?;
+        let positions = fpe_ff
+            .encrypt(seed, &FlexibleNumeralString::from(vec![0; length]))
+            .map_err(|e| AnoError::FPE(format!("FF1 generation failed: {e}")))?;
+        let mut positions = Vec::<u16>::from(positions).into_iter();
+        template
+            .chars()
+            .map(|c| {
+                if c != PREVIEW_PLACEHOLDER {
+                    return Ok(c);
+                }
+                positions
+                    .next()
+                    .and_then(|position| self.char_from_position(position))
+                    .ok_or_else(|| AnoError::FPE("failed generating a character".to_string()))
+            })
+            .collect()
+    }
+}
+
 impl Display for Alphabet {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         f.write_fmt(format_args!("{}", self.chars.iter().collect::<String>()))
@@ -381,6 +596,56 @@ impl Alphabet {
         }
     }
 
+    /// Creates an Alphabet with the Hiragana characters
+    pub fn hiragana() -> Self {
+        let chars = (0x3041..=0x3096_u32)
+            .filter_map(char::from_u32)
+            .collect::<Vec<char>>();
+        Self {
+            min_text_length: min_plaintext_length(chars.len()),
+            chars,
+        }
+    }
+
+    /// Creates an Alphabet with the Katakana characters, including the
+    /// prolonged sound mark
+    pub fn katakana() -> Self {
+        let chars = (0x30A1..=0x30FA_u32)
+            .chain([0x30FC])
+            .filter_map(char::from_u32)
+            .collect::<Vec<char>>();
+        Self {
+            min_text_length: min_plaintext_length(chars.len()),
+            chars,
+        }
+    }
+
+    /// Creates an Alphabet with the Hiragana, Katakana and Kanji (CJK unified
+    /// ideographs) characters
+    pub fn japanese() -> Self {
+        let chars = (0x3041..=0x3096_u32)
+            .chain(0x30A1..=0x30FA)
+            .chain([0x30FC])
+            .chain(0x4E00..=0x9FFF)
+            .filter_map(char::from_u32)
+            .collect::<Vec<char>>();
+        Self {
+            min_text_length: min_plaintext_length(chars.len()),
+            chars,
+        }
+    }
+
+    /// Creates an Alphabet with the Hangul syllables (supports Korean)
+    pub fn hangul() -> Self {
+        let chars = (0xAC00..=0xD7A3_u32)
+            .filter_map(char::from_u32)
+            .collect::<Vec<char>>();
+        Self {
+            min_text_length: min_plaintext_length(chars.len()),
+            chars,
+        }
+    }
+
     /// Creates an Alphabet with the latin-1 and latin1-supplement characters
     /// (supports French)
     pub fn latin1sup() -> Self {
@@ -0,0 +1,209 @@
+use itertools::Itertools;
+
+use crate::core::{Alphabet, AnoError};
+
+/// Number of leading digits kept in clear by default: the Bank Identification
+/// Number (BIN).
+pub const DEFAULT_KEEP_FIRST: usize = 6;
+
+/// Number of trailing digits kept in clear by default, check digit included.
+pub const DEFAULT_KEEP_LAST: usize = 4;
+
+/// Computes the Luhn check digit of the given digits.
+fn luhn_check_digit(digits: &[u8]) -> u8 {
+    let sum: u32 = digits
+        .iter()
+        .rev()
+        .enumerate()
+        .map(|(i, &d)| {
+            let d = u32::from(d);
+            if i % 2 == 0 {
+                let double = 2 * d;
+                if double > 9 {
+                    double - 9
+                } else {
+                    double
+                }
+            } else {
+                d
+            }
+        })
+        .sum();
+    ((10 - sum % 10) % 10) as u8
+}
+
+/// Returns `true` if the given card number has a valid Luhn check digit.
+/// Spaces and dashes are ignored.
+#[must_use]
+pub fn is_luhn_valid(card_number: &str) -> bool {
+    let digits = card_number
+        .chars()
+        .filter(|c| *c != ' ' && *c != '-')
+        .map(|c| c.to_digit(10).map(|d| d as u8))
+        .collect::<Option<Vec<_>>>();
+    match digits.as_deref() {
+        Some([payload @ .., check_digit]) if !payload.is_empty() => {
+            luhn_check_digit(payload) == *check_digit
+        }
+        _ => false,
+    }
+}
+
+/// Format-preserving encryption of credit card numbers.
+///
+/// The first `keep_first` digits (the BIN) and the last `keep_last` digits are
+/// left in clear while the middle digits are encrypted. The last digit is the
+/// Luhn check digit: it is recomputed so that the ciphertext is a valid card
+/// number too. Spaces and dashes are left in place.
+///
+/// At least 6 digits must remain to be encrypted: card numbers with 15 digits
+/// or less require keeping fewer digits in clear than the defaults.
+///
+/// # Example
+///
+/// ```
+/// use cloudproof_fpe::core::{is_luhn_valid, CreditCard};
+///
+/// let credit_card = CreditCard::default();
+/// let key = [0u8; 32];
+/// let tweak = b"tweak";
+///
+/// let encrypted = credit_card.encrypt(&key, tweak, "4532 0151 1283 0366").unwrap();
+/// assert!(encrypted.starts_with("4532 01"));
+/// assert_eq!("036", &encrypted[15..18]);
+/// assert!(is_luhn_valid(&encrypted));
+///
+/// let decrypted = credit_card.decrypt(&key, tweak, &encrypted).unwrap();
+/// assert_eq!("4532 0151 1283 0366", decrypted);
+/// ```
+#[derive(Debug, Clone)]
+pub struct CreditCard {
+    keep_first: usize,
+    keep_last: usize,
+    numeric_alphabet: Alphabet,
+}
+
+impl Default for CreditCard {
+    fn default() -> Self {
+        Self {
+            keep_first: DEFAULT_KEEP_FIRST,
+            keep_last: DEFAULT_KEEP_LAST,
+            numeric_alphabet: Alphabet::numeric(),
+        }
+    }
+}
+
+impl CreditCard {
+    /// Creates a new `CreditCard` keeping in clear the given number of leading
+    /// and trailing digits.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if `keep_last` is 0: the check digit cannot be
+    /// encrypted.
+    pub fn instantiate(keep_first: usize, keep_last: usize) -> Result<Self, AnoError> {
+        if keep_last == 0 {
+            return Err(AnoError::FPE(
+                "at least the check digit should be kept at the end of the card number".to_string(),
+            ));
+        }
+        Ok(Self {
+            keep_first,
+            keep_last,
+            numeric_alphabet: Alphabet::numeric(),
+        })
+    }
+
+    /// Encrypts the middle digits of the given card number and recomputes its
+    /// check digit.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the card number is not a valid Luhn number or if
+    /// it has too few middle digits to be securely encrypted.
+    pub fn encrypt(
+        &self,
+        key: &[u8; 32],
+        tweak: &[u8],
+        card_number: &str,
+    ) -> Result<String, AnoError> {
+        self.transform(card_number, |middle| {
+            self.numeric_alphabet.encrypt(key, tweak, middle)
+        })
+    }
+
+    /// Decrypts the middle digits of the given card number and recomputes its
+    /// check digit.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the card number is not a valid Luhn number or if
+    /// the decryption fails.
+    pub fn decrypt(
+        &self,
+        key: &[u8; 32],
+        tweak: &[u8],
+        card_number: &str,
+    ) -> Result<String, AnoError> {
+        self.transform(card_number, |middle| {
+            self.numeric_alphabet.decrypt(key, tweak, middle)
+        })
+    }
+
+    /// Applies `f` to the middle digits of the given card number and
+    /// recomputes its check digit, leaving the separators in place.
+    fn transform(
+        &self,
+        card_number: &str,
+        f: impl FnOnce(&str) -> Result<String, AnoError>,
+    ) -> Result<String, AnoError> {
+        // Only digits, spaces and dashes are allowed.
+        let invalid_chars = card_number
+            .chars()
+            .filter(|c| !c.is_ascii_digit() && *c != ' ' && *c != '-')
+            .sorted()
+            .dedup()
+            .collect::<Vec<_>>();
+        if !invalid_chars.is_empty() {
+            return Err(AnoError::AlphabetMismatch(invalid_chars));
+        }
+        if !is_luhn_valid(card_number) {
+            return Err(AnoError::FPE(
+                "the card number has an invalid Luhn check digit".to_string(),
+            ));
+        }
+
+        let digits = card_number
+            .chars()
+            .filter(char::is_ascii_digit)
+            .collect::<String>();
+        if digits.len() <= self.keep_first + self.keep_last {
+            return Err(AnoError::FPE(format!(
+                "the card number has {} digits, it should have more than the {} digits kept in \
+                 clear",
+                digits.len(),
+                self.keep_first + self.keep_last
+            )));
+        }
+
+        let middle_end = digits.len() - self.keep_last;
+        let mut output = digits[..self.keep_first].to_string();
+        output.push_str(&f(&digits[self.keep_first..middle_end])?);
+        output.push_str(&digits[middle_end..digits.len() - 1]);
+        let payload = output.bytes().map(|b| b - b'0').collect::<Vec<_>>();
+        output.push(char::from(b'0' + luhn_check_digit(&payload)));
+
+        // Re-insert the separators.
+        let mut output_digits = output.chars();
+        Ok(card_number
+            .chars()
+            .map(|c| {
+                if c.is_ascii_digit() {
+                    output_digits.next().unwrap_or(c)
+                } else {
+                    c
+                }
+            })
+            .collect())
+    }
+}
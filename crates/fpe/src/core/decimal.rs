@@ -0,0 +1,240 @@
+use num_bigint::BigUint;
+use num_traits::{ToPrimitive, Zero};
+
+use crate::core::{AnoError, Integer};
+
+/// Maximum number of digits of a `Decimal`: the scaled value of the greatest
+/// decimal must fit in an `i128`.
+pub const MAX_DECIMAL_DIGITS: usize = 38;
+
+/// Format-preserving encryption of decimal numbers with a fixed number of
+/// integer and fraction digits, such as money amounts.
+///
+/// The absolute value is encrypted as an integer of `integer_digits +
+/// fraction_digits` decimal digits while the sign is kept: the ciphertext of a
+/// positive amount is a positive amount with the same scale. Zero has no sign
+/// and is encrypted as a positive value.
+///
+/// Values can be given either as strings (e.g. `"-1234.56"`) or as `i128`
+/// scaled by `10^fraction_digits` (e.g. `-123_456` for `-1234.56` with 2
+/// fraction digits).
+///
+/// # Example
+///
+/// ```
+/// use cloudproof_fpe::core::Decimal;
+///
+/// let decimal = Decimal::instantiate(9, 2).unwrap();
+/// let key = [0u8; 32];
+/// let tweak = b"tweak";
+///
+/// let encrypted = decimal.encrypt_str(&key, tweak, "-1234.5").unwrap();
+/// assert!(encrypted.starts_with('-'));
+/// assert_eq!(Some(2), encrypted.split_once('.').map(|(_, f)| f.len()));
+///
+/// let decrypted = decimal.decrypt_str(&key, tweak, &encrypted).unwrap();
+/// assert_eq!("-1234.50", decrypted);
+/// ```
+pub struct Decimal {
+    integer_digits: usize,
+    fraction_digits: usize,
+    number: Integer,
+}
+
+impl Decimal {
+    /// Creates a new `Decimal` with the given number of integer and fraction
+    /// digits.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if there are less than 6 or more than
+    /// [`MAX_DECIMAL_DIGITS`] digits in total.
+    pub fn instantiate(integer_digits: usize, fraction_digits: usize) -> Result<Self, AnoError> {
+        let digits = integer_digits + fraction_digits;
+        if digits > MAX_DECIMAL_DIGITS {
+            return Err(AnoError::FPE(format!(
+                "the total number of digits of a decimal must be at most {MAX_DECIMAL_DIGITS}, \
+                 got {digits}"
+            )));
+        }
+        Ok(Self {
+            integer_digits,
+            fraction_digits,
+            number: Integer::instantiate(10, digits)?,
+        })
+    }
+
+    /// Encrypts the given decimal value scaled by `10^fraction_digits`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the absolute value has more than `integer_digits`
+    /// integer digits.
+    pub fn encrypt(&self, key: &[u8; 32], tweak: &[u8], value: i128) -> Result<i128, AnoError> {
+        self.permute(value, |magnitude| {
+            self.number.encrypt_big(key, tweak, magnitude)
+        })
+    }
+
+    /// Decrypts the given decimal value scaled by `10^fraction_digits`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the absolute value has more than `integer_digits`
+    /// integer digits.
+    pub fn decrypt(
+        &self,
+        key: &[u8; 32],
+        tweak: &[u8],
+        ciphertext: i128,
+    ) -> Result<i128, AnoError> {
+        self.permute(ciphertext, |magnitude| {
+            self.number.decrypt_big(key, tweak, magnitude)
+        })
+    }
+
+    /// Encrypts the given decimal string. The ciphertext always has
+    /// `fraction_digits` fraction digits.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the value is not a decimal number with at most
+    /// `integer_digits` integer digits and `fraction_digits` fraction digits.
+    pub fn encrypt_str(
+        &self,
+        key: &[u8; 32],
+        tweak: &[u8],
+        value: &str,
+    ) -> Result<String, AnoError> {
+        let ciphertext = self.encrypt(key, tweak, self.parse(value)?)?;
+        Ok(self.format(ciphertext))
+    }
+
+    /// Decrypts the given decimal string. The plaintext always has
+    /// `fraction_digits` fraction digits.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the ciphertext is not a decimal number with at
+    /// most `integer_digits` integer digits and `fraction_digits` fraction
+    /// digits.
+    pub fn decrypt_str(
+        &self,
+        key: &[u8; 32],
+        tweak: &[u8],
+        ciphertext: &str,
+    ) -> Result<String, AnoError> {
+        let plaintext = self.decrypt(key, tweak, self.parse(ciphertext)?)?;
+        Ok(self.format(plaintext))
+    }
+
+    /// Applies the given permutation of the absolute values to the given
+    /// value, keeping its sign.
+    fn permute(
+        &self,
+        value: i128,
+        permutation: impl Fn(&BigUint) -> Result<BigUint, AnoError>,
+    ) -> Result<i128, AnoError> {
+        let max_value = self.number.max_value();
+        let magnitude = BigUint::from(value.unsigned_abs());
+        if magnitude > max_value {
+            return Err(AnoError::FPE(format!(
+                "the absolute value of the scaled decimal: {value} must be lower or equal to \
+                 {max_value}"
+            )));
+        }
+        let mut permuted = permutation(&magnitude)?;
+        // Negative values cannot be mapped to 0 without losing their sign:
+        // cycle walk until a non-zero value is reached. Since only one value
+        // is excluded, the walk takes at most two steps and stays a
+        // permutation of the non-zero values.
+        while value < 0 && permuted.is_zero() {
+            permuted = permutation(&permuted)?;
+        }
+        let permuted = permuted.to_i128().ok_or_else(|| {
+            AnoError::FPE(format!(
+                "failed converting the value: {permuted}, to an i128"
+            ))
+        })?;
+        Ok(if value < 0 { -permuted } else { permuted })
+    }
+
+    /// Parses the given decimal string into its value scaled by
+    /// `10^fraction_digits`.
+    fn parse(&self, value: &str) -> Result<i128, AnoError> {
+        let (negative, unsigned) = match value.strip_prefix('-') {
+            Some(unsigned) => (true, unsigned),
+            None => (false, value),
+        };
+        let (integer_part, fraction_part) = match unsigned.split_once('.') {
+            Some((integer_part, fraction_part)) if !fraction_part.is_empty() => {
+                (integer_part, fraction_part)
+            }
+            Some(_) => {
+                return Err(AnoError::FPE(format!(
+                    "the decimal: {value} has no digit after its decimal point"
+                )))
+            }
+            None => (unsigned, ""),
+        };
+        let is_numeric = |s: &str| s.chars().all(|c| c.is_ascii_digit());
+        if integer_part.is_empty() || !is_numeric(integer_part) || !is_numeric(fraction_part) {
+            return Err(AnoError::FPE(format!(
+                "the value: {value} is not a decimal number"
+            )));
+        }
+        let integer_part = integer_part.trim_start_matches('0');
+        if integer_part.len() > self.integer_digits {
+            return Err(AnoError::FPE(format!(
+                "the decimal: {value} must have at most {} integer digits",
+                self.integer_digits
+            )));
+        }
+        if fraction_part.len() > self.fraction_digits {
+            return Err(AnoError::FPE(format!(
+                "the decimal: {value} must have at most {} fraction digits",
+                self.fraction_digits
+            )));
+        }
+        let fraction_digits = self.fraction_digits;
+        let digits = format!("{integer_part}{fraction_part:0<fraction_digits$}");
+        let magnitude = if digits.is_empty() {
+            0
+        } else {
+            // The number of digits is bounded by `MAX_DECIMAL_DIGITS`.
+            digits
+                .parse::<i128>()
+                .map_err(|e| AnoError::FPE(format!("failed parsing the decimal: {value}: {e}")))?
+        };
+        Ok(if negative { -magnitude } else { magnitude })
+    }
+
+    /// Formats the given value scaled by `10^fraction_digits` with exactly
+    /// `fraction_digits` fraction digits.
+    fn format(&self, value: i128) -> String {
+        let sign = if value < 0 { "-" } else { "" };
+        let magnitude = value.unsigned_abs();
+        if self.fraction_digits == 0 {
+            return format!("{sign}{magnitude}");
+        }
+        let scale = 10_u128.pow(self.fraction_digits as u32);
+        let fraction_digits = self.fraction_digits;
+        format!(
+            "{sign}{}.{:0>fraction_digits$}",
+            magnitude / scale,
+            magnitude % scale
+        )
+    }
+
+    /// The number of integer digits
+    #[must_use]
+    pub fn integer_digits(&self) -> usize {
+        self.integer_digits
+    }
+
+    /// The number of fraction digits
+    #[must_use]
+    pub fn fraction_digits(&self) -> usize {
+        self.fraction_digits
+    }
+}
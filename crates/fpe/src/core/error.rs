@@ -4,7 +4,18 @@ use core::fmt::Display;
 pub enum AnoError {
     Generic(String),
     FPE(String),
+    /// The key has the given length instead of the expected one.
     KeySize(usize, usize),
+    /// The input contains characters that are not part of the alphabet.
+    AlphabetMismatch(Vec<char>),
+    /// The input has fewer alphabet characters than the minimum required for
+    /// FPE to be secure. The characters that are not part of the alphabet are
+    /// given along with their position in the input.
+    InputTooShort {
+        length: usize,
+        min: usize,
+        non_alphabet_chars: Vec<(usize, char)>,
+    },
     ConversionError(String),
 }
 
@@ -22,6 +33,35 @@ impl Display for AnoError {
             Self::KeySize(given, expected) => {
                 write!(f, "Invalid key size {given}, expected: {expected}")
             }
+            Self::AlphabetMismatch(chars) => write!(
+                f,
+                "FPE error: the input contains characters that are not in the alphabet: {}",
+                chars.iter().map(|c| format!("{c:?}")).collect::<Vec<_>>().join(", ")
+            ),
+            Self::InputTooShort {
+                length,
+                min,
+                non_alphabet_chars,
+            } => {
+                write!(
+                    f,
+                    "FPE error: the stripped input length of {length} is too short, it should be \
+                     at least {min}"
+                )?;
+                if !non_alphabet_chars.is_empty() {
+                    let details = non_alphabet_chars
+                        .iter()
+                        .map(|(position, c)| format!("{c:?} at position {position}"))
+                        .collect::<Vec<_>>()
+                        .join(", ");
+                    write!(
+                        f,
+                        ". The input contains {} character(s) not in the alphabet: {details}",
+                        non_alphabet_chars.len()
+                    )?;
+                }
+                Ok(())
+            }
             Self::ConversionError(err) => write!(f, "Conversion error: {err}"),
         }
     }
@@ -258,6 +258,76 @@ impl Integer {
             .map_err(|e| AnoError::FPE(format!("failed generating the plaintext value {e}")))
     }
 
+    /// Encrypts the given fixed-width numeric string, preserving its leading
+    /// zeros: the ciphertext has the same number of digits as the plaintext.
+    ///
+    /// This is the way to encrypt zip codes or account ids, for which
+    /// `"000123"` and `"123"` are different values.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use cloudproof_fpe::core::Integer;
+    ///
+    /// let integer = Integer::instantiate(10, 6).unwrap();
+    /// let key = [0u8; 32];
+    /// let tweak = b"tweak";
+    ///
+    /// let encrypted = integer.encrypt_fixed_width(&key, tweak, "000123").unwrap();
+    /// assert_eq!(6, encrypted.len());
+    ///
+    /// let decrypted = integer.decrypt_fixed_width(&key, tweak, &encrypted).unwrap();
+    /// assert_eq!("000123", decrypted);
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if `value` is not made of exactly `digits` digits of
+    /// the radix.
+    pub fn encrypt_fixed_width(
+        &self,
+        key: &[u8; 32],
+        tweak: &[u8],
+        value: &str,
+    ) -> Result<String, AnoError> {
+        self.check_width(value)?;
+        self.numeric_alphabet.encrypt(key, tweak, value)
+    }
+
+    /// Decrypts the given fixed-width numeric string, preserving its leading
+    /// zeros: the plaintext has the same number of digits as the ciphertext.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if `ciphertext` is not made of exactly `digits` digits
+    /// of the radix.
+    pub fn decrypt_fixed_width(
+        &self,
+        key: &[u8; 32],
+        tweak: &[u8],
+        ciphertext: &str,
+    ) -> Result<String, AnoError> {
+        self.check_width(ciphertext)?;
+        self.numeric_alphabet.decrypt(key, tweak, ciphertext)
+    }
+
+    fn check_width(&self, value: &str) -> Result<(), AnoError> {
+        let invalid_chars = self.numeric_alphabet.validate(value);
+        if !invalid_chars.is_empty() {
+            return Err(AnoError::AlphabetMismatch(
+                invalid_chars.into_iter().collect(),
+            ));
+        }
+        let width = value.chars().count();
+        if width != self.digits {
+            return Err(AnoError::FPE(format!(
+                "the value: {value} must have exactly {} digits, got {width}",
+                self.digits
+            )));
+        }
+        Ok(())
+    }
+
     /// The maximum value supported by this Integer
     #[must_use]
     pub fn max_value(&self) -> BigUint {
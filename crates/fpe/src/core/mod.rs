@@ -1,5 +1,5 @@
 mod alphabet;
-pub use alphabet::Alphabet;
+pub use alphabet::{Alphabet, PREVIEW_PLACEHOLDER};
 
 mod integer;
 pub use integer::Integer;
@@ -7,6 +7,12 @@ pub use integer::Integer;
 mod float;
 pub use float::Float;
 
+mod decimal;
+pub use decimal::{Decimal, MAX_DECIMAL_DIGITS};
+
+mod credit_card;
+pub use credit_card::{is_luhn_valid, CreditCard, DEFAULT_KEEP_FIRST, DEFAULT_KEEP_LAST};
+
 mod error;
 pub use error::AnoError;
 
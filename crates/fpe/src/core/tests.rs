@@ -4,7 +4,9 @@ use rand::{thread_rng, Rng, RngCore, SeedableRng};
 use rand_chacha::ChaCha20Rng;
 use rand_distr::Alphanumeric;
 
-use crate::core::{error::AnoError, Alphabet, Float, Integer, KEY_LENGTH};
+use crate::core::{
+    error::AnoError, is_luhn_valid, Alphabet, CreditCard, Decimal, Float, Integer, KEY_LENGTH,
+};
 
 /// Generate a random key using a cryptographically
 /// secure random number generator that is suitable for use with FPE
@@ -223,6 +225,89 @@ fn fpe_ff1_names() -> Result<(), AnoError> {
     Ok(())
 }
 
+#[test]
+fn fpe_ff1_cjk() -> Result<(), AnoError> {
+    let alphabet = Alphabet::hiragana();
+    ["やまだ たろう", "すずき はなこ", "さとう-けんじ"]
+        .iter()
+        .for_each(|n| alphabet_check(n, &alphabet, " -"));
+
+    let alphabet = Alphabet::katakana();
+    ["ヤマダ タロウ", "スズキ ハナコ", "サトー-ケンジ"]
+        .iter()
+        .for_each(|n| alphabet_check(n, &alphabet, " -"));
+
+    let alphabet = Alphabet::japanese();
+    ["山田 太郎", "鈴木 はなこ", "佐藤-ケンジ"]
+        .iter()
+        .for_each(|n| alphabet_check(n, &alphabet, " -"));
+
+    let alphabet = Alphabet::hangul();
+    ["김민준", "이서연 박지호", "최-수아"]
+        .iter()
+        .for_each(|n| alphabet_check(n, &alphabet, " -"));
+
+    // Custom ranges: the Hiragana and Katakana alphabets.
+    let alphabet =
+        Alphabet::from_unicode_ranges(&[(0x3041, 0x3096), (0x30A1, 0x30FA), (0x30FC, 0x30FC)])?;
+    assert_eq!(
+        alphabet.alphabet_len(),
+        Alphabet::hiragana().alphabet_len() + Alphabet::katakana().alphabet_len()
+    );
+    ["やまだ タロウ", "スズキ はなこ"]
+        .iter()
+        .for_each(|n| alphabet_check(n, &alphabet, " -"));
+
+    // Overlapping ranges are merged and surrogates are skipped.
+    assert_eq!(
+        Alphabet::from_unicode_ranges(&[(0x30, 0x39), (0x35, 0x39)])?.alphabet_len(),
+        10
+    );
+    assert_eq!(
+        Alphabet::from_unicode_ranges(&[(0xD7FF, 0xE000)])?.alphabet_len(),
+        2
+    );
+
+    // Invalid ranges are rejected.
+    assert!(Alphabet::from_unicode_ranges(&[(0x39, 0x30)]).is_err());
+    assert!(Alphabet::from_unicode_ranges(&[(0x30, 0x30)]).is_err());
+    assert!(Alphabet::from_unicode_ranges(&[(0, 0x10FFFF)]).is_err());
+
+    Ok(())
+}
+
+#[test]
+fn test_alphabet_composition() -> Result<(), AnoError> {
+    // Alphanumeric characters and a few symbols.
+    let mut alphabet = Alphabet::alpha_numeric();
+    alphabet.extend_with_alphabet(&Alphabet::instantiate("@._-")?);
+    assert_eq!(alphabet.alphabet_len(), 66);
+    assert_eq!(alphabet.minimum_plaintext_length(), 4);
+    ["john.doe@example.com", "jane_doe-1@example.org"]
+        .iter()
+        .for_each(|n| alphabet_check(n, &alphabet, ""));
+
+    // Extending with overlapping alphabets removes the duplicates.
+    alphabet.extend_with_alphabet(&Alphabet::numeric());
+    assert_eq!(alphabet.alphabet_len(), 66);
+
+    // Easily confused characters are excluded.
+    let alphabet = alphabet.without("0O1lI")?;
+    assert_eq!(alphabet.alphabet_len(), 61);
+    assert!(alphabet.char_to_position('O').is_none());
+    ["john.doe@example.com", "jane_doe-2@example.org"]
+        .iter()
+        .for_each(|n| alphabet_check(n, &alphabet, "l"));
+
+    // Characters outside the alphabet are ignored.
+    assert_eq!(Alphabet::numeric().without("abc")?.alphabet_len(), 10);
+
+    // At least 2 characters must remain.
+    assert!(Alphabet::numeric().without("012345678").is_err());
+
+    Ok(())
+}
+
 #[test]
 fn fpe_ff1_string_same_alphabet() -> Result<(), AnoError> {
     for _ in 0..100 {
@@ -238,6 +323,93 @@ fn fpe_ff1_string_same_alphabet() -> Result<(), AnoError> {
     Ok(())
 }
 
+#[test]
+fn fpe_alphabet_validation() -> Result<(), AnoError> {
+    let alphabet = Alphabet::numeric();
+    assert!(alphabet.validate("1234123412341234").is_empty());
+    assert_eq!(
+        alphabet
+            .validate("1234-1234 1234-123a")
+            .into_iter()
+            .collect::<String>(),
+        " -a"
+    );
+
+    // Encryption errors point at the characters that are not in the alphabet.
+    let err = alphabet
+        .encrypt(&random_key(), &[], "12-3a b")
+        .unwrap_err()
+        .to_string();
+    assert!(err.contains("'-' at position 2"), "{err}");
+    assert!(err.contains("'a' at position 4"), "{err}");
+    assert!(err.contains("' ' at position 5"), "{err}");
+    Ok(())
+}
+
+#[test]
+fn fpe_error_kinds() {
+    let key = random_key();
+    let alphabet = Alphabet::numeric();
+
+    assert!(matches!(
+        alphabet.encrypt(&key[..16], &[], "1234567890"),
+        Err(AnoError::KeySize(16, KEY_LENGTH))
+    ));
+    assert!(matches!(
+        alphabet.decrypt(&key[..16], &[], "1234567890"),
+        Err(AnoError::KeySize(16, KEY_LENGTH))
+    ));
+
+    match alphabet.encrypt(&key, &[], "12-3a b") {
+        Err(AnoError::InputTooShort {
+            length,
+            min,
+            non_alphabet_chars,
+        }) => {
+            assert_eq!(length, 3);
+            assert_eq!(min, alphabet.minimum_plaintext_length());
+            assert_eq!(non_alphabet_chars, vec![(2, '-'), (4, 'a'), (5, ' '), (6, 'b')]);
+        }
+        res => panic!("unexpected result: {res:?}"),
+    }
+
+    let zip_code = Integer::instantiate(10, 6).unwrap();
+    match zip_code.encrypt_fixed_width(&key, &[], "0a-b12") {
+        Err(AnoError::AlphabetMismatch(chars)) => assert_eq!(chars, vec!['-', 'a', 'b']),
+        res => panic!("unexpected result: {res:?}"),
+    }
+    match CreditCard::default().encrypt(&key, &[], "4532/0151/1283/0366") {
+        Err(AnoError::AlphabetMismatch(chars)) => assert_eq!(chars, vec!['/']),
+        res => panic!("unexpected result: {res:?}"),
+    }
+}
+
+#[test]
+fn fpe_pseudonymize_preview() -> Result<(), AnoError> {
+    let alphabet = Alphabet::numeric();
+    let template = "###-##-####";
+    let ssn = alphabet.pseudonymize_preview(template, b"row 1")?;
+    assert_eq!(ssn.chars().count(), template.chars().count());
+    for (c, t) in ssn.chars().zip(template.chars()) {
+        if t == '-' {
+            assert_eq!(c, '-');
+        } else {
+            assert!(c.is_ascii_digit());
+        }
+    }
+
+    // Deterministic for a given seed, different for other seeds.
+    assert_eq!(ssn, alphabet.pseudonymize_preview(template, b"row 1")?);
+    assert_ne!(ssn, alphabet.pseudonymize_preview(template, b"row 2")?);
+
+    // Short templates are supported.
+    let initial = Alphabet::alpha_upper().pseudonymize_preview("#.", b"row 1")?;
+    assert_eq!(initial.len(), 2);
+    assert!(initial.starts_with(|c: char| c.is_ascii_uppercase()));
+    assert_eq!(alphabet.pseudonymize_preview("none", b"row 1")?, "none");
+    Ok(())
+}
+
 fn fpe_number_u64_(radix: u32, min_length: usize) -> Result<(), AnoError> {
     let key = random_key();
     let mut rng = thread_rng();
@@ -304,6 +476,149 @@ fn fpe_number_big_uint() -> Result<(), AnoError> {
     Ok(())
 }
 
+#[test]
+fn fpe_number_fixed_width() -> Result<(), AnoError> {
+    let key = random_key();
+    let zip_code = Integer::instantiate(10, 6)?;
+    for plaintext in ["000000", "000123", "012345", "999999"] {
+        let ciphertext = zip_code.encrypt_fixed_width(&key, &[], plaintext)?;
+        assert_eq!(ciphertext.len(), plaintext.len());
+        assert_eq!(
+            zip_code.decrypt_fixed_width(&key, &[], &ciphertext)?,
+            plaintext
+        );
+    }
+
+    let account_id = Integer::instantiate(16, 12)?;
+    let ciphertext = account_id.encrypt_fixed_width(&key, &[], "00000000beef")?;
+    assert_eq!(ciphertext.len(), 12);
+    assert_eq!(
+        account_id.decrypt_fixed_width(&key, &[], &ciphertext)?,
+        "00000000beef"
+    );
+
+    // The width must match and only digits of the radix are accepted.
+    assert!(zip_code.encrypt_fixed_width(&key, &[], "00123").is_err());
+    assert!(zip_code.encrypt_fixed_width(&key, &[], "0001234").is_err());
+    assert!(zip_code.encrypt_fixed_width(&key, &[], "000-12").is_err());
+    assert!(zip_code.decrypt_fixed_width(&key, &[], "00a123").is_err());
+    Ok(())
+}
+
+#[test]
+fn fpe_credit_card() -> Result<(), AnoError> {
+    let key = random_key();
+    let credit_card = CreditCard::default();
+    for plaintext in [
+        "4532015112830366",
+        "4532-0151-1283-0366",
+        "5425 2334 3010 9903",
+        "6011000990139424",
+    ] {
+        assert!(is_luhn_valid(plaintext));
+        let ciphertext = credit_card.encrypt(&key, b"tweak", plaintext)?;
+        assert!(is_luhn_valid(&ciphertext));
+        assert_eq!(ciphertext.len(), plaintext.len());
+        // The BIN, the last digits but the check digit, and the separators are
+        // kept.
+        let n = plaintext.len();
+        assert_eq!(ciphertext[..6], plaintext[..6]);
+        assert_eq!(ciphertext[n - 4..n - 1], plaintext[n - 4..n - 1]);
+        assert_eq!(credit_card.decrypt(&key, b"tweak", &ciphertext)?, plaintext);
+    }
+
+    // Custom number of kept digits.
+    let credit_card = CreditCard::instantiate(4, 1)?;
+    let ciphertext = credit_card.encrypt(&key, &[], "4532015112830366")?;
+    assert!(is_luhn_valid(&ciphertext));
+    assert_eq!(ciphertext[..4], *"4532");
+    assert_eq!(
+        credit_card.decrypt(&key, &[], &ciphertext)?,
+        "4532015112830366"
+    );
+    assert!(CreditCard::instantiate(6, 0).is_err());
+
+    // Invalid card numbers are rejected.
+    assert!(!is_luhn_valid("4532015112830367"));
+    assert!(credit_card.encrypt(&key, &[], "4532015112830367").is_err());
+    assert!(credit_card
+        .encrypt(&key, &[], "4532/0151/1283/0366")
+        .is_err());
+    // Not enough middle digits to be securely encrypted.
+    assert!(CreditCard::default()
+        .encrypt(&key, &[], "374245455400126")
+        .is_err());
+    Ok(())
+}
+
+#[test]
+fn fpe_decimal() -> Result<(), AnoError> {
+    let key = random_key();
+    let decimal = Decimal::instantiate(9, 2)?;
+    for plaintext in [
+        "0.00",
+        "1234.56",
+        "-1234.56",
+        "-0.01",
+        "999999999.99",
+        "-999999999.99",
+    ] {
+        let ciphertext = decimal.encrypt_str(&key, b"tweak", plaintext)?;
+        // The sign and the scale are preserved.
+        assert_eq!(plaintext.starts_with('-'), ciphertext.starts_with('-'));
+        assert_eq!(Some(2), ciphertext.split_once('.').map(|(_, f)| f.len()));
+        assert_eq!(decimal.decrypt_str(&key, b"tweak", &ciphertext)?, plaintext);
+    }
+
+    // Missing fraction digits and leading zeros are normalized.
+    let ciphertext = decimal.encrypt_str(&key, &[], "-001234.5")?;
+    assert_eq!(decimal.decrypt_str(&key, &[], &ciphertext)?, "-1234.50");
+    assert_eq!(
+        decimal.encrypt_str(&key, &[], "42")?,
+        decimal.encrypt_str(&key, &[], "42.00")?
+    );
+
+    // The string and scaled integer forms are consistent.
+    let ciphertext = decimal.encrypt(&key, &[], -123_456)?;
+    assert!(ciphertext < 0);
+    assert_eq!(
+        decimal.encrypt_str(&key, &[], "-1234.56")?,
+        format!("-{}.{:02}", -ciphertext / 100, -ciphertext % 100)
+    );
+    assert_eq!(decimal.decrypt(&key, &[], ciphertext)?, -123_456);
+
+    // Negative values never encrypt to 0, whatever the permutation.
+    let decimal = Decimal::instantiate(6, 0)?;
+    for value in 1..=1000 {
+        let ciphertext = decimal.encrypt(&key, &[], -value)?;
+        assert!(ciphertext < 0);
+        assert_eq!(decimal.decrypt(&key, &[], ciphertext)?, -value);
+    }
+    assert_eq!(decimal.encrypt_str(&key, &[], "123456")?.find('.'), None);
+
+    // Invalid decimals are rejected.
+    let decimal = Decimal::instantiate(4, 2)?;
+    for invalid in [
+        "", "-", "1.", ".5", "1.234", "12345", "1,5", "+1", "1e3", "--1",
+    ] {
+        assert!(
+            decimal.encrypt_str(&key, &[], invalid).is_err(),
+            "{invalid}"
+        );
+    }
+    assert!(decimal.encrypt(&key, &[], 1_000_000).is_err());
+    assert!(decimal.encrypt(&key, &[], -1_000_000).is_err());
+    assert!(Decimal::instantiate(3, 2).is_err());
+    assert!(Decimal::instantiate(30, 10).is_err());
+
+    // The greatest supported decimal fits in an i128.
+    let decimal = Decimal::instantiate(20, 18)?;
+    let max = 10_i128.pow(38) - 1;
+    let ciphertext = decimal.encrypt(&key, &[], -max)?;
+    assert_eq!(decimal.decrypt(&key, &[], ciphertext)?, -max);
+    Ok(())
+}
+
 #[test]
 fn fpe_float() -> Result<(), AnoError> {
     let key = random_key();
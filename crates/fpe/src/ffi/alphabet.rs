@@ -1,4 +1,7 @@
-use cosmian_ffi_utils::{ffi_read_bytes, ffi_read_string, ffi_unwrap, ffi_write_bytes, ErrorCode};
+use cosmian_ffi_utils::{
+    ffi_guard, ffi_not_null, ffi_read_bytes, ffi_read_string, ffi_unwrap, ffi_write_bytes,
+    ErrorCode,
+};
 
 use crate::get_alphabet;
 
@@ -90,20 +93,22 @@ pub unsafe extern "C" fn h_fpe_encrypt_alphabet(
     tweak_len: i32,
     additional_characters_ptr: *const i8,
 ) -> i32 {
-    // Calls the internal FPE encryption function with the specified alphabet and
-    // sets the "encrypt" flag to true.
-    fpe(
-        plaintext_ptr,
-        plaintext_len,
-        input_ptr,
-        alphabet_id_ptr,
-        key_ptr,
-        key_len,
-        tweak_ptr,
-        tweak_len,
-        additional_characters_ptr,
-        true,
-    )
+    ffi_guard!({
+        // Calls the internal FPE encryption function with the specified alphabet and
+        // sets the "encrypt" flag to true.
+        fpe(
+            plaintext_ptr,
+            plaintext_len,
+            input_ptr,
+            alphabet_id_ptr,
+            key_ptr,
+            key_len,
+            tweak_ptr,
+            tweak_len,
+            additional_characters_ptr,
+            true,
+        )
+    })
 }
 
 /// Decrypts a string using Format Preserving Encryption (FPE) algorithm with
@@ -150,16 +155,134 @@ pub unsafe extern "C" fn h_fpe_decrypt_alphabet(
     tweak_len: i32,
     additional_characters_ptr: *const i8,
 ) -> i32 {
-    fpe(
-        ciphertext_ptr,
-        ciphertext_len,
-        input_ptr,
-        alphabet_id_ptr,
-        key_ptr,
-        key_len,
-        tweak_ptr,
-        tweak_len,
-        additional_characters_ptr,
-        false,
-    )
+    ffi_guard!({
+        fpe(
+            ciphertext_ptr,
+            ciphertext_len,
+            input_ptr,
+            alphabet_id_ptr,
+            key_ptr,
+            key_len,
+            tweak_ptr,
+            tweak_len,
+            additional_characters_ptr,
+            false,
+        )
+    })
+}
+
+/// Generates a fake value following the given format template using the
+/// specified alphabet, for testing purposes.
+///
+/// Each `#` of the template is replaced by a character of the alphabet, the
+/// other characters are kept. The value only depends on the template and on
+/// the seed: no key is involved.
+///
+/// # Safety
+///
+/// This function is marked as `unsafe` due to the usage of raw pointers, which
+/// need to be properly allocated and dereferenced by the caller.
+///
+/// # Arguments
+///
+/// * `output_ptr` - a pointer to the buffer where the generated value will be
+///   written.
+/// * `output_len` - a pointer to the variable that stores the maximum size of
+///   the `output_ptr` buffer. After the function call, the variable will be
+///   updated with the actual size of the generated value.
+/// * `alphabet_id_ptr` - a pointer to a C string that represents the ID of the
+///   alphabet used for the generation.
+/// * `template_ptr` - a pointer to a C string that represents the format
+///   template.
+/// * `seed_ptr` - a pointer to the seed of the generation (e.g. a row number).
+/// * `seed_len` - the length of the seed.
+/// * `additional_characters_ptr` - a pointer to a C string that represents
+///   additional characters to be used in the alphabet.
+///
+/// # Returns
+///
+/// An integer that indicates whether the generation was successful. A value of
+/// `0` means success, while a non-zero value represents an error code.
+#[no_mangle]
+pub unsafe extern "C" fn h_fpe_pseudonymize_preview(
+    output_ptr: *mut u8,
+    output_len: *mut i32,
+    alphabet_id_ptr: *const i8,
+    template_ptr: *const i8,
+    seed_ptr: *const i8,
+    seed_len: i32,
+    additional_characters_ptr: *const i8,
+) -> i32 {
+    ffi_guard!({
+        let template_str = ffi_read_string!("template", template_ptr);
+        let seed_bytes = ffi_read_bytes!("seed", seed_ptr, seed_len);
+        let alphabet_id_str = ffi_read_string!("alphabet_id", alphabet_id_ptr);
+
+        let mut alphabet = ffi_unwrap!(
+            get_alphabet(&alphabet_id_str),
+            "Alphabet id not supported",
+            ErrorCode::Fpe
+        );
+        let additional_characters_str =
+            ffi_read_string!("additional_characters_ptr", additional_characters_ptr);
+        alphabet.extend_with(&additional_characters_str);
+
+        let output_str = ffi_unwrap!(
+            alphabet.pseudonymize_preview(&template_str, seed_bytes),
+            "fpe preview generation",
+            ErrorCode::Fpe
+        );
+
+        ffi_write_bytes!("output_ptr", output_str.as_bytes(), output_ptr, output_len);
+    })
+}
+
+/// Returns the minimum number of alphabet characters of the inputs for FPE to
+/// be secure using the specified alphabet: shorter inputs are rejected.
+///
+/// # Safety
+///
+/// This function is marked as `unsafe` due to the usage of raw pointers, which
+/// need to be properly allocated and dereferenced by the caller.
+///
+/// # Arguments
+///
+/// * `min_length` - a pointer to the variable where the minimum length will be
+///   written.
+/// * `alphabet_id_ptr` - a pointer to a C string that represents the ID of the
+///   alphabet.
+/// * `additional_characters_ptr` - a pointer to a C string that represents
+///   additional characters to be used in the alphabet.
+///
+/// # Returns
+///
+/// An integer that indicates whether the call was successful. A value of `0`
+/// means success, while a non-zero value represents an error code.
+#[no_mangle]
+pub unsafe extern "C" fn h_fpe_minimum_plaintext_length(
+    min_length: *mut i32,
+    alphabet_id_ptr: *const i8,
+    additional_characters_ptr: *const i8,
+) -> i32 {
+    ffi_guard!({
+        ffi_not_null!("min_length", min_length);
+        let alphabet_id_str = ffi_read_string!("alphabet_id", alphabet_id_ptr);
+
+        let mut alphabet = ffi_unwrap!(
+            get_alphabet(&alphabet_id_str),
+            "Alphabet id not supported",
+            ErrorCode::Fpe
+        );
+        let additional_characters_str =
+            ffi_read_string!("additional_characters_ptr", additional_characters_ptr);
+        alphabet.extend_with(&additional_characters_str);
+
+        *min_length = ffi_unwrap!(
+            i32::try_from(alphabet.minimum_plaintext_length()),
+            "minimum plaintext length overflow",
+            ErrorCode::Fpe
+        );
+
+        0
+    })
 }
@@ -0,0 +1,233 @@
+//! FPE contexts holding the key, tweak and alphabet on the Rust side.
+//!
+//! The raw key only crosses the FFI boundary once, upon context creation, and
+//! is zeroized when the context is destroyed.
+
+use std::{
+    collections::HashMap,
+    sync::{
+        atomic::{AtomicI32, Ordering},
+        RwLock,
+    },
+};
+
+use cosmian_ffi_utils::{
+    ffi_bail, ffi_guard, ffi_read_bytes, ffi_read_string, ffi_unwrap, ffi_write_bytes, ErrorCode,
+};
+use lazy_static::lazy_static;
+use zeroize::Zeroizing;
+
+use crate::{
+    core::{Alphabet, KEY_LENGTH},
+    get_alphabet,
+};
+
+// A static cache of the FPE contexts
+lazy_static! {
+    static ref FPE_CONTEXT_MAP: RwLock<HashMap<i32, FpeContext>> = RwLock::new(HashMap::new());
+    static ref NEXT_FPE_CONTEXT_ID: AtomicI32 = AtomicI32::new(0);
+}
+
+/// An FPE context caching Rust side the key, the tweak and the alphabet used
+/// by successive encryptions and decryptions.
+struct FpeContext {
+    key: Zeroizing<Vec<u8>>,
+    tweak: Vec<u8>,
+    alphabet: Alphabet,
+}
+
+/// Creates an FPE context holding the given key, tweak and alphabet. This
+/// context can be reused to encrypt and decrypt strings, which avoids passing
+/// the key to Rust in each call.
+///
+/// WARNING: [`h_fpe_destroy_context()`](h_fpe_destroy_context) should be
+/// called to zeroize the key and reclaim the context memory.
+///
+/// # Safety
+///
+/// This function is marked as `unsafe` due to the usage of raw pointers, which
+/// need to be properly allocated and dereferenced by the caller.
+///
+/// # Arguments
+///
+/// * `context_handle` - a pointer to the variable where the handle of the new
+///   context will be written.
+/// * `key_ptr` - a pointer to the key used for encryption.
+/// * `key_len` - the length of the key.
+/// * `tweak_ptr` - a pointer to the tweak used for encryption.
+/// * `tweak_len` - the length of the tweak.
+/// * `alphabet_id_ptr` - a pointer to a C string that represents the ID of the
+///   alphabet used for encryption.
+/// * `additional_characters_ptr` - a pointer to a C string that represents
+///   additional characters to be used in the alphabet.
+#[no_mangle]
+pub unsafe extern "C" fn h_fpe_create_context(
+    context_handle: *mut i32,
+    key_ptr: *const i8,
+    key_len: i32,
+    tweak_ptr: *const i8,
+    tweak_len: i32,
+    alphabet_id_ptr: *const i8,
+    additional_characters_ptr: *const i8,
+) -> i32 {
+    ffi_guard!({
+        let key_bytes = ffi_read_bytes!("key", key_ptr, key_len);
+        if key_bytes.len() != KEY_LENGTH {
+            ffi_bail!(
+                "FPE key should be {} bytes long, given {}",
+                KEY_LENGTH,
+                key_bytes.len()
+            );
+        }
+        let tweak_bytes = ffi_read_bytes!("tweak", tweak_ptr, tweak_len);
+        let alphabet_id_str = ffi_read_string!("alphabet_id", alphabet_id_ptr);
+
+        let mut alphabet = ffi_unwrap!(
+            get_alphabet(&alphabet_id_str),
+            "Alphabet id not supported",
+            ErrorCode::Fpe
+        );
+        let additional_characters_str =
+            ffi_read_string!("additional_characters_ptr", additional_characters_ptr);
+        alphabet.extend_with(&additional_characters_str);
+
+        let context = FpeContext {
+            key: Zeroizing::new(key_bytes.to_vec()),
+            tweak: tweak_bytes.to_vec(),
+            alphabet,
+        };
+        let id = NEXT_FPE_CONTEXT_ID.fetch_add(1, Ordering::Acquire);
+        let mut map = FPE_CONTEXT_MAP
+            .write()
+            .expect("A write mutex on FPE context map failed");
+        map.insert(id, context);
+        *context_handle = id;
+
+        0
+    })
+}
+
+/// Zeroizes the key of the given context and reclaims its memory.
+///
+/// Cf [`h_fpe_create_context()`](h_fpe_create_context).
+///
+/// # Safety
+#[no_mangle]
+pub unsafe extern "C" fn h_fpe_destroy_context(context_handle: i32) -> i32 {
+    ffi_guard!({
+        let mut map = FPE_CONTEXT_MAP
+            .write()
+            .expect("A write mutex on FPE context map failed");
+        map.remove(&context_handle);
+        0
+    })
+}
+
+unsafe fn fpe_with_context(
+    output_ptr: *mut u8,
+    output_len: *mut i32,
+    context_handle: i32,
+    input_ptr: *const i8,
+    encrypt_flag: bool,
+) -> i32 {
+    let input_str = ffi_read_string!("input", input_ptr);
+
+    let map = FPE_CONTEXT_MAP
+        .read()
+        .expect("a read mutex on the FPE context map failed");
+    let Some(context) = map.get(&context_handle) else {
+        ffi_bail!(format!("FPE: no context with handle: {context_handle}"));
+    };
+
+    let output_str = if encrypt_flag {
+        ffi_unwrap!(
+            context
+                .alphabet
+                .encrypt(&context.key, &context.tweak, &input_str),
+            "fpe encryption process",
+            ErrorCode::Encryption
+        )
+    } else {
+        ffi_unwrap!(
+            context
+                .alphabet
+                .decrypt(&context.key, &context.tweak, &input_str),
+            "fpe decryption process",
+            ErrorCode::Decryption
+        )
+    };
+
+    ffi_write_bytes!("output_ptr", output_str.as_bytes(), output_ptr, output_len);
+}
+
+/// Encrypts a string using the key, tweak and alphabet of the given FPE
+/// context.
+///
+/// # Safety
+///
+/// This function is marked as `unsafe` due to the usage of raw pointers, which
+/// need to be properly allocated and dereferenced by the caller.
+///
+/// # Arguments
+///
+/// * `ciphertext_ptr` - a pointer to the buffer where the encrypted string will
+///   be written.
+/// * `ciphertext_len` - a pointer to the variable that stores the maximum size
+///   of the `ciphertext_ptr` buffer. After the function call, the variable
+///   will be updated with the actual size of the encrypted string.
+/// * `context_handle` - the handle of the FPE context.
+/// * `plaintext_ptr` - a pointer to a C string that represents the plaintext to
+///   be encrypted.
+#[no_mangle]
+pub unsafe extern "C" fn h_fpe_encrypt_with_context(
+    ciphertext_ptr: *mut u8,
+    ciphertext_len: *mut i32,
+    context_handle: i32,
+    plaintext_ptr: *const i8,
+) -> i32 {
+    ffi_guard!({
+        fpe_with_context(
+            ciphertext_ptr,
+            ciphertext_len,
+            context_handle,
+            plaintext_ptr,
+            true,
+        )
+    })
+}
+
+/// Decrypts a string using the key, tweak and alphabet of the given FPE
+/// context.
+///
+/// # Safety
+///
+/// This function is marked as `unsafe` due to the usage of raw pointers, which
+/// need to be properly allocated and dereferenced by the caller.
+///
+/// # Arguments
+///
+/// * `plaintext_ptr` - a pointer to the buffer where the decrypted string will
+///   be written.
+/// * `plaintext_len` - a pointer to the variable that stores the maximum size
+///   of the `plaintext_ptr` buffer. After the function call, the variable will
+///   be updated with the actual size of the decrypted string.
+/// * `context_handle` - the handle of the FPE context.
+/// * `ciphertext_ptr` - a pointer to a C string that represents the ciphertext
+///   to be decrypted.
+#[no_mangle]
+pub unsafe extern "C" fn h_fpe_decrypt_with_context(
+    plaintext_ptr: *mut u8,
+    plaintext_len: *mut i32,
+    context_handle: i32,
+    ciphertext_ptr: *const i8,
+) -> i32 {
+    ffi_guard!({
+        fpe_with_context(
+            plaintext_ptr,
+            plaintext_len,
+            context_handle,
+            ciphertext_ptr,
+            false,
+        )
+    })
+}
@@ -0,0 +1,149 @@
+use cosmian_ffi_utils::{
+    ffi_guard, ffi_read_bytes, ffi_read_string, ffi_unwrap, ffi_write_bytes, ErrorCode,
+};
+
+use crate::core::{CreditCard, KEY_LENGTH};
+
+#[allow(clippy::too_many_arguments)]
+unsafe fn fpe_credit_card(
+    output_ptr: *mut u8,
+    output_len: *mut i32,
+    input_ptr: *const i8,
+    keep_first: u32,
+    keep_last: u32,
+    key_ptr: *const i8,
+    key_len: i32,
+    tweak_ptr: *const i8,
+    tweak_len: i32,
+    encrypt_flag: bool,
+) -> i32 {
+    let key_bytes = ffi_read_bytes!("key", key_ptr, key_len);
+    let tweak_bytes = ffi_read_bytes!("tweak", tweak_ptr, tweak_len);
+    let input_str = ffi_read_string!("input", input_ptr);
+
+    let credit_card = ffi_unwrap!(
+        CreditCard::instantiate(keep_first as usize, keep_last as usize),
+        "cannot instantiate FPE credit card",
+        ErrorCode::Fpe
+    );
+
+    // Copy the contents of the slice into the 32-array
+    let key: [u8; KEY_LENGTH] = ffi_unwrap!(
+        key_bytes.try_into(),
+        "key size is 32 bytes",
+        ErrorCode::Serialization
+    );
+
+    let output_str = if encrypt_flag {
+        ffi_unwrap!(
+            credit_card.encrypt(&key, tweak_bytes, &input_str),
+            "fpe encryption process",
+            ErrorCode::Encryption
+        )
+    } else {
+        ffi_unwrap!(
+            credit_card.decrypt(&key, tweak_bytes, &input_str),
+            "fpe decryption process",
+            ErrorCode::Decryption
+        )
+    };
+
+    ffi_write_bytes!("output_ptr", output_str.as_bytes(), output_ptr, output_len);
+}
+
+/// Encrypts the middle digits of a credit card number using the FPE
+/// algorithm. The first `keep_first` and last `keep_last` digits are kept, and
+/// the Luhn check digit is recomputed.
+///
+/// # Arguments
+///
+/// * `output_ptr` - a pointer to the output buffer where the encrypted card
+///   number will be written
+/// * `output_len` - a pointer to an integer that will be updated with the
+///   length of the encrypted card number
+/// * `input_ptr` - a pointer to the C string of the card number to encrypt;
+///   it may contain spaces and dashes
+/// * `keep_first` - the number of leading digits kept in clear (e.g. 6 for the
+///   BIN)
+/// * `keep_last` - the number of trailing digits kept in clear, check digit
+///   included (e.g. 4)
+/// * `key_ptr` - a pointer to the key buffer that will be used for encryption
+/// * `key_len` - the length of the key buffer
+/// * `tweak_ptr` - a pointer to the tweak buffer that will be used for
+///   encryption
+/// * `tweak_len` - the length of the tweak buffer
+///
+/// # Safety
+///
+/// This function is marked unsafe because it operates on raw pointers and
+/// performs unsafe memory operations.
+///
+/// # Returns
+///
+/// Returns 0 on success, an error code otherwise.
+#[no_mangle]
+pub unsafe extern "C" fn h_fpe_encrypt_credit_card(
+    output_ptr: *mut u8,
+    output_len: *mut i32,
+    input_ptr: *const i8,
+    keep_first: u32,
+    keep_last: u32,
+    key_ptr: *const i8,
+    key_len: i32,
+    tweak_ptr: *const i8,
+    tweak_len: i32,
+) -> i32 {
+    ffi_guard!({
+        fpe_credit_card(
+            output_ptr, output_len, input_ptr, keep_first, keep_last, key_ptr, key_len, tweak_ptr,
+            tweak_len, true,
+        )
+    })
+}
+
+/// Decrypts the middle digits of a credit card number encrypted using
+/// [`h_fpe_encrypt_credit_card()`](h_fpe_encrypt_credit_card).
+///
+/// # Arguments
+///
+/// * `output_ptr` - a pointer to the output buffer where the decrypted card
+///   number will be written
+/// * `output_len` - a pointer to an integer that will be updated with the
+///   length of the decrypted card number
+/// * `input_ptr` - a pointer to the C string of the card number to decrypt
+/// * `keep_first` - the number of leading digits kept in clear
+/// * `keep_last` - the number of trailing digits kept in clear, check digit
+///   included
+/// * `key_ptr` - a pointer to the key buffer that will be used for decryption
+/// * `key_len` - the length of the key buffer
+/// * `tweak_ptr` - a pointer to the tweak buffer that will be used for
+///   decryption
+/// * `tweak_len` - the length of the tweak buffer
+///
+/// # Safety
+///
+/// This function is marked unsafe because it operates on raw pointers and
+/// performs unsafe memory operations.
+///
+/// # Returns
+///
+/// Returns 0 on success, an error code otherwise.
+#[no_mangle]
+pub unsafe extern "C" fn h_fpe_decrypt_credit_card(
+    output_ptr: *mut u8,
+    output_len: *mut i32,
+    input_ptr: *const i8,
+    keep_first: u32,
+    keep_last: u32,
+    key_ptr: *const i8,
+    key_len: i32,
+    tweak_ptr: *const i8,
+    tweak_len: i32,
+) -> i32 {
+    ffi_guard!({
+        fpe_credit_card(
+            output_ptr, output_len, input_ptr, keep_first, keep_last, key_ptr, key_len, tweak_ptr,
+            tweak_len, false,
+        )
+    })
+}
@@ -0,0 +1,307 @@
+use cosmian_ffi_utils::{
+    ffi_guard, ffi_read_bytes, ffi_read_string, ffi_unwrap, ffi_write_bytes, ErrorCode,
+};
+
+use crate::core::{Decimal, KEY_LENGTH};
+
+#[allow(clippy::too_many_arguments)]
+unsafe fn fpe_decimal(
+    output: *mut i128,
+    input: i128,
+    integer_digits: u32,
+    fraction_digits: u32,
+    key_ptr: *const i8,
+    key_len: i32,
+    tweak_ptr: *const i8,
+    tweak_len: i32,
+    encrypt_flag: bool,
+) -> i32 {
+    let key_bytes = ffi_read_bytes!("key", key_ptr, key_len);
+    let tweak_bytes = ffi_read_bytes!("tweak", tweak_ptr, tweak_len);
+
+    // Copy the contents of the slice into the 32-array
+    let key: [u8; KEY_LENGTH] = ffi_unwrap!(
+        key_bytes.try_into(),
+        "key size is 32 bytes",
+        ErrorCode::Serialization
+    );
+
+    let decimal = ffi_unwrap!(
+        Decimal::instantiate(integer_digits as usize, fraction_digits as usize),
+        "cannot instantiate FPE decimal",
+        ErrorCode::Fpe
+    );
+
+    *output = if encrypt_flag {
+        ffi_unwrap!(
+            decimal.encrypt(&key, tweak_bytes, input),
+            "fpe encryption process",
+            ErrorCode::Encryption
+        )
+    } else {
+        ffi_unwrap!(
+            decimal.decrypt(&key, tweak_bytes, input),
+            "fpe decryption process",
+            ErrorCode::Decryption
+        )
+    };
+
+    0
+}
+
+/// Encrypts a decimal number scaled by `10^fraction_digits` (e.g. `-123456`
+/// for `-1234.56` with 2 fraction digits) using the FPE algorithm. The sign
+/// and the scale are preserved.
+///
+/// # Arguments
+///
+/// * `output` - a pointer to the location where the encrypted scaled decimal
+///   will be stored
+/// * `input` - the scaled decimal to encrypt
+/// * `integer_digits` - the number of integer digits of the decimals
+/// * `fraction_digits` - the number of fraction digits of the decimals
+/// * `key_ptr` - a pointer to the key buffer that will be used for encryption
+/// * `key_len` - the length of the key buffer
+/// * `tweak_ptr` - a pointer to the tweak buffer that will be used for
+///   encryption
+/// * `tweak_len` - the length of the tweak buffer
+///
+/// # Safety
+///
+/// This function is marked unsafe because it operates on raw pointers and
+/// performs unsafe memory operations.
+///
+/// # Returns
+///
+/// Returns 0 on success, an error code otherwise.
+#[no_mangle]
+pub unsafe extern "C" fn h_fpe_encrypt_scaled_decimal(
+    output: *mut i128,
+    input: i128,
+    integer_digits: u32,
+    fraction_digits: u32,
+    key_ptr: *const i8,
+    key_len: i32,
+    tweak_ptr: *const i8,
+    tweak_len: i32,
+) -> i32 {
+    ffi_guard!({
+        fpe_decimal(
+            output,
+            input,
+            integer_digits,
+            fraction_digits,
+            key_ptr,
+            key_len,
+            tweak_ptr,
+            tweak_len,
+            true,
+        )
+    })
+}
+
+/// Decrypts a decimal number scaled by `10^fraction_digits` encrypted using
+/// [`h_fpe_encrypt_scaled_decimal()`](h_fpe_encrypt_scaled_decimal).
+///
+/// # Arguments
+///
+/// * `output` - a pointer to the location where the decrypted scaled decimal
+///   will be stored
+/// * `input` - the scaled decimal to decrypt
+/// * `integer_digits` - the number of integer digits of the decimals
+/// * `fraction_digits` - the number of fraction digits of the decimals
+/// * `key_ptr` - a pointer to the key buffer that will be used for decryption
+/// * `key_len` - the length of the key buffer
+/// * `tweak_ptr` - a pointer to the tweak buffer that will be used for
+///   decryption
+/// * `tweak_len` - the length of the tweak buffer
+///
+/// # Safety
+///
+/// This function is marked unsafe because it operates on raw pointers and
+/// performs unsafe memory operations.
+///
+/// # Returns
+///
+/// Returns 0 on success, an error code otherwise.
+#[no_mangle]
+pub unsafe extern "C" fn h_fpe_decrypt_scaled_decimal(
+    output: *mut i128,
+    input: i128,
+    integer_digits: u32,
+    fraction_digits: u32,
+    key_ptr: *const i8,
+    key_len: i32,
+    tweak_ptr: *const i8,
+    tweak_len: i32,
+) -> i32 {
+    ffi_guard!({
+        fpe_decimal(
+            output,
+            input,
+            integer_digits,
+            fraction_digits,
+            key_ptr,
+            key_len,
+            tweak_ptr,
+            tweak_len,
+            false,
+        )
+    })
+}
+
+#[allow(clippy::too_many_arguments)]
+unsafe fn fpe_decimal_string(
+    output_ptr: *mut u8,
+    output_len: *mut i32,
+    input_ptr: *const i8,
+    integer_digits: u32,
+    fraction_digits: u32,
+    key_ptr: *const i8,
+    key_len: i32,
+    tweak_ptr: *const i8,
+    tweak_len: i32,
+    encrypt_flag: bool,
+) -> i32 {
+    let key_bytes = ffi_read_bytes!("key", key_ptr, key_len);
+    let tweak_bytes = ffi_read_bytes!("tweak", tweak_ptr, tweak_len);
+    let input_str = ffi_read_string!("input", input_ptr);
+
+    // Copy the contents of the slice into the 32-array
+    let key: [u8; KEY_LENGTH] = ffi_unwrap!(
+        key_bytes.try_into(),
+        "key size is 32 bytes",
+        ErrorCode::Serialization
+    );
+
+    let decimal = ffi_unwrap!(
+        Decimal::instantiate(integer_digits as usize, fraction_digits as usize),
+        "cannot instantiate FPE decimal",
+        ErrorCode::Fpe
+    );
+
+    let output_str = if encrypt_flag {
+        ffi_unwrap!(
+            decimal.encrypt_str(&key, tweak_bytes, &input_str),
+            "fpe encryption process",
+            ErrorCode::Encryption
+        )
+    } else {
+        ffi_unwrap!(
+            decimal.decrypt_str(&key, tweak_bytes, &input_str),
+            "fpe decryption process",
+            ErrorCode::Decryption
+        )
+    };
+
+    ffi_write_bytes!("output_ptr", output_str.as_bytes(), output_ptr, output_len);
+}
+
+/// Encrypts a decimal number given as a string (e.g. `"-1234.56"`) using the
+/// FPE algorithm. The sign is preserved and the ciphertext has exactly
+/// `fraction_digits` fraction digits.
+///
+/// # Arguments
+///
+/// * `output_ptr` - a pointer to the output buffer where the encrypted decimal
+///   will be written
+/// * `output_len` - a pointer to an integer that will be updated with the
+///   length of the encrypted decimal
+/// * `input_ptr` - a pointer to the C string of the decimal to encrypt
+/// * `integer_digits` - the number of integer digits of the decimals
+/// * `fraction_digits` - the number of fraction digits of the decimals
+/// * `key_ptr` - a pointer to the key buffer that will be used for encryption
+/// * `key_len` - the length of the key buffer
+/// * `tweak_ptr` - a pointer to the tweak buffer that will be used for
+///   encryption
+/// * `tweak_len` - the length of the tweak buffer
+///
+/// # Safety
+///
+/// This function is marked unsafe because it operates on raw pointers and
+/// performs unsafe memory operations.
+///
+/// # Returns
+///
+/// Returns 0 on success, an error code otherwise.
+#[no_mangle]
+pub unsafe extern "C" fn h_fpe_encrypt_decimal(
+    output_ptr: *mut u8,
+    output_len: *mut i32,
+    input_ptr: *const i8,
+    integer_digits: u32,
+    fraction_digits: u32,
+    key_ptr: *const i8,
+    key_len: i32,
+    tweak_ptr: *const i8,
+    tweak_len: i32,
+) -> i32 {
+    ffi_guard!({
+        fpe_decimal_string(
+            output_ptr,
+            output_len,
+            input_ptr,
+            integer_digits,
+            fraction_digits,
+            key_ptr,
+            key_len,
+            tweak_ptr,
+            tweak_len,
+            true,
+        )
+    })
+}
+
+/// Decrypts a decimal number given as a string encrypted using
+/// [`h_fpe_encrypt_decimal()`](h_fpe_encrypt_decimal).
+///
+/// # Arguments
+///
+/// * `output_ptr` - a pointer to the output buffer where the decrypted decimal
+///   will be written
+/// * `output_len` - a pointer to an integer that will be updated with the
+///   length of the decrypted decimal
+/// * `input_ptr` - a pointer to the C string of the decimal to decrypt
+/// * `integer_digits` - the number of integer digits of the decimals
+/// * `fraction_digits` - the number of fraction digits of the decimals
+/// * `key_ptr` - a pointer to the key buffer that will be used for decryption
+/// * `key_len` - the length of the key buffer
+/// * `tweak_ptr` - a pointer to the tweak buffer that will be used for
+///   decryption
+/// * `tweak_len` - the length of the tweak buffer
+///
+/// # Safety
+///
+/// This function is marked unsafe because it operates on raw pointers and
+/// performs unsafe memory operations.
+///
+/// # Returns
+///
+/// Returns 0 on success, an error code otherwise.
+#[no_mangle]
+pub unsafe extern "C" fn h_fpe_decrypt_decimal(
+    output_ptr: *mut u8,
+    output_len: *mut i32,
+    input_ptr: *const i8,
+    integer_digits: u32,
+    fraction_digits: u32,
+    key_ptr: *const i8,
+    key_len: i32,
+    tweak_ptr: *const i8,
+    tweak_len: i32,
+) -> i32 {
+    ffi_guard!({
+        fpe_decimal_string(
+            output_ptr,
+            output_len,
+            input_ptr,
+            integer_digits,
+            fraction_digits,
+            key_ptr,
+            key_len,
+            tweak_ptr,
+            tweak_len,
+            false,
+        )
+    })
+}
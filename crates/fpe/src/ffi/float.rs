@@ -1,4 +1,4 @@
-use cosmian_ffi_utils::{ffi_read_bytes, ffi_unwrap, ErrorCode};
+use cosmian_ffi_utils::{ffi_guard, ffi_read_bytes, ffi_unwrap, ErrorCode};
 
 use crate::core::{Float, KEY_LENGTH};
 
@@ -59,7 +59,7 @@ pub unsafe extern "C" fn h_fpe_encrypt_float(
     tweak_ptr: *const i8,
     tweak_len: i32,
 ) -> i32 {
-    fpe(output, input, key_ptr, key_len, tweak_ptr, tweak_len, true)
+    ffi_guard!({ fpe(output, input, key_ptr, key_len, tweak_ptr, tweak_len, true) })
 }
 
 /// Decrypts the input `f64` using the FPE algorithm with the given key and
@@ -80,5 +80,5 @@ pub unsafe extern "C" fn h_fpe_decrypt_float(
     tweak_ptr: *const i8,
     tweak_len: i32,
 ) -> i32 {
-    fpe(output, input, key_ptr, key_len, tweak_ptr, tweak_len, false)
+    ffi_guard!({ fpe(output, input, key_ptr, key_len, tweak_ptr, tweak_len, false) })
 }
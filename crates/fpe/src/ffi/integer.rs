@@ -1,4 +1,6 @@
-use cosmian_ffi_utils::{ffi_read_bytes, ffi_read_string, ffi_unwrap, ffi_write_bytes, ErrorCode};
+use cosmian_ffi_utils::{
+    ffi_guard, ffi_read_bytes, ffi_read_string, ffi_unwrap, ffi_write_bytes, ErrorCode,
+};
 use num_bigint::BigUint;
 use num_traits::Num;
 
@@ -83,9 +85,11 @@ pub unsafe extern "C" fn h_fpe_encrypt_integer(
     tweak_ptr: *const i8,
     tweak_len: i32,
 ) -> i32 {
-    fpe(
-        output, input, radix, digits, key_ptr, key_len, tweak_ptr, tweak_len, true,
-    )
+    ffi_guard!({
+        fpe(
+            output, input, radix, digits, key_ptr, key_len, tweak_ptr, tweak_len, true,
+        )
+    })
 }
 
 /// Decrypts an integer using the format-preserving encryption (FPE) algorithm.
@@ -123,9 +127,11 @@ pub unsafe extern "C" fn h_fpe_decrypt_integer(
     tweak_ptr: *const i8,
     tweak_len: i32,
 ) -> i32 {
-    fpe(
-        output, input, radix, digits, key_ptr, key_len, tweak_ptr, tweak_len, false,
-    )
+    ffi_guard!({
+        fpe(
+            output, input, radix, digits, key_ptr, key_len, tweak_ptr, tweak_len, false,
+        )
+    })
 }
 
 unsafe extern "C" fn fpe_big_integer(
@@ -220,10 +226,12 @@ pub unsafe extern "C" fn h_fpe_encrypt_big_integer(
     tweak_ptr: *const i8,
     tweak_len: i32,
 ) -> i32 {
-    fpe_big_integer(
-        output_ptr, output_len, input_ptr, radix, digits, key_ptr, key_len, tweak_ptr, tweak_len,
-        true,
-    )
+    ffi_guard!({
+        fpe_big_integer(
+            output_ptr, output_len, input_ptr, radix, digits, key_ptr, key_len, tweak_ptr,
+            tweak_len, true,
+        )
+    })
 }
 
 /// Decrypts an input big integer using the FPE algorithm and returns the
@@ -265,8 +273,150 @@ pub unsafe extern "C" fn h_fpe_decrypt_big_integer(
     tweak_ptr: *const i8,
     tweak_len: i32,
 ) -> i32 {
-    fpe_big_integer(
-        output_ptr, output_len, input_ptr, radix, digits, key_ptr, key_len, tweak_ptr, tweak_len,
-        false,
-    )
+    ffi_guard!({
+        fpe_big_integer(
+            output_ptr, output_len, input_ptr, radix, digits, key_ptr, key_len, tweak_ptr,
+            tweak_len, false,
+        )
+    })
+}
+
+unsafe extern "C" fn fpe_fixed_width_integer(
+    output_ptr: *mut u8,
+    output_len: *mut i32,
+    input_ptr: *const i8,
+    radix: u32,
+    digits: u32,
+    key_ptr: *const i8,
+    key_len: i32,
+    tweak_ptr: *const i8,
+    tweak_len: i32,
+    encrypt_flag: bool,
+) -> i32 {
+    let key_bytes = ffi_read_bytes!("key", key_ptr, key_len);
+    let tweak_bytes = ffi_read_bytes!("tweak", tweak_ptr, tweak_len);
+    let input_str = ffi_read_string!("input", input_ptr);
+
+    let itg = ffi_unwrap!(
+        Integer::instantiate(radix, digits as usize),
+        "cannot instantiate FPE integer",
+        ErrorCode::Fpe
+    );
+
+    // Copy the contents of the slice into the 32-array
+    let key: [u8; KEY_LENGTH] = ffi_unwrap!(
+        key_bytes.try_into(),
+        "key size is 32 bytes",
+        ErrorCode::Serialization
+    );
+
+    let output_str = if encrypt_flag {
+        ffi_unwrap!(
+            itg.encrypt_fixed_width(&key, tweak_bytes, &input_str),
+            "fpe encryption process",
+            ErrorCode::Encryption
+        )
+    } else {
+        ffi_unwrap!(
+            itg.decrypt_fixed_width(&key, tweak_bytes, &input_str),
+            "fpe decryption process",
+            ErrorCode::Decryption
+        )
+    };
+
+    ffi_write_bytes!("output_ptr", output_str.as_bytes(), output_ptr, output_len);
+}
+
+/// Encrypts a fixed-width numeric string using the FPE algorithm, preserving
+/// its leading zeros, and returns the encrypted value as an array of bytes.
+///
+/// # Arguments
+///
+/// * `output_ptr` - a pointer to the output buffer where the encrypted bytes
+///   will be written
+/// * `output_len` - a pointer to an integer that will be updated with the
+///   length of the encrypted bytes
+/// * `input_ptr` - a pointer to the numeric string to be encrypted; it must
+///   hold exactly `digits` digits
+/// * `radix` - the radix of the numeric string
+/// * `digits` - the number of digits in the numeric string
+/// * `key_ptr` - a pointer to the key buffer that will be used for encryption
+/// * `key_len` - the length of the key buffer
+/// * `tweak_ptr` - a pointer to the tweak buffer that will be used for
+///   encryption
+/// * `tweak_len` - the length of the tweak buffer
+///
+/// # Safety
+///
+/// This function is marked unsafe because it operates on raw pointers and
+/// performs unsafe memory operations.
+///
+/// # Returns
+///
+/// Returns 0 on success, an error code otherwise.
+#[no_mangle]
+pub unsafe extern "C" fn h_fpe_encrypt_fixed_width_integer(
+    output_ptr: *mut u8,
+    output_len: *mut i32,
+    input_ptr: *const i8,
+    radix: u32,
+    digits: u32,
+    key_ptr: *const i8,
+    key_len: i32,
+    tweak_ptr: *const i8,
+    tweak_len: i32,
+) -> i32 {
+    ffi_guard!({
+        fpe_fixed_width_integer(
+            output_ptr, output_len, input_ptr, radix, digits, key_ptr, key_len, tweak_ptr,
+            tweak_len, true,
+        )
+    })
+}
+
+/// Decrypts a fixed-width numeric string using the FPE algorithm, preserving
+/// its leading zeros, and returns the decrypted value as an array of bytes.
+///
+/// # Arguments
+///
+/// * `output_ptr` - a pointer to the output buffer where the decrypted bytes
+///   will be written
+/// * `output_len` - a pointer to an integer that will be updated with the
+///   length of the decrypted bytes
+/// * `input_ptr` - a pointer to the numeric string to be decrypted; it must
+///   hold exactly `digits` digits
+/// * `radix` - the radix of the numeric string
+/// * `digits` - the number of digits in the numeric string
+/// * `key_ptr` - a pointer to the key buffer that will be used for decryption
+/// * `key_len` - the length of the key buffer
+/// * `tweak_ptr` - a pointer to the tweak buffer that will be used for
+///   decryption
+/// * `tweak_len` - the length of the tweak buffer
+///
+/// # Safety
+///
+/// This function is marked unsafe because it operates on raw pointers and
+/// performs unsafe memory operations.
+///
+/// # Returns
+///
+/// Returns 0 on success, an error code otherwise.
+#[no_mangle]
+pub unsafe extern "C" fn h_fpe_decrypt_fixed_width_integer(
+    output_ptr: *mut u8,
+    output_len: *mut i32,
+    input_ptr: *const i8,
+    radix: u32,
+    digits: u32,
+    key_ptr: *const i8,
+    key_len: i32,
+    tweak_ptr: *const i8,
+    tweak_len: i32,
+) -> i32 {
+    ffi_guard!({
+        fpe_fixed_width_integer(
+            output_ptr, output_len, input_ptr, radix, digits, key_ptr, key_len, tweak_ptr,
+            tweak_len, false,
+        )
+    })
 }
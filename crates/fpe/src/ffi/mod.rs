@@ -1,4 +1,7 @@
 mod alphabet;
+mod context;
+mod credit_card;
+mod decimal;
 mod float;
 mod integer;
 
@@ -6,11 +6,23 @@ use rand_chacha::ChaCha20Rng;
 
 use super::integer::{h_fpe_decrypt_integer, h_fpe_encrypt_integer};
 use crate::{
-    core::{AnoError, KEY_LENGTH},
+    core::{is_luhn_valid, AnoError, KEY_LENGTH},
     ffi::{
-        alphabet::fpe,
+        alphabet::{fpe, h_fpe_minimum_plaintext_length, h_fpe_pseudonymize_preview},
+        context::{
+            h_fpe_create_context, h_fpe_decrypt_with_context, h_fpe_destroy_context,
+            h_fpe_encrypt_with_context,
+        },
+        credit_card::{h_fpe_decrypt_credit_card, h_fpe_encrypt_credit_card},
+        decimal::{
+            h_fpe_decrypt_decimal, h_fpe_decrypt_scaled_decimal, h_fpe_encrypt_decimal,
+            h_fpe_encrypt_scaled_decimal,
+        },
         float::{h_fpe_decrypt_float, h_fpe_encrypt_float},
-        integer::{h_fpe_decrypt_big_integer, h_fpe_encrypt_big_integer},
+        integer::{
+            h_fpe_decrypt_big_integer, h_fpe_decrypt_fixed_width_integer,
+            h_fpe_encrypt_big_integer, h_fpe_encrypt_fixed_width_integer,
+        },
     },
     get_alphabet,
 };
@@ -224,10 +236,88 @@ fn ffi_fpe_alphabet() -> Result<(), AnoError> {
         ]
         .iter()
         .for_each(|n| alphabet_check(n, "chinese", " -", ""));
+
+        ["やまだ たろう", "すずき はなこ"]
+            .iter()
+            .for_each(|n| alphabet_check(n, "hiragana", " -", ""));
+
+        ["ヤマダ タロウ", "スズキ ハナコ"]
+            .iter()
+            .for_each(|n| alphabet_check(n, "katakana", " -", ""));
+
+        ["山田 太郎", "鈴木 はなこ"]
+            .iter()
+            .for_each(|n| alphabet_check(n, "japanese", " -", ""));
+
+        ["김민준", "이서연 박지호"]
+            .iter()
+            .for_each(|n| alphabet_check(n, "hangul", " -", ""));
+
+        // composed alphabets
+        ["やまだ タロウ", "スズキ はなこ"]
+            .iter()
+            .for_each(|n| alphabet_check(n, "hiragana+katakana", " -", ""));
+        ["John Doe", "MalcolmX", "abcd"]
+            .iter()
+            .for_each(|n| alphabet_check(n, "alpha-0O1lI", " -l", ""));
+        ["john.doe@example.com", "MalcolmX"]
+            .iter()
+            .for_each(|n| alphabet_check(n, "alpha_lower+alpha_upper-lI", "l", "@."));
     }
     Ok(())
 }
 
+#[test]
+fn ffi_fpe_pseudonymize_preview() {
+    let template = CString::new("###-##-####").unwrap();
+    let alphabet_id = CString::new("numeric").unwrap();
+    let additional_characters = CString::new("").unwrap();
+    let seed = b"row 1";
+
+    let generate = || {
+        let mut output_bytes = vec![0u8; 11];
+        let mut output_len = output_bytes.len() as i32;
+        let ret = unsafe {
+            h_fpe_pseudonymize_preview(
+                output_bytes.as_mut_ptr(),
+                &mut output_len,
+                alphabet_id.as_ptr(),
+                template.as_ptr(),
+                seed.as_ptr().cast(),
+                seed.len() as i32,
+                additional_characters.as_ptr(),
+            )
+        };
+        assert_eq!(ret, 0, "{:?}", get_last_error());
+        String::from_utf8(output_bytes[..output_len as usize].to_vec()).unwrap()
+    };
+
+    let ssn = generate();
+    assert_eq!(ssn.len(), 11);
+    assert_eq!(ssn, generate());
+}
+
+#[test]
+fn ffi_fpe_minimum_plaintext_length() {
+    let minimum_plaintext_length = |alphabet_id: &str, additional_characters: &str| {
+        let alphabet_id = CString::new(alphabet_id).unwrap();
+        let additional_characters = CString::new(additional_characters).unwrap();
+        let mut min_length = 0;
+        let ret = unsafe {
+            h_fpe_minimum_plaintext_length(
+                &mut min_length,
+                alphabet_id.as_ptr(),
+                additional_characters.as_ptr(),
+            )
+        };
+        (ret == 0).then_some(min_length)
+    };
+
+    assert_eq!(minimum_plaintext_length("numeric", ""), Some(6));
+    assert_eq!(minimum_plaintext_length("numeric", "abcdef"), Some(5));
+    assert_eq!(minimum_plaintext_length("unknown", ""), None);
+}
+
 #[test]
 fn ffi_fpe_integer() {
     // FFI inputs
@@ -458,3 +548,235 @@ fn ffi_fpe_big_integer() {
         .iter()
         .for_each(|n| big_integer(n, 10, 6));
 }
+
+#[test]
+fn ffi_fpe_fixed_width_integer() {
+    let key = random_key();
+    let key_ptr = key.as_ptr().cast();
+    let key_len = key.len() as i32;
+    let tweak = random_key();
+    let tweak_ptr = tweak.as_ptr().cast();
+    let tweak_len = tweak.len() as i32;
+
+    for plaintext in ["000000", "000123", "012345"] {
+        unsafe {
+            let ciphertext = fpe_big_integer(
+                plaintext,
+                10,
+                6,
+                key_ptr,
+                key_len,
+                tweak_ptr,
+                tweak_len,
+                h_fpe_encrypt_fixed_width_integer,
+            );
+            assert_eq!(plaintext.len(), ciphertext.len());
+
+            let cleartext = fpe_big_integer(
+                &ciphertext,
+                10,
+                6,
+                key_ptr,
+                key_len,
+                tweak_ptr,
+                tweak_len,
+                h_fpe_decrypt_fixed_width_integer,
+            );
+            assert_eq!(plaintext, cleartext);
+        }
+    }
+}
+
+#[test]
+fn ffi_fpe_credit_card() {
+    let key = random_key();
+    let tweak = b"tweak";
+    let fpe_credit_card = |input: &str, encrypt_flag: bool| unsafe {
+        let mut output_bytes = vec![0u8; input.len()];
+        let mut output_len = output_bytes.len() as i32;
+        let input_cs = CString::new(input).unwrap();
+        let f = if encrypt_flag {
+            h_fpe_encrypt_credit_card
+        } else {
+            h_fpe_decrypt_credit_card
+        };
+        let ret = f(
+            output_bytes.as_mut_ptr(),
+            &mut output_len,
+            input_cs.as_ptr(),
+            6,
+            4,
+            key.as_ptr().cast(),
+            key.len() as i32,
+            tweak.as_ptr().cast(),
+            tweak.len() as i32,
+        );
+        assert_eq!(0, ret, "{:?}", get_last_error());
+        output_bytes.truncate(output_len as usize);
+        String::from_utf8(output_bytes).unwrap()
+    };
+
+    let plaintext = "4532-0151-1283-0366";
+    let ciphertext = fpe_credit_card(plaintext, true);
+    assert!(is_luhn_valid(&ciphertext));
+    assert_eq!(ciphertext[..7], plaintext[..7]);
+    assert_eq!(plaintext, fpe_credit_card(&ciphertext, false));
+}
+
+#[test]
+fn ffi_fpe_decimal() {
+    let key = random_key();
+    let tweak = b"tweak";
+    let fpe_decimal = |input: &str, encrypt_flag: bool| unsafe {
+        let mut output_bytes = vec![0u8; 16];
+        let mut output_len = output_bytes.len() as i32;
+        let input_cs = CString::new(input).unwrap();
+        let f = if encrypt_flag {
+            h_fpe_encrypt_decimal
+        } else {
+            h_fpe_decrypt_decimal
+        };
+        let ret = f(
+            output_bytes.as_mut_ptr(),
+            &mut output_len,
+            input_cs.as_ptr(),
+            9,
+            2,
+            key.as_ptr().cast(),
+            key.len() as i32,
+            tweak.as_ptr().cast(),
+            tweak.len() as i32,
+        );
+        assert_eq!(0, ret, "{:?}", get_last_error());
+        output_bytes.truncate(output_len as usize);
+        String::from_utf8(output_bytes).unwrap()
+    };
+    let fpe_scaled_decimal = |input: i128, encrypt_flag: bool| unsafe {
+        let mut output = 0_i128;
+        let f = if encrypt_flag {
+            h_fpe_encrypt_scaled_decimal
+        } else {
+            h_fpe_decrypt_scaled_decimal
+        };
+        let ret = f(
+            &mut output,
+            input,
+            9,
+            2,
+            key.as_ptr().cast(),
+            key.len() as i32,
+            tweak.as_ptr().cast(),
+            tweak.len() as i32,
+        );
+        assert_eq!(0, ret, "{:?}", get_last_error());
+        output
+    };
+
+    let ciphertext = fpe_decimal("-1234.56", true);
+    assert!(ciphertext.starts_with('-'));
+    assert_eq!("-1234.56", fpe_decimal(&ciphertext, false));
+
+    // Both forms give the same ciphertext.
+    let scaled_ciphertext = fpe_scaled_decimal(-123_456, true);
+    assert_eq!(
+        ciphertext,
+        format!(
+            "-{}.{:02}",
+            -scaled_ciphertext / 100,
+            -scaled_ciphertext % 100
+        )
+    );
+    assert_eq!(-123_456, fpe_scaled_decimal(scaled_ciphertext, false));
+}
+
+unsafe fn fpe_with_context(context_handle: i32, input_str: &str, encrypt_flag: bool) -> String {
+    let mut output_bytes = vec![0u8; 4 * input_str.len()];
+    let mut output_len = output_bytes.len() as i32;
+    let input_cs = CString::new(input_str).unwrap();
+
+    let ret = if encrypt_flag {
+        h_fpe_encrypt_with_context(
+            output_bytes.as_mut_ptr(),
+            &mut output_len,
+            context_handle,
+            input_cs.as_ptr(),
+        )
+    } else {
+        h_fpe_decrypt_with_context(
+            output_bytes.as_mut_ptr(),
+            &mut output_len,
+            context_handle,
+            input_cs.as_ptr(),
+        )
+    };
+    assert_eq!(0, ret, "{:?}", get_last_error());
+
+    output_bytes.truncate(output_len as usize);
+    String::from_utf8(output_bytes).unwrap()
+}
+
+#[test]
+fn ffi_fpe_context() {
+    let key = random_key();
+    let tweak = b"tweak";
+    let alphabet_cs = CString::new("alpha_numeric").unwrap();
+    let additional_characters_cs = CString::new("@").unwrap();
+
+    unsafe {
+        let mut context_handle = 0;
+        let ret = h_fpe_create_context(
+            &mut context_handle,
+            key.as_ptr().cast(),
+            key.len() as i32,
+            tweak.as_ptr().cast(),
+            tweak.len() as i32,
+            alphabet_cs.as_ptr(),
+            additional_characters_cs.as_ptr(),
+        );
+        assert_eq!(0, ret, "{:?}", get_last_error());
+
+        for plaintext in ["John Doe", "john.doe@cosmian.com"] {
+            let ciphertext = fpe_with_context(context_handle, plaintext, true);
+            assert_eq!(plaintext.len(), ciphertext.len());
+
+            // The context encrypts as the key, tweak and alphabet it holds.
+            let mut alphabet = get_alphabet("alpha_numeric").unwrap();
+            alphabet.extend_with("@");
+            assert_eq!(
+                ciphertext,
+                alphabet.encrypt(&key, tweak, plaintext).unwrap()
+            );
+
+            let cleartext = fpe_with_context(context_handle, &ciphertext, false);
+            assert_eq!(plaintext, cleartext);
+        }
+
+        assert_eq!(0, h_fpe_destroy_context(context_handle));
+        let mut output_bytes = vec![0u8; 8];
+        let mut output_len = output_bytes.len() as i32;
+        let input_cs = CString::new("John Doe").unwrap();
+        assert_ne!(
+            0,
+            h_fpe_encrypt_with_context(
+                output_bytes.as_mut_ptr(),
+                &mut output_len,
+                context_handle,
+                input_cs.as_ptr(),
+            )
+        );
+
+        // Keys of wrong size are rejected upon context creation.
+        assert_ne!(
+            0,
+            h_fpe_create_context(
+                &mut context_handle,
+                key.as_ptr().cast(),
+                16,
+                tweak.as_ptr().cast(),
+                tweak.len() as i32,
+                alphabet_cs.as_ptr(),
+                additional_characters_cs.as_ptr(),
+            )
+        );
+    }
+}
@@ -10,12 +10,38 @@ static ALPHABET_LIST: &[&str] = &[
     "alpha_numeric",
     "utf",
     "chinese",
+    "hiragana",
+    "katakana",
+    "japanese",
+    "hangul",
     "latin1sup",
     "latin1sup_alphanum",
 ];
 
+/// Instantiates the alphabet of the given id.
+///
+/// Predefined alphabets can be composed: `alpha_numeric+latin1sup` is the
+/// union of the given alphabets, and the characters following the first `-`
+/// are removed from it, e.g. `alpha_numeric-0O1l`.
 #[cfg(any(feature = "ffi", feature = "python", feature = "wasm"))]
 pub(crate) fn get_alphabet(alphabet_id: &str) -> Result<core::Alphabet, core::AnoError> {
+    let (ids, removed_characters) = match alphabet_id.split_once('-') {
+        Some((ids, removed_characters)) => (ids, Some(removed_characters)),
+        None => (alphabet_id, None),
+    };
+    let mut ids = ids.split('+');
+    let mut alphabet = get_predefined_alphabet(ids.next().unwrap_or_default())?;
+    for id in ids {
+        alphabet.extend_with_alphabet(&get_predefined_alphabet(id)?);
+    }
+    match removed_characters {
+        Some(characters) => alphabet.without(characters),
+        None => Ok(alphabet),
+    }
+}
+
+#[cfg(any(feature = "ffi", feature = "python", feature = "wasm"))]
+fn get_predefined_alphabet(alphabet_id: &str) -> Result<core::Alphabet, core::AnoError> {
     let alphabet = match alphabet_id {
         "numeric" => core::Alphabet::numeric(),
         "hexa_decimal" => core::Alphabet::hexa_decimal(),
@@ -25,12 +51,16 @@ pub(crate) fn get_alphabet(alphabet_id: &str) -> Result<core::Alphabet, core::An
         "alpha_numeric" => core::Alphabet::alpha_numeric(),
         "utf" => core::Alphabet::utf(),
         "chinese" => core::Alphabet::chinese(),
+        "hiragana" => core::Alphabet::hiragana(),
+        "katakana" => core::Alphabet::katakana(),
+        "japanese" => core::Alphabet::japanese(),
+        "hangul" => core::Alphabet::hangul(),
         "latin1sup" => core::Alphabet::latin1sup(),
         "latin1sup_alphanum" => core::Alphabet::latin1sup_alphanum(),
         _ => {
             return Err(core::AnoError::FPE(format!(
                 "Cannot instantiate from this id: {alphabet_id}. Possible values are \
-                 {ALPHABET_LIST:?}"
+                 {ALPHABET_LIST:?}, joined by `+`"
             )));
         }
     };
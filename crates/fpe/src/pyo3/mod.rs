@@ -1,16 +1,32 @@
 use pyo3::{pymodule, types::PyModule, PyResult, Python};
 
-use self::{py_alphabet::Alphabet, py_float::Float, py_integer::Integer};
+use self::{
+    py_alphabet::Alphabet,
+    py_credit_card::CreditCard,
+    py_decimal::Decimal,
+    py_error::{AlphabetMismatch, FpeError, InputTooShort, KeyLengthError},
+    py_float::Float,
+    py_integer::Integer,
+};
 
 mod py_alphabet;
+mod py_credit_card;
+mod py_decimal;
+mod py_error;
 mod py_float;
 mod py_integer;
 
 /// A Python module implemented in Rust.
 #[pymodule]
-fn cloudproof_fpe(_py: Python, m: &PyModule) -> PyResult<()> {
+fn cloudproof_fpe(py: Python, m: &PyModule) -> PyResult<()> {
     m.add_class::<Alphabet>()?;
     m.add_class::<Integer>()?;
     m.add_class::<Float>()?;
+    m.add_class::<CreditCard>()?;
+    m.add_class::<Decimal>()?;
+    m.add("FpeError", py.get_type::<FpeError>())?;
+    m.add("KeyLengthError", py.get_type::<KeyLengthError>())?;
+    m.add("AlphabetMismatch", py.get_type::<AlphabetMismatch>())?;
+    m.add("InputTooShort", py.get_type::<InputTooShort>())?;
     Ok(())
 }
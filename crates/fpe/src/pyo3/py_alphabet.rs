@@ -1,4 +1,7 @@
-use pyo3::{exceptions::PyException, prelude::*, types::PyString};
+use std::collections::BTreeSet;
+
+use pyo3::{prelude::*, types::PyString};
+use rayon::prelude::*;
 
 use crate::{core::Alphabet as AlphabetRust, get_alphabet};
 
@@ -23,18 +26,41 @@ impl Alphabet {
     /// * "alpha_numeric" - Alphanumeric alphabet
     /// * "utf" - UTF-8 alphabet
     /// * "chinese" - Chinese alphabet
+    /// * "hiragana" - Hiragana alphabet
+    /// * "katakana" - Katakana alphabet
+    /// * "japanese" - Hiragana, Katakana and Kanji alphabet
+    /// * "hangul" - Hangul syllables alphabet
     /// * "latin1sup" - Latin1 supplement alphabet
     /// * "latin1sup_alphanum" - Latin1 supplement alphanumeric alphabet
     ///
+    /// These ids can be composed: "alpha_numeric+latin1sup" is the union of
+    /// the given alphabets, and the characters following the first "-" are
+    /// removed from it, e.g. "alpha_numeric-0O1l".
+    ///
     /// # Errors
     ///
     /// This function will return an error if the alphabet_type is unknown or
     /// unsupported.
     #[new]
     fn new(alphabet_id: &str) -> PyResult<Self> {
-        Ok(Self(get_alphabet(alphabet_id).map_err(|e| {
-            PyException::new_err(format!("Instantiation failed, unknown type: {e:?}"))
-        })?))
+        Ok(Self(get_alphabet(alphabet_id)?))
+    }
+
+    /// Creates a new alphabet from inclusive ranges of Unicode code points.
+    ///
+    /// # Arguments
+    ///
+    /// * `ranges` - a list of `(start, end)` code points.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if a range is empty or if the
+    /// alphabet does not contain between 2 and 2^16 characters.
+    #[staticmethod]
+    pub fn from_unicode_ranges(ranges: Vec<(u32, u32)>) -> PyResult<Self> {
+        AlphabetRust::from_unicode_ranges(&ranges)
+            .map(Self)
+            .map_err(PyErr::from)
     }
 
     /// Encrypts a given plaintext using the specified key and tweak using the
@@ -63,7 +89,7 @@ impl Alphabet {
     ) -> PyResult<Py<PyString>> {
         match self.0.encrypt(&key, &tweak, &plaintext) {
             Ok(ciphertext) => Ok(PyString::new(py, &ciphertext).into()),
-            Err(e) => Err(PyException::new_err(e.to_string())),
+            Err(e) => Err(e.into()),
         }
     }
 
@@ -90,10 +116,105 @@ impl Alphabet {
     ) -> PyResult<Py<PyString>> {
         match self.0.decrypt(&key, &tweak, &ciphertext) {
             Ok(cleartext) => Ok(PyString::new(py, &cleartext).into()),
-            Err(e) => Err(PyException::new_err(e.to_string())),
+            Err(e) => Err(e.into()),
         }
     }
 
+    /// Encrypts all the given plaintexts using the specified key and tweak.
+    ///
+    /// The GIL is released and the plaintexts are encrypted in parallel,
+    /// which makes it suitable to encrypt a whole column at once.
+    ///
+    /// # Arguments
+    ///
+    /// * `key` - The key bytes used for encryption.
+    /// * `tweak` - The tweak bytes used for encryption.
+    /// * `plaintexts` - The plaintexts to encrypt, as a list of strings or a
+    ///   numpy array.
+    ///
+    /// # Returns
+    ///
+    /// Returns the ciphertexts in the same order as the plaintexts, or a
+    /// PyException error if any encryption failed.
+    pub fn encrypt_series(
+        &self,
+        key: Vec<u8>,
+        tweak: Vec<u8>,
+        plaintexts: Vec<String>,
+        py: Python,
+    ) -> PyResult<Vec<String>> {
+        py.allow_threads(|| {
+            plaintexts
+                .par_iter()
+                .map(|plaintext| self.0.encrypt(&key, &tweak, plaintext))
+                .collect::<Result<Vec<_>, _>>()
+        })
+        .map_err(PyErr::from)
+    }
+
+    /// Decrypts all the given ciphertexts using the specified key and tweak.
+    ///
+    /// The GIL is released and the ciphertexts are decrypted in parallel.
+    ///
+    /// # Arguments
+    ///
+    /// * `key` - The key bytes used for decryption.
+    /// * `tweak` - The tweak bytes used for decryption.
+    /// * `ciphertexts` - The ciphertexts to decrypt, as a list of strings or a
+    ///   numpy array.
+    ///
+    /// # Returns
+    ///
+    /// Returns the cleartexts in the same order as the ciphertexts, or a
+    /// PyException error if any decryption failed.
+    pub fn decrypt_series(
+        &self,
+        key: Vec<u8>,
+        tweak: Vec<u8>,
+        ciphertexts: Vec<String>,
+        py: Python,
+    ) -> PyResult<Vec<String>> {
+        py.allow_threads(|| {
+            ciphertexts
+                .par_iter()
+                .map(|ciphertext| self.0.decrypt(&key, &tweak, ciphertext))
+                .collect::<Result<Vec<_>, _>>()
+        })
+        .map_err(PyErr::from)
+    }
+
+    /// Returns the set of characters of the given input that are not part of
+    /// the alphabet and would be left unencrypted.
+    ///
+    /// # Arguments
+    ///
+    /// * `input` - the string to check against the alphabet.
+    pub fn validate(&self, input: &str) -> BTreeSet<char> {
+        self.0.validate(input)
+    }
+
+    /// Returns the minimum number of alphabet characters of the inputs for FPE
+    /// to be secure: shorter inputs are rejected with an `InputTooShort`
+    /// error.
+    pub fn minimum_plaintext_length(&self) -> usize {
+        self.0.minimum_plaintext_length()
+    }
+
+    /// Generates a fake value following the given format template, for
+    /// testing purposes: each `#` of the template is replaced by a character
+    /// of the alphabet.
+    ///
+    /// # Arguments
+    ///
+    /// * `template` - the format of the value to generate.
+    /// * `seed` - the seed of the generation (e.g. a row number): the same
+    ///   seed always generates the same value.
+    pub fn pseudonymize_preview(&self, template: &str, seed: Vec<u8>) -> PyResult<String> {
+        self.0
+            .pseudonymize_preview(template, &seed)
+            .map_err(PyErr::from)
+    }
+
     /// Extends the given object with additional characters.
     ///
     /// # Arguments
@@ -105,4 +226,26 @@ impl Alphabet {
     pub fn extend_with(&mut self, additional_characters: String) {
         self.0.extend_with(&additional_characters);
     }
+
+    /// Extends the alphabet with the characters of another alphabet.
+    ///
+    /// # Arguments
+    ///
+    /// * `other` - the alphabet whose characters to add.
+    pub fn extend_with_alphabet(&mut self, other: &Self) {
+        self.0.extend_with_alphabet(&other.0);
+    }
+
+    /// Returns a copy of the alphabet without the given characters.
+    ///
+    /// # Arguments
+    ///
+    /// * `characters` - the characters to remove from the alphabet.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if less than 2 characters remain.
+    pub fn without(&self, characters: &str) -> PyResult<Self> {
+        self.0.without(characters).map(Self).map_err(PyErr::from)
+    }
 }
@@ -0,0 +1,73 @@
+use pyo3::prelude::*;
+
+use crate::core::{
+    AnoError, CreditCard as CreditCardRust, DEFAULT_KEEP_FIRST, DEFAULT_KEEP_LAST, KEY_LENGTH,
+};
+
+#[pyclass]
+pub struct CreditCard(CreditCardRust);
+
+#[pymethods]
+impl CreditCard {
+    #[new]
+    #[pyo3(signature = (keep_first = DEFAULT_KEEP_FIRST, keep_last = DEFAULT_KEEP_LAST))]
+    fn new(keep_first: usize, keep_last: usize) -> PyResult<Self> {
+        Ok(Self(CreditCardRust::instantiate(keep_first, keep_last)?))
+    }
+
+    fn encrypt_decrypt(
+        &self,
+        key: Vec<u8>,
+        tweak: Vec<u8>,
+        input: &str,
+        encrypt_flag: bool,
+    ) -> PyResult<String> {
+        let key: [u8; KEY_LENGTH] = key
+            .try_into()
+            .map_err(|key: Vec<u8>| AnoError::KeySize(key.len(), KEY_LENGTH))?;
+
+        let output = if encrypt_flag {
+            self.0.encrypt(&key, &tweak, input)
+        } else {
+            self.0.decrypt(&key, &tweak, input)
+        };
+        output.map_err(PyErr::from)
+    }
+
+    /// Encrypts the middle digits of the given credit card number using the
+    /// specified key and tweak. The Luhn check digit is recomputed.
+    ///
+    /// # Arguments
+    ///
+    /// * `key` - A vector of bytes representing the key used for encryption.
+    /// * `tweak` - A vector of bytes representing the tweak used for
+    ///   encryption.
+    /// * `plaintext` - The card number to encrypt, which may contain spaces
+    ///   and dashes.
+    ///
+    /// # Returns
+    ///
+    /// A `PyResult` containing the encrypted card number. Returns an error if
+    /// the encryption fails.
+    pub fn encrypt(&self, key: Vec<u8>, tweak: Vec<u8>, plaintext: &str) -> PyResult<String> {
+        self.encrypt_decrypt(key, tweak, plaintext, true)
+    }
+
+    /// Decrypts the middle digits of the given credit card number using the
+    /// specified key and tweak. The Luhn check digit is recomputed.
+    ///
+    /// # Arguments
+    ///
+    /// * `key` - A vector of bytes representing the key used for decryption.
+    /// * `tweak` - A vector of bytes representing the tweak used for
+    ///   decryption.
+    /// * `ciphertext` - The card number to decrypt.
+    ///
+    /// # Returns
+    ///
+    /// A `PyResult` containing the decrypted card number. Returns an error if
+    /// the decryption fails.
+    pub fn decrypt(&self, key: Vec<u8>, tweak: Vec<u8>, ciphertext: &str) -> PyResult<String> {
+        self.encrypt_decrypt(key, tweak, ciphertext, false)
+    }
+}
@@ -0,0 +1,131 @@
+use pyo3::prelude::*;
+
+use crate::core::{AnoError, Decimal as DecimalRust, KEY_LENGTH};
+
+#[pyclass]
+pub struct Decimal(DecimalRust);
+
+impl Decimal {
+    fn key(key: Vec<u8>) -> PyResult<[u8; KEY_LENGTH]> {
+        key.try_into()
+            .map_err(|key: Vec<u8>| AnoError::KeySize(key.len(), KEY_LENGTH).into())
+    }
+}
+
+#[pymethods]
+impl Decimal {
+    #[new]
+    fn new(integer_digits: usize, fraction_digits: usize) -> PyResult<Self> {
+        Ok(Self(DecimalRust::instantiate(
+            integer_digits,
+            fraction_digits,
+        )?))
+    }
+
+    fn encrypt_decrypt(
+        &self,
+        key: Vec<u8>,
+        tweak: Vec<u8>,
+        input: i128,
+        encrypt_flag: bool,
+    ) -> PyResult<i128> {
+        let key = Self::key(key)?;
+        let output = if encrypt_flag {
+            self.0.encrypt(&key, &tweak, input)
+        } else {
+            self.0.decrypt(&key, &tweak, input)
+        };
+        output.map_err(PyErr::from)
+    }
+
+    fn encrypt_decrypt_str(
+        &self,
+        key: Vec<u8>,
+        tweak: Vec<u8>,
+        input: &str,
+        encrypt_flag: bool,
+    ) -> PyResult<String> {
+        let key = Self::key(key)?;
+        let output = if encrypt_flag {
+            self.0.encrypt_str(&key, &tweak, input)
+        } else {
+            self.0.decrypt_str(&key, &tweak, input)
+        };
+        output.map_err(PyErr::from)
+    }
+
+    /// Encrypts the given decimal scaled by `10^fraction_digits` using the
+    /// specified key and tweak. The sign is preserved.
+    ///
+    /// # Arguments
+    ///
+    /// * `key` - A vector of bytes representing the key used for encryption.
+    /// * `tweak` - A vector of bytes representing the tweak used for
+    ///   encryption.
+    /// * `plaintext` - The scaled decimal to encrypt (e.g. `-123456` for
+    ///   `-1234.56` with 2 fraction digits).
+    ///
+    /// # Returns
+    ///
+    /// A `PyResult` containing the encrypted scaled decimal. Returns an error
+    /// if the encryption fails.
+    pub fn encrypt(&self, key: Vec<u8>, tweak: Vec<u8>, plaintext: i128) -> PyResult<i128> {
+        self.encrypt_decrypt(key, tweak, plaintext, true)
+    }
+
+    /// Decrypts the given decimal scaled by `10^fraction_digits` using the
+    /// specified key and tweak. The sign is preserved.
+    ///
+    /// # Arguments
+    ///
+    /// * `key` - A vector of bytes representing the key used for decryption.
+    /// * `tweak` - A vector of bytes representing the tweak used for
+    ///   decryption.
+    /// * `ciphertext` - The scaled decimal to decrypt.
+    ///
+    /// # Returns
+    ///
+    /// A `PyResult` containing the decrypted scaled decimal. Returns an error
+    /// if the decryption fails.
+    pub fn decrypt(&self, key: Vec<u8>, tweak: Vec<u8>, ciphertext: i128) -> PyResult<i128> {
+        self.encrypt_decrypt(key, tweak, ciphertext, false)
+    }
+
+    /// Encrypts the given decimal string using the specified key and tweak.
+    /// The sign is preserved and the ciphertext has exactly `fraction_digits`
+    /// fraction digits.
+    ///
+    /// # Arguments
+    ///
+    /// * `key` - A vector of bytes representing the key used for encryption.
+    /// * `tweak` - A vector of bytes representing the tweak used for
+    ///   encryption.
+    /// * `plaintext` - The decimal to encrypt (e.g. `"-1234.56"`).
+    ///
+    /// # Returns
+    ///
+    /// A `PyResult` containing the encrypted decimal. Returns an error if the
+    /// encryption fails.
+    pub fn encrypt_str(&self, key: Vec<u8>, tweak: Vec<u8>, plaintext: &str) -> PyResult<String> {
+        self.encrypt_decrypt_str(key, tweak, plaintext, true)
+    }
+
+    /// Decrypts the given decimal string using the specified key and tweak.
+    /// The sign is preserved and the plaintext has exactly `fraction_digits`
+    /// fraction digits.
+    ///
+    /// # Arguments
+    ///
+    /// * `key` - A vector of bytes representing the key used for decryption.
+    /// * `tweak` - A vector of bytes representing the tweak used for
+    ///   decryption.
+    /// * `ciphertext` - The decimal to decrypt.
+    ///
+    /// # Returns
+    ///
+    /// A `PyResult` containing the decrypted decimal. Returns an error if the
+    /// decryption fails.
+    pub fn decrypt_str(&self, key: Vec<u8>, tweak: Vec<u8>, ciphertext: &str) -> PyResult<String> {
+        self.encrypt_decrypt_str(key, tweak, ciphertext, false)
+    }
+}
@@ -0,0 +1,61 @@
+use pyo3::{create_exception, exceptions::PyException, prelude::*};
+
+use crate::core::AnoError;
+
+create_exception!(
+    cloudproof_fpe,
+    FpeError,
+    PyException,
+    "Base class of the errors raised by the FPE methods."
+);
+create_exception!(
+    cloudproof_fpe,
+    KeyLengthError,
+    FpeError,
+    "The key does not have the expected length, given in `expected`."
+);
+create_exception!(
+    cloudproof_fpe,
+    AlphabetMismatch,
+    FpeError,
+    "The input contains characters that are not part of the alphabet, given in `chars`."
+);
+create_exception!(
+    cloudproof_fpe,
+    InputTooShort,
+    FpeError,
+    "The input has fewer alphabet characters than the minimum required for FPE to be secure, \
+     given in `min`."
+);
+
+impl From<AnoError> for PyErr {
+    fn from(e: AnoError) -> Self {
+        let message = e.to_string();
+        Python::with_gil(|py| {
+            let (err, attributes) = match e {
+                AnoError::KeySize(length, expected) => (
+                    KeyLengthError::new_err(message),
+                    vec![
+                        ("length", length.into_py(py)),
+                        ("expected", expected.into_py(py)),
+                    ],
+                ),
+                AnoError::AlphabetMismatch(chars) => (
+                    AlphabetMismatch::new_err(message),
+                    vec![("chars", chars.into_py(py))],
+                ),
+                AnoError::InputTooShort { length, min, .. } => (
+                    InputTooShort::new_err(message),
+                    vec![("length", length.into_py(py)), ("min", min.into_py(py))],
+                ),
+                _ => (FpeError::new_err(message), vec![]),
+            };
+            for (name, value) in attributes {
+                if let Err(e) = err.value(py).setattr(name, value) {
+                    return e;
+                }
+            }
+            err
+        })
+    }
+}
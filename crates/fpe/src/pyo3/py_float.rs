@@ -1,6 +1,6 @@
-use pyo3::{exceptions::PyException, prelude::*};
+use pyo3::prelude::*;
 
-use crate::core::{Float as FloatRust, KEY_LENGTH};
+use crate::core::{AnoError, Float as FloatRust, KEY_LENGTH};
 
 #[pyclass]
 pub struct Float(FloatRust);
@@ -9,12 +9,7 @@ pub struct Float(FloatRust);
 impl Float {
     #[new]
     fn new() -> PyResult<Self> {
-        match FloatRust::instantiate() {
-            Ok(itg) => Ok(Self(itg)),
-            Err(e) => Err(PyException::new_err(format!(
-                "FPE Float Instantiation failed: {e:?}"
-            ))),
-        }
+        Ok(Self(FloatRust::instantiate()?))
     }
 
     fn encrypt_decrypt(
@@ -25,11 +20,7 @@ impl Float {
         encrypt_flag: bool,
     ) -> PyResult<f64> {
         if key.len() != KEY_LENGTH {
-            return Err(PyException::new_err(format!(
-                "FPE Float error: key length incorrect: {}, expected {}",
-                key.len(),
-                KEY_LENGTH
-            )));
+            return Err(AnoError::KeySize(key.len(), KEY_LENGTH).into());
         }
         let mut k: [u8; 32] = [0; 32];
         k.copy_from_slice(&key);
@@ -41,7 +32,7 @@ impl Float {
         };
         match output {
             Ok(ciphertext) => Ok(ciphertext),
-            Err(e) => Err(PyException::new_err(e.to_string())),
+            Err(e) => Err(e.into()),
         }
     }
 
@@ -2,7 +2,7 @@ use num_bigint::BigUint;
 use num_traits::Num;
 use pyo3::{exceptions::PyException, prelude::*, types::PyString};
 
-use crate::core::{Integer as IntegerRust, KEY_LENGTH};
+use crate::core::{AnoError, Integer as IntegerRust, KEY_LENGTH};
 
 #[pyclass]
 pub struct Integer(IntegerRust);
@@ -11,12 +11,7 @@ pub struct Integer(IntegerRust);
 impl Integer {
     #[new]
     fn new(radix: u32, digits: usize) -> PyResult<Self> {
-        match IntegerRust::instantiate(radix, digits) {
-            Ok(itg) => Ok(Self(itg)),
-            Err(e) => Err(PyException::new_err(format!(
-                "FPE Integer Instantiation failed: {e:?}"
-            ))),
-        }
+        Ok(Self(IntegerRust::instantiate(radix, digits)?))
     }
 
     fn encrypt_decrypt(
@@ -27,11 +22,7 @@ impl Integer {
         encrypt_flag: bool,
     ) -> PyResult<u64> {
         if key.len() != KEY_LENGTH {
-            return Err(PyException::new_err(format!(
-                "FPE Integer error: key length incorrect: {}, expected {}",
-                key.len(),
-                KEY_LENGTH
-            )));
+            return Err(AnoError::KeySize(key.len(), KEY_LENGTH).into());
         }
         let mut k: [u8; 32] = [0; 32];
         k.copy_from_slice(&key);
@@ -43,7 +34,7 @@ impl Integer {
         };
         match output {
             Ok(ciphertext) => Ok(ciphertext),
-            Err(e) => Err(PyException::new_err(e.to_string())),
+            Err(e) => Err(e.into()),
         }
     }
 
@@ -56,11 +47,7 @@ impl Integer {
         py: Python,
     ) -> PyResult<Py<PyString>> {
         if key.len() != KEY_LENGTH {
-            return Err(PyException::new_err(format!(
-                "FPE Integer error: key length incorrect: {}, expected {}",
-                key.len(),
-                KEY_LENGTH
-            )));
+            return Err(AnoError::KeySize(key.len(), KEY_LENGTH).into());
         }
         let mut k: [u8; 32] = [0; 32];
         k.copy_from_slice(&key);
@@ -78,10 +65,31 @@ impl Integer {
         };
         match output {
             Ok(ciphertext) => Ok(PyString::new(py, &ciphertext.to_str_radix(self.0.radix)).into()),
-            Err(e) => Err(PyException::new_err(e.to_string())),
+            Err(e) => Err(e.into()),
         }
     }
 
+    fn encrypt_decrypt_fixed_width(
+        &self,
+        key: Vec<u8>,
+        tweak: Vec<u8>,
+        input: &str,
+        encrypt_flag: bool,
+    ) -> PyResult<String> {
+        if key.len() != KEY_LENGTH {
+            return Err(AnoError::KeySize(key.len(), KEY_LENGTH).into());
+        }
+        let mut k: [u8; 32] = [0; 32];
+        k.copy_from_slice(&key);
+
+        let output = if encrypt_flag {
+            self.0.encrypt_fixed_width(&k, &tweak, input)
+        } else {
+            self.0.decrypt_fixed_width(&k, &tweak, input)
+        };
+        output.map_err(PyErr::from)
+    }
+
     /// Encrypts a 64-bit plaintext value using the specified key and tweak.
     ///
     /// # Arguments
@@ -167,4 +175,50 @@ impl Integer {
     ) -> PyResult<Py<PyString>> {
         self.encrypt_decrypt_big(key, tweak, ciphertext, false, py)
     }
+
+    /// Encrypts the given fixed-width numeric string using the specified key
+    /// and tweak, preserving its leading zeros.
+    ///
+    /// # Arguments
+    ///
+    /// * `key` - A vector of bytes representing the key used for encryption.
+    /// * `tweak` - A vector of bytes representing the tweak used for
+    ///   encryption.
+    /// * `plaintext` - A string of exactly `digits` digits to encrypt.
+    ///
+    /// # Returns
+    ///
+    /// A `PyResult` containing the ciphertext, which has as many digits as
+    /// the plaintext. Returns an error if the encryption fails.
+    pub fn encrypt_fixed_width(
+        &self,
+        key: Vec<u8>,
+        tweak: Vec<u8>,
+        plaintext: &str,
+    ) -> PyResult<String> {
+        self.encrypt_decrypt_fixed_width(key, tweak, plaintext, true)
+    }
+
+    /// Decrypts the given fixed-width numeric string using the specified key
+    /// and tweak, preserving its leading zeros.
+    ///
+    /// # Arguments
+    ///
+    /// * `key` - A vector of bytes representing the key used for decryption.
+    /// * `tweak` - A vector of bytes representing the tweak used for
+    ///   decryption.
+    /// * `ciphertext` - A string of exactly `digits` digits to decrypt.
+    ///
+    /// # Returns
+    ///
+    /// A `PyResult` containing the plaintext, which has as many digits as the
+    /// ciphertext. Returns an error if the decryption fails.
+    pub fn decrypt_fixed_width(
+        &self,
+        key: Vec<u8>,
+        tweak: Vec<u8>,
+        ciphertext: &str,
+    ) -> PyResult<String> {
+        self.encrypt_decrypt_fixed_width(key, tweak, ciphertext, false)
+    }
 }
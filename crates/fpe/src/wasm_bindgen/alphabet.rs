@@ -10,8 +10,7 @@ fn fpe(
     additional_chars: &str,
     encrypt_flag: bool,
 ) -> Result<String, JsValue> {
-    let mut alphabet =
-        get_alphabet(alphabet_id).map_err(|e| JsValue::from_str(&format!("{e:?}")))?;
+    let mut alphabet = get_alphabet(alphabet_id)?;
 
     alphabet.extend_with(additional_chars);
 
@@ -20,7 +19,7 @@ fn fpe(
     } else {
         alphabet.decrypt(&key, &tweak, input)
     };
-    output.map_err(|e| JsValue::from_str(&format!("{e:?}")))
+    output.map_err(JsValue::from)
 }
 
 #[wasm_bindgen]
@@ -44,3 +43,47 @@ pub fn webassembly_fpe_decrypt_alphabet(
 ) -> Result<String, JsValue> {
     fpe(ciphertext, alphabet_id, key, tweak, additional_chars, false)
 }
+
+/// Returns the characters of the given input that are not part of the given
+/// alphabet, sorted and without duplicates.
+#[wasm_bindgen]
+pub fn webassembly_fpe_validate_alphabet(
+    input: &str,
+    alphabet_id: &str,
+    additional_chars: &str,
+) -> Result<String, JsValue> {
+    let mut alphabet = get_alphabet(alphabet_id)?;
+    alphabet.extend_with(additional_chars);
+    Ok(alphabet.validate(input).into_iter().collect())
+}
+
+/// Returns the minimum number of alphabet characters of the inputs for FPE to
+/// be secure using the given alphabet: shorter inputs are rejected with an
+/// `InputTooShort` error.
+#[wasm_bindgen]
+pub fn webassembly_fpe_minimum_plaintext_length(
+    alphabet_id: &str,
+    additional_chars: &str,
+) -> Result<usize, JsValue> {
+    let mut alphabet = get_alphabet(alphabet_id)?;
+    alphabet.extend_with(additional_chars);
+    Ok(alphabet.minimum_plaintext_length())
+}
+
+/// Generates a fake value following the given format template using the given
+/// alphabet, for testing purposes: each `#` of the template is replaced by a
+/// character of the alphabet. The value only depends on the template and on
+/// the seed.
+#[wasm_bindgen]
+pub fn webassembly_fpe_pseudonymize_preview(
+    template: &str,
+    alphabet_id: &str,
+    seed: Vec<u8>,
+    additional_chars: &str,
+) -> Result<String, JsValue> {
+    let mut alphabet = get_alphabet(alphabet_id)?;
+    alphabet.extend_with(additional_chars);
+    alphabet
+        .pseudonymize_preview(template, &seed)
+        .map_err(JsValue::from)
+}
@@ -0,0 +1,133 @@
+//! Batch FPE of strings, for Web Workers.
+//!
+//! Creating a `JsValue` per string dominates the cost of encrypting large
+//! columns. The batch functions instead take a single buffer holding all the
+//! strings and return a buffer in the same framing:
+//!
+//! ```txt
+//! batch = (u32_le(string.len()) || UTF-8 string) * n_strings
+//! ```
+//!
+//! The underlying `ArrayBuffer` of both the given and the returned
+//! `Uint8Array` can be transferred to and from a worker without copy.
+
+use js_sys::Reflect;
+use wasm_bindgen::{prelude::wasm_bindgen, JsValue};
+
+use crate::{core::AnoError, get_alphabet};
+
+/// Size of the length prefix of each string of a batch.
+const LENGTH_PREFIX_SIZE: usize = 4;
+
+/// Splits the given batch into its strings.
+fn read_batch(batch: &[u8]) -> Result<Vec<&str>, AnoError> {
+    let mut strings = Vec::new();
+    let mut rest = batch;
+    while !rest.is_empty() {
+        let (prefix, tail) = rest
+            .split_first_chunk::<LENGTH_PREFIX_SIZE>()
+            .ok_or_else(|| {
+                AnoError::ConversionError(format!(
+                    "batch: truncated length prefix of string {}",
+                    strings.len()
+                ))
+            })?;
+        let length = usize::try_from(u32::from_le_bytes(*prefix))?;
+        if tail.len() < length {
+            return Err(AnoError::ConversionError(format!(
+                "batch: string {} has length {length} but only {} bytes remain",
+                strings.len(),
+                tail.len()
+            )));
+        }
+        let (string, tail) = tail.split_at(length);
+        strings.push(std::str::from_utf8(string).map_err(|e| {
+            AnoError::ConversionError(format!(
+                "batch: string {} is not valid UTF-8: {e}",
+                strings.len()
+            ))
+        })?);
+        rest = tail;
+    }
+    Ok(strings)
+}
+
+/// Appends the given string to the batch.
+fn write_to_batch(batch: &mut Vec<u8>, string: &str) -> Result<(), AnoError> {
+    batch.extend_from_slice(&u32::try_from(string.len())?.to_le_bytes());
+    batch.extend_from_slice(string.as_bytes());
+    Ok(())
+}
+
+/// Converts the error of the string at the given index of a batch into a JS
+/// `Error` holding this index as its `index` property.
+fn batch_error(e: AnoError, index: usize) -> JsValue {
+    let error = JsValue::from(e);
+    // Setting a property of a fresh `Error` object cannot fail.
+    let _ = Reflect::set(&error, &JsValue::from_str("index"), &index.into());
+    error
+}
+
+fn fpe_batch(
+    batch: &[u8],
+    alphabet_id: &str,
+    key: &[u8],
+    tweak: &[u8],
+    additional_chars: &str,
+    encrypt_flag: bool,
+) -> Result<Vec<u8>, JsValue> {
+    let mut alphabet = get_alphabet(alphabet_id)?;
+    alphabet.extend_with(additional_chars);
+
+    let inputs = read_batch(batch)?;
+    let mut output = Vec::with_capacity(batch.len());
+    for (index, input) in inputs.into_iter().enumerate() {
+        let result = if encrypt_flag {
+            alphabet.encrypt(key, tweak, input)
+        } else {
+            alphabet.decrypt(key, tweak, input)
+        }
+        .map_err(|e| batch_error(e, index))?;
+        write_to_batch(&mut output, &result)?;
+    }
+    Ok(output)
+}
+
+/// Encrypts the strings of the given batch using the given alphabet.
+///
+/// Returns the ciphertexts in the same framing and order. If a string cannot
+/// be encrypted, the error thrown holds its position in the batch as its
+/// `index` property.
+#[wasm_bindgen]
+pub fn webassembly_fpe_encrypt_alphabet_batch(
+    plaintexts: &[u8],
+    alphabet_id: &str,
+    key: &[u8],
+    tweak: &[u8],
+    additional_chars: &str,
+) -> Result<Vec<u8>, JsValue> {
+    fpe_batch(plaintexts, alphabet_id, key, tweak, additional_chars, true)
+}
+
+/// Decrypts the strings of the given batch using the given alphabet.
+///
+/// Returns the plaintexts in the same framing and order. If a string cannot
+/// be decrypted, the error thrown holds its position in the batch as its
+/// `index` property.
+#[wasm_bindgen]
+pub fn webassembly_fpe_decrypt_alphabet_batch(
+    ciphertexts: &[u8],
+    alphabet_id: &str,
+    key: &[u8],
+    tweak: &[u8],
+    additional_chars: &str,
+) -> Result<Vec<u8>, JsValue> {
+    fpe_batch(
+        ciphertexts,
+        alphabet_id,
+        key,
+        tweak,
+        additional_chars,
+        false,
+    )
+}
@@ -0,0 +1,55 @@
+use wasm_bindgen::{prelude::wasm_bindgen, JsValue};
+
+use crate::core::{AnoError, CreditCard, KEY_LENGTH};
+
+fn fpe(
+    input: &str,
+    keep_first: usize,
+    keep_last: usize,
+    key: Vec<u8>,
+    tweak: Vec<u8>,
+    encrypt_flag: bool,
+) -> Result<String, JsValue> {
+    // Copy the key bytes into a 32-byte array
+    let k: [u8; KEY_LENGTH] = key
+        .try_into()
+        .map_err(|key: Vec<u8>| AnoError::KeySize(key.len(), KEY_LENGTH))?;
+
+    let credit_card = CreditCard::instantiate(keep_first, keep_last)?;
+
+    let result = if encrypt_flag {
+        credit_card.encrypt(&k, &tweak, input)
+    } else {
+        credit_card.decrypt(&k, &tweak, input)
+    };
+
+    result.map_err(JsValue::from)
+}
+
+/// Encrypts the middle digits of the given credit card number, keeping its
+/// first `keep_first` and last `keep_last` digits and recomputing its Luhn
+/// check digit.
+#[wasm_bindgen]
+pub fn webassembly_fpe_encrypt_credit_card(
+    input: &str,
+    keep_first: usize,
+    keep_last: usize,
+    key: Vec<u8>,
+    tweak: Vec<u8>,
+) -> Result<String, JsValue> {
+    fpe(input, keep_first, keep_last, key, tweak, true)
+}
+
+/// Decrypts the middle digits of the given credit card number, keeping its
+/// first `keep_first` and last `keep_last` digits and recomputing its Luhn
+/// check digit.
+#[wasm_bindgen]
+pub fn webassembly_fpe_decrypt_credit_card(
+    input: &str,
+    keep_first: usize,
+    keep_last: usize,
+    key: Vec<u8>,
+    tweak: Vec<u8>,
+) -> Result<String, JsValue> {
+    fpe(input, keep_first, keep_last, key, tweak, false)
+}
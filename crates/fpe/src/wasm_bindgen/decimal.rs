@@ -0,0 +1,57 @@
+use wasm_bindgen::{prelude::wasm_bindgen, JsValue};
+
+use crate::core::{AnoError, Decimal, KEY_LENGTH};
+
+fn fpe(
+    input: &str,
+    integer_digits: usize,
+    fraction_digits: usize,
+    key: Vec<u8>,
+    tweak: Vec<u8>,
+    encrypt_flag: bool,
+) -> Result<String, JsValue> {
+    // Copy the key bytes into a 32-byte array
+    let k: [u8; KEY_LENGTH] = key
+        .try_into()
+        .map_err(|key: Vec<u8>| AnoError::KeySize(key.len(), KEY_LENGTH))?;
+
+    let decimal = Decimal::instantiate(integer_digits, fraction_digits)?;
+
+    let result = if encrypt_flag {
+        decimal.encrypt_str(&k, &tweak, input)
+    } else {
+        decimal.decrypt_str(&k, &tweak, input)
+    };
+
+    result.map_err(JsValue::from)
+}
+
+/// Encrypts the given decimal number (e.g. `"-1234.56"`) with
+/// `integer_digits` integer digits and `fraction_digits` fraction digits,
+/// preserving its sign and its scale.
+///
+/// Decimals are passed as strings since JavaScript numbers cannot represent
+/// all the decimals exactly.
+#[wasm_bindgen]
+pub fn webassembly_fpe_encrypt_decimal(
+    input: &str,
+    integer_digits: usize,
+    fraction_digits: usize,
+    key: Vec<u8>,
+    tweak: Vec<u8>,
+) -> Result<String, JsValue> {
+    fpe(input, integer_digits, fraction_digits, key, tweak, true)
+}
+
+/// Decrypts the given decimal number with `integer_digits` integer digits and
+/// `fraction_digits` fraction digits, preserving its sign and its scale.
+#[wasm_bindgen]
+pub fn webassembly_fpe_decrypt_decimal(
+    input: &str,
+    integer_digits: usize,
+    fraction_digits: usize,
+    key: Vec<u8>,
+    tweak: Vec<u8>,
+) -> Result<String, JsValue> {
+    fpe(input, integer_digits, fraction_digits, key, tweak, false)
+}
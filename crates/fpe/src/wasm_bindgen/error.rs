@@ -0,0 +1,42 @@
+use js_sys::{Error, Reflect};
+use wasm_bindgen::JsValue;
+
+use crate::core::AnoError;
+
+/// Converts the given error into a JS `Error` whose `name` and `code` are the
+/// kind of the error: `KeyLengthError`, `AlphabetMismatch`, `InputTooShort`
+/// or `FpeError` for the other errors. The details of the error are given as
+/// properties:
+///
+/// - `KeyLengthError`   : `length` and `expected`
+/// - `AlphabetMismatch` : `chars`, the characters not in the alphabet
+/// - `InputTooShort`    : `length` and `min`
+impl From<AnoError> for JsValue {
+    fn from(e: AnoError) -> Self {
+        let error = Error::new(&e.to_string());
+        let (code, properties): (_, Vec<(_, JsValue)>) = match e {
+            AnoError::KeySize(length, expected) => (
+                "KeyLengthError",
+                vec![("length", length.into()), ("expected", expected.into())],
+            ),
+            AnoError::AlphabetMismatch(chars) => (
+                "AlphabetMismatch",
+                vec![("chars", chars.into_iter().collect::<String>().into())],
+            ),
+            AnoError::InputTooShort { length, min, .. } => (
+                "InputTooShort",
+                vec![("length", length.into()), ("min", min.into())],
+            ),
+            _ => ("FpeError", vec![]),
+        };
+        error.set_name(code);
+        for (name, value) in [("code", JsValue::from_str(code))]
+            .into_iter()
+            .chain(properties)
+        {
+            // Setting a property of a fresh `Error` object cannot fail.
+            let _ = Reflect::set(&error, &JsValue::from_str(name), &value);
+        }
+        error.into()
+    }
+}
@@ -2,7 +2,7 @@ use num_bigint::BigUint;
 use num_traits::Num;
 use wasm_bindgen::{prelude::wasm_bindgen, JsValue};
 
-use crate::core::{Integer, KEY_LENGTH};
+use crate::core::{AnoError, Integer, KEY_LENGTH};
 
 fn fpe(
     input: &str,
@@ -13,15 +13,12 @@ fn fpe(
     encrypt_flag: bool,
 ) -> Result<String, JsValue> {
     // Copy the key bytes into a 32-byte array
-    let k: [u8; KEY_LENGTH] = key.try_into().map_err(|_e| {
-        JsValue::from_str(&format!(
-            "FPE Float error: key length incorrect: expected {KEY_LENGTH}"
-        ))
-    })?;
+    let k: [u8; KEY_LENGTH] = key
+        .try_into()
+        .map_err(|key: Vec<u8>| AnoError::KeySize(key.len(), KEY_LENGTH))?;
 
     // Instantiate an FPE integer with the provided radix and digit count
-    let itg = Integer::instantiate(radix, digits)
-        .map_err(|e| JsValue::from_str(&format!("FPE Big Integer instantiation failed: {e:?}")))?;
+    let itg = Integer::instantiate(radix, digits)?;
 
     // Convert the input string to a BigUint
     let input_biguint = BigUint::from_str_radix(input, radix).map_err(|e| {
@@ -38,11 +35,7 @@ fn fpe(
     };
 
     // Convert the result to a string in the provided radix
-    let output = result.map_err(|e| {
-        JsValue::from_str(&format!(
-            "FPE Big Integer encryption/decryption failed: {e:?}"
-        ))
-    })?;
+    let output = result?;
     let output_str = output.to_str_radix(radix);
     Ok(output_str)
 }
@@ -68,3 +61,55 @@ pub fn webassembly_fpe_decrypt_big_integer(
 ) -> Result<String, JsValue> {
     fpe(input, radix, digits, key, tweak, false)
 }
+
+fn fpe_fixed_width(
+    input: &str,
+    radix: u32,
+    digits: usize,
+    key: Vec<u8>,
+    tweak: Vec<u8>,
+    encrypt_flag: bool,
+) -> Result<String, JsValue> {
+    // Copy the key bytes into a 32-byte array
+    let k: [u8; KEY_LENGTH] = key
+        .try_into()
+        .map_err(|key: Vec<u8>| AnoError::KeySize(key.len(), KEY_LENGTH))?;
+
+    // Instantiate an FPE integer with the provided radix and digit count
+    let itg = Integer::instantiate(radix, digits)?;
+
+    // Perform the encryption or decryption operation on the numeric string
+    let result = if encrypt_flag {
+        itg.encrypt_fixed_width(&k, &tweak, input)
+    } else {
+        itg.decrypt_fixed_width(&k, &tweak, input)
+    };
+
+    result.map_err(JsValue::from)
+}
+
+/// Encrypts a numeric string of exactly `digits` digits, preserving its
+/// leading zeros.
+#[wasm_bindgen]
+pub fn webassembly_fpe_encrypt_fixed_width_integer(
+    input: &str,
+    radix: u32,
+    digits: usize,
+    key: Vec<u8>,
+    tweak: Vec<u8>,
+) -> Result<String, JsValue> {
+    fpe_fixed_width(input, radix, digits, key, tweak, true)
+}
+
+/// Decrypts a numeric string of exactly `digits` digits, preserving its
+/// leading zeros.
+#[wasm_bindgen]
+pub fn webassembly_fpe_decrypt_fixed_width_integer(
+    input: &str,
+    radix: u32,
+    digits: usize,
+    key: Vec<u8>,
+    tweak: Vec<u8>,
+) -> Result<String, JsValue> {
+    fpe_fixed_width(input, radix, digits, key, tweak, false)
+}
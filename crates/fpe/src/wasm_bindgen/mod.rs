@@ -1,4 +1,8 @@
 mod alphabet;
+mod batch;
+mod credit_card;
+mod decimal;
+mod error;
 mod float;
 mod integer;
 
@@ -1,14 +1,25 @@
+use js_sys::Reflect;
 use rand::{RngCore, SeedableRng};
 use rand_chacha::ChaCha20Rng;
+use wasm_bindgen::JsValue;
 use wasm_bindgen_test::wasm_bindgen_test;
 
 use crate::{
     core::KEY_LENGTH,
     get_alphabet,
     wasm_bindgen::{
-        alphabet::{webassembly_fpe_decrypt_alphabet, webassembly_fpe_encrypt_alphabet},
+        alphabet::{
+            webassembly_fpe_decrypt_alphabet, webassembly_fpe_encrypt_alphabet,
+            webassembly_fpe_minimum_plaintext_length, webassembly_fpe_pseudonymize_preview,
+            webassembly_fpe_validate_alphabet,
+        },
+        batch::{webassembly_fpe_decrypt_alphabet_batch, webassembly_fpe_encrypt_alphabet_batch},
+        decimal::{webassembly_fpe_decrypt_decimal, webassembly_fpe_encrypt_decimal},
         float::{webassembly_fpe_decrypt_float, webassembly_fpe_encrypt_float},
-        integer::{webassembly_fpe_decrypt_big_integer, webassembly_fpe_encrypt_big_integer},
+        integer::{
+            webassembly_fpe_decrypt_big_integer, webassembly_fpe_decrypt_fixed_width_integer,
+            webassembly_fpe_encrypt_big_integer, webassembly_fpe_encrypt_fixed_width_integer,
+        },
     },
 };
 
@@ -101,6 +112,120 @@ fn test_encrypt_decrypt() {
     ]
     .iter()
     .for_each(|n| alphabet_check(n, "chinese", " -", ""));
+
+    ["やまだ たろう", "すずき はなこ"]
+        .iter()
+        .for_each(|n| alphabet_check(n, "hiragana", " -", ""));
+
+    ["ヤマダ タロウ", "スズキ ハナコ"]
+        .iter()
+        .for_each(|n| alphabet_check(n, "katakana", " -", ""));
+
+    ["山田 太郎", "鈴木 はなこ"]
+        .iter()
+        .for_each(|n| alphabet_check(n, "japanese", " -", ""));
+
+    ["김민준", "이서연 박지호"]
+        .iter()
+        .for_each(|n| alphabet_check(n, "hangul", " -", ""));
+
+    // composed alphabets
+    ["やまだ タロウ", "スズキ はなこ"]
+        .iter()
+        .for_each(|n| alphabet_check(n, "hiragana+katakana", " -", ""));
+    ["John Doe", "MalcolmX", "abcd"]
+        .iter()
+        .for_each(|n| alphabet_check(n, "alpha-0O1lI", " -l", ""));
+}
+
+#[wasm_bindgen_test]
+fn test_validate_alphabet() {
+    assert_eq!(
+        webassembly_fpe_validate_alphabet("1234-1234 1234-123a", "numeric", "").unwrap(),
+        " -a"
+    );
+    assert_eq!(
+        webassembly_fpe_validate_alphabet("1234-1234 1234-123a", "numeric", " -a").unwrap(),
+        ""
+    );
+    assert_eq!(
+        webassembly_fpe_validate_alphabet("1234-1234 1234-123a", "numeric+alpha_lower-0", "")
+            .unwrap(),
+        " -"
+    );
+    assert_eq!(
+        webassembly_fpe_validate_alphabet("O0", "alpha_numeric-0O1lI", "").unwrap(),
+        "0O"
+    );
+    assert!(webassembly_fpe_validate_alphabet("1234", "numeric+unknown", "").is_err());
+}
+
+#[wasm_bindgen_test]
+fn test_minimum_plaintext_length() {
+    assert_eq!(
+        webassembly_fpe_minimum_plaintext_length("numeric", "").unwrap(),
+        6
+    );
+    assert_eq!(
+        webassembly_fpe_minimum_plaintext_length("numeric", "abcdef").unwrap(),
+        5
+    );
+    assert!(webassembly_fpe_minimum_plaintext_length("unknown", "").is_err());
+
+    // Shorter inputs are rejected.
+    let key = random_key().to_vec();
+    assert!(webassembly_fpe_encrypt_alphabet("12345", "numeric", key.clone(), vec![], "").is_err());
+    assert!(webassembly_fpe_encrypt_alphabet("123456", "numeric", key, vec![], "").is_ok());
+}
+
+#[wasm_bindgen_test]
+fn test_pseudonymize_preview() {
+    let ssn = webassembly_fpe_pseudonymize_preview("###-##-####", "numeric", b"row 1".to_vec(), "")
+        .unwrap();
+    assert_eq!(ssn.len(), 11);
+    assert_eq!(
+        ssn,
+        webassembly_fpe_pseudonymize_preview("###-##-####", "numeric", b"row 1".to_vec(), "")
+            .unwrap()
+    );
+    assert!(webassembly_fpe_pseudonymize_preview("###", "unknown", vec![], "").is_err());
+}
+
+fn to_batch(strings: &[&str]) -> Vec<u8> {
+    strings
+        .iter()
+        .flat_map(|s| (s.len() as u32).to_le_bytes().into_iter().chain(s.bytes()))
+        .collect()
+}
+
+#[wasm_bindgen_test]
+fn test_alphabet_batch() {
+    let key = random_key();
+    let tweak = random_key();
+    let plaintexts = ["John Doe", "Jane Doe", "Émile Zola"];
+    let batch = to_batch(&plaintexts);
+
+    let ciphertexts =
+        webassembly_fpe_encrypt_alphabet_batch(&batch, "utf", &key, &tweak, "").unwrap();
+    assert_ne!(ciphertexts, batch);
+    // Each string is encrypted as by the single string API.
+    let first_length = u32::from_le_bytes(ciphertexts[..4].try_into().unwrap()) as usize;
+    assert_eq!(
+        std::str::from_utf8(&ciphertexts[4..4 + first_length]).unwrap(),
+        webassembly_fpe_encrypt_alphabet("John Doe", "utf", key.to_vec(), tweak.to_vec(), "")
+            .unwrap()
+    );
+
+    let cleartexts =
+        webassembly_fpe_decrypt_alphabet_batch(&ciphertexts, "utf", &key, &tweak, "").unwrap();
+    assert_eq!(cleartexts, batch);
+
+    // Truncated batches are rejected.
+    assert!(
+        webassembly_fpe_encrypt_alphabet_batch(&batch[..batch.len() - 1], "utf", &key, &tweak, "")
+            .is_err()
+    );
+    assert!(webassembly_fpe_encrypt_alphabet_batch(&[1, 0], "utf", &key, &tweak, "").is_err());
 }
 
 #[wasm_bindgen_test]
@@ -118,6 +243,33 @@ fn test_big_integer() {
     assert_eq!(cleartext, plaintext);
 }
 
+#[wasm_bindgen_test]
+fn test_fixed_width_integer() {
+    let key = random_key().to_vec();
+    let tweak = random_key().to_vec();
+    let plaintext = "000123";
+    let ciphertext =
+        webassembly_fpe_encrypt_fixed_width_integer(plaintext, 10, 6, key.clone(), tweak.clone())
+            .unwrap();
+    assert_eq!(ciphertext.len(), plaintext.len());
+    let cleartext =
+        webassembly_fpe_decrypt_fixed_width_integer(&ciphertext, 10, 6, key, tweak).unwrap();
+    assert_eq!(cleartext, plaintext);
+}
+
+#[wasm_bindgen_test]
+fn test_decimal() {
+    let key = random_key().to_vec();
+    let tweak = random_key().to_vec();
+    let plaintext = "-1234.56";
+    let ciphertext =
+        webassembly_fpe_encrypt_decimal(plaintext, 9, 2, key.clone(), tweak.clone()).unwrap();
+    assert!(ciphertext.starts_with('-'));
+    assert_eq!(ciphertext.split_once('.').unwrap().1.len(), 2);
+    let cleartext = webassembly_fpe_decrypt_decimal(&ciphertext, 9, 2, key, tweak).unwrap();
+    assert_eq!(cleartext, plaintext);
+}
+
 #[wasm_bindgen_test]
 fn test_float() {
     let key = random_key().to_vec();
@@ -127,3 +279,28 @@ fn test_float() {
     let cleartext = webassembly_fpe_decrypt_float(ciphertext, key, tweak).unwrap();
     assert_eq!(cleartext, plaintext);
 }
+
+#[wasm_bindgen_test]
+fn test_error_kinds() {
+    let key = random_key().to_vec();
+    let get = |error: &JsValue, property: &str| {
+        Reflect::get(error, &JsValue::from_str(property)).unwrap()
+    };
+
+    let error =
+        webassembly_fpe_encrypt_alphabet("1234567890", "numeric", key[..16].to_vec(), vec![], "")
+            .unwrap_err();
+    assert_eq!(get(&error, "code"), "KeyLengthError");
+    assert_eq!(get(&error, "length"), 16);
+    assert_eq!(get(&error, "expected"), KEY_LENGTH);
+
+    let error = webassembly_fpe_encrypt_alphabet("12-3a b", "numeric", key.clone(), vec![], "")
+        .unwrap_err();
+    assert_eq!(get(&error, "code"), "InputTooShort");
+    assert_eq!(get(&error, "min"), 6);
+
+    let error =
+        webassembly_fpe_encrypt_fixed_width_integer("0a-b12", 10, 6, key, vec![]).unwrap_err();
+    assert_eq!(get(&error, "code"), "AlphabetMismatch");
+    assert_eq!(get(&error, "chars"), "-ab");
+}
@@ -2,13 +2,15 @@
 set -eux
 
 test_python_interface() {
-    # The name of the crate should be given as first argument.
+    # The name of the crate should be given as first argument, optional extra
+    # features as second argument.
     crate_dir="crates/$1"
+    features="python${2:+,$2}"
 
     # Build and install dependencies
     pip install -r "$crate_dir/python/requirements.txt"
     rm -f ./target/wheels/*.whl
-    maturin build -m "$crate_dir/Cargo.toml" --release --features python
+    maturin build -m "$crate_dir/Cargo.toml" --release --features "$features"
     pip install --force-reinstall ./target/wheels/*.whl
 
     test_file="$crate_dir/python/tests/$1_test.py"
@@ -36,7 +38,7 @@ if [ -z "$test_crate" ]; then
     test_python_interface fpe
     test_python_interface anonymization
     test_python_interface aesgcm
-    test_python_interface ecies
+    test_python_interface ecies pq,nist_curves
 elif [ "$test_crate" = "findex" ]; then
     test_python_interface findex
 elif [ "$test_crate" = "cover_crypt" ]; then
@@ -46,7 +48,7 @@ elif [ "$test_crate" = "fpe" ]; then
 elif [ "$test_crate" = "aesgcm" ]; then
     test_python_interface aesgcm
 elif [ "$test_crate" = "ecies" ]; then
-    test_python_interface ecies
+    test_python_interface ecies pq,nist_curves
 elif [ "$test_crate" = "anonymization" ]; then
     test_python_interface anonymization
 else